@@ -1,32 +1,83 @@
 pub mod types;
 mod ui;
 
+use std::collections::{BTreeMap, BTreeSet};
 use std::fs::{self};
 use std::path::PathBuf;
-pub use ui::start_ui;
+use std::process::{self, Command};
+pub use ui::{start_editor, start_ui};
 
+use clarinet_deployments::cancellation::CancellationToken;
 use clarinet_deployments::types::{DeploymentGenerationArtifacts, DeploymentSpecification};
 use clarinet_files::{FileLocation, ProjectManifest, StacksNetwork};
+use clarity_repl::clarity::vm::ast::ContractAST;
+use clarity_repl::clarity::vm::types::QualifiedContractIdentifier;
 
+/// Location of a plan given relative to the project root, or as an http(s) URL
 pub fn get_absolute_deployment_path(
     manifest: &ProjectManifest,
     relative_deployment_path: &str,
 ) -> Result<FileLocation, String> {
+    if relative_deployment_path.starts_with("https://")
+        || relative_deployment_path.starts_with("http://")
+    {
+        return FileLocation::from_url_string(relative_deployment_path);
+    }
     let mut deployment_path = manifest.location.get_project_root_location()?;
     deployment_path.append_path(relative_deployment_path)?;
     Ok(deployment_path)
 }
 
+lazy_static! {
+    static ref INTERRUPTION: CancellationToken = CancellationToken::new();
+}
+
+/// Stops the generation and the analysis of the plan at the first Ctrl-C, between two
+/// requirements or two contracts, instead of killing the process in the middle of a write to
+/// the requirements cache. A second Ctrl-C exits right away.
+pub fn cancel_on_interrupt() {
+    let token = INTERRUPTION.clone();
+    let _ = ctrlc::set_handler(move || {
+        if token.is_cancelled() {
+            process::exit(130);
+        }
+        token.cancel();
+    });
+}
+
+pub fn is_interrupted() -> bool {
+    INTERRUPTION.is_cancelled()
+}
+
 pub fn generate_default_deployment(
     manifest: &ProjectManifest,
     network: &StacksNetwork,
     _no_batch: bool,
 ) -> Result<(DeploymentSpecification, DeploymentGenerationArtifacts), String> {
-    let future =
-        clarinet_deployments::generate_default_deployment(manifest, network, false, None, None);
+    let future = clarinet_deployments::generate_default_deployment_with_cancellation(
+        manifest,
+        network,
+        false,
+        None,
+        None,
+        &INTERRUPTION,
+    );
     hiro_system_kit::nestable_block_on(future)
 }
 
+pub fn setup_session_with_deployment(
+    manifest: &ProjectManifest,
+    deployment: &DeploymentSpecification,
+    contracts_asts: Option<&BTreeMap<QualifiedContractIdentifier, ContractAST>>,
+) -> DeploymentGenerationArtifacts {
+    clarinet_deployments::setup_session_with_deployment_and_cancellation(
+        manifest,
+        deployment,
+        contracts_asts,
+        &INTERRUPTION,
+    )
+}
+
 pub fn check_deployments(manifest: &ProjectManifest) -> Result<(), String> {
     let project_root_location = manifest.location.get_project_root_location()?;
     let files = get_deployments_files(&project_root_location)?;
@@ -95,3 +146,117 @@ pub fn write_deployment(
     target_location.write_content(&deployment.to_file_content()?)?;
     Ok(())
 }
+
+pub fn get_upgrade_deployment_path(
+    manifest: &ProjectManifest,
+    network: &StacksNetwork,
+) -> Result<FileLocation, String> {
+    let mut deployment_path = manifest.location.get_project_root_location()?;
+    deployment_path.append_path("deployments")?;
+    deployment_path.append_path(match network {
+        StacksNetwork::Simnet => "upgrade.simnet-plan.yaml",
+        StacksNetwork::Devnet => "upgrade.devnet-plan.yaml",
+        StacksNetwork::Testnet => "upgrade.testnet-plan.yaml",
+        StacksNetwork::Mainnet => "upgrade.mainnet-plan.yaml",
+    })?;
+    Ok(deployment_path)
+}
+
+fn run_git(project_root: &FileLocation, args: &[&str]) -> Result<Vec<String>, String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(project_root.to_string())
+        .args(args)
+        .output()
+        .map_err(|e| format!("unable to run git: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "git {} failed\n{}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| line.to_string())
+        .collect())
+}
+
+/// Returns the project contracts whose files, or included files, changed between a git
+/// revision and the working tree
+pub fn get_contracts_changed_since(
+    manifest: &ProjectManifest,
+    deployment: &DeploymentSpecification,
+    artifacts: &DeploymentGenerationArtifacts,
+    revision: &str,
+) -> Result<BTreeSet<QualifiedContractIdentifier>, String> {
+    let project_root = manifest.location.get_project_root_location()?;
+    let mut changed_files: BTreeSet<String> = run_git(
+        &project_root,
+        &["diff", "--name-only", "--relative", revision],
+    )?
+    .into_iter()
+    .collect();
+    changed_files.extend(run_git(
+        &project_root,
+        &["ls-files", "--others", "--exclude-standard"],
+    )?);
+    Ok(get_contracts_of_files(
+        &project_root,
+        deployment,
+        artifacts,
+        &changed_files,
+    ))
+}
+
+/// Returns the project contracts whose files, or included files, are staged in the git index
+pub fn get_staged_contracts(
+    manifest: &ProjectManifest,
+    deployment: &DeploymentSpecification,
+    artifacts: &DeploymentGenerationArtifacts,
+) -> Result<BTreeSet<QualifiedContractIdentifier>, String> {
+    let project_root = manifest.location.get_project_root_location()?;
+    let staged_files: BTreeSet<String> = run_git(
+        &project_root,
+        &["diff", "--cached", "--name-only", "--relative"],
+    )?
+    .into_iter()
+    .collect();
+    Ok(get_contracts_of_files(
+        &project_root,
+        deployment,
+        artifacts,
+        &staged_files,
+    ))
+}
+
+/// Contracts whose files, or included files, are in `files`, relative to the project root
+fn get_contracts_of_files(
+    project_root: &FileLocation,
+    deployment: &DeploymentSpecification,
+    artifacts: &DeploymentGenerationArtifacts,
+    files: &BTreeSet<String>,
+) -> BTreeSet<QualifiedContractIdentifier> {
+    let is_changed = |location: &FileLocation| {
+        location
+            .get_relative_path_from_base(project_root)
+            .is_ok_and(|path| files.contains(&path.replace('\\', "/")))
+    };
+    deployment
+        .contracts
+        .iter()
+        .filter(|(contract_id, (_, location))| {
+            is_changed(location)
+                || artifacts
+                    .source_maps
+                    .get(contract_id)
+                    .is_some_and(|source_map| {
+                        source_map
+                            .segments
+                            .iter()
+                            .any(|segment| is_changed(&segment.location))
+                    })
+        })
+        .map(|(contract_id, _)| contract_id.clone())
+        .collect()
+}
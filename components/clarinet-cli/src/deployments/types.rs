@@ -1,16 +1,22 @@
-use clarinet_deployments::types::{DeploymentSpecification, TransactionSpecification};
+use clarinet_deployments::principal_remap::rewrite_literal_principals;
+use clarinet_deployments::types::{
+    BnsOperation, DeploymentSpecification, TransactionSpecification,
+};
 use std::fmt::{Display, Formatter, Result};
 
 pub struct DeploymentSynthesis {
     pub blocks_count: u64,
     pub total_cost: u64,
     pub content: String,
+    /// Literal principals rewritten in the requirements, e.g. `SP...vault 2:24: 'SP... -> 'ST...`
+    pub principal_rewrites: Vec<String>,
 }
 
 impl DeploymentSynthesis {
     pub fn from_deployment(deployment: &DeploymentSpecification) -> DeploymentSynthesis {
         let mut blocks_count = 0;
         let mut total_cost = 0;
+        let mut principal_rewrites = vec![];
         for batch in deployment.plan.batches.iter() {
             blocks_count += 1;
             for tx in batch.transactions.iter() {
@@ -25,6 +31,24 @@ impl DeploymentSynthesis {
                         total_cost += tx.cost;
                         total_cost += tx.mstx_amount;
                     }
+                    TransactionSpecification::BnsOperation(tx) => {
+                        total_cost += tx.cost;
+                        if let BnsOperation::NamePreorder { stx_to_burn, .. } = tx.operation {
+                            total_cost += stx_to_burn;
+                        }
+                    }
+                    TransactionSpecification::RequirementPublish(tx)
+                        if tx.rewrite_literal_principals =>
+                    {
+                        let (_, rewrites) = rewrite_literal_principals(
+                            &tx.contract_id,
+                            &tx.source,
+                            &tx.remap_principals,
+                        );
+                        for rewrite in rewrites {
+                            principal_rewrites.push(format!("{} {}", tx.contract_id, rewrite));
+                        }
+                    }
                     _ => {}
                 }
             }
@@ -38,6 +62,7 @@ impl DeploymentSynthesis {
         DeploymentSynthesis {
             total_cost,
             blocks_count,
+            principal_rewrites,
             content: std::str::from_utf8(&content).unwrap().to_string(),
         }
     }
@@ -49,10 +74,17 @@ impl Display for DeploymentSynthesis {
         let int_part = self.total_cost / base.pow(6);
         let frac_part = self.total_cost % base.pow(6);
         let formatted_total_cost = format!("{}.{:06}", int_part, frac_part);
+        write!(f, "{}\n\n", green!(format!("{}", self.content)))?;
+        if !self.principal_rewrites.is_empty() {
+            writeln!(f, "{}", yellow!("Rewritten principals:"))?;
+            for rewrite in self.principal_rewrites.iter() {
+                writeln!(f, "  {}", rewrite)?;
+            }
+            writeln!(f)?;
+        }
         write!(
             f,
-            "{}\n\n{}\n{}",
-            green!(format!("{}", self.content)),
+            "{}\n{}",
             blue!(format!("Total cost:\t{} STX", formatted_total_cost)),
             blue!(format!("Duration:\t{} blocks", self.blocks_count))
         )
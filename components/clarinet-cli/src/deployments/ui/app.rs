@@ -1,3 +1,4 @@
+use clarinet_deployments::onchain::epochs::EpochWait;
 use clarinet_deployments::onchain::TransactionTracker;
 use ratatui::widgets::ListState;
 
@@ -9,6 +10,8 @@ pub struct StatefulList<T> {
 pub struct App<'a> {
     pub node_url: &'a str,
     pub transactions: StatefulList<TransactionTracker>,
+    /// Set while the next batch is held until the activation of its epoch
+    pub epoch_wait: Option<EpochWait>,
 }
 
 impl<'a> App<'a> {
@@ -19,6 +22,7 @@ impl<'a> App<'a> {
                 state: ListState::default(),
                 items: transaction_trackers,
             },
+            epoch_wait: None,
         }
     }
 
@@ -27,6 +31,7 @@ impl<'a> App<'a> {
     pub fn reset(&mut self) {}
 
     pub fn display_contract_status_update(&mut self, update: TransactionTracker) {
+        self.epoch_wait = None;
         self.transactions.items.remove(update.index);
         self.transactions.items.insert(update.index, update);
     }
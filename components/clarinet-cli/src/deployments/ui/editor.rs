@@ -0,0 +1,258 @@
+use clarinet_deployments::plan_editor::{get_transaction_cost, get_transaction_label, PlanEditor};
+use clarinet_deployments::types::DeploymentSpecification;
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::execute;
+use crossterm::terminal::{
+    disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
+};
+use ratatui::backend::CrosstermBackend;
+use ratatui::layout::{Constraint, Direction, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::{Frame, Terminal};
+use std::io::stdout;
+
+const HELP: &str = "↑/↓ select  K/J or shift+↑/↓ move  space toggle  c cost  s save  q quit";
+
+struct Editor {
+    plan: PlanEditor,
+    /// Batch and index of the selected transaction
+    selected: Option<(usize, usize)>,
+    /// Cost being typed, set while the cost of the selected transaction is edited
+    cost_input: Option<String>,
+    message: Option<String>,
+    problems: Vec<String>,
+}
+
+impl Editor {
+    fn new(plan: PlanEditor) -> Self {
+        let problems = plan.validate();
+        let mut editor = Editor {
+            plan,
+            selected: None,
+            cost_input: None,
+            message: None,
+            problems,
+        };
+        editor.selected = editor.get_positions().first().copied();
+        editor
+    }
+
+    fn get_positions(&self) -> Vec<(usize, usize)> {
+        self.plan
+            .batches
+            .iter()
+            .enumerate()
+            .flat_map(|(batch, transactions)| (0..transactions.len()).map(move |i| (batch, i)))
+            .collect()
+    }
+
+    fn select(&mut self, offset: isize) {
+        let positions = self.get_positions();
+        let Some(current) = self
+            .selected
+            .and_then(|selected| positions.iter().position(|p| *p == selected))
+        else {
+            return;
+        };
+        let next = (current as isize + offset).clamp(0, positions.len() as isize - 1);
+        self.selected = Some(positions[next as usize]);
+    }
+
+    fn apply(&mut self, result: Result<Option<(usize, usize)>, String>) {
+        match result {
+            Ok(selected) => {
+                self.message = None;
+                if selected.is_some() {
+                    self.selected = selected;
+                }
+            }
+            Err(message) => self.message = Some(message),
+        }
+        self.problems = self.plan.validate();
+    }
+
+    /// Handles a key, returns `Some` once the edition is over, with the plan to save if any
+    fn on_key(
+        &mut self,
+        code: KeyCode,
+        modifiers: KeyModifiers,
+    ) -> Option<Option<DeploymentSpecification>> {
+        if let Some(ref mut input) = self.cost_input {
+            match code {
+                KeyCode::Char(c) if c.is_ascii_digit() => input.push(c),
+                KeyCode::Backspace => {
+                    input.pop();
+                }
+                KeyCode::Enter => {
+                    let input = self.cost_input.take().unwrap_or_default();
+                    let result = match (input.parse::<u64>(), self.selected) {
+                        (Ok(cost), Some((batch, index))) => {
+                            self.plan.set_cost(batch, index, cost).map(|_| None)
+                        }
+                        (Err(_), _) => Err(format!("invalid cost '{}'", input)),
+                        (_, None) => Ok(None),
+                    };
+                    self.apply(result);
+                }
+                KeyCode::Esc => self.cost_input = None,
+                _ => {}
+            }
+            return None;
+        }
+
+        let shift = modifiers.contains(KeyModifiers::SHIFT);
+        match (code, self.selected) {
+            (KeyCode::Char('q') | KeyCode::Esc, _) => return Some(None),
+            (KeyCode::Char('s'), _) => {
+                if self.problems.is_empty() {
+                    return Some(Some(self.plan.get_deployment()));
+                }
+                self.message = Some(format!(
+                    "{} problem(s) to fix before saving the plan",
+                    self.problems.len()
+                ));
+            }
+            (KeyCode::Char('K'), Some((batch, index))) => {
+                let result = self.plan.move_up(batch, index).map(Some);
+                self.apply(result);
+            }
+            (KeyCode::Up, Some((batch, index))) if shift => {
+                let result = self.plan.move_up(batch, index).map(Some);
+                self.apply(result);
+            }
+            (KeyCode::Char('J'), Some((batch, index))) => {
+                let result = self.plan.move_down(batch, index).map(Some);
+                self.apply(result);
+            }
+            (KeyCode::Down, Some((batch, index))) if shift => {
+                let result = self.plan.move_down(batch, index).map(Some);
+                self.apply(result);
+            }
+            (KeyCode::Up | KeyCode::Char('k'), _) => self.select(-1),
+            (KeyCode::Down | KeyCode::Char('j'), _) => self.select(1),
+            (KeyCode::Char(' '), Some((batch, index))) => {
+                self.plan.toggle(batch, index);
+                self.apply(Ok(None));
+            }
+            (KeyCode::Char('c'), Some((batch, index))) => {
+                let transaction = &self.plan.batches[batch][index].transaction;
+                match get_transaction_cost(transaction) {
+                    Some(cost) => self.cost_input = Some(cost.to_string()),
+                    None => {
+                        self.message = Some(format!(
+                            "{} has no cost",
+                            get_transaction_label(transaction)
+                        ))
+                    }
+                }
+            }
+            _ => {}
+        }
+        None
+    }
+}
+
+fn draw(f: &mut Frame, editor: &Editor) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Min(3),
+            Constraint::Length(editor.problems.len().clamp(1, 8) as u16 + 2),
+            Constraint::Length(3),
+        ])
+        .split(f.size());
+
+    let mut items = vec![];
+    let mut selected_item = None;
+    for (batch, transactions) in editor.plan.batches.iter().enumerate() {
+        let header = format!("Batch {}", editor.plan.get_batch_id(batch));
+        items.push(ListItem::new(header).style(Style::default().fg(Color::Cyan)));
+        for (index, tx) in transactions.iter().enumerate() {
+            if editor.selected == Some((batch, index)) {
+                selected_item = Some(items.len());
+            }
+            let cost = match (get_transaction_cost(&tx.transaction), &editor.cost_input) {
+                (Some(_), Some(input)) if editor.selected == Some((batch, index)) => {
+                    format!(" (cost: {}_)", input)
+                }
+                (Some(cost), _) => format!(" (cost: {})", cost),
+                (None, _) => String::new(),
+            };
+            let checkbox = if tx.enabled { "[x]" } else { "[ ]" };
+            let line = format!(
+                "  {} {}{}",
+                checkbox,
+                get_transaction_label(&tx.transaction),
+                cost
+            );
+            let style = match tx.enabled {
+                true => Style::default().fg(Color::White),
+                false => Style::default().fg(Color::DarkGray),
+            };
+            items.push(ListItem::new(line).style(style));
+        }
+    }
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(HELP))
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+    let mut state = ListState::default();
+    state.select(selected_item);
+    f.render_stateful_widget(list, chunks[0], &mut state);
+
+    let problems: Vec<Line> = match editor.problems.is_empty() {
+        true => vec![Line::from("The plan is valid")],
+        false => editor
+            .problems
+            .iter()
+            .map(|problem| Line::from(problem.as_str()))
+            .collect(),
+    };
+    let color = match editor.problems.is_empty() {
+        true => Color::Green,
+        false => Color::Red,
+    };
+    let problems = Paragraph::new(problems)
+        .style(Style::default().fg(color))
+        .wrap(Wrap { trim: true })
+        .block(Block::default().borders(Borders::ALL).title("Validation"));
+    f.render_widget(problems, chunks[1]);
+
+    let message = Paragraph::new(editor.message.clone().unwrap_or_default())
+        .style(Style::default().fg(Color::Yellow))
+        .block(Block::default().borders(Borders::ALL));
+    f.render_widget(message, chunks[2]);
+}
+
+/// Opens the editor of a plan, returns the edited plan if it's saved
+pub fn start_editor(plan: PlanEditor) -> Result<Option<DeploymentSpecification>, String> {
+    let mut editor = Editor::new(plan);
+
+    enable_raw_mode().map_err(|e| format!("unable to setup user interface: {}", e))?;
+    let mut stdout = stdout();
+    execute!(stdout, EnterAlternateScreen)
+        .map_err(|e| format!("unable to setup user interface: {}", e))?;
+    let backend = CrosstermBackend::new(stdout);
+    let mut terminal =
+        Terminal::new(backend).map_err(|e| format!("unable to setup user interface: {}", e))?;
+
+    let res = loop {
+        if let Err(e) = terminal.draw(|f| draw(f, &editor)) {
+            break Err(format!("unable to draw user interface: {}", e));
+        }
+        match event::read() {
+            Ok(Event::Key(key)) if key.kind == KeyEventKind::Press => {
+                if let Some(result) = editor.on_key(key.code, key.modifiers) {
+                    break Ok(result);
+                }
+            }
+            Ok(_) => {}
+            Err(e) => break Err(format!("unable to read input: {}", e)),
+        }
+    };
+    let _ = disable_raw_mode();
+    let _ = execute!(terminal.backend_mut(), LeaveAlternateScreen);
+    let _ = terminal.show_cursor();
+    res
+}
@@ -1,15 +1,18 @@
 #[allow(dead_code)]
 mod app;
+mod editor;
 
 #[allow(clippy::module_inception)]
 mod ui;
 
 use app::App;
+use clarinet_deployments::onchain::post_checks::DeploymentReceipt;
 use clarinet_deployments::onchain::{DeploymentEvent, TransactionTracker};
 use crossterm::{
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
+pub use editor::start_editor;
 use ratatui::{backend::CrosstermBackend, Terminal};
 use std::io::stdout;
 use std::sync::mpsc::Receiver;
@@ -18,6 +21,7 @@ pub fn start_ui(
     node_url: &str,
     deployment_event_rx: Receiver<DeploymentEvent>,
     transaction_trackers: Vec<TransactionTracker>,
+    receipt: &mut DeploymentReceipt,
 ) -> Result<(), String> {
     enable_raw_mode().expect("unable to setup user interface");
 
@@ -36,6 +40,12 @@ pub fn start_ui(
             Ok(DeploymentEvent::TransactionUpdate(update)) => {
                 app.display_contract_status_update(update);
             }
+            Ok(DeploymentEvent::PostCheckCompleted(result)) => {
+                receipt.post_checks.push(result);
+            }
+            Ok(DeploymentEvent::WaitingForEpoch(wait)) => {
+                app.epoch_wait = Some(wait);
+            }
             Ok(DeploymentEvent::DeploymentCompleted) => {
                 break Ok(());
             }
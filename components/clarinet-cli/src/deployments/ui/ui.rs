@@ -35,8 +35,15 @@ fn draw_contracts_status(f: &mut Frame, app: &mut App, area: Rect) {
         .bottom_margin(0)
     });
 
+    let mut title = format!("Broadcasting transactions to {}", app.node_url);
+    if let Some(wait) = &app.epoch_wait {
+        title.push_str(&format!(
+            " - batch {} waiting for epoch {} (bitcoin block #{}/{})",
+            wait.batch_id, wait.epoch, wait.bitcoin_block_height, wait.activation_height
+        ));
+    }
     let t = Table::new(rows, vec![] as Vec<&Constraint>)
-        .block(Block::default().title(format!("Broadcasting transactions to {}", app.node_url)))
+        .block(Block::default().title(title))
         .style(Style::default().fg(Color::White))
         .widths([
             Constraint::Length(3),
@@ -24,7 +24,8 @@ fn pack_to_file(file_name: &str, package: ConfigurationPackage) -> Result<(), St
                 file_name,
                 e
             );
-            process::exit(1);
+            // exit code of the internal errors
+            process::exit(4);
         }
     };
 
@@ -21,6 +21,7 @@ pub fn start(
     deployment: DeploymentSpecification,
     log_tx: Option<Sender<LogData>>,
     display_dashboard: bool,
+    json_output: bool,
 ) -> Result<
     (
         Option<mpsc::Receiver<DevnetEvent>>,
@@ -35,8 +36,9 @@ pub fn start(
     ) {
         Ok(hooks) => hooks,
         Err(e) => {
-            println!("{}", e);
-            std::process::exit(1);
+            eprintln!("{}", format_err!(e));
+            // exit code of the configuration errors
+            std::process::exit(2);
         }
     };
 
@@ -76,15 +78,22 @@ pub fn start(
         &mut Some(hooks),
         log_tx,
         display_dashboard,
+        json_output,
         ctx,
         orchestrator_terminated_tx,
         Some(orchestrator_terminated_rx),
     ));
-    println!(
+    // stdout only carries the JSON events
+    let message = format!(
         "{} logs and chainstate available at location {}",
         yellow!("\nterminating devnet network:"),
         working_dir
     );
+    if json_output {
+        eprintln!("{}", message);
+    } else {
+        println!("{}", message);
+    }
 
     res
 }
@@ -1,53 +1,310 @@
 use std::fs::{self};
+use std::path::PathBuf;
 
 use std::env;
 
-#[derive(Serialize, Deserialize, Default)]
+use clarinet_files::net::HttpSettings;
+use clarinet_files::{get_manifest_location, StacksNetwork};
+
+/// Settings are read from the system, user and project files, in this order: a setting
+/// defined in a file overrides the same setting defined in the previous ones.
+#[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq)]
 pub struct GlobalSettings {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub enable_hints: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub enable_telemetry: Option<bool>,
+    /// Template used by `clarinet new` when `--template` is not specified
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_template: Option<String>,
+    /// Force colors on or off, instead of only coloring the output of terminals
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<bool>,
+    /// Stacks node used when settings/Testnet.toml doesn't specify one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub testnet_stacks_node: Option<String>,
+    /// Stacks node used when settings/Mainnet.toml doesn't specify one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mainnet_stacks_node: Option<String>,
+    /// Proxy, certificate authorities and headers of the requests to the Stacks APIs. The
+    /// section of the last layer defining it is used as a whole.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub http: Option<HttpSettings>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SettingsLayer {
+    System,
+    User,
+    Project,
+}
+
+impl SettingsLayer {
+    pub fn get_path(&self) -> Option<PathBuf> {
+        match self {
+            #[cfg(unix)]
+            SettingsLayer::System => Some(PathBuf::from("/etc/clarinet/clarinetrc.toml")),
+            #[cfg(not(unix))]
+            SettingsLayer::System => env::var("PROGRAMDATA")
+                .ok()
+                .map(|dir| PathBuf::from(dir).join("clarinet").join("clarinetrc.toml")),
+            SettingsLayer::User => {
+                dirs::home_dir().map(|home_dir| home_dir.join(".clarinet/clarinetrc.toml"))
+            }
+            SettingsLayer::Project => get_manifest_location(None)
+                .and_then(|location| {
+                    PathBuf::from(location.to_string())
+                        .parent()
+                        .map(|p| p.to_path_buf())
+                })
+                .map(|project_dir| project_dir.join("clarinetrc.toml")),
+        }
+    }
 }
 
+impl std::fmt::Display for SettingsLayer {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            SettingsLayer::System => write!(f, "system"),
+            SettingsLayer::User => write!(f, "user"),
+            SettingsLayer::Project => write!(f, "project"),
+        }
+    }
+}
+
+const LAYERS: [SettingsLayer; 3] = [
+    SettingsLayer::System,
+    SettingsLayer::User,
+    SettingsLayer::Project,
+];
+
+const KEYS: [&str; 6] = [
+    "enable_hints",
+    "enable_telemetry",
+    "default_template",
+    "color",
+    "testnet_stacks_node",
+    "mainnet_stacks_node",
+];
+
 impl GlobalSettings {
     pub fn get_settings_file_path() -> &'static str {
         "~/.clarinet/clarinetrc.toml"
     }
 
-    pub fn from_global_file() -> Self {
-        let home_dir = dirs::home_dir();
-
-        if let Some(path) = home_dir.map(|home_dir| home_dir.join(".clarinet/clarinetrc.toml")) {
-            if path.exists() {
-                match fs::read_to_string(path) {
-                    Ok(content) => match toml::from_str::<GlobalSettings>(&content) {
-                        Ok(res) => return res,
-                        Err(_) => {
-                            println!(
-                                "{} {}",
-                                format_warn!("unable to parse"),
-                                Self::get_settings_file_path()
-                            );
-                        }
-                    },
-                    Err(_) => {
-                        println!(
-                            "{} {}",
-                            format_warn!("unable to read file"),
-                            Self::get_settings_file_path()
-                        );
-                    }
+    pub fn get_keys() -> Vec<String> {
+        KEYS.iter().map(|key| key.to_string()).collect()
+    }
+
+    /// Settings of a single file, `None` if the file doesn't exist
+    pub fn from_layer(layer: SettingsLayer) -> Option<Self> {
+        let path = layer.get_path()?;
+        if !path.exists() {
+            return None;
+        }
+        match fs::read_to_string(&path) {
+            Ok(content) => match toml::from_str::<GlobalSettings>(&content) {
+                Ok(res) => return Some(res),
+                Err(_) => {
+                    println!("{} {}", format_warn!("unable to parse"), path.display());
                 }
+            },
+            Err(_) => {
+                println!("{} {}", format_warn!("unable to read file"), path.display());
             }
-        };
+        }
+        None
+    }
+
+    pub fn from_global_file() -> Self {
+        let mut settings = GlobalSettings::default();
+        for layer in LAYERS {
+            if let Some(layer_settings) = Self::from_layer(layer) {
+                settings.merge(layer_settings);
+            }
+        }
 
         // Keep backwards compatibility with ENV var
-        let enable_hints = match env::var("CLARINET_DISABLE_HINTS") {
-            Ok(v) => Some(v == "1"),
-            Err(_) => None,
+        if settings.enable_hints.is_none() {
+            settings.enable_hints = match env::var("CLARINET_DISABLE_HINTS") {
+                Ok(v) => Some(v == "1"),
+                Err(_) => None,
+            };
+        }
+        settings
+    }
+
+    fn merge(&mut self, other: GlobalSettings) {
+        self.enable_hints = other.enable_hints.or(self.enable_hints);
+        self.enable_telemetry = other.enable_telemetry.or(self.enable_telemetry);
+        self.default_template = other.default_template.or(self.default_template.take());
+        self.color = other.color.or(self.color);
+        self.testnet_stacks_node = other
+            .testnet_stacks_node
+            .or(self.testnet_stacks_node.take());
+        self.mainnet_stacks_node = other
+            .mainnet_stacks_node
+            .or(self.mainnet_stacks_node.take());
+        self.http = other.http.or(self.http.take());
+    }
+
+    pub fn get_default_stacks_node(&self, network: &StacksNetwork) -> Option<&String> {
+        match network {
+            StacksNetwork::Testnet => self.testnet_stacks_node.as_ref(),
+            StacksNetwork::Mainnet => self.mainnet_stacks_node.as_ref(),
+            _ => None,
+        }
+    }
+
+    pub fn get_value(&self, key: &str) -> Result<Option<String>, String> {
+        let value = match key {
+            "enable_hints" => self.enable_hints.map(|v| v.to_string()),
+            "enable_telemetry" => self.enable_telemetry.map(|v| v.to_string()),
+            "color" => self.color.map(|v| v.to_string()),
+            "default_template" => self.default_template.clone(),
+            "testnet_stacks_node" => self.testnet_stacks_node.clone(),
+            "mainnet_stacks_node" => self.mainnet_stacks_node.clone(),
+            _ => return Err(Self::unknown_key(key)),
         };
-        Self {
-            enable_hints,
-            ..Default::default()
+        Ok(value)
+    }
+
+    pub fn set_value(&mut self, key: &str, value: &str) -> Result<(), String> {
+        let parse_bool = || match value {
+            "true" => Ok(true),
+            "false" => Ok(false),
+            _ => Err(format!("{} expects true or false, got '{}'", key, value)),
+        };
+        match key {
+            "enable_hints" => self.enable_hints = Some(parse_bool()?),
+            "enable_telemetry" => self.enable_telemetry = Some(parse_bool()?),
+            "color" => self.color = Some(parse_bool()?),
+            "default_template" => self.default_template = Some(value.to_string()),
+            "testnet_stacks_node" => self.testnet_stacks_node = Some(value.to_string()),
+            "mainnet_stacks_node" => self.mainnet_stacks_node = Some(value.to_string()),
+            _ => return Err(Self::unknown_key(key)),
+        }
+        Ok(())
+    }
+
+    fn unknown_key(key: &str) -> String {
+        format!(
+            "unknown setting '{}' (expected one of: {})",
+            key,
+            KEYS.join(", ")
+        )
+    }
+
+    /// Layer defining the value of a setting, the last one when several layers define it
+    pub fn get_value_layer(key: &str) -> Option<SettingsLayer> {
+        LAYERS.iter().rev().copied().find(|layer| {
+            Self::from_layer(*layer)
+                .and_then(|settings| settings.get_value(key).ok().flatten())
+                .is_some()
+        })
+    }
+
+    /// Updates a setting in the file of a layer, creating the file if needed
+    pub fn write_value(layer: SettingsLayer, key: &str, value: &str) -> Result<PathBuf, String> {
+        let path = layer
+            .get_path()
+            .ok_or(format!("unable to locate the {} settings file", layer))?;
+        let mut settings = Self::from_layer(layer).unwrap_or_default();
+        settings.set_value(key, value)?;
+        let content = toml::to_string(&settings)
+            .map_err(|e| format!("unable to serialize settings: {}", e))?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .map_err(|e| format!("unable to create {}: {}", dir.display(), e))?;
         }
+        fs::write(&path, content)
+            .map_err(|e| format!("unable to write {}: {}", path.display(), e))?;
+        Ok(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_settings_layers() {
+        let mut settings = GlobalSettings {
+            enable_hints: Some(false),
+            testnet_stacks_node: Some("http://localhost:20443".to_string()),
+            ..Default::default()
+        };
+        settings.merge(GlobalSettings {
+            enable_hints: Some(true),
+            default_template: Some("dao".to_string()),
+            ..Default::default()
+        });
+        assert_eq!(settings.enable_hints, Some(true));
+        assert_eq!(settings.default_template, Some("dao".to_string()));
+        assert_eq!(
+            settings.testnet_stacks_node,
+            Some("http://localhost:20443".to_string())
+        );
+    }
+
+    #[test]
+    fn test_set_value() {
+        let mut settings = GlobalSettings::default();
+        settings.set_value("color", "false").unwrap();
+        assert_eq!(settings.get_value("color"), Ok(Some("false".to_string())));
+        assert!(settings.set_value("color", "no").is_err());
+        assert!(settings.set_value("colour", "false").is_err());
+    }
+
+    #[test]
+    fn test_http_settings() {
+        let settings: GlobalSettings = toml::from_str(
+            r#"
+color = false
+
+[http]
+proxy = "http://proxy.corp:3128"
+
+[[http.endpoints]]
+url = "https://api.hiro.so"
+headers = { "x-api-key" = "secret" }
+
+[[http.endpoints]]
+url = "https://api.hiro.so/v2"
+headers = { "x-api-key" = "other-secret" }
+"#,
+        )
+        .unwrap();
+        let http = settings.http.unwrap();
+        assert_eq!(http.proxy, Some("http://proxy.corp:3128".to_string()));
+
+        let headers = http
+            .get_endpoint_headers("https://api.hiro.so/v2/contracts/source/SP000.pox")
+            .unwrap();
+        assert_eq!(headers.get("x-api-key").unwrap(), "other-secret");
+        let headers = http
+            .get_endpoint_headers("https://api.hiro.so/extended/v1/address/SP000/transactions")
+            .unwrap();
+        assert_eq!(headers.get("x-api-key").unwrap(), "secret");
+        assert!(http
+            .get_endpoint_headers("https://api.testnet.hiro.so")
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_hiro_api_key() {
+        let http = HttpSettings {
+            hiro_api_key: Some("secret".to_string()),
+            ..Default::default()
+        };
+        let headers = http
+            .get_endpoint_headers("https://api.testnet.hiro.so/v2/info")
+            .unwrap();
+        assert_eq!(headers.get("x-api-key").unwrap(), "secret");
+        assert!(http
+            .get_endpoint_headers("https://stacks-node.example.com/v2/info")
+            .unwrap()
+            .is_empty());
     }
 }
@@ -1,49 +1,83 @@
 use crate::deployments::types::DeploymentSynthesis;
 use crate::deployments::{
     self, check_deployments, generate_default_deployment, get_absolute_deployment_path,
-    write_deployment,
+    setup_session_with_deployment, write_deployment,
 };
 use crate::devnet::package::{self as Package, ConfigurationPackage};
 use crate::devnet::start::start;
 use crate::generate::{
     self,
     changes::{Changes, TOMLEdition},
+    template::ProjectTemplate,
+    tests::TestHarness,
 };
 use crate::lsp::run_lsp;
 
-use clap::{CommandFactory, Parser, Subcommand};
+use clap::{Args, CommandFactory, Parser, Subcommand};
 use clap_complete::{Generator, Shell};
+use clarinet_deployments::artifacts_export::{self, AstFormat};
+use clarinet_deployments::baseline::{DiagnosticsBaseline, DEFAULT_BASELINE_FILE};
+use clarinet_deployments::bench;
+use clarinet_deployments::cache::{
+    clean_cache, list_cache_entries, parse_cache_age, CacheCleanFilter, CacheEntryStatus,
+};
 use clarinet_deployments::diagnostic_digest::DiagnosticsDigest;
+use clarinet_deployments::event_replay;
+use clarinet_deployments::fee_policy::{get_total_fee, FeeCheck, FeePolicy};
+use clarinet_deployments::history_import;
+use clarinet_deployments::onchain::post_checks::{DeploymentReceipt, PostCheckResult};
+use clarinet_deployments::onchain::read_only;
+use clarinet_deployments::onchain::simulation::{simulate_deployment, SimulationReport};
 use clarinet_deployments::onchain::{
     apply_on_chain_deployment, get_initial_transactions_trackers, update_deployment_costs,
     DeploymentCommand, DeploymentEvent,
 };
+use clarinet_deployments::plan_editor::PlanEditor;
+use clarinet_deployments::platform_export;
+use clarinet_deployments::profiling::{self, PhaseTimer, StartupProfile};
+use clarinet_deployments::staged_check;
 use clarinet_deployments::types::{DeploymentGenerationArtifacts, DeploymentSpecification};
+use clarinet_deployments::upgrade_impact;
+use clarinet_deployments::upgrade_plan::{get_contracts_to_republish, get_upgrade_deployment};
+use clarinet_deployments::{
+    changelog, clarity_migration, requirement_explain, scenarios, session_image, source_metadata,
+    stats, storage_layout, versions,
+};
 use clarinet_deployments::{
-    get_default_deployment_path, load_deployment, setup_session_with_deployment,
+    check_mainnet_variant, get_default_deployment_path, load_deployment, load_pinned_deployment,
 };
 use clarinet_files::StacksNetwork;
 use clarinet_files::{
-    get_manifest_location, FileLocation, NetworkManifest, ProjectManifest, ProjectManifestFile,
-    RequirementConfig,
+    get_epoch_and_clarity_version, get_manifest_location, AccountConfig, DevnetConfigFile,
+    DevnetService, FileLocation, NetworkManifest, ProjectManifest, ProjectManifestFile,
+    RequirementConfig, SettingOrigin, SoakConfigFile, DEFAULT_DEPLOYMENT_FEE_RATE,
 };
 use clarity_repl::analysis::call_checker::ContractAnalysis;
 use clarity_repl::clarity::vm::analysis::AnalysisDatabase;
 use clarity_repl::clarity::vm::costs::LimitedCostTracker;
-use clarity_repl::clarity::vm::types::QualifiedContractIdentifier;
+use clarity_repl::clarity::vm::diagnostic::Level;
+use clarity_repl::clarity::vm::types::{PrincipalData, QualifiedContractIdentifier};
 use clarity_repl::clarity::ClarityVersion;
+use clarity_repl::clarity::StacksEpochId;
 use clarity_repl::frontend::terminal::print_clarity_wasm_warning;
 use clarity_repl::repl::diagnostic::output_diagnostic;
+use clarity_repl::repl::repro::ReproBundle;
 use clarity_repl::repl::{ClarityCodeSource, ClarityContract, ContractDeployer, DEFAULT_EPOCH};
 use clarity_repl::{analysis, repl, Terminal};
-use stacks_network::{self, DevnetOrchestrator};
-use std::collections::HashMap;
+use stacks_network::cluster_access::{ClusterAccess, ClusterConnection};
+use stacks_network::{self, coordinator_state, registry, DevnetOrchestrator};
+use std::collections::{BTreeSet, HashMap};
 use std::fs::{self, File};
 use std::io::prelude::*;
+use std::path::PathBuf;
 use std::{env, process};
 use toml;
 
-use super::clarinetrc::GlobalSettings;
+use super::clarinetrc::{GlobalSettings, SettingsLayer};
+use super::completions::{
+    get_completion_values, get_dynamic_completion_script, CompletionValueKind,
+};
+use super::output::{self, exit_with_error, ExitCode, OutputMode};
 
 #[cfg(feature = "telemetry")]
 use super::telemetry::{telemetry_report_event, DeveloperUsageDigest, DeveloperUsageEvent};
@@ -56,6 +90,18 @@ use super::telemetry::{telemetry_report_event, DeveloperUsageDigest, DeveloperUs
 struct Opts {
     #[clap(subcommand)]
     command: Command,
+    /// Only print the errors, the exit code tells the result (0: success, 1: diagnostics
+    /// errors, 2: configuration errors, 3: network failures, 4: internal errors)
+    #[clap(
+        long = "quiet",
+        short = 'q',
+        global = true,
+        conflicts_with = "summary_only"
+    )]
+    quiet: bool,
+    /// Only print the final result of the command, such as the counts of diagnostics
+    #[clap(long = "summary-only", global = true)]
+    summary_only: bool,
 }
 
 #[allow(clippy::upper_case_acronyms)]
@@ -64,18 +110,33 @@ enum Command {
     /// Generate shell completions scripts
     #[clap(name = "completions", bin_name = "completions", aliases = &["completion"])]
     Completions(Completions),
+    /// List the values used by the dynamic shell completions
+    #[clap(name = "complete-values", bin_name = "complete-values", hide = true)]
+    CompleteValues(CompleteValues),
     /// Create and scaffold a new project
     #[clap(name = "new", bin_name = "new")]
     New(GenerateProject),
+    /// Create a project from an existing directory of contracts
+    #[clap(name = "init", bin_name = "init")]
+    Init(InitProject),
     /// Subcommands for working with contracts
     #[clap(subcommand, name = "contracts", aliases = &["contract"])]
     Contracts(Contracts),
+    /// Generate files from the analysis of the contracts
+    #[clap(subcommand, name = "generate")]
+    Generate(Generate),
     /// Interact with contracts deployed on Mainnet
     #[clap(subcommand, name = "requirements", aliases = &["requirement"])]
     Requirements(Requirements),
-    /// Subcommands for working with chainhooks (deprecated)
+    /// Inspect and clean the requirements cache
+    #[clap(subcommand, name = "cache")]
+    Cache(Cache),
+    /// Get and set the settings of the clarinetrc files
+    #[clap(subcommand, name = "config")]
+    Config(Config),
+    /// Test chainhooks predicates against recorded blocks
     #[clap(name = "chainhooks", aliases = &["chainhook"])]
-    Chainhooks,
+    Chainhooks(ChainhooksCommand),
     /// Manage contracts deployments on Simnet/Devnet/Testnet/Mainnet
     #[clap(subcommand, name = "deployments", aliases = &["deployment"])]
     Deployments(Deployments),
@@ -85,6 +146,36 @@ enum Command {
     /// Check contracts syntax
     #[clap(name = "check", bin_name = "check")]
     Check(Check),
+    /// Summarize the contracts, dependencies and test coverage of the project
+    #[clap(name = "stats", bin_name = "stats")]
+    Stats(Stats),
+    /// Measure the costs and the wall time of a snippet evaluated against the simnet state
+    #[clap(name = "bench", bin_name = "bench")]
+    Bench(Bench),
+    /// Run the YAML scenarios of the project against the simnet deployment
+    #[clap(name = "scenarios", aliases = &["scenario"], bin_name = "scenarios")]
+    Scenarios(Scenarios),
+    /// Migrate the contracts of the project to a newer Clarity version
+    #[clap(name = "migrate", bin_name = "migrate")]
+    Migrate(Migrate),
+    /// Rewrite the manifests of a project created with a legacy version of Clarinet
+    #[clap(name = "migrate-project", bin_name = "migrate-project")]
+    MigrateProject(MigrateProject),
+    /// Apply project-wide refactors to the contracts, manifests and deployment plans
+    #[clap(subcommand, name = "refactor")]
+    Refactor(Refactor),
+    /// Load the reproduction bundles exported from the console with ::export_repro
+    #[clap(subcommand, name = "repro")]
+    Repro(Repro),
+    /// Inspect the contracts of the project
+    #[clap(subcommand, name = "inspect")]
+    Inspect(Inspect),
+    /// Check the metadata embedded in a deployed contract against the source of the project
+    #[clap(name = "verify-metadata", bin_name = "verify-metadata")]
+    VerifyMetadata(VerifyMetadata),
+    /// Call a read-only function of a contract deployed on a network, without a project
+    #[clap(name = "call-read-only", bin_name = "call-read-only")]
+    CallReadOnly(CallReadOnly),
     /// Start a local Devnet network for interacting with your contracts from your browser
     #[clap(name = "integrate", bin_name = "integrate")]
     Integrate(DevnetStart),
@@ -93,7 +184,7 @@ enum Command {
     Devnet(Devnet),
     /// Get Clarity autocompletion and inline errors from your code editor (VSCode, vim, emacs, etc)
     #[clap(name = "lsp", bin_name = "lsp")]
-    LSP,
+    LSP(Lsp),
     /// Step by step debugging and breakpoints from your code editor (VSCode, vim, emacs, etc)
     #[clap(name = "dap", bin_name = "dap")]
     DAP,
@@ -108,6 +199,127 @@ enum Devnet {
     /// Start a local Devnet network for interacting with your contracts from your browser
     #[clap(name = "start", bin_name = "start")]
     DevnetStart(DevnetStart),
+
+    /// Pull the images of the Devnet services ahead of 'clarinet devnet start'
+    #[clap(name = "pull", bin_name = "pull")]
+    Pull(DevnetPull),
+
+    /// Publish a modified contract on a running Devnet, under a new versioned name
+    #[clap(name = "redeploy", bin_name = "redeploy")]
+    Redeploy(DevnetRedeploy),
+
+    /// Summarize the current PoX cycle, reward set and locked STX of a running Devnet
+    #[clap(name = "pox-info", bin_name = "pox-info")]
+    PoxInfo(DevnetPoxInfo),
+
+    /// Print the chain tips, epoch and PoX cycle of a running Devnet
+    #[clap(name = "status", bin_name = "status")]
+    Status(DevnetStatus),
+
+    /// Print the logs of the services of a Devnet instance
+    #[clap(name = "logs", bin_name = "logs")]
+    Logs(DevnetLogs),
+
+    /// List the Devnet instances started on this machine
+    #[clap(name = "ls", bin_name = "ls")]
+    List(DevnetList),
+
+    /// Stop a Devnet instance and remove its containers
+    #[clap(name = "stop", bin_name = "stop")]
+    Stop(DevnetStop),
+
+    /// Remove the containers and working directories of the Devnet instances that are no longer running
+    #[clap(name = "clean", bin_name = "clean")]
+    Clean(DevnetClean),
+
+    /// Inspect the resolved Devnet settings
+    #[clap(subcommand, name = "config")]
+    Config(DevnetConfigCommand),
+}
+
+#[derive(Subcommand, PartialEq, Clone, Debug)]
+enum DevnetConfigCommand {
+    /// Print the resolved settings with their origin, and check the constraints between them
+    #[clap(name = "explain", bin_name = "explain")]
+    Explain(DevnetConfigExplain),
+}
+
+#[derive(Parser, PartialEq, Clone, Debug)]
+struct DevnetConfigExplain {
+    /// Path to Clarinet.toml
+    #[clap(long = "manifest-path", short = 'm')]
+    pub manifest_path: Option<String>,
+    /// Apply the override of `clarinet devnet start --auto-ports`
+    #[clap(long = "auto-ports")]
+    pub auto_ports: bool,
+    /// Apply the override of `clarinet devnet start --services`
+    #[clap(long = "services", value_delimiter = ',')]
+    pub services: Option<Vec<String>>,
+    #[clap(flatten)]
+    pub epochs: DevnetEpochs,
+}
+
+/// Burn heights overriding the epochs of settings/Devnet.toml
+#[derive(Args, PartialEq, Clone, Debug, Default)]
+struct DevnetEpochs {
+    /// Start height of epoch 2.0
+    #[clap(long = "epoch-2-0", value_name = "HEIGHT")]
+    pub epoch_2_0: Option<u64>,
+    /// Start height of epoch 2.05
+    #[clap(long = "epoch-2-05", value_name = "HEIGHT")]
+    pub epoch_2_05: Option<u64>,
+    /// Start height of epoch 2.1
+    #[clap(long = "epoch-2-1", value_name = "HEIGHT")]
+    pub epoch_2_1: Option<u64>,
+    /// Start height of epoch 2.2
+    #[clap(long = "epoch-2-2", value_name = "HEIGHT")]
+    pub epoch_2_2: Option<u64>,
+    /// Start height of epoch 2.3
+    #[clap(long = "epoch-2-3", value_name = "HEIGHT")]
+    pub epoch_2_3: Option<u64>,
+    /// Start height of epoch 2.4
+    #[clap(long = "epoch-2-4", value_name = "HEIGHT")]
+    pub epoch_2_4: Option<u64>,
+    /// Start height of epoch 2.5
+    #[clap(long = "epoch-2-5", value_name = "HEIGHT")]
+    pub epoch_2_5: Option<u64>,
+    /// Start height of epoch 3.0, which must be in a reward phase
+    #[clap(long = "epoch-3-0", value_name = "HEIGHT")]
+    pub epoch_3_0: Option<u64>,
+    /// Start height of epoch 3.1
+    #[clap(long = "epoch-3-1", value_name = "HEIGHT")]
+    pub epoch_3_1: Option<u64>,
+    /// Activation height of PoX-2, after the start of epoch 2.1
+    #[clap(long = "pox-2-activation", value_name = "HEIGHT")]
+    pub pox_2_activation: Option<u64>,
+}
+
+#[derive(Parser, PartialEq, Clone, Debug)]
+struct Lsp {
+    #[clap(subcommand)]
+    pub transport: Option<LspTransport>,
+    /// Analyse the projects from scratch instead of reusing the analysis of the previous
+    /// session, and don't persist it
+    #[clap(long = "no-cache", global = true)]
+    pub no_cache: bool,
+}
+
+#[derive(Subcommand, PartialEq, Clone, Debug)]
+enum LspTransport {
+    /// Communicate with the editor over stdin and stdout (default)
+    #[clap(name = "stdio", bin_name = "stdio")]
+    Stdio,
+    /// Accept editor connections on a TCP port
+    #[clap(name = "tcp", bin_name = "tcp")]
+    Tcp(LspTcp),
+}
+
+#[derive(Parser, PartialEq, Clone, Debug)]
+struct LspTcp {
+    #[clap(long = "host", default_value = "127.0.0.1")]
+    pub host: String,
+    #[clap(long = "port", short = 'p', default_value_t = 9257)]
+    pub port: u16,
 }
 
 #[derive(Subcommand, PartialEq, Clone, Debug)]
@@ -118,6 +330,19 @@ enum Contracts {
     /// Remove files and settings for a contract
     #[clap(name = "rm", bin_name = "rm")]
     RemoveContract(RemoveContract),
+    /// Generate a stub and a mock contract from a contract interface JSON
+    #[clap(name = "scaffold", bin_name = "scaffold")]
+    ScaffoldContract(ScaffoldContract),
+    /// List the contracts and mainnet callers affected by the changes of a contract
+    #[clap(name = "impact", bin_name = "impact")]
+    ContractImpact(ContractImpact),
+}
+
+#[derive(Subcommand, PartialEq, Clone, Debug)]
+enum Generate {
+    /// Generate a test file with a stub test per public function of a contract
+    #[clap(name = "tests", bin_name = "tests")]
+    Tests(GenerateTests),
 }
 
 #[derive(Subcommand, PartialEq, Clone, Debug)]
@@ -125,6 +350,65 @@ enum Requirements {
     /// Interact with contracts published on Mainnet
     #[clap(name = "add", bin_name = "add")]
     AddRequirement(AddRequirement),
+    /// Compare a local contract with the on-chain source of a requirement
+    #[clap(name = "diff", bin_name = "diff")]
+    DiffRequirement(DiffRequirement),
+    /// Report the provenance, source hash and license of the requirements
+    #[clap(name = "report", bin_name = "report")]
+    ReportRequirements(ReportRequirements),
+    /// Look up the newer versions of the requirements published by their deployers
+    #[clap(name = "outdated", bin_name = "outdated")]
+    OutdatedRequirements(OutdatedRequirements),
+    /// Explain why a requirement is part of the deployment plan
+    #[clap(name = "explain", bin_name = "explain")]
+    ExplainRequirement(ExplainRequirement),
+}
+
+#[derive(Subcommand, PartialEq, Clone, Debug)]
+enum Refactor {
+    /// Replace a principal in the contracts, the manifests and the deployment plans
+    #[clap(name = "replace-principal", bin_name = "replace-principal")]
+    ReplacePrincipal(ReplacePrincipal),
+}
+
+#[derive(Subcommand, PartialEq, Clone, Debug)]
+enum Repro {
+    /// Start a console with the contracts, the state and the balances of a bundle
+    #[clap(name = "load", bin_name = "load")]
+    Load(ReproLoad),
+}
+
+#[derive(Subcommand, PartialEq, Clone, Debug)]
+enum Cache {
+    /// List the cached requirements, with their size and hash
+    #[clap(name = "ls", bin_name = "ls")]
+    List(CacheList),
+    /// Check the cached sources against their recorded hashes
+    #[clap(name = "verify", bin_name = "verify")]
+    Verify(CacheVerify),
+    /// Remove cached requirements, to have them fetched again
+    #[clap(name = "clean", bin_name = "clean")]
+    Clean(CacheClean),
+}
+
+#[derive(Subcommand, PartialEq, Clone, Debug)]
+enum Inspect {
+    /// Report the data vars, maps and tokens of the contracts, with the size of their entries
+    #[clap(name = "storage-layout", bin_name = "storage-layout")]
+    StorageLayout(StorageLayout),
+    /// Report the Clarity version, epoch, size and deployer of the contracts of a plan
+    #[clap(name = "versions", bin_name = "versions")]
+    Versions(InspectVersions),
+}
+
+#[derive(Subcommand, PartialEq, Clone, Debug)]
+enum Config {
+    /// Print the value of a setting, or of all the settings
+    #[clap(name = "get", bin_name = "get")]
+    Get(ConfigGet),
+    /// Set a setting in the user clarinetrc file (or the system or project one)
+    #[clap(name = "set", bin_name = "set")]
+    Set(ConfigSet),
 }
 
 #[allow(clippy::enum_variant_names)]
@@ -139,6 +423,21 @@ enum Deployments {
     /// Apply deployment
     #[clap(name = "apply", bin_name = "apply")]
     ApplyDeployment(ApplyDeployment),
+    /// Export a deployment plan as JSON for the Hiro Platform and stacks.js scripts
+    #[clap(name = "export", bin_name = "export")]
+    ExportDeployment(ExportDeployment),
+    /// Write the Markdown changelog of a deployment plan: contracts, sources, costs and calls
+    #[clap(name = "changelog", bin_name = "changelog")]
+    DeploymentChangelog(DeploymentChangelog),
+    /// Edit a deployment plan in the terminal: reorder, toggle and change the cost of its transactions
+    #[clap(name = "edit", bin_name = "edit")]
+    EditDeployment(EditDeployment),
+    /// Reconstruct a deployment plan from the contracts published by an address
+    #[clap(name = "import", bin_name = "import")]
+    ImportDeployment(ImportDeployment),
+    /// Reconstruct a simnet plan from a Stacks API event replay file or a node events archive
+    #[clap(name = "replay-events", bin_name = "replay-events")]
+    ReplayEvents(ReplayEvents),
 }
 
 #[derive(Parser, PartialEq, Clone, Debug)]
@@ -150,6 +449,160 @@ struct DevnetPackage {
     pub manifest_path: Option<String>,
 }
 
+#[derive(Parser, PartialEq, Clone, Debug)]
+struct ChainhooksCommand {
+    #[clap(subcommand)]
+    pub command: Option<Chainhooks>,
+}
+
+#[derive(Subcommand, PartialEq, Clone, Debug)]
+enum Chainhooks {
+    /// Evaluate a Stacks predicate against recorded blocks, and print the payloads it would send
+    #[clap(name = "test", bin_name = "test")]
+    Test(TestChainhook),
+    /// Measure the evaluation time and the match rate of Stacks predicates over recorded blocks
+    #[clap(name = "load", bin_name = "load")]
+    Load(ChainhooksLoad),
+}
+
+#[derive(Parser, PartialEq, Clone, Debug)]
+struct TestChainhook {
+    /// Path to the predicate (JSON)
+    pub predicate_path: String,
+    /// JSON lines file of blocks, such as the recorded-blocks.jsonl of a devnet
+    /// (record_blocks = true) or the payloads received by a chainhook
+    #[clap(long = "against", value_name = "PATH")]
+    pub against: String,
+    /// Use the testnet settings of the predicate (defaults to devnet)
+    #[clap(long = "testnet", conflicts_with = "mainnet")]
+    pub testnet: bool,
+    /// Use the mainnet settings of the predicate (defaults to devnet)
+    #[clap(long = "mainnet")]
+    pub mainnet: bool,
+    /// Only print the blocks and transactions triggering the predicate
+    #[clap(long = "no-payloads")]
+    pub no_payloads: bool,
+}
+
+#[derive(Parser, PartialEq, Clone, Debug)]
+struct ChainhooksLoad {
+    /// Paths to the predicates (JSON)
+    #[clap(required = true)]
+    pub predicates_paths: Vec<String>,
+    /// JSON lines file of blocks, such as the recorded-blocks.jsonl of a devnet
+    #[clap(long = "against", value_name = "PATH")]
+    pub against: String,
+    /// Use the testnet settings of the predicates (defaults to devnet)
+    #[clap(long = "testnet", conflicts_with = "mainnet")]
+    pub testnet: bool,
+    /// Use the mainnet settings of the predicates (defaults to devnet)
+    #[clap(long = "mainnet")]
+    pub mainnet: bool,
+    /// Output the reports as JSON
+    #[clap(long = "json")]
+    pub json: bool,
+}
+
+#[derive(Parser, PartialEq, Clone, Debug)]
+struct DevnetPoxInfo {
+    /// Path to Clarinet.toml
+    #[clap(long = "manifest-path", short = 'm')]
+    pub manifest_path: Option<String>,
+    /// Output the summary as JSON
+    #[clap(long = "json")]
+    pub json: bool,
+}
+
+#[derive(Parser, PartialEq, Clone, Debug)]
+struct DevnetStatus {
+    /// Path to Clarinet.toml
+    #[clap(long = "manifest-path", short = 'm')]
+    pub manifest_path: Option<String>,
+    /// Output the state as JSON
+    #[clap(long = "json")]
+    pub json: bool,
+    /// Query the devnet of a k8s namespace, through a port-forward
+    #[clap(long = "namespace")]
+    pub namespace: Option<String>,
+    /// Print the URLs of the ingress of this domain for the services of the namespace
+    #[clap(long = "ingress-domain", requires = "namespace")]
+    pub ingress_domain: Option<String>,
+}
+
+#[derive(Parser, PartialEq, Clone, Debug)]
+struct DevnetList {
+    /// Output the instances as JSON
+    #[clap(long = "json")]
+    pub json: bool,
+}
+
+#[derive(Parser, PartialEq, Clone, Debug)]
+struct DevnetPull {
+    /// Path to Clarinet.toml
+    #[clap(long = "manifest-path", short = 'm')]
+    pub manifest_path: Option<String>,
+    /// Only pull the images of these services (e.g. bitcoin,stacks-node,signer) or profiles
+    /// (minimal, default, full)
+    #[clap(long = "services", value_delimiter = ',')]
+    pub services: Option<Vec<String>>,
+    /// Attempts of each pull, a failed pull resumes from the layers already downloaded
+    #[clap(long = "attempts", default_value_t = stacks_network::DEFAULT_PULL_ATTEMPTS)]
+    pub attempts: u32,
+}
+
+#[derive(Parser, PartialEq, Clone, Debug)]
+struct DevnetRedeploy {
+    /// Name of the contract in Clarinet.toml
+    pub contract: String,
+    /// Path to Clarinet.toml
+    #[clap(long = "manifest-path", short = 'm')]
+    pub manifest_path: Option<String>,
+    /// Skip the call of [devnet.migrations.<contract>]
+    #[clap(long = "no-migration")]
+    pub no_migration: bool,
+}
+
+#[derive(Parser, PartialEq, Clone, Debug)]
+struct DevnetStop {
+    /// Name of the instance, as listed by 'clarinet devnet ls'
+    pub name: String,
+}
+
+#[derive(Parser, PartialEq, Clone, Debug)]
+struct DevnetLogs {
+    /// Services to print the logs of (stacks-node, bitcoind, api, signer, postgres, explorer,
+    /// bitcoin-explorer, subnet-node, subnet-api), all of them by default
+    pub services: Vec<String>,
+    /// Path to Clarinet.toml
+    #[clap(long = "manifest-path", short = 'm')]
+    pub manifest_path: Option<String>,
+    /// Name of the instance, as listed by 'clarinet devnet ls' (the instance of the project by default)
+    #[clap(long = "name")]
+    pub name: Option<String>,
+    /// Keep printing the logs as they are written
+    #[clap(long = "follow", short = 'f')]
+    pub follow: bool,
+    /// Skip the lines below this level
+    #[clap(long = "level", value_parser = ["trace", "debug", "info", "warn", "error"])]
+    pub level: Option<String>,
+    /// Skip the lines written before this time: a duration (e.g. 10m, 2h) or an RFC 3339 date
+    #[clap(long = "since")]
+    pub since: Option<String>,
+    /// Skip the lines written after this time: a duration (e.g. 10m, 2h) or an RFC 3339 date
+    #[clap(long = "until")]
+    pub until: Option<String>,
+    /// Only print the last lines of each container
+    #[clap(long = "tail")]
+    pub tail: Option<usize>,
+}
+
+#[derive(Parser, PartialEq, Clone, Debug)]
+struct DevnetClean {
+    /// Keep the working directories (logs, chainstates) of the instances
+    #[clap(long = "keep-working-dirs")]
+    pub keep_working_dirs: bool,
+}
+
 #[derive(Parser, PartialEq, Clone, Debug)]
 struct GenerateProject {
     /// Project's name
@@ -157,6 +610,21 @@ struct GenerateProject {
     /// Do not provide developer usage telemetry for this project
     #[clap(long = "disable-telemetry")]
     pub disable_telemetry: bool,
+    /// Create the project from a template, with its contracts, deployment plan and tests
+    #[clap(long = "template", value_parser = ["dao"])]
+    pub template: Option<String>,
+}
+
+#[derive(Parser, PartialEq, Clone, Debug)]
+struct InitProject {
+    /// Directory containing the contracts (defaults to the current directory)
+    pub path: Option<String>,
+    /// Use the default values instead of prompting
+    #[clap(long = "yes", short = 'y')]
+    pub yes: bool,
+    /// Do not provide developer usage telemetry for this project
+    #[clap(long = "disable-telemetry")]
+    pub disable_telemetry: bool,
 }
 
 #[derive(Parser, PartialEq, Clone, Debug)]
@@ -177,6 +645,48 @@ struct RemoveContract {
     pub manifest_path: Option<String>,
 }
 
+#[derive(Parser, PartialEq, Clone, Debug)]
+struct ScaffoldContract {
+    /// Contract's name
+    pub name: String,
+    /// Path to the interface JSON (from the Stacks API /v2/contracts/interface endpoint)
+    #[clap(long = "interface", short = 'i')]
+    pub interface_path: String,
+    /// Path to Clarinet.toml
+    #[clap(long = "manifest-path", short = 'm')]
+    pub manifest_path: Option<String>,
+}
+
+#[derive(Parser, PartialEq, Clone, Debug)]
+struct GenerateTests {
+    /// Contract's name
+    pub name: String,
+    /// Test harness: the clarinet-sdk with vitest (ts) or cargo tests (rust)
+    #[clap(long = "harness", value_parser = ["ts", "rust"], default_value = "ts")]
+    pub harness: String,
+    /// Path to Clarinet.toml
+    #[clap(long = "manifest-path", short = 'm')]
+    pub manifest_path: Option<String>,
+}
+
+#[derive(Parser, PartialEq, Clone, Debug)]
+struct ContractImpact {
+    /// Contract's name
+    pub name: String,
+    /// Path to the previous version of the contract (defaults to the version of the last git commit)
+    #[clap(long = "previous", short = 'p')]
+    pub previous_path: Option<String>,
+    /// Id of the contract deployed on mainnet, to list its recent callers (network connection required)
+    #[clap(long = "mainnet-contract-id")]
+    pub mainnet_contract_id: Option<String>,
+    /// Output the report as JSON
+    #[clap(long = "json")]
+    pub json: bool,
+    /// Path to Clarinet.toml
+    #[clap(long = "manifest-path", short = 'm')]
+    pub manifest_path: Option<String>,
+}
+
 #[derive(Parser, PartialEq, Clone, Debug)]
 struct AddRequirement {
     /// Contract id (ex. "SP2PABAF9FTAJYNFZH93XENAJ8FVY99RRM50D2JG9.nft-trait")
@@ -187,94 +697,111 @@ struct AddRequirement {
 }
 
 #[derive(Parser, PartialEq, Clone, Debug)]
-struct CheckDeployments {
+struct DiffRequirement {
+    /// Name of the local contract (ex. "nft-trait")
+    pub contract_name: String,
+    /// Requirement the contract was forked from, defaults to the requirement with the same name
+    #[clap(long = "requirement", short = 'r')]
+    pub requirement: Option<String>,
+    /// Compare the sources as is, without normalizing their formatting
+    #[clap(long = "raw")]
+    pub raw: bool,
     /// Path to Clarinet.toml
     #[clap(long = "manifest-path", short = 'm')]
     pub manifest_path: Option<String>,
 }
 
 #[derive(Parser, PartialEq, Clone, Debug)]
-struct GenerateDeployment {
-    /// Generate a deployment file for simnet environments (console, tests)
-    #[clap(
-        long = "simnet",
-        conflicts_with = "devnet",
-        conflicts_with = "testnet",
-        conflicts_with = "mainnet"
-    )]
-    pub simnet: bool,
-    /// Generate a deployment file for devnet, using settings/Devnet.toml
-    #[clap(
-        long = "devnet",
-        conflicts_with = "simnet",
-        conflicts_with = "testnet",
-        conflicts_with = "mainnet"
-    )]
-    pub devnet: bool,
-    /// Generate a deployment file for devnet, using settings/Testnet.toml
-    #[clap(
-        long = "testnet",
-        conflicts_with = "simnet",
-        conflicts_with = "devnet",
-        conflicts_with = "mainnet"
-    )]
-    pub testnet: bool,
-    /// Generate a deployment file for devnet, using settings/Mainnet.toml
-    #[clap(
-        long = "mainnet",
-        conflicts_with = "simnet",
-        conflicts_with = "testnet",
-        conflicts_with = "devnet"
-    )]
-    pub mainnet: bool,
+struct ReportRequirements {
+    /// Output the report as JSON
+    #[clap(long = "json")]
+    pub json: bool,
     /// Path to Clarinet.toml
     #[clap(long = "manifest-path", short = 'm')]
     pub manifest_path: Option<String>,
-    /// Generate a deployment file without trying to batch transactions (simnet only)
-    #[clap(
-        long = "no-batch",
-        conflicts_with = "devnet",
-        conflicts_with = "testnet",
-        conflicts_with = "mainnet"
-    )]
-    pub no_batch: bool,
-    /// Compute and set cost, using low priority (network connection required)
-    #[clap(
-        long = "low-cost",
-        conflicts_with = "medium_cost",
-        conflicts_with = "high_cost",
-        conflicts_with = "manual_cost"
-    )]
-    pub low_cost: bool,
-    /// Compute and set cost, using medium priority (network connection required)
-    #[clap(
-        conflicts_with = "low_cost",
-        long = "medium-cost",
-        conflicts_with = "high_cost",
-        conflicts_with = "manual_cost"
-    )]
-    pub medium_cost: bool,
-    /// Compute and set cost, using high priority (network connection required)
-    #[clap(
-        conflicts_with = "low_cost",
-        conflicts_with = "medium_cost",
-        long = "high-cost",
-        conflicts_with = "manual_cost"
-    )]
-    pub high_cost: bool,
-    /// Leave cost estimation manual
-    #[clap(
-        conflicts_with = "low_cost",
-        conflicts_with = "medium_cost",
-        conflicts_with = "high_cost",
-        long = "manual-cost"
-    )]
-    pub manual_cost: bool,
 }
 
 #[derive(Parser, PartialEq, Clone, Debug)]
-struct ApplyDeployment {
-    /// Apply default deployment settings/default.devnet-plan.toml
+struct ExplainRequirement {
+    /// Contract id of the requirement (ex. "SP2PABAF9FTAJYNFZH93XENAJ8FVY99RRM50D2JG9.nft-trait")
+    pub contract_id: String,
+    /// Output the explanation as JSON
+    #[clap(long = "json")]
+    pub json: bool,
+    /// Path to Clarinet.toml
+    #[clap(long = "manifest-path", short = 'm')]
+    pub manifest_path: Option<String>,
+}
+
+#[derive(Parser, PartialEq, Clone, Debug)]
+struct OutdatedRequirements {
+    /// Output the updates as JSON
+    #[clap(long = "json")]
+    pub json: bool,
+    /// Path to Clarinet.toml
+    #[clap(long = "manifest-path", short = 'm')]
+    pub manifest_path: Option<String>,
+}
+
+#[derive(Parser, PartialEq, Clone, Debug)]
+struct CacheList {
+    /// Output the entries as JSON
+    #[clap(long = "json")]
+    pub json: bool,
+    /// Path to Clarinet.toml
+    #[clap(long = "manifest-path", short = 'm')]
+    pub manifest_path: Option<String>,
+}
+
+#[derive(Parser, PartialEq, Clone, Debug)]
+struct Stats {
+    /// Output the statistics as JSON
+    #[clap(long = "json")]
+    pub json: bool,
+    /// Number of contracts listed as the largest ones
+    #[clap(long = "top", default_value = "5")]
+    pub top: usize,
+    /// Path to Clarinet.toml
+    #[clap(long = "manifest-path", short = 'm')]
+    pub manifest_path: Option<String>,
+}
+
+#[derive(Parser, PartialEq, Clone, Debug)]
+struct Bench {
+    /// Clarity snippet to evaluate (ex. "(contract-call? .amm swap u1000)")
+    pub snippet: String,
+    /// Number of evaluations
+    #[clap(long = "runs", default_value = "100")]
+    pub runs: usize,
+    /// Record the report in the baseline, for the next runs to be compared with it
+    #[clap(long = "save-baseline")]
+    pub save_baseline: bool,
+    /// Path to the baseline (default: clarinet-bench.json)
+    #[clap(long = "baseline")]
+    pub baseline_path: Option<String>,
+    /// Output the report as JSON
+    #[clap(long = "json")]
+    pub json: bool,
+    /// Path to Clarinet.toml
+    #[clap(long = "manifest-path", short = 'm')]
+    pub manifest_path: Option<String>,
+}
+
+#[derive(Parser, PartialEq, Clone, Debug)]
+struct StorageLayout {
+    /// Contracts to inspect (default: all the contracts of the project)
+    pub contracts: Vec<String>,
+    /// Output the layout as JSON
+    #[clap(long = "json")]
+    pub json: bool,
+    /// Path to Clarinet.toml
+    #[clap(long = "manifest-path", short = 'm')]
+    pub manifest_path: Option<String>,
+}
+
+#[derive(Parser, PartialEq, Clone, Debug)]
+struct InspectVersions {
+    /// Inspect deployments/default.devnet-plan.yaml
     #[clap(
         long = "devnet",
         conflicts_with = "deployment_plan_path",
@@ -282,7 +809,7 @@ struct ApplyDeployment {
         conflicts_with = "mainnet"
     )]
     pub devnet: bool,
-    /// Apply default deployment settings/default.testnet-plan.toml
+    /// Inspect deployments/default.testnet-plan.yaml
     #[clap(
         long = "testnet",
         conflicts_with = "deployment_plan_path",
@@ -290,7 +817,338 @@ struct ApplyDeployment {
         conflicts_with = "mainnet"
     )]
     pub testnet: bool,
-    /// Apply default deployment settings/default.mainnet-plan.toml
+    /// Inspect deployments/default.mainnet-plan.yaml (default: deployments/default.simnet-plan.yaml)
+    #[clap(
+        long = "mainnet",
+        conflicts_with = "deployment_plan_path",
+        conflicts_with = "devnet",
+        conflicts_with = "testnet"
+    )]
+    pub mainnet: bool,
+    /// Inspect the deployment plan specified
+    #[clap(long = "deployment-plan-path", short = 'p')]
+    pub deployment_plan_path: Option<String>,
+    /// Output the matrix as JSON
+    #[clap(long = "json")]
+    pub json: bool,
+    /// Path to Clarinet.toml
+    #[clap(long = "manifest-path", short = 'm')]
+    pub manifest_path: Option<String>,
+}
+
+#[derive(Parser, PartialEq, Clone, Debug)]
+struct Scenarios {
+    /// Scenarios to run (default: the .yaml files of the scenarios directory)
+    pub files: Vec<String>,
+    /// Output the reports as JSON
+    #[clap(long = "json")]
+    pub json: bool,
+    /// Run the scenarios under two epochs and compare the results, events and costs
+    /// (e.g. 2.5,3.0 or 3.0:2,3.0:3 to pick the Clarity versions)
+    #[clap(long = "compare-epochs", value_delimiter = ',')]
+    pub compare_epochs: Vec<String>,
+    /// Path to Clarinet.toml
+    #[clap(long = "manifest-path", short = 'm')]
+    pub manifest_path: Option<String>,
+}
+
+#[derive(Parser, PartialEq, Clone, Debug)]
+struct Migrate {
+    /// Clarity version to migrate to (only clarity3 is supported)
+    #[clap(long = "to", default_value = "clarity3")]
+    pub to: String,
+    /// Rewrite the mechanical changes and update the versions of Clarinet.toml
+    #[clap(long = "write")]
+    pub write: bool,
+    /// Output the issues as JSON
+    #[clap(long = "json")]
+    pub json: bool,
+    /// Path to Clarinet.toml
+    #[clap(long = "manifest-path", short = 'm')]
+    pub manifest_path: Option<String>,
+}
+
+#[derive(Parser, PartialEq, Clone, Debug)]
+struct MigrateProject {
+    /// Print the diff of each file instead of writing it
+    #[clap(long = "dry-run")]
+    pub dry_run: bool,
+    /// Path to Clarinet.toml
+    #[clap(long = "manifest-path", short = 'm')]
+    pub manifest_path: Option<String>,
+}
+
+#[derive(Parser, PartialEq, Clone, Debug)]
+struct ReplacePrincipal {
+    /// Principal to replace (ex. "ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM")
+    #[clap(long = "from")]
+    pub from: String,
+    /// Principal replacing it (ex. "SP3FBR2AGK5H9QBDH3EEN6DF8EK8JY7RX8QJ5SVTE")
+    #[clap(long = "to")]
+    pub to: String,
+    /// Print the diff of each file instead of writing it
+    #[clap(long = "dry-run")]
+    pub dry_run: bool,
+    /// Path to Clarinet.toml
+    #[clap(long = "manifest-path", short = 'm')]
+    pub manifest_path: Option<String>,
+}
+
+#[derive(Parser, PartialEq, Clone, Debug)]
+struct CacheVerify {
+    /// Remove the corrupted entries
+    #[clap(long = "fix")]
+    pub fix: bool,
+    /// Path to Clarinet.toml
+    #[clap(long = "manifest-path", short = 'm')]
+    pub manifest_path: Option<String>,
+}
+
+#[derive(Parser, PartialEq, Clone, Debug)]
+struct CacheClean {
+    /// Remove the entry of a requirement (ex. "SP2PABAF9FTAJYNFZH93XENAJ8FVY99RRM50D2JG9.nft-trait")
+    #[clap(long = "contract", conflicts_with_all = &["older_than", "all"])]
+    pub contract: Option<String>,
+    /// Remove the entries not updated for a given duration (ex. "30m", "12h", "7d")
+    #[clap(long = "older-than", conflicts_with = "all")]
+    pub older_than: Option<String>,
+    /// Remove all the entries
+    #[clap(long = "all")]
+    pub all: bool,
+    /// Path to Clarinet.toml
+    #[clap(long = "manifest-path", short = 'm')]
+    pub manifest_path: Option<String>,
+}
+
+#[derive(Parser, PartialEq, Clone, Debug)]
+struct ReproLoad {
+    /// Path to the bundle written by ::export_repro
+    pub bundle_path: String,
+}
+
+#[derive(Parser, PartialEq, Clone, Debug)]
+struct ConfigGet {
+    /// Name of the setting (ex. "enable_hints")
+    pub key: Option<String>,
+}
+
+#[derive(Parser, PartialEq, Clone, Debug)]
+struct ConfigSet {
+    /// Name of the setting (ex. "default_template")
+    pub key: String,
+    pub value: String,
+    /// Write the setting in the clarinetrc.toml of the current project
+    #[clap(long = "project", conflicts_with = "system")]
+    pub project: bool,
+    /// Write the setting in the system clarinetrc file
+    #[clap(long = "system")]
+    pub system: bool,
+}
+
+#[derive(Parser, PartialEq, Clone, Debug)]
+struct CheckDeployments {
+    /// Path to Clarinet.toml
+    #[clap(long = "manifest-path", short = 'm')]
+    pub manifest_path: Option<String>,
+}
+
+#[derive(Parser, PartialEq, Clone, Debug)]
+struct ExportDeployment {
+    /// Export deployments/default.devnet-plan.yaml
+    #[clap(
+        long = "devnet",
+        conflicts_with = "deployment_plan_path",
+        conflicts_with = "mainnet"
+    )]
+    pub devnet: bool,
+    /// Export deployments/default.mainnet-plan.yaml (default: deployments/default.testnet-plan.yaml)
+    #[clap(
+        long = "mainnet",
+        conflicts_with = "deployment_plan_path",
+        conflicts_with = "devnet"
+    )]
+    pub mainnet: bool,
+    /// Path to Clarinet.toml
+    #[clap(long = "manifest-path", short = 'm')]
+    pub manifest_path: Option<String>,
+    /// Export the deployment plan specified
+    #[clap(long = "deployment-plan-path", short = 'p')]
+    pub deployment_plan_path: Option<String>,
+}
+
+#[derive(Parser, PartialEq, Clone, Debug)]
+struct DeploymentChangelog {
+    /// Changelog of deployments/default.devnet-plan.yaml
+    #[clap(
+        long = "devnet",
+        conflicts_with = "deployment_plan_path",
+        conflicts_with = "mainnet"
+    )]
+    pub devnet: bool,
+    /// Changelog of deployments/default.mainnet-plan.yaml (default: deployments/default.testnet-plan.yaml)
+    #[clap(
+        long = "mainnet",
+        conflicts_with = "deployment_plan_path",
+        conflicts_with = "devnet"
+    )]
+    pub mainnet: bool,
+    /// Path to Clarinet.toml
+    #[clap(long = "manifest-path", short = 'm')]
+    pub manifest_path: Option<String>,
+    /// Changelog of the deployment plan specified
+    #[clap(long = "deployment-plan-path", short = 'p')]
+    pub deployment_plan_path: Option<String>,
+    /// Write the changelog to this file instead of stdout
+    #[clap(long = "output", short = 'o')]
+    pub output: Option<String>,
+}
+
+#[derive(Parser, PartialEq, Clone, Debug)]
+struct EditDeployment {
+    /// Path to the deployment plan to edit
+    pub deployment_plan_path: String,
+    /// Path to Clarinet.toml
+    #[clap(long = "manifest-path", short = 'm')]
+    pub manifest_path: Option<String>,
+}
+
+#[derive(Parser, PartialEq, Clone, Debug)]
+struct ImportDeployment {
+    /// Address that published the contracts
+    #[clap(long = "address")]
+    pub address: String,
+    #[clap(long = "network", value_parser = ["mainnet", "testnet"], default_value = "mainnet")]
+    pub network: String,
+    /// Stacks API to query (default: the Hiro API of the network)
+    #[clap(long = "api-url")]
+    pub api_url: Option<String>,
+    /// Path to Clarinet.toml
+    #[clap(long = "manifest-path", short = 'm')]
+    pub manifest_path: Option<String>,
+}
+
+#[derive(Parser, PartialEq, Clone, Debug)]
+struct ReplayEvents {
+    /// Events archive, as exported by the Stacks API (events.tsv) or as JSON lines
+    pub events_file: String,
+    /// Last block to replay (default: the last block of the archive)
+    #[clap(long = "height")]
+    pub height: Option<u64>,
+    /// Path to Clarinet.toml
+    #[clap(long = "manifest-path", short = 'm')]
+    pub manifest_path: Option<String>,
+}
+
+#[derive(Parser, PartialEq, Clone, Debug)]
+struct GenerateDeployment {
+    /// Generate a deployment file for simnet environments (console, tests)
+    #[clap(
+        long = "simnet",
+        conflicts_with = "devnet",
+        conflicts_with = "testnet",
+        conflicts_with = "mainnet"
+    )]
+    pub simnet: bool,
+    /// Generate a deployment file for devnet, using settings/Devnet.toml
+    #[clap(
+        long = "devnet",
+        conflicts_with = "simnet",
+        conflicts_with = "testnet",
+        conflicts_with = "mainnet"
+    )]
+    pub devnet: bool,
+    /// Generate a deployment file for devnet, using settings/Testnet.toml
+    #[clap(
+        long = "testnet",
+        conflicts_with = "simnet",
+        conflicts_with = "devnet",
+        conflicts_with = "mainnet"
+    )]
+    pub testnet: bool,
+    /// Generate a deployment file for devnet, using settings/Mainnet.toml
+    #[clap(
+        long = "mainnet",
+        conflicts_with = "simnet",
+        conflicts_with = "testnet",
+        conflicts_with = "devnet"
+    )]
+    pub mainnet: bool,
+    /// Path to Clarinet.toml
+    #[clap(long = "manifest-path", short = 'm')]
+    pub manifest_path: Option<String>,
+    /// Generate a deployment file without trying to batch transactions (simnet only)
+    #[clap(
+        long = "no-batch",
+        conflicts_with = "devnet",
+        conflicts_with = "testnet",
+        conflicts_with = "mainnet"
+    )]
+    pub no_batch: bool,
+    /// Compute and set cost, using low priority (network connection required)
+    #[clap(
+        long = "low-cost",
+        conflicts_with = "medium_cost",
+        conflicts_with = "high_cost",
+        conflicts_with = "manual_cost"
+    )]
+    pub low_cost: bool,
+    /// Compute and set cost, using medium priority (network connection required)
+    #[clap(
+        conflicts_with = "low_cost",
+        long = "medium-cost",
+        conflicts_with = "high_cost",
+        conflicts_with = "manual_cost"
+    )]
+    pub medium_cost: bool,
+    /// Compute and set cost, using high priority (network connection required)
+    #[clap(
+        conflicts_with = "low_cost",
+        conflicts_with = "medium_cost",
+        long = "high-cost",
+        conflicts_with = "manual_cost"
+    )]
+    pub high_cost: bool,
+    /// Leave cost estimation manual
+    #[clap(
+        conflicts_with = "low_cost",
+        conflicts_with = "medium_cost",
+        conflicts_with = "high_cost",
+        long = "manual-cost"
+    )]
+    pub manual_cost: bool,
+    /// Fail if any warning is produced while generating the deployment plan
+    #[clap(long = "strict")]
+    pub strict: bool,
+    /// Only publish the contracts changed since this git revision, and the contracts depending on them
+    #[clap(long = "since", conflicts_with = "simnet")]
+    pub since: Option<String>,
+    /// Write the ASTs, dependencies, analysis summaries and interfaces of the contracts in this directory
+    #[clap(long = "emit-artifacts")]
+    pub emit_artifacts: Option<String>,
+    /// Format of the emitted ASTs
+    #[clap(long = "ast-format", value_parser = ["sexp", "json"], default_value = "sexp", requires = "emit_artifacts")]
+    pub ast_format: String,
+}
+
+#[derive(Parser, PartialEq, Clone, Debug)]
+struct ApplyDeployment {
+    /// Apply default deployment settings/default.devnet-plan.toml
+    #[clap(
+        long = "devnet",
+        conflicts_with = "deployment_plan_path",
+        conflicts_with = "testnet",
+        conflicts_with = "mainnet"
+    )]
+    pub devnet: bool,
+    /// Apply default deployment settings/default.testnet-plan.toml
+    #[clap(
+        long = "testnet",
+        conflicts_with = "deployment_plan_path",
+        conflicts_with = "devnet",
+        conflicts_with = "mainnet"
+    )]
+    pub testnet: bool,
+    /// Apply default deployment settings/default.mainnet-plan.toml
     #[clap(
         long = "mainnet",
         conflicts_with = "deployment_plan_path",
@@ -301,15 +1159,19 @@ struct ApplyDeployment {
     /// Path to Clarinet.toml
     #[clap(long = "manifest-path", short = 'm')]
     pub manifest_path: Option<String>,
-    /// Apply deployment plan specified
+    /// Apply deployment plan specified, a path or an http(s) URL
     #[clap(
         long = "deployment-plan-path",
+        alias = "plan",
         short = 'p',
         conflicts_with = "devnet",
         conflicts_with = "testnet",
         conflicts_with = "mainnet"
     )]
     pub deployment_plan_path: Option<String>,
+    /// SHA-256 of the deployment plan specified, checked before the plan is used
+    #[clap(long = "sha256", requires = "deployment_plan_path")]
+    pub sha256: Option<String>,
     /// Display streams of logs instead of terminal UI dashboard
     #[clap(long = "no-dashboard")]
     pub no_dashboard: bool,
@@ -327,6 +1189,72 @@ struct ApplyDeployment {
         conflicts_with = "use_on_disk_deployment_plan"
     )]
     pub use_computed_deployment_plan: bool,
+    /// Maximum number of transactions waiting for confirmation. Transactions of the next
+    /// batches are broadcasted without waiting for the current one when it's possible
+    #[clap(long = "max-in-flight")]
+    pub max_in_flight: Option<usize>,
+    /// Check the transactions against the current state of the network instead of
+    /// broadcasting them (contract names, contracts and functions called, balances)
+    #[clap(long = "simulate")]
+    pub simulate: bool,
+    /// Total fee (in µSTX) above which the plan is not applied, overrides the
+    /// `max_total_fee` of the network manifest
+    #[clap(long = "max-total-fee")]
+    pub max_total_fee: Option<u64>,
+    /// Apply the plan without asking for confirmation, even above the
+    /// `require_confirmation_above` fee of the network manifest
+    #[clap(long = "yes", short = 'y')]
+    pub yes: bool,
+    /// Append a comment block to the published sources, with the hash of the source, the
+    /// version of Clarinet and the id of the plan (see 'clarinet verify-metadata')
+    #[clap(long = "embed-metadata")]
+    pub embed_metadata: bool,
+}
+
+#[derive(Parser, PartialEq, Clone, Debug)]
+struct VerifyMetadata {
+    /// Name of the contract in Clarinet.toml
+    pub contract: String,
+    /// Read the deployed contract from Devnet (defaults to the plan of Testnet)
+    #[clap(long = "devnet", conflicts_with_all = &["testnet", "mainnet"])]
+    pub devnet: bool,
+    /// Read the deployed contract from Testnet
+    #[clap(long = "testnet", conflicts_with = "mainnet")]
+    pub testnet: bool,
+    /// Read the deployed contract from Mainnet
+    #[clap(long = "mainnet")]
+    pub mainnet: bool,
+    /// Stacks node to read the contract from (defaults to the node of the plan)
+    #[clap(long = "node", value_name = "URL")]
+    pub node: Option<String>,
+    /// Path to Clarinet.toml
+    #[clap(long = "manifest-path", short = 'm')]
+    pub manifest_path: Option<String>,
+    /// Output the verification as JSON
+    #[clap(long = "json")]
+    pub json: bool,
+}
+
+#[derive(Parser, PartialEq, Clone, Debug)]
+struct CallReadOnly {
+    /// Contract to call, e.g. SP2C2YFP12AJZB4MABJBAJ55XECVS7E4PMMZ89YZR.arkadiko-token
+    pub contract_id: String,
+    /// Read-only function to call
+    pub function: String,
+    /// Arguments of the function, as Clarity literals (e.g. u1, 'SP000000000000000000002Q6VF78)
+    pub args: Vec<String>,
+    /// Call the function on Testnet (defaults to Mainnet)
+    #[clap(long = "testnet", conflicts_with = "devnet")]
+    pub testnet: bool,
+    /// Call the function on Devnet
+    #[clap(long = "devnet")]
+    pub devnet: bool,
+    /// Stacks node to call (defaults to the Hiro API of the network)
+    #[clap(long = "node", value_name = "URL")]
+    pub node: Option<String>,
+    /// Sender of the call (defaults to the deployer of the contract)
+    #[clap(long = "sender")]
+    pub sender: Option<String>,
 }
 
 #[derive(Parser, PartialEq, Clone, Debug)]
@@ -354,6 +1282,12 @@ struct Console {
     /// Allow the Clarity Wasm preview to run in parallel with the Clarity interpreter (beta)
     #[clap(long = "enable-clarity-wasm")]
     pub enable_clarity_wasm: bool,
+    /// Display the time spent in each phase of the session setup
+    #[clap(long = "profile-startup")]
+    pub profile_startup: bool,
+    /// Execute the deployment plan even if the session image of the cache is up to date
+    #[clap(long = "no-session-image")]
+    pub no_session_image: bool,
 }
 
 #[derive(Parser, PartialEq, Clone, Debug)]
@@ -364,9 +1298,26 @@ struct DevnetStart {
     /// Display streams of logs instead of terminal UI dashboard
     #[clap(long = "no-dashboard")]
     pub no_dashboard: bool,
+    /// Write the events of the devnet (services, blocks, deployment, ready, fatal errors) on
+    /// stdout as newline-delimited JSON, instead of the dashboard
+    #[clap(long = "json")]
+    pub json: bool,
     /// If specified, use this deployment file
-    #[clap(long = "deployment-plan-path", short = 'p')]
+    #[clap(
+        long = "deployment-plan-path",
+        visible_alias = "deployment-plan",
+        short = 'p'
+    )]
     pub deployment_plan_path: Option<String>,
+    /// Boot the network without deploying the project
+    #[clap(
+        long = "no-deployment",
+        conflicts_with = "deployment_plan_path",
+        conflicts_with = "use_on_disk_deployment_plan",
+        conflicts_with = "use_computed_deployment_plan",
+        conflicts_with = "package"
+    )]
+    pub no_deployment: bool,
     /// Use on disk deployment plan (prevent updates computing)
     #[clap(
         long = "use-on-disk-deployment-plan",
@@ -388,6 +1339,19 @@ struct DevnetStart {
         conflicts_with = "manifest_path"
     )]
     pub package: Option<String>,
+    /// Replace the ports already in use by free ones, to run several devnets side by side
+    #[clap(long = "auto-ports")]
+    pub auto_ports: bool,
+    /// Only start these services (e.g. bitcoin,stacks-node,signer) or profiles (minimal,
+    /// default, full)
+    #[clap(long = "services", value_delimiter = ',')]
+    pub services: Option<Vec<String>>,
+    #[clap(flatten)]
+    pub epochs: DevnetEpochs,
+    /// Keep mining while checking the invariants of [devnet.soak], for the given number of
+    /// minutes or the duration set in settings/Devnet.toml
+    #[clap(long = "soak", value_name = "MINUTES")]
+    pub soak: Option<Option<u64>>,
 }
 
 #[derive(Parser, PartialEq, Clone, Debug)]
@@ -417,6 +1381,30 @@ struct Check {
     /// Allow the Clarity Wasm preview to run in parallel with the Clarity interpreter (beta)
     #[clap(long = "enable-clarity-wasm")]
     pub enable_clarity_wasm: bool,
+    /// Display the time spent in each phase of the session setup
+    #[clap(long = "profile-startup")]
+    pub profile_startup: bool,
+    /// Record the current diagnostics in the baseline file, later checks only report new diagnostics
+    #[clap(long = "write-baseline")]
+    pub write_baseline: bool,
+    /// Path to the baseline file (default: clarinet-baseline.json, at the root of the project)
+    #[clap(long = "baseline-path")]
+    pub baseline_path: Option<String>,
+    /// Maximum number of diagnostics to display
+    #[clap(long = "max-diagnostics")]
+    pub max_diagnostics: Option<usize>,
+    /// Only check the contracts staged in the git index and the contracts depending on them
+    #[clap(
+        long = "staged",
+        conflicts_with_all = &[
+            "file",
+            "deployment_plan_path",
+            "use_on_disk_deployment_plan",
+            "enable_clarity_wasm",
+            "write_baseline",
+        ]
+    )]
+    pub staged: bool,
 }
 
 #[derive(Parser, PartialEq, Clone, Debug)]
@@ -426,6 +1414,16 @@ struct Completions {
     pub shell: Shell,
 }
 
+#[derive(Parser, PartialEq, Clone, Debug)]
+struct CompleteValues {
+    /// Kind of values to list
+    #[clap(value_enum)]
+    pub kind: CompletionValueKind,
+    /// Path to Clarinet.toml
+    #[clap(long = "manifest-path", short = 'm')]
+    pub manifest_path: Option<String>,
+}
+
 pub fn main() {
     let opts: Opts = match Opts::try_parse() {
         Ok(opts) => opts,
@@ -454,7 +1452,29 @@ pub fn main() {
         }
     };
 
+    output::exit_on_main_thread_panic();
+    output::set_output_mode(match (opts.quiet, opts.summary_only) {
+        (true, _) => OutputMode::Quiet,
+        (_, true) => OutputMode::SummaryOnly,
+        _ => OutputMode::Default,
+    });
+
+    // dynamic completions are consumed by the shell, nothing else should be printed
+    if let Command::CompleteValues(cmd) = &opts.command {
+        for value in get_completion_values(&cmd.kind, cmd.manifest_path.clone()) {
+            println!("{}", value);
+        }
+        return;
+    }
+
     let global_settings = GlobalSettings::from_global_file();
+    hiro_system_kit::set_colors(global_settings.color);
+    let mut http_settings = global_settings.http.clone().unwrap_or_default();
+    if http_settings.cache_dir.is_none() {
+        http_settings.cache_dir = dirs::home_dir()
+            .map(|home_dir| home_dir.join(".clarinet/cache/http").display().to_string());
+    }
+    clarinet_files::net::set_http_settings(http_settings);
 
     match opts.command {
         Command::Completions(cmd) => {
@@ -473,9 +1493,21 @@ pub fn main() {
                 }
             };
             clap_complete::generate(cmd.shell, &mut app, "clarinet", &mut file);
+            if let Some(script) = get_dynamic_completion_script(cmd.shell) {
+                if let Err(e) = file.write_all(script.as_bytes()) {
+                    eprintln!(
+                        "{} Unable to write file {}: {}",
+                        red!("error:"),
+                        file_name,
+                        e
+                    );
+                    std::process::exit(1);
+                }
+            }
             println!("{} {}", green!("Created file"), file_name.clone());
             println!("Check your shell's documentation for details about using this file to enable completions for clarinet");
         }
+        Command::CompleteValues(_) => unreachable!(),
         Command::New(project_opts) => {
             let current_path = std::env::current_dir().unwrap_or_else(|e| {
                 eprintln!("{}{}", format_err!("unable to get current directory"), e);
@@ -562,11 +1594,26 @@ pub fn main() {
                 );
             }
 
+            let template = match project_opts
+                .template
+                .as_deref()
+                .or(global_settings.default_template.as_deref())
+            {
+                Some(name) => match ProjectTemplate::from_name(name) {
+                    Some(template) => Some(template),
+                    None => {
+                        eprintln!("{}", format_err!(format!("unknown template '{}'", name)));
+                        std::process::exit(1);
+                    }
+                },
+                None => None,
+            };
             let changes = match generate::get_changes_for_new_project(
                 project_path,
                 project_id,
                 use_current_dir,
                 telemetry_enabled,
+                template,
             ) {
                 Ok(changes) => changes,
                 Err(message) => {
@@ -589,18 +1636,52 @@ pub fn main() {
                 )));
             }
         }
+        Command::Init(cmd) => init_project(cmd, &global_settings),
         Command::Deployments(subcommand) => match subcommand {
             Deployments::CheckDeployments(cmd) => {
                 let manifest = load_manifest_or_exit(cmd.manifest_path);
                 // Ensure that all the deployments can correctly be deserialized.
-                println!("Checking deployments");
+                if output::show_details() {
+                    println!("Checking deployments");
+                }
                 let res = check_deployments(&manifest);
                 if let Err(message) = res {
-                    eprintln!("{}", format_err!(message));
-                    process::exit(1);
+                    exit_with_error(ExitCode::Config, message);
+                }
+            }
+            Deployments::ExportDeployment(cmd) => {
+                let manifest = load_manifest_or_exit(cmd.manifest_path);
+                let deployment_location = match cmd.deployment_plan_path {
+                    Some(ref path) => get_absolute_deployment_path(&manifest, path),
+                    None => {
+                        let network = if cmd.devnet {
+                            StacksNetwork::Devnet
+                        } else if cmd.mainnet {
+                            StacksNetwork::Mainnet
+                        } else {
+                            StacksNetwork::Testnet
+                        };
+                        get_default_deployment_path(&manifest, &network)
+                    }
+                };
+                let exported = deployment_location
+                    .and_then(|location| load_deployment(&manifest, &location))
+                    .and_then(|deployment| platform_export::export_deployment_plan(&deployment));
+                match exported {
+                    Ok(exported) => {
+                        println!("{}", serde_json::to_string_pretty(&exported).unwrap())
+                    }
+                    Err(message) => {
+                        exit_with_error(ExitCode::Config, message);
+                    }
                 }
             }
+            Deployments::DeploymentChangelog(cmd) => deployment_changelog(cmd),
+            Deployments::EditDeployment(cmd) => edit_deployment(cmd),
+            Deployments::ImportDeployment(cmd) => import_deployment(cmd),
+            Deployments::ReplayEvents(cmd) => replay_events(cmd),
             Deployments::GenerateDeployment(cmd) => {
+                deployments::cancel_on_interrupt();
                 let manifest = load_manifest_or_exit(cmd.manifest_path);
 
                 let network = if cmd.devnet {
@@ -615,14 +1696,28 @@ pub fn main() {
 
                 let default_deployment_path =
                     get_default_deployment_path(&manifest, &network).unwrap();
-                let (mut deployment, _) =
+                let (mut deployment, artifacts) =
                     match generate_default_deployment(&manifest, &network, cmd.no_batch) {
                         Ok(deployment) => deployment,
+                        Err(_) if deployments::is_interrupted() => exit_interrupted(),
                         Err(message) => {
-                            eprintln!("{}", format_err!(message));
-                            std::process::exit(1);
+                            exit_with_error(ExitCode::Config, message);
                         }
                     };
+                use_default_stacks_node(&mut deployment, &manifest, &global_settings);
+
+                let mut warnings: Vec<String> = artifacts
+                    .warnings
+                    .iter()
+                    .map(|warning| warning.to_string())
+                    .collect();
+                for (contract_id, diags) in artifacts.diags.iter() {
+                    for diag in diags.iter() {
+                        if matches!(diag.level, Level::Warning) {
+                            warnings.push(format!("{}: {}", contract_id.name, diag.message));
+                        }
+                    }
+                }
 
                 if !cmd.manual_cost
                     && matches!(network, StacksNetwork::Testnet | StacksNetwork::Mainnet)
@@ -632,20 +1727,79 @@ pub fn main() {
                         (_, true, _) => 1,
                         (true, _, _) => 0,
                         (false, false, false) => {
-                            eprintln!("{}", format_err!("cost strategy not specified (--low-cost, --medium-cost, --high-cost, --manual-cost)"));
-                            std::process::exit(1);
+                            exit_with_error(ExitCode::Config, "cost strategy not specified (--low-cost, --medium-cost, --high-cost, --manual-cost)");
                         }
                     };
-                    match update_deployment_costs(&mut deployment, priority) {
-                        Ok(_) => {}
+                    if let Err(message) = update_deployment_costs(&mut deployment, priority) {
+                        warnings.push(format!("unable to update costs\n{}", message));
+                    };
+                }
+
+                for warning in warnings.iter() {
+                    eprintln!("{} {}", yellow!("warning:"), warning);
+                }
+                if cmd.strict && !warnings.is_empty() {
+                    eprintln!(
+                        "{}",
+                        format_err!(format!(
+                            "{} detected in strict mode",
+                            pluralize!(warnings.len(), "warning")
+                        ))
+                    );
+                    ExitCode::Diagnostics.exit();
+                }
+
+                if let Some(ref revision) = cmd.since {
+                    if matches!(network, StacksNetwork::Simnet) {
+                        eprintln!(
+                            "{}",
+                            format_err!("--since requires --devnet, --testnet or --mainnet")
+                        );
+                        ExitCode::Config.exit();
+                    }
+                    let changed = match deployments::get_contracts_changed_since(
+                        &manifest,
+                        &deployment,
+                        &artifacts,
+                        revision,
+                    ) {
+                        Ok(changed) => changed,
                         Err(message) => {
-                            eprintln!(
-                                "{} unable to update costs\n{}",
-                                yellow!("warning:"),
-                                message
-                            );
+                            exit_with_error(ExitCode::Config, message);
                         }
                     };
+                    if changed.is_empty() {
+                        if output::show_summary() {
+                            println!("No contract changed since {}", revision);
+                        }
+                        return;
+                    }
+                    let contracts = get_contracts_to_republish(&changed, &artifacts.deps);
+                    let upgrade = get_upgrade_deployment(&deployment, &contracts);
+                    let upgrade_deployment_path =
+                        deployments::get_upgrade_deployment_path(&manifest, &network).unwrap();
+                    if let Err(message) =
+                        write_deployment(&upgrade, &upgrade_deployment_path, false)
+                    {
+                        exit_with_error(ExitCode::Internal, message);
+                    }
+                    if output::show_details() {
+                        for contract_id in contracts.iter() {
+                            let reason = match changed.contains(contract_id) {
+                                true => "changed",
+                                false => "depends on a changed contract",
+                            };
+                            println!("{} {} ({})", yellow!("publish"), contract_id.name, reason);
+                        }
+                    }
+                    if output::show_summary() {
+                        println!(
+                            "{} {}",
+                            green!("Generated file"),
+                            upgrade_deployment_path.get_relative_location().unwrap()
+                        );
+                    }
+                    return;
                 }
 
                 let write_plan = if default_deployment_path.exists() {
@@ -657,8 +1811,9 @@ pub fn main() {
                                     "unable to load {default_deployment_path}\n{message}",
                                 ))
                             );
-                            process::exit(1);
+                            ExitCode::Config.exit();
                         });
+                    deployment.keep_descriptions(&existing_deployment);
                     should_existing_plan_be_replaced(&existing_deployment, &deployment)
                 } else {
                     true
@@ -667,15 +1822,40 @@ pub fn main() {
                 if write_plan {
                     let res = write_deployment(&deployment, &default_deployment_path, false);
                     if let Err(message) = res {
-                        eprintln!("{}", format_err!(message));
-                        process::exit(1);
+                        exit_with_error(ExitCode::Internal, message);
                     }
 
-                    println!(
-                        "{} {}",
-                        green!("Generated file"),
-                        default_deployment_path.get_relative_location().unwrap()
-                    );
+                    if output::show_summary() {
+                        println!(
+                            "{} {}",
+                            green!("Generated file"),
+                            default_deployment_path.get_relative_location().unwrap()
+                        );
+                    }
+                }
+
+                if let Some(output_dir) = cmd.emit_artifacts {
+                    let res = AstFormat::try_from(cmd.ast_format.as_str()).and_then(|format| {
+                        let output_dir = FileLocation::from_path_string(&output_dir)?;
+                        artifacts_export::write_artifacts(
+                            &deployment,
+                            &artifacts,
+                            &output_dir,
+                            format,
+                        )
+                    });
+                    match res {
+                        Ok(_) if !output::show_summary() => {}
+                        Ok(files) => println!(
+                            "{} {} in {}",
+                            green!("Emitted"),
+                            pluralize!(files.len(), "artifact"),
+                            output_dir
+                        ),
+                        Err(message) => {
+                            exit_with_error(ExitCode::Config, message);
+                        }
+                    }
                 }
             }
             Deployments::ApplyDeployment(cmd) => {
@@ -691,6 +1871,12 @@ pub fn main() {
                     None
                 };
 
+                let deployment_location = match (&network, &cmd.deployment_plan_path) {
+                    (Some(network), None) => get_default_deployment_path(&manifest, network).ok(),
+                    (None, Some(path)) => get_absolute_deployment_path(&manifest, path).ok(),
+                    _ => None,
+                };
+
                 let result = match (&network, cmd.deployment_plan_path) {
                     (None, None) => {
                         Err(format!("{}: a flag `--devnet`, `--testnet`, `--mainnet` or `--deployment-plan-path=path/to/yaml` should be provided.", yellow!("Command usage")))
@@ -699,28 +1885,33 @@ pub fn main() {
                         let res = load_deployment_if_exists(&manifest, network, cmd.use_on_disk_deployment_plan, cmd.use_computed_deployment_plan);
                         match res {
                             Some(Ok(deployment)) => {
-                                println!(
-                                    "{} using existing deployments/default.{}-plan.yaml",
-                                    yellow!("note:"),
-                                    format!("{:?}", network).to_lowercase(),
-                                );
+                                if output::show_details() {
+                                    println!(
+                                        "{} using existing deployments/default.{}-plan.yaml",
+                                        yellow!("note:"),
+                                        format!("{:?}", network).to_lowercase(),
+                                    );
+                                }
                                 Ok(deployment)
                             }
                             Some(Err(e)) => Err(e),
                             None => {
                                 let default_deployment_path = get_default_deployment_path(&manifest, network).unwrap();
-                                let (deployment, _) = match generate_default_deployment(&manifest, network, false) {
+                                let (mut deployment, _) = match generate_default_deployment(&manifest, network, false) {
                                     Ok(deployment) => deployment,
                                     Err(message) => {
                                         eprintln!("{}", red!(message));
-                                        std::process::exit(1);
+                                        ExitCode::Config.exit();
                                     }
                                 };
+                                use_default_stacks_node(&mut deployment, &manifest, &global_settings);
                                 let res = write_deployment(&deployment, &default_deployment_path, true);
                                 if let Err(message) = res {
                                     Err(message)
                                 } else {
-                                    println!("{} {}", green!("Generated file"), default_deployment_path.get_relative_location().unwrap());
+                                    if output::show_summary() {
+                                        println!("{} {}", green!("Generated file"), default_deployment_path.get_relative_location().unwrap());
+                                    }
                                     Ok(deployment)
                                 }
                             }
@@ -728,28 +1919,118 @@ pub fn main() {
                     }
                     (None, Some(deployment_plan_path)) => {
                         let deployment_path = get_absolute_deployment_path(&manifest, &deployment_plan_path).expect("unable to retrieve deployment");
-                        load_deployment(&manifest, &deployment_path)
+                        load_pinned_deployment(&manifest, &deployment_path, cmd.sha256.as_deref())
                     }
                     (_, _) => unreachable!()
                 };
 
-                let deployment = match result {
+                let mut deployment = match result {
                     Ok(deployment) => deployment,
                     Err(e) => {
                         eprintln!("{}", e);
-                        std::process::exit(1);
+                        ExitCode::Config.exit();
                     }
                 };
+                if cmd.embed_metadata {
+                    match source_metadata::embed_source_metadata(&mut deployment) {
+                        Ok(embedded) if output::show_details() => println!(
+                            "{} metadata embedded in {}",
+                            yellow!("note:"),
+                            pluralize!(embedded, "contract source")
+                        ),
+                        Ok(_) => {}
+                        Err(e) => exit_with_error(ExitCode::Internal, e),
+                    }
+                }
                 let network = deployment.network.clone();
 
                 let node_url = deployment.stacks_node.clone().unwrap();
 
-                println!(
-                    "The following deployment plan will be applied:\n{}\n\n",
-                    DeploymentSynthesis::from_deployment(&deployment)
-                );
+                if cmd.simulate {
+                    if output::show_details() {
+                        println!(
+                            "Simulating the deployment plan against {}:\n{}\n",
+                            node_url,
+                            DeploymentSynthesis::from_deployment(&deployment)
+                        );
+                    }
+                    match simulate_deployment(&deployment) {
+                        Ok(report) => {
+                            print_simulation_report(&report);
+                            if !report.is_success() {
+                                ExitCode::Diagnostics.exit();
+                            }
+                        }
+                        Err(e) => {
+                            exit_with_error(ExitCode::Network, e);
+                        }
+                    }
+                    return;
+                }
+
+                if output::show_details() {
+                    println!(
+                        "The following deployment plan will be applied:\n{}\n\n",
+                        DeploymentSynthesis::from_deployment(&deployment)
+                    );
+                }
+
+                let network_manifest = match NetworkManifest::from_project_manifest_location(
+                    &manifest.location,
+                    &network.get_networks(),
+                    Some(&manifest.project.cache_location),
+                    None,
+                ) {
+                    Ok(network_manifest) => network_manifest,
+                    Err(e) => {
+                        exit_with_error(ExitCode::Config, e);
+                    }
+                };
+                if let Err(e) = network_manifest.ensure_mainnet_keys_allowed() {
+                    exit_with_error(ExitCode::Config, e);
+                }
+                let redactor = network_manifest.clone();
+
+                let mut fee_policy = FeePolicy::from_network_manifest(&network_manifest);
+                if cmd.max_total_fee.is_some() {
+                    fee_policy.max_total_fee = cmd.max_total_fee;
+                }
+                let total_fee = get_total_fee(&deployment);
+                match fee_policy.check(total_fee) {
+                    FeeCheck::Accepted => {}
+                    FeeCheck::Refused { max_total_fee } => {
+                        eprintln!(
+                            "{}",
+                            format_err!(format!(
+                                "the total fee of the plan ({}) is above the maximum of {} (max_total_fee), check the fee rate and the costs of the plan",
+                                format_stx(total_fee),
+                                format_stx(max_total_fee)
+                            ))
+                        );
+                        ExitCode::Diagnostics.exit();
+                    }
+                    FeeCheck::ConfirmationRequired { threshold } if !cmd.yes => {
+                        let network_name = format!("{:?}", network).to_lowercase();
+                        println!(
+                            "{}",
+                            yellow!(format!(
+                                "The total fee of the plan ({}) is above {} (require_confirmation_above), type '{}' to continue:",
+                                format_stx(total_fee),
+                                format_stx(threshold),
+                                network_name
+                            ))
+                        );
+                        let mut buffer = String::new();
+                        std::io::stdin().read_line(&mut buffer).unwrap();
+                        if buffer.trim() != network_name {
+                            eprintln!("Deployment aborted");
+                            ExitCode::Diagnostics.exit();
+                        }
+                    }
+                    FeeCheck::ConfirmationRequired { .. } => {}
+                }
 
-                if !cmd.use_on_disk_deployment_plan {
+                if !cmd.use_on_disk_deployment_plan && !cmd.yes {
                     println!("{}", yellow!("Continue [Y/n]?"));
                     let mut buffer = String::new();
                     std::io::stdin().read_line(&mut buffer).unwrap();
@@ -758,13 +2039,12 @@ pub fn main() {
                         && !buffer.starts_with('\n')
                     {
                         eprintln!("Deployment aborted");
-                        std::process::exit(1);
+                        ExitCode::Diagnostics.exit();
                     }
                 }
 
                 let (command_tx, command_rx) = std::sync::mpsc::channel();
                 let (event_tx, event_rx) = std::sync::mpsc::channel();
-                let manifest_moved = manifest.clone();
 
                 if manifest.project.telemetry {
                     #[cfg(feature = "telemetry")]
@@ -782,22 +2062,7 @@ pub fn main() {
                 } else {
                     get_initial_transactions_trackers(&deployment)
                 };
-                let network_moved = network.clone();
                 std::thread::spawn(move || {
-                    let manifest = manifest_moved;
-                    let res = NetworkManifest::from_project_manifest_location(
-                        &manifest.location,
-                        &network_moved.get_networks(),
-                        Some(&manifest.project.cache_location),
-                        None,
-                    );
-                    let network_manifest = match res {
-                        Ok(network_manifest) => network_manifest,
-                        Err(e) => {
-                            let _ = event_tx.send(DeploymentEvent::Interrupted(e));
-                            return;
-                        }
-                    };
                     apply_on_chain_deployment(
                         network_manifest,
                         deployment,
@@ -806,11 +2071,14 @@ pub fn main() {
                         true,
                         None,
                         None,
+                        cmd.max_in_flight,
                     );
                 });
 
                 let _ = command_tx.send(DeploymentCommand::Start);
 
+                let mut receipt = DeploymentReceipt::default();
+                let mut publish_failed = false;
                 if cmd.no_dashboard {
                     loop {
                         let cmd = match event_rx.recv() {
@@ -822,40 +2090,106 @@ pub fn main() {
                                 eprintln!(
                                     "{} Error publishing transactions: {}",
                                     red!("x"),
-                                    message
+                                    redactor.redact(&message)
                                 );
+                                publish_failed = true;
                                 break;
                             }
                             DeploymentEvent::TransactionUpdate(update) => {
-                                println!("{} {:?} {}", blue!("➡"), update.status, update.name);
+                                if output::show_details() {
+                                    println!("{} {:?} {}", blue!("➡"), update.status, update.name);
+                                }
                             }
-                            DeploymentEvent::DeploymentCompleted => {
+                            DeploymentEvent::PostCheckCompleted(result) => {
+                                print_post_check_result(&result);
+                                receipt.post_checks.push(result);
+                            }
+                            DeploymentEvent::WaitingForEpoch(wait) if output::show_details() => {
                                 println!(
-                                    "{} Transactions successfully confirmed on {:?}",
-                                    green!("✔"),
-                                    network
+                                    "{} Batch {} waiting for epoch {} (bitcoin block #{}/{})",
+                                    yellow!("…"),
+                                    wait.batch_id,
+                                    wait.epoch,
+                                    wait.bitcoin_block_height,
+                                    wait.activation_height
                                 );
+                            }
+                            DeploymentEvent::WaitingForEpoch(_) => {}
+                            DeploymentEvent::DeploymentCompleted => {
+                                if output::show_summary() {
+                                    println!(
+                                        "{} Transactions successfully confirmed on {:?}",
+                                        green!("✔"),
+                                        network
+                                    );
+                                }
                                 break;
                             }
                         }
                     }
                 } else {
-                    let res = deployments::start_ui(&node_url, event_rx, transaction_trackers);
+                    let res = deployments::start_ui(
+                        &node_url,
+                        event_rx,
+                        transaction_trackers,
+                        &mut receipt,
+                    );
                     match res {
-                        Ok(()) => println!(
+                        Ok(()) if output::show_summary() => println!(
                             "{} Transactions successfully confirmed on {:?}",
                             green!("✔"),
                             network
                         ),
+                        Ok(()) => {}
                         Err(message) => {
-                            eprintln!("{} Error publishing transactions: {}", red!("x"), message)
+                            eprintln!(
+                                "{} Error publishing transactions: {}",
+                                red!("x"),
+                                redactor.redact(&message)
+                            );
+                            publish_failed = true;
+                        }
+                    }
+                    for result in receipt.post_checks.iter() {
+                        print_post_check_result(result);
+                    }
+                }
+
+                if !receipt.post_checks.is_empty() {
+                    if let Some(location) =
+                        deployment_location.as_ref().and_then(get_receipt_location)
+                    {
+                        let content = serde_json::to_vec_pretty(&receipt)
+                            .expect("unable to serialize the receipt");
+                        match location.write_content(&content) {
+                            Ok(()) if !output::show_details() => {}
+                            Ok(()) => println!(
+                                "{} receipt written to {}",
+                                yellow!("note:"),
+                                location
+                                    .get_relative_location()
+                                    .unwrap_or(location.to_string())
+                            ),
+                            Err(e) => eprintln!("{}", format_err!(e)),
                         }
                     }
+                    if !receipt.is_success() {
+                        ExitCode::Diagnostics.exit();
+                    }
+                }
+                if publish_failed {
+                    ExitCode::Network.exit();
                 }
             }
         },
-        Command::Chainhooks => {
-            let message = "This command is deprecated. Use the chainhooks library instead (https://github.com/hirosystems/chainhook)";
+        Command::Chainhooks(ChainhooksCommand {
+            command: Some(Chainhooks::Test(cmd)),
+        }) => test_chainhook(cmd),
+        Command::Chainhooks(ChainhooksCommand {
+            command: Some(Chainhooks::Load(cmd)),
+        }) => simulate_chainhooks_load(cmd),
+        Command::Chainhooks(ChainhooksCommand { command: None }) => {
+            let message = "This command is deprecated, except for `clarinet chainhooks test` and `clarinet chainhooks load`. Use the chainhooks library instead (https://github.com/hirosystems/chainhook)";
             eprintln!("{}", format_err!(message));
             std::process::exit(1);
         }
@@ -914,6 +2248,55 @@ pub fn main() {
                     display_post_check_hint();
                 }
             }
+            Contracts::ScaffoldContract(cmd) => {
+                let manifest = load_manifest_or_exit(cmd.manifest_path);
+                let interface = match fs::read_to_string(&cmd.interface_path) {
+                    Ok(content) => match serde_json::from_str(&content) {
+                        Ok(interface) => interface,
+                        Err(e) => {
+                            eprintln!(
+                                "{} unable to parse interface {}: {}",
+                                red!("error:"),
+                                cmd.interface_path,
+                                e
+                            );
+                            std::process::exit(1);
+                        }
+                    },
+                    Err(e) => {
+                        eprintln!(
+                            "{} unable to read file {}: {}",
+                            red!("error:"),
+                            cmd.interface_path,
+                            e
+                        );
+                        std::process::exit(1);
+                    }
+                };
+
+                let changes = match generate::get_changes_for_scaffold_contract(
+                    &manifest.location,
+                    cmd.name,
+                    interface,
+                ) {
+                    Ok(changes) => changes,
+                    Err(message) => {
+                        eprintln!("{}", format_err!(message));
+                        std::process::exit(1);
+                    }
+                };
+
+                if !execute_changes(changes) {
+                    std::process::exit(1);
+                }
+                if global_settings.enable_hints.unwrap_or(true) {
+                    display_post_check_hint();
+                }
+            }
+            Contracts::ContractImpact(cmd) => contract_impact(cmd),
+        },
+        Command::Generate(subcommand) => match subcommand {
+            Generate::Tests(cmd) => generate_tests(cmd),
         },
         Command::Requirements(subcommand) => match subcommand {
             Requirements::AddRequirement(cmd) => {
@@ -927,9 +2310,13 @@ pub fn main() {
                     ),
                     manifest_location: manifest.location.clone(),
                     contracts_to_rm: vec![],
+                    contracts_networks: HashMap::new(),
                     contracts_to_add: HashMap::new(),
                     requirements_to_add: vec![RequirementConfig {
                         contract_id: cmd.contract_id.clone(),
+                        mock: false,
+                        epoch: None,
+                        clarity_version: None,
                     }],
                 };
                 if !execute_changes(vec![Changes::EditTOML(change)]) {
@@ -939,14 +2326,63 @@ pub fn main() {
                     display_post_check_hint();
                 }
             }
+            Requirements::DiffRequirement(cmd) => diff_requirement(cmd),
+            Requirements::ReportRequirements(cmd) => report_requirements(cmd),
+            Requirements::OutdatedRequirements(cmd) => outdated_requirements(cmd),
+            Requirements::ExplainRequirement(cmd) => explain_requirement(cmd),
+        },
+        Command::Config(subcommand) => match subcommand {
+            Config::Get(cmd) => config_get(cmd, &global_settings),
+            Config::Set(cmd) => config_set(cmd),
+        },
+        Command::Stats(cmd) => project_stats(cmd),
+        Command::VerifyMetadata(cmd) => verify_metadata(cmd),
+        Command::CallReadOnly(cmd) => call_read_only(cmd),
+        Command::Bench(cmd) => bench_snippet(cmd),
+        Command::Scenarios(cmd) => run_scenarios(cmd),
+        Command::Migrate(cmd) => migrate_contracts(cmd),
+        Command::MigrateProject(cmd) => migrate_project(cmd),
+        Command::Refactor(subcommand) => match subcommand {
+            Refactor::ReplacePrincipal(cmd) => replace_principal(cmd),
+        },
+        Command::Repro(subcommand) => match subcommand {
+            Repro::Load(cmd) => repro_load(cmd),
+        },
+        Command::Inspect(subcommand) => match subcommand {
+            Inspect::StorageLayout(cmd) => inspect_storage_layout(cmd),
+            Inspect::Versions(cmd) => inspect_versions(cmd),
+        },
+        Command::Cache(subcommand) => match subcommand {
+            Cache::List(cmd) => cache_list(cmd),
+            Cache::Verify(cmd) => cache_verify(cmd),
+            Cache::Clean(cmd) => cache_clean(cmd),
         },
         Command::Console(cmd) => {
             // Loop to handle `::reload` command
             loop {
+                if cmd.profile_startup {
+                    profiling::start_profiling();
+                }
+                let timer = PhaseTimer::start("manifest loading");
                 let manifest = load_manifest_or_warn(cmd.manifest_path.clone());
+                timer.end();
+
+                let use_session_image = cmd.deployment_plan_path.is_none()
+                    && !cmd.enable_clarity_wasm
+                    && !cmd.no_session_image;
+                let restored_session = match manifest {
+                    Some(ref manifest) if use_session_image => {
+                        let timer = PhaseTimer::start("session image loading");
+                        let session = session_image::load_session_image(manifest);
+                        timer.end();
+                        session
+                    }
+                    _ => None,
+                };
 
-                let mut terminal = match manifest {
-                    Some(ref manifest) => {
+                let mut terminal = match (&manifest, restored_session) {
+                    (Some(_), Some(session)) => Terminal::load(session, None),
+                    (Some(manifest), None) => {
                         let (deployment, _, artifacts) = load_deployment_and_artifacts_or_exit(
                             manifest,
                             &cmd.deployment_plan_path,
@@ -956,7 +2392,7 @@ pub fn main() {
 
                         if !artifacts.success {
                             let diags_digest =
-                                DiagnosticsDigest::new(&artifacts.diags, &deployment);
+                                DiagnosticsDigest::from_artifacts(&artifacts, &deployment);
                             if diags_digest.has_feedbacks() {
                                 println!("{}", diags_digest.message);
                             }
@@ -984,10 +2420,21 @@ pub fn main() {
 
                             Terminal::load(artifacts.session, Some(wasm_artifacts.session))
                         } else {
+                            if use_session_image {
+                                if let Ok(image) =
+                                    session_image::get_session_image(manifest, &artifacts.session)
+                                {
+                                    let manifest = manifest.clone();
+                                    std::thread::spawn(move || {
+                                        let _ =
+                                            session_image::write_session_image(&manifest, &image);
+                                    });
+                                }
+                            }
                             Terminal::load(artifacts.session, None)
                         }
                     }
-                    None => {
+                    (None, _) => {
                         let settings = repl::SessionSettings::default();
                         if cmd.enable_clarity_wasm {
                             let mut settings_wasm = repl::SessionSettings::default();
@@ -998,6 +2445,9 @@ pub fn main() {
                         }
                     }
                 };
+                if let Some(profile) = profiling::stop_profiling() {
+                    display_startup_profile(&profile);
+                }
                 let reload = terminal.start();
 
                 // Report telemetry
@@ -1049,7 +2499,7 @@ pub fn main() {
                 Ok(code) => code,
                 _ => {
                     eprintln!("{} unable to read file: '{}'", red!("error:"), file);
-                    std::process::exit(1);
+                    ExitCode::Config.exit();
                 }
             };
             let contract_id = QualifiedContractIdentifier::transient();
@@ -1091,26 +2541,69 @@ pub fn main() {
 
             let lines = contract.expect_in_memory_code_source().lines();
             let formatted_lines: Vec<String> = lines.map(|l| l.to_string()).collect();
-            for d in diagnostics {
-                for line in output_diagnostic(&d, &file, &formatted_lines) {
-                    println!("{}", line);
+            if output::show_details() {
+                for d in diagnostics {
+                    for line in output_diagnostic(&d, &file, &formatted_lines) {
+                        println!("{}", line);
+                    }
                 }
             }
 
             if success {
-                println!("{} Syntax of contract successfully checked", green!("✔"))
+                if output::show_summary() {
+                    println!("{} Syntax of contract successfully checked", green!("✔"))
+                }
             } else {
-                std::process::exit(1);
+                ExitCode::Diagnostics.exit();
             }
         }
         Command::Check(cmd) => {
+            if cmd.profile_startup {
+                profiling::start_profiling();
+            }
+            deployments::cancel_on_interrupt();
+            let timer = PhaseTimer::start("manifest loading");
             let manifest = load_manifest_or_exit(cmd.manifest_path);
-            let (deployment, _, artifacts) = load_deployment_and_artifacts_or_exit(
-                &manifest,
-                &cmd.deployment_plan_path,
-                cmd.use_on_disk_deployment_plan,
-                cmd.use_computed_deployment_plan,
-            );
+            timer.end();
+            let (deployment, mut artifacts, checked) = if cmd.staged {
+                let (deployment, artifacts, checked) =
+                    load_staged_deployment_and_artifacts_or_exit(&manifest);
+                (deployment, artifacts, Some(checked))
+            } else {
+                let (deployment, _, artifacts) = load_deployment_and_artifacts_or_exit(
+                    &manifest,
+                    &cmd.deployment_plan_path,
+                    cmd.use_on_disk_deployment_plan,
+                    cmd.use_computed_deployment_plan,
+                );
+                (deployment, artifacts, None)
+            };
+
+            // contracts with cfg blocks are also checked without their simnet/devnet code
+            match check_mainnet_variant(&manifest, &deployment) {
+                Ok(Some(variant_diags)) => {
+                    for (contract_id, mut diags) in variant_diags {
+                        if checked
+                            .as_ref()
+                            .is_some_and(|checked| !checked.contains(&contract_id))
+                        {
+                            continue;
+                        }
+                        if diags.iter().any(|d| matches!(d.level, Level::Error)) {
+                            artifacts.success = false;
+                        }
+                        artifacts
+                            .diags
+                            .entry(contract_id)
+                            .or_default()
+                            .append(&mut diags);
+                    }
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    exit_with_error(ExitCode::Config, e);
+                }
+            }
 
             if cmd.enable_clarity_wasm {
                 let mut manifest_wasm = manifest.clone();
@@ -1123,38 +2616,90 @@ pub fn main() {
                 );
                 compare_wasm_artifacts(&deployment, &artifacts, &wasm_artifacts);
             }
+            if let Some(profile) = profiling::stop_profiling() {
+                display_startup_profile(&profile);
+            }
 
-            let diags_digest = DiagnosticsDigest::new(&artifacts.diags, &deployment);
-            if diags_digest.has_feedbacks() {
-                println!("{}", diags_digest.message);
+            let baseline_location = match get_baseline_location(&manifest, cmd.baseline_path) {
+                Ok(location) => location,
+                Err(e) => {
+                    exit_with_error(ExitCode::Config, e);
+                }
+            };
+            if cmd.write_baseline {
+                let baseline = DiagnosticsBaseline::from_diagnostics(&artifacts.diags);
+                if let Err(e) = baseline.write(&baseline_location) {
+                    exit_with_error(ExitCode::Internal, e);
+                }
+                if output::show_summary() {
+                    println!(
+                        "{} {} recorded in {}",
+                        green!("✔"),
+                        pluralize!(baseline.len(), "diagnostic"),
+                        baseline_location
+                    );
+                }
+                ExitCode::Success.exit();
+            }
+            let baseline_used = baseline_location.exists();
+            if baseline_used {
+                let suppressed = match DiagnosticsBaseline::from_location(&baseline_location) {
+                    Ok(baseline) => baseline.filter(&mut artifacts.diags),
+                    Err(e) => {
+                        exit_with_error(ExitCode::Config, e);
+                    }
+                };
+                if suppressed > 0 && output::show_details() {
+                    println!(
+                        "{} {} suppressed by the baseline",
+                        yellow!("!"),
+                        pluralize!(suppressed, "diagnostic")
+                    );
+                }
             }
 
-            if diags_digest.warnings > 0 {
-                println!(
-                    "{} {} detected",
-                    yellow!("!"),
-                    pluralize!(diags_digest.warnings, "warning")
-                );
+            let mut diags_digest = DiagnosticsDigest::from_artifacts(&artifacts, &deployment);
+            if let Some(max_diagnostics) = cmd.max_diagnostics {
+                diags_digest.set_max_diagnostics(max_diagnostics);
             }
-            if diags_digest.errors > 0 {
-                println!(
-                    "{} {} detected",
-                    red!("x"),
-                    pluralize!(diags_digest.errors, "error")
-                );
-            } else {
-                println!(
-                    "{} {} checked",
-                    green!("✔"),
-                    pluralize!(diags_digest.contracts_checked, "contract"),
-                );
+            if diags_digest.has_feedbacks() && output::show_details() {
+                println!("{}", diags_digest.message);
+            }
+
+            // the counts are the summary of the check
+            if output::show_summary() {
+                if diags_digest.warnings > 0 {
+                    println!(
+                        "{} {} detected",
+                        yellow!("!"),
+                        pluralize!(diags_digest.warnings, "warning")
+                    );
+                }
+                if diags_digest.errors > 0 {
+                    println!(
+                        "{} {} detected",
+                        red!("x"),
+                        pluralize!(diags_digest.errors, "error")
+                    );
+                } else {
+                    println!(
+                        "{} {} checked",
+                        green!("✔"),
+                        pluralize!(diags_digest.contracts_checked, "contract"),
+                    );
+                }
             }
-            let exit_code = match artifacts.success {
-                true => 0,
-                false => 1,
+            // with a baseline, any new diagnostic fails the check
+            let success = match baseline_used {
+                true => !diags_digest.has_feedbacks(),
+                false => artifacts.success,
+            };
+            let exit_code = match success {
+                true => ExitCode::Success,
+                false => ExitCode::Diagnostics,
             };
 
-            if global_settings.enable_hints.unwrap_or(true) {
+            if global_settings.enable_hints.unwrap_or(true) && output::show_details() {
                 display_post_check_hint();
             }
             if manifest.project.telemetry {
@@ -1163,7 +2708,7 @@ pub fn main() {
                     DeveloperUsageDigest::new(&manifest.project.name, &manifest.project.authors),
                 ));
             }
-            std::process::exit(exit_code);
+            exit_code.exit();
         }
         Command::Integrate(cmd) => {
             eprintln!(
@@ -1172,7 +2717,13 @@ pub fn main() {
             );
             devnet_start(cmd, global_settings)
         }
-        Command::LSP => run_lsp(),
+        Command::LSP(cmd) => {
+            let address = match cmd.transport {
+                None | Some(LspTransport::Stdio) => None,
+                Some(LspTransport::Tcp(tcp)) => Some(format!("{}:{}", tcp.host, tcp.port)),
+            };
+            run_lsp(address, !cmd.no_cache)
+        }
         Command::DAP => match super::dap::run_dap() {
             Ok(_) => (),
             Err(e) => {
@@ -1185,10 +2736,19 @@ pub fn main() {
                 let manifest = load_manifest_or_exit(cmd.manifest_path);
                 if let Err(e) = Package::pack(cmd.package_file_name, manifest) {
                     eprintln!("Could not execute the package command. {}", format_err!(e));
-                    process::exit(1);
+                    ExitCode::Config.exit();
                 }
             }
             Devnet::DevnetStart(cmd) => devnet_start(cmd, global_settings),
+            Devnet::Pull(cmd) => devnet_pull(cmd),
+            Devnet::Redeploy(cmd) => devnet_redeploy(cmd),
+            Devnet::PoxInfo(cmd) => devnet_pox_info(cmd),
+            Devnet::Status(cmd) => devnet_status(cmd),
+            Devnet::Logs(cmd) => devnet_logs(cmd),
+            Devnet::List(cmd) => devnet_list(cmd),
+            Devnet::Stop(cmd) => devnet_stop(cmd),
+            Devnet::Clean(cmd) => devnet_clean(cmd),
+            Devnet::Config(DevnetConfigCommand::Explain(cmd)) => devnet_config_explain(cmd),
         },
     };
 }
@@ -1198,7 +2758,7 @@ fn get_manifest_location_or_exit(path: Option<String>) -> FileLocation {
         Some(manifest_location) => manifest_location,
         None => {
             eprintln!("Could not find Clarinet.toml");
-            process::exit(1);
+            ExitCode::Config.exit();
         }
     }
 }
@@ -1216,21 +2776,77 @@ fn get_manifest_location_or_warn(path: Option<String>) -> Option<FileLocation> {
     }
 }
 
+fn display_startup_profile(profile: &StartupProfile) {
+    println!("{}", yellow!("Startup profile:"));
+    println!("{}", profile);
+}
+
 fn load_manifest_or_exit(path: Option<String>) -> ProjectManifest {
     let manifest_location = get_manifest_location_or_exit(path);
     match ProjectManifest::from_location(&manifest_location) {
-        Ok(manifest) => manifest,
+        Ok(manifest) => {
+            display_manifest_warnings(&manifest);
+            manifest
+        }
         Err(message) => {
             eprintln!(
                 "{} syntax errors in Clarinet.toml\n{}",
                 red!("error:"),
                 message,
             );
-            process::exit(1);
+            ExitCode::Config.exit();
         }
     }
 }
 
+fn display_manifest_warnings(manifest: &ProjectManifest) {
+    for warning in manifest.warnings.iter() {
+        eprintln!("{} {}", yellow!("warning:"), warning);
+    }
+}
+
+fn get_baseline_location(
+    manifest: &ProjectManifest,
+    path: Option<String>,
+) -> Result<FileLocation, String> {
+    if let Some(path) = path {
+        return FileLocation::from_path_string(&path);
+    }
+    let mut location = manifest.location.get_project_root_location()?;
+    location.append_path(DEFAULT_BASELINE_FILE)?;
+    Ok(location)
+}
+
+fn print_simulation_report(report: &SimulationReport) {
+    if !output::show_summary() {
+        return;
+    }
+    if output::show_details() {
+        for issue in report.issues.iter() {
+            println!(
+                "{} batch #{}, {}: {}",
+                red!("x"),
+                issue.batch_id,
+                issue.transaction,
+                issue.message
+            );
+        }
+    }
+    if report.is_success() {
+        println!(
+            "{} {} simulated, no issue found",
+            green!("✔"),
+            pluralize!(report.transactions, "transaction")
+        );
+    } else {
+        println!(
+            "{} found in {}",
+            red!(pluralize!(report.issues.len(), "issue")),
+            pluralize!(report.transactions, "transaction")
+        );
+    }
+}
+
 fn load_manifest_or_warn(path: Option<String>) -> Option<ProjectManifest> {
     if let Some(manifest_location) = get_manifest_location_or_warn(path) {
         let manifest = match ProjectManifest::from_location(&manifest_location) {
@@ -1241,15 +2857,73 @@ fn load_manifest_or_warn(path: Option<String>) -> Option<ProjectManifest> {
                     red!("error:"),
                     message,
                 );
-                process::exit(1);
+                ExitCode::Config.exit();
             }
         };
+        display_manifest_warnings(&manifest);
         Some(manifest)
     } else {
         None
     }
 }
 
+/// The computed simnet plan, and the artifacts of the session checking the contracts staged in
+/// the git index and their dependents, which are returned as well
+fn load_staged_deployment_and_artifacts_or_exit(
+    manifest: &ProjectManifest,
+) -> (
+    DeploymentSpecification,
+    DeploymentGenerationArtifacts,
+    BTreeSet<QualifiedContractIdentifier>,
+) {
+    let (deployment, mut ast_artifacts) =
+        match generate_default_deployment(manifest, &StacksNetwork::Simnet, false) {
+            Ok(generated) => generated,
+            Err(e) => {
+                exit_with_error(ExitCode::Config, e);
+            }
+        };
+    let staged = match deployments::get_staged_contracts(manifest, &deployment, &ast_artifacts) {
+        Ok(staged) => staged,
+        Err(e) => {
+            exit_with_error(ExitCode::Config, e);
+        }
+    };
+    if staged.is_empty() {
+        if output::show_summary() {
+            println!("{} No staged contract to check", green!("✔"));
+        }
+        ExitCode::Success.exit();
+    }
+    let checked = get_contracts_to_republish(&staged, &ast_artifacts.deps);
+    if output::show_details() {
+        println!(
+            "{} checking {} and {}",
+            yellow!("note:"),
+            pluralize!(staged.len(), "staged contract"),
+            pluralize!(checked.len() - staged.len(), "dependent")
+        );
+    }
+    ast_artifacts
+        .diags
+        .retain(|contract_id, _| checked.contains(contract_id));
+    if !ast_artifacts.success {
+        return (deployment, ast_artifacts, checked);
+    }
+
+    let mut artifacts =
+        staged_check::setup_session_for_contracts(manifest, &deployment, &ast_artifacts, &checked);
+    for (contract_id, mut parser_diags) in ast_artifacts.diags.into_iter() {
+        if let Some(ref mut diags) = artifacts.diags.remove(&contract_id) {
+            parser_diags.append(diags);
+        }
+        artifacts.diags.insert(contract_id, parser_diags);
+    }
+    artifacts.source_maps = ast_artifacts.source_maps;
+    artifacts.warnings = ast_artifacts.warnings;
+    (deployment, artifacts, checked)
+}
+
 fn load_deployment_and_artifacts_or_exit(
     manifest: &ProjectManifest,
     deployment_plan_path: &Option<String>,
@@ -1270,10 +2944,12 @@ fn load_deployment_and_artifacts_or_exit(
             );
             match res {
                 Some(Ok(deployment)) => {
-                    println!(
-                        "{} using deployments/default.simnet-plan.yaml",
-                        yellow!("note:")
-                    );
+                    if output::show_details() {
+                        println!(
+                            "{} using deployments/default.simnet-plan.yaml",
+                            yellow!("note:")
+                        );
+                    }
                     let artifacts = setup_session_with_deployment(manifest, &deployment, None);
                     Ok((deployment, None, artifacts))
                 }
@@ -1296,6 +2972,8 @@ fn load_deployment_and_artifacts_or_exit(
                                 }
                                 artifacts.diags.insert(contract_id, parser_diags);
                             }
+                            artifacts.source_maps = ast_artifacts.source_maps;
+                            artifacts.warnings = ast_artifacts.warnings;
                             Ok((deployment, None, artifacts))
                         }
                         Ok((deployment, ast_artifacts)) => Ok((deployment, None, ast_artifacts)),
@@ -1318,14 +2996,20 @@ fn load_deployment_and_artifacts_or_exit(
     };
 
     match result {
+        // the artifacts of an interrupted analysis are partial
+        _ if deployments::is_interrupted() => exit_interrupted(),
         Ok(deployment) => deployment,
         Err(e) => {
-            eprintln!("{}", format_err!(e));
-            process::exit(1);
+            exit_with_error(ExitCode::Config, e);
         }
     }
 }
 
+fn exit_interrupted() -> ! {
+    eprintln!("{}", format_err!("interrupted"));
+    process::exit(130);
+}
+
 fn should_existing_plan_be_replaced(
     existing_plan: &DeploymentSpecification,
     new_plan: &DeploymentSpecification,
@@ -1370,6 +3054,73 @@ fn should_existing_plan_be_replaced(
     !buffer.starts_with('n')
 }
 
+fn deployment_changelog(cmd: DeploymentChangelog) {
+    let manifest = load_manifest_or_exit(cmd.manifest_path);
+    let deployment_location = match cmd.deployment_plan_path {
+        Some(ref path) => get_absolute_deployment_path(&manifest, path),
+        None => {
+            let network = if cmd.devnet {
+                StacksNetwork::Devnet
+            } else if cmd.mainnet {
+                StacksNetwork::Mainnet
+            } else {
+                StacksNetwork::Testnet
+            };
+            get_default_deployment_path(&manifest, &network)
+        }
+    };
+    let deployment = deployment_location
+        .and_then(|location| load_deployment(&manifest, &location))
+        .unwrap_or_else(|message| exit_with_error(ExitCode::Config, message));
+    let project_root = manifest
+        .location
+        .get_project_root_location()
+        .unwrap_or_else(|message| exit_with_error(ExitCode::Config, message));
+    let content = changelog::get_plan_changelog(&deployment, &project_root);
+
+    let Some(output) = cmd.output else {
+        print!("{}", content);
+        return;
+    };
+    let written = FileLocation::from_path_string(&output)
+        .and_then(|location| location.write_content(content.as_bytes()));
+    if let Err(message) = written {
+        exit_with_error(ExitCode::Config, message);
+    }
+    if output::show_summary() {
+        println!("{} {}", green!("Generated file"), output);
+    }
+}
+
+fn edit_deployment(cmd: EditDeployment) {
+    let manifest = load_manifest_or_exit(cmd.manifest_path);
+    let deployment_location = get_absolute_deployment_path(&manifest, &cmd.deployment_plan_path)
+        .unwrap_or_else(|message| exit_with_error(ExitCode::Config, message));
+    if let FileLocation::Url { url } = &deployment_location {
+        exit_with_error(
+            ExitCode::Config,
+            format!("{} is read-only, download the plan to edit it", url),
+        );
+    }
+    let deployment = load_deployment(&manifest, &deployment_location)
+        .unwrap_or_else(|message| exit_with_error(ExitCode::Config, message));
+
+    let edited = deployments::start_editor(PlanEditor::new(&deployment))
+        .unwrap_or_else(|message| exit_with_error(ExitCode::Internal, message));
+    let Some(edited) = edited else {
+        if output::show_summary() {
+            println!("{}", yellow!("Deployment plan left unchanged"));
+        }
+        return;
+    };
+    if let Err(message) = write_deployment(&edited, &deployment_location, false) {
+        exit_with_error(ExitCode::Config, message);
+    }
+    if output::show_summary() {
+        println!("{} {}", green!("Updated file"), deployment_location);
+    }
+}
+
 fn load_deployment_if_exists(
     manifest: &ProjectManifest,
     network: &StacksNetwork,
@@ -1386,9 +3137,15 @@ fn load_deployment_if_exists(
 
     if !force_on_disk {
         match generate_default_deployment(manifest, network, true) {
-            Ok((deployment, _)) => {
+            Ok((mut deployment, _)) => {
                 use similar::{ChangeTag, TextDiff};
 
+                if let Ok(existing_deployment) =
+                    load_deployment(manifest, &default_deployment_location)
+                {
+                    deployment.keep_descriptions(&existing_deployment);
+                }
+
                 let current_version = match default_deployment_location.read_content() {
                     Ok(content) => content,
                     Err(message) => return Some(Err(message)),
@@ -1613,8 +3370,12 @@ fn execute_changes(changes: Vec<Changes>) -> bool {
                 for (contract_name, contract_config) in options.contracts_to_add.drain() {
                     config.contracts.insert(contract_name, contract_config);
                 }
+                for (contract_name, networks) in options.contracts_networks.drain() {
+                    config.contracts_networks.insert(contract_name, networks);
+                }
                 for contract_name in options.contracts_to_rm.iter() {
                     config.contracts.remove(contract_name);
+                    config.contracts_networks.remove(contract_name);
                 }
 
                 shared_config = Some(config);
@@ -1681,6 +3442,228 @@ fn prompt_user_to_continue() {
     }
 }
 
+fn prompt_user_with_default(question: &str, default: &str, use_default: bool) -> String {
+    if use_default {
+        return default.to_string();
+    }
+    println!("{} [{}]", yellow!(question), default);
+    let mut buffer = String::new();
+    std::io::stdin().read_line(&mut buffer).unwrap();
+    match buffer.trim() {
+        "" => default.to_string(),
+        answer => answer.to_string(),
+    }
+}
+
+fn prompt_epoch_and_clarity_version(
+    label: &str,
+    default_epoch: &str,
+    default_clarity_version: Option<&str>,
+    use_default: bool,
+) -> (StacksEpochId, ClarityVersion) {
+    loop {
+        let epoch = prompt_user_with_default(
+            &format!("Epoch for {label} (2.0 to 3.1)"),
+            default_epoch,
+            use_default,
+        );
+        let default_version = match get_epoch_and_clarity_version(Some(&epoch), None) {
+            Ok((_, ClarityVersion::Clarity1)) => "1",
+            Ok((_, ClarityVersion::Clarity2)) => "2",
+            Ok((_, ClarityVersion::Clarity3)) => "3",
+            Err(message) => {
+                eprintln!("{}", format_err!(message));
+                continue;
+            }
+        };
+        let clarity_version = prompt_user_with_default(
+            &format!("Clarity version for {label}"),
+            default_clarity_version.unwrap_or(default_version),
+            use_default,
+        );
+        match get_epoch_and_clarity_version(Some(&epoch), Some(&clarity_version)) {
+            Ok(result) => return result,
+            Err(message) => eprintln!("{}", format_err!(message)),
+        }
+    }
+}
+
+fn clarity_version_to_setting(clarity_version: ClarityVersion) -> &'static str {
+    match clarity_version {
+        ClarityVersion::Clarity1 => "1",
+        ClarityVersion::Clarity2 => "2",
+        ClarityVersion::Clarity3 => "3",
+    }
+}
+
+fn init_project(cmd: InitProject, global_settings: &GlobalSettings) {
+    let project_path = match cmd.path {
+        Some(ref path) => std::path::PathBuf::from(path),
+        None => std::env::current_dir().unwrap_or_else(|e| {
+            eprintln!("{}{}", format_err!("unable to get current directory"), e);
+            std::process::exit(1);
+        }),
+    };
+    let project_path = fs::canonicalize(&project_path).unwrap_or_else(|e| {
+        eprintln!(
+            "{} {}: {}",
+            format_err!("unable to open directory"),
+            project_path.display(),
+            e
+        );
+        std::process::exit(1);
+    });
+    if project_path.join("Clarinet.toml").exists() {
+        eprintln!(
+            "{}",
+            format_err!(format!(
+                "a Clarinet.toml file already exists in {}",
+                project_path.display()
+            ))
+        );
+        std::process::exit(1);
+    }
+
+    let discovered_contracts = match generate::init::discover_contracts(&project_path) {
+        Ok(contracts) if contracts.is_empty() => {
+            eprintln!(
+                "{}",
+                format_err!(format!(
+                    "no .clar files found in {}",
+                    project_path.display()
+                ))
+            );
+            std::process::exit(1);
+        }
+        Ok(contracts) => contracts,
+        Err(message) => {
+            eprintln!("{}", format_err!(message));
+            std::process::exit(1);
+        }
+    };
+    println!(
+        "{} {}",
+        green!("Found"),
+        pluralize!(discovered_contracts.len(), "contract")
+    );
+
+    let default_name = project_path
+        .file_name()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or("project".into());
+    let project_name = sanitize_project_name(&prompt_user_with_default(
+        "Project name",
+        &default_name,
+        cmd.yes,
+    ));
+
+    // defaults applied to every contract, that can then be customized
+    let deployer = prompt_user_with_default("Default deployer account", "deployer", cmd.yes);
+    let (epoch, clarity_version) = prompt_epoch_and_clarity_version(
+        "the contracts",
+        &DEFAULT_EPOCH.to_string(),
+        None,
+        cmd.yes,
+    );
+    let customize = !cmd.yes
+        && prompt_user_with_default("Customize each contract? (y/N)", "n", false)
+            .eq_ignore_ascii_case("y");
+
+    let local_names: Vec<String> = discovered_contracts
+        .iter()
+        .map(|c| c.name.clone())
+        .collect();
+    let mut contracts = vec![];
+    let mut external_references = std::collections::BTreeSet::new();
+    for discovered in discovered_contracts.into_iter() {
+        for local_reference in discovered.local_references.iter() {
+            if !local_names.contains(local_reference) {
+                println!(
+                    "{} {} references .{}, which was not found in the directory",
+                    yellow!("warning:"),
+                    discovered.path.display(),
+                    local_reference
+                );
+            }
+        }
+        external_references.extend(discovered.external_references);
+
+        let (name, contract_deployer, contract_epoch, contract_clarity_version) = if customize {
+            let name = prompt_user_with_default(
+                &format!("Contract name for {}", discovered.path.display()),
+                &discovered.name,
+                false,
+            );
+            let contract_deployer =
+                prompt_user_with_default(&format!("Deployer of {name}"), &deployer, false);
+            let (contract_epoch, contract_clarity_version) = prompt_epoch_and_clarity_version(
+                &name,
+                &epoch.to_string(),
+                Some(clarity_version_to_setting(clarity_version)),
+                false,
+            );
+            (
+                name,
+                contract_deployer,
+                contract_epoch,
+                contract_clarity_version,
+            )
+        } else {
+            (discovered.name, deployer.clone(), epoch, clarity_version)
+        };
+
+        contracts.push(ClarityContract {
+            code_source: ClarityCodeSource::ContractOnDisk(discovered.path),
+            name,
+            deployer: if contract_deployer == "deployer" {
+                ContractDeployer::DefaultDeployer
+            } else {
+                ContractDeployer::LabeledDeployer(contract_deployer)
+            },
+            clarity_version: contract_clarity_version,
+            epoch: contract_epoch,
+        });
+    }
+
+    let mut requirements = vec![];
+    for contract_id in external_references.into_iter() {
+        let question =
+            format!("{contract_id} is called by the contracts, add it as a requirement? (Y/n)");
+        if !prompt_user_with_default(&question, "y", cmd.yes).eq_ignore_ascii_case("n") {
+            requirements.push(RequirementConfig {
+                contract_id,
+                mock: false,
+                epoch: None,
+                clarity_version: None,
+            });
+        }
+    }
+
+    let telemetry_enabled = cfg!(feature = "telemetry")
+        && !cmd.disable_telemetry
+        && global_settings.enable_telemetry.unwrap_or(false);
+
+    let changes = match generate::get_changes_for_init_project(
+        project_path.to_string_lossy().to_string(),
+        project_name,
+        contracts,
+        requirements,
+        telemetry_enabled,
+    ) {
+        Ok(changes) => changes,
+        Err(message) => {
+            eprintln!("{}", format_err!(message));
+            std::process::exit(1);
+        }
+    };
+    if !execute_changes(changes) {
+        std::process::exit(1);
+    }
+    if global_settings.enable_hints.unwrap_or(true) {
+        display_post_check_hint();
+    }
+}
+
 fn display_separator() {
     println!("{}", yellow!("----------------------------"));
 }
@@ -1787,16 +3770,37 @@ fn display_deploy_hint() {
 }
 
 fn devnet_start(cmd: DevnetStart, global_settings: GlobalSettings) {
+    let json_output = cmd.json;
+    // with --json, stdout only carries the events of the devnet
+    let print_status = |message: String| {
+        if !output::show_details() {
+            return;
+        }
+        if json_output {
+            eprintln!("{}", message);
+        } else {
+            println!("{}", message);
+        }
+    };
     let manifest = load_manifest_or_exit(cmd.manifest_path);
-    println!("Computing deployment plan");
+    if !cmd.no_deployment {
+        print_status("Computing deployment plan".to_string());
+    }
     let result = match cmd.deployment_plan_path {
+        _ if cmd.no_deployment => {
+            print_status(format!(
+                "{} starting devnet without deploying the project",
+                yellow!("note:")
+            ));
+            Ok(DeploymentSpecification::empty(&StacksNetwork::Devnet))
+        }
         None => {
             let res = if let Some(package) = cmd.package {
                 let package_file = match File::open(package) {
                     Ok(file) => file,
                     Err(_) => {
                         eprintln!("{} package file not found", red!("error:"));
-                        std::process::exit(1);
+                        ExitCode::Config.exit();
                     }
                 };
                 let deployment: ConfigurationPackage = serde_json::from_reader(package_file)
@@ -1812,10 +3816,10 @@ fn devnet_start(cmd: DevnetStart, global_settings: GlobalSettings) {
             };
             match res {
                 Some(Ok(deployment)) => {
-                    println!(
+                    print_status(format!(
                         "{} using existing deployments/default.devnet-plan.yaml",
                         yellow!("note:")
-                    );
+                    ));
                     // TODO(lgalabru): Think more about the desired DX.
                     // Compute the latest version, display differences and propose overwrite?
                     Ok(deployment)
@@ -1830,18 +3834,18 @@ fn devnet_start(cmd: DevnetStart, global_settings: GlobalSettings) {
                             Ok(deployment) => deployment,
                             Err(message) => {
                                 eprintln!("{}", red!(message));
-                                std::process::exit(1);
+                                ExitCode::Config.exit();
                             }
                         };
                     let res = write_deployment(&deployment, &default_deployment_path, true);
                     if let Err(message) = res {
                         Err(message)
                     } else {
-                        println!(
+                        print_status(format!(
                             "{} {}",
                             green!("Generated file"),
                             default_deployment_path.get_relative_location().unwrap()
-                        );
+                        ));
                         Ok(deployment)
                     }
                 }
@@ -1857,18 +3861,26 @@ fn devnet_start(cmd: DevnetStart, global_settings: GlobalSettings) {
     let deployment = match result {
         Ok(deployment) => deployment,
         Err(e) => {
-            eprintln!("{}", format_err!(e));
-            std::process::exit(1);
+            exit_with_error(ExitCode::Config, e);
         }
     };
 
-    let orchestrator = match DevnetOrchestrator::new(manifest, None, None, true, cmd.no_dashboard) {
-        Ok(orchestrator) => orchestrator,
-        Err(e) => {
-            eprintln!("{}", format_err!(e));
-            process::exit(1);
-        }
-    };
+    let mut devnet_override = get_devnet_override(cmd.auto_ports, cmd.services, cmd.epochs);
+    if let Some(duration) = cmd.soak {
+        devnet_override.get_or_insert_with(Default::default).soak = Some(SoakConfigFile {
+            enabled: Some(true),
+            duration,
+            ..Default::default()
+        });
+    }
+    let log_to_stdout = cmd.no_dashboard && !json_output;
+    let orchestrator =
+        match DevnetOrchestrator::new(manifest, None, devnet_override, true, log_to_stdout) {
+            Ok(orchestrator) => orchestrator,
+            Err(e) => {
+                exit_with_error(ExitCode::Config, e);
+            }
+        };
 
     if orchestrator.manifest.project.telemetry {
         #[cfg(feature = "telemetry")]
@@ -1879,46 +3891,2923 @@ fn devnet_start(cmd: DevnetStart, global_settings: GlobalSettings) {
             ),
         ));
     }
-    match start(orchestrator, deployment, None, !cmd.no_dashboard) {
+    let display_dashboard = !cmd.no_dashboard && !json_output;
+    match start(
+        orchestrator,
+        deployment,
+        None,
+        display_dashboard,
+        json_output,
+    ) {
         Err(e) => {
-            eprintln!("{}", format_err!(e));
-            process::exit(1);
+            exit_with_error(ExitCode::Network, e);
         }
         Ok(_) => {
-            if global_settings.enable_hints.unwrap_or(true) {
+            if global_settings.enable_hints.unwrap_or(true)
+                && !json_output
+                && output::show_details()
+            {
                 display_deploy_hint();
             }
-            process::exit(0);
+            ExitCode::Success.exit();
         }
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use clap_complete::generate;
+fn get_devnet_override(
+    auto_ports: bool,
+    services: Option<Vec<String>>,
+    epochs: DevnetEpochs,
+) -> Option<DevnetConfigFile> {
+    if !auto_ports && services.is_none() && epochs == DevnetEpochs::default() {
+        return None;
+    }
+    Some(DevnetConfigFile {
+        auto_ports: auto_ports.then_some(true),
+        services,
+        epoch_2_0: epochs.epoch_2_0,
+        epoch_2_05: epochs.epoch_2_05,
+        epoch_2_1: epochs.epoch_2_1,
+        epoch_2_2: epochs.epoch_2_2,
+        epoch_2_3: epochs.epoch_2_3,
+        epoch_2_4: epochs.epoch_2_4,
+        epoch_2_5: epochs.epoch_2_5,
+        epoch_3_0: epochs.epoch_3_0,
+        epoch_3_1: epochs.epoch_3_1,
+        pox_2_activation: epochs.pox_2_activation,
+        ..Default::default()
+    })
+}
 
-    use super::*;
+fn devnet_config_explain(cmd: DevnetConfigExplain) {
+    let manifest = load_manifest_or_exit(cmd.manifest_path);
+    let devnet_override = get_devnet_override(cmd.auto_ports, cmd.services, cmd.epochs);
+    let (devnet, settings) = match NetworkManifest::explain_devnet_settings(
+        &manifest.location,
+        Some(&manifest.project.cache_location),
+        devnet_override,
+    ) {
+        Ok(res) => res,
+        Err(e) => {
+            eprintln!("{}", format_err!(e));
+            process::exit(1);
+        }
+    };
 
-    #[test]
-    fn test_completion_for_shells() {
-        for shell in [
-            Shell::Bash,
-            Shell::Elvish,
-            Shell::Fish,
-            Shell::PowerShell,
-            Shell::Zsh,
-        ] {
-            let result = std::panic::catch_unwind(move || {
-                let mut output_buffer = Vec::new();
-                let mut cmd = Opts::command();
-                generate(shell, &mut cmd, "clarinet", &mut output_buffer);
-                assert!(
-                    !output_buffer.is_empty(),
-                    "failed to generate completion for {shell}",
-                );
-            });
-            assert!(result.is_ok(), "failed to generate completion for {shell}",);
+    let key_width = settings.iter().map(|s| s.key.len()).max().unwrap_or(0);
+    for setting in settings.iter() {
+        let origin = format!("# {}", setting.origin);
+        let origin = match setting.origin {
+            SettingOrigin::DevnetToml | SettingOrigin::Override => green!(origin),
+            SettingOrigin::Default | SettingOrigin::Derived => black!(origin),
+        };
+        println!(
+            "{:<width$} = {} {}",
+            setting.key,
+            setting.value,
+            origin,
+            width = key_width
+        );
+    }
+
+    let services: Vec<&str> = devnet
+        .get_services()
+        .iter()
+        .map(|service| service.get_name())
+        .collect();
+    println!("\n{} {}", yellow!("Services:"), services.join(", "));
+
+    let errors = devnet.validate();
+    if !errors.is_empty() {
+        println!();
+        for error in errors.iter() {
+            eprintln!("{}", format_err!(error));
+        }
+        process::exit(1);
+    }
+}
+
+fn get_predicates_network(
+    testnet: bool,
+    mainnet: bool,
+) -> stacks_network::chainhook_sdk::types::StacksNetwork {
+    let network = if mainnet {
+        StacksNetwork::Mainnet
+    } else if testnet {
+        StacksNetwork::Testnet
+    } else {
+        StacksNetwork::Devnet
+    };
+    let (_, network) = network.get_networks();
+    network
+}
+
+fn test_chainhook(cmd: TestChainhook) {
+    let network = get_predicates_network(cmd.testnet, cmd.mainnet);
+    let predicate = match stacks_network::load_stacks_predicate(
+        &PathBuf::from(&cmd.predicate_path),
+        &network,
+    ) {
+        Ok(predicate) => predicate,
+        Err(e) => {
+            eprintln!("{}", format_err!(e));
+            process::exit(1);
+        }
+    };
+    let blocks = match stacks_network::read_recorded_blocks(&PathBuf::from(&cmd.against)) {
+        Ok(blocks) => blocks,
+        Err(e) => {
+            eprintln!("{}", format_err!(e));
+            process::exit(1);
+        }
+    };
+
+    let ctx = stacks_network::Context::empty();
+    let occurrences =
+        stacks_network::evaluate_predicate_on_recorded_blocks(&predicate, &blocks, &ctx);
+    for occurrence in occurrences.iter() {
+        println!(
+            "{} block #{} ({}): {}",
+            green!("✔"),
+            occurrence.block_identifier.index,
+            occurrence.block_identifier.hash,
+            pluralize!(occurrence.transactions.len(), "transaction")
+        );
+        for tx in occurrence.transactions.iter() {
+            println!("  {}", tx);
+        }
+        if !cmd.no_payloads {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&occurrence.payload).unwrap()
+            );
+        }
+    }
+    println!(
+        "{} triggered in {} out of {}",
+        predicate.uuid,
+        pluralize!(occurrences.len(), "block"),
+        pluralize!(blocks.len(), "recorded block"),
+    );
+}
+
+fn simulate_chainhooks_load(cmd: ChainhooksLoad) {
+    let network = get_predicates_network(cmd.testnet, cmd.mainnet);
+    let mut predicates = vec![];
+    for path in cmd.predicates_paths.iter() {
+        match stacks_network::load_stacks_predicate(&PathBuf::from(path), &network) {
+            Ok(predicate) => predicates.push(predicate),
+            Err(e) => {
+                eprintln!("{}", format_err!(format!("{}: {}", path, e)));
+                process::exit(1);
+            }
+        }
+    }
+    let blocks = match stacks_network::read_recorded_blocks(&PathBuf::from(&cmd.against)) {
+        Ok(blocks) => blocks,
+        Err(e) => {
+            eprintln!("{}", format_err!(e));
+            process::exit(1);
+        }
+    };
+
+    let ctx = stacks_network::Context::empty();
+    let reports = stacks_network::simulate_predicates_load(&predicates, &blocks, &ctx);
+    if cmd.json {
+        println!("{}", serde_json::to_string_pretty(&reports).unwrap());
+        return;
+    }
+
+    let transactions_count = reports.first().map(|r| r.transactions_count).unwrap_or(0);
+    println!(
+        "{} over {} ({})",
+        pluralize!(reports.len(), "predicate"),
+        pluralize!(blocks.len(), "recorded block"),
+        pluralize!(transactions_count, "transaction"),
+    );
+    for report in reports.iter() {
+        println!(
+            "{:<30} {:>10.1}ms total {:>8.1}µs/block  {:>4} blocks  {:>6} txs ({:.1}%)",
+            report.uuid,
+            report.evaluation_time_us as f64 / 1000.0,
+            report.get_average_block_time_us(),
+            report.blocks_matched,
+            report.transactions_matched,
+            report.get_match_rate() * 100.0,
+        );
+    }
+    for report in reports.iter().filter(|report| report.is_too_broad()) {
+        let message = format!(
+            "{} matches {:.1}% of the transactions (more than {}%), it would trigger on most blocks of a busy network: consider narrowing its scope",
+            report.uuid,
+            report.get_match_rate() * 100.0,
+            stacks_network::BROAD_PREDICATE_MATCH_RATE * 100.0,
+        );
+        println!("{}", format_warn!(message));
+    }
+}
+
+fn format_image_pull(
+    service: &DevnetService,
+    reference: &str,
+    status: &stacks_network::ImagePullStatus,
+) -> String {
+    use stacks_network::{format_progress_bar, ImagePullStatus};
+
+    let status = match status {
+        ImagePullStatus::Downloading { current, total } => {
+            format_progress_bar(*current, *total, 30)
+        }
+        ImagePullStatus::Retrying { attempt, error } => {
+            yellow!(format!("attempt {} failed, resuming ({})", attempt, error))
+        }
+        ImagePullStatus::Pulled(digest) => green!(format!("pulled {}", digest)),
+        ImagePullStatus::Failed(error) => red!(format!("failed: {}", error)),
+    };
+    format!("{:<16} {} {}", service.get_name(), reference, status)
+}
+
+fn devnet_pull(cmd: DevnetPull) {
+    use stacks_network::{pull_devnet_images, ImagePullStatus};
+    use std::collections::BTreeMap;
+    use std::io::IsTerminal;
+    use std::time::{Duration, Instant};
+
+    let manifest = load_manifest_or_exit(cmd.manifest_path);
+    let devnet_override = get_devnet_override(false, cmd.services, DevnetEpochs::default());
+    let network_manifest = match NetworkManifest::from_project_manifest_location(
+        &manifest.location,
+        &StacksNetwork::Devnet.get_networks(),
+        Some(&manifest.project.cache_location),
+        devnet_override,
+    ) {
+        Ok(network_manifest) => network_manifest,
+        Err(e) => {
+            exit_with_error(ExitCode::Config, e);
+        }
+    };
+    let Some(devnet_config) = network_manifest.devnet else {
+        exit_with_error(ExitCode::Config, "unable to retrieve devnet settings");
+    };
+
+    let (events_tx, events_rx) = std::sync::mpsc::channel();
+    let attempts = cmd.attempts.max(1);
+    let pull = std::thread::spawn(move || {
+        stacks_network::block_on(pull_devnet_images(&devnet_config, attempts, events_tx))
+    });
+
+    // in a terminal, the line of each image is redrawn as its pull progresses
+    let interactive = std::io::stdout().is_terminal();
+    let mut images = BTreeMap::new();
+    let mut drawn_lines = 0;
+    let mut last_draw = Instant::now();
+    for event in events_rx {
+        if !output::show_details() {
+            continue;
+        }
+        let is_progress = matches!(event.status, ImagePullStatus::Downloading { .. });
+        if !interactive {
+            if !is_progress {
+                println!(
+                    "{}",
+                    format_image_pull(&event.service, &event.reference, &event.status)
+                );
+            }
+            continue;
+        }
+        images.insert(event.service, (event.reference, event.status));
+        if is_progress && last_draw.elapsed() < Duration::from_millis(100) {
+            continue;
+        }
+        if drawn_lines > 0 {
+            print!("\x1b[{}A", drawn_lines);
+        }
+        for (service, (reference, status)) in images.iter() {
+            println!("\x1b[2K{}", format_image_pull(service, reference, status));
+        }
+        drawn_lines = images.len();
+        last_draw = Instant::now();
+    }
+
+    match pull.join() {
+        Ok(Ok(_)) if !output::show_summary() => {}
+        Ok(Ok(digests)) => println!(
+            "{} {} ready for 'clarinet devnet start'",
+            green!("✔"),
+            pluralize!(digests.len(), "image")
+        ),
+        Ok(Err(e)) => {
+            exit_with_error(ExitCode::Network, e);
+        }
+        Err(_) => {
+            exit_with_error(ExitCode::Internal, "the images pull panicked");
+        }
+    }
+}
+
+fn devnet_redeploy(cmd: DevnetRedeploy) {
+    use clarinet_deployments::redeploy::{
+        get_redeploy_deployment, ContractAlias, ContractAliases, DEVNET_ALIASES_FILE,
+    };
+
+    let manifest = load_manifest_or_exit(cmd.manifest_path);
+    if !manifest.contracts.contains_key(&cmd.contract) {
+        eprintln!(
+            "{}",
+            format_err!(format!(
+                "{} is not a contract of Clarinet.toml",
+                cmd.contract
+            ))
+        );
+        ExitCode::Config.exit();
+    }
+    let network_manifest = match NetworkManifest::from_project_manifest_location(
+        &manifest.location,
+        &StacksNetwork::Devnet.get_networks(),
+        Some(&manifest.project.cache_location),
+        None,
+    ) {
+        Ok(network_manifest) => network_manifest,
+        Err(e) => {
+            exit_with_error(ExitCode::Config, e);
+        }
+    };
+    let Some(devnet_config) = &network_manifest.devnet else {
+        exit_with_error(ExitCode::Config, "unable to retrieve devnet settings");
+    };
+
+    let (deployment, artifacts) =
+        match generate_default_deployment(&manifest, &StacksNetwork::Devnet, false) {
+            Ok(deployment) => deployment,
+            Err(e) => {
+                exit_with_error(ExitCode::Config, e);
+            }
+        };
+    if !artifacts.success {
+        eprintln!(
+            "{}",
+            format_err!(
+                "the contracts of the project have errors, run 'clarinet check' for details"
+            )
+        );
+        ExitCode::Diagnostics.exit();
+    }
+
+    let mut aliases_location = manifest.location.get_project_root_location().unwrap();
+    aliases_location.append_path("deployments").unwrap();
+    aliases_location.append_path(DEVNET_ALIASES_FILE).unwrap();
+    let mut aliases: ContractAliases = match aliases_location.read_content_as_utf8() {
+        Ok(content) => match serde_json::from_str(&content) {
+            Ok(aliases) => aliases,
+            Err(e) => {
+                eprintln!(
+                    "{}",
+                    format_err!(format!("unable to read {}: {}", DEVNET_ALIASES_FILE, e))
+                );
+                ExitCode::Config.exit();
+            }
+        },
+        Err(_) => ContractAliases::new(),
+    };
+    // the contracts of the plan are the first version
+    let previous = aliases.get(&cmd.contract).cloned().unwrap_or_else(|| {
+        let contract_id = deployment
+            .contracts
+            .keys()
+            .find(|contract_id| contract_id.name.as_str() == cmd.contract)
+            .map(|contract_id| contract_id.to_string())
+            .unwrap_or_default();
+        ContractAlias {
+            contract_id,
+            version: 1,
+        }
+    });
+    let version = previous.version + 1;
+    let migration = devnet_config
+        .migrations
+        .get(&cmd.contract)
+        .filter(|_| !cmd.no_migration);
+    let redeploy = match get_redeploy_deployment(
+        &deployment,
+        &cmd.contract,
+        version,
+        &previous.contract_id,
+        migration,
+    ) {
+        Ok(redeploy) => redeploy,
+        Err(e) => {
+            exit_with_error(ExitCode::Config, e);
+        }
+    };
+    let contract_id = redeploy.contracts.keys().next().unwrap().to_string();
+    if output::show_details() {
+        println!(
+            "Publishing {} on devnet{}",
+            contract_id,
+            if migration.is_some() {
+                ", then calling its migration"
+            } else {
+                ""
+            }
+        );
+    }
+
+    let (command_tx, command_rx) = std::sync::mpsc::channel();
+    let (event_tx, event_rx) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        apply_on_chain_deployment(
+            network_manifest,
+            redeploy,
+            event_tx,
+            command_rx,
+            true,
+            None,
+            None,
+            None,
+        );
+    });
+    let _ = command_tx.send(DeploymentCommand::Start);
+    for event in event_rx {
+        match event {
+            DeploymentEvent::TransactionUpdate(update) if output::show_details() => {
+                println!("{} {:?} {}", blue!("➡"), update.status, update.name);
+            }
+            DeploymentEvent::Interrupted(message) => {
+                eprintln!(
+                    "{}",
+                    format_err!(format!(
+                        "unable to redeploy {} (is devnet running?): {}",
+                        cmd.contract, message
+                    ))
+                );
+                ExitCode::Network.exit();
+            }
+            DeploymentEvent::DeploymentCompleted => break,
+            _ => {}
+        }
+    }
+
+    aliases.insert(
+        cmd.contract.clone(),
+        ContractAlias {
+            contract_id: contract_id.clone(),
+            version,
+        },
+    );
+    let content = serde_json::to_string_pretty(&aliases).unwrap();
+    if let Err(e) = aliases_location.write_content(content.as_bytes()) {
+        exit_with_error(ExitCode::Internal, e);
+    }
+    if output::show_summary() {
+        println!(
+            "{} {} published, deployments/{} updated",
+            green!("✔"),
+            contract_id,
+            DEVNET_ALIASES_FILE
+        );
+    }
+}
+
+fn devnet_pox_info(cmd: DevnetPoxInfo) {
+    let manifest = load_manifest_or_exit(cmd.manifest_path);
+    let network_manifest = match NetworkManifest::from_project_manifest_location(
+        &manifest.location,
+        &StacksNetwork::Devnet.get_networks(),
+        Some(&manifest.project.cache_location),
+        None,
+    ) {
+        Ok(network_manifest) => network_manifest,
+        Err(e) => {
+            exit_with_error(ExitCode::Config, e);
+        }
+    };
+    let Some(devnet_config) = network_manifest.devnet else {
+        exit_with_error(ExitCode::Config, "unable to retrieve devnet settings");
+    };
+
+    let node_rpc_url = format!("http://localhost:{}", devnet_config.stacks_node_rpc_port);
+    let accounts: Vec<AccountConfig> = network_manifest.accounts.into_values().collect();
+    let summary = stacks_network::block_on(stacks_network::pox::get_pox_summary(
+        &node_rpc_url,
+        &accounts,
+        &devnet_config.stacks_signers_keys,
+    ));
+    match summary {
+        Ok(summary) if cmd.json => {
+            println!("{}", serde_json::to_string_pretty(&summary).unwrap());
+        }
+        Ok(summary) => println!("{}", summary),
+        Err(e) => {
+            eprintln!(
+                "{}",
+                format_err!(format!(
+                    "unable to retrieve pox info (is devnet running?): {}",
+                    e
+                ))
+            );
+            ExitCode::Network.exit();
+        }
+    }
+}
+
+fn devnet_status(cmd: DevnetStatus) {
+    let manifest = load_manifest_or_exit(cmd.manifest_path);
+    let network_manifest = match NetworkManifest::from_project_manifest_location(
+        &manifest.location,
+        &StacksNetwork::Devnet.get_networks(),
+        Some(&manifest.project.cache_location),
+        None,
+    ) {
+        Ok(network_manifest) => network_manifest,
+        Err(e) => {
+            exit_with_error(ExitCode::Config, e);
+        }
+    };
+    let Some(devnet_config) = network_manifest.devnet else {
+        exit_with_error(ExitCode::Config, "unable to retrieve devnet settings");
+    };
+
+    let status = match cmd.namespace {
+        Some(ref namespace) => {
+            let access = match cmd.ingress_domain {
+                Some(domain) => ClusterAccess::Ingress { domain },
+                None => ClusterAccess::PortForward,
+            };
+            let connection = match ClusterConnection::connect(namespace, access, &devnet_config) {
+                Ok(connection) => connection,
+                Err(e) => {
+                    exit_with_error(ExitCode::Network, e);
+                }
+            };
+            // the port-forwards take a moment to listen
+            std::thread::sleep(std::time::Duration::from_secs(1));
+            connection.get_status()
+        }
+        None => coordinator_state::get_devnet_status(devnet_config.orchestrator_control_port),
+    };
+    let state = match status {
+        Ok(state) => state,
+        Err(e) => {
+            eprintln!(
+                "{}",
+                format_err!(format!(
+                    "unable to retrieve the devnet status (is devnet running?): {}",
+                    e
+                ))
+            );
+            ExitCode::Network.exit();
+        }
+    };
+    if cmd.json {
+        println!("{}", serde_json::to_string_pretty(&state).unwrap());
+        return;
+    }
+    let status = if state.boot_completed {
+        green!("ready")
+    } else {
+        yellow!("booting")
+    };
+    println!("Devnet {}", status);
+    println!("Bitcoin block #{}", state.bitcoin_block_height);
+    println!("Stacks block #{}", state.stacks_block_height);
+    if let Some(epoch) = state.epoch {
+        println!("Epoch {}", epoch);
+    }
+    if let Some(pox_cycle) = state.pox_cycle {
+        println!("PoX cycle {}", pox_cycle);
+    }
+    if let Some(services) = state.services {
+        println!("Stacks node: {}", services.stacks_node_host);
+        println!("Stacks API: {}", services.stacks_api_host);
+        println!("Bitcoin node: {}", services.bitcoin_node_host);
+    }
+}
+
+fn devnet_list(cmd: DevnetList) {
+    let instances = match registry::list_instances() {
+        Ok(instances) => instances,
+        Err(e) => {
+            exit_with_error(ExitCode::Internal, e);
+        }
+    };
+    if cmd.json {
+        println!("{}", serde_json::to_string_pretty(&instances).unwrap());
+        return;
+    }
+    if instances.is_empty() {
+        println!("No devnet instance");
+        return;
+    }
+    for instance in instances.iter() {
+        let status = if instance.is_running() {
+            green!("running")
+        } else {
+            yellow!("orphaned")
+        };
+        println!(
+            "{} ({}, pid {}, started at {})",
+            instance.network_name, status, instance.pid, instance.started_at
+        );
+        println!("  working dir: {}", instance.working_dir);
+        if let Some(port) = instance.ports.get("stacks_node_rpc_port") {
+            println!("  stacks node: http://localhost:{}", port);
+        }
+        if let Some(port) = instance.ports.get("bitcoin_node_rpc_port") {
+            println!("  bitcoin node: http://localhost:{}", port);
+        }
+    }
+}
+
+/// Registered instance of the project, the running one if there are several
+fn get_project_instance_or_exit(manifest_path: Option<String>) -> registry::DevnetRunMetadata {
+    let manifest_location = get_manifest_location_or_exit(manifest_path).to_string();
+    let instances = match registry::list_instances() {
+        Ok(instances) => instances,
+        Err(e) => {
+            exit_with_error(ExitCode::Internal, e);
+        }
+    };
+    let mut instances: Vec<_> = instances
+        .into_iter()
+        .filter(|instance| instance.manifest_location == manifest_location)
+        .collect();
+    if instances.len() > 1 {
+        instances.retain(|instance| instance.is_running());
+    }
+    match instances.len() {
+        1 => instances.remove(0),
+        0 => {
+            eprintln!(
+                "{}",
+                format_err!("no devnet instance of this project, start one with 'clarinet devnet start' or use --name")
+            );
+            ExitCode::Config.exit();
+        }
+        _ => {
+            let names: Vec<_> = instances.iter().map(|i| i.network_name.as_str()).collect();
+            eprintln!(
+                "{}",
+                format_err!(format!(
+                    "several devnet instances of this project are running ({}), use --name",
+                    names.join(", ")
+                ))
+            );
+            ExitCode::Config.exit();
+        }
+    }
+}
+
+fn devnet_logs(cmd: DevnetLogs) {
+    use stacks_network::service_logs::{
+        stream_service_logs, LogService, LogsFilter, LOG_SERVICES_NAMES,
+    };
+
+    let instance = match cmd.name {
+        Some(ref name) => match registry::get_instance(name) {
+            Ok(instance) => instance,
+            Err(e) => {
+                exit_with_error(ExitCode::Config, e);
+            }
+        },
+        None => get_project_instance_or_exit(cmd.manifest_path.clone()),
+    };
+
+    let names = if cmd.services.is_empty() {
+        LOG_SERVICES_NAMES
+            .iter()
+            .map(|name| name.to_string())
+            .collect()
+    } else {
+        cmd.services.clone()
+    };
+    let services: Vec<LogService> = match names
+        .iter()
+        .map(|name| LogService::from_name(name))
+        .collect()
+    {
+        Ok(services) => services,
+        Err(e) => {
+            exit_with_error(ExitCode::Config, e);
+        }
+    };
+    let filter = match LogsFilter::from_options(
+        cmd.level.as_deref(),
+        cmd.since.as_deref(),
+        cmd.until.as_deref(),
+        cmd.tail,
+        cmd.follow,
+    ) {
+        Ok(filter) => filter,
+        Err(e) => {
+            exit_with_error(ExitCode::Config, e);
+        }
+    };
+
+    // the signers run in several containers, their lines are prefixed even when selected alone
+    let prefixed = services.len() > 1 || services.contains(&LogService::Signer);
+    let mut labels: Vec<String> = vec![];
+    let mut on_line = |label: &str, line: &str| {
+        if !prefixed {
+            println!("{}", line);
+            return;
+        }
+        let index = match labels.iter().position(|l| l == label) {
+            Some(index) => index,
+            None => {
+                labels.push(label.to_string());
+                labels.len() - 1
+            }
+        };
+        let prefix = format!("{:<16} |", label);
+        let prefix = match index % 4 {
+            0 => blue!(prefix),
+            1 => green!(prefix),
+            2 => yellow!(prefix),
+            _ => purple!(prefix),
+        };
+        println!("{} {}", prefix, line);
+    };
+    let result = stacks_network::block_on(stream_service_logs(
+        &instance,
+        &services,
+        &filter,
+        &mut on_line,
+    ));
+    if let Err(e) = result {
+        exit_with_error(ExitCode::Network, e);
+    }
+}
+
+fn devnet_stop(cmd: DevnetStop) {
+    let instance = match registry::get_instance(&cmd.name) {
+        Ok(instance) => instance,
+        Err(e) => {
+            exit_with_error(ExitCode::Config, e);
+        }
+    };
+    match stacks_network::block_on(registry::stop_instance(&instance)) {
+        Ok(_) if !output::show_summary() => {}
+        Ok(removed) => println!(
+            "{} {} ({} containers removed)",
+            green!("Stopped"),
+            instance.network_name,
+            removed
+        ),
+        Err(e) => {
+            exit_with_error(ExitCode::Network, e);
+        }
+    }
+}
+
+fn devnet_clean(cmd: DevnetClean) {
+    let instances = match registry::list_instances() {
+        Ok(instances) => instances,
+        Err(e) => {
+            exit_with_error(ExitCode::Internal, e);
+        }
+    };
+    let orphaned: Vec<_> = instances.iter().filter(|i| !i.is_running()).collect();
+    if orphaned.is_empty() {
+        if output::show_summary() {
+            println!("No orphaned devnet instance");
+        }
+        return;
+    }
+    for instance in orphaned {
+        if let Err(e) = stacks_network::block_on(registry::stop_instance(instance)) {
+            eprintln!("{}", format_err!(e));
+            continue;
+        }
+        if !cmd.keep_working_dirs {
+            if let Err(e) = fs::remove_dir_all(&instance.working_dir) {
+                eprintln!(
+                    "{}",
+                    format_err!(format!("unable to remove {}: {}", instance.working_dir, e))
+                );
+            }
+        }
+        if output::show_summary() {
+            println!("{} {}", green!("Cleaned"), instance.network_name);
+        }
+    }
+}
+
+/// Uses the Stacks node of the clarinetrc files when the network manifest doesn't specify one
+fn use_default_stacks_node(
+    deployment: &mut DeploymentSpecification,
+    manifest: &ProjectManifest,
+    global_settings: &GlobalSettings,
+) {
+    let Some(stacks_node) = global_settings.get_default_stacks_node(&deployment.network) else {
+        return;
+    };
+    let Ok(network_manifest) = NetworkManifest::from_project_manifest_location(
+        &manifest.location,
+        &deployment.network.get_networks(),
+        Some(&manifest.project.cache_location),
+        None,
+    ) else {
+        return;
+    };
+    if network_manifest.network.stacks_node_rpc_address.is_none() {
+        deployment.stacks_node = Some(stacks_node.clone());
+    }
+}
+
+fn config_get(cmd: ConfigGet, global_settings: &GlobalSettings) {
+    let keys = match cmd.key {
+        Some(key) => vec![key],
+        None => GlobalSettings::get_keys(),
+    };
+    for key in keys {
+        match global_settings.get_value(&key) {
+            Ok(Some(value)) => match GlobalSettings::get_value_layer(&key) {
+                Some(layer) => println!("{} = {} ({})", key, value, layer),
+                None => println!("{} = {}", key, value),
+            },
+            Ok(None) => println!("{} is not set", key),
+            Err(e) => {
+                eprintln!("{}", format_err!(e));
+                process::exit(1);
+            }
+        }
+    }
+}
+
+fn config_set(cmd: ConfigSet) {
+    let layer = if cmd.project {
+        SettingsLayer::Project
+    } else if cmd.system {
+        SettingsLayer::System
+    } else {
+        SettingsLayer::User
+    };
+    match GlobalSettings::write_value(layer, &cmd.key, &cmd.value) {
+        Ok(path) => println!(
+            "{} {} = {} in {}",
+            green!("Updated"),
+            cmd.key,
+            cmd.value,
+            path.display()
+        ),
+        Err(e) => {
+            eprintln!("{}", format_err!(e));
+            process::exit(1);
+        }
+    }
+}
+
+fn format_size(size: u64) -> String {
+    match size {
+        0..=1023 => format!("{} B", size),
+        1024..=1_048_575 => format!("{:.1} KB", size as f64 / 1024.0),
+        _ => format!("{:.1} MB", size as f64 / 1_048_576.0),
+    }
+}
+
+fn print_post_check_result(result: &PostCheckResult) {
+    let batch = match result.batch_id {
+        Some(batch_id) => format!("batch {}", batch_id),
+        None => "plan".to_string(),
+    };
+    if result.passed {
+        if !output::show_details() {
+            return;
+        }
+        println!(
+            "{} post check ({}) {}: {}",
+            green!("✔"),
+            batch,
+            result.check,
+            result.message
+        );
+    } else if output::show_summary() {
+        println!(
+            "{} post check ({}) {}: {}",
+            red!("x"),
+            batch,
+            result.check,
+            result.message
+        );
+    }
+}
+
+/// `deployments/default.testnet-plan.yaml` -> `deployments/default.testnet-plan.receipt.json`
+fn get_receipt_location(plan_location: &FileLocation) -> Option<FileLocation> {
+    // the receipts of the plans served over http(s) are not kept
+    if let FileLocation::Url { .. } = plan_location {
+        return None;
+    }
+    let file_name = plan_location.get_file_name()?;
+    let stem = file_name
+        .strip_suffix(".yaml")
+        .or_else(|| file_name.strip_suffix(".yml"))
+        .unwrap_or(&file_name);
+    let mut location = plan_location.get_parent_location().ok()?;
+    location
+        .append_path(&format!("{}.receipt.json", stem))
+        .ok()?;
+    Some(location)
+}
+
+fn format_stx(ustx: u64) -> String {
+    format!("{}.{:06} STX", ustx / 1_000_000, ustx % 1_000_000)
+}
+
+fn bench_snippet(cmd: Bench) {
+    let manifest = load_manifest_or_exit(cmd.manifest_path);
+    let (_, _, artifacts) = load_deployment_and_artifacts_or_exit(&manifest, &None, false, false);
+    if !artifacts.success {
+        eprintln!(
+            "{}",
+            format_err!("the contracts of the project have errors, run clarinet check")
+        );
+        process::exit(1);
+    }
+    let report = match bench::run_benchmark(&artifacts.session, &cmd.snippet, cmd.runs) {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("{}", format_err!(e));
+            process::exit(1);
+        }
+    };
+
+    let baseline_location = match cmd.baseline_path {
+        Some(ref path) => FileLocation::from_path_string(path),
+        None => manifest
+            .location
+            .get_project_root_location()
+            .and_then(|mut location| {
+                location.append_path(bench::DEFAULT_BENCH_BASELINE_FILE)?;
+                Ok(location)
+            }),
+    };
+    let baseline_location = match baseline_location {
+        Ok(location) => location,
+        Err(e) => {
+            eprintln!("{}", format_err!(e));
+            process::exit(1);
+        }
+    };
+    let mut baseline = if baseline_location.exists() {
+        match bench::BenchBaseline::from_location(&baseline_location) {
+            Ok(baseline) => baseline,
+            Err(e) => {
+                eprintln!("{}", format_err!(e));
+                process::exit(1);
+            }
+        }
+    } else {
+        bench::BenchBaseline::default()
+    };
+    let changes = baseline
+        .reports
+        .get(&cmd.snippet)
+        .map(|previous| bench::compare_reports(previous, &report));
+
+    if cmd.json {
+        let output = json!({ "report": report, "changes": changes });
+        println!("{}", serde_json::to_string_pretty(&output).unwrap());
+    } else {
+        println!(
+            "{} ({})",
+            blue!(&report.snippet),
+            pluralize!(report.runs, "run")
+        );
+        println!(
+            "  cost: runtime {}, read_count {}, read_length {}, write_count {}, write_length {}",
+            report.cost.runtime,
+            report.cost.read_count,
+            report.cost.read_length,
+            report.cost.write_count,
+            report.cost.write_length
+        );
+        let wall_time = &report.wall_time;
+        println!(
+            "  wall time (µs): min {}, p50 {}, p95 {}, max {}, mean {}",
+            wall_time.min, wall_time.p50, wall_time.p95, wall_time.max, wall_time.mean
+        );
+        if let Some(ref changes) = changes {
+            println!("  compared to {}:", baseline_location);
+            for change in changes.iter() {
+                let percentage = match change.percentage() {
+                    Some(percentage) if percentage > 0.0 => red!(format!("+{:.1}%", percentage)),
+                    Some(percentage) if percentage < 0.0 => green!(format!("{:.1}%", percentage)),
+                    Some(_) => "=".to_string(),
+                    None => "-".to_string(),
+                };
+                println!(
+                    "    {}: {} -> {} ({})",
+                    change.metric, change.baseline, change.current, percentage
+                );
+            }
+        }
+    }
+
+    if cmd.save_baseline {
+        baseline.reports.insert(cmd.snippet.clone(), report);
+        if let Err(e) = baseline.write(&baseline_location) {
+            eprintln!("{}", format_err!(e));
+            process::exit(1);
+        }
+        eprintln!("{} baseline saved in {}", green!("✔"), baseline_location);
+    }
+}
+
+fn call_read_only(cmd: CallReadOnly) {
+    let contract_id = match QualifiedContractIdentifier::parse(&cmd.contract_id) {
+        Ok(contract_id) => contract_id,
+        Err(_) => exit_with_error(
+            ExitCode::Config,
+            format!("{} is not a valid contract id", cmd.contract_id),
+        ),
+    };
+    let network = if cmd.devnet {
+        StacksNetwork::Devnet
+    } else if cmd.testnet {
+        StacksNetwork::Testnet
+    } else {
+        StacksNetwork::Mainnet
+    };
+    let args = match read_only::parse_arguments(&cmd.args) {
+        Ok(args) => args,
+        Err(e) => exit_with_error(ExitCode::Config, e),
+    };
+    let node_url = match cmd.node {
+        Some(node_url) => node_url,
+        None => match read_only::get_default_node_url(&network) {
+            Ok(node_url) => node_url.to_string(),
+            Err(e) => exit_with_error(ExitCode::Config, e),
+        },
+    };
+    match read_only::remote_call_read_only_on_node(
+        &node_url,
+        &contract_id,
+        &cmd.function,
+        args,
+        cmd.sender.as_deref(),
+    ) {
+        Ok(value) => println!("{}", value),
+        Err(e) => exit_with_error(ExitCode::Network, e),
+    }
+}
+
+fn verify_metadata(cmd: VerifyMetadata) {
+    use clarinet_deployments::types::TransactionSpecification;
+
+    let manifest = load_manifest_or_exit(cmd.manifest_path);
+    if !manifest.contracts.contains_key(&cmd.contract) {
+        exit_with_error(
+            ExitCode::Config,
+            format!("{} is not a contract of Clarinet.toml", cmd.contract),
+        );
+    }
+    let network = if cmd.devnet {
+        StacksNetwork::Devnet
+    } else if cmd.mainnet {
+        StacksNetwork::Mainnet
+    } else {
+        StacksNetwork::Testnet
+    };
+    let deployment = match get_default_deployment_path(&manifest, &network)
+        .and_then(|location| load_deployment(&manifest, &location))
+    {
+        Ok(deployment) => deployment,
+        Err(e) => exit_with_error(
+            ExitCode::Config,
+            format!(
+                "unable to load the {:?} plan, generate it with 'clarinet deployments generate': {}",
+                network, e
+            ),
+        ),
+    };
+    let published = deployment
+        .plan
+        .batches
+        .iter()
+        .flat_map(|batch| batch.transactions.iter())
+        .find_map(|transaction| match transaction {
+            TransactionSpecification::ContractPublish(tx)
+                if tx.contract_name.as_str() == cmd.contract =>
+            {
+                let contract_id = QualifiedContractIdentifier::new(
+                    tx.expected_sender.clone(),
+                    tx.contract_name.clone(),
+                );
+                Some((contract_id, tx.source.clone()))
+            }
+            _ => None,
+        });
+    let Some((contract_id, local_source)) = published else {
+        exit_with_error(
+            ExitCode::Config,
+            format!("the {:?} plan doesn't publish {}", network, cmd.contract),
+        );
+    };
+    let Some(node_url) = cmd.node.or(deployment.stacks_node.clone()) else {
+        exit_with_error(ExitCode::Config, "no Stacks node in the plan, use --node");
+    };
+
+    let deployed_source = match hiro_system_kit::nestable_block_on(
+        source_metadata::fetch_deployed_source(&node_url, &contract_id),
+    ) {
+        Ok(Some(source)) => source,
+        Ok(None) => exit_with_error(
+            ExitCode::Diagnostics,
+            format!("{} is not deployed on {}", contract_id, node_url),
+        ),
+        Err(e) => exit_with_error(ExitCode::Network, e),
+    };
+    let remapped = matches!(network, StacksNetwork::Devnet | StacksNetwork::Testnet);
+    let verification = source_metadata::verify_source_metadata(
+        &contract_id,
+        &deployed_source,
+        &local_source,
+        remapped,
+    );
+
+    if cmd.json {
+        println!("{}", serde_json::to_string_pretty(&verification).unwrap());
+    } else if output::show_summary() {
+        let metadata = verification.metadata.as_ref();
+        if let Some(metadata) = metadata.filter(|_| output::show_details()) {
+            println!("{}", contract_id);
+            println!("  source hash: {}", metadata.source_hash);
+            println!("  clarinet version: {}", metadata.clarinet_version);
+            println!("  plan id: {}", metadata.plan_id);
+        }
+        if remapped && metadata.is_some() && !verification.deployed_code_matches {
+            println!(
+                "{} the deployed code differs from the described source, its requirements were remapped",
+                yellow!("note:")
+            );
+        }
+        for issue in verification.issues.iter() {
+            println!("{} {}", red!("x"), issue);
+        }
+        if verification.is_success() {
+            println!(
+                "{} {} matches the source of the project",
+                green!("✔"),
+                contract_id
+            );
+        }
+    }
+    if !verification.is_success() {
+        ExitCode::Diagnostics.exit();
+    }
+}
+
+fn project_stats(cmd: Stats) {
+    let manifest = load_manifest_or_exit(cmd.manifest_path);
+    let (deployment, _, artifacts) =
+        load_deployment_and_artifacts_or_exit(&manifest, &None, false, false);
+    // the fee rate of mainnet, the network where the deployment cost matters
+    let deployment_fee_rate = [StacksNetwork::Mainnet, StacksNetwork::Devnet]
+        .iter()
+        .find_map(|network| {
+            NetworkManifest::from_project_manifest_location(
+                &manifest.location,
+                &network.get_networks(),
+                Some(&manifest.project.cache_location),
+                None,
+            )
+            .ok()
+        })
+        .map(|network_manifest| network_manifest.network.deployment_fee_rate)
+        .unwrap_or(DEFAULT_DEPLOYMENT_FEE_RATE);
+    let stats = stats::get_project_stats(&manifest, &deployment, &artifacts, deployment_fee_rate);
+
+    if cmd.json {
+        println!("{}", serde_json::to_string_pretty(&stats).unwrap());
+        return;
+    }
+    println!("{}", blue!(manifest.project.name.as_str()));
+    println!(
+        "  {} ({})",
+        pluralize!(stats.contracts.len(), "contract"),
+        pluralize!(stats.lines, "line")
+    );
+    println!(
+        "  functions: {} public, {} read-only, {} private",
+        stats.public_functions, stats.read_only_functions, stats.private_functions
+    );
+    println!(
+        "  {} (dependency depth: {})",
+        pluralize!(stats.requirements, "requirement"),
+        stats.dependency_depth
+    );
+    println!(
+        "  estimated deployment cost: {}",
+        format_stx(stats.deployment_cost)
+    );
+    match stats.coverage {
+        Some(coverage) => println!("  test coverage: {:.1}%", coverage),
+        None => println!(
+            "  test coverage: unknown (no {} found)",
+            stats::COVERAGE_REPORT_FILE
+        ),
+    }
+
+    let largest = stats.get_largest_contracts(cmd.top);
+    if !largest.is_empty() {
+        println!("{}", blue!("Largest contracts"));
+        for contract in largest {
+            println!(
+                "  {} ({}, {}, {})",
+                contract.contract_id,
+                format_size(contract.size as u64),
+                pluralize!(contract.lines, "line"),
+                format_stx(contract.deployment_cost)
+            );
+        }
+    }
+}
+
+fn inspect_storage_layout(cmd: StorageLayout) {
+    let manifest = load_manifest_or_exit(cmd.manifest_path);
+    if let Some(name) = cmd
+        .contracts
+        .iter()
+        .find(|name| !manifest.contracts.contains_key(name.as_str()))
+    {
+        eprintln!(
+            "{}",
+            format_err!(format!("contract {} not found in Clarinet.toml", name))
+        );
+        process::exit(1);
+    }
+    let (deployment, _, artifacts) =
+        load_deployment_and_artifacts_or_exit(&manifest, &None, false, false);
+    let layouts: Vec<_> = storage_layout::get_storage_layout(&manifest, &deployment, &artifacts)
+        .into_iter()
+        .filter(|layout| {
+            cmd.contracts.is_empty()
+                || cmd
+                    .contracts
+                    .iter()
+                    .any(|name| layout.contract_id.ends_with(&format!(".{}", name)))
+        })
+        .collect();
+
+    if cmd.json {
+        println!("{}", serde_json::to_string_pretty(&layouts).unwrap());
+        return;
+    }
+    let format_size = |size: Option<u32>| {
+        size.map(|size| format!("{} bytes", size))
+            .unwrap_or_else(|| "unknown size".to_string())
+    };
+    for layout in layouts.iter() {
+        println!("{}", blue!(layout.contract_id.as_str()));
+        if layout.data_vars.is_empty() && layout.maps.is_empty() && layout.tokens.is_empty() {
+            println!("  no persistent storage");
+        }
+        for var in layout.data_vars.iter() {
+            println!(
+                "  data-var {}: {} = {} ({})",
+                var.name,
+                var.value_type,
+                var.initial_value.as_deref().unwrap_or("?"),
+                format_size(var.max_size)
+            );
+        }
+        for map in layout.maps.iter() {
+            println!(
+                "  map {}: {} -> {} ({} per entry{})",
+                map.name,
+                map.key_type,
+                map.value_type,
+                format_size(map.max_entry_size),
+                map.max_entries_per_block
+                    .map(|count| format!(", {} entries per block at most", count))
+                    .unwrap_or_default()
+            );
+        }
+        for token in layout.tokens.iter() {
+            match token.kind {
+                storage_layout::TokenKind::Fungible => println!(
+                    "  fungible-token {} (supply: {})",
+                    token.name,
+                    token.total_supply.as_deref().unwrap_or("unlimited")
+                ),
+                storage_layout::TokenKind::NonFungible => println!(
+                    "  non-fungible-token {}: {}",
+                    token.name,
+                    token.asset_type.as_deref().unwrap_or("?")
+                ),
+            }
+        }
+    }
+}
+
+fn inspect_versions(cmd: InspectVersions) {
+    let manifest = load_manifest_or_exit(cmd.manifest_path);
+    let network = if cmd.devnet {
+        StacksNetwork::Devnet
+    } else if cmd.testnet {
+        StacksNetwork::Testnet
+    } else if cmd.mainnet {
+        StacksNetwork::Mainnet
+    } else {
+        StacksNetwork::Simnet
+    };
+    // the plan on disk if there is one, as it is the one that gets applied
+    let deployment_location = match cmd.deployment_plan_path {
+        Some(ref path) => get_absolute_deployment_path(&manifest, path).map(Some),
+        None => get_default_deployment_path(&manifest, &network)
+            .map(|location| location.exists().then_some(location)),
+    };
+    let deployment = deployment_location.and_then(|location| match location {
+        Some(location) => load_deployment(&manifest, &location),
+        None => generate_default_deployment(&manifest, &network, false)
+            .map(|(deployment, _)| deployment),
+    });
+    let deployment = match deployment {
+        Ok(deployment) => deployment,
+        Err(message) => {
+            eprintln!("{}", format_err!(message));
+            process::exit(1);
+        }
+    };
+    let matrix = versions::get_versions_matrix(&manifest, &deployment);
+
+    if cmd.json {
+        println!("{}", serde_json::to_string_pretty(&matrix).unwrap());
+        return;
+    }
+    if matrix.is_empty() {
+        println!("no contract published by the plan");
+        return;
+    }
+    let width = matrix
+        .iter()
+        .map(|contract| contract.contract_id.len())
+        .max()
+        .unwrap_or_default();
+    println!(
+        "{:<width$}  {:>5}  {:>7}  {:>5}  {:>9}  deployer",
+        "contract", "batch", "clarity", "epoch", "size"
+    );
+    for contract in matrix.iter() {
+        let contract_id = if contract.is_requirement {
+            format!("{} (requirement)", contract.contract_id)
+        } else {
+            contract.contract_id.clone()
+        };
+        println!(
+            "{:<width$}  {:>5}  {:>7}  {:>5}  {:>9}  {}",
+            contract_id,
+            contract.batch_id,
+            contract.clarity_version,
+            contract
+                .epoch
+                .map(|epoch| epoch.to_string())
+                .unwrap_or_else(|| "-".to_string()),
+            format!("{} B", contract.size),
+            contract.deployer
+        );
+        for warning in contract.warnings.iter() {
+            println!("  {} {}", yellow!("warning:"), warning);
+        }
+    }
+    let warnings: usize = matrix.iter().map(|contract| contract.warnings.len()).sum();
+    if warnings > 0 {
+        println!("{}", yellow!(pluralize!(warnings, "warning")));
+    }
+    if matrix.iter().any(|contract| contract.epoch.is_none()) {
+        println!("-: published in the epoch of the network at the time of the deployment");
+    }
+}
+
+fn get_scenarios_locations(
+    manifest: &ProjectManifest,
+    files: Vec<String>,
+) -> Result<Vec<FileLocation>, String> {
+    if !files.is_empty() {
+        return files
+            .iter()
+            .map(|file| FileLocation::from_path_string(file))
+            .collect();
+    }
+    let mut location = manifest.location.get_project_root_location()?;
+    location.append_path(scenarios::SCENARIOS_DIR)?;
+    let entries = std::fs::read_dir(location.to_string())
+        .map_err(|e| format!("unable to read {}: {}", location, e))?;
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "yaml" || extension == "yml")
+        })
+        .collect();
+    paths.sort();
+    Ok(paths.into_iter().map(FileLocation::from_path).collect())
+}
+
+fn run_scenarios(cmd: Scenarios) {
+    let manifest = load_manifest_or_exit(cmd.manifest_path);
+    let locations = match get_scenarios_locations(&manifest, cmd.files) {
+        Ok(locations) => locations,
+        Err(e) => {
+            eprintln!("{}", format_err!(e));
+            process::exit(1);
+        }
+    };
+    if locations.is_empty() {
+        eprintln!("{}", format_err!("no scenario found"));
+        process::exit(1);
+    }
+    let mut named_scenarios = vec![];
+    for location in locations.iter() {
+        let scenario = match scenarios::Scenario::from_location(location) {
+            Ok(scenario) => scenario,
+            Err(e) => {
+                eprintln!("{}", format_err!(format!("{}: {}", location, e)));
+                process::exit(1);
+            }
+        };
+        let name = location.get_file_name().unwrap_or(location.to_string());
+        named_scenarios.push((name, scenario));
+    }
+
+    if !cmd.compare_epochs.is_empty() {
+        return compare_scenarios_epochs(
+            &manifest,
+            &named_scenarios,
+            &cmd.compare_epochs,
+            cmd.json,
+        );
+    }
+
+    let (_, _, artifacts) = load_deployment_and_artifacts_or_exit(&manifest, &None, false, false);
+    if !artifacts.success {
+        eprintln!(
+            "{}",
+            format_err!(
+                "the contracts must be fixed before running the scenarios (see clarinet check)"
+            )
+        );
+        process::exit(1);
+    }
+
+    let mut reports = vec![];
+    for (name, scenario) in named_scenarios.iter() {
+        // every scenario starts from the state of the deployment
+        let mut session = artifacts.session.clone();
+        reports.push(scenarios::run_scenario(&mut session, name, scenario));
+    }
+    let success = reports.iter().all(|report| report.success());
+
+    if cmd.json {
+        println!("{}", serde_json::to_string_pretty(&reports).unwrap());
+    } else {
+        for report in reports.iter() {
+            if report.success() {
+                println!("{} {}", green!("✔"), report.name);
+            } else {
+                println!("{} {}", red!("x"), report.name);
+            }
+            for step in report.steps.iter() {
+                match step.outcome {
+                    scenarios::StepOutcome::Passed => {
+                        println!("  {} {}", green!("✔"), step.description)
+                    }
+                    scenarios::StepOutcome::Failed(ref message) => {
+                        println!("  {} {}", red!("x"), step.description);
+                        println!("    {}", message);
+                    }
+                    scenarios::StepOutcome::Skipped => {
+                        println!("  {} {}", yellow!("-"), step.description)
+                    }
+                }
+            }
+        }
+        let failed = reports.iter().filter(|report| !report.success()).count();
+        println!(
+            "{} passed, {} failed",
+            pluralize!(reports.len() - failed, "scenario"),
+            failed
+        );
+    }
+    if !success {
+        process::exit(1);
+    }
+}
+
+#[derive(Serialize)]
+struct ContractMigration {
+    contract: String,
+    path: String,
+    issues: Vec<clarity_migration::MigrationIssue>,
+}
+
+fn migrate_contracts(cmd: Migrate) {
+    if cmd.to != "clarity3" {
+        eprintln!(
+            "{}",
+            format_err!(format!(
+                "unsupported migration target {}, expected clarity3",
+                cmd.to
+            ))
+        );
+        process::exit(1);
+    }
+    let manifest = load_manifest_or_exit(cmd.manifest_path);
+    let (deployment, _, artifacts) =
+        load_deployment_and_artifacts_or_exit(&manifest, &None, false, false);
+
+    let mut migrations = vec![];
+    let mut contracts_to_update = HashMap::new();
+    for (name, contract) in manifest.contracts.iter() {
+        if contract.clarity_version >= ClarityVersion::Clarity3 {
+            continue;
+        }
+        let Some((contract_id, (source, location))) = deployment
+            .contracts
+            .iter()
+            .find(|(contract_id, _)| contract_id.name.as_str() == name)
+        else {
+            continue;
+        };
+        let Some(ast) = artifacts.asts.get(contract_id) else {
+            continue;
+        };
+        let issues = clarity_migration::get_migration_issues(&ast.expressions);
+        let path = location
+            .get_relative_location()
+            .unwrap_or(location.to_string());
+
+        if cmd.write {
+            let rewrite = issues.iter().any(|issue| issue.replacement.is_some());
+            // the sources made of several files, or with injected defines, differ from the
+            // files and can't be rewritten
+            let rewritable = location.read_content().ok().as_deref() == Some(source.as_bytes());
+            if rewrite && !rewritable {
+                eprintln!(
+                    "{} the source of {} differs from {}, it has to be migrated by hand",
+                    yellow!("warning:"),
+                    name,
+                    path
+                );
+            } else if rewrite {
+                let written = clarity_migration::apply_migration(source, &issues)
+                    .and_then(|migrated| location.write_content(migrated.as_bytes()));
+                if let Err(e) = written {
+                    eprintln!("{}", format_err!(format!("{}: {}", path, e)));
+                    process::exit(1);
+                }
+            }
+            // the reserved names have to be renamed before the contract uses Clarity 3
+            let has_reserved_names = issues
+                .iter()
+                .any(|issue| issue.kind == clarity_migration::MigrationIssueKind::ReservedName);
+            if (!rewrite || rewritable) && !has_reserved_names {
+                let mut contract = contract.clone();
+                contract.clarity_version = ClarityVersion::Clarity3;
+                contract.epoch = contract.epoch.max(StacksEpochId::Epoch30);
+                contracts_to_update.insert(name.clone(), contract);
+            }
+        }
+        migrations.push(ContractMigration {
+            contract: name.clone(),
+            path,
+            issues,
+        });
+    }
+
+    if cmd.json {
+        println!("{}", serde_json::to_string_pretty(&migrations).unwrap());
+    } else {
+        for migration in migrations.iter() {
+            if migration.issues.is_empty() {
+                println!("{} {}", green!("✔"), migration.path);
+                continue;
+            }
+            println!("{}", migration.path);
+            for issue in migration.issues.iter() {
+                let kind = match issue.kind {
+                    clarity_migration::MigrationIssueKind::Removed => green!("rewrite:"),
+                    clarity_migration::MigrationIssueKind::ReservedName => red!("rename:"),
+                    clarity_migration::MigrationIssueKind::BehaviorChange => yellow!("review:"),
+                };
+                println!(
+                    "  {}:{} {} {}",
+                    issue.line, issue.column, kind, issue.message
+                );
+            }
+        }
+    }
+
+    if !contracts_to_update.is_empty() {
+        let change = TOMLEdition {
+            comment: format!(
+                "{}, {} using Clarity 3",
+                yellow!("Updated Clarinet.toml"),
+                pluralize!(contracts_to_update.len(), "contract")
+            ),
+            manifest_location: manifest.location.clone(),
+            contracts_to_add: contracts_to_update,
+            contracts_to_rm: vec![],
+            contracts_networks: HashMap::new(),
+            requirements_to_add: vec![],
+        };
+        if !execute_changes(vec![Changes::EditTOML(change)]) {
+            process::exit(1);
+        }
+    } else if !cmd.write && !cmd.json && !migrations.is_empty() {
+        println!(
+            "\n{} rewrite the mechanical changes and update Clarinet.toml with --write",
+            blue!("hint:")
+        );
+    }
+}
+
+fn print_text_diff(before: &str, after: &str) {
+    use similar::{ChangeTag, TextDiff};
+
+    let diff = TextDiff::from_lines(before, after);
+    for (index, group) in diff.grouped_ops(1).iter().enumerate() {
+        if index > 0 {
+            println!("{}", blue!("..."));
+        }
+        for op in group {
+            for change in diff.iter_changes(op) {
+                let line = change.to_string_lossy();
+                let line = line.trim_end_matches('\n');
+                match change.tag() {
+                    ChangeTag::Delete => println!("{}", red!(format!("- {}", line))),
+                    ChangeTag::Insert => println!("{}", green!(format!("+ {}", line))),
+                    ChangeTag::Equal => println!("  {}", line),
+                }
+            }
+        }
+    }
+}
+
+/// Files of a directory of the project with the given extension, sorted
+fn get_project_files(root: &FileLocation, directory: &str, extension: &str) -> Vec<FileLocation> {
+    let mut location = root.clone();
+    if location.append_path(directory).is_err() {
+        return vec![];
+    }
+    let Ok(entries) = std::fs::read_dir(location.to_string()) else {
+        return vec![];
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == extension))
+        .collect();
+    paths.sort();
+    paths.into_iter().map(FileLocation::from_path).collect()
+}
+
+fn replace_principal(cmd: ReplacePrincipal) {
+    use clarinet_deployments::refactor::{
+        replace_principal_in_contract, replace_principal_in_text,
+    };
+
+    let manifest = load_manifest_or_exit(cmd.manifest_path);
+    for principal in [&cmd.from, &cmd.to] {
+        if PrincipalData::parse_standard_principal(principal).is_err() {
+            eprintln!(
+                "{}",
+                format_err!(format!("invalid principal {}", principal))
+            );
+            process::exit(1);
+        }
+    }
+    let root = match manifest.location.get_project_root_location() {
+        Ok(root) => root,
+        Err(e) => {
+            eprintln!("{}", format_err!(e));
+            process::exit(1);
+        }
+    };
+
+    // (location, original content, rewritten content, replacements)
+    let mut changes = vec![];
+    let mut mentions = vec![];
+    for contract in manifest.contracts.values() {
+        let ClarityCodeSource::ContractOnDisk(ref path) = contract.code_source else {
+            continue;
+        };
+        let location = FileLocation::from_path(path.clone());
+        let Ok(source) = location.read_content_as_utf8() else {
+            continue;
+        };
+        match replace_principal_in_contract(&source, &cmd.from, &cmd.to) {
+            Ok((rewritten, rewrites, remaining)) => {
+                if remaining > 0 {
+                    mentions.push((location.clone(), remaining));
+                }
+                if !rewrites.is_empty() {
+                    changes.push((location, source, rewritten, rewrites.len()));
+                }
+            }
+            Err(e) => {
+                eprintln!("{}", format_err!(e));
+                process::exit(1);
+            }
+        }
+    }
+    let text_files = [manifest.location.clone()]
+        .into_iter()
+        .chain(get_project_files(&root, "settings", "toml"))
+        .chain(get_project_files(&root, "deployments", "yaml"));
+    for location in text_files {
+        let Ok(content) = location.read_content_as_utf8() else {
+            continue;
+        };
+        let (rewritten, count) = replace_principal_in_text(&content, &cmd.from, &cmd.to);
+        if count > 0 {
+            changes.push((location, content, rewritten, count));
+        }
+    }
+
+    let get_path = |location: &FileLocation| {
+        location
+            .get_relative_location()
+            .unwrap_or(location.to_string())
+    };
+    if changes.is_empty() {
+        println!("{} not found in the project", cmd.from);
+    }
+    for (location, original, rewritten, count) in changes.iter() {
+        let path = get_path(location);
+        if cmd.dry_run {
+            println!("{} ({})", path, pluralize!(*count, "replacement"));
+            print_text_diff(original, rewritten);
+            println!();
+        } else {
+            if let Err(e) = location.write_content(rewritten.as_bytes()) {
+                eprintln!("{}", format_err!(format!("{}: {}", path, e)));
+                process::exit(1);
+            }
+            println!(
+                "{} {} ({})",
+                green!("Updated"),
+                path,
+                pluralize!(*count, "replacement")
+            );
+        }
+    }
+    for (location, count) in mentions.iter() {
+        println!(
+            "{} {}: {} of {} left in comments or strings, review them by hand",
+            yellow!("warning:"),
+            get_path(location),
+            pluralize!(*count, "occurrence"),
+            cmd.from
+        );
+    }
+    if cmd.dry_run && !changes.is_empty() {
+        println!(
+            "{} {} to update, run without --dry-run to write them",
+            blue!("hint:"),
+            pluralize!(changes.len(), "file")
+        );
+    }
+}
+
+fn migrate_project(cmd: MigrateProject) {
+    use clarinet_deployments::types::DeploymentSpecificationFile;
+    use clarinet_files::{migrate_network_manifest, migrate_project_manifest};
+
+    let manifest_location = get_manifest_location_or_exit(cmd.manifest_path);
+    let root = match manifest_location.get_parent_location() {
+        Ok(root) => root,
+        Err(e) => {
+            eprintln!("{}", format_err!(e));
+            process::exit(1);
+        }
+    };
+    let get_path = |location: &FileLocation| {
+        location
+            .get_relative_path_from_base(&root)
+            .unwrap_or(location.to_string())
+    };
+
+    // every file is migrated before anything is written
+    let mut migrations = vec![];
+    let mut manual_steps = vec![];
+    let manifests = [manifest_location.clone()]
+        .into_iter()
+        .chain(get_project_files(&root, "settings", "toml"));
+    for location in manifests {
+        let Ok(content) = location.read_content_as_utf8() else {
+            continue;
+        };
+        let migration = if location == manifest_location {
+            migrate_project_manifest(&content)
+        } else {
+            migrate_network_manifest(&content)
+        };
+        match migration {
+            Ok(migration) => {
+                for step in migration.manual_steps.iter() {
+                    manual_steps.push(format!("{}: {}", get_path(&location), step));
+                }
+                if migration.is_changed() {
+                    migrations.push((location, content, migration));
+                }
+            }
+            Err(e) => {
+                eprintln!("{}", format_err!(format!("{}: {}", get_path(&location), e)));
+                process::exit(1);
+            }
+        }
+    }
+    for location in get_project_files(&root, "deployments", "yaml") {
+        let Ok(content) = location.read_content_as_utf8() else {
+            continue;
+        };
+        if let Err(e) = DeploymentSpecificationFile::from_file_content(&content) {
+            manual_steps.push(format!(
+                "{}: {}, regenerate it with `clarinet deployments generate`",
+                get_path(&location),
+                e
+            ));
+        }
+    }
+    let deno_tests = get_project_files(&root, "tests", "ts")
+        .into_iter()
+        .filter(|location| {
+            location
+                .read_content_as_utf8()
+                .is_ok_and(|content| content.contains("deno.land/x/clarinet"))
+        })
+        .count();
+    if deno_tests > 0 {
+        manual_steps.push(format!(
+            "tests: {} written for the Deno test runner, they have to be ported to the clarinet-sdk and vitest",
+            pluralize!(deno_tests, "file")
+        ));
+    }
+
+    if migrations.is_empty() && manual_steps.is_empty() {
+        println!(
+            "{} the project already uses the current formats",
+            green!("✔")
+        );
+        return;
+    }
+    for (location, content, migration) in migrations.iter() {
+        let path = get_path(location);
+        if cmd.dry_run {
+            println!(
+                "{} ({})",
+                path,
+                pluralize!(migration.changes.len(), "change")
+            );
+            print_text_diff(content, &migration.content);
+        } else {
+            if let Err(e) = location.write_content(migration.content.as_bytes()) {
+                eprintln!("{}", format_err!(format!("{}: {}", path, e)));
+                process::exit(1);
+            }
+            println!("{} {}", green!("Updated"), path);
+        }
+        for change in migration.changes.iter() {
+            println!("  - {}", change);
+        }
+        println!();
+    }
+    for step in manual_steps.iter() {
+        println!("{} {}", yellow!("warning:"), step);
+    }
+    if cmd.dry_run && !migrations.is_empty() {
+        println!(
+            "{} {} to migrate, run without --dry-run to write them",
+            blue!("hint:"),
+            pluralize!(migrations.len(), "file")
+        );
+    }
+}
+
+/// Runs each scenario against two versions of the simnet deployment, the contracts being
+/// published with the epoch and the Clarity version of each target
+fn compare_scenarios_epochs(
+    manifest: &ProjectManifest,
+    named_scenarios: &[(String, scenarios::Scenario)],
+    targets: &[String],
+    json: bool,
+) {
+    let [left_target, right_target] = targets else {
+        eprintln!(
+            "{}",
+            format_err!("--compare-epochs expects two epochs, such as 2.5,3.0")
+        );
+        process::exit(1);
+    };
+
+    let mut targets_reports = vec![];
+    for target in [left_target, right_target] {
+        let (epoch, clarity_version) = target.split_once(':').unwrap_or((target, ""));
+        let clarity_version = Some(clarity_version).filter(|v| !v.is_empty());
+        let (epoch, clarity_version) =
+            match get_epoch_and_clarity_version(Some(epoch), clarity_version) {
+                Ok(target) => target,
+                Err(e) => {
+                    eprintln!("{}", format_err!(format!("{}: {}", target, e)));
+                    process::exit(1);
+                }
+            };
+        let mut target_manifest = manifest.clone();
+        for contract in target_manifest.contracts.values_mut() {
+            contract.epoch = epoch;
+            contract.clarity_version = clarity_version;
+        }
+        let (_, _, artifacts) =
+            load_deployment_and_artifacts_or_exit(&target_manifest, &None, false, true);
+        if !artifacts.success {
+            eprintln!(
+                "{}",
+                format_err!(format!(
+                    "the contracts can't be deployed with epoch {} and {}",
+                    epoch, clarity_version
+                ))
+            );
+            process::exit(1);
+        }
+        let reports: Vec<scenarios::ScenarioReport> = named_scenarios
+            .iter()
+            .map(|(name, scenario)| {
+                let mut session = artifacts.session.clone();
+                scenarios::run_scenario(&mut session, name, scenario)
+            })
+            .collect();
+        targets_reports.push(reports);
+    }
+
+    let mut differences = vec![];
+    for (left, right) in targets_reports[0].iter().zip(targets_reports[1].iter()) {
+        differences.push((
+            left.name.clone(),
+            scenarios::diff_scenario_reports(left, right),
+        ));
+    }
+
+    if json {
+        let scenarios: Vec<serde_json::Value> = differences
+            .iter()
+            .map(|(name, differences)| json!({ "name": name, "differences": differences }))
+            .collect();
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&json!({
+                "left": left_target,
+                "right": right_target,
+                "scenarios": scenarios,
+            }))
+            .unwrap()
+        );
+    } else {
+        for (name, scenario_differences) in differences.iter() {
+            if scenario_differences.is_empty() {
+                println!("{} {}", green!("✔"), name);
+                continue;
+            }
+            println!("{} {}", red!("x"), name);
+            for difference in scenario_differences.iter() {
+                println!(
+                    "  step {} ({:?}): {}",
+                    difference.step, difference.kind, difference.description
+                );
+                println!(
+                    "    {} {}",
+                    left_target,
+                    difference.left.replace('\n', "\n      ")
+                );
+                println!(
+                    "    {} {}",
+                    right_target,
+                    difference.right.replace('\n', "\n      ")
+                );
+            }
+        }
+        let diverging = differences.iter().filter(|(_, d)| !d.is_empty()).count();
+        println!(
+            "{} behaving differently between {} and {}",
+            pluralize!(diverging, "scenario"),
+            left_target,
+            right_target
+        );
+    }
+    if differences.iter().any(|(_, d)| !d.is_empty()) {
+        process::exit(1);
+    }
+}
+
+fn cache_list(cmd: CacheList) {
+    let manifest = load_manifest_or_exit(cmd.manifest_path);
+    let entries = match list_cache_entries(&manifest.project.cache_location) {
+        Ok(entries) => entries,
+        Err(e) => {
+            eprintln!("{}", format_err!(e));
+            process::exit(1);
+        }
+    };
+    if cmd.json {
+        println!("{}", serde_json::to_string_pretty(&entries).unwrap());
+        return;
+    }
+    if entries.is_empty() {
+        println!("No cached requirement");
+        return;
+    }
+    let mut total_size = 0;
+    for entry in entries.iter() {
+        total_size += entry.size;
+        println!(
+            "{} ({}, {})",
+            entry.contract_id,
+            format_size(entry.size),
+            pluralize!(entry.files.len(), "file")
+        );
+        if let Some(ref hash) = entry.source_hash {
+            println!("  sha256: {}", hash);
+        }
+    }
+    println!(
+        "{} in {}",
+        pluralize!(entries.len(), "requirement"),
+        format_size(total_size)
+    );
+}
+
+fn cache_verify(cmd: CacheVerify) {
+    let manifest = load_manifest_or_exit(cmd.manifest_path);
+    let cache_location = &manifest.project.cache_location;
+    let entries = match list_cache_entries(cache_location) {
+        Ok(entries) => entries,
+        Err(e) => {
+            eprintln!("{}", format_err!(e));
+            process::exit(1);
+        }
+    };
+    let mut corrupted = 0;
+    for entry in entries.iter() {
+        match entry.verify() {
+            CacheEntryStatus::Valid => println!("{} {}", green!("✔"), entry.contract_id),
+            CacheEntryStatus::Unverified => {
+                println!("{} {} (no recorded hash)", yellow!("?"), entry.contract_id)
+            }
+            CacheEntryStatus::Corrupted(reason) => {
+                corrupted += 1;
+                println!("{} {}: {}", red!("x"), entry.contract_id, reason);
+            }
+        }
+    }
+    if corrupted == 0 {
+        println!(
+            "{} {} verified",
+            green!("✔"),
+            pluralize!(entries.len(), "requirement")
+        );
+        return;
+    }
+    if !cmd.fix {
+        eprintln!(
+            "{}",
+            format_err!(format!(
+                "{} corrupted, run 'clarinet cache verify --fix' to remove them",
+                pluralize!(corrupted, "requirement")
+            ))
+        );
+        process::exit(1);
+    }
+    match clean_cache(cache_location, &CacheCleanFilter::Corrupted) {
+        Ok(removed) => println!(
+            "{} {} removed, they will be fetched again",
+            green!("Cleaned"),
+            pluralize!(removed.len(), "corrupted requirement")
+        ),
+        Err(e) => {
+            eprintln!("{}", format_err!(e));
+            process::exit(1);
+        }
+    }
+}
+
+fn cache_clean(cmd: CacheClean) {
+    let manifest = load_manifest_or_exit(cmd.manifest_path);
+    let filter = match (cmd.contract, cmd.older_than, cmd.all) {
+        (Some(contract_id), _, _) => CacheCleanFilter::Contract(contract_id),
+        (None, Some(age), _) => match parse_cache_age(&age) {
+            Ok(age) => CacheCleanFilter::OlderThan(age),
+            Err(e) => {
+                eprintln!("{}", format_err!(e));
+                process::exit(1);
+            }
+        },
+        (None, None, true) => CacheCleanFilter::All,
+        (None, None, false) => {
+            eprintln!(
+                "{}",
+                format_err!("specify the entries to remove with --contract, --older-than or --all")
+            );
+            process::exit(1);
+        }
+    };
+    match clean_cache(&manifest.project.cache_location, &filter) {
+        Ok(removed) => {
+            for entry in removed.iter() {
+                println!("{} {}", red!("Removed"), entry.contract_id);
+            }
+            println!(
+                "{} {} removed",
+                green!("Cleaned"),
+                pluralize!(removed.len(), "requirement")
+            );
+        }
+        Err(e) => {
+            eprintln!("{}", format_err!(e));
+            process::exit(1);
+        }
+    }
+}
+
+/// Number of recent transactions of the deployed contract scanned for callers
+const IMPACT_MAINNET_TRANSACTIONS: usize = 200;
+
+fn get_previous_contract_source(location: &FileLocation) -> Result<String, String> {
+    let path = PathBuf::from(location.to_string());
+    let (Some(dir), Some(file_name)) = (path.parent(), path.file_name()) else {
+        return Err(format!("invalid contract path {}", location));
+    };
+    let output = process::Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .arg("show")
+        .arg(format!("HEAD:./{}", file_name.to_string_lossy()))
+        .output()
+        .map_err(|e| format!("unable to run git: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "unable to retrieve the last committed version of {}, use --previous\n{}",
+            location,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+fn generate_tests(cmd: GenerateTests) {
+    let manifest = load_manifest_or_exit(cmd.manifest_path);
+    if !manifest.contracts.contains_key(cmd.name.as_str()) {
+        eprintln!(
+            "{}",
+            format_err!(format!("contract {} not found in Clarinet.toml", cmd.name))
+        );
+        process::exit(1);
+    }
+    let harness = TestHarness::from_name(&cmd.harness).unwrap_or(TestHarness::TypeScript);
+    let (_, _, mut artifacts) =
+        load_deployment_and_artifacts_or_exit(&manifest, &None, false, false);
+    let Some(contract_id) = artifacts
+        .analysis
+        .keys()
+        .find(|contract_id| contract_id.name.as_str() == cmd.name)
+        .cloned()
+    else {
+        eprintln!(
+            "{}",
+            format_err!(format!("unable to analyze contract {}", cmd.name))
+        );
+        process::exit(1);
+    };
+    let analysis = artifacts.analysis.remove(&contract_id).unwrap();
+
+    let changes =
+        match generate::get_changes_for_contract_tests(&manifest.location, analysis, harness) {
+            Ok(changes) => changes,
+            Err(message) => {
+                eprintln!("{}", format_err!(message));
+                process::exit(1);
+            }
+        };
+    if !execute_changes(changes) {
+        process::exit(1);
+    }
+}
+
+fn contract_impact(cmd: ContractImpact) {
+    let manifest = load_manifest_or_exit(cmd.manifest_path);
+    let (deployment, artifacts) =
+        match generate_default_deployment(&manifest, &StacksNetwork::Simnet, false) {
+            Ok(deployment) => deployment,
+            Err(message) => {
+                eprintln!("{}", format_err!(message));
+                process::exit(1);
+            }
+        };
+    let Some((contract_id, (_, location))) = deployment
+        .contracts
+        .iter()
+        .find(|(contract_id, _)| contract_id.name.as_str() == cmd.name)
+    else {
+        eprintln!(
+            "{}",
+            format_err!(format!("contract {} not found in Clarinet.toml", cmd.name))
+        );
+        process::exit(1);
+    };
+
+    let previous_source = match cmd.previous_path {
+        Some(path) => {
+            fs::read_to_string(&path).map_err(|e| format!("unable to read {}: {}", path, e))
+        }
+        None => get_previous_contract_source(location),
+    };
+    let mut impact = match previous_source
+        .and_then(|source| upgrade_impact::analyze_upgrade_impact(contract_id, &source, &artifacts))
+    {
+        Ok(impact) => impact,
+        Err(e) => {
+            eprintln!("{}", format_err!(e));
+            process::exit(1);
+        }
+    };
+
+    if let Some(ref mainnet_contract_id) = cmd.mainnet_contract_id {
+        match hiro_system_kit::nestable_block_on(upgrade_impact::fetch_mainnet_callers(
+            "https://api.hiro.so",
+            mainnet_contract_id,
+            IMPACT_MAINNET_TRANSACTIONS,
+        )) {
+            Ok(callers) => impact.mainnet_callers = callers,
+            Err(e) => {
+                eprintln!("{}", format_err!(e));
+                process::exit(1);
+            }
+        }
+    }
+
+    if cmd.json {
+        println!("{}", serde_json::to_string_pretty(&impact).unwrap());
+        return;
+    }
+
+    if impact.changes.is_empty() {
+        println!(
+            "{} no public or read-only function of {} was removed or modified",
+            green!("✔"),
+            cmd.name
+        );
+    }
+    for change in impact.changes.iter() {
+        match change {
+            upgrade_impact::FunctionChange::Removed { function } => {
+                println!("{} {} was removed", red!("removed:"), function)
+            }
+            upgrade_impact::FunctionChange::SignatureChanged {
+                function,
+                previous,
+                current,
+            } => {
+                println!("{} {}", yellow!("modified:"), function);
+                println!("  - {}", previous);
+                println!("  + {}", current);
+            }
+        }
+    }
+    for function in impact.added_functions.iter() {
+        println!("{} {}", green!("added:"), function);
+    }
+
+    println!(
+        "\n{} depending on {}",
+        pluralize!(impact.dependents.len(), "project contract"),
+        cmd.name
+    );
+    for dependent in impact.affected_dependents.iter() {
+        println!("{} {}", red!("affected:"), dependent.contract_id);
+        for call in dependent.calls.iter() {
+            println!(
+                "  line {}: (contract-call? .{} {} ...)",
+                call.line, cmd.name, call.function
+            );
+        }
+    }
+
+    if cmd.mainnet_contract_id.is_some() {
+        let changed: Vec<&str> = impact.changes.iter().map(|c| c.function()).collect();
+        println!(
+            "\n{} in the last {} transactions",
+            pluralize!(impact.mainnet_callers.len(), "mainnet caller"),
+            IMPACT_MAINNET_TRANSACTIONS
+        );
+        for caller in impact.mainnet_callers.iter() {
+            let affected: Vec<&str> = caller
+                .functions
+                .iter()
+                .map(|f| f.as_str())
+                .filter(|f| changed.contains(f))
+                .collect();
+            let functions = if caller.functions.is_empty() {
+                "through a contract".to_string()
+            } else {
+                caller
+                    .functions
+                    .iter()
+                    .cloned()
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            };
+            let label = if affected.is_empty() {
+                blue!("caller:")
+            } else {
+                red!("affected:")
+            };
+            println!(
+                "{} {} ({}, {})",
+                label,
+                caller.caller,
+                pluralize!(caller.transactions, "transaction"),
+                functions
+            );
+        }
+    }
+}
+
+fn import_deployment(cmd: ImportDeployment) {
+    let manifest = load_manifest_or_exit(cmd.manifest_path);
+    let api_url = cmd.api_url.unwrap_or_else(|| match cmd.network.as_str() {
+        "testnet" => "https://api.testnet.hiro.so".to_string(),
+        _ => "https://api.hiro.so".to_string(),
+    });
+    let contracts = match hiro_system_kit::nestable_block_on(
+        history_import::fetch_contract_deploys(&api_url, &cmd.address),
+    ) {
+        Ok(contracts) => contracts,
+        Err(e) => {
+            eprintln!("{}", format_err!(e));
+            process::exit(1);
+        }
+    };
+    if contracts.is_empty() {
+        eprintln!(
+            "{}",
+            format_err!(format!("no contract published by {} found", cmd.address))
+        );
+        process::exit(1);
+    }
+
+    let project_root = manifest
+        .location
+        .get_project_root_location()
+        .expect("unable to get project root");
+    for contract in contracts.iter() {
+        let mut location = project_root.clone();
+        let path = history_import::get_contract_path(contract);
+        location.append_path(&path).expect("invalid contract path");
+        if location.exists() {
+            match location.read_content_as_utf8() {
+                Ok(existing) if existing == contract.source => continue,
+                _ => {
+                    eprintln!(
+                        "{}",
+                        format_err!(format!(
+                            "{} already exists and differs from the published contract",
+                            path
+                        ))
+                    );
+                    process::exit(1);
+                }
+            }
+        }
+        if let Err(e) = location.write_content(contract.source.as_bytes()) {
+            eprintln!("{}", format_err!(e));
+            process::exit(1);
+        }
+        println!("{} {}", green!("Created file"), path);
+    }
+
+    let plan =
+        history_import::build_imported_plan(&cmd.address, &cmd.network, &api_url, &contracts);
+    let mut plan_location = project_root;
+    let plan_path = format!("deployments/imported.{}-plan.yaml", cmd.network);
+    plan_location
+        .append_path(&plan_path)
+        .expect("invalid deployment path");
+    if let Err(e) = plan
+        .to_file_content()
+        .and_then(|content| plan_location.write_content(&content))
+    {
+        eprintln!("{}", format_err!(e));
+        process::exit(1);
+    }
+    println!(
+        "{} {} ({} from {})",
+        green!("Generated file"),
+        plan_path,
+        pluralize!(contracts.len(), "contract"),
+        cmd.address
+    );
+}
+
+fn replay_events(cmd: ReplayEvents) {
+    let manifest = load_manifest_or_exit(cmd.manifest_path);
+    let replay = match fs::read_to_string(&cmd.events_file)
+        .map_err(|e| format!("unable to read {}: {}", cmd.events_file, e))
+        .and_then(|content| event_replay::parse_event_replay(&content, cmd.height))
+    {
+        Ok(replay) => replay,
+        Err(e) => {
+            eprintln!("{}", format_err!(e));
+            process::exit(1);
+        }
+    };
+
+    let project_root = manifest
+        .location
+        .get_project_root_location()
+        .expect("unable to get project root");
+    for tx in replay
+        .blocks
+        .iter()
+        .flat_map(|block| block.transactions.iter())
+    {
+        let event_replay::ReplayedTransactionKind::ContractPublish {
+            ref contract_name,
+            ref source,
+            ..
+        } = tx.kind
+        else {
+            continue;
+        };
+        let mut location = project_root.clone();
+        let path = event_replay::get_contract_path(&tx.sender, contract_name);
+        location.append_path(&path).expect("invalid contract path");
+        if let Err(e) = location.write_content(source.as_bytes()) {
+            eprintln!("{}", format_err!(e));
+            process::exit(1);
+        }
+        println!("{} {}", green!("Created file"), path);
+    }
+
+    let plan = event_replay::build_replay_plan(&replay, &manifest.project.boot_contracts);
+    let mut plan_location = project_root;
+    let plan_path = "deployments/replay.simnet-plan.yaml";
+    plan_location
+        .append_path(plan_path)
+        .expect("invalid deployment path");
+    if let Err(e) = plan
+        .to_file_content()
+        .and_then(|content| plan_location.write_content(&content))
+    {
+        eprintln!("{}", format_err!(e));
+        process::exit(1);
+    }
+    let transactions_count: usize = replay.blocks.iter().map(|b| b.transactions.len()).sum();
+    println!(
+        "{} {} ({} up to block {}, {} skipped)",
+        green!("Generated file"),
+        plan_path,
+        pluralize!(transactions_count, "transaction"),
+        replay.last_height,
+        replay.skipped_transactions,
+    );
+    println!(
+        "Load it with `clarinet console --deployment-plan-path {} --use-on-disk-deployment-plan`",
+        plan_path
+    );
+}
+
+fn diff_requirement(cmd: DiffRequirement) {
+    use similar::{ChangeTag, TextDiff};
+
+    let manifest = load_manifest_or_exit(cmd.manifest_path);
+    let Some(contract) = manifest.contracts.get(&cmd.contract_name) else {
+        eprintln!(
+            "{}",
+            format_err!(format!(
+                "contract {} not found in Clarinet.toml",
+                cmd.contract_name
+            ))
+        );
+        process::exit(1);
+    };
+
+    let requirements = manifest.project.requirements.clone().unwrap_or_default();
+    let requirement = match cmd.requirement {
+        Some(requirement) => requirement,
+        None => {
+            let suffix = format!(".{}", cmd.contract_name);
+            match requirements
+                .iter()
+                .find(|requirement| requirement.contract_id.ends_with(&suffix))
+            {
+                Some(requirement) => requirement.contract_id.clone(),
+                None => {
+                    eprintln!(
+                        "{}",
+                        format_err!(format!(
+                            "no requirement named {} found, use --requirement to specify the forked contract",
+                            cmd.contract_name
+                        ))
+                    );
+                    process::exit(1);
+                }
+            }
+        }
+    };
+    let contract_id = match QualifiedContractIdentifier::parse(&requirement) {
+        Ok(contract_id) => contract_id,
+        Err(e) => {
+            eprintln!(
+                "{}",
+                format_err!(format!("invalid contract id {}: {}", requirement, e))
+            );
+            process::exit(1);
+        }
+    };
+
+    let local_source = match &contract.code_source {
+        ClarityCodeSource::ContractOnDisk(path) => {
+            clarinet_deployments::includes::read_source_with_includes(&FileLocation::from_path(
+                path.clone(),
+            ))
+        }
+        ClarityCodeSource::ContractInMemory(source) => Ok(source.clone()),
+        ClarityCodeSource::Empty => Ok(String::new()),
+    };
+    let remote_source =
+        hiro_system_kit::nestable_block_on(clarinet_deployments::requirements::retrieve_contract(
+            &contract_id,
+            &manifest.project.cache_location,
+            &None,
+        ))
+        .map(|(source, _, _, _)| source);
+    let (local_source, remote_source) = match (local_source, remote_source) {
+        (Ok(local_source), Ok(remote_source)) => (local_source, remote_source),
+        (Err(e), _) | (_, Err(e)) => {
+            eprintln!("{}", format_err!(e));
+            process::exit(1);
+        }
+    };
+
+    let (local_source, remote_source) = if cmd.raw {
+        (local_source, remote_source)
+    } else {
+        (
+            clarinet_deployments::requirements::normalize_contract_source(&local_source),
+            clarinet_deployments::requirements::normalize_contract_source(&remote_source),
+        )
+    };
+
+    let diff = TextDiff::from_lines(&remote_source, &local_source);
+    let (mut added, mut removed) = (0, 0);
+    for (index, group) in diff.grouped_ops(3).iter().enumerate() {
+        if index > 0 {
+            println!("{}", blue!("..."));
+        }
+        for op in group {
+            for change in diff.iter_changes(op) {
+                let line = change.to_string_lossy();
+                let line = line.trim_end_matches('\n');
+                match change.tag() {
+                    ChangeTag::Delete => {
+                        removed += 1;
+                        println!("{}", red!(format!("- {}", line)));
+                    }
+                    ChangeTag::Insert => {
+                        added += 1;
+                        println!("{}", green!(format!("+ {}", line)));
+                    }
+                    ChangeTag::Equal => println!("  {}", line),
+                }
+            }
+        }
+    }
+
+    if added == 0 && removed == 0 {
+        println!(
+            "{} {} matches the on-chain source of {}",
+            green!("✔"),
+            cmd.contract_name,
+            requirement
+        );
+    } else {
+        println!(
+            "\n{} {} differs from {}: {} lines added, {} lines removed",
+            yellow!("!"),
+            cmd.contract_name,
+            requirement,
+            added,
+            removed
+        );
+    }
+}
+
+fn explain_requirement(cmd: ExplainRequirement) {
+    let manifest = load_manifest_or_exit(cmd.manifest_path);
+    let contract_id = match QualifiedContractIdentifier::parse(&cmd.contract_id) {
+        Ok(contract_id) => contract_id,
+        Err(e) => {
+            eprintln!(
+                "{}",
+                format_err!(format!("invalid contract id {}: {}", cmd.contract_id, e))
+            );
+            process::exit(1);
+        }
+    };
+    let (deployment, _, artifacts) =
+        load_deployment_and_artifacts_or_exit(&manifest, &None, false, false);
+    let explanation = match requirement_explain::explain_requirement(
+        &manifest,
+        &deployment,
+        &artifacts,
+        &contract_id,
+    ) {
+        Ok(explanation) => explanation,
+        Err(e) => {
+            eprintln!("{}", format_err!(e));
+            process::exit(1);
+        }
+    };
+    if cmd.json {
+        println!("{}", serde_json::to_string_pretty(&explanation).unwrap());
+        return;
+    }
+
+    println!("{}", green!(&explanation.contract_id));
+    if explanation.declared {
+        println!("  listed in the requirements of Clarinet.toml");
+    }
+    if explanation.chains.is_empty() {
+        println!("  no contract of the project depends on it");
+    }
+    for chain in explanation.chains.iter() {
+        for (depth, link) in chain.iter().enumerate() {
+            let call_sites: Vec<String> = link
+                .call_sites
+                .iter()
+                .map(|site| format!("{}:{}", site.line, site.column))
+                .collect();
+            let indent = "  ".repeat(depth + 1);
+            let arrow = if depth == 0 { "" } else { "└─ " };
+            if call_sites.is_empty() {
+                println!("{}{}{}", indent, arrow, link.contract_id);
+            } else {
+                println!(
+                    "{}{}{} (at {})",
+                    indent,
+                    arrow,
+                    link.contract_id,
+                    call_sites.join(", ")
+                );
+            }
+        }
+    }
+    match explanation.publish {
+        Some(publish) => println!(
+            "  published in batch {}{}",
+            publish.batch_id,
+            publish
+                .epoch
+                .map(|epoch| format!(", epoch {}", epoch))
+                .unwrap_or_default()
+        ),
+        None => println!("  not published by the plan"),
+    }
+}
+
+fn report_requirements(cmd: ReportRequirements) {
+    let manifest = load_manifest_or_exit(cmd.manifest_path);
+    let contract_ids: Vec<String> = manifest
+        .project
+        .requirements
+        .iter()
+        .flatten()
+        .map(|requirement| requirement.contract_id.clone())
+        .collect();
+
+    // the requirements not cached yet are fetched, with their provenance
+    for contract_id in contract_ids.iter() {
+        let Ok(contract_id) = QualifiedContractIdentifier::parse(contract_id) else {
+            continue;
+        };
+        if let Err(e) = hiro_system_kit::nestable_block_on(
+            clarinet_deployments::requirements::retrieve_contract(
+                &contract_id,
+                &manifest.project.cache_location,
+                &None,
+            ),
+        ) {
+            eprintln!("{}", format_warn!(e));
+        }
+    }
+
+    let reports = match clarinet_deployments::provenance::get_requirements_report(
+        &manifest.project.cache_location,
+        &contract_ids,
+    ) {
+        Ok(reports) => reports,
+        Err(e) => {
+            eprintln!("{}", format_err!(e));
+            process::exit(1);
+        }
+    };
+    if cmd.json {
+        println!("{}", serde_json::to_string_pretty(&reports).unwrap());
+        return;
+    }
+    if reports.is_empty() {
+        println!("No requirement in Clarinet.toml");
+        return;
+    }
+    for report in reports.iter() {
+        println!("{}", green!(&report.contract_id));
+        if let Some(ref provenance) = report.provenance {
+            println!("  deployer: {}", provenance.deployer);
+            println!("  published at height: {}", provenance.publish_height);
+            if let Some(ref txid) = provenance.txid {
+                println!("  txid: {}", txid);
+            }
+            println!("  fetched from: {}", provenance.fetched_from);
+        }
+        if let Some(ref hash) = report.source_hash {
+            println!("  sha256: {}", hash);
+        }
+        println!(
+            "  license: {}",
+            report.license.as_deref().unwrap_or("not declared")
+        );
+        for warning in report.warnings.iter() {
+            println!("  {} {}", yellow!("!"), warning);
+        }
+    }
+}
+
+fn repro_load(cmd: ReproLoad) {
+    let bundle = fs::read_to_string(&cmd.bundle_path)
+        .map_err(|e| format!("unable to read {}: {}", cmd.bundle_path, e))
+        .and_then(|content| {
+            serde_json::from_str::<ReproBundle>(&content)
+                .map_err(|e| format!("invalid bundle {}: {}", cmd.bundle_path, e))
+        });
+    let bundle = match bundle {
+        Ok(bundle) => bundle,
+        Err(e) => {
+            eprintln!("{}", format_err!(e));
+            process::exit(1);
+        }
+    };
+
+    let mut session = repl::Session::new(repl::SessionSettings::default());
+    session.load_boot_contracts();
+    if let Err(e) = session.load_repro_bundle(&bundle) {
+        eprintln!("{}", format_err!(e));
+        process::exit(1);
+    }
+    println!(
+        "{} loaded in epoch {} at block #{}",
+        pluralize!(bundle.contracts.len(), "contract"),
+        bundle.epoch,
+        bundle.block_height
+    );
+    if let Some(ref snippet) = bundle.snippet {
+        println!("{} {}", yellow!("failing expression:"), snippet);
+    }
+    Terminal::load(session, None).start();
+}
+
+fn outdated_requirements(cmd: OutdatedRequirements) {
+    let manifest = load_manifest_or_exit(cmd.manifest_path);
+    let mut updates = vec![];
+    for requirement in manifest.project.requirements.iter().flatten() {
+        let Ok(contract_id) = QualifiedContractIdentifier::parse(&requirement.contract_id) else {
+            eprintln!(
+                "{}",
+                format_warn!(format!(
+                    "{} is not a valid contract id",
+                    requirement.contract_id
+                ))
+            );
+            continue;
+        };
+        let update = hiro_system_kit::nestable_block_on(async {
+            let (source, _, clarity_version, _) =
+                clarinet_deployments::requirements::retrieve_contract(
+                    &contract_id,
+                    &manifest.project.cache_location,
+                    &None,
+                )
+                .await?;
+            clarinet_deployments::requirement_updates::find_requirement_update(
+                &contract_id,
+                &source,
+                clarity_version,
+            )
+            .await
+        });
+        match update {
+            Ok(update) => updates.push(update),
+            Err(e) => {
+                eprintln!("{}", format_err!(e));
+                process::exit(1);
+            }
+        }
+    }
+
+    if cmd.json {
+        println!("{}", serde_json::to_string_pretty(&updates).unwrap());
+        return;
+    }
+    if updates.is_empty() {
+        println!("No requirement in Clarinet.toml");
+        return;
+    }
+    for update in updates.iter() {
+        let Some(latest) = update.latest() else {
+            println!("{} {}", green!("✔"), update.contract_id);
+            continue;
+        };
+        println!("{} {} -> {}", yellow!("↑"), update.contract_id, latest);
+        if update.candidates.len() > 1 {
+            println!("  successors: {}", update.candidates.join(", "));
+        }
+        let Some(ref diff) = update.diff else {
+            continue;
+        };
+        for function in diff.added_functions.iter() {
+            println!("  {} {}", green!("+"), function);
+        }
+        for change in diff.changes.iter() {
+            match change {
+                upgrade_impact::FunctionChange::Removed { function } => {
+                    println!("  {} {}", red!("-"), function)
+                }
+                upgrade_impact::FunctionChange::SignatureChanged {
+                    previous, current, ..
+                } => {
+                    println!("  {} {}", yellow!("~"), previous);
+                    println!("    {}", current);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap_complete::generate;
+
+    use super::*;
+
+    #[test]
+    fn test_completion_for_shells() {
+        for shell in [
+            Shell::Bash,
+            Shell::Elvish,
+            Shell::Fish,
+            Shell::PowerShell,
+            Shell::Zsh,
+        ] {
+            let result = std::panic::catch_unwind(move || {
+                let mut output_buffer = Vec::new();
+                let mut cmd = Opts::command();
+                generate(shell, &mut cmd, "clarinet", &mut output_buffer);
+                assert!(
+                    !output_buffer.is_empty(),
+                    "failed to generate completion for {shell}",
+                );
+            });
+            assert!(result.is_ok(), "failed to generate completion for {shell}",);
+        }
+    }
+
+    #[test]
+    fn test_dynamic_completion_rules_match_cli() {
+        use super::super::completions::DYNAMIC_COMPLETION_RULES;
+
+        let app = Opts::command();
+        for rule in DYNAMIC_COMPLETION_RULES {
+            let mut cmd = &app;
+            for name in rule.subcommands {
+                cmd = cmd
+                    .find_subcommand(name)
+                    .unwrap_or_else(|| panic!("unknown subcommand {name}"));
+            }
+            let arg = cmd.get_arguments().find(|arg| match rule.long {
+                Some(long) => arg.get_long() == Some(long) && arg.get_short() == rule.short,
+                None => arg.is_positional(),
+            });
+            assert!(
+                arg.is_some(),
+                "no matching argument for {}",
+                rule.subcommands.join(" ")
+            );
+        }
+
+        for shell in [Shell::Bash, Shell::Fish, Shell::Zsh] {
+            let script = get_dynamic_completion_script(shell).unwrap();
+            assert!(script.contains("clarinet complete-values deployment-plans"));
         }
+        assert!(get_dynamic_completion_script(Shell::PowerShell).is_none());
     }
 
     #[test]
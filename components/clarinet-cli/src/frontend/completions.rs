@@ -0,0 +1,317 @@
+use clap::ValueEnum;
+use clap_complete::Shell;
+use clarinet_files::{get_manifest_location, FileLocation, StacksNetwork};
+use std::fs;
+use toml::value::Value;
+
+/// Kinds of values that can't be known when generating the completions script,
+/// and are resolved by calling `clarinet complete-values <kind>` at completion time.
+#[derive(ValueEnum, PartialEq, Clone, Copy, Debug)]
+pub enum CompletionValueKind {
+    Contracts,
+    DeploymentPlans,
+    Networks,
+    Accounts,
+    DevnetInstances,
+}
+
+impl CompletionValueKind {
+    fn as_arg(&self) -> &'static str {
+        match self {
+            CompletionValueKind::Contracts => "contracts",
+            CompletionValueKind::DeploymentPlans => "deployment-plans",
+            CompletionValueKind::Networks => "networks",
+            CompletionValueKind::Accounts => "accounts",
+            CompletionValueKind::DevnetInstances => "devnet-instances",
+        }
+    }
+}
+
+/// An argument of the CLI for which values are completed dynamically.
+/// When `long` is `None`, the rule applies to the first positional argument
+/// of the subcommand.
+pub struct DynamicCompletionRule {
+    pub subcommands: &'static [&'static str],
+    pub long: Option<&'static str>,
+    pub short: Option<char>,
+    pub kind: CompletionValueKind,
+}
+
+pub static DYNAMIC_COMPLETION_RULES: &[DynamicCompletionRule] = &[
+    DynamicCompletionRule {
+        subcommands: &["contracts", "rm"],
+        long: None,
+        short: None,
+        kind: CompletionValueKind::Contracts,
+    },
+    DynamicCompletionRule {
+        subcommands: &["contracts", "impact"],
+        long: None,
+        short: None,
+        kind: CompletionValueKind::Contracts,
+    },
+    DynamicCompletionRule {
+        subcommands: &["generate", "tests"],
+        long: None,
+        short: None,
+        kind: CompletionValueKind::Contracts,
+    },
+    DynamicCompletionRule {
+        subcommands: &["devnet", "stop"],
+        long: None,
+        short: None,
+        kind: CompletionValueKind::DevnetInstances,
+    },
+    DynamicCompletionRule {
+        subcommands: &["devnet", "logs"],
+        long: Some("name"),
+        short: None,
+        kind: CompletionValueKind::DevnetInstances,
+    },
+    DynamicCompletionRule {
+        subcommands: &["requirements", "diff"],
+        long: None,
+        short: None,
+        kind: CompletionValueKind::Contracts,
+    },
+    DynamicCompletionRule {
+        subcommands: &["console"],
+        long: Some("deployment-plan-path"),
+        short: Some('p'),
+        kind: CompletionValueKind::DeploymentPlans,
+    },
+    DynamicCompletionRule {
+        subcommands: &["check"],
+        long: Some("deployment-plan-path"),
+        short: Some('p'),
+        kind: CompletionValueKind::DeploymentPlans,
+    },
+    DynamicCompletionRule {
+        subcommands: &["integrate"],
+        long: Some("deployment-plan-path"),
+        short: Some('p'),
+        kind: CompletionValueKind::DeploymentPlans,
+    },
+    DynamicCompletionRule {
+        subcommands: &["devnet", "start"],
+        long: Some("deployment-plan-path"),
+        short: Some('p'),
+        kind: CompletionValueKind::DeploymentPlans,
+    },
+    DynamicCompletionRule {
+        subcommands: &["deployments", "apply"],
+        long: Some("deployment-plan-path"),
+        short: Some('p'),
+        kind: CompletionValueKind::DeploymentPlans,
+    },
+];
+
+/// Returns the completion candidates for a given kind of value.
+/// Completion must never get in the way of the user: any error (missing manifest,
+/// invalid toml, etc) results in an empty list.
+pub fn get_completion_values(
+    kind: &CompletionValueKind,
+    manifest_path: Option<String>,
+) -> Vec<String> {
+    let manifest_location = get_manifest_location(manifest_path);
+    match kind {
+        CompletionValueKind::Networks => return get_networks_values(manifest_location.as_ref()),
+        CompletionValueKind::DevnetInstances => {
+            return stacks_network::registry::list_instances()
+                .map(|instances| instances.into_iter().map(|i| i.network_name).collect())
+                .unwrap_or_default()
+        }
+        _ => {}
+    }
+    let Some(manifest_location) = manifest_location else {
+        return vec![];
+    };
+
+    let values = match kind {
+        CompletionValueKind::Contracts => get_toml_table_keys(&manifest_location, "contracts"),
+        CompletionValueKind::DeploymentPlans => get_deployment_plans_values(&manifest_location),
+        CompletionValueKind::Accounts => manifest_location
+            .get_network_manifest_location(&StacksNetwork::Devnet)
+            .and_then(|location| get_toml_table_keys(&location, "accounts")),
+        CompletionValueKind::Networks | CompletionValueKind::DevnetInstances => Ok(vec![]),
+    };
+    values.unwrap_or_default()
+}
+
+fn get_networks_values(manifest_location: Option<&FileLocation>) -> Vec<String> {
+    let mut networks = vec!["simnet".to_string()];
+    for (network, name) in [
+        (StacksNetwork::Devnet, "devnet"),
+        (StacksNetwork::Testnet, "testnet"),
+        (StacksNetwork::Mainnet, "mainnet"),
+    ] {
+        // outside of a project, all networks are suggested
+        let exists = match manifest_location {
+            Some(location) => location
+                .get_network_manifest_location(&network)
+                .map(|l| l.exists())
+                .unwrap_or(false),
+            None => true,
+        };
+        if exists {
+            networks.push(name.to_string());
+        }
+    }
+    networks
+}
+
+fn get_toml_table_keys(location: &FileLocation, table: &str) -> Result<Vec<String>, String> {
+    let content = location.read_content_as_utf8()?;
+    let value: Value = toml::from_str(&content).map_err(|e| e.to_string())?;
+    Ok(match value.get(table) {
+        Some(Value::Table(entries)) => entries.keys().cloned().collect(),
+        _ => vec![],
+    })
+}
+
+fn get_deployment_plans_values(manifest_location: &FileLocation) -> Result<Vec<String>, String> {
+    let mut deployments_dir = manifest_location.get_project_root_location()?;
+    deployments_dir.append_path("deployments")?;
+    let entries = fs::read_dir(deployments_dir.to_string()).map_err(|e| e.to_string())?;
+    let mut plans = vec![];
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let is_plan = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext == "yml" || ext == "yaml")
+            .unwrap_or(false);
+        if let (true, Some(file_name)) = (is_plan, path.file_name().and_then(|f| f.to_str())) {
+            plans.push(format!("deployments/{}", file_name));
+        }
+    }
+    plans.sort();
+    Ok(plans)
+}
+
+/// Returns a script fragment, to be appended to the clap generated script, registering
+/// the dynamic completions. Only bash, zsh and fish are supported.
+pub fn get_dynamic_completion_script(shell: Shell) -> Option<String> {
+    match shell {
+        Shell::Bash => Some(bash_script()),
+        Shell::Zsh => Some(zsh_script()),
+        Shell::Fish => Some(fish_script()),
+        _ => None,
+    }
+}
+
+fn complete_values_command(kind: &CompletionValueKind) -> String {
+    format!("clarinet complete-values {} 2>/dev/null", kind.as_arg())
+}
+
+fn bash_script() -> String {
+    let mut script = vec![
+        "".to_string(),
+        "__clarinet_subcommand_is() {".to_string(),
+        "    local i=1 word".to_string(),
+        "    for word in \"$@\"; do".to_string(),
+        "        [[ \"${COMP_WORDS[i]}\" == \"${word}\" ]] || return 1".to_string(),
+        "        i=$((i+1))".to_string(),
+        "    done".to_string(),
+        "}".to_string(),
+        "".to_string(),
+        "_clarinet_dynamic() {".to_string(),
+        "    local cur prev".to_string(),
+        "    cur=\"${COMP_WORDS[COMP_CWORD]}\"".to_string(),
+        "    prev=\"${COMP_WORDS[COMP_CWORD-1]}\"".to_string(),
+    ];
+    for rule in DYNAMIC_COMPLETION_RULES {
+        let condition = match (rule.long, rule.short) {
+            (Some(long), Some(short)) => {
+                format!("[[ \"${{prev}}\" == \"--{long}\" || \"${{prev}}\" == \"-{short}\" ]]")
+            }
+            (Some(long), None) => format!("[[ \"${{prev}}\" == \"--{long}\" ]]"),
+            _ => format!(
+                "[[ ${{COMP_CWORD}} -eq {} && \"${{cur}}\" != -* ]]",
+                rule.subcommands.len() + 1
+            ),
+        };
+        script.push(format!(
+            "    if __clarinet_subcommand_is {} && {}; then",
+            rule.subcommands.join(" "),
+            condition
+        ));
+        script.push(format!(
+            "        COMPREPLY=( $(compgen -W \"$({})\" -- \"${{cur}}\") )",
+            complete_values_command(&rule.kind)
+        ));
+        script.push("        return 0".to_string());
+        script.push("    fi".to_string());
+    }
+    script.push("    _clarinet \"$@\"".to_string());
+    script.push("}".to_string());
+    script.push("".to_string());
+    script.push("complete -F _clarinet_dynamic -o bashdefault -o default clarinet".to_string());
+    script.push("".to_string());
+    script.join("\n")
+}
+
+fn zsh_script() -> String {
+    let mut script = vec![
+        "".to_string(),
+        "_clarinet_dynamic() {".to_string(),
+        "    local -a values".to_string(),
+    ];
+    for rule in DYNAMIC_COMPLETION_RULES {
+        let mut conditions = rule
+            .subcommands
+            .iter()
+            .enumerate()
+            .map(|(i, subcommand)| format!("\"${{words[{}]}}\" == \"{}\"", i + 2, subcommand))
+            .collect::<Vec<_>>();
+        match (rule.long, rule.short) {
+            (Some(long), Some(short)) => conditions.push(format!(
+                "( \"${{words[CURRENT-1]}}\" == \"--{long}\" || \"${{words[CURRENT-1]}}\" == \"-{short}\" )"
+            )),
+            (Some(long), None) => {
+                conditions.push(format!("\"${{words[CURRENT-1]}}\" == \"--{long}\""))
+            }
+            _ => conditions.push(format!("${{CURRENT}} -eq {}", rule.subcommands.len() + 2)),
+        }
+        script.push(format!("    if [[ {} ]]; then", conditions.join(" && ")));
+        script.push(format!(
+            "        values=(${{(f)\"$({})\"}})",
+            complete_values_command(&rule.kind)
+        ));
+        script.push("        compadd -a values".to_string());
+        script.push("        return".to_string());
+        script.push("    fi".to_string());
+    }
+    script.push("    _clarinet \"$@\"".to_string());
+    script.push("}".to_string());
+    script.push("".to_string());
+    script.push("compdef _clarinet_dynamic clarinet".to_string());
+    script.push("".to_string());
+    script.join("\n")
+}
+
+fn fish_script() -> String {
+    let mut script = vec!["".to_string()];
+    for rule in DYNAMIC_COMPLETION_RULES {
+        let condition = rule
+            .subcommands
+            .iter()
+            .map(|subcommand| format!("__fish_seen_subcommand_from {}", subcommand))
+            .collect::<Vec<_>>()
+            .join("; and ");
+        let mut line = format!("complete -c clarinet -n \"{}\"", condition);
+        if let Some(short) = rule.short {
+            line.push_str(&format!(" -s {}", short));
+        }
+        if let Some(long) = rule.long {
+            line.push_str(&format!(" -l {} -r", long));
+        }
+        line.push_str(&format!(
+            " -f -a \"({})\"",
+            complete_values_command(&rule.kind)
+        ));
+        script.push(line);
+    }
+    script.push("".to_string());
+    script.join("\n")
+}
@@ -1,4 +1,6 @@
 mod clarinetrc;
+mod completions;
+mod output;
 
 pub mod cli;
 pub mod dap;
@@ -0,0 +1,118 @@
+//! Exit codes and output modes of the commands. The exit codes are part of the interface of
+//! the CLI, scripts and CI branch on them: a code is never reassigned.
+//!
+//! | code | meaning                                                         |
+//! |------|-----------------------------------------------------------------|
+//! | 0    | success                                                         |
+//! | 1    | diagnostics errors, failed checks, aborted deployments          |
+//! | 2    | invalid configuration: manifests, plans, options                |
+//! | 3    | network failures: nodes, Docker, APIs out of reach              |
+//! | 4    | internal errors                                                 |
+//! | 130  | interrupted (Ctrl-C)                                            |
+
+use std::fmt::Display;
+use std::io::Write;
+use std::process;
+use std::sync::OnceLock;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitCode {
+    Success,
+    Diagnostics,
+    Config,
+    Network,
+    Internal,
+}
+
+impl ExitCode {
+    pub fn code(&self) -> i32 {
+        match self {
+            ExitCode::Success => 0,
+            ExitCode::Diagnostics => 1,
+            ExitCode::Config => 2,
+            ExitCode::Network => 3,
+            ExitCode::Internal => 4,
+        }
+    }
+
+    pub fn exit(self) -> ! {
+        let _ = std::io::stdout().lock().flush();
+        process::exit(self.code())
+    }
+}
+
+/// Prints the error, then exits with the code of its category
+pub fn exit_with_error(code: ExitCode, message: impl Display) -> ! {
+    eprintln!("{}", format_err!(message));
+    code.exit()
+}
+
+/// Panics of the main thread are internal errors. The panics of other threads are reported
+/// by the ones joining them.
+pub fn exit_on_main_thread_panic() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        default_hook(info);
+        if std::thread::current().name() == Some("main") {
+            ExitCode::Internal.exit();
+        }
+    }));
+}
+
+/// What the commands print on stdout, the errors are always printed on stderr
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputMode {
+    #[default]
+    Default,
+    /// Only the final result of the command: the counts of diagnostics, the generated files
+    SummaryOnly,
+    /// Nothing, the exit code tells the result
+    Quiet,
+}
+
+static OUTPUT_MODE: OnceLock<OutputMode> = OnceLock::new();
+
+pub fn set_output_mode(mode: OutputMode) {
+    let _ = OUTPUT_MODE.set(mode);
+}
+
+fn get_output_mode() -> OutputMode {
+    OUTPUT_MODE.get().copied().unwrap_or_default()
+}
+
+/// Whether to print the details of a command: diagnostics, notes, progress
+pub fn show_details() -> bool {
+    get_output_mode() == OutputMode::Default
+}
+
+/// Whether to print the final result of a command
+pub fn show_summary() -> bool {
+    get_output_mode() != OutputMode::Quiet
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exit_codes() {
+        let codes: Vec<i32> = [
+            ExitCode::Success,
+            ExitCode::Diagnostics,
+            ExitCode::Config,
+            ExitCode::Network,
+            ExitCode::Internal,
+        ]
+        .iter()
+        .map(ExitCode::code)
+        .collect();
+        assert_eq!(codes, vec![0, 1, 2, 3, 4]);
+
+        // the mode is set once, by the options of the command
+        assert!(show_details());
+        set_output_mode(OutputMode::SummaryOnly);
+        set_output_mode(OutputMode::Quiet);
+        assert!(!show_details());
+        assert!(show_summary());
+    }
+}
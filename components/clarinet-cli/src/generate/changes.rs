@@ -1,4 +1,4 @@
-use clarinet_files::{FileLocation, RequirementConfig};
+use clarinet_files::{FileLocation, RequirementConfig, StacksNetwork};
 use clarity_repl::repl::ClarityContract;
 use std::collections::HashMap;
 
@@ -27,6 +27,7 @@ pub struct TOMLEdition {
     pub manifest_location: FileLocation,
     pub contracts_to_add: HashMap<String, ClarityContract>,
     pub contracts_to_rm: Vec<String>,
+    pub contracts_networks: HashMap<String, Vec<StacksNetwork>>,
     pub requirements_to_add: Vec<RequirementConfig>,
 }
 
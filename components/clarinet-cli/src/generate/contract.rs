@@ -67,6 +67,7 @@ impl GetChangesForRmContract {
             ),
             manifest_location,
             contracts_to_rm,
+            contracts_networks: HashMap::new(),
             contracts_to_add: HashMap::new(),
             requirements_to_add: vec![],
         };
@@ -225,6 +226,7 @@ describe("example tests", () => {
             ),
             manifest_location,
             contracts_to_rm: vec![],
+            contracts_networks: HashMap::new(),
             contracts_to_add,
             requirements_to_add: vec![],
         };
@@ -0,0 +1,228 @@
+use super::changes::{Changes, TOMLEdition};
+use super::project::GetChangesForNewProject;
+use clarinet_files::{FileLocation, RequirementConfig};
+use clarity_repl::clarity::vm::types::QualifiedContractIdentifier;
+use clarity_repl::repl::ClarityContract;
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const IGNORED_DIRECTORIES: [&str; 4] = ["node_modules", "deployments", "settings", "tests"];
+
+#[derive(Clone, Debug)]
+pub struct DiscoveredContract {
+    pub name: String,
+    /// Path of the contract, relative to the project root
+    pub path: PathBuf,
+    /// Contracts called or referenced with a fully qualified identifier
+    pub external_references: BTreeSet<String>,
+    /// Contracts referenced with the `.contract-name` shorthand
+    pub local_references: BTreeSet<String>,
+}
+
+/// Recursively look for `.clar` files in a directory, ignoring hidden directories
+/// and the directories generated by Clarinet.
+pub fn discover_contracts(project_root: &Path) -> Result<Vec<DiscoveredContract>, String> {
+    let mut paths = vec![];
+    collect_clarity_files(project_root, &mut paths)?;
+    paths.sort();
+
+    let mut contracts: Vec<DiscoveredContract> = vec![];
+    for path in paths {
+        let source = fs::read_to_string(&path)
+            .map_err(|e| format!("unable to read {}: {}", path.display(), e))?;
+        let relative_path = path
+            .strip_prefix(project_root)
+            .map_err(|e| e.to_string())?
+            .to_path_buf();
+
+        let stem = relative_path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .ok_or(format!("invalid file name {}", relative_path.display()))?;
+        let mut name = sanitize_contract_name(stem);
+        if contracts.iter().any(|c| c.name == name) {
+            // two files with the same name in different directories
+            let parent = relative_path
+                .parent()
+                .and_then(|p| p.file_name())
+                .and_then(|p| p.to_str())
+                .unwrap_or("contract");
+            name = format!("{}-{}", sanitize_contract_name(parent), name);
+        }
+
+        let (external_references, local_references) = detect_references(&source);
+        contracts.push(DiscoveredContract {
+            name,
+            path: relative_path,
+            external_references,
+            local_references,
+        });
+    }
+    Ok(contracts)
+}
+
+fn collect_clarity_files(dir: &Path, paths: &mut Vec<PathBuf>) -> Result<(), String> {
+    let entries =
+        fs::read_dir(dir).map_err(|e| format!("unable to read {}: {}", dir.display(), e))?;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        if path.is_dir() {
+            if file_name.starts_with('.') || IGNORED_DIRECTORIES.contains(&file_name.as_str()) {
+                continue;
+            }
+            collect_clarity_files(&path, paths)?;
+        } else if path.extension().and_then(|e| e.to_str()) == Some("clar") {
+            paths.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn sanitize_contract_name(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    match name.chars().next() {
+        Some(c) if c.is_ascii_alphabetic() => name,
+        _ => format!("contract-{}", name),
+    }
+}
+
+/// Lightweight detection of the contracts referenced by a snippet. Tokens are
+/// looked up without parsing the contract, so that invalid sources can still be
+/// imported.
+fn detect_references(source: &str) -> (BTreeSet<String>, BTreeSet<String>) {
+    let mut external_references = BTreeSet::new();
+    let mut local_references = BTreeSet::new();
+
+    for line in source.lines() {
+        let code = match line.find(";;") {
+            Some(index) => &line[..index],
+            None => line,
+        };
+        for token in code.split(|c: char| c.is_whitespace() || c == '(' || c == ')') {
+            if let Some(principal) = token.strip_prefix('\'') {
+                // 'SP000.contract or 'SP000.contract.trait-name
+                let mut parts = principal.split('.');
+                if let (Some(address), Some(contract_name)) = (parts.next(), parts.next()) {
+                    let contract_id = format!("{}.{}", address, contract_name);
+                    if QualifiedContractIdentifier::parse(&contract_id).is_ok() {
+                        external_references.insert(contract_id);
+                    }
+                }
+            } else if let Some(contract_name) = token.strip_prefix('.') {
+                if let Some(contract_name) = contract_name.split('.').next() {
+                    if !contract_name.is_empty() {
+                        local_references.insert(contract_name.to_string());
+                    }
+                }
+            }
+        }
+    }
+    (external_references, local_references)
+}
+
+pub struct GetChangesForInitProject {
+    project_path: String,
+    project_name: String,
+    contracts: Vec<ClarityContract>,
+    requirements: Vec<RequirementConfig>,
+    telemetry_enabled: bool,
+}
+
+impl GetChangesForInitProject {
+    pub fn new(
+        project_path: String,
+        project_name: String,
+        contracts: Vec<ClarityContract>,
+        requirements: Vec<RequirementConfig>,
+        telemetry_enabled: bool,
+    ) -> Self {
+        Self {
+            project_path,
+            project_name,
+            contracts,
+            requirements,
+            telemetry_enabled,
+        }
+    }
+
+    pub fn run(&mut self) -> Result<Vec<Changes>, String> {
+        let mut changes = GetChangesForNewProject::new(
+            self.project_path.clone(),
+            self.project_name.clone(),
+            true,
+            self.telemetry_enabled,
+            None,
+        )
+        .run()?;
+
+        let manifest_location =
+            FileLocation::from_path_string(&format!("{}/Clarinet.toml", self.project_path))?;
+        let mut contracts_to_add = HashMap::new();
+        for contract in self.contracts.drain(..) {
+            contracts_to_add.insert(contract.name.clone(), contract);
+        }
+
+        changes.push(Changes::EditTOML(TOMLEdition {
+            comment: format!(
+                "{} with {} and {}",
+                yellow!("Updated Clarinet.toml"),
+                pluralize!(contracts_to_add.len(), "contract"),
+                pluralize!(self.requirements.len(), "requirement")
+            ),
+            manifest_location,
+            contracts_to_add,
+            contracts_to_rm: vec![],
+            contracts_networks: HashMap::new(),
+            requirements_to_add: self.requirements.clone(),
+        }));
+        Ok(changes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_references() {
+        let source = [
+            ";; .commented-out",
+            "(use-trait nft-trait 'SP2PABAF9FTAJYNFZH93XENAJ8FVY99RRM50D2JG9.nft-trait.nft-trait)",
+            "(define-public (test)",
+            "  (begin",
+            "    (try! (contract-call? .token transfer u1 tx-sender tx-sender none))",
+            "    (contract-call? 'SP3K8BC0PPEVCV7NZ6QSRWPQ2JE9E5B6N3PA0KBR9.amm-swap-pool get-pool)))",
+        ]
+        .join("\n");
+        let (external_references, local_references) = detect_references(&source);
+        assert_eq!(
+            external_references.into_iter().collect::<Vec<_>>(),
+            vec![
+                "SP2PABAF9FTAJYNFZH93XENAJ8FVY99RRM50D2JG9.nft-trait",
+                "SP3K8BC0PPEVCV7NZ6QSRWPQ2JE9E5B6N3PA0KBR9.amm-swap-pool",
+            ]
+        );
+        assert_eq!(
+            local_references.into_iter().collect::<Vec<_>>(),
+            vec!["token"]
+        );
+    }
+
+    #[test]
+    fn test_sanitize_contract_name() {
+        assert_eq!(sanitize_contract_name("counter"), "counter");
+        assert_eq!(sanitize_contract_name("my token"), "my-token");
+        assert_eq!(sanitize_contract_name("1-counter"), "contract-1-counter");
+    }
+}
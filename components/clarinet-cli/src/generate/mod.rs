@@ -1,11 +1,22 @@
 pub mod changes;
 mod contract;
+pub mod init;
 mod project;
+pub mod scaffold;
+pub mod template;
+pub mod tests;
 
 pub use changes::Changes;
-use clarinet_files::FileLocation;
+use clarinet_deployments::mocks::ContractInterfaceFile;
+use clarinet_files::{FileLocation, RequirementConfig};
+use clarity_repl::clarity::vm::analysis::ContractAnalysis;
+use clarity_repl::repl::ClarityContract;
 use contract::GetChangesForNewContract;
+use init::GetChangesForInitProject;
 use project::GetChangesForNewProject;
+use scaffold::GetChangesForScaffoldContract;
+use template::ProjectTemplate;
+use tests::{GetChangesForContractTests, TestHarness};
 
 use self::contract::GetChangesForRmContract;
 
@@ -14,12 +25,31 @@ pub fn get_changes_for_new_project(
     project_name: String,
     use_current_dir: bool,
     telemetry_enabled: bool,
+    template: Option<ProjectTemplate>,
 ) -> Result<Vec<Changes>, String> {
     let mut command = GetChangesForNewProject::new(
         project_path,
         project_name,
         use_current_dir,
         telemetry_enabled,
+        template,
+    );
+    command.run()
+}
+
+pub fn get_changes_for_init_project(
+    project_path: String,
+    project_name: String,
+    contracts: Vec<ClarityContract>,
+    requirements: Vec<RequirementConfig>,
+    telemetry_enabled: bool,
+) -> Result<Vec<Changes>, String> {
+    let mut command = GetChangesForInitProject::new(
+        project_path,
+        project_name,
+        contracts,
+        requirements,
+        telemetry_enabled,
     );
     command.run()
 }
@@ -35,6 +65,25 @@ pub fn get_changes_for_new_contract(
     command.run(include_test)
 }
 
+pub fn get_changes_for_scaffold_contract(
+    manifest_location: &FileLocation,
+    contract_name: String,
+    interface: ContractInterfaceFile,
+) -> Result<Vec<Changes>, String> {
+    let mut command =
+        GetChangesForScaffoldContract::new(manifest_location.clone(), contract_name, interface);
+    command.run()
+}
+
+pub fn get_changes_for_contract_tests(
+    manifest_location: &FileLocation,
+    analysis: ContractAnalysis,
+    harness: TestHarness,
+) -> Result<Vec<Changes>, String> {
+    let mut command = GetChangesForContractTests::new(manifest_location.clone(), analysis, harness);
+    command.run()
+}
+
 pub fn get_changes_for_rm_contract(
     manifest_location: &FileLocation,
     contract_name: String,
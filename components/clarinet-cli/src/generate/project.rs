@@ -9,6 +9,7 @@ use clarinet_files::{
 };
 
 use super::changes::{Changes, DirectoryCreation, FileCreation};
+use super::template::ProjectTemplate;
 
 pub struct GetChangesForNewProject {
     project_path: String,
@@ -16,6 +17,7 @@ pub struct GetChangesForNewProject {
     use_current_dir: bool,
     changes: Vec<Changes>,
     telemetry_enabled: bool,
+    template: Option<ProjectTemplate>,
 }
 
 impl GetChangesForNewProject {
@@ -24,6 +26,7 @@ impl GetChangesForNewProject {
         project_name: String,
         use_current_dir: bool,
         telemetry_enabled: bool,
+        template: Option<ProjectTemplate>,
     ) -> Self {
         let project_path = if use_current_dir {
             project_path.clone()
@@ -37,6 +40,7 @@ impl GetChangesForNewProject {
             use_current_dir,
             changes: vec![],
             telemetry_enabled,
+            template,
         }
     }
 
@@ -57,9 +61,28 @@ impl GetChangesForNewProject {
         self.create_gitignore();
         self.create_gitattributes();
         self.create_nodejs_files();
+        if let Some(template) = self.template {
+            self.create_template_files(template);
+        }
         Ok(self.changes.clone())
     }
 
+    fn create_template_files(&mut self, template: ProjectTemplate) {
+        for dir in template.get_directories() {
+            self.changes
+                .push(self.get_changes_for_new_root_dir(dir.into()));
+        }
+        for contract in template.get_contracts() {
+            let name = format!("contracts/{}.clar", contract.name);
+            self.changes
+                .push(self.get_changes_for_new_file(name, contract.source.into()));
+        }
+        for file in template.get_files() {
+            self.changes
+                .push(self.get_changes_for_new_file(file.path.into(), file.content.into()));
+        }
+    }
+
     fn create_root_directory(&mut self) {
         let change = DirectoryCreation {
             comment: format!("{} {}", green!("Created directory"), self.project_name),
@@ -160,6 +183,20 @@ vitest.config.js linguist-vendored
     }
 
     fn create_clarinet_toml(&mut self) {
+        let contracts = match self.template {
+            Some(template) => template
+                .get_contracts()
+                .iter()
+                .map(|contract| {
+                    format!(
+                        "[contracts.{name}]\npath = \"contracts/{name}.clar\"\nclarity_version = 3\nepoch = 3.1\n",
+                        name = contract.name
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+            None => "# [contracts.counter]\n# path = \"contracts/counter.clar\"\n".into(),
+        };
         let content = format!(
             r#"[project]
 name = "{}"
@@ -168,9 +205,7 @@ authors = []
 telemetry = {}
 cache_dir = "./.cache"
 
-# [contracts.counter]
-# path = "contracts/counter.clar"
-
+{}
 [repl.analysis]
 passes = ["check_checker"]
 check_checker = {{ trusted_sender = false, trusted_caller = false, callee_filter = false }}
@@ -183,7 +218,7 @@ check_checker = {{ trusted_sender = false, trusted_caller = false, callee_filter
 # caller.
 # More informations: https://www.hiro.so/blog/new-safety-checks-in-clarinet
 "#,
-            self.project_name, self.telemetry_enabled
+            self.project_name, self.telemetry_enabled, contracts
         );
         let name = "Clarinet.toml".into();
         self.changes
@@ -304,6 +339,11 @@ disable_stacks_api = false
 # disable_bitcoin_explorer = true
 # working_dir = "tmp/devnet"
 # stacks_node_events_observers = ["host.docker.internal:8002"]
+# record_contract_calls = true
+# record_blocks = true
+# enable_webhook_receiver = true
+# webhook_receiver_port = 20447
+# auto_ports = true
 # miner_mnemonic = "{default_stacks_miner_mnemonic}"
 # miner_derivation_path = "{default_derivation_path}"
 # faucet_mnemonic = "{default_stacks_faucet_mnemonic}"
@@ -326,6 +366,9 @@ disable_stacks_api = false
 # postgres_username = "postgres"
 # postgres_password = "postgres"
 # postgres_database = "postgres"
+# postgres_max_connections = 200
+# stacks_api_pg_connection_pool_max = 20
+# stacks_api_max_lag = 10
 # bitcoin_node_image_url = "{default_bitcoin_node_image}"
 # stacks_node_image_url = "{default_stacks_node_image}"
 # stacks_signer_image_url = "{default_stacks_signer_image}"
@@ -355,6 +398,24 @@ disable_stacks_api = false
 # epoch_3_0 = {DEFAULT_EPOCH_3_0}
 # epoch_3_1 = {DEFAULT_EPOCH_3_1}
 
+# Resources of a service: the API, the explorers and postgres are limited by default,
+# a limit of 0 removes it
+# [devnet.resources.stacks-api]
+# cpus = 1.0
+# memory = "1g"
+# platform = "linux/arm64"
+
+# Invariants checked every `check_interval` blocks by `clarinet devnet start --soak`
+# [devnet.soak]
+# duration = 360
+# check_interval = 10
+# [[devnet.soak.invariants]]
+# name = "total supply conservation"
+# contract = "token"
+# function = "check-total-supply"
+# args = []
+# expect = "true"
+
 # Send some stacking orders
 [[devnet.pox_stacking_orders]]
 start_at_cycle = 1
@@ -380,6 +441,16 @@ wallet = "wallet_3"
 slots = 2
 btc_address = "mvZtbibDAAA3WLpY7zXXFqRa3T4XSknBX7"
 
+# Stacking orders can be delegated to a pool operator, which stacks the delegated STX
+# [[devnet.pox_stacking_orders]]
+# start_at_cycle = 2
+# duration = 10
+# auto_extend = true
+# wallet = "wallet_4"
+# pool_operator = "wallet_5"
+# slots = 1
+# btc_address = "mg1C76bNTutiCDV3t9nWhZs3Dc8LzUufj8"
+
 "#,
             default_derivation_path = DEFAULT_DERIVATION_PATH,
             default_bitcoin_node_image = DEFAULT_BITCOIN_NODE_IMAGE,
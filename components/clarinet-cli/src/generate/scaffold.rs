@@ -0,0 +1,100 @@
+use super::changes::{Changes, DirectoryCreation, FileCreation, TOMLEdition};
+use clarinet_deployments::mocks::{
+    generate_mock_contract, generate_stub_contract, ContractInterfaceFile,
+};
+use clarinet_files::{FileLocation, StacksNetwork};
+use clarity_repl::repl::{
+    ClarityCodeSource, ClarityContract, ContractDeployer, DEFAULT_CLARITY_VERSION, DEFAULT_EPOCH,
+};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+pub struct GetChangesForScaffoldContract {
+    manifest_location: FileLocation,
+    contract_name: String,
+    interface: ContractInterfaceFile,
+    changes: Vec<Changes>,
+}
+
+impl GetChangesForScaffoldContract {
+    pub fn new(
+        manifest_location: FileLocation,
+        contract_name: String,
+        interface: ContractInterfaceFile,
+    ) -> Self {
+        Self {
+            manifest_location,
+            contract_name: contract_name.replace('.', "_"),
+            interface,
+            changes: vec![],
+        }
+    }
+
+    pub fn run(&mut self) -> Result<Vec<Changes>, String> {
+        let stub = generate_stub_contract(&self.contract_name, &self.interface)?;
+        let mock = generate_mock_contract(&self.contract_name, &self.interface)?;
+        let stub_path = format!("contracts/{}.clar", self.contract_name);
+        let mock_path = format!("contracts/mocks/{}.clar", self.contract_name);
+        self.create_file(&stub_path, stub)?;
+        self.create_mocks_directory()?;
+        self.create_file(&mock_path, mock)?;
+        self.index_mock_in_clarinet_toml(mock_path);
+        Ok(self.changes.clone())
+    }
+
+    fn create_mocks_directory(&mut self) -> Result<(), String> {
+        let mut mocks_dir = self.manifest_location.get_project_root_location()?;
+        mocks_dir.append_path("contracts/mocks")?;
+        if !mocks_dir.exists() {
+            self.changes.push(Changes::AddDirectory(DirectoryCreation {
+                comment: format!("{} contracts/mocks", green!("Created directory")),
+                path: mocks_dir.to_string(),
+            }));
+        }
+        Ok(())
+    }
+
+    fn create_file(&mut self, relative_path: &str, content: String) -> Result<(), String> {
+        let mut new_file = self.manifest_location.get_project_root_location()?;
+        new_file.append_path(relative_path)?;
+        if new_file.exists() {
+            return Err(format!("{} already exists", new_file));
+        }
+        let change = FileCreation {
+            comment: format!("{} {}", green!("Created file"), relative_path),
+            content,
+            path: new_file.to_string(),
+        };
+        self.changes.push(Changes::AddFile(change));
+        Ok(())
+    }
+
+    fn index_mock_in_clarinet_toml(&mut self, mock_path: String) {
+        let contract_config = ClarityContract {
+            code_source: ClarityCodeSource::ContractOnDisk(PathBuf::from(mock_path)),
+            deployer: ContractDeployer::DefaultDeployer,
+            name: self.contract_name.clone(),
+            clarity_version: DEFAULT_CLARITY_VERSION,
+            epoch: DEFAULT_EPOCH,
+        };
+        let mut contracts_to_add = HashMap::new();
+        contracts_to_add.insert(self.contract_name.clone(), contract_config);
+        // mocks must never be deployed outside of simnet
+        let mut contracts_networks = HashMap::new();
+        contracts_networks.insert(self.contract_name.clone(), vec![StacksNetwork::Simnet]);
+
+        let change = TOMLEdition {
+            comment: format!(
+                "{} with mock contract {} (simnet only)",
+                yellow!("Updated Clarinet.toml"),
+                self.contract_name
+            ),
+            manifest_location: self.manifest_location.clone(),
+            contracts_to_rm: vec![],
+            contracts_networks,
+            contracts_to_add,
+            requirements_to_add: vec![],
+        };
+        self.changes.push(Changes::EditTOML(change));
+    }
+}
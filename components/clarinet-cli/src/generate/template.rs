@@ -0,0 +1,118 @@
+/// Templates of `clarinet new --template <name>`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProjectTemplate {
+    /// Multisig governance: proposals, votes, treasury
+    Dao,
+}
+
+pub struct TemplateContract {
+    pub name: &'static str,
+    pub source: &'static str,
+}
+
+pub struct TemplateFile {
+    pub path: &'static str,
+    pub content: &'static str,
+}
+
+impl ProjectTemplate {
+    pub fn from_name(name: &str) -> Option<ProjectTemplate> {
+        match name {
+            "dao" => Some(ProjectTemplate::Dao),
+            _ => None,
+        }
+    }
+
+    /// Contracts of the template, in deployment order
+    pub fn get_contracts(&self) -> Vec<TemplateContract> {
+        match self {
+            ProjectTemplate::Dao => vec![
+                TemplateContract {
+                    name: "proposal-trait",
+                    source: include_str!("templates/dao/contracts/proposal-trait.clar"),
+                },
+                TemplateContract {
+                    name: "dao-core",
+                    source: include_str!("templates/dao/contracts/dao-core.clar"),
+                },
+                TemplateContract {
+                    name: "treasury",
+                    source: include_str!("templates/dao/contracts/treasury.clar"),
+                },
+                TemplateContract {
+                    name: "grant-proposal",
+                    source: include_str!("templates/dao/contracts/grant-proposal.clar"),
+                },
+                TemplateContract {
+                    name: "membership-proposal",
+                    source: include_str!("templates/dao/contracts/membership-proposal.clar"),
+                },
+            ],
+        }
+    }
+
+    /// Directories of the template, besides the ones of every project
+    pub fn get_directories(&self) -> Vec<&'static str> {
+        match self {
+            ProjectTemplate::Dao => vec!["deployments", "chainhooks"],
+        }
+    }
+
+    pub fn get_files(&self) -> Vec<TemplateFile> {
+        match self {
+            ProjectTemplate::Dao => vec![
+                TemplateFile {
+                    path: "README.md",
+                    content: include_str!("templates/dao/README.md"),
+                },
+                TemplateFile {
+                    path: "deployments/default.simnet-plan.yaml",
+                    content: include_str!("templates/dao/deployments/default.simnet-plan.yaml"),
+                },
+                TemplateFile {
+                    path: "chainhooks/proposal-created.chainhook.json",
+                    content: include_str!(
+                        "templates/dao/chainhooks/proposal-created.chainhook.json"
+                    ),
+                },
+                TemplateFile {
+                    path: "chainhooks/proposal-executed.chainhook.json",
+                    content: include_str!(
+                        "templates/dao/chainhooks/proposal-executed.chainhook.json"
+                    ),
+                },
+                TemplateFile {
+                    path: "tests/dao.test.ts",
+                    content: include_str!("templates/dao/tests/dao.test.ts"),
+                },
+            ],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clarity_repl::repl::{
+        ClarityCodeSource, ClarityContract, ContractDeployer, Session, SessionSettings,
+        DEFAULT_CLARITY_VERSION, DEFAULT_EPOCH,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_dao_template_contracts_deploy() {
+        let mut session = Session::new(SessionSettings::default());
+        session.update_epoch(DEFAULT_EPOCH);
+        for contract in ProjectTemplate::Dao.get_contracts() {
+            let clarity_contract = ClarityContract {
+                code_source: ClarityCodeSource::ContractInMemory(contract.source.to_string()),
+                name: contract.name.to_string(),
+                deployer: ContractDeployer::DefaultDeployer,
+                clarity_version: DEFAULT_CLARITY_VERSION,
+                epoch: DEFAULT_EPOCH,
+            };
+            let result = session.deploy_contract(&clarity_contract, false, None);
+            assert!(result.is_ok(), "unable to deploy {}", contract.name);
+        }
+    }
+}
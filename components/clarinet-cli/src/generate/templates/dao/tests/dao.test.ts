@@ -0,0 +1,138 @@
+import { Cl, ContractPrincipalCV } from "@stacks/transactions";
+import { describe, expect, it } from "vitest";
+
+const accounts = simnet.getAccounts();
+const deployer = accounts.get("deployer")!;
+const wallet1 = accounts.get("wallet_1")!;
+const wallet2 = accounts.get("wallet_2")!;
+const wallet3 = accounts.get("wallet_3")!;
+const wallet4 = accounts.get("wallet_4")!;
+
+const grantProposal = Cl.contractPrincipal(deployer, "grant-proposal");
+const membershipProposal = Cl.contractPrincipal(deployer, "membership-proposal");
+
+/*
+  The members, the threshold and the treasury funds are configured by the
+  post-deployment transactions of deployments/default.simnet-plan.yaml (batch 1).
+*/
+
+function propose(proposal: ContractPrincipalCV, title: string, sender: string) {
+  return simnet.callPublicFn("dao-core", "propose", [proposal, Cl.stringAscii(title)], sender);
+}
+
+function vote(proposalId: number, approve: boolean, sender: string) {
+  return simnet.callPublicFn(
+    "dao-core",
+    "vote",
+    [Cl.uint(proposalId), Cl.bool(approve)],
+    sender,
+  );
+}
+
+function execute(proposalId: number, proposal: ContractPrincipalCV, sender: string) {
+  return simnet.callPublicFn("dao-core", "execute", [Cl.uint(proposalId), proposal], sender);
+}
+
+describe("setup", () => {
+  it("is configured by the deployment plan", () => {
+    const threshold = simnet.callReadOnlyFn("dao-core", "get-threshold", [], deployer);
+    expect(threshold.result).toBeUint(2);
+    const membersCount = simnet.callReadOnlyFn("dao-core", "get-members-count", [], deployer);
+    expect(membersCount.result).toBeUint(3);
+    const isMember = simnet.callReadOnlyFn("dao-core", "is-member", [Cl.principal(wallet1)], deployer);
+    expect(isMember.result).toBeBool(true);
+    const balance = simnet.callReadOnlyFn("treasury", "get-balance", [], deployer);
+    expect(balance.result).toBeUint(10_000_000_000);
+  });
+
+  it("can't be changed by the deployer once finalized", () => {
+    const { result } = simnet.callPublicFn(
+      "dao-core",
+      "add-member",
+      [Cl.principal(wallet4)],
+      deployer,
+    );
+    expect(result).toBeErr(Cl.uint(1000));
+  });
+});
+
+describe("proposal lifecycle", () => {
+  it("executes a proposal once approved by the members: propose -> vote -> execute", () => {
+    const proposal = propose(grantProposal, "Grant 1,000 STX to wallet_4", wallet1);
+    expect(proposal.result).toBeOk(Cl.uint(1));
+    expect(proposal.events[0].event).toBe("print_event");
+
+    expect(vote(1, true, wallet1).result).toBeOk(Cl.bool(true));
+    // one approval out of the two required
+    expect(execute(1, grantProposal, wallet1).result).toBeErr(Cl.uint(1005));
+
+    expect(vote(1, true, wallet2).result).toBeOk(Cl.bool(true));
+    const execution = execute(1, grantProposal, wallet3);
+    expect(execution.result).toBeOk(Cl.bool(true));
+
+    const stxBalances = simnet.getAssetsMap().get("STX")!;
+    expect(stxBalances.get(wallet4)).toBe(100_001_000_000_000n);
+    const balance = simnet.callReadOnlyFn("treasury", "get-balance", [], deployer);
+    expect(balance.result).toBeUint(9_000_000_000);
+
+    // a proposal can only be executed once
+    expect(execute(1, grantProposal, wallet3).result).toBeErr(Cl.uint(1004));
+  });
+
+  it("lets the members manage the members through proposals", () => {
+    propose(membershipProposal, "Add wallet_4", wallet2);
+    vote(1, true, wallet2);
+    vote(1, true, wallet3);
+    expect(execute(1, membershipProposal, wallet2).result).toBeOk(Cl.bool(true));
+
+    const isMember = simnet.callReadOnlyFn("dao-core", "is-member", [Cl.principal(wallet4)], deployer);
+    expect(isMember.result).toBeBool(true);
+    const threshold = simnet.callReadOnlyFn("dao-core", "get-threshold", [], deployer);
+    expect(threshold.result).toBeUint(3);
+  });
+
+  it("doesn't count rejections as approvals", () => {
+    propose(grantProposal, "Grant 1,000 STX to wallet_4", wallet1);
+    vote(1, true, wallet1);
+    vote(1, false, wallet2);
+    vote(1, false, wallet3);
+    expect(execute(1, grantProposal, wallet1).result).toBeErr(Cl.uint(1005));
+  });
+
+  it("only accepts one vote per member", () => {
+    propose(grantProposal, "Grant 1,000 STX to wallet_4", wallet1);
+    expect(vote(1, true, wallet1).result).toBeOk(Cl.bool(true));
+    expect(vote(1, true, wallet1).result).toBeErr(Cl.uint(1003));
+  });
+
+  it("rejects proposals and votes from non members", () => {
+    expect(propose(grantProposal, "Grant 1,000 STX to wallet_4", wallet4).result).toBeErr(
+      Cl.uint(1001),
+    );
+    propose(grantProposal, "Grant 1,000 STX to wallet_4", wallet1);
+    expect(vote(1, true, wallet4).result).toBeErr(Cl.uint(1001));
+  });
+
+  it("executes the proposal that was voted only", () => {
+    propose(grantProposal, "Grant 1,000 STX to wallet_4", wallet1);
+    vote(1, true, wallet1);
+    vote(1, true, wallet2);
+    expect(execute(1, membershipProposal, wallet1).result).toBeErr(Cl.uint(1006));
+  });
+
+  it("rejects votes once the proposal expired", () => {
+    propose(grantProposal, "Grant 1,000 STX to wallet_4", wallet1);
+    simnet.mineEmptyBlocks(1440);
+    expect(vote(1, true, wallet1).result).toBeErr(Cl.uint(1008));
+  });
+
+  it("only lets dao-core spend the treasury", () => {
+    const { result } = simnet.callPublicFn(
+      "treasury",
+      "transfer",
+      [Cl.uint(1_000_000), Cl.principal(wallet1)],
+      wallet1,
+    );
+    expect(result).toBeErr(Cl.uint(2000));
+  });
+});
@@ -0,0 +1,360 @@
+use super::changes::{Changes, FileCreation};
+use clarinet_files::FileLocation;
+use clarity_repl::clarity::vm::analysis::ContractAnalysis;
+use clarity_repl::clarity::vm::types::{
+    FixedFunction, FunctionType, QualifiedContractIdentifier, SequenceSubtype, StringSubtype,
+    TypeSignature,
+};
+
+/// Harnesses of `clarinet generate tests --harness <name>`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TestHarness {
+    /// Vitest and the clarinet-sdk `simnet` global
+    TypeScript,
+    /// Cargo tests evaluating the calls in a session set up with clarinet-deployments
+    Rust,
+}
+
+impl TestHarness {
+    pub fn from_name(name: &str) -> Option<TestHarness> {
+        match name {
+            "ts" => Some(TestHarness::TypeScript),
+            "rust" => Some(TestHarness::Rust),
+            _ => None,
+        }
+    }
+}
+
+/// Value of the given type passed to the stubs, as a `Cl` helper call
+fn get_typescript_example(type_signature: &TypeSignature, contract_id: &str) -> String {
+    match type_signature {
+        TypeSignature::IntType => "Cl.int(1)".to_string(),
+        TypeSignature::UIntType => "Cl.uint(1)".to_string(),
+        TypeSignature::BoolType => "Cl.bool(true)".to_string(),
+        TypeSignature::PrincipalType => "Cl.principal(wallet1)".to_string(),
+        TypeSignature::SequenceType(SequenceSubtype::BufferType(_)) => {
+            "Cl.bufferFromHex(\"00\")".to_string()
+        }
+        TypeSignature::SequenceType(SequenceSubtype::StringType(StringSubtype::ASCII(_))) => {
+            "Cl.stringAscii(\"a\")".to_string()
+        }
+        TypeSignature::SequenceType(SequenceSubtype::StringType(StringSubtype::UTF8(_))) => {
+            "Cl.stringUtf8(\"a\")".to_string()
+        }
+        TypeSignature::SequenceType(SequenceSubtype::ListType(list)) => format!(
+            "Cl.list([{}])",
+            get_typescript_example(list.get_list_item_type(), contract_id)
+        ),
+        TypeSignature::OptionalType(inner) => {
+            format!("Cl.some({})", get_typescript_example(inner, contract_id))
+        }
+        TypeSignature::ResponseType(inner) => {
+            format!("Cl.ok({})", get_typescript_example(&inner.0, contract_id))
+        }
+        TypeSignature::TupleType(tuple) => {
+            let fields: Vec<String> = tuple
+                .get_type_map()
+                .iter()
+                .map(|(name, field_type)| {
+                    format!(
+                        "\"{}\": {}",
+                        name,
+                        get_typescript_example(field_type, contract_id)
+                    )
+                })
+                .collect();
+            format!("Cl.tuple({{ {} }})", fields.join(", "))
+        }
+        TypeSignature::NoType => "Cl.none()".to_string(),
+        // traits: a contract of the project implementing them is expected
+        _ => format!("Cl.contractPrincipal(deployer, \"{}\")", contract_id),
+    }
+}
+
+/// Value of the given type passed to the stubs, as a Clarity expression
+fn get_clarity_example(type_signature: &TypeSignature, contract_id: &str) -> String {
+    match type_signature {
+        TypeSignature::IntType => "1".to_string(),
+        TypeSignature::UIntType => "u1".to_string(),
+        TypeSignature::BoolType => "true".to_string(),
+        TypeSignature::PrincipalType => "tx-sender".to_string(),
+        TypeSignature::SequenceType(SequenceSubtype::BufferType(_)) => "0x00".to_string(),
+        TypeSignature::SequenceType(SequenceSubtype::StringType(StringSubtype::ASCII(_))) => {
+            "\"a\"".to_string()
+        }
+        TypeSignature::SequenceType(SequenceSubtype::StringType(StringSubtype::UTF8(_))) => {
+            "u\"a\"".to_string()
+        }
+        TypeSignature::SequenceType(SequenceSubtype::ListType(list)) => format!(
+            "(list {})",
+            get_clarity_example(list.get_list_item_type(), contract_id)
+        ),
+        TypeSignature::OptionalType(inner) => {
+            format!("(some {})", get_clarity_example(inner, contract_id))
+        }
+        TypeSignature::ResponseType(inner) => {
+            format!("(ok {})", get_clarity_example(&inner.0, contract_id))
+        }
+        TypeSignature::TupleType(tuple) => {
+            let fields: Vec<String> = tuple
+                .get_type_map()
+                .iter()
+                .map(|(name, field_type)| {
+                    format!("{}: {}", name, get_clarity_example(field_type, contract_id))
+                })
+                .collect();
+            format!("{{ {} }}", fields.join(", "))
+        }
+        TypeSignature::NoType => "none".to_string(),
+        _ => format!("'{}", contract_id),
+    }
+}
+
+fn to_rust_identifier(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+fn get_public_functions(analysis: &ContractAnalysis) -> Vec<(String, &FixedFunction)> {
+    analysis
+        .public_function_types
+        .iter()
+        .filter_map(|(name, signature)| match signature {
+            FunctionType::Fixed(function) => Some((name.to_string(), function)),
+            _ => None,
+        })
+        .collect()
+}
+
+fn generate_typescript_tests(
+    contract_id: &QualifiedContractIdentifier,
+    functions: &[(String, &FixedFunction)],
+) -> String {
+    let contract_name = contract_id.name.to_string();
+    let placeholder = format!("{}-trait-impl", contract_name);
+    let mut content = format!(
+        r#"import {{ Cl }} from "@stacks/transactions";
+import {{ describe, expect, it }} from "vitest";
+
+const accounts = simnet.getAccounts();
+const deployer = accounts.get("deployer")!;
+const wallet1 = accounts.get("wallet_1")!;
+
+/*
+  Generated by `clarinet generate tests {0}`. simnet is set up with the contracts of
+  deployments/default.simnet-plan.yaml, the arguments below are examples of their types.
+*/
+
+describe("{0}", () => {{
+"#,
+        contract_name
+    );
+    let stubs: Vec<String> = functions
+        .iter()
+        .map(|(name, function)| {
+            let args: Vec<String> = function
+                .args
+                .iter()
+                .map(|arg| get_typescript_example(&arg.signature, &placeholder))
+                .collect();
+            let expected = match function.returns {
+                TypeSignature::ResponseType(ref inner) => {
+                    get_typescript_example(&inner.0, &placeholder)
+                }
+                _ => "Cl.bool(true)".to_string(),
+            };
+            format!(
+                r#"  it("{1}", () => {{
+    const {{ result }} = simnet.callPublicFn("{0}", "{1}", [{2}], wallet1);
+    expect(result).toBeOk({3});
+  }});
+"#,
+                contract_name,
+                name,
+                args.join(", "),
+                expected
+            )
+        })
+        .collect();
+    content.push_str(&stubs.join("\n"));
+    content.push_str("});\n");
+    content
+}
+
+fn generate_rust_tests(
+    contract_id: &QualifiedContractIdentifier,
+    functions: &[(String, &FixedFunction)],
+) -> String {
+    let contract_name = contract_id.name.to_string();
+    let placeholder = format!("{}.{}-trait-impl", contract_id.issuer, contract_name);
+    let mut content = format!(
+        r#"//! Generated by `clarinet generate tests {0} --harness rust`. The session is set up with
+//! the contracts of deployments/default.simnet-plan.yaml, the arguments below are examples
+//! of their types.
+
+use clarinet_deployments::{{
+    get_default_deployment_path, load_deployment, setup_session_with_deployment,
+}};
+use clarinet_files::{{FileLocation, ProjectManifest, StacksNetwork}};
+use clarity_repl::repl::Session;
+
+/// Session of the default deployment, with wallet_1 as tx-sender
+fn setup() -> Session {{
+    let manifest_location =
+        FileLocation::from_path_string(concat!(env!("CARGO_MANIFEST_DIR"), "/Clarinet.toml"))
+            .unwrap();
+    let manifest = ProjectManifest::from_location(&manifest_location).unwrap();
+    let deployment_location =
+        get_default_deployment_path(&manifest, &StacksNetwork::Simnet).unwrap();
+    let deployment = load_deployment(&manifest, &deployment_location).unwrap();
+    let wallet_1 = deployment
+        .genesis
+        .as_ref()
+        .and_then(|genesis| genesis.wallets.iter().find(|w| w.name == "wallet_1"))
+        .map(|wallet| wallet.address.to_string())
+        .expect("wallet_1 missing from the deployment genesis");
+    let artifacts = setup_session_with_deployment(&manifest, &deployment, None);
+    assert!(artifacts.success, "unable to set up the session: {{:?}}", artifacts.diags);
+    let mut session = artifacts.session;
+    session.set_tx_sender(&wallet_1);
+    session
+}}
+"#,
+        contract_name
+    );
+    for (name, function) in functions.iter() {
+        let args: String = function
+            .args
+            .iter()
+            .map(|arg| format!(" {}", get_clarity_example(&arg.signature, &placeholder)))
+            .collect();
+        content.push_str(&format!(
+            r##"
+#[test]
+fn test_{0}() {{
+    let mut session = setup();
+    let snippet = r#"(contract-call? '{1} {2}{3})"#;
+    let result = session.eval(snippet.to_string(), false);
+    assert!(result.is_ok(), "{{:?}}", result.err());
+}}
+"##,
+            to_rust_identifier(name),
+            contract_id,
+            name,
+            args
+        ));
+    }
+    content
+}
+
+pub struct GetChangesForContractTests {
+    manifest_location: FileLocation,
+    analysis: ContractAnalysis,
+    harness: TestHarness,
+    changes: Vec<Changes>,
+}
+
+impl GetChangesForContractTests {
+    pub fn new(
+        manifest_location: FileLocation,
+        analysis: ContractAnalysis,
+        harness: TestHarness,
+    ) -> Self {
+        Self {
+            manifest_location,
+            analysis,
+            harness,
+            changes: vec![],
+        }
+    }
+
+    pub fn run(&mut self) -> Result<Vec<Changes>, String> {
+        let contract_id = &self.analysis.contract_identifier;
+        let functions = get_public_functions(&self.analysis);
+        if functions.is_empty() {
+            return Err(format!("{} has no public function", contract_id.name));
+        }
+        let (name, content) = match self.harness {
+            TestHarness::TypeScript => (
+                format!("{}.test.ts", contract_id.name),
+                generate_typescript_tests(contract_id, &functions),
+            ),
+            TestHarness::Rust => (
+                format!("{}_test.rs", to_rust_identifier(&contract_id.name)),
+                generate_rust_tests(contract_id, &functions),
+            ),
+        };
+        let mut new_file = self.manifest_location.get_project_root_location()?;
+        new_file.append_path("tests")?;
+        new_file.append_path(&name)?;
+        if new_file.exists() {
+            return Err(format!("{} already exists", new_file));
+        }
+        let change = FileCreation {
+            comment: format!("{} tests/{}", green!("Created file"), name),
+            content,
+            path: new_file.to_string(),
+        };
+        self.changes.push(Changes::AddFile(change));
+        Ok(self.changes.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clarity_repl::clarity::vm::types::{FunctionArg, TupleTypeSignature};
+    use clarity_repl::clarity::ClarityName;
+
+    #[test]
+    fn test_generate_contract_tests() {
+        let contract_id =
+            QualifiedContractIdentifier::parse("ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM.counter")
+                .unwrap();
+        let entry = TypeSignature::TupleType(
+            TupleTypeSignature::try_from(vec![
+                (
+                    ClarityName::try_from("amount".to_string()).unwrap(),
+                    TypeSignature::UIntType,
+                ),
+                (
+                    ClarityName::try_from("memo".to_string()).unwrap(),
+                    TypeSignature::new_option(TypeSignature::BoolType).unwrap(),
+                ),
+            ])
+            .unwrap(),
+        );
+        let add_entries = FixedFunction {
+            args: vec![
+                FunctionArg::new(
+                    TypeSignature::list_of(entry, 10).unwrap(),
+                    ClarityName::try_from("entries".to_string()).unwrap(),
+                ),
+                FunctionArg::new(
+                    TypeSignature::PrincipalType,
+                    ClarityName::try_from("owner".to_string()).unwrap(),
+                ),
+            ],
+            returns: TypeSignature::new_response(TypeSignature::IntType, TypeSignature::UIntType)
+                .unwrap(),
+        };
+        let functions = vec![("add-entries!".to_string(), &add_entries)];
+
+        let typescript = generate_typescript_tests(&contract_id, &functions);
+        assert!(typescript.contains(r#"describe("counter", () => {"#));
+        assert!(typescript.contains(
+            r#"simnet.callPublicFn("counter", "add-entries!", [Cl.list([Cl.tuple({ "amount": Cl.uint(1), "memo": Cl.some(Cl.bool(true)) })]), Cl.principal(wallet1)], wallet1);"#
+        ));
+        assert!(typescript.contains("expect(result).toBeOk(Cl.int(1));"));
+
+        let rust = generate_rust_tests(&contract_id, &functions);
+        assert!(rust.contains("fn test_add_entries_() {"));
+        assert!(rust.contains(
+            r#"(contract-call? 'ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM.counter add-entries! (list { amount: u1, memo: (some true) }) tx-sender)"#
+        ));
+
+        assert_eq!(TestHarness::from_name("rust"), Some(TestHarness::Rust));
+        assert_eq!(TestHarness::from_name("clarinet"), None);
+    }
+}
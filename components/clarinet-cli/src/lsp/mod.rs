@@ -1,6 +1,7 @@
 mod native_bridge;
 
 use self::native_bridge::LspNativeBridge;
+use clarity_lsp::state::EditorState;
 use clarity_lsp::utils;
 use clarity_repl::clarity::vm::diagnostic::{
     Diagnostic as ClarityDiagnostic, Level as ClarityLevel,
@@ -10,8 +11,16 @@ use std::sync::mpsc;
 use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, Position, Range};
 use tower_lsp::{LspService, Server};
 
-pub fn run_lsp() {
-    if let Err(_e) = block_on(do_run_lsp()) {
+/// Serves the editor over stdio, or over TCP when an address is given. With `cache_analysis`,
+/// the analysis of unchanged projects is reused from the previous session.
+pub fn run_lsp(address: Option<String>, cache_analysis: bool) {
+    clarity_lsp::cache::set_analysis_cache_enabled(cache_analysis);
+    let result = match address {
+        None => block_on(do_run_lsp(tokio::io::stdin(), tokio::io::stdout())),
+        Some(address) => run_tcp_lsp(&address),
+    };
+    if let Err(e) = result {
+        eprintln!("{}", e);
         std::process::exit(1)
     };
 }
@@ -24,15 +33,44 @@ where
     rt.block_on(future)
 }
 
-async fn do_run_lsp() -> Result<(), String> {
-    let stdin = tokio::io::stdin();
-    let stdout = tokio::io::stdout();
+/// Each editor connecting gets its own language server, running in its own thread
+fn run_tcp_lsp(address: &str) -> Result<(), String> {
+    let listener = std::net::TcpListener::bind(address)
+        .map_err(|e| format!("unable to listen on {}: {}", address, e))?;
+    eprintln!("Clarity language server listening on {}", address);
+    for stream in listener.incoming() {
+        let Ok(stream) = stream else {
+            continue;
+        };
+        std::thread::spawn(move || {
+            block_on(async move {
+                if stream.set_nonblocking(true).is_err() {
+                    return;
+                }
+                let Ok(stream) = tokio::net::TcpStream::from_std(stream) else {
+                    return;
+                };
+                let (input, output) = stream.into_split();
+                let _ = do_run_lsp(input, output).await;
+            })
+        });
+    }
+    Ok(())
+}
 
+async fn do_run_lsp<I, O>(input: I, output: O) -> Result<(), String>
+where
+    I: tokio::io::AsyncRead + Unpin,
+    O: tokio::io::AsyncWrite,
+{
     let (notification_tx, notification_rx) = unbounded();
     let (request_tx, request_rx) = unbounded();
     let (response_tx, response_rx) = mpsc::channel();
+    let editor_state = EditorState::new();
+    let build_cancellation = editor_state.build_cancellation.clone();
     std::thread::spawn(move || {
         hiro_system_kit::nestable_block_on(native_bridge::start_language_server(
+            editor_state,
             notification_rx,
             request_rx,
             response_tx,
@@ -40,9 +78,15 @@ async fn do_run_lsp() -> Result<(), String> {
     });
 
     let (service, socket) = LspService::new(|client| {
-        LspNativeBridge::new(client, notification_tx, request_tx, response_rx)
+        LspNativeBridge::new(
+            client,
+            notification_tx,
+            request_tx,
+            response_rx,
+            build_cancellation,
+        )
     });
-    Server::new(stdin, stdout, socket).serve(service).await;
+    Server::new(input, output, socket).serve(service).await;
     Ok(())
 }
 
@@ -103,6 +147,7 @@ fn test_opening_counter_contract_should_return_fresh_analysis() {
     let (response_tx, response_rx) = channel();
     std::thread::spawn(move || {
         hiro_system_kit::nestable_block_on(native_bridge::start_language_server(
+            EditorState::new(),
             notification_rx,
             request_rx,
             response_tx,
@@ -156,6 +201,7 @@ fn test_opening_counter_manifest_should_return_fresh_analysis() {
     let (response_tx, response_rx) = channel();
     std::thread::spawn(move || {
         hiro_system_kit::nestable_block_on(native_bridge::start_language_server(
+            EditorState::new(),
             notification_rx,
             request_rx,
             response_tx,
@@ -207,6 +253,7 @@ fn test_opening_simple_nft_manifest_should_return_fresh_analysis() {
     let (response_tx, response_rx) = channel();
     std::thread::spawn(move || {
         hiro_system_kit::nestable_block_on(native_bridge::start_language_server(
+            EditorState::new(),
             notification_rx,
             request_rx,
             response_tx,
@@ -233,3 +280,65 @@ fn test_opening_simple_nft_manifest_should_return_fresh_analysis() {
     // the counter project should emit 4 warnings and 4 notes coming from counter.clar
     assert_eq!(diags_0.len(), 8);
 }
+
+#[test]
+fn test_workspace_folders_should_load_and_clear_each_project() {
+    use crate::lsp::native_bridge::LspResponse;
+    use clarinet_files::FileLocation;
+    use clarity_lsp::backend::LspNotification;
+    use crossbeam_channel::unbounded;
+    use std::sync::mpsc::channel;
+
+    let (notification_tx, notification_rx) = unbounded();
+    let (_request_tx, request_rx) = unbounded();
+    let (response_tx, response_rx) = channel();
+    std::thread::spawn(move || {
+        hiro_system_kit::nestable_block_on(native_bridge::start_language_server(
+            EditorState::new(),
+            notification_rx,
+            request_rx,
+            response_tx,
+        ));
+    });
+
+    let get_example_location = |name: &str| {
+        let mut path = std::env::current_dir().expect("Unable to get current dir");
+        path.push("examples");
+        path.push(name);
+        FileLocation::from_path(path)
+    };
+    let counter_location = get_example_location("counter");
+    let simple_nft_location = get_example_location("simple-nft");
+
+    let _ = notification_tx.send(LspNotification::WorkspaceFoldersChanged {
+        added: vec![counter_location.clone(), simple_nft_location],
+        removed: vec![],
+    });
+    let response = response_rx.recv().expect("Unable to get response");
+    let response = if let LspResponse::Notification(response) = response {
+        response
+    } else {
+        panic!("Unable to get response")
+    };
+    // the contracts of both projects are analysed
+    assert_eq!(response.aggregated_diagnostics.len(), 3);
+
+    let _ = notification_tx.send(LspNotification::WorkspaceFoldersChanged {
+        added: vec![],
+        removed: vec![counter_location],
+    });
+    let response = response_rx.recv().expect("Unable to get response");
+    let response = if let LspResponse::Notification(response) = response {
+        response
+    } else {
+        panic!("Unable to get response")
+    };
+    // the diagnostics of the counter contracts are cleared
+    let cleared = response
+        .aggregated_diagnostics
+        .iter()
+        .filter(|(_, diags)| diags.is_empty())
+        .count();
+    assert_eq!(cleared, 2);
+    assert_eq!(response.aggregated_diagnostics.len(), 3);
+}
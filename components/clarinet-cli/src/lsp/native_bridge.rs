@@ -1,5 +1,6 @@
 use super::utils;
 use crate::lsp::clarity_diagnostics_to_tower_lsp_type;
+use clarinet_files::FileLocation;
 use clarity_lsp::backend::{
     process_mutating_request, process_notification, process_request, EditorStateInput,
     LspNotification, LspNotificationResponse, LspRequest, LspRequestResponse,
@@ -8,7 +9,7 @@ use clarity_lsp::lsp_types::{
     DocumentSymbolParams, DocumentSymbolResponse, GotoDefinitionParams, GotoDefinitionResponse,
     SignatureHelp, SignatureHelpParams,
 };
-use clarity_lsp::state::EditorState;
+use clarity_lsp::state::{BuildCancellation, EditorState};
 use crossbeam_channel::{Receiver as MultiplexableReceiver, Select, Sender as MultiplexableSender};
 use serde_json::Value;
 use std::sync::mpsc::{Receiver, Sender};
@@ -16,9 +17,10 @@ use std::sync::Arc;
 use std::sync::Mutex;
 use tower_lsp::jsonrpc::{Error, ErrorCode, Result};
 use tower_lsp::lsp_types::{
-    CompletionParams, CompletionResponse, DidChangeTextDocumentParams, DidCloseTextDocumentParams,
-    DidOpenTextDocumentParams, DidSaveTextDocumentParams, ExecuteCommandParams, Hover, HoverParams,
-    InitializeParams, InitializeResult, InitializedParams, MessageType, Url,
+    CompletionParams, CompletionResponse, DidChangeTextDocumentParams,
+    DidChangeWorkspaceFoldersParams, DidCloseTextDocumentParams, DidOpenTextDocumentParams,
+    DidSaveTextDocumentParams, ExecuteCommandParams, Hover, HoverParams, InitializeParams,
+    InitializeResult, InitializedParams, MessageType, Url, WorkspaceFolder,
 };
 use tower_lsp::{async_trait, Client, LanguageServer};
 
@@ -28,11 +30,12 @@ pub enum LspResponse {
 }
 
 pub async fn start_language_server(
+    editor_state: EditorState,
     notification_rx: MultiplexableReceiver<LspNotification>,
     request_rx: MultiplexableReceiver<LspRequest>,
     response_tx: Sender<LspResponse>,
 ) {
-    let mut editor_state = EditorStateInput::Owned(EditorState::new());
+    let mut editor_state = EditorStateInput::Owned(editor_state);
 
     let mut sel = Select::new();
     let notifications_oper = sel.recv(&notification_rx);
@@ -48,8 +51,9 @@ pub async fn start_language_server(
                         let _ = response_tx.send(LspResponse::Notification(response));
                     }
                 }
+                // the editor disconnected
                 Err(_e) => {
-                    continue;
+                    break;
                 }
             },
             i if i == requests_oper => match oper.recv(&request_rx) {
@@ -64,8 +68,9 @@ pub async fn start_language_server(
                         let _ = response_tx.send(LspResponse::Request(response));
                     }
                 }
+                // the editor disconnected
                 Err(_e) => {
-                    continue;
+                    break;
                 }
             },
             _ => unreachable!(),
@@ -79,6 +84,8 @@ pub struct LspNativeBridge {
     notification_tx: Arc<Mutex<MultiplexableSender<LspNotification>>>,
     request_tx: Arc<Mutex<MultiplexableSender<LspRequest>>>,
     response_rx: Arc<Mutex<Receiver<LspResponse>>>,
+    /// Cancels the build running in the language server thread, from the editor side
+    build_cancellation: BuildCancellation,
 }
 
 impl LspNativeBridge {
@@ -87,14 +94,54 @@ impl LspNativeBridge {
         notification_tx: MultiplexableSender<LspNotification>,
         request_tx: MultiplexableSender<LspRequest>,
         response_rx: Receiver<LspResponse>,
+        build_cancellation: BuildCancellation,
     ) -> Self {
         Self {
             client,
             notification_tx: Arc::new(Mutex::new(notification_tx)),
             request_tx: Arc::new(Mutex::new(request_tx)),
             response_rx: Arc::new(Mutex::new(response_rx)),
+            build_cancellation,
         }
     }
+
+    async fn send_notification(&self, notification: LspNotification) {
+        let _ = match self.notification_tx.lock() {
+            Ok(tx) => tx.send(notification),
+            Err(_) => return,
+        };
+
+        let mut aggregated_diagnostics = vec![];
+        let mut notification = None;
+        if let Ok(response_rx) = self.response_rx.lock() {
+            if let Ok(LspResponse::Notification(ref mut notification_response)) = response_rx.recv()
+            {
+                aggregated_diagnostics.append(&mut notification_response.aggregated_diagnostics);
+                notification = notification_response.notification.take();
+            }
+        }
+        for (location, mut diags) in aggregated_diagnostics.drain(..) {
+            if let Ok(url) = location.to_url_string() {
+                self.client
+                    .publish_diagnostics(
+                        Url::parse(&url).unwrap(),
+                        clarity_diagnostics_to_tower_lsp_type(&mut diags),
+                        None,
+                    )
+                    .await;
+            }
+        }
+        if let Some((level, message)) = notification {
+            self.client.show_message(level, message).await;
+        }
+    }
+}
+
+fn get_workspace_folders_locations(folders: &[WorkspaceFolder]) -> Vec<FileLocation> {
+    folders
+        .iter()
+        .filter_map(|folder| FileLocation::try_parse(folder.uri.as_str(), None))
+        .collect()
 }
 
 #[async_trait]
@@ -113,9 +160,20 @@ impl LanguageServer for LspNativeBridge {
         Err(Error::new(ErrorCode::InternalError))
     }
 
-    async fn initialized(&self, _params: InitializedParams) {}
+    async fn initialized(&self, _params: InitializedParams) {
+        self.send_notification(LspNotification::Initialized).await;
+    }
+
+    async fn did_change_workspace_folders(&self, params: DidChangeWorkspaceFoldersParams) {
+        self.send_notification(LspNotification::WorkspaceFoldersChanged {
+            added: get_workspace_folders_locations(&params.event.added),
+            removed: get_workspace_folders_locations(&params.event.removed),
+        })
+        .await;
+    }
 
     async fn shutdown(&self) -> Result<()> {
+        self.build_cancellation.cancel();
         Ok(())
     }
 
@@ -256,7 +314,13 @@ impl LanguageServer for LspNativeBridge {
     }
 
     async fn did_save(&self, params: DidSaveTextDocumentParams) {
+        // a build of the project still running is superseded by the one of this save
         if let Some(contract_location) = utils::get_contract_location(&params.text_document.uri) {
+            if let Ok(manifest_location) =
+                contract_location.get_project_manifest_location(None).await
+            {
+                self.build_cancellation.supersede(&manifest_location);
+            }
             let _ = match self.notification_tx.lock() {
                 Ok(tx) => tx.send(LspNotification::ContractSaved(contract_location)),
                 Err(_) => return,
@@ -264,6 +328,7 @@ impl LanguageServer for LspNativeBridge {
         } else if let Some(manifest_location) =
             utils::get_manifest_location(&params.text_document.uri)
         {
+            self.build_cancellation.supersede(&manifest_location);
             let _ = match self.notification_tx.lock() {
                 Ok(tx) => tx.send(LspNotification::ManifestSaved(manifest_location)),
                 Err(_) => return,
@@ -0,0 +1,90 @@
+use std::fs;
+use std::hint::black_box;
+
+use clarinet_deployments::{generate_default_deployment, setup_session_with_deployment};
+use clarinet_files::{FileLocation, ProjectManifest, StacksNetwork};
+use divan::Bencher;
+
+const DEPLOYER_MNEMONIC: &str = "twice kind fence tip hidden tilt action fragile skin nothing glory cousin green tomorrow spring wrist shed math olympic multiply hip blue scout claw";
+
+// project of `count` contracts, each one calling the previous one, written once in the temp dir
+fn get_fixture_manifest(count: usize) -> ProjectManifest {
+    let root = std::env::temp_dir().join(format!("clarinet-bench-{}", count));
+    fs::create_dir_all(root.join("contracts")).unwrap();
+    fs::create_dir_all(root.join("settings")).unwrap();
+
+    let mut manifest = "[project]\nname = \"bench\"\ntelemetry = false\n".to_string();
+    for i in 0..count {
+        manifest.push_str(&format!(
+            "\n[contracts.vault-{i}]\npath = \"contracts/vault-{i}.clar\"\nclarity_version = 2\nepoch = \"2.5\"\n"
+        ));
+        let mut src = [
+            "(define-map balances principal uint)",
+            "(define-read-only (get-balance (who principal))",
+            "  (default-to u0 (map-get? balances who)))",
+            "(define-public (deposit (amount uint))",
+            "  (ok (map-set balances tx-sender (+ (get-balance tx-sender) amount))))",
+        ]
+        .join("\n");
+        if i > 0 {
+            src.push_str(&format!(
+                "\n(define-public (forward (amount uint))\n  (contract-call? .vault-{} deposit amount))",
+                i - 1
+            ));
+        }
+        fs::write(root.join(format!("contracts/vault-{}.clar", i)), src).unwrap();
+    }
+    fs::write(root.join("Clarinet.toml"), manifest).unwrap();
+    fs::write(
+        root.join("settings/Devnet.toml"),
+        format!(
+            "[network]\nname = \"devnet\"\n\n[accounts.deployer]\nmnemonic = \"{}\"\nbalance = 100_000_000_000_000\n",
+            DEPLOYER_MNEMONIC
+        ),
+    )
+    .unwrap();
+
+    let location = FileLocation::from_path(root.join("Clarinet.toml"));
+    ProjectManifest::from_location(&location).unwrap()
+}
+
+#[divan::bench(args = [10, 50])]
+fn plan_generation(bencher: Bencher, count: usize) {
+    let manifest = get_fixture_manifest(count);
+    bencher.bench_local(|| {
+        let (deployment, artifacts) =
+            hiro_system_kit::nestable_block_on(generate_default_deployment(
+                black_box(&manifest),
+                &StacksNetwork::Simnet,
+                false,
+                None,
+                None,
+            ))
+            .unwrap();
+        assert!(artifacts.success);
+        deployment
+    });
+}
+
+// execution of a generated plan in a new session, as done by `clarinet check` and the LSP
+#[divan::bench(args = [10, 50])]
+fn session_replay(bencher: Bencher, count: usize) {
+    let manifest = get_fixture_manifest(count);
+    let (deployment, artifacts) = hiro_system_kit::nestable_block_on(generate_default_deployment(
+        &manifest,
+        &StacksNetwork::Simnet,
+        false,
+        None,
+        None,
+    ))
+    .unwrap();
+    bencher.bench_local(|| {
+        let artifacts =
+            setup_session_with_deployment(&manifest, black_box(&deployment), Some(&artifacts.asts));
+        assert!(artifacts.success);
+    });
+}
+
+fn main() {
+    divan::main();
+}
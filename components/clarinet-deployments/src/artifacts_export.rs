@@ -0,0 +1,213 @@
+//! Writes the intermediate products of the plan generation (ASTs, dependencies, analysis
+//! summaries and interfaces) to disk, one directory per contract, for external tooling.
+
+use clarinet_files::FileLocation;
+use clarity_repl::clarity::diagnostic::Level;
+use clarity_repl::clarity::vm::types::QualifiedContractIdentifier;
+
+use crate::types::{
+    DeploymentGenerationArtifacts, DeploymentSpecification, TransactionSpecification,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AstFormat {
+    SExpression,
+    Json,
+}
+
+impl TryFrom<&str> for AstFormat {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "sexp" => Ok(AstFormat::SExpression),
+            "json" => Ok(AstFormat::Json),
+            _ => Err(format!("unknown AST format '{}' (sexp, json)", value)),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct DependencyEntry {
+    contract_id: String,
+    required_before_publish: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct AnalysisSummary {
+    contract_id: String,
+    epoch: String,
+    clarity_version: String,
+    public_functions: Vec<String>,
+    read_only_functions: Vec<String>,
+    private_functions: Vec<String>,
+    maps: Vec<String>,
+    data_vars: Vec<String>,
+    fungible_tokens: Vec<String>,
+    non_fungible_tokens: Vec<String>,
+    defined_traits: Vec<String>,
+    implemented_traits: Vec<String>,
+    errors: usize,
+    warnings: usize,
+}
+
+#[derive(Debug, Serialize)]
+struct ArtifactsIndex {
+    /// Project contracts, in deployment order
+    contracts: Vec<String>,
+    ast_format: String,
+}
+
+/// Project contracts, in the order of the publish transactions of the plan
+fn get_contracts_in_deployment_order(
+    deployment: &DeploymentSpecification,
+) -> Vec<QualifiedContractIdentifier> {
+    deployment
+        .plan
+        .batches
+        .iter()
+        .flat_map(|batch| batch.transactions.iter())
+        .filter_map(|tx| match tx {
+            TransactionSpecification::ContractPublish(spec) => {
+                Some(QualifiedContractIdentifier::new(
+                    spec.expected_sender.clone(),
+                    spec.contract_name.clone(),
+                ))
+            }
+            TransactionSpecification::EmulatedContractPublish(spec) => {
+                Some(QualifiedContractIdentifier::new(
+                    spec.emulated_sender.clone(),
+                    spec.contract_name.clone(),
+                ))
+            }
+            _ => None,
+        })
+        .filter(|contract_id| deployment.contracts.contains_key(contract_id))
+        .collect()
+}
+
+fn names<'a, T: ToString + 'a>(keys: impl Iterator<Item = &'a T>) -> Vec<String> {
+    keys.map(|key| key.to_string()).collect()
+}
+
+fn write_json<T: serde::Serialize>(
+    location: &FileLocation,
+    file_name: &str,
+    value: &T,
+) -> Result<FileLocation, String> {
+    let mut file_location = location.clone();
+    file_location.append_path(file_name)?;
+    let content = serde_json::to_vec_pretty(value)
+        .map_err(|e| format!("unable to serialize {}: {}", file_name, e))?;
+    file_location.write_content(&content)?;
+    Ok(file_location)
+}
+
+fn write_contract_artifacts(
+    contract_id: &QualifiedContractIdentifier,
+    artifacts: &DeploymentGenerationArtifacts,
+    output_dir: &FileLocation,
+    ast_format: AstFormat,
+) -> Result<Vec<FileLocation>, String> {
+    let mut contract_dir = output_dir.clone();
+    contract_dir.append_path(&contract_id.to_string())?;
+    let mut written = vec![];
+
+    if let Some(ast) = artifacts.asts.get(contract_id) {
+        match ast_format {
+            AstFormat::SExpression => {
+                let mut location = contract_dir.clone();
+                location.append_path("ast.clar")?;
+                let content: Vec<String> = ast
+                    .expressions
+                    .iter()
+                    .map(|expr| expr.to_string())
+                    .collect();
+                location.write_content(content.join("\n").as_bytes())?;
+                written.push(location);
+            }
+            AstFormat::Json => {
+                written.push(write_json(&contract_dir, "ast.json", &ast.expressions)?);
+            }
+        }
+    }
+
+    if let Some(deps) = artifacts.deps.get(contract_id) {
+        let deps: Vec<DependencyEntry> = deps
+            .set
+            .iter()
+            .map(|dep| DependencyEntry {
+                contract_id: dep.contract_id.to_string(),
+                required_before_publish: dep.required_before_publish,
+            })
+            .collect();
+        written.push(write_json(&contract_dir, "dependencies.json", &deps)?);
+    }
+
+    if let Some(analysis) = artifacts.analysis.get(contract_id) {
+        let diags = artifacts.diags.get(contract_id);
+        let count = |level: Level| {
+            diags
+                .map(|diags| diags.iter().filter(|d| d.level == level).count())
+                .unwrap_or_default()
+        };
+        let summary = AnalysisSummary {
+            contract_id: contract_id.to_string(),
+            epoch: analysis.epoch.to_string(),
+            clarity_version: analysis.clarity_version.to_string(),
+            public_functions: names(analysis.public_function_types.keys()),
+            read_only_functions: names(analysis.read_only_function_types.keys()),
+            private_functions: names(analysis.private_function_types.keys()),
+            maps: names(analysis.map_types.keys()),
+            data_vars: names(analysis.persisted_variable_types.keys()),
+            fungible_tokens: names(analysis.fungible_tokens.iter()),
+            non_fungible_tokens: names(analysis.non_fungible_tokens.keys()),
+            defined_traits: names(analysis.defined_traits.keys()),
+            implemented_traits: analysis
+                .implemented_traits
+                .iter()
+                .map(|trait_id| format!("{}.{}", trait_id.contract_identifier, trait_id.name))
+                .collect(),
+            errors: count(Level::Error),
+            warnings: count(Level::Warning),
+        };
+        written.push(write_json(&contract_dir, "analysis.json", &summary)?);
+
+        if let Some(ref interface) = analysis.contract_interface {
+            written.push(write_json(&contract_dir, "interface.json", interface)?);
+        }
+    }
+
+    Ok(written)
+}
+
+/// Writes the artifacts of the project contracts in `output_dir/<contract_id>/`,
+/// and an `index.json` listing the contracts in deployment order
+pub fn write_artifacts(
+    deployment: &DeploymentSpecification,
+    artifacts: &DeploymentGenerationArtifacts,
+    output_dir: &FileLocation,
+    ast_format: AstFormat,
+) -> Result<Vec<FileLocation>, String> {
+    let mut written = vec![];
+    let mut contracts = vec![];
+    for contract_id in get_contracts_in_deployment_order(deployment) {
+        written.append(&mut write_contract_artifacts(
+            &contract_id,
+            artifacts,
+            output_dir,
+            ast_format,
+        )?);
+        contracts.push(contract_id.to_string());
+    }
+
+    let index = ArtifactsIndex {
+        contracts,
+        ast_format: match ast_format {
+            AstFormat::SExpression => "sexp".to_string(),
+            AstFormat::Json => "json".to_string(),
+        },
+    };
+    written.push(write_json(output_dir, "index.json", &index)?);
+    Ok(written)
+}
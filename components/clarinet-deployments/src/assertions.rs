@@ -0,0 +1,191 @@
+//! Assertion helpers for the execution results of a simnet session.
+//! All the helpers panic with a descriptive message when the assertion fails.
+
+use clarity_repl::clarity::vm::events::{FTEventType, STXEventType, StacksTransactionEvent};
+use clarity_repl::clarity::vm::types::{
+    OptionalData, QualifiedContractIdentifier, ResponseData, Value,
+};
+use clarity_repl::clarity::vm::{EvaluationResult, ExecutionResult};
+use clarity_repl::repl::Session;
+
+/// Returns the value returned by a snippet, a contract call or a contract deployment
+pub fn get_result_value(result: &ExecutionResult) -> Option<&Value> {
+    match &result.result {
+        EvaluationResult::Snippet(snippet) => Some(&snippet.result),
+        EvaluationResult::Contract(contract) => contract.result.as_ref(),
+    }
+}
+
+#[track_caller]
+fn expect_response(result: &ExecutionResult) -> (bool, &Value) {
+    match get_result_value(result) {
+        Some(Value::Response(ResponseData { committed, data })) => (*committed, &**data),
+        Some(value) => panic!("expected a response, got {}", value),
+        None => panic!("expected a response, got no value"),
+    }
+}
+
+#[track_caller]
+pub fn assert_ok_value(result: &ExecutionResult, expected: &Value) {
+    match expect_response(result) {
+        (true, value) => assert_eq!(value, expected, "unexpected ok value"),
+        (false, value) => panic!("expected (ok {}), got (err {})", expected, value),
+    }
+}
+
+#[track_caller]
+pub fn assert_err_value(result: &ExecutionResult, expected: &Value) {
+    match expect_response(result) {
+        (false, value) => assert_eq!(value, expected, "unexpected err value"),
+        (true, value) => panic!("expected (err {}), got (ok {})", expected, value),
+    }
+}
+
+#[track_caller]
+pub fn assert_event_stx_transfer(result: &ExecutionResult, from: &str, to: &str, amount: u128) {
+    let found = result.events.iter().any(|event| match event {
+        StacksTransactionEvent::STXEvent(STXEventType::STXTransferEvent(data)) => {
+            data.sender.to_string() == from
+                && data.recipient.to_string() == to
+                && data.amount == amount
+        }
+        _ => false,
+    });
+    assert!(
+        found,
+        "no stx transfer of {} from {} to {} in {} events",
+        amount,
+        from,
+        to,
+        result.events.len()
+    );
+}
+
+/// `token` is the sugared asset identifier (e.g. `.my-token::token`) or the
+/// fully qualified one (e.g. `ST1...my-token::token`)
+#[track_caller]
+pub fn assert_event_ft_transfer(
+    result: &ExecutionResult,
+    token: &str,
+    from: &str,
+    to: &str,
+    amount: u128,
+) {
+    let found = result.events.iter().any(|event| match event {
+        StacksTransactionEvent::FTEvent(FTEventType::FTTransferEvent(data)) => {
+            let asset_id = format!(
+                "{}::{}",
+                data.asset_identifier.contract_identifier, data.asset_identifier.asset_name
+            );
+            (data.asset_identifier.sugared() == token || asset_id == token)
+                && data.sender.to_string() == from
+                && data.recipient.to_string() == to
+                && data.amount == amount
+        }
+        _ => false,
+    });
+    assert!(
+        found,
+        "no {} transfer of {} from {} to {} in {} events",
+        token,
+        amount,
+        from,
+        to,
+        result.events.len()
+    );
+}
+
+#[track_caller]
+pub fn assert_data_var_eq(
+    session: &mut Session,
+    contract_id: &QualifiedContractIdentifier,
+    var_name: &str,
+    expected: &Value,
+) {
+    let value = session
+        .interpreter
+        .get_data_var(contract_id, var_name)
+        .and_then(|hex| Value::try_deserialize_hex_untyped(&hex[2..]).ok())
+        .unwrap_or_else(|| panic!("data var {} not found in {}", var_name, contract_id));
+    assert_eq!(&value, expected, "unexpected value for {}", var_name);
+}
+
+#[track_caller]
+pub fn assert_map_entry_eq(
+    session: &mut Session,
+    contract_id: &QualifiedContractIdentifier,
+    map_name: &str,
+    key: &Value,
+    expected: Option<&Value>,
+) {
+    let entry = session
+        .interpreter
+        .get_map_entry(contract_id, map_name, key)
+        .and_then(|hex| Value::try_deserialize_hex_untyped(&hex[2..]).ok());
+    // map entries are stored as optionals, deleted entries being `none`
+    let value = match entry {
+        Some(Value::Optional(OptionalData { data })) => data.map(|value| *value),
+        other => other,
+    };
+    assert_eq!(
+        value.as_ref(),
+        expected,
+        "unexpected entry for {} in map {}",
+        key,
+        map_name
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clarity_repl::repl::{SessionSettings, DEFAULT_EPOCH};
+
+    fn get_session() -> Session {
+        let mut session = Session::new(SessionSettings::default());
+        session.update_epoch(DEFAULT_EPOCH);
+        session
+    }
+
+    #[test]
+    fn test_assert_result_values() {
+        let mut session = get_session();
+        let ok = session.eval("(ok u1)".into(), false).unwrap();
+        assert_ok_value(&ok, &Value::UInt(1));
+        let err = session.eval("(err u2)".into(), false).unwrap();
+        assert_err_value(&err, &Value::UInt(2));
+    }
+
+    #[test]
+    #[should_panic(expected = "expected (ok u1), got (err u1)")]
+    fn test_assert_ok_value_fails_on_err() {
+        let mut session = get_session();
+        let err = session.eval("(err u1)".into(), false).unwrap();
+        assert_ok_value(&err, &Value::UInt(1));
+    }
+
+    #[test]
+    fn test_assert_stored_values() {
+        let mut session = get_session();
+        let contract = [
+            "(define-data-var count uint u3)",
+            "(define-map owners uint principal)",
+            "(map-set owners u1 tx-sender)",
+        ]
+        .join("\n");
+        session.eval(contract, false).unwrap();
+        let (contract_id, _) = session.contracts.first_key_value().unwrap();
+        let contract_id = contract_id.clone();
+        let tx_sender = Value::Principal(session.interpreter.get_tx_sender().into());
+
+        assert_data_var_eq(&mut session, &contract_id, "count", &Value::UInt(3));
+        assert_map_entry_eq(
+            &mut session,
+            &contract_id,
+            "owners",
+            &Value::UInt(1),
+            Some(&tx_sender),
+        );
+        assert_map_entry_eq(&mut session, &contract_id, "owners", &Value::UInt(2), None);
+    }
+}
@@ -0,0 +1,182 @@
+//! A baseline records the diagnostics of a project at a given time (`clarinet check --write-baseline`).
+//! Diagnostics found in the baseline are then suppressed, so that only new ones are reported.
+//! Entries don't include positions, editing a contract doesn't invalidate its baseline.
+
+use std::collections::{BTreeMap, HashMap};
+
+use clarinet_files::FileLocation;
+use clarity_repl::clarity::diagnostic::{Diagnostic, Level};
+use clarity_repl::clarity::vm::types::QualifiedContractIdentifier;
+
+pub const DEFAULT_BASELINE_FILE: &str = "clarinet-baseline.json";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BaselineEntry {
+    pub contract_id: String,
+    pub level: String,
+    pub message: String,
+    pub count: usize,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DiagnosticsBaseline {
+    pub entries: Vec<BaselineEntry>,
+}
+
+fn get_level_name(level: &Level) -> Option<&'static str> {
+    match level {
+        Level::Error => Some("error"),
+        Level::Warning => Some("warning"),
+        // notes are attached to the previous diagnostic
+        Level::Note => None,
+    }
+}
+
+type BaselineKey = (String, &'static str, String);
+
+impl DiagnosticsBaseline {
+    pub fn from_diagnostics(
+        contracts_diags: &HashMap<QualifiedContractIdentifier, Vec<Diagnostic>>,
+    ) -> Self {
+        let mut counts: BTreeMap<BaselineKey, usize> = BTreeMap::new();
+        for (contract_id, diags) in contracts_diags.iter() {
+            for diagnostic in diags {
+                let Some(level) = get_level_name(&diagnostic.level) else {
+                    continue;
+                };
+                let key = (contract_id.to_string(), level, diagnostic.message.clone());
+                *counts.entry(key).or_default() += 1;
+            }
+        }
+        let entries = counts
+            .into_iter()
+            .map(|((contract_id, level, message), count)| BaselineEntry {
+                contract_id,
+                level: level.to_string(),
+                message,
+                count,
+            })
+            .collect();
+        Self { entries }
+    }
+
+    pub fn from_location(location: &FileLocation) -> Result<Self, String> {
+        let content = location.read_content()?;
+        serde_json::from_slice(&content)
+            .map_err(|e| format!("unable to parse baseline {}: {}", location, e))
+    }
+
+    pub fn write(&self, location: &FileLocation) -> Result<(), String> {
+        let content = serde_json::to_vec_pretty(self)
+            .map_err(|e| format!("unable to serialize baseline: {}", e))?;
+        location.write_content(&content)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.iter().map(|entry| entry.count).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes the diagnostics recorded in the baseline, along with their notes.
+    /// Each entry suppresses at most `count` diagnostics. Returns the number of
+    /// suppressed diagnostics.
+    pub fn filter(
+        &self,
+        contracts_diags: &mut HashMap<QualifiedContractIdentifier, Vec<Diagnostic>>,
+    ) -> usize {
+        let mut remaining: HashMap<(&str, &str, &str), usize> = self
+            .entries
+            .iter()
+            .map(|entry| {
+                (
+                    (
+                        entry.contract_id.as_str(),
+                        entry.level.as_str(),
+                        entry.message.as_str(),
+                    ),
+                    entry.count,
+                )
+            })
+            .collect();
+
+        let mut suppressed = 0;
+        for (contract_id, diags) in contracts_diags.iter_mut() {
+            let contract_id = contract_id.to_string();
+            let mut suppress_notes = false;
+            diags.retain(|diagnostic| {
+                let Some(level) = get_level_name(&diagnostic.level) else {
+                    return !suppress_notes;
+                };
+                suppress_notes = false;
+                let key = (contract_id.as_str(), level, diagnostic.message.as_str());
+                match remaining.get_mut(&key) {
+                    Some(count) if *count > 0 => {
+                        *count -= 1;
+                        suppressed += 1;
+                        suppress_notes = true;
+                        false
+                    }
+                    _ => true,
+                }
+            });
+        }
+        suppressed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diagnostic(level: Level, message: &str) -> Diagnostic {
+        Diagnostic {
+            level,
+            message: message.to_string(),
+            spans: vec![],
+            suggestion: None,
+        }
+    }
+
+    #[test]
+    fn test_baseline_suppresses_recorded_diagnostics() {
+        let contract_id = QualifiedContractIdentifier::transient();
+        let mut contracts_diags = HashMap::new();
+        contracts_diags.insert(
+            contract_id.clone(),
+            vec![
+                diagnostic(Level::Warning, "use of potentially unchecked data"),
+                diagnostic(Level::Note, "source of untrusted input here"),
+                diagnostic(Level::Warning, "use of potentially unchecked data"),
+            ],
+        );
+        let baseline = DiagnosticsBaseline::from_diagnostics(&contracts_diags);
+        assert_eq!(baseline.entries.len(), 1);
+        assert_eq!(baseline.len(), 2);
+
+        contracts_diags.insert(
+            contract_id.clone(),
+            vec![
+                diagnostic(Level::Warning, "use of potentially unchecked data"),
+                diagnostic(Level::Note, "source of untrusted input here"),
+                diagnostic(Level::Warning, "use of potentially unchecked data"),
+                diagnostic(Level::Warning, "use of potentially unchecked data"),
+                diagnostic(Level::Error, "use of unresolved function 'foo'"),
+            ],
+        );
+        assert_eq!(baseline.filter(&mut contracts_diags), 2);
+        let messages: Vec<&str> = contracts_diags[&contract_id]
+            .iter()
+            .map(|d| d.message.as_str())
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                "use of potentially unchecked data",
+                "use of unresolved function 'foo'"
+            ]
+        );
+    }
+}
@@ -0,0 +1,209 @@
+//! Benchmark of a Clarity snippet against the simnet state of a project (`clarinet bench`).
+//! Each run evaluates the snippet in a copy of the session, so that the runs start from the
+//! same state and report the same costs; the wall time is measured around the evaluation only.
+//! The reports can be saved as a baseline, keyed by snippet, to compare the next runs with.
+
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+use clarinet_files::FileLocation;
+use clarity_repl::clarity::vm::costs::ExecutionCost;
+use clarity_repl::repl::Session;
+
+pub const DEFAULT_BENCH_BASELINE_FILE: &str = "clarinet-bench.json";
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SnippetCost {
+    pub runtime: u64,
+    pub read_count: u64,
+    pub read_length: u64,
+    pub write_count: u64,
+    pub write_length: u64,
+}
+
+impl From<&ExecutionCost> for SnippetCost {
+    fn from(cost: &ExecutionCost) -> Self {
+        SnippetCost {
+            runtime: cost.runtime,
+            read_count: cost.read_count,
+            read_length: cost.read_length,
+            write_count: cost.write_count,
+            write_length: cost.write_length,
+        }
+    }
+}
+
+/// Wall time of the runs, in microseconds
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WallTime {
+    pub min: u64,
+    pub p50: u64,
+    pub p95: u64,
+    pub max: u64,
+    pub mean: u64,
+}
+
+impl WallTime {
+    pub fn from_durations(durations: &[Duration]) -> Self {
+        let mut micros: Vec<u64> = durations
+            .iter()
+            .map(|duration| duration.as_micros() as u64)
+            .collect();
+        if micros.is_empty() {
+            return WallTime::default();
+        }
+        micros.sort_unstable();
+        // nearest-rank percentiles
+        let percentile = |p: usize| micros[(micros.len() * p).div_ceil(100).max(1) - 1];
+        WallTime {
+            min: micros[0],
+            p50: percentile(50),
+            p95: percentile(95),
+            max: micros[micros.len() - 1],
+            mean: micros.iter().sum::<u64>() / micros.len() as u64,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BenchReport {
+    pub snippet: String,
+    pub runs: usize,
+    pub cost: SnippetCost,
+    pub wall_time: WallTime,
+}
+
+/// Evaluates `snippet` `runs` times, each time in a copy of `session`
+pub fn run_benchmark(session: &Session, snippet: &str, runs: usize) -> Result<BenchReport, String> {
+    if runs == 0 {
+        return Err("at least one run is required".to_string());
+    }
+    let mut durations = Vec::with_capacity(runs);
+    let mut cost = SnippetCost::default();
+    for _ in 0..runs {
+        let mut session = session.clone();
+        let started_at = Instant::now();
+        let result = session.eval(snippet.to_string(), true);
+        durations.push(started_at.elapsed());
+        let result = result.map_err(|diagnostics| {
+            let messages: Vec<String> = diagnostics.into_iter().map(|d| d.message).collect();
+            format!("unable to evaluate the snippet: {}", messages.join(", "))
+        })?;
+        if let Some(ref synthesis) = result.cost {
+            cost = SnippetCost::from(&synthesis.total);
+        }
+    }
+    Ok(BenchReport {
+        snippet: snippet.to_string(),
+        runs,
+        cost,
+        wall_time: WallTime::from_durations(&durations),
+    })
+}
+
+/// Saved reports, keyed by snippet
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BenchBaseline {
+    pub reports: BTreeMap<String, BenchReport>,
+}
+
+impl BenchBaseline {
+    pub fn from_location(location: &FileLocation) -> Result<Self, String> {
+        let content = location.read_content_as_utf8()?;
+        serde_json::from_str(&content).map_err(|e| format!("unable to parse {}: {}", location, e))
+    }
+
+    pub fn write(&self, location: &FileLocation) -> Result<(), String> {
+        let content = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        location.write_content(content.as_bytes())
+    }
+}
+
+/// A metric of a report compared with the baseline
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MetricChange {
+    pub metric: &'static str,
+    pub baseline: u64,
+    pub current: u64,
+}
+
+impl MetricChange {
+    /// Change in percent of the baseline, `None` when the baseline is zero
+    pub fn percentage(&self) -> Option<f64> {
+        if self.baseline == 0 {
+            return None;
+        }
+        Some((self.current as f64 - self.baseline as f64) * 100.0 / self.baseline as f64)
+    }
+}
+
+pub fn compare_reports(baseline: &BenchReport, current: &BenchReport) -> Vec<MetricChange> {
+    [
+        ("runtime", baseline.cost.runtime, current.cost.runtime),
+        (
+            "read_count",
+            baseline.cost.read_count,
+            current.cost.read_count,
+        ),
+        (
+            "read_length",
+            baseline.cost.read_length,
+            current.cost.read_length,
+        ),
+        (
+            "write_count",
+            baseline.cost.write_count,
+            current.cost.write_count,
+        ),
+        (
+            "write_length",
+            baseline.cost.write_length,
+            current.cost.write_length,
+        ),
+        ("p50 (µs)", baseline.wall_time.p50, current.wall_time.p50),
+        ("p95 (µs)", baseline.wall_time.p95, current.wall_time.p95),
+    ]
+    .into_iter()
+    .map(|(metric, baseline, current)| MetricChange {
+        metric,
+        baseline,
+        current,
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use clarity_repl::repl::{SessionSettings, DEFAULT_EPOCH};
+
+    use super::*;
+
+    #[test]
+    fn test_run_benchmark() {
+        let durations: Vec<Duration> = (1..=20).map(Duration::from_micros).collect();
+        let wall_time = WallTime::from_durations(&durations);
+        assert_eq!(
+            wall_time,
+            WallTime {
+                min: 1,
+                p50: 10,
+                p95: 19,
+                max: 20,
+                mean: 10,
+            }
+        );
+
+        let mut session = Session::new(SessionSettings::default());
+        session.update_epoch(DEFAULT_EPOCH);
+        let report = run_benchmark(&session, "(+ u1 u2)", 5).unwrap();
+        assert_eq!(report.runs, 5);
+        assert!(report.cost.runtime > 0);
+        assert!(run_benchmark(&session, "(+ u1", 1).is_err());
+
+        let mut slower = report.clone();
+        slower.cost.runtime = report.cost.runtime * 2;
+        let changes = compare_reports(&report, &slower);
+        assert_eq!(changes[0].metric, "runtime");
+        assert_eq!(changes[0].percentage(), Some(100.0));
+    }
+}
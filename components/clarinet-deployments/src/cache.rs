@@ -0,0 +1,217 @@
+//! Entries of the requirements cache (`<cache_dir>/requirements`): the source and metadata
+//! of each requirement fetched from the network, and its interface and mock when mocks are
+//! enabled. The files of an entry are all prefixed with the contract id of the requirement.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+use clarinet_files::FileLocation;
+
+use crate::requirements::{get_source_hash, ContractMetadata};
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "status", content = "reason", rename_all = "snake_case")]
+pub enum CacheEntryStatus {
+    Valid,
+    /// Written by an older version, without a recorded hash
+    Unverified,
+    Corrupted(String),
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CacheEntry {
+    pub contract_id: String,
+    pub files: Vec<PathBuf>,
+    pub size: u64,
+    /// Hash of the cached source
+    pub source_hash: Option<String>,
+    #[serde(skip)]
+    pub modified_at: SystemTime,
+}
+
+impl CacheEntry {
+    fn get_file(&self, suffix: &str) -> Option<&PathBuf> {
+        let file_name = format!("{}{}", self.contract_id, suffix);
+        self.files
+            .iter()
+            .find(|path| path.file_name().is_some_and(|name| *name == *file_name))
+    }
+
+    /// Checks the source of the entry against the hash recorded in its metadata
+    pub fn verify(&self) -> CacheEntryStatus {
+        let source = self
+            .get_file(".clar")
+            .map(|path| fs::read_to_string(path).map_err(|e| e.to_string()));
+        let metadata = self
+            .get_file(".json")
+            .map(|path| fs::read_to_string(path).map_err(|e| e.to_string()));
+        match (source, metadata) {
+            (Some(Err(e)), _) => CacheEntryStatus::Corrupted(format!("unreadable source: {}", e)),
+            (_, Some(Err(e))) => CacheEntryStatus::Corrupted(format!("unreadable metadata: {}", e)),
+            (Some(Ok(_)), None) => CacheEntryStatus::Corrupted("missing metadata".to_string()),
+            (None, Some(Ok(_))) => CacheEntryStatus::Corrupted("missing source".to_string()),
+            (None, None) => match self.get_file(".interface.json") {
+                Some(path) => match fs::read_to_string(path) {
+                    Ok(interface)
+                        if serde_json::from_str::<serde_json::Value>(&interface).is_ok() =>
+                    {
+                        CacheEntryStatus::Valid
+                    }
+                    _ => CacheEntryStatus::Corrupted("unparsable interface".to_string()),
+                },
+                None => CacheEntryStatus::Valid,
+            },
+            (Some(Ok(source)), Some(Ok(metadata))) => {
+                match serde_json::from_str::<ContractMetadata>(&metadata) {
+                    Err(e) => CacheEntryStatus::Corrupted(format!("unparsable metadata: {}", e)),
+                    Ok(ContractMetadata {
+                        source_hash: None, ..
+                    }) => CacheEntryStatus::Unverified,
+                    Ok(ContractMetadata {
+                        source_hash: Some(hash),
+                        ..
+                    }) if hash != get_source_hash(&source) => CacheEntryStatus::Corrupted(
+                        "the source doesn't match its recorded hash".to_string(),
+                    ),
+                    Ok(_) => CacheEntryStatus::Valid,
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CacheCleanFilter {
+    All,
+    Contract(String),
+    OlderThan(Duration),
+    Corrupted,
+}
+
+impl CacheCleanFilter {
+    fn matches(&self, entry: &CacheEntry) -> bool {
+        match self {
+            CacheCleanFilter::All => true,
+            CacheCleanFilter::Contract(contract_id) => &entry.contract_id == contract_id,
+            CacheCleanFilter::OlderThan(age) => entry
+                .modified_at
+                .elapsed()
+                .is_ok_and(|elapsed| elapsed > *age),
+            CacheCleanFilter::Corrupted => {
+                matches!(entry.verify(), CacheEntryStatus::Corrupted(_))
+            }
+        }
+    }
+}
+
+/// Parses a duration such as `30m`, `12h` or `7d`
+pub fn parse_cache_age(age: &str) -> Result<Duration, String> {
+    let invalid = || format!("invalid duration '{}' (expected e.g. 30m, 12h or 7d)", age);
+    let Some((index, _)) = age.char_indices().last() else {
+        return Err(invalid());
+    };
+    let (value, unit) = age.split_at(index);
+    let value: u64 = value.parse().map_err(|_| invalid())?;
+    let seconds = match unit {
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        _ => return Err(invalid()),
+    };
+    Ok(Duration::from_secs(value * seconds))
+}
+
+fn get_requirements_cache_path(cache_location: &FileLocation) -> Result<PathBuf, String> {
+    let mut requirements_location = cache_location.clone();
+    requirements_location.append_path("requirements")?;
+    Ok(PathBuf::from(requirements_location.to_string()))
+}
+
+/// Entries of the requirements cache, sorted by contract id
+pub fn list_cache_entries(cache_location: &FileLocation) -> Result<Vec<CacheEntry>, String> {
+    let path = get_requirements_cache_path(cache_location)?;
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+    let dir =
+        fs::read_dir(&path).map_err(|e| format!("unable to read {}: {}", path.display(), e))?;
+
+    let mut entries: BTreeMap<String, CacheEntry> = BTreeMap::new();
+    for file in dir.flatten() {
+        let file_path = file.path();
+        let Ok(metadata) = file.metadata() else {
+            continue;
+        };
+        if !metadata.is_file() {
+            continue;
+        }
+        // `<address>.<contract-name>.<extension>`
+        let file_name = file.file_name().to_string_lossy().to_string();
+        let mut parts = file_name.splitn(3, '.');
+        let (Some(address), Some(name), Some(_)) = (parts.next(), parts.next(), parts.next())
+        else {
+            continue;
+        };
+        let contract_id = format!("{}.{}", address, name);
+        let modified_at = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+        let entry = entries
+            .entry(contract_id.clone())
+            .or_insert_with(|| CacheEntry {
+                contract_id,
+                files: vec![],
+                size: 0,
+                source_hash: None,
+                modified_at,
+            });
+        entry.size += metadata.len();
+        entry.modified_at = entry.modified_at.max(modified_at);
+        if file_name.ends_with(".clar") && !file_name.ends_with(".mock.clar") {
+            entry.source_hash = fs::read_to_string(&file_path)
+                .ok()
+                .map(|source| get_source_hash(&source));
+        }
+        entry.files.push(file_path);
+    }
+
+    let mut entries: Vec<CacheEntry> = entries.into_values().collect();
+    for entry in entries.iter_mut() {
+        entry.files.sort();
+    }
+    Ok(entries)
+}
+
+/// Removes the entries matching `filter`, and returns them
+pub fn clean_cache(
+    cache_location: &FileLocation,
+    filter: &CacheCleanFilter,
+) -> Result<Vec<CacheEntry>, String> {
+    let mut removed = vec![];
+    for entry in list_cache_entries(cache_location)? {
+        if !filter.matches(&entry) {
+            continue;
+        }
+        for file in entry.files.iter() {
+            fs::remove_file(file)
+                .map_err(|e| format!("unable to remove {}: {}", file.display(), e))?;
+        }
+        removed.push(entry);
+    }
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cache_age() {
+        assert_eq!(parse_cache_age("30m"), Ok(Duration::from_secs(1800)));
+        assert_eq!(parse_cache_age("12h"), Ok(Duration::from_secs(43_200)));
+        assert_eq!(parse_cache_age("7d"), Ok(Duration::from_secs(604_800)));
+        assert!(parse_cache_age("7").is_err());
+        assert!(parse_cache_age("d").is_err());
+        assert!(parse_cache_age("1w").is_err());
+    }
+}
@@ -0,0 +1,57 @@
+//! Cancellation of the generation and the execution of a deployment plan. The token is checked
+//! between two requirements, two contracts and two batches of a plan: a requirement is either
+//! fully written in the cache or not at all, a cancelled run never leaves a half written entry.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Error returned by a cancelled generation
+pub const CANCELLED_ERROR: &str = "operation cancelled";
+
+/// Shared flag, every clone of a token is cancelled with it
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+
+    /// Fails with `CANCELLED_ERROR` once the token is cancelled, to be used with `?`
+    pub fn check(&self) -> Result<(), String> {
+        if self.is_cancelled() {
+            Err(CANCELLED_ERROR.to_string())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+pub fn is_cancelled_error(error: &str) -> bool {
+    error == CANCELLED_ERROR
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cancellation_token() {
+        let token = CancellationToken::new();
+        let clone = token.clone();
+        assert!(token.check().is_ok());
+        clone.cancel();
+        assert!(token.is_cancelled());
+        let error = token.check().unwrap_err();
+        assert!(is_cancelled_error(&error));
+        assert!(CancellationToken::new().check().is_ok());
+    }
+}
@@ -0,0 +1,120 @@
+//! Blocks of code restricted to some networks, delimited by `;; #[cfg(simnet, devnet)]`
+//! and `;; #[end-cfg]`. The lines of the blocks that don't match the network of a
+//! deployment are blanked out, so that the lines of the diagnostics are unchanged.
+
+use clarinet_files::StacksNetwork;
+
+const CFG_START: &str = ";; #[cfg(";
+const CFG_END: &str = ";; #[end-cfg]";
+
+pub fn has_cfg_blocks(source: &str) -> bool {
+    source.contains(CFG_START)
+}
+
+fn parse_networks(line: &str, line_number: usize) -> Result<Vec<StacksNetwork>, String> {
+    let invalid = || format!("line {}: invalid cfg directive: {}", line_number, line);
+    let networks = line
+        .strip_prefix(CFG_START)
+        .and_then(|args| args.strip_suffix(")]"))
+        .ok_or_else(invalid)?;
+    networks
+        .split(',')
+        .map(|network| match network.trim() {
+            "simnet" => Ok(StacksNetwork::Simnet),
+            "devnet" => Ok(StacksNetwork::Devnet),
+            "testnet" => Ok(StacksNetwork::Testnet),
+            "mainnet" => Ok(StacksNetwork::Mainnet),
+            _ => Err(invalid()),
+        })
+        .collect()
+}
+
+/// Removes the code of the blocks that are not enabled on `network`
+pub fn strip_cfg_blocks(source: &str, network: &StacksNetwork) -> Result<String, String> {
+    if !has_cfg_blocks(source) {
+        return Ok(source.to_string());
+    }
+
+    let mut lines = vec![];
+    // (line of the directive, is the block enabled)
+    let mut current_block: Option<(usize, bool)> = None;
+    for (index, line) in source.lines().enumerate() {
+        let line_number = index + 1;
+        let directive = line.trim();
+        if directive.starts_with(CFG_START) {
+            if let Some((start, _)) = current_block {
+                return Err(format!(
+                    "line {}: nested cfg blocks are not supported (block opened line {})",
+                    line_number, start
+                ));
+            }
+            let networks = parse_networks(directive, line_number)?;
+            current_block = Some((line_number, networks.contains(network)));
+            lines.push(line);
+        } else if directive == CFG_END {
+            if current_block.take().is_none() {
+                return Err(format!("line {}: unexpected end-cfg", line_number));
+            }
+            lines.push(line);
+        } else if let Some((_, false)) = current_block {
+            lines.push("");
+        } else {
+            lines.push(line);
+        }
+    }
+    if let Some((start, _)) = current_block {
+        return Err(format!("line {}: cfg block is never closed", start));
+    }
+
+    let mut stripped = lines.join("\n");
+    if source.ends_with('\n') {
+        stripped.push('\n');
+    }
+    Ok(stripped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_cfg_blocks() {
+        let source = [
+            "(define-fungible-token token)",
+            ";; #[cfg(simnet, devnet)]",
+            "(define-public (test-mint (amount uint))",
+            "  (ft-mint? token amount tx-sender))",
+            ";; #[end-cfg]",
+            "(define-read-only (get-supply) (ft-get-supply token))",
+        ]
+        .join("\n");
+
+        let simnet = strip_cfg_blocks(&source, &StacksNetwork::Simnet).unwrap();
+        assert_eq!(simnet, source);
+
+        let mainnet = strip_cfg_blocks(&source, &StacksNetwork::Mainnet).unwrap();
+        let lines: Vec<&str> = mainnet.lines().collect();
+        assert_eq!(lines.len(), 6);
+        assert_eq!(lines[2], "");
+        assert_eq!(lines[3], "");
+        assert_eq!(
+            lines[5],
+            "(define-read-only (get-supply) (ft-get-supply token))"
+        );
+    }
+
+    #[test]
+    fn test_invalid_cfg_blocks() {
+        let unclosed = ";; #[cfg(simnet)]\n(define-constant A u1)";
+        assert_eq!(
+            strip_cfg_blocks(unclosed, &StacksNetwork::Mainnet),
+            Err("line 1: cfg block is never closed".to_string())
+        );
+
+        let unknown_network = ";; #[cfg(regtest)]\n;; #[end-cfg]";
+        assert!(strip_cfg_blocks(unknown_network, &StacksNetwork::Mainnet).is_err());
+
+        let nested = ";; #[cfg(simnet)]\n;; #[cfg(devnet)]\n;; #[end-cfg]\n;; #[end-cfg]";
+        assert!(strip_cfg_blocks(nested, &StacksNetwork::Mainnet).is_err());
+    }
+}
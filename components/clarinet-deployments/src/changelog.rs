@@ -0,0 +1,360 @@
+//! Markdown changelog of a plan (`clarinet deployments changelog`), for governance proposals
+//! and release announcements: the contracts published with their sources, the calls made
+//! once they are, the estimated cost, and the descriptions written in the plan.
+
+use clarinet_files::FileLocation;
+
+use crate::fee_policy::get_total_fee;
+use crate::requirements::get_source_hash;
+use crate::types::{DeploymentSpecification, TransactionSpecification};
+
+/// µSTX as STX, with the 6 decimals
+pub fn format_stx(ustx: u64) -> String {
+    format!("{}.{:06} STX", ustx / 1_000_000, ustx % 1_000_000)
+}
+
+/// Text of a cell of a table: the pipes are escaped, the lines joined
+fn escape_cell(text: &str) -> String {
+    text.replace('|', "\\|")
+        .lines()
+        .map(str::trim)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn get_source_path(location: &FileLocation, project_root: &FileLocation) -> String {
+    match location {
+        FileLocation::FileSystem { .. } => location
+            .get_relative_path_from_base(project_root)
+            .unwrap_or_else(|_| location.to_string()),
+        FileLocation::Url { .. } => location.to_string(),
+    }
+}
+
+struct PublishedContract {
+    batch: usize,
+    contract_id: String,
+    source_path: String,
+    source_hash: String,
+    cost: Option<u64>,
+    description: Option<String>,
+}
+
+struct PostDeployCall {
+    batch: usize,
+    summary: String,
+    cost: Option<u64>,
+    description: Option<String>,
+}
+
+/// Changelog of the plan, the sources are shown relative to `project_root`
+pub fn get_plan_changelog(
+    deployment: &DeploymentSpecification,
+    project_root: &FileLocation,
+) -> String {
+    let mut contracts = vec![];
+    let mut calls = vec![];
+    for batch in deployment.plan.batches.iter() {
+        for tx in batch.transactions.iter() {
+            let description = tx.get_description().cloned();
+            let (contract_id, location, cost) = match tx {
+                TransactionSpecification::ContractPublish(tx) => (
+                    format!("{}.{}", tx.expected_sender, tx.contract_name),
+                    &tx.location,
+                    Some(tx.cost),
+                ),
+                TransactionSpecification::EmulatedContractPublish(tx) => (
+                    format!("{}.{}", tx.emulated_sender, tx.contract_name),
+                    &tx.location,
+                    None,
+                ),
+                TransactionSpecification::RequirementPublish(tx) => (
+                    tx.get_remapped_contract_id().to_string(),
+                    &tx.location,
+                    Some(tx.cost),
+                ),
+                _ => {
+                    let (summary, cost) = get_call_summary(tx);
+                    calls.push(PostDeployCall {
+                        batch: batch.id,
+                        summary,
+                        cost,
+                        description,
+                    });
+                    continue;
+                }
+            };
+            let source = deployment
+                .contracts
+                .iter()
+                .find(|(_, (_, contract_location))| contract_location == location)
+                .map(|(_, (source, _))| source);
+            contracts.push(PublishedContract {
+                batch: batch.id,
+                contract_id,
+                source_path: get_source_path(location, project_root),
+                source_hash: source
+                    .map(|source| get_source_hash(source))
+                    .unwrap_or_default(),
+                cost,
+                description,
+            });
+        }
+    }
+
+    let mut changelog = format!("# {}\n\n", deployment.name);
+    if let Some(ref description) = deployment.description {
+        changelog.push_str(&format!("{}\n\n", description.trim()));
+    }
+    changelog.push_str(&format!("- Network: {:?}\n", deployment.network));
+    changelog.push_str(&format!("- Contracts published: {}\n", contracts.len()));
+    changelog.push_str(&format!("- Post-deploy calls: {}\n", calls.len()));
+    changelog.push_str(&format!(
+        "- Estimated cost: {}\n",
+        format_stx(get_total_fee(deployment))
+    ));
+
+    let batches_descriptions: Vec<_> = deployment
+        .plan
+        .batches
+        .iter()
+        .filter_map(|batch| Some((batch, batch.description.as_ref()?)))
+        .collect();
+    if !batches_descriptions.is_empty() {
+        changelog.push_str("\n## Batches\n\n");
+        for (batch, description) in batches_descriptions {
+            let epoch = batch
+                .epoch
+                .map(|epoch| format!(" (epoch {})", epoch))
+                .unwrap_or_default();
+            changelog.push_str(&format!(
+                "- Batch {}{}: {}\n",
+                batch.id,
+                epoch,
+                escape_cell(description)
+            ));
+        }
+    }
+
+    if !contracts.is_empty() {
+        changelog.push_str("\n## Contracts\n\n");
+        changelog.push_str("| Batch | Contract | Source | SHA-256 | Cost | Notes |\n");
+        changelog.push_str("|---|---|---|---|---|---|\n");
+        for contract in contracts.iter() {
+            changelog.push_str(&format!(
+                "| {} | `{}` | `{}` | `{}` | {} | {} |\n",
+                contract.batch,
+                contract.contract_id,
+                contract.source_path,
+                &contract.source_hash[..contract.source_hash.len().min(16)],
+                contract
+                    .cost
+                    .map(format_stx)
+                    .unwrap_or_else(|| "-".to_string()),
+                escape_cell(contract.description.as_deref().unwrap_or_default()),
+            ));
+        }
+    }
+
+    if !calls.is_empty() {
+        changelog.push_str("\n## Post-deploy calls\n\n");
+        for (index, call) in calls.iter().enumerate() {
+            let cost = call
+                .cost
+                .map(|cost| format!(", {}", format_stx(cost)))
+                .unwrap_or_default();
+            changelog.push_str(&format!(
+                "{}. {} (batch {}{})\n",
+                index + 1,
+                call.summary,
+                call.batch,
+                cost
+            ));
+            if let Some(ref description) = call.description {
+                for line in description.trim().lines() {
+                    changelog.push_str(&format!("   {}\n", line));
+                }
+            }
+        }
+    }
+
+    if !deployment.plan.post_checks.is_empty() {
+        changelog.push_str(&format!(
+            "\nThe deployment is verified by {} post-deploy check(s).\n",
+            deployment.plan.post_checks.len()
+        ));
+    }
+    changelog
+}
+
+/// What a transaction other than a publish does, and its cost if it's broadcasted
+fn get_call_summary(tx: &TransactionSpecification) -> (String, Option<u64>) {
+    match tx {
+        TransactionSpecification::ContractCall(tx) => (
+            format!(
+                "`({} {})` on `{}`, by `{}`",
+                tx.method,
+                tx.parameters.join(" "),
+                tx.contract_id,
+                tx.expected_sender
+            )
+            .replace(" )`", ")`"),
+            Some(tx.cost),
+        ),
+        TransactionSpecification::EmulatedContractCall(tx) => (
+            format!(
+                "`({} {})` on `{}`, by `{}`",
+                tx.method,
+                tx.parameters.join(" "),
+                tx.contract_id,
+                tx.emulated_sender
+            )
+            .replace(" )`", ")`"),
+            None,
+        ),
+        TransactionSpecification::EmulatedEval(tx) => (
+            format!(
+                "evaluation of `{}`, by `{}`",
+                escape_cell(&tx.code),
+                tx.emulated_sender
+            ),
+            None,
+        ),
+        TransactionSpecification::StxTransfer(tx) => (
+            format!(
+                "transfer of {} from `{}` to `{}`",
+                format_stx(tx.mstx_amount),
+                tx.expected_sender,
+                tx.recipient
+            ),
+            Some(tx.cost),
+        ),
+        TransactionSpecification::BtcTransfer(tx) => (
+            format!(
+                "transfer of {} sats from `{}` to `{}`",
+                tx.sats_amount, tx.expected_sender, tx.recipient
+            ),
+            None,
+        ),
+        TransactionSpecification::BnsOperation(tx) => (
+            format!(
+                "BNS `{}` of `{}`, by `{}`",
+                tx.get_method(),
+                tx.get_fully_qualified_name(),
+                tx.expected_sender
+            ),
+            Some(tx.cost),
+        ),
+        TransactionSpecification::ContractPublish(_)
+        | TransactionSpecification::EmulatedContractPublish(_)
+        | TransactionSpecification::RequirementPublish(_) => unreachable!(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use clarinet_files::StacksNetwork;
+    use clarity_repl::clarity::vm::types::QualifiedContractIdentifier;
+    use clarity_repl::clarity::{ClarityName, ClarityVersion, ContractName};
+
+    use super::*;
+    use crate::types::{
+        AnchorMode, ContractCallSpecification, ContractPublishSpecification,
+        TransactionPlanSpecification, TransactionsBatchSpecification,
+    };
+
+    #[test]
+    fn test_plan_changelog() {
+        let contract_id =
+            QualifiedContractIdentifier::parse("ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM.counter")
+                .unwrap();
+        let sender = contract_id.issuer.clone();
+        let location = FileLocation::from_path_string("/project/contracts/counter.clar").unwrap();
+        let source = "(define-data-var count uint u0)".to_string();
+        let publish = TransactionSpecification::ContractPublish(ContractPublishSpecification {
+            contract_name: ContractName::try_from("counter".to_string()).unwrap(),
+            expected_sender: sender.clone(),
+            location: location.clone(),
+            source: source.clone(),
+            clarity_version: ClarityVersion::Clarity2,
+            cost: 41_000,
+            anchor_mode: AnchorMode::OnChainOnly,
+            description: Some("Counter | with the overflow fix".to_string()),
+        });
+        let call = TransactionSpecification::ContractCall(ContractCallSpecification {
+            contract_id: contract_id.clone(),
+            expected_sender: sender,
+            method: ClarityName::try_from("set-owner".to_string()).unwrap(),
+            parameters: vec!["'ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM".to_string()],
+            cost: 1_500_000,
+            anchor_mode: AnchorMode::OnChainOnly,
+            description: Some("Hand over the ownership".to_string()),
+        });
+        let deployment = DeploymentSpecification {
+            id: 0,
+            name: "Release 1.2".to_string(),
+            description: Some("Fixes the overflow of the counter.".to_string()),
+            network: StacksNetwork::Testnet,
+            stacks_node: None,
+            bitcoin_node: None,
+            genesis: None,
+            plan: TransactionPlanSpecification {
+                batches: vec![
+                    TransactionsBatchSpecification {
+                        id: 0,
+                        description: Some("Contracts".to_string()),
+                        transactions: vec![publish],
+                        epoch: None,
+                        post_checks: vec![],
+                    },
+                    TransactionsBatchSpecification {
+                        id: 1,
+                        description: None,
+                        transactions: vec![call],
+                        epoch: None,
+                        post_checks: vec![],
+                    },
+                ],
+                post_checks: vec![],
+            },
+            contracts: BTreeMap::from([(contract_id, (source.clone(), location))]),
+        };
+
+        let project_root = FileLocation::from_path_string("/project").unwrap();
+        let changelog = get_plan_changelog(&deployment, &project_root);
+        let hash = get_source_hash(&source);
+        assert_eq!(
+            changelog,
+            format!(
+                "# Release 1.2
+
+Fixes the overflow of the counter.
+
+- Network: Testnet
+- Contracts published: 1
+- Post-deploy calls: 1
+- Estimated cost: 1.541000 STX
+
+## Batches
+
+- Batch 0: Contracts
+
+## Contracts
+
+| Batch | Contract | Source | SHA-256 | Cost | Notes |
+|---|---|---|---|---|---|
+| 0 | `ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM.counter` | `contracts/counter.clar` | `{}` | 0.041000 STX | Counter \\| with the overflow fix |
+
+## Post-deploy calls
+
+1. `(set-owner 'ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM)` on `ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM.counter`, by `ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM` (batch 1, 1.500000 STX)
+   Hand over the ownership
+",
+                &hash[..16]
+            )
+        );
+        assert_eq!(format_stx(5), "0.000005 STX");
+    }
+}
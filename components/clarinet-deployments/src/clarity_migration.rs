@@ -0,0 +1,227 @@
+//! Migration of the Clarity 1 and 2 contracts to Clarity 3. The keywords and functions
+//! removed in Clarity 3 are rewritten with their epoch 3.0 equivalents, the names reserved
+//! by Clarity 3 and the expressions behaving differently since the epoch 3.0 are flagged,
+//! they have to be reviewed by hand.
+
+use std::ops::Range;
+
+use clarity_repl::clarity::vm::representations::Span;
+use clarity_repl::clarity::vm::SymbolicExpression;
+
+/// Names of the keywords and functions introduced by Clarity 3
+pub const CLARITY3_RESERVED_NAMES: &[&str] = &[
+    "stacks-block-height",
+    "tenure-height",
+    "get-stacks-block-info?",
+    "get-tenure-info?",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MigrationIssueKind {
+    /// Keyword or function removed in Clarity 3, rewritten
+    Removed,
+    /// Name of a definition or of a binding reserved by Clarity 3
+    ReservedName,
+    /// Expression behaving differently since the epoch 3.0
+    BehaviorChange,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MigrationIssue {
+    pub kind: MigrationIssueKind,
+    pub line: u32,
+    pub column: u32,
+    pub message: String,
+    /// Text replacing the flagged expression, for the mechanical changes
+    pub replacement: Option<String>,
+    #[serde(skip)]
+    pub span: Span,
+}
+
+fn block_info_replacement(property: &str) -> &'static str {
+    match property {
+        "header-hash" | "id-header-hash" => "get-stacks-block-info?",
+        _ => "get-tenure-info?",
+    }
+}
+
+fn collect_migration_issues(expression: &SymbolicExpression, issues: &mut Vec<MigrationIssue>) {
+    if let Some(atom) = expression.match_atom() {
+        let issue = match atom.as_str() {
+            "block-height" => Some(MigrationIssue {
+                kind: MigrationIssueKind::Removed,
+                line: expression.span.start_line,
+                column: expression.span.start_column,
+                message: "block-height is removed, tenure-height has the same value since the epoch 3.0, stacks-block-height counts the (faster) Stacks blocks".to_string(),
+                replacement: Some("tenure-height".to_string()),
+                span: expression.span.clone(),
+            }),
+            name if CLARITY3_RESERVED_NAMES.contains(&name) => Some(MigrationIssue {
+                kind: MigrationIssueKind::ReservedName,
+                line: expression.span.start_line,
+                column: expression.span.start_column,
+                message: format!("{} is reserved by Clarity 3 and has to be renamed", name),
+                replacement: None,
+                span: expression.span.clone(),
+            }),
+            _ => None,
+        };
+        issues.extend(issue);
+        return;
+    }
+
+    let Some(list) = expression.match_list() else {
+        return;
+    };
+    match list {
+        [keyword, property, ..]
+            if keyword.match_atom().map(|a| a.as_str()) == Some("get-block-info?") =>
+        {
+            let property = property
+                .match_atom()
+                .map(|a| a.to_string())
+                .unwrap_or_default();
+            let function = block_info_replacement(&property);
+            issues.push(MigrationIssue {
+                kind: MigrationIssueKind::Removed,
+                line: keyword.span.start_line,
+                column: keyword.span.start_column,
+                message: format!(
+                    "get-block-info? is removed, {} returns {} (the height argument is a Stacks block height with get-stacks-block-info? and a tenure height with get-tenure-info?)",
+                    function, property
+                ),
+                replacement: Some(function.to_string()),
+                span: keyword.span.clone(),
+            });
+        }
+        [keyword, ..] if keyword.match_atom().map(|a| a.as_str()) == Some("at-block") => {
+            issues.push(MigrationIssue {
+                kind: MigrationIssueKind::BehaviorChange,
+                line: keyword.span.start_line,
+                column: keyword.span.start_column,
+                message: "at-block refers to a Stacks block since the epoch 3.0, blocks are produced several times per tenure".to_string(),
+                replacement: None,
+                span: keyword.span.clone(),
+            });
+        }
+        _ => {}
+    }
+    for expression in list {
+        collect_migration_issues(expression, issues);
+    }
+}
+
+/// Lists the expressions of a Clarity 1 or 2 contract to change for Clarity 3
+pub fn get_migration_issues(expressions: &[SymbolicExpression]) -> Vec<MigrationIssue> {
+    let mut issues = vec![];
+    for expression in expressions {
+        collect_migration_issues(expression, &mut issues);
+    }
+    issues.sort_by_key(|issue| (issue.line, issue.column));
+    issues
+}
+
+/// Byte offset of a line and a column (both starting at 1, columns counting characters)
+fn get_offset(source: &str, line: u32, column: u32) -> Option<usize> {
+    let mut current_line = 1;
+    let mut current_column = 1;
+    for (offset, c) in source.char_indices() {
+        if current_line == line && current_column == column {
+            return Some(offset);
+        }
+        if c == '\n' {
+            current_line += 1;
+            current_column = 1;
+        } else {
+            current_column += 1;
+        }
+    }
+    None
+}
+
+/// Byte range of the source of an expression
+pub(crate) fn get_span_range(source: &str, span: &Span) -> Option<Range<usize>> {
+    let start = get_offset(source, span.start_line, span.start_column)?;
+    let end = get_offset(source, span.end_line, span.end_column).and_then(|offset| {
+        source[offset..]
+            .chars()
+            .next()
+            .map(|c| offset + c.len_utf8())
+    })?;
+    (start < end).then_some(start..end)
+}
+
+/// Applies the replacements of the mechanical changes to the source of the contract
+pub fn apply_migration(source: &str, issues: &[MigrationIssue]) -> Result<String, String> {
+    let mut migrated = source.to_string();
+    // from the end of the source, for the offsets of the next replacements to stay valid
+    for issue in issues.iter().rev() {
+        let Some(ref replacement) = issue.replacement else {
+            continue;
+        };
+        let Some(range) = get_span_range(source, &issue.span) else {
+            return Err(format!(
+                "unable to locate the expression at line {}, column {}",
+                issue.line, issue.column
+            ));
+        };
+        migrated.replace_range(range, replacement);
+    }
+    Ok(migrated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clarity_repl::clarity::ast::{build_ast_with_rules, ASTRules};
+    use clarity_repl::clarity::vm::types::QualifiedContractIdentifier;
+    use clarity_repl::clarity::vm::ClarityVersion;
+    use clarity_repl::clarity::StacksEpochId;
+
+    #[test]
+    fn test_migrate_to_clarity3() {
+        let source = "(define-data-var tenure-height uint u0)
+(define-read-only (get-info)
+  (list block-height (unwrap-panic (get-block-info? time u1))))
+(define-read-only (get-hash (height uint))
+  (get-block-info? id-header-hash height))
+(define-read-only (get-past-value)
+  (at-block 0x01 (var-get tenure-height)))
+";
+        let expressions = build_ast_with_rules(
+            &QualifiedContractIdentifier::transient(),
+            source,
+            &mut (),
+            ClarityVersion::Clarity2,
+            StacksEpochId::Epoch25,
+            ASTRules::Typical,
+        )
+        .unwrap()
+        .expressions;
+
+        let issues = get_migration_issues(&expressions);
+        let kinds: Vec<(u32, MigrationIssueKind)> = issues
+            .iter()
+            .map(|issue| (issue.line, issue.kind))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (1, MigrationIssueKind::ReservedName),
+                (3, MigrationIssueKind::Removed),
+                (3, MigrationIssueKind::Removed),
+                (5, MigrationIssueKind::Removed),
+                (7, MigrationIssueKind::BehaviorChange),
+                (7, MigrationIssueKind::ReservedName),
+            ]
+        );
+
+        let migrated = apply_migration(source, &issues).unwrap();
+        assert!(
+            migrated.contains("(list tenure-height (unwrap-panic (get-tenure-info? time u1))))")
+        );
+        assert!(migrated.contains("(get-stacks-block-info? id-header-hash height))"));
+        assert!(migrated.contains("(define-data-var tenure-height uint u0)"));
+    }
+}
@@ -0,0 +1,453 @@
+//! Programmatic construction of deployment plans, for the tests and the tools (such as
+//! stacks-devnet-js) building a plan without a Clarinet.toml. The transactions are added to
+//! the current batch, `epoch` and `batch` start a new one, and the plan is validated by
+//! `build`.
+
+use std::collections::BTreeMap;
+
+use clarinet_files::{FileLocation, StacksNetwork, DEFAULT_DEPLOYMENT_FEE_RATE};
+use clarity_repl::clarity::vm::types::{
+    PrincipalData, QualifiedContractIdentifier, StandardPrincipalData,
+};
+use clarity_repl::clarity::{ClarityName, ClarityVersion, ContractName, StacksEpochId};
+use clarity_repl::repl::{clarity_versions, DEFAULT_EPOCH};
+
+use crate::types::{
+    AnchorMode, ContractCallSpecification, ContractPublishSpecification, DeploymentSpecification,
+    EmulatedContractCallSpecification, EmulatedContractPublishSpecification, EpochSpec,
+    GenesisSpecification, StxTransferSpecification, TransactionPlanSpecification,
+    TransactionSpecification, TransactionsBatchSpecification,
+};
+
+/// Cost of the contract calls and transfers, when not set with `cost`
+const DEFAULT_TRANSACTION_COST: u64 = 10_000;
+
+impl DeploymentSpecification {
+    pub fn builder() -> DeploymentSpecificationBuilder {
+        DeploymentSpecificationBuilder::default()
+    }
+}
+
+/// Builds a validated `DeploymentSpecification`. The network defaults to simnet, where the
+/// contracts are published and called with emulated transactions, and the epoch to
+/// `DEFAULT_EPOCH`; the network is to be set before adding the transactions. The errors of the
+/// setters are reported by `build`.
+#[derive(Debug, Clone)]
+pub struct DeploymentSpecificationBuilder {
+    name: Option<String>,
+    network: StacksNetwork,
+    stacks_node: Option<String>,
+    bitcoin_node: Option<String>,
+    genesis: Option<GenesisSpecification>,
+    sender: Option<StandardPrincipalData>,
+    fee_rate: u64,
+    cost: u64,
+    batches: Vec<(Option<EpochSpec>, Vec<TransactionSpecification>)>,
+    contracts: BTreeMap<QualifiedContractIdentifier, (String, FileLocation)>,
+    errors: Vec<String>,
+}
+
+impl Default for DeploymentSpecificationBuilder {
+    fn default() -> Self {
+        Self {
+            name: None,
+            network: StacksNetwork::Simnet,
+            stacks_node: None,
+            bitcoin_node: None,
+            genesis: None,
+            sender: None,
+            fee_rate: DEFAULT_DEPLOYMENT_FEE_RATE,
+            cost: DEFAULT_TRANSACTION_COST,
+            batches: vec![(None, vec![])],
+            contracts: BTreeMap::new(),
+            errors: vec![],
+        }
+    }
+}
+
+impl DeploymentSpecificationBuilder {
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    pub fn network(mut self, network: StacksNetwork) -> Self {
+        self.network = network;
+        self
+    }
+
+    pub fn stacks_node(mut self, url: impl Into<String>) -> Self {
+        self.stacks_node = Some(url.into());
+        self
+    }
+
+    pub fn bitcoin_node(mut self, url: impl Into<String>) -> Self {
+        self.bitcoin_node = Some(url.into());
+        self
+    }
+
+    pub fn genesis(mut self, genesis: GenesisSpecification) -> Self {
+        self.genesis = Some(genesis);
+        self
+    }
+
+    /// Sender of the transactions added next
+    pub fn sender(mut self, address: &str) -> Self {
+        match PrincipalData::parse_standard_principal(address) {
+            Ok(sender) => self.sender = Some(sender),
+            Err(_) => self.errors.push(format!("invalid sender {}", address)),
+        }
+        self
+    }
+
+    /// µSTX per byte paid by the contracts published next
+    pub fn fee_rate(mut self, fee_rate: u64) -> Self {
+        self.fee_rate = fee_rate;
+        self
+    }
+
+    /// µSTX paid by the contract calls and transfers added next
+    pub fn cost(mut self, cost: u64) -> Self {
+        self.cost = cost;
+        self
+    }
+
+    /// Starts a batch in `epoch`
+    pub fn epoch(mut self, epoch: EpochSpec) -> Self {
+        self.batches.push((Some(epoch), vec![]));
+        self
+    }
+
+    /// Starts a batch in the epoch of the current one
+    pub fn batch(mut self) -> Self {
+        let epoch = self.current_epoch();
+        self.batches.push((epoch, vec![]));
+        self
+    }
+
+    fn current_epoch(&self) -> Option<EpochSpec> {
+        self.batches.last().and_then(|(epoch, _)| *epoch)
+    }
+
+    fn get_sender(&mut self, transaction: &str) -> Option<StandardPrincipalData> {
+        if self.sender.is_none() {
+            self.errors.push(format!(
+                "{}: no sender set, call sender() first",
+                transaction
+            ));
+        }
+        self.sender.clone()
+    }
+
+    /// Adds a transaction to the current batch, as is
+    pub fn transaction(mut self, transaction: TransactionSpecification) -> Self {
+        if let Some((_, transactions)) = self.batches.last_mut() {
+            transactions.push(transaction);
+        }
+        self
+    }
+
+    /// Publishes a contract with the default Clarity version of the epoch of the batch
+    pub fn publish(self, contract_name: &str, source: impl Into<String>) -> Self {
+        let epoch = self
+            .current_epoch()
+            .map_or(DEFAULT_EPOCH, StacksEpochId::from);
+        self.publish_with_version(
+            contract_name,
+            source,
+            ClarityVersion::default_for_epoch(epoch),
+        )
+    }
+
+    pub fn publish_with_version(
+        mut self,
+        contract_name: &str,
+        source: impl Into<String>,
+        clarity_version: ClarityVersion,
+    ) -> Self {
+        let source = source.into();
+        let Some(sender) = self.get_sender(&format!("publish {}", contract_name)) else {
+            return self;
+        };
+        let Ok(name) = ContractName::try_from(contract_name.to_string()) else {
+            self.errors
+                .push(format!("invalid contract name {}", contract_name));
+            return self;
+        };
+        let location =
+            match FileLocation::from_path_string(&format!("contracts/{}.clar", contract_name)) {
+                Ok(location) => location,
+                Err(e) => {
+                    self.errors.push(e);
+                    return self;
+                }
+            };
+        let contract_id = QualifiedContractIdentifier::new(sender.clone(), name.clone());
+        if self.contracts.contains_key(&contract_id) {
+            self.errors
+                .push(format!("{} is published twice", contract_id));
+            return self;
+        }
+        self.contracts
+            .insert(contract_id, (source.clone(), location.clone()));
+
+        let transaction = if matches!(self.network, StacksNetwork::Simnet) {
+            TransactionSpecification::EmulatedContractPublish(
+                EmulatedContractPublishSpecification {
+                    contract_name: name,
+                    emulated_sender: sender,
+                    source,
+                    clarity_version,
+                    location,
+                    description: None,
+                },
+            )
+        } else {
+            TransactionSpecification::ContractPublish(ContractPublishSpecification {
+                contract_name: name,
+                expected_sender: sender,
+                location,
+                cost: self.fee_rate.saturating_mul(source.len() as u64),
+                source,
+                clarity_version,
+                anchor_mode: AnchorMode::OnChainOnly,
+                description: None,
+            })
+        };
+        self.transaction(transaction)
+    }
+
+    /// Calls a public function, `parameters` being Clarity expressions (`u1`, `'ST1...`)
+    pub fn call(mut self, contract_id: &str, method: &str, parameters: &[&str]) -> Self {
+        let Some(sender) = self.get_sender(&format!("call {}::{}", contract_id, method)) else {
+            return self;
+        };
+        let Ok(contract_id) = QualifiedContractIdentifier::parse(contract_id) else {
+            self.errors
+                .push(format!("invalid contract id {}", contract_id));
+            return self;
+        };
+        let Ok(method) = ClarityName::try_from(method.to_string()) else {
+            self.errors
+                .push(format!("invalid function name {}", method));
+            return self;
+        };
+        let parameters = parameters.iter().map(|arg| arg.to_string()).collect();
+        let transaction = if matches!(self.network, StacksNetwork::Simnet) {
+            TransactionSpecification::EmulatedContractCall(EmulatedContractCallSpecification {
+                contract_id,
+                emulated_sender: sender,
+                method,
+                parameters,
+                description: None,
+            })
+        } else {
+            TransactionSpecification::ContractCall(ContractCallSpecification {
+                contract_id,
+                expected_sender: sender,
+                method,
+                parameters,
+                cost: self.cost,
+                anchor_mode: AnchorMode::Any,
+                description: None,
+            })
+        };
+        self.transaction(transaction)
+    }
+
+    pub fn stx_transfer(mut self, recipient: &str, mstx_amount: u64) -> Self {
+        let Some(sender) = self.get_sender(&format!("transfer to {}", recipient)) else {
+            return self;
+        };
+        let Ok(recipient) = PrincipalData::parse(recipient) else {
+            self.errors.push(format!("invalid recipient {}", recipient));
+            return self;
+        };
+        let transaction = TransactionSpecification::StxTransfer(StxTransferSpecification {
+            expected_sender: sender,
+            recipient,
+            mstx_amount,
+            memo: [0u8; 34],
+            cost: self.cost,
+            anchor_mode: AnchorMode::Any,
+            description: None,
+        });
+        self.transaction(transaction)
+    }
+
+    /// Checks that the epochs of the batches don't decrease, that the Clarity versions are
+    /// available in the epochs of their contracts, and that the contracts of the plan are
+    /// published before being called. The empty batches are dropped.
+    pub fn build(self) -> Result<DeploymentSpecification, String> {
+        if let Some(error) = self.errors.into_iter().next() {
+            return Err(error);
+        }
+
+        let mut batches: Vec<TransactionsBatchSpecification> = vec![];
+        let mut published = vec![];
+        let mut previous_epoch: Option<EpochSpec> = None;
+        for (epoch, transactions) in self.batches.into_iter() {
+            if transactions.is_empty() {
+                continue;
+            }
+            let id = batches.len();
+            if let (Some(previous), Some(epoch)) = (previous_epoch, epoch) {
+                if epoch < previous {
+                    return Err(format!(
+                        "batch {} is in epoch {}, after a batch in epoch {}",
+                        id, epoch, previous
+                    ));
+                }
+            }
+            previous_epoch = epoch.or(previous_epoch);
+            let batch_epoch = epoch.unwrap_or(EpochSpec::from(DEFAULT_EPOCH));
+
+            for transaction in transactions.iter() {
+                let (publish, clarity_version) = match transaction {
+                    TransactionSpecification::ContractPublish(tx) => (
+                        Some(QualifiedContractIdentifier::new(
+                            tx.expected_sender.clone(),
+                            tx.contract_name.clone(),
+                        )),
+                        Some(tx.clarity_version),
+                    ),
+                    TransactionSpecification::EmulatedContractPublish(tx) => (
+                        Some(QualifiedContractIdentifier::new(
+                            tx.emulated_sender.clone(),
+                            tx.contract_name.clone(),
+                        )),
+                        Some(tx.clarity_version),
+                    ),
+                    _ => (None, None),
+                };
+                if let (Some(contract_id), Some(clarity_version)) = (publish, clarity_version) {
+                    if !clarity_versions::is_supported(batch_epoch.into(), clarity_version) {
+                        return Err(format!(
+                            "batch {}: {} can't be published with {} in epoch {}",
+                            id, contract_id, clarity_version, batch_epoch
+                        ));
+                    }
+                    published.push(contract_id);
+                    continue;
+                }
+
+                let called = match transaction {
+                    TransactionSpecification::ContractCall(tx) => &tx.contract_id,
+                    TransactionSpecification::EmulatedContractCall(tx) => &tx.contract_id,
+                    _ => continue,
+                };
+                // the contracts published outside of the plan can't be checked
+                if self.contracts.contains_key(called) && !published.contains(called) {
+                    return Err(format!(
+                        "batch {}: {} is called before being published",
+                        id, called
+                    ));
+                }
+            }
+            batches.push(TransactionsBatchSpecification {
+                id,
+                transactions,
+                epoch,
+                post_checks: vec![],
+                description: None,
+            });
+        }
+
+        Ok(DeploymentSpecification {
+            id: 0,
+            name: self.name.unwrap_or("Deployment".to_string()),
+            network: self.network,
+            stacks_node: self.stacks_node,
+            bitcoin_node: self.bitcoin_node,
+            genesis: self.genesis,
+            plan: TransactionPlanSpecification {
+                batches,
+                post_checks: vec![],
+            },
+            contracts: self.contracts,
+            description: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEPLOYER: &str = "ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM";
+
+    #[test]
+    fn test_deployment_specification_builder() {
+        let deployment = DeploymentSpecification::builder()
+            .network(StacksNetwork::Devnet)
+            .sender(DEPLOYER)
+            .epoch(EpochSpec::Epoch2_1)
+            .publish("counter", "(define-data-var count uint u0)")
+            .batch()
+            .call(&format!("{}.counter", DEPLOYER), "increment", &["u1"])
+            .epoch(EpochSpec::Epoch3_0)
+            .stx_transfer("ST2CY5V39NHDPWSXMW9QDT3HC3GD6Q6XX4CFRK9AG", 1_000)
+            .build()
+            .unwrap();
+        let batches = &deployment.plan.batches;
+        assert_eq!(batches.len(), 3);
+        assert_eq!(batches[1].id, 1);
+        assert_eq!(batches[1].epoch, Some(EpochSpec::Epoch2_1));
+        let TransactionSpecification::ContractPublish(ref publish) = batches[0].transactions[0]
+        else {
+            panic!("expected a contract publish");
+        };
+        assert_eq!(publish.clarity_version, ClarityVersion::Clarity2);
+        assert_eq!(publish.cost, 310);
+        assert_eq!(deployment.contracts.len(), 1);
+
+        // the simnet plans are emulated
+        let simnet = DeploymentSpecification::builder()
+            .sender(DEPLOYER)
+            .publish("counter", "(define-data-var count uint u0)")
+            .build()
+            .unwrap();
+        assert!(matches!(
+            simnet.plan.batches[0].transactions[0],
+            TransactionSpecification::EmulatedContractPublish(_)
+        ));
+
+        let errors = [
+            DeploymentSpecification::builder()
+                .publish("counter", "")
+                .build(),
+            DeploymentSpecification::builder()
+                .sender(DEPLOYER)
+                .call(&format!("{}.counter", DEPLOYER), "increment", &[])
+                .publish("counter", "")
+                .build(),
+            DeploymentSpecification::builder()
+                .sender(DEPLOYER)
+                .epoch(EpochSpec::Epoch2_05)
+                .publish_with_version("counter", "", ClarityVersion::Clarity2)
+                .build(),
+            DeploymentSpecification::builder()
+                .sender(DEPLOYER)
+                .epoch(EpochSpec::Epoch3_0)
+                .publish("counter", "")
+                .epoch(EpochSpec::Epoch2_5)
+                .publish("token", "")
+                .build(),
+        ];
+        assert_eq!(
+            errors.map(|result| result.unwrap_err()),
+            [
+                "publish counter: no sender set, call sender() first".to_string(),
+                format!(
+                    "batch 0: {}.counter is called before being published",
+                    DEPLOYER
+                ),
+                format!(
+                    "batch 0: {}.counter can't be published with Clarity 2 in epoch 2.05",
+                    DEPLOYER
+                ),
+                "batch 1 is in epoch 2.5, after a batch in epoch 3.0".to_string(),
+            ]
+        );
+    }
+}
@@ -2,10 +2,16 @@ use std::collections::BTreeMap;
 
 use clarinet_files::{FileLocation, StacksNetwork};
 use clarity_repl::clarity::{
-    vm::types::QualifiedContractIdentifier, ClarityName, ClarityVersion, ContractName,
+    vm::types::{PrincipalData, QualifiedContractIdentifier},
+    ClarityName, ClarityVersion, ContractName, Value,
 };
+use clarity_repl::repl::{Session, SessionSettings};
 
+use crate::cancellation::CancellationToken;
 use crate::types::*;
+use crate::{
+    update_session_with_deployment_plan, FailedTransactionSummary, PlanExecutionHook, PlanExecutor,
+};
 
 fn get_test_txs() -> (TransactionSpecification, TransactionSpecification) {
     let contract_id =
@@ -20,6 +26,7 @@ fn get_test_txs() -> (TransactionSpecification, TransactionSpecification) {
             location: FileLocation::from_path_string("/contracts/test.clar").unwrap(),
             source: "(ok true)".to_string(),
             clarity_version: ClarityVersion::Clarity2,
+            description: None,
         });
 
     let contract_call_txs =
@@ -31,6 +38,7 @@ fn get_test_txs() -> (TransactionSpecification, TransactionSpecification) {
             emulated_sender: tx_sender.clone(),
             method: ClarityName::try_from("test".to_string()).unwrap(),
             parameters: vec![],
+            description: None,
         });
 
     (contract_publish_tx, contract_call_txs)
@@ -47,7 +55,11 @@ fn build_test_deployement_plan(
         bitcoin_node: None,
         genesis: None,
         contracts: BTreeMap::new(),
-        plan: TransactionPlanSpecification { batches },
+        plan: TransactionPlanSpecification {
+            batches,
+            post_checks: vec![],
+        },
+        description: None,
     }
 }
 
@@ -60,11 +72,15 @@ fn test_extract_no_contract_publish_txs() {
             id: 0,
             transactions: vec![contract_publish_tx.clone()],
             epoch: Some(EpochSpec::Epoch2_4),
+            post_checks: vec![],
+            description: None,
         },
         TransactionsBatchSpecification {
             id: 1,
             transactions: vec![contract_call_txs.clone()],
             epoch: Some(EpochSpec::Epoch2_4),
+            post_checks: vec![],
+            description: None,
         },
     ]);
 
@@ -76,6 +92,8 @@ fn test_extract_no_contract_publish_txs() {
             id: 0,
             transactions: vec![contract_publish_tx.clone()],
             epoch: Some(EpochSpec::Epoch2_4),
+            post_checks: vec![],
+            description: None,
         },])
     );
 
@@ -85,6 +103,8 @@ fn test_extract_no_contract_publish_txs() {
             id: 1,
             transactions: vec![contract_call_txs.clone()],
             epoch: Some(EpochSpec::Epoch2_4),
+            post_checks: vec![],
+            description: None,
         }]
     );
 }
@@ -98,11 +118,15 @@ fn test_merge_batches() {
             id: 0,
             transactions: vec![contract_publish_tx.clone()],
             epoch: Some(EpochSpec::Epoch2_4),
+            post_checks: vec![],
+            description: None,
         },
         TransactionsBatchSpecification {
             id: 1,
             transactions: vec![contract_call_txs.clone()],
             epoch: Some(EpochSpec::Epoch2_4),
+            post_checks: vec![],
+            description: None,
         },
     ]);
 
@@ -114,3 +138,471 @@ fn test_merge_batches() {
 
     assert_eq!(plan, new_plan);
 }
+
+#[test]
+fn test_keep_descriptions() {
+    let (contract_publish_tx, contract_call_txs) = get_test_txs();
+    let batch = |id, tx: &TransactionSpecification| TransactionsBatchSpecification {
+        id,
+        transactions: vec![tx.clone()],
+        epoch: Some(EpochSpec::Epoch2_4),
+        post_checks: vec![],
+        description: None,
+    };
+
+    let mut previous = build_test_deployement_plan(vec![
+        batch(0, &contract_publish_tx),
+        batch(1, &contract_call_txs),
+    ]);
+    previous.description = Some("Release 1.2".to_string());
+    previous.plan.batches[0].description = Some("Contracts".to_string());
+    previous.plan.batches[1].transactions[0].set_description("Initialize".to_string());
+
+    let mut regenerated = build_test_deployement_plan(vec![
+        batch(0, &contract_publish_tx),
+        batch(1, &contract_call_txs),
+    ]);
+    regenerated.keep_descriptions(&previous);
+    assert_eq!(regenerated, previous);
+
+    // a call with other arguments is another transaction
+    let TransactionSpecification::EmulatedContractCall(mut other_call) = contract_call_txs else {
+        unreachable!()
+    };
+    other_call.parameters = vec!["u1".to_string()];
+    let mut regenerated = build_test_deployement_plan(vec![batch(
+        0,
+        &TransactionSpecification::EmulatedContractCall(other_call),
+    )]);
+    regenerated.keep_descriptions(&previous);
+    assert_eq!(
+        regenerated.plan.batches[0].transactions[0].get_description(),
+        None
+    );
+    assert_eq!(
+        regenerated.plan.batches[0].description,
+        Some("Contracts".to_string())
+    );
+}
+
+#[test]
+fn test_update_session_execution_summary() {
+    let (contract_publish_tx, contract_call_txs) = get_test_txs();
+
+    let plan = build_test_deployement_plan(vec![
+        TransactionsBatchSpecification {
+            id: 0,
+            transactions: vec![contract_publish_tx],
+            epoch: Some(EpochSpec::Epoch2_4),
+            post_checks: vec![],
+            description: None,
+        },
+        TransactionsBatchSpecification {
+            id: 1,
+            transactions: vec![contract_call_txs],
+            epoch: Some(EpochSpec::Epoch2_4),
+            post_checks: vec![],
+            description: None,
+        },
+    ]);
+
+    let mut session = Session::new(SessionSettings::default());
+    let result = update_session_with_deployment_plan(&mut session, &plan, None, None);
+
+    assert_eq!(result.batches_contracts.len(), 2);
+    assert_eq!(result.batches_contracts[0].len(), 1);
+    assert!(result.batches_contracts[1].is_empty());
+    assert_eq!(result.get_batches_costs().len(), 2);
+    assert_eq!(result.get_contracts_events().len(), 1);
+
+    // the `test` method does not exist
+    let failed_transactions = result.get_failed_transactions();
+    assert_eq!(failed_transactions.len(), 1);
+    assert_eq!(failed_transactions[0].batch_id, 1);
+    assert_eq!(
+        failed_transactions[0].description,
+        "contract-call ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM.test::test"
+    );
+}
+
+#[derive(Default)]
+struct RecordingHook {
+    published: Vec<String>,
+    failures: Vec<usize>,
+}
+
+impl PlanExecutionHook for RecordingHook {
+    fn on_contract_published(
+        &mut self,
+        contract_id: &QualifiedContractIdentifier,
+        result: &Result<
+            clarity_repl::clarity::vm::ExecutionResult,
+            Vec<clarity_repl::clarity::vm::diagnostic::Diagnostic>,
+        >,
+    ) {
+        assert!(result.is_ok());
+        self.published.push(contract_id.name.to_string());
+    }
+
+    fn on_transaction_failed(&mut self, failure: &FailedTransactionSummary) {
+        self.failures.push(failure.batch_id);
+    }
+}
+
+#[test]
+fn test_plan_executor_hooks_and_costs() {
+    let (contract_publish_tx, contract_call_txs) = get_test_txs();
+
+    let plan = build_test_deployement_plan(vec![
+        TransactionsBatchSpecification {
+            id: 0,
+            transactions: vec![contract_publish_tx],
+            epoch: Some(EpochSpec::Epoch2_4),
+            post_checks: vec![],
+            description: None,
+        },
+        TransactionsBatchSpecification {
+            id: 1,
+            transactions: vec![contract_call_txs],
+            epoch: Some(EpochSpec::Epoch2_4),
+            post_checks: vec![],
+            description: None,
+        },
+    ]);
+
+    let mut hook = RecordingHook::default();
+    let mut session = Session::new(SessionSettings::default());
+    let result = PlanExecutor::new(&plan)
+        .with_costs(true)
+        .with_hook(&mut hook)
+        .execute(&mut session);
+
+    assert!(result.get_batches_costs()[0].runtime > 0);
+    assert_eq!(hook.published, vec!["test".to_string()]);
+    assert_eq!(hook.failures, vec![1]);
+}
+
+#[test]
+fn test_plan_executor_cancellation() {
+    let (contract_publish_tx, _) = get_test_txs();
+    let plan = build_test_deployement_plan(vec![TransactionsBatchSpecification {
+        id: 0,
+        transactions: vec![contract_publish_tx],
+        epoch: Some(EpochSpec::Epoch2_4),
+        post_checks: vec![],
+        description: None,
+    }]);
+
+    let cancellation = CancellationToken::new();
+    cancellation.cancel();
+    let mut session = Session::new(SessionSettings::default());
+    let result = PlanExecutor::new(&plan)
+        .with_cancellation(&cancellation)
+        .execute(&mut session);
+    assert!(result.cancelled);
+    assert!(result.contracts.is_empty());
+}
+
+#[test]
+fn test_plan_executor_emulated_evals() {
+    let (contract_publish_tx, _) = get_test_txs();
+    let emulated_sender =
+        PrincipalData::parse_standard_principal("ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM")
+            .unwrap();
+    let eval = |code: &str| {
+        TransactionSpecification::EmulatedEval(EmulatedEvalSpecification {
+            emulated_sender: emulated_sender.clone(),
+            code: code.to_string(),
+            description: None,
+        })
+    };
+    let plan = build_test_deployement_plan(vec![TransactionsBatchSpecification {
+        id: 0,
+        transactions: vec![
+            contract_publish_tx,
+            eval("(is-eq tx-sender 'ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM)"),
+            eval("(+ u1 1)"),
+        ],
+        epoch: Some(EpochSpec::Epoch2_4),
+        post_checks: vec![],
+        description: None,
+    }]);
+
+    let mut session = Session::new(SessionSettings::default());
+    let result = PlanExecutor::new(&plan).execute(&mut session);
+
+    // the snippets are not published
+    assert_eq!(result.batches_contracts[0].len(), 1);
+    assert_eq!(result.evals.len(), 2);
+    assert!(matches!(result.evals[0].result, Ok(Value::Bool(true))));
+    assert!(result.evals[1].result.is_err());
+    let failed_transactions = result.get_failed_transactions();
+    assert_eq!(failed_transactions.len(), 1);
+    assert_eq!(failed_transactions[0].description, "emulated-eval (+ u1 1)");
+}
+
+#[test]
+fn test_order_constraints() {
+    use clarity_repl::analysis::ast_dependency_detector::{ASTDependencyDetector, DependencySet};
+    use std::collections::HashMap;
+
+    let contract_id = |name: &str| {
+        QualifiedContractIdentifier::parse(&format!(
+            "ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM.{}",
+            name
+        ))
+        .unwrap()
+    };
+    let mut dependencies = BTreeMap::new();
+    for name in ["a-peripheral", "b-token", "z-registry"] {
+        dependencies.insert(contract_id(name), DependencySet::new());
+    }
+    let epochs = HashMap::new();
+
+    // without explicit order, independent contracts are sorted by identifier
+    let ordered = ASTDependencyDetector::order_contracts(&dependencies, &epochs).unwrap();
+    let names: Vec<_> = ordered.iter().map(|id| id.name.to_string()).collect();
+    assert_eq!(names, vec!["a-peripheral", "b-token", "z-registry"]);
+
+    let mut contracts_order = BTreeMap::new();
+    contracts_order.insert(contract_id("z-registry"), 1);
+    contracts_order.insert(contract_id("a-peripheral"), 2);
+    let constrained = crate::add_order_constraints(&dependencies, &contracts_order);
+    let ordered = ASTDependencyDetector::order_contracts(&constrained, &epochs).unwrap();
+    let names: Vec<_> = ordered.iter().map(|id| id.name.to_string()).collect();
+    assert_eq!(names, vec!["z-registry", "a-peripheral", "b-token"]);
+    assert!(dependencies[&contract_id("a-peripheral")].set.is_empty());
+}
+
+#[test]
+fn test_anchor_mode_specification() {
+    let parse = |anchor: &str| {
+        let file: ContractCallSpecificationFile = serde_yaml::from_str(&format!(
+            "contract-id: ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM.test
+expected-sender: ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM
+method: test
+parameters: []
+cost: 1000
+{}",
+            anchor
+        ))
+        .unwrap();
+        ContractCallSpecification::from_specifications(&file)
+            .unwrap()
+            .anchor_mode
+    };
+    assert_eq!(parse(""), AnchorMode::OnChainOnly);
+    assert_eq!(parse("anchor-block-only: false"), AnchorMode::Any);
+    assert_eq!(parse("anchor-mode: microblock-allowed"), AnchorMode::Any);
+    assert_eq!(
+        parse("anchor-mode: off-chain-only"),
+        AnchorMode::OffChainOnly
+    );
+    assert_eq!(
+        parse("anchor-block-only: true\nanchor-mode: any"),
+        AnchorMode::Any
+    );
+}
+
+#[test]
+fn test_post_checks_specification() {
+    let batch: TransactionsBatchSpecificationFile = serde_yaml::from_str(
+        "id: 0
+transactions: []
+post-checks:
+  - read-only-call:
+      contract-id: ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM.counter
+      method: get-count
+      expected: u0
+      halt-on-failure: true
+  - map-entry:
+      contract-id: ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM.counter
+      map: owners
+      key: u1
+",
+    )
+    .unwrap();
+    let checks = PostCheckSpecification::from_specifications_list(&batch.post_checks).unwrap();
+    assert_eq!(checks.len(), 2);
+    assert!(checks[0].halt_on_failure());
+    assert!(!checks[1].halt_on_failure());
+    assert_eq!(
+        checks[1].get_description(),
+        "map entry ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM.counter::owners u1"
+    );
+    let files: Vec<_> = checks
+        .iter()
+        .map(|check| check.to_specification_file())
+        .collect();
+    assert_eq!(Some(files), batch.post_checks);
+
+    let invalid: PostCheckSpecificationFile = serde_yaml::from_str(
+        "read-only-call:
+  contract-id: counter
+  method: get-count
+  expected: u0",
+    )
+    .unwrap();
+    assert!(PostCheckSpecification::from_specifications(&invalid).is_err());
+}
+
+/// Xorshift generator, so that the plans of the round-trip tests are random but reproducible
+struct TestRng(u64);
+
+impl TestRng {
+    fn next(&mut self, bound: u64) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0 % bound
+    }
+}
+
+fn build_random_simnet_plan(
+    rng: &mut TestRng,
+) -> (
+    DeploymentSpecification,
+    std::collections::HashMap<String, String>,
+) {
+    use clarity_repl::clarity::vm::types::PrincipalData;
+
+    let senders = [
+        "ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM",
+        "ST1SJ3DTE5DN7X54YDH5D64R3BCB6A2AG2ZQ8YPD5",
+        "ST2CY5V39NHDPWSXMW9QDT3HC3GD6Q6XX4CFRK9AG",
+    ]
+    .map(|address| PrincipalData::parse_standard_principal(address).unwrap());
+    let epochs = [
+        EpochSpec::Epoch2_1,
+        EpochSpec::Epoch2_4,
+        EpochSpec::Epoch2_5,
+        EpochSpec::Epoch3_0,
+    ];
+
+    let mut sources = std::collections::HashMap::new();
+    let mut published: Vec<QualifiedContractIdentifier> = vec![];
+    let mut batches = vec![];
+    for id in 0..(1 + rng.next(4) as usize) {
+        let mut transactions = vec![];
+        for _ in 0..(1 + rng.next(5)) {
+            let sender = senders[rng.next(3) as usize].clone();
+            let transaction = match rng.next(3) {
+                1 if !published.is_empty() => {
+                    let contract_id = published[rng.next(published.len() as u64) as usize].clone();
+                    TransactionSpecification::EmulatedContractCall(
+                        EmulatedContractCallSpecification {
+                            contract_id,
+                            emulated_sender: sender,
+                            method: ClarityName::try_from("get-value".to_string()).unwrap(),
+                            parameters: vec![format!("u{}", rng.next(1000))],
+                            description: None,
+                        },
+                    )
+                }
+                2 => TransactionSpecification::StxTransfer(StxTransferSpecification {
+                    expected_sender: sender,
+                    recipient: PrincipalData::Standard(senders[rng.next(3) as usize].clone()),
+                    mstx_amount: rng.next(1_000_000),
+                    memo: [0; 34],
+                    cost: rng.next(10_000),
+                    anchor_mode: AnchorMode::OnChainOnly,
+                    description: None,
+                }),
+                _ => {
+                    let name = format!("contract-{}", published.len());
+                    let source = format!("(define-read-only (get-value (n uint)) (+ n u{}))", id);
+                    let location = FileLocation::from_path_string(&format!(
+                        "/project/contracts/{}.clar",
+                        name
+                    ))
+                    .unwrap();
+                    sources.insert(location.to_string(), source.clone());
+                    published.push(QualifiedContractIdentifier::new(
+                        sender.clone(),
+                        ContractName::try_from(name.clone()).unwrap(),
+                    ));
+                    TransactionSpecification::EmulatedContractPublish(
+                        EmulatedContractPublishSpecification {
+                            contract_name: ContractName::try_from(name).unwrap(),
+                            emulated_sender: sender,
+                            source,
+                            location,
+                            clarity_version: ClarityVersion::Clarity2,
+                            description: None,
+                        },
+                    )
+                }
+            };
+            transactions.push(transaction);
+        }
+        batches.push(TransactionsBatchSpecification {
+            id,
+            transactions,
+            epoch: Some(epochs[rng.next(4) as usize]),
+            post_checks: vec![],
+            description: None,
+        });
+    }
+
+    let mut plan = build_test_deployement_plan(batches);
+    // the contracts are indexed in the reverse order of their publishes
+    for tx in plan
+        .plan
+        .batches
+        .iter()
+        .rev()
+        .flat_map(|b| b.transactions.iter().rev())
+    {
+        if let TransactionSpecification::EmulatedContractPublish(tx) = tx {
+            plan.contracts.insert(
+                QualifiedContractIdentifier::new(
+                    tx.emulated_sender.clone(),
+                    tx.contract_name.clone(),
+                ),
+                (tx.source.clone(), tx.location.clone()),
+            );
+        }
+    }
+    plan.genesis = Some(GenesisSpecification {
+        wallets: senders
+            .iter()
+            .enumerate()
+            .map(|(index, address)| WalletSpecification {
+                name: format!("wallet_{}", index),
+                address: address.clone(),
+                balance: rng.next(100_000_000_000) as u128,
+            })
+            .collect(),
+        contracts: vec![],
+    });
+    (plan, sources)
+}
+
+#[test]
+fn test_plan_serialization_round_trip() {
+    let project_root = FileLocation::from_path_string("/project").unwrap();
+    let mut rng = TestRng(0x5eed_cafe_f00d);
+    for _ in 0..32 {
+        let (plan, sources) = build_random_simnet_plan(&mut rng);
+
+        let content = plan.to_file_content().unwrap();
+        assert_eq!(plan.to_file_content().unwrap(), content);
+        let file: DeploymentSpecificationFile = serde_yaml::from_slice(&content).unwrap();
+        let parsed = DeploymentSpecification::from_specifications(
+            &file,
+            &StacksNetwork::Simnet,
+            &project_root,
+            Some(&sources),
+        )
+        .unwrap();
+        assert_eq!(parsed.plan, plan.plan);
+        assert_eq!(parsed.genesis, plan.genesis);
+        assert_eq!(parsed.contracts, plan.contracts);
+        assert_eq!(parsed.to_file_content().unwrap(), content);
+
+        let json = serde_json::to_string(&plan).unwrap();
+        let parsed: DeploymentSpecification = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, plan);
+        assert_eq!(serde_json::to_string(&parsed).unwrap(), json);
+    }
+}
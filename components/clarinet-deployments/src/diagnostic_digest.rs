@@ -1,4 +1,8 @@
-use std::collections::HashMap;
+//! Rendering of the diagnostics of a project as printed by `clarinet check`, `clarinet console`
+//! and the SDK. `DiagnosticsDigest::from_artifacts` is the entry point: it never panics, a
+//! contract without source in the deployment is reported as an internal error instead.
+
+use std::collections::{BTreeSet, HashMap};
 
 use clarity_repl::{
     clarity::{
@@ -9,7 +13,50 @@ use clarity_repl::{
 };
 use colored::*;
 
-use crate::types::DeploymentSpecification;
+use crate::includes::SourceMap;
+use crate::types::{
+    DeploymentGenerationArtifacts, DeploymentSpecification, TransactionSpecification,
+};
+
+/// Identical diagnostics (same file, position, level and message) are reported once,
+/// e.g. when a file is included in several contracts
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct DiagnosticKey {
+    path: String,
+    position: Option<(u32, u32)>,
+    level: String,
+    message: String,
+}
+
+#[derive(Debug, Clone)]
+struct DiagnosticGroup {
+    level: Level,
+    message: String,
+    /// Location and code lines, and the attached notes
+    lines: Vec<String>,
+    count: usize,
+}
+
+impl DiagnosticGroup {
+    fn render(&self) -> Vec<String> {
+        let header = match self.level {
+            Level::Error => format!("{} {}", "error:".red().bold(), self.message),
+            Level::Warning => format!("{} {}", "warning:".yellow().bold(), self.message),
+            Level::Note => format!("{}: {}", "note:".blue().bold(), self.message),
+        };
+        let header = match self.count {
+            1 => header,
+            count => format!(
+                "{} {}",
+                header,
+                format!("(reported {} times)", count).dimmed()
+            ),
+        };
+        let mut lines = vec![header];
+        lines.extend(self.lines.iter().cloned());
+        lines
+    }
+}
 
 #[allow(dead_code)]
 pub struct DiagnosticsDigest {
@@ -17,30 +64,113 @@ pub struct DiagnosticsDigest {
     pub errors: usize,
     pub warnings: usize,
     pub contracts_checked: usize,
+    /// Contracts of the artifacts without source in the deployment
+    pub missing_contracts: Vec<QualifiedContractIdentifier>,
     full_success: usize,
     total: usize,
+    groups: Vec<DiagnosticGroup>,
+}
+
+/// Contracts published by the plan, requirements included
+fn get_published_contracts(
+    deployment: &DeploymentSpecification,
+) -> BTreeSet<QualifiedContractIdentifier> {
+    let mut contracts = BTreeSet::new();
+    for transaction in deployment
+        .plan
+        .batches
+        .iter()
+        .flat_map(|batch| batch.transactions.iter())
+    {
+        match transaction {
+            TransactionSpecification::ContractPublish(tx) => {
+                contracts.insert(QualifiedContractIdentifier::new(
+                    tx.expected_sender.clone(),
+                    tx.contract_name.clone(),
+                ));
+            }
+            TransactionSpecification::EmulatedContractPublish(tx) => {
+                contracts.insert(QualifiedContractIdentifier::new(
+                    tx.emulated_sender.clone(),
+                    tx.contract_name.clone(),
+                ));
+            }
+            TransactionSpecification::RequirementPublish(tx) => {
+                contracts.insert(tx.contract_id.clone());
+                contracts.insert(tx.get_remapped_contract_id());
+            }
+            _ => {}
+        }
+    }
+    contracts
 }
 
 impl DiagnosticsDigest {
+    /// Digest of the diagnostics of a generation. The contracts of the dependencies and of the
+    /// diagnostics that are neither sources of `deployment` nor published by its plan are
+    /// reported as internal errors.
+    pub fn from_artifacts(
+        artifacts: &DeploymentGenerationArtifacts,
+        deployment: &DeploymentSpecification,
+    ) -> DiagnosticsDigest {
+        let mut digest =
+            Self::new_with_source_maps(&artifacts.diags, deployment, &artifacts.source_maps);
+        let published = get_published_contracts(deployment);
+        for contract_id in artifacts.deps.keys() {
+            if deployment.contracts.contains_key(contract_id)
+                || published.contains(contract_id)
+                || digest.missing_contracts.contains(contract_id)
+            {
+                continue;
+            }
+            digest.add_missing_contract(contract_id, 0);
+        }
+        for eval in artifacts.evals.iter() {
+            if let Err(diagnostics) = &eval.result {
+                digest.add_failed_eval(eval.batch_id, &eval.code, diagnostics);
+            }
+        }
+        digest.message = digest.render(None);
+        digest
+    }
+
     pub fn new(
         contracts_diags: &HashMap<QualifiedContractIdentifier, Vec<Diagnostic>>,
         deployment: &DeploymentSpecification,
+    ) -> DiagnosticsDigest {
+        Self::new_with_source_maps(contracts_diags, deployment, &HashMap::new())
+    }
+
+    /// Diagnostics of the contracts made of several files point to the included files
+    pub fn new_with_source_maps(
+        contracts_diags: &HashMap<QualifiedContractIdentifier, Vec<Diagnostic>>,
+        deployment: &DeploymentSpecification,
+        source_maps: &HashMap<QualifiedContractIdentifier, SourceMap>,
     ) -> DiagnosticsDigest {
         let mut full_success = 0;
         let mut warnings = 0;
         let mut errors = 0;
         let mut contracts_checked = 0;
-        let mut outputs = vec![];
+        let mut missing_contracts = vec![];
+        let mut groups: Vec<DiagnosticGroup> = vec![];
+        let published = get_published_contracts(deployment);
+        let mut groups_index: HashMap<DiagnosticKey, usize> = HashMap::new();
         let total = deployment.contracts.len();
 
-        for (contract_id, diags) in contracts_diags.iter() {
+        // contracts are sorted, so that the output is stable
+        let mut contracts_diags: Vec<_> = contracts_diags.iter().collect();
+        contracts_diags.sort_by_key(|(contract_id, _)| contract_id.to_string());
+
+        for (contract_id, diags) in contracts_diags {
             let (source, contract_location) = match deployment.contracts.get(contract_id) {
                 Some(entry) => {
                     contracts_checked += 1;
                     entry
                 }
+                // `deployment.contracts` only includes contracts from the project, requirements should be ignored
+                None if published.contains(contract_id) => continue,
                 None => {
-                    // `deployment.contracts` only includes contracts from the project, requirements should be ignored
+                    missing_contracts.push((contract_id.clone(), diags.len()));
                     continue;
                 }
             };
@@ -51,60 +181,274 @@ impl DiagnosticsDigest {
 
             let lines = source.lines();
             let formatted_lines: Vec<String> = lines.map(|l| l.to_string()).collect();
+            let contract_path = match contract_location.get_relative_location() {
+                Ok(contract_path) => contract_path,
+                _ => contract_location.to_string(),
+            };
 
+            // notes are attached to the previous diagnostic, and dropped with its duplicates
+            let mut current_group: Option<usize> = None;
             for diagnostic in diags {
-                match diagnostic.level {
-                    Level::Error => {
-                        errors += 1;
-                        outputs.push(format!("{} {}", "error:".red().bold(), diagnostic.message));
-                    }
-                    Level::Warning => {
-                        warnings += 1;
-                        outputs.push(format!(
-                            "{} {}",
-                            "warning:".yellow().bold(),
-                            diagnostic.message
-                        ));
-                    }
-                    Level::Note => {
-                        outputs.push(format!("{}: {}", "note:".blue().bold(), diagnostic.message));
-                        outputs.append(&mut output_code(diagnostic, &formatted_lines));
-                        continue;
+                if let Level::Note = diagnostic.level {
+                    match current_group {
+                        Some(index) => {
+                            let group = &mut groups[index];
+                            if group.count == 1 {
+                                group.lines.push(format!(
+                                    "{}: {}",
+                                    "note:".blue().bold(),
+                                    diagnostic.message
+                                ));
+                                group
+                                    .lines
+                                    .append(&mut output_code(diagnostic, &formatted_lines));
+                            }
+                        }
+                        None => {
+                            groups.push(DiagnosticGroup {
+                                level: Level::Note,
+                                message: diagnostic.message.clone(),
+                                lines: output_code(diagnostic, &formatted_lines),
+                                count: 1,
+                            });
+                        }
                     }
+                    continue;
                 }
-                let contract_path = match contract_location.get_relative_location() {
-                    Ok(contract_path) => contract_path,
-                    _ => contract_location.to_string(),
-                };
 
+                let mut lines = vec![];
+                let mut key = DiagnosticKey {
+                    path: contract_path.clone(),
+                    position: None,
+                    level: format!("{:?}", diagnostic.level),
+                    message: diagnostic.message.clone(),
+                };
                 if let Some(span) = diagnostic.spans.first() {
-                    outputs.push(format!(
+                    let original_location = source_maps
+                        .get(contract_id)
+                        .and_then(|source_map| source_map.get_original_location(span.start_line));
+                    let (contract_path, line) = match original_location {
+                        Some((location, line)) => (
+                            location
+                                .get_relative_location()
+                                .unwrap_or(location.to_string()),
+                            line,
+                        ),
+                        None => (contract_path.clone(), span.start_line),
+                    };
+                    lines.push(format!(
                         "{} {}:{}:{}",
                         "-->".blue().bold(),
                         contract_path,
-                        span.start_line,
+                        line,
                         span.start_column
                     ));
+                    key.path = contract_path;
+                    key.position = Some((line, span.start_column));
                 }
-                outputs.append(&mut output_code(diagnostic, &formatted_lines));
 
+                if let Some(index) = groups_index.get(&key) {
+                    groups[*index].count += 1;
+                    current_group = Some(*index);
+                    continue;
+                }
+
+                match diagnostic.level {
+                    Level::Error => errors += 1,
+                    Level::Warning => warnings += 1,
+                    Level::Note => {}
+                }
+                lines.append(&mut output_code(diagnostic, &formatted_lines));
                 if let Some(ref suggestion) = diagnostic.suggestion {
-                    outputs.push(suggestion.to_string());
+                    lines.push(suggestion.to_string());
                 }
+                groups_index.insert(key, groups.len());
+                current_group = Some(groups.len());
+                groups.push(DiagnosticGroup {
+                    level: diagnostic.level.clone(),
+                    message: diagnostic.message.clone(),
+                    lines,
+                    count: 1,
+                });
             }
         }
 
-        DiagnosticsDigest {
+        let mut digest = DiagnosticsDigest {
             full_success,
             errors,
             warnings,
             total,
             contracts_checked,
-            message: outputs.join("\n").to_string(),
+            missing_contracts: vec![],
+            message: String::new(),
+            groups,
+        };
+        for (contract_id, diagnostics) in missing_contracts {
+            digest.add_missing_contract(&contract_id, diagnostics);
+        }
+        digest.message = digest.render(None);
+        digest
+    }
+
+    fn add_missing_contract(
+        &mut self,
+        contract_id: &QualifiedContractIdentifier,
+        diagnostics: usize,
+    ) {
+        let mut message = format!(
+            "internal error: unable to retrieve contract {}",
+            contract_id
+        );
+        if diagnostics > 0 {
+            message = format!(
+                "{} ({} not displayed)",
+                message,
+                pluralize(diagnostics, "diagnostic")
+            );
         }
+        self.errors += 1;
+        self.missing_contracts.push(contract_id.clone());
+        self.groups.push(DiagnosticGroup {
+            level: Level::Error,
+            message,
+            lines: vec![],
+            count: 1,
+        });
+    }
+
+    /// The diagnostics of an `emulated-eval` snippet point to the lines of the snippet
+    fn add_failed_eval(&mut self, batch_id: usize, code: &str, diagnostics: &[Diagnostic]) {
+        let formatted_lines: Vec<String> = code.lines().map(|l| l.to_string()).collect();
+        for diagnostic in diagnostics {
+            match diagnostic.level {
+                Level::Error => self.errors += 1,
+                Level::Warning => self.warnings += 1,
+                Level::Note => {}
+            }
+            let mut location =
+                format!("{} emulated-eval (batch {})", "-->".blue().bold(), batch_id);
+            if let Some(span) = diagnostic.spans.first() {
+                location = format!("{}:{}:{}", location, span.start_line, span.start_column);
+            }
+            let mut lines = vec![location];
+            lines.append(&mut output_code(diagnostic, &formatted_lines));
+            self.groups.push(DiagnosticGroup {
+                level: diagnostic.level.clone(),
+                message: diagnostic.message.clone(),
+                lines,
+                count: 1,
+            });
+        }
+    }
+
+    /// Only displays the first `max` diagnostics, followed by the count of the hidden ones
+    pub fn set_max_diagnostics(&mut self, max: usize) {
+        self.message = self.render(Some(max));
+    }
+
+    fn render(&self, max: Option<usize>) -> String {
+        let displayed = max.unwrap_or(self.groups.len()).min(self.groups.len());
+        let mut outputs: Vec<String> = self.groups[..displayed]
+            .iter()
+            .flat_map(|group| group.render())
+            .collect();
+        let hidden = &self.groups[displayed..];
+        if !hidden.is_empty() {
+            let hidden_errors = hidden
+                .iter()
+                .filter(|group| matches!(group.level, Level::Error))
+                .count();
+            let hidden_warnings = hidden
+                .iter()
+                .filter(|group| matches!(group.level, Level::Warning))
+                .count();
+            outputs.push(format!(
+                "{} {} and {} not displayed",
+                "...".dimmed(),
+                pluralize(hidden_errors, "error"),
+                pluralize(hidden_warnings, "warning"),
+            ));
+        }
+        outputs.join("\n")
     }
 
     pub fn has_feedbacks(&self) -> bool {
         self.errors > 0 || self.warnings > 0
     }
 }
+
+fn pluralize(count: usize, word: &str) -> String {
+    match count {
+        1 => format!("1 {}", word),
+        count => format!("{} {}s", count, word),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use clarinet_files::{FileLocation, StacksNetwork};
+    use clarity_repl::repl::{Session, SessionSettings};
+
+    use super::*;
+    use crate::types::TransactionPlanSpecification;
+
+    #[test]
+    fn test_digest_of_missing_contracts() {
+        let counter =
+            QualifiedContractIdentifier::parse("ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM.counter")
+                .unwrap();
+        let missing =
+            QualifiedContractIdentifier::parse("ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM.missing")
+                .unwrap();
+        let location = FileLocation::from_path_string("/contracts/counter.clar").unwrap();
+        let deployment = DeploymentSpecification {
+            id: 1,
+            name: "test".to_string(),
+            network: StacksNetwork::Simnet,
+            stacks_node: None,
+            bitcoin_node: None,
+            genesis: None,
+            contracts: BTreeMap::from([(counter.clone(), ("(ok u1)".to_string(), location))]),
+            plan: TransactionPlanSpecification {
+                batches: vec![],
+                post_checks: vec![],
+            },
+            description: None,
+        };
+        let warning = Diagnostic {
+            level: Level::Warning,
+            message: "unused variable".to_string(),
+            spans: vec![],
+            suggestion: None,
+        };
+        let artifacts = DeploymentGenerationArtifacts {
+            asts: BTreeMap::new(),
+            deps: BTreeMap::from([
+                (counter.clone(), Default::default()),
+                (missing.clone(), Default::default()),
+            ]),
+            diags: HashMap::from([
+                (counter.clone(), vec![warning.clone()]),
+                (missing.clone(), vec![warning]),
+            ]),
+            analysis: HashMap::new(),
+            results_values: HashMap::new(),
+            source_maps: HashMap::new(),
+            warnings: vec![],
+            evals: vec![],
+            session: Session::new(SessionSettings::default()),
+            success: true,
+        };
+
+        let digest = DiagnosticsDigest::from_artifacts(&artifacts, &deployment);
+        assert_eq!(digest.warnings, 1);
+        assert_eq!(digest.errors, 1);
+        assert_eq!(digest.missing_contracts, vec![missing.clone()]);
+        assert!(digest.message.contains(&format!(
+            "internal error: unable to retrieve contract {} (1 diagnostic not displayed)",
+            missing
+        )));
+    }
+}
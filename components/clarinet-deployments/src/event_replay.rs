@@ -0,0 +1,455 @@
+//! Reconstruction of a chain state from the events a Stacks node sent to its observers, as
+//! archived by the Stacks API (the `events.tsv` file of `event-replay`) or dumped as JSON
+//! lines (`{"path": "/new_block", "payload": {..}}`). The successful transactions of the
+//! blocks mined up to a height are turned into a simnet plan, one batch per block, and the
+//! STX minted to the accounts become their genesis balances.
+
+use std::collections::BTreeMap;
+use std::io::Cursor;
+
+use clarity_repl::clarity::codec::StacksMessageCodec;
+use clarity_repl::clarity::util::hash::hex_bytes;
+use clarity_repl::clarity::vm::types::{CharType, SequenceData, Value};
+use clarity_repl::repl::clarity_versions::version_number;
+use stacks_codec::codec::{StacksTransaction, TransactionPayload};
+
+use crate::types::{
+    DeploymentSpecificationFile, EmulatedContractCallSpecificationFile,
+    EmulatedContractPublishSpecificationFile, GenesisSpecificationFile,
+    StxTransferSpecificationFile, TransactionPlanSpecificationFile, TransactionSpecificationFile,
+    TransactionsBatchSpecificationFile, WalletSpecificationFile,
+};
+
+/// Principal data is displayed without the leading quote, which is required in Clarity code
+pub fn value_to_clarity_expression(value: &Value) -> String {
+    match value {
+        Value::Principal(principal) => format!("'{}", principal),
+        Value::CallableContract(callable) => format!("'{}", callable.contract_identifier),
+        Value::Optional(optional) => match &optional.data {
+            Some(data) => format!("(some {})", value_to_clarity_expression(data)),
+            None => "none".to_string(),
+        },
+        Value::Response(response) => format!(
+            "({} {})",
+            if response.committed { "ok" } else { "err" },
+            value_to_clarity_expression(&response.data)
+        ),
+        Value::Tuple(tuple) => {
+            let entries: Vec<String> = tuple
+                .data_map
+                .iter()
+                .map(|(name, value)| format!("{}: {}", name, value_to_clarity_expression(value)))
+                .collect();
+            format!("{{ {} }}", entries.join(", "))
+        }
+        Value::Sequence(SequenceData::List(list)) => {
+            let items: Vec<String> = list.data.iter().map(value_to_clarity_expression).collect();
+            if items.is_empty() {
+                "(list)".to_string()
+            } else {
+                format!("(list {})", items.join(" "))
+            }
+        }
+        Value::Sequence(SequenceData::String(CharType::ASCII(_)))
+        | Value::Sequence(SequenceData::String(CharType::UTF8(_)))
+        | Value::Sequence(SequenceData::Buffer(_))
+        | Value::Int(_)
+        | Value::UInt(_)
+        | Value::Bool(_) => value.to_string(),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReplayedTransactionKind {
+    ContractPublish {
+        contract_name: String,
+        source: String,
+        clarity_version: Option<u8>,
+    },
+    ContractCall {
+        contract_id: String,
+        method: String,
+        /// Arguments, as Clarity expressions
+        arguments: Vec<String>,
+    },
+    StxTransfer {
+        recipient: String,
+        amount: u64,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplayedTransaction {
+    pub txid: String,
+    pub sender: String,
+    pub kind: ReplayedTransactionKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplayedBlock {
+    pub height: u64,
+    pub transactions: Vec<ReplayedTransaction>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EventReplay {
+    /// Blocks with at least one transaction to replay, by height
+    pub blocks: Vec<ReplayedBlock>,
+    /// STX minted to each address, in µSTX
+    pub minted: BTreeMap<String, u64>,
+    /// Failed transactions, and the ones simnet can not execute (coinbases, tenure changes)
+    pub skipped_transactions: usize,
+    pub last_height: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct ObservedEvent {
+    #[serde(alias = "event_path")]
+    path: String,
+    payload: serde_json::Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct NewBlockTransaction {
+    txid: String,
+    raw_tx: String,
+    status: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct StxMintEvent {
+    recipient: String,
+    amount: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct NewBlockEvent {
+    #[serde(default = "default_committed")]
+    committed: bool,
+    stx_mint_event: Option<StxMintEvent>,
+}
+
+fn default_committed() -> bool {
+    true
+}
+
+#[derive(Debug, Deserialize)]
+struct NewBlock {
+    block_height: u64,
+    transactions: Vec<NewBlockTransaction>,
+    #[serde(default)]
+    events: Vec<NewBlockEvent>,
+}
+
+/// Returns the path and the payload of an archived event, the API archives are tab separated
+/// (id, timestamp, path, payload)
+fn parse_event_line(line: &str) -> Result<ObservedEvent, String> {
+    if line.starts_with('{') {
+        return serde_json::from_str(line).map_err(|e| format!("invalid event: {}", e));
+    }
+    let columns: Vec<&str> = line.splitn(4, '\t').collect();
+    let [_, _, path, payload] = columns[..] else {
+        return Err("expected 4 tab separated columns (id, timestamp, path, payload)".into());
+    };
+    Ok(ObservedEvent {
+        path: path.to_string(),
+        payload: serde_json::from_str(payload).map_err(|e| format!("invalid payload: {}", e))?,
+    })
+}
+
+fn decode_transaction(tx: &NewBlockTransaction) -> Option<ReplayedTransaction> {
+    let bytes = hex_bytes(tx.raw_tx.trim_start_matches("0x")).ok()?;
+    let decoded = StacksTransaction::consensus_deserialize(&mut Cursor::new(&bytes)).ok()?;
+    let sender = decoded.origin_address().to_string();
+    let kind = match decoded.payload {
+        TransactionPayload::SmartContract(contract, clarity_version) => {
+            ReplayedTransactionKind::ContractPublish {
+                contract_name: contract.name.to_string(),
+                source: contract.code_body.to_string(),
+                clarity_version: clarity_version.map(version_number),
+            }
+        }
+        TransactionPayload::ContractCall(call) => ReplayedTransactionKind::ContractCall {
+            contract_id: format!("{}.{}", call.address, call.contract_name),
+            method: call.function_name.to_string(),
+            arguments: call
+                .function_args
+                .iter()
+                .map(value_to_clarity_expression)
+                .collect(),
+        },
+        TransactionPayload::TokenTransfer(recipient, amount, _) => {
+            ReplayedTransactionKind::StxTransfer {
+                recipient: recipient.to_string(),
+                amount,
+            }
+        }
+        _ => return None,
+    };
+    Some(ReplayedTransaction {
+        txid: tx.txid.clone(),
+        sender,
+        kind,
+    })
+}
+
+/// Reads the blocks of an events archive, up to `target_height` (included) if specified
+pub fn parse_event_replay(
+    content: &str,
+    target_height: Option<u64>,
+) -> Result<EventReplay, String> {
+    let mut replay = EventReplay::default();
+    for (index, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let event = parse_event_line(line).map_err(|e| format!("line {}: {}", index + 1, e))?;
+        if event.path != "/new_block" {
+            continue;
+        }
+        let block: NewBlock = serde_json::from_value(event.payload)
+            .map_err(|e| format!("line {}: invalid block: {}", index + 1, e))?;
+        if matches!(target_height, Some(height) if block.block_height > height) {
+            break;
+        }
+        replay.last_height = block.block_height;
+
+        for event in block.events.iter().filter(|event| event.committed) {
+            let Some(ref mint) = event.stx_mint_event else {
+                continue;
+            };
+            let amount: u64 = mint
+                .amount
+                .parse()
+                .map_err(|e| format!("line {}: invalid amount: {}", index + 1, e))?;
+            *replay.minted.entry(mint.recipient.clone()).or_default() += amount;
+        }
+
+        let mut transactions = vec![];
+        for tx in block.transactions.iter() {
+            match decode_transaction(tx) {
+                Some(transaction) if tx.status == "success" => transactions.push(transaction),
+                _ => replay.skipped_transactions += 1,
+            }
+        }
+        if !transactions.is_empty() {
+            replay.blocks.push(ReplayedBlock {
+                height: block.block_height,
+                transactions,
+            });
+        }
+    }
+    Ok(replay)
+}
+
+/// Path of the source of a replayed contract, relative to the project root
+pub fn get_contract_path(sender: &str, contract_name: &str) -> String {
+    format!("replay/contracts/{}.{}.clar", sender, contract_name)
+}
+
+/// Builds a simnet plan executing the replayed transactions, one batch per block. The
+/// heights of the simnet blocks do not match the ones of the archive, the blocks without
+/// transactions to replay are not kept.
+pub fn build_replay_plan(
+    replay: &EventReplay,
+    boot_contracts: &[String],
+) -> DeploymentSpecificationFile {
+    let wallets = replay
+        .minted
+        .iter()
+        .map(|(address, balance)| WalletSpecificationFile {
+            name: address.clone(),
+            address: address.clone(),
+            balance: balance.to_string(),
+        })
+        .collect();
+
+    let batches = replay
+        .blocks
+        .iter()
+        .enumerate()
+        .map(|(id, block)| TransactionsBatchSpecificationFile {
+            id,
+            transactions: block
+                .transactions
+                .iter()
+                .map(|tx| match &tx.kind {
+                    ReplayedTransactionKind::ContractPublish {
+                        contract_name,
+                        clarity_version,
+                        ..
+                    } => TransactionSpecificationFile::EmulatedContractPublish(
+                        EmulatedContractPublishSpecificationFile {
+                            contract_name: contract_name.clone(),
+                            emulated_sender: tx.sender.clone(),
+                            location: None,
+                            path: Some(get_contract_path(&tx.sender, contract_name)),
+                            url: None,
+                            clarity_version: *clarity_version,
+                            description: None,
+                        },
+                    ),
+                    ReplayedTransactionKind::ContractCall {
+                        contract_id,
+                        method,
+                        arguments,
+                    } => TransactionSpecificationFile::EmulatedContractCall(
+                        EmulatedContractCallSpecificationFile {
+                            contract_id: contract_id.clone(),
+                            emulated_sender: tx.sender.clone(),
+                            method: method.clone(),
+                            parameters: arguments.clone(),
+                            description: None,
+                        },
+                    ),
+                    ReplayedTransactionKind::StxTransfer { recipient, amount } => {
+                        TransactionSpecificationFile::StxTransfer(StxTransferSpecificationFile {
+                            expected_sender: tx.sender.clone(),
+                            recipient: recipient.clone(),
+                            mstx_amount: *amount,
+                            memo: None,
+                            cost: 0,
+                            anchor_block_only: None,
+                            anchor_mode: None,
+                            description: None,
+                        })
+                    }
+                })
+                .collect(),
+            epoch: None,
+            post_checks: None,
+            description: None,
+        })
+        .collect();
+
+    DeploymentSpecificationFile {
+        id: Some(0),
+        name: format!("Replayed events up to block {}", replay.last_height),
+        network: "simnet".to_string(),
+        stacks_node: None,
+        bitcoin_node: None,
+        node: None,
+        genesis: Some(GenesisSpecificationFile {
+            wallets,
+            contracts: boot_contracts.to_vec(),
+        }),
+        remapped_contracts: None,
+        plan: Some(TransactionPlanSpecificationFile {
+            batches,
+            post_checks: None,
+        }),
+        description: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clarity_repl::clarity::util::hash::{to_hex, Hash160};
+    use clarity_repl::clarity::util::secp256k1::MessageSignature;
+    use clarity_repl::clarity::ContractName;
+    use serde_json::json;
+    use stacks_codec::codec::{
+        SinglesigHashMode, SinglesigSpendingCondition, StacksString, TransactionAnchorMode,
+        TransactionAuth, TransactionPostConditionMode, TransactionPublicKeyEncoding,
+        TransactionSmartContract, TransactionSpendingCondition, TransactionVersion,
+    };
+    use std::str::FromStr;
+
+    fn raw_contract_publish(name: &str, source: &str) -> String {
+        let tx = StacksTransaction {
+            version: TransactionVersion::Mainnet,
+            chain_id: 1,
+            auth: TransactionAuth::Standard(TransactionSpendingCondition::Singlesig(
+                SinglesigSpendingCondition {
+                    hash_mode: SinglesigHashMode::P2PKH,
+                    signer: Hash160([0; 20]),
+                    nonce: 0,
+                    tx_fee: 1000,
+                    key_encoding: TransactionPublicKeyEncoding::Compressed,
+                    signature: MessageSignature::empty(),
+                },
+            )),
+            anchor_mode: TransactionAnchorMode::OnChainOnly,
+            post_condition_mode: TransactionPostConditionMode::Deny,
+            post_conditions: vec![],
+            payload: TransactionPayload::SmartContract(
+                TransactionSmartContract {
+                    name: ContractName::try_from(name.to_string()).unwrap(),
+                    code_body: StacksString::from_str(source).unwrap(),
+                },
+                None,
+            ),
+        };
+        let mut bytes = vec![];
+        tx.consensus_serialize(&mut bytes).unwrap();
+        format!("0x{}", to_hex(&bytes))
+    }
+
+    fn block_line(id: u64, height: u64, transactions: serde_json::Value) -> String {
+        let payload = json!({
+            "block_height": height,
+            "transactions": transactions,
+            "events": [{
+                "txid": "0x00",
+                "committed": true,
+                "type": "stx_mint_event",
+                "stx_mint_event": {
+                    "recipient": "SP000000000000000000002Q6VF78",
+                    "amount": "1000",
+                },
+            }],
+        });
+        format!("{}\t2024-01-01\t/new_block\t{}", id, payload)
+    }
+
+    #[test]
+    fn test_parse_event_replay() {
+        let publish = raw_contract_publish("counter", "(define-data-var count uint u0)");
+        let content = [
+            block_line(
+                1,
+                1,
+                json!([{ "txid": "0x00", "raw_tx": "0x00", "status": "success" }]),
+            ),
+            format!("2\t2024-01-01\t/new_mempool_tx\t{}", json!([publish])),
+            block_line(
+                3,
+                2,
+                json!([
+                    { "txid": "0x01", "raw_tx": publish, "status": "success" },
+                    { "txid": "0x02", "raw_tx": publish, "status": "abort_by_response" },
+                ]),
+            ),
+            block_line(
+                4,
+                3,
+                json!([{ "txid": "0x03", "raw_tx": publish, "status": "success" }]),
+            ),
+        ]
+        .join("\n");
+
+        let replay = parse_event_replay(&content, Some(2)).unwrap();
+        assert_eq!(replay.last_height, 2);
+        assert_eq!(replay.skipped_transactions, 2);
+        assert_eq!(replay.minted.values().sum::<u64>(), 2000);
+        assert_eq!(replay.blocks.len(), 1);
+        let tx = &replay.blocks[0].transactions[0];
+        assert_eq!(tx.txid, "0x01");
+        assert!(matches!(
+            tx.kind,
+            ReplayedTransactionKind::ContractPublish { ref contract_name, .. } if contract_name == "counter"
+        ));
+
+        let plan = build_replay_plan(&replay, &[]);
+        let batches = plan.plan.unwrap().batches;
+        assert_eq!(batches.len(), 1);
+        let TransactionSpecificationFile::EmulatedContractPublish(ref publish) =
+            batches[0].transactions[0]
+        else {
+            panic!("expected an emulated contract publish");
+        };
+        assert_eq!(publish.path, Some(get_contract_path(&tx.sender, "counter")));
+        assert_eq!(plan.genesis.unwrap().wallets[0].balance, "2000");
+    }
+}
@@ -0,0 +1,306 @@
+//! Replay of a simnet deployment plan in a session, shared by `clarinet check`, the SDK and
+//! the language server so that every frontend builds the same state from a plan. Frontends
+//! opt in to coverage and costs collection, and observe the execution through hooks.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use clarity_repl::clarity::vm::ast::ContractAST;
+use clarity_repl::clarity::vm::diagnostic::{Diagnostic, Level};
+use clarity_repl::clarity::vm::types::QualifiedContractIdentifier;
+use clarity_repl::clarity::vm::{EvaluationResult, ExecutionResult, Value};
+use clarity_repl::clarity::StacksEpochId;
+use clarity_repl::repl::session::BOOT_CONTRACTS_DATA;
+use clarity_repl::repl::settings::RequirementsExecution;
+use clarity_repl::repl::{Session, DEFAULT_EPOCH};
+
+use crate::cancellation::CancellationToken;
+use crate::profiling::PhaseTimer;
+use crate::types::{DeploymentSpecification, EmulatedEvalResult, TransactionSpecification};
+use crate::{
+    get_batch_epoch, get_contract_call_errors, handle_emulated_contract_call,
+    handle_emulated_contract_publish, handle_emulated_eval, handle_stx_transfer,
+    update_session_with_genesis_accounts, FailedTransactionSummary, UpdateSessionExecutionResult,
+};
+
+/// Called by a [`PlanExecutor`] as the transactions of the plan are executed
+pub trait PlanExecutionHook {
+    /// A contract of the plan was published, or failed to be
+    fn on_contract_published(
+        &mut self,
+        _contract_id: &QualifiedContractIdentifier,
+        _result: &Result<ExecutionResult, Vec<Diagnostic>>,
+    ) {
+    }
+
+    /// A transaction failed, or returned an `err` response
+    fn on_transaction_failed(&mut self, _failure: &FailedTransactionSummary) {}
+}
+
+pub struct PlanExecutor<'a> {
+    deployment: &'a DeploymentSpecification,
+    contracts_asts: Option<&'a BTreeMap<QualifiedContractIdentifier, ContractAST>>,
+    forced_min_epoch: Option<StacksEpochId>,
+    track_coverage: bool,
+    track_costs: bool,
+    cancellation: Option<&'a CancellationToken>,
+    requirements: BTreeSet<QualifiedContractIdentifier>,
+    hooks: Vec<&'a mut dyn PlanExecutionHook>,
+}
+
+impl<'a> PlanExecutor<'a> {
+    pub fn new(deployment: &'a DeploymentSpecification) -> Self {
+        PlanExecutor {
+            deployment,
+            contracts_asts: None,
+            forced_min_epoch: None,
+            track_coverage: false,
+            track_costs: false,
+            cancellation: None,
+            requirements: BTreeSet::new(),
+            hooks: vec![],
+        }
+    }
+
+    /// Reuses the ASTs built during the generation of the plan instead of parsing the
+    /// contracts again
+    pub fn with_asts(
+        mut self,
+        contracts_asts: Option<&'a BTreeMap<QualifiedContractIdentifier, ContractAST>>,
+    ) -> Self {
+        self.contracts_asts = contracts_asts;
+        self
+    }
+
+    /// Epoch below which the batches without an epoch are not executed
+    pub fn with_min_epoch(mut self, forced_min_epoch: Option<StacksEpochId>) -> Self {
+        self.forced_min_epoch = forced_min_epoch;
+        self
+    }
+
+    /// Records the coverage of the contracts executed by the plan, and of the later
+    /// executions in the session
+    pub fn with_coverage(mut self, track_coverage: bool) -> Self {
+        self.track_coverage = track_coverage;
+        self
+    }
+
+    /// Tracks the costs of the contracts publishes, see
+    /// [`UpdateSessionExecutionResult::get_batches_costs`]
+    pub fn with_costs(mut self, track_costs: bool) -> Self {
+        self.track_costs = track_costs;
+        self
+    }
+
+    /// Stops before the next batch once the token is cancelled, see
+    /// [`UpdateSessionExecutionResult::cancelled`]
+    pub fn with_cancellation(mut self, cancellation: &'a CancellationToken) -> Self {
+        self.cancellation = Some(cancellation);
+        self
+    }
+
+    /// Requirements of the project: unless `requirements_execution` is `eager` in the repl
+    /// settings of the session, they are published by their analysis only and executed when
+    /// a call needs them
+    pub fn with_requirements(
+        mut self,
+        requirements: BTreeSet<QualifiedContractIdentifier>,
+    ) -> Self {
+        self.requirements = requirements;
+        self
+    }
+
+    pub fn with_hook(mut self, hook: &'a mut dyn PlanExecutionHook) -> Self {
+        self.hooks.push(hook);
+        self
+    }
+
+    fn report_failure(&mut self, failure: FailedTransactionSummary) -> FailedTransactionSummary {
+        for hook in self.hooks.iter_mut() {
+            hook.on_transaction_failed(&failure);
+        }
+        failure
+    }
+
+    pub fn execute(mut self, session: &mut Session) -> UpdateSessionExecutionResult {
+        let deployment = self.deployment;
+        if self.track_coverage {
+            session.enable_coverage();
+        }
+        update_session_with_genesis_accounts(session, deployment);
+
+        let timer = PhaseTimer::start("boot contracts execution");
+        let mut boot_contracts = BTreeMap::new();
+        for (contract_id, (boot_contract, ast)) in BOOT_CONTRACTS_DATA.iter() {
+            let result = session
+                .interpreter
+                .run(boot_contract, Some(ast.as_ref()), false, None);
+            boot_contracts.insert(contract_id.clone(), result);
+        }
+        timer.end();
+
+        let mut contracts = BTreeMap::new();
+        let mut batches_contracts = vec![];
+        let mut failed_transactions = vec![];
+        let mut evals = vec![];
+        let max_epoch = session.settings.max_epoch.unwrap_or(DEFAULT_EPOCH);
+        let defer_requirements =
+            session.settings.repl_settings.requirements_execution != RequirementsExecution::Eager;
+        let mut cancelled = false;
+        for batch in deployment.plan.batches.iter() {
+            if self.cancellation.is_some_and(|token| token.is_cancelled()) {
+                cancelled = true;
+                break;
+            }
+            let mut batch_contracts = vec![];
+            let epoch = get_batch_epoch(batch, self.forced_min_epoch, max_epoch);
+            if epoch > max_epoch {
+                let message = format!(
+                    "batch {} requires {}, but the session is limited to {}",
+                    batch.id, epoch, max_epoch
+                );
+                for transaction in batch.transactions.iter() {
+                    let TransactionSpecification::EmulatedContractPublish(tx) = transaction else {
+                        continue;
+                    };
+                    let contract_id = QualifiedContractIdentifier::new(
+                        tx.emulated_sender.clone(),
+                        tx.contract_name.clone(),
+                    );
+                    failed_transactions.push(self.report_failure(FailedTransactionSummary {
+                        batch_id: batch.id,
+                        description: format!("contract-publish {}", contract_id),
+                        errors: vec![message.clone()],
+                    }));
+                    let diagnostic = Diagnostic {
+                        level: Level::Error,
+                        message: message.clone(),
+                        spans: vec![],
+                        suggestion: None,
+                    };
+                    let result = Err(vec![diagnostic]);
+                    for hook in self.hooks.iter_mut() {
+                        hook.on_contract_published(&contract_id, &result);
+                    }
+                    contracts.insert(contract_id, result);
+                }
+                batches_contracts.push(batch_contracts);
+                continue;
+            }
+            session.advance_chain_tip(1);
+            session.update_epoch(epoch);
+
+            for transaction in batch.transactions.iter() {
+                match transaction {
+                    TransactionSpecification::RequirementPublish(_)
+                    | TransactionSpecification::BtcTransfer(_)
+                    | TransactionSpecification::ContractCall(_)
+                    | TransactionSpecification::ContractPublish(_) => {
+                        panic!("emulated-contract-call and emulated-contract-publish are the only operations admitted in simnet deployments")
+                    }
+                    TransactionSpecification::EmulatedContractPublish(tx) => {
+                        let contract_id = QualifiedContractIdentifier::new(
+                            tx.emulated_sender.clone(),
+                            tx.contract_name.clone(),
+                        );
+                        let contract_ast = self.contracts_asts.and_then(|m| m.get(&contract_id));
+                        let timer = PhaseTimer::start(&format!("deployment of {}", contract_id));
+                        let deferred =
+                            defer_requirements && self.requirements.contains(&contract_id);
+                        let result = handle_emulated_contract_publish(
+                            session,
+                            tx,
+                            contract_ast,
+                            epoch,
+                            self.track_costs,
+                            deferred,
+                        );
+                        timer.end();
+                        if let Err(diagnostics) = &result {
+                            failed_transactions.push(self.report_failure(
+                                FailedTransactionSummary {
+                                    batch_id: batch.id,
+                                    description: format!("contract-publish {}", contract_id),
+                                    errors: diagnostics.iter().map(|d| d.message.clone()).collect(),
+                                },
+                            ));
+                        }
+                        for hook in self.hooks.iter_mut() {
+                            hook.on_contract_published(&contract_id, &result);
+                        }
+                        batch_contracts.push(contract_id.clone());
+                        contracts.insert(contract_id, result);
+                    }
+                    TransactionSpecification::EmulatedContractCall(tx) => {
+                        let result = handle_emulated_contract_call(session, tx);
+                        let errors = get_contract_call_errors(&result);
+                        if !errors.is_empty() {
+                            failed_transactions.push(self.report_failure(
+                                FailedTransactionSummary {
+                                    batch_id: batch.id,
+                                    description: format!(
+                                        "contract-call {}::{}",
+                                        tx.contract_id, tx.method
+                                    ),
+                                    errors,
+                                },
+                            ));
+                        }
+                    }
+                    TransactionSpecification::EmulatedEval(tx) => {
+                        let result = handle_emulated_eval(session, tx);
+                        let errors = get_contract_call_errors(&result);
+                        if !errors.is_empty() {
+                            failed_transactions.push(self.report_failure(
+                                FailedTransactionSummary {
+                                    batch_id: batch.id,
+                                    description: format!("emulated-eval {}", tx.code.trim()),
+                                    errors,
+                                },
+                            ));
+                        }
+                        evals.push(EmulatedEvalResult {
+                            batch_id: batch.id,
+                            code: tx.code.clone(),
+                            result: result.map(|execution_result| match execution_result.result {
+                                EvaluationResult::Snippet(snippet_result) => snippet_result.result,
+                                EvaluationResult::Contract(contract_result) => {
+                                    contract_result.result.unwrap_or_else(Value::none)
+                                }
+                            }),
+                        });
+                    }
+                    TransactionSpecification::StxTransfer(tx) => {
+                        handle_stx_transfer(session, tx);
+                    }
+                    TransactionSpecification::BnsOperation(tx) => {
+                        let result =
+                            handle_emulated_contract_call(session, &tx.as_emulated_contract_call());
+                        let errors = get_contract_call_errors(&result);
+                        if !errors.is_empty() {
+                            failed_transactions.push(self.report_failure(
+                                FailedTransactionSummary {
+                                    batch_id: batch.id,
+                                    description: format!(
+                                        "bns {} {}",
+                                        tx.get_method(),
+                                        tx.get_fully_qualified_name()
+                                    ),
+                                    errors,
+                                },
+                            ));
+                        }
+                    }
+                }
+            }
+            batches_contracts.push(batch_contracts);
+        }
+        UpdateSessionExecutionResult {
+            boot_contracts,
+            contracts,
+            batches_contracts,
+            failed_transactions,
+            evals,
+            cancelled,
+        }
+    }
+}
@@ -0,0 +1,102 @@
+//! Limits on the fees of a plan applied by `clarinet deployment apply`, read from the
+//! `[network]` section of the network manifest (`max_total_fee`, `require_confirmation_above`)
+//! so that a mistyped fee rate or cost can not go through unnoticed.
+
+use clarinet_files::NetworkManifest;
+
+use crate::types::{DeploymentSpecification, TransactionSpecification};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FeePolicy {
+    /// Total fee above which the plan is not applied, in µSTX
+    pub max_total_fee: Option<u64>,
+    /// Total fee above which the plan has to be confirmed by typing the name of the network
+    pub require_confirmation_above: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FeeCheck {
+    Accepted,
+    ConfirmationRequired { threshold: u64 },
+    Refused { max_total_fee: u64 },
+}
+
+impl FeePolicy {
+    pub fn from_network_manifest(network_manifest: &NetworkManifest) -> FeePolicy {
+        FeePolicy {
+            max_total_fee: network_manifest.network.max_total_fee,
+            require_confirmation_above: network_manifest.network.require_confirmation_above,
+        }
+    }
+
+    pub fn check(&self, total_fee: u64) -> FeeCheck {
+        if let Some(max_total_fee) = self.max_total_fee {
+            if total_fee > max_total_fee {
+                return FeeCheck::Refused { max_total_fee };
+            }
+        }
+        match self.require_confirmation_above {
+            Some(threshold) if total_fee > threshold => {
+                FeeCheck::ConfirmationRequired { threshold }
+            }
+            _ => FeeCheck::Accepted,
+        }
+    }
+}
+
+/// Fees paid by the Stacks transactions of the plan, in µSTX. Unlike the total cost of the
+/// plan, the transferred and burnt STX are not included, and the emulated transactions
+/// (never broadcasted) and the bitcoin transfers (paid in sats) are ignored.
+pub fn get_total_fee(deployment: &DeploymentSpecification) -> u64 {
+    deployment
+        .plan
+        .batches
+        .iter()
+        .flat_map(|batch| batch.transactions.iter())
+        .map(|tx| match tx {
+            TransactionSpecification::ContractCall(tx) => tx.cost,
+            TransactionSpecification::ContractPublish(tx) => tx.cost,
+            TransactionSpecification::RequirementPublish(tx) => tx.cost,
+            TransactionSpecification::StxTransfer(tx) => tx.cost,
+            TransactionSpecification::BnsOperation(tx) => tx.cost,
+            TransactionSpecification::EmulatedContractCall(_)
+            | TransactionSpecification::EmulatedContractPublish(_)
+            | TransactionSpecification::EmulatedEval(_)
+            | TransactionSpecification::BtcTransfer(_) => 0,
+        })
+        .fold(0u64, |total, cost| total.saturating_add(cost))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fee_policy() {
+        assert_eq!(FeePolicy::default().check(u64::MAX), FeeCheck::Accepted);
+
+        let policy = FeePolicy {
+            max_total_fee: Some(5_000_000),
+            require_confirmation_above: Some(1_000_000),
+        };
+        assert_eq!(policy.check(1_000_000), FeeCheck::Accepted);
+        assert_eq!(
+            policy.check(1_000_001),
+            FeeCheck::ConfirmationRequired {
+                threshold: 1_000_000
+            }
+        );
+        assert_eq!(
+            policy.check(5_000_000),
+            FeeCheck::ConfirmationRequired {
+                threshold: 1_000_000
+            }
+        );
+        assert_eq!(
+            policy.check(5_000_001),
+            FeeCheck::Refused {
+                max_total_fee: 5_000_000
+            }
+        );
+    }
+}
@@ -0,0 +1,214 @@
+//! Reconstruction of a deployment plan from the contracts an address published on chain,
+//! used by `clarinet deployments import`. Contracts are grouped in one batch per block
+//! and keep the fee, anchor mode and Clarity version of their original transaction.
+
+use crate::types::{
+    AnchorMode, ContractPublishSpecificationFile, DeploymentSpecificationFile,
+    TransactionPlanSpecificationFile, TransactionSpecificationFile,
+    TransactionsBatchSpecificationFile,
+};
+
+#[derive(Debug, Deserialize)]
+struct SmartContractData {
+    contract_id: String,
+    source_code: String,
+    clarity_version: Option<u8>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AddressTransaction {
+    tx_id: String,
+    tx_type: String,
+    tx_status: String,
+    block_height: u64,
+    tx_index: u64,
+    fee_rate: String,
+    sender_address: String,
+    anchor_mode: String,
+    smart_contract: Option<SmartContractData>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AddressTransactions {
+    results: Vec<AddressTransaction>,
+    total: usize,
+}
+
+const TRANSACTIONS_PAGE_SIZE: usize = 50;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedContract {
+    pub contract_name: String,
+    pub source: String,
+    pub txid: String,
+    pub block_height: u64,
+    pub tx_index: u64,
+    /// In µSTX
+    pub cost: u64,
+    pub anchor_mode: AnchorMode,
+    pub clarity_version: u8,
+}
+
+/// Lists the contracts successfully published by `address`, in the order they were mined
+pub async fn fetch_contract_deploys(
+    api_url: &str,
+    address: &str,
+) -> Result<Vec<ImportedContract>, String> {
+    let mut contracts = vec![];
+    let mut offset = 0;
+    loop {
+        let request_url = format!(
+            "{}/extended/v1/address/{}/transactions?limit={}&offset={}",
+            api_url, address, TRANSACTIONS_PAGE_SIZE, offset
+        );
+        let response = clarinet_files::net::get_text(&request_url).await?;
+        let page: AddressTransactions = serde_json::from_str(&response)
+            .map_err(|e| format!("unable to parse {}: {}", request_url, e))?;
+
+        for tx in page.results.iter() {
+            let Some(ref smart_contract) = tx.smart_contract else {
+                continue;
+            };
+            if tx.tx_type != "smart_contract"
+                || tx.tx_status != "success"
+                || tx.sender_address != address
+            {
+                continue;
+            }
+            let Some((_, contract_name)) = smart_contract.contract_id.split_once('.') else {
+                continue;
+            };
+            contracts.push(ImportedContract {
+                contract_name: contract_name.to_string(),
+                source: smart_contract.source_code.clone(),
+                txid: tx.tx_id.clone(),
+                block_height: tx.block_height,
+                tx_index: tx.tx_index,
+                cost: tx.fee_rate.parse().map_err(|e| {
+                    format!("unable to parse fee of transaction {}: {}", tx.tx_id, e)
+                })?,
+                anchor_mode: match tx.anchor_mode.as_str() {
+                    "off_chain_only" => AnchorMode::OffChainOnly,
+                    "any" => AnchorMode::Any,
+                    _ => AnchorMode::OnChainOnly,
+                },
+                clarity_version: smart_contract.clarity_version.unwrap_or(1),
+            });
+        }
+
+        offset += page.results.len();
+        if page.results.is_empty() || offset >= page.total {
+            break;
+        }
+    }
+
+    contracts.sort_by_key(|contract| (contract.block_height, contract.tx_index));
+    Ok(contracts)
+}
+
+/// Path of the source of an imported contract, relative to the project root
+pub fn get_contract_path(contract: &ImportedContract) -> String {
+    format!("contracts/{}.clar", contract.contract_name)
+}
+
+pub fn build_imported_plan(
+    address: &str,
+    network: &str,
+    api_url: &str,
+    contracts: &[ImportedContract],
+) -> DeploymentSpecificationFile {
+    let mut batches: Vec<TransactionsBatchSpecificationFile> = vec![];
+    let mut current_height = None;
+    for contract in contracts.iter() {
+        if current_height != Some(contract.block_height) {
+            current_height = Some(contract.block_height);
+            batches.push(TransactionsBatchSpecificationFile {
+                id: batches.len(),
+                transactions: vec![],
+                epoch: None,
+                post_checks: None,
+                description: None,
+            });
+        }
+        let batch = batches.last_mut().expect("a batch was just pushed");
+        batch
+            .transactions
+            .push(TransactionSpecificationFile::ContractPublish(
+                ContractPublishSpecificationFile {
+                    contract_name: contract.contract_name.clone(),
+                    expected_sender: address.to_string(),
+                    cost: contract.cost,
+                    location: None,
+                    path: Some(get_contract_path(contract)),
+                    url: None,
+                    anchor_block_only: None,
+                    anchor_mode: Some(contract.anchor_mode),
+                    clarity_version: Some(contract.clarity_version),
+                    txid: Some(contract.txid.clone()),
+                    block_height: Some(contract.block_height),
+                    description: None,
+                },
+            ));
+    }
+
+    DeploymentSpecificationFile {
+        id: Some(0),
+        name: format!("Imported {} deployment", network),
+        network: network.to_string(),
+        stacks_node: Some(api_url.to_string()),
+        bitcoin_node: None,
+        node: None,
+        genesis: None,
+        remapped_contracts: None,
+        plan: Some(TransactionPlanSpecificationFile {
+            batches,
+            post_checks: None,
+        }),
+        description: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contract(name: &str, block_height: u64, tx_index: u64) -> ImportedContract {
+        ImportedContract {
+            contract_name: name.to_string(),
+            source: "(define-read-only (get-one) u1)".to_string(),
+            txid: format!("0x{:064x}", block_height * 100 + tx_index),
+            block_height,
+            tx_index,
+            cost: 2500,
+            anchor_mode: AnchorMode::OnChainOnly,
+            clarity_version: 2,
+        }
+    }
+
+    #[test]
+    fn test_build_imported_plan() {
+        let address = "SP1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM";
+        let contracts = vec![
+            contract("traits", 1000, 3),
+            contract("token", 1000, 4),
+            contract("dao", 1012, 0),
+        ];
+        let plan = build_imported_plan(address, "mainnet", "https://api.hiro.so", &contracts);
+
+        let batches = plan.plan.unwrap().batches;
+        assert_eq!(batches.len(), 2);
+        assert_eq!(batches[0].transactions.len(), 2);
+        assert_eq!(batches[1].id, 1);
+        let TransactionSpecificationFile::ContractPublish(ref dao) = batches[1].transactions[0]
+        else {
+            panic!("expected a contract publish");
+        };
+        assert_eq!(dao.path, Some("contracts/dao.clar".to_string()));
+        assert_eq!(dao.expected_sender, address);
+        assert_eq!(dao.block_height, Some(1012));
+        assert_eq!(dao.txid, Some(contracts[2].txid.clone()));
+
+        let yaml = serde_yaml::to_string(&batches[1]).unwrap();
+        assert!(yaml.contains("block-height: 1012"));
+    }
+}
@@ -0,0 +1,360 @@
+//! Contracts can be split in several files with `;; #include "lib/math.clar"` directives.
+//! Paths are relative to the including file. The directives are replaced by the content
+//! of the included files, and a source map keeps track of the original location of each line.
+//! Files can also be included from an URL, pinned to their SHA-256 with
+//! `;; #include "https://example.com/math.clar" sha256=<hex>`: a file that doesn't match its
+//! pin is rejected before the contract is expanded.
+
+use std::collections::HashMap;
+
+use clarinet_files::FileLocation;
+
+const INCLUDE_DIRECTIVE: &str = ";; #include";
+
+/// A range of lines of the expanded source, coming from a single file
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceMapSegment {
+    pub location: FileLocation,
+    /// First line of the segment in the expanded source (1-indexed)
+    pub line: u32,
+    /// First line of the segment in the original file (1-indexed)
+    pub original_line: u32,
+    pub lines_count: u32,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SourceMap {
+    pub segments: Vec<SourceMapSegment>,
+}
+
+impl SourceMap {
+    /// Returns the file and the line a line of the expanded source comes from
+    pub fn get_original_location(&self, line: u32) -> Option<(&FileLocation, u32)> {
+        self.segments
+            .iter()
+            .find(|segment| line >= segment.line && line < segment.line + segment.lines_count)
+            .map(|segment| {
+                (
+                    &segment.location,
+                    segment.original_line + line - segment.line,
+                )
+            })
+    }
+}
+
+/// Path of the included file, and the SHA-256 it's pinned to
+fn parse_include_directive(line: &str) -> Option<Result<(&str, Option<&str>), String>> {
+    let args = line.trim().strip_prefix(INCLUDE_DIRECTIVE)?;
+    if !args.starts_with(char::is_whitespace) {
+        return None;
+    }
+    let invalid = || format!("invalid include directive: {}", line.trim());
+    let Some((path, pin)) = args
+        .trim()
+        .strip_prefix('"')
+        .and_then(|args| args.split_once('"'))
+        .filter(|(path, _)| !path.is_empty())
+    else {
+        return Some(Err(invalid()));
+    };
+    let directive = match pin.trim() {
+        "" => Ok((path, None)),
+        pin => match pin.strip_prefix("sha256=") {
+            Some(sha256) if !sha256.is_empty() => Ok((path, Some(sha256))),
+            _ => Err(invalid()),
+        },
+    };
+    Some(directive)
+}
+
+fn get_included_location(location: &FileLocation, path: &str) -> Result<FileLocation, String> {
+    if path.starts_with("https://") || path.starts_with("http://") {
+        return FileLocation::from_url_string(path);
+    }
+    let mut included_location = location.get_parent_location()?;
+    included_location.append_path(path)?;
+    Ok(included_location)
+}
+
+/// Returns the files directly included by a source, with the SHA-256 they're pinned to
+fn get_pinned_includes(
+    location: &FileLocation,
+    source: &str,
+) -> Result<Vec<(FileLocation, Option<String>)>, String> {
+    source
+        .lines()
+        .filter_map(parse_include_directive)
+        .map(|directive| {
+            let (path, sha256) = directive?;
+            Ok((
+                get_included_location(location, path)?,
+                sha256.map(String::from),
+            ))
+        })
+        .collect()
+}
+
+/// Returns the locations of the files directly included by a source
+pub fn get_includes(location: &FileLocation, source: &str) -> Result<Vec<FileLocation>, String> {
+    Ok(get_pinned_includes(location, source)?
+        .into_iter()
+        .map(|(location, _)| location)
+        .collect())
+}
+
+/// Checks the included files of `sources` against the SHA-256 they're pinned to
+pub fn check_pinned_includes(sources: &HashMap<String, String>) -> Result<(), String> {
+    for (location, source) in sources.iter() {
+        if !source.contains(INCLUDE_DIRECTIVE) {
+            continue;
+        }
+        let location = FileLocation::try_parse(location, None)
+            .ok_or(format!("unable to parse location {}", location))?;
+        for (included, sha256) in get_pinned_includes(&location, source)? {
+            let (Some(sha256), Some(content)) = (sha256, sources.get(&included.to_string())) else {
+                continue;
+            };
+            clarinet_files::net::check_sha256(&included.to_string(), content.as_bytes(), &sha256)?;
+        }
+    }
+    Ok(())
+}
+
+/// Returns the locations of the files, included directly or not, that are missing in `sources`
+pub fn get_missing_includes(
+    sources: &HashMap<String, String>,
+) -> Result<Vec<FileLocation>, String> {
+    let mut missing = vec![];
+    for (location, source) in sources.iter() {
+        if !source.contains(INCLUDE_DIRECTIVE) {
+            continue;
+        }
+        let location = FileLocation::try_parse(location, None)
+            .ok_or(format!("unable to parse location {}", location))?;
+        for included in get_includes(&location, source)? {
+            if !sources.contains_key(&included.to_string()) && !missing.contains(&included) {
+                missing.push(included);
+            }
+        }
+    }
+    Ok(missing)
+}
+
+fn expand(
+    location: &FileLocation,
+    sources: &HashMap<String, String>,
+    stack: &mut Vec<String>,
+    lines: &mut Vec<String>,
+    source_map: &mut SourceMap,
+) -> Result<(), String> {
+    let key = location.to_string();
+    if stack.contains(&key) {
+        return Err(format!("circular include of {}", key));
+    }
+    let source = sources
+        .get(&key)
+        .ok_or(format!("unable to find included file {}", key))?;
+    stack.push(key);
+
+    let mut segment_start = 0;
+    let original_lines: Vec<&str> = source.lines().collect();
+    for (index, line) in original_lines.iter().enumerate() {
+        let Some(directive) = parse_include_directive(line) else {
+            continue;
+        };
+        push_segment(
+            location,
+            &original_lines[segment_start..index],
+            segment_start,
+            lines,
+            source_map,
+        );
+        let (path, _) = directive?;
+        let included_location = get_included_location(location, path)?;
+        expand(&included_location, sources, stack, lines, source_map)?;
+        segment_start = index + 1;
+    }
+    push_segment(
+        location,
+        &original_lines[segment_start..],
+        segment_start,
+        lines,
+        source_map,
+    );
+
+    stack.pop();
+    Ok(())
+}
+
+fn push_segment(
+    location: &FileLocation,
+    segment_lines: &[&str],
+    original_index: usize,
+    lines: &mut Vec<String>,
+    source_map: &mut SourceMap,
+) {
+    if segment_lines.is_empty() {
+        return;
+    }
+    source_map.segments.push(SourceMapSegment {
+        location: location.clone(),
+        line: lines.len() as u32 + 1,
+        original_line: original_index as u32 + 1,
+        lines_count: segment_lines.len() as u32,
+    });
+    lines.extend(segment_lines.iter().map(|line| line.to_string()));
+}
+
+/// Replaces the include directives of a contract by the content of the included files,
+/// which must be present in `sources`. Returns `None` if the contract has no include.
+pub fn expand_includes(
+    location: &FileLocation,
+    sources: &HashMap<String, String>,
+) -> Result<Option<(String, SourceMap)>, String> {
+    let source = sources
+        .get(&location.to_string())
+        .ok_or(format!("unable to find contract at {}", location))?;
+    if !source.contains(INCLUDE_DIRECTIVE) || get_includes(location, source)?.is_empty() {
+        return Ok(None);
+    }
+    let mut lines = vec![];
+    let mut source_map = SourceMap::default();
+    expand(location, sources, &mut vec![], &mut lines, &mut source_map)?;
+    Ok(Some((lines.join("\n"), source_map)))
+}
+
+/// Reads a contract from the file system, resolving its include directives
+pub fn read_source_with_includes(location: &FileLocation) -> Result<String, String> {
+    let mut sources = HashMap::new();
+    sources.insert(location.to_string(), location.read_content_as_utf8()?);
+    loop {
+        let missing = get_missing_includes(&sources)?;
+        if missing.is_empty() {
+            break;
+        }
+        for included in missing {
+            let content = included
+                .read_content_as_utf8()
+                .map_err(|_| format!("unable to find included file {}", included))?;
+            sources.insert(included.to_string(), content);
+        }
+    }
+    check_pinned_includes(&sources)?;
+    match expand_includes(location, &sources)? {
+        Some((source, _)) => Ok(source),
+        None => Ok(sources.remove(&location.to_string()).unwrap_or_default()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clarity_repl::clarity::util::hash::Sha256Sum;
+
+    use super::*;
+
+    fn location(path: &str) -> FileLocation {
+        FileLocation::from_path_string(path).unwrap()
+    }
+
+    #[test]
+    fn test_expand_includes() {
+        let mut sources = HashMap::new();
+        sources.insert(
+            "/project/contracts/main.clar".to_string(),
+            [
+                ";; main",
+                ";; #include \"lib/math.clar\"",
+                "(define-read-only (get-double (n uint)) (double n))",
+            ]
+            .join("\n"),
+        );
+        sources.insert(
+            "/project/contracts/lib/math.clar".to_string(),
+            ["(define-private (double (n uint))", "  (* n u2))"].join("\n"),
+        );
+        let main = location("/project/contracts/main.clar");
+
+        assert_eq!(get_missing_includes(&sources), Ok(vec![]));
+        let (source, source_map) = expand_includes(&main, &sources).unwrap().unwrap();
+        assert_eq!(source.lines().count(), 4);
+        assert_eq!(
+            source.lines().nth(1),
+            Some("(define-private (double (n uint))")
+        );
+
+        let math = location("/project/contracts/lib/math.clar");
+        assert_eq!(source_map.get_original_location(1), Some((&main, 1)));
+        assert_eq!(source_map.get_original_location(3), Some((&math, 2)));
+        assert_eq!(source_map.get_original_location(4), Some((&main, 3)));
+        assert_eq!(source_map.get_original_location(5), None);
+    }
+
+    #[test]
+    fn test_circular_includes() {
+        let mut sources = HashMap::new();
+        sources.insert(
+            "/project/contracts/a.clar".to_string(),
+            ";; #include \"b.clar\"".to_string(),
+        );
+        sources.insert(
+            "/project/contracts/b.clar".to_string(),
+            ";; #include \"a.clar\"".to_string(),
+        );
+        let result = expand_includes(&location("/project/contracts/a.clar"), &sources);
+        assert_eq!(
+            result,
+            Err("circular include of /project/contracts/a.clar".to_string())
+        );
+    }
+
+    #[test]
+    fn test_missing_includes() {
+        let mut sources = HashMap::new();
+        sources.insert(
+            "/project/contracts/a.clar".to_string(),
+            ";; #include \"lib/b.clar\"\n;; #include \"lib/b.clar\"".to_string(),
+        );
+        assert_eq!(
+            get_missing_includes(&sources),
+            Ok(vec![location("/project/contracts/lib/b.clar")])
+        );
+    }
+
+    #[test]
+    fn test_pinned_includes() {
+        let math = "(define-private (double (n uint)) (* n u2))".to_string();
+        let sha256 = Sha256Sum::from_data(math.as_bytes()).to_hex();
+        let mut sources = HashMap::new();
+        sources.insert(
+            "/project/contracts/main.clar".to_string(),
+            format!(
+                ";; #include \"https://example.com/lib/math.clar\" sha256={}",
+                sha256
+            ),
+        );
+        assert_eq!(
+            get_missing_includes(&sources),
+            Ok(vec![FileLocation::from_url_string(
+                "https://example.com/lib/math.clar"
+            )
+            .unwrap()])
+        );
+        sources.insert("https://example.com/lib/math.clar".to_string(), math);
+        assert_eq!(check_pinned_includes(&sources), Ok(()));
+        let (source, _) = expand_includes(&location("/project/contracts/main.clar"), &sources)
+            .unwrap()
+            .unwrap();
+        assert!(source.starts_with("(define-private (double"));
+
+        sources.insert(
+            "https://example.com/lib/math.clar".to_string(),
+            "(define-private (double (n uint)) (* n u3))".to_string(),
+        );
+        assert!(check_pinned_includes(&sources)
+            .unwrap_err()
+            .starts_with("integrity check of https://example.com/lib/math.clar failed"));
+        assert!(parse_include_directive(";; #include \"math.clar\" sha256")
+            .unwrap()
+            .is_err());
+    }
+}
@@ -1,62 +1,258 @@
-use clarity_repl::clarity::vm::SymbolicExpression;
+use clarity_repl::clarity::vm::{ClarityVersion, SymbolicExpression};
 use clarity_repl::clarity::StacksEpochId;
-use clarity_repl::repl::{ClarityCodeSource, ClarityContract, ContractDeployer};
-use clarity_repl::repl::{DEFAULT_CLARITY_VERSION, DEFAULT_EPOCH};
+use clarity_repl::repl::DEFAULT_CLARITY_VERSION;
+use clarity_repl::repl::{ClarityContract, ContractDeployer};
 
 extern crate serde;
 
 #[macro_use]
 extern crate serde_derive;
 
+pub mod artifacts_export;
+pub mod assertions;
+pub mod baseline;
+#[cfg(feature = "cli")]
+pub mod bench;
+#[cfg(feature = "cli")]
+pub mod cache;
+pub mod cancellation;
+pub mod cfg_blocks;
+pub mod changelog;
+pub mod clarity_migration;
+pub mod deployment_builder;
 pub mod diagnostic_digest;
+#[cfg(feature = "cli")]
+pub mod event_replay;
+pub mod executor;
+pub mod fee_policy;
+pub mod history_import;
+pub mod includes;
+pub mod mocks;
 #[cfg(feature = "onchain")]
 pub mod onchain;
+pub mod plan_editor;
+pub mod platform_export;
+pub mod principal_remap;
+pub mod profiling;
+#[cfg(feature = "cli")]
+pub mod provenance;
+pub mod redeploy;
+pub mod refactor;
+pub mod requirement_explain;
+pub mod requirement_updates;
 pub mod requirements;
+pub mod scenarios;
+#[cfg(feature = "cli")]
+pub mod session_image;
+pub mod source_metadata;
+pub mod staged_check;
+pub mod stats;
+pub mod storage_layout;
+pub mod token_metadata;
 pub mod types;
+pub mod upgrade_impact;
+pub mod upgrade_plan;
+pub mod versions;
 
 #[cfg(test)]
 mod deployment_plan_test;
 
+use self::cancellation::CancellationToken;
+pub use self::executor::{PlanExecutionHook, PlanExecutor};
+use self::profiling::PhaseTimer;
 use self::types::{
-    DeploymentSpecification, EmulatedContractPublishSpecification, GenesisSpecification,
-    TransactionPlanSpecification, TransactionsBatchSpecification, WalletSpecification,
+    DeploymentSpecification, DeploymentSpecificationFile, EmulatedContractPublishSpecification,
+    GenesisSpecification, TransactionPlanSpecification, TransactionsBatchSpecification,
+    WalletSpecification,
 };
 use clarinet_files::StacksNetwork;
-use clarinet_files::{FileAccessor, FileLocation};
+use clarinet_files::{
+    check_clarity_version_for_epoch, validate_token_metadata, FileAccessor, FileLocation,
+};
 use clarinet_files::{NetworkManifest, ProjectManifest};
 use clarity_repl::analysis::ast_dependency_detector::{ASTDependencyDetector, DependencySet};
+use clarity_repl::analysis::dependency_cycle::find_dependency_cycle;
+use clarity_repl::clarity::ast::build_ast_with_diagnostics;
 use clarity_repl::clarity::vm::ast::ContractAST;
-use clarity_repl::clarity::vm::diagnostic::Diagnostic;
+use clarity_repl::clarity::vm::costs::ExecutionCost;
+use clarity_repl::clarity::vm::diagnostic::{Diagnostic, Level};
+use clarity_repl::clarity::vm::events::StacksTransactionEvent;
 use clarity_repl::clarity::vm::types::PrincipalData;
 use clarity_repl::clarity::vm::types::QualifiedContractIdentifier;
+use clarity_repl::clarity::vm::types::{ResponseData, Value};
 use clarity_repl::clarity::vm::ContractName;
 use clarity_repl::clarity::vm::EvaluationResult;
 use clarity_repl::clarity::vm::ExecutionResult;
+use clarity_repl::repl::address_book::AddressBook;
+use clarity_repl::repl::clarity_versions;
 use clarity_repl::repl::session::BOOT_CONTRACTS_DATA;
 use clarity_repl::repl::Session;
 use clarity_repl::repl::SessionSettings;
+#[cfg(feature = "cli")]
+use rayon::prelude::*;
 use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+use std::sync::Arc;
+use types::AnchorMode;
 use types::TransactionSpecification;
 use types::{ContractPublishSpecification, EpochSpec};
 use types::{DeploymentGenerationArtifacts, StxTransferSpecification};
 use types::{EmulatedContractCallSpecification, RequirementPublishSpecification};
+use types::{EmulatedEvalResult, EmulatedEvalSpecification};
+use types::{Warning, WarningCode};
+
+type BuiltContractAST = (
+    QualifiedContractIdentifier,
+    ClarityContract,
+    (ContractAST, Vec<Diagnostic>, bool),
+);
+
+/// The ASTs of the contracts don't depend on each other and are built on all the cores with
+/// the cli. The analysis needs the types of the dependencies, it happens when the contracts
+/// are deployed in the session, in order.
+fn build_contracts_asts(
+    contracts: Vec<(QualifiedContractIdentifier, ClarityContract)>,
+    cancellation: &CancellationToken,
+) -> Vec<BuiltContractAST> {
+    #[cfg(feature = "cli")]
+    let contracts = contracts.into_par_iter();
+    #[cfg(not(feature = "cli"))]
+    let contracts = contracts.into_iter();
+    contracts
+        .filter_map(|(contract_id, contract)| {
+            if cancellation.is_cancelled() {
+                return None;
+            }
+            let result = build_ast_with_diagnostics(
+                &contract_id,
+                contract.expect_in_memory_code_source(),
+                &mut (),
+                contract.clarity_version,
+                contract.epoch,
+            );
+            Some((contract_id, contract, result))
+        })
+        .collect()
+}
 
 pub type ExecutionResultMap =
     BTreeMap<QualifiedContractIdentifier, Result<ExecutionResult, Vec<Diagnostic>>>;
 
+/// A transaction of a deployment plan that failed, or that returned an `err` response
+#[derive(Debug, Clone)]
+pub struct FailedTransactionSummary {
+    pub batch_id: usize,
+    pub description: String,
+    pub errors: Vec<String>,
+}
+
 pub struct UpdateSessionExecutionResult {
     pub boot_contracts: ExecutionResultMap,
     pub contracts: ExecutionResultMap,
+    /// Contracts published by each batch of the plan
+    pub batches_contracts: Vec<Vec<QualifiedContractIdentifier>>,
+    pub failed_transactions: Vec<FailedTransactionSummary>,
+    /// Results of the `emulated-eval` snippets, in plan order
+    pub evals: Vec<EmulatedEvalResult>,
+    /// The execution was cancelled before the end of the plan, the later batches are missing
+    pub cancelled: bool,
+}
+
+impl UpdateSessionExecutionResult {
+    /// Total costs of the contracts published in each batch. Costs are only known
+    /// for the deployments executed with costs tracking.
+    pub fn get_batches_costs(&self) -> Vec<ExecutionCost> {
+        self.batches_contracts
+            .iter()
+            .map(|contracts_ids| {
+                let mut total = ExecutionCost::zero();
+                for contract_id in contracts_ids {
+                    if let Some(Ok(ExecutionResult {
+                        cost: Some(cost), ..
+                    })) = self.contracts.get(contract_id)
+                    {
+                        total.runtime = total.runtime.saturating_add(cost.total.runtime);
+                        total.read_count = total.read_count.saturating_add(cost.total.read_count);
+                        total.read_length =
+                            total.read_length.saturating_add(cost.total.read_length);
+                        total.write_count =
+                            total.write_count.saturating_add(cost.total.write_count);
+                        total.write_length =
+                            total.write_length.saturating_add(cost.total.write_length);
+                    }
+                }
+                total
+            })
+            .collect()
+    }
+
+    /// Events emitted by each contract publish
+    pub fn get_contracts_events(
+        &self,
+    ) -> BTreeMap<&QualifiedContractIdentifier, &Vec<StacksTransactionEvent>> {
+        self.contracts
+            .iter()
+            .filter_map(|(contract_id, result)| match result {
+                Ok(execution_result) => Some((contract_id, &execution_result.events)),
+                Err(_) => None,
+            })
+            .collect()
+    }
+
+    pub fn get_failed_transactions(&self) -> &[FailedTransactionSummary] {
+        &self.failed_transactions
+    }
 }
 
 pub fn setup_session_with_deployment(
     manifest: &ProjectManifest,
     deployment: &DeploymentSpecification,
     contracts_asts: Option<&BTreeMap<QualifiedContractIdentifier, ContractAST>>,
+) -> DeploymentGenerationArtifacts {
+    setup_session_with_deployment_and_cancellation(
+        manifest,
+        deployment,
+        contracts_asts,
+        &CancellationToken::new(),
+    )
+}
+
+/// Once `cancellation` is cancelled, the batches left are not executed and the artifacts
+/// only cover the contracts already published
+pub fn setup_session_with_deployment_and_cancellation(
+    manifest: &ProjectManifest,
+    deployment: &DeploymentSpecification,
+    contracts_asts: Option<&BTreeMap<QualifiedContractIdentifier, ContractAST>>,
+    cancellation: &CancellationToken,
+) -> DeploymentGenerationArtifacts {
+    setup_session_with_deferred_contracts(
+        manifest,
+        deployment,
+        contracts_asts,
+        cancellation,
+        get_requirements_contracts(manifest, deployment),
+    )
+}
+
+/// The contracts `deferred` are published for their analysis only, unless
+/// `requirements_execution` is `eager`
+pub(crate) fn setup_session_with_deferred_contracts(
+    manifest: &ProjectManifest,
+    deployment: &DeploymentSpecification,
+    contracts_asts: Option<&BTreeMap<QualifiedContractIdentifier, ContractAST>>,
+    cancellation: &CancellationToken,
+    deferred: BTreeSet<QualifiedContractIdentifier>,
 ) -> DeploymentGenerationArtifacts {
     let mut session = initiate_session_from_manifest(manifest);
-    let UpdateSessionExecutionResult { contracts, .. } =
-        update_session_with_deployment_plan(&mut session, deployment, contracts_asts, None);
+    let UpdateSessionExecutionResult {
+        contracts, evals, ..
+    } = PlanExecutor::new(deployment)
+        .with_asts(contracts_asts)
+        .with_cancellation(cancellation)
+        .with_requirements(deferred)
+        .execute(&mut session);
+    session
+        .address_book
+        .extend(&get_deployment_address_book(manifest, deployment));
 
     let deps = BTreeMap::new();
     let mut diags = HashMap::new();
@@ -80,25 +276,57 @@ pub fn setup_session_with_deployment(
             }
         }
     }
+    if evals.iter().any(|eval| eval.result.is_err()) {
+        success = false;
+    }
 
     DeploymentGenerationArtifacts {
         asts,
         deps,
         diags,
         results_values,
+        source_maps: HashMap::new(),
+        warnings: vec![],
+        evals,
         success,
         session,
         analysis: contracts_analysis,
     }
 }
 
-pub fn initiate_session_from_manifest(manifest: &ProjectManifest) -> Session {
-    let settings = SessionSettings {
+/// Contracts of the plan the project requires, as opposed to its own contracts
+pub fn get_requirements_contracts(
+    manifest: &ProjectManifest,
+    deployment: &DeploymentSpecification,
+) -> BTreeSet<QualifiedContractIdentifier> {
+    let requirements_ids: BTreeSet<&String> = manifest
+        .project
+        .requirements
+        .iter()
+        .flatten()
+        .map(|requirement| &requirement.contract_id)
+        .collect();
+    deployment
+        .contracts
+        .keys()
+        .filter(|contract_id| {
+            !manifest.contracts.contains_key(contract_id.name.as_str())
+                || requirements_ids.contains(&contract_id.to_string())
+        })
+        .cloned()
+        .collect()
+}
+
+pub fn get_session_settings(manifest: &ProjectManifest) -> SessionSettings {
+    SessionSettings {
         repl_settings: manifest.repl_settings.clone(),
         disk_cache_enabled: true,
         ..Default::default()
-    };
-    Session::new(settings)
+    }
+}
+
+pub fn initiate_session_from_manifest(manifest: &ProjectManifest) -> Session {
+    Session::new(get_session_settings(manifest))
 }
 
 fn update_session_with_genesis_accounts(
@@ -114,66 +342,39 @@ fn update_session_with_genesis_accounts(
             if wallet.name == "deployer" {
                 session.set_tx_sender(&wallet.address.to_string());
             }
+            let _ = session
+                .address_book
+                .insert(&wallet.name, &wallet.address.to_string());
         }
     }
 }
 
+/// Executes a plan with a [`PlanExecutor`], without coverage, costs or hooks
 pub fn update_session_with_deployment_plan(
     session: &mut Session,
     deployment: &DeploymentSpecification,
     contracts_asts: Option<&BTreeMap<QualifiedContractIdentifier, ContractAST>>,
     forced_min_epoch: Option<StacksEpochId>,
 ) -> UpdateSessionExecutionResult {
-    update_session_with_genesis_accounts(session, deployment);
-
-    let boot_contracts_data = BOOT_CONTRACTS_DATA.clone();
-
-    let mut boot_contracts = BTreeMap::new();
-    for (contract_id, (boot_contract, ast)) in boot_contracts_data {
-        let result = session
-            .interpreter
-            .run(&boot_contract, Some(&ast), false, None);
-        boot_contracts.insert(contract_id, result);
-    }
-
-    let mut contracts = BTreeMap::new();
-    for batch in deployment.plan.batches.iter() {
-        let epoch: StacksEpochId = match (batch.epoch, forced_min_epoch) {
-            (Some(epoch), _) => epoch.into(),
-            _ => DEFAULT_EPOCH,
-        };
-        session.advance_chain_tip(1);
-        session.update_epoch(epoch);
+    PlanExecutor::new(deployment)
+        .with_asts(contracts_asts)
+        .with_min_epoch(forced_min_epoch)
+        .execute(session)
+}
 
-        for transaction in batch.transactions.iter() {
-            match transaction {
-                TransactionSpecification::RequirementPublish(_)
-                | TransactionSpecification::BtcTransfer(_)
-                | TransactionSpecification::ContractCall(_)
-                | TransactionSpecification::ContractPublish(_) => {
-                    panic!("emulated-contract-call and emulated-contract-publish are the only operations admitted in simnet deployments")
-                }
-                TransactionSpecification::EmulatedContractPublish(tx) => {
-                    let contract_id = QualifiedContractIdentifier::new(
-                        tx.emulated_sender.clone(),
-                        tx.contract_name.clone(),
-                    );
-                    let contract_ast = contracts_asts.as_ref().and_then(|m| m.get(&contract_id));
-                    let result = handle_emulated_contract_publish(session, tx, contract_ast, epoch);
-                    contracts.insert(contract_id, result);
-                }
-                TransactionSpecification::EmulatedContractCall(tx) => {
-                    let _ = handle_emulated_contract_call(session, tx);
-                }
-                TransactionSpecification::StxTransfer(tx) => {
-                    handle_stx_transfer(session, tx);
-                }
-            }
-        }
-    }
-    UpdateSessionExecutionResult {
-        boot_contracts,
-        contracts,
+fn get_contract_call_errors(result: &Result<ExecutionResult, Vec<Diagnostic>>) -> Vec<String> {
+    match result {
+        Err(diagnostics) => diagnostics.iter().map(|d| d.message.clone()).collect(),
+        Ok(execution_result) => match &execution_result.result {
+            EvaluationResult::Snippet(snippet_result) => match &snippet_result.result {
+                Value::Response(ResponseData {
+                    committed: false,
+                    data,
+                }) => vec![format!("returned (err {})", data)],
+                _ => vec![],
+            },
+            EvaluationResult::Contract(_) => vec![],
+        },
     }
 }
 
@@ -186,25 +387,61 @@ fn handle_stx_transfer(session: &mut Session, tx: &StxTransferSpecification) {
     session.set_tx_sender(&default_tx_sender);
 }
 
+/// Epoch a batch is executed in: its own epoch if specified, otherwise the latest epoch
+/// of the session (or `forced_min_epoch` if later), moved up to the epoch required by
+/// the Clarity version of its contracts
+pub(crate) fn get_batch_epoch(
+    batch: &TransactionsBatchSpecification,
+    forced_min_epoch: Option<StacksEpochId>,
+    max_epoch: StacksEpochId,
+) -> StacksEpochId {
+    if let Some(epoch) = batch.epoch {
+        return epoch.into();
+    }
+    let min_epoch = forced_min_epoch.map_or(max_epoch, |epoch| std::cmp::max(epoch, max_epoch));
+    batch
+        .transactions
+        .iter()
+        .filter_map(|tx| match tx {
+            TransactionSpecification::EmulatedContractPublish(tx) => {
+                Some(clarity_versions::min_epoch(tx.clarity_version).into())
+            }
+            _ => None,
+        })
+        .fold(min_epoch, std::cmp::max)
+}
+
+/// `deferred` contracts are published by their analysis only, see [`Session::defer_contract`]
 fn handle_emulated_contract_publish(
     session: &mut Session,
     tx: &EmulatedContractPublishSpecification,
     contract_ast: Option<&ContractAST>,
     epoch: StacksEpochId,
+    cost_track: bool,
+    deferred: bool,
 ) -> Result<ExecutionResult, Vec<Diagnostic>> {
+    if let Err(message) = check_clarity_version_for_epoch(epoch, tx.clarity_version) {
+        return Err(vec![Diagnostic {
+            level: Level::Error,
+            message: format!("unable to publish {}: {}", tx.contract_name, message),
+            spans: vec![],
+            suggestion: None,
+        }]);
+    }
     let default_tx_sender = session.get_tx_sender();
     session.set_tx_sender(&tx.emulated_sender.to_string());
 
-    let contract = ClarityContract {
-        code_source: ClarityCodeSource::ContractInMemory(tx.source.clone()),
-        deployer: ContractDeployer::Address(tx.emulated_sender.to_string()),
-        name: tx.contract_name.to_string(),
-        clarity_version: tx.clarity_version,
-        epoch,
+    let result = match tx.to_clarity_contract(epoch) {
+        Ok(contract) if deferred => session.defer_contract(&contract, contract_ast),
+        Ok(contract) => session.deploy_contract(&contract, cost_track, contract_ast),
+        Err(message) => Err(vec![Diagnostic {
+            level: Level::Error,
+            message,
+            spans: vec![],
+            suggestion: None,
+        }]),
     };
 
-    let result = session.deploy_contract(&contract, false, contract_ast);
-
     session.set_tx_sender(&default_tx_sender);
     result
 }
@@ -247,6 +484,21 @@ fn handle_emulated_contract_call(
     result
 }
 
+/// Evaluates the snippet of an `emulated-eval` entry, as its emulated sender. Nothing is
+/// deployed: the definitions of the snippet end up in the default contract of the session.
+fn handle_emulated_eval(
+    session: &mut Session,
+    tx: &EmulatedEvalSpecification,
+) -> Result<ExecutionResult, Vec<Diagnostic>> {
+    let default_tx_sender = session.get_tx_sender();
+    session.set_tx_sender(&tx.emulated_sender.to_string());
+
+    let result = session.eval(tx.code.clone(), false);
+
+    session.set_tx_sender(&default_tx_sender);
+    result
+}
+
 pub async fn generate_default_deployment(
     manifest: &ProjectManifest,
     network: &StacksNetwork,
@@ -254,6 +506,28 @@ pub async fn generate_default_deployment(
     file_accessor: Option<&dyn FileAccessor>,
     forced_min_epoch: Option<StacksEpochId>,
 ) -> Result<(DeploymentSpecification, DeploymentGenerationArtifacts), String> {
+    generate_default_deployment_with_cancellation(
+        manifest,
+        network,
+        no_batch,
+        file_accessor,
+        forced_min_epoch,
+        &CancellationToken::new(),
+    )
+    .await
+}
+
+/// Same as `generate_default_deployment`, but stops with `cancellation::CANCELLED_ERROR` between
+/// two requirements or two contracts once `cancellation` is cancelled
+pub async fn generate_default_deployment_with_cancellation(
+    manifest: &ProjectManifest,
+    network: &StacksNetwork,
+    no_batch: bool,
+    file_accessor: Option<&dyn FileAccessor>,
+    forced_min_epoch: Option<StacksEpochId>,
+    cancellation: &CancellationToken,
+) -> Result<(DeploymentSpecification, DeploymentGenerationArtifacts), String> {
+    let timer = PhaseTimer::start("network manifest loading");
     let network_manifest = match file_accessor {
         None => NetworkManifest::from_project_manifest_location(
             &manifest.location,
@@ -270,6 +544,7 @@ pub async fn generate_default_deployment(
             .await?
         }
     };
+    timer.end();
 
     let (stacks_node, bitcoin_node) = match network {
         StacksNetwork::Simnet => (None, None),
@@ -346,16 +621,17 @@ pub async fn generate_default_deployment(
 
     let session = Session::new(settings.clone());
 
-    let boot_contracts_data = BOOT_CONTRACTS_DATA.clone();
     let mut boot_contracts_ids = BTreeSet::new();
     let mut boot_contracts_asts = BTreeMap::new();
-    for (id, (contract, ast)) in boot_contracts_data {
+    for (id, (contract, ast)) in BOOT_CONTRACTS_DATA.iter() {
         boot_contracts_ids.insert(id.clone());
-        boot_contracts_asts.insert(id, (contract.clarity_version, ast));
+        boot_contracts_asts.insert(id.clone(), (contract.clarity_version, Arc::clone(ast)));
     }
     requirements_data.append(&mut boot_contracts_asts);
 
     let mut queue = VecDeque::new();
+    // Non fatal issues, reported to the user (and turned into errors in strict mode)
+    let mut warnings = vec![];
 
     if let Some(ref devnet) = network_manifest.devnet {
         if devnet.enable_subnet_node {
@@ -375,10 +651,13 @@ pub async fn generate_default_deployment(
     let mut contract_epochs = HashMap::new();
 
     // Build the ASTs / DependencySet for requirements - step required for Simnet/Devnet/Testnet/Mainnet
+    let timer = PhaseTimer::start("requirements resolution");
     if let Some(ref requirements) = manifest.project.requirements {
         let cache_location = &manifest.project.cache_location;
         let mut emulated_contracts_publish = HashMap::new();
         let mut requirements_publish = HashMap::new();
+        let mut mocked_requirements = BTreeSet::new();
+        let mut requirements_overrides = HashMap::new();
 
         // Load all the requirements
         // Some requirements are explicitly listed, some are discovered as we compute the ASTs.
@@ -392,10 +671,31 @@ pub async fn generate_default_deployment(
                     ))
                 }
             };
+            if requirement.mock && matches!(network, StacksNetwork::Simnet) {
+                if requirement_mocks_enabled() {
+                    mocked_requirements.insert(contract_id.clone());
+                } else {
+                    warnings.push(Warning::new(
+                        WarningCode::MockIgnored,
+                        format!(
+                            "mock of {} ignored, requirements mocks are disabled by CLARINET_MOCK_REQUIREMENTS",
+                            contract_id
+                        ),
+                        Some(manifest.location.clone()),
+                    ));
+                }
+            }
+            if requirement.epoch.is_some() || requirement.clarity_version.is_some() {
+                requirements_overrides.insert(
+                    contract_id.clone(),
+                    (requirement.epoch, requirement.clarity_version),
+                );
+            }
             queue.push_front((contract_id, None));
         }
 
         while let Some((contract_id, forced_clarity_version)) = queue.pop_front() {
+            cancellation.check()?;
             if requirements_deps.contains_key(&contract_id) {
                 continue;
             }
@@ -413,11 +713,35 @@ pub async fn generate_default_deployment(
                         )
                         .await?;
 
+                    let (source, contract_location) = if mocked_requirements.contains(&contract_id)
+                    {
+                        requirements::retrieve_contract_mock(
+                            &contract_id,
+                            cache_location,
+                            &file_accessor,
+                        )
+                        .await?
+                    } else {
+                        (source, contract_location)
+                    };
+
+                    let (epoch, clarity_version) = apply_requirement_overrides(
+                        &contract_id,
+                        epoch,
+                        clarity_version,
+                        &requirements_overrides,
+                    );
+
                     let epoch = match forced_min_epoch {
                         Some(min_epoch) => std::cmp::max(min_epoch, epoch),
                         None => epoch,
                     };
 
+                    if requirements_overrides.contains_key(&contract_id) {
+                        check_clarity_version_for_epoch(epoch, clarity_version)
+                            .map_err(|e| format!("requirement {}: {}", contract_id, e))?;
+                    }
+
                     contract_epochs.insert(contract_id.clone(), epoch);
 
                     // Build the struct representing the requirement in the deployment
@@ -428,6 +752,7 @@ pub async fn generate_default_deployment(
                             source: source.clone(),
                             location: contract_location,
                             clarity_version,
+                            description: None,
                         };
                         emulated_contracts_publish.insert(contract_id.clone(), data);
                     } else if matches!(network, StacksNetwork::Devnet | StacksNetwork::Testnet) {
@@ -455,36 +780,47 @@ pub async fn generate_default_deployment(
                             location: contract_location,
                             cost: deployment_fee_rate * source.len() as u64,
                             remap_principals,
+                            remap_contract_name: None,
+                            rewrite_literal_principals: false,
                             clarity_version,
+                            anchor_mode: AnchorMode::OnChainOnly,
+                            description: None,
                         };
                         requirements_publish.insert(contract_id.clone(), data);
                     }
 
                     // Compute the AST
-                    let contract = ClarityContract {
-                        code_source: ClarityCodeSource::ContractInMemory(source),
-                        name: contract_id.name.to_string(),
-                        deployer: ContractDeployer::ContractIdentifier(contract_id.clone()),
-                        clarity_version,
-                        epoch,
-                    };
+                    let contract = ClarityContract::builder()
+                        .source(source)
+                        .deployer(ContractDeployer::ContractIdentifier(contract_id.clone()))
+                        .clarity_version(clarity_version)
+                        .epoch(epoch)
+                        .build()?;
                     let (ast, _, _) = session.interpreter.build_ast(&contract);
-                    (clarity_version, ast)
+                    (clarity_version, Arc::new(ast))
                 }
             };
 
             // Detect the eventual dependencies for this AST
             let mut contract_data = BTreeMap::new();
             let (_, ast) = requirement_data;
+            // only the ASTs of the boot contracts are shared
+            let ast = Arc::try_unwrap(ast).unwrap_or_else(|ast| ContractAST::clone(&ast));
             let clarity_version = match forced_clarity_version {
                 Some(clarity_version) => clarity_version,
                 None => {
-                    let (_, _, clarity_version, _) = requirements::retrieve_contract(
+                    let (_, epoch, clarity_version, _) = requirements::retrieve_contract(
                         &contract_id,
                         cache_location,
                         &file_accessor,
                     )
                     .await?;
+                    let (_, clarity_version) = apply_requirement_overrides(
+                        &contract_id,
+                        epoch,
+                        clarity_version,
+                        &requirements_overrides,
+                    );
                     clarity_version
                 }
             };
@@ -499,7 +835,14 @@ pub async fn generate_default_deployment(
             match dependencies {
                 Ok(inferable_dependencies) => {
                     if inferable_dependencies.len() > 1 {
-                        println!("warning: inferable_dependencies contains more than one entry");
+                        warnings.push(Warning::new(
+                            WarningCode::AmbiguousDependencies,
+                            format!(
+                                "ambiguous dependencies detected for {}, only the first entry is used",
+                                contract_id
+                            ),
+                            None,
+                        ));
                     }
                     // We submitted a HashMap with one contract, so we have at most one result in the `inferable_dependencies` map.
                     // We will extract and keep the associated data (source, ast, deps).
@@ -510,7 +853,8 @@ pub async fn generate_default_deployment(
                             queue.push_back((dependency.contract_id.clone(), None));
                         }
                         requirements_deps.insert(contract_id.clone(), dependencies);
-                        requirements_data.insert(contract_id.clone(), (clarity_version, ast));
+                        requirements_data
+                            .insert(contract_id.clone(), (clarity_version, Arc::new(ast)));
                     }
                 }
                 Err((inferable_dependencies, non_inferable_dependencies)) => {
@@ -522,7 +866,7 @@ pub async fn generate_default_deployment(
                             queue.push_back((dependency.contract_id.clone(), None));
                         }
                     }
-                    requirements_data.insert(contract_id.clone(), (clarity_version, ast));
+                    requirements_data.insert(contract_id.clone(), (clarity_version, Arc::new(ast)));
                     queue.push_front((contract_id, None));
 
                     for non_inferable_contract_id in non_inferable_dependencies.into_iter() {
@@ -539,7 +883,14 @@ pub async fn generate_default_deployment(
                 &contract_epochs,
             ) {
                 Ok(ordered_contracts) => ordered_contracts,
-                Err(e) => return Err(format!("unable to order requirements {}", e)),
+                Err(e) => {
+                    return Err(format_ordering_error(
+                        &format!("unable to order requirements {}", e),
+                        &requirements_deps,
+                        &BTreeMap::new(),
+                        &requirements_data,
+                    ))
+                }
             };
 
             // Filter out boot contracts from requirement dependencies
@@ -558,10 +909,49 @@ pub async fn generate_default_deployment(
                     );
                 }
             } else if matches!(network, StacksNetwork::Devnet | StacksNetwork::Testnet) {
+                // Requirements are all published by the default deployer: their names can
+                // collide with each other, or with the contracts of the project
+                let mut taken_names = BTreeMap::new();
+                for (name, contract_config) in manifest.contracts.iter() {
+                    let deployer_address = match &contract_config.deployer {
+                        ContractDeployer::DefaultDeployer => Some(&default_deployer.stx_address),
+                        ContractDeployer::LabeledDeployer(label) => network_manifest
+                            .accounts
+                            .get(label)
+                            .map(|account| &account.stx_address),
+                        _ => None,
+                    };
+                    if deployer_address == Some(&default_deployer.stx_address) {
+                        taken_names.insert(name.to_string(), format!("contract {}", name));
+                    }
+                }
                 for contract_id in ordered_contracts_ids.iter() {
-                    let data = requirements_publish
+                    let mut data = requirements_publish
                         .remove(contract_id)
                         .expect("unable to retrieve contract");
+                    let contract_name = contract_id.name.to_string();
+                    if let Some(owner) = taken_names.get(&contract_name) {
+                        let remapped_name =
+                            get_available_contract_name(&contract_name, &taken_names)?;
+                        warnings.push(Warning::new(
+                            WarningCode::RequirementRemapped,
+                            format!(
+                                "requirement {} would be published as {}.{}, already used by {}: it will be published as {}.{} instead (set remap_contract_name in the deployment plan to pick another name)",
+                                contract_id,
+                                default_deployer.stx_address,
+                                contract_name,
+                                owner,
+                                default_deployer.stx_address,
+                                remapped_name,
+                            ),
+                            Some(manifest.location.clone()),
+                        ));
+                        data.remap_contract_name = Some(remapped_name);
+                    }
+                    taken_names.insert(
+                        data.get_remapped_contract_id().name.to_string(),
+                        format!("requirement {}", contract_id),
+                    );
                     let tx = TransactionSpecification::RequirementPublish(data);
                     add_transaction_to_epoch(
                         &mut transactions,
@@ -573,6 +963,8 @@ pub async fn generate_default_deployment(
         }
     }
 
+    timer.end();
+
     let mut contracts = HashMap::new();
     let mut contracts_sources = HashMap::new();
 
@@ -615,6 +1007,51 @@ pub async fn generate_default_deployment(
         }
     };
 
+    // Load the files included by the contracts, until all the includes are resolved
+    let mut sources = sources;
+    loop {
+        let missing_includes = includes::get_missing_includes(&sources)?;
+        if missing_includes.is_empty() {
+            break;
+        }
+        let mut included_sources = match file_accessor {
+            None => {
+                let mut included_sources = HashMap::new();
+                for location in missing_includes.iter() {
+                    if let Ok(source) = location.read_content_as_utf8() {
+                        included_sources.insert(location.to_string(), source);
+                    }
+                }
+                included_sources
+            }
+            Some(file_accessor) => {
+                file_accessor
+                    .read_files(missing_includes.iter().map(|l| l.to_string()).collect())
+                    .await?
+            }
+        };
+        for location in missing_includes.iter() {
+            match included_sources.remove(&location.to_string()) {
+                Some(source) => sources.insert(location.to_string(), source),
+                None => return Err(format!("unable to find included file {}", location)),
+            };
+        }
+    }
+    includes::check_pinned_includes(&sources)?;
+    let mut source_maps = HashMap::new();
+    let address_book = manifest.get_address_book(
+        network,
+        network_manifest
+            .accounts
+            .iter()
+            .map(|(name, account)| (name.as_str(), account.stx_address.as_str())),
+    );
+
+    // Contracts excluded from this network are still parsed, to make sure that
+    // no other contract depends on them.
+    let mut excluded_contracts_ids = BTreeSet::new();
+    let mut contracts_order = BTreeMap::new();
+
     for (name, contract_config) in manifest.contracts.iter() {
         let contract_name = match ContractName::try_from(name.to_string()) {
             Ok(res) => res,
@@ -656,6 +1093,27 @@ pub async fn generate_default_deployment(
             .clone();
 
         let contract_id = QualifiedContractIdentifier::new(sender.clone(), contract_name.clone());
+        let source = match includes::expand_includes(&contract_location, &sources)? {
+            Some((expanded_source, source_map)) => {
+                source_maps.insert(contract_id.clone(), source_map);
+                expanded_source
+            }
+            None => source,
+        };
+        let source = cfg_blocks::strip_cfg_blocks(&source, network)
+            .map_err(|e| format!("{}: {}", name, e))?;
+        let defines = manifest
+            .get_contract_defines(name, network, &address_book)
+            .map_err(|e| format!("{}: {}", name, e))?;
+        let source = match defines {
+            Some(defines) => inject_defines(&source, &defines),
+            None => source,
+        };
+        if !manifest.is_contract_enabled_on_network(name, network) {
+            excluded_contracts_ids.insert(contract_id.clone());
+        } else if let Some(order) = manifest.contracts_order.get(name) {
+            contracts_order.insert(contract_id.clone(), *order);
+        }
 
         let epoch = match forced_min_epoch {
             Some(min_epoch) => std::cmp::max(min_epoch, contract_config.epoch),
@@ -664,13 +1122,13 @@ pub async fn generate_default_deployment(
 
         contracts_sources.insert(
             contract_id.clone(),
-            ClarityContract {
-                code_source: ClarityCodeSource::ContractInMemory(source.clone()),
-                deployer: ContractDeployer::Address(sender.to_address()),
-                name: contract_name.to_string(),
-                clarity_version: contract_config.clarity_version,
-                epoch,
-            },
+            ClarityContract::builder()
+                .source(source.clone())
+                .name(contract_name.to_string())
+                .deployer(ContractDeployer::Address(sender.to_address()))
+                .clarity_version(contract_config.clarity_version)
+                .epoch(epoch)
+                .build()?,
         );
 
         let contract_spec = if matches!(network, StacksNetwork::Simnet) {
@@ -681,6 +1139,7 @@ pub async fn generate_default_deployment(
                     source,
                     location: contract_location,
                     clarity_version: contract_config.clarity_version,
+                    description: None,
                 },
             )
         } else {
@@ -691,12 +1150,15 @@ pub async fn generate_default_deployment(
                 cost: deployment_fee_rate
                     .saturating_mul(source.as_bytes().len().try_into().unwrap()),
                 source,
-                anchor_block_only: true,
+                anchor_mode: AnchorMode::OnChainOnly,
                 clarity_version: contract_config.clarity_version,
+                description: None,
             })
         };
 
-        contracts.insert(contract_id, contract_spec);
+        if !excluded_contracts_ids.contains(&contract_id) {
+            contracts.insert(contract_id, contract_spec);
+        }
     }
 
     let session = Session::new(settings);
@@ -707,12 +1169,20 @@ pub async fn generate_default_deployment(
 
     let mut asts_success = true;
 
-    for (contract_id, contract) in contracts_sources.into_iter() {
-        let (ast, diags, ast_success) = session.interpreter.build_ast(&contract);
+    let timer = PhaseTimer::start("contracts asts");
+    let contracts_asts =
+        build_contracts_asts(contracts_sources.into_iter().collect(), cancellation);
+    timer.end();
+    cancellation.check()?;
+    for (contract_id, contract, (ast, diags, ast_success)) in contracts_asts.into_iter() {
+        contract_epochs.insert(contract_id.clone(), contract.epoch);
+        if excluded_contracts_ids.contains(&contract_id) {
+            contract_data.insert(contract_id, (contract.clarity_version, ast));
+            continue;
+        }
         contract_asts.insert(contract_id.clone(), ast.clone());
         contract_data.insert(contract_id.clone(), (contract.clarity_version, ast));
-        contract_diags.insert(contract_id.clone(), diags);
-        contract_epochs.insert(contract_id, contract.epoch);
+        contract_diags.insert(contract_id, diags);
         asts_success = asts_success && ast_success;
     }
 
@@ -728,16 +1198,45 @@ pub async fn generate_default_deployment(
         }
     };
 
+    for (contract_id, contract_dependencies) in dependencies.iter() {
+        if excluded_contracts_ids.contains(contract_id) {
+            continue;
+        }
+        if let Some(dependency) = contract_dependencies
+            .iter()
+            .find(|dependency| excluded_contracts_ids.contains(&dependency.contract_id))
+        {
+            return Err(format!(
+                "contract {} depends on {}, which is not enabled on {:?}",
+                contract_id.name, dependency.contract_id.name, network
+            ));
+        }
+    }
+    dependencies.retain(|contract_id, _| !excluded_contracts_ids.contains(contract_id));
+
     for contract_id in boot_contracts_ids.into_iter() {
         dependencies.insert(contract_id.clone(), DependencySet::new());
     }
 
     dependencies.extend(requirements_deps);
 
+    let ordering_constraints = add_order_constraints(&dependencies, &contracts_order);
     let ordered_contracts_ids =
-        match ASTDependencyDetector::order_contracts(&dependencies, &contract_epochs) {
+        match ASTDependencyDetector::order_contracts(&ordering_constraints, &contract_epochs) {
             Ok(ordered_contracts_ids) => ordered_contracts_ids,
-            Err(e) => return Err(e.err.to_string()),
+            Err(_) if ASTDependencyDetector::order_contracts(&dependencies, &contract_epochs).is_ok() => {
+                return Err(
+                    "unable to order contracts: the `order` settings of Clarinet.toml conflict with the dependencies of the contracts".to_string(),
+                )
+            }
+            Err(e) => {
+                return Err(format_ordering_error(
+                    &e.err.to_string(),
+                    &dependencies,
+                    &contract_data,
+                    &requirements_data,
+                ))
+            }
         };
 
     // Track the latest epoch that a contract is deployed in, so that we can
@@ -768,6 +1267,58 @@ pub async fn generate_default_deployment(
         add_transaction_to_epoch(&mut transactions, tx, &contract_epochs[contract_id].into());
     }
 
+    // The SIP-016 metadata setters are called once all the contracts are published
+    let control_port = network_manifest
+        .devnet
+        .as_ref()
+        .map_or(20446, |devnet| devnet.orchestrator_control_port);
+    for contract_id in contracts_map.keys() {
+        let Some(config) = manifest.contracts_metadata.get(contract_id.name.as_str()) else {
+            continue;
+        };
+        let Some(metadata_location) = manifest.get_token_metadata_location(&contract_id.name)?
+        else {
+            continue;
+        };
+        let content = match file_accessor {
+            None => metadata_location.read_content_as_utf8(),
+            Some(file_accessor) => file_accessor.read_file(metadata_location.to_string()).await,
+        }
+        .map_err(|_| {
+            format!(
+                "unable to find the metadata of {} at {}",
+                contract_id.name, metadata_location
+            )
+        })?;
+        validate_token_metadata(&content, config).map_err(|errors| {
+            format!(
+                "invalid SIP-016 metadata {}: {}",
+                metadata_location,
+                errors.join(", ")
+            )
+        })?;
+        let Some(uri) =
+            token_metadata::get_metadata_uri(config, network, control_port, contract_id)
+        else {
+            warnings.push(Warning::new(
+                WarningCode::MetadataUriMissing,
+                format!(
+                    "no metadata uri for {} on {:?}, {} is not called",
+                    contract_id.name, network, config.setter
+                ),
+                Some(manifest.location.clone()),
+            ));
+            continue;
+        };
+        let Some(ast) = contract_asts.get(contract_id) else {
+            continue;
+        };
+        let argument = token_metadata::get_uri_argument(&ast.expressions, &config.setter, &uri)
+            .map_err(|e| format!("contract {}: {}", contract_id.name, e))?;
+        let tx = token_metadata::get_metadata_call(network, contract_id, &config.setter, argument)?;
+        add_transaction_to_epoch(&mut transactions, tx, &contract_epochs[contract_id].into());
+    }
+
     let tx_chain_limit = match no_batch {
         true => 100_000,
         false => 25,
@@ -781,6 +1332,8 @@ pub async fn generate_default_deployment(
                 id: batch_count,
                 transactions: txs.to_vec(),
                 epoch: Some(epoch),
+                post_checks: vec![],
+                description: None,
             });
             batch_count += 1;
         }
@@ -826,8 +1379,12 @@ pub async fn generate_default_deployment(
         } else {
             None
         },
-        plan: TransactionPlanSpecification { batches },
+        plan: TransactionPlanSpecification {
+            batches,
+            post_checks: vec![],
+        },
         contracts: contracts_map,
+        description: None,
     };
 
     let artifacts = DeploymentGenerationArtifacts {
@@ -836,6 +1393,9 @@ pub async fn generate_default_deployment(
         diags: contract_diags,
         success: asts_success,
         results_values: HashMap::new(),
+        source_maps,
+        warnings,
+        evals: vec![],
         analysis: HashMap::new(),
         session,
     };
@@ -843,6 +1403,101 @@ pub async fn generate_default_deployment(
     Ok((deployment, artifacts))
 }
 
+/// Contracts are deployed after their dependencies. Contracts with an `order` setting are also
+/// deployed after the contracts with a lower `order`, as if they depended on them.
+/// Remaining ties are broken by the contract identifiers, in alphabetical order, so that
+/// regenerating a plan doesn't shuffle unrelated transactions.
+/// Suffixes `name` (`name-2`, `name-3`, ...) until it doesn't collide with a taken name
+fn get_available_contract_name(
+    name: &str,
+    taken_names: &BTreeMap<String, String>,
+) -> Result<ContractName, String> {
+    let mut index = 2;
+    loop {
+        let candidate = format!("{}-{}", name, index);
+        if !taken_names.contains_key(&candidate) {
+            return ContractName::try_from(candidate.clone()).map_err(|_| {
+                format!(
+                    "unable to remap {}: {} is not a valid contract name, set remap_contract_name in the deployment plan",
+                    name, candidate
+                )
+            });
+        }
+        index += 1;
+    }
+}
+
+fn add_order_constraints(
+    dependencies: &BTreeMap<QualifiedContractIdentifier, DependencySet>,
+    contracts_order: &BTreeMap<QualifiedContractIdentifier, i64>,
+) -> BTreeMap<QualifiedContractIdentifier, DependencySet> {
+    let mut constrained_dependencies = dependencies.clone();
+    for (contract_id, order) in contracts_order.iter() {
+        let Some(contract_dependencies) = constrained_dependencies.get_mut(contract_id) else {
+            continue;
+        };
+        for (other_contract_id, other_order) in contracts_order.iter() {
+            if other_order < order {
+                contract_dependencies.add_dependency(other_contract_id.clone(), true);
+            }
+        }
+    }
+    constrained_dependencies
+}
+
+/// Requirements flagged with `mock = true` are replaced by their mocks in simnet.
+/// Setting `CLARINET_MOCK_REQUIREMENTS=0` switches back to the real sources, without
+/// editing the manifest.
+/// Append the report of the dependency cycle, if any, to an ordering error
+fn format_ordering_error(
+    message: &str,
+    dependencies: &BTreeMap<QualifiedContractIdentifier, DependencySet>,
+    contracts_data: &BTreeMap<QualifiedContractIdentifier, (ClarityVersion, ContractAST)>,
+    requirements_data: &BTreeMap<QualifiedContractIdentifier, (ClarityVersion, Arc<ContractAST>)>,
+) -> String {
+    let cycle = find_dependency_cycle(dependencies, |contract_id| {
+        contracts_data
+            .get(contract_id)
+            .map(|(_, ast)| ast)
+            .or_else(|| {
+                requirements_data
+                    .get(contract_id)
+                    .map(|(_, ast)| ast.as_ref())
+            })
+    });
+    match cycle {
+        Some(cycle) => format!("{}\n{}", message, cycle),
+        None => message.to_string(),
+    }
+}
+
+/// Replace the epoch and clarity version retrieved from the network by the ones
+/// specified on the requirement in Clarinet.toml, if any
+fn apply_requirement_overrides(
+    contract_id: &QualifiedContractIdentifier,
+    epoch: StacksEpochId,
+    clarity_version: ClarityVersion,
+    overrides: &HashMap<
+        QualifiedContractIdentifier,
+        (Option<StacksEpochId>, Option<ClarityVersion>),
+    >,
+) -> (StacksEpochId, ClarityVersion) {
+    match overrides.get(contract_id) {
+        Some((epoch_override, clarity_version_override)) => (
+            epoch_override.unwrap_or(epoch),
+            clarity_version_override.unwrap_or(clarity_version),
+        ),
+        None => (epoch, clarity_version),
+    }
+}
+
+fn requirement_mocks_enabled() -> bool {
+    match std::env::var("CLARINET_MOCK_REQUIREMENTS") {
+        Ok(value) => value != "0" && !value.eq_ignore_ascii_case("false"),
+        Err(_) => true,
+    }
+}
+
 fn add_transaction_to_epoch(
     transactions: &mut BTreeMap<EpochSpec, Vec<TransactionSpecification>>,
     transaction: TransactionSpecification,
@@ -873,32 +1528,230 @@ pub fn get_default_deployment_path(
     Ok(deployment_path)
 }
 
+/// Principals a deployment can refer to by name, its genesis wallets are the accounts
+pub fn get_deployment_address_book(
+    manifest: &ProjectManifest,
+    deployment: &DeploymentSpecification,
+) -> AddressBook {
+    let wallets: Vec<(String, String)> = match deployment.genesis {
+        Some(ref genesis) => genesis
+            .wallets
+            .iter()
+            .map(|wallet| (wallet.name.clone(), wallet.address.to_string()))
+            .collect(),
+        None => vec![],
+    };
+    manifest.get_address_book(
+        &deployment.network,
+        wallets
+            .iter()
+            .map(|(name, address)| (name.as_str(), address.as_str())),
+    )
+}
+
+/// Principals a plan file can refer to by name. The plans without genesis (testnet and
+/// mainnet) use the accounts of the network manifest, which is only loaded when needed.
+fn get_plan_address_book(
+    manifest: &ProjectManifest,
+    network: &StacksNetwork,
+    specification_file: &mut DeploymentSpecificationFile,
+) -> AddressBook {
+    if let Some(ref genesis) = specification_file.genesis {
+        return manifest.get_address_book(
+            network,
+            genesis
+                .wallets
+                .iter()
+                .map(|wallet| (wallet.name.as_str(), wallet.address.as_str())),
+        );
+    }
+    let accounts = if specification_file.has_named_principals() {
+        NetworkManifest::from_project_manifest_location(
+            &manifest.location,
+            &network.get_networks(),
+            Some(&manifest.project.cache_location),
+            None,
+        )
+        .map(|network_manifest| network_manifest.accounts)
+        .unwrap_or_default()
+    } else {
+        BTreeMap::new()
+    };
+    manifest.get_address_book(
+        network,
+        accounts
+            .iter()
+            .map(|(name, account)| (name.as_str(), account.stx_address.as_str())),
+    )
+}
+
 pub fn load_deployment(
     manifest: &ProjectManifest,
     deployment_plan_location: &FileLocation,
+) -> Result<DeploymentSpecification, String> {
+    load_pinned_deployment(manifest, deployment_plan_location, None)
+}
+
+/// Loads a plan, local or served over http(s), checked against the SHA-256 it's pinned to
+/// before it's parsed
+pub fn load_pinned_deployment(
+    manifest: &ProjectManifest,
+    deployment_plan_location: &FileLocation,
+    sha256: Option<&str>,
 ) -> Result<DeploymentSpecification, String> {
     let project_root_location = manifest.location.get_project_root_location()?;
-    let spec = match DeploymentSpecification::from_config_file(
-        deployment_plan_location,
-        &project_root_location,
-    ) {
-        Ok(spec) => spec,
-        Err(msg) => {
-            return Err(format!(
-                "error: {} syntax incorrect\n{}",
-                deployment_plan_location, msg
-            ));
-        }
+    let syntax_error = |msg: String| {
+        format!(
+            "error: {} syntax incorrect\n{}",
+            deployment_plan_location, msg
+        )
     };
+    let content = deployment_plan_location.read_content()?;
+    if let Some(sha256) = sha256 {
+        clarinet_files::net::check_sha256(&deployment_plan_location.to_string(), &content, sha256)?;
+    }
+    let mut specification_file =
+        DeploymentSpecificationFile::from_content(&content).map_err(syntax_error)?;
+    let network = specification_file.get_network().map_err(syntax_error)?;
+    let address_book = get_plan_address_book(manifest, &network, &mut specification_file);
+    specification_file
+        .resolve_named_principals(&address_book)
+        .map_err(syntax_error)?;
+    let mut spec = DeploymentSpecification::from_specifications(
+        &specification_file,
+        &network,
+        &project_root_location,
+        None,
+    )
+    .map_err(syntax_error)?;
+    apply_manifest_to_deployment_sources(manifest, &mut spec, &address_book)?;
     Ok(spec)
 }
 
+/// Appends the `define-constant` declarations from `[project.defines]` to a contract.
+/// They are added at the end of the source, so that the lines of the diagnostics are unchanged.
+fn inject_defines(source: &str, defines: &str) -> String {
+    format!(
+        "{}\n\n;; [project.defines]\n{}\n",
+        source.trim_end(),
+        defines
+    )
+}
+
+/// The sources of the deployment plans on disk are read from the contracts files,
+/// the cfg blocks and the defines of the manifest have to be applied again
+fn apply_manifest_to_deployment_sources(
+    manifest: &ProjectManifest,
+    deployment: &mut DeploymentSpecification,
+    address_book: &AddressBook,
+) -> Result<(), String> {
+    let base_location = manifest.location.get_parent_location()?;
+    let mut contracts_defines = HashMap::new();
+    for (name, contract_config) in manifest.contracts.iter() {
+        if let Some(defines) =
+            manifest.get_contract_defines(name, &deployment.network, address_book)?
+        {
+            let mut contract_location = base_location.clone();
+            contract_location.append_path(contract_config.expect_contract_path_as_str())?;
+            contracts_defines.insert(contract_location, defines);
+        }
+    }
+
+    for batch in deployment.plan.batches.iter_mut() {
+        for transaction in batch.transactions.iter_mut() {
+            let (contract_id, source, location) = match transaction {
+                TransactionSpecification::ContractPublish(tx) => (
+                    QualifiedContractIdentifier::new(
+                        tx.expected_sender.clone(),
+                        tx.contract_name.clone(),
+                    ),
+                    &mut tx.source,
+                    &tx.location,
+                ),
+                TransactionSpecification::EmulatedContractPublish(tx) => (
+                    QualifiedContractIdentifier::new(
+                        tx.emulated_sender.clone(),
+                        tx.contract_name.clone(),
+                    ),
+                    &mut tx.source,
+                    &tx.location,
+                ),
+                _ => continue,
+            };
+            let defines = contracts_defines.get(location);
+            if !cfg_blocks::has_cfg_blocks(source) && defines.is_none() {
+                continue;
+            }
+            *source = cfg_blocks::strip_cfg_blocks(source, &deployment.network)
+                .map_err(|e| format!("{}: {}", contract_id.name, e))?;
+            if let Some(defines) = defines {
+                *source = inject_defines(source, defines);
+            }
+            if let Some((contract_source, _)) = deployment.contracts.get_mut(&contract_id) {
+                *contract_source = source.clone();
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Analyzes the mainnet variant of the contracts with cfg blocks, in which the blocks
+/// restricted to other networks are removed. Returns `None` if there are no cfg blocks.
+pub fn check_mainnet_variant(
+    manifest: &ProjectManifest,
+    deployment: &DeploymentSpecification,
+) -> Result<Option<HashMap<QualifiedContractIdentifier, Vec<Diagnostic>>>, String> {
+    let mut variant = deployment.clone();
+    let mut variant_contracts = BTreeSet::new();
+    for batch in variant.plan.batches.iter_mut() {
+        for transaction in batch.transactions.iter_mut() {
+            let TransactionSpecification::EmulatedContractPublish(tx) = transaction else {
+                continue;
+            };
+            if !cfg_blocks::has_cfg_blocks(&tx.source) {
+                continue;
+            }
+            tx.source = cfg_blocks::strip_cfg_blocks(&tx.source, &StacksNetwork::Mainnet)?;
+            let contract_id = QualifiedContractIdentifier::new(
+                tx.emulated_sender.clone(),
+                tx.contract_name.clone(),
+            );
+            if let Some((contract_source, _)) = variant.contracts.get_mut(&contract_id) {
+                *contract_source = tx.source.clone();
+            }
+            variant_contracts.insert(contract_id);
+        }
+    }
+    if variant_contracts.is_empty() {
+        return Ok(None);
+    }
+
+    let artifacts = setup_session_with_deployment(manifest, &variant, None);
+    let diags = artifacts
+        .diags
+        .into_iter()
+        .filter(|(contract_id, _)| variant_contracts.contains(contract_id))
+        .map(|(contract_id, diags)| {
+            let diags = diags
+                .into_iter()
+                .map(|mut diag| {
+                    diag.message = format!("(mainnet variant) {}", diag.message);
+                    diag
+                })
+                .collect();
+            (contract_id, diags)
+        })
+        .collect();
+    Ok(Some(diags))
+}
+
 #[cfg(test)]
 mod tests {
     use clarity::vm::{types::TupleData, ClarityName, ClarityVersion, Value};
     use clarity_repl::repl::{clarity_values::to_raw_value, SessionSettings};
 
     use super::*;
+    use crate::types::{BnsOperation, BnsOperationSpecification};
 
     static DEPLOYER: &str = "ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM";
 
@@ -914,9 +1767,54 @@ mod tests {
             source: source.to_string(),
             clarity_version: ClarityVersion::Clarity2,
             location: FileLocation::from_path_string("/contracts/contract_1.clar").unwrap(),
+            description: None,
         };
 
-        handle_emulated_contract_publish(session, &emulated_publish_spec, None, epoch)
+        handle_emulated_contract_publish(session, &emulated_publish_spec, None, epoch, false, false)
+    }
+
+    #[test]
+    fn test_get_batch_epoch_for_clarity_version() {
+        let batch = |clarity_version| TransactionsBatchSpecification {
+            id: 0,
+            transactions: vec![TransactionSpecification::EmulatedContractPublish(
+                EmulatedContractPublishSpecification {
+                    contract_name: ContractName::from("contract_1"),
+                    emulated_sender: PrincipalData::parse_standard_principal(DEPLOYER).unwrap(),
+                    source: "(define-data-var count uint u0)".to_string(),
+                    clarity_version,
+                    location: FileLocation::from_path_string("/contracts/contract_1.clar").unwrap(),
+                    description: None,
+                },
+            )],
+            epoch: None,
+            post_checks: vec![],
+            description: None,
+        };
+        assert_eq!(
+            get_batch_epoch(
+                &batch(ClarityVersion::Clarity1),
+                None,
+                StacksEpochId::Epoch20
+            ),
+            StacksEpochId::Epoch20
+        );
+        assert_eq!(
+            get_batch_epoch(
+                &batch(ClarityVersion::Clarity2),
+                None,
+                StacksEpochId::Epoch20
+            ),
+            StacksEpochId::Epoch21
+        );
+        assert_eq!(
+            get_batch_epoch(
+                &batch(ClarityVersion::Clarity3),
+                None,
+                StacksEpochId::Epoch25
+            ),
+            StacksEpochId::Epoch30
+        );
     }
 
     #[test]
@@ -981,6 +1879,7 @@ mod tests {
             emulated_sender: PrincipalData::parse_standard_principal(DEPLOYER).unwrap(),
             method: ClarityName::from("add"),
             parameters: vec!["1".to_string()],
+            description: None,
         };
         let result = handle_emulated_contract_call(&mut session, &contract_call_spec);
         assert!(result.is_ok());
@@ -1015,6 +1914,7 @@ mod tests {
             emulated_sender: PrincipalData::parse_standard_principal(DEPLOYER).unwrap(),
             method: ClarityName::from("set-sum"),
             parameters: vec!["2".to_string(), "(list 20 20)".to_string()],
+            description: None,
         };
         let result = handle_emulated_contract_call(&mut session, &contract_call_spec);
         assert!(result.is_ok());
@@ -1049,6 +1949,7 @@ mod tests {
             emulated_sender: PrincipalData::parse_standard_principal(DEPLOYER).unwrap(),
             method: ClarityName::from("set-data"),
             parameters: vec!["{ a: 2 }".to_string(), "{ b: u3 }".to_string()],
+            description: None,
         };
         let result = handle_emulated_contract_call(&mut session, &contract_call_spec);
         assert!(result.is_ok());
@@ -1092,8 +1993,9 @@ mod tests {
             recipient: PrincipalData::Standard(receiver_principal).clone(),
             mstx_amount: 1000,
             cost: 0,
-            anchor_block_only: true,
+            anchor_mode: AnchorMode::OnChainOnly,
             memo: [0u8; 34],
+            description: None,
         };
 
         handle_stx_transfer(&mut session, &stx_transfer_spec);
@@ -1103,4 +2005,64 @@ mod tests {
         assert_eq!(*stx_maps.get(sender).unwrap(), 999000);
         assert_eq!(*stx_maps.get(receiver).unwrap(), 1000);
     }
+
+    #[test]
+    fn test_bns_name_preorder() {
+        let mut session = Session::new(SessionSettings::default());
+        session.update_epoch(StacksEpochId::Epoch25);
+        let bns_contract_id =
+            QualifiedContractIdentifier::parse("ST000000000000000000002AMW42H.bns").unwrap();
+        let (bns_contract, bns_ast) = BOOT_CONTRACTS_DATA.get(&bns_contract_id).unwrap();
+        let _ = session
+            .interpreter
+            .run(bns_contract, Some(bns_ast.as_ref()), false, None);
+
+        let sender = PrincipalData::parse_standard_principal(DEPLOYER).unwrap();
+        let _ = session
+            .interpreter
+            .mint_stx_balance(PrincipalData::Standard(sender.clone()), 1000000);
+
+        let mut preorder_spec = BnsOperationSpecification {
+            expected_sender: sender,
+            namespace: "btc".to_string(),
+            name: "clarinet".to_string(),
+            operation: BnsOperation::NamePreorder {
+                salt: vec![1, 2, 3],
+                stx_to_burn: 1000,
+            },
+            cost: 0,
+            anchor_mode: AnchorMode::OnChainOnly,
+            description: None,
+        };
+        assert_eq!(preorder_spec.get_bns_contract_id(), bns_contract_id);
+
+        let result =
+            handle_emulated_contract_call(&mut session, &preorder_spec.as_emulated_contract_call());
+        assert!(result.is_ok());
+        assert!(get_contract_call_errors(&result).is_empty());
+
+        preorder_spec.name = "clarinet-sdk".to_string();
+        preorder_spec.operation = BnsOperation::NamePreorder {
+            salt: vec![1, 2, 3],
+            stx_to_burn: 2000000,
+        };
+        let result =
+            handle_emulated_contract_call(&mut session, &preorder_spec.as_emulated_contract_call());
+        assert_eq!(
+            get_contract_call_errors(&result),
+            vec!["returned (err 4001)".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_get_available_contract_name() {
+        let mut taken_names = BTreeMap::new();
+        taken_names.insert("token".to_string(), "contract token".to_string());
+        let name = get_available_contract_name("token", &taken_names).unwrap();
+        assert_eq!(name.as_str(), "token-2");
+
+        taken_names.insert("token-2".to_string(), "requirement SP1.token".to_string());
+        let name = get_available_contract_name("token", &taken_names).unwrap();
+        assert_eq!(name.as_str(), "token-3");
+    }
 }
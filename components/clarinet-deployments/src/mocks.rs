@@ -0,0 +1,287 @@
+use serde_json::Value;
+
+/// Subset of the contract interface, as returned by the Stacks API
+/// (`/v2/contracts/interface/{address}/{name}`).
+#[derive(Deserialize, Debug)]
+pub struct ContractInterfaceFile {
+    #[serde(default)]
+    pub functions: Vec<InterfaceFunction>,
+    #[serde(default)]
+    pub variables: Vec<InterfaceVariable>,
+    #[serde(default)]
+    pub maps: Vec<InterfaceMap>,
+    #[serde(default)]
+    pub fungible_tokens: Vec<InterfaceToken>,
+    #[serde(default)]
+    pub non_fungible_tokens: Vec<InterfaceToken>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct InterfaceFunction {
+    pub name: String,
+    pub access: String,
+    pub args: Vec<InterfaceArg>,
+    pub outputs: InterfaceOutputs,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct InterfaceArg {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub type_: Value,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct InterfaceOutputs {
+    #[serde(rename = "type")]
+    pub type_: Value,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct InterfaceVariable {
+    pub name: String,
+    pub access: String,
+    #[serde(rename = "type")]
+    pub type_: Value,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct InterfaceMap {
+    pub name: String,
+    pub key: Value,
+    pub value: Value,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct InterfaceToken {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub type_: Option<Value>,
+}
+
+/// Returns the Clarity type signature of an interface type.
+/// `none` (the type of a response branch that is never used) can't be written
+/// in Clarity and is replaced by `uint`.
+fn type_signature(type_: &Value) -> Result<String, String> {
+    let signature = match type_ {
+        Value::String(atom) => match atom.as_str() {
+            "uint128" => "uint".to_string(),
+            "int128" => "int".to_string(),
+            "bool" => "bool".to_string(),
+            "principal" | "trait_reference" => "principal".to_string(),
+            "none" => "uint".to_string(),
+            _ => return Err(format!("unsupported type {}", atom)),
+        },
+        Value::Object(entries) => {
+            let (kind, inner) = entries
+                .iter()
+                .next()
+                .ok_or("unexpected empty type".to_string())?;
+            match kind.as_str() {
+                "buffer" => format!("(buff {})", get_length(inner)?),
+                "string-ascii" => format!("(string-ascii {})", get_length(inner)?),
+                "string-utf8" => format!("(string-utf8 {})", get_length(inner)?),
+                "optional" => format!("(optional {})", type_signature(inner)?),
+                "response" => format!(
+                    "(response {} {})",
+                    type_signature(&inner["ok"])?,
+                    type_signature(&inner["error"])?
+                ),
+                "list" => format!(
+                    "(list {} {})",
+                    get_length(inner)?,
+                    type_signature(&inner["type"])?
+                ),
+                "tuple" => {
+                    let fields = get_tuple_fields(inner)?
+                        .into_iter()
+                        .map(|(name, type_)| Ok(format!("{}: {}", name, type_signature(type_)?)))
+                        .collect::<Result<Vec<_>, String>>()?;
+                    format!("{{ {} }}", fields.join(", "))
+                }
+                _ => return Err(format!("unsupported type {}", kind)),
+            }
+        }
+        _ => return Err(format!("unsupported type {}", type_)),
+    };
+    Ok(signature)
+}
+
+/// Returns a value of the given type, used as the placeholder return value of
+/// the stubs and as the initial value of the mocks.
+fn default_value(type_: &Value) -> Result<String, String> {
+    let value = match type_ {
+        Value::String(atom) => match atom.as_str() {
+            "uint128" | "none" => "u0".to_string(),
+            "int128" => "0".to_string(),
+            "bool" => "false".to_string(),
+            "principal" | "trait_reference" => "tx-sender".to_string(),
+            _ => return Err(format!("unsupported type {}", atom)),
+        },
+        Value::Object(entries) => {
+            let (kind, inner) = entries
+                .iter()
+                .next()
+                .ok_or("unexpected empty type".to_string())?;
+            match kind.as_str() {
+                "buffer" => "0x".to_string(),
+                "string-ascii" => "\"\"".to_string(),
+                "string-utf8" => "u\"\"".to_string(),
+                "optional" => "none".to_string(),
+                "response" => format!("(ok {})", default_value(&inner["ok"])?),
+                "list" => "(list)".to_string(),
+                "tuple" => {
+                    let fields = get_tuple_fields(inner)?
+                        .into_iter()
+                        .map(|(name, type_)| Ok(format!("{}: {}", name, default_value(type_)?)))
+                        .collect::<Result<Vec<_>, String>>()?;
+                    format!("{{ {} }}", fields.join(", "))
+                }
+                _ => return Err(format!("unsupported type {}", kind)),
+            }
+        }
+        _ => return Err(format!("unsupported type {}", type_)),
+    };
+    Ok(value)
+}
+
+fn get_length(type_: &Value) -> Result<u64, String> {
+    type_["length"]
+        .as_u64()
+        .ok_or(format!("missing length in type {}", type_))
+}
+
+fn get_tuple_fields(fields: &Value) -> Result<Vec<(&str, &Value)>, String> {
+    fields
+        .as_array()
+        .ok_or(format!("invalid tuple type {}", fields))?
+        .iter()
+        .map(|field| {
+            let name = field["name"]
+                .as_str()
+                .ok_or(format!("invalid tuple field {}", field))?;
+            Ok((name, &field["type"]))
+        })
+        .collect()
+}
+
+fn function_signature(function: &InterfaceFunction) -> Result<String, String> {
+    let keyword = match function.access.as_str() {
+        "public" => "define-public",
+        "read_only" => "define-read-only",
+        "private" => "define-private",
+        access => return Err(format!("unknown access {} for {}", access, function.name)),
+    };
+    let mut signature = format!("({} ({}", keyword, function.name);
+    for arg in function.args.iter() {
+        signature.push_str(&format!(" ({} {})", arg.name, type_signature(&arg.type_)?));
+    }
+    signature.push(')');
+    Ok(signature)
+}
+
+/// Generates the definitions shared by the stub and the mock (tokens, variables and maps).
+fn definitions(interface: &ContractInterfaceFile) -> Result<Vec<String>, String> {
+    let mut lines = vec![];
+    for token in interface.fungible_tokens.iter() {
+        lines.push(format!("(define-fungible-token {})", token.name));
+    }
+    for token in interface.non_fungible_tokens.iter() {
+        let type_ = token
+            .type_
+            .as_ref()
+            .ok_or(format!("missing type for token {}", token.name))?;
+        lines.push(format!(
+            "(define-non-fungible-token {} {})",
+            token.name,
+            type_signature(type_)?
+        ));
+    }
+    for variable in interface.variables.iter() {
+        if variable.access == "constant" {
+            lines.push(format!(
+                "(define-constant {} {})",
+                variable.name,
+                default_value(&variable.type_)?
+            ));
+        } else {
+            lines.push(format!(
+                "(define-data-var {} {} {})",
+                variable.name,
+                type_signature(&variable.type_)?,
+                default_value(&variable.type_)?
+            ));
+        }
+    }
+    for map in interface.maps.iter() {
+        lines.push(format!(
+            "(define-map {} {} {})",
+            map.name,
+            type_signature(&map.key)?,
+            type_signature(&map.value)?
+        ));
+    }
+    Ok(lines)
+}
+
+pub fn generate_stub_contract(
+    contract_name: &str,
+    interface: &ContractInterfaceFile,
+) -> Result<String, String> {
+    let mut lines = vec![
+        format!(";; title: {}", contract_name),
+        ";; Stub generated from a contract interface, the function bodies have to be implemented"
+            .to_string(),
+        "".to_string(),
+    ];
+    lines.append(&mut definitions(interface)?);
+
+    for function in interface.functions.iter() {
+        lines.push("".to_string());
+        lines.push(function_signature(function)?);
+        lines.push("  ;; TODO: implement".to_string());
+        lines.push(format!("  {})", default_value(&function.outputs.type_)?));
+    }
+    lines.push("".to_string());
+    Ok(lines.join("\n"))
+}
+
+/// The mock exposes the same public and read-only functions as the original contract.
+/// Each function returns a value stored in a data-var, that tests can set with the
+/// generated `set-<function>-result` functions.
+pub fn generate_mock_contract(
+    contract_name: &str,
+    interface: &ContractInterfaceFile,
+) -> Result<String, String> {
+    let mut lines = vec![
+        format!(";; title: {} (mock)", contract_name),
+        ";; Mock generated from a contract interface".to_string(),
+        ";; Use the set-<function>-result functions to configure the returned values".to_string(),
+        "".to_string(),
+    ];
+    lines.append(&mut definitions(interface)?);
+
+    for function in interface.functions.iter() {
+        if function.access == "private" {
+            continue;
+        }
+        let result_var = format!("{}-result", function.name);
+        let result_type = type_signature(&function.outputs.type_)?;
+        lines.push("".to_string());
+        lines.push(format!(
+            "(define-data-var {} {} {})",
+            result_var,
+            result_type,
+            default_value(&function.outputs.type_)?
+        ));
+        lines.push(format!(
+            "(define-public (set-{} (result {}))",
+            result_var, result_type
+        ));
+        lines.push(format!("  (ok (var-set {} result)))", result_var));
+        lines.push(function_signature(function)?);
+        lines.push(format!("  (var-get {}))", result_var));
+    }
+    lines.push("".to_string());
+    Ok(lines.join("\n"))
+}
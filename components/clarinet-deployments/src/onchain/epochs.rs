@@ -0,0 +1,135 @@
+//! Epoch schedule of the devnet. The batches of a plan are broadcasted once the bitcoin chain
+//! reaches the activation height of their epoch, as configured in Devnet.toml; the batches
+//! that can't run in their epoch are rejected before anything is broadcasted.
+
+use clarinet_files::DevnetConfig;
+use clarity_repl::repl::clarity_versions;
+
+use crate::types::{EpochSpec, TransactionSpecification, TransactionsBatchSpecification};
+
+/// Progress of a batch held until the activation of its epoch
+#[derive(Debug, Clone, PartialEq)]
+pub struct EpochWait {
+    pub batch_id: usize,
+    pub epoch: EpochSpec,
+    /// Bitcoin block height of the activation of the epoch
+    pub activation_height: u64,
+    pub bitcoin_block_height: u64,
+}
+
+pub fn get_devnet_epoch_height(devnet: &DevnetConfig, epoch: &EpochSpec) -> u64 {
+    match epoch {
+        EpochSpec::Epoch2_0 => devnet.epoch_2_0,
+        EpochSpec::Epoch2_05 => devnet.epoch_2_05,
+        EpochSpec::Epoch2_1 => devnet.epoch_2_1,
+        EpochSpec::Epoch2_2 => devnet.epoch_2_2,
+        EpochSpec::Epoch2_3 => devnet.epoch_2_3,
+        EpochSpec::Epoch2_4 => devnet.epoch_2_4,
+        EpochSpec::Epoch2_5 => devnet.epoch_2_5,
+        EpochSpec::Epoch3_0 => devnet.epoch_3_0,
+        EpochSpec::Epoch3_1 => devnet.epoch_3_1,
+    }
+}
+
+/// Checks that the contracts published by each batch can be deployed in the epoch of the
+/// batch. Before epoch 2.1, the contracts are published without Clarity version and the node
+/// would deploy them as Clarity 1 contracts.
+pub fn check_batches_epochs(
+    batches: &[TransactionsBatchSpecification],
+    default_epoch: EpochSpec,
+    get_epoch_height: impl Fn(&EpochSpec) -> u64,
+) -> Result<(), String> {
+    for batch in batches.iter() {
+        let epoch = batch.epoch.unwrap_or(default_epoch);
+        for transaction in batch.transactions.iter() {
+            let (name, clarity_version) = match transaction {
+                TransactionSpecification::ContractPublish(tx) => {
+                    (tx.contract_name.to_string(), &tx.clarity_version)
+                }
+                TransactionSpecification::RequirementPublish(tx) => {
+                    (tx.contract_id.name.to_string(), &tx.clarity_version)
+                }
+                _ => continue,
+            };
+            let version = clarity_versions::version_number(*clarity_version);
+            let required_epoch = clarity_versions::min_epoch(*clarity_version);
+            if epoch < required_epoch {
+                return Err(format!(
+                    "batch {} publishes {} (Clarity {}) in epoch {}: Clarity {} requires epoch {}, activated at bitcoin block #{}",
+                    batch.id,
+                    name,
+                    version,
+                    epoch,
+                    version,
+                    required_epoch,
+                    get_epoch_height(&required_epoch)
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use clarinet_files::FileLocation;
+    use clarity_repl::clarity::vm::types::StandardPrincipalData;
+    use clarity_repl::clarity::{ClarityVersion, ContractName};
+
+    use super::*;
+    use crate::types::{AnchorMode, ContractPublishSpecification};
+
+    fn get_batch(
+        id: usize,
+        epoch: EpochSpec,
+        clarity_version: ClarityVersion,
+    ) -> TransactionsBatchSpecification {
+        TransactionsBatchSpecification {
+            id,
+            transactions: vec![TransactionSpecification::ContractPublish(
+                ContractPublishSpecification {
+                    contract_name: ContractName::try_from("counter".to_string()).unwrap(),
+                    expected_sender: StandardPrincipalData::transient(),
+                    location: FileLocation::from_path_string("/contracts/counter.clar").unwrap(),
+                    source: "(define-data-var count uint u0)".to_string(),
+                    clarity_version,
+                    cost: 10_000,
+                    anchor_mode: AnchorMode::OnChainOnly,
+                    description: None,
+                },
+            )],
+            epoch: Some(epoch),
+            post_checks: vec![],
+            description: None,
+        }
+    }
+
+    #[test]
+    fn test_check_batches_epochs() {
+        let get_epoch_height = |epoch: &EpochSpec| match epoch {
+            EpochSpec::Epoch2_1 => 102,
+            EpochSpec::Epoch3_0 => 142,
+            _ => 100,
+        };
+        let batches = vec![
+            get_batch(0, EpochSpec::Epoch2_05, ClarityVersion::Clarity1),
+            get_batch(1, EpochSpec::Epoch2_1, ClarityVersion::Clarity2),
+            get_batch(2, EpochSpec::Epoch3_0, ClarityVersion::Clarity3),
+        ];
+        assert!(check_batches_epochs(&batches, EpochSpec::Epoch2_05, get_epoch_height).is_ok());
+
+        let batches = vec![get_batch(3, EpochSpec::Epoch2_5, ClarityVersion::Clarity3)];
+        assert_eq!(
+            check_batches_epochs(&batches, EpochSpec::Epoch2_05, get_epoch_height),
+            Err("batch 3 publishes counter (Clarity 3) in epoch 2.5: Clarity 3 requires epoch 3.0, activated at bitcoin block #142".to_string())
+        );
+
+        // the batches without epoch run in the default epoch
+        let mut batch = get_batch(4, EpochSpec::Epoch2_1, ClarityVersion::Clarity2);
+        batch.epoch = None;
+        assert!(
+            check_batches_epochs(&[batch.clone()], EpochSpec::Epoch2_1, get_epoch_height).is_ok()
+        );
+        assert!(check_batches_epochs(&[batch], EpochSpec::Epoch2_05, get_epoch_height).is_err());
+    }
+}
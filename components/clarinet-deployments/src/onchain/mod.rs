@@ -3,42 +3,49 @@ use clarinet_files::StacksNetwork;
 use clarinet_files::{AccountConfig, NetworkManifest};
 use clarinet_utils::get_bip39_seed_from_mnemonic;
 use clarity_repl::clarity::chainstate::StacksAddress;
-use clarity_repl::clarity::codec::StacksMessageCodec;
-use clarity_repl::clarity::util::secp256k1::{
-    MessageSignature, Secp256k1PrivateKey, Secp256k1PublicKey,
-};
 use clarity_repl::clarity::vm::types::{
     PrincipalData, QualifiedContractIdentifier, StandardPrincipalData,
 };
 use clarity_repl::clarity::vm::{ClarityName, Value};
 use clarity_repl::clarity::{ClarityVersion, ContractName, EvaluationResult};
-use clarity_repl::repl::session::{
-    BOOT_MAINNET_ADDRESS, BOOT_TESTNET_ADDRESS, V1_BOOT_CONTRACTS, V2_BOOT_CONTRACTS,
-    V3_BOOT_CONTRACTS,
-};
 use clarity_repl::repl::{Session, SessionSettings};
 use reqwest::Url;
+use stacks_codec::builder::TxBuilder;
 use stacks_codec::codec::{
-    SinglesigHashMode, SinglesigSpendingCondition, StacksString, StacksTransactionSigner,
-    TokenTransferMemo, TransactionAuth, TransactionContractCall, TransactionPayload,
-    TransactionPostConditionMode, TransactionPublicKeyEncoding, TransactionSmartContract,
-    TransactionSpendingCondition, TransactionVersion,
+    StacksString, TokenTransferMemo, TransactionContractCall, TransactionPayload,
+    TransactionSmartContract, TransactionVersion,
 };
 use stacks_codec::codec::{StacksTransaction, TransactionAnchorMode};
+use stacks_rpc_client::signer::{InMemorySigner, TxSigner};
 use stacks_rpc_client::StacksRpc;
-use std::collections::{BTreeMap, HashSet, VecDeque};
+use std::collections::{BTreeMap, VecDeque};
 use std::str::FromStr;
 use std::sync::mpsc::{Receiver, Sender};
 use tiny_hderive::bip32::ExtendedPrivKey;
 
-use clarity_repl::clarity::address::{
-    AddressHashMode, C32_ADDRESS_VERSION_MAINNET_SINGLESIG, C32_ADDRESS_VERSION_TESTNET_SINGLESIG,
-};
-use libsecp256k1::{PublicKey, SecretKey};
-
 mod bitcoin_deployment;
+pub mod epochs;
+pub mod post_checks;
+pub mod read_only;
+pub mod simulation;
 
-use crate::types::{DeploymentSpecification, EpochSpec, TransactionSpecification};
+use crate::principal_remap::{
+    get_boot_contracts_remaps, get_remapped_requirement_source, remap_contracts_ids,
+};
+use crate::types::{
+    AnchorMode, ContractCallSpecification, DeploymentSpecification, EpochSpec,
+    PostCheckSpecification, TransactionSpecification,
+};
+use epochs::{check_batches_epochs, get_devnet_epoch_height, EpochWait};
+use post_checks::{run_post_checks, PostCheckResult};
+
+fn get_transaction_anchor_mode(anchor_mode: AnchorMode) -> TransactionAnchorMode {
+    match anchor_mode {
+        AnchorMode::OnChainOnly => TransactionAnchorMode::OnChainOnly,
+        AnchorMode::OffChainOnly => TransactionAnchorMode::OffChainOnly,
+        AnchorMode::Any => TransactionAnchorMode::Any,
+    }
+}
 
 fn get_btc_keypair(
     account: &AccountConfig,
@@ -58,34 +65,6 @@ fn get_btc_keypair(
     (secret_key, public_key)
 }
 
-fn get_keypair(account: &AccountConfig) -> (ExtendedPrivKey, Secp256k1PrivateKey, PublicKey) {
-    let bip39_seed = match get_bip39_seed_from_mnemonic(&account.mnemonic, "") {
-        Ok(bip39_seed) => bip39_seed,
-        Err(_) => panic!(),
-    };
-    let ext = ExtendedPrivKey::derive(&bip39_seed[..], account.derivation.as_str()).unwrap();
-    let wrapped_secret_key = Secp256k1PrivateKey::from_slice(&ext.secret()).unwrap();
-    let secret_key = SecretKey::parse_slice(&ext.secret()).unwrap();
-    let public_key = PublicKey::from_secret_key(&secret_key);
-    (ext, wrapped_secret_key, public_key)
-}
-
-fn get_stacks_address(public_key: &PublicKey, network: &StacksNetwork) -> StacksAddress {
-    let wrapped_public_key =
-        Secp256k1PublicKey::from_slice(&public_key.serialize_compressed()).unwrap();
-
-    StacksAddress::from_public_keys(
-        match network {
-            StacksNetwork::Mainnet => C32_ADDRESS_VERSION_MAINNET_SINGLESIG,
-            _ => C32_ADDRESS_VERSION_TESTNET_SINGLESIG,
-        },
-        &AddressHashMode::SerializeP2PKH,
-        1,
-        &vec![wrapped_public_key],
-    )
-    .unwrap()
-}
-
 fn sign_transaction_payload(
     account: &AccountConfig,
     payload: TransactionPayload,
@@ -94,44 +73,17 @@ fn sign_transaction_payload(
     anchor_mode: TransactionAnchorMode,
     network: &StacksNetwork,
 ) -> Result<StacksTransaction, String> {
-    let (_, secret_key, public_key) = get_keypair(account);
-    let signer_addr = get_stacks_address(&public_key, network);
-
-    let spending_condition = TransactionSpendingCondition::Singlesig(SinglesigSpendingCondition {
-        signer: signer_addr.bytes,
-        nonce,
-        tx_fee,
-        hash_mode: SinglesigHashMode::P2PKH,
-        key_encoding: TransactionPublicKeyEncoding::Compressed,
-        signature: MessageSignature::empty(),
-    });
-
-    let auth = TransactionAuth::Standard(spending_condition);
-    let unsigned_tx = StacksTransaction {
-        version: match network {
-            StacksNetwork::Mainnet => TransactionVersion::Mainnet,
-            _ => TransactionVersion::Testnet,
-        },
-        chain_id: match network {
-            StacksNetwork::Mainnet => 0x00000001,
-            _ => 0x80000000,
-        },
-        auth,
-        anchor_mode,
-        post_condition_mode: TransactionPostConditionMode::Allow,
-        post_conditions: vec![],
-        payload,
-    };
-
-    let mut unsigned_tx_bytes = vec![];
-    unsigned_tx
-        .consensus_serialize(&mut unsigned_tx_bytes)
-        .expect("FATAL: invalid transaction");
-
-    let mut tx_signer = StacksTransactionSigner::new(&unsigned_tx);
-    tx_signer.sign_origin(&secret_key).unwrap();
-    let signed_tx = tx_signer.get_tx().unwrap();
-    Ok(signed_tx)
+    let signer = InMemorySigner::from_mnemonic(&account.mnemonic, &account.derivation)?;
+    signer.sign(
+        TxBuilder::new(payload)
+            .network(match network {
+                StacksNetwork::Mainnet => TransactionVersion::Mainnet,
+                _ => TransactionVersion::Testnet,
+            })
+            .nonce(nonce)
+            .fee(tx_fee)
+            .anchor_mode(anchor_mode),
+    )
 }
 
 pub fn encode_contract_call(
@@ -226,6 +178,8 @@ pub enum TransactionCheck {
 #[derive(Clone, Debug)]
 pub enum DeploymentEvent {
     TransactionUpdate(TransactionTracker),
+    PostCheckCompleted(PostCheckResult),
+    WaitingForEpoch(EpochWait),
     Interrupted(String),
     DeploymentCompleted,
 }
@@ -234,6 +188,30 @@ pub enum DeploymentCommand {
     Start,
 }
 
+fn get_contract_call_payload(
+    session: &mut Session,
+    tx: &ContractCallSpecification,
+) -> TransactionPayload {
+    let function_args = tx
+        .parameters
+        .iter()
+        .map(|value| {
+            let execution = session.eval(value.to_string(), false).unwrap();
+            match execution.result {
+                EvaluationResult::Snippet(result) => result.result,
+                _ => unreachable!("Contract result from snippet"),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    TransactionPayload::ContractCall(TransactionContractCall {
+        contract_name: tx.contract_id.name.clone(),
+        address: StacksAddress::from(tx.contract_id.issuer.clone()),
+        function_name: tx.method.clone(),
+        function_args,
+    })
+}
+
 pub fn update_deployment_costs(
     deployment: &mut DeploymentSpecification,
     priority: usize,
@@ -242,7 +220,10 @@ pub fn update_deployment_costs(
         .stacks_node
         .as_ref()
         .expect("unable to get stacks node rcp address");
-    let stacks_rpc = StacksRpc::new(stacks_node_url);
+    let stacks_rpc = StacksRpc::with_client(
+        stacks_node_url,
+        clarinet_files::net::build_blocking_client(stacks_node_url)?,
+    );
     let mut session = Session::new(SessionSettings::default());
 
     for batch_spec in deployment.plan.batches.iter_mut() {
@@ -266,26 +247,20 @@ pub fn update_deployment_costs(
                     };
                 }
                 TransactionSpecification::ContractCall(tx) => {
-                    let function_args = tx
-                        .parameters
-                        .iter()
-                        .map(|value| {
-                            let execution = session.eval(value.to_string(), false).unwrap();
-                            match execution.result {
-                                EvaluationResult::Snippet(result) => result.result,
-                                _ => unreachable!("Contract result from snippet"),
-                            }
-                        })
-                        .collect::<Vec<_>>();
-
+                    let transaction_payload = get_contract_call_payload(&mut session, tx);
+                    match stacks_rpc.estimate_transaction_fee(&transaction_payload, priority) {
+                        Ok(fee) => {
+                            tx.cost = fee;
+                        }
+                        Err(e) => {
+                            println!("unable to estimate fee for transaction: {}", e);
+                            continue;
+                        }
+                    };
+                }
+                TransactionSpecification::BnsOperation(tx) => {
                     let transaction_payload =
-                        TransactionPayload::ContractCall(TransactionContractCall {
-                            contract_name: tx.contract_id.name.clone(),
-                            address: StacksAddress::from(tx.contract_id.issuer.clone()),
-                            function_name: tx.method.clone(),
-                            function_args,
-                        });
-
+                        get_contract_call_payload(&mut session, &tx.as_contract_call());
                     match stacks_rpc.estimate_transaction_fee(&transaction_payload, priority) {
                         Ok(fee) => {
                             tx.cost = fee;
@@ -318,13 +293,113 @@ pub fn update_deployment_costs(
                 TransactionSpecification::RequirementPublish(_)
                 | TransactionSpecification::BtcTransfer(_)
                 | TransactionSpecification::EmulatedContractPublish(_)
-                | TransactionSpecification::EmulatedContractCall(_) => continue,
+                | TransactionSpecification::EmulatedContractCall(_)
+                | TransactionSpecification::EmulatedEval(_) => continue,
             };
         }
     }
     Ok(())
 }
 
+/// A transaction broadcasted and waiting for its confirmation
+struct InFlightTransaction {
+    batch_index: usize,
+    sender: Option<StandardPrincipalData>,
+    nonce: u64,
+    tracker: TransactionTracker,
+}
+
+/// Broadcasts of a transaction failing because the node is unreachable or overloaded
+const MAX_BROADCAST_ATTEMPTS: u64 = 5;
+
+/// Reason of a transaction rejected by the node, such as `BadNonce` or `NotEnoughFunds`,
+/// followed by its details
+fn get_rejection_diagnostic(message: &str) -> String {
+    let Ok(rejection) = serde_json::from_str::<serde_json::Value>(message) else {
+        return message.to_string();
+    };
+    match (rejection["reason"].as_str(), rejection.get("reason_data")) {
+        (Some(reason), Some(data)) => format!("{} {}", reason, data),
+        (Some(reason), None) => reason.to_string(),
+        _ => message.to_string(),
+    }
+}
+
+fn get_transaction_sender(tracker: &TransactionTracker) -> Option<StandardPrincipalData> {
+    match &tracker.status {
+        TransactionStatus::Encoded(_, TransactionCheck::NonceCheck(sender, _))
+        | TransactionStatus::Encoded(_, TransactionCheck::ContractPublish(sender, _)) => {
+            Some(sender.clone())
+        }
+        _ => None,
+    }
+}
+
+/// Transactions of a batch don't depend on each other, but can depend on any transaction
+/// of the previous batches. When pipelining, a transaction can still be broadcasted before
+/// the previous batches are confirmed if their pending transactions have the same sender.
+fn can_broadcast(
+    in_flight: &[InFlightTransaction],
+    batch_index: usize,
+    sender: Option<&StandardPrincipalData>,
+    pipelining: bool,
+) -> bool {
+    in_flight.iter().all(|transaction| {
+        transaction.batch_index == batch_index
+            || (pipelining && sender.is_some() && transaction.sender.as_ref() == sender)
+    })
+}
+
+/// `on_bitcoin_block` is called with the height of each new bitcoin block observed
+fn wait_for_bitcoin_block(
+    stacks_rpc: &StacksRpc,
+    after_bitcoin_block: u64,
+    current_bitcoin_block_height: &mut u64,
+    current_block_height: &mut u64,
+    delay_between_checks: u64,
+    on_bitcoin_block: impl Fn(u64),
+) {
+    while *current_bitcoin_block_height <= after_bitcoin_block {
+        let (bitcoin_block_tip, stacks_block_tip) = match stacks_rpc.get_info() {
+            Ok(info) => {
+                if info.stacks_tip_height == 0 {
+                    // Always loop if we have not yet seen the genesis block.
+                    std::thread::sleep(std::time::Duration::from_secs(delay_between_checks));
+                    continue;
+                }
+                (info.burn_block_height, info.stacks_tip_height)
+            }
+            Err(_e) => {
+                std::thread::sleep(std::time::Duration::from_secs(delay_between_checks));
+                continue;
+            }
+        };
+
+        // If no bitcoin block has been mined since `delay_between_checks`,
+        // avoid flooding the stacks-node with status update requests.
+        if bitcoin_block_tip <= *current_bitcoin_block_height {
+            std::thread::sleep(std::time::Duration::from_secs(delay_between_checks));
+            continue;
+        }
+
+        *current_bitcoin_block_height = bitcoin_block_tip;
+        on_bitcoin_block(bitcoin_block_tip);
+
+        // If no stacks block has been mined despite the new bitcoin block,
+        // avoid flooding the stacks-node with status update requests.
+        if stacks_block_tip <= *current_block_height {
+            std::thread::sleep(std::time::Duration::from_secs(delay_between_checks));
+            continue;
+        }
+
+        *current_block_height = stacks_block_tip;
+
+        if *current_bitcoin_block_height <= after_bitcoin_block {
+            std::thread::sleep(std::time::Duration::from_secs(delay_between_checks));
+        }
+    }
+}
+
 pub fn apply_on_chain_deployment(
     network_manifest: NetworkManifest,
     deployment: DeploymentSpecification,
@@ -333,6 +408,7 @@ pub fn apply_on_chain_deployment(
     fetch_initial_nonces: bool,
     override_bitcoin_rpc_url: Option<String>,
     override_stacks_rpc_url: Option<String>,
+    max_in_flight: Option<usize>,
 ) {
     let networks = deployment.network.get_networks();
     let delay_between_checks: u64 = if matches!(networks.1, StacksNetwork::Devnet) {
@@ -371,7 +447,13 @@ pub fn apply_on_chain_deployment(
             .expect("unable to get stacks node rcp address")
     };
 
-    let stacks_rpc = StacksRpc::new(&stacks_node_url);
+    let stacks_rpc = match clarinet_files::net::build_blocking_client(&stacks_node_url) {
+        Ok(client) => StacksRpc::with_client(&stacks_node_url, client),
+        Err(e) => {
+            let _ = deployment_event_tx.send(DeploymentEvent::Interrupted(e));
+            return;
+        }
+    };
 
     let bitcoin_node_url = if let Some(url) = override_bitcoin_rpc_url {
         url
@@ -381,36 +463,40 @@ pub fn apply_on_chain_deployment(
             .expect("unable to get bitcoin node rcp address")
     };
 
+    // The node would reject, or deploy as Clarity 1, the contracts published in an epoch
+    // that doesn't support their Clarity version
+    if let (StacksNetwork::Devnet, Some(devnet)) = (&network, &network_manifest.devnet) {
+        if let Err(message) =
+            check_batches_epochs(&deployment.plan.batches, default_epoch, |epoch| {
+                get_devnet_epoch_height(devnet, epoch)
+            })
+        {
+            let _ = deployment_event_tx.send(DeploymentEvent::Interrupted(message));
+            return;
+        }
+    }
+
     // Phase 1: we traverse the deployment plan and encode all the transactions,
     // keeping the order.
     // Using a session to encode + coerce/check (todo) contract calls arguments.
     let mut session = Session::new(SessionSettings::default());
     let mut index = 0;
-    let mut contracts_ids_to_remap: HashSet<(String, String)> = HashSet::new();
-
-    for contract in V1_BOOT_CONTRACTS {
-        contracts_ids_to_remap.insert((
-            format!("{}:{}", BOOT_MAINNET_ADDRESS, contract),
-            format!("{}:{}", BOOT_TESTNET_ADDRESS, contract),
-        ));
-    }
-    for contract in V2_BOOT_CONTRACTS {
-        contracts_ids_to_remap.insert((
-            format!("{}:{}", BOOT_MAINNET_ADDRESS, contract),
-            format!("{}:{}", BOOT_TESTNET_ADDRESS, contract),
-        ));
-    }
-    for contract in V3_BOOT_CONTRACTS {
-        contracts_ids_to_remap.insert((
-            format!("{}:{}", BOOT_MAINNET_ADDRESS, contract),
-            format!("{}:{}", BOOT_TESTNET_ADDRESS, contract),
-        ));
-    }
+    let mut contracts_ids_to_remap = get_boot_contracts_remaps();
 
     for batch_spec in deployment.plan.batches.iter() {
         let epoch = batch_spec.epoch.unwrap_or(default_epoch);
         let mut batch = Vec::new();
         for transaction in batch_spec.transactions.iter() {
+            // BNS operations are contract calls to the bns boot contract
+            let bns_contract_call;
+            let transaction = match transaction {
+                TransactionSpecification::BnsOperation(tx) => {
+                    bns_contract_call =
+                        TransactionSpecification::ContractCall(tx.as_contract_call());
+                    &bns_contract_call
+                }
+                _ => transaction,
+            };
             let tracker = match transaction {
                 TransactionSpecification::StxTransfer(tx) => {
                     let issuer_address = tx.expected_sender.to_address();
@@ -422,10 +508,7 @@ pub fn apply_on_chain_deployment(
                     };
                     let account = stx_accounts_lookup.get(&issuer_address).unwrap();
 
-                    let anchor_mode = match tx.anchor_block_only {
-                        true => TransactionAnchorMode::OnChainOnly,
-                        false => TransactionAnchorMode::Any,
-                    };
+                    let anchor_mode = get_transaction_anchor_mode(tx.anchor_mode);
 
                     let transaction = match encode_stx_transfer(
                         tx.recipient.clone(),
@@ -523,10 +606,7 @@ pub fn apply_on_chain_deployment(
                         };
                     }
 
-                    let anchor_mode = match tx.anchor_block_only {
-                        true => TransactionAnchorMode::OnChainOnly,
-                        false => TransactionAnchorMode::Any,
-                    };
+                    let anchor_mode = get_transaction_anchor_mode(tx.anchor_mode);
 
                     let transaction = match encode_contract_call(
                         &tx.contract_id,
@@ -577,30 +657,12 @@ pub fn apply_on_chain_deployment(
                         deployment.network,
                         StacksNetwork::Devnet | StacksNetwork::Testnet
                     ) {
-                        // Remapping - This is happening
-                        let mut source = tx.source.clone();
-                        for (old_contract_id, new_contract_id) in contracts_ids_to_remap.iter() {
-                            let mut matched_indices = source
-                                .match_indices(old_contract_id)
-                                .map(|(i, _)| i)
-                                .collect::<Vec<usize>>();
-                            matched_indices.reverse();
-                            for index in matched_indices {
-                                source.replace_range(
-                                    index..index + old_contract_id.len(),
-                                    new_contract_id,
-                                );
-                            }
-                        }
-                        source
+                        remap_contracts_ids(&tx.source, &contracts_ids_to_remap)
                     } else {
                         tx.source.clone()
                     };
 
-                    let anchor_mode = match tx.anchor_block_only {
-                        true => TransactionAnchorMode::OnChainOnly,
-                        false => TransactionAnchorMode::Any,
-                    };
+                    let anchor_mode = get_transaction_anchor_mode(tx.anchor_mode);
 
                     let clarity_version = if epoch >= EpochSpec::Epoch2_1 {
                         Some(tx.clarity_version)
@@ -645,19 +707,16 @@ pub fn apply_on_chain_deployment(
                     if matches!(deployment.network, StacksNetwork::Mainnet) {
                         panic!("Deployment specification malformed - requirements publish not supported on mainnet");
                     }
+                    let remapped_contract_id = tx.get_remapped_contract_id();
                     let old_contract_id = tx.contract_id.to_string();
-                    let new_contract_id = QualifiedContractIdentifier::new(
-                        tx.remap_sender.clone(),
-                        tx.contract_id.name.clone(),
-                    )
-                    .to_string();
+                    let new_contract_id = remapped_contract_id.to_string();
                     contracts_ids_to_remap.insert((old_contract_id, new_contract_id));
 
                     // Testnet handling: don't re-deploy previously deployed contracts
                     if matches!(deployment.network, StacksNetwork::Testnet) {
                         let res = stacks_rpc.get_contract_source(
                             &tx.remap_sender.to_address(),
-                            &tx.contract_id.name.to_string(),
+                            &remapped_contract_id.name.to_string(),
                         );
                         if let Ok(_contract) = res {
                             continue;
@@ -674,34 +733,12 @@ pub fn apply_on_chain_deployment(
                     };
                     let account = stx_accounts_lookup.get(&issuer_address).unwrap();
 
-                    // Remapping principals - This is happening
-                    let mut source = tx.source.clone();
-                    for (src_principal, dst_principal) in tx
-                        .remap_principals
-                        .iter()
-                        .map(|(src, dst)| (src.to_address(), dst.to_address()))
-                        .chain(
-                            contracts_ids_to_remap
-                                .iter()
-                                .map(|(k, v)| (k.clone(), v.clone())),
-                        )
-                    {
-                        let src = src_principal;
-                        let dst = dst_principal;
-                        let mut matched_indices = source
-                            .match_indices(&src)
-                            .map(|(i, _)| i)
-                            .collect::<Vec<usize>>();
-                        matched_indices.reverse();
-                        for index in matched_indices {
-                            source.replace_range(index..index + src.len(), &dst);
-                        }
-                    }
+                    let source = get_remapped_requirement_source(tx, &contracts_ids_to_remap);
 
-                    let anchor_mode = TransactionAnchorMode::OnChainOnly;
+                    let anchor_mode = get_transaction_anchor_mode(tx.anchor_mode);
 
                     let transaction = match encode_contract_publish(
-                        &tx.contract_id.name,
+                        &remapped_contract_id.name,
                         &source,
                         None,
                         account,
@@ -718,10 +755,10 @@ pub fn apply_on_chain_deployment(
                     };
 
                     accounts_cached_nonces.insert(issuer_address.clone(), nonce + 1);
-                    let name = format!("Publish {}.{}", tx.remap_sender, tx.contract_id.name);
+                    let name = format!("Publish {}", remapped_contract_id);
                     let check = TransactionCheck::ContractPublish(
-                        tx.remap_sender.clone(),
-                        tx.contract_id.name.clone(),
+                        remapped_contract_id.issuer.clone(),
+                        remapped_contract_id.name.clone(),
                     );
                     TransactionTracker {
                         index,
@@ -729,8 +766,12 @@ pub fn apply_on_chain_deployment(
                         status: TransactionStatus::Encoded(transaction, check),
                     }
                 }
+                TransactionSpecification::BnsOperation(_) => {
+                    unreachable!("BNS operations are encoded as contract calls")
+                }
                 TransactionSpecification::EmulatedContractPublish(_)
-                | TransactionSpecification::EmulatedContractCall(_) => continue,
+                | TransactionSpecification::EmulatedContractCall(_)
+                | TransactionSpecification::EmulatedEval(_) => continue,
             };
 
             batch.push(tracker.clone());
@@ -751,87 +792,152 @@ pub fn apply_on_chain_deployment(
         }
     };
 
-    // Phase 2: we submit all the transactions previously encoded,
-    // and wait for their inclusion in a block before moving to the next batch.
+    // Phase 2: we submit the transactions previously encoded, and wait for their inclusion
+    // in a block. By default, a batch is submitted once the previous one is confirmed.
+    // With a `max_in_flight` window, the transactions of the next batches are submitted
+    // without waiting, as long as the only pending transactions of the previous batches
+    // come from the same sender: the nonces guarantee that they will be mined in order.
+    // The post checks of a batch are run once all its transactions are confirmed. The
+    // transactions of the next batches are held until the checks that can stop the
+    // deployment (`halt-on-failure`) have passed.
+    let batches_post_checks: Vec<(usize, Vec<PostCheckSpecification>)> = deployment
+        .plan
+        .batches
+        .iter()
+        .map(|batch| (batch.id, batch.post_checks.clone()))
+        .collect();
+    let mut next_checked_batch = 0;
+
+    let window_size = max_in_flight.unwrap_or(usize::MAX).max(1);
+    let mut queue: VecDeque<(usize, EpochSpec, TransactionTracker)> = batches
+        .into_iter()
+        .enumerate()
+        .flat_map(|(batch_index, (epoch, batch))| {
+            batch
+                .into_iter()
+                .map(move |tracker| (batch_index, epoch, tracker))
+        })
+        .collect();
+    let mut in_flight: Vec<InFlightTransaction> = vec![];
     let mut current_block_height = 0;
     let mut current_bitcoin_block_height = 0;
-    for (epoch, batch) in batches.into_iter() {
-        if network == StacksNetwork::Devnet {
-            // Devnet only: ensure we've reached the appropriate epoch for this batch
-            let after_bitcoin_block = match epoch {
-                EpochSpec::Epoch2_0 => network_manifest.devnet.as_ref().unwrap().epoch_2_0,
-                EpochSpec::Epoch2_05 => network_manifest.devnet.as_ref().unwrap().epoch_2_05,
-                EpochSpec::Epoch2_1 => network_manifest.devnet.as_ref().unwrap().epoch_2_1,
-                EpochSpec::Epoch2_2 => network_manifest.devnet.as_ref().unwrap().epoch_2_2,
-                EpochSpec::Epoch2_3 => network_manifest.devnet.as_ref().unwrap().epoch_2_3,
-                EpochSpec::Epoch2_4 => network_manifest.devnet.as_ref().unwrap().epoch_2_4,
-                EpochSpec::Epoch2_5 => network_manifest.devnet.as_ref().unwrap().epoch_2_5,
-                EpochSpec::Epoch3_0 => network_manifest.devnet.as_ref().unwrap().epoch_3_0,
-                EpochSpec::Epoch3_1 => network_manifest.devnet.as_ref().unwrap().epoch_3_1,
-            };
-            let mut epoch_transition_successful =
-                current_bitcoin_block_height > after_bitcoin_block;
-
-            while !epoch_transition_successful {
-                let (bitcoin_block_tip, stacks_block_tip) = match stacks_rpc.get_info() {
-                    Ok(info) => {
-                        if info.stacks_tip_height == 0 {
-                            // Always loop if we have not yet seen the genesis block.
-                            std::thread::sleep(std::time::Duration::from_secs(
-                                delay_between_checks,
-                            ));
-                            continue;
-                        }
-                        (info.burn_block_height, info.stacks_tip_height)
-                    }
-                    Err(_e) => {
-                        std::thread::sleep(std::time::Duration::from_secs(delay_between_checks));
-                        continue;
-                    }
-                };
-
-                // If no bitcoin block has been mined since `delay_between_checks`,
-                // avoid flooding the stacks-node with status update requests.
-                if bitcoin_block_tip <= current_bitcoin_block_height {
-                    std::thread::sleep(std::time::Duration::from_secs(delay_between_checks));
-                    continue;
-                }
-
-                current_bitcoin_block_height = bitcoin_block_tip;
-
-                // If no stacks block has been mined despite the new bitcoin block,
-                // avoid flooding the stacks-node with status update requests.
-                if stacks_block_tip <= current_block_height {
-                    std::thread::sleep(std::time::Duration::from_secs(delay_between_checks));
-                    continue;
-                }
-
-                current_block_height = stacks_block_tip;
+    let mut last_stacks_chain_check_at_height = 0;
+    let mut last_bitcoin_chain_check_at_height = 0;
+
+    loop {
+        let pending_batch = queue
+            .front()
+            .map(|(batch_index, _, _)| *batch_index)
+            .into_iter()
+            .chain(in_flight.iter().map(|transaction| transaction.batch_index))
+            .min()
+            .unwrap_or(batches_post_checks.len());
+        while next_checked_batch < pending_batch {
+            let (batch_id, checks) = &batches_post_checks[next_checked_batch];
+            if let Some(message) = run_post_checks(
+                &stacks_rpc,
+                &mut session,
+                Some(*batch_id),
+                checks,
+                &deployment_event_tx,
+            ) {
+                let _ = deployment_event_tx.send(DeploymentEvent::Interrupted(message));
+                return;
+            }
+            next_checked_batch += 1;
+        }
 
-                if current_bitcoin_block_height > after_bitcoin_block {
-                    epoch_transition_successful = true;
-                } else {
-                    std::thread::sleep(std::time::Duration::from_secs(delay_between_checks));
+        while let Some((batch_index, epoch, tracker)) = queue.front() {
+            if in_flight.len() >= window_size {
+                break;
+            }
+            let is_held = batches_post_checks[next_checked_batch..*batch_index]
+                .iter()
+                .any(|(_, checks)| checks.iter().any(|check| check.halt_on_failure()));
+            if is_held {
+                break;
+            }
+            let sender = get_transaction_sender(tracker);
+            if !can_broadcast(
+                &in_flight,
+                *batch_index,
+                sender.as_ref(),
+                max_in_flight.is_some(),
+            ) {
+                break;
+            }
+            if let (StacksNetwork::Devnet, Some(devnet)) = (&network, &network_manifest.devnet) {
+                // Devnet only: ensure we've reached the appropriate epoch for this batch
+                let after_bitcoin_block = get_devnet_epoch_height(devnet, epoch);
+                if current_bitcoin_block_height <= after_bitcoin_block {
+                    if !in_flight.is_empty() {
+                        break;
+                    }
+                    let batch_id = batches_post_checks[*batch_index].0;
+                    let epoch = *epoch;
+                    wait_for_bitcoin_block(
+                        &stacks_rpc,
+                        after_bitcoin_block,
+                        &mut current_bitcoin_block_height,
+                        &mut current_block_height,
+                        delay_between_checks,
+                        |bitcoin_block_height| {
+                            if bitcoin_block_height <= after_bitcoin_block {
+                                let _ = deployment_event_tx.send(DeploymentEvent::WaitingForEpoch(
+                                    EpochWait {
+                                        batch_id,
+                                        epoch,
+                                        activation_height: after_bitcoin_block,
+                                        bitcoin_block_height,
+                                    },
+                                ));
+                            }
+                        },
+                    );
                 }
             }
-        }
 
-        let mut ongoing_batch = BTreeMap::new();
-        for mut tracker in batch.into_iter() {
+            let Some((batch_index, _, mut tracker)) = queue.pop_front() else {
+                break;
+            };
             let (transaction, check) = match tracker.status {
                 TransactionStatus::Encoded(transaction, check) => (transaction, check),
                 _ => unreachable!(),
             };
-            match stacks_rpc.post_transaction(&transaction) {
+            let mut attempt = 1;
+            let result = loop {
+                match stacks_rpc.post_transaction(&transaction) {
+                    Err(e) if e.is_transient() && attempt < MAX_BROADCAST_ATTEMPTS => {
+                        std::thread::sleep(std::time::Duration::from_secs(
+                            delay_between_checks * attempt,
+                        ));
+                        attempt += 1;
+                    }
+                    result => break result,
+                }
+            };
+            match result {
                 Ok(res) => {
+                    let confirmation_needed = !matches!(check, TransactionCheck::BtcTransfer);
                     tracker.status = TransactionStatus::Broadcasted(check, res.txid.clone());
 
                     let _ = deployment_event_tx
                         .send(DeploymentEvent::TransactionUpdate(tracker.clone()));
-                    ongoing_batch.insert(res.txid, tracker);
+                    if confirmation_needed {
+                        in_flight.push(InFlightTransaction {
+                            batch_index,
+                            sender,
+                            nonce: transaction.get_origin_nonce(),
+                            tracker,
+                        });
+                    }
                 }
                 Err(e) => {
-                    let message = format!("unable to post transaction\n{}", e);
+                    let message = format!(
+                        "unable to broadcast {}: {}",
+                        tracker.name,
+                        get_rejection_diagnostic(&e.to_string())
+                    );
                     tracker.status = TransactionStatus::Error(message.clone());
 
                     let _ = deployment_event_tx
@@ -841,95 +947,105 @@ pub fn apply_on_chain_deployment(
                 }
             };
         }
-        let mut last_stacks_chain_check_at_height = 0;
-        let mut last_bitcoin_chain_check_at_height = 0;
-
-        loop {
-            let (bitcoin_tip_height, stacks_tip_height) = match stacks_rpc.get_info() {
-                Ok(info) => (info.burn_block_height, info.stacks_tip_height),
-                _ => {
-                    std::thread::sleep(std::time::Duration::from_secs(delay_between_checks));
-                    continue;
-                }
-            };
-
-            let mut keep_looping = false;
 
-            // Handle Stacks releated checks
-            if stacks_tip_height > last_stacks_chain_check_at_height {
-                for (_, tracker) in ongoing_batch.iter_mut() {
-                    let TransactionStatus::Broadcasted(brodcasting_status, _) = &tracker.status
-                    else {
-                        continue;
-                    };
+        if in_flight.is_empty() {
+            if queue.is_empty() {
+                break;
+            }
+            continue;
+        }
 
-                    match &brodcasting_status {
-                        TransactionCheck::ContractPublish(deployer, contract_name) => {
-                            let deployer_address = deployer.to_address();
-                            let res =
-                                stacks_rpc.get_contract_source(&deployer_address, contract_name);
-                            match res {
-                                Ok(_contract) => {
-                                    tracker.status = TransactionStatus::Confirmed;
-                                    let _ = deployment_event_tx
-                                        .send(DeploymentEvent::TransactionUpdate(tracker.clone()));
-                                }
-                                Err(_e) => {
-                                    keep_looping = true;
-                                    break;
-                                }
-                            }
-                        }
-                        TransactionCheck::NonceCheck(tx_sender, expected_nonce) => {
-                            let tx_sender_address = tx_sender.to_address();
-                            let res = stacks_rpc.get_nonce(&tx_sender_address);
-                            if let Ok(current_nonce) = res {
-                                if current_nonce.gt(expected_nonce) {
-                                    tracker.status = TransactionStatus::Confirmed;
-                                    let _ = deployment_event_tx
-                                        .send(DeploymentEvent::TransactionUpdate(tracker.clone()));
-                                } else {
-                                    keep_looping = true;
-                                    break;
-                                }
-                            }
-                        }
-                        _ => {}
-                    }
-                }
-            } else {
+        let (bitcoin_tip_height, stacks_tip_height) = match stacks_rpc.get_info() {
+            Ok(info) => (info.burn_block_height, info.stacks_tip_height),
+            _ => {
                 std::thread::sleep(std::time::Duration::from_secs(delay_between_checks));
                 continue;
             }
+        };
 
-            // Handle Bitcoin releated checks
-            if bitcoin_tip_height > last_bitcoin_chain_check_at_height {
-                for (_, tracker) in ongoing_batch.iter_mut() {
-                    let TransactionStatus::Broadcasted(brodcasting_status, _) = &tracker.status
-                    else {
-                        continue;
-                    };
-                    match &brodcasting_status {
-                        TransactionCheck::BtcTransfer => {
-                            // TODO
-                        }
-                        TransactionCheck::ContractPublish(_, _)
-                        | TransactionCheck::NonceCheck(_, _) => {}
+        // Confirmations are only checked once new anchored blocks are observed
+        if stacks_tip_height <= last_stacks_chain_check_at_height
+            || bitcoin_tip_height <= last_bitcoin_chain_check_at_height
+        {
+            std::thread::sleep(std::time::Duration::from_secs(delay_between_checks));
+            continue;
+        }
+
+        for transaction in in_flight.iter_mut() {
+            let tracker = &mut transaction.tracker;
+            let TransactionStatus::Broadcasted(brodcasting_status, txid) = &tracker.status else {
+                continue;
+            };
+
+            let confirmed = match &brodcasting_status {
+                TransactionCheck::ContractPublish(deployer, contract_name) => {
+                    let deployer_address = deployer.to_address();
+                    // mined, read before the source to not miss a contract published in between
+                    let mined = stacks_rpc
+                        .get_nonce(&deployer_address)
+                        .is_ok_and(|current_nonce| current_nonce > transaction.nonce);
+                    let source = stacks_rpc.get_contract_source(&deployer_address, contract_name);
+                    let published = source.is_ok();
+                    // the transaction was mined, but the contract was rejected
+                    let aborted = mined && source.is_err_and(|e| !e.is_transient());
+                    if aborted {
+                        let message = format!(
+                            "{} failed: transaction {} was mined but the contract was not published (analysis or runtime error)",
+                            tracker.name, txid
+                        );
+                        tracker.status = TransactionStatus::Error(message.clone());
+                        let _ = deployment_event_tx
+                            .send(DeploymentEvent::TransactionUpdate(tracker.clone()));
+                        let _ = deployment_event_tx.send(DeploymentEvent::Interrupted(message));
+                        return;
                     }
+                    published
                 }
-            } else {
-                std::thread::sleep(std::time::Duration::from_secs(delay_between_checks));
-                continue;
+                TransactionCheck::NonceCheck(tx_sender, expected_nonce) => {
+                    let tx_sender_address = tx_sender.to_address();
+                    match stacks_rpc.get_nonce(&tx_sender_address) {
+                        Ok(current_nonce) => current_nonce.gt(expected_nonce),
+                        Err(_) => false,
+                    }
+                }
+                TransactionCheck::BtcTransfer => true,
+            };
+            if confirmed {
+                tracker.status = TransactionStatus::Confirmed;
+                let _ =
+                    deployment_event_tx.send(DeploymentEvent::TransactionUpdate(tracker.clone()));
             }
+        }
+        in_flight.retain(|transaction| {
+            !matches!(transaction.tracker.status, TransactionStatus::Confirmed)
+        });
 
-            last_stacks_chain_check_at_height = stacks_tip_height;
-            last_bitcoin_chain_check_at_height = bitcoin_tip_height;
+        last_stacks_chain_check_at_height = stacks_tip_height;
+        last_bitcoin_chain_check_at_height = bitcoin_tip_height;
+    }
 
-            if !keep_looping {
-                break;
-            }
+    for (batch_id, checks) in batches_post_checks[next_checked_batch..].iter() {
+        if let Some(message) = run_post_checks(
+            &stacks_rpc,
+            &mut session,
+            Some(*batch_id),
+            checks,
+            &deployment_event_tx,
+        ) {
+            let _ = deployment_event_tx.send(DeploymentEvent::Interrupted(message));
+            return;
         }
     }
+    if let Some(message) = run_post_checks(
+        &stacks_rpc,
+        &mut session,
+        None,
+        &deployment.plan.post_checks,
+        &deployment_event_tx,
+    ) {
+        let _ = deployment_event_tx.send(DeploymentEvent::Interrupted(message));
+        return;
+    }
 
     let _ = deployment_event_tx.send(DeploymentEvent::DeploymentCompleted);
 }
@@ -965,11 +1081,7 @@ pub fn get_initial_transactions_trackers(
                     }
                     TransactionTracker {
                         index,
-                        name: format!(
-                            "Contract publish {}.{}",
-                            tx.remap_sender.to_address(),
-                            tx.contract_id.name
-                        ),
+                        name: format!("Contract publish {}", tx.get_remapped_contract_id()),
                         status: TransactionStatus::Queued,
                     }
                 }
@@ -991,8 +1103,14 @@ pub fn get_initial_transactions_trackers(
                     ),
                     status: TransactionStatus::Queued,
                 },
+                TransactionSpecification::BnsOperation(tx) => TransactionTracker {
+                    index,
+                    name: format!("BNS {} {}", tx.get_method(), tx.get_fully_qualified_name()),
+                    status: TransactionStatus::Queued,
+                },
                 TransactionSpecification::EmulatedContractPublish(_)
-                | TransactionSpecification::EmulatedContractCall(_) => continue,
+                | TransactionSpecification::EmulatedContractCall(_)
+                | TransactionSpecification::EmulatedEval(_) => continue,
             };
             trackers.push(tracker);
             index += 1;
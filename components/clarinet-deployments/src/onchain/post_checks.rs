@@ -0,0 +1,167 @@
+//! Post-deployment checks of a plan (`post-checks`), run against the network once the
+//! transactions of a batch, or of the whole plan, are confirmed. The checks are read-only:
+//! calls of read-only functions compared with an expected result, and map entries that
+//! must exist.
+
+use std::sync::mpsc::Sender;
+
+use clarity_repl::clarity::vm::types::Value;
+use clarity_repl::repl::Session;
+use stacks_rpc_client::StacksRpc;
+
+use super::read_only::evaluate_literal;
+use super::DeploymentEvent;
+use crate::types::PostCheckSpecification;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PostCheckResult {
+    /// `None` for the checks of the plan, run after the last batch
+    pub batch_id: Option<usize>,
+    /// e.g. `read-only call ST...counter::get-count`
+    pub check: String,
+    pub passed: bool,
+    /// Value read on chain, or the reason of the failure
+    pub message: String,
+    pub halt_on_failure: bool,
+}
+
+impl PostCheckResult {
+    pub fn is_halting(&self) -> bool {
+        !self.passed && self.halt_on_failure
+    }
+}
+
+/// Results of the checks of an applied plan, written next to the plan
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct DeploymentReceipt {
+    pub post_checks: Vec<PostCheckResult>,
+}
+
+impl DeploymentReceipt {
+    pub fn is_success(&self) -> bool {
+        self.post_checks.iter().all(|result| result.passed)
+    }
+}
+
+/// Compares the value read on chain (`None` for a missing map entry) with the expected one
+pub fn get_check_outcome(actual: Option<&Value>, expected: Option<&Value>) -> (bool, String) {
+    match (actual, expected) {
+        (None, _) => (false, "no entry found".to_string()),
+        (Some(actual), Some(expected)) if actual != expected => {
+            (false, format!("expected {}, got {}", expected, actual))
+        }
+        (Some(actual), _) => (true, actual.to_string()),
+    }
+}
+
+fn read_check_value(
+    stacks_rpc: &StacksRpc,
+    session: &mut Session,
+    check: &PostCheckSpecification,
+) -> Result<(Option<Value>, Option<Value>), String> {
+    match check {
+        PostCheckSpecification::ReadOnlyCall(check) => {
+            let arguments = check
+                .parameters
+                .iter()
+                .map(|parameter| evaluate_literal(session, parameter))
+                .collect::<Result<Vec<_>, _>>()?;
+            let expected = evaluate_literal(session, &check.expected)?;
+            let address = check.contract_id.issuer.to_address();
+            let actual = stacks_rpc
+                .call_read_only_fn(
+                    &address,
+                    check.contract_id.name.as_str(),
+                    check.method.as_str(),
+                    arguments,
+                    &address,
+                )
+                .map_err(|e| format!("read-only call failed: {}", e))?;
+            Ok((Some(actual), Some(expected)))
+        }
+        PostCheckSpecification::MapEntry(check) => {
+            let key = evaluate_literal(session, &check.key)?;
+            let expected = match check.expected {
+                Some(ref expected) => Some(evaluate_literal(session, expected)?),
+                None => None,
+            };
+            let actual = stacks_rpc
+                .get_map_entry(
+                    &check.contract_id.issuer.to_address(),
+                    check.contract_id.name.as_str(),
+                    check.map.as_str(),
+                    &key,
+                )
+                .map_err(|e| format!("unable to read the map entry: {}", e))?;
+            Ok((actual, expected))
+        }
+    }
+}
+
+pub fn run_post_check(
+    stacks_rpc: &StacksRpc,
+    session: &mut Session,
+    batch_id: Option<usize>,
+    check: &PostCheckSpecification,
+) -> PostCheckResult {
+    let (passed, message) = match read_check_value(stacks_rpc, session, check) {
+        Ok((actual, expected)) => get_check_outcome(actual.as_ref(), expected.as_ref()),
+        Err(message) => (false, message),
+    };
+    PostCheckResult {
+        batch_id,
+        check: check.get_description(),
+        passed,
+        message,
+        halt_on_failure: check.halt_on_failure(),
+    }
+}
+
+/// Runs the checks and sends their results, returns the failure stopping the deployment
+pub(crate) fn run_post_checks(
+    stacks_rpc: &StacksRpc,
+    session: &mut Session,
+    batch_id: Option<usize>,
+    checks: &[PostCheckSpecification],
+    deployment_event_tx: &Sender<DeploymentEvent>,
+) -> Option<String> {
+    let mut halting_failure = None;
+    for check in checks {
+        let result = run_post_check(stacks_rpc, session, batch_id, check);
+        if result.is_halting() && halting_failure.is_none() {
+            halting_failure = Some(format!(
+                "post check {} failed: {}",
+                result.check, result.message
+            ));
+        }
+        let _ = deployment_event_tx.send(DeploymentEvent::PostCheckCompleted(result));
+    }
+    halting_failure
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_check_outcome() {
+        let one = Value::UInt(1);
+        let two = Value::UInt(2);
+        assert_eq!(
+            get_check_outcome(Some(&one), Some(&one)),
+            (true, "u1".to_string())
+        );
+        assert_eq!(
+            get_check_outcome(Some(&two), Some(&one)),
+            (false, "expected u1, got u2".to_string())
+        );
+        assert_eq!(
+            get_check_outcome(Some(&two), None),
+            (true, "u2".to_string())
+        );
+        assert_eq!(
+            get_check_outcome(None, None),
+            (false, "no entry found".to_string())
+        );
+    }
+}
@@ -0,0 +1,106 @@
+//! Read-only calls against a network, without a project (`clarinet call-read-only`): the
+//! arguments are Clarity literals, evaluated in a bare session, and the call is made through
+//! the RPC endpoint of a Stacks node.
+
+use clarinet_files::StacksNetwork;
+use clarity_repl::clarity::vm::types::{QualifiedContractIdentifier, Value};
+use clarity_repl::clarity::EvaluationResult;
+use clarity_repl::repl::{Session, SessionSettings, DEFAULT_EPOCH};
+use stacks_rpc_client::StacksRpc;
+
+pub const MAINNET_NODE_URL: &str = "https://api.hiro.so";
+pub const TESTNET_NODE_URL: &str = "https://api.testnet.hiro.so";
+pub const DEVNET_NODE_URL: &str = "http://localhost:20443";
+
+/// Node queried for a network when no node is specified
+pub fn get_default_node_url(network: &StacksNetwork) -> Result<&'static str, String> {
+    match network {
+        StacksNetwork::Mainnet => Ok(MAINNET_NODE_URL),
+        StacksNetwork::Testnet => Ok(TESTNET_NODE_URL),
+        StacksNetwork::Devnet => Ok(DEVNET_NODE_URL),
+        StacksNetwork::Simnet => Err("simnet has no node to call".to_string()),
+    }
+}
+
+pub fn evaluate_literal(session: &mut Session, literal: &str) -> Result<Value, String> {
+    let execution = session
+        .eval(literal.to_string(), false)
+        .map_err(|_| format!("unable to evaluate '{}'", literal))?;
+    match execution.result {
+        EvaluationResult::Snippet(result) => Ok(result.result),
+        _ => Err(format!("unable to evaluate '{}'", literal)),
+    }
+}
+
+/// Values of Clarity literals, such as `u1`, `'SP3FBR2AGK5H9QBDH3EEN6DF8EK8JY7RX8QJ5SVTE` or
+/// `(tuple (id u1))`
+pub fn parse_arguments(arguments: &[String]) -> Result<Vec<Value>, String> {
+    let mut session = Session::new(SessionSettings::default());
+    session.update_epoch(DEFAULT_EPOCH);
+    arguments
+        .iter()
+        .map(|argument| evaluate_literal(&mut session, argument))
+        .collect()
+}
+
+/// Calls a read-only function on the default node of `network`, as its deployer
+pub fn remote_call_read_only(
+    network: &StacksNetwork,
+    contract_id: &QualifiedContractIdentifier,
+    function: &str,
+    args: Vec<Value>,
+) -> Result<Value, String> {
+    let node_url = get_default_node_url(network)?;
+    remote_call_read_only_on_node(node_url, contract_id, function, args, None)
+}
+
+/// Calls a read-only function on the node at `node_url`, the sender defaulting to the
+/// deployer of the contract
+pub fn remote_call_read_only_on_node(
+    node_url: &str,
+    contract_id: &QualifiedContractIdentifier,
+    function: &str,
+    args: Vec<Value>,
+    sender: Option<&str>,
+) -> Result<Value, String> {
+    let address = contract_id.issuer.to_address();
+    StacksRpc::new(node_url)
+        .call_read_only_fn(
+            &address,
+            contract_id.name.as_str(),
+            function,
+            args,
+            sender.unwrap_or(&address),
+        )
+        .map_err(|e| {
+            format!(
+                "read-only call of {}::{} failed: {}",
+                contract_id, function, e
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_arguments() {
+        let arguments = vec![
+            "u1".to_string(),
+            "'ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM".to_string(),
+            "(tuple (id u2))".to_string(),
+        ];
+        let values = parse_arguments(&arguments).unwrap();
+        assert_eq!(values[0], Value::UInt(1));
+        assert!(matches!(values[1], Value::Principal(_)));
+        assert_eq!(values[2].to_string(), "(tuple (id u2))");
+        assert!(parse_arguments(&["(unknown-function)".to_string()]).is_err());
+
+        assert_eq!(
+            get_default_node_url(&StacksNetwork::Mainnet),
+            Ok(MAINNET_NODE_URL)
+        );
+        assert!(get_default_node_url(&StacksNetwork::Simnet).is_err());
+    }
+}
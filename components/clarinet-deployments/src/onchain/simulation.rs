@@ -0,0 +1,349 @@
+//! Pre-flight check of a deployment plan: each transaction is checked against the current
+//! state of the network (read-only), without broadcasting anything. The simulation catches
+//! contract names already taken, missing contracts, traits or functions, and senders that
+//! can't afford the fees and transfers of the plan.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use clarity_repl::clarity::ast::build_ast_with_diagnostics;
+use clarity_repl::clarity::vm::types::{
+    PrincipalData, QualifiedContractIdentifier, StandardPrincipalData, Value,
+};
+use clarity_repl::clarity::vm::{SymbolicExpression, SymbolicExpressionType};
+use clarity_repl::repl::{DEFAULT_CLARITY_VERSION, DEFAULT_EPOCH};
+use stacks_rpc_client::StacksRpc;
+
+use crate::types::{BnsOperation, DeploymentSpecification, TransactionSpecification};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimulationIssue {
+    pub batch_id: usize,
+    /// Transaction description, e.g. `contract publish ST...counter`
+    pub transaction: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SimulationReport {
+    pub transactions: usize,
+    pub issues: Vec<SimulationIssue>,
+}
+
+impl SimulationReport {
+    pub fn is_success(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+fn collect_contract_references(
+    expression: &SymbolicExpression,
+    references: &mut BTreeSet<QualifiedContractIdentifier>,
+) {
+    match &expression.expr {
+        SymbolicExpressionType::LiteralValue(Value::Principal(PrincipalData::Contract(id))) => {
+            references.insert(id.clone());
+        }
+        SymbolicExpressionType::LiteralValue(Value::CallableContract(callable)) => {
+            references.insert(callable.contract_identifier.clone());
+        }
+        SymbolicExpressionType::Field(trait_id) => {
+            references.insert(trait_id.contract_identifier.clone());
+        }
+        SymbolicExpressionType::List(list) => {
+            for expression in list.iter() {
+                collect_contract_references(expression, references);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Contracts referenced by a source: `contract-call?` targets, traits and principals
+pub fn get_contract_references(
+    contract_id: &QualifiedContractIdentifier,
+    source: &str,
+) -> BTreeSet<QualifiedContractIdentifier> {
+    let (ast, _, _) = build_ast_with_diagnostics(
+        contract_id,
+        source,
+        &mut (),
+        DEFAULT_CLARITY_VERSION,
+        DEFAULT_EPOCH,
+    );
+    let mut references = BTreeSet::new();
+    for expression in ast.expressions.iter() {
+        collect_contract_references(expression, &mut references);
+    }
+    references.remove(contract_id);
+    references
+}
+
+fn remap_contract_id(
+    contract_id: &QualifiedContractIdentifier,
+    remapped_contracts: &BTreeMap<QualifiedContractIdentifier, QualifiedContractIdentifier>,
+    remap_principals: &BTreeMap<StandardPrincipalData, StandardPrincipalData>,
+) -> QualifiedContractIdentifier {
+    if let Some(remapped_contract_id) = remapped_contracts.get(contract_id) {
+        return remapped_contract_id.clone();
+    }
+    match remap_principals.get(&contract_id.issuer) {
+        Some(issuer) => QualifiedContractIdentifier::new(issuer.clone(), contract_id.name.clone()),
+        None => contract_id.clone(),
+    }
+}
+
+/// Remote state of the network, queried once per contract or account
+struct RemoteState<'a> {
+    rpc: &'a StacksRpc,
+    published_contracts: BTreeMap<QualifiedContractIdentifier, bool>,
+}
+
+impl RemoteState<'_> {
+    fn is_published(&mut self, contract_id: &QualifiedContractIdentifier) -> Result<bool, String> {
+        if let Some(published) = self.published_contracts.get(contract_id) {
+            return Ok(*published);
+        }
+        let published = self
+            .rpc
+            .get_contract_interface(&contract_id.issuer.to_address(), contract_id.name.as_str())
+            .map_err(|e| format!("unable to retrieve contract {}: {}", contract_id, e))?
+            .is_some();
+        self.published_contracts
+            .insert(contract_id.clone(), published);
+        Ok(published)
+    }
+}
+
+struct PlanSimulation<'a> {
+    remote: RemoteState<'a>,
+    /// Contracts published by the previous transactions of the plan
+    planned_contracts: BTreeSet<QualifiedContractIdentifier>,
+    /// Requirements published under a new contract id
+    remapped_contracts: BTreeMap<QualifiedContractIdentifier, QualifiedContractIdentifier>,
+    /// Fees and transfers of each sender
+    spendings: BTreeMap<StandardPrincipalData, u128>,
+    report: SimulationReport,
+    batch_id: usize,
+}
+
+impl PlanSimulation<'_> {
+    fn add_issue(&mut self, transaction: &str, message: String) {
+        self.report.issues.push(SimulationIssue {
+            batch_id: self.batch_id,
+            transaction: transaction.to_string(),
+            message,
+        });
+    }
+
+    fn spend(&mut self, sender: &StandardPrincipalData, amount: u64) {
+        *self.spendings.entry(sender.clone()).or_default() += amount as u128;
+    }
+
+    fn exists(&mut self, contract_id: &QualifiedContractIdentifier) -> Result<bool, String> {
+        Ok(
+            self.planned_contracts.contains(contract_id)
+                || self.remote.is_published(contract_id)?,
+        )
+    }
+
+    fn check_publish(
+        &mut self,
+        name: &str,
+        contract_id: &QualifiedContractIdentifier,
+        references: BTreeSet<QualifiedContractIdentifier>,
+    ) -> Result<(), String> {
+        if self.remote.is_published(contract_id)? {
+            self.add_issue(
+                name,
+                format!("contract name already taken: {} is published", contract_id),
+            );
+        } else if self.planned_contracts.contains(contract_id) {
+            self.add_issue(
+                name,
+                format!("{} is published twice in the plan", contract_id),
+            );
+        }
+        for reference in references {
+            if !self.exists(&reference)? {
+                self.add_issue(
+                    name,
+                    format!(
+                        "{} is not published, and not published by the plan before this transaction",
+                        reference
+                    ),
+                );
+            }
+        }
+        self.planned_contracts.insert(contract_id.clone());
+        Ok(())
+    }
+
+    fn check_transaction(&mut self, transaction: &TransactionSpecification) -> Result<(), String> {
+        match transaction {
+            TransactionSpecification::BnsOperation(tx) => {
+                let contract_call = TransactionSpecification::ContractCall(tx.as_contract_call());
+                self.check_transaction(&contract_call)?;
+                if let BnsOperation::NamePreorder { stx_to_burn, .. } = tx.operation {
+                    self.spend(&tx.expected_sender, stx_to_burn);
+                }
+            }
+            TransactionSpecification::ContractPublish(tx) => {
+                let contract_id = QualifiedContractIdentifier::new(
+                    tx.expected_sender.clone(),
+                    tx.contract_name.clone(),
+                );
+                let name = format!("contract publish {}", contract_id);
+                let references = get_contract_references(&contract_id, &tx.source);
+                self.check_publish(&name, &contract_id, references)?;
+                self.spend(&tx.expected_sender, tx.cost);
+            }
+            TransactionSpecification::RequirementPublish(tx) => {
+                let contract_id = tx.get_remapped_contract_id();
+                let name = format!("requirement publish {}", contract_id);
+                let references = get_contract_references(&tx.contract_id, &tx.source)
+                    .iter()
+                    .map(|reference| {
+                        remap_contract_id(reference, &self.remapped_contracts, &tx.remap_principals)
+                    })
+                    .collect();
+                self.check_publish(&name, &contract_id, references)?;
+                self.spend(&tx.remap_sender, tx.cost);
+            }
+            TransactionSpecification::ContractCall(tx) => {
+                let name = format!("contract call {}::{}", tx.contract_id, tx.method);
+                self.spend(&tx.expected_sender, tx.cost);
+                if self.planned_contracts.contains(&tx.contract_id) {
+                    return Ok(());
+                }
+                let interface = self
+                    .remote
+                    .rpc
+                    .get_contract_interface(
+                        &tx.contract_id.issuer.to_address(),
+                        tx.contract_id.name.as_str(),
+                    )
+                    .map_err(|e| {
+                        format!("unable to retrieve contract {}: {}", tx.contract_id, e)
+                    })?;
+                let Some(interface) = interface else {
+                    self.add_issue(&name, format!("{} is not published", tx.contract_id));
+                    return Ok(());
+                };
+                match interface
+                    .functions
+                    .iter()
+                    .find(|function| function.name == tx.method.as_str())
+                {
+                    None => self.add_issue(
+                        &name,
+                        format!("function {} not found in {}", tx.method, tx.contract_id),
+                    ),
+                    Some(function) if function.access != "public" => self.add_issue(
+                        &name,
+                        format!("function {} is {}, not public", tx.method, function.access),
+                    ),
+                    Some(function) if function.args.len() != tx.parameters.len() => self.add_issue(
+                        &name,
+                        format!(
+                            "function {} expects {} arguments, {} provided",
+                            tx.method,
+                            function.args.len(),
+                            tx.parameters.len()
+                        ),
+                    ),
+                    Some(_) => {}
+                }
+            }
+            TransactionSpecification::StxTransfer(tx) => {
+                self.spend(&tx.expected_sender, tx.cost);
+                self.spend(&tx.expected_sender, tx.mstx_amount);
+            }
+            TransactionSpecification::BtcTransfer(_)
+            | TransactionSpecification::EmulatedContractPublish(_)
+            | TransactionSpecification::EmulatedContractCall(_)
+            | TransactionSpecification::EmulatedEval(_) => {}
+        }
+        Ok(())
+    }
+}
+
+/// Checks the transactions of a plan, in order, against the network of its Stacks node
+pub fn simulate_deployment(
+    deployment: &DeploymentSpecification,
+) -> Result<SimulationReport, String> {
+    let node_url = deployment
+        .stacks_node
+        .as_ref()
+        .ok_or("the deployment plan doesn't specify a stacks node")?;
+    let rpc = StacksRpc::with_client(
+        node_url,
+        clarinet_files::net::build_blocking_client(node_url)?,
+    );
+    let mut simulation = PlanSimulation {
+        remote: RemoteState {
+            rpc: &rpc,
+            published_contracts: BTreeMap::new(),
+        },
+        planned_contracts: BTreeSet::new(),
+        remapped_contracts: deployment.get_remapped_contracts(),
+        spendings: BTreeMap::new(),
+        report: SimulationReport::default(),
+        batch_id: 0,
+    };
+
+    for batch in deployment.plan.batches.iter() {
+        simulation.batch_id = batch.id;
+        for transaction in batch.transactions.iter() {
+            simulation.report.transactions += 1;
+            simulation.check_transaction(transaction)?;
+        }
+    }
+
+    for (sender, spending) in simulation.spendings.clone() {
+        let address = sender.to_address();
+        let balance = rpc
+            .get_balance(&address)
+            .map_err(|e| format!("unable to retrieve balance of {}: {}", address, e))?;
+        if balance < spending {
+            simulation.add_issue(
+                &format!("account {}", address),
+                format!(
+                    "insufficient balance: {} µSTX required by the plan, {} µSTX available",
+                    spending, balance
+                ),
+            );
+        }
+    }
+
+    Ok(simulation.report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_contract_references() {
+        let contract_id =
+            QualifiedContractIdentifier::parse("ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM.vault")
+                .unwrap();
+        let source = r#"
+(impl-trait 'SP3FBR2AGK5H9QBDH3EEN6DF8EK8JY7RX8QJ5SVTE.sip-010-trait-ft-standard.sip-010-trait)
+(use-trait ft-trait 'SP3FBR2AGK5H9QBDH3EEN6DF8EK8JY7RX8QJ5SVTE.sip-010-trait-ft-standard.sip-010-trait)
+(define-public (deposit (amount uint))
+  (contract-call? .token transfer amount tx-sender (as-contract tx-sender) none))
+(define-read-only (get-owner) .vault)
+"#;
+        let references: Vec<String> = get_contract_references(&contract_id, source)
+            .iter()
+            .map(|id| id.to_string())
+            .collect();
+        assert_eq!(
+            references,
+            vec![
+                "SP3FBR2AGK5H9QBDH3EEN6DF8EK8JY7RX8QJ5SVTE.sip-010-trait-ft-standard",
+                "ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM.token",
+            ]
+        );
+    }
+}
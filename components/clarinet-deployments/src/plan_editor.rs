@@ -0,0 +1,418 @@
+//! Edition of a deployment plan (`clarinet deployments edit`): the transactions are moved
+//! within and across the batches, their costs changed, and they are toggled on and off. A
+//! transaction can't be moved before the publish of a contract it uses, and the plan is
+//! validated after each change. The transactions toggled off are left out of the saved plan.
+
+use std::collections::BTreeSet;
+
+use clarity_repl::clarity::ast::build_ast_with_diagnostics;
+use clarity_repl::clarity::vm::types::QualifiedContractIdentifier;
+use clarity_repl::clarity::{ClarityVersion, StacksEpochId};
+use clarity_repl::repl::clarity_versions;
+use clarity_repl::repl::deferred::get_referenced_contracts;
+use clarity_repl::repl::DEFAULT_EPOCH;
+
+use crate::types::{DeploymentSpecification, TransactionSpecification};
+
+#[derive(Debug, Clone)]
+pub struct EditedTransaction {
+    pub transaction: TransactionSpecification,
+    pub enabled: bool,
+    /// Contract published by the transaction
+    pub published: Option<QualifiedContractIdentifier>,
+    /// Contracts the transaction calls, or refers to in its source or its arguments
+    pub dependencies: BTreeSet<QualifiedContractIdentifier>,
+}
+
+#[derive(Debug, Clone)]
+pub struct PlanEditor {
+    deployment: DeploymentSpecification,
+    /// Transactions of each batch of the plan
+    pub batches: Vec<Vec<EditedTransaction>>,
+}
+
+fn get_code_dependencies(
+    context: &QualifiedContractIdentifier,
+    code: &str,
+    clarity_version: ClarityVersion,
+    epoch: StacksEpochId,
+) -> BTreeSet<QualifiedContractIdentifier> {
+    let (ast, _, _) = build_ast_with_diagnostics(context, code, &mut (), clarity_version, epoch);
+    let mut dependencies = get_referenced_contracts(&ast.expressions);
+    dependencies.remove(context);
+    dependencies
+}
+
+fn get_edited_transaction(
+    transaction: &TransactionSpecification,
+    epoch: StacksEpochId,
+) -> EditedTransaction {
+    let clarity_version = clarity_versions::latest_version(epoch);
+    let (published, dependencies) = match transaction {
+        TransactionSpecification::ContractPublish(tx) => {
+            let contract_id = QualifiedContractIdentifier::new(
+                tx.expected_sender.clone(),
+                tx.contract_name.clone(),
+            );
+            let dependencies =
+                get_code_dependencies(&contract_id, &tx.source, tx.clarity_version, epoch);
+            (Some(contract_id), dependencies)
+        }
+        TransactionSpecification::EmulatedContractPublish(tx) => {
+            let contract_id = QualifiedContractIdentifier::new(
+                tx.emulated_sender.clone(),
+                tx.contract_name.clone(),
+            );
+            let dependencies =
+                get_code_dependencies(&contract_id, &tx.source, tx.clarity_version, epoch);
+            (Some(contract_id), dependencies)
+        }
+        TransactionSpecification::RequirementPublish(tx) => {
+            let contract_id = tx.get_remapped_contract_id();
+            let dependencies =
+                get_code_dependencies(&contract_id, &tx.source, tx.clarity_version, epoch);
+            (Some(contract_id), dependencies)
+        }
+        TransactionSpecification::ContractCall(tx) => {
+            let code = tx.parameters.join(" ");
+            let mut dependencies =
+                get_code_dependencies(&tx.contract_id, &code, clarity_version, epoch);
+            dependencies.insert(tx.contract_id.clone());
+            (None, dependencies)
+        }
+        TransactionSpecification::EmulatedContractCall(tx) => {
+            let code = tx.parameters.join(" ");
+            let mut dependencies =
+                get_code_dependencies(&tx.contract_id, &code, clarity_version, epoch);
+            dependencies.insert(tx.contract_id.clone());
+            (None, dependencies)
+        }
+        TransactionSpecification::EmulatedEval(tx) => {
+            let context =
+                QualifiedContractIdentifier::new(tx.emulated_sender.clone(), "plan-editor".into());
+            let dependencies = get_code_dependencies(&context, &tx.code, clarity_version, epoch);
+            (None, dependencies)
+        }
+        TransactionSpecification::StxTransfer(_)
+        | TransactionSpecification::BtcTransfer(_)
+        | TransactionSpecification::BnsOperation(_) => (None, BTreeSet::new()),
+    };
+    EditedTransaction {
+        transaction: transaction.clone(),
+        enabled: true,
+        published,
+        dependencies,
+    }
+}
+
+/// Short description of a transaction, as listed by the editor
+pub fn get_transaction_label(transaction: &TransactionSpecification) -> String {
+    match transaction {
+        TransactionSpecification::ContractPublish(tx) => {
+            format!("publish {}.{}", tx.expected_sender, tx.contract_name)
+        }
+        TransactionSpecification::EmulatedContractPublish(tx) => {
+            format!("publish {}.{}", tx.emulated_sender, tx.contract_name)
+        }
+        TransactionSpecification::RequirementPublish(tx) => {
+            format!("publish requirement {}", tx.get_remapped_contract_id())
+        }
+        TransactionSpecification::ContractCall(tx) => {
+            format!("call {}::{}", tx.contract_id, tx.method)
+        }
+        TransactionSpecification::EmulatedContractCall(tx) => {
+            format!("call {}::{}", tx.contract_id, tx.method)
+        }
+        TransactionSpecification::EmulatedEval(tx) => format!("eval {}", tx.code.trim()),
+        TransactionSpecification::StxTransfer(tx) => {
+            format!("transfer {} µSTX to {}", tx.mstx_amount, tx.recipient)
+        }
+        TransactionSpecification::BtcTransfer(tx) => {
+            format!("transfer {} sats to {}", tx.sats_amount, tx.recipient)
+        }
+        TransactionSpecification::BnsOperation(tx) => {
+            format!("BNS {} {}", tx.get_method(), tx.get_fully_qualified_name())
+        }
+    }
+}
+
+/// Fee of a transaction broadcasted to a network, `None` for the simnet transactions
+pub fn get_transaction_cost(transaction: &TransactionSpecification) -> Option<u64> {
+    match transaction {
+        TransactionSpecification::ContractPublish(tx) => Some(tx.cost),
+        TransactionSpecification::RequirementPublish(tx) => Some(tx.cost),
+        TransactionSpecification::ContractCall(tx) => Some(tx.cost),
+        TransactionSpecification::StxTransfer(tx) => Some(tx.cost),
+        TransactionSpecification::BnsOperation(tx) => Some(tx.cost),
+        TransactionSpecification::EmulatedContractPublish(_)
+        | TransactionSpecification::EmulatedContractCall(_)
+        | TransactionSpecification::EmulatedEval(_)
+        | TransactionSpecification::BtcTransfer(_) => None,
+    }
+}
+
+impl PlanEditor {
+    pub fn new(deployment: &DeploymentSpecification) -> Self {
+        let batches = deployment
+            .plan
+            .batches
+            .iter()
+            .map(|batch| {
+                let epoch = batch.epoch.map_or(DEFAULT_EPOCH, StacksEpochId::from);
+                batch
+                    .transactions
+                    .iter()
+                    .map(|tx| get_edited_transaction(tx, epoch))
+                    .collect()
+            })
+            .collect();
+        PlanEditor {
+            deployment: deployment.clone(),
+            batches,
+        }
+    }
+
+    pub fn get_batch_id(&self, batch: usize) -> usize {
+        self.deployment.plan.batches[batch].id
+    }
+
+    /// Checks that `moved_up` can be moved before `passed`
+    fn check_order(moved_up: &EditedTransaction, passed: &EditedTransaction) -> Result<(), String> {
+        match passed.published {
+            Some(ref contract_id) if moved_up.dependencies.contains(contract_id) => Err(format!(
+                "{} uses {}, it must come after its publish",
+                get_transaction_label(&moved_up.transaction),
+                contract_id
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Moves a transaction one step up, to the end of the previous batch if it's the first of
+    /// its batch. Returns its new position.
+    pub fn move_up(&mut self, batch: usize, index: usize) -> Result<(usize, usize), String> {
+        if index == 0 {
+            if batch == 0 {
+                return Err("the transaction is the first of the plan".to_string());
+            }
+            let transaction = self.batches[batch].remove(0);
+            self.batches[batch - 1].push(transaction);
+            return Ok((batch - 1, self.batches[batch - 1].len() - 1));
+        }
+        Self::check_order(&self.batches[batch][index], &self.batches[batch][index - 1])?;
+        self.batches[batch].swap(index, index - 1);
+        Ok((batch, index - 1))
+    }
+
+    /// Moves a transaction one step down, to the start of the next batch if it's the last of
+    /// its batch. Returns its new position.
+    pub fn move_down(&mut self, batch: usize, index: usize) -> Result<(usize, usize), String> {
+        if index + 1 == self.batches[batch].len() {
+            if batch + 1 == self.batches.len() {
+                return Err("the transaction is the last of the plan".to_string());
+            }
+            let transaction = self.batches[batch].remove(index);
+            self.batches[batch + 1].insert(0, transaction);
+            return Ok((batch + 1, 0));
+        }
+        Self::check_order(&self.batches[batch][index + 1], &self.batches[batch][index])?;
+        self.batches[batch].swap(index, index + 1);
+        Ok((batch, index + 1))
+    }
+
+    pub fn toggle(&mut self, batch: usize, index: usize) {
+        let transaction = &mut self.batches[batch][index];
+        transaction.enabled = !transaction.enabled;
+    }
+
+    pub fn set_cost(&mut self, batch: usize, index: usize, cost: u64) -> Result<(), String> {
+        let transaction = &mut self.batches[batch][index].transaction;
+        match transaction {
+            TransactionSpecification::ContractPublish(tx) => tx.cost = cost,
+            TransactionSpecification::RequirementPublish(tx) => tx.cost = cost,
+            TransactionSpecification::ContractCall(tx) => tx.cost = cost,
+            TransactionSpecification::StxTransfer(tx) => tx.cost = cost,
+            TransactionSpecification::BnsOperation(tx) => tx.cost = cost,
+            _ => {
+                return Err(format!(
+                    "{} has no cost",
+                    get_transaction_label(transaction)
+                ))
+            }
+        }
+        Ok(())
+    }
+
+    /// Problems of the plan as edited: the transactions using a contract published after
+    /// them or toggled off, the contracts published in an epoch not supporting their Clarity
+    /// version
+    pub fn validate(&self) -> Vec<String> {
+        let plan_contracts: BTreeSet<&QualifiedContractIdentifier> = self
+            .batches
+            .iter()
+            .flatten()
+            .filter_map(|tx| tx.published.as_ref())
+            .collect();
+        let mut published = BTreeSet::new();
+        let mut problems = vec![];
+        for (batch, transactions) in self.batches.iter().enumerate() {
+            let epoch = self.deployment.plan.batches[batch].epoch;
+            for tx in transactions.iter().filter(|tx| tx.enabled) {
+                for dependency in tx.dependencies.iter() {
+                    if plan_contracts.contains(dependency) && !published.contains(dependency) {
+                        problems.push(format!(
+                            "batch {}: {} uses {}, which is not published before",
+                            self.get_batch_id(batch),
+                            get_transaction_label(&tx.transaction),
+                            dependency
+                        ));
+                    }
+                }
+                let clarity_version = match &tx.transaction {
+                    TransactionSpecification::ContractPublish(tx) => Some(tx.clarity_version),
+                    TransactionSpecification::EmulatedContractPublish(tx) => {
+                        Some(tx.clarity_version)
+                    }
+                    TransactionSpecification::RequirementPublish(tx) => Some(tx.clarity_version),
+                    _ => None,
+                };
+                if let (Some(epoch), Some(clarity_version)) = (epoch, clarity_version) {
+                    if !clarity_versions::is_supported(epoch.into(), clarity_version) {
+                        problems.push(format!(
+                            "batch {}: {} can't be published with {} in epoch {}",
+                            self.get_batch_id(batch),
+                            get_transaction_label(&tx.transaction),
+                            clarity_version,
+                            epoch
+                        ));
+                    }
+                }
+                if let Some(ref contract_id) = tx.published {
+                    published.insert(contract_id);
+                }
+            }
+        }
+        problems
+    }
+
+    /// The plan as edited, without the transactions toggled off. The batches left without
+    /// transactions nor post-checks are dropped.
+    pub fn get_deployment(&self) -> DeploymentSpecification {
+        let mut deployment = self.deployment.clone();
+        for (batch, transactions) in deployment.plan.batches.iter_mut().zip(self.batches.iter()) {
+            batch.transactions = transactions
+                .iter()
+                .filter(|tx| tx.enabled)
+                .map(|tx| tx.transaction.clone())
+                .collect();
+        }
+        deployment
+            .plan
+            .batches
+            .retain(|batch| !batch.transactions.is_empty() || !batch.post_checks.is_empty());
+        deployment
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use clarinet_files::{FileLocation, StacksNetwork};
+    use clarity_repl::clarity::{ClarityName, ContractName};
+
+    use super::*;
+    use crate::types::{
+        AnchorMode, ContractCallSpecification, ContractPublishSpecification,
+        TransactionPlanSpecification, TransactionsBatchSpecification,
+    };
+
+    #[test]
+    fn test_plan_editor() {
+        let token_id =
+            QualifiedContractIdentifier::parse("ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM.token")
+                .unwrap();
+        let publish = |name: &str, source: &str| {
+            TransactionSpecification::ContractPublish(ContractPublishSpecification {
+                contract_name: ContractName::try_from(name.to_string()).unwrap(),
+                expected_sender: token_id.issuer.clone(),
+                location: FileLocation::from_path_string(&format!("/contracts/{}.clar", name))
+                    .unwrap(),
+                source: source.to_string(),
+                clarity_version: ClarityVersion::Clarity2,
+                cost: 10_000,
+                anchor_mode: AnchorMode::OnChainOnly,
+                description: None,
+            })
+        };
+        let mint = TransactionSpecification::ContractCall(ContractCallSpecification {
+            contract_id: token_id.clone(),
+            expected_sender: token_id.issuer.clone(),
+            method: ClarityName::try_from("mint".to_string()).unwrap(),
+            parameters: vec!["u100".to_string()],
+            cost: 1_000,
+            anchor_mode: AnchorMode::OnChainOnly,
+            description: None,
+        });
+        let batch = |id, transactions| TransactionsBatchSpecification {
+            id,
+            transactions,
+            epoch: Some(crate::types::EpochSpec::Epoch2_5),
+            post_checks: vec![],
+            description: None,
+        };
+        let deployment = DeploymentSpecification {
+            id: 0,
+            name: "testnet".to_string(),
+            network: StacksNetwork::Testnet,
+            stacks_node: None,
+            bitcoin_node: None,
+            genesis: None,
+            plan: TransactionPlanSpecification {
+                batches: vec![
+                    batch(
+                        0,
+                        vec![
+                            publish("token", "(define-public (mint (amount uint)) (ok amount))"),
+                            publish(
+                                "vault",
+                                "(define-public (deposit) (contract-call? .token mint u1))",
+                            ),
+                        ],
+                    ),
+                    batch(1, vec![mint]),
+                ],
+                post_checks: vec![],
+            },
+            contracts: BTreeMap::new(),
+            description: None,
+        };
+
+        let mut editor = PlanEditor::new(&deployment);
+        assert!(editor.validate().is_empty());
+        assert!(editor.move_up(0, 1).is_err());
+        assert_eq!(editor.move_up(1, 0), Ok((0, 2)));
+        assert_eq!(editor.move_up(0, 2), Ok((0, 1)));
+        assert!(editor.move_up(0, 1).is_err());
+        assert_eq!(editor.move_down(0, 1), Ok((0, 2)));
+        assert!(editor.move_down(0, 0).is_err());
+        editor.set_cost(0, 2, 2_500).unwrap();
+
+        editor.toggle(0, 0);
+        let problems = editor.validate();
+        assert_eq!(problems.len(), 2);
+        assert_eq!(
+            problems[1],
+            format!(
+                "batch 0: call {}::mint uses {}, which is not published before",
+                token_id, token_id
+            )
+        );
+        editor.toggle(0, 0);
+        editor.toggle(0, 1);
+        assert!(editor.validate().is_empty());
+        let edited = editor.get_deployment();
+        assert_eq!(edited.plan.batches.len(), 1);
+        assert_eq!(edited.plan.batches[0].transactions.len(), 2);
+    }
+}
@@ -0,0 +1,278 @@
+//! Conversion of a deployment plan to the JSON consumed by the Hiro Platform deployment
+//! endpoints and by stacks.js broadcast scripts. Each transaction carries the options of
+//! the matching stacks.js builder (`makeContractDeploy`, `makeContractCall` and
+//! `makeSTXTokenTransfer`); keys and nonces are left to the broadcaster.
+
+use clarity_repl::repl::clarity_versions::version_number;
+
+use crate::principal_remap::{
+    get_boot_contracts_remaps, get_remapped_requirement_source, remap_contracts_ids,
+};
+use crate::types::{AnchorMode, DeploymentSpecification, TransactionSpecification};
+use clarinet_files::StacksNetwork;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportedPlan {
+    pub name: String,
+    pub network: String,
+    pub node_url: Option<String>,
+    pub batches: Vec<ExportedBatch>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ExportedBatch {
+    pub id: usize,
+    /// Transactions of a batch can be broadcast in the same block
+    pub transactions: Vec<ExportedTransaction>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum ExportedTransaction {
+    #[serde(rename_all = "camelCase")]
+    ContractDeploy {
+        contract_name: String,
+        code_body: String,
+        clarity_version: u8,
+        sender_address: String,
+        fee: u64,
+        anchor_mode: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    ContractCall {
+        contract_address: String,
+        contract_name: String,
+        function_name: String,
+        /// Clarity literals, to be converted with `Cl.parse`
+        function_args: Vec<String>,
+        sender_address: String,
+        fee: u64,
+        anchor_mode: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    StxTransfer {
+        recipient: String,
+        /// In µSTX, as a string to fit in a JS number
+        amount: String,
+        memo: String,
+        sender_address: String,
+        fee: u64,
+        anchor_mode: String,
+    },
+}
+
+fn get_anchor_mode(anchor_mode: AnchorMode) -> String {
+    match anchor_mode {
+        AnchorMode::OnChainOnly => "onChainOnly".to_string(),
+        AnchorMode::OffChainOnly => "offChainOnly".to_string(),
+        AnchorMode::Any => "any".to_string(),
+    }
+}
+
+pub fn export_deployment_plan(
+    deployment: &DeploymentSpecification,
+) -> Result<ExportedPlan, String> {
+    if matches!(deployment.network, StacksNetwork::Simnet) {
+        return Err("simnet deployment plans can not be broadcast".to_string());
+    }
+    let remap_contracts = !matches!(deployment.network, StacksNetwork::Mainnet);
+    let mut contracts_ids_to_remap = get_boot_contracts_remaps();
+
+    let mut batches = vec![];
+    for batch in deployment.plan.batches.iter() {
+        let mut transactions = vec![];
+        for transaction in batch.transactions.iter() {
+            let bns_contract_call;
+            let transaction = match transaction {
+                TransactionSpecification::BnsOperation(tx) => {
+                    bns_contract_call =
+                        TransactionSpecification::ContractCall(tx.as_contract_call());
+                    &bns_contract_call
+                }
+                _ => transaction,
+            };
+            let exported = match transaction {
+                TransactionSpecification::ContractPublish(tx) => {
+                    let code_body = match remap_contracts {
+                        true => remap_contracts_ids(&tx.source, &contracts_ids_to_remap),
+                        false => tx.source.clone(),
+                    };
+                    ExportedTransaction::ContractDeploy {
+                        contract_name: tx.contract_name.to_string(),
+                        code_body,
+                        clarity_version: version_number(tx.clarity_version),
+                        sender_address: tx.expected_sender.to_address(),
+                        fee: tx.cost,
+                        anchor_mode: get_anchor_mode(tx.anchor_mode),
+                    }
+                }
+                TransactionSpecification::RequirementPublish(tx) => {
+                    if !remap_contracts {
+                        return Err(format!(
+                            "requirement {} can not be published on mainnet",
+                            tx.contract_id
+                        ));
+                    }
+                    let remapped_contract_id = tx.get_remapped_contract_id();
+                    contracts_ids_to_remap
+                        .insert((tx.contract_id.to_string(), remapped_contract_id.to_string()));
+                    ExportedTransaction::ContractDeploy {
+                        contract_name: remapped_contract_id.name.to_string(),
+                        code_body: get_remapped_requirement_source(tx, &contracts_ids_to_remap),
+                        clarity_version: version_number(tx.clarity_version),
+                        sender_address: tx.remap_sender.to_address(),
+                        fee: tx.cost,
+                        anchor_mode: get_anchor_mode(tx.anchor_mode),
+                    }
+                }
+                TransactionSpecification::ContractCall(tx) => ExportedTransaction::ContractCall {
+                    contract_address: tx.contract_id.issuer.to_address(),
+                    contract_name: tx.contract_id.name.to_string(),
+                    function_name: tx.method.to_string(),
+                    function_args: tx.parameters.clone(),
+                    sender_address: tx.expected_sender.to_address(),
+                    fee: tx.cost,
+                    anchor_mode: get_anchor_mode(tx.anchor_mode),
+                },
+                TransactionSpecification::StxTransfer(tx) => {
+                    let memo_length = tx
+                        .memo
+                        .iter()
+                        .rposition(|byte| *byte != 0)
+                        .map_or(0, |index| index + 1);
+                    ExportedTransaction::StxTransfer {
+                        recipient: tx.recipient.to_string(),
+                        amount: tx.mstx_amount.to_string(),
+                        memo: String::from_utf8_lossy(&tx.memo[..memo_length]).to_string(),
+                        sender_address: tx.expected_sender.to_address(),
+                        fee: tx.cost,
+                        anchor_mode: get_anchor_mode(tx.anchor_mode),
+                    }
+                }
+                TransactionSpecification::BtcTransfer(_) => {
+                    return Err(format!(
+                        "batch {}: bitcoin transfers can not be exported",
+                        batch.id
+                    ))
+                }
+                TransactionSpecification::EmulatedContractCall(_)
+                | TransactionSpecification::EmulatedContractPublish(_)
+                | TransactionSpecification::EmulatedEval(_) => {
+                    return Err(format!(
+                        "batch {}: emulated transactions can not be exported",
+                        batch.id
+                    ))
+                }
+                TransactionSpecification::BnsOperation(_) => unreachable!(),
+            };
+            transactions.push(exported);
+        }
+        batches.push(ExportedBatch {
+            id: batch.id,
+            transactions,
+        });
+    }
+
+    Ok(ExportedPlan {
+        name: deployment.name.clone(),
+        network: format!("{:?}", deployment.network).to_lowercase(),
+        node_url: deployment.stacks_node.clone(),
+        batches,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{
+        ContractCallSpecification, StxTransferSpecification, TransactionPlanSpecification,
+        TransactionsBatchSpecification,
+    };
+    use clarity_repl::clarity::vm::types::{PrincipalData, QualifiedContractIdentifier};
+    use clarity_repl::clarity::ClarityName;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_export_deployment_plan() {
+        let sender =
+            PrincipalData::parse_standard_principal("ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM")
+                .unwrap();
+        let mut memo = [0u8; 34];
+        memo[..5].copy_from_slice(b"hello");
+        let deployment = DeploymentSpecification {
+            id: 0,
+            name: "default".to_string(),
+            network: StacksNetwork::Testnet,
+            stacks_node: Some("https://api.testnet.hiro.so".to_string()),
+            bitcoin_node: None,
+            genesis: None,
+            plan: TransactionPlanSpecification {
+                batches: vec![TransactionsBatchSpecification {
+                    id: 0,
+                    epoch: None,
+                    transactions: vec![
+                        TransactionSpecification::ContractCall(ContractCallSpecification {
+                            contract_id: QualifiedContractIdentifier::parse(
+                                "ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM.counter",
+                            )
+                            .unwrap(),
+                            expected_sender: sender.clone(),
+                            method: ClarityName::from("add"),
+                            parameters: vec!["u1".to_string()],
+                            cost: 1000,
+                            anchor_mode: AnchorMode::OnChainOnly,
+                            description: None,
+                        }),
+                        TransactionSpecification::StxTransfer(StxTransferSpecification {
+                            expected_sender: sender.clone(),
+                            recipient: PrincipalData::parse(
+                                "ST2CY5V39NHDPWSXMW9QDT3HC3GD6Q6XX4CFRK9AG",
+                            )
+                            .unwrap(),
+                            mstx_amount: 100,
+                            memo,
+                            cost: 500,
+                            anchor_mode: AnchorMode::Any,
+                            description: None,
+                        }),
+                    ],
+                    post_checks: vec![],
+                    description: None,
+                }],
+                post_checks: vec![],
+            },
+            contracts: BTreeMap::new(),
+            description: None,
+        };
+
+        let exported = export_deployment_plan(&deployment).unwrap();
+        assert_eq!(exported.network, "testnet");
+        assert_eq!(
+            exported.batches[0].transactions,
+            vec![
+                ExportedTransaction::ContractCall {
+                    contract_address: "ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM".to_string(),
+                    contract_name: "counter".to_string(),
+                    function_name: "add".to_string(),
+                    function_args: vec!["u1".to_string()],
+                    sender_address: "ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM".to_string(),
+                    fee: 1000,
+                    anchor_mode: "onChainOnly".to_string(),
+                },
+                ExportedTransaction::StxTransfer {
+                    recipient: "ST2CY5V39NHDPWSXMW9QDT3HC3GD6Q6XX4CFRK9AG".to_string(),
+                    amount: "100".to_string(),
+                    memo: "hello".to_string(),
+                    sender_address: "ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM".to_string(),
+                    fee: 500,
+                    anchor_mode: "any".to_string(),
+                },
+            ]
+        );
+
+        let json = serde_json::to_value(&exported.batches[0].transactions[0]).unwrap();
+        assert_eq!(json["type"], "contract-call");
+        assert_eq!(json["functionName"], "add");
+    }
+}
@@ -0,0 +1,256 @@
+//! Rewriting of the literal principals of a requirement republished under another sender.
+//! Unlike a plain text replacement, only the principals of the AST are rewritten: comments
+//! and strings mentioning an address are left untouched.
+
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+
+use clarity_repl::clarity::ast::build_ast_with_diagnostics;
+use clarity_repl::clarity::vm::types::{
+    PrincipalData, QualifiedContractIdentifier, StandardPrincipalData, Value,
+};
+use clarity_repl::clarity::vm::{SymbolicExpression, SymbolicExpressionType};
+use clarity_repl::repl::session::{
+    BOOT_MAINNET_ADDRESS, BOOT_TESTNET_ADDRESS, V1_BOOT_CONTRACTS, V2_BOOT_CONTRACTS,
+    V3_BOOT_CONTRACTS,
+};
+use clarity_repl::repl::{DEFAULT_CLARITY_VERSION, DEFAULT_EPOCH};
+
+use crate::types::RequirementPublishSpecification;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrincipalRewrite {
+    pub line: u32,
+    pub column: u32,
+    pub original: String,
+    pub rewritten: String,
+}
+
+impl fmt::Display for PrincipalRewrite {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}: {} -> {}",
+            self.line, self.column, self.original, self.rewritten
+        )
+    }
+}
+
+fn get_principal_issuer(expression: &SymbolicExpression) -> Option<&StandardPrincipalData> {
+    match &expression.expr {
+        SymbolicExpressionType::LiteralValue(Value::Principal(PrincipalData::Standard(issuer))) => {
+            Some(issuer)
+        }
+        SymbolicExpressionType::LiteralValue(Value::Principal(PrincipalData::Contract(id))) => {
+            Some(&id.issuer)
+        }
+        SymbolicExpressionType::LiteralValue(Value::CallableContract(callable)) => {
+            Some(&callable.contract_identifier.issuer)
+        }
+        SymbolicExpressionType::Field(trait_id) => Some(&trait_id.contract_identifier.issuer),
+        _ => None,
+    }
+}
+
+fn collect_principals<'a>(
+    expression: &'a SymbolicExpression,
+    principals: &mut Vec<&'a SymbolicExpression>,
+) {
+    if let SymbolicExpressionType::List(list) = &expression.expr {
+        for expression in list.iter() {
+            collect_principals(expression, principals);
+        }
+    } else if get_principal_issuer(expression).is_some() {
+        principals.push(expression);
+    }
+}
+
+/// Byte offset of a 1-based (line, column) position
+fn get_offset(lines_offsets: &[usize], source: &str, line: u32, column: u32) -> Option<usize> {
+    let line_offset = *lines_offsets.get(line.checked_sub(1)? as usize)?;
+    source[line_offset..]
+        .char_indices()
+        .nth(column.checked_sub(1)? as usize)
+        .map(|(offset, _)| line_offset + offset)
+}
+
+/// Rewrites the issuer of the literal principals of `source` found in `remap_principals`.
+/// Sugared contract identifiers (`.token`) don't mention their issuer and are kept as is.
+pub fn rewrite_literal_principals(
+    contract_id: &QualifiedContractIdentifier,
+    source: &str,
+    remap_principals: &BTreeMap<StandardPrincipalData, StandardPrincipalData>,
+) -> (String, Vec<PrincipalRewrite>) {
+    let (ast, _, _) = build_ast_with_diagnostics(
+        contract_id,
+        source,
+        &mut (),
+        DEFAULT_CLARITY_VERSION,
+        DEFAULT_EPOCH,
+    );
+    let mut principals = vec![];
+    for expression in ast.expressions.iter() {
+        collect_principals(expression, &mut principals);
+    }
+
+    let mut lines_offsets = vec![0];
+    lines_offsets.extend(source.match_indices('\n').map(|(offset, _)| offset + 1));
+
+    let mut replacements = vec![];
+    for expression in principals {
+        let Some(issuer) = get_principal_issuer(expression) else {
+            continue;
+        };
+        let Some(new_issuer) = remap_principals.get(issuer) else {
+            continue;
+        };
+        let span = &expression.span;
+        let (Some(start), Some(end)) = (
+            get_offset(&lines_offsets, source, span.start_line, span.start_column),
+            get_offset(&lines_offsets, source, span.end_line, span.end_column),
+        ) else {
+            continue;
+        };
+        let end = end + source[end..].chars().next().map_or(0, |c| c.len_utf8());
+        let original = &source[start..end];
+        let address = issuer.to_address();
+        let Some(index) = original.find(&address) else {
+            continue;
+        };
+        let rewritten = original.replacen(&address, &new_issuer.to_address(), 1);
+        replacements.push((
+            start + index..start + index + address.len(),
+            new_issuer.to_address(),
+            PrincipalRewrite {
+                line: span.start_line,
+                column: span.start_column,
+                original: original.to_string(),
+                rewritten,
+            },
+        ));
+    }
+
+    replacements.sort_by_key(|(range, _, _)| range.start);
+    let mut rewritten_source = source.to_string();
+    for (range, new_address, _) in replacements.iter().rev() {
+        rewritten_source.replace_range(range.clone(), new_address);
+    }
+    let report = replacements
+        .into_iter()
+        .map(|(_, _, rewrite)| rewrite)
+        .collect();
+    (rewritten_source, report)
+}
+
+/// Boot contracts ids to rewrite in the sources published on devnet and testnet, where the
+/// boot contracts are deployed by the testnet boot address
+pub fn get_boot_contracts_remaps() -> HashSet<(String, String)> {
+    V1_BOOT_CONTRACTS
+        .iter()
+        .chain(V2_BOOT_CONTRACTS.iter())
+        .chain(V3_BOOT_CONTRACTS.iter())
+        .map(|contract| {
+            (
+                format!("{}:{}", BOOT_MAINNET_ADDRESS, contract),
+                format!("{}:{}", BOOT_TESTNET_ADDRESS, contract),
+            )
+        })
+        .collect()
+}
+
+/// Replaces every occurrence of the source of each pair of `remaps` with its destination
+pub fn remap_occurrences<'a>(
+    source: &str,
+    remaps: impl IntoIterator<Item = (&'a str, &'a str)>,
+) -> String {
+    let mut source = source.to_string();
+    for (src, dst) in remaps {
+        let mut matched_indices = source
+            .match_indices(src)
+            .map(|(i, _)| i)
+            .collect::<Vec<usize>>();
+        matched_indices.reverse();
+        for index in matched_indices {
+            source.replace_range(index..index + src.len(), dst);
+        }
+    }
+    source
+}
+
+/// `source` with the contracts ids of `contracts_ids_to_remap` replaced
+pub fn remap_contracts_ids(
+    source: &str,
+    contracts_ids_to_remap: &HashSet<(String, String)>,
+) -> String {
+    remap_occurrences(
+        source,
+        contracts_ids_to_remap
+            .iter()
+            .map(|(src, dst)| (src.as_str(), dst.as_str())),
+    )
+}
+
+/// Source of a requirement as republished by `tx`: contracts ids first, as a remapped
+/// contract can have a new name, then the remapped principals
+pub fn get_remapped_requirement_source(
+    tx: &RequirementPublishSpecification,
+    contracts_ids_to_remap: &HashSet<(String, String)>,
+) -> String {
+    let source = remap_contracts_ids(&tx.source, contracts_ids_to_remap);
+    if tx.rewrite_literal_principals {
+        return rewrite_literal_principals(&tx.contract_id, &source, &tx.remap_principals).0;
+    }
+    let remap_principals: Vec<(String, String)> = tx
+        .remap_principals
+        .iter()
+        .map(|(src, dst)| (src.to_address(), dst.to_address()))
+        .collect();
+    remap_occurrences(
+        &source,
+        remap_principals
+            .iter()
+            .map(|(src, dst)| (src.as_str(), dst.as_str())),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rewrite_literal_principals() {
+        let contract_id =
+            QualifiedContractIdentifier::parse("SP3FBR2AGK5H9QBDH3EEN6DF8EK8JY7RX8QJ5SVTE.vault")
+                .unwrap();
+        let source = r#";; owned by SP3FBR2AGK5H9QBDH3EEN6DF8EK8JY7RX8QJ5SVTE
+(define-constant owner 'SP3FBR2AGK5H9QBDH3EEN6DF8EK8JY7RX8QJ5SVTE)
+(define-constant label "SP3FBR2AGK5H9QBDH3EEN6DF8EK8JY7RX8QJ5SVTE")
+(define-read-only (get-token) 'SP3FBR2AGK5H9QBDH3EEN6DF8EK8JY7RX8QJ5SVTE.token)
+(define-read-only (get-self) .vault)
+"#;
+        let mut remap_principals = BTreeMap::new();
+        remap_principals.insert(
+            contract_id.issuer.clone(),
+            PrincipalData::parse_standard_principal("ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM")
+                .unwrap(),
+        );
+
+        let (rewritten, report) =
+            rewrite_literal_principals(&contract_id, source, &remap_principals);
+        assert_eq!(
+            rewritten,
+            r#";; owned by SP3FBR2AGK5H9QBDH3EEN6DF8EK8JY7RX8QJ5SVTE
+(define-constant owner 'ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM)
+(define-constant label "SP3FBR2AGK5H9QBDH3EEN6DF8EK8JY7RX8QJ5SVTE")
+(define-read-only (get-token) 'ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM.token)
+(define-read-only (get-self) .vault)
+"#
+        );
+        assert_eq!(report.len(), 2);
+        assert_eq!(report[0].line, 2);
+        assert_eq!(
+            report[1].rewritten,
+            "'ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM.token"
+        );
+    }
+}
@@ -0,0 +1,117 @@
+use std::cell::RefCell;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+thread_local! {
+    static CURRENT_PROFILE: RefCell<Option<StartupProfile>> = const { RefCell::new(None) };
+}
+
+#[derive(Debug, Clone)]
+pub struct StartupPhase {
+    pub name: String,
+    pub duration: Duration,
+}
+
+/// Time spent in each phase of the session setup (manifest loading, requirements
+/// resolution, ASTs, boot contracts, deployment replay).
+#[derive(Debug, Clone, Default)]
+pub struct StartupProfile {
+    pub phases: Vec<StartupPhase>,
+}
+
+impl StartupProfile {
+    pub fn total(&self) -> Duration {
+        self.phases.iter().map(|p| p.duration).sum()
+    }
+}
+
+impl fmt::Display for StartupProfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let total = self.total();
+        let name_width = self
+            .phases
+            .iter()
+            .map(|p| p.name.len())
+            .max()
+            .unwrap_or(0)
+            .max(5);
+        for phase in self.phases.iter() {
+            let share = if total.is_zero() {
+                0.0
+            } else {
+                phase.duration.as_secs_f64() * 100.0 / total.as_secs_f64()
+            };
+            writeln!(
+                f,
+                "{:<name_width$}  {:>10.2}ms  {:>5.1}%",
+                phase.name,
+                phase.duration.as_secs_f64() * 1000.0,
+                share
+            )?;
+        }
+        write!(
+            f,
+            "{:<name_width$}  {:>10.2}ms",
+            "total",
+            total.as_secs_f64() * 1000.0
+        )
+    }
+}
+
+/// Start collecting the timings of the phases executed on the current thread.
+pub fn start_profiling() {
+    CURRENT_PROFILE.with(|profile| *profile.borrow_mut() = Some(StartupProfile::default()));
+}
+
+/// Stop collecting timings and return the phases recorded since `start_profiling`.
+pub fn stop_profiling() -> Option<StartupProfile> {
+    CURRENT_PROFILE.with(|profile| profile.borrow_mut().take())
+}
+
+fn is_profiling() -> bool {
+    CURRENT_PROFILE.with(|profile| profile.borrow().is_some())
+}
+
+/// Measures a phase, from `start` to `end`. When profiling is disabled, no clock is
+/// read, so that timers can be used in environments without a system clock (wasm).
+pub struct PhaseTimer {
+    started: Option<(String, Instant)>,
+}
+
+impl PhaseTimer {
+    pub fn start(name: &str) -> Self {
+        let started = is_profiling().then(|| (name.to_string(), Instant::now()));
+        Self { started }
+    }
+
+    pub fn end(self) {
+        if let Some((name, started_at)) = self.started {
+            let duration = started_at.elapsed();
+            CURRENT_PROFILE.with(|profile| {
+                if let Some(profile) = profile.borrow_mut().as_mut() {
+                    profile.phases.push(StartupPhase { name, duration });
+                }
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_phases_are_only_recorded_while_profiling() {
+        PhaseTimer::start("ignored").end();
+
+        start_profiling();
+        PhaseTimer::start("first").end();
+        PhaseTimer::start("second").end();
+        let profile = stop_profiling().unwrap();
+        let names: Vec<_> = profile.phases.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["first", "second"]);
+
+        PhaseTimer::start("ignored").end();
+        assert!(stop_profiling().is_none());
+    }
+}
@@ -0,0 +1,146 @@
+//! Provenance of the requirements of a project (`clarinet requirements report`): the on-chain
+//! origin recorded in the cache when each source was fetched, the hash of the source the
+//! project is built and tested against, and the license the contract declares.
+
+use std::fs;
+
+use clarinet_files::FileLocation;
+
+use crate::requirements::{get_source_hash, ContractMetadata, RequirementProvenance};
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RequirementReport {
+    pub contract_id: String,
+    /// Hash of the cached source
+    pub source_hash: Option<String>,
+    /// SPDX identifier declared in the comments of the source
+    pub license: Option<String>,
+    pub provenance: Option<RequirementProvenance>,
+    pub warnings: Vec<String>,
+}
+
+/// License of a contract, from a `;; SPDX-License-Identifier: <id>` comment
+pub fn get_license(source: &str) -> Option<String> {
+    source
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| line.starts_with(";;"))
+        .find_map(|line| {
+            let (_, license) = line.split_once("SPDX-License-Identifier:")?;
+            let license = license.trim();
+            (!license.is_empty()).then(|| license.to_string())
+        })
+}
+
+pub fn get_requirement_report(
+    contract_id: &str,
+    source: Option<String>,
+    metadata: Option<ContractMetadata>,
+) -> RequirementReport {
+    let mut warnings = vec![];
+    let source_hash = source.as_deref().map(get_source_hash);
+    match (&source_hash, &metadata) {
+        (None, _) => warnings.push("not found in the requirements cache".to_string()),
+        (Some(_), None) => warnings.push("no metadata in the requirements cache".to_string()),
+        (Some(hash), Some(metadata)) => {
+            if metadata
+                .source_hash
+                .as_ref()
+                .is_some_and(|recorded| recorded != hash)
+            {
+                warnings.push("the cached source doesn't match its recorded hash".to_string());
+            }
+            if metadata.provenance.is_none() {
+                warnings.push(format!(
+                    "fetched without provenance, run 'clarinet cache clean --contract {}' to fetch it again",
+                    contract_id
+                ));
+            }
+        }
+    }
+    RequirementReport {
+        contract_id: contract_id.to_string(),
+        license: source.as_deref().and_then(get_license),
+        source_hash,
+        provenance: metadata.and_then(|metadata| metadata.provenance),
+        warnings,
+    }
+}
+
+/// Reports of the requirements, read from the cache
+pub fn get_requirements_report(
+    cache_location: &FileLocation,
+    contract_ids: &[String],
+) -> Result<Vec<RequirementReport>, String> {
+    let mut requirements_location = cache_location.clone();
+    requirements_location.append_path("requirements")?;
+    let read_file = |file_name: String| -> Result<Option<String>, String> {
+        let mut location = requirements_location.clone();
+        location.append_path(&file_name)?;
+        Ok(fs::read_to_string(location.to_string()).ok())
+    };
+
+    let mut reports = vec![];
+    for contract_id in contract_ids {
+        let source = read_file(format!("{}.clar", contract_id))?;
+        let metadata = read_file(format!("{}.json", contract_id))?
+            .and_then(|metadata| serde_json::from_str::<ContractMetadata>(&metadata).ok());
+        reports.push(get_requirement_report(contract_id, source, metadata));
+    }
+    Ok(reports)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_requirement_report() {
+        let source = ";; nft trait\n;; SPDX-License-Identifier: MIT\n(define-trait nft-trait ())";
+        assert_eq!(get_license(source), Some("MIT".to_string()));
+        assert_eq!(get_license("(define-trait nft-trait ())"), None);
+        assert_eq!(
+            get_license("(define-constant SPDX-License-Identifier: u1)"),
+            None
+        );
+
+        let contract_id = "SP2PABAF9FTAJYNFZH93XENAJ8FVY99RRM50D2JG9.nft-trait";
+        let provenance = RequirementProvenance {
+            deployer: "SP2PABAF9FTAJYNFZH93XENAJ8FVY99RRM50D2JG9".to_string(),
+            publish_height: 3,
+            txid: None,
+            fetched_from: "https://api.hiro.so/v2/contracts/source/SP2PABAF9FTAJYNFZH93XENAJ8FVY99RRM50D2JG9/nft-trait?proof=0".to_string(),
+        };
+        let metadata = ContractMetadata {
+            source_hash: Some(get_source_hash(source)),
+            provenance: Some(provenance.clone()),
+            ..Default::default()
+        };
+        let report = get_requirement_report(
+            contract_id,
+            Some(source.to_string()),
+            Some(metadata.clone()),
+        );
+        assert!(report.warnings.is_empty());
+        assert_eq!(report.provenance, Some(provenance));
+        assert_eq!(report.license, Some("MIT".to_string()));
+
+        let report =
+            get_requirement_report(contract_id, Some("(ok u1)".to_string()), Some(metadata));
+        assert_eq!(
+            report.warnings,
+            vec!["the cached source doesn't match its recorded hash"]
+        );
+
+        let report = get_requirement_report(
+            contract_id,
+            Some(source.to_string()),
+            Some(ContractMetadata::default()),
+        );
+        assert_eq!(report.warnings.len(), 1);
+        assert_eq!(report.provenance, None);
+
+        let report = get_requirement_report(contract_id, None, None);
+        assert_eq!(report.warnings, vec!["not found in the requirements cache"]);
+    }
+}
@@ -0,0 +1,199 @@
+//! Redeployment of a modified contract on a running devnet (`clarinet devnet redeploy`). A
+//! published contract can't be replaced, the new version is published under a versioned name
+//! (`counter-v2`, `counter-v3`, ...) and the frontends find the current version of each
+//! contract in `deployments/devnet-aliases.json`.
+
+use std::collections::BTreeMap;
+
+use clarinet_files::MigrationCallConfig;
+use clarity_repl::clarity::vm::types::QualifiedContractIdentifier;
+use clarity_repl::clarity::{ClarityName, ContractName};
+
+use crate::types::{
+    ContractCallSpecification, DeploymentSpecification, TransactionPlanSpecification,
+    TransactionSpecification, TransactionsBatchSpecification,
+};
+
+pub const DEVNET_ALIASES_FILE: &str = "devnet-aliases.json";
+
+const MIGRATION_CALL_COST: u64 = 10_000;
+
+/// Current version of a contract, as written in `devnet-aliases.json`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContractAlias {
+    pub contract_id: String,
+    pub version: u32,
+}
+
+/// Aliases keyed by the name of the contracts in Clarinet.toml
+pub type ContractAliases = BTreeMap<String, ContractAlias>;
+
+pub fn get_versioned_name(contract_name: &str, version: u32) -> String {
+    format!("{}-v{}", contract_name, version)
+}
+
+/// Plan publishing `version` of `contract_name`, with the source, deployer and epoch of its
+/// publish transaction in `deployment` (the default devnet plan), then calling its migration
+/// function. `{previous}` is replaced by `previous_contract_id` in the arguments of the call.
+pub fn get_redeploy_deployment(
+    deployment: &DeploymentSpecification,
+    contract_name: &str,
+    version: u32,
+    previous_contract_id: &str,
+    migration: Option<&MigrationCallConfig>,
+) -> Result<DeploymentSpecification, String> {
+    let (epoch, publish) = deployment
+        .plan
+        .batches
+        .iter()
+        .find_map(|batch| {
+            batch.transactions.iter().find_map(|tx| match tx {
+                TransactionSpecification::ContractPublish(spec)
+                    if spec.contract_name.as_str() == contract_name =>
+                {
+                    Some((batch.epoch, spec))
+                }
+                _ => None,
+            })
+        })
+        .ok_or(format!(
+            "{} is not published by the devnet deployment plan",
+            contract_name
+        ))?;
+
+    let versioned_name = get_versioned_name(contract_name, version);
+    let mut publish = publish.clone();
+    publish.contract_name = ContractName::try_from(versioned_name.clone())
+        .map_err(|_| format!("{} is not a valid contract name", versioned_name))?;
+    let contract_id = QualifiedContractIdentifier::new(
+        publish.expected_sender.clone(),
+        publish.contract_name.clone(),
+    );
+
+    let mut batches = vec![TransactionsBatchSpecification {
+        id: 0,
+        transactions: vec![TransactionSpecification::ContractPublish(publish.clone())],
+        epoch,
+        post_checks: vec![],
+        description: None,
+    }];
+    if let Some(migration) = migration {
+        let method = ClarityName::try_from(migration.function.clone())
+            .map_err(|_| format!("{} is not a valid function name", migration.function))?;
+        let parameters = migration
+            .args
+            .iter()
+            .map(|arg| arg.replace("{previous}", &format!("'{}", previous_contract_id)))
+            .collect();
+        batches.push(TransactionsBatchSpecification {
+            id: 1,
+            transactions: vec![TransactionSpecification::ContractCall(
+                ContractCallSpecification {
+                    contract_id: contract_id.clone(),
+                    expected_sender: publish.expected_sender.clone(),
+                    method,
+                    parameters,
+                    cost: MIGRATION_CALL_COST,
+                    anchor_mode: publish.anchor_mode,
+                    description: None,
+                },
+            )],
+            epoch,
+            post_checks: vec![],
+            description: None,
+        });
+    }
+
+    Ok(DeploymentSpecification {
+        id: 0,
+        name: format!("Redeploy {}", versioned_name),
+        network: deployment.network.clone(),
+        stacks_node: deployment.stacks_node.clone(),
+        bitcoin_node: deployment.bitcoin_node.clone(),
+        genesis: None,
+        plan: TransactionPlanSpecification {
+            batches,
+            post_checks: vec![],
+        },
+        contracts: BTreeMap::from([(contract_id, (publish.source, publish.location))]),
+        description: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use clarinet_files::{FileLocation, StacksNetwork};
+    use clarity_repl::clarity::ClarityVersion;
+
+    use super::*;
+    use crate::types::{AnchorMode, ContractPublishSpecification, EpochSpec};
+
+    #[test]
+    fn test_get_redeploy_deployment() {
+        let contract_id =
+            QualifiedContractIdentifier::parse("ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM.counter")
+                .unwrap();
+        let mut deployment = DeploymentSpecification::empty(&StacksNetwork::Devnet);
+        deployment
+            .plan
+            .batches
+            .push(TransactionsBatchSpecification {
+                id: 0,
+                transactions: vec![TransactionSpecification::ContractPublish(
+                    ContractPublishSpecification {
+                        contract_name: contract_id.name.clone(),
+                        expected_sender: contract_id.issuer.clone(),
+                        location: FileLocation::from_path_string("/contracts/counter.clar")
+                            .unwrap(),
+                        source: "(define-data-var count uint u0)".to_string(),
+                        clarity_version: ClarityVersion::Clarity2,
+                        cost: 20_000,
+                        anchor_mode: AnchorMode::OnChainOnly,
+                        description: None,
+                    },
+                )],
+                epoch: Some(EpochSpec::Epoch2_5),
+                post_checks: vec![],
+                description: None,
+            });
+
+        let migration = MigrationCallConfig {
+            function: "migrate".to_string(),
+            args: vec!["{previous}".to_string(), "u1".to_string()],
+        };
+        let redeploy = get_redeploy_deployment(
+            &deployment,
+            "counter",
+            3,
+            "ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM.counter-v2",
+            Some(&migration),
+        )
+        .unwrap();
+        assert_eq!(redeploy.plan.batches.len(), 2);
+        let TransactionSpecification::ContractPublish(publish) =
+            &redeploy.plan.batches[0].transactions[0]
+        else {
+            panic!("expected a contract publish");
+        };
+        assert_eq!(publish.contract_name.as_str(), "counter-v3");
+        assert_eq!(redeploy.plan.batches[0].epoch, Some(EpochSpec::Epoch2_5));
+        let TransactionSpecification::ContractCall(call) =
+            &redeploy.plan.batches[1].transactions[0]
+        else {
+            panic!("expected a contract call");
+        };
+        assert_eq!(
+            call.contract_id.to_string(),
+            "ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM.counter-v3"
+        );
+        assert_eq!(
+            call.parameters,
+            vec![
+                "'ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM.counter-v2",
+                "u1"
+            ]
+        );
+
+        assert!(get_redeploy_deployment(&deployment, "token", 2, "", None).is_err());
+    }
+}
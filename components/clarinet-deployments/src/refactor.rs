@@ -0,0 +1,99 @@
+//! Replacement of a principal across a project (`clarinet refactor replace-principal`), e.g.
+//! the testnet deployer by the mainnet one before a launch. The literal principals of the
+//! contracts are rewritten from their AST, the manifests and the deployment plans, which only
+//! mention principals as values, are rewritten as text.
+
+use std::collections::BTreeMap;
+
+use clarity_repl::clarity::vm::types::{PrincipalData, QualifiedContractIdentifier};
+
+use crate::principal_remap::{rewrite_literal_principals, PrincipalRewrite};
+
+/// Rewrites the literal principals issued by `from`. The comments and strings mentioning
+/// `from` are kept, the count of these mentions is returned with the rewrites.
+pub fn replace_principal_in_contract(
+    source: &str,
+    from: &str,
+    to: &str,
+) -> Result<(String, Vec<PrincipalRewrite>, usize), String> {
+    let from_principal = PrincipalData::parse_standard_principal(from)
+        .map_err(|_| format!("invalid principal {}", from))?;
+    let to_principal = PrincipalData::parse_standard_principal(to)
+        .map_err(|_| format!("invalid principal {}", to))?;
+    let mut remap_principals = BTreeMap::new();
+    remap_principals.insert(from_principal, to_principal);
+    let (rewritten, rewrites) = rewrite_literal_principals(
+        &QualifiedContractIdentifier::transient(),
+        source,
+        &remap_principals,
+    );
+    let (_, mentions) = replace_principal_in_text(&rewritten, from, to);
+    Ok((rewritten, rewrites, mentions))
+}
+
+/// Replaces the occurrences of the address `from` which are not part of a longer word, so
+/// that an address prefixing another one is left untouched. Returns the count of replacements.
+pub fn replace_principal_in_text(text: &str, from: &str, to: &str) -> (String, usize) {
+    let is_word_char = |c: char| c.is_ascii_alphanumeric();
+    let mut rewritten = String::with_capacity(text.len());
+    let mut count = 0;
+    let mut last = 0;
+    for (index, _) in text.match_indices(from) {
+        let end = index + from.len();
+        let preceded = text[..index].chars().next_back().is_some_and(is_word_char);
+        let followed = text[end..].chars().next().is_some_and(is_word_char);
+        if preceded || followed {
+            continue;
+        }
+        rewritten.push_str(&text[last..index]);
+        rewritten.push_str(to);
+        last = end;
+        count += 1;
+    }
+    rewritten.push_str(&text[last..]);
+    (rewritten, count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TESTNET: &str = "ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM";
+    const MAINNET: &str = "SP3FBR2AGK5H9QBDH3EEN6DF8EK8JY7RX8QJ5SVTE";
+
+    #[test]
+    fn test_replace_principal() {
+        let source = format!(
+            ";; deployed by {t}\n(define-constant owner '{t})\n(define-read-only (get-token) '{t}.token)\n",
+            t = TESTNET
+        );
+        let (rewritten, rewrites, mentions) =
+            replace_principal_in_contract(&source, TESTNET, MAINNET).unwrap();
+        assert_eq!(
+            rewritten,
+            format!(
+                ";; deployed by {t}\n(define-constant owner '{m})\n(define-read-only (get-token) '{m}.token)\n",
+                t = TESTNET,
+                m = MAINNET
+            )
+        );
+        assert_eq!(rewrites.len(), 2);
+        assert_eq!(mentions, 1);
+        assert!(replace_principal_in_contract(&source, "ST1", MAINNET).is_err());
+
+        let plan = format!(
+            "expected-sender: {t}\ncontract-id: {t}.token\nother: {t}X\n",
+            t = TESTNET
+        );
+        let (rewritten, count) = replace_principal_in_text(&plan, TESTNET, MAINNET);
+        assert_eq!(count, 2);
+        assert_eq!(
+            rewritten,
+            format!(
+                "expected-sender: {m}\ncontract-id: {m}.token\nother: {t}X\n",
+                t = TESTNET,
+                m = MAINNET
+            )
+        );
+    }
+}
@@ -0,0 +1,274 @@
+//! Why a requirement ends up in a deployment plan (`clarinet requirements explain`): the
+//! chains of dependencies from the contracts of the project down to the requirement, with
+//! the places where each contract of a chain refers to the next one, and the batch of the
+//! plan publishing the requirement.
+
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+
+use clarinet_files::ProjectManifest;
+use clarity_repl::analysis::ast_dependency_detector::DependencySet;
+use clarity_repl::clarity::vm::types::{PrincipalData, QualifiedContractIdentifier, Value};
+use clarity_repl::clarity::vm::{SymbolicExpression, SymbolicExpressionType};
+use clarity_repl::repl::EpochSpec;
+
+use crate::types::{
+    DeploymentGenerationArtifacts, DeploymentSpecification, TransactionSpecification,
+};
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CallSite {
+    pub line: u32,
+    pub column: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DependencyLink {
+    pub contract_id: String,
+    /// References to the next contract of the chain, empty for the requirement
+    pub call_sites: Vec<CallSite>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RequirementPublish {
+    pub batch_id: usize,
+    pub epoch: Option<EpochSpec>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RequirementExplanation {
+    pub contract_id: String,
+    /// Listed in the requirements of Clarinet.toml
+    pub declared: bool,
+    /// Shortest chain from each project contract depending on the requirement, through
+    /// requirements only
+    pub chains: Vec<Vec<DependencyLink>>,
+    pub publish: Option<RequirementPublish>,
+}
+
+fn collect_call_sites(
+    expression: &SymbolicExpression,
+    target: &QualifiedContractIdentifier,
+    call_sites: &mut Vec<CallSite>,
+) {
+    let contract_id = match &expression.expr {
+        SymbolicExpressionType::LiteralValue(Value::Principal(PrincipalData::Contract(id))) => {
+            Some(id)
+        }
+        SymbolicExpressionType::LiteralValue(Value::CallableContract(callable)) => {
+            Some(&callable.contract_identifier)
+        }
+        SymbolicExpressionType::Field(trait_id) => Some(&trait_id.contract_identifier),
+        SymbolicExpressionType::List(list) => {
+            for expression in list.iter() {
+                collect_call_sites(expression, target, call_sites);
+            }
+            None
+        }
+        _ => None,
+    };
+    if contract_id == Some(target) {
+        call_sites.push(CallSite {
+            line: expression.span.start_line,
+            column: expression.span.start_column,
+        });
+    }
+}
+
+/// References to `target`: `contract-call?`, traits and contract principals
+pub fn find_call_sites(
+    expressions: &[SymbolicExpression],
+    target: &QualifiedContractIdentifier,
+) -> Vec<CallSite> {
+    let mut call_sites = vec![];
+    for expression in expressions {
+        collect_call_sites(expression, target, &mut call_sites);
+    }
+    call_sites
+}
+
+/// Shortest chain from each root to `target`, the chains going through another root are
+/// left out (the chain of that root explains them)
+pub fn get_dependency_chains(
+    roots: &BTreeSet<QualifiedContractIdentifier>,
+    dependencies: &BTreeMap<QualifiedContractIdentifier, DependencySet>,
+    target: &QualifiedContractIdentifier,
+) -> Vec<Vec<QualifiedContractIdentifier>> {
+    let mut chains = vec![];
+    for root in roots.iter().filter(|root| *root != target) {
+        let mut previous: BTreeMap<&QualifiedContractIdentifier, &QualifiedContractIdentifier> =
+            BTreeMap::new();
+        let mut queue = VecDeque::from([root]);
+        while let Some(contract_id) = queue.pop_front() {
+            if contract_id == target {
+                break;
+            }
+            let Some(deps) = dependencies.get(contract_id) else {
+                continue;
+            };
+            for dep in deps.set.iter() {
+                let dep_id = &dep.contract_id;
+                if dep_id == root || previous.contains_key(dep_id) {
+                    continue;
+                }
+                if dep_id != target && roots.contains(dep_id) {
+                    continue;
+                }
+                previous.insert(dep_id, contract_id);
+                queue.push_back(dep_id);
+            }
+        }
+        if !previous.contains_key(target) {
+            continue;
+        }
+        let mut chain = vec![target.clone()];
+        let mut contract_id = target;
+        while let Some(dependent) = previous.get(contract_id) {
+            chain.push((*dependent).clone());
+            contract_id = *dependent;
+        }
+        chain.reverse();
+        chains.push(chain);
+    }
+    chains
+}
+
+fn get_requirement_publish(
+    deployment: &DeploymentSpecification,
+    contract_id: &QualifiedContractIdentifier,
+) -> Option<RequirementPublish> {
+    deployment.plan.batches.iter().find_map(|batch| {
+        let published = batch.transactions.iter().any(|tx| match tx {
+            TransactionSpecification::EmulatedContractPublish(spec) => {
+                spec.emulated_sender == contract_id.issuer && spec.contract_name == contract_id.name
+            }
+            TransactionSpecification::RequirementPublish(spec) => &spec.contract_id == contract_id,
+            _ => false,
+        });
+        published.then_some(RequirementPublish {
+            batch_id: batch.id,
+            epoch: batch.epoch,
+        })
+    })
+}
+
+pub fn explain_requirement(
+    manifest: &ProjectManifest,
+    deployment: &DeploymentSpecification,
+    artifacts: &DeploymentGenerationArtifacts,
+    contract_id: &QualifiedContractIdentifier,
+) -> Result<RequirementExplanation, String> {
+    if !deployment.contracts.contains_key(contract_id) {
+        return Err(format!(
+            "{} is not part of the deployment plan",
+            contract_id
+        ));
+    }
+    let declared = manifest
+        .project
+        .requirements
+        .iter()
+        .flatten()
+        .any(|requirement| requirement.contract_id == contract_id.to_string());
+    let roots: BTreeSet<QualifiedContractIdentifier> = deployment
+        .contracts
+        .keys()
+        .filter(|id| manifest.contracts.contains_key(id.name.as_str()))
+        .cloned()
+        .collect();
+
+    let chains = get_dependency_chains(&roots, &artifacts.deps, contract_id)
+        .into_iter()
+        .map(|chain| {
+            let mut links: Vec<DependencyLink> = chain
+                .windows(2)
+                .map(|pair| DependencyLink {
+                    contract_id: pair[0].to_string(),
+                    call_sites: artifacts
+                        .asts
+                        .get(&pair[0])
+                        .map(|ast| find_call_sites(&ast.expressions, &pair[1]))
+                        .unwrap_or_default(),
+                })
+                .collect();
+            links.push(DependencyLink {
+                contract_id: contract_id.to_string(),
+                call_sites: vec![],
+            });
+            links
+        })
+        .collect();
+
+    Ok(RequirementExplanation {
+        contract_id: contract_id.to_string(),
+        declared,
+        chains,
+        publish: get_requirement_publish(deployment, contract_id),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clarity_repl::clarity::ast::{build_ast_with_rules, ASTRules};
+    use clarity_repl::clarity::vm::ClarityVersion;
+    use clarity_repl::clarity::StacksEpochId;
+
+    fn contract_id(id: &str) -> QualifiedContractIdentifier {
+        QualifiedContractIdentifier::parse(id).unwrap()
+    }
+
+    #[test]
+    fn test_explain_requirement() {
+        let market = contract_id("ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM.market");
+        let router = contract_id("ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM.router");
+        let pool = contract_id("SP3K8BC0PPEVCV7NZ6QSRWPQ2JE9E5B6N3PA0KBR9.amm-swap-pool");
+        let trait_id = contract_id("SP2PABAF9FTAJYNFZH93XENAJ8FVY99RRM50D2JG9.nft-trait");
+
+        let mut dependencies = BTreeMap::new();
+        let mut deps = DependencySet::new();
+        deps.add_dependency(router.clone(), true);
+        dependencies.insert(market.clone(), deps);
+        let mut deps = DependencySet::new();
+        deps.add_dependency(pool.clone(), true);
+        dependencies.insert(router.clone(), deps);
+        let mut deps = DependencySet::new();
+        deps.add_dependency(trait_id.clone(), true);
+        dependencies.insert(pool.clone(), deps);
+
+        let roots = BTreeSet::from([market.clone(), router.clone()]);
+        assert_eq!(
+            get_dependency_chains(&roots, &dependencies, &trait_id),
+            vec![vec![router.clone(), pool.clone(), trait_id.clone()]]
+        );
+        let roots = BTreeSet::from([market.clone()]);
+        assert_eq!(
+            get_dependency_chains(&roots, &dependencies, &pool),
+            vec![vec![market, router, pool.clone()]]
+        );
+
+        let source = [
+            "(use-trait nft-trait 'SP2PABAF9FTAJYNFZH93XENAJ8FVY99RRM50D2JG9.nft-trait.nft-trait)",
+            "(define-public (swap)",
+            "  (contract-call? 'SP3K8BC0PPEVCV7NZ6QSRWPQ2JE9E5B6N3PA0KBR9.amm-swap-pool get-pool))",
+        ]
+        .join("\n");
+        let expressions = build_ast_with_rules(
+            &pool,
+            &source,
+            &mut (),
+            ClarityVersion::Clarity2,
+            StacksEpochId::Epoch25,
+            ASTRules::Typical,
+        )
+        .unwrap()
+        .expressions;
+        assert_eq!(
+            find_call_sites(&expressions, &pool),
+            vec![CallSite {
+                line: 3,
+                column: 19
+            }]
+        );
+        assert_eq!(find_call_sites(&expressions, &trait_id).len(), 1);
+    }
+}
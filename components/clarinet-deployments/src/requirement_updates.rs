@@ -0,0 +1,209 @@
+//! Newer versions of the requirements (`clarinet requirements outdated`). A published contract
+//! can't be upgraded, the protocols publish their new versions under a versioned name
+//! (`amm-pool-v2-01`, `sbtc-token-v2`, ...) from the same deployer: the successors named after
+//! this convention are looked up on chain, and the interface of the latest one is compared to
+//! the interface of the required contract.
+
+use std::collections::BTreeMap;
+
+use clarity_repl::clarity::ast::build_ast_with_diagnostics;
+use clarity_repl::clarity::vm::types::QualifiedContractIdentifier;
+use clarity_repl::clarity::{ClarityVersion, ContractName};
+use clarity_repl::repl::DEFAULT_EPOCH;
+
+use crate::requirements::fetch_published_contract;
+use crate::upgrade_impact::{
+    compare_functions_signatures, get_functions_signatures, FunctionChange, FunctionSignature,
+};
+
+/// Successors followed from a requirement, to bound the number of requests
+const MAX_SUCCESSORS: usize = 10;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct InterfaceDiff {
+    /// Public and read-only functions only defined by the successor
+    pub added_functions: Vec<String>,
+    pub changes: Vec<FunctionChange>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RequirementUpdate {
+    pub contract_id: String,
+    /// Successors found on chain, from the oldest to the latest
+    pub candidates: Vec<String>,
+    /// Interface of the latest successor compared to the required contract
+    pub diff: Option<InterfaceDiff>,
+}
+
+impl RequirementUpdate {
+    pub fn latest(&self) -> Option<&String> {
+        self.candidates.last()
+    }
+}
+
+/// Names of the next versions of a contract, the next patch level first: `token-v1-2` is
+/// followed by `token-v1-3`, `token-v2-0` and `token-v2`, an unversioned `token` by
+/// `token-v2`. The zero padding of the levels is kept (`pool-v2-01` is followed by
+/// `pool-v2-02`).
+pub fn get_successor_names(contract_name: &str) -> Vec<String> {
+    let levels = contract_name.rsplit_once("-v").and_then(|(base, version)| {
+        let levels: Option<Vec<(u32, usize)>> = version
+            .split('-')
+            .map(|level| {
+                if level.is_empty() || !level.chars().all(|c| c.is_ascii_digit()) {
+                    return None;
+                }
+                level
+                    .parse::<u32>()
+                    .ok()
+                    .map(|number| (number, level.len()))
+            })
+            .collect();
+        levels.map(|levels| (base, levels))
+    });
+    let Some((base, levels)) = levels else {
+        return vec![format!("{}-v2", contract_name)];
+    };
+
+    let format_version = |levels: &[(u32, usize)]| {
+        levels
+            .iter()
+            .map(|(number, width)| format!("{:0width$}", number, width = width))
+            .collect::<Vec<String>>()
+            .join("-")
+    };
+    let mut names = vec![];
+    for depth in (0..levels.len()).rev() {
+        let mut next = levels[..=depth].to_vec();
+        next[depth].0 += 1;
+        let mut padded = next.clone();
+        padded.extend(levels[depth + 1..].iter().map(|(_, width)| (0, *width)));
+        names.push(format!("{}-v{}", base, format_version(&padded)));
+        if depth == 0 && levels.len() > 1 {
+            names.push(format!("{}-v{}", base, format_version(&next)));
+        }
+    }
+    names
+}
+
+fn parse_functions(
+    contract_id: &QualifiedContractIdentifier,
+    source: &str,
+    clarity_version: ClarityVersion,
+) -> Result<BTreeMap<String, FunctionSignature>, String> {
+    let (ast, _, success) =
+        build_ast_with_diagnostics(contract_id, source, &mut (), clarity_version, DEFAULT_EPOCH);
+    if !success {
+        return Err(format!("unable to parse {}", contract_id));
+    }
+    Ok(get_functions_signatures(&ast.expressions))
+}
+
+/// Compares the public and read-only functions of a requirement and of its successor
+pub fn compare_interfaces(
+    (contract_id, source, clarity_version): (&QualifiedContractIdentifier, &str, ClarityVersion),
+    (successor_id, successor_source, successor_version): (
+        &QualifiedContractIdentifier,
+        &str,
+        ClarityVersion,
+    ),
+) -> Result<InterfaceDiff, String> {
+    let previous = parse_functions(contract_id, source, clarity_version)?;
+    let current = parse_functions(successor_id, successor_source, successor_version)?;
+    let added_functions = current
+        .keys()
+        .filter(|name| !previous.contains_key(*name))
+        .cloned()
+        .collect();
+    Ok(InterfaceDiff {
+        added_functions,
+        changes: compare_functions_signatures(&previous, &current),
+    })
+}
+
+/// Looks up the successors of a requirement, `source` and `clarity_version` being the ones of
+/// the required contract
+pub async fn find_requirement_update(
+    contract_id: &QualifiedContractIdentifier,
+    source: &str,
+    clarity_version: ClarityVersion,
+) -> Result<RequirementUpdate, String> {
+    let mut candidates = vec![];
+    let mut latest = None;
+    let mut name = contract_id.name.to_string();
+    for _ in 0..MAX_SUCCESSORS {
+        let mut successor = None;
+        for successor_name in get_successor_names(&name) {
+            let Ok(successor_name) = ContractName::try_from(successor_name) else {
+                continue;
+            };
+            let successor_id =
+                QualifiedContractIdentifier::new(contract_id.issuer.clone(), successor_name);
+            if let Some(published) = fetch_published_contract(&successor_id).await? {
+                successor = Some((successor_id, published));
+                break;
+            }
+        }
+        let Some((successor_id, published)) = successor else {
+            break;
+        };
+        name = successor_id.name.to_string();
+        candidates.push(successor_id.to_string());
+        latest = Some((successor_id, published));
+    }
+
+    let diff = match latest {
+        Some((successor_id, (successor_source, successor_version))) => Some(compare_interfaces(
+            (contract_id, source, clarity_version),
+            (&successor_id, &successor_source, successor_version),
+        )?),
+        None => None,
+    };
+    Ok(RequirementUpdate {
+        contract_id: contract_id.to_string(),
+        candidates,
+        diff,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_successor_names() {
+        assert_eq!(get_successor_names("sbtc-token"), vec!["sbtc-token-v2"]);
+        assert_eq!(get_successor_names("counter-v2"), vec!["counter-v3"]);
+        assert_eq!(
+            get_successor_names("amm-pool-v2-01"),
+            vec!["amm-pool-v2-02", "amm-pool-v3-00", "amm-pool-v3"]
+        );
+        assert_eq!(
+            get_successor_names("token-v1-2-3"),
+            vec!["token-v1-2-4", "token-v1-3-0", "token-v2-0-0", "token-v2"]
+        );
+        // a suffix which isn't a version is part of the name
+        assert_eq!(get_successor_names("vault-vx"), vec!["vault-vx-v2"]);
+
+        let contract_id = QualifiedContractIdentifier::transient();
+        let diff = compare_interfaces(
+            (
+                &contract_id,
+                "(define-public (swap (amount uint)) (ok amount))
+                (define-read-only (get-fee) u30)",
+                ClarityVersion::Clarity2,
+            ),
+            (
+                &contract_id,
+                "(define-public (swap (amount uint) (min-out uint)) (ok amount))
+                (define-read-only (get-fee) u30)
+                (define-read-only (get-reserves) (ok u0))",
+                ClarityVersion::Clarity3,
+            ),
+        )
+        .unwrap();
+        assert_eq!(diff.added_functions, vec!["get-reserves"]);
+        assert_eq!(diff.changes.len(), 1);
+        assert_eq!(diff.changes[0].function(), "swap");
+    }
+}
@@ -1,17 +1,36 @@
+use crate::mocks::{generate_mock_contract, ContractInterfaceFile};
 use clarinet_files::{FileAccessor, FileLocation};
 use clarity_repl::{
     clarity::{
-        chainstate::StacksAddress, vm::types::QualifiedContractIdentifier, Address, ClarityVersion,
-        StacksEpochId,
+        chainstate::StacksAddress, util::hash::Sha256Sum, vm::types::QualifiedContractIdentifier,
+        Address, ClarityVersion, StacksEpochId,
     },
     repl::{DEFAULT_CLARITY_VERSION, DEFAULT_EPOCH},
 };
-use reqwest;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ContractMetadata {
     pub epoch: StacksEpochId,
     pub clarity_version: ClarityVersion,
+    /// Hash of the cached source, absent from the caches written by older versions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_hash: Option<String>,
+    /// Where and when the requirement was published, absent from the caches written by older
+    /// versions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<RequirementProvenance>,
+}
+
+/// On-chain origin of a requirement, recorded when its source is fetched
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RequirementProvenance {
+    pub deployer: String,
+    pub publish_height: u32,
+    /// Id of the transaction publishing the contract, when the API serving the source knows it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub txid: Option<String>,
+    /// Endpoint the source was fetched from
+    pub fetched_from: String,
 }
 
 impl Default for ContractMetadata {
@@ -19,10 +38,16 @@ impl Default for ContractMetadata {
         ContractMetadata {
             epoch: DEFAULT_EPOCH,
             clarity_version: DEFAULT_CLARITY_VERSION,
+            source_hash: None,
+            provenance: None,
         }
     }
 }
 
+pub fn get_source_hash(source: &str) -> String {
+    Sha256Sum::from_data(source.as_bytes()).to_hex()
+}
+
 pub async fn retrieve_contract(
     contract_id: &QualifiedContractIdentifier,
     cache_location: &FileLocation,
@@ -48,26 +73,26 @@ pub async fn retrieve_contract(
         ),
     };
 
+    // A corrupted cache entry (unparsable metadata, or a source not matching its recorded
+    // hash) is fetched again and overwritten
     if let (Ok(contract_source), Ok(metadata_json)) = (contract_source, metadata_json) {
-        let metadata: ContractMetadata = serde_json::from_str(&metadata_json)
-            .map_err(|e| format!("Unable to parse metadata file: {}", e))?;
-
-        return Ok((
-            contract_source,
-            metadata.epoch,
-            metadata.clarity_version,
-            contract_location,
-        ));
+        if let Ok(metadata) = serde_json::from_str::<ContractMetadata>(&metadata_json) {
+            let is_intact = metadata
+                .source_hash
+                .as_ref()
+                .map_or(true, |hash| hash == &get_source_hash(&contract_source));
+            if is_intact {
+                return Ok((
+                    contract_source,
+                    metadata.epoch,
+                    metadata.clarity_version,
+                    contract_location,
+                ));
+            }
+        }
     }
 
-    let is_mainnet = StacksAddress::from_string(&contract_deployer)
-        .unwrap()
-        .is_mainnet();
-    let stacks_node_addr = if is_mainnet {
-        "https://api.hiro.so".to_string()
-    } else {
-        "https://api.testnet.hiro.so".to_string()
-    };
+    let (is_mainnet, stacks_node_addr) = get_api_host(&contract_deployer);
 
     let request_url = format!(
         "{host}/v2/contracts/source/{addr}/{name}?proof=0",
@@ -76,44 +101,40 @@ pub async fn retrieve_contract(
         name = contract_name
     );
 
-    let contract = fetch_contract(request_url).await?;
+    let contract = fetch_contract(&request_url).await?;
+    let txid = fetch_contract_txid(&format!(
+        "{host}/extended/v1/contract/{addr}.{name}",
+        host = stacks_node_addr,
+        addr = contract_deployer,
+        name = contract_name
+    ))
+    .await;
     let epoch = epoch_for_height(is_mainnet, contract.publish_height);
-    let clarity_version = match contract.clarity_version {
-        Some(1) => ClarityVersion::Clarity1,
-        Some(2) => ClarityVersion::Clarity2,
-        Some(3) => ClarityVersion::Clarity3,
-        Some(_) => {
-            return Err("unable to parse clarity_version (can either be '1' or '2'".to_string())
-        }
-        None => ClarityVersion::default_for_epoch(epoch),
-    };
+    let clarity_version = get_clarity_version(contract.clarity_version, epoch)?;
 
+    let metadata_json = serde_json::to_string_pretty(&ContractMetadata {
+        epoch,
+        clarity_version,
+        source_hash: Some(get_source_hash(&contract.source)),
+        provenance: Some(RequirementProvenance {
+            deployer: contract_deployer.clone(),
+            publish_height: contract.publish_height,
+            txid,
+            fetched_from: request_url,
+        }),
+    })
+    .unwrap();
     match file_accessor {
         None => {
             contract_location.write_content(contract.source.as_bytes())?;
-            metadata_location.write_content(
-                serde_json::to_string_pretty(&ContractMetadata {
-                    epoch,
-                    clarity_version,
-                })
-                .unwrap()
-                .as_bytes(),
-            )?;
+            metadata_location.write_content(metadata_json.as_bytes())?;
         }
         Some(file_accessor) => {
             file_accessor
                 .write_file(contract_location.to_string(), contract.source.as_bytes())
                 .await?;
             file_accessor
-                .write_file(
-                    metadata_location.to_string(),
-                    serde_json::to_string_pretty(&ContractMetadata {
-                        epoch,
-                        clarity_version,
-                    })
-                    .unwrap()
-                    .as_bytes(),
-                )
+                .write_file(metadata_location.to_string(), metadata_json.as_bytes())
                 .await?;
         }
     };
@@ -121,6 +142,141 @@ pub async fn retrieve_contract(
     Ok((contract.source, epoch, clarity_version, contract_location))
 }
 
+/// Retrieve the mock of a requirement, generated from the contract interface.
+/// The mock is written in the cache next to the original source, so that it can
+/// be edited, and is only generated if it doesn't exist.
+pub async fn retrieve_contract_mock(
+    contract_id: &QualifiedContractIdentifier,
+    cache_location: &FileLocation,
+    file_accessor: &Option<&dyn FileAccessor>,
+) -> Result<(String, FileLocation), String> {
+    let contract_deployer = contract_id.issuer.to_address();
+    let contract_name = contract_id.name.to_string();
+
+    let mut mock_location = cache_location.clone();
+    mock_location.append_path("requirements")?;
+    let mut interface_location = mock_location.clone();
+    mock_location.append_path(&format!(
+        "{}.{}.mock.clar",
+        contract_deployer, contract_name
+    ))?;
+    interface_location.append_path(&format!(
+        "{}.{}.interface.json",
+        contract_deployer, contract_name
+    ))?;
+
+    let (mock_source, interface_json) = match file_accessor {
+        None => (
+            mock_location.read_content_as_utf8(),
+            interface_location.read_content_as_utf8(),
+        ),
+        Some(file_accessor) => (
+            file_accessor.read_file(mock_location.to_string()).await,
+            file_accessor
+                .read_file(interface_location.to_string())
+                .await,
+        ),
+    };
+
+    if let Ok(mock_source) = mock_source {
+        return Ok((mock_source, mock_location));
+    }
+
+    let interface_json = match interface_json {
+        Ok(interface_json) => interface_json,
+        Err(_) => {
+            let is_mainnet = StacksAddress::from_string(&contract_deployer)
+                .unwrap()
+                .is_mainnet();
+            let stacks_node_addr = if is_mainnet {
+                "https://api.hiro.so".to_string()
+            } else {
+                "https://api.testnet.hiro.so".to_string()
+            };
+            let request_url = format!(
+                "{host}/v2/contracts/interface/{addr}/{name}",
+                host = stacks_node_addr,
+                addr = contract_deployer,
+                name = contract_name
+            );
+            let interface_json = fetch_contract_interface(request_url).await?;
+            match file_accessor {
+                None => interface_location.write_content(interface_json.as_bytes())?,
+                Some(file_accessor) => {
+                    file_accessor
+                        .write_file(interface_location.to_string(), interface_json.as_bytes())
+                        .await?
+                }
+            };
+            interface_json
+        }
+    };
+
+    let interface: ContractInterfaceFile = serde_json::from_str(&interface_json)
+        .map_err(|e| format!("Unable to parse interface of {}: {}", contract_id, e))?;
+    let mock_source = generate_mock_contract(&contract_name, &interface)?;
+
+    match file_accessor {
+        None => mock_location.write_content(mock_source.as_bytes())?,
+        Some(file_accessor) => {
+            file_accessor
+                .write_file(mock_location.to_string(), mock_source.as_bytes())
+                .await?
+        }
+    };
+
+    Ok((mock_source, mock_location))
+}
+
+/// Normalizes the formatting of a contract, so that a local fork can be compared with the
+/// on-chain source regardless of line endings, indentation and whitespace.
+/// String literals are kept untouched.
+pub fn normalize_contract_source(source: &str) -> String {
+    let mut lines: Vec<String> = vec![];
+    for line in source.lines() {
+        let line = normalize_whitespaces(line.trim());
+        if line.is_empty() && lines.last().map_or(true, |last| last.is_empty()) {
+            continue;
+        }
+        lines.push(line);
+    }
+    while lines.last().is_some_and(|last| last.is_empty()) {
+        lines.pop();
+    }
+    lines.join("\n")
+}
+
+fn normalize_whitespaces(line: &str) -> String {
+    let mut normalized = String::with_capacity(line.len());
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut previous_is_whitespace = false;
+    for c in line.chars() {
+        if in_string {
+            normalized.push(c);
+            match c {
+                '\\' if !escaped => escaped = true,
+                '"' if !escaped => in_string = false,
+                _ => escaped = false,
+            }
+            continue;
+        }
+        if c.is_whitespace() {
+            if !previous_is_whitespace {
+                normalized.push(' ');
+            }
+            previous_is_whitespace = true;
+            continue;
+        }
+        previous_is_whitespace = false;
+        if c == '"' {
+            in_string = true;
+        }
+        normalized.push(c);
+    }
+    normalized
+}
+
 pub const MAINNET_20_START_HEIGHT: u32 = 1;
 pub const MAINNET_2_05_START_HEIGHT: u32 = 40_607;
 pub const MAINNET_21_START_HEIGHT: u32 = 99_113;
@@ -201,15 +357,106 @@ struct Contract {
     clarity_version: Option<u8>,
 }
 
-async fn fetch_contract(request_url: String) -> Result<Contract, String> {
-    let response = reqwest::get(&request_url)
+/// Whether the deployer is a mainnet address, and the API serving its contracts
+fn get_api_host(contract_deployer: &str) -> (bool, String) {
+    let is_mainnet = StacksAddress::from_string(contract_deployer)
+        .unwrap()
+        .is_mainnet();
+    let stacks_node_addr = if is_mainnet {
+        "https://api.hiro.so".to_string()
+    } else {
+        "https://api.testnet.hiro.so".to_string()
+    };
+    (is_mainnet, stacks_node_addr)
+}
+
+fn get_clarity_version(
+    clarity_version: Option<u8>,
+    epoch: StacksEpochId,
+) -> Result<ClarityVersion, String> {
+    match clarity_version {
+        Some(1) => Ok(ClarityVersion::Clarity1),
+        Some(2) => Ok(ClarityVersion::Clarity2),
+        Some(3) => Ok(ClarityVersion::Clarity3),
+        Some(_) => Err("unable to parse clarity_version (can either be '1' or '2'".to_string()),
+        None => Ok(ClarityVersion::default_for_epoch(epoch)),
+    }
+}
+
+/// Source and Clarity version of a published contract, without going through the cache.
+/// `None` if no contract is published under this id.
+pub(crate) async fn fetch_published_contract(
+    contract_id: &QualifiedContractIdentifier,
+) -> Result<Option<(String, ClarityVersion)>, String> {
+    let contract_deployer = contract_id.issuer.to_address();
+    let (is_mainnet, stacks_node_addr) = get_api_host(&contract_deployer);
+    let request_url = format!(
+        "{host}/v2/contracts/source/{addr}/{name}?proof=0",
+        host = stacks_node_addr,
+        addr = contract_deployer,
+        name = contract_id.name
+    );
+    let response = match clarinet_files::net::get_text(&request_url).await {
+        Ok(response) => response,
+        Err(e) if e.contains("(404") => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    let contract: Contract = serde_json::from_str(&response)
+        .map_err(|_| format!("Unable to parse contract {}", request_url))?;
+    let epoch = epoch_for_height(is_mainnet, contract.publish_height);
+    let clarity_version = get_clarity_version(contract.clarity_version, epoch)?;
+    Ok(Some((contract.source, clarity_version)))
+}
+
+async fn fetch_contract(request_url: &str) -> Result<Contract, String> {
+    let response = clarinet_files::net::get_text(request_url)
         .await
         .map_err(|_| format!("Unable to retrieve contract {}", request_url))?;
 
-    let contract = response
-        .json()
-        .await
+    let contract = serde_json::from_str(&response)
         .map_err(|_| format!("Unable to parse contract {}", request_url))?;
 
     Ok(contract)
 }
+
+#[derive(Deserialize, Debug)]
+struct ContractTransaction {
+    tx_id: String,
+}
+
+/// The node RPC doesn't expose the publishing transaction, the API does
+async fn fetch_contract_txid(request_url: &str) -> Option<String> {
+    let response = clarinet_files::net::get_text(request_url).await.ok()?;
+    serde_json::from_str::<ContractTransaction>(&response)
+        .ok()
+        .map(|contract| contract.tx_id)
+}
+
+async fn fetch_contract_interface(request_url: String) -> Result<String, String> {
+    clarinet_files::net::get_text(&request_url).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_contract_source() {
+        let on_chain = "(define-public (transfer (amount uint))\n  (ok    amount))\n";
+        let fork = "\r\n(define-public   (transfer (amount uint))\r\n\t(ok amount))\r\n\r\n\r\n";
+        assert_eq!(
+            normalize_contract_source(on_chain),
+            normalize_contract_source(fork)
+        );
+        assert_eq!(
+            normalize_contract_source(on_chain),
+            "(define-public (transfer (amount uint))\n(ok amount))"
+        );
+
+        let source = "(define-constant ERR   \"not  \\\"authorized\\\"  \")\n\n\n(ok  u1)";
+        assert_eq!(
+            normalize_contract_source(source),
+            "(define-constant ERR \"not  \\\"authorized\\\"  \")\n\n(ok u1)"
+        );
+    }
+}
@@ -0,0 +1,574 @@
+//! Scenarios are regression tests written in YAML, for the people who don't write TypeScript
+//! or Rust. A scenario is a sequence of steps (contract calls, blocks advances, assertions on
+//! the state of the contracts) executed against the simnet deployment of the project:
+//!
+//! ```yaml
+//! name: transfer tokens
+//! steps:
+//!   - call:
+//!       contract: token
+//!       function: transfer
+//!       args: [u100, "'@deployer", "'@wallet_1", none]
+//!       as: "@deployer"
+//!       expect: (ok true)
+//!   - advance-blocks: 10
+//!   - assert-map-entry:
+//!       contract: token
+//!       map: balances
+//!       key: "'@wallet_1"
+//!       equals: (some u100)
+//! ```
+//!
+//! The principals can be referred to by name (see [`AddressBook`]), the contracts are deployed
+//! by `@deployer` unless their identifier is fully qualified.
+//!
+//! The results, events and costs of the steps are kept in the reports, for the reports of a
+//! scenario executed under two epochs (or Clarity versions) to be compared with
+//! [`diff_scenario_reports`].
+
+use clarinet_files::FileLocation;
+use clarity_repl::clarity::vm::costs::ExecutionCost;
+use clarity_repl::clarity::vm::types::{QualifiedContractIdentifier, ResponseData, Value};
+use clarity_repl::clarity::vm::{EvaluationResult, SymbolicExpression};
+use clarity_repl::repl::Session;
+use clarity_repl::utils::serialize_event;
+
+use crate::assertions::get_result_value;
+
+/// Directory of the scenarios of a project, relative to its root
+pub const SCENARIOS_DIR: &str = "scenarios";
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct CallStep {
+    pub contract: String,
+    pub function: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Defaults to `@deployer`
+    #[serde(alias = "as")]
+    pub sender: Option<String>,
+    /// `ok`, `err`, or the expected value (e.g. `(ok true)`)
+    pub expect: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct DataVarStep {
+    pub contract: String,
+    pub var: String,
+    pub equals: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct MapEntryStep {
+    pub contract: String,
+    pub map: String,
+    pub key: String,
+    /// Compared to the result of `map-get?`, `none` for a missing entry
+    pub equals: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct BalanceStep {
+    pub principal: String,
+    /// In µSTX
+    pub equals: u128,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ScenarioStep {
+    Call(CallStep),
+    AdvanceBlocks(u32),
+    AssertDataVar(DataVarStep),
+    AssertMapEntry(MapEntryStep),
+    AssertBalance(BalanceStep),
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Scenario {
+    pub name: Option<String>,
+    pub steps: Vec<ScenarioStep>,
+}
+
+impl Scenario {
+    pub fn from_file_content(content: &str) -> Result<Scenario, String> {
+        serde_yaml::from_str(content).map_err(|e| format!("unable to read scenario: {}", e))
+    }
+
+    pub fn from_location(location: &FileLocation) -> Result<Scenario, String> {
+        let content = location.read_content_as_utf8()?;
+        Scenario::from_file_content(&content)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case", tag = "status", content = "message")]
+pub enum StepOutcome {
+    Passed,
+    Failed(String),
+    /// The steps following a failure are not executed
+    Skipped,
+}
+
+/// What a step observed: the result of a call, or the value checked by an assertion
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StepExecution {
+    pub value: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub events: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cost: Option<ExecutionCost>,
+}
+
+impl StepExecution {
+    fn from_value(value: &Value) -> Self {
+        StepExecution {
+            value: value.to_string(),
+            events: vec![],
+            cost: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StepReport {
+    pub description: String,
+    #[serde(flatten)]
+    pub outcome: StepOutcome,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub execution: Option<StepExecution>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ScenarioReport {
+    pub name: String,
+    pub steps: Vec<StepReport>,
+}
+
+impl ScenarioReport {
+    pub fn success(&self) -> bool {
+        self.steps
+            .iter()
+            .all(|step| step.outcome == StepOutcome::Passed)
+    }
+}
+
+fn describe_step(step: &ScenarioStep) -> String {
+    match step {
+        ScenarioStep::Call(call) => {
+            let mut description = format!("call {}::{}", call.contract, call.function);
+            if !call.args.is_empty() {
+                description.push_str(&format!(" {}", call.args.join(" ")));
+            }
+            if let Some(ref sender) = call.sender {
+                description.push_str(&format!(" as {}", sender));
+            }
+            if let Some(ref expect) = call.expect {
+                description.push_str(&format!(" expect {}", expect));
+            }
+            description
+        }
+        ScenarioStep::AdvanceBlocks(count) => format!("advance {} blocks", count),
+        ScenarioStep::AssertDataVar(step) => {
+            format!(
+                "assert {}::{} equals {}",
+                step.contract, step.var, step.equals
+            )
+        }
+        ScenarioStep::AssertMapEntry(step) => format!(
+            "assert {}::{}[{}] equals {}",
+            step.contract, step.map, step.key, step.equals
+        ),
+        ScenarioStep::AssertBalance(step) => {
+            format!(
+                "assert balance of {} equals {}",
+                step.principal, step.equals
+            )
+        }
+    }
+}
+
+fn eval_value(session: &mut Session, expression: &str) -> Result<Value, String> {
+    let expression = session.address_book.resolve_expression(expression)?;
+    match session.eval(expression.clone(), false) {
+        Ok(result) => match result.result {
+            EvaluationResult::Snippet(snippet) => Ok(snippet.result),
+            EvaluationResult::Contract(_) => Err(format!("{} is not a value", expression)),
+        },
+        Err(diagnostics) => Err(format!(
+            "unable to evaluate {}: {}",
+            expression,
+            diagnostics
+                .first()
+                .map(|d| d.message.as_str())
+                .unwrap_or_default()
+        )),
+    }
+}
+
+fn get_contract_id(
+    session: &Session,
+    contract: &str,
+) -> Result<QualifiedContractIdentifier, String> {
+    let contract_id = if contract.starts_with('S') || contract.starts_with('@') {
+        session.address_book.resolve_contract_id(contract)?
+    } else {
+        let deployer = session.address_book.resolve_principal("@deployer")?;
+        format!("{}.{}", deployer, contract.trim_start_matches('.'))
+    };
+    QualifiedContractIdentifier::parse(&contract_id)
+        .map_err(|_| format!("invalid contract identifier: {}", contract_id))
+}
+
+fn expect_equal(actual: &Value, expected: &Value) -> Result<Option<StepExecution>, String> {
+    if actual != expected {
+        return Err(format!("expected {}, got {}", expected, actual));
+    }
+    Ok(Some(StepExecution::from_value(actual)))
+}
+
+fn decode_stored_value(value_hex: Option<String>) -> Result<Option<Value>, String> {
+    let Some(value_hex) = value_hex else {
+        return Ok(None);
+    };
+    let value_hex = value_hex.strip_prefix("0x").unwrap_or(&value_hex);
+    Value::try_deserialize_hex_untyped(value_hex)
+        .map(Some)
+        .map_err(|e| format!("unable to decode the stored value: {}", e))
+}
+
+fn run_call(session: &mut Session, call: &CallStep) -> Result<Option<StepExecution>, String> {
+    let contract_id = get_contract_id(session, &call.contract)?;
+    let sender = session
+        .address_book
+        .resolve_principal(call.sender.as_deref().unwrap_or("@deployer"))?;
+    let mut args = vec![];
+    for arg in call.args.iter() {
+        args.push(SymbolicExpression::atom_value(eval_value(session, arg)?));
+    }
+    let result = session
+        .call_contract_fn(
+            &contract_id.to_string(),
+            &call.function,
+            &args,
+            &sender,
+            false,
+            true,
+        )
+        .map_err(|diagnostics| {
+            diagnostics
+                .iter()
+                .map(|d| d.message.clone())
+                .collect::<Vec<_>>()
+                .join("\n")
+        })?;
+    let value = get_result_value(&result).ok_or("the call returned no value")?;
+    let execution = StepExecution {
+        value: value.to_string(),
+        events: result
+            .events
+            .iter()
+            .map(|event| serialize_event(event).to_string())
+            .collect(),
+        cost: result.cost.as_ref().map(|cost| cost.total.clone()),
+    };
+    match (call.expect.as_deref(), value) {
+        (None, _) => Ok(Some(execution)),
+        (
+            Some("ok"),
+            Value::Response(ResponseData {
+                committed: true, ..
+            }),
+        )
+        | (
+            Some("err"),
+            Value::Response(ResponseData {
+                committed: false, ..
+            }),
+        ) => Ok(Some(execution)),
+        (Some(expected @ ("ok" | "err")), value) => {
+            Err(format!("expected an {} response, got {}", expected, value))
+        }
+        (Some(expected), value) => {
+            let expected = eval_value(session, expected)?;
+            expect_equal(value, &expected).map(|_| Some(execution))
+        }
+    }
+}
+
+fn run_step(session: &mut Session, step: &ScenarioStep) -> Result<Option<StepExecution>, String> {
+    match step {
+        ScenarioStep::Call(call) => run_call(session, call),
+        ScenarioStep::AdvanceBlocks(count) => {
+            session.advance_chain_tip(*count);
+            Ok(None)
+        }
+        ScenarioStep::AssertDataVar(step) => {
+            let contract_id = get_contract_id(session, &step.contract)?;
+            let value_hex = session.interpreter.get_data_var(&contract_id, &step.var);
+            let actual = decode_stored_value(value_hex)?
+                .ok_or(format!("{} has no data-var {}", contract_id, step.var))?;
+            let expected = eval_value(session, &step.equals)?;
+            expect_equal(&actual, &expected)
+        }
+        ScenarioStep::AssertMapEntry(step) => {
+            let contract_id = get_contract_id(session, &step.contract)?;
+            let key = eval_value(session, &step.key)?;
+            let value_hex = session
+                .interpreter
+                .get_map_entry(&contract_id, &step.map, &key);
+            // entries are stored as optionals, like returned by `map-get?`
+            let actual = decode_stored_value(value_hex)?.unwrap_or(Value::none());
+            let expected = eval_value(session, &step.equals)?;
+            expect_equal(&actual, &expected)
+        }
+        ScenarioStep::AssertBalance(step) => {
+            let principal = session.address_book.resolve_principal(&step.principal)?;
+            let actual = eval_value(session, &format!("(stx-get-balance '{})", principal))?;
+            expect_equal(&actual, &Value::UInt(step.equals))
+        }
+    }
+}
+
+/// Executes the steps of a scenario until one of them fails. The session should be
+/// dedicated to the scenario, the scenarios are not isolated from each other otherwise.
+pub fn run_scenario(session: &mut Session, name: &str, scenario: &Scenario) -> ScenarioReport {
+    let mut steps = vec![];
+    let mut failed = false;
+    for step in scenario.steps.iter() {
+        let (outcome, execution) = if failed {
+            (StepOutcome::Skipped, None)
+        } else {
+            match run_step(session, step) {
+                Ok(execution) => (StepOutcome::Passed, execution),
+                Err(message) => {
+                    failed = true;
+                    (StepOutcome::Failed(message), None)
+                }
+            }
+        };
+        steps.push(StepReport {
+            description: describe_step(step),
+            outcome,
+            execution,
+        });
+    }
+    ScenarioReport {
+        name: scenario.name.clone().unwrap_or(name.to_string()),
+        steps,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StepDifferenceKind {
+    Outcome,
+    Value,
+    Events,
+    Cost,
+}
+
+/// A step behaving differently in two executions of a scenario
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StepDifference {
+    pub step: usize,
+    pub description: String,
+    pub kind: StepDifferenceKind,
+    pub left: String,
+    pub right: String,
+}
+
+fn describe_outcome(outcome: &StepOutcome) -> String {
+    match outcome {
+        StepOutcome::Passed => "passed".to_string(),
+        StepOutcome::Failed(message) => format!("failed: {}", message),
+        StepOutcome::Skipped => "skipped".to_string(),
+    }
+}
+
+fn describe_cost(cost: &Option<ExecutionCost>) -> String {
+    match cost {
+        Some(cost) => format!(
+            "runtime {}, read count {}, read length {}, write count {}, write length {}",
+            cost.runtime, cost.read_count, cost.read_length, cost.write_count, cost.write_length
+        ),
+        None => "unknown".to_string(),
+    }
+}
+
+/// Compares the steps of two reports of the same scenario
+pub fn diff_scenario_reports(left: &ScenarioReport, right: &ScenarioReport) -> Vec<StepDifference> {
+    let mut differences = vec![];
+    for (index, (left_step, right_step)) in left.steps.iter().zip(right.steps.iter()).enumerate() {
+        let mut push = |kind, left: String, right: String| {
+            differences.push(StepDifference {
+                step: index + 1,
+                description: left_step.description.clone(),
+                kind,
+                left,
+                right,
+            })
+        };
+        if left_step.outcome != right_step.outcome {
+            push(
+                StepDifferenceKind::Outcome,
+                describe_outcome(&left_step.outcome),
+                describe_outcome(&right_step.outcome),
+            );
+        }
+        let (Some(left_execution), Some(right_execution)) =
+            (&left_step.execution, &right_step.execution)
+        else {
+            continue;
+        };
+        if left_execution.value != right_execution.value {
+            push(
+                StepDifferenceKind::Value,
+                left_execution.value.clone(),
+                right_execution.value.clone(),
+            );
+        }
+        if left_execution.events != right_execution.events {
+            push(
+                StepDifferenceKind::Events,
+                left_execution.events.join("\n"),
+                right_execution.events.join("\n"),
+            );
+        }
+        if left_execution.cost != right_execution.cost {
+            push(
+                StepDifferenceKind::Cost,
+                describe_cost(&left_execution.cost),
+                describe_cost(&right_execution.cost),
+            );
+        }
+    }
+    differences
+}
+
+#[cfg(test)]
+mod tests {
+    use clarity_repl::repl::settings::Account;
+    use clarity_repl::repl::{
+        ClarityCodeSource, ClarityContract, ContractDeployer, SessionSettings,
+    };
+    use clarity_repl::repl::{DEFAULT_CLARITY_VERSION, DEFAULT_EPOCH};
+
+    use super::*;
+
+    const DEPLOYER: &str = "ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM";
+
+    fn get_session() -> Session {
+        let mut session = Session::new(SessionSettings {
+            initial_accounts: vec![Account {
+                address: DEPLOYER.to_string(),
+                balance: 1_000_000,
+                name: "deployer".to_string(),
+            }],
+            ..Default::default()
+        });
+        session.update_epoch(DEFAULT_EPOCH);
+        let contract = ClarityContract {
+            code_source: ClarityCodeSource::ContractInMemory(
+                r#"(define-data-var count uint u0)
+                (define-map counts principal uint)
+                (define-public (increment)
+                    (begin
+                        (var-set count (+ (var-get count) u1))
+                        (ok (map-set counts tx-sender (var-get count)))))"#
+                    .to_string(),
+            ),
+            name: "counter".to_string(),
+            deployer: ContractDeployer::Address(DEPLOYER.to_string()),
+            clarity_version: DEFAULT_CLARITY_VERSION,
+            epoch: DEFAULT_EPOCH,
+        };
+        session.deploy_contract(&contract, false, None).unwrap();
+        session
+    }
+
+    #[test]
+    fn it_runs_scenarios() {
+        let scenario = Scenario::from_file_content(
+            r#"
+            name: increment
+            steps:
+              - call:
+                  contract: counter
+                  function: increment
+                  as: "@deployer"
+                  expect: (ok true)
+              - assert-data-var:
+                  contract: counter
+                  var: count
+                  equals: u1
+              - assert-map-entry:
+                  contract: counter
+                  map: counts
+                  key: "'@deployer"
+                  equals: (some u1)
+              - advance-blocks: 10
+              - call:
+                  contract: counter
+                  function: increment
+                  expect: err
+              - assert-data-var:
+                  contract: counter
+                  var: count
+                  equals: u2
+            "#,
+        )
+        .unwrap();
+        let report = run_scenario(&mut get_session(), "increment.yaml", &scenario);
+        assert_eq!(report.name, "increment");
+        assert!(!report.success());
+        assert_eq!(report.steps[3].outcome, StepOutcome::Passed);
+        assert_eq!(
+            report.steps[4].outcome,
+            StepOutcome::Failed("expected an err response, got (ok true)".to_string())
+        );
+        assert_eq!(report.steps[5].outcome, StepOutcome::Skipped);
+    }
+
+    #[test]
+    fn it_diffs_scenario_reports() {
+        let scenario = Scenario::from_file_content(
+            r#"
+            steps:
+              - call:
+                  contract: counter
+                  function: increment
+              - assert-data-var:
+                  contract: counter
+                  var: count
+                  equals: u1
+            "#,
+        )
+        .unwrap();
+        let left = run_scenario(&mut get_session(), "increment.yaml", &scenario);
+        let mut right = left.clone();
+        assert!(diff_scenario_reports(&left, &right).is_empty());
+
+        right.steps[1].outcome = StepOutcome::Failed("expected u1, got u2".to_string());
+        right.steps[1].execution = None;
+        if let Some(ref mut execution) = right.steps[0].execution {
+            execution.value = "(ok false)".to_string();
+        }
+        let kinds: Vec<(usize, StepDifferenceKind)> = diff_scenario_reports(&left, &right)
+            .iter()
+            .map(|difference| (difference.step, difference.kind))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (1, StepDifferenceKind::Value),
+                (2, StepDifferenceKind::Outcome)
+            ]
+        );
+        assert!(left.steps[0].execution.as_ref().unwrap().cost.is_some());
+    }
+}
@@ -0,0 +1,150 @@
+//! Warm standby sessions (`<cache_dir>/session-image.json`): the state of the session once the
+//! default simnet plan is executed, boot contracts included. The image is keyed by a
+//! fingerprint of the inputs of the plan (the manifests, the on-disk plan and the contracts
+//! sources), a console opened with unchanged inputs restores it instead of executing the plan.
+
+use std::fs;
+
+use clarinet_files::{FileLocation, ProjectManifest, StacksNetwork};
+use clarity_repl::clarity::util::hash::Sha256Sum;
+use clarity_repl::repl::session_image::SessionImage;
+use clarity_repl::repl::{ClarityCodeSource, Session};
+
+use crate::{get_default_deployment_path, get_session_settings};
+
+const SESSION_IMAGE_FILE: &str = "session-image.json";
+
+/// Hash of the inputs, as `(name, content)`. A missing input is hashed as such, so that
+/// creating the file invalidates the image.
+pub fn get_fingerprint(inputs: &[(String, Option<Vec<u8>>)]) -> String {
+    let mut data = env!("CARGO_PKG_VERSION").as_bytes().to_vec();
+    for (name, content) in inputs {
+        data.extend(name.as_bytes());
+        match content {
+            Some(content) => {
+                data.extend((content.len() as u64).to_be_bytes());
+                data.extend(content);
+            }
+            None => data.push(0),
+        }
+    }
+    Sha256Sum::from_data(&data).to_hex()
+}
+
+fn get_fingerprint_inputs(
+    manifest: &ProjectManifest,
+) -> Result<Vec<(String, Option<Vec<u8>>)>, String> {
+    let mut devnet_manifest = manifest.location.get_project_root_location()?;
+    devnet_manifest.append_path("settings/Devnet.toml")?;
+    let mut locations = vec![
+        manifest.location.clone(),
+        devnet_manifest,
+        get_default_deployment_path(manifest, &StacksNetwork::Simnet)?,
+    ];
+    for contract in manifest.contracts.values() {
+        if let ClarityCodeSource::ContractOnDisk(path) = &contract.code_source {
+            locations.push(FileLocation::from_path(path.clone()));
+        }
+    }
+    Ok(locations
+        .into_iter()
+        .map(|location| {
+            let content = location
+                .exists()
+                .then(|| location.read_content().ok())
+                .flatten();
+            (location.to_string(), content)
+        })
+        .collect())
+}
+
+fn get_session_image_location(manifest: &ProjectManifest) -> Result<FileLocation, String> {
+    let mut location = manifest.project.cache_location.clone();
+    location.append_path(SESSION_IMAGE_FILE)?;
+    Ok(location)
+}
+
+/// Session restored from the image, if its fingerprint matches the current inputs
+pub fn load_session_image(manifest: &ProjectManifest) -> Option<Session> {
+    let fingerprint = get_fingerprint(&get_fingerprint_inputs(manifest).ok()?);
+    let location = get_session_image_location(manifest).ok()?;
+    let content = location.read_content().ok()?;
+    let image: SessionImage = serde_json::from_slice(&content).ok()?;
+    if image.fingerprint != fingerprint {
+        return None;
+    }
+    image.restore(get_session_settings(manifest)).ok()
+}
+
+/// Image of a session executing the default plan of the project, keyed by the current inputs
+pub fn get_session_image(
+    manifest: &ProjectManifest,
+    session: &Session,
+) -> Result<SessionImage, String> {
+    let fingerprint = get_fingerprint(&get_fingerprint_inputs(manifest)?);
+    Ok(SessionImage::from_session(session, &fingerprint))
+}
+
+/// Serializing the image takes a while, callers usually write it from a background thread
+pub fn write_session_image(manifest: &ProjectManifest, image: &SessionImage) -> Result<(), String> {
+    let content = serde_json::to_vec(image)
+        .map_err(|e| format!("unable to serialize the session image: {}", e))?;
+    let location = get_session_image_location(manifest)?;
+    // Written aside then renamed, a console starting meanwhile never reads a partial image
+    let path = location.to_string();
+    let tmp_path = format!("{}.tmp", path);
+    let parent = location.get_parent_location()?;
+    fs::create_dir_all(parent.to_string())
+        .map_err(|e| format!("unable to create {}: {}", parent, e))?;
+    fs::write(&tmp_path, content).map_err(|e| format!("unable to write {}: {}", tmp_path, e))?;
+    fs::rename(&tmp_path, &path).map_err(|e| format!("unable to write {}: {}", path, e))
+}
+
+/// Removes the image, if any
+pub fn clear_session_image(manifest: &ProjectManifest) -> Result<(), String> {
+    let path = get_session_image_location(manifest)?.to_string();
+    match fs::remove_file(&path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+            Err(format!("unable to remove {}: {}", path, e))
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fingerprint() {
+        let inputs = vec![
+            ("Clarinet.toml".to_string(), Some(b"[project]".to_vec())),
+            (
+                "contracts/counter.clar".to_string(),
+                Some(b"(ok u1)".to_vec()),
+            ),
+            ("deployments/default.simnet-plan.yaml".to_string(), None),
+        ];
+        let fingerprint = get_fingerprint(&inputs);
+        assert_eq!(fingerprint, get_fingerprint(&inputs));
+
+        let mut edited = inputs.clone();
+        edited[1].1 = Some(b"(ok u2)".to_vec());
+        assert_ne!(fingerprint, get_fingerprint(&edited));
+
+        let mut created = inputs.clone();
+        created[2].1 = Some(vec![]);
+        assert_ne!(fingerprint, get_fingerprint(&created));
+
+        // the content of an input doesn't leak into the next one
+        let shifted = vec![
+            ("a".to_string(), Some(b"bc".to_vec())),
+            ("d".to_string(), Some(vec![])),
+        ];
+        let other = vec![
+            ("a".to_string(), Some(b"b".to_vec())),
+            ("cd".to_string(), Some(vec![])),
+        ];
+        assert_ne!(get_fingerprint(&shifted), get_fingerprint(&other));
+    }
+}
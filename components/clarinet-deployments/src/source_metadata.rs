@@ -0,0 +1,233 @@
+//! Metadata embedded in the sources of the published contracts (`deployments apply
+//! --embed-metadata`): a comment block appended to each source, with the hash of the local
+//! source, the version of Clarinet and the id of the plan. `clarinet verify-metadata` reads it
+//! back from the chain to tie a deployed contract to a revision of the project.
+//!
+//! ```clarity
+//! ;; @clarinet-metadata
+//! ;; source-hash: 5f2b...e1
+//! ;; clarinet-version: 2.11.0
+//! ;; plan-id: 84c0a3d9e2b1f7a4
+//! ```
+
+use clarity_repl::clarity::util::hash::Sha256Sum;
+use clarity_repl::clarity::vm::types::QualifiedContractIdentifier;
+
+use crate::requirements::get_source_hash;
+use crate::types::{DeploymentSpecification, TransactionSpecification};
+
+const METADATA_HEADER: &str = ";; @clarinet-metadata";
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SourceMetadata {
+    pub source_hash: String,
+    pub clarinet_version: String,
+    pub plan_id: String,
+}
+
+impl SourceMetadata {
+    fn to_comment_block(&self) -> String {
+        format!(
+            "{}\n;; source-hash: {}\n;; clarinet-version: {}\n;; plan-id: {}\n",
+            METADATA_HEADER, self.source_hash, self.clarinet_version, self.plan_id
+        )
+    }
+}
+
+/// Id of a plan, the hash of its content: two plans with the same transactions share it
+pub fn get_plan_id(deployment: &DeploymentSpecification) -> Result<String, String> {
+    let content = deployment.to_file_content()?;
+    let hash = Sha256Sum::from_data(&content).to_hex();
+    Ok(hash[..16].to_string())
+}
+
+/// Appends the metadata block to the sources of the contracts published by the plan, returns
+/// the number of contracts updated
+pub fn embed_source_metadata(deployment: &mut DeploymentSpecification) -> Result<usize, String> {
+    let plan_id = get_plan_id(deployment)?;
+    let mut embedded = 0;
+    for batch in deployment.plan.batches.iter_mut() {
+        for transaction in batch.transactions.iter_mut() {
+            let TransactionSpecification::ContractPublish(tx) = transaction else {
+                continue;
+            };
+            if split_source_metadata(&tx.source).1.is_some() {
+                continue;
+            }
+            let metadata = SourceMetadata {
+                source_hash: get_source_hash(&tx.source),
+                clarinet_version: env!("CARGO_PKG_VERSION").to_string(),
+                plan_id: plan_id.clone(),
+            };
+            tx.source.push('\n');
+            tx.source.push_str(&metadata.to_comment_block());
+            embedded += 1;
+        }
+    }
+    Ok(embedded)
+}
+
+/// The source without its metadata block (and the newline added before it), and the metadata
+/// if the source has a valid block
+pub fn split_source_metadata(source: &str) -> (&str, Option<SourceMetadata>) {
+    let Some(start) = source.rfind(METADATA_HEADER) else {
+        return (source, None);
+    };
+    let mut source_hash = None;
+    let mut clarinet_version = None;
+    let mut plan_id = None;
+    for line in source[start..].lines().skip(1) {
+        let Some((key, value)) = line.trim_start_matches(";;").split_once(':') else {
+            return (source, None);
+        };
+        let value = Some(value.trim().to_string());
+        match key.trim() {
+            "source-hash" => source_hash = value,
+            "clarinet-version" => clarinet_version = value,
+            "plan-id" => plan_id = value,
+            _ => return (source, None),
+        }
+    }
+    let (Some(source_hash), Some(clarinet_version), Some(plan_id)) =
+        (source_hash, clarinet_version, plan_id)
+    else {
+        return (source, None);
+    };
+    let body = &source[..start];
+    let body = body.strip_suffix('\n').unwrap_or(body);
+    let metadata = SourceMetadata {
+        source_hash,
+        clarinet_version,
+        plan_id,
+    };
+    (body, Some(metadata))
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MetadataVerification {
+    pub contract_id: String,
+    pub metadata: Option<SourceMetadata>,
+    /// Hash of the source of the project
+    pub local_hash: String,
+    /// The deployed code, stripped of its block, is the one the metadata describes
+    pub deployed_code_matches: bool,
+    pub issues: Vec<String>,
+}
+
+impl MetadataVerification {
+    pub fn is_success(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// Checks the metadata of a deployed source against the source of the project. The sources
+/// published on devnet and testnet have their requirements remapped, their code can differ
+/// from the one described without being tampered with (`remapped`).
+pub fn verify_source_metadata(
+    contract_id: &QualifiedContractIdentifier,
+    deployed_source: &str,
+    local_source: &str,
+    remapped: bool,
+) -> MetadataVerification {
+    let local_hash = get_source_hash(local_source);
+    let (body, metadata) = split_source_metadata(deployed_source);
+    let mut issues = vec![];
+    let deployed_code_matches = match &metadata {
+        None => {
+            issues.push("no metadata in the deployed source".to_string());
+            false
+        }
+        Some(metadata) => {
+            if metadata.source_hash != local_hash {
+                issues.push(format!(
+                    "deployed from another source (hash {}), the local source hashes to {}",
+                    metadata.source_hash, local_hash
+                ));
+            }
+            let matches = get_source_hash(body) == metadata.source_hash;
+            if !matches && !remapped {
+                issues.push("the deployed code doesn't match the hash of its metadata".to_string());
+            }
+            matches
+        }
+    };
+    MetadataVerification {
+        contract_id: contract_id.to_string(),
+        metadata,
+        local_hash,
+        deployed_code_matches,
+        issues,
+    }
+}
+
+/// Source of a contract deployed on the network of `node_url`, `None` if it isn't published
+pub async fn fetch_deployed_source(
+    node_url: &str,
+    contract_id: &QualifiedContractIdentifier,
+) -> Result<Option<String>, String> {
+    let request_url = format!(
+        "{}/v2/contracts/source/{}/{}?proof=0",
+        node_url.trim_end_matches('/'),
+        contract_id.issuer,
+        contract_id.name
+    );
+    let response = match clarinet_files::net::get_text(&request_url).await {
+        Ok(response) => response,
+        Err(e) if e.contains("(404") => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    let contract: serde_json::Value = serde_json::from_str(&response)
+        .map_err(|_| format!("unable to parse contract {}", request_url))?;
+    match contract.get("source") {
+        Some(serde_json::Value::String(source)) => Ok(Some(source.clone())),
+        _ => Err(format!("unable to parse contract {}", request_url)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verify_source_metadata() {
+        let contract_id =
+            QualifiedContractIdentifier::parse("ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM.counter")
+                .unwrap();
+        let local_source = "(define-data-var count uint u0)\n";
+        let metadata = SourceMetadata {
+            source_hash: get_source_hash(local_source),
+            clarinet_version: "2.11.0".to_string(),
+            plan_id: "84c0a3d9e2b1f7a4".to_string(),
+        };
+        let deployed_source = format!("{}\n{}", local_source, metadata.to_comment_block());
+        assert_eq!(
+            split_source_metadata(&deployed_source),
+            (local_source, Some(metadata.clone()))
+        );
+        assert_eq!(split_source_metadata(local_source), (local_source, None));
+
+        let verification =
+            verify_source_metadata(&contract_id, &deployed_source, local_source, false);
+        assert!(verification.is_success());
+        assert!(verification.deployed_code_matches);
+
+        let edited = "(define-data-var count uint u1)\n";
+        let verification = verify_source_metadata(&contract_id, &deployed_source, edited, false);
+        assert_eq!(verification.issues.len(), 1);
+
+        let tampered = deployed_source.replace("u0", "u2");
+        let verification = verify_source_metadata(&contract_id, &tampered, local_source, false);
+        assert!(!verification.deployed_code_matches);
+        assert_eq!(
+            verification.issues,
+            vec!["the deployed code doesn't match the hash of its metadata".to_string()]
+        );
+        assert!(verify_source_metadata(&contract_id, &tampered, local_source, true).is_success());
+
+        let verification = verify_source_metadata(&contract_id, local_source, local_source, false);
+        assert_eq!(
+            verification.issues,
+            vec!["no metadata in the deployed source".to_string()]
+        );
+    }
+}
@@ -0,0 +1,110 @@
+//! Check of a part of the project (`clarinet check --staged`): the contracts changed and the
+//! contracts depending on them are analyzed and executed, the contracts they depend on are
+//! published for their analysis only, the other contracts are left out of the session.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use clarinet_files::ProjectManifest;
+use clarity_repl::analysis::ast_dependency_detector::DependencySet;
+use clarity_repl::clarity::vm::types::QualifiedContractIdentifier;
+use clarity_repl::repl::settings::RequirementsExecution;
+
+use crate::cancellation::CancellationToken;
+use crate::setup_session_with_deferred_contracts;
+use crate::types::{DeploymentGenerationArtifacts, DeploymentSpecification};
+use crate::upgrade_plan::get_upgrade_deployment;
+
+/// The contracts and the ones they depend on, directly or not
+pub fn get_contracts_with_dependencies(
+    contracts: &BTreeSet<QualifiedContractIdentifier>,
+    dependencies: &BTreeMap<QualifiedContractIdentifier, DependencySet>,
+) -> BTreeSet<QualifiedContractIdentifier> {
+    let mut closure = contracts.clone();
+    let mut pending: Vec<&QualifiedContractIdentifier> = contracts.iter().collect();
+    while let Some(contract_id) = pending.pop() {
+        let Some(deps) = dependencies.get(contract_id) else {
+            continue;
+        };
+        for dep in deps.set.iter() {
+            if closure.insert(dep.contract_id.clone()) {
+                pending.push(&dep.contract_id);
+            }
+        }
+    }
+    closure
+}
+
+/// Session of the simnet plan reduced to the publishes of `checked` and of their
+/// dependencies. The artifacts only cover the contracts `checked`.
+pub fn setup_session_for_contracts(
+    manifest: &ProjectManifest,
+    deployment: &DeploymentSpecification,
+    artifacts: &DeploymentGenerationArtifacts,
+    checked: &BTreeSet<QualifiedContractIdentifier>,
+) -> DeploymentGenerationArtifacts {
+    let published = get_contracts_with_dependencies(checked, &artifacts.deps);
+    let partial_deployment = get_upgrade_deployment(deployment, &published);
+    let deferred = published.difference(checked).cloned().collect();
+
+    let mut manifest = manifest.clone();
+    manifest.repl_settings.requirements_execution = RequirementsExecution::Lazy;
+    let mut partial_artifacts = setup_session_with_deferred_contracts(
+        &manifest,
+        &partial_deployment,
+        Some(&artifacts.asts),
+        &CancellationToken::new(),
+        deferred,
+    );
+    partial_artifacts
+        .diags
+        .retain(|contract_id, _| checked.contains(contract_id));
+    partial_artifacts
+        .analysis
+        .retain(|contract_id, _| checked.contains(contract_id));
+    partial_artifacts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contract_id(name: &str) -> QualifiedContractIdentifier {
+        QualifiedContractIdentifier::parse(&format!(
+            "ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM.{}",
+            name
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_get_contracts_with_dependencies() {
+        let mut dependencies = BTreeMap::new();
+        dependencies.insert(contract_id("trait"), DependencySet::new());
+        dependencies.insert(contract_id("token"), DependencySet::new());
+        let mut deps = DependencySet::new();
+        deps.add_dependency(contract_id("token"), true);
+        deps.add_dependency(contract_id("trait"), false);
+        dependencies.insert(contract_id("vault"), deps);
+        let mut deps = DependencySet::new();
+        deps.add_dependency(contract_id("vault"), true);
+        dependencies.insert(contract_id("router"), deps);
+        dependencies.insert(contract_id("oracle"), DependencySet::new());
+
+        let contracts = BTreeSet::from([contract_id("router")]);
+        assert_eq!(
+            get_contracts_with_dependencies(&contracts, &dependencies),
+            BTreeSet::from([
+                contract_id("router"),
+                contract_id("vault"),
+                contract_id("token"),
+                contract_id("trait"),
+            ])
+        );
+
+        let contracts = BTreeSet::from([contract_id("token")]);
+        assert_eq!(
+            get_contracts_with_dependencies(&contracts, &dependencies),
+            contracts
+        );
+    }
+}
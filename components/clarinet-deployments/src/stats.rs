@@ -0,0 +1,192 @@
+//! Summary of a project, printed by `clarinet stats`: size and functions of the contracts,
+//! their estimated deployment cost, the depth of their dependencies and the test coverage.
+
+use std::collections::{BTreeMap, HashMap};
+
+use clarinet_files::ProjectManifest;
+use clarity_repl::analysis::ast_dependency_detector::DependencySet;
+use clarity_repl::clarity::vm::types::QualifiedContractIdentifier;
+
+use crate::types::{DeploymentGenerationArtifacts, DeploymentSpecification};
+
+/// Written by the coverage reporter of the clarinet-sdk
+pub const COVERAGE_REPORT_FILE: &str = "lcov.info";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ContractStats {
+    pub contract_id: String,
+    pub lines: usize,
+    pub size: usize,
+    pub public_functions: usize,
+    pub read_only_functions: usize,
+    pub private_functions: usize,
+    /// In µSTX
+    pub deployment_cost: u64,
+    /// Length of the longest chain of contracts this contract depends on
+    pub dependency_depth: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProjectStats {
+    pub contracts: Vec<ContractStats>,
+    pub lines: usize,
+    pub public_functions: usize,
+    pub read_only_functions: usize,
+    pub private_functions: usize,
+    pub deployment_cost: u64,
+    pub dependency_depth: usize,
+    /// Requirements published in the plan, including the dependencies of the requirements
+    pub requirements: usize,
+    /// Percentage of lines covered, if a coverage report was found
+    pub coverage: Option<f64>,
+}
+
+impl ProjectStats {
+    /// Contracts sorted by size, largest first
+    pub fn get_largest_contracts(&self, count: usize) -> Vec<&ContractStats> {
+        let mut contracts: Vec<&ContractStats> = self.contracts.iter().collect();
+        contracts.sort_by(|a, b| b.size.cmp(&a.size));
+        contracts.truncate(count);
+        contracts
+    }
+}
+
+fn get_dependency_depth(
+    contract_id: &QualifiedContractIdentifier,
+    deps: &BTreeMap<QualifiedContractIdentifier, DependencySet>,
+    depths: &mut HashMap<QualifiedContractIdentifier, usize>,
+) -> usize {
+    if let Some(depth) = depths.get(contract_id) {
+        return *depth;
+    }
+    // placeholder guarding against cycles, which are reported by the plan generation
+    depths.insert(contract_id.clone(), 0);
+    let depth = deps
+        .get(contract_id)
+        .map(|dependencies| {
+            dependencies
+                .set
+                .iter()
+                .map(|dependency| 1 + get_dependency_depth(&dependency.contract_id, deps, depths))
+                .max()
+                .unwrap_or(0)
+        })
+        .unwrap_or(0);
+    depths.insert(contract_id.clone(), depth);
+    depth
+}
+
+/// Percentage of lines hit in an LCOV report
+pub fn get_lcov_coverage(report: &str) -> Option<f64> {
+    let (mut found, mut hit) = (0u64, 0u64);
+    for line in report.lines() {
+        if let Some(count) = line.strip_prefix("LF:") {
+            found += count.trim().parse::<u64>().ok()?;
+        } else if let Some(count) = line.strip_prefix("LH:") {
+            hit += count.trim().parse::<u64>().ok()?;
+        }
+    }
+    match found {
+        0 => None,
+        found => Some(hit as f64 * 100.0 / found as f64),
+    }
+}
+
+fn get_coverage(manifest: &ProjectManifest) -> Option<f64> {
+    let mut report_location = manifest.location.get_project_root_location().ok()?;
+    report_location.append_path(COVERAGE_REPORT_FILE).ok()?;
+    let report = report_location.read_content().ok()?;
+    get_lcov_coverage(std::str::from_utf8(&report).ok()?)
+}
+
+pub fn get_project_stats(
+    manifest: &ProjectManifest,
+    deployment: &DeploymentSpecification,
+    artifacts: &DeploymentGenerationArtifacts,
+    deployment_fee_rate: u64,
+) -> ProjectStats {
+    let mut depths = HashMap::new();
+    let mut contracts = vec![];
+    let mut requirements = 0;
+    let requirements_ids: Vec<String> = manifest
+        .project
+        .requirements
+        .iter()
+        .flatten()
+        .map(|requirement| requirement.contract_id.clone())
+        .collect();
+
+    for (contract_id, (source, _)) in deployment.contracts.iter() {
+        let is_project_contract = manifest.contracts.contains_key(contract_id.name.as_str())
+            && !requirements_ids.contains(&contract_id.to_string());
+        if !is_project_contract {
+            requirements += 1;
+            continue;
+        }
+        let analysis = artifacts.analysis.get(contract_id);
+        contracts.push(ContractStats {
+            contract_id: contract_id.to_string(),
+            lines: source.lines().count(),
+            size: source.len(),
+            public_functions: analysis.map_or(0, |a| a.public_function_types.len()),
+            read_only_functions: analysis.map_or(0, |a| a.read_only_function_types.len()),
+            private_functions: analysis.map_or(0, |a| a.private_function_types.len()),
+            deployment_cost: deployment_fee_rate.saturating_mul(source.len() as u64),
+            dependency_depth: get_dependency_depth(contract_id, &artifacts.deps, &mut depths),
+        });
+    }
+
+    ProjectStats {
+        lines: contracts.iter().map(|c| c.lines).sum(),
+        public_functions: contracts.iter().map(|c| c.public_functions).sum(),
+        read_only_functions: contracts.iter().map(|c| c.read_only_functions).sum(),
+        private_functions: contracts.iter().map(|c| c.private_functions).sum(),
+        deployment_cost: contracts.iter().map(|c| c.deployment_cost).sum(),
+        dependency_depth: contracts
+            .iter()
+            .map(|c| c.dependency_depth)
+            .max()
+            .unwrap_or(0),
+        contracts,
+        requirements,
+        coverage: get_coverage(manifest),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_lcov_coverage() {
+        let report = "TN:\nSF:contracts/counter.clar\nLF:10\nLH:8\nend_of_record\nSF:contracts/token.clar\nLF:30\nLH:12\nend_of_record\n";
+        assert_eq!(get_lcov_coverage(report), Some(50.0));
+        assert_eq!(get_lcov_coverage("TN:\n"), None);
+    }
+
+    #[test]
+    fn test_get_dependency_depth() {
+        let id = |name: &str| {
+            QualifiedContractIdentifier::parse(&format!(
+                "ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM.{}",
+                name
+            ))
+            .unwrap()
+        };
+        let mut deps = BTreeMap::new();
+        for (contract, dependencies) in [
+            ("dao", vec!["token", "traits"]),
+            ("token", vec!["traits"]),
+            ("traits", vec![]),
+        ] {
+            let mut set = DependencySet::new();
+            for dependency in dependencies {
+                set.add_dependency(id(dependency), false);
+            }
+            deps.insert(id(contract), set);
+        }
+        let mut depths = HashMap::new();
+        assert_eq!(get_dependency_depth(&id("dao"), &deps, &mut depths), 2);
+        assert_eq!(get_dependency_depth(&id("traits"), &deps, &mut depths), 0);
+    }
+}
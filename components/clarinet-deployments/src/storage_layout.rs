@@ -0,0 +1,238 @@
+//! Persistent storage of the project contracts, reported by `clarinet inspect storage-layout`:
+//! data vars, maps and tokens with their types, and the size of the entries as they are
+//! written to the chain state.
+
+use std::collections::BTreeMap;
+
+use clarinet_files::ProjectManifest;
+use clarity_repl::clarity::vm::analysis::ContractAnalysis;
+use clarity_repl::clarity::vm::types::TypeSignature;
+use clarity_repl::clarity::vm::SymbolicExpression;
+use clarity_repl::repl::interpreter::BLOCK_LIMIT_MAINNET;
+
+use crate::clarity_migration::get_span_range;
+use crate::types::{DeploymentGenerationArtifacts, DeploymentSpecification};
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DataVarLayout {
+    pub name: String,
+    pub value_type: String,
+    pub initial_value: Option<String>,
+    /// Maximum size of the serialized value, in bytes
+    pub max_size: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MapLayout {
+    pub name: String,
+    pub key_type: String,
+    pub value_type: String,
+    /// Maximum size written by a `map-set` or a `map-insert` (key and value), in bytes
+    pub max_entry_size: Option<u32>,
+    /// Entries of the maximum size a block can write, bounded by the mainnet block limit
+    pub max_entries_per_block: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TokenKind {
+    Fungible,
+    NonFungible,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TokenLayout {
+    pub name: String,
+    pub kind: TokenKind,
+    /// Type of the asset identifiers of a non-fungible token
+    pub asset_type: Option<String>,
+    /// Total supply of a fungible token, unlimited if not set
+    pub total_supply: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ContractStorageLayout {
+    pub contract_id: String,
+    pub data_vars: Vec<DataVarLayout>,
+    pub maps: Vec<MapLayout>,
+    pub tokens: Vec<TokenLayout>,
+}
+
+/// Source of the last argument of the `define-data-var` (initial value) and of the
+/// `define-fungible-token` (total supply) definitions, by name
+pub fn get_definitions_values(
+    source: &str,
+    expressions: &[SymbolicExpression],
+) -> BTreeMap<String, String> {
+    let mut values = BTreeMap::new();
+    for expression in expressions {
+        let Some(list) = expression.match_list() else {
+            continue;
+        };
+        let value = match list {
+            [keyword, name, _, value]
+                if keyword.match_atom().map(|a| a.as_str()) == Some("define-data-var") =>
+            {
+                Some((name, value))
+            }
+            [keyword, name, supply]
+                if keyword.match_atom().map(|a| a.as_str()) == Some("define-fungible-token") =>
+            {
+                Some((name, supply))
+            }
+            _ => None,
+        };
+        let Some((name, value)) = value else {
+            continue;
+        };
+        let Some(name) = name.match_atom() else {
+            continue;
+        };
+        let text = get_span_range(source, &value.span)
+            .map(|range| source[range].to_string())
+            .unwrap_or_else(|| value.to_string());
+        values.insert(name.to_string(), text);
+    }
+    values
+}
+
+/// Writes of `entry_size` bytes fitting in a block, one write each
+pub fn get_max_entries_per_block(entry_size: u32) -> u64 {
+    let by_length = BLOCK_LIMIT_MAINNET.write_length / std::cmp::max(entry_size, 1) as u64;
+    std::cmp::min(by_length, BLOCK_LIMIT_MAINNET.write_count)
+}
+
+fn get_max_size(signature: &TypeSignature) -> Option<u32> {
+    signature.max_serialized_size().ok()
+}
+
+pub fn get_contract_storage_layout(
+    contract_id: String,
+    source: &str,
+    expressions: &[SymbolicExpression],
+    analysis: &ContractAnalysis,
+) -> ContractStorageLayout {
+    let mut values = get_definitions_values(source, expressions);
+
+    let data_vars = analysis
+        .persisted_variable_types
+        .iter()
+        .map(|(name, value_type)| DataVarLayout {
+            name: name.to_string(),
+            value_type: value_type.to_string(),
+            initial_value: values.remove(name.as_str()),
+            max_size: get_max_size(value_type),
+        })
+        .collect();
+
+    let maps = analysis
+        .map_types
+        .iter()
+        .map(|(name, (key_type, value_type))| {
+            let max_entry_size = get_max_size(key_type)
+                .zip(get_max_size(value_type))
+                .map(|(key_size, value_size)| key_size.saturating_add(value_size));
+            MapLayout {
+                name: name.to_string(),
+                key_type: key_type.to_string(),
+                value_type: value_type.to_string(),
+                max_entry_size,
+                max_entries_per_block: max_entry_size.map(get_max_entries_per_block),
+            }
+        })
+        .collect();
+
+    let fungible_tokens = analysis.fungible_tokens.iter().map(|name| TokenLayout {
+        name: name.to_string(),
+        kind: TokenKind::Fungible,
+        asset_type: None,
+        total_supply: values.remove(name.as_str()),
+    });
+    let non_fungible_tokens = analysis
+        .non_fungible_tokens
+        .iter()
+        .map(|(name, asset_type)| TokenLayout {
+            name: name.to_string(),
+            kind: TokenKind::NonFungible,
+            asset_type: Some(asset_type.to_string()),
+            total_supply: None,
+        });
+
+    ContractStorageLayout {
+        contract_id,
+        data_vars,
+        maps,
+        tokens: fungible_tokens.chain(non_fungible_tokens).collect(),
+    }
+}
+
+/// Storage layout of the contracts of the project, requirements excluded
+pub fn get_storage_layout(
+    manifest: &ProjectManifest,
+    deployment: &DeploymentSpecification,
+    artifacts: &DeploymentGenerationArtifacts,
+) -> Vec<ContractStorageLayout> {
+    deployment
+        .contracts
+        .iter()
+        .filter(|(contract_id, _)| manifest.contracts.contains_key(contract_id.name.as_str()))
+        .filter_map(|(contract_id, (source, _))| {
+            let analysis = artifacts.analysis.get(contract_id)?;
+            let expressions = artifacts
+                .asts
+                .get(contract_id)
+                .map(|ast| ast.expressions.as_slice())
+                .unwrap_or_default();
+            Some(get_contract_storage_layout(
+                contract_id.to_string(),
+                source,
+                expressions,
+                analysis,
+            ))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clarity_repl::clarity::ast::{build_ast_with_rules, ASTRules};
+    use clarity_repl::clarity::vm::types::QualifiedContractIdentifier;
+    use clarity_repl::clarity::vm::ClarityVersion;
+    use clarity_repl::clarity::StacksEpochId;
+
+    #[test]
+    fn test_get_definitions_values() {
+        let source = "(define-data-var owner principal 'ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM)
+(define-data-var config { fee: uint, paused: bool } { fee: u100, paused: false })
+(define-fungible-token capped u1000000)
+(define-fungible-token uncapped)
+(define-map balances principal uint)
+";
+        let expressions = build_ast_with_rules(
+            &QualifiedContractIdentifier::transient(),
+            source,
+            &mut (),
+            ClarityVersion::Clarity2,
+            StacksEpochId::Epoch25,
+            ASTRules::Typical,
+        )
+        .unwrap()
+        .expressions;
+
+        let values = get_definitions_values(source, &expressions);
+        assert_eq!(values.len(), 3);
+        assert_eq!(
+            values.get("owner").map(|v| v.as_str()),
+            Some("'ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM")
+        );
+        assert_eq!(
+            values.get("config").map(|v| v.as_str()),
+            Some("{ fee: u100, paused: false }")
+        );
+        assert_eq!(values.get("capped").map(|v| v.as_str()), Some("u1000000"));
+
+        assert_eq!(get_max_entries_per_block(100), 15_000);
+        assert_eq!(get_max_entries_per_block(10_000), 1_500);
+    }
+}
@@ -0,0 +1,192 @@
+//! Publishing of the SIP-016 metadata of the token contracts: once a contract with a
+//! `[contracts.<name>.metadata]` setting is published, the generated plans call its setter with
+//! the URI of the metadata. On devnet (and simnet) the URI is the one of the file served by the
+//! orchestrator, on testnet and mainnet it's the `uri` of the setting.
+
+use clarinet_files::{StacksNetwork, TokenMetadataConfig};
+use clarity_repl::clarity::vm::types::QualifiedContractIdentifier;
+use clarity_repl::clarity::vm::SymbolicExpression;
+use clarity_repl::clarity::ClarityName;
+
+use crate::types::{
+    AnchorMode, ContractCallSpecification, EmulatedContractCallSpecification,
+    TransactionSpecification,
+};
+
+pub const DEVNET_METADATA_ROUTE: &str = "/v1/metadata/";
+
+const METADATA_CALL_COST: u64 = 10_000;
+
+/// URI of the metadata of a contract, as served by the orchestrator control port
+pub fn get_devnet_metadata_uri(
+    control_port: u16,
+    contract_id: &QualifiedContractIdentifier,
+) -> String {
+    format!(
+        "http://localhost:{}{}{}.json",
+        control_port, DEVNET_METADATA_ROUTE, contract_id
+    )
+}
+
+/// URI of the metadata on a network, `None` when it isn't published there
+pub fn get_metadata_uri(
+    config: &TokenMetadataConfig,
+    network: &StacksNetwork,
+    control_port: u16,
+    contract_id: &QualifiedContractIdentifier,
+) -> Option<String> {
+    match network {
+        StacksNetwork::Simnet | StacksNetwork::Devnet => {
+            Some(get_devnet_metadata_uri(control_port, contract_id))
+        }
+        StacksNetwork::Testnet | StacksNetwork::Mainnet => config.uri.clone(),
+    }
+}
+
+fn format_uri_argument(type_expression: &SymbolicExpression, uri: &str) -> Option<String> {
+    let list = type_expression.match_list()?;
+    let escaped = uri.replace('\\', "\\\\").replace('"', "\\\"");
+    match list.first()?.match_atom()?.as_str() {
+        "string-ascii" if uri.is_ascii() => Some(format!("\"{}\"", escaped)),
+        "string-utf8" => Some(format!("u\"{}\"", escaped)),
+        "optional" => Some(format!(
+            "(some {})",
+            format_uri_argument(list.get(1)?, uri)?
+        )),
+        _ => None,
+    }
+}
+
+/// Argument of the setter for `uri`, after the type of its parameter: a `string-ascii`, a
+/// `string-utf8`, or an optional one
+pub fn get_uri_argument(
+    expressions: &[SymbolicExpression],
+    setter: &str,
+    uri: &str,
+) -> Result<String, String> {
+    let signature = expressions.iter().find_map(|expression| {
+        let list = expression.match_list()?;
+        if list.first()?.match_atom()?.as_str() != "define-public" {
+            return None;
+        }
+        let signature = list.get(1)?.match_list()?;
+        (signature.first()?.match_atom()?.as_str() == setter).then_some(signature)
+    });
+    let Some(signature) = signature else {
+        return Err(format!("no public function {}", setter));
+    };
+    let parameter_type = match signature {
+        [_, parameter] => parameter
+            .match_list()
+            .and_then(|parameter| parameter.get(1)),
+        _ => None,
+    };
+    parameter_type
+        .and_then(|parameter_type| format_uri_argument(parameter_type, uri))
+        .ok_or_else(|| {
+            format!(
+                "{} must take a single string-ascii or string-utf8 parameter, for the URI {}",
+                setter, uri
+            )
+        })
+}
+
+/// Call of the setter of a contract, by its deployer
+pub fn get_metadata_call(
+    network: &StacksNetwork,
+    contract_id: &QualifiedContractIdentifier,
+    setter: &str,
+    argument: String,
+) -> Result<TransactionSpecification, String> {
+    let method = ClarityName::try_from(setter.to_string())
+        .map_err(|_| format!("{} is not a valid function name", setter))?;
+    let tx = match network {
+        StacksNetwork::Simnet => {
+            TransactionSpecification::EmulatedContractCall(EmulatedContractCallSpecification {
+                contract_id: contract_id.clone(),
+                emulated_sender: contract_id.issuer.clone(),
+                method,
+                parameters: vec![argument],
+                description: None,
+            })
+        }
+        _ => TransactionSpecification::ContractCall(ContractCallSpecification {
+            contract_id: contract_id.clone(),
+            expected_sender: contract_id.issuer.clone(),
+            method,
+            parameters: vec![argument],
+            cost: METADATA_CALL_COST,
+            anchor_mode: AnchorMode::OnChainOnly,
+            description: None,
+        }),
+    };
+    Ok(tx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clarity_repl::clarity::ast::{build_ast_with_rules, ASTRules};
+    use clarity_repl::clarity::vm::ClarityVersion;
+    use clarity_repl::clarity::StacksEpochId;
+
+    fn get_expressions(source: &str) -> Vec<SymbolicExpression> {
+        let contract_id =
+            QualifiedContractIdentifier::parse("ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM.token")
+                .unwrap();
+        build_ast_with_rules(
+            &contract_id,
+            source,
+            &mut (),
+            ClarityVersion::Clarity2,
+            StacksEpochId::Epoch25,
+            ASTRules::Typical,
+        )
+        .unwrap()
+        .expressions
+    }
+
+    #[test]
+    fn test_uri_argument() {
+        let uri = "http://localhost:20446/v1/metadata/token.json";
+        let expressions = get_expressions(
+            "(define-public (set-token-uri (value (string-utf8 256))) (ok true))
+(define-public (set-logo (value (optional (string-ascii 256)))) (ok true))
+(define-public (set-owner (owner principal)) (ok true))",
+        );
+        assert_eq!(
+            get_uri_argument(&expressions, "set-token-uri", uri),
+            Ok(format!("u\"{}\"", uri))
+        );
+        assert_eq!(
+            get_uri_argument(&expressions, "set-logo", "ipfs://\"logo\""),
+            Ok("(some \"ipfs://\\\"logo\\\"\")".to_string())
+        );
+        assert!(get_uri_argument(&expressions, "set-owner", uri).is_err());
+        assert_eq!(
+            get_uri_argument(&expressions, "set-uri", uri),
+            Err("no public function set-uri".to_string())
+        );
+
+        let contract_id =
+            QualifiedContractIdentifier::parse("ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM.token")
+                .unwrap();
+        let config = TokenMetadataConfig {
+            file: "metadata/token.json".to_string(),
+            uri: None,
+            setter: "set-token-uri".to_string(),
+            decimals: None,
+        };
+        assert_eq!(
+            get_metadata_uri(&config, &StacksNetwork::Devnet, 20446, &contract_id),
+            Some(
+                "http://localhost:20446/v1/metadata/ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM.token.json"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            get_metadata_uri(&config, &StacksNetwork::Mainnet, 20446, &contract_id),
+            None
+        );
+    }
+}
@@ -1,6 +1,6 @@
 use clarinet_files::StacksNetwork;
 use clarinet_files::{FileAccessor, FileLocation};
-use clarity_repl::clarity::util::hash::{hex_bytes, to_hex};
+use clarity_repl::clarity::util::hash::{hex_bytes, to_hex, Hash160};
 use clarity_repl::clarity::vm::analysis::ContractAnalysis;
 use clarity_repl::clarity::vm::ast::ContractAST;
 use clarity_repl::clarity::vm::diagnostic::Diagnostic;
@@ -10,66 +10,21 @@ use clarity_repl::clarity::vm::types::{
 
 use clarity_repl::analysis::ast_dependency_detector::DependencySet;
 use clarity_repl::clarity::{ClarityName, ClarityVersion, ContractName, StacksEpochId, Value};
-use clarity_repl::repl::{Session, DEFAULT_CLARITY_VERSION};
+use clarity_repl::repl::address_book::AddressBook;
+use clarity_repl::repl::session::{BOOT_MAINNET_ADDRESS, BOOT_TESTNET_ADDRESS};
+use clarity_repl::repl::{
+    clarity_versions, ClarityCodeSource, ClarityContract, ContractDeployer, Session,
+    DEFAULT_CLARITY_VERSION,
+};
 use serde::{Deserialize, Serialize};
 use serde_yaml;
 use std::collections::BTreeMap;
 use std::collections::HashMap;
+use std::fmt;
 
-#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Copy, Eq, PartialOrd, Ord)]
-pub enum EpochSpec {
-    #[serde(rename = "2.0")]
-    Epoch2_0,
-    #[serde(rename = "2.05")]
-    Epoch2_05,
-    #[serde(rename = "2.1")]
-    Epoch2_1,
-    #[serde(rename = "2.2")]
-    Epoch2_2,
-    #[serde(rename = "2.3")]
-    Epoch2_3,
-    #[serde(rename = "2.4")]
-    Epoch2_4,
-    #[serde(rename = "2.5")]
-    Epoch2_5,
-    #[serde(rename = "3.0")]
-    Epoch3_0,
-    #[serde(rename = "3.1")]
-    Epoch3_1,
-}
-
-impl From<StacksEpochId> for EpochSpec {
-    fn from(epoch: StacksEpochId) -> Self {
-        match epoch {
-            StacksEpochId::Epoch20 => EpochSpec::Epoch2_0,
-            StacksEpochId::Epoch2_05 => EpochSpec::Epoch2_05,
-            StacksEpochId::Epoch21 => EpochSpec::Epoch2_1,
-            StacksEpochId::Epoch22 => EpochSpec::Epoch2_2,
-            StacksEpochId::Epoch23 => EpochSpec::Epoch2_3,
-            StacksEpochId::Epoch24 => EpochSpec::Epoch2_4,
-            StacksEpochId::Epoch25 => EpochSpec::Epoch2_5,
-            StacksEpochId::Epoch30 => EpochSpec::Epoch3_0,
-            StacksEpochId::Epoch31 => EpochSpec::Epoch3_1,
-            StacksEpochId::Epoch10 => unreachable!("epoch 1.0 is not supported"),
-        }
-    }
-}
+use crate::includes::{read_source_with_includes, SourceMap};
 
-impl From<EpochSpec> for StacksEpochId {
-    fn from(val: EpochSpec) -> Self {
-        match val {
-            EpochSpec::Epoch2_0 => StacksEpochId::Epoch20,
-            EpochSpec::Epoch2_05 => StacksEpochId::Epoch2_05,
-            EpochSpec::Epoch2_1 => StacksEpochId::Epoch21,
-            EpochSpec::Epoch2_2 => StacksEpochId::Epoch22,
-            EpochSpec::Epoch2_3 => StacksEpochId::Epoch23,
-            EpochSpec::Epoch2_4 => StacksEpochId::Epoch24,
-            EpochSpec::Epoch2_5 => StacksEpochId::Epoch25,
-            EpochSpec::Epoch3_0 => StacksEpochId::Epoch30,
-            EpochSpec::Epoch3_1 => StacksEpochId::Epoch31,
-        }
-    }
-}
+pub use clarity_repl::repl::EpochSpec;
 
 #[derive(Debug, Clone)]
 pub struct DeploymentGenerationArtifacts {
@@ -78,28 +33,137 @@ pub struct DeploymentGenerationArtifacts {
     pub diags: HashMap<QualifiedContractIdentifier, Vec<Diagnostic>>,
     pub analysis: HashMap<QualifiedContractIdentifier, ContractAnalysis>,
     pub results_values: HashMap<QualifiedContractIdentifier, Option<Value>>,
+    /// Source maps of the contracts made of several files
+    pub source_maps: HashMap<QualifiedContractIdentifier, SourceMap>,
+    /// Issues that didn't prevent the generation of the deployment plan
+    pub warnings: Vec<Warning>,
+    /// Results of the `emulated-eval` snippets, in plan order
+    pub evals: Vec<EmulatedEvalResult>,
     pub session: Session,
     pub success: bool,
 }
 
+/// Outcome of an `emulated-eval` snippet, evaluated in the session
+#[derive(Debug, Clone)]
+pub struct EmulatedEvalResult {
+    pub batch_id: usize,
+    pub code: String,
+    pub result: Result<Value, Vec<Diagnostic>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WarningCode {
+    /// A requirement mock was ignored because `CLARINET_MOCK_REQUIREMENTS` is disabled
+    MockIgnored,
+    /// Several contracts could satisfy the dependencies of a requirement
+    AmbiguousDependencies,
+    /// A requirement was published under another name to avoid a collision
+    RequirementRemapped,
+    /// The metadata of a token has no URI on the network, its setter isn't called
+    MetadataUriMissing,
+}
+
+impl WarningCode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WarningCode::MockIgnored => "mock-ignored",
+            WarningCode::AmbiguousDependencies => "ambiguous-dependencies",
+            WarningCode::RequirementRemapped => "requirement-remapped",
+            WarningCode::MetadataUriMissing => "metadata-uri-missing",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Warning {
+    pub code: WarningCode,
+    pub message: String,
+    /// File the warning relates to, usually the manifest declaring the requirement
+    pub location: Option<FileLocation>,
+}
+
+impl Warning {
+    pub fn new(code: WarningCode, message: String, location: Option<FileLocation>) -> Self {
+        Warning {
+            code,
+            message,
+            location,
+        }
+    }
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[{}] {}", self.code.as_str(), self.message)?;
+        if let Some(ref location) = self.location {
+            write!(f, " ({})", location)?;
+        }
+        Ok(())
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 pub struct TransactionPlanSpecification {
     pub batches: Vec<TransactionsBatchSpecification>,
+    /// Checks run once every batch is confirmed
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub post_checks: Vec<PostCheckSpecification>,
 }
 
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 pub struct TransactionPlanSpecificationFile {
     pub batches: Vec<TransactionsBatchSpecificationFile>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub post_checks: Option<Vec<PostCheckSpecificationFile>>,
 }
 
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 pub struct TransactionsBatchSpecificationFile {
     pub id: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
     pub transactions: Vec<TransactionSpecificationFile>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub epoch: Option<EpochSpec>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub post_checks: Option<Vec<PostCheckSpecificationFile>>,
+}
+
+/// Read-only verification of the state of the chain, run by `deployment apply` once the
+/// transactions of a batch (or of the whole plan) are confirmed
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "kebab-case")]
+pub enum PostCheckSpecificationFile {
+    ReadOnlyCall(ReadOnlyCallCheckSpecificationFile),
+    MapEntry(MapEntryCheckSpecificationFile),
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "kebab-case")]
+pub struct ReadOnlyCallCheckSpecificationFile {
+    pub contract_id: String,
+    pub method: String,
+    #[serde(default)]
+    pub parameters: Vec<String>,
+    /// Clarity literal of the expected result, e.g. `(ok u1)`
+    pub expected: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub halt_on_failure: Option<bool>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "kebab-case")]
+pub struct MapEntryCheckSpecificationFile {
+    pub contract_id: String,
+    pub map: String,
+    pub key: String,
+    /// Clarity literal of the expected value, any value is accepted when not set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expected: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub halt_on_failure: Option<bool>,
 }
 
 impl TransactionsBatchSpecificationFile {
@@ -122,9 +186,13 @@ pub enum TransactionSpecificationFile {
     ContractPublish(ContractPublishSpecificationFile),
     EmulatedContractCall(EmulatedContractCallSpecificationFile),
     EmulatedContractPublish(EmulatedContractPublishSpecificationFile),
+    EmulatedEval(EmulatedEvalSpecificationFile),
     RequirementPublish(RequirementPublishSpecificationFile),
     BtcTransfer(BtcTransferSpecificationFile),
     StxTransfer(StxTransferSpecificationFile),
+    BnsNamePreorder(BnsNamePreorderSpecificationFile),
+    BnsNameRegister(BnsNameRegisterSpecificationFile),
+    BnsNameUpdate(BnsNameUpdateSpecificationFile),
 }
 
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
@@ -136,8 +204,66 @@ pub struct StxTransferSpecificationFile {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub memo: Option<String>,
     pub cost: u64,
+    /// Legacy, `anchor-mode: any` when false
     #[serde(skip_serializing_if = "Option::is_none")]
     pub anchor_block_only: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub anchor_mode: Option<AnchorMode>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct BnsNamePreorderSpecificationFile {
+    pub expected_sender: String,
+    pub namespace: String,
+    pub name: String,
+    pub salt: String,
+    pub stx_to_burn: u64,
+    pub cost: u64,
+    /// Legacy, `anchor-mode: any` when false
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub anchor_block_only: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub anchor_mode: Option<AnchorMode>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct BnsNameRegisterSpecificationFile {
+    pub expected_sender: String,
+    pub namespace: String,
+    pub name: String,
+    pub salt: String,
+    pub zonefile_hash: String,
+    pub cost: u64,
+    /// Legacy, `anchor-mode: any` when false
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub anchor_block_only: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub anchor_mode: Option<AnchorMode>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct BnsNameUpdateSpecificationFile {
+    pub expected_sender: String,
+    pub namespace: String,
+    pub name: String,
+    pub zonefile_hash: String,
+    pub cost: u64,
+    /// Legacy, `anchor-mode: any` when false
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub anchor_block_only: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub anchor_mode: Option<AnchorMode>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
 }
 
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
@@ -147,6 +273,8 @@ pub struct BtcTransferSpecificationFile {
     pub recipient: String,
     pub sats_amount: u64,
     pub sats_per_byte: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
 }
 
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
@@ -157,8 +285,13 @@ pub struct ContractCallSpecificationFile {
     pub method: String,
     pub parameters: Vec<String>,
     pub cost: u64,
+    /// Legacy, `anchor-mode: any` when false
     #[serde(skip_serializing_if = "Option::is_none")]
     pub anchor_block_only: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub anchor_mode: Option<AnchorMode>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
 }
 
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
@@ -167,7 +300,13 @@ pub struct RequirementPublishSpecificationFile {
     pub contract_id: String,
     pub remap_sender: String,
     pub remap_principals: Option<BTreeMap<String, String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remap_contract_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rewrite_literal_principals: Option<bool>,
     pub cost: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub anchor_mode: Option<AnchorMode>,
     #[serde(flatten)]
     pub location: Option<FileLocation>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -176,6 +315,8 @@ pub struct RequirementPublishSpecificationFile {
     pub url: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub clarity_version: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
 }
 
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
@@ -190,10 +331,21 @@ pub struct ContractPublishSpecificationFile {
     pub path: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub url: Option<String>,
+    /// Legacy, `anchor-mode: any` when false
     #[serde(skip_serializing_if = "Option::is_none")]
     pub anchor_block_only: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub anchor_mode: Option<AnchorMode>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub clarity_version: Option<u8>,
+    /// Transaction that published the contract, recorded by `clarinet deployments import`.
+    /// Informative only, it is not kept when the plan is regenerated.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub txid: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_height: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
 }
 
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
@@ -203,6 +355,18 @@ pub struct EmulatedContractCallSpecificationFile {
     pub emulated_sender: String,
     pub method: String,
     pub parameters: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+/// Clarity snippet evaluated in the simnet session, without being deployed
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct EmulatedEvalSpecificationFile {
+    pub emulated_sender: String,
+    pub code: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
 }
 
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
@@ -218,13 +382,157 @@ pub struct EmulatedContractPublishSpecificationFile {
     pub url: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub clarity_version: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 pub struct TransactionsBatchSpecification {
     pub id: usize,
+    /// Free-form note on the batch, reported by the changelog of the plan
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
     pub transactions: Vec<TransactionSpecification>,
     pub epoch: Option<EpochSpec>,
+    /// Checks run once the transactions of the batch are confirmed
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub post_checks: Vec<PostCheckSpecification>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(tag = "check_type")]
+pub enum PostCheckSpecification {
+    ReadOnlyCall(ReadOnlyCallCheckSpecification),
+    MapEntry(MapEntryCheckSpecification),
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct ReadOnlyCallCheckSpecification {
+    #[serde(with = "qualified_contract_identifier_serde")]
+    pub contract_id: QualifiedContractIdentifier,
+    pub method: ClarityName,
+    pub parameters: Vec<String>,
+    pub expected: String,
+    pub halt_on_failure: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct MapEntryCheckSpecification {
+    #[serde(with = "qualified_contract_identifier_serde")]
+    pub contract_id: QualifiedContractIdentifier,
+    pub map: ClarityName,
+    pub key: String,
+    pub expected: Option<String>,
+    pub halt_on_failure: bool,
+}
+
+impl PostCheckSpecification {
+    pub fn from_specifications(
+        specs: &PostCheckSpecificationFile,
+    ) -> Result<PostCheckSpecification, String> {
+        let parse_contract_id = |contract_id: &str| {
+            QualifiedContractIdentifier::parse(contract_id).map_err(|_| {
+                format!(
+                    "unable to parse '{}' as a valid contract identifier",
+                    contract_id
+                )
+            })
+        };
+        let parse_name = |name: &str| {
+            ClarityName::try_from(name.to_string())
+                .map_err(|_| format!("unable to parse '{}' as a valid clarity name", name))
+        };
+        let check = match specs {
+            PostCheckSpecificationFile::ReadOnlyCall(specs) => {
+                PostCheckSpecification::ReadOnlyCall(ReadOnlyCallCheckSpecification {
+                    contract_id: parse_contract_id(&specs.contract_id)?,
+                    method: parse_name(&specs.method)?,
+                    parameters: specs.parameters.clone(),
+                    expected: specs.expected.clone(),
+                    halt_on_failure: specs.halt_on_failure.unwrap_or(false),
+                })
+            }
+            PostCheckSpecificationFile::MapEntry(specs) => {
+                PostCheckSpecification::MapEntry(MapEntryCheckSpecification {
+                    contract_id: parse_contract_id(&specs.contract_id)?,
+                    map: parse_name(&specs.map)?,
+                    key: specs.key.clone(),
+                    expected: specs.expected.clone(),
+                    halt_on_failure: specs.halt_on_failure.unwrap_or(false),
+                })
+            }
+        };
+        Ok(check)
+    }
+
+    pub fn from_specifications_list(
+        specs: &Option<Vec<PostCheckSpecificationFile>>,
+    ) -> Result<Vec<PostCheckSpecification>, String> {
+        specs
+            .iter()
+            .flatten()
+            .map(PostCheckSpecification::from_specifications)
+            .collect()
+    }
+
+    pub fn to_specification_file(&self) -> PostCheckSpecificationFile {
+        match self {
+            PostCheckSpecification::ReadOnlyCall(check) => {
+                PostCheckSpecificationFile::ReadOnlyCall(ReadOnlyCallCheckSpecificationFile {
+                    contract_id: check.contract_id.to_string(),
+                    method: check.method.to_string(),
+                    parameters: check.parameters.clone(),
+                    expected: check.expected.clone(),
+                    halt_on_failure: check.halt_on_failure.then_some(true),
+                })
+            }
+            PostCheckSpecification::MapEntry(check) => {
+                PostCheckSpecificationFile::MapEntry(MapEntryCheckSpecificationFile {
+                    contract_id: check.contract_id.to_string(),
+                    map: check.map.to_string(),
+                    key: check.key.clone(),
+                    expected: check.expected.clone(),
+                    halt_on_failure: check.halt_on_failure.then_some(true),
+                })
+            }
+        }
+    }
+
+    pub fn halt_on_failure(&self) -> bool {
+        match self {
+            PostCheckSpecification::ReadOnlyCall(check) => check.halt_on_failure,
+            PostCheckSpecification::MapEntry(check) => check.halt_on_failure,
+        }
+    }
+
+    /// e.g. `read-only call ST...counter::get-count` or `map entry ST...token::balances`
+    pub fn get_description(&self) -> String {
+        match self {
+            PostCheckSpecification::ReadOnlyCall(check) => {
+                format!("read-only call {}::{}", check.contract_id, check.method)
+            }
+            PostCheckSpecification::MapEntry(check) => {
+                format!(
+                    "map entry {}::{} {}",
+                    check.contract_id, check.map, check.key
+                )
+            }
+        }
+    }
+}
+
+fn post_checks_to_specification_file(
+    post_checks: &[PostCheckSpecification],
+) -> Option<Vec<PostCheckSpecificationFile>> {
+    if post_checks.is_empty() {
+        return None;
+    }
+    Some(
+        post_checks
+            .iter()
+            .map(|check| check.to_specification_file())
+            .collect(),
+    )
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
@@ -235,8 +543,112 @@ pub enum TransactionSpecification {
     RequirementPublish(RequirementPublishSpecification),
     EmulatedContractCall(EmulatedContractCallSpecification),
     EmulatedContractPublish(EmulatedContractPublishSpecification),
+    EmulatedEval(EmulatedEvalSpecification),
     BtcTransfer(BtcTransferSpecification),
     StxTransfer(StxTransferSpecification),
+    BnsOperation(BnsOperationSpecification),
+}
+
+impl TransactionSpecification {
+    pub fn get_description(&self) -> Option<&String> {
+        match self {
+            TransactionSpecification::ContractCall(tx) => tx.description.as_ref(),
+            TransactionSpecification::ContractPublish(tx) => tx.description.as_ref(),
+            TransactionSpecification::RequirementPublish(tx) => tx.description.as_ref(),
+            TransactionSpecification::EmulatedContractCall(tx) => tx.description.as_ref(),
+            TransactionSpecification::EmulatedContractPublish(tx) => tx.description.as_ref(),
+            TransactionSpecification::EmulatedEval(tx) => tx.description.as_ref(),
+            TransactionSpecification::BtcTransfer(tx) => tx.description.as_ref(),
+            TransactionSpecification::StxTransfer(tx) => tx.description.as_ref(),
+            TransactionSpecification::BnsOperation(tx) => tx.description.as_ref(),
+        }
+    }
+
+    pub fn set_description(&mut self, description: String) {
+        let field = match self {
+            TransactionSpecification::ContractCall(tx) => &mut tx.description,
+            TransactionSpecification::ContractPublish(tx) => &mut tx.description,
+            TransactionSpecification::RequirementPublish(tx) => &mut tx.description,
+            TransactionSpecification::EmulatedContractCall(tx) => &mut tx.description,
+            TransactionSpecification::EmulatedContractPublish(tx) => &mut tx.description,
+            TransactionSpecification::EmulatedEval(tx) => &mut tx.description,
+            TransactionSpecification::BtcTransfer(tx) => &mut tx.description,
+            TransactionSpecification::StxTransfer(tx) => &mut tx.description,
+            TransactionSpecification::BnsOperation(tx) => &mut tx.description,
+        };
+        *field = Some(description);
+    }
+
+    /// What the transaction does, the same for a transaction and its regenerated version: the
+    /// contract published, the function called with its arguments, the transfer
+    fn get_key(&self) -> String {
+        match self {
+            TransactionSpecification::ContractCall(tx) => format!(
+                "call {}::{}({})",
+                tx.contract_id,
+                tx.method,
+                tx.parameters.join(" ")
+            ),
+            TransactionSpecification::EmulatedContractCall(tx) => format!(
+                "call {}::{}({})",
+                tx.contract_id,
+                tx.method,
+                tx.parameters.join(" ")
+            ),
+            TransactionSpecification::ContractPublish(tx) => {
+                format!("publish {}.{}", tx.expected_sender, tx.contract_name)
+            }
+            TransactionSpecification::EmulatedContractPublish(tx) => {
+                format!("publish {}.{}", tx.emulated_sender, tx.contract_name)
+            }
+            TransactionSpecification::RequirementPublish(tx) => {
+                format!("requirement {}", tx.contract_id)
+            }
+            TransactionSpecification::EmulatedEval(tx) => format!("eval {}", tx.code),
+            TransactionSpecification::BtcTransfer(tx) => {
+                format!(
+                    "btc {} {} {}",
+                    tx.expected_sender, tx.recipient, tx.sats_amount
+                )
+            }
+            TransactionSpecification::StxTransfer(tx) => {
+                format!(
+                    "stx {} {} {}",
+                    tx.expected_sender, tx.recipient, tx.mstx_amount
+                )
+            }
+            TransactionSpecification::BnsOperation(tx) => {
+                format!("bns {} {}", tx.get_method(), tx.get_fully_qualified_name())
+            }
+        }
+    }
+}
+
+/// Whether a transaction can be included in an anchored block or in a microblock. Microblocks
+/// are not produced since the epoch 3.0, where `any` and `on-chain-only` are equivalent.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum AnchorMode {
+    #[default]
+    OnChainOnly,
+    #[serde(alias = "microblock-only")]
+    OffChainOnly,
+    #[serde(alias = "microblock-allowed")]
+    Any,
+}
+
+impl AnchorMode {
+    /// `anchor-mode` takes precedence over the legacy `anchor-block-only`
+    pub fn from_specifications(
+        anchor_mode: Option<AnchorMode>,
+        anchor_block_only: Option<bool>,
+    ) -> AnchorMode {
+        match (anchor_mode, anchor_block_only) {
+            (Some(anchor_mode), _) => anchor_mode,
+            (None, Some(false)) => AnchorMode::Any,
+            (None, _) => AnchorMode::OnChainOnly,
+        }
+    }
 }
 
 type Memo = [u8; 34];
@@ -251,7 +663,10 @@ pub struct StxTransferSpecification {
     #[serde(with = "memo_serde")]
     pub memo: Memo,
     pub cost: u64,
-    pub anchor_block_only: bool,
+    #[serde(default)]
+    pub anchor_mode: AnchorMode,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
 }
 
 pub mod memo_serde {
@@ -364,9 +779,235 @@ impl StxTransferSpecification {
             memo,
             mstx_amount: specs.mstx_amount,
             cost: specs.cost,
-            anchor_block_only: specs.anchor_block_only.unwrap_or(true),
+            anchor_mode: AnchorMode::from_specifications(
+                specs.anchor_mode,
+                specs.anchor_block_only,
+            ),
+            description: specs.description.clone(),
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub enum BnsOperation {
+    NamePreorder {
+        salt: Vec<u8>,
+        stx_to_burn: u64,
+    },
+    NameRegister {
+        salt: Vec<u8>,
+        zonefile_hash: Vec<u8>,
+    },
+    NameUpdate {
+        zonefile_hash: Vec<u8>,
+    },
+}
+
+/// A name-preorder, name-register or name-update transaction, sent to the bns boot contract
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct BnsOperationSpecification {
+    #[serde(with = "standard_principal_data_serde")]
+    pub expected_sender: StandardPrincipalData,
+    pub namespace: String,
+    pub name: String,
+    pub operation: BnsOperation,
+    pub cost: u64,
+    #[serde(default)]
+    pub anchor_mode: AnchorMode,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+fn parse_bns_label(label: &str, field: &str, max_len: usize) -> Result<String, String> {
+    if label.is_empty() || label.len() > max_len {
+        return Err(format!(
+            "unable to parse {} '{}' (1 to {} characters)",
+            field, label, max_len
+        ));
+    }
+    if !label
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+    {
+        return Err(format!(
+            "unable to parse {} '{}' (lowercase letters, digits, '-' and '_' only)",
+            field, label
+        ));
+    }
+    Ok(label.to_string())
+}
+
+fn parse_bns_buffer(value: &str, field: &str) -> Result<Vec<u8>, String> {
+    let bytes = match value.strip_prefix("0x").map(hex_bytes) {
+        Some(Ok(bytes)) if bytes.len() <= 20 => bytes,
+        _ => {
+            return Err(format!(
+                "unable to parse {} '{}' (up to 20 bytes, starting with '0x')",
+                field, value
+            ))
+        }
+    };
+    Ok(bytes)
+}
+
+impl BnsOperationSpecification {
+    fn new(
+        expected_sender: &str,
+        namespace: &str,
+        name: &str,
+        operation: BnsOperation,
+        cost: u64,
+        anchor_mode: AnchorMode,
+        description: Option<String>,
+    ) -> Result<BnsOperationSpecification, String> {
+        let expected_sender = match PrincipalData::parse_standard_principal(expected_sender) {
+            Ok(res) => res,
+            Err(_) => {
+                return Err(format!(
+                    "unable to parse expected sender '{}' as a valid Stacks address",
+                    expected_sender
+                ))
+            }
+        };
+        Ok(BnsOperationSpecification {
+            expected_sender,
+            namespace: parse_bns_label(namespace, "namespace", 20)?,
+            name: parse_bns_label(name, "name", 48)?,
+            operation,
+            cost,
+            anchor_mode,
+            description,
         })
     }
+
+    pub fn from_preorder_specifications(
+        specs: &BnsNamePreorderSpecificationFile,
+    ) -> Result<BnsOperationSpecification, String> {
+        let operation = BnsOperation::NamePreorder {
+            salt: parse_bns_buffer(&specs.salt, "salt")?,
+            stx_to_burn: specs.stx_to_burn,
+        };
+        BnsOperationSpecification::new(
+            &specs.expected_sender,
+            &specs.namespace,
+            &specs.name,
+            operation,
+            specs.cost,
+            AnchorMode::from_specifications(specs.anchor_mode, specs.anchor_block_only),
+            specs.description.clone(),
+        )
+    }
+
+    pub fn from_register_specifications(
+        specs: &BnsNameRegisterSpecificationFile,
+    ) -> Result<BnsOperationSpecification, String> {
+        let operation = BnsOperation::NameRegister {
+            salt: parse_bns_buffer(&specs.salt, "salt")?,
+            zonefile_hash: parse_bns_buffer(&specs.zonefile_hash, "zonefile hash")?,
+        };
+        BnsOperationSpecification::new(
+            &specs.expected_sender,
+            &specs.namespace,
+            &specs.name,
+            operation,
+            specs.cost,
+            AnchorMode::from_specifications(specs.anchor_mode, specs.anchor_block_only),
+            specs.description.clone(),
+        )
+    }
+
+    pub fn from_update_specifications(
+        specs: &BnsNameUpdateSpecificationFile,
+    ) -> Result<BnsOperationSpecification, String> {
+        let operation = BnsOperation::NameUpdate {
+            zonefile_hash: parse_bns_buffer(&specs.zonefile_hash, "zonefile hash")?,
+        };
+        BnsOperationSpecification::new(
+            &specs.expected_sender,
+            &specs.namespace,
+            &specs.name,
+            operation,
+            specs.cost,
+            AnchorMode::from_specifications(specs.anchor_mode, specs.anchor_block_only),
+            specs.description.clone(),
+        )
+    }
+
+    /// The bns boot contract of the network of the sender
+    pub fn get_bns_contract_id(&self) -> QualifiedContractIdentifier {
+        let address = self.expected_sender.to_address();
+        let boot_address = if address.starts_with("SP") || address.starts_with("SM") {
+            BOOT_MAINNET_ADDRESS
+        } else {
+            BOOT_TESTNET_ADDRESS
+        };
+        QualifiedContractIdentifier::parse(&format!("{}.bns", boot_address)).unwrap()
+    }
+
+    pub fn get_fully_qualified_name(&self) -> String {
+        format!("{}.{}", self.name, self.namespace)
+    }
+
+    /// Hash committed by name-preorder, and revealed by name-register
+    pub fn get_hashed_salted_fqn(&self, salt: &[u8]) -> Hash160 {
+        let mut data = self.get_fully_qualified_name().into_bytes();
+        data.extend_from_slice(salt);
+        Hash160::from_data(&data)
+    }
+
+    pub fn get_method(&self) -> ClarityName {
+        let method = match self.operation {
+            BnsOperation::NamePreorder { .. } => "name-preorder",
+            BnsOperation::NameRegister { .. } => "name-register",
+            BnsOperation::NameUpdate { .. } => "name-update",
+        };
+        ClarityName::from(method)
+    }
+
+    pub fn get_parameters(&self) -> Vec<String> {
+        let namespace = format!("0x{}", to_hex(self.namespace.as_bytes()));
+        let name = format!("0x{}", to_hex(self.name.as_bytes()));
+        match &self.operation {
+            BnsOperation::NamePreorder { salt, stx_to_burn } => vec![
+                format!("0x{}", to_hex(self.get_hashed_salted_fqn(salt).as_bytes())),
+                format!("u{}", stx_to_burn),
+            ],
+            BnsOperation::NameRegister {
+                salt,
+                zonefile_hash,
+            } => vec![
+                namespace,
+                name,
+                format!("0x{}", to_hex(salt)),
+                format!("0x{}", to_hex(zonefile_hash)),
+            ],
+            BnsOperation::NameUpdate { zonefile_hash } => {
+                vec![namespace, name, format!("0x{}", to_hex(zonefile_hash))]
+            }
+        }
+    }
+
+    pub fn as_contract_call(&self) -> ContractCallSpecification {
+        ContractCallSpecification {
+            contract_id: self.get_bns_contract_id(),
+            expected_sender: self.expected_sender.clone(),
+            method: self.get_method(),
+            parameters: self.get_parameters(),
+            cost: self.cost,
+            anchor_mode: self.anchor_mode,
+            description: self.description.clone(),
+        }
+    }
+
+    pub fn as_emulated_contract_call(&self) -> EmulatedContractCallSpecification {
+        EmulatedContractCallSpecification {
+            contract_id: self.get_bns_contract_id(),
+            emulated_sender: self.expected_sender.clone(),
+            method: self.get_method(),
+            parameters: self.get_parameters(),
+            description: self.description.clone(),
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
@@ -375,6 +1016,8 @@ pub struct BtcTransferSpecification {
     pub recipient: String,
     pub sats_amount: u64,
     pub sats_per_byte: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
 }
 
 impl BtcTransferSpecification {
@@ -387,6 +1030,7 @@ impl BtcTransferSpecification {
             recipient: specs.recipient.clone(),
             sats_amount: specs.sats_amount,
             sats_per_byte: specs.sats_per_byte,
+            description: specs.description.clone(),
         })
     }
 }
@@ -400,7 +1044,10 @@ pub struct ContractCallSpecification {
     pub method: ClarityName,
     pub parameters: Vec<String>,
     pub cost: u64,
-    pub anchor_block_only: bool,
+    #[serde(default)]
+    pub anchor_mode: AnchorMode,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
 }
 
 impl ContractCallSpecification {
@@ -444,7 +1091,11 @@ impl ContractCallSpecification {
             method,
             parameters: specs.parameters.clone(),
             cost: specs.cost,
-            anchor_block_only: specs.anchor_block_only.unwrap_or(true),
+            anchor_mode: AnchorMode::from_specifications(
+                specs.anchor_mode,
+                specs.anchor_block_only,
+            ),
+            description: specs.description.clone(),
         })
     }
 }
@@ -460,10 +1111,23 @@ pub struct ContractPublishSpecification {
     #[serde(with = "clarity_version_serde")]
     pub clarity_version: ClarityVersion,
     pub cost: u64,
-    pub anchor_block_only: bool,
+    #[serde(default)]
+    pub anchor_mode: AnchorMode,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
 }
 
 impl ContractPublishSpecification {
+    pub fn to_clarity_contract(&self, epoch: StacksEpochId) -> Result<ClarityContract, String> {
+        ClarityContract::builder()
+            .source(self.source.clone())
+            .name(self.contract_name.to_string())
+            .deployer(ContractDeployer::Address(self.expected_sender.to_address()))
+            .clarity_version(self.clarity_version)
+            .epoch(epoch)
+            .build()
+    }
+
     pub fn from_specifications(
         specs: &ContractPublishSpecificationFile,
         project_root_location: &FileLocation,
@@ -497,23 +1161,12 @@ impl ContractPublishSpecification {
         }
         .ok_or("unable to parse file location (can either be 'path' or 'url'".to_string())?;
 
-        let source = location.read_content_as_utf8()?;
+        let source = read_source_with_includes(&location)?;
 
         let clarity_version = match specs.clarity_version {
-            Some(clarity_version) => {
-                if clarity_version.eq(&1) {
-                    Ok(ClarityVersion::Clarity1)
-                } else if clarity_version.eq(&2) {
-                    Ok(ClarityVersion::Clarity2)
-                } else if clarity_version.eq(&3) {
-                    Ok(ClarityVersion::Clarity3)
-                } else {
-                    Err(
-                        "unable to parse clarity_version, it can either be '1', '2', or '3'"
-                            .to_string(),
-                    )
-                }
-            }
+            Some(clarity_version) => clarity_versions::from_version_number(clarity_version).ok_or(
+                "unable to parse clarity_version, it can either be '1', '2', or '3'".to_string(),
+            ),
             _ => Ok(DEFAULT_CLARITY_VERSION),
         }?;
 
@@ -523,8 +1176,12 @@ impl ContractPublishSpecification {
             source,
             location,
             cost: specs.cost,
-            anchor_block_only: specs.anchor_block_only.unwrap_or(true),
+            anchor_mode: AnchorMode::from_specifications(
+                specs.anchor_mode,
+                specs.anchor_block_only,
+            ),
             clarity_version,
+            description: specs.description.clone(),
         })
     }
 }
@@ -537,12 +1194,22 @@ pub struct RequirementPublishSpecification {
     pub remap_sender: StandardPrincipalData,
     #[serde(with = "remap_principals_serde")]
     pub remap_principals: BTreeMap<StandardPrincipalData, StandardPrincipalData>,
+    /// Name of the published contract, when the name of the requirement is already taken
+    /// under the remap sender
+    pub remap_contract_name: Option<ContractName>,
+    /// Rewrite the remapped principals of the AST only, instead of every occurrence of
+    /// their address in the source
+    pub rewrite_literal_principals: bool,
     #[serde(with = "source_serde")]
     pub source: String,
     #[serde(with = "clarity_version_serde")]
     pub clarity_version: ClarityVersion,
     pub cost: u64,
     pub location: FileLocation,
+    #[serde(default)]
+    pub anchor_mode: AnchorMode,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
 }
 
 pub mod source_serde {
@@ -624,7 +1291,7 @@ pub mod qualified_contract_identifier_serde {
 pub mod remap_principals_serde {
     use clarity_repl::clarity::vm::types::{PrincipalData, StandardPrincipalData};
     use serde::{ser::SerializeMap, Deserializer, Serializer};
-    use std::collections::{BTreeMap, HashMap};
+    use std::collections::BTreeMap;
 
     pub fn serialize<S>(
         target: &BTreeMap<StandardPrincipalData, StandardPrincipalData>,
@@ -646,7 +1313,7 @@ pub mod remap_principals_serde {
     where
         D: Deserializer<'de>,
     {
-        let container: HashMap<String, String> = serde::Deserialize::deserialize(des)?;
+        let container: BTreeMap<String, String> = serde::Deserialize::deserialize(des)?;
         let mut m = BTreeMap::new();
         for (k, v) in container {
             m.insert(
@@ -661,17 +1328,14 @@ pub mod remap_principals_serde {
 pub mod clarity_version_serde {
     use clarinet_files::INVALID_CLARITY_VERSION;
     use clarity_repl::clarity::ClarityVersion;
+    use clarity_repl::repl::clarity_versions;
     use serde::{Deserialize, Deserializer, Serializer};
 
     pub fn serialize<S>(clarity_version: &ClarityVersion, s: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
-        match clarity_version {
-            ClarityVersion::Clarity1 => s.serialize_i64(1),
-            ClarityVersion::Clarity2 => s.serialize_i64(2),
-            ClarityVersion::Clarity3 => s.serialize_i64(3),
-        }
+        s.serialize_i64(clarity_versions::version_number(*clarity_version).into())
     }
 
     pub fn deserialize<'de, D>(deserializer: D) -> Result<ClarityVersion, D::Error>
@@ -679,16 +1343,34 @@ pub mod clarity_version_serde {
         D: Deserializer<'de>,
     {
         let cv = i64::deserialize(deserializer)?;
-        match cv {
-            1 => Ok(ClarityVersion::Clarity1),
-            2 => Ok(ClarityVersion::Clarity2),
-            3 => Ok(ClarityVersion::Clarity3),
-            _ => Err(serde::de::Error::custom(INVALID_CLARITY_VERSION)),
-        }
+        u8::try_from(cv)
+            .ok()
+            .and_then(clarity_versions::from_version_number)
+            .ok_or_else(|| serde::de::Error::custom(INVALID_CLARITY_VERSION))
     }
 }
 
 impl RequirementPublishSpecification {
+    pub fn to_clarity_contract(&self, epoch: StacksEpochId) -> Result<ClarityContract, String> {
+        ClarityContract::builder()
+            .source(self.source.clone())
+            .deployer(ContractDeployer::ContractIdentifier(
+                self.contract_id.clone(),
+            ))
+            .clarity_version(self.clarity_version)
+            .epoch(epoch)
+            .build()
+    }
+
+    /// Identifier of the requirement once published by the remap sender
+    pub fn get_remapped_contract_id(&self) -> QualifiedContractIdentifier {
+        let name = self
+            .remap_contract_name
+            .as_ref()
+            .unwrap_or(&self.contract_id.name);
+        QualifiedContractIdentifier::new(self.remap_sender.clone(), name.clone())
+    }
+
     pub fn from_specifications(
         specs: &RequirementPublishSpecificationFile,
         project_root_location: &FileLocation,
@@ -738,6 +1420,19 @@ impl RequirementPublishSpecification {
             }
         }
 
+        let remap_contract_name = match specs.remap_contract_name {
+            Some(ref name) => match ContractName::try_from(name.to_string()) {
+                Ok(res) => Some(res),
+                Err(_) => {
+                    return Err(format!(
+                        "unable to parse remap contract name '{}' as a valid contract name",
+                        name
+                    ))
+                }
+            },
+            None => None,
+        };
+
         let location = match (&specs.path, &specs.url) {
             (Some(location_string), None) | (None, Some(location_string)) => {
                 FileLocation::try_parse(location_string, Some(project_root_location))
@@ -749,20 +1444,9 @@ impl RequirementPublishSpecification {
         let source = location.read_content_as_utf8()?;
 
         let clarity_version = match specs.clarity_version {
-            Some(clarity_version) => {
-                if clarity_version.eq(&1) {
-                    Ok(ClarityVersion::Clarity1)
-                } else if clarity_version.eq(&2) {
-                    Ok(ClarityVersion::Clarity2)
-                } else if clarity_version.eq(&3) {
-                    Ok(ClarityVersion::Clarity3)
-                } else {
-                    Err(
-                        "unable to parse clarity_version, it can either be '1', '2', or '3'"
-                            .to_string(),
-                    )
-                }
-            }
+            Some(clarity_version) => clarity_versions::from_version_number(clarity_version).ok_or(
+                "unable to parse clarity_version, it can either be '1', '2', or '3'".to_string(),
+            ),
             _ => Ok(DEFAULT_CLARITY_VERSION),
         }?;
 
@@ -770,10 +1454,14 @@ impl RequirementPublishSpecification {
             contract_id,
             remap_sender,
             remap_principals,
+            remap_contract_name,
+            rewrite_literal_principals: specs.rewrite_literal_principals.unwrap_or(false),
             source,
             clarity_version,
             location,
             cost: specs.cost,
+            anchor_mode: specs.anchor_mode.unwrap_or_default(),
+            description: specs.description.clone(),
         })
     }
 }
@@ -785,6 +1473,8 @@ pub struct EmulatedContractCallSpecification {
     pub emulated_sender: StandardPrincipalData,
     pub method: ClarityName,
     pub parameters: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
 }
 
 impl EmulatedContractCallSpecification {
@@ -827,6 +1517,42 @@ impl EmulatedContractCallSpecification {
             emulated_sender,
             method,
             parameters: specs.parameters.clone(),
+            description: specs.description.clone(),
+        })
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
+pub struct EmulatedEvalSpecification {
+    #[serde(with = "standard_principal_data_serde")]
+    pub emulated_sender: StandardPrincipalData,
+    pub code: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+impl EmulatedEvalSpecification {
+    pub fn from_specifications(
+        specs: &EmulatedEvalSpecificationFile,
+    ) -> Result<EmulatedEvalSpecification, String> {
+        let emulated_sender = match PrincipalData::parse_standard_principal(&specs.emulated_sender)
+        {
+            Ok(res) => res,
+            Err(_) => {
+                return Err(format!(
+                    "unable to parse emulated sender '{}' as a valid Stacks address",
+                    specs.emulated_sender
+                ))
+            }
+        };
+        if specs.code.trim().is_empty() {
+            return Err("emulated-eval: the code can't be empty".to_string());
+        }
+
+        Ok(EmulatedEvalSpecification {
+            emulated_sender,
+            code: specs.code.clone(),
+            description: specs.description.clone(),
         })
     }
 }
@@ -840,9 +1566,61 @@ pub struct EmulatedContractPublishSpecification {
     #[serde(with = "clarity_version_serde")]
     pub clarity_version: ClarityVersion,
     pub location: FileLocation,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
 }
 
 impl EmulatedContractPublishSpecification {
+    pub fn to_clarity_contract(&self, epoch: StacksEpochId) -> Result<ClarityContract, String> {
+        ClarityContract::builder()
+            .source(self.source.clone())
+            .name(self.contract_name.to_string())
+            .deployer(ContractDeployer::Address(self.emulated_sender.to_string()))
+            .clarity_version(self.clarity_version)
+            .epoch(epoch)
+            .build()
+    }
+
+    /// The contract must have its source in memory and a resolved deployer
+    pub fn from_clarity_contract(
+        contract: &ClarityContract,
+        location: FileLocation,
+    ) -> Result<EmulatedContractPublishSpecification, String> {
+        let ClarityCodeSource::ContractInMemory(source) = &contract.code_source else {
+            return Err(format!(
+                "source of contract {} expected to be in memory",
+                contract.name
+            ));
+        };
+        let emulated_sender = match &contract.deployer {
+            ContractDeployer::Address(address) => PrincipalData::parse_standard_principal(address)
+                .map_err(|_| {
+                    format!(
+                        "unable to parse emulated sender '{}' as a valid Stacks address",
+                        address
+                    )
+                })?,
+            ContractDeployer::ContractIdentifier(contract_id) => contract_id.issuer.clone(),
+            ContractDeployer::Transient => StandardPrincipalData::transient(),
+            _ => {
+                return Err(format!(
+                    "deployer of contract {} expected to be resolved",
+                    contract.name
+                ))
+            }
+        };
+        contract.validate()?;
+        Ok(EmulatedContractPublishSpecification {
+            contract_name: ContractName::try_from(contract.name.clone())
+                .map_err(|_| format!("invalid contract name {}", contract.name))?,
+            emulated_sender,
+            source: source.clone(),
+            clarity_version: contract.clarity_version,
+            location,
+            description: None,
+        })
+    }
+
     pub fn from_specifications(
         specs: &EmulatedContractPublishSpecificationFile,
         project_root_location: &FileLocation,
@@ -878,26 +1656,15 @@ impl EmulatedContractPublishSpecification {
         .ok_or("unable to parse file location (can either be 'path' or 'url'".to_string())?;
 
         let clarity_version = match specs.clarity_version {
-            Some(clarity_version) => {
-                if clarity_version.eq(&1) {
-                    Ok(ClarityVersion::Clarity1)
-                } else if clarity_version.eq(&2) {
-                    Ok(ClarityVersion::Clarity2)
-                } else if clarity_version.eq(&3) {
-                    Ok(ClarityVersion::Clarity3)
-                } else {
-                    Err(
-                        "unable to parse clarity_version, it can either be '1', '2', or '3'"
-                            .to_string(),
-                    )
-                }
-            }
+            Some(clarity_version) => clarity_versions::from_version_number(clarity_version).ok_or(
+                "unable to parse clarity_version, it can either be '1', '2', or '3'".to_string(),
+            ),
             _ => Ok(DEFAULT_CLARITY_VERSION),
         }?;
 
         let source = match source {
             Some(source) => source,
-            None => location.read_content_as_utf8()?,
+            None => read_source_with_includes(&location)?,
         };
 
         Ok(EmulatedContractPublishSpecification {
@@ -906,6 +1673,7 @@ impl EmulatedContractPublishSpecification {
             source,
             location,
             clarity_version,
+            description: specs.description.clone(),
         })
     }
 }
@@ -914,6 +1682,9 @@ impl EmulatedContractPublishSpecification {
 pub struct DeploymentSpecification {
     pub id: u32,
     pub name: String,
+    /// Free-form notes on the plan: the release, the governance proposal
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
     pub network: StacksNetwork,
     pub stacks_node: Option<String>,
     pub bitcoin_node: Option<String>,
@@ -930,7 +1701,7 @@ pub mod contracts_serde {
     use clarinet_files::FileLocation;
     use clarity_repl::clarity::vm::types::QualifiedContractIdentifier;
     use serde::{ser::SerializeSeq, Deserializer, Serializer};
-    use std::collections::{BTreeMap, HashMap};
+    use std::collections::BTreeMap;
 
     use super::source_serde;
 
@@ -966,7 +1737,7 @@ pub mod contracts_serde {
     {
         let mut res: BTreeMap<QualifiedContractIdentifier, (String, FileLocation)> =
             BTreeMap::new();
-        let container: Vec<HashMap<String, String>> = serde::Deserialize::deserialize(des)?;
+        let container: Vec<BTreeMap<String, String>> = serde::Deserialize::deserialize(des)?;
 
         for entry in container {
             let contract_id = match entry.get("contract_id") {
@@ -1004,30 +1775,30 @@ pub mod contracts_serde {
 }
 
 impl DeploymentSpecification {
+    /// A plan without transactions, used to boot a network without deploying the project
+    pub fn empty(network: &StacksNetwork) -> DeploymentSpecification {
+        DeploymentSpecification {
+            id: 0,
+            name: "Empty deployment".to_string(),
+            network: network.clone(),
+            stacks_node: None,
+            bitcoin_node: None,
+            genesis: None,
+            plan: TransactionPlanSpecification {
+                batches: vec![],
+                post_checks: vec![],
+            },
+            contracts: BTreeMap::new(),
+            description: None,
+        }
+    }
+
     pub fn from_config_file(
         deployment_location: &FileLocation,
         project_root_location: &FileLocation,
     ) -> Result<DeploymentSpecification, String> {
-        let spec_file_content = deployment_location.read_content()?;
-
-        let specification_file: DeploymentSpecificationFile =
-            match serde_yaml::from_slice(&spec_file_content[..]) {
-                Ok(res) => res,
-                Err(msg) => return Err(format!("unable to read file {}", msg)),
-            };
-
-        let network = match specification_file.network.to_lowercase().as_str() {
-            "simnet" => StacksNetwork::Simnet,
-            "devnet" => StacksNetwork::Devnet,
-            "testnet" => StacksNetwork::Testnet,
-            "mainnet" => StacksNetwork::Mainnet,
-            _ => {
-                return Err(format!(
-                    "network '{}' not supported (simnet, devnet, testnet, mainnet)",
-                    specification_file.network
-                ));
-            }
-        };
+        let specification_file = DeploymentSpecificationFile::from_location(deployment_location)?;
+        let network = specification_file.get_network()?;
 
         let deployment_spec = DeploymentSpecification::from_specifications(
             &specification_file,
@@ -1058,6 +1829,9 @@ impl DeploymentSpecification {
                                 TransactionSpecificationFile::EmulatedContractCall(spec) => {
                                     TransactionSpecification::EmulatedContractCall(EmulatedContractCallSpecification::from_specifications(spec)?)
                                 }
+                                TransactionSpecificationFile::EmulatedEval(spec) => {
+                                    TransactionSpecification::EmulatedEval(EmulatedEvalSpecification::from_specifications(spec)?)
+                                }
                                 TransactionSpecificationFile::EmulatedContractPublish(spec) => {
                                     let source = contracts_sources.as_ref().map(|contracts_sources| {
                                         let contract_path = FileLocation::try_parse(spec.path.as_ref().expect("missing path"), Some(project_root_location))
@@ -1077,6 +1851,15 @@ impl DeploymentSpecification {
                                     let spec = StxTransferSpecification::from_specifications(spec)?;
                                     TransactionSpecification::StxTransfer(spec)
                                 }
+                                TransactionSpecificationFile::BnsNamePreorder(spec) => {
+                                    TransactionSpecification::BnsOperation(BnsOperationSpecification::from_preorder_specifications(spec)?)
+                                }
+                                TransactionSpecificationFile::BnsNameRegister(spec) => {
+                                    TransactionSpecification::BnsOperation(BnsOperationSpecification::from_register_specifications(spec)?)
+                                }
+                                TransactionSpecificationFile::BnsNameUpdate(spec) => {
+                                    TransactionSpecification::BnsOperation(BnsOperationSpecification::from_update_specifications(spec)?)
+                                }
                                 TransactionSpecificationFile::BtcTransfer(_) | TransactionSpecificationFile::ContractCall(_) | TransactionSpecificationFile::ContractPublish(_) | TransactionSpecificationFile::RequirementPublish(_) => {
                                     return Err(format!("{} only supports transactions of type 'emulated-contract-call' and 'emulated-contract-publish", specs.network.to_lowercase()))
                                 }
@@ -1087,6 +1870,10 @@ impl DeploymentSpecification {
                             id: batch.id,
                             transactions,
                             epoch: batch.epoch,
+                            post_checks: PostCheckSpecification::from_specifications_list(
+                                &batch.post_checks,
+                            )?,
+                            description: batch.description.clone(),
                         });
                     }
                 }
@@ -1094,7 +1881,19 @@ impl DeploymentSpecification {
                     let genesis_specs = GenesisSpecification::from_specifications(genesis_specs)?;
                     genesis = Some(genesis_specs);
                 }
-                (TransactionPlanSpecification { batches }, genesis)
+                let post_checks = match specs.plan {
+                    Some(ref plan) => {
+                        PostCheckSpecification::from_specifications_list(&plan.post_checks)?
+                    }
+                    None => vec![],
+                };
+                (
+                    TransactionPlanSpecification {
+                        batches,
+                        post_checks,
+                    },
+                    genesis,
+                )
             }
             StacksNetwork::Devnet | StacksNetwork::Testnet | StacksNetwork::Mainnet => {
                 let mut batches = vec![];
@@ -1128,7 +1927,16 @@ impl DeploymentSpecification {
                                     let spec = StxTransferSpecification::from_specifications(spec)?;
                                     TransactionSpecification::StxTransfer(spec)
                                 }
-                                TransactionSpecificationFile::EmulatedContractCall(_) | TransactionSpecificationFile::EmulatedContractPublish(_) => {
+                                TransactionSpecificationFile::BnsNamePreorder(spec) => {
+                                    TransactionSpecification::BnsOperation(BnsOperationSpecification::from_preorder_specifications(spec)?)
+                                }
+                                TransactionSpecificationFile::BnsNameRegister(spec) => {
+                                    TransactionSpecification::BnsOperation(BnsOperationSpecification::from_register_specifications(spec)?)
+                                }
+                                TransactionSpecificationFile::BnsNameUpdate(spec) => {
+                                    TransactionSpecification::BnsOperation(BnsOperationSpecification::from_update_specifications(spec)?)
+                                }
+                                TransactionSpecificationFile::EmulatedContractCall(_) | TransactionSpecificationFile::EmulatedContractPublish(_) | TransactionSpecificationFile::EmulatedEval(_) => {
                                     return Err(format!("{} only supports transactions of type 'contract-call' and 'contract-publish'", specs.network.to_lowercase()))
                                 }
                             };
@@ -1138,10 +1946,26 @@ impl DeploymentSpecification {
                             id: batch.id,
                             transactions,
                             epoch: batch.epoch,
+                            post_checks: PostCheckSpecification::from_specifications_list(
+                                &batch.post_checks,
+                            )?,
+                            description: batch.description.clone(),
                         });
                     }
                 }
-                (TransactionPlanSpecification { batches }, None)
+                let post_checks = match specs.plan {
+                    Some(ref plan) => {
+                        PostCheckSpecification::from_specifications_list(&plan.post_checks)?
+                    }
+                    None => vec![],
+                };
+                (
+                    TransactionPlanSpecification {
+                        batches,
+                        post_checks,
+                    },
+                    None,
+                )
             }
         };
         let stacks_node = match (&specs.stacks_node, &specs.node) {
@@ -1162,6 +1986,7 @@ impl DeploymentSpecification {
             genesis,
             plan,
             contracts,
+            description: specs.description.clone(),
         })
     }
 
@@ -1179,13 +2004,42 @@ impl DeploymentSpecification {
             bitcoin_node: self.bitcoin_node.clone(),
             node: None,
             genesis: self.genesis.as_ref().map(|g| g.to_specification_file()),
+            remapped_contracts: {
+                let remapped_contracts = self.get_remapped_contracts();
+                if remapped_contracts.is_empty() {
+                    None
+                } else {
+                    Some(
+                        remapped_contracts
+                            .iter()
+                            .map(|(src, dst)| (src.to_string(), dst.to_string()))
+                            .collect(),
+                    )
+                }
+            },
             plan: Some(self.plan.to_specification_file()),
+            description: self.description.clone(),
+        }
+    }
+
+    /// Identifiers of the requirements published by the plan, and their published identifiers
+    pub fn get_remapped_contracts(
+        &self,
+    ) -> BTreeMap<QualifiedContractIdentifier, QualifiedContractIdentifier> {
+        let mut remapped_contracts = BTreeMap::new();
+        for batch in self.plan.batches.iter() {
+            for tx in batch.transactions.iter() {
+                if let TransactionSpecification::RequirementPublish(tx) = tx {
+                    remapped_contracts
+                        .insert(tx.contract_id.clone(), tx.get_remapped_contract_id());
+                }
+            }
         }
+        remapped_contracts
     }
 
     pub fn to_file_content(&self) -> Result<Vec<u8>, String> {
-        serde_yaml::to_vec(&self.to_specification_file())
-            .map_err(|err| format!("failed to serialize deployment\n{}", err))
+        self.to_specification_file().to_file_content()
     }
 
     pub fn sort_batches_by_epoch(&mut self) {
@@ -1214,6 +2068,8 @@ impl DeploymentSpecification {
                     id: batch.id,
                     transactions: custom_txs,
                     epoch: batch.epoch,
+                    post_checks: vec![],
+                    description: batch.description.clone(),
                 });
             }
         }
@@ -1226,6 +2082,39 @@ impl DeploymentSpecification {
         (deployment_only_contract_publish_txs, custom_txs_batches)
     }
 
+    /// Copies the descriptions of a previous version of the plan, for the regenerated plans
+    /// to keep the notes written in the file. The batches are matched by id, the transactions
+    /// by what they do.
+    pub fn keep_descriptions(&mut self, previous: &DeploymentSpecification) {
+        if self.description.is_none() {
+            self.description.clone_from(&previous.description);
+        }
+        let mut descriptions = BTreeMap::new();
+        for batch in previous.plan.batches.iter() {
+            for tx in batch.transactions.iter() {
+                if let Some(description) = tx.get_description() {
+                    descriptions.insert(tx.get_key(), description.clone());
+                }
+            }
+        }
+        for batch in self.plan.batches.iter_mut() {
+            if batch.description.is_none() {
+                batch.description = previous
+                    .plan
+                    .batches
+                    .iter()
+                    .find(|previous_batch| previous_batch.id == batch.id)
+                    .and_then(|previous_batch| previous_batch.description.clone());
+            }
+            for tx in batch.transactions.iter_mut() {
+                let description = descriptions.get(&tx.get_key()).cloned();
+                if let (Some(description), None) = (description, tx.get_description()) {
+                    tx.set_description(description);
+                }
+            }
+        }
+    }
+
     pub fn merge_batches(&mut self, custom_batches: Vec<TransactionsBatchSpecification>) {
         for custom_batch in custom_batches {
             if let Some(batch) = self
@@ -1248,6 +2137,8 @@ impl DeploymentSpecification {
 pub struct DeploymentSpecificationFile {
     pub id: Option<u32>,
     pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
     pub network: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub stacks_node: Option<String>,
@@ -1257,6 +2148,10 @@ pub struct DeploymentSpecificationFile {
     pub node: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub genesis: Option<GenesisSpecificationFile>,
+    /// Requirements published under another identifier (devnet and testnet), for the
+    /// tools reading the plan. Informative only, the plan transactions are authoritative.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remapped_contracts: Option<BTreeMap<String, String>>,
     pub plan: Option<TransactionPlanSpecificationFile>,
 }
 
@@ -1276,6 +2171,127 @@ impl DeploymentSpecificationFile {
         serde_yaml::from_str(spec_file_content)
             .map_err(|msg| format!("unable to read file {}", msg))
     }
+
+    pub fn from_location(location: &FileLocation) -> Result<DeploymentSpecificationFile, String> {
+        let spec_file_content = location.read_content()?;
+        DeploymentSpecificationFile::from_content(&spec_file_content)
+    }
+
+    pub fn from_content(content: &[u8]) -> Result<DeploymentSpecificationFile, String> {
+        serde_yaml::from_slice(content).map_err(|msg| format!("unable to read file {}", msg))
+    }
+
+    pub fn get_network(&self) -> Result<StacksNetwork, String> {
+        match self.network.to_lowercase().as_str() {
+            "simnet" => Ok(StacksNetwork::Simnet),
+            "devnet" => Ok(StacksNetwork::Devnet),
+            "testnet" => Ok(StacksNetwork::Testnet),
+            "mainnet" => Ok(StacksNetwork::Mainnet),
+            _ => Err(format!(
+                "network '{}' not supported (simnet, devnet, testnet, mainnet)",
+                self.network
+            )),
+        }
+    }
+
+    /// Calls `f` with the fields of the transactions holding a principal, and whether the
+    /// field is a Clarity expression (the arguments of the contract calls)
+    fn visit_principals(
+        &mut self,
+        f: &mut dyn FnMut(&mut String, bool) -> Result<(), String>,
+    ) -> Result<(), String> {
+        let Some(ref mut plan) = self.plan else {
+            return Ok(());
+        };
+        for batch in plan.batches.iter_mut() {
+            for transaction in batch.transactions.iter_mut() {
+                match transaction {
+                    TransactionSpecificationFile::ContractCall(tx) => {
+                        f(&mut tx.contract_id, false)?;
+                        f(&mut tx.expected_sender, false)?;
+                        for parameter in tx.parameters.iter_mut() {
+                            f(parameter, true)?;
+                        }
+                    }
+                    TransactionSpecificationFile::EmulatedContractCall(tx) => {
+                        f(&mut tx.contract_id, false)?;
+                        f(&mut tx.emulated_sender, false)?;
+                        for parameter in tx.parameters.iter_mut() {
+                            f(parameter, true)?;
+                        }
+                    }
+                    TransactionSpecificationFile::ContractPublish(tx) => {
+                        f(&mut tx.expected_sender, false)?
+                    }
+                    TransactionSpecificationFile::EmulatedContractPublish(tx) => {
+                        f(&mut tx.emulated_sender, false)?
+                    }
+                    TransactionSpecificationFile::EmulatedEval(tx) => {
+                        f(&mut tx.emulated_sender, false)?
+                    }
+                    TransactionSpecificationFile::RequirementPublish(tx) => {
+                        f(&mut tx.contract_id, false)?;
+                        f(&mut tx.remap_sender, false)?;
+                        if let Some(remap_principals) = tx.remap_principals.take() {
+                            let mut resolved = BTreeMap::new();
+                            for (mut src, mut dst) in remap_principals {
+                                f(&mut src, false)?;
+                                f(&mut dst, false)?;
+                                resolved.insert(src, dst);
+                            }
+                            tx.remap_principals = Some(resolved);
+                        }
+                    }
+                    TransactionSpecificationFile::StxTransfer(tx) => {
+                        f(&mut tx.expected_sender, false)?;
+                        f(&mut tx.recipient, false)?;
+                    }
+                    TransactionSpecificationFile::BnsNamePreorder(tx) => {
+                        f(&mut tx.expected_sender, false)?
+                    }
+                    TransactionSpecificationFile::BnsNameRegister(tx) => {
+                        f(&mut tx.expected_sender, false)?
+                    }
+                    TransactionSpecificationFile::BnsNameUpdate(tx) => {
+                        f(&mut tx.expected_sender, false)?
+                    }
+                    // bitcoin addresses
+                    TransactionSpecificationFile::BtcTransfer(_) => {}
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether the transactions refer to principals by name (`@deployer`)
+    pub fn has_named_principals(&mut self) -> bool {
+        let mut found = false;
+        let _ = self.visit_principals(&mut |field, is_expression| {
+            found |= if is_expression {
+                field.contains("'@")
+            } else {
+                field.starts_with('@')
+            };
+            Ok(())
+        });
+        found
+    }
+
+    /// Replaces the principals referred to by name in the transactions
+    pub fn resolve_named_principals(&mut self, address_book: &AddressBook) -> Result<(), String> {
+        self.visit_principals(&mut |field, is_expression| {
+            *field = if is_expression {
+                address_book.resolve_expression(field)?
+            } else {
+                address_book.resolve_principal(field)?
+            };
+            Ok(())
+        })
+    }
+
+    pub fn to_file_content(&self) -> Result<Vec<u8>, String> {
+        serde_yaml::to_vec(self).map_err(|err| format!("failed to serialize deployment\n{}", err))
+    }
 }
 
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
@@ -1385,7 +2401,9 @@ impl TransactionPlanSpecification {
                             method: tx.method.to_string(),
                             parameters: tx.parameters.clone(),
                             cost: tx.cost,
-                            anchor_block_only: Some(tx.anchor_block_only),
+                            anchor_block_only: None,
+                            anchor_mode: Some(tx.anchor_mode),
+                            description: tx.description.clone(),
                         })
                     }
                     TransactionSpecification::ContractPublish(tx) => {
@@ -1397,12 +2415,14 @@ impl TransactionPlanSpecification {
                                 path: None,
                                 url: None,
                                 cost: tx.cost,
-                                anchor_block_only: Some(tx.anchor_block_only),
-                                clarity_version: match tx.clarity_version {
-                                    ClarityVersion::Clarity1 => Some(1),
-                                    ClarityVersion::Clarity2 => Some(2),
-                                    ClarityVersion::Clarity3 => Some(3),
-                                },
+                                anchor_block_only: None,
+                                anchor_mode: Some(tx.anchor_mode),
+                                clarity_version: Some(clarity_versions::version_number(
+                                    tx.clarity_version,
+                                )),
+                                txid: None,
+                                block_height: None,
+                                description: tx.description.clone(),
                             },
                         )
                     }
@@ -1413,9 +2433,17 @@ impl TransactionPlanSpecification {
                                 emulated_sender: tx.emulated_sender.to_address(),
                                 method: tx.method.to_string(),
                                 parameters: tx.parameters.clone(),
+                                description: tx.description.clone(),
                             },
                         )
                     }
+                    TransactionSpecification::EmulatedEval(tx) => {
+                        TransactionSpecificationFile::EmulatedEval(EmulatedEvalSpecificationFile {
+                            emulated_sender: tx.emulated_sender.to_address(),
+                            code: tx.code.clone(),
+                            description: tx.description.clone(),
+                        })
+                    }
                     TransactionSpecification::EmulatedContractPublish(tx) => {
                         TransactionSpecificationFile::EmulatedContractPublish(
                             EmulatedContractPublishSpecificationFile {
@@ -1424,11 +2452,10 @@ impl TransactionPlanSpecification {
                                 location: Some(tx.location.clone()),
                                 path: None,
                                 url: None,
-                                clarity_version: match tx.clarity_version {
-                                    ClarityVersion::Clarity1 => Some(1),
-                                    ClarityVersion::Clarity2 => Some(2),
-                                    ClarityVersion::Clarity3 => Some(3),
-                                },
+                                clarity_version: Some(clarity_versions::version_number(
+                                    tx.clarity_version,
+                                )),
+                                description: tx.description.clone(),
                             },
                         )
                     }
@@ -1442,15 +2469,23 @@ impl TransactionPlanSpecification {
                                 contract_id: tx.contract_id.to_string(),
                                 remap_sender: tx.remap_sender.to_address(),
                                 remap_principals: Some(remap_principals),
+                                remap_contract_name: tx
+                                    .remap_contract_name
+                                    .as_ref()
+                                    .map(|name| name.to_string()),
+                                rewrite_literal_principals: tx
+                                    .rewrite_literal_principals
+                                    .then_some(true),
                                 location: Some(tx.location.clone()),
                                 path: None,
                                 url: None,
                                 cost: tx.cost,
-                                clarity_version: match tx.clarity_version {
-                                    ClarityVersion::Clarity1 => Some(1),
-                                    ClarityVersion::Clarity2 => Some(2),
-                                    ClarityVersion::Clarity3 => Some(3),
-                                },
+                                anchor_mode: Some(tx.anchor_mode)
+                                    .filter(|mode| *mode != AnchorMode::OnChainOnly),
+                                clarity_version: Some(clarity_versions::version_number(
+                                    tx.clarity_version,
+                                )),
+                                description: tx.description.clone(),
                             },
                         )
                     }
@@ -1460,6 +2495,7 @@ impl TransactionPlanSpecification {
                             recipient: tx.recipient.clone(),
                             sats_amount: tx.sats_amount,
                             sats_per_byte: tx.sats_per_byte,
+                            description: tx.description.clone(),
                         })
                     }
                     TransactionSpecification::StxTransfer(tx) => {
@@ -1473,9 +2509,65 @@ impl TransactionPlanSpecification {
                                 Some(format!("0x{}", to_hex(&tx.memo)))
                             },
                             cost: tx.cost,
-                            anchor_block_only: Some(tx.anchor_block_only),
+                            anchor_block_only: None,
+                            anchor_mode: Some(tx.anchor_mode),
+                            description: tx.description.clone(),
                         })
                     }
+                    TransactionSpecification::BnsOperation(tx) => {
+                        let expected_sender = tx.expected_sender.to_address();
+                        let namespace = tx.namespace.clone();
+                        let name = tx.name.clone();
+                        let cost = tx.cost;
+                        let anchor_mode = Some(tx.anchor_mode);
+                        match &tx.operation {
+                            BnsOperation::NamePreorder { salt, stx_to_burn } => {
+                                TransactionSpecificationFile::BnsNamePreorder(
+                                    BnsNamePreorderSpecificationFile {
+                                        expected_sender,
+                                        namespace,
+                                        name,
+                                        salt: format!("0x{}", to_hex(salt)),
+                                        stx_to_burn: *stx_to_burn,
+                                        cost,
+                                        anchor_block_only: None,
+                                        anchor_mode,
+                                        description: tx.description.clone(),
+                                    },
+                                )
+                            }
+                            BnsOperation::NameRegister {
+                                salt,
+                                zonefile_hash,
+                            } => TransactionSpecificationFile::BnsNameRegister(
+                                BnsNameRegisterSpecificationFile {
+                                    expected_sender,
+                                    namespace,
+                                    name,
+                                    salt: format!("0x{}", to_hex(salt)),
+                                    zonefile_hash: format!("0x{}", to_hex(zonefile_hash)),
+                                    cost,
+                                    anchor_block_only: None,
+                                    anchor_mode,
+                                    description: tx.description.clone(),
+                                },
+                            ),
+                            BnsOperation::NameUpdate { zonefile_hash } => {
+                                TransactionSpecificationFile::BnsNameUpdate(
+                                    BnsNameUpdateSpecificationFile {
+                                        expected_sender,
+                                        namespace,
+                                        name,
+                                        zonefile_hash: format!("0x{}", to_hex(zonefile_hash)),
+                                        cost,
+                                        anchor_block_only: None,
+                                        anchor_mode,
+                                        description: tx.description.clone(),
+                                    },
+                                )
+                            }
+                        }
+                    }
                 };
                 transactions.push(tx);
             }
@@ -1484,9 +2576,14 @@ impl TransactionPlanSpecification {
                 id: batch.id,
                 transactions,
                 epoch: batch.epoch,
+                post_checks: post_checks_to_specification_file(&batch.post_checks),
+                description: batch.description.clone(),
             });
         }
 
-        TransactionPlanSpecificationFile { batches }
+        TransactionPlanSpecificationFile {
+            batches,
+            post_checks: post_checks_to_specification_file(&self.post_checks),
+        }
     }
 }
@@ -0,0 +1,452 @@
+//! Impact of the modification of a contract on the contracts and accounts calling it.
+//! The public and read-only functions of the previous and current versions are compared,
+//! then the `contract-call?` of the dependents and the mainnet transactions are matched
+//! against the removed and modified functions.
+//! Dynamic calls, made through a trait, can't be attributed to a contract and are ignored.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use clarity_repl::clarity::ast::build_ast_with_diagnostics;
+use clarity_repl::clarity::vm::types::{PrincipalData, QualifiedContractIdentifier, Value};
+use clarity_repl::clarity::vm::{ClarityVersion, SymbolicExpression, SymbolicExpressionType};
+use clarity_repl::clarity::StacksEpochId;
+use clarity_repl::repl::{DEFAULT_CLARITY_VERSION, DEFAULT_EPOCH};
+
+use crate::types::DeploymentGenerationArtifacts;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FunctionAccess {
+    Public,
+    ReadOnly,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FunctionSignature {
+    pub name: String,
+    pub access: FunctionAccess,
+    /// Names and types of the arguments
+    pub args: Vec<(String, String)>,
+}
+
+impl fmt::Display for FunctionSignature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let keyword = match self.access {
+            FunctionAccess::Public => "define-public",
+            FunctionAccess::ReadOnly => "define-read-only",
+        };
+        write!(f, "({} ({}", keyword, self.name)?;
+        for (name, arg_type) in self.args.iter() {
+            write!(f, " ({} {})", name, arg_type)?;
+        }
+        write!(f, "))")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case", tag = "kind")]
+pub enum FunctionChange {
+    Removed {
+        function: String,
+    },
+    SignatureChanged {
+        function: String,
+        previous: String,
+        current: String,
+    },
+}
+
+impl FunctionChange {
+    pub fn function(&self) -> &str {
+        match self {
+            FunctionChange::Removed { function } => function,
+            FunctionChange::SignatureChanged { function, .. } => function,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ContractCallReference {
+    pub function: String,
+    pub line: u32,
+    pub arguments: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AffectedDependent {
+    pub contract_id: String,
+    /// Calls to removed or modified functions
+    pub calls: Vec<ContractCallReference>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MainnetCaller {
+    /// Sender of the transactions, or contract calling the upgraded contract
+    pub caller: String,
+    /// Functions called; unknown when the calls go through another contract
+    pub functions: BTreeSet<String>,
+    pub transactions: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UpgradeImpact {
+    pub contract_id: String,
+    pub changes: Vec<FunctionChange>,
+    pub added_functions: Vec<String>,
+    pub dependents: Vec<String>,
+    pub affected_dependents: Vec<AffectedDependent>,
+    pub mainnet_callers: Vec<MainnetCaller>,
+}
+
+/// Types are formatted without the padding of the `SymbolicExpression` display
+fn format_type(expression: &SymbolicExpression) -> String {
+    match expression.match_list() {
+        Some(list) => {
+            let items: Vec<String> = list.iter().map(format_type).collect();
+            format!("({})", items.join(" "))
+        }
+        None => expression.to_string(),
+    }
+}
+
+fn get_function_signature(
+    access: FunctionAccess,
+    args: &[SymbolicExpression],
+) -> Option<FunctionSignature> {
+    let signature = args.first()?.match_list()?;
+    let (name, params) = signature.split_first()?;
+    let params = params
+        .iter()
+        .map(|param| {
+            let param = param.match_list()?;
+            Some((
+                param.first()?.match_atom()?.to_string(),
+                format_type(param.get(1)?),
+            ))
+        })
+        .collect::<Option<Vec<_>>>()?;
+    Some(FunctionSignature {
+        name: name.match_atom()?.to_string(),
+        access,
+        args: params,
+    })
+}
+
+/// Returns the signatures of the public and read-only functions
+pub fn get_functions_signatures(
+    expressions: &[SymbolicExpression],
+) -> BTreeMap<String, FunctionSignature> {
+    let mut signatures = BTreeMap::new();
+    for expression in expressions {
+        let Some((keyword, args)) = expression.match_list().and_then(|list| list.split_first())
+        else {
+            continue;
+        };
+        let access = match keyword.match_atom().map(|atom| atom.as_str()) {
+            Some("define-public") => FunctionAccess::Public,
+            Some("define-read-only") => FunctionAccess::ReadOnly,
+            _ => continue,
+        };
+        if let Some(signature) = get_function_signature(access, args) {
+            signatures.insert(signature.name.clone(), signature);
+        }
+    }
+    signatures
+}
+
+pub fn compare_functions_signatures(
+    previous: &BTreeMap<String, FunctionSignature>,
+    current: &BTreeMap<String, FunctionSignature>,
+) -> Vec<FunctionChange> {
+    let mut changes = vec![];
+    for (name, previous_signature) in previous.iter() {
+        match current.get(name) {
+            None => changes.push(FunctionChange::Removed {
+                function: name.clone(),
+            }),
+            Some(current_signature) if current_signature != previous_signature => {
+                changes.push(FunctionChange::SignatureChanged {
+                    function: name.clone(),
+                    previous: previous_signature.to_string(),
+                    current: current_signature.to_string(),
+                })
+            }
+            Some(_) => {}
+        }
+    }
+    changes
+}
+
+fn collect_contract_calls(
+    expression: &SymbolicExpression,
+    target: &QualifiedContractIdentifier,
+    calls: &mut Vec<ContractCallReference>,
+) {
+    let Some(list) = expression.match_list() else {
+        return;
+    };
+    if let [keyword, contract, function, args @ ..] = list {
+        let contract_id = match &contract.expr {
+            SymbolicExpressionType::LiteralValue(Value::Principal(PrincipalData::Contract(id))) => {
+                Some(id)
+            }
+            SymbolicExpressionType::LiteralValue(Value::CallableContract(callable)) => {
+                Some(&callable.contract_identifier)
+            }
+            _ => None,
+        };
+        if keyword.match_atom().map(|atom| atom.as_str()) == Some("contract-call?")
+            && contract_id == Some(target)
+        {
+            if let Some(function) = function.match_atom() {
+                calls.push(ContractCallReference {
+                    function: function.to_string(),
+                    line: expression.span.start_line,
+                    arguments: args.len(),
+                });
+            }
+        }
+    }
+    for expression in list {
+        collect_contract_calls(expression, target, calls);
+    }
+}
+
+/// Returns the static `contract-call?` made to `target`
+pub fn find_contract_calls(
+    expressions: &[SymbolicExpression],
+    target: &QualifiedContractIdentifier,
+) -> Vec<ContractCallReference> {
+    let mut calls = vec![];
+    for expression in expressions {
+        collect_contract_calls(expression, target, &mut calls);
+    }
+    calls
+}
+
+/// Compares the current version of a project contract with its previous source, and lists
+/// the dependents calling removed or modified functions
+pub fn analyze_upgrade_impact(
+    contract_id: &QualifiedContractIdentifier,
+    previous_source: &str,
+    artifacts: &DeploymentGenerationArtifacts,
+) -> Result<UpgradeImpact, String> {
+    let current_ast = artifacts
+        .asts
+        .get(contract_id)
+        .ok_or(format!("contract {} not found", contract_id))?;
+    let (clarity_version, epoch): (ClarityVersion, StacksEpochId) =
+        match artifacts.analysis.get(contract_id) {
+            Some(analysis) => (analysis.clarity_version, analysis.epoch),
+            None => (DEFAULT_CLARITY_VERSION, DEFAULT_EPOCH),
+        };
+    let (previous_ast, _, success) = build_ast_with_diagnostics(
+        contract_id,
+        previous_source,
+        &mut (),
+        clarity_version,
+        epoch,
+    );
+    if !success {
+        return Err(format!(
+            "unable to parse the previous version of {}",
+            contract_id
+        ));
+    }
+
+    let previous = get_functions_signatures(&previous_ast.expressions);
+    let current = get_functions_signatures(&current_ast.expressions);
+    let changes = compare_functions_signatures(&previous, &current);
+    let added_functions = current
+        .keys()
+        .filter(|name| !previous.contains_key(*name))
+        .cloned()
+        .collect();
+
+    let changed_functions: BTreeSet<&str> = changes.iter().map(|c| c.function()).collect();
+    let mut dependents = vec![];
+    let mut affected_dependents = vec![];
+    for (dependent_id, deps) in artifacts.deps.iter() {
+        if !deps.set.iter().any(|dep| &dep.contract_id == contract_id) {
+            continue;
+        }
+        dependents.push(dependent_id.to_string());
+        let Some(ast) = artifacts.asts.get(dependent_id) else {
+            continue;
+        };
+        let calls: Vec<ContractCallReference> = find_contract_calls(&ast.expressions, contract_id)
+            .into_iter()
+            .filter(|call| changed_functions.contains(call.function.as_str()))
+            .collect();
+        if !calls.is_empty() {
+            affected_dependents.push(AffectedDependent {
+                contract_id: dependent_id.to_string(),
+                calls,
+            });
+        }
+    }
+
+    Ok(UpgradeImpact {
+        contract_id: contract_id.to_string(),
+        changes,
+        added_functions,
+        dependents,
+        affected_dependents,
+        mainnet_callers: vec![],
+    })
+}
+
+#[derive(Debug, Deserialize)]
+struct ContractCallData {
+    contract_id: String,
+    function_name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct AddressTransaction {
+    tx_type: String,
+    sender_address: String,
+    contract_call: Option<ContractCallData>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AddressTransactions {
+    results: Vec<AddressTransaction>,
+    total: usize,
+}
+
+const TRANSACTIONS_PAGE_SIZE: usize = 50;
+
+/// Lists the callers of a deployed contract, from its most recent transactions
+pub async fn fetch_mainnet_callers(
+    api_url: &str,
+    contract_id: &str,
+    max_transactions: usize,
+) -> Result<Vec<MainnetCaller>, String> {
+    let mut callers: BTreeMap<String, MainnetCaller> = BTreeMap::new();
+    let mut offset = 0;
+    while offset < max_transactions {
+        let request_url = format!(
+            "{}/extended/v1/address/{}/transactions?limit={}&offset={}",
+            api_url, contract_id, TRANSACTIONS_PAGE_SIZE, offset
+        );
+        let response = clarinet_files::net::get_text(&request_url).await?;
+        let page: AddressTransactions = serde_json::from_str(&response)
+            .map_err(|e| format!("unable to parse {}: {}", request_url, e))?;
+
+        for tx in page.results.iter() {
+            let Some(ref contract_call) = tx.contract_call else {
+                continue;
+            };
+            if tx.tx_type != "contract_call" {
+                continue;
+            }
+            let (caller, function) = if contract_call.contract_id == contract_id {
+                (
+                    tx.sender_address.clone(),
+                    Some(&contract_call.function_name),
+                )
+            } else {
+                (contract_call.contract_id.clone(), None)
+            };
+            let entry = callers
+                .entry(caller.clone())
+                .or_insert_with(|| MainnetCaller {
+                    caller,
+                    functions: BTreeSet::new(),
+                    transactions: 0,
+                });
+            entry.transactions += 1;
+            if let Some(function) = function {
+                entry.functions.insert(function.clone());
+            }
+        }
+
+        offset += page.results.len();
+        if page.results.is_empty() || offset >= page.total {
+            break;
+        }
+    }
+
+    let mut callers: Vec<MainnetCaller> = callers.into_values().collect();
+    callers.sort_by(|a, b| b.transactions.cmp(&a.transactions));
+    Ok(callers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clarity_repl::clarity::ast::build_ast_with_rules;
+    use clarity_repl::clarity::ast::ASTRules;
+
+    fn parse(source: &str) -> Vec<SymbolicExpression> {
+        build_ast_with_rules(
+            &QualifiedContractIdentifier::transient(),
+            source,
+            &mut (),
+            ClarityVersion::Clarity2,
+            StacksEpochId::Epoch21,
+            ASTRules::Typical,
+        )
+        .unwrap()
+        .expressions
+    }
+
+    #[test]
+    fn test_compare_functions_signatures() {
+        let previous = get_functions_signatures(&parse(
+            "(define-public (transfer (amount uint) (to principal)) (ok true))
+            (define-read-only (get-balance (who principal)) u0)
+            (define-public (burn (amount uint)) (ok true))
+            (define-private (helper) true)",
+        ));
+        let current = get_functions_signatures(&parse(
+            "(define-public (transfer (amount uint) (to principal) (memo (buff 34))) (ok true))
+            (define-read-only (get-balance (who principal)) u1)
+            (define-read-only (get-supply) u0)",
+        ));
+        assert_eq!(previous.len(), 3);
+
+        let changes = compare_functions_signatures(&previous, &current);
+        assert_eq!(
+            changes,
+            vec![
+                FunctionChange::Removed {
+                    function: "burn".to_string()
+                },
+                FunctionChange::SignatureChanged {
+                    function: "transfer".to_string(),
+                    previous: "(define-public (transfer (amount uint) (to principal)))".to_string(),
+                    current:
+                        "(define-public (transfer (amount uint) (to principal) (memo (buff 34))))"
+                            .to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find_contract_calls() {
+        let target = QualifiedContractIdentifier::parse(&format!(
+            "{}.token",
+            QualifiedContractIdentifier::transient().issuer
+        ))
+        .unwrap();
+        let expressions = parse(
+            "(define-public (pay (amount uint))
+              (begin
+                (try! (contract-call? .token transfer amount tx-sender))
+                (contract-call? .other transfer amount)))",
+        );
+        let calls = find_contract_calls(&expressions, &target);
+        assert_eq!(
+            calls,
+            vec![ContractCallReference {
+                function: "transfer".to_string(),
+                line: 3,
+                arguments: 2,
+            }]
+        );
+    }
+}
@@ -0,0 +1,120 @@
+//! Contracts can't be modified once published: upgrading a contract means publishing it
+//! again, along with the contracts referring to it, directly or not.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use clarity_repl::analysis::ast_dependency_detector::DependencySet;
+use clarity_repl::clarity::vm::types::QualifiedContractIdentifier;
+
+use crate::types::{DeploymentSpecification, TransactionSpecification};
+
+/// Returns the changed contracts and their dependents
+pub fn get_contracts_to_republish(
+    changed_contracts: &BTreeSet<QualifiedContractIdentifier>,
+    dependencies: &BTreeMap<QualifiedContractIdentifier, DependencySet>,
+) -> BTreeSet<QualifiedContractIdentifier> {
+    let mut contracts = changed_contracts.clone();
+    loop {
+        let dependents: Vec<QualifiedContractIdentifier> = dependencies
+            .iter()
+            .filter(|(contract_id, deps)| {
+                !contracts.contains(*contract_id)
+                    && deps
+                        .set
+                        .iter()
+                        .any(|dep| contracts.contains(&dep.contract_id))
+            })
+            .map(|(contract_id, _)| contract_id.clone())
+            .collect();
+        if dependents.is_empty() {
+            return contracts;
+        }
+        contracts.extend(dependents);
+    }
+}
+
+fn get_published_contract_id(
+    transaction: &TransactionSpecification,
+) -> Option<QualifiedContractIdentifier> {
+    match transaction {
+        TransactionSpecification::ContractPublish(spec) => Some(QualifiedContractIdentifier::new(
+            spec.expected_sender.clone(),
+            spec.contract_name.clone(),
+        )),
+        TransactionSpecification::EmulatedContractPublish(spec) => {
+            Some(QualifiedContractIdentifier::new(
+                spec.emulated_sender.clone(),
+                spec.contract_name.clone(),
+            ))
+        }
+        _ => None,
+    }
+}
+
+/// Keeps the publish transactions of the given contracts only. The other transactions,
+/// including the requirements publishes, are removed, as well as the empty batches.
+pub fn get_upgrade_deployment(
+    deployment: &DeploymentSpecification,
+    contracts: &BTreeSet<QualifiedContractIdentifier>,
+) -> DeploymentSpecification {
+    let mut upgrade = deployment.clone();
+    upgrade.plan.batches = vec![];
+    for batch in deployment.plan.batches.iter() {
+        let transactions: Vec<TransactionSpecification> = batch
+            .transactions
+            .iter()
+            .filter(|tx| get_published_contract_id(tx).is_some_and(|id| contracts.contains(&id)))
+            .cloned()
+            .collect();
+        if transactions.is_empty() {
+            continue;
+        }
+        let mut batch = batch.clone();
+        batch.id = upgrade.plan.batches.len();
+        batch.transactions = transactions;
+        upgrade.plan.batches.push(batch);
+    }
+    upgrade
+        .contracts
+        .retain(|contract_id, _| contracts.contains(contract_id));
+    upgrade
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contract_id(name: &str) -> QualifiedContractIdentifier {
+        QualifiedContractIdentifier::parse(&format!(
+            "ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM.{}",
+            name
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_get_contracts_to_republish() {
+        let mut dependencies = BTreeMap::new();
+        dependencies.insert(contract_id("trait"), DependencySet::new());
+        dependencies.insert(contract_id("token"), DependencySet::new());
+        let mut deps = DependencySet::new();
+        deps.add_dependency(contract_id("token"), true);
+        dependencies.insert(contract_id("vault"), deps);
+        let mut deps = DependencySet::new();
+        deps.add_dependency(contract_id("vault"), true);
+        dependencies.insert(contract_id("router"), deps);
+
+        let changed = BTreeSet::from([contract_id("token")]);
+        assert_eq!(
+            get_contracts_to_republish(&changed, &dependencies),
+            BTreeSet::from([
+                contract_id("token"),
+                contract_id("vault"),
+                contract_id("router")
+            ])
+        );
+
+        let changed = BTreeSet::from([contract_id("trait")]);
+        assert_eq!(get_contracts_to_republish(&changed, &dependencies), changed);
+    }
+}
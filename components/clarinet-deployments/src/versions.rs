@@ -0,0 +1,174 @@
+//! Clarity version and epoch of the contracts of a plan, printed by `clarinet inspect versions`,
+//! with the combinations worth a second look before the plan is applied.
+
+use std::collections::BTreeMap;
+
+use clarinet_files::{check_clarity_version_for_epoch, ProjectManifest, StacksNetwork};
+use clarity_repl::clarity::vm::ClarityVersion;
+use clarity_repl::clarity::StacksEpochId;
+use clarity_repl::repl::clarity_versions::{self, version_number};
+use clarity_repl::repl::{EpochSpec, DEFAULT_EPOCH};
+
+use crate::get_batch_epoch;
+use crate::types::{DeploymentSpecification, TransactionSpecification};
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ContractVersions {
+    pub contract_id: String,
+    /// Label of the deployer in the genesis of the plan, or its address
+    pub deployer: String,
+    pub batch_id: usize,
+    pub clarity_version: u8,
+    /// `None` when the contract is published in the epoch of the network at the time of the
+    /// deployment (batches without an epoch, on devnet, testnet and mainnet)
+    pub epoch: Option<EpochSpec>,
+    /// Size of the source, in bytes
+    pub size: usize,
+    pub is_requirement: bool,
+    pub warnings: Vec<String>,
+}
+
+/// Warnings of a contract published with `clarity_version` in `epoch`. `manifest_settings`
+/// are the version and epoch of the contract in Clarinet.toml, if it is a project contract.
+pub fn get_versions_warnings(
+    clarity_version: ClarityVersion,
+    epoch: Option<StacksEpochId>,
+    manifest_settings: Option<(ClarityVersion, StacksEpochId)>,
+) -> Vec<String> {
+    let mut warnings = vec![];
+    if let Some(epoch) = epoch {
+        let latest_version = clarity_versions::latest_version(epoch);
+        if let Err(message) = check_clarity_version_for_epoch(epoch, clarity_version) {
+            warnings.push(message);
+        } else if clarity_version < latest_version {
+            warnings.push(format!(
+                "published with Clarity {} in epoch {}, where Clarity {} is available",
+                version_number(clarity_version),
+                EpochSpec::from(epoch),
+                version_number(latest_version),
+            ));
+        }
+    }
+    if let Some((manifest_version, manifest_epoch)) = manifest_settings {
+        if manifest_version != clarity_version {
+            warnings.push(format!(
+                "Clarity {} in the plan, Clarity {} in Clarinet.toml",
+                version_number(clarity_version),
+                version_number(manifest_version),
+            ));
+        }
+        match epoch {
+            Some(epoch) if epoch < manifest_epoch => warnings.push(format!(
+                "published in epoch {}, before the epoch {} of Clarinet.toml",
+                EpochSpec::from(epoch),
+                EpochSpec::from(manifest_epoch),
+            )),
+            _ => {}
+        }
+    }
+    warnings
+}
+
+pub fn get_versions_matrix(
+    manifest: &ProjectManifest,
+    deployment: &DeploymentSpecification,
+) -> Vec<ContractVersions> {
+    let labels: BTreeMap<String, String> = deployment
+        .genesis
+        .as_ref()
+        .map(|genesis| {
+            genesis
+                .wallets
+                .iter()
+                .map(|wallet| (wallet.address.to_address(), wallet.name.clone()))
+                .collect()
+        })
+        .unwrap_or_default();
+    let get_deployer = |address: String| labels.get(&address).cloned().unwrap_or(address);
+    let is_simnet = deployment.network == StacksNetwork::Simnet;
+
+    let mut matrix = vec![];
+    for batch in deployment.plan.batches.iter() {
+        // the simnet moves the batches without an epoch to the latest one
+        let epoch = if is_simnet {
+            Some(get_batch_epoch(batch, None, DEFAULT_EPOCH))
+        } else {
+            batch.epoch.map(StacksEpochId::from)
+        };
+        for transaction in batch.transactions.iter() {
+            let (contract_name, deployer, clarity_version, source, is_requirement) =
+                match transaction {
+                    TransactionSpecification::ContractPublish(tx) => (
+                        tx.contract_name.to_string(),
+                        tx.expected_sender.to_address(),
+                        tx.clarity_version,
+                        &tx.source,
+                        false,
+                    ),
+                    TransactionSpecification::EmulatedContractPublish(tx) => (
+                        tx.contract_name.to_string(),
+                        tx.emulated_sender.to_address(),
+                        tx.clarity_version,
+                        &tx.source,
+                        false,
+                    ),
+                    TransactionSpecification::RequirementPublish(tx) => (
+                        tx.remap_contract_name
+                            .as_ref()
+                            .unwrap_or(&tx.contract_id.name)
+                            .to_string(),
+                        tx.remap_sender.to_address(),
+                        tx.clarity_version,
+                        &tx.source,
+                        true,
+                    ),
+                    _ => continue,
+                };
+            let manifest_settings = manifest
+                .contracts
+                .get(&contract_name)
+                .filter(|_| !is_requirement)
+                .map(|contract| (contract.clarity_version, contract.epoch));
+            matrix.push(ContractVersions {
+                contract_id: format!("{}.{}", deployer, contract_name),
+                warnings: get_versions_warnings(clarity_version, epoch, manifest_settings),
+                deployer: get_deployer(deployer),
+                batch_id: batch.id,
+                clarity_version: version_number(clarity_version),
+                epoch: epoch.map(EpochSpec::from),
+                size: source.len(),
+                is_requirement,
+            });
+        }
+    }
+    matrix
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_versions_warnings() {
+        use ClarityVersion::*;
+        use StacksEpochId::*;
+
+        assert!(get_versions_warnings(Clarity3, Some(Epoch31), None).is_empty());
+        assert!(get_versions_warnings(Clarity2, None, None).is_empty());
+        assert_eq!(
+            get_versions_warnings(Clarity1, Some(Epoch30), None),
+            vec!["published with Clarity 1 in epoch 3.0, where Clarity 3 is available"]
+        );
+        assert_eq!(
+            get_versions_warnings(Clarity3, Some(Epoch25), None).len(),
+            1
+        );
+        assert_eq!(
+            get_versions_warnings(Clarity2, Some(Epoch25), Some((Clarity3, Epoch30))),
+            vec![
+                "Clarity 2 in the plan, Clarity 3 in Clarinet.toml",
+                "published in epoch 2.5, before the epoch 3.0 of Clarinet.toml"
+            ]
+        );
+    }
+}
@@ -6,9 +6,15 @@ extern crate serde_derive;
 pub extern crate bip39;
 pub extern crate url;
 
+mod mainnet_keys;
+pub mod net;
 mod network_manifest;
+mod network_manifest_editor;
 mod project_manifest;
+mod project_migration;
+mod token_metadata;
 
+pub use mainnet_keys::{redact_address, ALLOW_MAINNET_KEYS_ENV};
 pub use network_manifest::{BitcoinNetwork, StacksNetwork};
 
 #[cfg(feature = "wasm")]
@@ -17,20 +23,29 @@ mod wasm_fs_accessor;
 pub use wasm_fs_accessor::WASMFileSystemAccessor;
 
 pub use network_manifest::{
-    compute_addresses, AccountConfig, DevnetConfig, DevnetConfigFile, NetworkManifest,
-    NetworkManifestFile, PoxStackingOrder, DEFAULT_BITCOIN_EXPLORER_IMAGE,
-    DEFAULT_BITCOIN_NODE_IMAGE, DEFAULT_DERIVATION_PATH, DEFAULT_DOCKER_PLATFORM,
-    DEFAULT_EPOCH_2_0, DEFAULT_EPOCH_2_05, DEFAULT_EPOCH_2_1, DEFAULT_EPOCH_2_2, DEFAULT_EPOCH_2_3,
-    DEFAULT_EPOCH_2_4, DEFAULT_EPOCH_2_5, DEFAULT_EPOCH_3_0, DEFAULT_EPOCH_3_1,
-    DEFAULT_FAUCET_MNEMONIC, DEFAULT_FIRST_BURN_HEADER_HEIGHT, DEFAULT_POSTGRES_IMAGE,
-    DEFAULT_STACKER_MNEMONIC, DEFAULT_STACKS_API_IMAGE, DEFAULT_STACKS_EXPLORER_IMAGE,
-    DEFAULT_STACKS_MINER_MNEMONIC, DEFAULT_STACKS_NODE_IMAGE, DEFAULT_STACKS_SIGNER_IMAGE,
-    DEFAULT_SUBNET_API_IMAGE, DEFAULT_SUBNET_CONTRACT_ID, DEFAULT_SUBNET_MNEMONIC,
-    DEFAULT_SUBNET_NODE_IMAGE,
+    compute_addresses, resolve_devnet_services, AccountConfig, BootTimeouts, BootTimeoutsFile,
+    ContainerResources, ContainerResourcesFile, DevnetConfig, DevnetConfigFile, DevnetService,
+    InvariantConfig, MigrationCallConfig, NetworkManifest, NetworkManifestFile, PoxStackingOrder,
+    ResolvedSetting, SettingOrigin, SoakConfig, SoakConfigFile, DEFAULT_BITCOIN_EXPLORER_IMAGE,
+    DEFAULT_BITCOIN_NODE_IMAGE, DEFAULT_DEPLOYMENT_FEE_RATE, DEFAULT_DERIVATION_PATH,
+    DEFAULT_DOCKER_PLATFORM, DEFAULT_EPOCH_2_0, DEFAULT_EPOCH_2_05, DEFAULT_EPOCH_2_1,
+    DEFAULT_EPOCH_2_2, DEFAULT_EPOCH_2_3, DEFAULT_EPOCH_2_4, DEFAULT_EPOCH_2_5, DEFAULT_EPOCH_3_0,
+    DEFAULT_EPOCH_3_1, DEFAULT_FAUCET_MNEMONIC, DEFAULT_FIRST_BURN_HEADER_HEIGHT,
+    DEFAULT_POSTGRES_IMAGE, DEFAULT_STACKER_MNEMONIC, DEFAULT_STACKS_API_IMAGE,
+    DEFAULT_STACKS_EXPLORER_IMAGE, DEFAULT_STACKS_MINER_MNEMONIC, DEFAULT_STACKS_NODE_IMAGE,
+    DEFAULT_STACKS_SIGNER_IMAGE, DEFAULT_SUBNET_API_IMAGE, DEFAULT_SUBNET_CONTRACT_ID,
+    DEFAULT_SUBNET_MNEMONIC, DEFAULT_SUBNET_NODE_IMAGE,
+};
+pub use network_manifest_editor::{
+    NetworkManifestEditor, DEVNET_DEFAULT_EPOCHS, DEVNET_DEFAULT_PORTS,
 };
 pub use project_manifest::{
+    check_clarity_version_for_epoch, get_epoch_and_clarity_version, ProjectAddress, ProjectDefine,
     ProjectManifest, ProjectManifestFile, RequirementConfig, INVALID_CLARITY_VERSION,
 };
+pub use project_migration::{
+    migrate_network_manifest, migrate_project_manifest, ManifestMigration,
+};
 use serde::ser::{Serialize, SerializeMap, Serializer};
 use std::collections::HashMap;
 use std::fmt;
@@ -38,6 +53,7 @@ use std::future::Future;
 use std::path::Path;
 use std::pin::Pin;
 use std::{borrow::BorrowMut, path::PathBuf, str::FromStr};
+pub use token_metadata::{validate_token_metadata, TokenMetadataConfig, DEFAULT_METADATA_SETTER};
 use url::Url;
 
 pub type FileAccessorResult<T> = Pin<Box<dyn Future<Output = Result<T, String>>>>;
@@ -287,18 +303,25 @@ impl FileLocation {
         Ok(network_manifest_location)
     }
 
+    /// Path of the location, percent-decoded for the urls
+    fn get_path_string(&self) -> String {
+        match self {
+            FileLocation::FileSystem { path } => path.to_string_lossy().into_owned(),
+            FileLocation::Url { url } => percent_decode(url.path()),
+        }
+    }
+
+    /// Path relative to `base_location`, with `/` separators on every platform
     pub fn get_relative_path_from_base(
         &self,
         base_location: &FileLocation,
     ) -> Result<String, String> {
-        let file = self.to_string();
-        Ok(file[(base_location.to_string().len() + 1)..].to_string())
+        get_relative_path(&self.get_path_string(), &base_location.get_path_string())
     }
 
     pub fn get_relative_location(&self) -> Result<String, String> {
-        let base = self.get_project_root_location().map(|l| l.to_string())?;
-        let file = self.to_string();
-        Ok(file[(base.len() + 1)..].to_string())
+        let base = self.get_project_root_location()?;
+        self.get_relative_path_from_base(&base)
     }
 
     pub fn get_file_name(&self) -> Option<String> {
@@ -325,9 +348,8 @@ impl FileLocation {
                         .map_err(|e| format!("unable to convert url {} to path\n{:?}", url, e))?;
                     FileLocation::fs_read_content(&path)
                 }
-                "http" | "https" => {
-                    unimplemented!()
-                }
+                #[cfg(not(feature = "wasm"))]
+                "http" | "https" => net::get_bytes_blocking(url.as_str()),
                 _ => {
                     unimplemented!()
                 }
@@ -346,7 +368,7 @@ impl FileLocation {
     pub fn write_content(&self, content: &[u8]) -> Result<(), String> {
         match self {
             FileLocation::FileSystem { path } => FileLocation::fs_write_content(path, content),
-            FileLocation::Url { url: _url } => unimplemented!(),
+            FileLocation::Url { url } => Err(format!("unable to write to {}", url)),
         }
     }
 
@@ -388,6 +410,114 @@ impl Serialize for FileLocation {
     }
 }
 
+fn percent_decode(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = (bytes[i] == b'%')
+            .then(|| input.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match hex {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[derive(Debug, PartialEq)]
+struct NormalizedPath {
+    /// `/`, `C:`, `//server/share`, or empty for a relative path
+    root: String,
+    components: Vec<String>,
+    /// Drive and UNC paths are compared ignoring the case, as Windows does
+    is_windows: bool,
+}
+
+fn has_drive_prefix(path: &str) -> bool {
+    let bytes = path.as_bytes();
+    bytes.len() >= 2
+        && bytes[0].is_ascii_alphabetic()
+        && bytes[1] == b':'
+        && (bytes.len() == 2 || bytes[2] == b'/' || bytes[2] == b'\\')
+}
+
+/// Splits a path on the Windows and Unix separators, without the long path prefixes
+/// (`\\?\C:\`, `\\?\UNC\server\share`) and with the `.` and `..` components resolved
+fn normalize_path(path: &str) -> NormalizedPath {
+    let path = match path.strip_prefix(r"\\?\UNC\") {
+        Some(unc_path) => format!(r"\\{}", unc_path),
+        None => path.strip_prefix(r"\\?\").unwrap_or(path).to_string(),
+    };
+    // the path of a file url on Windows, such as /c:/Users
+    let path = match path.strip_prefix('/') {
+        Some(drive_path) if has_drive_prefix(drive_path) => drive_path.to_string(),
+        _ => path,
+    };
+    let is_unc = path.starts_with(r"\\") || path.starts_with("//");
+    let mut segments = path
+        .split(['/', '\\'])
+        .filter(|segment| !segment.is_empty() && *segment != ".");
+    let (root, is_windows) = if is_unc {
+        let server = segments.next().unwrap_or_default();
+        let share = segments.next().unwrap_or_default();
+        (format!("//{}/{}", server, share), true)
+    } else if has_drive_prefix(&path) {
+        segments.next();
+        (path[..2].to_ascii_uppercase(), true)
+    } else if path.starts_with('/') {
+        ("/".to_string(), false)
+    } else {
+        (String::new(), false)
+    };
+
+    let mut components: Vec<String> = vec![];
+    for segment in segments {
+        if segment != ".." {
+            components.push(segment.to_string());
+        } else if components.last().is_some_and(|last| last != "..") {
+            components.pop();
+        } else if root.is_empty() {
+            components.push(segment.to_string());
+        }
+    }
+    NormalizedPath {
+        root,
+        components,
+        is_windows,
+    }
+}
+
+/// Path of `path` relative to `base`, any of them using the Windows or Unix separators
+pub fn get_relative_path(path: &str, base: &str) -> Result<String, String> {
+    let normalized_path = normalize_path(path);
+    let normalized_base = normalize_path(base);
+    let is_same = |a: &String, b: &String| match normalized_base.is_windows {
+        true => a.eq_ignore_ascii_case(b),
+        false => a == b,
+    };
+    let base_len = normalized_base.components.len();
+    if !is_same(&normalized_path.root, &normalized_base.root)
+        || normalized_path.components.len() <= base_len
+        || !normalized_base
+            .components
+            .iter()
+            .zip(normalized_path.components.iter())
+            .all(|(a, b)| is_same(a, b))
+    {
+        return Err(format!("{} is not located in {}", path, base));
+    }
+    Ok(normalized_path.components[base_len..].join("/"))
+}
+
 pub fn get_manifest_location(path: Option<String>) -> Option<FileLocation> {
     if let Some(path) = path {
         let manifest_path = PathBuf::from(path);
@@ -411,3 +541,103 @@ pub fn get_manifest_location(path: Option<String>) -> Option<FileLocation> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_relative_path() {
+        let relative = |path, base| get_relative_path(path, base).unwrap();
+        assert_eq!(
+            relative(
+                "/home/dev/project/contracts/counter.clar",
+                "/home/dev/project"
+            ),
+            "contracts/counter.clar"
+        );
+        assert_eq!(
+            relative(
+                "/home/dev/project/./contracts/../contracts/a.clar",
+                "/home/dev/project/"
+            ),
+            "contracts/a.clar"
+        );
+        assert!(get_relative_path("/home/dev/project-2/a.clar", "/home/dev/project").is_err());
+        assert!(get_relative_path("/home/dev/Project/a.clar", "/home/dev/project").is_err());
+
+        // drive letters, mixed separators and long path prefixes
+        assert_eq!(
+            relative(
+                r"c:\Users\dev\project/contracts\counter.clar",
+                r"C:\Users\dev\project"
+            ),
+            "contracts/counter.clar"
+        );
+        assert_eq!(
+            relative(
+                r"C:\Users\dev\project\contracts\a.clar",
+                r"\\?\C:\Users\dev\project"
+            ),
+            "contracts/a.clar"
+        );
+        assert_eq!(
+            relative(
+                "/c:/Users/dev/project/contracts/a.clar",
+                r"C:\Users\Dev\project"
+            ),
+            "contracts/a.clar"
+        );
+        assert!(get_relative_path(r"D:\project\a.clar", r"C:\project").is_err());
+
+        // UNC paths
+        assert_eq!(
+            relative(
+                r"\\server\share\project\contracts\a.clar",
+                r"\\?\UNC\server\share\project"
+            ),
+            "contracts/a.clar"
+        );
+        assert_eq!(
+            relative(
+                "//server/share/project/contracts/a.clar",
+                r"\\SERVER\share\project"
+            ),
+            "contracts/a.clar"
+        );
+        assert!(get_relative_path(r"\\server\other\project\a.clar", r"\\server\share").is_err());
+    }
+
+    #[test]
+    fn test_get_relative_path_from_url() {
+        let base = FileLocation::from_url_string("vscode-vfs://github/dev/project").unwrap();
+        let location = FileLocation::from_url_string(
+            "vscode-vfs://github/dev/project/contracts/my%20counter.clar",
+        )
+        .unwrap();
+        assert_eq!(
+            location.get_relative_path_from_base(&base).unwrap(),
+            "contracts/my counter.clar"
+        );
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_windows_relative_path_from_base() {
+        let base = FileLocation::from_path_string(r"\\?\C:\Users\dev\project").unwrap();
+        let mut location = FileLocation::from_path_string(r"c:\Users\dev\project").unwrap();
+        location.append_path("contracts/counter.clar").unwrap();
+        assert_eq!(
+            location.get_relative_path_from_base(&base).unwrap(),
+            "contracts/counter.clar"
+        );
+
+        let base = FileLocation::from_path_string(r"\\server\share\project").unwrap();
+        let mut location = FileLocation::from_path_string(r"\\?\UNC\server\share\project").unwrap();
+        location.append_path(r"contracts\counter.clar").unwrap();
+        assert_eq!(
+            location.get_relative_path_from_base(&base).unwrap(),
+            "contracts/counter.clar"
+        );
+    }
+}
@@ -0,0 +1,180 @@
+//! Guardrails around the mnemonics of `settings/Mainnet.toml`. The other networks refuse to
+//! load an account using one of them (the devnet mnemonics end up in containers and logs),
+//! `deployment apply` on mainnet requires `CLARINET_ALLOW_MAINNET_KEYS=1`, and the messages
+//! printed while applying a plan are redacted.
+
+use std::collections::BTreeSet;
+
+use toml::value::Value;
+
+use super::network_manifest::{NetworkManifest, StacksNetwork};
+
+pub const ALLOW_MAINNET_KEYS_ENV: &str = "CLARINET_ALLOW_MAINNET_KEYS";
+
+const REDACTED_MNEMONIC: &str = "<redacted mnemonic>";
+
+fn normalize_mnemonic(mnemonic: &str) -> String {
+    mnemonic
+        .split_whitespace()
+        .map(|word| word.to_lowercase())
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+/// Mnemonics of the accounts of a network manifest, the invalid files are ignored
+pub(crate) fn get_accounts_mnemonics(content: &[u8]) -> BTreeSet<String> {
+    let Ok(Value::Table(manifest)) = toml::from_slice::<Value>(content) else {
+        return BTreeSet::new();
+    };
+    let Some(Value::Table(accounts)) = manifest.get("accounts") else {
+        return BTreeSet::new();
+    };
+    accounts
+        .values()
+        .filter_map(|account| match account.get("mnemonic") {
+            Some(Value::String(mnemonic)) => Some(normalize_mnemonic(mnemonic)),
+            _ => None,
+        })
+        .collect()
+}
+
+fn get_manifest_mnemonics(manifest: &NetworkManifest) -> Vec<(String, &str)> {
+    let mut mnemonics: Vec<(String, &str)> = manifest
+        .accounts
+        .values()
+        .map(|account| (account.label.clone(), account.mnemonic.as_str()))
+        .collect();
+    if let Some(ref devnet) = manifest.devnet {
+        mnemonics.extend([
+            ("miner_mnemonic".to_string(), devnet.miner_mnemonic.as_str()),
+            (
+                "faucet_mnemonic".to_string(),
+                devnet.faucet_mnemonic.as_str(),
+            ),
+            (
+                "stacker_mnemonic".to_string(),
+                devnet.stacker_mnemonic.as_str(),
+            ),
+            (
+                "subnet_leader_mnemonic".to_string(),
+                devnet.subnet_leader_mnemonic.as_str(),
+            ),
+        ]);
+    }
+    mnemonics
+}
+
+/// Refuses a manifest of another network using a mnemonic of `settings/Mainnet.toml`
+pub(crate) fn check_mainnet_mnemonics_reuse(
+    manifest: &NetworkManifest,
+    network: &StacksNetwork,
+    mainnet_mnemonics: &BTreeSet<String>,
+) -> Result<(), String> {
+    for (name, mnemonic) in get_manifest_mnemonics(manifest) {
+        if mainnet_mnemonics.contains(&normalize_mnemonic(mnemonic)) {
+            return Err(format!(
+                "{} of settings/{:?}.toml uses a mnemonic of settings/Mainnet.toml, the mainnet keys can only be used on mainnet",
+                name, network
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Mainnet keys are only used once `ack` (the value of `CLARINET_ALLOW_MAINNET_KEYS`) is `1`
+pub(crate) fn check_mainnet_keys_ack(
+    manifest: &NetworkManifest,
+    ack: Option<&str>,
+) -> Result<(), String> {
+    let uses_mainnet_keys = manifest.accounts.values().any(|account| account.is_mainnet);
+    if !uses_mainnet_keys || ack == Some("1") {
+        return Ok(());
+    }
+    Err(format!(
+        "applying a plan with the mnemonics of settings/Mainnet.toml requires {}=1",
+        ALLOW_MAINNET_KEYS_ENV
+    ))
+}
+
+/// Keeps the first 5 and the last 4 characters of an address
+pub fn redact_address(address: &str) -> String {
+    if address.len() <= 12 {
+        return address.to_string();
+    }
+    format!("{}…{}", &address[..5], &address[address.len() - 4..])
+}
+
+pub(crate) fn redact(manifest: &NetworkManifest, message: &str) -> String {
+    let mut message = message.to_string();
+    for (_, mnemonic) in get_manifest_mnemonics(manifest) {
+        if !mnemonic.is_empty() {
+            message = message.replace(mnemonic, REDACTED_MNEMONIC);
+        }
+    }
+    for account in manifest.accounts.values().filter(|a| a.is_mainnet) {
+        for address in [&account.stx_address, &account.btc_address] {
+            if !address.is_empty() {
+                message = message.replace(address.as_str(), &redact_address(address));
+            }
+        }
+    }
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network_manifest::NetworkManifestFile;
+
+    const MNEMONIC: &str = "twice kind fence tip hidden tilt action fragile skin nothing glory cousin green tomorrow spring wrist shed math olympic multiply hip blue scout claw";
+
+    fn get_manifest_content(network: &StacksNetwork, mnemonic: &str) -> String {
+        format!(
+            "[network]\nname = \"{:?}\"\n\n[accounts.deployer]\nmnemonic = \"{}\"\n",
+            network, mnemonic
+        )
+        .to_lowercase()
+    }
+
+    fn get_manifest(network: StacksNetwork) -> NetworkManifest {
+        let mut file: NetworkManifestFile =
+            toml::from_str(&get_manifest_content(&network, MNEMONIC)).unwrap();
+        NetworkManifest::from_network_manifest_file(&mut file, &network.get_networks(), None, None)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_mainnet_keys_guardrails() {
+        let mainnet_content =
+            get_manifest_content(&StacksNetwork::Mainnet, &format!("  {}", MNEMONIC));
+        let mainnet_mnemonics = get_accounts_mnemonics(mainnet_content.as_bytes());
+        assert_eq!(mainnet_mnemonics.len(), 1);
+
+        let testnet = get_manifest(StacksNetwork::Testnet);
+        let error =
+            check_mainnet_mnemonics_reuse(&testnet, &StacksNetwork::Testnet, &mainnet_mnemonics)
+                .unwrap_err();
+        assert!(error.starts_with("deployer of settings/Testnet.toml"));
+        assert!(!error.contains(MNEMONIC));
+        assert!(
+            check_mainnet_mnemonics_reuse(&testnet, &StacksNetwork::Testnet, &BTreeSet::new())
+                .is_ok()
+        );
+
+        let mainnet = get_manifest(StacksNetwork::Mainnet);
+        assert!(check_mainnet_keys_ack(&mainnet, None).is_err());
+        assert!(check_mainnet_keys_ack(&mainnet, Some("true")).is_err());
+        assert!(check_mainnet_keys_ack(&mainnet, Some("1")).is_ok());
+        assert!(check_mainnet_keys_ack(&testnet, None).is_ok());
+
+        let address = &mainnet.accounts["deployer"].stx_address;
+        let message = format!("unable to sign with {} ({})", address, MNEMONIC);
+        assert_eq!(
+            redact(&mainnet, &message),
+            format!(
+                "unable to sign with {} (<redacted mnemonic>)",
+                redact_address(address)
+            )
+        );
+    }
+}
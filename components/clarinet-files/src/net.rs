@@ -0,0 +1,336 @@
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+use std::time::Duration;
+
+use clarity::util::hash::Sha256Sum;
+use lazy_static::lazy_static;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+
+/// `[http]` section of clarinetrc.toml. The HTTPS_PROXY, HTTP_PROXY and NO_PROXY
+/// variables are honored unless a proxy is set here.
+#[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq)]
+pub struct HttpSettings {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<String>,
+    /// PEM file of certificate authorities trusted in addition to the built-in ones
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ca_bundle: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub endpoints: Vec<EndpointSettings>,
+    /// Sent as `x-api-key` to the Hiro APIs (default: the HIRO_API_KEY variable)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hiro_api_key: Option<String>,
+    /// Attempts of a request rate limited by the server (429), with an increasing delay
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_retries: Option<u32>,
+    /// Seconds during which the API responses are served from the cache (default: 0,
+    /// disabled). A cached response is also used when the API can't be reached.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache_ttl: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache_dir: Option<String>,
+}
+
+/// Headers (such as an API key) sent with the requests to the URLs starting with `url`
+#[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq)]
+pub struct EndpointSettings {
+    pub url: String,
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+}
+
+lazy_static! {
+    static ref HTTP_SETTINGS: RwLock<HttpSettings> = RwLock::new(HttpSettings::default());
+}
+
+#[cfg(feature = "cli")]
+const DEFAULT_MAX_RETRIES: u32 = 5;
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60);
+
+pub fn set_http_settings(settings: HttpSettings) {
+    *HTTP_SETTINGS.write().unwrap() = settings;
+}
+
+pub fn get_http_settings() -> HttpSettings {
+    HTTP_SETTINGS.read().unwrap().clone()
+}
+
+impl HttpSettings {
+    /// Headers of the longest endpoint matching `url`, and the API key of the Hiro APIs
+    pub fn get_endpoint_headers(&self, url: &str) -> Result<HeaderMap, String> {
+        let mut headers = HeaderMap::new();
+        if is_hiro_api(url) {
+            let api_key = self
+                .hiro_api_key
+                .clone()
+                .or_else(|| std::env::var("HIRO_API_KEY").ok());
+            if let Some(api_key) = api_key {
+                let value = HeaderValue::from_str(&api_key)
+                    .map_err(|e| format!("invalid hiro api key: {}", e))?;
+                headers.insert("x-api-key", value);
+            }
+        }
+        let Some(endpoint) = self
+            .endpoints
+            .iter()
+            .filter(|endpoint| url.starts_with(&endpoint.url))
+            .max_by_key(|endpoint| endpoint.url.len())
+        else {
+            return Ok(headers);
+        };
+        for (name, value) in endpoint.headers.iter() {
+            let name = HeaderName::from_bytes(name.as_bytes())
+                .map_err(|e| format!("invalid header name '{}': {}", name, e))?;
+            let value = HeaderValue::from_str(value)
+                .map_err(|e| format!("invalid value for header {}: {}", name, e))?;
+            headers.insert(name, value);
+        }
+        Ok(headers)
+    }
+
+    #[cfg(not(feature = "wasm"))]
+    fn get_proxy(&self) -> Result<Option<reqwest::Proxy>, String> {
+        self.proxy
+            .as_ref()
+            .map(|proxy| {
+                reqwest::Proxy::all(proxy).map_err(|e| format!("invalid proxy {}: {}", proxy, e))
+            })
+            .transpose()
+    }
+
+    #[cfg(not(feature = "wasm"))]
+    fn get_certificates(&self) -> Result<Vec<reqwest::Certificate>, String> {
+        let Some(ref ca_bundle) = self.ca_bundle else {
+            return Ok(vec![]);
+        };
+        let pem = std::fs::read(ca_bundle)
+            .map_err(|e| format!("unable to read CA bundle {}: {}", ca_bundle, e))?;
+        reqwest::Certificate::from_pem_bundle(&pem)
+            .map_err(|e| format!("unable to parse CA bundle {}: {}", ca_bundle, e))
+    }
+}
+
+/// Client for the requests to `url`, configured by the `[http]` settings
+pub fn build_client(url: &str) -> Result<reqwest::Client, String> {
+    let settings = get_http_settings();
+    #[allow(unused_mut)]
+    let mut builder =
+        reqwest::Client::builder().default_headers(settings.get_endpoint_headers(url)?);
+    #[cfg(not(feature = "wasm"))]
+    {
+        if let Some(proxy) = settings.get_proxy()? {
+            builder = builder.proxy(proxy);
+        }
+        for certificate in settings.get_certificates()? {
+            builder = builder.add_root_certificate(certificate);
+        }
+    }
+    builder
+        .build()
+        .map_err(|e| format!("unable to build http client: {}", e))
+}
+
+#[cfg(not(feature = "wasm"))]
+pub fn build_blocking_client(url: &str) -> Result<reqwest::blocking::Client, String> {
+    let settings = get_http_settings();
+    let mut builder =
+        reqwest::blocking::Client::builder().default_headers(settings.get_endpoint_headers(url)?);
+    if let Some(proxy) = settings.get_proxy()? {
+        builder = builder.proxy(proxy);
+    }
+    for certificate in settings.get_certificates()? {
+        builder = builder.add_root_certificate(certificate);
+    }
+    builder
+        .build()
+        .map_err(|e| format!("unable to build http client: {}", e))
+}
+
+fn is_hiro_api(url: &str) -> bool {
+    url::Url::parse(url)
+        .ok()
+        .and_then(|url| url.host_str().map(|host| host.to_string()))
+        .is_some_and(|host| host == "hiro.so" || host.ends_with(".hiro.so"))
+}
+
+/// Delay before the next attempt of a rate limited request: the `Retry-After` of the
+/// response if any, doubling from one second otherwise
+pub fn get_retry_delay(attempt: u32, retry_after: Option<&str>) -> Duration {
+    let delay = retry_after
+        .and_then(|seconds| seconds.trim().parse::<u64>().ok())
+        .map(Duration::from_secs)
+        .unwrap_or_else(|| Duration::from_secs(1 << attempt.min(6)));
+    delay.min(MAX_RETRY_DELAY)
+}
+
+/// Drop-in replacement of `reqwest::get`, going through the configured client. Rate
+/// limited requests are retried `max_retries` times.
+pub async fn get(url: &str) -> Result<reqwest::Response, String> {
+    let client = build_client(url)?;
+    #[allow(unused_mut)]
+    let mut response = client.get(url).send().await.map_err(|e| e.to_string())?;
+    #[cfg(feature = "cli")]
+    {
+        let max_retries = get_http_settings()
+            .max_retries
+            .unwrap_or(DEFAULT_MAX_RETRIES);
+        let mut attempt = 0;
+        while response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS && attempt < max_retries {
+            let retry_after = response
+                .headers()
+                .get(reqwest::header::RETRY_AFTER)
+                .and_then(|value| value.to_str().ok());
+            tokio::time::sleep(get_retry_delay(attempt, retry_after)).await;
+            response = client.get(url).send().await.map_err(|e| e.to_string())?;
+            attempt += 1;
+        }
+    }
+    Ok(response)
+}
+
+async fn fetch_text(url: &str) -> Result<String, String> {
+    let response = get(url)
+        .await
+        .map_err(|e| format!("unable to retrieve {}: {}", url, e))?;
+    if !response.status().is_success() {
+        return Err(format!(
+            "unable to retrieve {} ({})",
+            url,
+            response.status()
+        ));
+    }
+    response
+        .text()
+        .await
+        .map_err(|e| format!("unable to read {}: {}", url, e))
+}
+
+/// Body of a GET request made by synchronous code, such as the reading of a remote file. Rate
+/// limited requests are retried like the ones of `get`.
+#[cfg(not(feature = "wasm"))]
+pub fn get_bytes_blocking(url: &str) -> Result<Vec<u8>, String> {
+    let client = build_blocking_client(url)?;
+    let send = || {
+        client
+            .get(url)
+            .send()
+            .map_err(|e| format!("unable to retrieve {}: {}", url, e))
+    };
+    #[allow(unused_mut)]
+    let mut response = send()?;
+    #[cfg(feature = "cli")]
+    {
+        let max_retries = get_http_settings()
+            .max_retries
+            .unwrap_or(DEFAULT_MAX_RETRIES);
+        let mut attempt = 0;
+        while response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS && attempt < max_retries {
+            let retry_after = response
+                .headers()
+                .get(reqwest::header::RETRY_AFTER)
+                .and_then(|value| value.to_str().ok());
+            std::thread::sleep(get_retry_delay(attempt, retry_after));
+            response = send()?;
+            attempt += 1;
+        }
+    }
+    if !response.status().is_success() {
+        return Err(format!(
+            "unable to retrieve {} ({})",
+            url,
+            response.status()
+        ));
+    }
+    response
+        .bytes()
+        .map(|bytes| bytes.to_vec())
+        .map_err(|e| format!("unable to read {}: {}", url, e))
+}
+
+/// Checks a content retrieved from `source` against the SHA-256 (hex) it's pinned to
+pub fn check_sha256(source: &str, content: &[u8], expected: &str) -> Result<(), String> {
+    let expected = expected.trim().to_lowercase();
+    if expected.len() != 64 || !expected.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!(
+            "invalid sha256 '{}' (expected 64 hexadecimal characters)",
+            expected
+        ));
+    }
+    let sha256 = Sha256Sum::from_data(content).to_hex();
+    if sha256 != expected {
+        return Err(format!(
+            "integrity check of {} failed: its sha256 is {}, {} was expected",
+            source, sha256, expected
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "wasm"))]
+#[derive(Serialize, Deserialize)]
+struct CachedResponse {
+    url: String,
+    /// Seconds since the Unix epoch
+    fetched_at: u64,
+    body: String,
+}
+
+#[cfg(not(feature = "wasm"))]
+fn get_cache_path(cache_dir: &str, url: &str) -> std::path::PathBuf {
+    use std::hash::{Hash, Hasher};
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    url.hash(&mut hasher);
+    std::path::Path::new(cache_dir).join(format!("{:016x}.json", hasher.finish()))
+}
+
+#[cfg(not(feature = "wasm"))]
+fn now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+/// Body of a successful GET request, served from the response cache while it's fresh
+#[cfg(not(feature = "wasm"))]
+pub async fn get_text(url: &str) -> Result<String, String> {
+    let settings = get_http_settings();
+    let (Some(cache_dir), Some(ttl)) = (settings.cache_dir, settings.cache_ttl) else {
+        return fetch_text(url).await;
+    };
+    if ttl == 0 {
+        return fetch_text(url).await;
+    }
+
+    let cache_path = get_cache_path(&cache_dir, url);
+    let cached = std::fs::read_to_string(&cache_path)
+        .ok()
+        .and_then(|content| serde_json::from_str::<CachedResponse>(&content).ok())
+        .filter(|cached| cached.url == url);
+    if let Some(ref cached) = cached {
+        if now().saturating_sub(cached.fetched_at) < ttl {
+            return Ok(cached.body.clone());
+        }
+    }
+
+    match fetch_text(url).await {
+        Ok(body) => {
+            let entry = CachedResponse {
+                url: url.to_string(),
+                fetched_at: now(),
+                body,
+            };
+            // a cache that can't be written only costs a request
+            if let Ok(content) = serde_json::to_string(&entry) {
+                let _ = std::fs::create_dir_all(&cache_dir);
+                let _ = std::fs::write(&cache_path, content);
+            }
+            Ok(entry.body)
+        }
+        Err(e) => cached.map(|cached| cached.body).ok_or(e),
+    }
+}
+
+#[cfg(feature = "wasm")]
+pub async fn get_text(url: &str) -> Result<String, String> {
+    fetch_text(url).await
+}
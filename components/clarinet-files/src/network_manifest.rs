@@ -1,5 +1,9 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 
+use super::mainnet_keys::{
+    check_mainnet_keys_ack, check_mainnet_mnemonics_reuse, get_accounts_mnemonics, redact,
+    redact_address, ALLOW_MAINNET_KEYS_ENV,
+};
 use super::{FileAccessor, FileLocation};
 use bip39::{Language, Mnemonic};
 use clarinet_utils::get_bip39_seed_from_mnemonic;
@@ -14,6 +18,7 @@ use tiny_hderive::bip32::ExtendedPrivKey;
 use toml::value::Value;
 
 pub const DEFAULT_DERIVATION_PATH: &str = "m/44'/5757'/0'/0/0";
+pub const DEFAULT_DEPLOYMENT_FEE_RATE: u64 = 10;
 
 pub const DEFAULT_STACKS_NODE_IMAGE: &str = "quay.io/hirosystems/stacks-node:devnet-3.1";
 pub const DEFAULT_STACKS_SIGNER_IMAGE: &str = "quay.io/hirosystems/stacks-signer:devnet-3.1";
@@ -56,6 +61,12 @@ pub const DEFAULT_POX_PREPARE_LENGTH: u64 = 4;
 pub const DEFAULT_POX_REWARD_LENGTH: u64 = 10;
 pub const DEFAULT_FIRST_BURN_HEADER_HEIGHT: u64 = 100;
 
+// The reward set is computed by the stacks-node of the devnet, configured with reward cycles of
+// 20 blocks including a prepare phase of 5 blocks, each block commit paying 2 reward slots.
+const DEVNET_REWARD_SLOTS: u128 = (20 - 5) * 2;
+// The stacking threshold is rounded up to an increment of 10,000 STX
+const POX_THRESHOLD_STEPS_USTX: u128 = 10_000_000_000;
+
 lazy_static! {
     pub static ref DEFAULT_PRIVATE_KEYS: [StacksPrivateKey; 1] = [StacksPrivateKey::from_hex(
         "7287ba251d44a4d3fd9276c88ce34c5c52a038955511cccaf77e61068649c17801",
@@ -107,6 +118,8 @@ pub struct NetworkConfigFile {
     bitcoin_node_rpc_address: Option<String>,
     deployment_fee_rate: Option<u64>,
     sats_per_bytes: Option<u64>,
+    max_total_fee: Option<u64>,
+    require_confirmation_above: Option<u64>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Default)]
@@ -152,6 +165,9 @@ pub struct DevnetConfigFile {
     pub postgres_password: Option<String>,
     pub stacks_api_postgres_database: Option<String>,
     pub subnet_api_postgres_database: Option<String>,
+    pub postgres_max_connections: Option<u32>,
+    pub stacks_api_pg_connection_pool_max: Option<u32>,
+    pub stacks_api_max_lag: Option<u64>,
     pub pox_stacking_orders: Option<Vec<PoxStackingOrder>>,
     pub execute_script: Option<Vec<ExecuteScript>>,
     pub bitcoin_node_image_url: Option<String>,
@@ -165,6 +181,9 @@ pub struct DevnetConfigFile {
     pub disable_stacks_explorer: Option<bool>,
     pub disable_stacks_api: Option<bool>,
     pub disable_postgres: Option<bool>,
+    /// Services to start, or profiles (`minimal`, `default`, `full`), instead of the
+    /// `disable_*` and `enable_subnet_node` settings
+    pub services: Option<Vec<String>>,
     pub bind_containers_volumes: Option<bool>,
     pub enable_subnet_node: Option<bool>,
     pub subnet_node_image_url: Option<String>,
@@ -191,8 +210,83 @@ pub struct DevnetConfigFile {
     pub epoch_2_5: Option<u64>,
     pub epoch_3_0: Option<u64>,
     pub epoch_3_1: Option<u64>,
+    /// Burn height at which PoX-2 activates, after the start of epoch 2.1
+    pub pox_2_activation: Option<u64>,
     pub use_docker_gateway_routing: Option<bool>,
     pub docker_platform: Option<String>,
+    /// CPU, memory and platform of the containers, keyed by service
+    pub resources: Option<BTreeMap<String, ContainerResourcesFile>>,
+    /// `sha256:<digest>` the images are pinned to, keyed by service
+    pub image_digests: Option<BTreeMap<String, String>>,
+    pub record_contract_calls: Option<bool>,
+    pub record_blocks: Option<bool>,
+    pub enable_webhook_receiver: Option<bool>,
+    pub webhook_receiver_port: Option<u16>,
+    pub auto_ports: Option<bool>,
+    pub soak: Option<SoakConfigFile>,
+    pub boot_timeouts: Option<BootTimeoutsFile>,
+    /// Calls run by `clarinet devnet redeploy` once a new version is published, keyed by
+    /// contract
+    pub migrations: Option<BTreeMap<String, MigrationCallConfig>>,
+}
+
+/// `[devnet.soak]`, the invariants checked in soak mode (`clarinet devnet start --soak`)
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct SoakConfigFile {
+    pub enabled: Option<bool>,
+    /// Minutes of mining before stopping the devnet, 0 to keep it running until stopped
+    pub duration: Option<u64>,
+    /// Stacks blocks between two checks of the invariants
+    pub check_interval: Option<u64>,
+    pub invariants: Option<Vec<InvariantConfig>>,
+}
+
+/// `[[devnet.soak.invariants]]`, a read-only function expected to return the same value
+/// for the whole life of the devnet
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct InvariantConfig {
+    pub name: String,
+    /// Contract of the deployer (`counter`), or contract identifier (`@sbtc-token`)
+    pub contract: String,
+    pub function: String,
+    /// Clarity expressions, which can refer to principals by name
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default = "default_invariant_expect")]
+    pub expect: String,
+}
+
+fn default_invariant_expect() -> String {
+    "true".to_string()
+}
+
+/// `[devnet.migrations.<contract>]`, a public function of the new version of a contract,
+/// called by its deployer after `clarinet devnet redeploy`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct MigrationCallConfig {
+    pub function: String,
+    /// Clarity expressions, `{previous}` standing for the previous version of the contract
+    #[serde(default)]
+    pub args: Vec<String>,
+}
+
+/// `[devnet.boot_timeouts]`, seconds allowed to each phase of the boot sequence
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct BootTimeoutsFile {
+    pub container_pull: Option<u64>,
+    pub bitcoin_ready: Option<u64>,
+    pub stacks_genesis: Option<u64>,
+    pub epoch_3_reached: Option<u64>,
+    pub api_synced: Option<u64>,
+}
+
+/// `[devnet.resources.<service>]`, such as `[devnet.resources.stacks-api]`. A limit of 0
+/// removes the default one.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ContainerResourcesFile {
+    pub cpus: Option<f64>,
+    pub memory: Option<String>,
+    pub platform: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -269,6 +363,10 @@ pub struct NetworkConfig {
     pub bitcoin_node_rpc_address: Option<String>,
     pub deployment_fee_rate: u64,
     pub sats_per_bytes: u64,
+    /// Total fee of a plan above which `deployment apply` refuses to proceed, in µSTX
+    pub max_total_fee: Option<u64>,
+    /// Total fee of a plan above which `deployment apply` asks to type the network name
+    pub require_confirmation_above: Option<u64>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -319,6 +417,13 @@ pub struct DevnetConfig {
     pub postgres_password: String,
     pub stacks_api_postgres_database: String,
     pub subnet_api_postgres_database: String,
+    /// `max_connections` of postgres, its own default if `None`
+    pub postgres_max_connections: Option<u32>,
+    /// Connections of the stacks-api pool, shared by the ingestion of the blocks and the
+    /// queries, the default of the API if `None`
+    pub stacks_api_pg_connection_pool_max: Option<u32>,
+    /// Blocks the stacks-api can be behind the tip of the stacks-node before a warning
+    pub stacks_api_max_lag: u64,
     pub pox_stacking_orders: Vec<PoxStackingOrder>,
     pub execute_script: Vec<ExecuteScript>,
     pub bitcoin_node_image_url: String,
@@ -363,8 +468,622 @@ pub struct DevnetConfig {
     pub epoch_2_5: u64,
     pub epoch_3_0: u64,
     pub epoch_3_1: u64,
+    pub pox_2_activation: Option<u64>,
     pub use_docker_gateway_routing: bool,
     pub docker_platform: String,
+    /// Resources of the containers of every service, keyed by service name
+    pub resources: BTreeMap<String, ContainerResources>,
+    /// Digests the images are pinned to, keyed by service name. A pulled image not matching
+    /// its digest stops the boot.
+    pub image_digests: BTreeMap<String, String>,
+    /// Record the calls to the project contracts, to replay them in simnet
+    pub record_contract_calls: bool,
+    /// Append the Stacks blocks to `recorded-blocks.jsonl`, to test chainhooks predicates
+    pub record_blocks: bool,
+    /// Capture the deliveries of the chainhooks webhooks on `webhook_receiver_port`
+    pub enable_webhook_receiver: bool,
+    pub webhook_receiver_port: u16,
+    /// Replace the ports already in use on the host by free ones when booting
+    pub auto_ports: bool,
+    /// Invariants monitored while mining, `None` unless the soak mode is enabled
+    pub soak: Option<SoakConfig>,
+    /// Seconds allowed to each phase of the boot sequence before giving up
+    pub boot_timeouts: BootTimeouts,
+    pub migrations: BTreeMap<String, MigrationCallConfig>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SoakConfig {
+    /// Minutes of mining before stopping the devnet, unlimited if `None`
+    pub duration: Option<u64>,
+    pub check_interval: u64,
+    pub invariants: Vec<InvariantConfig>,
+}
+
+const DEFAULT_SOAK_CHECK_INTERVAL: u64 = 10;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BootTimeouts {
+    /// Pull of a container image, for each image
+    pub container_pull: u64,
+    /// From the start of the bitcoin-node container to its first RPC response
+    pub bitcoin_ready: u64,
+    /// From the start of the stacks-node container to its first block
+    pub stacks_genesis: u64,
+    /// From the first Stacks block to the epoch 3.0 burn height
+    pub epoch_3_reached: u64,
+    /// From the start of the stacks-api container to its sync with the stacks-node
+    pub api_synced: u64,
+}
+
+impl Default for BootTimeouts {
+    fn default() -> Self {
+        BootTimeouts {
+            container_pull: 600,
+            bitcoin_ready: 60,
+            stacks_genesis: 180,
+            epoch_3_reached: 600,
+            api_synced: 180,
+        }
+    }
+}
+
+fn resolve_boot_timeouts(timeouts: Option<BootTimeoutsFile>) -> BootTimeouts {
+    let defaults = BootTimeouts::default();
+    let Some(timeouts) = timeouts else {
+        return defaults;
+    };
+    BootTimeouts {
+        container_pull: timeouts.container_pull.unwrap_or(defaults.container_pull),
+        bitcoin_ready: timeouts.bitcoin_ready.unwrap_or(defaults.bitcoin_ready),
+        stacks_genesis: timeouts.stacks_genesis.unwrap_or(defaults.stacks_genesis),
+        epoch_3_reached: timeouts.epoch_3_reached.unwrap_or(defaults.epoch_3_reached),
+        api_synced: timeouts.api_synced.unwrap_or(defaults.api_synced),
+    }
+}
+
+fn resolve_soak_config(soak: Option<SoakConfigFile>) -> Result<Option<SoakConfig>, String> {
+    let Some(soak) = soak.filter(|soak| soak.enabled.unwrap_or(false)) else {
+        return Ok(None);
+    };
+    let check_interval = soak.check_interval.unwrap_or(DEFAULT_SOAK_CHECK_INTERVAL);
+    if check_interval == 0 {
+        return Err("[devnet.soak] check_interval must be at least 1 block".to_string());
+    }
+    let invariants = soak.invariants.unwrap_or_default();
+    if invariants.is_empty() {
+        return Err("the soak mode requires at least one [[devnet.soak.invariants]]".to_string());
+    }
+    let mut names = BTreeSet::new();
+    for invariant in invariants.iter() {
+        if !names.insert(invariant.name.as_str()) {
+            return Err(format!("duplicated invariant '{}'", invariant.name));
+        }
+    }
+    Ok(Some(SoakConfig {
+        duration: soak.duration.filter(|duration| *duration > 0),
+        check_interval,
+        invariants,
+    }))
+}
+
+impl DevnetConfig {
+    /// Returns the ports of the devnet services, keyed by their `Devnet.toml` setting
+    pub fn get_ports(&self) -> BTreeMap<String, u16> {
+        [
+            ("orchestrator_port", self.orchestrator_ingestion_port),
+            ("orchestrator_control_port", self.orchestrator_control_port),
+            ("bitcoin_node_p2p_port", self.bitcoin_node_p2p_port),
+            ("bitcoin_node_rpc_port", self.bitcoin_node_rpc_port),
+            ("stacks_node_p2p_port", self.stacks_node_p2p_port),
+            ("stacks_node_rpc_port", self.stacks_node_rpc_port),
+            ("stacks_api_port", self.stacks_api_port),
+            ("stacks_api_events_port", self.stacks_api_events_port),
+            ("stacks_explorer_port", self.stacks_explorer_port),
+            ("bitcoin_explorer_port", self.bitcoin_explorer_port),
+            ("postgres_port", self.postgres_port),
+            ("subnet_node_p2p_port", self.subnet_node_p2p_port),
+            ("subnet_node_rpc_port", self.subnet_node_rpc_port),
+            (
+                "subnet_events_ingestion_port",
+                self.subnet_events_ingestion_port,
+            ),
+            ("subnet_api_port", self.subnet_api_port),
+            ("subnet_api_events_port", self.subnet_api_events_port),
+            ("webhook_receiver_port", self.webhook_receiver_port),
+        ]
+        .into_iter()
+        .map(|(key, port)| (key.to_string(), port))
+        .collect()
+    }
+
+    pub fn get_ports_mut(&mut self) -> Vec<(&'static str, &mut u16)> {
+        vec![
+            ("orchestrator_port", &mut self.orchestrator_ingestion_port),
+            (
+                "orchestrator_control_port",
+                &mut self.orchestrator_control_port,
+            ),
+            ("bitcoin_node_p2p_port", &mut self.bitcoin_node_p2p_port),
+            ("bitcoin_node_rpc_port", &mut self.bitcoin_node_rpc_port),
+            ("stacks_node_p2p_port", &mut self.stacks_node_p2p_port),
+            ("stacks_node_rpc_port", &mut self.stacks_node_rpc_port),
+            ("stacks_api_port", &mut self.stacks_api_port),
+            ("stacks_api_events_port", &mut self.stacks_api_events_port),
+            ("stacks_explorer_port", &mut self.stacks_explorer_port),
+            ("bitcoin_explorer_port", &mut self.bitcoin_explorer_port),
+            ("postgres_port", &mut self.postgres_port),
+            ("subnet_node_p2p_port", &mut self.subnet_node_p2p_port),
+            ("subnet_node_rpc_port", &mut self.subnet_node_rpc_port),
+            (
+                "subnet_events_ingestion_port",
+                &mut self.subnet_events_ingestion_port,
+            ),
+            ("subnet_api_port", &mut self.subnet_api_port),
+            ("subnet_api_events_port", &mut self.subnet_api_events_port),
+            ("webhook_receiver_port", &mut self.webhook_receiver_port),
+        ]
+    }
+
+    pub fn get_resources(&self, service: DevnetService) -> ContainerResources {
+        self.resources
+            .get(service.get_name())
+            .cloned()
+            .unwrap_or_else(|| ContainerResources {
+                cpus: None,
+                memory: None,
+                platform: self.docker_platform.clone(),
+            })
+    }
+
+    pub fn get_image_url(&self, service: DevnetService) -> &str {
+        match service {
+            DevnetService::BitcoinNode => &self.bitcoin_node_image_url,
+            DevnetService::StacksNode => &self.stacks_node_image_url,
+            DevnetService::StacksSigner => &self.stacks_signer_image_url,
+            DevnetService::Postgres => &self.postgres_image_url,
+            DevnetService::StacksApi => &self.stacks_api_image_url,
+            DevnetService::StacksExplorer => &self.stacks_explorer_image_url,
+            DevnetService::BitcoinExplorer => &self.bitcoin_explorer_image_url,
+            DevnetService::SubnetNode => &self.subnet_node_image_url,
+            DevnetService::SubnetApi => &self.subnet_api_image_url,
+        }
+    }
+
+    pub fn get_image_digest(&self, service: DevnetService) -> Option<&str> {
+        self.image_digests
+            .get(service.get_name())
+            .map(|digest| digest.as_str())
+    }
+
+    /// Services started by the orchestrator
+    pub fn get_services(&self) -> BTreeSet<DevnetService> {
+        DevnetService::ALL
+            .into_iter()
+            .filter(|service| match service {
+                DevnetService::Postgres => !self.disable_postgres,
+                DevnetService::StacksApi => !self.disable_stacks_api,
+                DevnetService::StacksExplorer => !self.disable_stacks_explorer,
+                DevnetService::BitcoinExplorer => !self.disable_bitcoin_explorer,
+                DevnetService::SubnetNode => self.enable_subnet_node,
+                DevnetService::SubnetApi => self.enable_subnet_node && !self.disable_subnet_api,
+                _ => true,
+            })
+            .collect()
+    }
+
+    /// Whether the service listening on a port of `get_ports` is started
+    fn is_port_used(&self, key: &str) -> bool {
+        if key.starts_with("subnet_api_") {
+            !self.disable_subnet_api
+        } else if key.starts_with("subnet_") {
+            self.enable_subnet_node
+        } else if key.starts_with("stacks_api_") {
+            !self.disable_stacks_api
+        } else {
+            match key {
+                "stacks_explorer_port" => !self.disable_stacks_explorer,
+                "bitcoin_explorer_port" => !self.disable_bitcoin_explorer,
+                "postgres_port" => !self.disable_postgres,
+                "webhook_receiver_port" => self.enable_webhook_receiver,
+                _ => true,
+            }
+        }
+    }
+
+    /// Checks the constraints between settings that would only fail once the containers
+    /// are started: epochs in order, PoX-2 activated during epoch 2.1 or later, ports used
+    /// by a single service, and node images supporting the last epoch
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors = vec![];
+
+        let epochs = [
+            ("epoch_2_0", self.epoch_2_0),
+            ("epoch_2_05", self.epoch_2_05),
+            ("epoch_2_1", self.epoch_2_1),
+            ("epoch_2_2", self.epoch_2_2),
+            ("epoch_2_3", self.epoch_2_3),
+            ("epoch_2_4", self.epoch_2_4),
+            ("epoch_2_5", self.epoch_2_5),
+            ("epoch_3_0", self.epoch_3_0),
+            ("epoch_3_1", self.epoch_3_1),
+        ];
+        for pair in epochs.windows(2) {
+            let ((previous, previous_height), (epoch, height)) = (pair[0], pair[1]);
+            if height < previous_height {
+                errors.push(format!(
+                    "{} ({}) must not start before {} ({})",
+                    epoch, height, previous, previous_height
+                ));
+            }
+        }
+        if let Some(pox_2_activation) = self.pox_2_activation {
+            if pox_2_activation <= self.epoch_2_1 {
+                errors.push(format!(
+                    "pox_2_activation ({}) must be after the start of epoch_2_1 ({})",
+                    pox_2_activation, self.epoch_2_1
+                ));
+            }
+        }
+
+        let mut services_by_port: BTreeMap<u16, Vec<String>> = BTreeMap::new();
+        for (key, port) in self.get_ports() {
+            if self.is_port_used(&key) {
+                services_by_port.entry(port).or_default().push(key);
+            }
+        }
+        for (port, keys) in services_by_port {
+            if keys.len() > 1 {
+                errors.push(format!("port {} is used by {}", port, keys.join(", ")));
+            }
+        }
+
+        for (key, image) in [
+            ("stacks_node_image_url", &self.stacks_node_image_url),
+            ("stacks_signer_image_url", &self.stacks_signer_image_url),
+        ] {
+            if let Some((major, minor)) = get_image_version(image) {
+                if (major, minor) < (3, 1) {
+                    errors.push(format!(
+                        "{} ({}) is version {}.{}, epoch 3.1 (block {}) requires 3.1 or later",
+                        key, image, major, minor, self.epoch_3_1
+                    ));
+                }
+            }
+        }
+
+        errors
+    }
+}
+
+/// Version of the tag of an image, such as `3.1` for `stacks-node:devnet-3.1` or
+/// `stacks-signer:3.1.0.0.4`. `None` for tags that aren't versions, such as `latest`.
+fn get_image_version(image: &str) -> Option<(u32, u32)> {
+    let (_, tag) = image.rsplit_once(':')?;
+    if tag.contains('/') {
+        return None;
+    }
+    let tag = tag.strip_prefix("devnet-").unwrap_or(tag);
+    let tag = tag.strip_prefix('v').unwrap_or(tag);
+    let mut parts = tag.split(['.', '-']);
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next()?.parse().ok()?;
+    Some((major, minor))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DevnetService {
+    BitcoinNode,
+    StacksNode,
+    StacksSigner,
+    Postgres,
+    StacksApi,
+    StacksExplorer,
+    BitcoinExplorer,
+    SubnetNode,
+    SubnetApi,
+}
+
+impl DevnetService {
+    pub const ALL: [DevnetService; 9] = [
+        DevnetService::BitcoinNode,
+        DevnetService::StacksNode,
+        DevnetService::StacksSigner,
+        DevnetService::Postgres,
+        DevnetService::StacksApi,
+        DevnetService::StacksExplorer,
+        DevnetService::BitcoinExplorer,
+        DevnetService::SubnetNode,
+        DevnetService::SubnetApi,
+    ];
+
+    /// Services the orchestrator can't boot a devnet without
+    pub const REQUIRED: [DevnetService; 3] = [
+        DevnetService::BitcoinNode,
+        DevnetService::StacksNode,
+        DevnetService::StacksSigner,
+    ];
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            DevnetService::BitcoinNode => "bitcoin-node",
+            DevnetService::StacksNode => "stacks-node",
+            DevnetService::StacksSigner => "stacks-signer",
+            DevnetService::Postgres => "postgres",
+            DevnetService::StacksApi => "stacks-api",
+            DevnetService::StacksExplorer => "stacks-explorer",
+            DevnetService::BitcoinExplorer => "bitcoin-explorer",
+            DevnetService::SubnetNode => "subnet-node",
+            DevnetService::SubnetApi => "subnet-api",
+        }
+    }
+
+    /// Accepts the short names `bitcoin`, `signer`, `api` and `explorer` as well
+    pub fn from_name(name: &str) -> Option<DevnetService> {
+        match name {
+            "bitcoin" => Some(DevnetService::BitcoinNode),
+            "signer" => Some(DevnetService::StacksSigner),
+            "api" => Some(DevnetService::StacksApi),
+            "explorer" => Some(DevnetService::StacksExplorer),
+            name => DevnetService::ALL
+                .into_iter()
+                .find(|service| service.get_name() == name),
+        }
+    }
+
+    pub fn get_dependencies(&self) -> &'static [DevnetService] {
+        match self {
+            DevnetService::BitcoinNode | DevnetService::Postgres => &[],
+            DevnetService::StacksNode => &[DevnetService::BitcoinNode],
+            DevnetService::StacksSigner | DevnetService::SubnetNode => &[DevnetService::StacksNode],
+            DevnetService::StacksApi => &[DevnetService::StacksNode, DevnetService::Postgres],
+            DevnetService::StacksExplorer => &[DevnetService::StacksApi],
+            DevnetService::BitcoinExplorer => &[DevnetService::BitcoinNode],
+            DevnetService::SubnetApi => &[DevnetService::SubnetNode, DevnetService::Postgres],
+        }
+    }
+}
+
+impl std::fmt::Display for DevnetService {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.get_name())
+    }
+}
+
+fn get_devnet_profile(name: &str) -> Option<Vec<DevnetService>> {
+    let profile = match name {
+        "minimal" => DevnetService::REQUIRED.to_vec(),
+        // everything but the subnet
+        "default" => DevnetService::ALL[..7].to_vec(),
+        "full" => DevnetService::ALL.to_vec(),
+        _ => return None,
+    };
+    Some(profile)
+}
+
+/// Resolves the `services` setting, a list of services and profiles such as
+/// `["minimal", "stacks-api", "postgres"]`, checking that the dependencies of every
+/// service are started
+pub fn resolve_devnet_services(selection: &[String]) -> Result<BTreeSet<DevnetService>, String> {
+    let mut services = BTreeSet::new();
+    for name in selection.iter().map(|name| name.trim()) {
+        if let Some(profile) = get_devnet_profile(name) {
+            services.extend(profile);
+        } else if let Some(service) = DevnetService::from_name(name) {
+            services.insert(service);
+        } else {
+            return Err(format!(
+                "unknown devnet service '{}' (expected minimal, default, full or one of: {})",
+                name,
+                DevnetService::ALL
+                    .map(|service| service.get_name())
+                    .join(", ")
+            ));
+        }
+    }
+
+    let mut errors = vec![];
+    for service in DevnetService::REQUIRED {
+        if !services.contains(&service) {
+            errors.push(format!("{} can not be disabled", service));
+        }
+    }
+    for service in services.iter() {
+        for dependency in service.get_dependencies() {
+            if !services.contains(dependency) {
+                errors.push(format!("{} requires {}", service, dependency));
+            }
+        }
+    }
+    match errors.is_empty() {
+        true => Ok(services),
+        false => Err(format!("invalid devnet services: {}", errors.join(", "))),
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ContainerResources {
+    /// Unlimited if `None`
+    pub cpus: Option<f64>,
+    /// Such as `512m` or `2g`, unlimited if `None`
+    pub memory: Option<String>,
+    pub platform: String,
+}
+
+impl ContainerResources {
+    /// CPU quota in the unit of the Docker API (10^-9 CPUs)
+    pub fn get_nano_cpus(&self) -> Option<i64> {
+        self.cpus.map(|cpus| (cpus * 1_000_000_000.0) as i64)
+    }
+
+    pub fn get_memory_bytes(&self) -> Option<i64> {
+        self.memory
+            .as_deref()
+            .and_then(|memory| parse_memory_size(memory).ok())
+    }
+}
+
+/// Size in bytes of a memory limit such as `512m` or `2g`
+pub fn parse_memory_size(size: &str) -> Result<i64, String> {
+    let invalid_size = || {
+        format!(
+            "invalid memory size '{}' (expected a number of bytes or a size such as 512m or 2g)",
+            size
+        )
+    };
+    let lowercase = size.trim().to_lowercase();
+    let (number, unit) = match lowercase.find(|c: char| !c.is_ascii_digit()) {
+        Some(index) => lowercase.split_at(index),
+        None => (lowercase.as_str(), ""),
+    };
+    let multiplier: i64 = match unit.trim_end_matches('b') {
+        "" => 1,
+        "k" => 1 << 10,
+        "m" => 1 << 20,
+        "g" => 1 << 30,
+        _ => return Err(invalid_size()),
+    };
+    let number: i64 = number.parse().map_err(|_| invalid_size())?;
+    number.checked_mul(multiplier).ok_or_else(invalid_size)
+}
+
+/// Limits keeping the API, the explorers and postgres from starving the nodes, which
+/// aren't limited
+fn get_default_resources(service: DevnetService) -> (Option<f64>, Option<&'static str>) {
+    match service {
+        DevnetService::Postgres
+        | DevnetService::StacksApi
+        | DevnetService::SubnetApi
+        | DevnetService::StacksExplorer => (Some(1.0), Some("1g")),
+        DevnetService::BitcoinExplorer => (Some(0.5), Some("512m")),
+        DevnetService::BitcoinNode
+        | DevnetService::StacksNode
+        | DevnetService::StacksSigner
+        | DevnetService::SubnetNode => (None, None),
+    }
+}
+
+/// Accepts `arm64` and `amd64` as short names of `linux/arm64` and `linux/amd64`
+fn get_platform(platform: &str) -> String {
+    match platform.contains('/') {
+        true => platform.to_string(),
+        false => format!("linux/{}", platform),
+    }
+}
+
+fn resolve_container_resources(
+    resources_files: BTreeMap<String, ContainerResourcesFile>,
+    docker_platform: &str,
+) -> Result<BTreeMap<String, ContainerResources>, String> {
+    let mut files = BTreeMap::new();
+    for (name, file) in resources_files {
+        let service = DevnetService::from_name(&name)
+            .ok_or(format!("unknown service '{}' in [devnet.resources]", name))?;
+        files.insert(service, file);
+    }
+
+    let mut resources = BTreeMap::new();
+    for service in DevnetService::ALL {
+        let (default_cpus, default_memory) = get_default_resources(service);
+        let file = files.remove(&service).unwrap_or_default();
+        let cpus = match file.cpus {
+            Some(cpus) if cpus < 0.0 => {
+                return Err(format!("{}: cpus must not be negative", service));
+            }
+            Some(cpus) => Some(cpus).filter(|cpus| *cpus > 0.0),
+            None => default_cpus,
+        };
+        let memory = match file.memory {
+            Some(memory) => match parse_memory_size(&memory) {
+                Ok(0) => None,
+                Ok(_) => Some(memory),
+                Err(e) => return Err(format!("{}: {}", service, e)),
+            },
+            None => default_memory.map(|memory| memory.to_string()),
+        };
+        let platform = get_platform(file.platform.as_deref().unwrap_or(docker_platform));
+        resources.insert(
+            service.get_name().to_string(),
+            ContainerResources {
+                cpus,
+                memory,
+                platform,
+            },
+        );
+    }
+    Ok(resources)
+}
+
+fn resolve_image_digests(
+    digests_files: BTreeMap<String, String>,
+) -> Result<BTreeMap<String, String>, String> {
+    let mut digests = BTreeMap::new();
+    for (name, digest) in digests_files {
+        let service = DevnetService::from_name(&name).ok_or(format!(
+            "unknown service '{}' in [devnet.image_digests]",
+            name
+        ))?;
+        let is_digest = digest.strip_prefix("sha256:").is_some_and(|hash| {
+            hash.len() == 64 && hash.chars().all(|c| matches!(c, '0'..='9' | 'a'..='f'))
+        });
+        if !is_digest {
+            return Err(format!(
+                "{}: invalid image digest '{}', expected sha256:<64 hex characters>",
+                service, digest
+            ));
+        }
+        digests.insert(service.get_name().to_string(), digest);
+    }
+    Ok(digests)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SettingOrigin {
+    Default,
+    DevnetToml,
+    Override,
+    /// Computed from other settings, such as the addresses derived from a mnemonic
+    Derived,
+}
+
+impl std::fmt::Display for SettingOrigin {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            SettingOrigin::Default => write!(f, "default"),
+            SettingOrigin::DevnetToml => write!(f, "Devnet.toml"),
+            SettingOrigin::Override => write!(f, "override"),
+            SettingOrigin::Derived => write!(f, "derived"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ResolvedSetting {
+    pub key: String,
+    pub value: String,
+    pub origin: SettingOrigin,
+}
+
+const DERIVED_DEVNET_SETTINGS: [&str; 10] = [
+    "miner_stx_address",
+    "miner_secret_key_hex",
+    "miner_btc_address",
+    "faucet_stx_address",
+    "faucet_secret_key_hex",
+    "faucet_btc_address",
+    "subnet_leader_stx_address",
+    "subnet_leader_secret_key_hex",
+    "subnet_leader_btc_address",
+    "remapped_subnet_contract_id",
+];
+
+fn get_table_keys(value: Option<Value>) -> Vec<String> {
+    match value {
+        Some(Value::Table(table)) => table.keys().cloned().collect(),
+        _ => vec![],
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -375,9 +1094,11 @@ pub struct PoxStackingOrder {
     pub slots: u64,
     pub btc_address: String,
     pub auto_extend: Option<bool>,
+    /// Label of the account stacking the delegated STX (`delegate-stx` + `delegate-stack-stx`)
+    pub pool_operator: Option<String>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Clone)]
 pub struct AccountConfig {
     pub label: String,
     pub mnemonic: String,
@@ -388,6 +1109,27 @@ pub struct AccountConfig {
     pub is_mainnet: bool,
 }
 
+impl std::fmt::Debug for AccountConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (stx_address, btc_address) = match self.is_mainnet {
+            true => (
+                redact_address(&self.stx_address),
+                redact_address(&self.btc_address),
+            ),
+            false => (self.stx_address.clone(), self.btc_address.clone()),
+        };
+        f.debug_struct("AccountConfig")
+            .field("label", &self.label)
+            .field("mnemonic", &"<redacted>")
+            .field("derivation", &self.derivation)
+            .field("balance", &self.balance)
+            .field("stx_address", &stx_address)
+            .field("btc_address", &btc_address)
+            .field("is_mainnet", &self.is_mainnet)
+            .finish()
+    }
+}
+
 impl NetworkManifest {
     pub fn from_project_manifest_location(
         project_manifest_location: &FileLocation,
@@ -435,12 +1177,91 @@ impl NetworkManifest {
         let network_manifest_file_content = location.read_content()?;
         let mut network_manifest_file: NetworkManifestFile =
             toml::from_slice(&network_manifest_file_content[..]).unwrap();
-        NetworkManifest::from_network_manifest_file(
+        let manifest = NetworkManifest::from_network_manifest_file(
             &mut network_manifest_file,
             networks,
             cache_location,
             devnet_override,
-        )
+        )?;
+        let is_file = matches!(location, FileLocation::FileSystem { .. });
+        if is_file && !matches!(networks.1, StacksNetwork::Mainnet) {
+            let mut mainnet_location = location.get_parent_location()?;
+            mainnet_location.append_path("Mainnet.toml")?;
+            if mainnet_location.exists() {
+                let mainnet_mnemonics = get_accounts_mnemonics(&mainnet_location.read_content()?);
+                check_mainnet_mnemonics_reuse(&manifest, &networks.1, &mainnet_mnemonics)?;
+            }
+        }
+        Ok(manifest)
+    }
+
+    /// Signing with the mainnet keys has to be acknowledged with `CLARINET_ALLOW_MAINNET_KEYS=1`
+    pub fn ensure_mainnet_keys_allowed(&self) -> Result<(), String> {
+        let ack = std::env::var(ALLOW_MAINNET_KEYS_ENV).ok();
+        check_mainnet_keys_ack(self, ack.as_deref())
+    }
+
+    /// `message` without the mnemonics of the manifest, and with the mainnet addresses shortened
+    pub fn redact(&self, message: &str) -> String {
+        redact(self, message)
+    }
+
+    /// Resolved devnet settings of a project, with the origin of each of them
+    pub fn explain_devnet_settings(
+        project_manifest_location: &FileLocation,
+        cache_location: Option<&FileLocation>,
+        devnet_override: Option<DevnetConfigFile>,
+    ) -> Result<(DevnetConfig, Vec<ResolvedSetting>), String> {
+        let networks = StacksNetwork::Devnet.get_networks();
+        let location = project_manifest_location.get_network_manifest_location(&networks.1)?;
+        let content = location.read_content()?;
+        let file_keys = get_table_keys(
+            toml::from_slice::<Value>(&content)
+                .map_err(|e| format!("unable to parse {}: {}", location, e))?
+                .get("devnet")
+                .cloned(),
+        );
+        let override_keys = get_table_keys(
+            devnet_override
+                .as_ref()
+                .and_then(|devnet_override| Value::try_from(devnet_override).ok()),
+        );
+
+        let manifest =
+            NetworkManifest::from_location(&location, &networks, cache_location, devnet_override)?;
+        let devnet = manifest
+            .devnet
+            .ok_or("unable to resolve the devnet settings".to_string())?;
+        let Value::Table(resolved) = Value::try_from(&devnet)
+            .map_err(|e| format!("unable to serialize the devnet settings: {}", e))?
+        else {
+            unreachable!("the devnet settings are serialized as a table");
+        };
+
+        let settings = resolved
+            .into_iter()
+            .map(|(key, value)| {
+                let key = match key.as_str() {
+                    "orchestrator_ingestion_port" => "orchestrator_port".to_string(),
+                    _ => key,
+                };
+                let origin = if DERIVED_DEVNET_SETTINGS.contains(&key.as_str()) {
+                    SettingOrigin::Derived
+                } else if override_keys.contains(&key) {
+                    SettingOrigin::Override
+                } else if file_keys.contains(&key) {
+                    SettingOrigin::DevnetToml
+                } else {
+                    SettingOrigin::Default
+                };
+                ResolvedSetting {
+                    key,
+                    value: value.to_string(),
+                    origin,
+                }
+            })
+            .collect();
+        Ok((devnet, settings))
     }
 
     pub fn from_network_manifest_file(
@@ -466,8 +1287,10 @@ impl NetworkManifest {
             deployment_fee_rate: network_manifest_file
                 .network
                 .deployment_fee_rate
-                .unwrap_or(10),
+                .unwrap_or(DEFAULT_DEPLOYMENT_FEE_RATE),
             sats_per_bytes: network_manifest_file.network.sats_per_bytes.unwrap_or(10),
+            max_total_fee: network_manifest_file.network.max_total_fee,
+            require_confirmation_above: network_manifest_file.network.require_confirmation_above,
         };
 
         let mut accounts = BTreeMap::new();
@@ -674,6 +1497,86 @@ impl NetworkManifest {
                     devnet_config.disable_postgres = Some(val);
                 }
 
+                if let Some(ref val) = devnet_override.services {
+                    devnet_config.services = Some(val.clone());
+                }
+
+                if let Some(val) = devnet_override.record_contract_calls {
+                    devnet_config.record_contract_calls = Some(val);
+                }
+
+                if let Some(val) = devnet_override.record_blocks {
+                    devnet_config.record_blocks = Some(val);
+                }
+
+                if let Some(val) = devnet_override.enable_webhook_receiver {
+                    devnet_config.enable_webhook_receiver = Some(val);
+                }
+
+                if let Some(val) = devnet_override.webhook_receiver_port {
+                    devnet_config.webhook_receiver_port = Some(val);
+                }
+
+                if let Some(val) = devnet_override.auto_ports {
+                    devnet_config.auto_ports = Some(val);
+                }
+
+                if let Some(val) = devnet_override.postgres_max_connections {
+                    devnet_config.postgres_max_connections = Some(val);
+                }
+
+                if let Some(val) = devnet_override.stacks_api_pg_connection_pool_max {
+                    devnet_config.stacks_api_pg_connection_pool_max = Some(val);
+                }
+
+                if let Some(val) = devnet_override.stacks_api_max_lag {
+                    devnet_config.stacks_api_max_lag = Some(val);
+                }
+
+                if let Some(ref val) = devnet_override.soak {
+                    let soak = devnet_config.soak.get_or_insert_with(Default::default);
+                    if let Some(enabled) = val.enabled {
+                        soak.enabled = Some(enabled);
+                    }
+                    if let Some(duration) = val.duration {
+                        soak.duration = Some(duration);
+                    }
+                    if let Some(check_interval) = val.check_interval {
+                        soak.check_interval = Some(check_interval);
+                    }
+                    if let Some(ref invariants) = val.invariants {
+                        soak.invariants = Some(invariants.clone());
+                    }
+                }
+
+                if let Some(ref val) = devnet_override.migrations {
+                    devnet_config
+                        .migrations
+                        .get_or_insert_with(Default::default)
+                        .extend(val.clone());
+                }
+
+                if let Some(ref val) = devnet_override.boot_timeouts {
+                    let timeouts = devnet_config
+                        .boot_timeouts
+                        .get_or_insert_with(Default::default);
+                    if let Some(container_pull) = val.container_pull {
+                        timeouts.container_pull = Some(container_pull);
+                    }
+                    if let Some(bitcoin_ready) = val.bitcoin_ready {
+                        timeouts.bitcoin_ready = Some(bitcoin_ready);
+                    }
+                    if let Some(stacks_genesis) = val.stacks_genesis {
+                        timeouts.stacks_genesis = Some(stacks_genesis);
+                    }
+                    if let Some(epoch_3_reached) = val.epoch_3_reached {
+                        timeouts.epoch_3_reached = Some(epoch_3_reached);
+                    }
+                    if let Some(api_synced) = val.api_synced {
+                        timeouts.api_synced = Some(api_synced);
+                    }
+                }
+
                 if let Some(val) = devnet_override.bitcoin_controller_automining_disabled {
                     devnet_config.bitcoin_controller_automining_disabled = Some(val);
                 }
@@ -750,6 +1653,10 @@ impl NetworkManifest {
                     devnet_config.epoch_3_1 = Some(*val);
                 }
 
+                if let Some(val) = devnet_override.pox_2_activation {
+                    devnet_config.pox_2_activation = Some(val);
+                }
+
                 if let Some(val) = devnet_override.network_id {
                     devnet_config.network_id = Some(val);
                 }
@@ -757,6 +1664,20 @@ impl NetworkManifest {
                 if let Some(val) = devnet_override.use_docker_gateway_routing {
                     devnet_config.use_docker_gateway_routing = Some(val);
                 }
+
+                if let Some(ref val) = devnet_override.resources {
+                    devnet_config
+                        .resources
+                        .get_or_insert_with(BTreeMap::new)
+                        .extend(val.clone());
+                }
+
+                if let Some(ref val) = devnet_override.image_digests {
+                    devnet_config
+                        .image_digests
+                        .get_or_insert_with(BTreeMap::new)
+                        .extend(val.clone());
+                }
             };
 
             let now = clarity::util::get_epoch_time_secs();
@@ -818,6 +1739,18 @@ impl NetworkManifest {
                 networks,
             );
 
+            if let Some(ref services) = devnet_config.services {
+                let services = resolve_devnet_services(services)?;
+                let disabled = |service| Some(!services.contains(&service));
+                devnet_config.disable_postgres = disabled(DevnetService::Postgres);
+                devnet_config.disable_stacks_api = disabled(DevnetService::StacksApi);
+                devnet_config.disable_stacks_explorer = disabled(DevnetService::StacksExplorer);
+                devnet_config.disable_bitcoin_explorer = disabled(DevnetService::BitcoinExplorer);
+                devnet_config.disable_subnet_api = disabled(DevnetService::SubnetApi);
+                devnet_config.enable_subnet_node =
+                    Some(services.contains(&DevnetService::SubnetNode));
+            }
+
             let enable_subnet_node = devnet_config.enable_subnet_node.unwrap_or(false);
             let subnet_events_ingestion_port =
                 devnet_config.subnet_events_ingestion_port.unwrap_or(30445);
@@ -880,6 +1813,7 @@ impl NetworkManifest {
             let mut add_default_stacking_order = true;
             // for stacking orders, we validate that wallet names match one of the provided accounts
             if let Some(mut val) = devnet_config.pox_stacking_orders {
+                let min_stacking_amount = get_min_stacking_amount(&accounts);
                 for (i, stacking_order) in val.iter().enumerate() {
                     let wallet_name = &stacking_order.wallet;
 
@@ -894,6 +1828,9 @@ impl NetworkManifest {
                     if !wallet_is_in_accounts {
                         return Err(format!("Account data was not provided for the wallet ({}) listed in stacking order {}.", wallet_name, i + 1));
                     };
+
+                    validate_stacking_order(stacking_order, &accounts, min_stacking_amount)
+                        .map_err(|e| format!("Invalid stacking order {}: {}", i + 1, e))?;
                 }
 
                 stacking_orders.append(&mut val);
@@ -913,10 +1850,24 @@ impl NetworkManifest {
                         wallet: "stacker".into(),
                         slots: 10,
                         btc_address: account_config.btc_address.clone(),
+                        pool_operator: None,
                     })
                 }
             }
 
+            let docker_platform = devnet_config
+                .docker_platform
+                .take()
+                .unwrap_or(DEFAULT_DOCKER_PLATFORM.to_string());
+            let resources = resolve_container_resources(
+                devnet_config.resources.take().unwrap_or_default(),
+                &docker_platform,
+            )?;
+            let image_digests =
+                resolve_image_digests(devnet_config.image_digests.take().unwrap_or_default())?;
+            let soak = resolve_soak_config(devnet_config.soak.take())?;
+            let boot_timeouts = resolve_boot_timeouts(devnet_config.boot_timeouts.take());
+
             let config = DevnetConfig {
                 name: devnet_config.name.take().unwrap_or("devnet".into()),
                 network_id: devnet_config.network_id,
@@ -994,6 +1945,9 @@ impl NetworkManifest {
                     .subnet_api_postgres_database
                     .take()
                     .unwrap_or("subnet_api".to_string()),
+                postgres_max_connections: devnet_config.postgres_max_connections,
+                stacks_api_pg_connection_pool_max: devnet_config.stacks_api_pg_connection_pool_max,
+                stacks_api_max_lag: devnet_config.stacks_api_max_lag.unwrap_or(10),
                 execute_script: devnet_config.execute_script.take().unwrap_or_default(),
                 bitcoin_node_image_url: devnet_config
                     .bitcoin_node_image_url
@@ -1027,6 +1981,14 @@ impl NetworkManifest {
                 disable_bitcoin_explorer: devnet_config.disable_bitcoin_explorer.unwrap_or(false),
                 disable_stacks_api: devnet_config.disable_stacks_api.unwrap_or(false),
                 disable_postgres: devnet_config.disable_postgres.unwrap_or(false),
+                record_contract_calls: devnet_config.record_contract_calls.unwrap_or(false),
+                record_blocks: devnet_config.record_blocks.unwrap_or(false),
+                enable_webhook_receiver: devnet_config.enable_webhook_receiver.unwrap_or(false),
+                webhook_receiver_port: devnet_config.webhook_receiver_port.unwrap_or(20447),
+                auto_ports: devnet_config.auto_ports.unwrap_or(false),
+                soak,
+                boot_timeouts,
+                migrations: devnet_config.migrations.take().unwrap_or_default(),
                 disable_stacks_explorer: devnet_config.disable_stacks_explorer.unwrap_or(false),
                 bind_containers_volumes: devnet_config.bind_containers_volumes.unwrap_or(false),
                 enable_subnet_node,
@@ -1070,6 +2032,7 @@ impl NetworkManifest {
                 epoch_2_5: devnet_config.epoch_2_5.unwrap_or(DEFAULT_EPOCH_2_5),
                 epoch_3_0: devnet_config.epoch_3_0.unwrap_or(DEFAULT_EPOCH_3_0),
                 epoch_3_1: devnet_config.epoch_3_1.unwrap_or(DEFAULT_EPOCH_3_1),
+                pox_2_activation: devnet_config.pox_2_activation,
                 stacks_node_env_vars: devnet_config
                     .stacks_node_env_vars
                     .take()
@@ -1100,9 +2063,9 @@ impl NetworkManifest {
                 use_docker_gateway_routing: devnet_config
                     .use_docker_gateway_routing
                     .unwrap_or(false),
-                docker_platform: devnet_config
-                    .docker_platform
-                    .unwrap_or(DEFAULT_DOCKER_PLATFORM.to_string()),
+                docker_platform,
+                resources,
+                image_digests,
             };
             Some(config)
         } else {
@@ -1176,6 +2139,62 @@ fn compute_btc_address(public_key: &PublicKey, network: &BitcoinNetwork) -> Stri
 
 // This logic was taken from stacks-core:
 // https://github.com/stacks-network/stacks-core/blob/524b0e1ae9ad3c8d2d2ac37e72be4aee2c045ef8/src/burnchains/mod.rs#L513C30-L530
+fn validate_stacking_order(
+    stacking_order: &PoxStackingOrder,
+    accounts: &BTreeMap<String, AccountConfig>,
+    min_stacking_amount: u64,
+) -> Result<(), String> {
+    if stacking_order.start_at_cycle == 0 {
+        return Err("start_at_cycle must be greater than 0".into());
+    }
+    if stacking_order.duration == 0 || stacking_order.duration > 12 {
+        return Err("duration must be between 1 and 12 cycles".into());
+    }
+    if stacking_order.slots == 0 {
+        return Err("slots must be greater than 0".into());
+    }
+    if let Some(ref pool_operator) = stacking_order.pool_operator {
+        if pool_operator == &stacking_order.wallet {
+            return Err("the pool operator must be different from the stacking wallet".into());
+        }
+        if !accounts.contains_key(pool_operator) {
+            return Err(format!(
+                "account data was not provided for the pool operator ({})",
+                pool_operator
+            ));
+        }
+    }
+    // the delegated STX are locked from the balance of the wallet as well
+    let required_balance = (stacking_order.slots as u128) * (min_stacking_amount as u128);
+    let balance = accounts
+        .get(&stacking_order.wallet)
+        .map(|account| account.balance)
+        .unwrap_or_default();
+    if (balance as u128) < required_balance {
+        return Err(format!(
+            "{} needs a balance of at least {} µSTX to stack {} slots ({} µSTX per slot), its balance is {} µSTX",
+            stacking_order.wallet, required_balance, stacking_order.slots, min_stacking_amount, balance
+        ));
+    }
+    Ok(())
+}
+
+/// Amount locked per slot in the first reward cycles, the stacking threshold of the stacks-node
+/// with a participation under 25% of the liquid supply (the genesis balances of the accounts).
+/// The threshold raises with the participation, so this is a lower bound.
+pub fn get_min_stacking_amount(accounts: &BTreeMap<String, AccountConfig>) -> u64 {
+    let liquid_ustx: u128 = accounts
+        .values()
+        .map(|account| account.balance as u128)
+        .sum();
+    let threshold = liquid_ustx / 4 / DEVNET_REWARD_SLOTS;
+    let threshold = match threshold % POX_THRESHOLD_STEPS_USTX {
+        0 => threshold,
+        remainder => threshold + POX_THRESHOLD_STEPS_USTX - remainder,
+    };
+    threshold.max(POX_THRESHOLD_STEPS_USTX) as u64
+}
+
 pub fn is_in_reward_phase(
     first_block_height: u64,
     reward_cycle_length: u64,
@@ -0,0 +1,293 @@
+//! Typed edits of the network manifests (`settings/Devnet.toml`, ...).
+//! The document is edited in place, so that comments and formatting are preserved
+//! when the manifest is written back.
+
+use std::collections::BTreeMap;
+
+use bip39::{Language, Mnemonic};
+use toml_edit::{value, Document, Item, Table};
+
+use super::network_manifest::{
+    is_in_reward_phase, DEFAULT_POX_PREPARE_LENGTH, DEFAULT_POX_REWARD_LENGTH,
+};
+use super::{
+    BitcoinNetwork, FileLocation, NetworkManifest, NetworkManifestFile, StacksNetwork,
+    DEFAULT_DERIVATION_PATH, DEFAULT_EPOCH_2_0, DEFAULT_EPOCH_2_05, DEFAULT_EPOCH_2_1,
+    DEFAULT_EPOCH_2_2, DEFAULT_EPOCH_2_3, DEFAULT_EPOCH_2_4, DEFAULT_EPOCH_2_5, DEFAULT_EPOCH_3_0,
+    DEFAULT_EPOCH_3_1, DEFAULT_FIRST_BURN_HEADER_HEIGHT,
+};
+
+/// The ports settings of the `[devnet]` section, with their default values
+pub const DEVNET_DEFAULT_PORTS: [(&str, u16); 17] = [
+    ("orchestrator_port", 20445),
+    ("orchestrator_control_port", 20446),
+    ("bitcoin_node_p2p_port", 18444),
+    ("bitcoin_node_rpc_port", 18443),
+    ("stacks_node_p2p_port", 20444),
+    ("stacks_node_rpc_port", 20443),
+    ("stacks_api_port", 3999),
+    ("stacks_api_events_port", 3700),
+    ("stacks_explorer_port", 8000),
+    ("bitcoin_explorer_port", 8001),
+    ("postgres_port", 5432),
+    ("subnet_node_p2p_port", 30444),
+    ("subnet_node_rpc_port", 30443),
+    ("subnet_events_ingestion_port", 30445),
+    ("subnet_api_port", 13999),
+    ("subnet_api_events_port", 13700),
+    ("webhook_receiver_port", 20447),
+];
+
+/// The epochs settings of the `[devnet]` section, in activation order
+pub const DEVNET_DEFAULT_EPOCHS: [(&str, u64); 9] = [
+    ("2.0", DEFAULT_EPOCH_2_0),
+    ("2.05", DEFAULT_EPOCH_2_05),
+    ("2.1", DEFAULT_EPOCH_2_1),
+    ("2.2", DEFAULT_EPOCH_2_2),
+    ("2.3", DEFAULT_EPOCH_2_3),
+    ("2.4", DEFAULT_EPOCH_2_4),
+    ("2.5", DEFAULT_EPOCH_2_5),
+    ("3.0", DEFAULT_EPOCH_3_0),
+    ("3.1", DEFAULT_EPOCH_3_1),
+];
+
+fn get_epoch_key(epoch: &str) -> String {
+    format!("epoch_{}", epoch.replace('.', "_"))
+}
+
+pub struct NetworkManifestEditor {
+    pub location: FileLocation,
+    document: Document,
+}
+
+impl NetworkManifestEditor {
+    pub fn from_location(location: &FileLocation) -> Result<Self, String> {
+        let content = location.read_content_as_utf8()?;
+        Self::from_content(location, &content)
+    }
+
+    pub fn from_content(location: &FileLocation, content: &str) -> Result<Self, String> {
+        let document = content
+            .parse::<Document>()
+            .map_err(|e| format!("unable to parse {}: {}", location, e))?;
+        let editor = Self {
+            location: location.clone(),
+            document,
+        };
+        editor.get_network_manifest_file()?;
+        Ok(editor)
+    }
+
+    fn get_network_manifest_file(&self) -> Result<NetworkManifestFile, String> {
+        toml::from_str(&self.document.to_string())
+            .map_err(|e| format!("invalid network manifest {}: {}", self.location, e))
+    }
+
+    fn get_or_insert_table(&mut self, key: &str) -> Result<&mut Table, String> {
+        let item = self.document.entry(key).or_insert_with(|| {
+            let mut table = Table::new();
+            table.set_implicit(true);
+            Item::Table(table)
+        });
+        item.as_table_mut()
+            .ok_or(format!("[{}] must be a table", key))
+    }
+
+    fn get_devnet_integer(&self, key: &str) -> Option<i64> {
+        self.document
+            .get("devnet")
+            .and_then(|devnet| devnet.get(key))
+            .and_then(|item| item.as_integer())
+    }
+
+    pub fn get_accounts_names(&self) -> Vec<String> {
+        self.document
+            .get("accounts")
+            .and_then(|accounts| accounts.as_table())
+            .map(|accounts| accounts.iter().map(|(name, _)| name.to_string()).collect())
+            .unwrap_or_default()
+    }
+
+    pub fn add_account(
+        &mut self,
+        name: &str,
+        mnemonic: &str,
+        derivation: Option<&str>,
+        balance: u64,
+    ) -> Result<(), String> {
+        if name.is_empty() || name.contains(|c: char| c.is_whitespace() || c == '.') {
+            return Err(format!("invalid account name '{}'", name));
+        }
+        if self
+            .get_accounts_names()
+            .iter()
+            .any(|account| account == name)
+        {
+            return Err(format!("account '{}' already exists", name));
+        }
+        let mnemonic = Mnemonic::parse_in_normalized(Language::English, mnemonic)
+            .map_err(|e| format!("mnemonic of account '{}' is invalid: {}", name, e))?;
+        let balance =
+            i64::try_from(balance).map_err(|_| format!("balance {} is too large", balance))?;
+
+        let mut account = Table::new();
+        account.insert("mnemonic", value(mnemonic.to_string()));
+        if let Some(derivation) = derivation.filter(|d| *d != DEFAULT_DERIVATION_PATH) {
+            account.insert("derivation", value(derivation));
+        }
+        account.insert("balance", value(balance));
+        self.get_or_insert_table("accounts")?
+            .insert(name, Item::Table(account));
+        Ok(())
+    }
+
+    pub fn set_account_balance(&mut self, name: &str, balance: u64) -> Result<(), String> {
+        let balance =
+            i64::try_from(balance).map_err(|_| format!("balance {} is too large", balance))?;
+        let account = self
+            .document
+            .get_mut("accounts")
+            .and_then(|accounts| accounts.get_mut(name))
+            .and_then(|account| account.as_table_mut())
+            .ok_or(format!("account '{}' not found", name))?;
+        account.insert("balance", value(balance));
+        Ok(())
+    }
+
+    pub fn remove_account(&mut self, name: &str) -> Result<(), String> {
+        self.document
+            .get_mut("accounts")
+            .and_then(|accounts| accounts.as_table_mut())
+            .and_then(|accounts| accounts.remove(name))
+            .map(|_| ())
+            .ok_or(format!("account '{}' not found", name))
+    }
+
+    /// Returns the activation height of each epoch, from the manifest or the defaults
+    pub fn get_epochs_heights(&self) -> Vec<(&'static str, u64)> {
+        DEVNET_DEFAULT_EPOCHS
+            .iter()
+            .map(|(epoch, default)| {
+                let height = self
+                    .get_devnet_integer(&get_epoch_key(epoch))
+                    .map(|height| height as u64)
+                    .unwrap_or(*default);
+                (*epoch, height)
+            })
+            .collect()
+    }
+
+    /// Sets the activation height of an epoch (`"2.5"`, `"3.0"`, ...). Epochs must keep
+    /// activating in order, and epoch 3.0 must start during a reward phase.
+    pub fn set_epoch_height(&mut self, epoch: &str, height: u64) -> Result<(), String> {
+        if !DEVNET_DEFAULT_EPOCHS.iter().any(|(e, _)| *e == epoch) {
+            return Err(format!("unknown epoch {}", epoch));
+        }
+        let mut heights = self.get_epochs_heights();
+        for (e, h) in heights.iter_mut() {
+            if *e == epoch {
+                *h = height;
+            }
+        }
+        for window in heights.windows(2) {
+            let ((previous, previous_height), (next, next_height)) = (window[0], window[1]);
+            if next_height < previous_height {
+                return Err(format!(
+                    "epoch {} (block {}) can't start before epoch {} (block {})",
+                    next, next_height, previous, previous_height
+                ));
+            }
+        }
+        if epoch == "3.0"
+            && !is_in_reward_phase(
+                DEFAULT_FIRST_BURN_HEADER_HEIGHT,
+                DEFAULT_POX_REWARD_LENGTH,
+                DEFAULT_POX_PREPARE_LENGTH,
+                &height,
+            )
+        {
+            return Err(format!(
+                "epoch 3.0 must start during a reward phase, block {} is in a prepare phase",
+                height
+            ));
+        }
+        let height = i64::try_from(height).map_err(|_| format!("invalid height {}", height))?;
+        self.get_or_insert_table("devnet")?
+            .insert(&get_epoch_key(epoch), value(height));
+        Ok(())
+    }
+
+    /// Returns the port of each devnet service, from the manifest or the defaults
+    pub fn get_devnet_ports(&self) -> BTreeMap<&'static str, u16> {
+        DEVNET_DEFAULT_PORTS
+            .iter()
+            .map(|(key, default)| {
+                let port = self
+                    .get_devnet_integer(key)
+                    .and_then(|port| u16::try_from(port).ok())
+                    .unwrap_or(*default);
+                (*key, port)
+            })
+            .collect()
+    }
+
+    /// Overrides some devnet ports (`stacks_node_rpc_port`, ...). The overrides are rejected
+    /// if two services end up sharing a port.
+    pub fn override_devnet_ports(&mut self, overrides: &[(&str, u16)]) -> Result<(), String> {
+        let mut ports = self.get_devnet_ports();
+        for (key, port) in overrides.iter() {
+            let Some(current) = ports.get_mut(key) else {
+                return Err(format!("unknown port setting {}", key));
+            };
+            if *port == 0 {
+                return Err(format!("invalid port 0 for {}", key));
+            }
+            *current = *port;
+        }
+        let mut used: BTreeMap<u16, &str> = BTreeMap::new();
+        for (key, port) in ports.iter() {
+            if let Some(other) = used.insert(*port, *key) {
+                return Err(format!(
+                    "port {} is used by both {} and {}",
+                    port, other, key
+                ));
+            }
+        }
+        let devnet = self.get_or_insert_table("devnet")?;
+        for (key, port) in overrides.iter() {
+            devnet.insert(key, value(*port as i64));
+        }
+        Ok(())
+    }
+
+    pub fn set_devnet_port(&mut self, key: &str, port: u16) -> Result<(), String> {
+        self.override_devnet_ports(&[(key, port)])
+    }
+
+    pub fn to_network_manifest(
+        &self,
+        networks: &(BitcoinNetwork, StacksNetwork),
+        cache_location: Option<&FileLocation>,
+    ) -> Result<NetworkManifest, String> {
+        let mut network_manifest_file = self.get_network_manifest_file()?;
+        NetworkManifest::from_network_manifest_file(
+            &mut network_manifest_file,
+            networks,
+            cache_location,
+            None,
+        )
+    }
+
+    /// Writes the manifest back to its location, after checking that it can still be loaded
+    pub fn write(&self) -> Result<(), String> {
+        self.get_network_manifest_file()?;
+        self.location
+            .write_content(self.document.to_string().as_bytes())
+    }
+}
+
+impl std::fmt::Display for NetworkManifestEditor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.document)
+    }
+}
@@ -1,10 +1,15 @@
-use crate::FileAccessor;
+use crate::token_metadata::{get_token_metadata, TokenMetadataConfig};
+use crate::{FileAccessor, StacksNetwork};
 
 use super::FileLocation;
 use clarity::types::StacksEpochId;
-use clarity::vm::ClarityVersion;
+use clarity::vm::types::PrincipalData;
+use clarity::vm::{ClarityName, ClarityVersion};
 use clarity_repl::repl;
-use clarity_repl::repl::{ClarityCodeSource, ClarityContract, ContractDeployer};
+use clarity_repl::repl::address_book::AddressBook;
+use clarity_repl::repl::clarity_versions;
+use clarity_repl::repl::session::{SBTC_CONTRACTS, SBTC_MAINNET_ADDRESS, SBTC_TESTNET_ADDRESS};
+use clarity_repl::repl::{ClarityCodeSource, ClarityContract, ContractDeployer, EpochSpec};
 use serde::ser::SerializeMap;
 use serde::{Deserializer, Serialize, Serializer};
 use serde_json::Value as JsonValue;
@@ -15,8 +20,12 @@ use toml::Value as TomlValue;
 
 pub const INVALID_CLARITY_VERSION: &str =
     "clarity_version field invalid (value supported: 1, 2, 3)";
-const INVALID_EPOCH: &str =
-    "epoch field invalid (value supported: 2.0, 2.05, 2.1, 2.2, 2.3, 2.4, 3.0)";
+const INVALID_NETWORKS: &str =
+    "networks field invalid (value supported: simnet, devnet, testnet, mainnet)";
+const INVALID_DEFINE: &str =
+    "define invalid (expected a Clarity expression, or a table with a value and network overrides)";
+const INVALID_ADDRESS: &str =
+    "address invalid (expected a principal, or a table with a value and network overrides)";
 
 #[derive(Deserialize, Debug, Clone)]
 pub struct ClarityContractMetadata {
@@ -40,6 +49,8 @@ pub struct ProjectConfigFile {
     description: Option<String>,
     telemetry: Option<bool>,
     requirements: Option<TomlValue>,
+    defines: Option<TomlValue>,
+    addresses: Option<TomlValue>,
     boot_contracts: Option<Vec<String>>,
 
     // The fields below have been moved into repl above, but are kept here for
@@ -48,19 +59,85 @@ pub struct ProjectConfigFile {
     cache_dir: Option<String>,
 }
 
-#[derive(Deserialize, Serialize, Debug, Clone)]
+#[derive(Deserialize, Debug, Clone)]
 pub struct ProjectManifest {
     pub project: ProjectConfig,
-    #[serde(serialize_with = "toml::ser::tables_last")]
     #[serde(deserialize_with = "contracts_deserializer")]
     pub contracts: BTreeMap<String, ClarityContract>,
     #[serde(rename = "repl")]
     pub repl_settings: repl::Settings,
-    #[serde(skip_serializing)]
     #[serde(default = "default_location")]
     pub location: FileLocation,
-    #[serde(skip_serializing, skip_deserializing)]
+    #[serde(skip_deserializing)]
     pub contracts_settings: HashMap<FileLocation, ClarityContractMetadata>,
+    /// Networks a contract is restricted to, contracts not listed are deployed on every network
+    #[serde(skip_deserializing)]
+    pub contracts_networks: BTreeMap<String, Vec<StacksNetwork>>,
+    /// Names of the project defines injected in each contract
+    #[serde(skip_deserializing)]
+    pub contracts_defines: BTreeMap<String, Vec<String>>,
+    /// Explicit deployment order of the contracts, on top of their dependencies
+    #[serde(skip_deserializing)]
+    pub contracts_order: BTreeMap<String, i64>,
+    /// SIP-016 metadata of the token contracts
+    #[serde(skip_deserializing)]
+    pub contracts_metadata: BTreeMap<String, TokenMetadataConfig>,
+    /// Settings read with a fallback, e.g. an epoch unknown to this version of clarinet
+    #[serde(skip_deserializing)]
+    pub warnings: Vec<String>,
+}
+
+impl Serialize for ProjectManifest {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut contracts = BTreeMap::new();
+        for (contract_name, contract) in self.contracts.iter() {
+            let mut contract_settings =
+                TomlValue::try_from(contract).map_err(serde::ser::Error::custom)?;
+            if let (Some(networks), TomlValue::Table(settings)) = (
+                self.contracts_networks.get(contract_name),
+                &mut contract_settings,
+            ) {
+                settings.insert(
+                    "networks".into(),
+                    TomlValue::try_from(networks).map_err(serde::ser::Error::custom)?,
+                );
+            }
+            if let (Some(defines), TomlValue::Table(settings)) = (
+                self.contracts_defines.get(contract_name),
+                &mut contract_settings,
+            ) {
+                settings.insert(
+                    "defines".into(),
+                    TomlValue::try_from(defines).map_err(serde::ser::Error::custom)?,
+                );
+            }
+            if let (Some(order), TomlValue::Table(settings)) = (
+                self.contracts_order.get(contract_name),
+                &mut contract_settings,
+            ) {
+                settings.insert("order".into(), TomlValue::Integer(*order));
+            }
+            if let (Some(metadata), TomlValue::Table(settings)) = (
+                self.contracts_metadata.get(contract_name),
+                &mut contract_settings,
+            ) {
+                settings.insert(
+                    "metadata".into(),
+                    TomlValue::try_from(metadata).map_err(serde::ser::Error::custom)?,
+                );
+            }
+            contracts.insert(contract_name, contract_settings);
+        }
+
+        let mut map = serializer.serialize_map(Some(3))?;
+        map.serialize_entry("project", &self.project)?;
+        map.serialize_entry("contracts", &contracts)?;
+        map.serialize_entry("repl", &self.repl_settings)?;
+        map.end()
+    }
 }
 
 fn default_location() -> FileLocation {
@@ -102,7 +179,7 @@ where
             None => None,
             Some(JsonValue::String(epoch)) => Some(epoch.as_str()),
             Some(JsonValue::Number(epoch)) => Some(epoch.as_str()),
-            _ => return Err(serde::de::Error::custom(INVALID_EPOCH)),
+            _ => return Err(serde::de::Error::custom(get_invalid_epoch_error())),
         };
 
         let parsed_clarity_version = match contract_settings.get("clarity_version") {
@@ -135,6 +212,12 @@ pub struct ProjectConfig {
     pub description: String,
     pub telemetry: bool,
     pub requirements: Option<Vec<RequirementConfig>>,
+    /// Constants injected in the contracts listing them in their `defines` setting
+    #[serde(default)]
+    pub defines: BTreeMap<String, ProjectDefine>,
+    /// Principals the plans, the defines and the console can refer to as `@name`
+    #[serde(default)]
+    pub addresses: BTreeMap<String, ProjectAddress>,
     #[serde(rename = "cache_dir")]
     #[serde(deserialize_with = "cache_location_deserializer")]
     pub cache_location: FileLocation,
@@ -170,16 +253,227 @@ impl Serialize for ProjectConfig {
         if self.requirements.is_some() {
             map.serialize_entry("requirements", &self.requirements)?;
         }
+        if !self.defines.is_empty() {
+            map.serialize_entry(
+                "defines",
+                &TomlTableSerializer(&self.defines, |define| define.networks_values.is_empty()),
+            )?;
+        }
+        if !self.addresses.is_empty() {
+            map.serialize_entry(
+                "addresses",
+                &TomlTableSerializer(&self.addresses, |address| {
+                    address.networks_principals.is_empty() && address.principal.is_some()
+                }),
+            )?;
+        }
         map.end()
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
 pub struct RequirementConfig {
     pub contract_id: String,
+    /// In simnet, replace the contract by a mock generated from its interface
+    #[serde(default)]
+    pub mock: bool,
+    /// Override the epoch the requirement was deployed in
+    #[serde(default)]
+    pub epoch: Option<StacksEpochId>,
+    /// Override the clarity version of the requirement
+    #[serde(default)]
+    pub clarity_version: Option<ClarityVersion>,
+}
+
+impl Serialize for RequirementConfig {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(None)?;
+        map.serialize_entry("contract_id", &self.contract_id)?;
+        if self.mock {
+            map.serialize_entry("mock", &true)?;
+        }
+        if let Some(clarity_version) = self.clarity_version {
+            map.serialize_entry(
+                "clarity_version",
+                &clarity_versions::version_number(clarity_version),
+            )?;
+        }
+        if let Some(epoch) = self.epoch {
+            map.serialize_entry("epoch", &epoch_to_float(epoch))?;
+        }
+        map.end()
+    }
+}
+
+fn epoch_to_float(epoch: StacksEpochId) -> f64 {
+    match epoch {
+        StacksEpochId::Epoch10 => 1.0,
+        StacksEpochId::Epoch20 => 2.0,
+        StacksEpochId::Epoch2_05 => 2.05,
+        StacksEpochId::Epoch21 => 2.1,
+        StacksEpochId::Epoch22 => 2.2,
+        StacksEpochId::Epoch23 => 2.3,
+        StacksEpochId::Epoch24 => 2.4,
+        StacksEpochId::Epoch25 => 2.5,
+        StacksEpochId::Epoch30 => 3.0,
+        StacksEpochId::Epoch31 => 3.1,
+    }
+}
+
+/// A constant shared by several contracts, expressed in Clarity (e.g. `u30`),
+/// with optional per network overrides
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct ProjectDefine {
+    pub value: String,
+    #[serde(default)]
+    pub networks_values: Vec<(StacksNetwork, String)>,
+}
+
+impl ProjectDefine {
+    pub fn get_value(&self, network: &StacksNetwork) -> &str {
+        self.networks_values
+            .iter()
+            .find(|(define_network, _)| define_network == network)
+            .map(|(_, value)| value.as_str())
+            .unwrap_or(&self.value)
+    }
+}
+
+impl Serialize for ProjectDefine {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if self.networks_values.is_empty() {
+            return serializer.serialize_str(&self.value);
+        }
+        let mut map = serializer.serialize_map(None)?;
+        map.serialize_entry("value", &self.value)?;
+        for (network, value) in self.networks_values.iter() {
+            map.serialize_entry(get_network_name(network), value)?;
+        }
+        map.end()
+    }
+}
+
+/// A principal that can be referred to as `@name`, with optional per network overrides.
+/// An address without a `value` is only defined on the networks it lists.
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct ProjectAddress {
+    pub principal: Option<String>,
+    #[serde(default)]
+    pub networks_principals: Vec<(StacksNetwork, String)>,
+}
+
+impl ProjectAddress {
+    pub fn get_principal(&self, network: &StacksNetwork) -> Option<&str> {
+        self.networks_principals
+            .iter()
+            .find(|(address_network, _)| address_network == network)
+            .map(|(_, principal)| principal.as_str())
+            .or(self.principal.as_deref())
+    }
+}
+
+impl Serialize for ProjectAddress {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if let (Some(principal), true) = (&self.principal, self.networks_principals.is_empty()) {
+            return serializer.serialize_str(principal);
+        }
+        let mut map = serializer.serialize_map(None)?;
+        if let Some(principal) = &self.principal {
+            map.serialize_entry("value", principal)?;
+        }
+        for (network, principal) in self.networks_principals.iter() {
+            map.serialize_entry(get_network_name(network), principal)?;
+        }
+        map.end()
+    }
+}
+
+/// Entries of a table of the manifest, the second field tells the entries written as
+/// plain values from the ones written as tables
+struct TomlTableSerializer<'a, T>(&'a BTreeMap<String, T>, fn(&T) -> bool);
+
+impl<T: Serialize> Serialize for TomlTableSerializer<'_, T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        // toml requires the plain values to be written before the tables
+        let (values, tables): (Vec<_>, Vec<_>) =
+            self.0.iter().partition(|(_, entry)| (self.1)(entry));
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for (name, entry) in values.into_iter().chain(tables) {
+            map.serialize_entry(name, entry)?;
+        }
+        map.end()
+    }
 }
 
 impl ProjectManifest {
+    /// Returns the `define-constant` declarations injected in a contract on a network. The
+    /// principals referred to by name in the values are resolved with `address_book`.
+    pub fn get_contract_defines(
+        &self,
+        contract_name: &str,
+        network: &StacksNetwork,
+        address_book: &AddressBook,
+    ) -> Result<Option<String>, String> {
+        let Some(names) = self.contracts_defines.get(contract_name) else {
+            return Ok(None);
+        };
+        let mut declarations = vec![];
+        for name in names.iter() {
+            let Some(define) = self.project.defines.get(name) else {
+                continue;
+            };
+            let value = address_book
+                .resolve_expression(define.get_value(network))
+                .map_err(|e| format!("define {}: {}", name, e))?;
+            declarations.push(format!("(define-constant {} {})", name, value));
+        }
+        if declarations.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(declarations.join("\n")))
+    }
+
+    /// Principals named on a network: the sBTC contracts, the accounts of the network (e.g.
+    /// `deployer`, `wallet_1`) and the `[project.addresses]`, by order of precedence
+    pub fn get_address_book<'a>(
+        &self,
+        network: &StacksNetwork,
+        accounts: impl IntoIterator<Item = (&'a str, &'a str)>,
+    ) -> AddressBook {
+        let mut address_book = AddressBook::new();
+        let sbtc_address = match network {
+            StacksNetwork::Testnet => SBTC_TESTNET_ADDRESS,
+            _ => SBTC_MAINNET_ADDRESS,
+        };
+        for contract_name in SBTC_CONTRACTS {
+            let _ = address_book.insert(
+                contract_name,
+                &format!("{}.{}", sbtc_address, contract_name),
+            );
+        }
+        for (name, address) in accounts {
+            let _ = address_book.insert(name, address);
+        }
+        for (name, address) in self.project.addresses.iter() {
+            if let Some(principal) = address.get_principal(network) {
+                let _ = address_book.insert(name, principal);
+            }
+        }
+        address_book
+    }
+
     pub async fn from_file_accessor(
         location: &FileLocation,
         file_accessor: &dyn FileAccessor,
@@ -226,6 +520,9 @@ impl ProjectManifest {
 
         let project_name = project_manifest_file.project.name;
         let project_root_location = manifest_location.get_parent_location()?;
+        repl_settings
+            .analysis
+            .resolve_plugins_paths(&project_root_location.to_string());
         let cache_location = match project_manifest_file.project.cache_dir {
             Some(ref path) => FileLocation::try_parse(path, Some(&project_root_location))
                 .ok_or(format!("unable to parse path {}", path))?,
@@ -236,9 +533,23 @@ impl ProjectManifest {
             }
         };
 
+        let defines = match project_manifest_file.project.defines {
+            Some(TomlValue::Table(ref defines)) => get_defines(defines)?,
+            Some(_) => return Err(INVALID_DEFINE.into()),
+            None => BTreeMap::new(),
+        };
+
+        let addresses = match project_manifest_file.project.addresses {
+            Some(TomlValue::Table(ref addresses)) => get_addresses(addresses)?,
+            Some(_) => return Err(INVALID_ADDRESS.into()),
+            None => BTreeMap::new(),
+        };
+
         let project = ProjectConfig {
             name: project_name.clone(),
             requirements: None,
+            defines,
+            addresses,
             description: project_manifest_file
                 .project
                 .description
@@ -266,19 +577,27 @@ impl ProjectManifest {
             repl_settings,
             location: manifest_location.clone(),
             contracts_settings: HashMap::new(),
+            contracts_networks: BTreeMap::new(),
+            contracts_defines: BTreeMap::new(),
+            contracts_order: BTreeMap::new(),
+            contracts_metadata: BTreeMap::new(),
+            warnings: vec![],
         };
         let mut config_contracts = BTreeMap::new();
         let mut contracts_settings = HashMap::new();
+        let mut contracts_networks = BTreeMap::new();
+        let mut contracts_defines = BTreeMap::new();
+        let mut contracts_order = BTreeMap::new();
+        let mut contracts_metadata = BTreeMap::new();
         let mut config_requirements: Vec<RequirementConfig> = Vec::new();
+        let mut warnings = vec![];
 
         if let Some(TomlValue::Array(requirements)) = project_manifest_file.project.requirements {
             for link_settings in requirements.iter() {
                 if let TomlValue::Table(link_settings) = link_settings {
-                    let contract_id = match link_settings.get("contract_id") {
-                        Some(TomlValue::String(contract_id)) => contract_id.to_string(),
-                        _ => continue,
-                    };
-                    config_requirements.push(RequirementConfig { contract_id });
+                    if let Some(requirement) = get_requirement(link_settings, &mut warnings)? {
+                        config_requirements.push(requirement);
+                    }
                 }
             }
         };
@@ -302,12 +621,11 @@ impl ProjectManifest {
                         _ => ContractDeployer::DefaultDeployer,
                     };
 
-                    let parsed_epoch = match contract_settings.get("epoch") {
-                        Some(TomlValue::String(epoch)) => Some(epoch.clone()),
-                        Some(TomlValue::Float(epoch)) => Some(epoch.to_string()),
-                        None => None,
-                        _ => return Err(INVALID_EPOCH.into()),
-                    };
+                    let parsed_epoch = get_epoch_setting(
+                        contract_settings.get("epoch"),
+                        &format!("contract {}", contract_name),
+                        &mut warnings,
+                    )?;
 
                     let parsed_clarity_version = match contract_settings.get("clarity_version") {
                         Some(TomlValue::Integer(clarity_version)) => {
@@ -318,10 +636,40 @@ impl ProjectManifest {
                     };
 
                     let (epoch, clarity_version) = get_epoch_and_clarity_version(
-                        parsed_epoch.as_deref(),
+                        parsed_epoch.map(|epoch| epoch.as_str()),
                         parsed_clarity_version.as_deref(),
                     )?;
 
+                    if let Some(networks) = contract_settings.get("networks") {
+                        contracts_networks
+                            .insert(contract_name.to_string(), get_networks(networks)?);
+                    }
+
+                    if let Some(defines) = contract_settings.get("defines") {
+                        let defines = get_contract_defines(defines, &config.project.defines)
+                            .map_err(|e| format!("contract {}: {}", contract_name, e))?;
+                        contracts_defines.insert(contract_name.to_string(), defines);
+                    }
+
+                    match contract_settings.get("order") {
+                        Some(TomlValue::Integer(order)) => {
+                            contracts_order.insert(contract_name.to_string(), *order);
+                        }
+                        Some(_) => {
+                            return Err(format!(
+                                "contract {}: order must be an integer",
+                                contract_name
+                            ))
+                        }
+                        None => {}
+                    }
+
+                    if let Some(metadata) = contract_settings.get("metadata") {
+                        let metadata = get_token_metadata(metadata)
+                            .map_err(|e| format!("contract {}: {}", contract_name, e))?;
+                        contracts_metadata.insert(contract_name.to_string(), metadata);
+                    }
+
                     config_contracts.insert(
                         contract_name.to_string(),
                         ClarityContract {
@@ -349,12 +697,215 @@ impl ProjectManifest {
         };
         config.contracts = config_contracts;
         config.contracts_settings = contracts_settings;
+        config.contracts_networks = contracts_networks;
+        config.contracts_defines = contracts_defines;
+        config.contracts_order = contracts_order;
+        config.contracts_metadata = contracts_metadata;
         config.project.requirements = Some(config_requirements);
+        config.warnings = warnings;
         Ok(config)
     }
+
+    pub fn is_contract_enabled_on_network(
+        &self,
+        contract_name: &str,
+        network: &StacksNetwork,
+    ) -> bool {
+        match self.contracts_networks.get(contract_name) {
+            Some(networks) => networks.contains(network),
+            None => true,
+        }
+    }
+
+    /// Location of the SIP-016 metadata JSON of a contract, if it has one
+    pub fn get_token_metadata_location(
+        &self,
+        contract_name: &str,
+    ) -> Result<Option<FileLocation>, String> {
+        let Some(metadata) = self.contracts_metadata.get(contract_name) else {
+            return Ok(None);
+        };
+        let mut location = self.location.get_project_root_location()?;
+        location.append_path(&metadata.file)?;
+        Ok(Some(location))
+    }
+}
+
+fn get_requirement(
+    settings: &toml::map::Map<String, TomlValue>,
+    warnings: &mut Vec<String>,
+) -> Result<Option<RequirementConfig>, String> {
+    let contract_id = match settings.get("contract_id") {
+        Some(TomlValue::String(contract_id)) => contract_id.to_string(),
+        _ => return Ok(None),
+    };
+    let mock = matches!(settings.get("mock"), Some(TomlValue::Boolean(true)));
+
+    let epoch = get_epoch_setting(
+        settings.get("epoch"),
+        &format!("requirement {}", contract_id),
+        warnings,
+    )?
+    .map(StacksEpochId::from);
+    let clarity_version = match settings.get("clarity_version") {
+        Some(TomlValue::Integer(clarity_version)) => {
+            Some(parse_clarity_version(&clarity_version.to_string())?)
+        }
+        None => None,
+        _ => return Err(INVALID_CLARITY_VERSION.into()),
+    };
+    if let (Some(epoch), Some(clarity_version)) = (epoch, clarity_version) {
+        check_clarity_version_for_epoch(epoch, clarity_version)
+            .map_err(|e| format!("requirement {}: {}", contract_id, e))?;
+    }
+
+    Ok(Some(RequirementConfig {
+        contract_id,
+        mock,
+        epoch,
+        clarity_version,
+    }))
+}
+
+fn parse_network(network: &str) -> Option<StacksNetwork> {
+    match network {
+        "simnet" => Some(StacksNetwork::Simnet),
+        "devnet" => Some(StacksNetwork::Devnet),
+        "testnet" => Some(StacksNetwork::Testnet),
+        "mainnet" => Some(StacksNetwork::Mainnet),
+        _ => None,
+    }
+}
+
+fn get_network_name(network: &StacksNetwork) -> &'static str {
+    match network {
+        StacksNetwork::Simnet => "simnet",
+        StacksNetwork::Devnet => "devnet",
+        StacksNetwork::Testnet => "testnet",
+        StacksNetwork::Mainnet => "mainnet",
+    }
+}
+
+fn get_networks(settings_networks: &TomlValue) -> Result<Vec<StacksNetwork>, String> {
+    let TomlValue::Array(networks) = settings_networks else {
+        return Err(INVALID_NETWORKS.into());
+    };
+    networks
+        .iter()
+        .map(|network| {
+            network
+                .as_str()
+                .and_then(parse_network)
+                .ok_or(INVALID_NETWORKS.into())
+        })
+        .collect()
+}
+
+fn get_defines(
+    settings: &toml::map::Map<String, TomlValue>,
+) -> Result<BTreeMap<String, ProjectDefine>, String> {
+    let mut defines = BTreeMap::new();
+    for (name, settings) in settings.iter() {
+        if ClarityName::try_from(name.to_string()).is_err() {
+            return Err(format!("invalid define name: {}", name));
+        }
+        let define = match settings {
+            TomlValue::String(value) => ProjectDefine {
+                value: value.clone(),
+                networks_values: vec![],
+            },
+            TomlValue::Table(settings) => {
+                let Some(TomlValue::String(value)) = settings.get("value") else {
+                    return Err(format!("{}: {}", name, INVALID_DEFINE));
+                };
+                let mut networks_values = vec![];
+                for (key, network_value) in settings.iter() {
+                    if key == "value" {
+                        continue;
+                    }
+                    match (parse_network(key), network_value) {
+                        (Some(network), TomlValue::String(network_value)) => {
+                            networks_values.push((network, network_value.clone()))
+                        }
+                        _ => return Err(format!("{}: {}", name, INVALID_DEFINE)),
+                    }
+                }
+                ProjectDefine {
+                    value: value.clone(),
+                    networks_values,
+                }
+            }
+            _ => return Err(format!("{}: {}", name, INVALID_DEFINE)),
+        };
+        defines.insert(name.clone(), define);
+    }
+    Ok(defines)
 }
 
-fn get_epoch_and_clarity_version(
+fn parse_address_principal(name: &str, principal: &TomlValue) -> Result<String, String> {
+    match principal {
+        TomlValue::String(principal) if PrincipalData::parse(principal).is_ok() => {
+            Ok(principal.clone())
+        }
+        _ => Err(format!("{}: {}", name, INVALID_ADDRESS)),
+    }
+}
+
+fn get_addresses(
+    settings: &toml::map::Map<String, TomlValue>,
+) -> Result<BTreeMap<String, ProjectAddress>, String> {
+    let mut addresses = BTreeMap::new();
+    for (name, settings) in settings.iter() {
+        if name.is_empty()
+            || !name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(format!("invalid address name: {}", name));
+        }
+        let address = match settings {
+            TomlValue::Table(settings) => {
+                let mut address = ProjectAddress::default();
+                for (key, principal) in settings.iter() {
+                    let principal = parse_address_principal(name, principal)?;
+                    match (key.as_str(), parse_network(key)) {
+                        ("value", _) => address.principal = Some(principal),
+                        (_, Some(network)) => {
+                            address.networks_principals.push((network, principal))
+                        }
+                        _ => return Err(format!("{}: {}", name, INVALID_ADDRESS)),
+                    }
+                }
+                address
+            }
+            principal => ProjectAddress {
+                principal: Some(parse_address_principal(name, principal)?),
+                networks_principals: vec![],
+            },
+        };
+        addresses.insert(name.clone(), address);
+    }
+    Ok(addresses)
+}
+
+fn get_contract_defines(
+    settings_defines: &TomlValue,
+    defines: &BTreeMap<String, ProjectDefine>,
+) -> Result<Vec<String>, String> {
+    let TomlValue::Array(names) = settings_defines else {
+        return Err("defines field invalid (expected a list of project defines)".into());
+    };
+    names
+        .iter()
+        .map(|name| match name.as_str() {
+            Some(name) if defines.contains_key(name) => Ok(name.to_string()),
+            Some(name) => Err(format!("{} is not defined in [project.defines]", name)),
+            None => Err("defines field invalid (expected a list of project defines)".into()),
+        })
+        .collect()
+}
+
+pub fn get_epoch_and_clarity_version(
     settings_epoch: Option<&str>,
     settings_clarity_version: Option<&str>,
 ) -> Result<(StacksEpochId, ClarityVersion), String> {
@@ -363,36 +914,68 @@ fn get_epoch_and_clarity_version(
 
     let epoch = match settings_epoch {
         None => StacksEpochId::Epoch2_05,
-        Some(epoch) => match epoch {
-            "2" | "2.0" => StacksEpochId::Epoch20,
-            "2.05" => StacksEpochId::Epoch2_05,
-            "2.1" => StacksEpochId::Epoch21,
-            "2.2" => StacksEpochId::Epoch22,
-            "2.3" => StacksEpochId::Epoch23,
-            "2.4" => StacksEpochId::Epoch24,
-            "2.5" => StacksEpochId::Epoch25,
-            "3" | "3.0" => StacksEpochId::Epoch30,
-            "3.1" => StacksEpochId::Epoch31,
-            _ => return Err(INVALID_EPOCH.into()),
-        },
+        Some(epoch) => parse_epoch(epoch)?,
     };
 
     let clarity_version = match settings_clarity_version {
         None => match settings_epoch {
             None => ClarityVersion::Clarity1,
-            Some(_) => ClarityVersion::default_for_epoch(epoch),
+            Some(_) => clarity_versions::latest_version(epoch),
         },
-        Some(version) => ClarityVersion::from_str(&format!("clarity{version}"))
-            .map_err(|_| INVALID_CLARITY_VERSION)?,
+        Some(version) => parse_clarity_version(version)?,
     };
 
-    if clarity_version > ClarityVersion::default_for_epoch(epoch) {
-        return Err(format!("{clarity_version} can not be used with {epoch}"));
-    }
+    check_clarity_version_for_epoch(epoch, clarity_version)?;
 
     Ok((epoch, clarity_version))
 }
 
+fn get_invalid_epoch_error() -> String {
+    format!(
+        "epoch field invalid (value supported: {})",
+        EpochSpec::get_supported_list()
+    )
+}
+
+fn parse_epoch(epoch: &str) -> Result<StacksEpochId, String> {
+    EpochSpec::from_str(epoch).map(StacksEpochId::from)
+}
+
+/// Epoch of a contract or of a requirement, as a string (`"2.4"`) or as a number (`2.4`, `3`)
+fn get_epoch_setting(
+    value: Option<&TomlValue>,
+    owner: &str,
+    warnings: &mut Vec<String>,
+) -> Result<Option<EpochSpec>, String> {
+    let epoch = match value {
+        Some(TomlValue::String(epoch)) => epoch.clone(),
+        Some(TomlValue::Float(epoch)) => epoch.to_string(),
+        Some(TomlValue::Integer(epoch)) => epoch.to_string(),
+        None => return Ok(None),
+        _ => return Err(get_invalid_epoch_error()),
+    };
+    let (epoch, warning) = EpochSpec::parse_forward_compatible(&epoch)?;
+    if let Some(warning) = warning {
+        warnings.push(format!("{}: {}", owner, warning));
+    }
+    Ok(Some(epoch))
+}
+
+fn parse_clarity_version(version: &str) -> Result<ClarityVersion, String> {
+    ClarityVersion::from_str(&format!("clarity{version}"))
+        .map_err(|_| INVALID_CLARITY_VERSION.into())
+}
+
+pub fn check_clarity_version_for_epoch(
+    epoch: StacksEpochId,
+    clarity_version: ClarityVersion,
+) -> Result<(), String> {
+    if !clarity_versions::is_supported(epoch, clarity_version) {
+        return Err(format!("{clarity_version} can not be used with {epoch}"));
+    }
+    Ok(())
+}
+
 #[test]
 fn test_get_epoch_and_clarity_version() {
     use ClarityVersion::*;
@@ -444,3 +1027,142 @@ fn test_get_epoch_and_clarity_version() {
     let result = get_epoch_and_clarity_version(Some("2.1"), Some("2"));
     assert_eq!(result, Ok((Epoch21, Clarity2)));
 }
+
+#[test]
+fn test_get_networks() {
+    let networks = TomlValue::Array(vec![
+        TomlValue::String("simnet".into()),
+        TomlValue::String("devnet".into()),
+    ]);
+    assert_eq!(
+        get_networks(&networks),
+        Ok(vec![StacksNetwork::Simnet, StacksNetwork::Devnet])
+    );
+
+    let networks = TomlValue::Array(vec![TomlValue::String("regtest".into())]);
+    assert_eq!(get_networks(&networks), Err(INVALID_NETWORKS.into()));
+
+    let networks = TomlValue::String("simnet".into());
+    assert_eq!(get_networks(&networks), Err(INVALID_NETWORKS.into()));
+}
+
+#[test]
+fn test_get_requirement() {
+    let settings: TomlValue = toml::from_str(
+        r#"
+        contract_id = "SP000000000000000000002Q6VF78.pox-4"
+        epoch = 2.5
+        clarity_version = 2
+        "#,
+    )
+    .unwrap();
+    let requirement = get_requirement(settings.as_table().unwrap(), &mut vec![]).unwrap();
+    assert_eq!(
+        requirement,
+        Some(RequirementConfig {
+            contract_id: "SP000000000000000000002Q6VF78.pox-4".into(),
+            mock: false,
+            epoch: Some(StacksEpochId::Epoch25),
+            clarity_version: Some(ClarityVersion::Clarity2),
+        })
+    );
+
+    let settings: TomlValue = toml::from_str(
+        r#"
+        contract_id = "SP000000000000000000002Q6VF78.pox-4"
+        epoch = "2.05"
+        clarity_version = 2
+        "#,
+    )
+    .unwrap();
+    let result = get_requirement(settings.as_table().unwrap(), &mut vec![]);
+    assert!(result.unwrap_err().contains("can not be used with"));
+
+    let settings: TomlValue = toml::from_str(
+        r#"
+        contract_id = "SP000000000000000000002Q6VF78.pox-4"
+        epoch = 1.5
+        "#,
+    )
+    .unwrap();
+    let result = get_requirement(settings.as_table().unwrap(), &mut vec![]);
+    assert!(result.unwrap_err().contains("did you mean 2.0?"));
+
+    let settings: TomlValue = toml::from_str(
+        r#"
+        contract_id = "SP000000000000000000002Q6VF78.pox-4"
+        epoch = 3.2
+        "#,
+    )
+    .unwrap();
+    let mut warnings = vec![];
+    let requirement = get_requirement(settings.as_table().unwrap(), &mut warnings).unwrap();
+    assert_eq!(requirement.unwrap().epoch, Some(StacksEpochId::Epoch31));
+    assert_eq!(
+        warnings,
+        vec!["requirement SP000000000000000000002Q6VF78.pox-4: epoch 3.2 is not supported by this version of clarinet, epoch 3.1 is used instead"]
+    );
+}
+
+#[test]
+fn test_get_defines() {
+    let settings: TomlValue = toml::from_str(
+        r#"
+        FEE_BPS = { value = "u30", mainnet = "u25" }
+        ADMIN = "'ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM"
+        "#,
+    )
+    .unwrap();
+    let defines = get_defines(settings.as_table().unwrap()).unwrap();
+    assert_eq!(defines["FEE_BPS"].get_value(&StacksNetwork::Simnet), "u30");
+    assert_eq!(defines["FEE_BPS"].get_value(&StacksNetwork::Mainnet), "u25");
+    assert!(defines["ADMIN"].networks_values.is_empty());
+
+    let names = TomlValue::Array(vec![TomlValue::String("FEE_BPS".into())]);
+    assert_eq!(
+        get_contract_defines(&names, &defines),
+        Ok(vec!["FEE_BPS".to_string()])
+    );
+    let names = TomlValue::Array(vec![TomlValue::String("FEES".into())]);
+    assert!(get_contract_defines(&names, &defines).is_err());
+
+    let settings: TomlValue = toml::from_str(
+        r#"
+        FEE_BPS = { value = "u30", regtest = "u25" }
+        "#,
+    )
+    .unwrap();
+    assert!(get_defines(settings.as_table().unwrap()).is_err());
+}
+
+#[test]
+fn test_get_addresses() {
+    let settings: TomlValue = toml::from_str(
+        r#"
+        oracle = "SP2C2YFP12AJZB4MABJBAJ55XECVS7E4PMMZ89YZR.oracle"
+        treasury = { testnet = "ST2CY5V39NHDPWSXMW9QDT3HC3GD6Q6XX4CFRK9AG", mainnet = "SP2C2YFP12AJZB4MABJBAJ55XECVS7E4PMMZ89YZR" }
+        "#,
+    )
+    .unwrap();
+    let addresses = get_addresses(settings.as_table().unwrap()).unwrap();
+    assert_eq!(
+        addresses["oracle"].get_principal(&StacksNetwork::Devnet),
+        Some("SP2C2YFP12AJZB4MABJBAJ55XECVS7E4PMMZ89YZR.oracle")
+    );
+    assert_eq!(
+        addresses["treasury"].get_principal(&StacksNetwork::Testnet),
+        Some("ST2CY5V39NHDPWSXMW9QDT3HC3GD6Q6XX4CFRK9AG")
+    );
+    assert_eq!(
+        addresses["treasury"].get_principal(&StacksNetwork::Simnet),
+        None
+    );
+
+    let settings: TomlValue = toml::from_str(
+        r#"
+        treasury = "ST2CY5V39NHDPWSXMW9QDT3HC3GD6Q6XX4CFRK9AG.treasury.vault"
+        "#,
+    )
+    .unwrap();
+    assert!(get_addresses(settings.as_table().unwrap()).is_err());
+}
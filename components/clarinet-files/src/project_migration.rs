@@ -0,0 +1,299 @@
+//! Migration of the manifests written by the first versions of Clarinet to the current
+//! schemas. The documents are edited in place, the settings that can't be rewritten
+//! mechanically are reported instead of being dropped.
+
+use toml_edit::{value, Array, Document, InlineTable, Item, Table, Value};
+
+use super::network_manifest::{DevnetConfigFile, NetworkManifestFile};
+use super::project_manifest::ProjectManifestFile;
+
+/// Settings that no longer have any effect, with the reason
+const REMOVED_PROJECT_SETTINGS: [(&str, &str); 2] = [
+    (
+        "costs_version",
+        "the costs are the ones of the epoch of each contract",
+    ),
+    (
+        "parser_version",
+        "the parser is the one of the epoch of each contract",
+    ),
+];
+
+const REMOVED_DEVNET_SETTINGS: [(&str, &str); 1] = [(
+    "enable_next_features",
+    "the features of each epoch are enabled at its activation height",
+)];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ManifestMigration {
+    pub content: String,
+    /// Rewrites applied to the document
+    pub changes: Vec<String>,
+    /// Legacy settings left as is, to be migrated by hand
+    pub manual_steps: Vec<String>,
+}
+
+impl ManifestMigration {
+    pub fn is_changed(&self) -> bool {
+        !self.changes.is_empty()
+    }
+}
+
+fn parse_document(content: &str) -> Result<Document, String> {
+    content
+        .parse::<Document>()
+        .map_err(|e| format!("unable to parse the manifest: {}", e))
+}
+
+fn remove_settings(
+    table: &mut Table,
+    section: &str,
+    settings: &[(&str, &str)],
+    changes: &mut Vec<String>,
+) {
+    for (key, reason) in settings {
+        if table.remove(key).is_some() {
+            changes.push(format!("removed {}.{}: {}", section, key, reason));
+        }
+    }
+}
+
+/// Rewrites a legacy `Clarinet.toml`
+pub fn migrate_project_manifest(content: &str) -> Result<ManifestMigration, String> {
+    let mut document = parse_document(content)?;
+    let mut changes = vec![];
+    let mut manual_steps = vec![];
+
+    let project = document
+        .get_mut("project")
+        .and_then(|project| project.as_table_mut())
+        .ok_or("the [project] section is missing")?;
+    remove_settings(project, "project", &REMOVED_PROJECT_SETTINGS, &mut changes);
+    let passes = project.remove("analysis");
+    if let Some(Item::Value(Value::Array(requirements))) = project.get_mut("requirements") {
+        // the requirements used to be listed by contract id
+        if requirements.iter().any(|requirement| requirement.is_str()) {
+            let mut migrated = Array::new();
+            for requirement in requirements.iter() {
+                match requirement.as_str() {
+                    Some(contract_id) => {
+                        let mut table = InlineTable::new();
+                        table.insert("contract_id", contract_id.into());
+                        migrated.push(table);
+                    }
+                    None => migrated.push(requirement.clone()),
+                }
+            }
+            *requirements = migrated;
+            changes.push("rewrote the requirements as { contract_id = ... } tables".to_string());
+        }
+    }
+
+    let repl = document.entry("repl").or_insert_with(|| {
+        let mut table = Table::new();
+        table.set_implicit(true);
+        Item::Table(table)
+    });
+    if let Some(repl) = repl.as_table_mut() {
+        remove_settings(repl, "repl", &REMOVED_PROJECT_SETTINGS, &mut changes);
+        if let Some(passes) = passes {
+            let analysis = repl
+                .entry("analysis")
+                .or_insert_with(|| Item::Table(Table::new()));
+            match analysis.get("passes") {
+                Some(_) => changes.push(
+                    "removed project.analysis, repl.analysis.passes takes precedence".to_string(),
+                ),
+                None => {
+                    analysis["passes"] = passes;
+                    changes.push("moved project.analysis to repl.analysis.passes".to_string());
+                }
+            }
+        }
+    }
+
+    if let Some(contracts) = document
+        .get_mut("contracts")
+        .and_then(|contracts| contracts.as_table_like_mut())
+    {
+        let names: Vec<String> = contracts.iter().map(|(name, _)| name.to_string()).collect();
+        for name in names.iter() {
+            let Some(contract) = contracts
+                .get_mut(name)
+                .and_then(|contract| contract.as_table_like_mut())
+            else {
+                continue;
+            };
+            if contract.remove("depends_on").is_some() {
+                changes.push(format!(
+                    "removed contracts.{}.depends_on: the dependencies are detected from the contract calls",
+                    name
+                ));
+            }
+            // the defaults of the contracts written before the epochs were configurable
+            if contract.get("clarity_version").is_none() && contract.get("epoch").is_none() {
+                contract.insert("clarity_version", value(1));
+                contract.insert("epoch", value(2.05));
+                changes.push(format!(
+                    "set contracts.{} to clarity_version 1 and epoch 2.05, the implicit defaults",
+                    name
+                ));
+            }
+        }
+    }
+
+    if document.contains_key("notebooks") {
+        manual_steps.push(
+            "[notebooks] is no longer supported, the notebooks can be turned into tests"
+                .to_string(),
+        );
+    }
+
+    let content = document.to_string();
+    toml::from_str::<ProjectManifestFile>(&content)
+        .map_err(|e| format!("the migrated manifest is invalid: {}", e))?;
+    Ok(ManifestMigration {
+        content,
+        changes,
+        manual_steps,
+    })
+}
+
+/// Rewrites a legacy network manifest (`settings/Devnet.toml`, ...)
+pub fn migrate_network_manifest(content: &str) -> Result<ManifestMigration, String> {
+    let mut document = parse_document(content)?;
+    let mut changes = vec![];
+    let mut manual_steps = vec![];
+
+    if let Some(network) = document
+        .get_mut("network")
+        .and_then(|network| network.as_table_mut())
+    {
+        if let Some(address) = network.remove("node_rpc_address") {
+            if network.contains_key("stacks_node_rpc_address") {
+                changes.push(
+                    "removed network.node_rpc_address, network.stacks_node_rpc_address takes precedence"
+                        .to_string(),
+                );
+            } else {
+                network.insert("stacks_node_rpc_address", address);
+                changes.push(
+                    "renamed network.node_rpc_address to network.stacks_node_rpc_address"
+                        .to_string(),
+                );
+            }
+        }
+    }
+
+    if let Some(devnet) = document
+        .get_mut("devnet")
+        .and_then(|devnet| devnet.as_table_mut())
+    {
+        remove_settings(devnet, "devnet", &REMOVED_DEVNET_SETTINGS, &mut changes);
+        // the hyperchains were renamed subnets
+        let keys: Vec<String> = devnet.iter().map(|(key, _)| key.to_string()).collect();
+        for key in keys.iter().filter(|key| key.contains("hyperchain")) {
+            let renamed = key.replace("hyperchain", "subnet");
+            if let Some(item) = devnet.remove(key) {
+                if devnet.contains_key(&renamed) {
+                    changes.push(format!(
+                        "removed devnet.{}, devnet.{} takes precedence",
+                        key, renamed
+                    ));
+                } else {
+                    devnet.insert(&renamed, item);
+                    changes.push(format!("renamed devnet.{} to devnet.{}", key, renamed));
+                }
+            }
+        }
+
+        let known_settings =
+            serde_json::to_value(DevnetConfigFile::default()).map_err(|e| e.to_string())?;
+        for (key, _) in devnet.iter() {
+            if known_settings.get(key).is_none() {
+                manual_steps.push(format!("devnet.{} is unknown and ignored", key));
+            }
+        }
+    }
+
+    let content = document.to_string();
+    toml::from_str::<NetworkManifestFile>(&content)
+        .map_err(|e| format!("the migrated manifest is invalid: {}", e))?;
+    Ok(ManifestMigration {
+        content,
+        changes,
+        manual_steps,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migrate_legacy_manifests() {
+        let manifest = r#"[project]
+name = "counter"
+costs_version = 1
+analysis = ["check_checker"]
+requirements = ["SP2KAF9RF86PVX3NEE27DFV1CQX0T4WGR41X3S45C.bitcoin-whales"]
+
+[contracts.counter]
+path = "contracts/counter.clar"
+depends_on = []
+
+[contracts.token]
+path = "contracts/token.clar"
+clarity_version = 2
+epoch = 2.1
+
+[notebooks]
+"#;
+        let migration = migrate_project_manifest(manifest).unwrap();
+        assert_eq!(migration.changes.len(), 5);
+        assert_eq!(migration.manual_steps.len(), 1);
+        assert!(!migration.content.contains("costs_version"));
+        assert!(!migration.content.contains("depends_on"));
+        assert!(migration.content.contains(
+            r#"requirements = [{ contract_id = "SP2KAF9RF86PVX3NEE27DFV1CQX0T4WGR41X3S45C.bitcoin-whales" }]"#
+        ));
+        assert!(migration
+            .content
+            .contains("[repl.analysis]\npasses = [\"check_checker\"]"));
+        assert!(migration
+            .content
+            .contains("path = \"contracts/token.clar\"\nclarity_version = 2\nepoch = 2.1"));
+
+        // a migrated manifest is left untouched
+        let migrated = migrate_project_manifest(&migration.content).unwrap();
+        assert!(!migrated.is_changed());
+        assert_eq!(migrated.content, migration.content);
+
+        let settings = r#"[network]
+name = "devnet"
+node_rpc_address = "http://localhost:20443"
+
+[devnet]
+enable_next_features = true
+enable_hyperchain_node = false
+bitcoin_node_rpc_port = 18443
+unknown_port = 1
+"#;
+        let migration = migrate_network_manifest(settings).unwrap();
+        assert_eq!(
+            migration.changes,
+            vec![
+                "renamed network.node_rpc_address to network.stacks_node_rpc_address",
+                "removed devnet.enable_next_features: the features of each epoch are enabled at its activation height",
+                "renamed devnet.enable_hyperchain_node to devnet.enable_subnet_node",
+            ]
+        );
+        assert_eq!(
+            migration.manual_steps,
+            vec!["devnet.unknown_port is unknown and ignored"]
+        );
+        assert!(migration
+            .content
+            .contains("stacks_node_rpc_address = \"http://localhost:20443\""));
+    }
+}
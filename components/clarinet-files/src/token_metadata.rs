@@ -0,0 +1,189 @@
+//! SIP-016 token metadata of the contracts (`[contracts.<name>.metadata]`): the JSON file
+//! describing the token, the URI it's published at once deployed, and the public function of
+//! the contract setting that URI. The generated plans call the setter after the publish.
+
+use serde_json::Value as JsonValue;
+use toml::value::Value as TomlValue;
+
+pub const DEFAULT_METADATA_SETTER: &str = "set-token-uri";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TokenMetadataConfig {
+    /// Path of the metadata JSON, relative to the project root
+    pub file: String,
+    /// URI of the metadata on testnet and mainnet, the devnet hosts the file itself
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uri: Option<String>,
+    pub setter: String,
+    /// Decimals of a fungible token, checked against the metadata
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub decimals: Option<u8>,
+}
+
+pub(crate) fn get_token_metadata(settings: &TomlValue) -> Result<TokenMetadataConfig, String> {
+    let TomlValue::Table(settings) = settings else {
+        return Err("metadata field invalid (expected a table with a file)".into());
+    };
+    let file = match settings.get("file") {
+        Some(TomlValue::String(file)) => file.clone(),
+        _ => return Err("metadata file missing (expected a path to a SIP-016 JSON)".into()),
+    };
+    let uri = match settings.get("uri") {
+        Some(TomlValue::String(uri)) => Some(uri.clone()),
+        None => None,
+        _ => return Err("metadata uri invalid (expected a string)".into()),
+    };
+    let setter = match settings.get("setter") {
+        Some(TomlValue::String(setter)) => setter.clone(),
+        None => DEFAULT_METADATA_SETTER.to_string(),
+        _ => return Err("metadata setter invalid (expected a function name)".into()),
+    };
+    let decimals = match settings.get("decimals") {
+        Some(TomlValue::Integer(decimals)) => Some(
+            u8::try_from(*decimals).map_err(|_| "metadata decimals invalid (expected 0-255)")?,
+        ),
+        None => None,
+        _ => return Err("metadata decimals invalid (expected an integer)".into()),
+    };
+    Ok(TokenMetadataConfig {
+        file,
+        uri,
+        setter,
+        decimals,
+    })
+}
+
+fn check_string_field(
+    metadata: &serde_json::Map<String, JsonValue>,
+    field: &str,
+    required: bool,
+    errors: &mut Vec<String>,
+) {
+    match metadata.get(field) {
+        Some(JsonValue::String(_)) => {}
+        None if !required => {}
+        None => errors.push(format!("missing field '{}'", field)),
+        Some(_) => errors.push(format!("'{}' must be a string", field)),
+    }
+}
+
+/// Checks a metadata JSON against the SIP-016 schema, and its decimals against the manifest
+pub fn validate_token_metadata(
+    content: &str,
+    config: &TokenMetadataConfig,
+) -> Result<(), Vec<String>> {
+    let metadata = match serde_json::from_str::<JsonValue>(content) {
+        Ok(JsonValue::Object(metadata)) => metadata,
+        Ok(_) => return Err(vec!["expected a JSON object".to_string()]),
+        Err(e) => return Err(vec![format!("invalid JSON: {}", e)]),
+    };
+
+    let mut errors = vec![];
+    match metadata.get("sip") {
+        Some(sip) if sip.as_u64() == Some(16) => {}
+        Some(_) => errors.push("'sip' must be 16".to_string()),
+        None => errors.push("missing field 'sip'".to_string()),
+    }
+    check_string_field(&metadata, "name", true, &mut errors);
+    check_string_field(&metadata, "description", false, &mut errors);
+    check_string_field(&metadata, "image", false, &mut errors);
+
+    match metadata.get("attributes") {
+        None => {}
+        Some(JsonValue::Array(attributes)) => {
+            for (index, attribute) in attributes.iter().enumerate() {
+                let is_valid = attribute.get("trait_type").is_some_and(|t| t.is_string())
+                    && attribute.get("value").is_some();
+                if !is_valid {
+                    errors.push(format!(
+                        "attributes[{}] must have a 'trait_type' and a 'value'",
+                        index
+                    ));
+                }
+            }
+        }
+        Some(_) => errors.push("'attributes' must be a list".to_string()),
+    }
+
+    let decimals = match metadata.get("properties") {
+        None => None,
+        Some(JsonValue::Object(properties)) => properties.get("decimals"),
+        Some(_) => {
+            errors.push("'properties' must be an object".to_string());
+            None
+        }
+    };
+    if let Some(localization) = metadata.get("localization") {
+        let is_valid = localization.get("uri").is_some_and(|uri| uri.is_string())
+            && localization.get("default").is_some_and(|d| d.is_string())
+            && localization.get("locales").is_some_and(|l| l.is_array());
+        if !is_valid {
+            errors.push("'localization' must have a 'uri', a 'default' and 'locales'".to_string());
+        }
+    }
+
+    match (config.decimals, decimals.map(|decimals| decimals.as_u64())) {
+        (_, Some(None)) => errors.push("'properties.decimals' must be an integer".to_string()),
+        (Some(expected), Some(Some(decimals))) if decimals != u64::from(expected) => {
+            errors.push(format!(
+                "'properties.decimals' is {}, Clarinet.toml sets {}",
+                decimals, expected
+            ))
+        }
+        _ => {}
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_config(decimals: Option<u8>) -> TokenMetadataConfig {
+        TokenMetadataConfig {
+            file: "metadata/token.json".to_string(),
+            uri: None,
+            setter: DEFAULT_METADATA_SETTER.to_string(),
+            decimals,
+        }
+    }
+
+    #[test]
+    fn test_validate_token_metadata() {
+        let settings: TomlValue = toml::from_str("file = \"metadata/token.json\"").unwrap();
+        assert_eq!(get_token_metadata(&settings), Ok(get_config(None)));
+        let settings: TomlValue = toml::from_str("uri = \"ipfs://token\"").unwrap();
+        assert!(get_token_metadata(&settings).is_err());
+
+        let metadata = r#"{
+            "sip": 16,
+            "name": "Token",
+            "image": "https://example.com/token.png",
+            "attributes": [{ "trait_type": "tier", "value": "gold" }],
+            "properties": { "decimals": 6 }
+        }"#;
+        assert!(validate_token_metadata(metadata, &get_config(Some(6))).is_ok());
+        assert_eq!(
+            validate_token_metadata(metadata, &get_config(Some(8))),
+            Err(vec![
+                "'properties.decimals' is 6, Clarinet.toml sets 8".to_string()
+            ])
+        );
+
+        let metadata = r#"{ "sip": 10, "attributes": [{ "value": 1 }] }"#;
+        assert_eq!(
+            validate_token_metadata(metadata, &get_config(None)),
+            Err(vec![
+                "'sip' must be 16".to_string(),
+                "missing field 'name'".to_string(),
+                "attributes[0] must have a 'trait_type' and a 'value'".to_string(),
+            ])
+        );
+        assert!(validate_token_metadata("[]", &get_config(None)).is_err());
+    }
+}
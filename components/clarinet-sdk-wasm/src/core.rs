@@ -4,8 +4,8 @@ use clarinet_deployments::types::{
     TransactionSpecification,
 };
 use clarinet_deployments::{
-    generate_default_deployment, initiate_session_from_manifest,
-    update_session_with_deployment_plan,
+    generate_default_deployment, get_deployment_address_book, get_requirements_contracts,
+    initiate_session_from_manifest, PlanExecutor,
 };
 use clarinet_files::StacksNetwork;
 use clarinet_files::{FileAccessor, FileLocation, ProjectManifest, WASMFileSystemAccessor};
@@ -13,17 +13,20 @@ use clarity_repl::clarity::analysis::contract_interface_builder::{
     ContractInterface, ContractInterfaceFunction, ContractInterfaceFunctionAccess,
 };
 use clarity_repl::clarity::chainstate::StacksAddress;
+use clarity_repl::clarity::vm::ast::ContractAST;
 use clarity_repl::clarity::vm::types::{
-    PrincipalData, QualifiedContractIdentifier, StandardPrincipalData,
+    PrincipalData, QualifiedContractIdentifier, SequenceData, StandardPrincipalData, Value,
 };
 use clarity_repl::clarity::{
     Address, ClarityVersion, EvaluationResult, ExecutionResult, StacksEpochId, SymbolicExpression,
 };
 use clarity_repl::repl::clarity_values::{uint8_to_string, uint8_to_value};
-use clarity_repl::repl::session::{CostsReport, BOOT_CONTRACTS_DATA};
+use clarity_repl::repl::profiler::ProfileMetric;
+use clarity_repl::repl::session::{CostsReport, GenesisConstants, BOOT_CONTRACTS_DATA};
+use clarity_repl::repl::state_dump::{get_fixture_path, StateDump, StateFixture};
 use clarity_repl::repl::{
-    clarity_values, ClarityCodeSource, ClarityContract, ContractDeployer, Session, SessionSettings,
-    DEFAULT_CLARITY_VERSION, DEFAULT_EPOCH,
+    clarity_values, ClarityCodeSource, ClarityContract, ContractDeployer, EpochSpec, Session,
+    SessionSettings, DEFAULT_CLARITY_VERSION, DEFAULT_EPOCH,
 };
 use gloo_utils::format::JsValueSerdeExt;
 use js_sys::Function as JsFunction;
@@ -31,6 +34,7 @@ use serde::{Deserialize, Serialize};
 use serde_json::json;
 use serde_wasm_bindgen::to_value as encode_to_js;
 use std::collections::{BTreeMap, HashMap};
+use std::str::FromStr;
 use std::{panic, path::PathBuf};
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsValue;
@@ -51,6 +55,10 @@ extern "C" {
     pub type IContractAST;
     #[wasm_bindgen(typescript_type = "Map<string, IContractInterface>")]
     pub type IContractInterfaces;
+    #[wasm_bindgen(typescript_type = "BootContractInfo[]")]
+    pub type BootContracts;
+    #[wasm_bindgen(typescript_type = "GenesisConstants")]
+    pub type IGenesisConstants;
 }
 
 impl EpochString {
@@ -219,6 +227,16 @@ pub struct TransactionRes {
     pub costs: String,
 }
 
+/// Outcome of a snippet of `evalMany`, encoded as the `TransactionRes` are
+#[derive(Debug, Serialize)]
+pub struct EvalOutcomeRes {
+    /// `None` when the evaluation failed or the snippet is only made of definitions
+    pub result: Option<String>,
+    pub events: String,
+    pub costs: String,
+    pub diagnostics: Vec<String>,
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct TransactionResRaw {
     pub result: String,
@@ -252,6 +270,13 @@ pub fn execution_result_to_transaction_res(execution: &ExecutionResult) -> Trans
     }
 }
 
+/// Measurement started by `beginScope`, reports recorded after its start are attributed to it
+struct MeasurementScope {
+    previous_test_name: String,
+    first_coverage_report: usize,
+    first_costs_report: usize,
+}
+
 #[derive(Clone)]
 struct ProjectCache {
     accounts: HashMap<String, String>,
@@ -288,10 +313,12 @@ pub struct SDK {
     contracts_locations: HashMap<QualifiedContractIdentifier, FileLocation>,
     contracts_interfaces: HashMap<QualifiedContractIdentifier, ContractInterface>,
     session: Option<Session>,
+    project_root: Option<FileLocation>,
     file_accessor: Box<dyn FileAccessor>,
     options: SDKOptions,
     current_test_name: String,
     costs_reports: Vec<CostsReport>,
+    scope: Option<MeasurementScope>,
 }
 
 #[wasm_bindgen]
@@ -312,6 +339,7 @@ impl SDK {
             contracts_interfaces: HashMap::new(),
             contracts_locations: HashMap::new(),
             session: None,
+            project_root: None,
             file_accessor: fs,
             options: SDKOptions {
                 track_coverage,
@@ -319,6 +347,7 @@ impl SDK {
             },
             current_test_name: String::new(),
             costs_reports: vec![],
+            scope: None,
         }
     }
 
@@ -353,6 +382,7 @@ impl SDK {
     pub async fn init_empty_session(&mut self) -> Result<(), String> {
         let session = Session::new(SessionSettings::default());
         self.session = Some(session);
+        self.project_root = None;
         Ok(())
     }
 
@@ -375,6 +405,7 @@ impl SDK {
 
         self.deployer = session.interpreter.get_tx_sender().to_string();
 
+        self.project_root = Some(manifest_location.get_parent_location()?);
         self.contracts_interfaces = contracts_interfaces;
         self.contracts_locations = contracts_locations;
         self.accounts = accounts;
@@ -404,12 +435,13 @@ impl SDK {
         .await?;
 
         if !artifacts.success {
-            let diags_digest = DiagnosticsDigest::new(&artifacts.diags, &deployment);
+            let diags_digest = DiagnosticsDigest::from_artifacts(&artifacts, &deployment);
             if diags_digest.errors > 0 {
                 return Err(diags_digest.message);
             }
         }
 
+        let address_book = get_deployment_address_book(&manifest, &deployment);
         if self
             .file_accessor
             .file_exists(deployment_plan_location.to_string())
@@ -421,6 +453,7 @@ impl SDK {
                 .await?;
 
             let mut spec_file = DeploymentSpecificationFile::from_file_content(&spec_file_content)?;
+            spec_file.resolve_named_principals(&address_book)?;
 
             // the contract publish txs are managed by the manifest
             // keep the user added txs and merge them with the default deployment plan
@@ -437,6 +470,7 @@ impl SDK {
                 None,
             )?;
 
+            deployment.keep_descriptions(&existing_deployment);
             deployment.merge_batches(existing_deployment.plan.batches);
 
             self.write_deployment_plan(
@@ -451,16 +485,18 @@ impl SDK {
                 .await?;
         }
 
-        let mut session = initiate_session_from_manifest(&manifest);
-        if self.options.track_coverage {
-            session.enable_coverage();
+        for warning in artifacts.warnings.iter() {
+            log!("warning: {}", warning);
         }
-        let executed_contracts = update_session_with_deployment_plan(
-            &mut session,
-            &deployment,
-            Some(&artifacts.asts),
-            Some(DEFAULT_EPOCH),
-        );
+
+        let mut session = initiate_session_from_manifest(&manifest);
+        let executed_contracts = PlanExecutor::new(&deployment)
+            .with_asts(Some(&artifacts.asts))
+            .with_min_epoch(Some(DEFAULT_EPOCH))
+            .with_coverage(self.options.track_coverage)
+            .with_requirements(get_requirements_contracts(&manifest, &deployment))
+            .execute(&mut session);
+        session.address_book.extend(&address_book);
 
         let mut accounts = HashMap::new();
         if let Some(ref spec) = deployment.genesis {
@@ -597,6 +633,12 @@ impl SDK {
         session.interpreter.get_burn_block_height()
     }
 
+    #[wasm_bindgen(getter, js_name=currentRewardCycle)]
+    pub fn current_reward_cycle(&mut self) -> u32 {
+        let session = self.get_session_mut();
+        session.get_current_reward_cycle()
+    }
+
     #[wasm_bindgen(getter, js_name=currentEpoch)]
     pub fn current_epoch(&mut self) -> String {
         let session = self.get_session_mut();
@@ -606,18 +648,10 @@ impl SDK {
     #[wasm_bindgen(js_name=setEpoch)]
     pub fn set_epoch(&mut self, epoch: EpochString) {
         let epoch = epoch.as_string().unwrap_or("2.4".into());
-        let epoch = match epoch.as_str() {
-            "2.0" => StacksEpochId::Epoch20,
-            "2.05" => StacksEpochId::Epoch2_05,
-            "2.1" => StacksEpochId::Epoch21,
-            "2.2" => StacksEpochId::Epoch22,
-            "2.3" => StacksEpochId::Epoch23,
-            "2.4" => StacksEpochId::Epoch24,
-            "2.5" => StacksEpochId::Epoch25,
-            "3.0" => StacksEpochId::Epoch30,
-            "3.1" => StacksEpochId::Epoch31,
-            _ => {
-                log!("Invalid epoch {epoch}. Using default epoch");
+        let epoch = match EpochSpec::from_str(&epoch) {
+            Ok(epoch) => StacksEpochId::from(epoch),
+            Err(e) => {
+                log!("{e}. Using default epoch");
                 DEFAULT_EPOCH
             }
         };
@@ -662,6 +696,17 @@ impl SDK {
         Ok(encode_to_js(&assets_maps)?.unchecked_into::<AssetsMap>())
     }
 
+    #[wasm_bindgen(js_name=getBootContracts)]
+    pub fn get_boot_contracts(&mut self) -> Result<BootContracts, JsError> {
+        let boot_contracts = self.get_session_mut().get_boot_contracts();
+        Ok(encode_to_js(&boot_contracts)?.unchecked_into::<BootContracts>())
+    }
+
+    #[wasm_bindgen(js_name=getGenesisConstants)]
+    pub fn get_genesis_constants(&self) -> Result<IGenesisConstants, JsError> {
+        Ok(encode_to_js(&GenesisConstants::default())?.unchecked_into::<IGenesisConstants>())
+    }
+
     #[wasm_bindgen(js_name=getAccounts)]
     pub fn get_accounts(&mut self) -> Result<Accounts, JsError> {
         Ok(encode_to_js(&self.accounts)?.unchecked_into::<Accounts>())
@@ -696,6 +741,72 @@ impl SDK {
             .ok_or("value not found".into())
     }
 
+    #[wasm_bindgen(js_name=dumpState)]
+    pub fn dump_state(&mut self, contract: Option<String>) -> Result<String, String> {
+        let contract_id = match contract {
+            Some(contract) => Some(self.desugar_contract_id(&contract)?),
+            None => None,
+        };
+        let session = self.get_session_mut();
+        let dump = session.dump_state(contract_id.as_ref())?;
+        serde_json::to_string(&dump).map_err(|e| e.to_string())
+    }
+
+    #[wasm_bindgen(js_name=loadState)]
+    pub fn load_state(&mut self, dump: &str) -> Result<(), String> {
+        let dump: StateDump =
+            serde_json::from_str(dump).map_err(|e| format!("invalid state dump: {}", e))?;
+        let session = self.get_session_mut();
+        session.load_state(&dump)
+    }
+
+    fn get_fixture_location(&self, name: &str) -> Result<FileLocation, String> {
+        let project_root = self
+            .project_root
+            .as_ref()
+            .ok_or("fixtures require a session initialised with initSession()")?;
+        FileLocation::try_parse(&get_fixture_path(name), Some(project_root))
+            .ok_or(format!("invalid fixture name {}", name))
+    }
+
+    // returns false if the fixture doesn't exist or was created with outdated contracts
+    #[wasm_bindgen(js_name=loadFixture)]
+    pub async fn load_fixture(&mut self, name: String) -> Result<bool, String> {
+        let fixture_location = self.get_fixture_location(&name)?;
+        if !self
+            .file_accessor
+            .file_exists(fixture_location.to_string())
+            .await?
+        {
+            return Ok(false);
+        }
+        let content = self
+            .file_accessor
+            .read_file(fixture_location.to_string())
+            .await?;
+        let fixture: StateFixture = serde_json::from_str(&content)
+            .map_err(|e| format!("invalid fixture {}: {}", name, e))?;
+
+        let session = self.get_session_mut();
+        match session.load_fixture(&fixture) {
+            Ok(()) => Ok(true),
+            Err(e) => {
+                log!("{}", e);
+                Ok(false)
+            }
+        }
+    }
+
+    #[wasm_bindgen(js_name=saveFixture)]
+    pub async fn save_fixture(&mut self, name: String) -> Result<(), String> {
+        let fixture_location = self.get_fixture_location(&name)?;
+        let fixture = self.get_session().create_fixture()?;
+        let content = serde_json::to_vec_pretty(&fixture).map_err(|e| e.to_string())?;
+        self.file_accessor
+            .write_file(fixture_location.to_string(), &content)
+            .await
+    }
+
     fn get_function_interface(
         &self,
         contract: &str,
@@ -912,6 +1023,47 @@ impl SDK {
         self.inner_call_private_fn(args, true)
     }
 
+    // the sender is the contract the public function is called from, with `as-contract`
+    #[wasm_bindgen(js_name = "callPublicFnAsContract")]
+    pub fn call_public_fn_as_contract(
+        &mut self,
+        args: &CallFnArgs,
+    ) -> Result<TransactionRes, String> {
+        let interface = self.get_function_interface(&args.contract, &args.method)?;
+        if interface.access != ContractInterfaceFunctionAccess::public {
+            return Err(format!("{} is not a public function", &args.method));
+        }
+        let SDKOptions { track_costs, .. } = self.options;
+        let parsed_args = args
+            .args
+            .iter()
+            .map(|a| SymbolicExpression::atom_value(uint8_to_value(a)))
+            .collect::<Vec<SymbolicExpression>>();
+
+        let session = self.get_session_mut();
+        session.advance_chain_tip(1);
+        let execution = session
+            .call_as_contract(
+                &args.sender,
+                &args.contract,
+                &args.method,
+                &parsed_args,
+                false,
+                track_costs,
+            )
+            .map_err(|diagnostics| {
+                let mut message = format!(
+                    "Call contract function error: {}::{} as {}",
+                    args.contract, args.method, args.sender
+                );
+                if let Some(diag) = diagnostics.last() {
+                    message = format!("{} -> {}", message, diag.message);
+                }
+                message
+            })?;
+        Ok(execution_result_to_transaction_res(&execution))
+    }
+
     #[wasm_bindgen(js_name=mineBlock)]
     pub fn mine_block_js(&mut self, js_txs: js_sys::Array) -> Result<JsValue, String> {
         let mut results: Vec<TransactionRes> = vec![];
@@ -980,6 +1132,94 @@ impl SDK {
         session.advance_burn_chain_tip(count.unwrap_or(1))
     }
 
+    /// Mines up to the first burn block of the `count`th next reward cycle, returns the new cycle
+    #[wasm_bindgen(js_name=advanceRewardCycles)]
+    pub fn advance_reward_cycles(
+        &mut self,
+        count: u32,
+        stacks_blocks_per_tenure: Option<u32>,
+    ) -> Result<u32, String> {
+        let session = self.get_session_mut();
+        session.advance_reward_cycles(count, stacks_blocks_per_tenure)
+    }
+
+    /// Mines the burn blocks of `seconds`, returns the new burn block height
+    #[wasm_bindgen(js_name=advanceTime)]
+    pub fn advance_time(
+        &mut self,
+        seconds: u32,
+        stacks_blocks_per_tenure: Option<u32>,
+    ) -> Result<u32, String> {
+        let session = self.get_session_mut();
+        session.advance_time(seconds.into(), stacks_blocks_per_tenure)
+    }
+
+    #[wasm_bindgen(js_name=setBurnBlockHeaderHash)]
+    pub fn set_burn_block_header_hash(
+        &mut self,
+        height: u32,
+        header_hash: String,
+    ) -> Result<(), String> {
+        let session = self.get_session_mut();
+        session.set_burn_block_header_hash(height, &header_hash)
+    }
+
+    /// `addresses` is a serialized list of `{ version, hashbytes }` tuples
+    #[wasm_bindgen(js_name=setPoxPayoutAddresses)]
+    pub fn set_pox_payout_addresses(
+        &mut self,
+        height: u32,
+        addresses: Vec<u8>,
+        payout: u64,
+    ) -> Result<(), String> {
+        let addresses = match uint8_to_value(&addresses) {
+            Value::Sequence(SequenceData::List(list)) => list.data,
+            _ => return Err("addresses must be a list of tuples".to_string()),
+        };
+        let session = self.get_session_mut();
+        session.set_pox_payout_addrs(height, addresses, payout.into())
+    }
+
+    #[wasm_bindgen(js_name=getBlockId)]
+    pub fn get_block_id(&mut self, height: u32) -> Result<String, String> {
+        self.get_session_mut().get_block_id(height)
+    }
+
+    /// `value` is a serialized clarity value
+    #[wasm_bindgen(js_name=setDataVarAtBlock)]
+    pub fn set_data_var_at_block(
+        &mut self,
+        height: u32,
+        contract: &str,
+        var_name: &str,
+        value: Vec<u8>,
+    ) -> Result<(), String> {
+        let contract_id = self.desugar_contract_id(contract)?;
+        let session = self.get_session_mut();
+        session.set_data_var_at_block(height, &contract_id, var_name, uint8_to_value(&value))
+    }
+
+    /// `map_key` and `value` are serialized clarity values, the entry is deleted without `value`
+    #[wasm_bindgen(js_name=setMapEntryAtBlock)]
+    pub fn set_map_entry_at_block(
+        &mut self,
+        height: u32,
+        contract: &str,
+        map_name: &str,
+        map_key: Vec<u8>,
+        value: Option<Vec<u8>>,
+    ) -> Result<(), String> {
+        let contract_id = self.desugar_contract_id(contract)?;
+        let session = self.get_session_mut();
+        session.set_map_entry_at_block(
+            height,
+            &contract_id,
+            map_name,
+            uint8_to_value(&map_key),
+            value.map(|value| uint8_to_value(&value)),
+        )
+    }
+
     #[wasm_bindgen(js_name=runSnippet)]
     pub fn run_snippet(&mut self, snippet: String) -> String {
         let session = self.get_session_mut();
@@ -1016,6 +1256,37 @@ impl SDK {
         }
     }
 
+    // the snippets are evaluated in a single call, saving the crossings of the wasm boundary
+    #[wasm_bindgen(js_name=evalMany)]
+    pub fn eval_many(&mut self, js_snippets: js_sys::Array) -> Result<JsValue, String> {
+        let snippets: Vec<String> = js_snippets
+            .into_serde()
+            .map_err(|e| format!("Failed to parse snippets: {:}", e))?;
+        let session = self.get_session_mut();
+        let outcomes: Vec<EvalOutcomeRes> = session
+            .eval_many(snippets, true)
+            .into_iter()
+            .map(|outcome| {
+                let events = outcome
+                    .events
+                    .iter()
+                    .map(|e| json!(serialize_event(e)).to_string())
+                    .collect::<Vec<String>>();
+                EvalOutcomeRes {
+                    result: outcome.value.as_ref().map(clarity_values::to_raw_value),
+                    events: json!(events).to_string(),
+                    costs: json!(outcome.cost).to_string(),
+                    diagnostics: outcome
+                        .diagnostics
+                        .iter()
+                        .map(|d| d.message.to_string())
+                        .collect(),
+                }
+            })
+            .collect();
+        encode_to_js(&outcomes).map_err(|e| format!("error: {}", e))
+    }
+
     #[wasm_bindgen(js_name=executeCommand)]
     pub fn execute_command(&mut self, snippet: String) -> String {
         let session = self.get_session_mut();
@@ -1028,6 +1299,7 @@ impl SDK {
     #[wasm_bindgen(js_name=mintSTX)]
     pub fn mint_stx(&mut self, recipient: String, amount: u64) -> Result<String, String> {
         let session = self.get_session_mut();
+        session.invalidate_read_only_cache();
 
         session.interpreter.mint_stx_balance(
             PrincipalData::Standard(StandardPrincipalData::from(
@@ -1037,6 +1309,39 @@ impl SDK {
         )
     }
 
+    #[wasm_bindgen(js_name=mintSbtc)]
+    pub fn mint_sbtc(&mut self, recipient: String, amount: u64) -> Result<TransactionRes, String> {
+        let session = self.get_session_mut();
+        let execution = session.mint_sbtc(&recipient, amount.into())?;
+        Ok(execution_result_to_transaction_res(&execution))
+    }
+
+    #[wasm_bindgen(js_name=getSbtcBalance)]
+    pub fn get_sbtc_balance(&mut self, principal: String) -> Result<u64, String> {
+        let session = self.get_session_mut();
+        let balance = session.get_sbtc_balance(&principal)?;
+        u64::try_from(balance).map_err(|e| e.to_string())
+    }
+
+    #[wasm_bindgen(js_name=getStxBalance)]
+    pub fn get_stx_balance(&mut self, principal: String) -> Result<u64, String> {
+        let session = self.get_session_mut();
+        let balance = session.get_stx_balance(&principal)?;
+        u64::try_from(balance).map_err(|e| e.to_string())
+    }
+
+    #[wasm_bindgen(js_name=getFtBalance)]
+    pub fn get_ft_balance(
+        &mut self,
+        contract: String,
+        token_name: String,
+        principal: String,
+    ) -> Result<u64, String> {
+        let session = self.get_session_mut();
+        let balance = session.get_ft_balance(&contract, &token_name, &principal)?;
+        u64::try_from(balance).map_err(|e| e.to_string())
+    }
+
     #[wasm_bindgen(js_name=setCurrentTestName)]
     pub fn set_current_test_name(&mut self, test_name: String) {
         let session = self.get_session_mut();
@@ -1044,16 +1349,42 @@ impl SDK {
         self.current_test_name = test_name;
     }
 
-    // this method empty the session costs and coverage reports
-    // and returns this report
-    #[wasm_bindgen(js_name=collectReport)]
-    pub fn collect_report(
-        &mut self,
-        include_boot_contracts: bool,
-        boot_contracts_path: String,
-    ) -> Result<SessionReport, String> {
-        let contracts_locations = self.contracts_locations.clone();
+    // wall time can't be measured in wasm, only runtime costs are profiled
+    #[wasm_bindgen(js_name=enableProfiling)]
+    pub fn enable_profiling(&mut self) {
+        let session = self.get_session_mut();
+        session.enable_profiling(false);
+    }
+
+    // this method empty the profiler and returns the runtime costs as folded stacks
+    #[wasm_bindgen(js_name=collectProfile)]
+    pub fn collect_profile(&mut self) -> String {
+        let session = self.get_session_mut();
+        session.collect_folded_stacks(ProfileMetric::Runtime)
+    }
+
+    // read-only calls with the same arguments and chain state return the memoized result
+    #[wasm_bindgen(js_name=enableReadOnlyCache)]
+    pub fn enable_read_only_cache(&mut self) {
         let session = self.get_session_mut();
+        session.enable_read_only_cache();
+    }
+
+    #[wasm_bindgen(js_name=getReadOnlyCacheStats)]
+    pub fn get_read_only_cache_stats(&self) -> Result<JsValue, JsError> {
+        let stats = self.get_session().get_read_only_cache_stats();
+        Ok(encode_to_js(&stats)?)
+    }
+
+    fn get_coverage_sources(
+        &self,
+        include_boot_contracts: bool,
+        boot_contracts_path: &str,
+    ) -> (
+        BTreeMap<QualifiedContractIdentifier, ContractAST>,
+        BTreeMap<String, String>,
+    ) {
+        let session = self.get_session();
 
         let mut asts = BTreeMap::new();
         let mut contract_paths = BTreeMap::new();
@@ -1061,13 +1392,13 @@ impl SDK {
         for (contract_id, contract) in session.contracts.iter() {
             asts.insert(contract_id.clone(), contract.ast.clone());
         }
-        for (contract_id, contract_location) in contracts_locations.iter() {
+        for (contract_id, contract_location) in self.contracts_locations.iter() {
             contract_paths.insert(contract_id.name.to_string(), contract_location.to_string());
         }
 
         if include_boot_contracts {
             for (contract_id, (_, ast)) in BOOT_CONTRACTS_DATA.iter() {
-                asts.insert(contract_id.clone(), ast.clone());
+                asts.insert(contract_id.clone(), ContractAST::clone(ast));
                 contract_paths.insert(
                     contract_id.name.to_string(),
                     format!("{boot_contracts_path}/{}.clar", contract_id.name),
@@ -1075,11 +1406,81 @@ impl SDK {
             }
         }
 
-        let coverage = session.collect_lcov_content(&asts, &contract_paths);
+        (asts, contract_paths)
+    }
+
+    // this method empty the session costs and coverage reports
+    // and returns this report
+    #[wasm_bindgen(js_name=collectReport)]
+    pub fn collect_report(
+        &mut self,
+        include_boot_contracts: bool,
+        boot_contracts_path: String,
+    ) -> Result<SessionReport, String> {
+        let (asts, contract_paths) =
+            self.get_coverage_sources(include_boot_contracts, &boot_contracts_path);
+
+        let coverage = self
+            .get_session_mut()
+            .collect_lcov_content(&asts, &contract_paths);
 
         let costs = serde_json::to_string(&self.costs_reports).map_err(|e| e.to_string())?;
         self.costs_reports.clear();
 
+        // the reports of an ongoing scope have been collected
+        if let Some(ref mut scope) = self.scope {
+            scope.first_coverage_report = 0;
+            scope.first_costs_report = 0;
+        }
+
+        Ok(SessionReport { coverage, costs })
+    }
+
+    // starts attributing coverage and costs to `name` (usually a test name),
+    // until `endScope` is called
+    #[wasm_bindgen(js_name=beginScope)]
+    pub fn begin_scope(&mut self, name: String) -> Result<(), String> {
+        if self.scope.is_some() {
+            return Err("a measurement scope is already in progress".to_string());
+        }
+        let first_coverage_report = self.get_session().get_coverage_reports_count();
+        self.scope = Some(MeasurementScope {
+            previous_test_name: self.current_test_name.clone(),
+            first_coverage_report,
+            first_costs_report: self.costs_reports.len(),
+        });
+        self.set_current_test_name(name);
+        Ok(())
+    }
+
+    // returns the coverage and costs recorded since `beginScope`
+    // the reports are kept and still returned by `collectReport`
+    #[wasm_bindgen(js_name=endScope)]
+    pub fn end_scope(
+        &mut self,
+        include_boot_contracts: bool,
+        boot_contracts_path: String,
+    ) -> Result<SessionReport, String> {
+        let scope = self
+            .scope
+            .take()
+            .ok_or("no measurement scope in progress")?;
+        let (asts, contract_paths) =
+            self.get_coverage_sources(include_boot_contracts, &boot_contracts_path);
+
+        let coverage = self.get_session().get_lcov_content_since(
+            scope.first_coverage_report,
+            &asts,
+            &contract_paths,
+        );
+        let costs_reports = self
+            .costs_reports
+            .get(scope.first_costs_report..)
+            .unwrap_or_default();
+        let costs = serde_json::to_string(costs_reports).map_err(|e| e.to_string())?;
+
+        self.set_current_test_name(scope.previous_test_name);
+
         Ok(SessionReport { coverage, costs })
     }
 }
@@ -162,3 +162,22 @@ const CONTRACT_INTERFACE_STRING: &'static str = r#"export type IContractInterfac
   epoch: StacksEpochId;
   clarity_version: ClarityVersionString;
 };"#;
+
+#[wasm_bindgen(typescript_custom_section)]
+const BOOT_CONTRACT_INFO_STRING: &'static str = r#"export type BootContractInfo = {
+  contract_id: string;
+  name: string;
+  address: string;
+  clarity_version: string;
+  epoch: string;
+  source_hash: string;
+};"#;
+
+#[wasm_bindgen(typescript_custom_section)]
+const GENESIS_CONSTANTS_STRING: &'static str = r#"export type GenesisConstants = {
+  boot_testnet_address: string;
+  boot_mainnet_address: string;
+  sbtc_testnet_address: string;
+  sbtc_mainnet_address: string;
+  sbtc_contracts: string[];
+};"#;
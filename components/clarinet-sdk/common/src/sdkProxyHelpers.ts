@@ -49,6 +49,8 @@ export type TransferSTX = (
   sender: string,
 ) => ParsedTransactionResult;
 
+export type MintSbtc = (recipient: string, amount: number | bigint) => ParsedTransactionResult;
+
 export type Tx =
   | {
       callPublicFn: {
@@ -157,5 +159,30 @@ export function parseCosts(costs: string): ClarityCosts | null {
 
 export type MineBlock = (txs: Array<Tx>) => ParsedTransactionResult[];
 export type Execute = (snippet: string) => ParsedTransactionResult;
+export type EvalOutcome = {
+  result: ClarityValue | null;
+  events: ClarityEvent[];
+  costs: ClarityCosts | null;
+  diagnostics: string[];
+};
+export type EvalMany = (snippets: string[]) => EvalOutcome[];
 export type GetDataVar = (contract: string, dataVar: string) => ClarityValue;
 export type GetMapEntry = (contract: string, mapName: string, mapKey: ClarityValue) => ClarityValue;
+export type SetPoxPayoutAddresses = (
+  height: number,
+  addresses: ClarityValue[],
+  payout: bigint,
+) => void;
+export type SetDataVarAtBlock = (
+  height: number,
+  contract: string,
+  varName: string,
+  value: ClarityValue,
+) => void;
+export type SetMapEntryAtBlock = (
+  height: number,
+  contract: string,
+  mapName: string,
+  mapKey: ClarityValue,
+  value: ClarityValue | null,
+) => void;
@@ -14,10 +14,15 @@ import {
   type DeployContract,
   type GetDataVar,
   type GetMapEntry,
+  type SetPoxPayoutAddresses,
+  type SetDataVarAtBlock,
+  type SetMapEntryAtBlock,
   type MineBlock,
   type ParsedTransactionResult,
   type Execute,
+  type EvalMany,
   type TransferSTX,
+  type MintSbtc,
   parseCosts,
 } from "../../common/src/sdkProxyHelpers.js";
 
@@ -26,23 +31,37 @@ type RunSnippet = SDK["runSnippet"];
 
 // because the session is wrapped in a proxy the types need to be hardcoded
 export type Simnet = {
-  [K in keyof SDK]: K extends "callReadOnlyFn" | "callPublicFn" | "callPrivateFn"
+  [K in keyof SDK]: K extends
+    | "callReadOnlyFn"
+    | "callPublicFn"
+    | "callPrivateFn"
+    | "callPublicFnAsContract"
     ? CallFn
     : K extends "execute"
       ? Execute
-      : K extends "runSnippet"
-        ? RunSnippet
-        : K extends "deployContract"
-          ? DeployContract
-          : K extends "transferSTX"
-            ? TransferSTX
-            : K extends "mineBlock"
-              ? MineBlock
-              : K extends "getDataVar"
-                ? GetDataVar
-                : K extends "getMapEntry"
-                  ? GetMapEntry
-                  : SDK[K];
+      : K extends "evalMany"
+        ? EvalMany
+        : K extends "runSnippet"
+          ? RunSnippet
+          : K extends "deployContract"
+            ? DeployContract
+            : K extends "transferSTX"
+              ? TransferSTX
+              : K extends "mineBlock"
+                ? MineBlock
+                : K extends "getDataVar"
+                  ? GetDataVar
+                  : K extends "getMapEntry"
+                    ? GetMapEntry
+                    : K extends "setPoxPayoutAddresses"
+                      ? SetPoxPayoutAddresses
+                      : K extends "setDataVarAtBlock"
+                        ? SetDataVarAtBlock
+                        : K extends "setMapEntryAtBlock"
+                          ? SetMapEntryAtBlock
+                          : K extends "mintSbtc"
+                            ? MintSbtc
+                            : SDK[K];
 };
 
 function parseTxResponse(response: TransactionRes): ParsedTransactionResult {
@@ -60,7 +79,12 @@ export function getSessionProxy() {
       // - serialize clarity values input argument
       // - deserialize output into clarity values
 
-      if (prop === "callReadOnlyFn" || prop === "callPublicFn" || prop === "callPrivateFn") {
+      if (
+        prop === "callReadOnlyFn" ||
+        prop === "callPublicFn" ||
+        prop === "callPrivateFn" ||
+        prop === "callPublicFnAsContract"
+      ) {
         const callFn: CallFn = (contract, method, args, sender) => {
           const response = session[prop](
             new CallFnArgs(
@@ -83,6 +107,24 @@ export function getSessionProxy() {
         return execute;
       }
 
+      if (prop === "evalMany") {
+        const evalMany: EvalMany = (snippets) => {
+          const outcomes: {
+            result: string | null;
+            events: string;
+            costs: string;
+            diagnostics: string[];
+          }[] = session.evalMany(snippets);
+          return outcomes.map((outcome) => ({
+            result: outcome.result ? Cl.deserialize(outcome.result) : null,
+            events: parseEvents(outcome.events),
+            costs: parseCosts(outcome.costs),
+            diagnostics: outcome.diagnostics,
+          }));
+        };
+        return evalMany;
+      }
+
       if (prop === "deployContract") {
         const callDeployContract: DeployContract = (name, content, options, sender) => {
           const rustOptions = options
@@ -105,6 +147,14 @@ export function getSessionProxy() {
         return callTransferSTX;
       }
 
+      if (prop === "mintSbtc") {
+        const mintSbtc: MintSbtc = (recipient, amount) => {
+          const response = session.mintSbtc(recipient, BigInt(amount));
+          return parseTxResponse(response);
+        };
+        return mintSbtc;
+      }
+
       if (prop === "mineBlock") {
         const callMineBlock: MineBlock = (txs) => {
           const serializedTxs = txs.map((tx) => {
@@ -151,6 +201,39 @@ export function getSessionProxy() {
         return getMapEntry;
       }
 
+      if (prop === "setPoxPayoutAddresses") {
+        const setPoxPayoutAddresses: SetPoxPayoutAddresses = (height, addresses, payout) => {
+          session.setPoxPayoutAddresses(height, Cl.serialize(Cl.list(addresses)), payout);
+        };
+        return setPoxPayoutAddresses;
+      }
+
+      if (prop === "setDataVarAtBlock") {
+        const setDataVarAtBlock: SetDataVarAtBlock = (height, contract, varName, value) => {
+          session.setDataVarAtBlock(height, contract, varName, Cl.serialize(value));
+        };
+        return setDataVarAtBlock;
+      }
+
+      if (prop === "setMapEntryAtBlock") {
+        const setMapEntryAtBlock: SetMapEntryAtBlock = (
+          height,
+          contract,
+          mapName,
+          mapKey,
+          value,
+        ) => {
+          session.setMapEntryAtBlock(
+            height,
+            contract,
+            mapName,
+            Cl.serialize(mapKey),
+            value ? Cl.serialize(value) : undefined,
+          );
+        };
+        return setMapEntryAtBlock;
+      }
+
       return Reflect.get(session, prop, receiver);
     },
   };
@@ -39,6 +39,16 @@ export function getClarinetVitestsArgv() {
       alias: "costs-file",
       type: "string",
       default: "costs-reports.json",
+    })
+    .option("flaky-reruns", {
+      description: "Re-run the failing tests this many times, and list the flaky ones",
+      type: "number",
+      default: 0,
+    })
+    .option("quarantine-filename", {
+      alias: "quarantine-file",
+      type: "string",
+      default: "quarantine.json",
     }).argv;
 }
 
@@ -16,11 +16,15 @@ import { vitestSetupFilePath, getClarinetVitestsArgv } from "@hirosystems/clarin
   The `getClarinetVitestsArgv()` will parse options passed to the command `vitest run --`
     - vitest run -- --manifest ./Clarinet.toml  # pass a custom path
     - vitest run -- --coverage --costs          # collect coverage and cost reports
+    - vitest run -- --flaky-reruns 3            # re-run failing tests, list the flaky ones in quarantine.json
 */
 
+const clarinetArgv = getClarinetVitestsArgv();
+
 export default defineConfig({
   test: {
     environment: "clarinet", // use vitest-environment-clarinet
+    retry: clarinetArgv.flakyReruns,
     pool: "forks",
     poolOptions: {
       threads: { singleThread: true },
@@ -32,7 +36,7 @@ export default defineConfig({
     ],
     environmentOptions: {
       clarinet: {
-        ...getClarinetVitestsArgv(),
+        ...clarinetArgv,
         // add or override options
       },
     },
@@ -108,6 +108,49 @@ describe("simnet can report both costs and coverage", () => {
   });
 });
 
+describe("simnet can measure coverage and costs per scope", () => {
+  it("returns the reports recorded within a scope", async () => {
+    const simnet = await initSimnet("tests/fixtures/Clarinet.toml", true, {
+      trackCoverage: true,
+      trackCosts: true,
+    });
+
+    simnet.beginScope("test1");
+    simnet.callPublicFn("counter", "increment", [], address1);
+    const scope1 = simnet.endScope(false, "");
+
+    simnet.beginScope("test2");
+    simnet.callPublicFn("counter", "increment", [], address1);
+    simnet.callPublicFn("counter", "increment", [], address1);
+    const scope2 = simnet.endScope(false, "");
+
+    expect(scope1.coverage).toContain("TN:test1");
+    expect(scope1.coverage).toContain("FNDA:1,increment");
+    expect(scope1.coverage).not.toContain("TN:test2");
+    expect(JSON.parse(scope1.costs)).toHaveLength(1);
+
+    expect(scope2.coverage).toContain("FNDA:2,increment");
+    expect(scope2.coverage).not.toContain("TN:test1");
+    expect(JSON.parse(scope2.costs)).toHaveLength(2);
+
+    // the reports of the scopes are still collected
+    const reports = simnet.collectReport(false, "");
+    expect(JSON.parse(reports.costs)).toHaveLength(3);
+  });
+
+  it("can not nest scopes", async () => {
+    const simnet = await initSimnet("tests/fixtures/Clarinet.toml", true, {
+      trackCoverage: true,
+      trackCosts: true,
+    });
+
+    simnet.beginScope("test1");
+    expect(() => simnet.beginScope("test2")).toThrow();
+    simnet.endScope(false, "");
+    expect(() => simnet.endScope(false, "")).toThrow();
+  });
+});
+
 describe("simnet.run-snippet and .execute also report coverage", () => {
   it("simnet.execute reports coverage", async () => {
     const simnet = await initSimnet("tests/fixtures/Clarinet.toml", true, {
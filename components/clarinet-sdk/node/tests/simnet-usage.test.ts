@@ -105,6 +105,18 @@ describe("simnet epoch 3", () => {
     expect(simnet.burnBlockHeight).toBe(burnBlockHeight + 4);
     expect(simnet.stacksBlockHeight).toBe(blockHeight + 9);
   });
+
+  it("can advance by reward cycles and durations", () => {
+    simnet.setEpoch("3.0");
+    const rewardCycle = simnet.currentRewardCycle;
+    expect(simnet.advanceRewardCycles(1)).toBe(rewardCycle + 1);
+    expect(simnet.currentRewardCycle).toBe(rewardCycle + 1);
+
+    const blockHeight = simnet.stacksBlockHeight;
+    const burnBlockHeight = simnet.burnBlockHeight;
+    expect(simnet.advanceTime(60 * 60, 2)).toBe(burnBlockHeight + 6);
+    expect(simnet.stacksBlockHeight).toBe(blockHeight + 12);
+  });
 });
 describe("simnet can run arbitrary snippets", () => {
   it("can run simple snippets", () => {
@@ -117,6 +129,25 @@ describe("simnet can run arbitrary snippets", () => {
       simnet.execute("(+ 1 u2)");
     }).toThrow("error: expecting expression of type 'int', found 'uint'");
   });
+
+  it("can evaluate snippets in batch", () => {
+    const outcomes = simnet.evalMany(["(+ 1 2)", "(+ 1 u2)", "(print u1)"]);
+    expect(outcomes).toHaveLength(3);
+    expect(outcomes[0].result).toStrictEqual(Cl.int(3));
+    expect(outcomes[0].costs).not.toBeNull();
+    expect(outcomes[1].result).toBeNull();
+    expect(outcomes[1].diagnostics).toStrictEqual([
+      "expecting expression of type 'int', found 'uint'",
+    ]);
+    expect(outcomes[2].events).toHaveLength(1);
+  });
+
+  it("can not mint sBTC without the sBTC contracts", () => {
+    expect(() => simnet.mintSbtc(address1, 1000)).toThrow(
+      "SM3VDXK3WZZSA84XXFKAFAF15NNZX32CTSG82JFQ4.sbtc-deposit is not deployed",
+    );
+    expect(() => simnet.getSbtcBalance(address1)).toThrow("sbtc-token is not deployed");
+  });
 });
 
 describe("simnet can call contracts function", () => {
@@ -0,0 +1,68 @@
+import fs from "node:fs";
+
+/*
+  When failing tests are re-run (`vitest run -- --flaky-reruns 3`), each attempt of a test is
+  recorded. A test that eventually passes is flaky, a test that fails every attempt is a
+  deterministic failure. Both lists are written to a quarantine file shared by the test files.
+*/
+
+export type TestAttempts = {
+  file: string;
+  name: string;
+  attempts: number;
+  failures: number;
+  passed: boolean;
+};
+
+export type QuarantinedTest = Omit<TestAttempts, "passed">;
+
+export type Quarantine = {
+  flaky: QuarantinedTest[];
+  failing: QuarantinedTest[];
+};
+
+export function recordAttempt(
+  attempts: Map<string, TestAttempts>,
+  file: string,
+  name: string,
+  failed: boolean,
+) {
+  const key = `${file}__${name}`;
+  const test = attempts.get(key) || { file, name, attempts: 0, failures: 0, passed: false };
+  test.attempts += 1;
+  if (failed) test.failures += 1;
+  test.passed = !failed;
+  attempts.set(key, test);
+}
+
+export function classifyAttempts(attempts: TestAttempts[]): Quarantine {
+  const quarantine: Quarantine = { flaky: [], failing: [] };
+  for (const { passed, ...test } of attempts) {
+    if (test.failures === 0) continue;
+    if (passed) quarantine.flaky.push(test);
+    else quarantine.failing.push(test);
+  }
+  return quarantine;
+}
+
+// the entries of the test files run again replace their previous entries
+export function mergeQuarantine(previous: Quarantine, current: Quarantine, files: string[]) {
+  const keep = (test: QuarantinedTest) => !files.includes(test.file);
+  return {
+    flaky: [...previous.flaky.filter(keep), ...current.flaky],
+    failing: [...previous.failing.filter(keep), ...current.failing],
+  };
+}
+
+export function writeQuarantine(filename: string, current: Quarantine, files: string[]) {
+  let previous: Quarantine = { flaky: [], failing: [] };
+  if (fs.existsSync(filename)) {
+    try {
+      previous = JSON.parse(fs.readFileSync(filename, "utf-8"));
+    } catch (e) {
+      console.warn(`Ignoring invalid quarantine file ${filename}`);
+    }
+  }
+  const quarantine = mergeQuarantine(previous, current, files);
+  fs.writeFileSync(filename, JSON.stringify(quarantine, null, 2));
+}
@@ -15,6 +15,8 @@ declare global {
       costsFilename: string;
       includeBootContracts: boolean;
       bootContractsPath: string;
+      flakyReruns: number;
+      quarantineFilename: string;
     };
   };
 }
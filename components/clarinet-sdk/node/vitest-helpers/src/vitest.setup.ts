@@ -1,6 +1,9 @@
 import { Task, afterAll, beforeAll, beforeEach, afterEach } from "vitest";
 
 import "./clarityValuesMatchers";
+import { TestAttempts, classifyAttempts, recordAttempt, writeQuarantine } from "./flakiness";
+
+const testsAttempts = new Map<string, TestAttempts>();
 
 function getFullTestName(task: Task, names: string[]) {
   const fullNames = [task.name, ...names];
@@ -24,6 +27,12 @@ beforeEach(async (ctx) => {
     await simnet.initSession(process.cwd(), manifestPath);
   }
 
+  // re-runs of a failing test start at a later block, to surface block timing dependencies
+  const retryCount = ctx.task.result?.retryCount || 0;
+  if (retryCount > 0) {
+    simnet.mineEmptyBlocks(retryCount);
+  }
+
   if (coverage) {
     const suiteTestNames = getFullTestName(ctx.task, []);
     const fullName = [ctx.task.file?.name || "", ...suiteTestNames].join("__");
@@ -31,9 +40,21 @@ beforeEach(async (ctx) => {
   }
 });
 
-afterEach(async () => {
-  const { coverage, costs, initBeforeEach, includeBootContracts, bootContractsPath } =
-    global.options.clarinet;
+afterEach(async (ctx) => {
+  const {
+    coverage,
+    costs,
+    initBeforeEach,
+    includeBootContracts,
+    bootContractsPath,
+    flakyReruns,
+  } = global.options.clarinet;
+
+  if (flakyReruns > 0) {
+    const name = getFullTestName(ctx.task, []).join(" > ");
+    const failed = ctx.task.result?.state === "fail";
+    recordAttempt(testsAttempts, ctx.task.file?.name || "", name, failed);
+  }
 
   if (initBeforeEach && (coverage || costs)) {
     const report = simnet.collectReport(includeBootContracts, bootContractsPath || "");
@@ -50,9 +71,21 @@ beforeAll(async () => {
   }
 });
 
-afterAll(() => {
-  const { coverage, costs, initBeforeEach, includeBootContracts, bootContractsPath } =
-    global.options.clarinet;
+afterAll((suite) => {
+  const {
+    coverage,
+    costs,
+    initBeforeEach,
+    includeBootContracts,
+    bootContractsPath,
+    flakyReruns,
+    quarantineFilename,
+  } = global.options.clarinet;
+
+  if (flakyReruns > 0) {
+    const quarantine = classifyAttempts([...testsAttempts.values()]);
+    writeQuarantine(quarantineFilename, quarantine, [suite.file?.name || suite.name]);
+  }
 
   if (!initBeforeEach && (coverage || costs)) {
     const report = simnet.collectReport(includeBootContracts, bootContractsPath || "");
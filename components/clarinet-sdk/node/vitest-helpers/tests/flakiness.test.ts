@@ -0,0 +1,30 @@
+import { describe, it, expect } from "vitest";
+
+import { TestAttempts, classifyAttempts, mergeQuarantine, recordAttempt } from "../src/flakiness";
+
+describe("classifies the failing tests", () => {
+  it("separates the flaky tests from the deterministic failures", () => {
+    const attempts = new Map<string, TestAttempts>();
+    recordAttempt(attempts, "counter.test.ts", "increments", true);
+    recordAttempt(attempts, "counter.test.ts", "increments", false);
+    recordAttempt(attempts, "counter.test.ts", "decrements", true);
+    recordAttempt(attempts, "counter.test.ts", "decrements", true);
+    recordAttempt(attempts, "counter.test.ts", "reads", false);
+
+    const quarantine = classifyAttempts([...attempts.values()]);
+    expect(quarantine.flaky).toStrictEqual([
+      { file: "counter.test.ts", name: "increments", attempts: 2, failures: 1 },
+    ]);
+    expect(quarantine.failing).toStrictEqual([
+      { file: "counter.test.ts", name: "decrements", attempts: 2, failures: 2 },
+    ]);
+
+    const previous = {
+      flaky: [{ file: "counter.test.ts", name: "reads", attempts: 2, failures: 1 }],
+      failing: [{ file: "token.test.ts", name: "mints", attempts: 3, failures: 3 }],
+    };
+    const merged = mergeQuarantine(previous, quarantine, ["counter.test.ts"]);
+    expect(merged.flaky.map((test) => test.name)).toStrictEqual(["increments"]);
+    expect(merged.failing.map((test) => test.name)).toStrictEqual(["mints", "decrements"]);
+  });
+});
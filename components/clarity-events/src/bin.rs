@@ -6,11 +6,14 @@ extern crate serde_derive;
 extern crate serde_json;
 
 pub mod analysis;
+pub mod codegen;
+pub mod schema;
 
 use analysis::{EventCollector, Settings};
 use clap::{Parser, Subcommand};
 use clarinet_files::FileLocation;
 use clarity_repl::clarity::analysis::type_checker::v2_05::TypeChecker;
+use clarity_repl::clarity::vm::analysis::types::ContractAnalysis;
 use clarity_repl::{
     clarity::{costs::LimitedCostTracker, EvaluationResult},
     repl::{Session, SessionSettings},
@@ -28,6 +31,9 @@ enum Command {
     /// Format file
     #[clap(name = "scan", bin_name = "scan")]
     Scan(Scan),
+    /// Print the schema of the events of a contract, or decoders of these events
+    #[clap(name = "schema", bin_name = "schema")]
+    Schema(Schema),
 }
 
 #[derive(Parser, PartialEq, Clone, Debug)]
@@ -36,6 +42,38 @@ struct Scan {
     pub file_path: String,
 }
 
+#[derive(Parser, PartialEq, Clone, Debug)]
+struct Schema {
+    /// File path
+    pub file_path: String,
+    /// Generate a TypeScript decoder
+    #[clap(long = "typescript", conflicts_with = "rust")]
+    pub typescript: bool,
+    /// Generate a Rust decoder
+    #[clap(long = "rust")]
+    pub rust: bool,
+}
+
+fn get_contract_analysis(file_path: &str) -> (Session, ContractAnalysis) {
+    let file = FileLocation::from_path_string(file_path).unwrap();
+    let snippet = file.read_content_as_utf8().unwrap();
+    let mut session = Session::new(SessionSettings::default());
+    let contract_analysis = match session.eval(snippet, false) {
+        Ok(execution) => match execution.result {
+            EvaluationResult::Contract(evaluation) => evaluation.contract.analysis,
+            _ => {
+                println!("empty contract");
+                std::process::exit(1);
+            }
+        },
+        Err(e) => {
+            println!("Error path: {:?}", e);
+            std::process::exit(1);
+        }
+    };
+    (session, contract_analysis)
+}
+
 pub fn main() {
     let opts: Opts = match Opts::try_parse() {
         Ok(opts) => opts,
@@ -47,22 +85,7 @@ pub fn main() {
 
     match opts.command {
         Command::Scan(cmd) => {
-            let file = FileLocation::from_path_string(&cmd.file_path).unwrap();
-            let snippet = file.read_content_as_utf8().unwrap();
-            let mut session = Session::new(SessionSettings::default());
-            let mut contract_analysis = match session.eval(snippet, false) {
-                Ok(execution) => match execution.result {
-                    EvaluationResult::Contract(evaluation) => evaluation.contract.analysis,
-                    _ => {
-                        println!("empty contract");
-                        std::process::exit(1);
-                    }
-                },
-                Err(e) => {
-                    println!("Error path: {:?}", e);
-                    std::process::exit(1);
-                }
-            };
+            let (mut session, mut contract_analysis) = get_contract_analysis(&cmd.file_path);
 
             {
                 let mut analysis_db = session.interpreter.clarity_datastore.as_analysis_db();
@@ -85,5 +108,19 @@ pub fn main() {
                 }
             }
         }
+        Command::Schema(cmd) => {
+            let (mut session, mut contract_analysis) = get_contract_analysis(&cmd.file_path);
+            let mut analysis_db = session.interpreter.clarity_datastore.as_analysis_db();
+            let cost_track = LimitedCostTracker::new_free();
+            let type_checker = TypeChecker::new(&mut analysis_db, cost_track, true);
+            let events_schema = schema::extract_events_schema(&mut contract_analysis, type_checker);
+            if cmd.typescript {
+                print!("{}", codegen::generate_typescript_decoder(&events_schema));
+            } else if cmd.rust {
+                print!("{}", codegen::generate_rust_decoder(&events_schema));
+            } else {
+                println!("{}", serde_json::to_string_pretty(&events_schema).unwrap());
+            }
+        }
     };
 }
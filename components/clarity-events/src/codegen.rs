@@ -0,0 +1,318 @@
+//! Decoders of the events of a contract, generated from their schema for the indexers and
+//! chainhook consumers. The decoders take the hex encoded value of a print event (the
+//! `raw_value` of the chainhook payloads) and return a typed event when its topic is known.
+//! Only the events with a topic get a decoder.
+
+use clarity_repl::clarity::vm::types::{SequenceSubtype, StringSubtype, TypeSignature};
+
+use crate::schema::{get_topic_field, ContractEventsSchema, EventSchema};
+
+const RUST_KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern",
+    "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub",
+    "ref", "return", "static", "struct", "super", "trait", "true", "type", "unsafe", "use",
+    "where", "while",
+];
+
+fn to_pascal_case(name: &str) -> String {
+    let name: String = name
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+                None => String::new(),
+            }
+        })
+        .collect();
+    match name.chars().next() {
+        Some(first) if first.is_ascii_alphabetic() => name,
+        _ => format!("E{}", name),
+    }
+}
+
+fn to_snake_case(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if RUST_KEYWORDS.contains(&name.as_str()) {
+        format!("r#{}", name)
+    } else if name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("_{}", name)
+    } else {
+        name
+    }
+}
+
+fn get_contract_name(schema: &ContractEventsSchema) -> &str {
+    schema
+        .contract_id
+        .rsplit('.')
+        .next()
+        .unwrap_or(&schema.contract_id)
+}
+
+/// When a topic is printed with different types, the first one is decoded
+fn get_topic_events(schema: &ContractEventsSchema) -> Vec<(&str, &str, &EventSchema)> {
+    let mut events: Vec<(&str, &str, &EventSchema)> = vec![];
+    for event in schema.events.iter() {
+        let (Some(topic), Some(field)) = (
+            event.topic.as_deref(),
+            get_topic_field(&event.type_signature),
+        ) else {
+            continue;
+        };
+        if !events.iter().any(|(known, _, _)| *known == topic) {
+            events.push((topic, field, event));
+        }
+    }
+    events
+}
+
+/// Type of the values returned by the `decodeValue` helper
+fn get_typescript_type(type_signature: &TypeSignature) -> String {
+    match type_signature {
+        TypeSignature::BoolType => "boolean".to_string(),
+        TypeSignature::OptionalType(inner) => format!("{} | null", get_typescript_type(inner)),
+        TypeSignature::ResponseType(inner) => {
+            let (ok_type, err_type) = &**inner;
+            format!(
+                "{{ ok: {} }} | {{ err: {} }}",
+                get_typescript_type(ok_type),
+                get_typescript_type(err_type)
+            )
+        }
+        TypeSignature::SequenceType(SequenceSubtype::ListType(list)) => {
+            format!("({})[]", get_typescript_type(list.get_list_item_type()))
+        }
+        TypeSignature::TupleType(tuple) => {
+            let fields: Vec<String> = tuple
+                .get_type_map()
+                .iter()
+                .map(|(name, field_type)| {
+                    format!("\"{}\": {}", name, get_typescript_type(field_type))
+                })
+                .collect();
+            format!("{{ {} }}", fields.join("; "))
+        }
+        TypeSignature::NoType => "unknown".to_string(),
+        _ => "string".to_string(),
+    }
+}
+
+const TYPESCRIPT_DECODE_VALUE: &str = r#"function decodeValue(cv: ClarityValue): any {
+  switch (cv.type) {
+    case ClarityType.Int:
+    case ClarityType.UInt:
+      return cv.value.toString();
+    case ClarityType.BoolTrue:
+      return true;
+    case ClarityType.BoolFalse:
+      return false;
+    case ClarityType.PrincipalStandard:
+    case ClarityType.PrincipalContract:
+      return cvToString(cv);
+    case ClarityType.Buffer:
+      return Array.from(cv.buffer, (byte) => byte.toString(16).padStart(2, "0")).join("");
+    case ClarityType.StringASCII:
+    case ClarityType.StringUTF8:
+      return cv.data;
+    case ClarityType.OptionalNone:
+      return null;
+    case ClarityType.OptionalSome:
+      return decodeValue(cv.value);
+    case ClarityType.ResponseOk:
+      return { ok: decodeValue(cv.value) };
+    case ClarityType.ResponseErr:
+      return { err: decodeValue(cv.value) };
+    case ClarityType.List:
+      return cv.list.map(decodeValue);
+    case ClarityType.Tuple:
+      return Object.fromEntries(
+        Object.entries(cv.data).map(([key, value]) => [key, decodeValue(value)]),
+      );
+  }
+}
+"#;
+
+pub fn generate_typescript_decoder(schema: &ContractEventsSchema) -> String {
+    let contract_type = to_pascal_case(get_contract_name(schema));
+    let events = get_topic_events(schema);
+
+    let mut code = String::new();
+    code.push_str(&format!(
+        "// Events of {}, generated by clarity-events\n\n",
+        schema.contract_id
+    ));
+    code.push_str(
+        "import { ClarityType, ClarityValue, cvToString, hexToCV } from \"@stacks/transactions\";\n\n",
+    );
+
+    let mut event_types = vec![];
+    for (topic, field, event) in events.iter() {
+        let event_type = format!("{}Event", to_pascal_case(topic));
+        let TypeSignature::TupleType(tuple) = &event.type_signature else {
+            continue;
+        };
+        code.push_str(&format!("export type {} = {{\n", event_type));
+        for (name, field_type) in tuple.get_type_map().iter() {
+            let ts_type = if name.as_str() == *field {
+                format!("\"{}\"", topic)
+            } else {
+                get_typescript_type(field_type)
+            };
+            code.push_str(&format!("  \"{}\": {};\n", name, ts_type));
+        }
+        code.push_str("};\n\n");
+        event_types.push(event_type);
+    }
+
+    if event_types.is_empty() {
+        code.push_str(&format!("export type {}Event = never;\n\n", contract_type));
+    } else {
+        code.push_str(&format!(
+            "export type {}Event = {};\n\n",
+            contract_type,
+            event_types.join(" | ")
+        ));
+    }
+
+    code.push_str(TYPESCRIPT_DECODE_VALUE);
+    code.push('\n');
+    code.push_str(&format!(
+        "export function decode{}Event(rawValue: string): {}Event | undefined {{\n",
+        contract_type, contract_type
+    ));
+    code.push_str("  const value = decodeValue(hexToCV(rawValue));\n");
+    code.push_str("  if (typeof value !== \"object\" || value === null) return undefined;\n");
+    for (topic, field, _) in events.iter() {
+        code.push_str(&format!(
+            "  if (value[\"{}\"] === \"{}\") return value;\n",
+            field, topic
+        ));
+    }
+    code.push_str("  return undefined;\n}\n");
+    code
+}
+
+/// Rust type of a field, and the conversion of `value` (a `&Value`) to this type. The
+/// composite types are left as clarity values.
+fn get_rust_field(type_signature: &TypeSignature, value: &str) -> (String, String) {
+    match type_signature {
+        TypeSignature::IntType => (
+            "i128".into(),
+            format!("{}.clone().expect_i128().ok()?", value),
+        ),
+        TypeSignature::UIntType => (
+            "u128".into(),
+            format!("{}.clone().expect_u128().ok()?", value),
+        ),
+        TypeSignature::BoolType => (
+            "bool".into(),
+            format!("{}.clone().expect_bool().ok()?", value),
+        ),
+        TypeSignature::PrincipalType => (
+            "String".into(),
+            format!("{}.clone().expect_principal().ok()?.to_string()", value),
+        ),
+        TypeSignature::SequenceType(SequenceSubtype::BufferType(len)) => (
+            "Vec<u8>".into(),
+            format!("{}.clone().expect_buff({}).ok()?", value, u32::from(len)),
+        ),
+        TypeSignature::SequenceType(SequenceSubtype::StringType(StringSubtype::ASCII(_))) => (
+            "String".into(),
+            format!("{}.clone().expect_ascii().ok()?", value),
+        ),
+        _ => ("Value".into(), format!("{}.clone()", value)),
+    }
+}
+
+pub fn generate_rust_decoder(schema: &ContractEventsSchema) -> String {
+    let contract_type = to_pascal_case(get_contract_name(schema));
+    let events = get_topic_events(schema);
+
+    let mut code = String::new();
+    code.push_str(&format!(
+        "// Events of {}, generated by clarity-events\n\n",
+        schema.contract_id
+    ));
+    code.push_str("use clarity::vm::types::TupleData;\nuse clarity::vm::Value;\n\n");
+
+    let mut variants = vec![];
+    let mut decoders = vec![];
+    for (topic, field, event) in events.iter() {
+        let variant = to_pascal_case(topic);
+        let event_type = format!("{}Event", variant);
+        let TypeSignature::TupleType(tuple) = &event.type_signature else {
+            continue;
+        };
+        code.push_str("#[derive(Debug, Clone, PartialEq)]\n");
+        code.push_str(&format!("pub struct {} {{\n", event_type));
+        let mut conversions = vec![];
+        for (name, field_type) in tuple.get_type_map().iter() {
+            if name.as_str() == *field {
+                continue;
+            }
+            let (rust_type, conversion) =
+                get_rust_field(field_type, &format!("tuple.get(\"{}\").ok()?", name));
+            let field_name = to_snake_case(name);
+            code.push_str(&format!("    pub {}: {},\n", field_name, rust_type));
+            conversions.push(format!("            {}: {},\n", field_name, conversion));
+        }
+        code.push_str("}\n\n");
+
+        let mut decoder = format!(
+            "        if get_topic(&tuple, \"{}\").as_deref() == Some(\"{}\") {{\n",
+            field, topic
+        );
+        decoder.push_str(&format!(
+            "            return Some({}Event::{}({} {{\n",
+            contract_type, variant, event_type
+        ));
+        for conversion in conversions {
+            decoder.push_str("    ");
+            decoder.push_str(&conversion);
+        }
+        decoder.push_str("            }));\n        }\n");
+        decoders.push(decoder);
+        variants.push(format!("    {}({}),\n", variant, event_type));
+    }
+
+    code.push_str("#[derive(Debug, Clone, PartialEq)]\n");
+    code.push_str(&format!("pub enum {}Event {{\n", contract_type));
+    for variant in variants {
+        code.push_str(&variant);
+    }
+    code.push_str("}\n\n");
+
+    code.push_str("fn get_topic(tuple: &TupleData, field: &str) -> Option<String> {\n");
+    code.push_str("    tuple.get(field).ok()?.clone().expect_ascii().ok()\n}\n\n");
+
+    code.push_str(&format!("impl {}Event {{\n", contract_type));
+    code.push_str("    /// Decodes the hex encoded value of a print event\n");
+    code.push_str("    pub fn decode(raw_value: &str) -> Option<Self> {\n");
+    code.push_str("        let raw_value = raw_value.strip_prefix(\"0x\").unwrap_or(raw_value);\n");
+    code.push_str(
+        "        let tuple = Value::try_deserialize_hex_untyped(raw_value).ok()?.expect_tuple().ok()?;\n",
+    );
+    for decoder in decoders {
+        code.push_str(&decoder);
+    }
+    code.push_str("        None\n    }\n}\n");
+    code
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identifiers() {
+        assert_eq!(to_pascal_case("token-transfer"), "TokenTransfer");
+        assert_eq!(to_pascal_case("2-step"), "E2Step");
+        assert_eq!(to_snake_case("new-owner"), "new_owner");
+        assert_eq!(to_snake_case("type"), "r#type");
+    }
+}
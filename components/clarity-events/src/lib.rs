@@ -4,3 +4,5 @@ extern crate serde;
 extern crate serde_json;
 
 pub mod analysis;
+pub mod codegen;
+pub mod schema;
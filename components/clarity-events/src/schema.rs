@@ -0,0 +1,309 @@
+//! Shapes of the events emitted by a contract with `print`, inferred from the types of the
+//! printed values. An event is identified by its topic: a `topic` (or `event`) field of a
+//! printed tuple, set to a string literal. Prints of the same topic and type are merged, and
+//! the schema of each event is a JSON schema of the printed value.
+
+use clarity_repl::analysis::ast_visitor::{traverse, ASTVisitor, TypedVar};
+use clarity_repl::clarity::analysis::type_checker::v2_05::TypeChecker;
+use clarity_repl::clarity::vm::analysis::types::ContractAnalysis;
+use clarity_repl::clarity::vm::types::{
+    SequenceSubtype, StringSubtype, TupleTypeSignature, TypeSignature,
+};
+use clarity_repl::clarity::vm::{ClarityName, SymbolicExpression};
+use clarity_repl::clarity::SymbolicExpressionType;
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+
+/// Names of the tuple fields identifying an event, by order of precedence
+pub const TOPIC_FIELDS: &[&str] = &["topic", "event"];
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EventSchema {
+    /// None for the prints of values that are not tuples with a literal topic
+    pub topic: Option<String>,
+    pub clarity_type: String,
+    /// Functions printing the event
+    pub functions: Vec<String>,
+    pub schema: JsonValue,
+    #[serde(skip)]
+    pub type_signature: TypeSignature,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ContractEventsSchema {
+    pub contract_id: String,
+    pub events: Vec<EventSchema>,
+}
+
+/// JSON representation of the values of a type, as decoded by the generated decoders:
+/// integers are strings (they can exceed the precision of JSON numbers) and buffers are
+/// hex encoded
+pub fn type_signature_to_json_schema(type_signature: &TypeSignature) -> JsonValue {
+    let mut schema = match type_signature {
+        TypeSignature::IntType => json!({ "type": "string", "pattern": "^-?[0-9]+$" }),
+        TypeSignature::UIntType => json!({ "type": "string", "pattern": "^[0-9]+$" }),
+        TypeSignature::BoolType => json!({ "type": "boolean" }),
+        TypeSignature::PrincipalType
+        | TypeSignature::CallableType(_)
+        | TypeSignature::ListUnionType(_)
+        | TypeSignature::TraitReferenceType(_) => json!({ "type": "string" }),
+        TypeSignature::SequenceType(SequenceSubtype::BufferType(len)) => json!({
+            "type": "string",
+            "contentEncoding": "base16",
+            "maxLength": u32::from(len) * 2,
+        }),
+        TypeSignature::SequenceType(SequenceSubtype::StringType(StringSubtype::ASCII(len))) => {
+            json!({ "type": "string", "maxLength": u32::from(len) })
+        }
+        TypeSignature::SequenceType(SequenceSubtype::StringType(StringSubtype::UTF8(len))) => {
+            json!({ "type": "string", "maxLength": u32::from(len) })
+        }
+        TypeSignature::SequenceType(SequenceSubtype::ListType(list)) => json!({
+            "type": "array",
+            "items": type_signature_to_json_schema(list.get_list_item_type()),
+            "maxItems": list.get_max_len(),
+        }),
+        TypeSignature::OptionalType(inner) => json!({
+            "anyOf": [type_signature_to_json_schema(inner), { "type": "null" }],
+        }),
+        TypeSignature::ResponseType(inner) => {
+            let (ok_type, err_type) = &**inner;
+            json!({
+                "oneOf": [
+                    {
+                        "type": "object",
+                        "properties": { "ok": type_signature_to_json_schema(ok_type) },
+                        "required": ["ok"],
+                        "additionalProperties": false,
+                    },
+                    {
+                        "type": "object",
+                        "properties": { "err": type_signature_to_json_schema(err_type) },
+                        "required": ["err"],
+                        "additionalProperties": false,
+                    },
+                ],
+            })
+        }
+        TypeSignature::TupleType(tuple) => {
+            let mut properties = serde_json::Map::new();
+            for (name, field_type) in tuple.get_type_map().iter() {
+                properties.insert(name.to_string(), type_signature_to_json_schema(field_type));
+            }
+            let required: Vec<&str> = tuple.get_type_map().keys().map(|k| k.as_str()).collect();
+            json!({
+                "type": "object",
+                "properties": properties,
+                "required": required,
+                "additionalProperties": false,
+            })
+        }
+        TypeSignature::NoType => json!({}),
+    };
+    schema["x-clarity-type"] = json!(type_signature.to_string());
+    schema
+}
+
+/// Field of a tuple type holding the topic of the event
+pub fn get_topic_field(type_signature: &TypeSignature) -> Option<&'static str> {
+    let TypeSignature::TupleType(tuple) = type_signature else {
+        return None;
+    };
+    TOPIC_FIELDS.iter().copied().find(|field| {
+        tuple
+            .get_type_map()
+            .keys()
+            .any(|name| name.as_str() == *field)
+    })
+}
+
+/// Topic of a printed tuple literal
+fn get_event_topic(value: &SymbolicExpression) -> Option<String> {
+    let (name, fields) = value.match_list()?.split_first()?;
+    if name.match_atom()?.as_str() != "tuple" {
+        return None;
+    }
+    for topic_field in TOPIC_FIELDS {
+        for field in fields.iter() {
+            let Some([key, value]) = field.match_list() else {
+                continue;
+            };
+            if key.match_atom().map(|k| k.as_str()) != Some(*topic_field) {
+                continue;
+            }
+            // a topic that isn't a literal can't identify the event
+            return match &value.expr {
+                SymbolicExpressionType::LiteralValue(value)
+                | SymbolicExpressionType::AtomValue(value) => value.clone().expect_ascii().ok(),
+                _ => None,
+            };
+        }
+    }
+    None
+}
+
+struct PrintCollector<'a, 'b> {
+    type_checker: TypeChecker<'a, 'b>,
+    events: Vec<EventSchema>,
+    /// Prints of the function being traversed, the functions are visited after their body
+    pending: Vec<(Option<String>, TypeSignature)>,
+}
+
+impl PrintCollector<'_, '_> {
+    fn add_function(&mut self, name: Option<&ClarityName>) {
+        for (topic, type_signature) in std::mem::take(&mut self.pending) {
+            let position = self
+                .events
+                .iter()
+                .position(|e| e.topic == topic && e.type_signature == type_signature);
+            let event = match position {
+                Some(position) => &mut self.events[position],
+                None => {
+                    let mut schema = type_signature_to_json_schema(&type_signature);
+                    if let (Some(topic), Some(field)) = (&topic, get_topic_field(&type_signature)) {
+                        schema["properties"][field]["const"] = json!(topic);
+                    }
+                    self.events.push(EventSchema {
+                        topic,
+                        clarity_type: type_signature.to_string(),
+                        functions: vec![],
+                        schema,
+                        type_signature,
+                    });
+                    self.events.last_mut().unwrap()
+                }
+            };
+            if let Some(name) = name {
+                if !event.functions.contains(&name.to_string()) {
+                    event.functions.push(name.to_string());
+                }
+            }
+        }
+    }
+}
+
+impl ASTVisitor<'_> for PrintCollector<'_, '_> {
+    fn visit_define_public(
+        &mut self,
+        _expr: &SymbolicExpression,
+        name: &ClarityName,
+        _parameters: Option<Vec<TypedVar<'_>>>,
+        _body: &SymbolicExpression,
+    ) -> bool {
+        self.add_function(Some(name));
+        true
+    }
+
+    fn visit_define_private(
+        &mut self,
+        _expr: &SymbolicExpression,
+        name: &ClarityName,
+        _parameters: Option<Vec<TypedVar<'_>>>,
+        _body: &SymbolicExpression,
+    ) -> bool {
+        self.add_function(Some(name));
+        true
+    }
+
+    fn visit_define_read_only(
+        &mut self,
+        _expr: &SymbolicExpression,
+        name: &ClarityName,
+        _parameters: Option<Vec<TypedVar<'_>>>,
+        _body: &SymbolicExpression,
+    ) -> bool {
+        self.add_function(Some(name));
+        true
+    }
+
+    fn visit_print(&mut self, expr: &SymbolicExpression, value: &SymbolicExpression) -> bool {
+        if let Some(type_signature) = self.type_checker.type_map.get_type_expected(expr) {
+            let mut type_signature = type_signature.clone();
+            let topic = get_event_topic(value);
+            // the topic is a literal, its length doesn't matter to the consumers
+            if let (Some(_), TypeSignature::TupleType(_)) = (&topic, &type_signature) {
+                type_signature = normalize_topic_type(type_signature);
+            }
+            self.pending.push((topic, type_signature));
+        }
+        true
+    }
+}
+
+/// Widens the type of the topic field, so that the prints of a topic with the same fields
+/// share a type
+fn normalize_topic_type(type_signature: TypeSignature) -> TypeSignature {
+    let TypeSignature::TupleType(tuple) = &type_signature else {
+        return type_signature;
+    };
+    let fields = tuple
+        .get_type_map()
+        .iter()
+        .map(|(name, field_type)| {
+            if Some(name.as_str()) == get_topic_field(&type_signature) {
+                (
+                    name.clone(),
+                    TypeSignature::max_string_ascii().unwrap_or(field_type.clone()),
+                )
+            } else {
+                (name.clone(), field_type.clone())
+            }
+        })
+        .collect::<Vec<_>>();
+    match TupleTypeSignature::try_from(fields) {
+        Ok(tuple) => TypeSignature::TupleType(tuple),
+        Err(_) => type_signature,
+    }
+}
+
+/// Events printed by a contract. The prints at the top level of the contract, executed when
+/// it's deployed, are listed without function.
+pub fn extract_events_schema(
+    contract_analysis: &mut ContractAnalysis,
+    mut type_checker: TypeChecker,
+) -> ContractEventsSchema {
+    let _ = type_checker.run(contract_analysis);
+    let mut collector = PrintCollector {
+        type_checker,
+        events: vec![],
+        pending: vec![],
+    };
+    for expression in contract_analysis.expressions.iter() {
+        traverse(&mut collector, std::slice::from_ref(expression));
+        collector.add_function(None);
+    }
+    ContractEventsSchema {
+        contract_id: contract_analysis.contract_identifier.to_string(),
+        events: collector.events,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clarity_repl::clarity::vm::types::BufferLength;
+
+    use super::*;
+
+    #[test]
+    fn test_tuple_json_schema() {
+        let tuple = TupleTypeSignature::try_from(vec![
+            ("amount".into(), TypeSignature::UIntType),
+            (
+                "memo".into(),
+                TypeSignature::OptionalType(Box::new(TypeSignature::SequenceType(
+                    SequenceSubtype::BufferType(BufferLength::try_from(34u32).unwrap()),
+                ))),
+            ),
+        ])
+        .unwrap();
+        let schema = type_signature_to_json_schema(&TypeSignature::TupleType(tuple));
+        assert_eq!(schema["type"], "object");
+        assert_eq!(schema["required"], json!(["amount", "memo"]));
+        assert_eq!(schema["properties"]["amount"]["type"], "string");
+        assert_eq!(
+            schema["properties"]["memo"]["anyOf"][0]["maxLength"],
+            json!(68)
+        );
+        assert_eq!(schema["properties"]["memo"]["anyOf"][1]["type"], "null");
+    }
+}
@@ -1,6 +1,7 @@
 use crate::lsp_types::MessageType;
-use crate::state::{build_state, EditorState, ProtocolState};
+use crate::state::{build_state_with_cancellation, EditorState, ProtocolState};
 use crate::utils::get_contract_location;
+use clarinet_deployments::cancellation::is_cancelled_error;
 use clarinet_files::{FileAccessor, FileLocation, ProjectManifest};
 use clarity_repl::clarity::diagnostic::Diagnostic;
 use clarity_repl::repl::ContractDeployer;
@@ -56,6 +57,11 @@ pub enum LspNotification {
     ContractSaved(FileLocation),
     ContractChanged(FileLocation, String),
     ContractClosed(FileLocation),
+    Initialized,
+    WorkspaceFoldersChanged {
+        added: Vec<FileLocation>,
+        removed: Vec<FileLocation>,
+    },
 }
 
 #[derive(Debug, Default, PartialEq, Deserialize, Serialize)]
@@ -66,6 +72,10 @@ pub struct LspNotificationResponse {
 
 impl LspNotificationResponse {
     pub fn error(message: &str) -> LspNotificationResponse {
+        // a cancelled build was superseded by another one, which reports the diagnostics
+        if is_cancelled_error(message) {
+            return LspNotificationResponse::default();
+        }
         LspNotificationResponse {
             aggregated_diagnostics: vec![],
             notification: Some((MessageType::ERROR, format!("Internal error: {}", message))),
@@ -73,6 +83,77 @@ impl LspNotificationResponse {
     }
 }
 
+/// Builds the state of a project, the editor can cancel the build through the editor state
+async fn build_protocol_state(
+    manifest_location: &FileLocation,
+    protocol_state: &mut ProtocolState,
+    editor_state: &EditorStateInput,
+    file_accessor: Option<&dyn FileAccessor>,
+) -> Result<(), String> {
+    let cancellation =
+        editor_state.try_read(|es| es.build_cancellation.start(manifest_location))?;
+    build_state_with_cancellation(
+        manifest_location,
+        protocol_state,
+        file_accessor,
+        &cancellation,
+    )
+    .await
+}
+
+/// Builds the state of the projects found at the root of the workspace folders.
+/// Folders without a Clarinet.toml are skipped, their projects get loaded when one of
+/// their files is opened.
+async fn load_workspace_folders(
+    folders: Vec<FileLocation>,
+    editor_state: &mut EditorStateInput,
+    file_accessor: Option<&dyn FileAccessor>,
+) -> Result<LspNotificationResponse, String> {
+    let mut errors = vec![];
+    for folder in folders.into_iter() {
+        let mut manifest_location = folder;
+        manifest_location.append_path("Clarinet.toml")?;
+        let manifest_exists = match file_accessor {
+            None => manifest_location.exists(),
+            Some(file_accessor) => file_accessor
+                .file_exists(manifest_location.to_string())
+                .await
+                .unwrap_or(false),
+        };
+        if !manifest_exists
+            || editor_state.try_read(|es| es.protocols.contains_key(&manifest_location))?
+        {
+            continue;
+        }
+
+        let mut protocol_state = ProtocolState::new();
+        match build_protocol_state(
+            &manifest_location,
+            &mut protocol_state,
+            editor_state,
+            file_accessor,
+        )
+        .await
+        {
+            Ok(_) => {
+                editor_state.try_write(|es| es.index_protocol(manifest_location, protocol_state))?
+            }
+            Err(e) if is_cancelled_error(&e) => {}
+            Err(e) => errors.push(e),
+        }
+    }
+
+    if !errors.is_empty() {
+        return Ok(LspNotificationResponse::error(&errors.join("\n")));
+    }
+    let (aggregated_diagnostics, notification) =
+        editor_state.try_read(|es| es.get_aggregated_diagnostics())?;
+    Ok(LspNotificationResponse {
+        aggregated_diagnostics,
+        notification,
+    })
+}
+
 pub async fn process_notification(
     command: LspNotification,
     editor_state: &mut EditorStateInput,
@@ -87,7 +168,14 @@ pub async fn process_notification(
 
             // With this manifest_location, let's initialize our state.
             let mut protocol_state = ProtocolState::new();
-            match build_state(&manifest_location, &mut protocol_state, file_accessor).await {
+            match build_protocol_state(
+                &manifest_location,
+                &mut protocol_state,
+                editor_state,
+                file_accessor,
+            )
+            .await
+            {
                 Ok(_) => {
                     editor_state
                         .try_write(|es| es.index_protocol(manifest_location, protocol_state))?;
@@ -105,7 +193,14 @@ pub async fn process_notification(
         LspNotification::ManifestSaved(manifest_location) => {
             // We will rebuild the entire state, without to try any optimizations for now
             let mut protocol_state = ProtocolState::new();
-            match build_state(&manifest_location, &mut protocol_state, file_accessor).await {
+            match build_protocol_state(
+                &manifest_location,
+                &mut protocol_state,
+                editor_state,
+                file_accessor,
+            )
+            .await
+            {
                 Ok(_) => {
                     editor_state
                         .try_write(|es| es.index_protocol(manifest_location, protocol_state))?;
@@ -186,7 +281,14 @@ pub async fn process_notification(
             }
 
             let mut protocol_state = ProtocolState::new();
-            match build_state(&manifest_location, &mut protocol_state, file_accessor).await {
+            match build_protocol_state(
+                &manifest_location,
+                &mut protocol_state,
+                editor_state,
+                file_accessor,
+            )
+            .await
+            {
                 Ok(_) => {
                     editor_state
                         .try_write(|es| es.index_protocol(manifest_location, protocol_state))?;
@@ -215,7 +317,14 @@ pub async fn process_notification(
 
             // TODO(): introduce partial analysis #604
             let mut protocol_state = ProtocolState::new();
-            match build_state(&manifest_location, &mut protocol_state, file_accessor).await {
+            match build_protocol_state(
+                &manifest_location,
+                &mut protocol_state,
+                editor_state,
+                file_accessor,
+            )
+            .await
+            {
                 Ok(_) => {
                     editor_state.try_write(|es| {
                         es.index_protocol(manifest_location, protocol_state);
@@ -248,6 +357,35 @@ pub async fn process_notification(
             editor_state.try_write(|es| es.active_contracts.remove_entry(&contract_location))?;
             Ok(LspNotificationResponse::default())
         }
+
+        LspNotification::Initialized => {
+            let folders =
+                editor_state.try_read(|es| es.workspace_folders.keys().cloned().collect())?;
+            load_workspace_folders(folders, editor_state, file_accessor).await
+        }
+
+        LspNotification::WorkspaceFoldersChanged { added, removed } => {
+            let mut cleared_contracts = vec![];
+            editor_state.try_write(|es| {
+                for folder in removed.iter() {
+                    cleared_contracts.append(&mut es.remove_workspace_folder(folder));
+                }
+                for folder in added.iter() {
+                    if !es.workspace_folders.contains_key(folder) {
+                        es.add_workspace_folder(folder.clone(), es.settings.clone());
+                    }
+                }
+            })?;
+
+            let mut response = load_workspace_folders(added, editor_state, file_accessor).await?;
+            // publishing empty diagnostics clears the ones of the removed contracts
+            response.aggregated_diagnostics.extend(
+                cleared_contracts
+                    .into_iter()
+                    .map(|contract_location| (contract_location, vec![])),
+            );
+            Ok(response)
+        }
     }
 }
 
@@ -370,12 +508,27 @@ pub fn process_mutating_request(
 ) -> Result<LspRequestResponse, String> {
     match command {
         LspRequest::Initialize(params) => {
-            let initialization_options = params
-                .initialization_options
-                .and_then(|o| serde_json::from_value(o).ok())
-                .unwrap_or(InitializationOptions::default());
+            let (initialization_options, workspace_folders_settings) =
+                InitializationOptions::from_value(params.initialization_options);
+
+            let mut workspace_folders = vec![];
+            for folder in params.workspace_folders.unwrap_or_default() {
+                if let Some(location) = FileLocation::try_parse(folder.uri.as_str(), None) {
+                    let settings = workspace_folders_settings
+                        .iter()
+                        .find(|(uri, _)| *uri == folder.uri)
+                        .map(|(_, settings)| settings.clone())
+                        .unwrap_or(initialization_options.clone());
+                    workspace_folders.push((location, settings));
+                }
+            }
 
-            match editor_state.try_write(|es| es.settings = initialization_options.clone()) {
+            match editor_state.try_write(|es| {
+                es.settings = initialization_options.clone();
+                for (location, settings) in workspace_folders.into_iter() {
+                    es.add_workspace_folder(location, settings);
+                }
+            }) {
                 Ok(_) => Ok(LspRequestResponse::Initialize(Box::new(InitializeResult {
                     server_info: None,
                     capabilities: get_capabilities(&initialization_options),
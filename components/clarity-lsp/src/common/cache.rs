@@ -0,0 +1,120 @@
+//! Analysis of the projects persisted between the sessions of the language server, so that
+//! restarting the editor doesn't trigger a full analysis of the projects that didn't change.
+//! The cache of a project is keyed by a hash of its manifests and of its contracts sources.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use clarinet_files::{FileLocation, ProjectManifest, StacksNetwork};
+use clarity_repl::clarity::util::hash::Sha256Sum;
+use serde::{Deserialize, Serialize};
+
+use super::state::{ContractState, ProtocolState};
+
+/// Written in the cache directory of the project
+pub const ANALYSIS_CACHE_FILE: &str = "lsp-analysis.json";
+
+static ANALYSIS_CACHE_ENABLED: AtomicBool = AtomicBool::new(false);
+
+pub fn set_analysis_cache_enabled(enabled: bool) {
+    ANALYSIS_CACHE_ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub fn is_analysis_cache_enabled() -> bool {
+    ANALYSIS_CACHE_ENABLED.load(Ordering::Relaxed)
+}
+
+#[derive(Serialize, Deserialize)]
+struct CachedAnalysis {
+    /// Caches written by other versions of clarinet are discarded
+    version: String,
+    key: String,
+    contracts: Vec<ContractState>,
+}
+
+/// Hash of the inputs of the analysis of a project: Clarinet.toml, settings/Devnet.toml
+/// (which defines the simnet accounts) and the sources of the contracts
+pub fn get_analysis_key(manifest: &ProjectManifest) -> Result<String, String> {
+    let mut content = manifest.location.read_content()?;
+    if let Ok(network_manifest) = manifest
+        .location
+        .get_network_manifest_location(&StacksNetwork::Simnet)
+        .and_then(|location| location.read_content())
+    {
+        content.extend(network_manifest);
+    }
+
+    let mut contracts_locations: Vec<&FileLocation> = manifest.contracts_settings.keys().collect();
+    contracts_locations.sort_by_key(|location| location.to_string());
+    for location in contracts_locations {
+        content.extend(location.to_string().as_bytes());
+        content.extend(location.read_content()?);
+    }
+    Ok(Sha256Sum::from_data(&content).to_hex())
+}
+
+fn get_cache_location(manifest: &ProjectManifest) -> Option<FileLocation> {
+    let mut location = manifest.project.cache_location.clone();
+    if !matches!(location, FileLocation::FileSystem { .. }) {
+        return None;
+    }
+    location.append_path(ANALYSIS_CACHE_FILE).ok()?;
+    Some(location)
+}
+
+/// State of the contracts of a project, if it was persisted with the same key
+pub fn load_cached_analysis(manifest: &ProjectManifest, key: &str) -> Option<ProtocolState> {
+    let location = get_cache_location(manifest)?;
+    if !location.exists() {
+        return None;
+    }
+    let content = location.read_content().ok()?;
+    let cached: CachedAnalysis = serde_json::from_slice(&content).ok()?;
+    if cached.version != env!("CARGO_PKG_VERSION") || cached.key != key {
+        return None;
+    }
+    Some(ProtocolState::from_contracts(cached.contracts))
+}
+
+pub fn save_analysis(manifest: &ProjectManifest, key: &str, protocol_state: &ProtocolState) {
+    let Some(location) = get_cache_location(manifest) else {
+        return;
+    };
+    let cached = CachedAnalysis {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        key: key.to_string(),
+        contracts: protocol_state.get_contracts().cloned().collect(),
+    };
+    // a cache that can't be written only costs an analysis on the next start
+    if let Ok(content) = serde_json::to_vec(&cached) {
+        let _ = location.write_content(&content);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_analysis_key_tracks_sources() {
+        let mut project_path = std::env::temp_dir();
+        project_path.push(format!("clarity-lsp-cache-{}", std::process::id()));
+        let contract_path = project_path.join("contracts").join("counter.clar");
+        std::fs::create_dir_all(contract_path.parent().unwrap()).unwrap();
+        std::fs::write(
+            project_path.join("Clarinet.toml"),
+            "[project]\nname = \"counter\"\n\n[contracts.counter]\npath = \"contracts/counter.clar\"\n",
+        )
+        .unwrap();
+        std::fs::write(&contract_path, "(define-data-var count uint u0)").unwrap();
+
+        let manifest_location = FileLocation::from_path(project_path.join("Clarinet.toml"));
+        let manifest = ProjectManifest::from_location(&manifest_location).unwrap();
+        let key = get_analysis_key(&manifest).unwrap();
+        assert_eq!(get_analysis_key(&manifest).unwrap(), key);
+
+        std::fs::write(&contract_path, "(define-data-var count uint u1)").unwrap();
+        assert_ne!(get_analysis_key(&manifest).unwrap(), key);
+
+        let _ = std::fs::remove_dir_all(project_path);
+    }
+}
@@ -1,4 +1,6 @@
 mod requests;
 
 pub mod backend;
+#[cfg(feature = "cli")]
+pub mod cache;
 pub mod state;
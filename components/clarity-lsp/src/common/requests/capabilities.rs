@@ -1,9 +1,11 @@
 use lsp_types::{
     CompletionOptions, HoverProviderCapability, ServerCapabilities, SignatureHelpOptions,
     TextDocumentSyncCapability, TextDocumentSyncKind, TextDocumentSyncOptions,
-    TextDocumentSyncSaveOptions,
+    TextDocumentSyncSaveOptions, Url, WorkspaceFoldersServerCapabilities,
+    WorkspaceServerCapabilities,
 };
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
 #[derive(Debug, Clone, Deserialize, Serialize, Default)]
 #[serde(rename_all = "camelCase", default)]
@@ -29,6 +31,37 @@ impl InitializationOptions {
             signature_help: true,
         }
     }
+
+    /// Reads the options sent by the client. The settings of each workspace folder, in
+    /// `workspaceFolders: [{ uri, ...options }]`, override the global ones.
+    pub fn from_value(value: Option<Value>) -> (Self, Vec<(Url, Self)>) {
+        let Some(Value::Object(options)) = value else {
+            return (Self::default(), vec![]);
+        };
+        let global_settings =
+            serde_json::from_value(Value::Object(options.clone())).unwrap_or(Self::default());
+
+        let mut workspace_folders_settings = vec![];
+        let folders = options.get("workspaceFolders").and_then(|f| f.as_array());
+        for folder in folders.into_iter().flatten() {
+            let Some(folder) = folder.as_object() else {
+                continue;
+            };
+            let Some(uri) = folder
+                .get("uri")
+                .and_then(|uri| uri.as_str())
+                .and_then(|uri| Url::parse(uri).ok())
+            else {
+                continue;
+            };
+            let mut folder_options = options.clone();
+            folder_options.extend(folder.clone());
+            if let Ok(settings) = serde_json::from_value(Value::Object(folder_options)) {
+                workspace_folders_settings.push((uri, settings));
+            }
+        }
+        (global_settings, workspace_folders_settings)
+    }
 }
 
 pub fn get_capabilities(initialization_options: &InitializationOptions) -> ServerCapabilities {
@@ -66,6 +99,13 @@ pub fn get_capabilities(initialization_options: &InitializationOptions) -> Serve
             }),
             false => None,
         },
+        workspace: Some(WorkspaceServerCapabilities {
+            workspace_folders: Some(WorkspaceFoldersServerCapabilities {
+                supported: Some(true),
+                change_notifications: Some(lsp_types::OneOf::Left(true)),
+            }),
+            file_operations: None,
+        }),
         ..ServerCapabilities::default()
     }
 }
@@ -1,25 +1,33 @@
-use std::{collections::HashMap, vec};
+use std::{
+    collections::{BTreeMap, HashMap},
+    vec,
+};
 
 use clarity_repl::{
     analysis::ast_visitor::{traverse, ASTVisitor, TypedVar},
+    analysis::natives::{get_native_keywords, is_native_available, NativeKind},
     clarity::{
         analysis::ContractAnalysis,
         docs::{make_api_reference, make_define_reference, make_keyword_reference},
         functions::{define::DefineFunctions, NativeFunctions},
         variables::NativeVariables,
-        vm::types::{BlockInfoProperty, FunctionType, TypeSignature},
-        ClarityName, ClarityVersion, SymbolicExpression,
+        vm::representations::TraitDefinition,
+        vm::types::{FunctionSignature, FunctionType, PrincipalData, TypeSignature, Value},
+        ClarityName, ClarityVersion, SymbolicExpression, SymbolicExpressionType,
     },
     repl::DEFAULT_EPOCH,
 };
 use lazy_static::lazy_static;
 use lsp_types::{
     CompletionItem, CompletionItemKind, Documentation, InsertTextFormat, MarkupContent, MarkupKind,
-    Position,
+    ParameterInformation, ParameterLabel, Position, SignatureInformation,
 };
 use regex::Regex;
+use serde::{Deserialize, Serialize};
 
-use super::helpers::{get_function_at_position, is_position_within_span};
+use super::helpers::{
+    get_function_at_position, get_function_call_at_position, is_position_within_span,
+};
 
 lazy_static! {
     static ref COMPLETION_ITEMS_CLARITY_1: Vec<CompletionItem> =
@@ -76,6 +84,133 @@ lazy_static! {
         build_fold_valid_cb_completion_items(ClarityVersion::Clarity3);
 }
 
+/// A public or read-only function of a contract, or a function of a trait
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ContractFunction {
+    pub name: String,
+    /// Name and type of the arguments, trait functions arguments don't have names
+    pub args: Vec<(Option<String>, String)>,
+    pub returns: String,
+}
+
+impl ContractFunction {
+    fn get_args_labels(&self) -> Vec<String> {
+        self.args
+            .iter()
+            .map(|(name, signature)| match name {
+                Some(name) => format!("({} {})", name, signature),
+                None => signature.to_string(),
+            })
+            .collect()
+    }
+
+    fn get_snippet(&self) -> String {
+        let snippet_args: Vec<String> = self
+            .args
+            .iter()
+            .enumerate()
+            .map(|(i, (name, signature))| match name {
+                Some(name) => format!("${{{}:{}:{}}}", i + 1, name, signature),
+                None => format!("${{{}:{}}}", i + 1, signature),
+            })
+            .collect();
+        match snippet_args.len() {
+            0 => self.name.clone(),
+            _ => format!("{} {}", self.name, snippet_args.join(" ")),
+        }
+    }
+
+    pub fn to_completion_item(&self, contract_name: &str) -> CompletionItem {
+        let doc_args = self.args.iter().map(|(name, signature)| match name {
+            Some(name) => format!("{} `{}`", name, signature),
+            None => format!("`{}`", signature),
+        });
+        let documentation = MarkupContent {
+            kind: MarkupKind::Markdown,
+            value: [format!("**{}**", self.name)]
+                .into_iter()
+                .chain(doc_args)
+                .chain([format!("returns `{}`", self.returns)])
+                .collect::<Vec<String>>()
+                .join("\n\n"),
+        };
+        CompletionItem {
+            label: self.name.clone(),
+            detail: Some(contract_name.to_string()),
+            documentation: Some(Documentation::MarkupContent(documentation)),
+            kind: Some(CompletionItemKind::METHOD),
+            insert_text: Some(self.get_snippet()),
+            insert_text_format: Some(InsertTextFormat::SNIPPET),
+            ..Default::default()
+        }
+    }
+
+    pub fn to_signature_information(&self, active_parameter: Option<u32>) -> SignatureInformation {
+        let args = self.get_args_labels();
+        let label = match args.len() {
+            0 => format!("({}) -> {}", self.name, self.returns),
+            _ => format!("({} {}) -> {}", self.name, args.join(" "), self.returns),
+        };
+        SignatureInformation {
+            label,
+            documentation: None,
+            parameters: Some(
+                args.into_iter()
+                    .map(|label| ParameterInformation {
+                        label: ParameterLabel::Simple(label),
+                        documentation: None,
+                    })
+                    .collect(),
+            ),
+            active_parameter,
+        }
+    }
+}
+
+/// What the first argument of a `contract-call?` refers to
+#[derive(Clone, Debug, PartialEq)]
+pub enum ContractCallTarget {
+    Contract(String),
+    /// Trait defined in the contract itself
+    LocalTrait(String),
+    ImportedTrait {
+        contract_name: String,
+        trait_name: String,
+    },
+}
+
+impl ContractCallTarget {
+    fn from_trait_definition(trait_definition: &TraitDefinition) -> Self {
+        match trait_definition {
+            TraitDefinition::Defined(identifier) => {
+                ContractCallTarget::LocalTrait(identifier.name.to_string())
+            }
+            TraitDefinition::Imported(identifier) => ContractCallTarget::ImportedTrait {
+                contract_name: identifier.contract_identifier.name.to_string(),
+                trait_name: identifier.name.to_string(),
+            },
+        }
+    }
+}
+
+/// Returns the target of the `contract-call?` at the position, the called function if it's
+/// already written, and the index of the argument at the position
+pub fn get_contract_call_at_position<'a>(
+    position: &Position,
+    expressions: &'a [SymbolicExpression],
+) -> Option<(&'a SymbolicExpression, Option<&'a ClarityName>, u32)> {
+    let (call, param) = get_function_call_at_position(position, expressions)?;
+    let (function_name, args) = call.split_first()?;
+    if function_name.match_atom()?.as_str() != "contract-call?" {
+        return None;
+    }
+    Some((
+        args.first()?,
+        args.get(1).and_then(|f| f.match_atom()),
+        param?,
+    ))
+}
+
 #[derive(Clone, Debug, Default)]
 pub struct ContractDefinedData {
     position: Position,
@@ -86,6 +221,11 @@ pub struct ContractDefinedData {
     pub fts: Vec<String>,
     pub nfts: Vec<String>,
     pub functions_completion_items: Vec<CompletionItem>,
+    /// Key and value types of the maps
+    pub maps_types: HashMap<String, (TypeSignature, TypeSignature)>,
+    tuple_fields: Vec<(String, String)>,
+    pub traits: HashMap<String, Vec<ContractFunction>>,
+    trait_params: HashMap<String, ContractCallTarget>,
 }
 
 impl ContractDefinedData {
@@ -109,10 +249,22 @@ impl ContractDefinedData {
     ) {
         let mut completion_args: Vec<String> = vec![];
         for (i, typed_var) in parameters.iter().enumerate() {
+            if let SymbolicExpressionType::TraitReference(_, trait_definition) =
+                &typed_var.type_expr.expr
+            {
+                if is_position_within_span(&self.position, &expr.span, 0) {
+                    self.trait_params.insert(
+                        typed_var.name.to_string(),
+                        ContractCallTarget::from_trait_definition(trait_definition),
+                    );
+                }
+            }
+
             if let Ok(signature) =
                 TypeSignature::parse_type_repr(DEFAULT_EPOCH, typed_var.type_expr, &mut ())
             {
                 completion_args.push(format!("${{{}:{}:{}}}", i + 1, typed_var.name, signature));
+                self.add_tuple_fields(&signature);
 
                 if is_position_within_span(&self.position, &expr.span, 0) {
                     self.locals
@@ -135,6 +287,84 @@ impl ContractDefinedData {
         });
     }
 
+    fn add_tuple_fields(&mut self, signature: &TypeSignature) {
+        match signature {
+            TypeSignature::TupleType(tuple) => {
+                for (name, field_type) in tuple.get_type_map() {
+                    if !self
+                        .tuple_fields
+                        .iter()
+                        .any(|(field, _)| field == name.as_str())
+                    {
+                        self.tuple_fields
+                            .push((name.to_string(), field_type.to_string()));
+                    }
+                    self.add_tuple_fields(field_type);
+                }
+            }
+            TypeSignature::OptionalType(inner) => self.add_tuple_fields(inner),
+            _ => {}
+        }
+    }
+
+    /// Returns a template of the key tuple of a map, or of its value tuple
+    pub fn get_map_entry_template(&self, map_name: &str, value: bool) -> Option<CompletionItem> {
+        let (key_type, value_type) = self.maps_types.get(map_name)?;
+        let TypeSignature::TupleType(tuple) = (if value { value_type } else { key_type }) else {
+            return None;
+        };
+        let fields = tuple.get_type_map();
+        let label = fields
+            .iter()
+            .map(|(name, field_type)| format!("{}: {}", name, field_type))
+            .collect::<Vec<String>>()
+            .join(", ");
+        let snippet = fields
+            .iter()
+            .enumerate()
+            .map(|(i, (name, field_type))| format!("{}: ${{{}:{}}}", name, i + 1, field_type))
+            .collect::<Vec<String>>()
+            .join(", ");
+        Some(CompletionItem {
+            label: format!("{{ {} }}", label),
+            kind: Some(CompletionItemKind::STRUCT),
+            detail: Some(map_name.to_string()),
+            insert_text: Some(format!("{{ {} }}", snippet)),
+            insert_text_format: Some(InsertTextFormat::SNIPPET),
+            ..Default::default()
+        })
+    }
+
+    pub fn get_tuple_fields_completion_items(&self) -> Vec<CompletionItem> {
+        self.tuple_fields
+            .iter()
+            .map(|(name, field_type)| CompletionItem {
+                label: name.to_string(),
+                kind: Some(CompletionItemKind::FIELD),
+                detail: Some(field_type.to_string()),
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    pub fn get_contract_call_target(
+        &self,
+        target: &SymbolicExpression,
+    ) -> Option<ContractCallTarget> {
+        match &target.expr {
+            SymbolicExpressionType::LiteralValue(Value::Principal(PrincipalData::Contract(
+                contract_identifier,
+            ))) => Some(ContractCallTarget::Contract(
+                contract_identifier.name.to_string(),
+            )),
+            SymbolicExpressionType::LiteralValue(Value::CallableContract(callable)) => Some(
+                ContractCallTarget::Contract(callable.contract_identifier.name.to_string()),
+            ),
+            SymbolicExpressionType::Atom(name) => self.trait_params.get(name.as_str()).cloned(),
+            _ => None,
+        }
+    }
+
     pub fn populate_snippet_with_options(
         &self,
         version: &ClarityVersion,
@@ -203,10 +433,13 @@ impl<'a> ASTVisitor<'a> for ContractDefinedData {
         &mut self,
         _expr: &'a SymbolicExpression,
         name: &'a ClarityName,
-        _data_type: &'a SymbolicExpression,
+        data_type: &'a SymbolicExpression,
         _initial: &'a SymbolicExpression,
     ) -> bool {
         self.vars.push(name.to_string());
+        if let Ok(signature) = TypeSignature::parse_type_repr(DEFAULT_EPOCH, data_type, &mut ()) {
+            self.add_tuple_fields(&signature);
+        }
         true
     }
 
@@ -214,10 +447,37 @@ impl<'a> ASTVisitor<'a> for ContractDefinedData {
         &mut self,
         _expr: &'a SymbolicExpression,
         name: &'a ClarityName,
-        _key_type: &'a SymbolicExpression,
-        _value_type: &'a SymbolicExpression,
+        key_type: &'a SymbolicExpression,
+        value_type: &'a SymbolicExpression,
     ) -> bool {
         self.maps.push(name.to_string());
+        if let (Ok(key_type), Ok(value_type)) = (
+            TypeSignature::parse_type_repr(DEFAULT_EPOCH, key_type, &mut ()),
+            TypeSignature::parse_type_repr(DEFAULT_EPOCH, value_type, &mut ()),
+        ) {
+            self.add_tuple_fields(&key_type);
+            self.add_tuple_fields(&value_type);
+            self.maps_types
+                .insert(name.to_string(), (key_type, value_type));
+        }
+        true
+    }
+
+    fn visit_define_trait(
+        &mut self,
+        _expr: &'a SymbolicExpression,
+        name: &'a ClarityName,
+        functions: &'a [SymbolicExpression],
+    ) -> bool {
+        if let Ok(trait_definition) = TypeSignature::parse_trait_type_repr(
+            functions,
+            &mut (),
+            DEFAULT_EPOCH,
+            ClarityVersion::default_for_epoch(DEFAULT_EPOCH),
+        ) {
+            self.traits
+                .insert(name.to_string(), get_trait_functions(&trait_definition));
+        }
         true
     }
 
@@ -301,6 +561,51 @@ fn build_contract_calls_args(signature: &FunctionType) -> (Vec<String>, Vec<Stri
     (snippet_args, doc_args)
 }
 
+pub fn get_contract_functions(analysis: &ContractAnalysis) -> Vec<ContractFunction> {
+    analysis
+        .public_function_types
+        .iter()
+        .chain(analysis.read_only_function_types.iter())
+        .filter_map(|(name, signature)| match signature {
+            FunctionType::Fixed(function) => Some(ContractFunction {
+                name: name.to_string(),
+                args: function
+                    .args
+                    .iter()
+                    .map(|arg| (Some(arg.name.to_string()), arg.signature.to_string()))
+                    .collect(),
+                returns: function.returns.to_string(),
+            }),
+            _ => None,
+        })
+        .collect()
+}
+
+fn get_trait_functions(
+    trait_definition: &BTreeMap<ClarityName, FunctionSignature>,
+) -> Vec<ContractFunction> {
+    trait_definition
+        .iter()
+        .map(|(name, signature)| ContractFunction {
+            name: name.to_string(),
+            args: signature
+                .args
+                .iter()
+                .map(|arg| (None, arg.to_string()))
+                .collect(),
+            returns: signature.returns.to_string(),
+        })
+        .collect()
+}
+
+pub fn get_contract_traits(analysis: &ContractAnalysis) -> HashMap<String, Vec<ContractFunction>> {
+    analysis
+        .defined_traits
+        .iter()
+        .map(|(name, trait_definition)| (name.to_string(), get_trait_functions(trait_definition)))
+        .collect()
+}
+
 pub fn get_contract_calls(analysis: &ContractAnalysis) -> Vec<CompletionItem> {
     let mut inter_contract = vec![];
     for (name, signature) in analysis
@@ -368,6 +673,31 @@ pub fn build_completion_item_list(
                 .collect();
         }
 
+        // - for map-* methods, return a template of the key tuple, or of the value tuple
+        if MAP_FUNCTIONS.contains(&function_name.to_string()) && matches!(param, Some(1 | 2)) {
+            let with_value = [NativeFunctions::InsertEntry, NativeFunctions::SetEntry]
+                .iter()
+                .any(|f| f.to_string() == function_name.as_str());
+            let map_name = get_function_call_at_position(position, expressions)
+                .and_then(|(call, _)| call.get(1)?.match_atom());
+            if param == Some(1) || with_value {
+                if let Some(template) = map_name.and_then(|name| {
+                    active_contract_defined_data
+                        .get_map_entry_template(name.as_str(), param == Some(2))
+                }) {
+                    return vec![template];
+                }
+            }
+        }
+
+        // - for get, return the fields of the tuples known in the contract
+        if function_name.as_str() == NativeFunctions::TupleGet.to_string() && param == Some(0) {
+            let fields = active_contract_defined_data.get_tuple_fields_completion_items();
+            if !fields.is_empty() {
+                return fields;
+            }
+        }
+
         // - for iterator methods (filter, fold, map) return the list of available and valid functions
         if ITERATOR_FUNCTIONS.contains(&function_name.to_string()) && param == Some(0) {
             let mut completion_items: Vec<CompletionItem> = vec![];
@@ -512,9 +842,7 @@ pub fn build_default_native_keywords_list(version: ClarityVersion) -> Vec<Comple
         .iter()
         .filter_map(|func| {
             let mut api = make_api_reference(func);
-            if version < api.min_version
-                || version > api.max_version.unwrap_or(ClarityVersion::latest())
-            {
+            if !is_native_available(&api.name, NativeKind::Function, version) {
                 return None;
             }
             if clarity2_aliased_functions.contains(func) {
@@ -546,9 +874,7 @@ pub fn build_default_native_keywords_list(version: ClarityVersion) -> Vec<Comple
         .iter()
         .filter_map(|func| {
             let api = make_define_reference(func);
-            if version < api.min_version
-                || version > api.max_version.unwrap_or(ClarityVersion::latest())
-            {
+            if !is_native_available(&api.name, NativeKind::Define, version) {
                 return None;
             }
             Some(CompletionItem {
@@ -571,9 +897,7 @@ pub fn build_default_native_keywords_list(version: ClarityVersion) -> Vec<Comple
         .iter()
         .filter_map(|var| {
             if let Some(api) = make_keyword_reference(var) {
-                if version < api.min_version
-                    || version > api.max_version.unwrap_or(ClarityVersion::latest())
-                {
+                if !is_native_available(api.name, NativeKind::Variable, version) {
                     return None;
                 }
                 Some(CompletionItem {
@@ -594,19 +918,15 @@ pub fn build_default_native_keywords_list(version: ClarityVersion) -> Vec<Comple
         })
         .collect();
 
-    let block_properties: Vec<CompletionItem> = BlockInfoProperty::ALL
+    let block_properties: Vec<CompletionItem> = get_native_keywords()
         .iter()
-        .filter_map(|var| {
-            if var.get_min_version() > version {
-                return None;
-            }
-            Some(CompletionItem {
-                label: var.to_string(),
-                kind: Some(CompletionItemKind::FIELD),
-                insert_text: Some(var.to_string()),
-                insert_text_format: Some(InsertTextFormat::PLAIN_TEXT),
-                ..Default::default()
-            })
+        .filter(|keyword| keyword.kind == NativeKind::Property && keyword.is_available_in(version))
+        .map(|property| CompletionItem {
+            label: property.name.clone(),
+            kind: Some(CompletionItemKind::FIELD),
+            insert_text: Some(property.name.clone()),
+            insert_text_format: Some(InsertTextFormat::PLAIN_TEXT),
+            ..Default::default()
         })
         .collect();
 
@@ -831,6 +1151,103 @@ mod get_contract_global_data_tests {
         let data = get_defined_data("(define-non-fungible-token bitcoin-nft uint)");
         assert_eq!(data.nfts, ["bitcoin-nft"]);
     }
+
+    #[test]
+    fn get_map_entry_templates() {
+        let data = get_defined_data(
+            "(define-map names { owner: principal, id: uint } { name: (buff 48) })",
+        );
+        let key_template = data.get_map_entry_template("names", false).unwrap();
+        assert_eq!(key_template.label, "{ id: uint, owner: principal }");
+        assert_eq!(
+            key_template.insert_text,
+            Some("{ id: ${1:uint}, owner: ${2:principal} }".to_string())
+        );
+        let value_template = data.get_map_entry_template("names", true).unwrap();
+        assert_eq!(value_template.label, "{ name: (buff 48) }");
+
+        let fields: Vec<String> = data
+            .get_tuple_fields_completion_items()
+            .into_iter()
+            .map(|item| item.label)
+            .collect();
+        assert_eq!(fields, ["id", "owner", "name"]);
+    }
+}
+
+#[cfg(test)]
+mod contract_call_tests {
+    use clarity_repl::clarity::ast::build_ast_with_rules;
+    use clarity_repl::clarity::vm::types::QualifiedContractIdentifier;
+    use clarity_repl::clarity::{ClarityVersion, StacksEpochId};
+    use lsp_types::Position;
+
+    use super::{
+        get_contract_call_at_position, ContractCallTarget, ContractDefinedData, ContractFunction,
+    };
+
+    #[test]
+    fn get_local_trait_functions() {
+        let source = [
+            "(define-trait token-trait ((get-balance (principal) (response uint uint))))",
+            "(define-public (check (token <token-trait>))",
+            "  (contract-call? token get-balance tx-sender))",
+        ]
+        .join("\n");
+        let contract_ast = build_ast_with_rules(
+            &QualifiedContractIdentifier::transient(),
+            &source,
+            &mut (),
+            ClarityVersion::Clarity2,
+            StacksEpochId::Epoch21,
+            clarity_repl::clarity::ast::ASTRules::Typical,
+        )
+        .unwrap();
+        let position = Position {
+            line: 3,
+            character: 40,
+        };
+        let data = ContractDefinedData::new(&contract_ast.expressions, &position);
+
+        let (target, function_name, param) =
+            get_contract_call_at_position(&position, &contract_ast.expressions).unwrap();
+        assert_eq!(function_name.unwrap().as_str(), "get-balance");
+        assert_eq!(param, 2);
+        assert_eq!(
+            data.get_contract_call_target(target),
+            Some(ContractCallTarget::LocalTrait("token-trait".to_string()))
+        );
+        assert_eq!(
+            data.traits.get("token-trait").unwrap(),
+            &vec![ContractFunction {
+                name: "get-balance".to_string(),
+                args: vec![(None, "principal".to_string())],
+                returns: "(response uint uint)".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn get_contract_function_signature() {
+        let function = ContractFunction {
+            name: "transfer".to_string(),
+            args: vec![
+                (Some("amount".to_string()), "uint".to_string()),
+                (Some("recipient".to_string()), "principal".to_string()),
+            ],
+            returns: "(response bool uint)".to_string(),
+        };
+        let signature = function.to_signature_information(Some(1));
+        assert_eq!(
+            signature.label,
+            "(transfer (amount uint) (recipient principal)) -> (response bool uint)"
+        );
+        assert_eq!(signature.parameters.unwrap().len(), 2);
+        assert_eq!(
+            function.to_completion_item("token").insert_text,
+            Some("transfer ${1:amount:uint} ${2:recipient:principal}".to_string())
+        );
+    }
 }
 
 #[cfg(test)]
@@ -965,3 +1382,33 @@ mod populate_snippet_with_options_tests {
         );
     }
 }
+
+#[cfg(test)]
+mod native_keywords_tests {
+    use clarity_repl::clarity::ClarityVersion;
+
+    use super::build_default_native_keywords_list;
+
+    fn get_labels(version: ClarityVersion) -> Vec<String> {
+        build_default_native_keywords_list(version)
+            .into_iter()
+            .map(|item| item.label)
+            .collect()
+    }
+
+    #[test]
+    fn gate_keywords_by_version() {
+        let clarity_2 = get_labels(ClarityVersion::Clarity2);
+        assert!(clarity_2.contains(&"block-height".to_string()));
+        assert!(clarity_2.contains(&"get-burn-block-info?".to_string()));
+        assert!(!clarity_2.contains(&"tenure-height".to_string()));
+        assert!(!clarity_2.contains(&"get-tenure-info?".to_string()));
+
+        let clarity_3 = get_labels(ClarityVersion::Clarity3);
+        assert!(clarity_3.contains(&"tenure-height".to_string()));
+        assert!(clarity_3.contains(&"stacks-block-height".to_string()));
+        assert!(clarity_3.contains(&"get-tenure-info?".to_string()));
+        assert!(!clarity_3.contains(&"block-height".to_string()));
+        assert!(!clarity_3.contains(&"get-block-info?".to_string()));
+    }
+}
@@ -59,6 +59,18 @@ pub fn get_function_at_position(
         }
     }
 
+    let (function_name, _) = expressions.split_first()?;
+
+    Some((
+        function_name.match_atom()?.to_owned(),
+        get_position_in_parameters(position, expressions),
+    ))
+}
+
+fn get_position_in_parameters(
+    position: &Position,
+    expressions: &[SymbolicExpression],
+) -> Option<u32> {
     let mut position_in_parameters: i32 = -1;
     for parameter in expressions {
         match position.line.cmp(&parameter.span.end_line) {
@@ -71,12 +83,25 @@ pub fn get_function_at_position(
             _ => {}
         }
     }
+    position_in_parameters.try_into().ok()
+}
 
-    let (function_name, _) = expressions.split_first()?;
-
+/// Same as `get_function_at_position`, but returns the whole list of the function call
+pub fn get_function_call_at_position<'a>(
+    position: &Position,
+    expressions: &'a [SymbolicExpression],
+) -> Option<(&'a [SymbolicExpression], Option<u32>)> {
+    for expr in expressions {
+        if is_position_within_span(position, &expr.span, 0) {
+            if let Some(expressions) = expr.match_list() {
+                return get_function_call_at_position(position, expressions);
+            }
+        }
+    }
+    expressions.first()?.match_atom()?;
     Some((
-        function_name.match_atom()?.to_owned(),
-        position_in_parameters.try_into().ok(),
+        expressions,
+        get_position_in_parameters(position, expressions),
     ))
 }
 
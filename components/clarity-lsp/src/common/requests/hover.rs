@@ -1,15 +1,211 @@
-use clarity_repl::clarity::SymbolicExpression;
+use std::collections::HashMap;
+
+use clarity_repl::analysis::natives::{lookup_native_keyword, NativeKind};
+use clarity_repl::clarity::representations::Span;
+use clarity_repl::clarity::{ClarityVersion, SymbolicExpression};
+use clarity_repl::repl::session::BOOT_CONTRACTS_DATA;
+use clarity_repl::repl::ClarityCodeSource;
+use lazy_static::lazy_static;
 use lsp_types::Position;
+use serde::{Deserialize, Serialize};
 
 use super::{api_ref::API_REF, helpers::get_expression_name_at_position};
 
+lazy_static! {
+    static ref BOOT_CONTRACTS_DOCUMENTATION: HashMap<String, HashMap<String, FunctionDocumentation>> = {
+        let mut documentation = HashMap::new();
+        for (contract_id, (contract, ast)) in BOOT_CONTRACTS_DATA.iter() {
+            if let ClarityCodeSource::ContractInMemory(source) = &contract.code_source {
+                documentation
+                    .entry(contract_id.name.to_string())
+                    .or_insert_with(|| get_functions_documentation(source, &ast.expressions));
+            }
+        }
+        documentation
+    };
+}
+
+/// Signature and leading comments of a public or read-only function
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FunctionDocumentation {
+    pub signature: String,
+    pub comments: String,
+}
+
+impl FunctionDocumentation {
+    pub fn to_markdown(&self, contract_name: &str) -> String {
+        let mut documentation = vec![
+            format!("**{}**", contract_name),
+            ["```clarity", &self.signature, "```"].join("\n"),
+        ];
+        if !self.comments.is_empty() {
+            documentation.push(self.comments.clone());
+        }
+        documentation.join("\n\n")
+    }
+}
+
+fn get_source_at_span(lines: &[&str], span: &Span) -> Option<String> {
+    let mut source = vec![];
+    for line_number in span.start_line..=span.end_line {
+        let line: Vec<char> = lines
+            .get(line_number.checked_sub(1)? as usize)?
+            .chars()
+            .collect();
+        let start = match line_number == span.start_line {
+            true => span.start_column.checked_sub(1)? as usize,
+            false => 0,
+        };
+        let end = match line_number == span.end_line {
+            true => (span.end_column as usize).min(line.len()),
+            false => line.len(),
+        };
+        source.push(line.get(start..end)?.iter().collect::<String>());
+    }
+    Some(
+        source
+            .join(" ")
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" "),
+    )
+}
+
+fn get_leading_comments(lines: &[&str], start_line: u32) -> String {
+    let mut comments = vec![];
+    let mut line_number = start_line.saturating_sub(1) as usize;
+    while line_number > 0 {
+        line_number -= 1;
+        let Some(comment) = lines[line_number].trim().strip_prefix(";;") else {
+            break;
+        };
+        comments.push(comment.trim_start_matches(';').trim());
+    }
+    comments.reverse();
+    comments.join("\n")
+}
+
+/// Collects the documentation of the public and read-only functions of a contract
+pub fn get_functions_documentation(
+    source: &str,
+    expressions: &[SymbolicExpression],
+) -> HashMap<String, FunctionDocumentation> {
+    let lines: Vec<&str> = source.lines().collect();
+    let mut documentation = HashMap::new();
+    for expr in expressions {
+        let Some((define, args)) = expr.match_list().and_then(|list| list.split_first()) else {
+            continue;
+        };
+        let Some(define) = define
+            .match_atom()
+            .filter(|atom| ["define-public", "define-read-only"].contains(&atom.as_str()))
+        else {
+            continue;
+        };
+        let Some(header) = args.first() else {
+            continue;
+        };
+        let Some(name) = header
+            .match_list()
+            .and_then(|header| header.first())
+            .and_then(|name| name.match_atom())
+        else {
+            continue;
+        };
+        let Some(header) = get_source_at_span(&lines, &header.span) else {
+            continue;
+        };
+        documentation.insert(
+            name.to_string(),
+            FunctionDocumentation {
+                signature: format!("({} {})", define, header),
+                comments: get_leading_comments(&lines, expr.span.start_line),
+            },
+        );
+    }
+    documentation
+}
+
+pub fn get_boot_contract_function_documentation(
+    contract_name: &str,
+    function_name: &str,
+) -> Option<&'static FunctionDocumentation> {
+    BOOT_CONTRACTS_DOCUMENTATION
+        .get(contract_name)?
+        .get(function_name)
+}
+
+/// Documentation of the native keyword at `position`, with a warning when the keyword is not
+/// available in the Clarity version of the contract
 pub fn get_expression_documentation(
     position: &Position,
+    clarity_version: ClarityVersion,
     expressions: &Vec<SymbolicExpression>,
 ) -> Option<String> {
     let expression_name = get_expression_name_at_position(position, expressions)?;
+    let (documentation, _) = API_REF.get(&expression_name.to_string())?;
+
+    let unavailability = [
+        NativeKind::Function,
+        NativeKind::Define,
+        NativeKind::Variable,
+    ]
+    .into_iter()
+    .find_map(|kind| lookup_native_keyword(&expression_name, kind))
+    .and_then(|keyword| keyword.get_unavailability_reason(clarity_version));
+    Some(match unavailability {
+        Some(reason) => format!("**⚠ {}**\n\n{}", reason, documentation),
+        None => documentation.to_owned(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use clarity_repl::clarity::ast::build_ast_with_rules;
+    use clarity_repl::clarity::vm::types::QualifiedContractIdentifier;
+    use clarity_repl::clarity::{ClarityVersion, StacksEpochId};
+
+    use super::{get_boot_contract_function_documentation, get_functions_documentation};
+
+    #[test]
+    fn get_function_leading_comments() {
+        let source = [
+            ";; Increments the counter",
+            ";; by the given step",
+            "(define-public (increment",
+            "    (step uint))",
+            "  (ok step))",
+            "",
+            "(define-private (helper) true)",
+        ]
+        .join("\n");
+        let contract_ast = build_ast_with_rules(
+            &QualifiedContractIdentifier::transient(),
+            &source,
+            &mut (),
+            ClarityVersion::Clarity2,
+            StacksEpochId::Epoch21,
+            clarity_repl::clarity::ast::ASTRules::Typical,
+        )
+        .unwrap();
+        let documentation = get_functions_documentation(&source, &contract_ast.expressions);
+        assert_eq!(documentation.len(), 1);
+        let increment = documentation.get("increment").unwrap();
+        assert_eq!(
+            increment.signature,
+            "(define-public (increment (step uint)))"
+        );
+        assert_eq!(
+            increment.comments,
+            "Increments the counter\nby the given step"
+        );
+    }
 
-    API_REF
-        .get(&expression_name.to_string())
-        .map(|(documentation, _)| documentation.to_owned())
+    #[test]
+    fn get_boot_contract_documentation() {
+        let stack_stx = get_boot_contract_function_documentation("pox-4", "stack-stx").unwrap();
+        assert!(stack_stx
+            .signature
+            .starts_with("(define-public (stack-stx (amount-ustx uint)"));
+    }
 }
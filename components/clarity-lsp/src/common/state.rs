@@ -1,7 +1,8 @@
 use crate::common::requests::completion::check_if_should_wrap;
+use clarinet_deployments::cancellation::{CancellationToken, CANCELLED_ERROR};
 use clarinet_deployments::{
-    generate_default_deployment, initiate_session_from_manifest,
-    update_session_with_deployment_plan, UpdateSessionExecutionResult,
+    generate_default_deployment_with_cancellation, get_requirements_contracts,
+    initiate_session_from_manifest, PlanExecutor, UpdateSessionExecutionResult,
 };
 use clarinet_files::ProjectManifest;
 use clarinet_files::StacksNetwork;
@@ -19,20 +20,29 @@ use lsp_types::{
     CompletionItem, DocumentSymbol, Hover, Location, MessageType, Position, Range, SignatureHelp,
     Url,
 };
+use serde::{Deserialize, Serialize};
 use std::borrow::BorrowMut;
 use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::{Arc, Mutex};
 use std::vec;
 
+#[cfg(feature = "cli")]
+use super::cache;
 use super::requests::capabilities::InitializationOptions;
 use super::requests::completion::{
-    build_completion_item_list, get_contract_calls, ContractDefinedData,
+    build_completion_item_list, get_contract_call_at_position, get_contract_calls,
+    get_contract_functions, get_contract_traits, ContractCallTarget, ContractDefinedData,
+    ContractFunction,
 };
 use super::requests::definitions::{
     get_definitions, get_public_function_definitions, DefinitionLocation,
 };
 use super::requests::document_symbols::ASTSymbols;
 use super::requests::helpers::get_atom_start_at_position;
-use super::requests::hover::get_expression_documentation;
+use super::requests::hover::{
+    get_boot_contract_function_documentation, get_expression_documentation,
+    get_functions_documentation, FunctionDocumentation,
+};
 use super::requests::signature_help::get_signatures;
 
 #[derive(Debug, Clone, PartialEq)]
@@ -124,9 +134,12 @@ impl ActiveContractData {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ContractState {
     contract_calls: Vec<CompletionItem>,
+    functions: Vec<ContractFunction>,
+    traits: HashMap<String, Vec<ContractFunction>>,
+    functions_documentation: HashMap<String, FunctionDocumentation>,
     errors: Vec<ClarityDiagnostic>,
     warnings: Vec<ClarityDiagnostic>,
     notes: Vec<ClarityDiagnostic>,
@@ -145,6 +158,7 @@ impl ContractState {
         mut diags: Vec<ClarityDiagnostic>,
         analysis: Option<ContractAnalysis>,
         definitions: HashMap<ClarityName, Range>,
+        functions_documentation: HashMap<String, FunctionDocumentation>,
         location: FileLocation,
         clarity_version: ClarityVersion,
     ) -> ContractState {
@@ -166,14 +180,21 @@ impl ContractState {
             }
         }
 
-        let contract_calls = match analysis {
-            Some(ref analysis) => get_contract_calls(analysis),
-            None => vec![],
+        let (contract_calls, functions, traits) = match analysis {
+            Some(ref analysis) => (
+                get_contract_calls(analysis),
+                get_contract_functions(analysis),
+                get_contract_traits(analysis),
+            ),
+            None => (vec![], vec![], HashMap::new()),
         };
 
         ContractState {
             contract_id,
             contract_calls,
+            functions,
+            traits,
+            functions_documentation,
             errors,
             warnings,
             notes,
@@ -194,12 +215,60 @@ pub struct ContractMetadata {
     pub deployer: ContractDeployer,
 }
 
+/// Build of a project in progress in the language server. The editor cancels it when a new
+/// save of the project supersedes it, or when it shuts the server down.
+#[derive(Clone, Default, Debug)]
+pub struct BuildCancellation(Arc<Mutex<Option<(FileLocation, CancellationToken)>>>);
+
+impl BuildCancellation {
+    pub fn start(&self, manifest_location: &FileLocation) -> CancellationToken {
+        let token = CancellationToken::new();
+        if let Ok(mut build) = self.0.lock() {
+            *build = Some((manifest_location.clone(), token.clone()));
+        }
+        token
+    }
+
+    pub fn supersede(&self, manifest_location: &FileLocation) {
+        if let Ok(build) = self.0.lock() {
+            match build.as_ref() {
+                Some((location, token)) if location == manifest_location => token.cancel(),
+                _ => {}
+            }
+        }
+    }
+
+    pub fn cancel(&self) {
+        if let Ok(build) = self.0.lock() {
+            if let Some((_, token)) = build.as_ref() {
+                token.cancel();
+            }
+        }
+    }
+}
+
 #[derive(Clone, Default, Debug)]
 pub struct EditorState {
     pub protocols: HashMap<FileLocation, ProtocolState>,
     pub contracts_lookup: HashMap<FileLocation, ContractMetadata>,
     pub active_contracts: HashMap<FileLocation, ActiveContractData>,
     pub settings: InitializationOptions,
+    /// Root locations of the workspace folders, with their own settings
+    pub workspace_folders: HashMap<FileLocation, InitializationOptions>,
+    pub build_cancellation: BuildCancellation,
+}
+
+fn is_location_in_folder(location: &FileLocation, folder: &FileLocation) -> bool {
+    match (location, folder) {
+        (FileLocation::FileSystem { path }, FileLocation::FileSystem { path: folder }) => {
+            path.starts_with(folder)
+        }
+        (FileLocation::Url { url }, FileLocation::Url { url: folder }) => url
+            .as_str()
+            .strip_prefix(folder.as_str().trim_end_matches('/'))
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('/')),
+        _ => false,
+    }
 }
 
 impl EditorState {
@@ -209,7 +278,56 @@ impl EditorState {
             contracts_lookup: HashMap::new(),
             active_contracts: HashMap::new(),
             settings: InitializationOptions::default(),
+            workspace_folders: HashMap::new(),
+            build_cancellation: BuildCancellation::default(),
+        }
+    }
+
+    pub fn add_workspace_folder(
+        &mut self,
+        location: FileLocation,
+        settings: InitializationOptions,
+    ) {
+        self.workspace_folders.insert(location, settings);
+    }
+
+    /// Forgets a workspace folder, along with the protocols and the contracts it contains.
+    /// Returns the locations of the contracts that were analysed, so that their diagnostics
+    /// can be cleared.
+    pub fn remove_workspace_folder(&mut self, location: &FileLocation) -> Vec<FileLocation> {
+        self.workspace_folders.remove(location);
+
+        let manifests_locations: Vec<FileLocation> = self
+            .protocols
+            .keys()
+            .filter(|manifest_location| is_location_in_folder(manifest_location, location))
+            .cloned()
+            .collect();
+        let mut contracts_locations = vec![];
+        for manifest_location in manifests_locations.iter() {
+            if let Some(protocol) = self.protocols.get(manifest_location) {
+                contracts_locations.extend(protocol.contracts.keys().cloned());
+            }
+            self.clear_protocol(manifest_location);
         }
+        self.active_contracts
+            .retain(|contract_location, _| !is_location_in_folder(contract_location, location));
+        contracts_locations
+    }
+
+    /// Returns the innermost workspace folder containing a location
+    pub fn get_workspace_folder(&self, location: &FileLocation) -> Option<&FileLocation> {
+        self.workspace_folders
+            .keys()
+            .filter(|folder| is_location_in_folder(location, folder))
+            .max_by_key(|folder| folder.to_string().len())
+    }
+
+    /// Returns the settings of the workspace folder containing a location, or the global ones
+    pub fn get_settings(&self, location: &FileLocation) -> &InitializationOptions {
+        self.get_workspace_folder(location)
+            .and_then(|folder| self.workspace_folders.get(folder))
+            .unwrap_or(&self.settings)
     }
 
     pub fn index_protocol(&mut self, manifest_location: FileLocation, protocol: ProtocolState) {
@@ -310,9 +428,31 @@ impl EditorState {
             .unwrap_or_default();
 
         let expressions = active_contract.expressions.as_ref();
+        let clarity_position = Position {
+            line: position.line + 1,
+            character: position.character + 1,
+        };
         let active_contract_defined_data =
-            ContractDefinedData::new(expressions.unwrap_or(&vec![]), position);
-        let should_wrap = match self.settings.completion_smart_parenthesis_wrap {
+            ContractDefinedData::new(expressions.unwrap_or(&vec![]), &clarity_position);
+
+        // after `(contract-call? <contract-or-trait> `, the functions it exposes are listed
+        if let Some((target, _, 1)) =
+            expressions.and_then(|e| get_contract_call_at_position(&clarity_position, e))
+        {
+            if let Some((name, functions)) = self.get_contract_call_functions(
+                contract_location,
+                &active_contract_defined_data,
+                target,
+            ) {
+                return functions
+                    .iter()
+                    .map(|function| function.to_completion_item(&name))
+                    .collect();
+            }
+        }
+
+        let settings = self.get_settings(contract_location);
+        let should_wrap = match settings.completion_smart_parenthesis_wrap {
             true => check_if_should_wrap(&active_contract.source, position),
             false => true,
         };
@@ -320,17 +460,49 @@ impl EditorState {
         build_completion_item_list(
             &active_contract.clarity_version,
             expressions.unwrap_or(&vec![]),
-            &Position {
-                line: position.line + 1,
-                character: position.character + 1,
-            },
+            &clarity_position,
             &active_contract_defined_data,
             contract_calls,
             should_wrap,
-            self.settings.completion_include_native_placeholders,
+            settings.completion_include_native_placeholders,
         )
     }
 
+    /// Returns the name of the contract or trait targeted by a `contract-call?`, and its functions
+    fn get_contract_call_functions(
+        &self,
+        contract_location: &FileLocation,
+        defined_data: &ContractDefinedData,
+        target: &SymbolicExpression,
+    ) -> Option<(String, Vec<ContractFunction>)> {
+        let target = defined_data.get_contract_call_target(target)?;
+        if let ContractCallTarget::LocalTrait(trait_name) = &target {
+            return Some((
+                trait_name.clone(),
+                defined_data.traits.get(trait_name)?.clone(),
+            ));
+        }
+
+        let protocol = self
+            .contracts_lookup
+            .get(contract_location)
+            .and_then(|d| self.protocols.get(&d.manifest_location))?;
+        match target {
+            ContractCallTarget::Contract(contract_name) => {
+                let functions = protocol.get_contract_functions(&contract_name)?;
+                Some((contract_name, functions))
+            }
+            ContractCallTarget::ImportedTrait {
+                contract_name,
+                trait_name,
+            } => {
+                let functions = protocol.get_trait_functions(&contract_name, &trait_name)?;
+                Some((trait_name, functions))
+            }
+            ContractCallTarget::LocalTrait(_) => None,
+        }
+    }
+
     pub fn get_document_symbols_for_contract(
         &self,
         contract_location: &FileLocation,
@@ -414,7 +586,14 @@ impl EditorState {
             character: position.character + 1,
         };
         let documentation =
-            get_expression_documentation(&position, contract.expressions.as_ref()?)?;
+            match self.get_contract_call_documentation(contract_location, contract, &position) {
+                Some(documentation) => documentation,
+                None => get_expression_documentation(
+                    &position,
+                    contract.clarity_version,
+                    contract.expressions.as_ref()?,
+                )?,
+            };
 
         Some(Hover {
             contents: lsp_types::HoverContents::Markup(lsp_types::MarkupContent {
@@ -425,6 +604,40 @@ impl EditorState {
         })
     }
 
+    /// Documentation of the function targeted by a `contract-call?`, when hovering its contract
+    /// or its name. Project and requirements contracts are looked up first, then boot contracts.
+    fn get_contract_call_documentation(
+        &self,
+        contract_location: &FileLocation,
+        contract: &ActiveContractData,
+        position: &Position,
+    ) -> Option<String> {
+        let expressions = contract.expressions.as_ref()?;
+        let (target, Some(function_name), 0 | 1) =
+            get_contract_call_at_position(position, expressions)?
+        else {
+            return None;
+        };
+        let defined_data = ContractDefinedData::new(expressions, position);
+        let ContractCallTarget::Contract(contract_name) =
+            defined_data.get_contract_call_target(target)?
+        else {
+            return None;
+        };
+
+        let documentation = self
+            .contracts_lookup
+            .get(contract_location)
+            .and_then(|d| self.protocols.get(&d.manifest_location))
+            .and_then(|protocol| {
+                protocol.get_function_documentation(&contract_name, function_name.as_str())
+            })
+            .or_else(|| {
+                get_boot_contract_function_documentation(&contract_name, function_name.as_str())
+            })?;
+        Some(documentation.to_markdown(&contract_name))
+    }
+
     pub fn get_signature_help(
         &self,
         contract_location: &FileLocation,
@@ -437,6 +650,29 @@ impl EditorState {
             character: position.character + 1,
         };
 
+        // the signature of the function targeted by a `contract-call?` is resolved, once
+        // the contract or trait, and the function, are known
+        let expressions = contract.expressions.as_ref()?;
+        if let Some((target, Some(function_name), param)) =
+            get_contract_call_at_position(&position, expressions)
+        {
+            if param >= 2 {
+                let defined_data = ContractDefinedData::new(expressions, &position);
+                if let Some((_, functions)) =
+                    self.get_contract_call_functions(contract_location, &defined_data, target)
+                {
+                    let function = functions
+                        .iter()
+                        .find(|function| function.name == function_name.as_str())?;
+                    return Some(SignatureHelp {
+                        signatures: vec![function.to_signature_information(Some(param - 2))],
+                        active_signature: Some(0),
+                        active_parameter: None,
+                    });
+                }
+            }
+        }
+
         let signatures = get_signatures(contract, &position)?;
 
         Some(SignatureHelp {
@@ -555,6 +791,24 @@ impl ProtocolState {
         ProtocolState::default()
     }
 
+    /// Rebuilds the state of a protocol from the state of its contracts
+    pub fn from_contracts(contracts: Vec<ContractState>) -> Self {
+        let mut protocol_state = ProtocolState::default();
+        for contract in contracts.into_iter() {
+            protocol_state
+                .locations_lookup
+                .insert(contract.contract_id.clone(), contract.location.clone());
+            protocol_state
+                .contracts
+                .insert(contract.location.clone(), contract);
+        }
+        protocol_state
+    }
+
+    pub fn get_contracts(&self) -> impl Iterator<Item = &ContractState> {
+        self.contracts.values()
+    }
+
     pub fn consolidate(
         &mut self,
         locations: &mut HashMap<QualifiedContractIdentifier, FileLocation>,
@@ -562,6 +816,10 @@ impl ProtocolState {
         deps: &mut BTreeMap<QualifiedContractIdentifier, DependencySet>,
         diags: &mut HashMap<QualifiedContractIdentifier, Vec<ClarityDiagnostic>>,
         definitions: &mut HashMap<QualifiedContractIdentifier, HashMap<ClarityName, Range>>,
+        functions_documentation: &mut HashMap<
+            QualifiedContractIdentifier,
+            HashMap<String, FunctionDocumentation>,
+        >,
         analyses: &mut HashMap<QualifiedContractIdentifier, Option<ContractAnalysis>>,
         clarity_versions: &mut HashMap<QualifiedContractIdentifier, ClarityVersion>,
     ) {
@@ -585,6 +843,9 @@ impl ProtocolState {
                 None => DEFAULT_CLARITY_VERSION,
             };
             let definitions = definitions.remove(&contract_id).unwrap_or_default();
+            let functions_documentation = functions_documentation
+                .remove(&contract_id)
+                .unwrap_or_default();
 
             let contract_state = ContractState::new(
                 contract_id.clone(),
@@ -593,6 +854,7 @@ impl ProtocolState {
                 diags,
                 analysis,
                 definitions,
+                functions_documentation,
                 contract_location.clone(),
                 clarity_version,
             );
@@ -604,6 +866,37 @@ impl ProtocolState {
         }
     }
 
+    fn get_contract_by_name(&self, contract_name: &str) -> Option<&ContractState> {
+        self.contracts
+            .values()
+            .find(|contract| contract.contract_id.name.as_str() == contract_name)
+    }
+
+    pub fn get_contract_functions(&self, contract_name: &str) -> Option<Vec<ContractFunction>> {
+        Some(self.get_contract_by_name(contract_name)?.functions.clone())
+    }
+
+    pub fn get_trait_functions(
+        &self,
+        contract_name: &str,
+        trait_name: &str,
+    ) -> Option<Vec<ContractFunction>> {
+        self.get_contract_by_name(contract_name)?
+            .traits
+            .get(trait_name)
+            .cloned()
+    }
+
+    pub fn get_function_documentation(
+        &self,
+        contract_name: &str,
+        function_name: &str,
+    ) -> Option<&FunctionDocumentation> {
+        self.get_contract_by_name(contract_name)?
+            .functions_documentation
+            .get(function_name)
+    }
+
     pub fn get_contract_calls_for_contract(
         &self,
         contract_uri: &FileLocation,
@@ -622,10 +915,28 @@ pub async fn build_state(
     manifest_location: &FileLocation,
     protocol_state: &mut ProtocolState,
     file_accessor: Option<&dyn FileAccessor>,
+) -> Result<(), String> {
+    build_state_with_cancellation(
+        manifest_location,
+        protocol_state,
+        file_accessor,
+        &CancellationToken::new(),
+    )
+    .await
+}
+
+/// A cancelled build fails with `CANCELLED_ERROR`, the protocol state and the analysis cache
+/// are left untouched
+pub async fn build_state_with_cancellation(
+    manifest_location: &FileLocation,
+    protocol_state: &mut ProtocolState,
+    file_accessor: Option<&dyn FileAccessor>,
+    cancellation: &CancellationToken,
 ) -> Result<(), String> {
     let mut locations = HashMap::new();
     let mut analyses = HashMap::new();
     let mut definitions = HashMap::new();
+    let mut functions_documentation = HashMap::new();
     let mut clarity_versions = HashMap::new();
 
     // In the LSP use case, trying to load an existing deployment
@@ -640,24 +951,45 @@ pub async fn build_state(
         }
     };
 
-    let (deployment, mut artifacts) = generate_default_deployment(
+    #[cfg(feature = "cli")]
+    let analysis_key = match file_accessor {
+        None if cache::is_analysis_cache_enabled() => cache::get_analysis_key(&manifest).ok(),
+        _ => None,
+    };
+    #[cfg(feature = "cli")]
+    if let Some(ref analysis_key) = analysis_key {
+        if let Some(cached_protocol_state) = cache::load_cached_analysis(&manifest, analysis_key) {
+            *protocol_state = cached_protocol_state;
+            return Ok(());
+        }
+    }
+
+    let (deployment, mut artifacts) = generate_default_deployment_with_cancellation(
         &manifest,
         &StacksNetwork::Simnet,
         false,
         file_accessor,
         Some(StacksEpochId::Epoch21),
+        cancellation,
     )
     .await?;
 
     let mut session = initiate_session_from_manifest(&manifest);
-    let UpdateSessionExecutionResult { contracts, .. } = update_session_with_deployment_plan(
-        &mut session,
-        &deployment,
-        Some(&artifacts.asts),
-        Some(StacksEpochId::Epoch21),
-    );
+    let UpdateSessionExecutionResult {
+        contracts,
+        cancelled,
+        ..
+    } = PlanExecutor::new(&deployment)
+        .with_asts(Some(&artifacts.asts))
+        .with_min_epoch(Some(StacksEpochId::Epoch21))
+        .with_cancellation(cancellation)
+        .with_requirements(get_requirements_contracts(&manifest, &deployment))
+        .execute(&mut session);
+    if cancelled {
+        return Err(CANCELLED_ERROR.to_string());
+    }
     for (contract_id, mut result) in contracts.into_iter() {
-        let (_, contract_location) = match deployment.contracts.get(&contract_id) {
+        let (source, contract_location) = match deployment.contracts.get(&contract_id) {
             Some(entry) => entry,
             None => continue,
         };
@@ -678,6 +1010,10 @@ pub async fn build_state(
                             contract_id.clone(),
                             get_public_function_definitions(&ast.expressions),
                         );
+                        functions_documentation.insert(
+                            contract_id.clone(),
+                            get_functions_documentation(source, &ast.expressions),
+                        );
                     }
                     analyses.insert(contract_id.clone(), Some(contract_result.contract.analysis));
                 };
@@ -697,9 +1033,15 @@ pub async fn build_state(
         &mut artifacts.deps,
         &mut artifacts.diags,
         &mut definitions,
+        &mut functions_documentation,
         &mut analyses,
         &mut clarity_versions,
     );
 
+    #[cfg(feature = "cli")]
+    if let Some(ref analysis_key) = analysis_key {
+        cache::save_analysis(&manifest, analysis_key, protocol_state);
+    }
+
     Ok(())
 }
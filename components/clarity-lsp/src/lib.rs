@@ -4,5 +4,7 @@ pub mod utils;
 pub mod vscode_bridge;
 
 pub use common::backend;
+#[cfg(feature = "cli")]
+pub use common::cache;
 pub use common::state;
 pub use lsp_types;
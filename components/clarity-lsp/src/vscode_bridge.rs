@@ -5,19 +5,20 @@ use crate::backend::{
 };
 use crate::state::EditorState;
 use crate::utils::{clarity_diagnostics_to_lsp_type, get_contract_location, get_manifest_location};
-use clarinet_files::{FileAccessor, WASMFileSystemAccessor};
+use clarinet_files::{FileAccessor, FileLocation, WASMFileSystemAccessor};
 use js_sys::{Function as JsFunction, Promise};
 use lsp_types::notification::{
-    DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, DidSaveTextDocument,
-    Initialized, Notification,
+    DidChangeTextDocument, DidChangeWorkspaceFolders, DidCloseTextDocument, DidOpenTextDocument,
+    DidSaveTextDocument, Initialized, Notification,
 };
 use lsp_types::request::{
     Completion, DocumentSymbolRequest, GotoDefinition, HoverRequest, Initialize, Request,
     SignatureHelpRequest,
 };
 use lsp_types::{
-    DidChangeTextDocumentParams, DidCloseTextDocumentParams, DidOpenTextDocumentParams,
-    DidSaveTextDocumentParams, MessageType, PublishDiagnosticsParams, Url,
+    DidChangeTextDocumentParams, DidChangeWorkspaceFoldersParams, DidCloseTextDocumentParams,
+    DidOpenTextDocumentParams, DidSaveTextDocumentParams, MessageType, PublishDiagnosticsParams,
+    Url, WorkspaceFolder,
 };
 use serde::Serialize;
 use serde_wasm_bindgen::{from_value as decode_from_js, to_value as encode_to_js, Serializer};
@@ -29,6 +30,13 @@ use wasm_bindgen_futures::future_to_promise;
 #[cfg(debug_assertions)]
 use crate::utils::log;
 
+fn get_workspace_folders_locations(folders: &[WorkspaceFolder]) -> Vec<FileLocation> {
+    folders
+        .iter()
+        .filter_map(|folder| FileLocation::try_parse(folder.uri.as_str(), None))
+        .collect()
+}
+
 #[wasm_bindgen]
 pub struct LspVscodeBridge {
     client_diagnostic_tx: JsFunction,
@@ -58,8 +66,21 @@ impl LspVscodeBridge {
     #[wasm_bindgen(js_name=onNotification)]
     pub fn notification_handler(&self, method: String, js_params: JsValue) -> Promise {
         let command = match method.as_str() {
-            Initialized::METHOD => {
-                return Promise::resolve(&JsValue::TRUE);
+            Initialized::METHOD => LspNotification::Initialized,
+
+            DidChangeWorkspaceFolders::METHOD => {
+                let params: DidChangeWorkspaceFoldersParams = match decode_from_js(js_params) {
+                    Ok(params) => params,
+                    Err(err) => {
+                        return Promise::reject(&JsValue::from(format!(
+                            "error (did change workspace folders): {err}"
+                        )))
+                    }
+                };
+                LspNotification::WorkspaceFoldersChanged {
+                    added: get_workspace_folders_locations(&params.event.added),
+                    removed: get_workspace_folders_locations(&params.event.removed),
+                }
             }
 
             DidOpenTextDocument::METHOD => {
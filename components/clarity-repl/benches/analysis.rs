@@ -0,0 +1,81 @@
+use std::collections::BTreeMap;
+use std::hint::black_box;
+
+use clarity::ast::build_ast_with_diagnostics;
+use clarity::types::StacksEpochId;
+use clarity::vm::ast::ContractAST;
+use clarity::vm::types::QualifiedContractIdentifier;
+use clarity::vm::ClarityVersion;
+use clarity_repl::analysis::ast_dependency_detector::ASTDependencyDetector;
+use divan::Bencher;
+
+const DEPLOYER: &str = "ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM";
+
+// a chain of vaults, each one calling the previous one, to get a dependency graph as deep as
+// the contracts count
+fn get_contracts(count: usize) -> Vec<(QualifiedContractIdentifier, String)> {
+    (0..count)
+        .map(|i| {
+            let contract_id =
+                QualifiedContractIdentifier::parse(&format!("{}.vault-{}", DEPLOYER, i)).unwrap();
+            let mut src = [
+                "(define-map balances principal uint)",
+                "(define-data-var total uint u0)",
+                "(define-read-only (get-balance (who principal))",
+                "  (default-to u0 (map-get? balances who)))",
+                "(define-public (deposit (amount uint))",
+                "  (let ((balance (get-balance tx-sender)))",
+                "    (map-set balances tx-sender (+ balance amount))",
+                "    (ok (var-set total (+ (var-get total) amount)))))",
+            ]
+            .join("\n");
+            if i > 0 {
+                src.push_str(&format!(
+                    "\n(define-public (forward (amount uint))\n  (contract-call? .vault-{} deposit amount))",
+                    i - 1
+                ));
+            }
+            (contract_id, src)
+        })
+        .collect()
+}
+
+fn build_asts(
+    contracts: &[(QualifiedContractIdentifier, String)],
+) -> BTreeMap<QualifiedContractIdentifier, (ClarityVersion, ContractAST)> {
+    contracts
+        .iter()
+        .map(|(contract_id, src)| {
+            let (ast, _, success) = build_ast_with_diagnostics(
+                contract_id,
+                src,
+                &mut (),
+                ClarityVersion::Clarity2,
+                StacksEpochId::Epoch25,
+            );
+            assert!(success);
+            (contract_id.clone(), (ClarityVersion::Clarity2, ast))
+        })
+        .collect()
+}
+
+#[divan::bench(args = [10, 100])]
+fn ast_building(bencher: Bencher, count: usize) {
+    let contracts = get_contracts(count);
+    bencher.bench_local(|| build_asts(black_box(&contracts)));
+}
+
+#[divan::bench(args = [10, 100])]
+fn dependency_detection(bencher: Bencher, count: usize) {
+    let asts = build_asts(&get_contracts(count));
+    let preloaded = BTreeMap::new();
+    bencher.bench_local(|| {
+        let dependencies =
+            ASTDependencyDetector::detect_dependencies(black_box(&asts), &preloaded).unwrap();
+        assert_eq!(dependencies.len(), count);
+    });
+}
+
+fn main() {
+    divan::main();
+}
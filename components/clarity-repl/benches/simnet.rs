@@ -7,6 +7,7 @@ use clarity::{
         Value as ClarityValue,
     },
 };
+use clarity_repl::repl::storage::StorageBackendKind;
 use clarity_repl::repl::{
     ClarityCodeSource, ClarityContract, ContractDeployer, Session, SessionSettings,
     DEFAULT_CLARITY_VERSION, DEFAULT_EPOCH,
@@ -14,7 +15,14 @@ use clarity_repl::repl::{
 use divan::Bencher;
 
 fn init_session() -> Session {
-    let mut session = Session::new(SessionSettings::default());
+    init_session_with_storage(StorageBackendKind::InMemory)
+}
+
+fn init_session_with_storage(storage_backend: StorageBackendKind) -> Session {
+    let mut session = Session::new(SessionSettings {
+        storage_backend,
+        ..Default::default()
+    });
     session.update_epoch(StacksEpochId::Epoch30);
     session.advance_burn_chain_tip(1);
     assert_eq!(session.interpreter.get_block_height(), 2);
@@ -147,7 +155,19 @@ fn simnet_noop_public(bencher: Bencher) {
 
 #[divan::bench(sample_count = 10_000)]
 fn simnet_save(bencher: Bencher) {
-    let mut session = init_session();
+    bench_save(bencher, init_session());
+}
+
+// same writes as simnet_save, in a sqlite database instead of the memory
+#[divan::bench(sample_count = 10_000)]
+fn simnet_save_sqlite(bencher: Bencher) {
+    bench_save(
+        bencher,
+        init_session_with_storage(StorageBackendKind::Sqlite(None)),
+    );
+}
+
+fn bench_save(bencher: Bencher, mut session: Session) {
     let initial_block_height = session.interpreter.get_block_height();
     let mut i: u32 = 0;
 
@@ -7,6 +7,8 @@ pub enum AnnotationKind {
     Allow(WarningKind),
     Filter(Vec<ClarityName>),
     FilterAll,
+    /// `TODO` or `FIXME` comment, reported by the style pass
+    Todo(String),
 }
 
 impl std::str::FromStr for AnnotationKind {
@@ -14,8 +14,8 @@ use clarity::vm::types::{
 };
 use clarity::vm::{ClarityName, ClarityVersion, SymbolicExpressionType};
 use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
-use std::iter::FromIterator;
 use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
 
 use super::ast_visitor::TypedVar;
 
@@ -57,7 +57,7 @@ pub struct ASTDependencyDetector<'a> {
     >,
     params: Option<Vec<TypedVar<'a>>>,
     top_level: bool,
-    preloaded: &'a BTreeMap<QualifiedContractIdentifier, (ClarityVersion, ContractAST)>,
+    preloaded: &'a BTreeMap<QualifiedContractIdentifier, (ClarityVersion, Arc<ContractAST>)>,
 }
 
 #[derive(Clone, Debug, Eq)]
@@ -193,7 +193,7 @@ impl DerefMut for DependencySet {
 impl<'a> ASTDependencyDetector<'a> {
     pub fn detect_dependencies(
         contract_asts: &'a BTreeMap<QualifiedContractIdentifier, (ClarityVersion, ContractAST)>,
-        preloaded: &'a BTreeMap<QualifiedContractIdentifier, (ClarityVersion, ContractAST)>,
+        preloaded: &'a BTreeMap<QualifiedContractIdentifier, (ClarityVersion, Arc<ContractAST>)>,
     ) -> Result<
         BTreeMap<QualifiedContractIdentifier, DependencySet>,
         (
@@ -918,8 +918,12 @@ impl GraphWalker {
             return None;
         }
 
-        let nodes = HashSet::from_iter(sorted_indexes.iter().cloned());
-        let deps = nodes.difference(&tainted).copied().collect();
+        // sorted, so that the contracts of a cycle are always reported in the same order
+        let nodes: BTreeSet<usize> = sorted_indexes.iter().copied().collect();
+        let deps = nodes
+            .into_iter()
+            .filter(|node| !tainted.contains(node))
+            .collect();
         Some(deps)
     }
 }
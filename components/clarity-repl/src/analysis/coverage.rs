@@ -58,10 +58,32 @@ impl CoverageHook {
         self.reports.clear();
     }
 
+    /// LCOV content of all the reports, which are then cleared
     pub fn collect_lcov_content(
         &mut self,
         asts: &BTreeMap<QualifiedContractIdentifier, ContractAST>,
         contract_paths: &BTreeMap<String, String>,
+    ) -> String {
+        let file_content = Self::get_lcov_content(&self.reports, asts, contract_paths);
+        self.clear();
+        file_content
+    }
+
+    /// LCOV content of the reports recorded from `first_report` on, leaving them in place
+    pub fn get_lcov_content_since(
+        &self,
+        first_report: usize,
+        asts: &BTreeMap<QualifiedContractIdentifier, ContractAST>,
+        contract_paths: &BTreeMap<String, String>,
+    ) -> String {
+        let reports = self.reports.get(first_report..).unwrap_or_default();
+        Self::get_lcov_content(reports, asts, contract_paths)
+    }
+
+    fn get_lcov_content(
+        reports: &[CoverageReport],
+        asts: &BTreeMap<QualifiedContractIdentifier, ContractAST>,
+        contract_paths: &BTreeMap<String, String>,
     ) -> String {
         let mut file_content = String::new();
 
@@ -82,12 +104,10 @@ impl CoverageHook {
 
         // for consistency in the result, we use a btreemap instead of a hashmap
         let reports_per_tests: BTreeMap<&String, Vec<&CoverageReport>> =
-            self.reports
-                .iter()
-                .fold(BTreeMap::new(), |mut acc, report| {
-                    acc.entry(&report.test_name).or_default().push(report);
-                    acc
-                });
+            reports.iter().fold(BTreeMap::new(), |mut acc, report| {
+                acc.entry(&report.test_name).or_default().push(report);
+                acc
+            });
 
         for (test_name, test_reports) in reports_per_tests.iter() {
             file_content.push_str(&format!("TN:{}\n", **test_name));
@@ -198,8 +218,6 @@ impl CoverageHook {
             }
         }
 
-        self.clear();
-
         file_content
     }
 }
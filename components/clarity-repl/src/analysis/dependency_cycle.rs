@@ -0,0 +1,294 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use clarity::vm::ast::ContractAST;
+use clarity::vm::representations::SymbolicExpressionType;
+use clarity::vm::types::{PrincipalData, QualifiedContractIdentifier, Value};
+use clarity::vm::SymbolicExpression;
+
+use super::ast_dependency_detector::DependencySet;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CallSiteKind {
+    /// `(contract-call? .contract function ...)`
+    ContractCall(String),
+    /// `use-trait` or `impl-trait` of a trait defined by the contract
+    Trait(String),
+    /// Any other reference to the contract principal
+    Reference,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CallSite {
+    pub kind: CallSiteKind,
+    pub line: u32,
+    pub column: u32,
+}
+
+impl fmt::Display for CallSite {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            CallSiteKind::ContractCall(function) => write!(f, "contract-call? {}", function)?,
+            CallSiteKind::Trait(name) => write!(f, "trait {}", name)?,
+            CallSiteKind::Reference => write!(f, "principal reference")?,
+        }
+        write!(f, " (line {}, column {})", self.line, self.column)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CycleEdge {
+    pub from: QualifiedContractIdentifier,
+    pub to: QualifiedContractIdentifier,
+    pub call_sites: Vec<CallSite>,
+}
+
+impl CycleEdge {
+    /// Only edges exclusively made of `contract-call?` can be replaced by a trait
+    fn can_use_trait(&self) -> bool {
+        !self.call_sites.is_empty()
+            && self
+                .call_sites
+                .iter()
+                .all(|site| matches!(site.kind, CallSiteKind::ContractCall(_)))
+    }
+}
+
+/// A circular dependency between contracts, reported when the contracts can't be ordered
+#[derive(Debug, Clone, PartialEq)]
+pub struct DependencyCycle {
+    pub edges: Vec<CycleEdge>,
+}
+
+impl DependencyCycle {
+    /// Returns the edge that is the easiest to break: the one with the fewest call sites,
+    /// favoring the ones that can be converted to a trait
+    pub fn suggested_cut(&self) -> Option<&CycleEdge> {
+        self.edges
+            .iter()
+            .min_by_key(|edge| (!edge.can_use_trait(), edge.call_sites.len()))
+    }
+}
+
+impl fmt::Display for DependencyCycle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Some(first) = self.edges.first() else {
+            return Ok(());
+        };
+        let mut path = vec![first.from.name.to_string()];
+        path.extend(self.edges.iter().map(|edge| edge.to.name.to_string()));
+        writeln!(f, "circular dependency: {}", path.join(" → "))?;
+
+        for edge in self.edges.iter() {
+            writeln!(f, "  {} → {}", edge.from.name, edge.to.name)?;
+            for site in edge.call_sites.iter() {
+                writeln!(f, "    {}", site)?;
+            }
+        }
+
+        let Some(cut) = self.suggested_cut() else {
+            return Ok(());
+        };
+        if cut.can_use_trait() {
+            write!(
+                f,
+                "suggestion: remove the {} contract-call? from {} to {}, or call {} through a trait passed as an argument",
+                cut.call_sites.len(),
+                cut.from.name,
+                cut.to.name,
+                cut.to.name,
+            )
+        } else {
+            write!(
+                f,
+                "suggestion: remove the references from {} to {}",
+                cut.from.name, cut.to.name,
+            )
+        }
+    }
+}
+
+/// Returns the first cycle found in the dependencies, with the call sites of each edge.
+/// `get_ast` is used to look up the call sites in the contracts sources.
+pub fn find_dependency_cycle<'a>(
+    dependencies: &BTreeMap<QualifiedContractIdentifier, DependencySet>,
+    get_ast: impl Fn(&QualifiedContractIdentifier) -> Option<&'a ContractAST>,
+) -> Option<DependencyCycle> {
+    let mut done = BTreeSet::new();
+    for contract_id in dependencies.keys() {
+        let mut path = vec![];
+        if let Some(cycle) = find_cycle_from(contract_id, dependencies, &mut path, &mut done) {
+            let edges = cycle
+                .iter()
+                .zip(cycle.iter().cycle().skip(1))
+                .map(|(from, to)| CycleEdge {
+                    from: from.clone(),
+                    to: to.clone(),
+                    call_sites: get_ast(from)
+                        .map(|ast| find_call_sites(&ast.expressions, to))
+                        .unwrap_or_default(),
+                })
+                .collect();
+            return Some(DependencyCycle { edges });
+        }
+    }
+    None
+}
+
+fn find_cycle_from(
+    contract_id: &QualifiedContractIdentifier,
+    dependencies: &BTreeMap<QualifiedContractIdentifier, DependencySet>,
+    path: &mut Vec<QualifiedContractIdentifier>,
+    done: &mut BTreeSet<QualifiedContractIdentifier>,
+) -> Option<Vec<QualifiedContractIdentifier>> {
+    if let Some(position) = path.iter().position(|id| id == contract_id) {
+        return Some(path[position..].to_vec());
+    }
+    if done.contains(contract_id) {
+        return None;
+    }
+    path.push(contract_id.clone());
+    if let Some(contract_dependencies) = dependencies.get(contract_id) {
+        for dependency in contract_dependencies.iter() {
+            if let Some(cycle) = find_cycle_from(&dependency.contract_id, dependencies, path, done)
+            {
+                return Some(cycle);
+            }
+        }
+    }
+    path.pop();
+    done.insert(contract_id.clone());
+    None
+}
+
+fn get_call_site(expr: &SymbolicExpression, kind: CallSiteKind) -> CallSite {
+    CallSite {
+        kind,
+        line: expr.span.start_line,
+        column: expr.span.start_column,
+    }
+}
+
+fn is_contract(expr: &SymbolicExpression, target: &QualifiedContractIdentifier) -> bool {
+    matches!(
+        expr.match_literal_value(),
+        Some(Value::Principal(PrincipalData::Contract(contract_id))) if contract_id == target
+    )
+}
+
+fn find_call_sites(
+    expressions: &[SymbolicExpression],
+    target: &QualifiedContractIdentifier,
+) -> Vec<CallSite> {
+    let mut call_sites = vec![];
+    for expr in expressions {
+        match &expr.expr {
+            SymbolicExpressionType::List(list) => {
+                let is_contract_call = list
+                    .first()
+                    .and_then(|function| function.match_atom())
+                    .is_some_and(|function| function.as_str() == "contract-call?");
+                if is_contract_call && list.get(1).is_some_and(|e| is_contract(e, target)) {
+                    let function = list
+                        .get(2)
+                        .and_then(|e| e.match_atom())
+                        .map(|name| name.to_string())
+                        .unwrap_or_default();
+                    call_sites.push(get_call_site(expr, CallSiteKind::ContractCall(function)));
+                    call_sites.append(&mut find_call_sites(list.get(3..).unwrap_or(&[]), target));
+                } else {
+                    call_sites.append(&mut find_call_sites(list, target));
+                }
+            }
+            SymbolicExpressionType::Field(trait_identifier)
+                if &trait_identifier.contract_identifier == target =>
+            {
+                call_sites.push(get_call_site(
+                    expr,
+                    CallSiteKind::Trait(trait_identifier.name.to_string()),
+                ));
+            }
+            _ if is_contract(expr, target) => {
+                call_sites.push(get_call_site(expr, CallSiteKind::Reference));
+            }
+            _ => {}
+        }
+    }
+    call_sites
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::ast_dependency_detector::ASTDependencyDetector;
+    use crate::repl::session::Session;
+    use crate::repl::{
+        ClarityCodeSource, ClarityContract, ContractDeployer, SessionSettings,
+        DEFAULT_CLARITY_VERSION, DEFAULT_EPOCH,
+    };
+    use clarity::vm::ClarityVersion;
+
+    fn build_ast(session: &Session, snippet: &str, name: &str) -> ContractAST {
+        let contract = ClarityContract {
+            code_source: ClarityCodeSource::ContractInMemory(snippet.to_string()),
+            name: name.to_string(),
+            deployer: ContractDeployer::Transient,
+            clarity_version: DEFAULT_CLARITY_VERSION,
+            epoch: DEFAULT_EPOCH,
+        };
+        let (ast, _, _) = session.interpreter.build_ast(&contract);
+        ast
+    }
+
+    #[test]
+    fn test_cycle_report() {
+        let session = Session::new(SessionSettings::default());
+        let snippets = [
+            ("a", "(define-public (f) (contract-call? .b g))"),
+            (
+                "b",
+                "(define-public (g) (contract-call? .c h))\n(define-public (i) (ok u1))",
+            ),
+            (
+                "c",
+                "(define-public (h) (begin (try! (contract-call? .a f)) (contract-call? .a f)))",
+            ),
+        ];
+        let mut contracts: BTreeMap<QualifiedContractIdentifier, (ClarityVersion, ContractAST)> =
+            BTreeMap::new();
+        for (name, snippet) in snippets {
+            let ast = build_ast(&session, snippet, name);
+            contracts.insert(
+                ast.contract_identifier.clone(),
+                (DEFAULT_CLARITY_VERSION, ast),
+            );
+        }
+
+        let dependencies =
+            ASTDependencyDetector::detect_dependencies(&contracts, &BTreeMap::new()).unwrap();
+        let cycle =
+            find_dependency_cycle(&dependencies, |id| contracts.get(id).map(|(_, ast)| ast))
+                .unwrap();
+
+        let names: Vec<_> = cycle
+            .edges
+            .iter()
+            .map(|e| e.from.name.to_string())
+            .collect();
+        assert_eq!(names, vec!["a", "b", "c"]);
+        assert_eq!(cycle.edges[2].call_sites.len(), 2);
+        assert_eq!(
+            cycle.edges[0].call_sites,
+            vec![CallSite {
+                kind: CallSiteKind::ContractCall("g".into()),
+                line: 1,
+                column: 20,
+            }]
+        );
+        let cut = cycle.suggested_cut().unwrap();
+        assert_eq!(cut.from.name.to_string(), "a");
+
+        let report = cycle.to_string();
+        assert!(report.starts_with("circular dependency: a → b → c → a"));
+    }
+}
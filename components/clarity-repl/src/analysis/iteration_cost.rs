@@ -0,0 +1,419 @@
+//! Detects iterations over large sequences, typically used to emulate an iteration over
+//! the entries of a map: Clarity has no recursion and no way to list the keys of a map,
+//! so contracts `fold` over a fixed list of indexes instead, and read the map for each
+//! index. The cost of these calls grows with the declared size of the sequence.
+
+#![allow(unused_variables)]
+
+use std::collections::{HashMap, HashSet};
+
+use crate::analysis::annotation::Annotation;
+use crate::analysis::{self, AnalysisPass, AnalysisResult};
+use clarity::vm::analysis::analysis_db::AnalysisDatabase;
+use clarity::vm::analysis::types::ContractAnalysis;
+use clarity::vm::diagnostic::{Diagnostic, Level};
+use clarity::vm::types::Value;
+use clarity::vm::SymbolicExpression;
+
+const MAP_OPERATIONS: [&str; 4] = ["map-get?", "map-set", "map-insert", "map-delete"];
+const SEQUENCE_TYPES: [&str; 4] = ["list", "buff", "string-ascii", "string-utf8"];
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Settings {
+    // Iterations over sequences longer than this are reported
+    max_iterations: u64,
+    // Iterations performing more map operations than this are reported
+    max_map_operations: u64,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            max_iterations: 100,
+            max_map_operations: 100,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
+pub struct SettingsFile {
+    max_iterations: Option<u64>,
+    max_map_operations: Option<u64>,
+}
+
+impl From<SettingsFile> for Settings {
+    fn from(from_file: SettingsFile) -> Self {
+        let default = Settings::default();
+        Settings {
+            max_iterations: from_file.max_iterations.unwrap_or(default.max_iterations),
+            max_map_operations: from_file
+                .max_map_operations
+                .unwrap_or(default.max_map_operations),
+        }
+    }
+}
+
+struct FunctionInfo<'a> {
+    // Maximum length of the sequence parameters
+    params: HashMap<&'a str, u128>,
+    body: &'a SymbolicExpression,
+}
+
+pub struct IterationCostChecker<'a> {
+    settings: Settings,
+    // Maximum length of the sequence data-vars
+    data_vars: HashMap<&'a str, u128>,
+    functions: HashMap<&'a str, FunctionInfo<'a>>,
+    // Worst-case number of map operations performed by a call to each function
+    functions_cost: HashMap<&'a str, u128>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+/// Maximum length of a sequence type, such as `(list 200 uint)`
+fn get_sequence_type_length(type_expr: &SymbolicExpression) -> Option<u128> {
+    let list = type_expr.match_list()?;
+    if !SEQUENCE_TYPES.contains(&list.first()?.match_atom()?.as_str()) {
+        return None;
+    }
+    get_length_literal(list.get(1)?)
+}
+
+fn get_length_literal(expr: &SymbolicExpression) -> Option<u128> {
+    match expr.match_literal_value()? {
+        Value::UInt(length) => Some(*length),
+        Value::Int(length) => u128::try_from(*length).ok(),
+        _ => None,
+    }
+}
+
+impl<'a> IterationCostChecker<'a> {
+    fn new(settings: Settings) -> IterationCostChecker<'a> {
+        Self {
+            settings,
+            data_vars: HashMap::new(),
+            functions: HashMap::new(),
+            functions_cost: HashMap::new(),
+            diagnostics: Vec::new(),
+        }
+    }
+
+    fn run(mut self, contract_analysis: &'a ContractAnalysis) -> AnalysisResult {
+        let expressions = &contract_analysis.expressions;
+        for expr in expressions {
+            self.collect_definition(expr);
+        }
+
+        let names: Vec<&'a str> = self.functions.keys().copied().collect();
+        for name in names {
+            let cost = self.get_function_cost(name, &mut HashSet::new());
+            self.functions_cost.insert(name, cost);
+        }
+
+        for expr in expressions {
+            let scope = self.get_definition_scope(expr);
+            self.walk(expr, &scope, None);
+        }
+
+        self.diagnostics.sort_by(|a, b| a.spans[0].cmp(&b.spans[0]));
+        Ok(self.diagnostics)
+    }
+
+    fn collect_definition(&mut self, expr: &'a SymbolicExpression) {
+        let Some(list) = expr.match_list() else {
+            return;
+        };
+        let Some(define) = list.first().and_then(|define| define.match_atom()) else {
+            return;
+        };
+        match define.as_str() {
+            "define-data-var" => {
+                if let (Some(name), Some(length)) = (
+                    list.get(1).and_then(|name| name.match_atom()),
+                    list.get(2).and_then(get_sequence_type_length),
+                ) {
+                    self.data_vars.insert(name.as_str(), length);
+                }
+            }
+            "define-public" | "define-private" | "define-read-only" => {
+                let (Some(signature), Some(body)) =
+                    (list.get(1).and_then(|s| s.match_list()), list.get(2))
+                else {
+                    return;
+                };
+                let Some(name) = signature.first().and_then(|name| name.match_atom()) else {
+                    return;
+                };
+                let mut params = HashMap::new();
+                for param in signature.iter().skip(1) {
+                    let Some(param) = param.match_list() else {
+                        continue;
+                    };
+                    if let (Some(param_name), Some(length)) = (
+                        param.first().and_then(|name| name.match_atom()),
+                        param.get(1).and_then(get_sequence_type_length),
+                    ) {
+                        params.insert(param_name.as_str(), length);
+                    }
+                }
+                self.functions
+                    .insert(name.as_str(), FunctionInfo { params, body });
+            }
+            _ => {}
+        }
+    }
+
+    fn get_definition_scope(&self, expr: &'a SymbolicExpression) -> HashMap<&'a str, u128> {
+        expr.match_list()
+            .and_then(|list| list.get(1))
+            .and_then(|signature| signature.match_list())
+            .and_then(|signature| signature.first())
+            .and_then(|name| name.match_atom())
+            .and_then(|name| self.functions.get(name.as_str()))
+            .map(|function| function.params.clone())
+            .unwrap_or_default()
+    }
+
+    fn get_function_cost(&mut self, name: &'a str, visiting: &mut HashSet<&'a str>) -> u128 {
+        if let Some(cost) = self.functions_cost.get(name) {
+            return *cost;
+        }
+        let Some(function) = self.functions.get(name) else {
+            return 0;
+        };
+        // Recursion is rejected by the type checker, this only guards against looping
+        if !visiting.insert(name) {
+            return 0;
+        }
+        let (params, body) = (function.params.clone(), function.body);
+        let cost = self.walk(body, &params, Some(visiting));
+        visiting.remove(name);
+        self.functions_cost.insert(name, cost);
+        cost
+    }
+
+    /// Worst-case length of a sequence expression
+    fn get_sequence_length(
+        &self,
+        expr: &'a SymbolicExpression,
+        scope: &HashMap<&'a str, u128>,
+    ) -> Option<u128> {
+        if let Some(name) = expr.match_atom() {
+            return scope.get(name.as_str()).copied();
+        }
+        let list = expr.match_list()?;
+        let function = list.first()?.match_atom()?;
+        match function.as_str() {
+            "list" => Some(list.len() as u128 - 1),
+            "var-get" => self
+                .data_vars
+                .get(list.get(1)?.match_atom()?.as_str())
+                .copied(),
+            "as-max-len?" => get_length_literal(list.get(2)?),
+            "unwrap-panic" | "unwrap!" => self.get_sequence_length(list.get(1)?, scope),
+            _ => None,
+        }
+    }
+
+    /// Returns the worst-case number of map operations performed by `expr`. Iterations
+    /// found along the way are reported, unless computing the cost of a function.
+    fn walk(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        scope: &HashMap<&'a str, u128>,
+        mut visiting: Option<&mut HashSet<&'a str>>,
+    ) -> u128 {
+        let Some(list) = expr.match_list() else {
+            return 0;
+        };
+        let function = list.first().and_then(|function| function.match_atom());
+
+        let mut inner_scope;
+        let mut scope = scope;
+        let mut cost: u128 = 0;
+        if let Some(function) = function {
+            match function.as_str() {
+                name if MAP_OPERATIONS.contains(&name) => cost = 1,
+                "let" => {
+                    inner_scope = scope.clone();
+                    for binding in list
+                        .get(1)
+                        .and_then(|bindings| bindings.match_list())
+                        .unwrap_or_default()
+                    {
+                        let Some(binding) = binding.match_list() else {
+                            continue;
+                        };
+                        if let (Some(name), Some(value)) =
+                            (binding.first().and_then(|n| n.match_atom()), binding.get(1))
+                        {
+                            if let Some(length) = self.get_sequence_length(value, scope) {
+                                inner_scope.insert(name.as_str(), length);
+                            }
+                        }
+                    }
+                    scope = &inner_scope;
+                }
+                "map" | "fold" | "filter" => {
+                    if let Some(iterated) = list.get(1).and_then(|f| f.match_atom()) {
+                        let sequences = match function.as_str() {
+                            "fold" | "filter" => &list[2..list.len().min(3)],
+                            _ => &list[2..],
+                        };
+                        let length = sequences
+                            .iter()
+                            .filter_map(|sequence| self.get_sequence_length(sequence, scope))
+                            .min();
+                        if let Some(length) = length {
+                            let iterated_cost = match visiting.as_deref_mut() {
+                                Some(visiting) => {
+                                    self.get_function_cost(iterated.as_str(), visiting)
+                                }
+                                None => self
+                                    .functions_cost
+                                    .get(iterated.as_str())
+                                    .copied()
+                                    .unwrap_or(0),
+                            };
+                            let iteration_cost = length.saturating_mul(iterated_cost);
+                            cost = cost.saturating_add(iteration_cost);
+                            if visiting.is_none() {
+                                self.check_iteration(
+                                    expr,
+                                    function.as_str(),
+                                    iterated.as_str(),
+                                    length,
+                                    iteration_cost,
+                                );
+                            }
+                        }
+                    }
+                }
+                name => {
+                    if self.functions.contains_key(name) {
+                        let callee_cost = match visiting.as_deref_mut() {
+                            Some(visiting) => self.get_function_cost(name, visiting),
+                            None => self.functions_cost.get(name).copied().unwrap_or(0),
+                        };
+                        cost = cost.saturating_add(callee_cost);
+                    }
+                }
+            }
+        }
+
+        for child in list.iter() {
+            let child_cost = self.walk(child, scope, visiting.as_deref_mut());
+            cost = cost.saturating_add(child_cost);
+        }
+        cost
+    }
+
+    fn check_iteration(
+        &mut self,
+        expr: &SymbolicExpression,
+        function: &str,
+        iterated: &str,
+        length: u128,
+        map_operations: u128,
+    ) {
+        let message = if map_operations > u128::from(self.settings.max_map_operations) {
+            format!(
+                "{} over up to {} elements calls {}, performing up to {} map operations",
+                function, length, iterated, map_operations
+            )
+        } else if length > u128::from(self.settings.max_iterations) {
+            format!(
+                "{} over up to {} elements calls {} {} times",
+                function, length, iterated, length
+            )
+        } else {
+            return;
+        };
+        self.diagnostics.push(Diagnostic {
+            level: Level::Warning,
+            message,
+            spans: vec![expr.span.clone()],
+            suggestion: Some(
+                "paginate: take an offset as a parameter and process a bounded page of entries per call"
+                    .to_string(),
+            ),
+        });
+    }
+}
+
+impl AnalysisPass for IterationCostChecker<'_> {
+    fn run_pass(
+        contract_analysis: &mut ContractAnalysis,
+        analysis_db: &mut AnalysisDatabase,
+        annotations: &Vec<Annotation>,
+        settings: &analysis::Settings,
+    ) -> AnalysisResult {
+        let checker = IterationCostChecker::new(settings.iteration_cost);
+        checker.run(contract_analysis)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analysis::Pass;
+    use crate::repl::session::Session;
+    use crate::repl::SessionSettings;
+
+    fn get_messages(snippet: String) -> Vec<String> {
+        let mut settings = SessionSettings::default();
+        settings.repl_settings.analysis.passes = vec![Pass::IterationCost];
+        let mut session = Session::new(settings);
+        match session.formatted_interpretation(snippet, Some("iteration".to_string()), false, None)
+        {
+            Ok((_, result)) => result
+                .diagnostics
+                .into_iter()
+                .map(|diagnostic| diagnostic.message)
+                .collect(),
+            _ => panic!("Expected successful interpretation"),
+        }
+    }
+
+    #[test]
+    fn iteration_over_map_entries() {
+        let snippet = "
+(define-map balances uint uint)
+(define-data-var ids (list 200 uint) (list))
+(define-private (add-balance (id uint) (total uint))
+    (+ total (default-to u0 (map-get? balances id)))
+)
+(define-read-only (get-total)
+    (fold add-balance (var-get ids) u0)
+)
+(define-read-only (get-page-total (page (list 20 uint)))
+    (fold add-balance page u0)
+)"
+        .to_string();
+        assert_eq!(
+            get_messages(snippet),
+            vec!["fold over up to 200 elements calls add-balance, performing up to 200 map operations"]
+        );
+    }
+
+    #[test]
+    fn nested_iterations() {
+        let snippet = "
+(define-map owners { group: uint, index: uint } principal)
+(define-private (is-owner (index uint) (group uint))
+    (begin
+        (map-get? owners { group: group, index: index })
+        group
+    )
+)
+(define-private (count-group (group uint) (count uint))
+    (fold is-owner (list u0 u1 u2 u3 u4 u5 u6 u7 u8 u9) group)
+)
+(define-read-only (get-count (groups (list 50 uint)))
+    (fold count-group groups u0)
+)"
+        .to_string();
+        assert_eq!(
+            get_messages(snippet),
+            vec!["fold over up to 50 elements calls count-group, performing up to 500 map operations"]
+        );
+    }
+}
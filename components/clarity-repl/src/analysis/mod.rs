@@ -6,42 +6,110 @@ pub mod check_checker;
 pub mod coverage;
 #[cfg(test)]
 mod coverage_tests;
+pub mod dependency_cycle;
+pub mod iteration_cost;
+pub mod native_policy;
+pub mod natives;
+#[cfg(feature = "cli")]
+pub mod plugins;
+pub mod registry;
+pub mod style;
+pub mod unchecked_response;
 
 use serde::Serialize;
 
 use crate::analysis::annotation::Annotation;
 use clarity::vm::analysis::analysis_db::AnalysisDatabase;
 use clarity::vm::analysis::types::ContractAnalysis;
-use clarity::vm::diagnostic::Diagnostic;
+use clarity::vm::diagnostic::{Diagnostic, Level};
 
 use self::call_checker::CallChecker;
-use self::check_checker::CheckChecker;
+use self::native_policy::NativePolicyChecker;
+use self::registry::resolve_passes;
 
 pub type AnalysisResult = Result<Vec<Diagnostic>, Vec<Diagnostic>>;
 
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "snake_case")]
+#[serde(from = "String", into = "String")]
 pub enum Pass {
     All,
     CheckChecker,
+    Style,
+    UncheckedResponse,
+    IterationCost,
+    /// Pass registered with `registry::register_pass`
+    Custom(String),
+}
+
+impl Pass {
+    pub fn get_name(&self) -> &str {
+        match self {
+            Pass::All => "all",
+            Pass::CheckChecker => "check_checker",
+            Pass::Style => "style",
+            Pass::UncheckedResponse => "unchecked_response",
+            Pass::IterationCost => "iteration_cost",
+            Pass::Custom(name) => name,
+        }
+    }
+}
+
+impl From<String> for Pass {
+    fn from(name: String) -> Self {
+        match name.as_str() {
+            "all" => Pass::All,
+            "check_checker" => Pass::CheckChecker,
+            "style" => Pass::Style,
+            "unchecked_response" => Pass::UncheckedResponse,
+            "iteration_cost" => Pass::IterationCost,
+            _ => Pass::Custom(name),
+        }
+    }
+}
+
+impl From<Pass> for String {
+    fn from(pass: Pass) -> Self {
+        pass.get_name().to_string()
+    }
 }
 
 #[derive(Debug, Default, Clone, Deserialize, Serialize)]
 pub struct Settings {
     passes: Vec<Pass>,
     check_checker: check_checker::Settings,
+    #[serde(default)]
+    style: style::Settings,
+    #[serde(default)]
+    iteration_cost: iteration_cost::Settings,
+    #[serde(default)]
+    native_policy: native_policy::Settings,
+    /// Paths of the WASM lint plugins
+    #[serde(default)]
+    plugins: Vec<String>,
 }
 
 impl Settings {
     pub fn enable_all_passes(&mut self) {
-        self.passes = ALL_PASSES.to_vec();
+        self.passes = get_all_passes();
+    }
+
+    /// Resolves the relative paths of the plugins from the root of the project
+    pub fn resolve_plugins_paths(&mut self, project_root: &str) {
+        for path in self.plugins.iter_mut() {
+            if !std::path::Path::new(path.as_str()).is_absolute() {
+                *path = std::path::Path::new(project_root)
+                    .join(path.as_str())
+                    .to_string_lossy()
+                    .to_string();
+            }
+        }
     }
 
     pub fn set_passes(&mut self, passes: Vec<Pass>) {
         for pass in passes {
             match pass {
                 Pass::All => {
-                    self.passes = ALL_PASSES.to_vec();
+                    self.passes = get_all_passes();
                     return;
                 }
                 pass => self.passes.push(pass),
@@ -63,22 +131,42 @@ pub enum OneOrList<T> {
 pub struct SettingsFile {
     passes: Option<OneOrList<Pass>>,
     check_checker: Option<check_checker::SettingsFile>,
+    style: Option<style::SettingsFile>,
+    iteration_cost: Option<iteration_cost::SettingsFile>,
+    native_policy: Option<native_policy::SettingsFile>,
+    plugins: Option<Vec<String>>,
 }
 
 // Each new pass should be included in this list
-static ALL_PASSES: [Pass; 1] = [Pass::CheckChecker];
+static ALL_PASSES: [Pass; 4] = [
+    Pass::CheckChecker,
+    Pass::Style,
+    Pass::UncheckedResponse,
+    Pass::IterationCost,
+];
+
+/// Built-in passes, followed by the passes registered so far
+fn get_all_passes() -> Vec<Pass> {
+    let mut passes = ALL_PASSES.to_vec();
+    passes.extend(
+        registry::get_custom_pass_names()
+            .into_iter()
+            .map(Pass::Custom),
+    );
+    passes
+}
 
 impl From<SettingsFile> for Settings {
     fn from(from_file: SettingsFile) -> Self {
         let passes = if let Some(file_passes) = from_file.passes {
             match file_passes {
                 OneOrList::One(pass) => match pass {
-                    Pass::All => ALL_PASSES.to_vec(),
+                    Pass::All => get_all_passes(),
                     pass => vec![pass],
                 },
                 OneOrList::List(passes) => {
                     if passes.contains(&Pass::All) {
-                        ALL_PASSES.to_vec()
+                        get_all_passes()
                     } else {
                         passes
                     }
@@ -98,6 +186,19 @@ impl From<SettingsFile> for Settings {
         Self {
             passes,
             check_checker: checker_settings,
+            style: from_file
+                .style
+                .map(style::Settings::from)
+                .unwrap_or_default(),
+            iteration_cost: from_file
+                .iteration_cost
+                .map(iteration_cost::Settings::from)
+                .unwrap_or_default(),
+            native_policy: from_file
+                .native_policy
+                .map(native_policy::Settings::from)
+                .unwrap_or_default(),
+            plugins: from_file.plugins.unwrap_or_default(),
         }
     }
 }
@@ -119,18 +220,20 @@ pub fn run_analysis(
     settings: &Settings,
 ) -> AnalysisResult {
     let mut errors: Vec<Diagnostic> = Vec::new();
-    let mut passes: Vec<
-        fn(
-            &mut ContractAnalysis,
-            &mut AnalysisDatabase,
-            &Vec<Annotation>,
-            settings: &Settings,
-        ) -> AnalysisResult,
-    > = vec![CallChecker::run_pass];
-    for pass in &settings.passes {
-        match pass {
-            Pass::CheckChecker => passes.push(CheckChecker::run_pass),
-            Pass::All => panic!("unexpected All in list of passes"),
+    let mut passes: Vec<registry::AnalysisPassFn> = vec![CallChecker::run_pass];
+    // the policy of the session applies whatever the enabled passes
+    if settings.native_policy.is_enabled() {
+        passes.push(NativePolicyChecker::run_pass);
+    }
+    match resolve_passes(&settings.passes) {
+        Ok(resolved) => passes.extend(resolved.into_iter().map(|pass| pass.run)),
+        Err(message) => {
+            return Err(vec![Diagnostic {
+                level: Level::Error,
+                message,
+                spans: vec![],
+                suggestion: None,
+            }])
         }
     }
 
@@ -145,6 +248,24 @@ pub fn run_analysis(
                 }
             }
         }
+        if !settings.plugins.is_empty() {
+            #[cfg(feature = "cli")]
+            let mut diagnostics = plugins::run_plugins(contract_analysis, &settings.plugins);
+            #[cfg(not(feature = "cli"))]
+            let mut diagnostics = vec![Diagnostic {
+                level: Level::Warning,
+                message: "analysis plugins are only supported by the clarinet CLI".to_string(),
+                spans: vec![],
+                suggestion: None,
+            }];
+            let has_errors = diagnostics
+                .iter()
+                .any(|diagnostic| matches!(diagnostic.level, Level::Error));
+            errors.append(&mut diagnostics);
+            if has_errors {
+                return Err(errors);
+            }
+        }
         Ok(errors)
     })
 }
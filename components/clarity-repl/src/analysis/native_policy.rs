@@ -0,0 +1,300 @@
+#![allow(unused_variables)]
+
+use std::collections::BTreeSet;
+
+use crate::analysis::annotation::Annotation;
+use crate::analysis::ast_visitor::{traverse, ASTVisitor};
+use crate::analysis::natives::{is_native_available, lookup_native_keyword, NativeKind};
+use crate::analysis::{self, AnalysisPass, AnalysisResult};
+use clarity::vm::analysis::analysis_db::AnalysisDatabase;
+use clarity::vm::analysis::types::ContractAnalysis;
+use clarity::vm::diagnostic::{Diagnostic, Level};
+use clarity::vm::representations::Span;
+use clarity::vm::types::QualifiedContractIdentifier;
+use clarity::vm::{ClarityName, ClarityVersion, SymbolicExpression};
+
+/// Native functions and variables the contracts of a session can use
+/// (`[repl.analysis.native_policy]`), e.g. to forbid the block based randomness with
+/// `denied = ["block-height", "get-stacks-block-info?"]`. A contract breaking the policy can
+/// not be deployed, and a snippet breaking it is not evaluated.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Settings {
+    /// Names the contracts can not use
+    #[serde(default)]
+    denied: Vec<String>,
+    /// When set, the only names the contracts can use
+    #[serde(default)]
+    allowed: Option<Vec<String>>,
+    /// Contracts `contract-call?` can target: contract identifiers, or addresses allowing all
+    /// the contracts they deployed. When set, the calls through a trait are denied, their
+    /// target being only known at runtime.
+    #[serde(default)]
+    contract_call_targets: Option<Vec<String>>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct SettingsFile {
+    denied: Option<Vec<String>>,
+    allowed: Option<Vec<String>>,
+    contract_call_targets: Option<Vec<String>>,
+}
+
+impl From<SettingsFile> for Settings {
+    fn from(from_file: SettingsFile) -> Self {
+        Settings {
+            denied: from_file.denied.unwrap_or_default(),
+            allowed: from_file.allowed,
+            contract_call_targets: from_file.contract_call_targets,
+        }
+    }
+}
+
+impl Settings {
+    pub fn is_enabled(&self) -> bool {
+        !self.denied.is_empty() || self.allowed.is_some() || self.contract_call_targets.is_some()
+    }
+
+    /// Names of the policy which are neither native functions nor native variables
+    fn get_unknown_names(&self) -> Vec<&str> {
+        self.denied
+            .iter()
+            .chain(self.allowed.iter().flatten())
+            .map(|name| name.as_str())
+            .filter(|name| {
+                lookup_native_keyword(name, NativeKind::Function).is_none()
+                    && lookup_native_keyword(name, NativeKind::Variable).is_none()
+            })
+            .collect()
+    }
+}
+
+pub struct NativePolicyChecker<'a> {
+    settings: &'a Settings,
+    clarity_version: ClarityVersion,
+    diagnostics: Vec<Diagnostic>,
+    /// Names already reported, once per contract
+    reported: BTreeSet<String>,
+}
+
+impl<'a> NativePolicyChecker<'a> {
+    fn new(settings: &'a Settings, clarity_version: ClarityVersion) -> NativePolicyChecker<'a> {
+        Self {
+            settings,
+            clarity_version,
+            diagnostics: Vec::new(),
+            reported: BTreeSet::new(),
+        }
+    }
+
+    fn run(mut self, contract_analysis: &'a ContractAnalysis) -> AnalysisResult {
+        let unknown_names = self.settings.get_unknown_names();
+        if !unknown_names.is_empty() {
+            return Err(vec![Diagnostic {
+                level: Level::Error,
+                message: format!(
+                    "unknown native functions in the native policy: {}",
+                    unknown_names.join(", ")
+                ),
+                spans: vec![],
+                suggestion: None,
+            }]);
+        }
+
+        traverse(&mut self, &contract_analysis.expressions);
+
+        if self.diagnostics.is_empty() {
+            Ok(vec![])
+        } else {
+            Err(self.diagnostics)
+        }
+    }
+
+    fn add_error(&mut self, message: String, span: &Span) {
+        self.diagnostics.push(Diagnostic {
+            level: Level::Error,
+            message,
+            spans: vec![span.clone()],
+            suggestion: None,
+        });
+    }
+
+    fn check_name(&mut self, name: &str, span: &Span) {
+        if self.reported.contains(name) {
+            return;
+        }
+        let message = if self.settings.denied.iter().any(|denied| denied == name) {
+            format!("'{}' is denied by the native policy of the session", name)
+        } else if self
+            .settings
+            .allowed
+            .as_ref()
+            .is_some_and(|allowed| !allowed.iter().any(|allowed| allowed == name))
+        {
+            format!(
+                "'{}' is not allowed by the native policy of the session",
+                name
+            )
+        } else {
+            return;
+        };
+        self.reported.insert(name.to_string());
+        self.add_error(message, span);
+    }
+}
+
+impl<'a> ASTVisitor<'a> for NativePolicyChecker<'a> {
+    fn visit_list(&mut self, expr: &'a SymbolicExpression, list: &'a [SymbolicExpression]) -> bool {
+        if let Some(function) = list.first() {
+            if let Some(name) = function.match_atom() {
+                if is_native_available(name, NativeKind::Function, self.clarity_version) {
+                    self.check_name(name, &function.span);
+                }
+            }
+        }
+        true
+    }
+
+    fn visit_atom(&mut self, expr: &'a SymbolicExpression, atom: &'a ClarityName) -> bool {
+        if is_native_available(atom, NativeKind::Variable, self.clarity_version) {
+            self.check_name(atom, &expr.span);
+        }
+        true
+    }
+
+    fn visit_static_contract_call(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        contract_identifier: &'a QualifiedContractIdentifier,
+        function_name: &'a ClarityName,
+        args: &'a [SymbolicExpression],
+    ) -> bool {
+        if let Some(ref targets) = self.settings.contract_call_targets {
+            let contract_id = contract_identifier.to_string();
+            let issuer = contract_identifier.issuer.to_address();
+            if !targets
+                .iter()
+                .any(|target| *target == contract_id || *target == issuer)
+            {
+                self.add_error(
+                    format!(
+                        "contract-call? to {} is not allowed by the native policy of the session",
+                        contract_id
+                    ),
+                    &expr.span,
+                );
+            }
+        }
+        true
+    }
+
+    fn visit_dynamic_contract_call(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        trait_ref: &'a SymbolicExpression,
+        function_name: &'a ClarityName,
+        args: &'a [SymbolicExpression],
+    ) -> bool {
+        if self.settings.contract_call_targets.is_some() {
+            self.add_error(
+                "contract-call? through a trait is not allowed by the native policy of the session, its target can not be checked".to_string(),
+                &expr.span,
+            );
+        }
+        true
+    }
+}
+
+impl AnalysisPass for NativePolicyChecker<'_> {
+    fn run_pass(
+        contract_analysis: &mut ContractAnalysis,
+        analysis_db: &mut AnalysisDatabase,
+        annotations: &Vec<Annotation>,
+        settings: &analysis::Settings,
+    ) -> AnalysisResult {
+        let checker =
+            NativePolicyChecker::new(&settings.native_policy, contract_analysis.clarity_version);
+        checker.run(contract_analysis)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repl::session::Session;
+    use crate::repl::SessionSettings;
+
+    fn get_errors(session: &mut Session, name: &str, snippet: &str) -> Vec<String> {
+        match session.formatted_interpretation(
+            snippet.to_string(),
+            Some(name.to_string()),
+            false,
+            None,
+        ) {
+            Ok(_) => vec![],
+            Err((_, diagnostics)) => diagnostics
+                .into_iter()
+                .map(|diagnostic| diagnostic.message)
+                .collect(),
+        }
+    }
+
+    fn get_session(policy: Settings) -> Session {
+        let mut settings = SessionSettings::default();
+        settings.repl_settings.analysis.native_policy = policy;
+        Session::new(settings)
+    }
+
+    #[test]
+    fn native_policy() {
+        let random = "(define-read-only (get-random) (mod (+ stacks-block-height u1) u10))";
+        let mut session = get_session(Settings::default());
+        assert!(get_errors(&mut session, "random", random).is_empty());
+
+        let mut session = get_session(Settings {
+            denied: vec!["stacks-block-height".to_string()],
+            ..Default::default()
+        });
+        assert_eq!(
+            get_errors(&mut session, "random", random),
+            vec!["'stacks-block-height' is denied by the native policy of the session"]
+        );
+
+        let mut session = get_session(Settings {
+            allowed: Some(vec!["+".to_string(), "mod".to_string()]),
+            ..Default::default()
+        });
+        assert!(get_errors(&mut session, "sum", "(mod (+ 1 2) 2)").is_empty());
+        assert_eq!(
+            get_errors(&mut session, "difference", "(- 1 2)"),
+            vec!["'-' is not allowed by the native policy of the session"]
+        );
+
+        // contracts deployed by the burn address, the default deployer
+        let counter = "(define-read-only (get-count) (ok u1))";
+        let call = "(define-public (call) (contract-call? .counter get-count))";
+        let mut session = get_session(Settings {
+            contract_call_targets: Some(vec!["ST000000000000000000002AMW42H".to_string()]),
+            ..Default::default()
+        });
+        assert!(get_errors(&mut session, "counter", counter).is_empty());
+        assert!(get_errors(&mut session, "caller", call).is_empty());
+        let mut session = get_session(Settings {
+            contract_call_targets: Some(vec![]),
+            ..Default::default()
+        });
+        assert!(get_errors(&mut session, "counter", counter).is_empty());
+        assert_eq!(
+            get_errors(&mut session, "caller", call),
+            vec!["contract-call? to ST000000000000000000002AMW42H.counter is not allowed by the native policy of the session"]
+        );
+
+        let mut session = get_session(Settings {
+            denied: vec!["block-heigth".to_string()],
+            ..Default::default()
+        });
+        assert_eq!(
+            get_errors(&mut session, "sum", "(+ 1 2)"),
+            vec!["unknown native functions in the native policy: block-heigth"]
+        );
+    }
+}
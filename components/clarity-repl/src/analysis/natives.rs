@@ -0,0 +1,195 @@
+//! Native keywords of Clarity (functions, definitions, variables, and the properties of the
+//! `get-*-info?` functions) with the versions of Clarity they are available in. The analysis
+//! passes, the console and the completion and hover of the LSP all look the keywords up here,
+//! so that a Clarity 3 builtin such as `tenure-height` is not suggested in a Clarity 2 contract.
+
+use std::collections::BTreeMap;
+
+use clarity::vm::docs::{make_api_reference, make_define_reference, make_keyword_reference};
+use clarity::vm::functions::define::DefineFunctions;
+use clarity::vm::functions::NativeFunctions;
+use clarity::vm::types::{
+    BlockInfoProperty, BurnBlockInfoProperty, StacksBlockInfoProperty, TenureInfoProperty,
+};
+use clarity::vm::variables::NativeVariables;
+use clarity::vm::ClarityVersion;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NativeKind {
+    Function,
+    Define,
+    Variable,
+    /// Property of `get-block-info?`, `get-burn-block-info?`, `get-stacks-block-info?` or
+    /// `get-tenure-info?`
+    Property,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NativeKeyword {
+    pub name: String,
+    pub kind: NativeKind,
+    pub min_version: ClarityVersion,
+    /// Last version the keyword is available in, `None` if it still is in the latest one
+    pub max_version: Option<ClarityVersion>,
+}
+
+impl NativeKeyword {
+    pub fn is_available_in(&self, version: ClarityVersion) -> bool {
+        version >= self.min_version && self.max_version.map_or(true, |max| version <= max)
+    }
+
+    /// Why the keyword can not be used in a contract written in `version`
+    pub fn get_unavailability_reason(&self, version: ClarityVersion) -> Option<String> {
+        if version < self.min_version {
+            return Some(format!(
+                "`{}` is not available in {}, it was introduced in {}",
+                self.name, version, self.min_version
+            ));
+        }
+        match self.max_version {
+            Some(max_version) if version > max_version => Some(format!(
+                "`{}` is not available in {}, it was only available up to {}",
+                self.name, version, max_version
+            )),
+            _ => None,
+        }
+    }
+}
+
+fn build_native_keywords() -> Vec<NativeKeyword> {
+    let mut keywords = vec![];
+    for function in NativeFunctions::ALL.iter() {
+        let api = make_api_reference(function);
+        keywords.push(NativeKeyword {
+            name: api.name,
+            kind: NativeKind::Function,
+            min_version: api.min_version,
+            max_version: api.max_version,
+        });
+    }
+    for define in DefineFunctions::ALL.iter() {
+        let api = make_define_reference(define);
+        keywords.push(NativeKeyword {
+            name: api.name,
+            kind: NativeKind::Define,
+            min_version: api.min_version,
+            max_version: api.max_version,
+        });
+    }
+    for variable in NativeVariables::ALL.iter() {
+        if let Some(api) = make_keyword_reference(variable) {
+            keywords.push(NativeKeyword {
+                name: api.name.to_string(),
+                kind: NativeKind::Variable,
+                min_version: api.min_version,
+                max_version: api.max_version,
+            });
+        }
+    }
+
+    // the functions share some of their properties, a property is available from the first
+    // version one of them accepts it
+    let mut properties: BTreeMap<String, ClarityVersion> = BTreeMap::new();
+    let mut add_property = |name: String, version: ClarityVersion| {
+        let min_version = properties.entry(name).or_insert(version);
+        if version < *min_version {
+            *min_version = version;
+        }
+    };
+    for property in BlockInfoProperty::ALL.iter() {
+        add_property(property.to_string(), property.get_min_version());
+    }
+    for property in BurnBlockInfoProperty::ALL.iter() {
+        add_property(property.to_string(), ClarityVersion::Clarity2);
+    }
+    for property in StacksBlockInfoProperty::ALL.iter() {
+        add_property(property.to_string(), ClarityVersion::Clarity3);
+    }
+    for property in TenureInfoProperty::ALL.iter() {
+        add_property(property.to_string(), ClarityVersion::Clarity3);
+    }
+    for (name, min_version) in properties {
+        keywords.push(NativeKeyword {
+            name,
+            kind: NativeKind::Property,
+            min_version,
+            max_version: None,
+        });
+    }
+    keywords
+}
+
+lazy_static! {
+    static ref NATIVE_KEYWORDS: Vec<NativeKeyword> = build_native_keywords();
+}
+
+pub fn get_native_keywords() -> &'static [NativeKeyword] {
+    &NATIVE_KEYWORDS
+}
+
+pub fn lookup_native_keyword(name: &str, kind: NativeKind) -> Option<&'static NativeKeyword> {
+    NATIVE_KEYWORDS
+        .iter()
+        .find(|keyword| keyword.kind == kind && keyword.name == name)
+}
+
+pub fn is_native_available(name: &str, kind: NativeKind, version: ClarityVersion) -> bool {
+    lookup_native_keyword(name, kind).is_some_and(|keyword| keyword.is_available_in(version))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn native_keywords_availability() {
+        use ClarityVersion::*;
+
+        let tenure_height = lookup_native_keyword("tenure-height", NativeKind::Variable).unwrap();
+        assert!(tenure_height.is_available_in(Clarity3));
+        assert!(!tenure_height.is_available_in(Clarity2));
+        assert_eq!(
+            tenure_height.get_unavailability_reason(Clarity2),
+            Some(
+                "`tenure-height` is not available in Clarity 2, it was introduced in Clarity 3"
+                    .to_string()
+            )
+        );
+
+        assert!(is_native_available(
+            "block-height",
+            NativeKind::Variable,
+            Clarity2
+        ));
+        assert!(!is_native_available(
+            "block-height",
+            NativeKind::Variable,
+            Clarity3
+        ));
+        assert!(is_native_available(
+            "get-tenure-info?",
+            NativeKind::Function,
+            Clarity3
+        ));
+        assert!(!is_native_available(
+            "get-block-info?",
+            NativeKind::Function,
+            Clarity3
+        ));
+        assert!(is_native_available(
+            "define-public",
+            NativeKind::Define,
+            Clarity1
+        ));
+        assert!(is_native_available(
+            "vrf-seed",
+            NativeKind::Property,
+            Clarity1
+        ));
+        assert!(!is_native_available(
+            "tenure-height",
+            NativeKind::Function,
+            Clarity3
+        ));
+    }
+}
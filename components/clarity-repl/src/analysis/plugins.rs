@@ -0,0 +1,177 @@
+//! Lint rules compiled to WASM, listed in `[repl.analysis] plugins`.
+//!
+//! A plugin is a WASM module exporting:
+//! - `memory`
+//! - `alloc(len: i32) -> i32`, returning a buffer of `len` bytes in its memory
+//! - `check(ptr: i32, len: i32)`, called once per contract with a JSON document
+//!   `{ "contract_id": "...", "expressions": [...] }` (the AST of the contract)
+//!
+//! and importing `clarinet.emit_diagnostic(ptr: i32, len: i32)`, called with a JSON
+//! document `{ "level": "warning", "message": "...", "line": 1, "column": 1 }` for each
+//! issue found. `level` is one of `note`, `warning` (default) and `error`.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use clarity::vm::analysis::types::ContractAnalysis;
+use clarity::vm::diagnostic::{Diagnostic, Level};
+use clarity::vm::representations::Span;
+use wasmtime::{Caller, Engine, Linker, Module, Store};
+
+lazy_static! {
+    static ref ENGINE: Engine = Engine::default();
+    static ref MODULES: Mutex<HashMap<String, Module>> = Mutex::new(HashMap::new());
+}
+
+#[derive(Deserialize)]
+struct PluginDiagnostic {
+    #[serde(default)]
+    level: Option<String>,
+    message: String,
+    #[serde(default)]
+    line: u32,
+    #[serde(default)]
+    column: u32,
+}
+
+#[derive(Default)]
+struct PluginState {
+    payloads: Vec<Vec<u8>>,
+    error: Option<String>,
+}
+
+fn get_module(path: &str) -> Result<Module, String> {
+    let mut modules = MODULES.lock().unwrap();
+    if let Some(module) = modules.get(path) {
+        return Ok(module.clone());
+    }
+    let module = Module::from_file(&ENGINE, path)
+        .map_err(|e| format!("unable to load analysis plugin {}: {}", path, e))?;
+    modules.insert(path.to_string(), module.clone());
+    Ok(module)
+}
+
+fn emit_diagnostic(mut caller: Caller<'_, PluginState>, ptr: i32, len: i32) {
+    let Some(memory) = caller
+        .get_export("memory")
+        .and_then(|export| export.into_memory())
+    else {
+        caller.data_mut().error = Some("missing memory export".to_string());
+        return;
+    };
+    let mut payload = vec![0u8; len.max(0) as usize];
+    match memory.read(&caller, ptr as usize, &mut payload) {
+        Ok(()) => caller.data_mut().payloads.push(payload),
+        Err(e) => caller.data_mut().error = Some(format!("invalid diagnostic buffer: {}", e)),
+    }
+}
+
+fn run_plugin(path: &str, input: &[u8]) -> Result<Vec<PluginDiagnostic>, String> {
+    let module = get_module(path)?;
+    let mut store = Store::new(&ENGINE, PluginState::default());
+    let mut linker = Linker::new(&ENGINE);
+    linker
+        .func_wrap("clarinet", "emit_diagnostic", emit_diagnostic)
+        .map_err(|e| e.to_string())?;
+    let instance = linker
+        .instantiate(&mut store, &module)
+        .map_err(|e| format!("unable to instantiate: {}", e))?;
+    let memory = instance
+        .get_memory(&mut store, "memory")
+        .ok_or("missing memory export")?;
+    let alloc = instance
+        .get_typed_func::<i32, i32>(&mut store, "alloc")
+        .map_err(|e| format!("invalid alloc export: {}", e))?;
+    let check = instance
+        .get_typed_func::<(i32, i32), ()>(&mut store, "check")
+        .map_err(|e| format!("invalid check export: {}", e))?;
+
+    let len = i32::try_from(input.len()).map_err(|_| "contract too large")?;
+    let ptr = alloc
+        .call(&mut store, len)
+        .map_err(|e| format!("alloc failed: {}", e))?;
+    memory
+        .write(&mut store, ptr as usize, input)
+        .map_err(|e| format!("invalid input buffer: {}", e))?;
+    check
+        .call(&mut store, (ptr, len))
+        .map_err(|e| format!("check failed: {}", e))?;
+
+    let state = store.into_data();
+    if let Some(error) = state.error {
+        return Err(error);
+    }
+    state
+        .payloads
+        .iter()
+        .map(|payload| {
+            serde_json::from_slice(payload).map_err(|e| format!("invalid diagnostic: {}", e))
+        })
+        .collect()
+}
+
+fn get_plugin_name(path: &str) -> &str {
+    let file_name = path.rsplit(['/', '\\']).next().unwrap_or(path);
+    file_name.strip_suffix(".wasm").unwrap_or(file_name)
+}
+
+pub fn run_plugins(contract_analysis: &ContractAnalysis, plugins: &[String]) -> Vec<Diagnostic> {
+    if plugins.is_empty() {
+        return vec![];
+    }
+    let input = json!({
+        "contract_id": contract_analysis.contract_identifier.to_string(),
+        "expressions": contract_analysis.expressions,
+    })
+    .to_string();
+
+    let mut diagnostics = vec![];
+    for path in plugins {
+        let name = get_plugin_name(path);
+        match run_plugin(path, input.as_bytes()) {
+            Ok(plugin_diagnostics) => {
+                for diagnostic in plugin_diagnostics {
+                    let level = match diagnostic.level.as_deref() {
+                        Some("error") => Level::Error,
+                        Some("note") => Level::Note,
+                        _ => Level::Warning,
+                    };
+                    let spans = match diagnostic.line {
+                        0 => vec![],
+                        line => vec![Span {
+                            start_line: line,
+                            start_column: diagnostic.column,
+                            end_line: line,
+                            end_column: diagnostic.column,
+                        }],
+                    };
+                    diagnostics.push(Diagnostic {
+                        level,
+                        message: format!("[{}] {}", name, diagnostic.message),
+                        spans,
+                        suggestion: None,
+                    });
+                }
+            }
+            Err(e) => diagnostics.push(Diagnostic {
+                level: Level::Error,
+                message: format!("analysis plugin {} failed: {}", name, e),
+                spans: vec![],
+                suggestion: None,
+            }),
+        }
+    }
+    diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_plugin_name() {
+        assert_eq!(get_plugin_name("./lints/naming.wasm"), "naming");
+        assert_eq!(get_plugin_name("C:\\lints\\naming.wasm"), "naming");
+        assert_eq!(get_plugin_name("naming"), "naming");
+    }
+}
@@ -0,0 +1,221 @@
+//! Registry of the analysis passes that can be enabled in `[repl.analysis] passes`.
+//! Besides the built-in passes, other crates can register their own `AnalysisPass`
+//! implementations under a name, along with the passes they depend on.
+
+use std::collections::BTreeSet;
+use std::sync::Mutex;
+
+use clarity::vm::analysis::analysis_db::AnalysisDatabase;
+use clarity::vm::analysis::types::ContractAnalysis;
+
+use super::annotation::Annotation;
+use super::check_checker::CheckChecker;
+use super::iteration_cost::IterationCostChecker;
+use super::style::StyleChecker;
+use super::unchecked_response::UncheckedResponseChecker;
+use super::{get_all_passes, AnalysisPass, AnalysisResult, Pass, Settings};
+
+pub type AnalysisPassFn = fn(
+    &mut ContractAnalysis,
+    &mut AnalysisDatabase,
+    &Vec<Annotation>,
+    settings: &Settings,
+) -> AnalysisResult;
+
+#[derive(Clone)]
+pub struct PassRegistration {
+    pub name: String,
+    pub run: AnalysisPassFn,
+    /// Passes enabled along with this one, and run before it
+    pub dependencies: Vec<String>,
+}
+
+impl PassRegistration {
+    pub fn new<P: AnalysisPass>(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            run: P::run_pass,
+            dependencies: vec![],
+        }
+    }
+
+    pub fn depends_on(mut self, name: &str) -> Self {
+        self.dependencies.push(name.to_string());
+        self
+    }
+}
+
+lazy_static! {
+    static ref CUSTOM_PASSES: Mutex<Vec<PassRegistration>> = Mutex::new(vec![]);
+}
+
+/// The call checker always runs, and can't be enabled or overridden
+const RESERVED_NAMES: [&str; 2] = ["all", "call_checker"];
+
+fn get_builtin_pass(name: &str) -> Option<PassRegistration> {
+    match name {
+        "check_checker" => Some(PassRegistration::new::<CheckChecker>(name)),
+        "style" => Some(PassRegistration::new::<StyleChecker>(name)),
+        "unchecked_response" => Some(PassRegistration::new::<UncheckedResponseChecker>(name)),
+        "iteration_cost" => Some(PassRegistration::new::<IterationCostChecker>(name)),
+        _ => None,
+    }
+}
+
+pub fn register_pass(registration: PassRegistration) -> Result<(), String> {
+    if RESERVED_NAMES.contains(&registration.name.as_str())
+        || get_builtin_pass(&registration.name).is_some()
+    {
+        return Err(format!(
+            "analysis pass name '{}' is reserved",
+            registration.name
+        ));
+    }
+    let mut passes = CUSTOM_PASSES.lock().unwrap();
+    if passes.iter().any(|pass| pass.name == registration.name) {
+        return Err(format!(
+            "analysis pass '{}' is already registered",
+            registration.name
+        ));
+    }
+    passes.push(registration);
+    Ok(())
+}
+
+pub fn get_custom_pass_names() -> Vec<String> {
+    let passes = CUSTOM_PASSES.lock().unwrap();
+    passes.iter().map(|pass| pass.name.clone()).collect()
+}
+
+fn get_pass(name: &str) -> Option<PassRegistration> {
+    get_builtin_pass(name).or_else(|| {
+        let passes = CUSTOM_PASSES.lock().unwrap();
+        passes.iter().find(|pass| pass.name == name).cloned()
+    })
+}
+
+fn visit_pass(
+    name: &str,
+    visiting: &mut Vec<String>,
+    visited: &mut BTreeSet<String>,
+    resolved: &mut Vec<PassRegistration>,
+) -> Result<(), String> {
+    if visited.contains(name) {
+        return Ok(());
+    }
+    if let Some(index) = visiting.iter().position(|pass| pass == name) {
+        let mut cycle = visiting[index..].to_vec();
+        cycle.push(name.to_string());
+        return Err(format!(
+            "circular dependency between analysis passes: {}",
+            cycle.join(" -> ")
+        ));
+    }
+    let pass = match get_pass(name) {
+        Some(pass) => pass,
+        None => {
+            let passes = get_all_passes();
+            let known: Vec<&str> = passes.iter().map(|pass| pass.get_name()).collect();
+            return Err(format!(
+                "unknown analysis pass '{}' (available passes: {})",
+                name,
+                known.join(", ")
+            ));
+        }
+    };
+    visiting.push(name.to_string());
+    for dependency in pass.dependencies.iter() {
+        visit_pass(dependency, visiting, visited, resolved)?;
+    }
+    visiting.pop();
+    visited.insert(name.to_string());
+    resolved.push(pass);
+    Ok(())
+}
+
+/// Passes to run, in order: each pass runs after its dependencies, which are enabled
+/// even if they're not listed
+pub fn resolve_passes(passes: &[Pass]) -> Result<Vec<PassRegistration>, String> {
+    let mut resolved = vec![];
+    let mut visited = BTreeSet::new();
+    for pass in passes {
+        if let Pass::All = pass {
+            return Err("unexpected All in list of passes".to_string());
+        }
+        visit_pass(pass.get_name(), &mut vec![], &mut visited, &mut resolved)?;
+    }
+    Ok(resolved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct NoopPass;
+
+    impl AnalysisPass for NoopPass {
+        fn run_pass(
+            _contract_analysis: &mut ContractAnalysis,
+            _analysis_db: &mut AnalysisDatabase,
+            _annotations: &Vec<Annotation>,
+            _settings: &Settings,
+        ) -> AnalysisResult {
+            Ok(vec![])
+        }
+    }
+
+    fn get_names(passes: &[PassRegistration]) -> Vec<&str> {
+        passes.iter().map(|pass| pass.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_resolve_passes_with_dependencies() {
+        register_pass(
+            PassRegistration::new::<NoopPass>("test_dependent").depends_on("test_dependency"),
+        )
+        .unwrap();
+        register_pass(
+            PassRegistration::new::<NoopPass>("test_dependency").depends_on("check_checker"),
+        )
+        .unwrap();
+
+        let passes = resolve_passes(&[Pass::Custom("test_dependent".to_string())]).unwrap();
+        assert_eq!(
+            get_names(&passes),
+            vec!["check_checker", "test_dependency", "test_dependent"]
+        );
+
+        let passes = resolve_passes(&[
+            Pass::CheckChecker,
+            Pass::Custom("test_dependent".to_string()),
+        ])
+        .unwrap();
+        assert_eq!(
+            get_names(&passes),
+            vec!["check_checker", "test_dependency", "test_dependent"]
+        );
+    }
+
+    #[test]
+    fn test_register_pass_errors() {
+        assert!(register_pass(PassRegistration::new::<NoopPass>("check_checker")).is_err());
+        register_pass(PassRegistration::new::<NoopPass>("test_duplicate")).unwrap();
+        assert!(register_pass(PassRegistration::new::<NoopPass>("test_duplicate")).is_err());
+    }
+
+    #[test]
+    fn test_resolve_passes_errors() {
+        register_pass(PassRegistration::new::<NoopPass>("test_cycle_a").depends_on("test_cycle_b"))
+            .unwrap();
+        register_pass(PassRegistration::new::<NoopPass>("test_cycle_b").depends_on("test_cycle_a"))
+            .unwrap();
+        let err = resolve_passes(&[Pass::Custom("test_cycle_a".to_string())]).unwrap_err();
+        assert_eq!(
+            err,
+            "circular dependency between analysis passes: test_cycle_a -> test_cycle_b -> test_cycle_a"
+        );
+
+        let err = resolve_passes(&[Pass::Custom("test_unknown".to_string())]).unwrap_err();
+        assert!(err.starts_with("unknown analysis pass 'test_unknown'"));
+    }
+}
@@ -0,0 +1,340 @@
+#![allow(unused_variables)]
+
+use crate::analysis::annotation::{Annotation, AnnotationKind};
+use crate::analysis::ast_visitor::{traverse, ASTVisitor, TypedVar};
+use crate::analysis::{self, AnalysisPass, AnalysisResult};
+use clarity::vm::analysis::analysis_db::AnalysisDatabase;
+use clarity::vm::analysis::types::ContractAnalysis;
+use clarity::vm::diagnostic::{Diagnostic, Level};
+use clarity::vm::representations::Span;
+use clarity::vm::{ClarityName, SymbolicExpression};
+use regex::Regex;
+
+lazy_static! {
+    static ref KEBAB_CASE: Regex = Regex::new(r"^[a-z][a-z0-9]*(-[a-z0-9]+)*[?!]?$").unwrap();
+}
+
+const DEFAULT_MAX_FUNCTION_LINES: u32 = 50;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Settings {
+    // Functions, parameters, data-vars and maps are named in kebab-case
+    kebab_case: bool,
+    // Constants holding an `(err ...)` value are prefixed with `ERR-`
+    error_constants: bool,
+    // Read-only functions are prefixed with `get-`
+    read_only_prefix: bool,
+    // Functions are at most `max_function_lines` long
+    function_length: bool,
+    max_function_lines: u32,
+    // Report `TODO` and `FIXME` comments
+    todo_markers: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            kebab_case: true,
+            error_constants: true,
+            read_only_prefix: true,
+            function_length: true,
+            max_function_lines: DEFAULT_MAX_FUNCTION_LINES,
+            todo_markers: true,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
+pub struct SettingsFile {
+    kebab_case: Option<bool>,
+    error_constants: Option<bool>,
+    read_only_prefix: Option<bool>,
+    function_length: Option<bool>,
+    max_function_lines: Option<u32>,
+    todo_markers: Option<bool>,
+}
+
+impl From<SettingsFile> for Settings {
+    fn from(from_file: SettingsFile) -> Self {
+        let default = Settings::default();
+        Settings {
+            kebab_case: from_file.kebab_case.unwrap_or(default.kebab_case),
+            error_constants: from_file.error_constants.unwrap_or(default.error_constants),
+            read_only_prefix: from_file
+                .read_only_prefix
+                .unwrap_or(default.read_only_prefix),
+            function_length: from_file.function_length.unwrap_or(default.function_length),
+            max_function_lines: from_file
+                .max_function_lines
+                .unwrap_or(default.max_function_lines),
+            todo_markers: from_file.todo_markers.unwrap_or(default.todo_markers),
+        }
+    }
+}
+
+pub struct StyleChecker<'a> {
+    settings: Settings,
+    annotations: &'a Vec<Annotation>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'a> StyleChecker<'a> {
+    fn new(annotations: &'a Vec<Annotation>, settings: Settings) -> StyleChecker<'a> {
+        Self {
+            settings,
+            annotations,
+            diagnostics: Vec::new(),
+        }
+    }
+
+    fn run(mut self, contract_analysis: &'a ContractAnalysis) -> AnalysisResult {
+        traverse(&mut self, &contract_analysis.expressions);
+
+        if self.settings.todo_markers {
+            for annotation in self.annotations.iter() {
+                if let AnnotationKind::Todo(comment) = &annotation.kind {
+                    self.add_warning(format!("unresolved marker: {}", comment), &annotation.span);
+                }
+            }
+        }
+
+        self.diagnostics.sort_by(|a, b| a.spans[0].cmp(&b.spans[0]));
+        Ok(self.diagnostics)
+    }
+
+    fn add_warning(&mut self, message: String, span: &Span) {
+        self.diagnostics.push(Diagnostic {
+            level: Level::Warning,
+            message,
+            spans: vec![span.clone()],
+            suggestion: None,
+        });
+    }
+
+    fn check_kebab_case(&mut self, kind: &str, name: &ClarityName, span: &Span) {
+        if self.settings.kebab_case && !KEBAB_CASE.is_match(name.as_str()) {
+            self.add_warning(
+                format!("{} '{}' should be named in kebab-case", kind, name),
+                span,
+            );
+        }
+    }
+
+    fn check_function(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        name: &'a ClarityName,
+        parameters: &Option<Vec<TypedVar<'a>>>,
+    ) {
+        let name_span = get_function_name_span(expr);
+        self.check_kebab_case("function", name, &name_span);
+        for parameter in parameters.iter().flatten() {
+            self.check_kebab_case("parameter", parameter.name, &parameter.decl_span);
+        }
+
+        let lines = expr.span.end_line.saturating_sub(expr.span.start_line) + 1;
+        if self.settings.function_length && lines > self.settings.max_function_lines {
+            self.add_warning(
+                format!(
+                    "function '{}' is {} lines long (maximum: {})",
+                    name, lines, self.settings.max_function_lines
+                ),
+                &name_span,
+            );
+        }
+    }
+}
+
+/// Span of the name of a `define-*` form, or of the whole form if it's malformed
+fn get_name_span(expr: &SymbolicExpression) -> Span {
+    expr.match_list()
+        .and_then(|list| list.get(1))
+        .map_or(expr.span.clone(), |name| name.span.clone())
+}
+
+/// Span of the name in the signature of a function
+fn get_function_name_span(expr: &SymbolicExpression) -> Span {
+    expr.match_list()
+        .and_then(|list| list.get(1))
+        .and_then(|signature| signature.match_list())
+        .and_then(|signature| signature.first())
+        .map_or(expr.span.clone(), |name| name.span.clone())
+}
+
+fn is_error_value(value: &SymbolicExpression) -> bool {
+    value
+        .match_list()
+        .and_then(|list| list.first())
+        .and_then(|function| function.match_atom())
+        .is_some_and(|function| function.as_str() == "err")
+}
+
+impl<'a> ASTVisitor<'a> for StyleChecker<'a> {
+    fn visit_define_constant(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        name: &'a ClarityName,
+        value: &'a SymbolicExpression,
+    ) -> bool {
+        if self.settings.error_constants
+            && is_error_value(value)
+            && !name.as_str().to_uppercase().starts_with("ERR-")
+        {
+            self.add_warning(
+                format!("error constant '{}' should be prefixed with ERR-", name),
+                &get_name_span(expr),
+            );
+        }
+        true
+    }
+
+    fn visit_define_private(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        name: &'a ClarityName,
+        parameters: Option<Vec<TypedVar<'a>>>,
+        body: &'a SymbolicExpression,
+    ) -> bool {
+        self.check_function(expr, name, &parameters);
+        true
+    }
+
+    fn visit_define_public(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        name: &'a ClarityName,
+        parameters: Option<Vec<TypedVar<'a>>>,
+        body: &'a SymbolicExpression,
+    ) -> bool {
+        self.check_function(expr, name, &parameters);
+        true
+    }
+
+    fn visit_define_read_only(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        name: &'a ClarityName,
+        parameters: Option<Vec<TypedVar<'a>>>,
+        body: &'a SymbolicExpression,
+    ) -> bool {
+        self.check_function(expr, name, &parameters);
+        if self.settings.read_only_prefix && !name.as_str().starts_with("get-") {
+            self.add_warning(
+                format!("read-only function '{}' should be prefixed with get-", name),
+                &get_function_name_span(expr),
+            );
+        }
+        true
+    }
+
+    fn visit_define_data_var(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        name: &'a ClarityName,
+        data_type: &'a SymbolicExpression,
+        initial: &'a SymbolicExpression,
+    ) -> bool {
+        self.check_kebab_case("data-var", name, &get_name_span(expr));
+        true
+    }
+
+    fn visit_define_map(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        name: &'a ClarityName,
+        key_type: &'a SymbolicExpression,
+        value_type: &'a SymbolicExpression,
+    ) -> bool {
+        self.check_kebab_case("map", name, &get_name_span(expr));
+        true
+    }
+}
+
+impl AnalysisPass for StyleChecker<'_> {
+    fn run_pass(
+        contract_analysis: &mut ContractAnalysis,
+        analysis_db: &mut AnalysisDatabase,
+        annotations: &Vec<Annotation>,
+        settings: &analysis::Settings,
+    ) -> AnalysisResult {
+        let checker = StyleChecker::new(annotations, settings.style);
+        checker.run(contract_analysis)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analysis::Pass;
+    use crate::repl::session::Session;
+    use crate::repl::SessionSettings;
+
+    fn get_messages(snippet: &str) -> Vec<String> {
+        let mut settings = SessionSettings::default();
+        settings.repl_settings.analysis.passes = vec![Pass::Style];
+        let mut session = Session::new(settings);
+        match session.formatted_interpretation(
+            snippet.to_string(),
+            Some("style".to_string()),
+            false,
+            None,
+        ) {
+            Ok((_, result)) => result
+                .diagnostics
+                .into_iter()
+                .map(|diagnostic| diagnostic.message)
+                .collect(),
+            _ => panic!("Expected successful interpretation"),
+        }
+    }
+
+    #[test]
+    fn naming_conventions() {
+        let snippet = "
+(define-constant not-authorized (err u100))
+(define-constant ERR-NOT-FOUND (err u101))
+(define-data-var totalSupply uint u0)
+(define-map balances principal uint)
+(define-read-only (balance-of (who principal))
+    (default-to u0 (map-get? balances who))
+)
+(define-public (set_supply (newSupply uint))
+    (ok (var-set totalSupply newSupply))
+)";
+        assert_eq!(
+            get_messages(snippet),
+            vec![
+                "error constant 'not-authorized' should be prefixed with ERR-",
+                "data-var 'totalSupply' should be named in kebab-case",
+                "read-only function 'balance-of' should be prefixed with get-",
+                "function 'set_supply' should be named in kebab-case",
+                "parameter 'newSupply' should be named in kebab-case",
+            ]
+        );
+    }
+
+    #[test]
+    fn todo_markers_and_function_length() {
+        let mut body = String::new();
+        for _ in 0..50 {
+            body.push_str("        (var-set counter (+ (var-get counter) u1))\n");
+        }
+        let snippet = format!(
+            "
+(define-data-var counter uint u0)
+;; TODO: make this configurable
+(define-public (increment-many)
+    (begin
+{}        (ok true)
+    )
+)",
+            body
+        );
+        assert_eq!(
+            get_messages(&snippet),
+            vec![
+                "unresolved marker: TODO: make this configurable",
+                "function 'increment-many' is 55 lines long (maximum: 50)",
+            ]
+        );
+    }
+}
@@ -0,0 +1,229 @@
+#![allow(unused_variables)]
+
+use std::collections::HashSet;
+
+use crate::analysis::annotation::Annotation;
+use crate::analysis::ast_visitor::{traverse, ASTVisitor};
+use crate::analysis::{self, AnalysisPass, AnalysisResult};
+use clarity::vm::analysis::analysis_db::AnalysisDatabase;
+use clarity::vm::analysis::types::ContractAnalysis;
+use clarity::vm::diagnostic::{Diagnostic, Level};
+use clarity::vm::types::{FunctionType, TypeSignature};
+use clarity::vm::{ClarityName, SymbolicExpression};
+
+/// Native functions returning a response
+const RESPONSE_FUNCTIONS: [&str; 10] = [
+    "contract-call?",
+    "stx-transfer?",
+    "stx-transfer-memo?",
+    "stx-burn?",
+    "ft-transfer?",
+    "ft-mint?",
+    "ft-burn?",
+    "nft-transfer?",
+    "nft-mint?",
+    "nft-burn?",
+];
+
+pub struct UncheckedResponseChecker<'a> {
+    // User-defined functions returning a response
+    response_funcs: HashSet<&'a ClarityName>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'a> UncheckedResponseChecker<'a> {
+    fn new(contract_analysis: &'a ContractAnalysis) -> UncheckedResponseChecker<'a> {
+        let response_funcs = contract_analysis
+            .public_function_types
+            .iter()
+            .chain(contract_analysis.read_only_function_types.iter())
+            .chain(contract_analysis.private_function_types.iter())
+            .filter_map(|(name, function_type)| match function_type {
+                FunctionType::Fixed(function) => match function.returns {
+                    TypeSignature::ResponseType(_) => Some(name),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect();
+        Self {
+            response_funcs,
+            diagnostics: Vec::new(),
+        }
+    }
+
+    fn run(mut self, contract_analysis: &'a ContractAnalysis) -> AnalysisResult {
+        traverse(&mut self, &contract_analysis.expressions);
+        self.diagnostics.sort_by(|a, b| a.spans[0].cmp(&b.spans[0]));
+        Ok(self.diagnostics)
+    }
+
+    /// Name of the function called by `expr`, if it returns a response. Responses of
+    /// `as-contract` are those of the expression it wraps.
+    fn get_response_call(&self, expr: &'a SymbolicExpression) -> Option<&'a str> {
+        let list = expr.match_list()?;
+        let function = list.first()?.match_atom()?;
+        if function.as_str() == "as-contract" {
+            return self.get_response_call(list.get(1)?);
+        }
+        if RESPONSE_FUNCTIONS.contains(&function.as_str())
+            || self.response_funcs.contains(&function)
+        {
+            return Some(function.as_str());
+        }
+        None
+    }
+
+    fn add_warning(&mut self, expr: &SymbolicExpression, message: String, suggestion: String) {
+        self.diagnostics.push(Diagnostic {
+            level: Level::Warning,
+            message,
+            spans: vec![expr.span.clone()],
+            suggestion: Some(suggestion),
+        });
+    }
+
+    /// `(ok (stx-transfer? ...))` succeeds even if the transfer fails
+    fn check_wrapped(&mut self, wrapper: &str, value: &'a SymbolicExpression) {
+        if let Some(function) = self.get_response_call(value) {
+            self.add_warning(
+                value,
+                format!(
+                    "response of {} is wrapped in {}, an error would be ignored",
+                    function, wrapper
+                ),
+                format!(
+                    "unwrap the response first: ({} (try! ({} ...)))",
+                    wrapper, function
+                ),
+            );
+        }
+    }
+
+    /// Statements whose value is dropped, such as an `(is-ok (contract-call? ...))`
+    /// that is not the last expression of a `begin`
+    fn check_statements(&mut self, statements: &'a [SymbolicExpression]) {
+        let Some((_, dropped)) = statements.split_last() else {
+            return;
+        };
+        for statement in dropped {
+            let Some(list) = statement.match_list() else {
+                continue;
+            };
+            let (Some(check), Some(value)) = (
+                list.first().and_then(|function| function.match_atom()),
+                list.get(1),
+            ) else {
+                continue;
+            };
+            if check.as_str() != "is-ok" && check.as_str() != "is-err" {
+                continue;
+            }
+            if let Some(function) = self.get_response_call(value) {
+                self.add_warning(
+                    statement,
+                    format!(
+                        "result of {} is not used, the response of {} is ignored",
+                        check, function
+                    ),
+                    format!("propagate the error: (try! ({} ...))", function),
+                );
+            }
+        }
+    }
+}
+
+impl<'a> ASTVisitor<'a> for UncheckedResponseChecker<'a> {
+    fn visit_begin(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        statements: &'a [SymbolicExpression],
+    ) -> bool {
+        self.check_statements(statements);
+        true
+    }
+
+    fn visit_let(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        bindings: &std::collections::HashMap<&'a ClarityName, &'a SymbolicExpression>,
+        body: &'a [SymbolicExpression],
+    ) -> bool {
+        self.check_statements(body);
+        true
+    }
+
+    fn visit_ok(&mut self, expr: &'a SymbolicExpression, value: &'a SymbolicExpression) -> bool {
+        self.check_wrapped("ok", value);
+        true
+    }
+
+    fn visit_some(&mut self, expr: &'a SymbolicExpression, value: &'a SymbolicExpression) -> bool {
+        self.check_wrapped("some", value);
+        true
+    }
+}
+
+impl AnalysisPass for UncheckedResponseChecker<'_> {
+    fn run_pass(
+        contract_analysis: &mut ContractAnalysis,
+        analysis_db: &mut AnalysisDatabase,
+        annotations: &Vec<Annotation>,
+        settings: &analysis::Settings,
+    ) -> AnalysisResult {
+        let checker = UncheckedResponseChecker::new(contract_analysis);
+        checker.run(contract_analysis)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analysis::Pass;
+    use crate::repl::session::Session;
+    use crate::repl::SessionSettings;
+
+    #[test]
+    fn unchecked_responses() {
+        let mut settings = SessionSettings::default();
+        settings.repl_settings.analysis.passes = vec![Pass::UncheckedResponse];
+        let mut session = Session::new(settings);
+        let snippet = "
+(define-private (pay (amount uint))
+    (stx-transfer? amount tx-sender (as-contract tx-sender))
+)
+(define-public (deposit (amount uint))
+    (begin
+        (is-ok (pay amount))
+        (ok (as-contract (stx-transfer? amount tx-sender contract-caller)))
+    )
+)
+(define-public (checked (amount uint))
+    (begin
+        (try! (pay amount))
+        (ok (is-ok (pay amount)))
+    )
+)"
+        .to_string();
+        match session.formatted_interpretation(snippet, Some("checker".to_string()), false, None) {
+            Ok((output, result)) => {
+                let messages: Vec<&str> = result
+                    .diagnostics
+                    .iter()
+                    .map(|diagnostic| diagnostic.message.as_str())
+                    .collect();
+                assert_eq!(
+                    messages,
+                    vec![
+                        "result of is-ok is not used, the response of pay is ignored",
+                        "response of stx-transfer? is wrapped in ok, an error would be ignored",
+                    ]
+                );
+                assert_eq!(
+                    output[3],
+                    format!("{} propagate the error: (try! (pay ...))", blue!("help:"))
+                );
+            }
+            _ => panic!("Expected successful interpretation"),
+        };
+    }
+}
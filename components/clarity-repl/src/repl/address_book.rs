@@ -0,0 +1,202 @@
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+
+use clarity::vm::types::{PrincipalData, QualifiedContractIdentifier};
+use clarity::vm::ContractName;
+
+/// Principals referred to by name (`@deployer`, `@treasury`, `@sbtc-token`) in the plans,
+/// the manifests and the console, instead of their address. A book holds the principals of
+/// a single network. A name can be followed by a contract name when it designates a standard
+/// principal: `@deployer.counter`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AddressBook {
+    principals: BTreeMap<String, String>,
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '-' || c == '_'
+}
+
+impl AddressBook {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Names an existing principal, replacing the principal previously registered under
+    /// this name
+    pub fn insert(&mut self, name: &str, principal: &str) -> Result<(), String> {
+        if name.is_empty() || !name.chars().all(is_name_char) {
+            return Err(format!("invalid principal name: {}", name));
+        }
+        if PrincipalData::parse(principal).is_err() {
+            return Err(format!("@{}: invalid principal {}", name, principal));
+        }
+        self.principals
+            .insert(name.to_string(), principal.to_string());
+        Ok(())
+    }
+
+    /// Adds the principals of another book, they take precedence over the ones of this book
+    pub fn extend(&mut self, other: &AddressBook) {
+        for (name, principal) in other.principals.iter() {
+            self.principals.insert(name.clone(), principal.clone());
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.principals.get(name).map(|p| p.as_str())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &String)> {
+        self.principals.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.principals.is_empty()
+    }
+
+    /// Returns the principal designated by `@name` or `@name.contract`, other principals
+    /// are returned unchanged
+    pub fn resolve_principal(&self, principal: &str) -> Result<String, String> {
+        let Some(reference) = principal.strip_prefix('@') else {
+            return Ok(principal.to_string());
+        };
+        let (name, contract_name) = match reference.split_once('.') {
+            Some((name, contract_name)) => (name, Some(contract_name)),
+            None => (reference, None),
+        };
+        let resolved = self
+            .principals
+            .get(name)
+            .ok_or(format!("unknown principal @{}", name))?;
+        match contract_name {
+            None => Ok(resolved.clone()),
+            Some(contract_name) => {
+                if resolved.contains('.') {
+                    return Err(format!(
+                        "@{} is a contract, it can't be followed by a contract name",
+                        name
+                    ));
+                }
+                ContractName::try_from(contract_name.to_string())
+                    .map_err(|_| format!("invalid contract name: {}", contract_name))?;
+                Ok(format!("{}.{}", resolved, contract_name))
+            }
+        }
+    }
+
+    /// Returns the principal, or the contract identifier, designated by `@name.contract`
+    pub fn resolve_contract_id(&self, contract_id: &str) -> Result<String, String> {
+        let contract_id = self.resolve_principal(contract_id)?;
+        QualifiedContractIdentifier::parse(&contract_id)
+            .map_err(|_| format!("invalid contract identifier: {}", contract_id))?;
+        Ok(contract_id)
+    }
+
+    /// Replaces the principal literals using a name (`'@treasury`) in a Clarity expression.
+    /// Strings and comments are left as is.
+    pub fn resolve_expression(&self, expression: &str) -> Result<String, String> {
+        if !expression.contains("'@") {
+            return Ok(expression.to_string());
+        }
+        let mut resolved = String::with_capacity(expression.len());
+        let mut chars = expression.char_indices().peekable();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '"' => {
+                    resolved.push(c);
+                    let mut escaped = false;
+                    for (_, c) in chars.by_ref() {
+                        resolved.push(c);
+                        match c {
+                            '\\' if !escaped => escaped = true,
+                            '"' if !escaped => break,
+                            _ => escaped = false,
+                        }
+                    }
+                }
+                ';' if matches!(chars.peek(), Some((_, ';'))) => {
+                    resolved.push(c);
+                    for (_, c) in chars.by_ref() {
+                        resolved.push(c);
+                        if c == '\n' {
+                            break;
+                        }
+                    }
+                }
+                '\'' if matches!(chars.peek(), Some((_, '@'))) => {
+                    let start = i + 2;
+                    let mut end = start;
+                    chars.next();
+                    while let Some((j, c)) = chars.peek() {
+                        if !is_name_char(*c) && *c != '.' {
+                            break;
+                        }
+                        end = j + c.len_utf8();
+                        chars.next();
+                    }
+                    let reference = &expression[start - 1..end];
+                    resolved.push('\'');
+                    resolved.push_str(&self.resolve_principal(reference)?);
+                }
+                c => resolved.push(c),
+            }
+        }
+        Ok(resolved)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_address_book() -> AddressBook {
+        let mut address_book = AddressBook::new();
+        address_book
+            .insert("deployer", "ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM")
+            .unwrap();
+        address_book
+            .insert(
+                "sbtc-token",
+                "SM3VDXK3WZZSA84XXFKAFAF15NNZX32CTSG82JFQ4.sbtc-token",
+            )
+            .unwrap();
+        address_book
+    }
+
+    #[test]
+    fn it_resolves_named_principals() {
+        let address_book = get_address_book();
+        assert_eq!(
+            address_book.resolve_principal("@deployer"),
+            Ok("ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM".to_string())
+        );
+        assert_eq!(
+            address_book.resolve_principal("@deployer.counter"),
+            Ok("ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM.counter".to_string())
+        );
+        assert_eq!(
+            address_book.resolve_principal("ST000000000000000000002AMW42H"),
+            Ok("ST000000000000000000002AMW42H".to_string())
+        );
+        assert!(address_book.resolve_principal("@treasury").is_err());
+        assert!(address_book.resolve_principal("@sbtc-token.vault").is_err());
+        assert!(address_book.insert("treasury", "not-a-principal").is_err());
+    }
+
+    #[test]
+    fn it_resolves_named_principals_in_expressions() {
+        let address_book = get_address_book();
+        assert_eq!(
+            address_book.resolve_expression(
+                "(contract-call? '@sbtc-token transfer u1 tx-sender '@deployer none) ;; '@treasury"
+            ),
+            Ok("(contract-call? 'SM3VDXK3WZZSA84XXFKAFAF15NNZX32CTSG82JFQ4.sbtc-token transfer u1 tx-sender 'ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM none) ;; '@treasury".to_string())
+        );
+        assert_eq!(
+            address_book.resolve_expression("(print \"'@treasury\")"),
+            Ok("(print \"'@treasury\")".to_string())
+        );
+        assert!(address_book.resolve_expression("'@treasury").is_err());
+    }
+}
@@ -0,0 +1,105 @@
+//! Support matrix of the Clarity versions: the epoch each version is introduced in, the
+//! versions a contract of an epoch can use. The manifest validation, the ordering of the
+//! deployment plans and the on-chain epoch checks all read this table, which lists every
+//! Clarity version clarinet knows.
+
+use clarity::types::StacksEpochId;
+use clarity::vm::ClarityVersion;
+
+use super::EpochSpec;
+
+/// The Clarity versions, with the epoch introducing them, in order
+pub const CLARITY_VERSIONS: [(ClarityVersion, EpochSpec); 3] = [
+    (ClarityVersion::Clarity1, EpochSpec::Epoch2_0),
+    (ClarityVersion::Clarity2, EpochSpec::Epoch2_1),
+    (ClarityVersion::Clarity3, EpochSpec::Epoch3_0),
+];
+
+/// The Clarity versions a contract deployed in `epoch` can use, oldest first
+pub fn supported_versions(epoch: StacksEpochId) -> Vec<ClarityVersion> {
+    CLARITY_VERSIONS
+        .iter()
+        .filter(|(_, min_epoch)| StacksEpochId::from(*min_epoch) <= epoch)
+        .map(|(version, _)| *version)
+        .collect()
+}
+
+/// The most recent Clarity version available in `epoch`
+pub fn latest_version(epoch: StacksEpochId) -> ClarityVersion {
+    supported_versions(epoch)
+        .pop()
+        .unwrap_or(ClarityVersion::Clarity1)
+}
+
+/// The first epoch supporting a Clarity version
+pub fn min_epoch(clarity_version: ClarityVersion) -> EpochSpec {
+    CLARITY_VERSIONS
+        .iter()
+        .find(|(version, _)| *version == clarity_version)
+        .map(|(_, epoch)| *epoch)
+        .unwrap_or(EpochSpec::LATEST)
+}
+
+pub fn is_supported(epoch: StacksEpochId, clarity_version: ClarityVersion) -> bool {
+    supported_versions(epoch).contains(&clarity_version)
+}
+
+/// The number of a Clarity version, as written in Clarinet.toml and in deployment plans
+pub fn version_number(clarity_version: ClarityVersion) -> u8 {
+    CLARITY_VERSIONS
+        .iter()
+        .position(|(version, _)| *version == clarity_version)
+        .map(|index| index as u8 + 1)
+        .unwrap_or_else(|| unreachable!("{} is missing from the support matrix", clarity_version))
+}
+
+/// The Clarity version of a number of Clarinet.toml or of a deployment plan
+pub fn from_version_number(number: u8) -> Option<ClarityVersion> {
+    let index = usize::from(number).checked_sub(1)?;
+    CLARITY_VERSIONS.get(index).map(|(version, _)| *version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_support_matrix() {
+        assert_eq!(
+            CLARITY_VERSIONS.last().map(|(version, _)| *version),
+            Some(ClarityVersion::latest())
+        );
+        for epoch in EpochSpec::ALL {
+            let epoch = StacksEpochId::from(epoch);
+            assert_eq!(
+                latest_version(epoch),
+                ClarityVersion::default_for_epoch(epoch)
+            );
+        }
+        for (version, epoch) in CLARITY_VERSIONS {
+            assert!(is_supported(epoch.into(), version));
+            assert_eq!(min_epoch(version), epoch);
+        }
+
+        assert_eq!(
+            supported_versions(StacksEpochId::Epoch31),
+            vec![
+                ClarityVersion::Clarity1,
+                ClarityVersion::Clarity2,
+                ClarityVersion::Clarity3
+            ]
+        );
+        assert_eq!(
+            supported_versions(StacksEpochId::Epoch2_05),
+            vec![ClarityVersion::Clarity1]
+        );
+        assert!(!is_supported(
+            StacksEpochId::Epoch25,
+            ClarityVersion::Clarity3
+        ));
+        assert_eq!(version_number(ClarityVersion::Clarity2), 2);
+        assert_eq!(from_version_number(3), Some(ClarityVersion::Clarity3));
+        assert_eq!(from_version_number(0), None);
+        assert_eq!(from_version_number(4), None);
+    }
+}
@@ -21,9 +21,11 @@ use pox_locking::handle_contract_call_special_cases;
 use sha2::{Digest, Sha512_256};
 
 use super::interpreter::BLOCK_LIMIT_MAINNET;
+use super::session_image::{ClarityDatastoreImage, DatastoreImage, StacksBlockImage};
+use super::storage::{InMemoryStorage, StorageBackend, StorageBackendKind};
 
-const SECONDS_BETWEEN_BURN_BLOCKS: u64 = 600;
-const SECONDS_BETWEEN_STACKS_BLOCKS: u64 = 10;
+pub const SECONDS_BETWEEN_BURN_BLOCKS: u64 = 600;
+pub const SECONDS_BETWEEN_STACKS_BLOCKS: u64 = 10;
 
 fn epoch_to_peer_version(epoch: StacksEpochId) -> u8 {
     use clarity::consts::*;
@@ -41,15 +43,11 @@ fn epoch_to_peer_version(epoch: StacksEpochId) -> u8 {
     }
 }
 
-#[derive(Clone, Debug)]
-struct StoreEntry(StacksBlockId, String);
-
 #[derive(Clone, Debug)]
 pub struct ClarityDatastore {
     open_chain_tip: StacksBlockId,
     current_chain_tip: StacksBlockId,
-    store: HashMap<String, Vec<StoreEntry>>,
-    metadata: HashMap<(String, String), String>,
+    storage: Box<dyn StorageBackend>,
     block_id_lookup: HashMap<StacksBlockId, StacksBlockId>,
     height_at_chain_tip: HashMap<StacksBlockId, u32>,
 }
@@ -90,6 +88,17 @@ pub struct Datastore {
     current_epoch: StacksEpochId,
     current_epoch_start_height: u32,
     constants: StacksConstants,
+    /// Burn block header hashes set by tests, by burn block height
+    burn_block_header_hashes: HashMap<u32, BurnchainHeaderHash>,
+    /// PoX payout addresses and amounts set by tests, by burn block height
+    pox_payout_addrs: HashMap<u32, (Vec<TupleData>, u128)>,
+}
+
+fn parse_hex<T>(
+    hex: &str,
+    parse: impl Fn(&str) -> std::result::Result<T, clarity::util::HexError>,
+) -> std::result::Result<T, String> {
+    parse(hex).map_err(|e| format!("invalid hash {} in the session image: {:?}", hex, e))
 }
 
 fn height_to_hashed_bytes(height: u32) -> [u8; 32] {
@@ -118,17 +127,24 @@ impl Default for ClarityDatastore {
 
 impl ClarityDatastore {
     pub fn new() -> Self {
+        Self::with_storage(Box::new(InMemoryStorage::default()))
+    }
+
+    pub fn with_storage(storage: Box<dyn StorageBackend>) -> Self {
         let id = height_to_id(0);
         Self {
             open_chain_tip: id,
             current_chain_tip: id,
-            store: HashMap::new(),
-            metadata: HashMap::new(),
+            storage,
             block_id_lookup: HashMap::from([(id, id)]),
             height_at_chain_tip: HashMap::from([(id, 0)]),
         }
     }
 
+    pub fn with_storage_backend(kind: &StorageBackendKind) -> std::result::Result<Self, String> {
+        Ok(Self::with_storage(kind.open()?))
+    }
+
     pub fn open(_path_str: &str, _miner_tip: Option<&StacksBlockId>) -> Result<ClarityDatastore> {
         Ok(ClarityDatastore::new())
     }
@@ -181,31 +197,124 @@ impl ClarityDatastore {
     }
 
     fn put(&mut self, key: &str, value: &str) {
-        if let Some(entries) = self.store.get_mut(key) {
-            entries.push(StoreEntry(self.open_chain_tip, value.to_string()));
-        } else {
-            self.store.insert(
-                key.to_string(),
-                vec![StoreEntry(self.open_chain_tip, value.to_string())],
-            );
-        }
+        self.storage.push_entry(key, self.open_chain_tip, value);
+    }
+
+    /// Latest version of a key written at or before the current chain tip. The versions
+    /// written at a past block (see `write_at_block`) are ordered by height, not by insertion.
+    fn get_latest_data(&self, key: &str) -> Option<String> {
+        let current_height = *self.height_at_chain_tip.get(&self.current_chain_tip)?;
+        self.storage
+            .get_entries(key)
+            .into_iter()
+            .filter_map(|(tip, value)| {
+                let height = *self.height_at_chain_tip.get(&tip)?;
+                (height <= current_height).then_some((height, value))
+            })
+            .max_by_key(|(height, _)| *height)
+            .map(|(_, value)| value)
     }
 
-    fn get_latest_data(&self, data: &[StoreEntry]) -> Option<String> {
-        let StoreEntry(tip, value) = data.last()?;
-
-        if self.height_at_chain_tip.get(tip)?
-            <= self.height_at_chain_tip.get(&self.current_chain_tip)?
-        {
-            Some(value.clone())
-        } else {
-            self.get_latest_data(&data[..data.len() - 1])
-        }
+    /// Runs `f` with its writes recorded at `block_id` rather than at the open chain tip, to
+    /// fabricate the state read by `at-block` at this block and the next ones, up to the
+    /// next version of each key.
+    pub fn write_at_block<T>(
+        &mut self,
+        block_id: StacksBlockId,
+        f: impl FnOnce(&mut ClarityDatastore) -> T,
+    ) -> T {
+        let open_chain_tip = self.open_chain_tip;
+        self.open_chain_tip = block_id;
+        let result = f(self);
+        self.open_chain_tip = open_chain_tip;
+        result
+    }
+
+    /// Returns the latest value of every key starting with `prefix`, at the current chain tip
+    pub fn get_latest_entries_with_prefix(&self, prefix: &str) -> Vec<(String, String)> {
+        let mut entries: Vec<(String, String)> = self
+            .storage
+            .get_keys_with_prefix(prefix)
+            .into_iter()
+            .filter_map(|key| {
+                let value = self.get_latest_data(&key)?;
+                Some((key, value))
+            })
+            .collect();
+        entries.sort();
+        entries
     }
 
     pub fn make_contract_hash_key(contract: &QualifiedContractIdentifier) -> String {
         format!("clarity-contract::{}", contract)
     }
+
+    pub(crate) fn to_image(&self) -> ClarityDatastoreImage {
+        let entries = self
+            .storage
+            .get_keys_with_prefix("")
+            .into_iter()
+            .map(|key| {
+                let versions = self
+                    .storage
+                    .get_entries(&key)
+                    .into_iter()
+                    .map(|(block_id, value)| (block_id.to_hex(), value))
+                    .collect();
+                (key, versions)
+            })
+            .collect();
+        ClarityDatastoreImage {
+            open_chain_tip: self.open_chain_tip.to_hex(),
+            current_chain_tip: self.current_chain_tip.to_hex(),
+            block_id_lookup: self
+                .block_id_lookup
+                .iter()
+                .map(|(id, lookup_id)| (id.to_hex(), lookup_id.to_hex()))
+                .collect(),
+            height_at_chain_tip: self
+                .height_at_chain_tip
+                .iter()
+                .map(|(id, height)| (id.to_hex(), *height))
+                .collect(),
+            entries,
+            metadata: self.storage.get_metadata_entries(),
+        }
+    }
+
+    /// Writes the entries of the image in the storage, which is expected to be empty
+    pub(crate) fn import_image(
+        &mut self,
+        image: &ClarityDatastoreImage,
+    ) -> std::result::Result<(), String> {
+        for (key, versions) in image.entries.iter() {
+            for (block_id, value) in versions.iter() {
+                let block_id = parse_hex(block_id, StacksBlockId::from_hex)?;
+                self.storage.push_entry(key, block_id, value);
+            }
+        }
+        for (contract, key, value) in image.metadata.iter() {
+            self.storage.insert_metadata(contract, key, value);
+        }
+        self.open_chain_tip = parse_hex(&image.open_chain_tip, StacksBlockId::from_hex)?;
+        self.current_chain_tip = parse_hex(&image.current_chain_tip, StacksBlockId::from_hex)?;
+        self.block_id_lookup = image
+            .block_id_lookup
+            .iter()
+            .map(|(id, lookup_id)| {
+                Ok((
+                    parse_hex(id, StacksBlockId::from_hex)?,
+                    parse_hex(lookup_id, StacksBlockId::from_hex)?,
+                ))
+            })
+            .collect::<std::result::Result<_, String>>()?;
+        self.height_at_chain_tip = image
+            .height_at_chain_tip
+            .iter()
+            .map(|(id, height)| Ok((parse_hex(id, StacksBlockId::from_hex)?, *height)))
+            .collect::<std::result::Result<_, String>>()?;
+        Ok(())
+    }
 }
 
 impl ClarityBackingStore for ClarityDatastore {
@@ -218,10 +327,7 @@ impl ClarityBackingStore for ClarityDatastore {
 
     /// fetch K-V out of the committed datastore
     fn get_data(&mut self, key: &str) -> Result<Option<String>> {
-        match self.store.get(key) {
-            Some(data) => Ok(self.get_latest_data(data)),
-            None => Ok(None),
-        }
+        Ok(self.get_latest_data(key))
     }
 
     fn get_data_from_path(&mut self, _hash: &TrieHash) -> Result<Option<String>> {
@@ -289,8 +395,8 @@ impl ClarityBackingStore for ClarityDatastore {
         key: &str,
         value: &str,
     ) -> Result<()> {
-        self.metadata
-            .insert((contract.to_string(), key.to_string()), value.to_string());
+        self.storage
+            .insert_metadata(&contract.to_string(), key, value);
         Ok(())
     }
 
@@ -299,12 +405,7 @@ impl ClarityBackingStore for ClarityDatastore {
         contract: &QualifiedContractIdentifier,
         key: &str,
     ) -> Result<Option<String>> {
-        let key = &(contract.to_string(), key.to_string());
-
-        match self.metadata.get(key) {
-            Some(result) => Ok(Some(result.to_string())),
-            None => Ok(None),
-        }
+        Ok(self.storage.get_metadata(&contract.to_string(), key))
     }
 
     fn get_contract_hash(
@@ -384,6 +485,8 @@ impl Datastore {
             current_epoch: StacksEpochId::Epoch2_05,
             current_epoch_start_height: 0,
             constants,
+            burn_block_header_hashes: HashMap::new(),
+            pox_payout_addrs: HashMap::new(),
         }
     }
 
@@ -399,6 +502,15 @@ impl Datastore {
         self.burn_chain_height
     }
 
+    pub fn get_constants(&self) -> &StacksConstants {
+        &self.constants
+    }
+
+    /// Index block hash (`id-header-hash`) of a mined Stacks block
+    pub fn get_stacks_block_id(&self, height: u32) -> Option<StacksBlockId> {
+        (height <= self.stacks_chain_height).then(|| height_to_id(height))
+    }
+
     fn build_next_stacks_block(&self, clarity_datastore: &ClarityDatastore) -> StacksBlockInfo {
         let burn_chain_height = self.burn_chain_height;
         let stacks_block_height = self.stacks_chain_height;
@@ -540,6 +652,145 @@ impl Datastore {
             self.advance_burn_chain_tip(clarity_datastore, 1);
         }
     }
+
+    /// Overrides the header hash returned by `get-burn-block-info?` for a burn block height
+    pub fn set_burn_block_header_hash(&mut self, height: u32, header_hash: BurnchainHeaderHash) {
+        self.burn_block_header_hashes.insert(height, header_hash);
+    }
+
+    /// Overrides the PoX payouts returned by `get-burn-block-info?` for a burn block height.
+    /// Addresses are `{ version: (buff 1), hashbytes: (buff 32) }` tuples.
+    pub fn set_pox_payout_addrs(&mut self, height: u32, addrs: Vec<TupleData>, payout: u128) {
+        self.pox_payout_addrs.insert(height, (addrs, payout));
+    }
+
+    pub(crate) fn to_image(&self) -> DatastoreImage {
+        DatastoreImage {
+            genesis_id: self.genesis_id.to_hex(),
+            burn_chain_height: self.burn_chain_height,
+            burn_blocks: self
+                .burn_blocks
+                .iter()
+                .map(|(hash, block)| {
+                    (
+                        hash.to_hex(),
+                        (block.burn_block_time, block.burn_block_height),
+                    )
+                })
+                .collect(),
+            stacks_chain_height: self.stacks_chain_height,
+            stacks_blocks: self
+                .stacks_blocks
+                .iter()
+                .map(|(id, block)| {
+                    let block = StacksBlockImage {
+                        block_header_hash: block.block_header_hash.to_hex(),
+                        burn_block_header_hash: block.burn_block_header_hash.to_hex(),
+                        consensus_hash: block.consensus_hash.to_hex(),
+                        vrf_seed: block.vrf_seed.to_hex(),
+                        stacks_block_time: block.stacks_block_time,
+                    };
+                    (id.to_hex(), block)
+                })
+                .collect(),
+            sortition_lookup: self
+                .sortition_lookup
+                .iter()
+                .map(|(sortition_id, id)| (sortition_id.to_hex(), id.to_hex()))
+                .collect(),
+            tenure_blocks_height: self
+                .tenure_blocks_height
+                .iter()
+                .map(|(burn_height, height)| (*burn_height, *height))
+                .collect(),
+            consensus_hash_lookup: self
+                .consensus_hash_lookup
+                .iter()
+                .map(|(hash, sortition_id)| (hash.to_hex(), sortition_id.to_hex()))
+                .collect(),
+            current_epoch: self.current_epoch,
+            current_epoch_start_height: self.current_epoch_start_height,
+            burn_start_height: self.constants.burn_start_height,
+            pox_prepare_length: self.constants.pox_prepare_length,
+            pox_reward_cycle_length: self.constants.pox_reward_cycle_length,
+            pox_rejection_fraction: self.constants.pox_rejection_fraction,
+            burn_block_header_hashes: self
+                .burn_block_header_hashes
+                .iter()
+                .map(|(height, hash)| (*height, hash.to_hex()))
+                .collect(),
+            pox_payout_addrs: self
+                .pox_payout_addrs
+                .iter()
+                .map(|(height, payouts)| (*height, payouts.clone()))
+                .collect(),
+        }
+    }
+
+    pub(crate) fn from_image(image: &DatastoreImage) -> std::result::Result<Self, String> {
+        let mut burn_blocks = HashMap::new();
+        for (hash, (burn_block_time, burn_block_height)) in image.burn_blocks.iter() {
+            let block = BurnBlockInfo {
+                burn_block_time: *burn_block_time,
+                burn_block_height: *burn_block_height,
+            };
+            burn_blocks.insert(parse_hex(hash, BurnchainHeaderHash::from_hex)?, block);
+        }
+        let mut stacks_blocks = HashMap::new();
+        for (id, block) in image.stacks_blocks.iter() {
+            let block = StacksBlockInfo {
+                block_header_hash: parse_hex(&block.block_header_hash, BlockHeaderHash::from_hex)?,
+                burn_block_header_hash: parse_hex(
+                    &block.burn_block_header_hash,
+                    BurnchainHeaderHash::from_hex,
+                )?,
+                consensus_hash: parse_hex(&block.consensus_hash, ConsensusHash::from_hex)?,
+                vrf_seed: parse_hex(&block.vrf_seed, VRFSeed::from_hex)?,
+                stacks_block_time: block.stacks_block_time,
+            };
+            stacks_blocks.insert(parse_hex(id, StacksBlockId::from_hex)?, block);
+        }
+        let mut sortition_lookup = HashMap::new();
+        for (sortition_id, id) in image.sortition_lookup.iter() {
+            sortition_lookup.insert(
+                parse_hex(sortition_id, SortitionId::from_hex)?,
+                parse_hex(id, StacksBlockId::from_hex)?,
+            );
+        }
+        let mut consensus_hash_lookup = HashMap::new();
+        for (hash, sortition_id) in image.consensus_hash_lookup.iter() {
+            consensus_hash_lookup.insert(
+                parse_hex(hash, ConsensusHash::from_hex)?,
+                parse_hex(sortition_id, SortitionId::from_hex)?,
+            );
+        }
+        let mut burn_block_header_hashes = HashMap::new();
+        for (height, hash) in image.burn_block_header_hashes.iter() {
+            burn_block_header_hashes
+                .insert(*height, parse_hex(hash, BurnchainHeaderHash::from_hex)?);
+        }
+
+        Ok(Datastore {
+            genesis_id: parse_hex(&image.genesis_id, StacksBlockId::from_hex)?,
+            burn_chain_height: image.burn_chain_height,
+            burn_blocks,
+            stacks_chain_height: image.stacks_chain_height,
+            stacks_blocks,
+            sortition_lookup,
+            tenure_blocks_height: image.tenure_blocks_height.clone().into_iter().collect(),
+            consensus_hash_lookup,
+            current_epoch: image.current_epoch,
+            current_epoch_start_height: image.current_epoch_start_height,
+            constants: StacksConstants {
+                burn_start_height: image.burn_start_height,
+                pox_prepare_length: image.pox_prepare_length,
+                pox_reward_cycle_length: image.pox_reward_cycle_length,
+                pox_rejection_fraction: image.pox_rejection_fraction,
+            },
+            burn_block_header_hashes,
+            pox_payout_addrs: image.pox_payout_addrs.clone().into_iter().collect(),
+        })
+    }
 }
 
 impl HeadersDB for Datastore {
@@ -722,9 +973,12 @@ impl BurnStateDB for Datastore {
     /// Returns Some if `self.get_burn_start_height() <= height < self.get_burn_block_height(sorition_id)`, and None otherwise.
     fn get_burn_header_hash(
         &self,
-        _height: u32,
+        height: u32,
         sortition_id: &SortitionId,
     ) -> Option<BurnchainHeaderHash> {
+        if let Some(header_hash) = self.burn_block_header_hashes.get(&height) {
+            return Some(*header_hash);
+        }
         self.sortition_lookup
             .get(sortition_id)
             .and_then(|id| self.stacks_blocks.get(id))
@@ -763,6 +1017,9 @@ impl BurnStateDB for Datastore {
         height: u32,
         _sortition_id: &SortitionId,
     ) -> Option<(Vec<TupleData>, u128)> {
+        if let Some(payout) = self.pox_payout_addrs.get(&height) {
+            return Some(payout.clone());
+        }
         if height <= self.burn_chain_height {
             Some((vec![], 0))
         } else {
@@ -778,6 +1035,7 @@ impl BurnStateDB for Datastore {
 #[cfg(test)]
 mod tests {
     use clarity::types::StacksEpoch;
+    use clarity::vm::Value;
 
     use super::*;
 
@@ -789,6 +1047,74 @@ mod tests {
         assert_eq!(datastore.stacks_chain_height, 5);
     }
 
+    #[test]
+    fn test_burn_block_info_overrides() {
+        let mut datastore = Datastore::default();
+        let mut clarity_datastore = ClarityDatastore::new();
+        datastore.advance_burn_chain_tip(&mut clarity_datastore, 5);
+        let sortition_id = SortitionId(height_to_hashed_bytes(5));
+
+        let header_hash = BurnchainHeaderHash([0x01; 32]);
+        datastore.set_burn_block_header_hash(3, header_hash);
+        assert_eq!(
+            datastore.get_burn_header_hash(3, &sortition_id),
+            Some(header_hash)
+        );
+        assert_ne!(
+            datastore.get_burn_header_hash(4, &sortition_id),
+            Some(header_hash)
+        );
+
+        let addr = TupleData::from_data(vec![
+            ("version".into(), Value::buff_from_byte(0x04)),
+            (
+                "hashbytes".into(),
+                Value::buff_from(vec![0x02; 20]).unwrap(),
+            ),
+        ])
+        .unwrap();
+        datastore.set_pox_payout_addrs(3, vec![addr.clone()], 1000);
+        assert_eq!(
+            datastore.get_pox_payout_addrs(3, &sortition_id),
+            Some((vec![addr], 1000))
+        );
+        assert_eq!(
+            datastore.get_pox_payout_addrs(4, &sortition_id),
+            Some((vec![], 0))
+        );
+    }
+
+    #[test]
+    fn test_write_at_block() {
+        let mut datastore = Datastore::default();
+        let mut clarity_datastore = ClarityDatastore::new();
+        datastore.advance_burn_chain_tip(&mut clarity_datastore, 2);
+        clarity_datastore.put("key", "a");
+        datastore.advance_burn_chain_tip(&mut clarity_datastore, 4);
+        clarity_datastore.put("key", "c");
+        assert_eq!(datastore.get_stacks_block_id(7), None);
+
+        let block_4 = datastore.get_stacks_block_id(4).unwrap();
+        clarity_datastore.write_at_block(block_4, |store| store.put("key", "b"));
+        assert_eq!(
+            clarity_datastore.get_latest_data("key"),
+            Some("c".to_string())
+        );
+
+        let mut read_at = |height: u32| {
+            let tip = clarity_datastore.open_chain_tip;
+            let block_id = datastore.get_stacks_block_id(height).unwrap();
+            clarity_datastore.set_block_hash(block_id).unwrap();
+            let value = clarity_datastore.get_data("key").unwrap();
+            clarity_datastore.set_block_hash(tip).unwrap();
+            value
+        };
+        assert_eq!(read_at(1), None);
+        assert_eq!(read_at(3), Some("a".to_string()));
+        assert_eq!(read_at(5), Some("b".to_string()));
+        assert_eq!(read_at(6), Some("c".to_string()));
+    }
+
     #[test]
     fn test_set_current_epoch() {
         let mut datastore = Datastore::default();
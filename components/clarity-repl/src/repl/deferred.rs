@@ -0,0 +1,148 @@
+//! Contracts published by analysis only (`requirements_execution = "lazy"` or `"disabled"`):
+//! their analysis is saved so that the contracts depending on them type-check, and the session
+//! executes them once a call or a contract references them.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use clarity::vm::analysis::ContractAnalysis;
+use clarity::vm::ast::ContractAST;
+use clarity::vm::representations::SymbolicExpressionType::{
+    AtomValue, Field, List, LiteralValue, TraitReference,
+};
+use clarity::vm::representations::{SymbolicExpression, TraitDefinition};
+use clarity::vm::types::{
+    FunctionType, PrincipalData, QualifiedContractIdentifier, StandardPrincipalData,
+};
+use clarity::vm::Value;
+
+use super::ClarityContract;
+
+#[derive(Clone, Debug)]
+pub struct DeferredContract {
+    pub contract: ClarityContract,
+    pub ast: ContractAST,
+    pub analysis: ContractAnalysis,
+    /// tx-sender of the publish, the deployer of the contract
+    pub sender: StandardPrincipalData,
+}
+
+fn collect_referenced_contracts(
+    expression: &SymbolicExpression,
+    contracts: &mut BTreeSet<QualifiedContractIdentifier>,
+) {
+    match &expression.expr {
+        LiteralValue(Value::Principal(PrincipalData::Contract(contract_id)))
+        | AtomValue(Value::Principal(PrincipalData::Contract(contract_id))) => {
+            contracts.insert(contract_id.clone());
+        }
+        Field(trait_id) | TraitReference(_, TraitDefinition::Imported(trait_id)) => {
+            contracts.insert(trait_id.contract_identifier.clone());
+        }
+        List(expressions) => {
+            for expression in expressions.iter() {
+                collect_referenced_contracts(expression, contracts);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Contracts the expressions refer to: called, passed as arguments, or defining a trait
+pub fn get_referenced_contracts(
+    expressions: &[SymbolicExpression],
+) -> BTreeSet<QualifiedContractIdentifier> {
+    let mut contracts = BTreeSet::new();
+    for expression in expressions.iter() {
+        collect_referenced_contracts(expression, &mut contracts);
+    }
+    contracts
+}
+
+/// Contracts the code executed as a contract is published refers to: its top-level
+/// expressions, without the bodies of its functions and its traits
+pub fn get_top_level_referenced_contracts(
+    expressions: &[SymbolicExpression],
+) -> BTreeSet<QualifiedContractIdentifier> {
+    let executed: Vec<SymbolicExpression> = expressions
+        .iter()
+        .filter(|expression| {
+            let name = expression
+                .match_list()
+                .and_then(|list| list.first())
+                .and_then(|first| first.match_atom());
+            !matches!(
+                name.map(|name| name.as_str()),
+                Some(
+                    "define-public"
+                        | "define-private"
+                        | "define-read-only"
+                        | "define-trait"
+                        | "use-trait"
+                        | "impl-trait"
+                )
+            )
+        })
+        .cloned()
+        .collect();
+    get_referenced_contracts(&executed)
+}
+
+/// Arguments of the public functions, as the session lists them for the executed contracts
+pub fn get_public_functions_args(analysis: &ContractAnalysis) -> BTreeMap<String, Vec<String>> {
+    analysis
+        .public_function_types
+        .iter()
+        .filter_map(|(name, function_type)| match function_type {
+            FunctionType::Fixed(function) => Some((
+                name.to_string(),
+                function
+                    .args
+                    .iter()
+                    .map(|arg| format!("({} {})", arg.name, arg.signature))
+                    .collect(),
+            )),
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use clarity::types::StacksEpochId;
+    use clarity::vm::ast::build_ast_with_diagnostics;
+    use clarity::vm::ClarityVersion;
+
+    use super::*;
+
+    #[test]
+    fn test_referenced_contracts() {
+        let contract_id =
+            QualifiedContractIdentifier::parse("ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM.vault")
+                .unwrap();
+        let source = "(use-trait token-trait 'SP3FBR2AGK5H9QBDH3EEN6DF8EK8JY7RX8QJ5SVTE.sip-010-trait.sip-010-trait)
+(define-public (deposit (token <token-trait>) (amount uint))
+  (begin
+    (try! (contract-call? token transfer amount tx-sender (as-contract tx-sender) none))
+    (contract-call? .ledger credit tx-sender amount)))";
+        let (ast, _, success) = build_ast_with_diagnostics(
+            &contract_id,
+            source,
+            &mut (),
+            ClarityVersion::Clarity2,
+            StacksEpochId::Epoch25,
+        );
+        assert!(success);
+        let referenced: Vec<String> = get_referenced_contracts(&ast.expressions)
+            .iter()
+            .map(|contract_id| contract_id.to_string())
+            .collect();
+        assert_eq!(
+            referenced,
+            vec![
+                "SP3FBR2AGK5H9QBDH3EEN6DF8EK8JY7RX8QJ5SVTE.sip-010-trait",
+                "ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM.ledger",
+            ]
+        );
+        assert!(get_top_level_referenced_contracts(&ast.expressions).is_empty());
+    }
+}
@@ -29,6 +29,9 @@ pub fn output_diagnostic(diagnostic: &Diagnostic, name: &str, lines: &[String])
         ));
     }
     output.append(&mut output_code(diagnostic, lines));
+    if let Some(suggestion) = &diagnostic.suggestion {
+        output.push(format!("{} {}", blue!("help:"), suggestion));
+    }
     output
 }
 
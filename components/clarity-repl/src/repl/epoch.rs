@@ -0,0 +1,245 @@
+use std::fmt;
+use std::str::FromStr;
+
+use clarity::types::StacksEpochId;
+use serde::de::{self, Deserializer, Visitor};
+use serde::Deserialize;
+
+/// The epochs contracts can be deployed in, as written in Clarinet.toml and in deployment plans.
+/// They are serialized as strings (`"2.4"`) and parsed from strings or numbers (`2.4`, `3`).
+#[derive(Serialize, PartialEq, Debug, Clone, Copy, Eq, PartialOrd, Ord)]
+pub enum EpochSpec {
+    #[serde(rename = "2.0")]
+    Epoch2_0,
+    #[serde(rename = "2.05")]
+    Epoch2_05,
+    #[serde(rename = "2.1")]
+    Epoch2_1,
+    #[serde(rename = "2.2")]
+    Epoch2_2,
+    #[serde(rename = "2.3")]
+    Epoch2_3,
+    #[serde(rename = "2.4")]
+    Epoch2_4,
+    #[serde(rename = "2.5")]
+    Epoch2_5,
+    #[serde(rename = "3.0")]
+    Epoch3_0,
+    #[serde(rename = "3.1")]
+    Epoch3_1,
+}
+
+impl EpochSpec {
+    pub const ALL: [EpochSpec; 9] = [
+        EpochSpec::Epoch2_0,
+        EpochSpec::Epoch2_05,
+        EpochSpec::Epoch2_1,
+        EpochSpec::Epoch2_2,
+        EpochSpec::Epoch2_3,
+        EpochSpec::Epoch2_4,
+        EpochSpec::Epoch2_5,
+        EpochSpec::Epoch3_0,
+        EpochSpec::Epoch3_1,
+    ];
+
+    pub const LATEST: EpochSpec = EpochSpec::Epoch3_1;
+
+    /// The supported epochs, as listed in the parsing errors
+    pub fn get_supported_list() -> String {
+        EpochSpec::ALL.map(|spec| spec.as_str()).join(", ")
+    }
+
+    /// Parses an epoch of Clarinet.toml. An epoch more recent than the ones this version of
+    /// clarinet knows is read as the latest one, with a warning, so that a project written for a
+    /// newer clarinet can still be checked.
+    pub fn parse_forward_compatible(epoch: &str) -> Result<(EpochSpec, Option<String>), String> {
+        match EpochSpec::from_str(epoch) {
+            Ok(spec) => Ok((spec, None)),
+            Err(e) => match epoch.trim().parse::<f64>() {
+                Ok(value) if value > EpochSpec::LATEST.as_f64() => Ok((
+                    EpochSpec::LATEST,
+                    Some(format!(
+                        "epoch {} is not supported by this version of clarinet, epoch {} is used instead",
+                        epoch.trim(),
+                        EpochSpec::LATEST
+                    )),
+                )),
+                _ => Err(e),
+            },
+        }
+    }
+
+    fn as_f64(&self) -> f64 {
+        self.as_str().parse().unwrap()
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EpochSpec::Epoch2_0 => "2.0",
+            EpochSpec::Epoch2_05 => "2.05",
+            EpochSpec::Epoch2_1 => "2.1",
+            EpochSpec::Epoch2_2 => "2.2",
+            EpochSpec::Epoch2_3 => "2.3",
+            EpochSpec::Epoch2_4 => "2.4",
+            EpochSpec::Epoch2_5 => "2.5",
+            EpochSpec::Epoch3_0 => "3.0",
+            EpochSpec::Epoch3_1 => "3.1",
+        }
+    }
+}
+
+impl fmt::Display for EpochSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for EpochSpec {
+    type Err = String;
+
+    /// Also accepts the numeric forms of the epochs ("3", "3.00", "2.50"), which is how
+    /// TOML and YAML floats such as `3.0` are displayed
+    fn from_str(epoch: &str) -> Result<Self, Self::Err> {
+        let epoch = epoch.trim();
+        let value = epoch.parse::<f64>().ok();
+        if let Some(spec) = EpochSpec::ALL
+            .into_iter()
+            .find(|spec| spec.as_str() == epoch || Some(spec.as_f64()) == value)
+        {
+            return Ok(spec);
+        }
+
+        let mut message = format!(
+            "unsupported epoch {} (supported: {})",
+            epoch,
+            EpochSpec::get_supported_list()
+        );
+        if let Some(value) = value {
+            let nearest = EpochSpec::ALL
+                .into_iter()
+                .min_by(|a, b| {
+                    (a.as_f64() - value)
+                        .abs()
+                        .total_cmp(&(b.as_f64() - value).abs())
+                })
+                .unwrap();
+            message.push_str(&format!(", did you mean {}?", nearest));
+        }
+        Err(message)
+    }
+}
+
+struct EpochSpecVisitor;
+
+impl<'de> Visitor<'de> for EpochSpecVisitor {
+    type Value = EpochSpec;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "an epoch ({})", EpochSpec::get_supported_list())
+    }
+
+    fn visit_str<E: de::Error>(self, epoch: &str) -> Result<EpochSpec, E> {
+        EpochSpec::from_str(epoch).map_err(E::custom)
+    }
+
+    fn visit_f64<E: de::Error>(self, epoch: f64) -> Result<EpochSpec, E> {
+        self.visit_str(&epoch.to_string())
+    }
+
+    fn visit_u64<E: de::Error>(self, epoch: u64) -> Result<EpochSpec, E> {
+        self.visit_str(&epoch.to_string())
+    }
+
+    fn visit_i64<E: de::Error>(self, epoch: i64) -> Result<EpochSpec, E> {
+        self.visit_str(&epoch.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for EpochSpec {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<EpochSpec, D::Error> {
+        deserializer.deserialize_any(EpochSpecVisitor)
+    }
+}
+
+impl From<StacksEpochId> for EpochSpec {
+    fn from(epoch: StacksEpochId) -> Self {
+        match epoch {
+            StacksEpochId::Epoch20 => EpochSpec::Epoch2_0,
+            StacksEpochId::Epoch2_05 => EpochSpec::Epoch2_05,
+            StacksEpochId::Epoch21 => EpochSpec::Epoch2_1,
+            StacksEpochId::Epoch22 => EpochSpec::Epoch2_2,
+            StacksEpochId::Epoch23 => EpochSpec::Epoch2_3,
+            StacksEpochId::Epoch24 => EpochSpec::Epoch2_4,
+            StacksEpochId::Epoch25 => EpochSpec::Epoch2_5,
+            StacksEpochId::Epoch30 => EpochSpec::Epoch3_0,
+            StacksEpochId::Epoch31 => EpochSpec::Epoch3_1,
+            StacksEpochId::Epoch10 => unreachable!("epoch 1.0 is not supported"),
+        }
+    }
+}
+
+impl From<EpochSpec> for StacksEpochId {
+    fn from(val: EpochSpec) -> Self {
+        match val {
+            EpochSpec::Epoch2_0 => StacksEpochId::Epoch20,
+            EpochSpec::Epoch2_05 => StacksEpochId::Epoch2_05,
+            EpochSpec::Epoch2_1 => StacksEpochId::Epoch21,
+            EpochSpec::Epoch2_2 => StacksEpochId::Epoch22,
+            EpochSpec::Epoch2_3 => StacksEpochId::Epoch23,
+            EpochSpec::Epoch2_4 => StacksEpochId::Epoch24,
+            EpochSpec::Epoch2_5 => StacksEpochId::Epoch25,
+            EpochSpec::Epoch3_0 => StacksEpochId::Epoch30,
+            EpochSpec::Epoch3_1 => StacksEpochId::Epoch31,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_epoch_spec_round_trip() {
+        for spec in EpochSpec::ALL {
+            assert_eq!(EpochSpec::from_str(spec.as_str()), Ok(spec));
+            assert_eq!(EpochSpec::from(StacksEpochId::from(spec)), spec);
+            let json = serde_json::to_string(&spec).unwrap();
+            assert_eq!(serde_json::from_str::<EpochSpec>(&json).unwrap(), spec);
+            // epochs are stored as floats in Clarinet.toml
+            let float: f64 = spec.as_str().parse().unwrap();
+            assert_eq!(EpochSpec::from_str(&float.to_string()), Ok(spec));
+        }
+        assert!(EpochSpec::from_str("1.0").is_err());
+    }
+
+    #[test]
+    fn test_epoch_spec_parsing() {
+        assert_eq!(EpochSpec::from_str(" 3.00"), Ok(EpochSpec::Epoch3_0));
+        assert_eq!(EpochSpec::from_str("2.50"), Ok(EpochSpec::Epoch2_5));
+        assert_eq!(
+            EpochSpec::from_str("2.6"),
+            Err(
+                "unsupported epoch 2.6 (supported: 2.0, 2.05, 2.1, 2.2, 2.3, 2.4, 2.5, 3.0, 3.1), did you mean 2.5?"
+                    .to_string()
+            )
+        );
+        assert!(!EpochSpec::from_str("latest")
+            .unwrap_err()
+            .contains("did you mean"));
+
+        for json in ["\"2.4\"", "2.4", "3"] {
+            assert!(serde_json::from_str::<EpochSpec>(json).is_ok());
+        }
+        assert!(serde_json::from_str::<EpochSpec>("\"2.6\"").is_err());
+        assert!(serde_json::from_str::<EpochSpec>("true").is_err());
+
+        assert_eq!(
+            EpochSpec::parse_forward_compatible("3.1"),
+            Ok((EpochSpec::Epoch3_1, None))
+        );
+        let (spec, warning) = EpochSpec::parse_forward_compatible("3.2").unwrap();
+        assert_eq!(spec, EpochSpec::LATEST);
+        assert!(warning.unwrap().contains("epoch 3.2 is not supported"));
+        assert!(EpochSpec::parse_forward_compatible("1.0").is_err());
+    }
+}
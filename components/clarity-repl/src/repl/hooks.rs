@@ -0,0 +1,251 @@
+//! Instrumentation of the executions of a session: a `SessionHook` registered on a session
+//! sees every contract call start and end, with its result and cost, and the events emitted.
+//! A hook can also evaluate the expressions as an `EvalHook`, the way the coverage and the
+//! profiler do, and reject calls before they are executed.
+
+use std::fmt;
+
+use clarity::vm::events::StacksTransactionEvent;
+use clarity::vm::types::QualifiedContractIdentifier;
+use clarity::vm::{CostSynthesis, EvalHook, ExecutionResult, SymbolicExpression};
+
+use crate::analysis::coverage::CoverageHook;
+use crate::repl::profiler::ProfilerHook;
+
+pub trait SessionHook: Send {
+    /// Called before a contract call is executed, an error rejects the call
+    fn on_call_start(
+        &mut self,
+        _contract_id: &QualifiedContractIdentifier,
+        _function: &str,
+        _args: &[SymbolicExpression],
+    ) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Called once a contract call is executed, `cost` is only set when costs are tracked
+    fn on_call_end(
+        &mut self,
+        _contract_id: &QualifiedContractIdentifier,
+        _function: &str,
+        _result: Result<&ExecutionResult, &str>,
+        _cost: Option<&CostSynthesis>,
+    ) {
+    }
+
+    /// Called for each event emitted by the executions of the session
+    fn on_event(&mut self, _event: &StacksTransactionEvent) {}
+
+    /// Whether the executions must track their costs while the hook is registered
+    fn track_costs(&self) -> bool {
+        false
+    }
+
+    /// Hook called on the evaluation of each expression
+    fn as_eval_hook(&mut self) -> Option<&mut dyn EvalHook> {
+        None
+    }
+}
+
+impl SessionHook for CoverageHook {
+    fn as_eval_hook(&mut self) -> Option<&mut dyn EvalHook> {
+        Some(self)
+    }
+}
+
+impl SessionHook for ProfilerHook {
+    fn track_costs(&self) -> bool {
+        true
+    }
+
+    fn as_eval_hook(&mut self) -> Option<&mut dyn EvalHook> {
+        Some(self)
+    }
+}
+
+/// Hooks registered on a session. They observe the session they are registered on: a clone of
+/// the session starts without hooks.
+#[derive(Default)]
+pub struct SessionHooks {
+    hooks: Vec<Box<dyn SessionHook>>,
+}
+
+impl SessionHooks {
+    pub fn register(&mut self, hook: Box<dyn SessionHook>) {
+        self.hooks.push(hook);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hooks.is_empty()
+    }
+
+    pub fn track_costs(&self) -> bool {
+        self.hooks.iter().any(|hook| hook.track_costs())
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut dyn SessionHook> {
+        self.hooks
+            .iter_mut()
+            .map(|hook| hook.as_mut() as &mut dyn SessionHook)
+    }
+
+    /// Asks each hook whether the call can be executed, the first rejection is returned
+    pub fn start_call(
+        &mut self,
+        contract_id: &QualifiedContractIdentifier,
+        function: &str,
+        args: &[SymbolicExpression],
+    ) -> Result<(), String> {
+        for hook in self.hooks.iter_mut() {
+            hook.on_call_start(contract_id, function, args)
+                .map_err(|e| format!("call of {}::{} rejected: {}", contract_id, function, e))?;
+        }
+        Ok(())
+    }
+
+    pub fn end_call(
+        &mut self,
+        contract_id: &QualifiedContractIdentifier,
+        function: &str,
+        result: Result<&ExecutionResult, &str>,
+    ) {
+        if let Ok(execution) = result {
+            self.emit_events(execution);
+        }
+        let cost = result.ok().and_then(|execution| execution.cost.as_ref());
+        for hook in self.hooks.iter_mut() {
+            hook.on_call_end(contract_id, function, result, cost);
+        }
+    }
+
+    pub fn emit_events(&mut self, execution: &ExecutionResult) {
+        for event in execution.events.iter() {
+            for hook in self.hooks.iter_mut() {
+                hook.on_event(event);
+            }
+        }
+    }
+}
+
+impl Clone for SessionHooks {
+    fn clone(&self) -> Self {
+        SessionHooks::default()
+    }
+}
+
+impl fmt::Debug for SessionHooks {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SessionHooks({})", self.hooks.len())
+    }
+}
+
+/// The evaluation hooks of the coverage, of the profiler and of the registered hooks, in this
+/// order
+pub fn get_eval_hooks<'a>(
+    coverage_hook: &'a mut Option<CoverageHook>,
+    profiler_hook: &'a mut Option<ProfilerHook>,
+    hooks: &'a mut SessionHooks,
+) -> Vec<&'a mut dyn EvalHook> {
+    let coverage_hook = coverage_hook
+        .iter_mut()
+        .map(|hook| hook as &mut dyn SessionHook);
+    let profiler_hook = profiler_hook
+        .iter_mut()
+        .map(|hook| hook as &mut dyn SessionHook);
+    coverage_hook
+        .chain(profiler_hook)
+        .chain(hooks.iter_mut())
+        .filter_map(|hook| hook.as_eval_hook())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::repl::{Session, SessionSettings, DEFAULT_EPOCH};
+    use crate::test_fixtures::clarity_contract::ClarityContractBuilder;
+
+    /// Logs the calls and the events, and rejects the calls of `incr` once `locked` is set
+    struct PolicyHook {
+        log: Arc<Mutex<Vec<String>>>,
+        locked: bool,
+    }
+
+    impl SessionHook for PolicyHook {
+        fn on_call_start(
+            &mut self,
+            _contract_id: &QualifiedContractIdentifier,
+            function: &str,
+            _args: &[SymbolicExpression],
+        ) -> Result<(), String> {
+            self.log.lock().unwrap().push(format!("start {}", function));
+            if self.locked && function == "incr" {
+                return Err("the contract is locked".to_string());
+            }
+            Ok(())
+        }
+
+        fn on_call_end(
+            &mut self,
+            _contract_id: &QualifiedContractIdentifier,
+            function: &str,
+            result: Result<&ExecutionResult, &str>,
+            cost: Option<&CostSynthesis>,
+        ) {
+            self.locked = true;
+            self.log.lock().unwrap().push(format!(
+                "end {} ok={} cost={}",
+                function,
+                result.is_ok(),
+                cost.is_some()
+            ));
+        }
+
+        fn on_event(&mut self, _event: &StacksTransactionEvent) {
+            self.log.lock().unwrap().push("event".to_string());
+        }
+
+        fn track_costs(&self) -> bool {
+            true
+        }
+    }
+
+    #[test]
+    fn test_session_hooks() {
+        let mut session = Session::new(SessionSettings::default());
+        session.start().expect("session could not start");
+        session.update_epoch(DEFAULT_EPOCH);
+        let log = Arc::new(Mutex::new(vec![]));
+        session.register_hook(Box::new(PolicyHook {
+            log: log.clone(),
+            locked: false,
+        }));
+
+        let code = "(define-public (incr) (begin (print u1) (ok u1)))";
+        let contract = ClarityContractBuilder::default()
+            .code_source(code.to_string())
+            .build();
+        session.deploy_contract(&contract, false, None).unwrap();
+
+        let sender = session.get_tx_sender();
+        assert!(session
+            .call_contract_fn("contract", "incr", &[], &sender, false, false)
+            .is_ok());
+        let rejected = session
+            .call_contract_fn("contract", "incr", &[], &sender, false, false)
+            .unwrap_err();
+        assert!(rejected[0].message.contains("the contract is locked"));
+
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec![
+                "start incr",
+                "event",
+                "end incr ok=true cost=true",
+                "start incr"
+            ]
+        );
+    }
+}
@@ -7,6 +7,7 @@ use crate::repl::datastore::ClarityDatastore;
 use crate::repl::datastore::Datastore;
 use crate::repl::Settings;
 use clarity::consts::CHAIN_ID_TESTNET;
+use clarity::types::chainstate::{BurnchainHeaderHash, StacksBlockId};
 use clarity::types::StacksEpochId;
 use clarity::vm::analysis::ContractAnalysis;
 use clarity::vm::ast::{build_ast_with_diagnostics, ContractAST};
@@ -20,7 +21,7 @@ use clarity::vm::diagnostic::{Diagnostic, Level};
 use clarity::vm::representations::SymbolicExpressionType::{Atom, List};
 use clarity::vm::representations::{Span, SymbolicExpression};
 use clarity::vm::types::{
-    PrincipalData, QualifiedContractIdentifier, StandardPrincipalData, Value,
+    PrincipalData, QualifiedContractIdentifier, StandardPrincipalData, TupleData, Value,
 };
 use clarity::vm::{analysis::AnalysisDatabase, database::ClarityBackingStore};
 use clarity::vm::{eval, eval_all, EvaluationResult, SnippetEvaluationResult};
@@ -80,6 +81,67 @@ impl ClarityInterpreter {
         self.run_interpreter(contract, ast, cost_track, eval_hooks)
     }
 
+    /// Analyzes a contract and saves its analysis, for the contracts depending on it, without
+    /// executing it: the contract doesn't exist on chain until [`Self::instantiate_contract`]
+    pub fn run_analysis_only(
+        &mut self,
+        contract: &ClarityContract,
+        cached_ast: Option<&ContractAST>,
+    ) -> Result<(ContractAST, ContractAnalysis, Vec<Diagnostic>), Vec<Diagnostic>> {
+        let (ast, mut diagnostics, success) = match cached_ast {
+            Some(ast) => (ast.clone(), vec![], true),
+            None => self.build_ast(contract),
+        };
+
+        let code_source = contract.expect_in_memory_code_source();
+        let (annotations, mut annotation_diagnostics) = self.collect_annotations(code_source);
+        diagnostics.append(&mut annotation_diagnostics);
+
+        let analysis = match self.run_analysis(contract, &ast, &annotations) {
+            Ok((analysis, mut analysis_diagnostics)) => {
+                diagnostics.append(&mut analysis_diagnostics);
+                analysis
+            }
+            Err(diagnostic) => {
+                diagnostics.push(diagnostic);
+                return Err(diagnostics);
+            }
+        };
+        if !success {
+            return Err(diagnostics);
+        }
+
+        let contract_id = contract.expect_resolved_contract_identifier(Some(&self.tx_sender));
+        let mut analysis_db = AnalysisDatabase::new(&mut self.clarity_datastore);
+        if let Err(e) = analysis_db.execute(|db| db.insert_contract(&contract_id, &analysis)) {
+            diagnostics.push(Diagnostic {
+                level: Level::Error,
+                message: format!("unable to save the analysis of {}: {:?}", contract_id, e),
+                spans: vec![],
+                suggestion: None,
+            });
+            return Err(diagnostics);
+        }
+        Ok((ast, analysis, diagnostics))
+    }
+
+    /// Executes a contract analyzed by [`Self::run_analysis_only`]
+    pub fn instantiate_contract(
+        &mut self,
+        contract: &ClarityContract,
+        ast: &ContractAST,
+        analysis: ContractAnalysis,
+        eval_hooks: Option<Vec<&mut dyn EvalHook>>,
+    ) -> Result<ExecutionResult, String> {
+        #[cfg(feature = "cli")]
+        if self.repl_settings.clarity_wasm_mode {
+            let mut module = clar2wasm::compile_contract(analysis.clone())
+                .map_err(|e| format!("Wasm Generator Error: {:?}", e))?;
+            return self.execute_wasm(contract, ast, analysis, &mut module, false, eval_hooks);
+        }
+        self.execute(contract, ast, analysis, false, eval_hooks)
+    }
+
     fn run_interpreter(
         &mut self,
         contract: &ClarityContract,
@@ -278,6 +340,20 @@ impl ClarityInterpreter {
                             suggestion: None,
                         });
                     }
+                    continue;
+                }
+            }
+            if let Some(index) = line.find(";;") {
+                let comment = line[index + 2..].trim();
+                if comment.starts_with("TODO") || comment.starts_with("FIXME") {
+                    let span = Span {
+                        start_line: (n + 1) as u32,
+                        start_column: (index + 1) as u32,
+                        end_line: (n + 1) as u32,
+                        end_column: line.len() as u32,
+                    };
+                    let kind = AnnotationKind::Todo(comment.to_string());
+                    annotations.push(Annotation { kind, span });
                 }
             }
         }
@@ -356,6 +432,76 @@ impl ClarityInterpreter {
         Some(format!("0x{value_hex}"))
     }
 
+    /// Index block hash of the block at `height`, as used by `at-block`
+    pub fn get_block_id(&self, height: u32) -> Option<StacksBlockId> {
+        self.datastore.get_stacks_block_id(height)
+    }
+
+    /// Writes the value of a data var as of the block at `height`
+    pub fn set_data_var_at_block(
+        &mut self,
+        height: u32,
+        contract_id: &QualifiedContractIdentifier,
+        var_name: &str,
+        value: Value,
+    ) -> Result<(), String> {
+        self.write_at_block(height, |conn, _epoch| {
+            conn.set_variable_unknown_descriptor(contract_id, var_name, value)
+                .map(|_| ())
+                .map_err(|e| format!("unable to set {}: {}", var_name, e))
+        })
+    }
+
+    /// Writes an entry of a map as of the block at `height`, or deletes it if `value` is `None`
+    pub fn set_map_entry_at_block(
+        &mut self,
+        height: u32,
+        contract_id: &QualifiedContractIdentifier,
+        map_name: &str,
+        key: Value,
+        value: Option<Value>,
+    ) -> Result<(), String> {
+        self.write_at_block(height, |conn, epoch| {
+            let result = match value {
+                Some(value) => {
+                    conn.set_entry_unknown_descriptor(contract_id, map_name, key, value, &epoch)
+                }
+                None => conn.load_map(contract_id, map_name).and_then(|descriptor| {
+                    conn.delete_entry(contract_id, map_name, &key, &descriptor, &epoch)
+                }),
+            };
+            result
+                .map(|_| ())
+                .map_err(|e| format!("unable to set an entry of {}: {}", map_name, e))
+        })
+    }
+
+    fn write_at_block(
+        &mut self,
+        height: u32,
+        f: impl FnOnce(&mut ClarityDatabase, StacksEpochId) -> Result<(), String>,
+    ) -> Result<(), String> {
+        let block_id = self.datastore.get_stacks_block_id(height).ok_or(format!(
+            "block {} not mined yet (current height: {})",
+            height,
+            self.datastore.get_current_stacks_block_height()
+        ))?;
+        let datastore = &self.datastore;
+        let epoch = datastore.get_current_epoch();
+        self.clarity_datastore
+            .write_at_block(block_id, |clarity_datastore| {
+                let mut conn = ClarityDatabase::new(clarity_datastore, datastore, datastore);
+                conn.begin();
+                match f(&mut conn, epoch) {
+                    Ok(()) => conn.commit().map_err(|e| e.to_string()),
+                    Err(e) => {
+                        let _ = conn.roll_back();
+                        Err(e)
+                    }
+                }
+            })
+    }
+
     fn execute(
         &mut self,
         contract: &ClarityContract,
@@ -574,9 +720,13 @@ impl ClarityInterpreter {
         }
 
         if contract_saved {
+            // the analysis of the contracts instantiated after their analysis is already saved
             let mut analysis_db = AnalysisDatabase::new(&mut self.clarity_datastore);
             analysis_db
-                .execute(|db| db.insert_contract(&contract_id, &analysis))
+                .execute(|db| match db.has_contract(&contract_id) {
+                    true => Ok(()),
+                    false => db.insert_contract(&contract_id, &analysis),
+                })
                 .expect("Unable to save data");
         }
 
@@ -814,9 +964,13 @@ impl ClarityInterpreter {
         }
 
         if contract_saved {
+            // the analysis of the contracts instantiated after their analysis is already saved
             let mut analysis_db = AnalysisDatabase::new(&mut self.clarity_datastore);
             analysis_db
-                .execute(|db| db.insert_contract(&contract_id, &analysis))
+                .execute(|db| match db.has_contract(&contract_id) {
+                    true => Ok(()),
+                    false => db.insert_contract(&contract_id, &analysis),
+                })
                 .expect("Unable to save data");
         }
 
@@ -832,6 +986,34 @@ impl ClarityInterpreter {
         clarity_version: ClarityVersion,
         track_costs: bool,
         allow_private: bool,
+        eval_hooks: Vec<&mut dyn EvalHook>,
+    ) -> Result<ExecutionResult, String> {
+        let tx_sender: PrincipalData = self.tx_sender.clone().into();
+        self.call_contract_fn_as(
+            tx_sender,
+            contract_id,
+            method,
+            args,
+            epoch,
+            clarity_version,
+            track_costs,
+            allow_private,
+            eval_hooks,
+        )
+    }
+
+    /// Calls a function with `tx_sender` as `tx-sender` and `contract-caller`, which can be a
+    /// contract principal
+    pub fn call_contract_fn_as(
+        &mut self,
+        tx_sender: PrincipalData,
+        contract_id: &QualifiedContractIdentifier,
+        method: &str,
+        args: &[SymbolicExpression],
+        epoch: StacksEpochId,
+        clarity_version: ClarityVersion,
+        track_costs: bool,
+        allow_private: bool,
         mut eval_hooks: Vec<&mut dyn EvalHook>,
     ) -> Result<ExecutionResult, String> {
         let mut conn = ClarityDatabase::new(
@@ -839,7 +1021,6 @@ impl ClarityInterpreter {
             &self.datastore,
             &self.datastore,
         );
-        let tx_sender: PrincipalData = self.tx_sender.clone().into();
         conn.begin();
         conn.set_clarity_epoch_version(epoch)
             .map_err(|e| e.to_string())?;
@@ -1083,6 +1264,47 @@ impl ClarityInterpreter {
         Ok(format!("→ {}: {} µSTX", recipient, final_balance))
     }
 
+    /// Unlocked STX of a principal, as stored in the datastore
+    pub fn get_stx_balance(&mut self, principal: &PrincipalData) -> Result<u128, String> {
+        let mut conn = ClarityDatabase::new(
+            &mut self.clarity_datastore,
+            &self.datastore,
+            &self.datastore,
+        );
+        conn.begin();
+        let balance = conn
+            .get_stx_balance_snapshot(principal)
+            .and_then(|mut snapshot| snapshot.get_available_balance())
+            .map_err(|e| format!("unable to get the balance of {}: {}", principal, e));
+        conn.roll_back().map_err(|e| e.to_string())?;
+        balance
+    }
+
+    /// Balance of the fungible token `token_name` defined by `contract_id`
+    pub fn get_ft_balance(
+        &mut self,
+        contract_id: &QualifiedContractIdentifier,
+        token_name: &str,
+        principal: &PrincipalData,
+    ) -> Result<u128, String> {
+        let mut conn = ClarityDatabase::new(
+            &mut self.clarity_datastore,
+            &self.datastore,
+            &self.datastore,
+        );
+        conn.begin();
+        let balance = conn
+            .get_ft_balance(contract_id, token_name, principal, None)
+            .map_err(|e| {
+                format!(
+                    "unable to get the balance of {}::{} of {}: {}",
+                    contract_id, token_name, principal, e
+                )
+            });
+        conn.roll_back().map_err(|e| e.to_string())?;
+        balance
+    }
+
     pub fn set_tx_sender(&mut self, tx_sender: StandardPrincipalData) {
         self.tx_sender = tx_sender;
     }
@@ -1104,6 +1326,15 @@ impl ClarityInterpreter {
         new_height
     }
 
+    pub fn set_burn_block_header_hash(&mut self, height: u32, header_hash: BurnchainHeaderHash) {
+        self.datastore
+            .set_burn_block_header_hash(height, header_hash);
+    }
+
+    pub fn set_pox_payout_addrs(&mut self, height: u32, addrs: Vec<TupleData>, payout: u128) {
+        self.datastore.set_pox_payout_addrs(height, addrs, payout);
+    }
+
     pub fn advance_stacks_chain_tip(&mut self, count: u32) -> Result<u32, String> {
         let current_epoch = self.datastore.get_current_epoch();
         if current_epoch < StacksEpochId::Epoch30 {
@@ -1184,6 +1415,15 @@ impl ClarityInterpreter {
         self.accounts.clone().into_iter().collect::<Vec<_>>()
     }
 
+    pub(crate) fn restore_accounts(
+        &mut self,
+        accounts: BTreeSet<String>,
+        tokens: BTreeMap<String, BTreeMap<String, u128>>,
+    ) {
+        self.accounts = accounts;
+        self.tokens = tokens;
+    }
+
     pub fn get_balance_for_account(&self, account: &str, token: &str) -> u128 {
         match self.tokens.get(token) {
             Some(balances) => match balances.get(account) {
@@ -1308,6 +1548,7 @@ mod tests {
     fn test_advance_stacks_chain_tip() {
         let wasm_settings = Settings {
             analysis: AnalysisSettings::default(),
+            requirements_execution: Default::default(),
             clarity_wasm_mode: true,
             show_timings: false,
         };
@@ -1761,11 +2002,9 @@ mod tests {
         let mut interpreter =
             ClarityInterpreter::new(StandardPrincipalData::transient(), repl_settings);
 
-        let boot_contracts_data = BOOT_CONTRACTS_DATA.clone();
-
-        for (_, (boot_contract, ast)) in boot_contracts_data {
+        for (_, (boot_contract, ast)) in BOOT_CONTRACTS_DATA.iter() {
             let res = interpreter
-                .run(&boot_contract, Some(&ast), false, None)
+                .run(boot_contract, Some(ast.as_ref()), false, None)
                 .unwrap_or_else(|err| {
                     dbg!(&err);
                     panic!("failed to interpret {} boot contract", &boot_contract.name)
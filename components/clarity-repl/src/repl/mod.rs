@@ -1,11 +1,23 @@
+pub mod address_book;
 pub mod boot;
 pub mod clarity_values;
+pub mod clarity_versions;
 pub mod datastore;
+pub mod deferred;
 pub mod diagnostic;
+pub mod epoch;
+pub mod hooks;
 pub mod interpreter;
+pub mod profiler;
+pub mod read_only_cache;
+pub mod repro;
 pub mod session;
+pub mod session_image;
 pub mod settings;
+pub mod state_dump;
+pub mod storage;
 pub mod tracer;
+pub mod transcript;
 
 #[cfg(any(feature = "cli", feature = "dap"))]
 pub mod debug;
@@ -16,13 +28,15 @@ use std::fmt::Display;
 use std::path::PathBuf;
 
 use ::clarity::vm::types::{PrincipalData, QualifiedContractIdentifier, StandardPrincipalData};
+pub use epoch::EpochSpec;
+pub use hooks::SessionHook;
 pub use interpreter::ClarityInterpreter;
 pub use session::Session;
 pub use settings::SessionSettings;
 pub use settings::{Settings, SettingsFile};
 
 use clarity::types::StacksEpochId;
-use clarity::vm::ClarityVersion;
+use clarity::vm::{ClarityVersion, ContractName};
 
 pub const DEFAULT_CLARITY_VERSION: ClarityVersion = ClarityVersion::Clarity3;
 pub const DEFAULT_EPOCH: StacksEpochId = StacksEpochId::Epoch31;
@@ -55,49 +69,19 @@ impl Serialize for ClarityContract {
             ContractDeployer::DefaultDeployer => {}
             _ => unreachable!(),
         }
-        match self.clarity_version {
-            ClarityVersion::Clarity1 => {
-                map.serialize_entry("clarity_version", &1)?;
-            }
-            ClarityVersion::Clarity2 => {
-                map.serialize_entry("clarity_version", &2)?;
-            }
-            ClarityVersion::Clarity3 => {
-                map.serialize_entry("clarity_version", &3)?;
-            }
-        }
-        match self.epoch {
+        map.serialize_entry(
+            "clarity_version",
+            &clarity_versions::version_number(self.clarity_version),
+        )?;
+        // Clarinet.toml stores epochs as numbers
+        let epoch = match self.epoch {
             StacksEpochId::Epoch10 => {
-                map.serialize_entry("epoch", &1.0)?;
-            }
-            StacksEpochId::Epoch20 => {
-                map.serialize_entry("epoch", &2.0)?;
-            }
-            StacksEpochId::Epoch2_05 => {
-                map.serialize_entry("epoch", &2.05)?;
-            }
-            StacksEpochId::Epoch21 => {
-                map.serialize_entry("epoch", &2.1)?;
-            }
-            StacksEpochId::Epoch22 => {
-                map.serialize_entry("epoch", &2.2)?;
-            }
-            StacksEpochId::Epoch23 => {
-                map.serialize_entry("epoch", &2.3)?;
-            }
-            StacksEpochId::Epoch24 => {
-                map.serialize_entry("epoch", &2.4)?;
-            }
-            StacksEpochId::Epoch25 => {
-                map.serialize_entry("epoch", &2.5)?;
-            }
-            StacksEpochId::Epoch30 => {
-                map.serialize_entry("epoch", &3.0)?;
-            }
-            StacksEpochId::Epoch31 => {
-                map.serialize_entry("epoch", &3.1)?;
+                return Err(serde::ser::Error::custom("epoch 1.0 is not supported"))
             }
-        }
+            epoch => EpochSpec::from(epoch),
+        };
+        let epoch: f64 = epoch.as_str().parse().map_err(serde::ser::Error::custom)?;
+        map.serialize_entry("epoch", &epoch)?;
         map.end()
     }
 }
@@ -115,6 +99,24 @@ impl Display for ClarityContract {
 }
 
 impl ClarityContract {
+    pub fn builder() -> ClarityContractBuilder {
+        ClarityContractBuilder::default()
+    }
+
+    /// Checks that the name is a valid contract name, and that the clarity version
+    /// is available in the epoch of the contract
+    pub fn validate(&self) -> Result<(), String> {
+        ContractName::try_from(self.name.clone())
+            .map_err(|_| format!("invalid contract name {}", self.name))?;
+        if !clarity_versions::is_supported(self.epoch, self.clarity_version) {
+            return Err(format!(
+                "contract {}: {} is not available in epoch {}",
+                self.name, self.clarity_version, self.epoch
+            ));
+        }
+        Ok(())
+    }
+
     pub fn expect_in_memory_code_source(&self) -> &str {
         match self.code_source {
             ClarityCodeSource::ContractInMemory(ref code_source) => code_source.as_str(),
@@ -155,6 +157,82 @@ impl ClarityContract {
     }
 }
 
+/// Builds a validated `ClarityContract`. The epoch defaults to `DEFAULT_EPOCH`,
+/// and the clarity version to the default version of the epoch.
+#[derive(Debug, Clone)]
+pub struct ClarityContractBuilder {
+    code_source: ClarityCodeSource,
+    name: Option<String>,
+    deployer: ContractDeployer,
+    clarity_version: Option<ClarityVersion>,
+    epoch: StacksEpochId,
+}
+
+impl Default for ClarityContractBuilder {
+    fn default() -> Self {
+        Self {
+            code_source: ClarityCodeSource::Empty,
+            name: None,
+            deployer: ContractDeployer::DefaultDeployer,
+            clarity_version: None,
+            epoch: DEFAULT_EPOCH,
+        }
+    }
+}
+
+impl ClarityContractBuilder {
+    pub fn source(mut self, source: impl Into<String>) -> Self {
+        self.code_source = ClarityCodeSource::ContractInMemory(source.into());
+        self
+    }
+
+    pub fn path(mut self, path: PathBuf) -> Self {
+        self.code_source = ClarityCodeSource::ContractOnDisk(path);
+        self
+    }
+
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    pub fn deployer(mut self, deployer: ContractDeployer) -> Self {
+        self.deployer = deployer;
+        self
+    }
+
+    pub fn clarity_version(mut self, clarity_version: ClarityVersion) -> Self {
+        self.clarity_version = Some(clarity_version);
+        self
+    }
+
+    pub fn epoch(mut self, epoch: StacksEpochId) -> Self {
+        self.epoch = epoch;
+        self
+    }
+
+    pub fn build(self) -> Result<ClarityContract, String> {
+        let name = match self.name {
+            Some(name) => name,
+            None => match &self.deployer {
+                ContractDeployer::ContractIdentifier(contract_id) => contract_id.name.to_string(),
+                _ => return Err("contract name is missing".to_string()),
+            },
+        };
+        let contract = ClarityContract {
+            code_source: self.code_source,
+            name,
+            deployer: self.deployer,
+            clarity_version: self
+                .clarity_version
+                .unwrap_or(ClarityVersion::default_for_epoch(self.epoch)),
+            epoch: self.epoch,
+        };
+        contract.validate()?;
+        Ok(contract)
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub enum ContractDeployer {
     Transient,
@@ -170,3 +248,40 @@ pub enum ClarityCodeSource {
     ContractOnDisk(PathBuf),
     Empty,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clarity_contract_builder() {
+        let contract = ClarityContract::builder()
+            .source("(ok true)")
+            .name("counter")
+            .epoch(StacksEpochId::Epoch24)
+            .build()
+            .unwrap();
+        assert_eq!(contract.clarity_version, ClarityVersion::Clarity2);
+        assert_eq!(contract.deployer, ContractDeployer::DefaultDeployer);
+
+        let contract_id = QualifiedContractIdentifier::transient();
+        let contract = ClarityContract::builder()
+            .deployer(ContractDeployer::ContractIdentifier(contract_id.clone()))
+            .build()
+            .unwrap();
+        assert_eq!(contract.name, contract_id.name.to_string());
+
+        assert!(ClarityContract::builder().build().is_err());
+        assert!(ClarityContract::builder()
+            .name("no spaces")
+            .build()
+            .is_err());
+        let error = ClarityContract::builder()
+            .name("counter")
+            .clarity_version(ClarityVersion::Clarity3)
+            .epoch(StacksEpochId::Epoch25)
+            .build()
+            .unwrap_err();
+        assert!(error.starts_with("contract counter:"));
+    }
+}
@@ -0,0 +1,269 @@
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+use clarity::vm::functions::NativeFunctions;
+use clarity::vm::types::{PrincipalData, Value};
+use clarity::vm::{
+    contexts::{Environment, LocalContext},
+    ClarityVersion, EvalHook, ExecutionResult, SymbolicExpression,
+};
+
+/// Metric used as the weight of the folded stacks
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProfileMetric {
+    /// Runtime cost, as computed by the cost tracker
+    Runtime,
+    /// Wall time, in microseconds
+    WallTime,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ProfileSample {
+    pub calls: u64,
+    /// Runtime cost spent in the frame itself, excluding the nested calls
+    pub runtime: u64,
+    /// Wall time spent in the frame itself, excluding the nested calls
+    pub wall_time: Duration,
+}
+
+#[derive(Debug, Clone)]
+struct Frame {
+    expr_id: u64,
+    name: String,
+    /// Arguments of the call still to be evaluated. Their evaluation is attributed to the
+    /// caller: the frame only starts once they are all evaluated.
+    pending_args: Vec<u64>,
+    runtime_start: u64,
+    started_at: Option<Instant>,
+    children_runtime: u64,
+    children_wall_time: Duration,
+}
+
+/// Attributes the runtime cost and the wall time of an execution to the Clarity
+/// functions frames, including across `contract-call?` boundaries.
+/// Runtime costs are only available when the execution tracks costs.
+#[derive(Debug, Clone, Default)]
+pub struct ProfilerHook {
+    track_wall_time: bool,
+    stack: Vec<Frame>,
+    samples: BTreeMap<String, ProfileSample>,
+}
+
+impl ProfilerHook {
+    /// `track_wall_time` must be turned off on platforms without a monotonic clock (wasm).
+    pub fn new(track_wall_time: bool) -> Self {
+        Self {
+            track_wall_time,
+            stack: vec![],
+            samples: BTreeMap::new(),
+        }
+    }
+
+    pub fn samples(&self) -> &BTreeMap<String, ProfileSample> {
+        &self.samples
+    }
+
+    fn start_frame(&mut self, env: &mut Environment) {
+        if let Some(frame) = self.stack.last_mut() {
+            frame.runtime_start = env.global_context.cost_track.get_total().runtime;
+            frame.started_at = self.track_wall_time.then(Instant::now);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.stack.clear();
+        self.samples.clear();
+    }
+
+    /// Returns the collected samples in the folded stacks format (`frame;frame;frame weight`),
+    /// which can be rendered with `flamegraph.pl` or `inferno-flamegraph`.
+    pub fn to_folded_stacks(&self, metric: ProfileMetric) -> String {
+        let mut folded = String::new();
+        for (stack, sample) in self.samples.iter() {
+            let weight = match metric {
+                ProfileMetric::Runtime => sample.runtime,
+                ProfileMetric::WallTime => sample.wall_time.as_micros() as u64,
+            };
+            if weight > 0 {
+                folded.push_str(&format!("{} {}\n", stack, weight));
+            }
+        }
+        folded
+    }
+}
+
+/// Returns the name of the frame and the ids of the evaluated arguments, if the
+/// expression is a call to a user-defined function or a `contract-call?`
+fn get_frame(
+    env: &Environment,
+    context: &LocalContext,
+    expr: &SymbolicExpression,
+) -> Option<(String, Vec<u64>)> {
+    let (function_name, args) = expr.match_list()?.split_first()?;
+    let function_name = function_name.match_atom()?;
+
+    if let Some(native_function) =
+        NativeFunctions::lookup_by_name_at_version(function_name, &ClarityVersion::latest())
+    {
+        if native_function != NativeFunctions::ContractCall {
+            return None;
+        }
+        let callee = args.first()?;
+        let method = args.get(1)?.match_atom()?;
+        let contract_id = match callee.match_literal_value() {
+            Some(Value::Principal(PrincipalData::Contract(contract_id))) => contract_id.to_string(),
+            _ => match callee
+                .match_atom()
+                .and_then(|name| context.lookup_variable(name))
+            {
+                Some(Value::CallableContract(callable)) => callable.contract_identifier.to_string(),
+                Some(Value::Principal(PrincipalData::Contract(contract_id))) => {
+                    contract_id.to_string()
+                }
+                _ => callee.to_string(),
+            },
+        };
+        let pending_args = args[2..].iter().rev().map(|arg| arg.id).collect();
+        return Some((format!("{}::{}", contract_id, method), pending_args));
+    }
+
+    env.contract_context.lookup_function(function_name)?;
+    let pending_args = args.iter().rev().map(|arg| arg.id).collect();
+    Some((
+        format!(
+            "{}::{}",
+            env.contract_context.contract_identifier, function_name
+        ),
+        pending_args,
+    ))
+}
+
+impl EvalHook for ProfilerHook {
+    fn will_begin_eval(
+        &mut self,
+        env: &mut Environment,
+        context: &LocalContext,
+        expr: &SymbolicExpression,
+    ) {
+        let Some((name, pending_args)) = get_frame(env, context, expr) else {
+            return;
+        };
+        let is_ready = pending_args.is_empty();
+        self.stack.push(Frame {
+            expr_id: expr.id,
+            name,
+            pending_args,
+            runtime_start: 0,
+            started_at: None,
+            children_runtime: 0,
+            children_wall_time: Duration::ZERO,
+        });
+        if is_ready {
+            self.start_frame(env);
+        }
+    }
+
+    fn did_finish_eval(
+        &mut self,
+        env: &mut Environment,
+        _context: &LocalContext,
+        expr: &SymbolicExpression,
+        _res: &Result<Value, clarity::vm::errors::Error>,
+    ) {
+        let Some(frame) = self.stack.last_mut() else {
+            return;
+        };
+        if frame.pending_args.last() == Some(&expr.id) {
+            frame.pending_args.pop();
+            if frame.pending_args.is_empty() {
+                self.start_frame(env);
+            }
+            return;
+        }
+        if frame.expr_id != expr.id || !frame.pending_args.is_empty() {
+            return;
+        }
+        let Some(frame) = self.stack.pop() else {
+            return;
+        };
+
+        let runtime = env
+            .global_context
+            .cost_track
+            .get_total()
+            .runtime
+            .saturating_sub(frame.runtime_start);
+        let wall_time = frame
+            .started_at
+            .map(|started_at| started_at.elapsed())
+            .unwrap_or_default();
+
+        // frames still evaluating their arguments are not part of the stack yet
+        let mut stack: Vec<&str> = self
+            .stack
+            .iter()
+            .filter(|f| f.pending_args.is_empty())
+            .map(|f| f.name.as_str())
+            .collect();
+        stack.push(&frame.name);
+        let sample = self.samples.entry(stack.join(";")).or_default();
+        sample.calls += 1;
+        sample.runtime += runtime.saturating_sub(frame.children_runtime);
+        sample.wall_time += wall_time.saturating_sub(frame.children_wall_time);
+
+        if let Some(parent) = self
+            .stack
+            .iter_mut()
+            .rev()
+            .find(|f| f.pending_args.is_empty())
+        {
+            parent.children_runtime += runtime;
+            parent.children_wall_time += wall_time;
+        }
+    }
+
+    fn did_complete(&mut self, _result: Result<&mut ExecutionResult, String>) {
+        // frames interrupted by a runtime error are never finished
+        self.stack.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repl::session::Session;
+    use crate::repl::SessionSettings;
+
+    #[test]
+    fn test_costs_are_attributed_to_nested_frames() {
+        let mut session = Session::new(SessionSettings::default());
+        session.enable_profiling(false);
+
+        let contract = [
+            "(define-private (double (n uint)) (* n u2))",
+            "(define-read-only (quadruple (n uint)) (double (double n)))",
+        ]
+        .join("\n");
+        let _ = session.eval(contract, false);
+        let _ = session.eval("(contract-call? .contract-0 quadruple u2)".into(), false);
+
+        let profiler = session.get_profiler().unwrap();
+        let stacks: Vec<(&String, &ProfileSample)> = profiler.samples().iter().collect();
+        assert_eq!(stacks.len(), 2);
+
+        let (root, root_sample) = stacks[0];
+        assert!(root.ends_with(".contract-0::quadruple"));
+        assert_eq!(root_sample.calls, 1);
+
+        let (nested, nested_sample) = stacks[1];
+        assert!(nested.starts_with(root));
+        assert!(nested.ends_with(".contract-0::double"));
+        assert_eq!(nested.matches(';').count(), 1);
+        assert_eq!(nested_sample.calls, 2);
+        assert!(nested_sample.runtime > 0);
+        assert_eq!(nested_sample.wall_time, Duration::ZERO);
+
+        let folded = profiler.to_folded_stacks(ProfileMetric::Runtime);
+        assert_eq!(folded.lines().count(), 2);
+    }
+}
@@ -0,0 +1,78 @@
+use std::collections::HashMap;
+
+use clarity::types::StacksEpochId;
+use clarity::vm::ExecutionResult;
+
+/// A read-only call, in the chain state it was made in
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ReadOnlyCallKey {
+    pub contract_id: String,
+    pub method: String,
+    pub args: Vec<String>,
+    pub sender: String,
+    pub epoch: StacksEpochId,
+    pub block_height: u32,
+    pub burn_block_height: u32,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct ReadOnlyCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub entries: usize,
+    pub invalidations: u64,
+}
+
+impl ReadOnlyCacheStats {
+    pub fn hit_rate(&self) -> f64 {
+        match self.hits + self.misses {
+            0 => 0.0,
+            calls => self.hits as f64 / calls as f64,
+        }
+    }
+}
+
+/// Results of the read-only calls of a session. The results only depend on the arguments
+/// and the chain state, so the cache is emptied on every write and chain tip advance.
+#[derive(Clone, Debug, Default)]
+pub struct ReadOnlyCallCache {
+    entries: HashMap<ReadOnlyCallKey, ExecutionResult>,
+    hits: u64,
+    misses: u64,
+    invalidations: u64,
+}
+
+impl ReadOnlyCallCache {
+    pub fn get(&mut self, key: &ReadOnlyCallKey) -> Option<ExecutionResult> {
+        match self.entries.get(key) {
+            Some(result) => {
+                self.hits += 1;
+                Some(result.clone())
+            }
+            None => {
+                self.misses += 1;
+                None
+            }
+        }
+    }
+
+    pub fn insert(&mut self, key: ReadOnlyCallKey, result: ExecutionResult) {
+        self.entries.insert(key, result);
+    }
+
+    pub fn invalidate(&mut self) {
+        if !self.entries.is_empty() {
+            self.entries.clear();
+            self.invalidations += 1;
+        }
+    }
+
+    pub fn get_stats(&self) -> ReadOnlyCacheStats {
+        ReadOnlyCacheStats {
+            hits: self.hits,
+            misses: self.misses,
+            entries: self.entries.len(),
+            invalidations: self.invalidations,
+        }
+    }
+}
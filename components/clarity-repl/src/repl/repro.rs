@@ -0,0 +1,167 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use clarity::codec::StacksMessageCodec;
+use clarity::types::StacksEpochId;
+use clarity::vm::types::{PrincipalData, QualifiedContractIdentifier, SequenceData, Value};
+use clarity::vm::{ClarityVersion, SymbolicExpression, SymbolicExpressionType};
+
+use super::state_dump::StateDump;
+
+/// A contract of a reproduction bundle, deployed under its original id
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ReproContract {
+    pub contract_id: String,
+    pub source: String,
+    pub clarity_version: ClarityVersion,
+}
+
+/// Minimal state of a session, to reproduce a failure in a fresh session
+/// (`clarinet repro load`). Only the contracts involved and the accounts they deal with are
+/// exported: the mnemonics and the other accounts of the project are left out.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ReproBundle {
+    pub epoch: StacksEpochId,
+    pub block_height: u32,
+    /// Contracts in deployment order, the dependencies first
+    pub contracts: Vec<ReproContract>,
+    pub state: StateDump,
+    /// µSTX balances of the accounts involved
+    pub stx_balances: BTreeMap<String, u128>,
+    pub tx_sender: String,
+    /// Last expression evaluated in the session, expected to reproduce the failure
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snippet: Option<String>,
+}
+
+/// Contracts referenced by literal principals (`contract-call?`, `.contract`, ...)
+pub fn get_referenced_contracts(
+    expressions: &[SymbolicExpression],
+) -> BTreeSet<QualifiedContractIdentifier> {
+    let mut contracts = BTreeSet::new();
+    let mut stack: Vec<&SymbolicExpression> = expressions.iter().collect();
+    while let Some(expression) = stack.pop() {
+        match &expression.expr {
+            SymbolicExpressionType::List(list) => stack.extend(list.iter()),
+            SymbolicExpressionType::LiteralValue(Value::Principal(PrincipalData::Contract(
+                contract_id,
+            )))
+            | SymbolicExpressionType::AtomValue(Value::Principal(PrincipalData::Contract(
+                contract_id,
+            ))) => {
+                contracts.insert(contract_id.clone());
+            }
+            _ => {}
+        }
+    }
+    contracts
+}
+
+fn collect_standard_principals(value: &Value, principals: &mut BTreeSet<String>) {
+    match value {
+        Value::Principal(PrincipalData::Standard(principal)) => {
+            principals.insert(principal.to_address());
+        }
+        Value::Optional(optional) => {
+            if let Some(ref data) = optional.data {
+                collect_standard_principals(data, principals);
+            }
+        }
+        Value::Response(response) => collect_standard_principals(&response.data, principals),
+        Value::Tuple(tuple) => {
+            for value in tuple.data_map.values() {
+                collect_standard_principals(value, principals);
+            }
+        }
+        Value::Sequence(SequenceData::List(list)) => {
+            for value in list.data.iter() {
+                collect_standard_principals(value, principals);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn decode_value(hex: &str) -> Option<Value> {
+    let hex = hex.strip_prefix("0x").unwrap_or(hex);
+    let bytes = (0..hex.len())
+        .step_by(2)
+        .map(|i| {
+            hex.get(i..i + 2)
+                .and_then(|byte| u8::from_str_radix(byte, 16).ok())
+        })
+        .collect::<Option<Vec<u8>>>()?;
+    Value::consensus_deserialize(&mut &bytes[..]).ok()
+}
+
+/// Standard principals found in the keys and values of a state dump, the accounts the
+/// contracts deal with. The entries that can't be decoded are skipped.
+pub fn get_state_accounts(state: &StateDump) -> BTreeSet<String> {
+    let mut accounts = BTreeSet::new();
+    for contract in state.contracts.values() {
+        let keyed_entries = contract
+            .maps
+            .values()
+            .chain(contract.fungible_tokens.values())
+            .chain(contract.non_fungible_tokens.values());
+        for entries in keyed_entries {
+            for (key, value) in entries.iter() {
+                // the tokens balances are keyed by the address of their owner
+                if PrincipalData::parse_standard_principal(key).is_ok() {
+                    accounts.insert(key.clone());
+                }
+                for hex in [key, value] {
+                    if let Some(value) = decode_value(hex) {
+                        collect_standard_principals(&value, &mut accounts);
+                    }
+                }
+            }
+        }
+        for value in contract.data_vars.values() {
+            if let Some(value) = decode_value(value) {
+                collect_standard_principals(&value, &mut accounts);
+            }
+        }
+    }
+    accounts
+}
+
+#[cfg(test)]
+mod tests {
+    use clarity::vm::types::{StandardPrincipalData, TupleData};
+    use clarity::vm::ClarityName;
+
+    use super::*;
+    use crate::repl::state_dump::ContractStateDump;
+
+    #[test]
+    fn test_get_state_accounts() {
+        let alice = StandardPrincipalData::transient();
+        let key = Value::Tuple(
+            TupleData::from_data(vec![(
+                ClarityName::from("owner"),
+                Value::Principal(PrincipalData::Standard(alice.clone())),
+            )])
+            .unwrap(),
+        );
+        let contract = ContractStateDump {
+            maps: BTreeMap::from([(
+                "balances".to_string(),
+                BTreeMap::from([
+                    (key.serialize_to_hex().unwrap(), "0x0100".to_string()),
+                    ("not-hex".to_string(), "0x0100".to_string()),
+                ]),
+            )]),
+            ..Default::default()
+        };
+        let state = StateDump {
+            contracts: BTreeMap::from([(
+                "S1G2081040G2081040G2081040G208105NK8PE5.c".to_string(),
+                contract,
+            )]),
+        };
+        assert_eq!(
+            get_state_accounts(&state),
+            BTreeSet::from([alice.to_address()])
+        );
+    }
+}
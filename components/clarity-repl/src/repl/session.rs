@@ -1,31 +1,53 @@
+use super::address_book::AddressBook;
 use super::boot::{STACKS_BOOT_CODE_MAINNET, STACKS_BOOT_CODE_TESTNET};
+use super::clarity_versions;
+use super::datastore::{ClarityDatastore, SECONDS_BETWEEN_BURN_BLOCKS};
+use super::deferred::{
+    get_public_functions_args, get_referenced_contracts, get_top_level_referenced_contracts,
+    DeferredContract,
+};
 use super::diagnostic::output_diagnostic;
-use super::{ClarityCodeSource, ClarityContract, ClarityInterpreter, ContractDeployer};
+use super::hooks::{get_eval_hooks, SessionHook, SessionHooks};
+use super::settings::RequirementsExecution;
+use super::storage::StorageBackendKind;
+use super::{ClarityCodeSource, ClarityContract, ClarityInterpreter, ContractDeployer, EpochSpec};
 use crate::analysis::coverage::CoverageHook;
+use crate::analysis::natives::{is_native_available, NativeKind};
 use crate::repl::clarity_values::value_to_string;
+use crate::repl::profiler::{ProfileMetric, ProfilerHook};
+use crate::repl::read_only_cache::{ReadOnlyCacheStats, ReadOnlyCallCache, ReadOnlyCallKey};
+use crate::repl::repro::{self, ReproBundle, ReproContract};
+use crate::repl::state_dump::{self, StateDump, StateFixture};
+use crate::repl::transcript::{self, TranscriptEntry};
 use crate::repl::Settings;
 use crate::utils;
 use clarity::codec::StacksMessageCodec;
-use clarity::types::chainstate::StacksAddress;
+use clarity::types::chainstate::{BurnchainHeaderHash, StacksAddress};
 use clarity::types::StacksEpochId;
+use clarity::util::hash::Sha256Sum;
 use clarity::vm::ast::ContractAST;
+use clarity::vm::database::ClarityBackingStore;
 use clarity::vm::diagnostic::{Diagnostic, Level};
 use clarity::vm::docs::{make_api_reference, make_define_reference, make_keyword_reference};
+use clarity::vm::events::StacksTransactionEvent;
 use clarity::vm::functions::define::DefineFunctions;
 use clarity::vm::functions::NativeFunctions;
 use clarity::vm::types::{
-    PrincipalData, QualifiedContractIdentifier, StandardPrincipalData, Value,
+    BuffData, PrincipalData, QualifiedContractIdentifier, SequenceData, StandardPrincipalData,
+    Value,
 };
 use clarity::vm::variables::NativeVariables;
 use clarity::vm::{
-    ClarityVersion, CostSynthesis, EvalHook, EvaluationResult, ExecutionResult, ParsedContract,
-    SymbolicExpression,
+    ClarityVersion, ContractEvaluationResult, ContractName, CostSynthesis, EvalHook,
+    EvaluationResult, ExecutionResult, ParsedContract, SymbolicExpression,
 };
 use colored::*;
 use prettytable::{Cell, Row, Table};
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::fmt;
 use std::num::ParseIntError;
+use std::str::FromStr;
+use std::sync::Arc;
 
 #[cfg(feature = "cli")]
 use clarity::vm::analysis::ContractAnalysis;
@@ -35,17 +57,52 @@ use super::SessionSettings;
 pub static BOOT_TESTNET_ADDRESS: &str = "ST000000000000000000002AMW42H";
 pub static BOOT_MAINNET_ADDRESS: &str = "SP000000000000000000002Q6VF78";
 
+pub static SBTC_TESTNET_ADDRESS: &str = "ST1F7QA2MDF17S807EPA36TSS8AMEFY4KA9TVGWXT";
+pub static SBTC_MAINNET_ADDRESS: &str = "SM3VDXK3WZZSA84XXFKAFAF15NNZX32CTSG82JFQ4";
+pub static SBTC_CONTRACTS: &[&str] = &[
+    "sbtc-registry",
+    "sbtc-token",
+    "sbtc-deposit",
+    "sbtc-withdrawal",
+];
+
 pub static V1_BOOT_CONTRACTS: &[&str] = &["bns"];
 pub static V2_BOOT_CONTRACTS: &[&str] = &["pox-2", "costs-3"];
 pub static V3_BOOT_CONTRACTS: &[&str] = &["pox-3"];
 pub static V4_BOOT_CONTRACTS: &[&str] = &["pox-4"];
 
+fn is_boot_contract(contract_id: &QualifiedContractIdentifier) -> bool {
+    let issuer = contract_id.issuer.to_address();
+    issuer == BOOT_TESTNET_ADDRESS || issuer == BOOT_MAINNET_ADDRESS
+}
+
+/// Appends a contract to `sorted` after the contracts it depends on, the boot contracts
+/// being left out
+fn sort_dependencies(
+    contracts: &BTreeMap<QualifiedContractIdentifier, ParsedContract>,
+    contract_id: &QualifiedContractIdentifier,
+    visited: &mut BTreeSet<QualifiedContractIdentifier>,
+    sorted: &mut Vec<QualifiedContractIdentifier>,
+) {
+    if is_boot_contract(contract_id) || !visited.insert(contract_id.clone()) {
+        return;
+    }
+    let Some(contract) = contracts.get(contract_id) else {
+        return;
+    };
+    for dependency in repro::get_referenced_contracts(&contract.ast.expressions) {
+        sort_dependencies(contracts, &dependency, visited, sorted);
+    }
+    sorted.push(contract_id.clone());
+}
+
 lazy_static! {
     static ref BOOT_TESTNET_PRINCIPAL: StandardPrincipalData =
         PrincipalData::parse_standard_principal(BOOT_TESTNET_ADDRESS).unwrap();
     static ref BOOT_MAINNET_PRINCIPAL: StandardPrincipalData =
         PrincipalData::parse_standard_principal(BOOT_MAINNET_ADDRESS).unwrap();
-    pub static ref BOOT_CONTRACTS_DATA: BTreeMap<QualifiedContractIdentifier, (ClarityContract, ContractAST)> = {
+    /// The ASTs are shared by the sessions and deployment plans, instead of being copied
+    pub static ref BOOT_CONTRACTS_DATA: BTreeMap<QualifiedContractIdentifier, (ClarityContract, Arc<ContractAST>)> = {
         let mut result = BTreeMap::new();
         let deploy: [(&StandardPrincipalData, [(&str, &str); 13]); 2] = [
             (&*BOOT_TESTNET_PRINCIPAL, *STACKS_BOOT_CODE_TESTNET),
@@ -76,7 +133,7 @@ lazy_static! {
                 let (ast, _, _) = interpreter.build_ast(&boot_contract);
                 result.insert(
                     boot_contract.expect_resolved_contract_identifier(None),
-                    (boot_contract, ast),
+                    (boot_contract, Arc::new(ast)),
                 );
             }
         }
@@ -84,6 +141,40 @@ lazy_static! {
     };
 }
 
+/// A boot contract, as loaded in a session
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct BootContractInfo {
+    pub contract_id: String,
+    pub name: String,
+    pub address: String,
+    pub clarity_version: String,
+    pub epoch: String,
+    /// Hex encoded sha256 of the source of the contract
+    pub source_hash: String,
+}
+
+/// Addresses of the genesis principals, for the tools that would otherwise hardcode them
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct GenesisConstants {
+    pub boot_testnet_address: String,
+    pub boot_mainnet_address: String,
+    pub sbtc_testnet_address: String,
+    pub sbtc_mainnet_address: String,
+    pub sbtc_contracts: Vec<String>,
+}
+
+impl Default for GenesisConstants {
+    fn default() -> Self {
+        GenesisConstants {
+            boot_testnet_address: BOOT_TESTNET_ADDRESS.to_string(),
+            boot_mainnet_address: BOOT_MAINNET_ADDRESS.to_string(),
+            sbtc_testnet_address: SBTC_TESTNET_ADDRESS.to_string(),
+            sbtc_mainnet_address: SBTC_MAINNET_ADDRESS.to_string(),
+            sbtc_contracts: SBTC_CONTRACTS.iter().map(|name| name.to_string()).collect(),
+        }
+    }
+}
+
 #[derive(Clone, Debug, Serialize)]
 pub struct CostsReport {
     pub test_name: String,
@@ -93,6 +184,26 @@ pub struct CostsReport {
     pub cost_result: CostSynthesis,
 }
 
+/// Outcome of one of the snippets evaluated by `Session::eval_many`
+#[derive(Clone, Debug)]
+pub struct EvalOutcome {
+    /// Value of the snippet, `None` when it failed or is only made of definitions
+    pub value: Option<Value>,
+    pub events: Vec<StacksTransactionEvent>,
+    pub cost: Option<CostSynthesis>,
+    /// Errors of a failed evaluation, or the warnings of a successful one
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl EvalOutcome {
+    pub fn is_success(&self) -> bool {
+        !self
+            .diagnostics
+            .iter()
+            .any(|diagnostic| diagnostic.level == Level::Error)
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct Session {
     pub settings: SessionSettings,
@@ -102,9 +213,21 @@ pub struct Session {
     api_reference: HashMap<String, String>,
     pub show_costs: bool,
     pub executed: Vec<String>,
+    /// Console commands and their output, written by `::save_session`
+    pub transcript: Vec<TranscriptEntry>,
     keywords_reference: HashMap<String, String>,
 
     coverage_hook: Option<CoverageHook>,
+    profiler_hook: Option<ProfilerHook>,
+    hooks: SessionHooks,
+    read_only_cache: Option<ReadOnlyCallCache>,
+    /// Principals the console commands and snippets can refer to by name, the initial
+    /// accounts are registered by default
+    pub address_book: AddressBook,
+    /// Deposits completed by `mint_sbtc`, to derive a new bitcoin txid for each of them
+    sbtc_deposits: u64,
+    /// Contracts published by analysis only, not executed yet
+    deferred_contracts: BTreeMap<QualifiedContractIdentifier, DeferredContract>,
 }
 
 impl Session {
@@ -118,17 +241,37 @@ impl Session {
                 .expect("Unable to parse deployer's address")
         };
 
+        let mut address_book = AddressBook::new();
+        for account in settings.initial_accounts.iter() {
+            let _ = address_book.insert(&account.name, &account.address);
+        }
+
+        let mut interpreter = ClarityInterpreter::new(tx_sender, settings.repl_settings.clone());
+        if settings.storage_backend != StorageBackendKind::InMemory {
+            match ClarityDatastore::with_storage_backend(&settings.storage_backend) {
+                Ok(clarity_datastore) => interpreter.clarity_datastore = clarity_datastore,
+                Err(e) => eprintln!("unable to open the session storage, kept in memory: {}", e),
+            }
+        }
+
         Self {
-            interpreter: ClarityInterpreter::new(tx_sender, settings.repl_settings.clone()),
+            interpreter,
             current_epoch: settings.epoch_id.unwrap_or(StacksEpochId::Epoch2_05),
             contracts: BTreeMap::new(),
             api_reference: build_api_reference(),
             show_costs: false,
             settings,
             executed: Vec::new(),
+            transcript: Vec::new(),
             keywords_reference: clarity_keywords(),
 
             coverage_hook: None,
+            profiler_hook: None,
+            hooks: SessionHooks::default(),
+            read_only_cache: None,
+            address_book,
+            sbtc_deposits: 0,
+            deferred_contracts: BTreeMap::new(),
         }
     }
 
@@ -136,6 +279,70 @@ impl Session {
         self.coverage_hook = Some(CoverageHook::new());
     }
 
+    /// Attribute the costs of every execution to the Clarity functions frames.
+    /// Costs are tracked for all executions while profiling is enabled.
+    pub fn enable_profiling(&mut self, track_wall_time: bool) {
+        self.profiler_hook = Some(ProfilerHook::new(track_wall_time));
+    }
+
+    /// Registers a hook called around each contract call and for each event emitted. Read-only
+    /// calls are always executed while hooks are registered.
+    pub fn register_hook(&mut self, hook: Box<dyn SessionHook>) {
+        self.hooks.register(hook);
+    }
+
+    fn tracks_costs(&self) -> bool {
+        self.profiler_hook.is_some() || self.hooks.track_costs()
+    }
+
+    /// Memoize the results of the read-only calls made with `call_contract_fn`. Calls are
+    /// executed when costs are tracked or coverage is collected, since both record every
+    /// execution.
+    pub fn enable_read_only_cache(&mut self) {
+        self.read_only_cache = Some(ReadOnlyCallCache::default());
+    }
+
+    pub fn get_read_only_cache_stats(&self) -> Option<ReadOnlyCacheStats> {
+        self.read_only_cache.as_ref().map(|cache| cache.get_stats())
+    }
+
+    /// Must be called after writing to the datastore without going through the session
+    pub fn invalidate_read_only_cache(&mut self) {
+        if let Some(cache) = self.read_only_cache.as_mut() {
+            cache.invalidate();
+        }
+    }
+
+    fn is_read_only_function(
+        &self,
+        contract_id: &QualifiedContractIdentifier,
+        method: &str,
+    ) -> bool {
+        self.contracts.get(contract_id).is_some_and(|contract| {
+            contract
+                .analysis
+                .read_only_function_types
+                .keys()
+                .any(|name| name.as_str() == method)
+        })
+    }
+
+    pub fn get_profiler(&self) -> Option<&ProfilerHook> {
+        self.profiler_hook.as_ref()
+    }
+
+    /// Returns the folded stacks collected since the last call, and clears the profiler
+    pub fn collect_folded_stacks(&mut self, metric: ProfileMetric) -> String {
+        match &mut self.profiler_hook {
+            Some(profiler_hook) => {
+                let folded = profiler_hook.to_folded_stacks(metric);
+                profiler_hook.clear();
+                folded
+            }
+            None => "".to_string(),
+        }
+    }
+
     pub fn set_test_name(&mut self, name: String) {
         if let Some(coverage_hook) = &mut self.coverage_hook {
             coverage_hook.set_current_test_name(name);
@@ -155,6 +362,27 @@ impl Session {
         }
     }
 
+    /// Number of coverage reports recorded so far, one per execution
+    pub fn get_coverage_reports_count(&self) -> usize {
+        self.coverage_hook
+            .as_ref()
+            .map_or(0, |coverage_hook| coverage_hook.reports.len())
+    }
+
+    pub fn get_lcov_content_since(
+        &self,
+        first_report: usize,
+        asts: &BTreeMap<QualifiedContractIdentifier, ContractAST>,
+        contract_paths: &BTreeMap<String, String>,
+    ) -> String {
+        match &self.coverage_hook {
+            Some(coverage_hook) => {
+                coverage_hook.get_lcov_content_since(first_report, asts, contract_paths)
+            }
+            None => "".to_string(),
+        }
+    }
+
     pub fn load_boot_contracts(&mut self) {
         let default_tx_sender = self.interpreter.get_tx_sender();
 
@@ -219,6 +447,31 @@ impl Session {
         bool,
         Vec<String>,
         Option<Result<ExecutionResult, Vec<Diagnostic>>>,
+    ) {
+        match command {
+            cmd if cmd.starts_with("::save_session") || cmd.starts_with("::save-session") => {
+                (false, vec![self.save_session(cmd)], None)
+            }
+            cmd if cmd.starts_with("::replay") => (false, self.replay(cmd), None),
+            cmd => {
+                let (reload, output, result) = self.run_console_input(cmd);
+                self.transcript.push(TranscriptEntry {
+                    command: cmd.to_string(),
+                    output: output.clone(),
+                });
+                (reload, output, result)
+            }
+        }
+    }
+
+    #[cfg(feature = "cli")]
+    fn run_console_input(
+        &mut self,
+        command: &str,
+    ) -> (
+        bool,
+        Vec<String>,
+        Option<Result<ExecutionResult, Vec<Diagnostic>>>,
     ) {
         let mut output = Vec::<String>::new();
 
@@ -229,17 +482,33 @@ impl Session {
             #[cfg(feature = "cli")]
             cmd if cmd.starts_with("::read") => self.read(&mut output, cmd),
             #[cfg(feature = "cli")]
+            cmd if cmd.starts_with("::load") => self.load(&mut output, cmd),
+            #[cfg(feature = "cli")]
             cmd if cmd.starts_with("::debug") => self.debug(&mut output, cmd),
             #[cfg(feature = "cli")]
             cmd if cmd.starts_with("::trace") => self.trace(&mut output, cmd),
             #[cfg(feature = "cli")]
             cmd if cmd.starts_with("::get_costs") => self.get_costs(&mut output, cmd),
+            #[cfg(feature = "cli")]
+            cmd if cmd.starts_with("::profile") => self.profile(&mut output, cmd),
+            #[cfg(feature = "cli")]
+            cmd if cmd.starts_with("::export_repro") || cmd.starts_with("::export-repro") => {
+                output.push(self.export_repro(cmd))
+            }
 
             cmd if cmd.starts_with("::") => {
                 output.push(self.handle_command(cmd));
             }
 
             snippet => {
+                let snippet = match self.address_book.resolve_expression(snippet) {
+                    Ok(snippet) => snippet,
+                    Err(e) => {
+                        output.push(e.red().to_string());
+                        return (false, output, None);
+                    }
+                };
+                let snippet = snippet.as_str();
                 let execution_result = self.run_snippet(&mut output, self.show_costs, snippet);
                 return (false, output, Some(execution_result));
             }
@@ -264,6 +533,12 @@ impl Session {
             cmd if cmd.starts_with("::toggle_timings") => self.toggle_timings(),
 
             cmd if cmd.starts_with("::mint_stx") => self.mint_stx(cmd),
+            cmd if cmd.starts_with("::sbtc-balance") || cmd.starts_with("::sbtc_balance") => {
+                self.parse_and_get_sbtc_balance(cmd)
+            }
+            cmd if cmd.starts_with("::sbtc-fund") || cmd.starts_with("::sbtc_fund") => {
+                self.parse_and_mint_sbtc(cmd)
+            }
             cmd if cmd.starts_with("::set_tx_sender") => self.parse_and_set_tx_sender(cmd),
             cmd if cmd.starts_with("::get_assets_maps") => {
                 self.get_accounts().unwrap_or("No account found".into())
@@ -271,6 +546,7 @@ impl Session {
             cmd if cmd.starts_with("::get_contracts") => {
                 self.get_contracts().unwrap_or("No contract found".into())
             }
+            cmd if cmd.starts_with("::get_addresses") => self.get_addresses(),
             cmd if cmd.starts_with("::get_burn_block_height") => self.get_burn_block_height(),
             cmd if cmd.starts_with("::get_stacks_block_height") => self.get_block_height(),
             cmd if cmd.starts_with("::get_block_height") => self.get_block_height(),
@@ -281,10 +557,20 @@ impl Session {
             cmd if cmd.starts_with("::advance_burn_chain_tip") => {
                 self.parse_and_advance_burn_chain_tip(cmd)
             }
+            cmd if cmd.starts_with("::advance_cycles") || cmd.starts_with("::advance-cycles") => {
+                self.parse_and_advance_reward_cycles(cmd)
+            }
+            cmd if cmd.starts_with("::advance_time") || cmd.starts_with("::advance-time") => {
+                self.parse_and_advance_time(cmd)
+            }
             cmd if cmd.starts_with("::get_epoch") => self.get_epoch(),
             cmd if cmd.starts_with("::set_epoch") => self.set_epoch(cmd),
+            cmd if cmd.starts_with("::dump_state") || cmd.starts_with("::dump-state") => {
+                self.parse_and_dump_state(cmd)
+            }
             cmd if cmd.starts_with("::encode") => self.encode(cmd),
             cmd if cmd.starts_with("::decode") => self.decode(cmd),
+            cmd if cmd.starts_with("::call") => self.parse_and_call(cmd),
 
             _ => "Invalid command. Try `::help`".yellow().to_string(),
         }
@@ -468,7 +754,7 @@ impl Session {
 
         let mut tracer = Tracer::new(snippet.to_string());
 
-        match self.eval_with_hooks(snippet.to_string(), Some(vec![&mut tracer]), false) {
+        match self.eval_with_hooks(snippet.to_string(), Some(vec![&mut tracer]), true) {
             Ok(_) => (),
             Err(diagnostics) => {
                 let lines = snippet.lines();
@@ -480,6 +766,54 @@ impl Session {
         };
     }
 
+    #[cfg(feature = "cli")]
+    pub fn profile(&mut self, output: &mut Vec<String>, cmd: &str) {
+        let snippet = match cmd.split_once(' ') {
+            Some((_, snippet)) => snippet,
+            _ => return output.push("Usage: ::profile <expr>".red().to_string()),
+        };
+
+        let mut profiler = ProfilerHook::new(true);
+        if let Err(diagnostics) =
+            self.eval_with_hooks(snippet.to_string(), Some(vec![&mut profiler]), true)
+        {
+            let lines = snippet.lines();
+            let formatted_lines: Vec<String> = lines.map(|l| l.to_string()).collect();
+            for d in diagnostics {
+                output.append(&mut output_diagnostic(&d, "<snippet>", &formatted_lines));
+            }
+            return;
+        }
+
+        let mut table = Table::new();
+        table.add_row(row!["Frame", "Calls", "Runtime", "Wall time"]);
+        for (stack, sample) in profiler.samples().iter() {
+            table.add_row(Row::new(vec![
+                Cell::new(stack.rsplit(';').next().unwrap_or(stack)),
+                Cell::new(&sample.calls.to_string()),
+                Cell::new(&sample.runtime.to_string()),
+                Cell::new(&format!("{:?}", sample.wall_time)),
+            ]));
+        }
+        output.push(format!("{}", table));
+
+        let folded_path = "profile.folded";
+        match std::fs::write(
+            folded_path,
+            profiler.to_folded_stacks(ProfileMetric::Runtime),
+        ) {
+            Ok(_) => output.push(format!(
+                "Runtime costs folded stacks written to {}",
+                folded_path.green()
+            )),
+            Err(e) => output.push(
+                format!("unable to write {}: {}", folded_path, e)
+                    .red()
+                    .to_string(),
+            ),
+        }
+    }
+
     #[cfg(feature = "cli")]
     pub fn start(&mut self) -> Result<(String, Vec<(ContractAnalysis, String, String)>), String> {
         let mut output_err = Vec::<String>::new();
@@ -536,6 +870,132 @@ impl Session {
         };
     }
 
+    /// Write the commands executed so far, and their output as comments, in a script
+    #[cfg(feature = "cli")]
+    pub fn save_session(&mut self, cmd: &str) -> String {
+        let filename = match cmd.split_once(' ') {
+            Some((_, filename)) => filename.trim(),
+            _ => return "Usage: ::save_session <filename>".red().to_string(),
+        };
+        match std::fs::write(filename, transcript::to_script(&self.transcript)) {
+            Ok(_) => format!("{} commands saved in {}", self.transcript.len(), filename)
+                .green()
+                .to_string(),
+            Err(err) => format!("unable to write {}: {}", filename, err)
+                .red()
+                .to_string(),
+        }
+    }
+
+    /// Execute the commands of a script written by `::save_session`, one command per line
+    #[cfg(feature = "cli")]
+    pub fn replay(&mut self, cmd: &str) -> Vec<String> {
+        let filename = match cmd.split_once(' ') {
+            Some((_, filename)) => filename.trim(),
+            _ => return vec!["Usage: ::replay <filename>".red().to_string()],
+        };
+        let script = match std::fs::read_to_string(filename) {
+            Ok(script) => script,
+            Err(err) => {
+                return vec![format!("unable to read {}: {}", filename, err)
+                    .red()
+                    .to_string()]
+            }
+        };
+
+        let mut output = vec![];
+        for command in transcript::parse_script(&script) {
+            output.push(format!(">> {}", command).bold().to_string());
+            if command.starts_with("::replay")
+                || command.starts_with("::save_session")
+                || command.starts_with("::save-session")
+                || command.starts_with("::reload")
+            {
+                output.push(
+                    format!("{} skipped while replaying", command)
+                        .yellow()
+                        .to_string(),
+                );
+                continue;
+            }
+            // the replayed commands are recorded, for the session to be saved again
+            let (_, command_output, _) = self.process_console_input(&command);
+            output.extend(command_output);
+        }
+        output
+    }
+
+    /// Deploy the contract of a file, named after the file unless `as <contract-name>` is given
+    #[cfg(feature = "cli")]
+    pub fn load(&mut self, output: &mut Vec<String>, cmd: &str) {
+        let usage = "Usage: ::load <filename> [as <contract-name>] [deployer]";
+        let args: Vec<&str> = cmd.split_whitespace().skip(1).collect();
+        let (filename, name, deployer) = match args.as_slice() {
+            [filename] => (*filename, None, None),
+            [filename, "as", name] => (*filename, Some(*name), None),
+            [filename, "as", name, deployer] => (*filename, Some(*name), Some(*deployer)),
+            [filename, deployer] => (*filename, None, Some(*deployer)),
+            _ => return output.push(usage.red().to_string()),
+        };
+
+        let name = match name {
+            Some(name) => name.to_string(),
+            None => match std::path::Path::new(filename).file_stem() {
+                Some(stem) => stem.to_string_lossy().to_string(),
+                None => return output.push(usage.red().to_string()),
+            },
+        };
+        if ContractName::try_from(name.clone()).is_err() {
+            return output.push(format!("invalid contract name: {}", name).red().to_string());
+        }
+        let deployer = match deployer.map(|d| self.address_book.resolve_principal(d)) {
+            Some(Err(e)) => return output.push(e.red().to_string()),
+            Some(Ok(deployer)) => match PrincipalData::parse_standard_principal(&deployer) {
+                Ok(_) => ContractDeployer::Address(deployer),
+                Err(_) => {
+                    return output.push(format!("invalid deployer: {}", deployer).red().to_string())
+                }
+            },
+            None => ContractDeployer::Address(self.get_tx_sender()),
+        };
+
+        let code = match std::fs::read_to_string(filename) {
+            Ok(code) => code,
+            Err(err) => {
+                return output.push(
+                    format!("unable to read {}: {}", filename, err)
+                        .red()
+                        .to_string(),
+                )
+            }
+        };
+        let lines: Vec<String> = code.lines().map(|line| line.to_string()).collect();
+        let contract = ClarityContract {
+            code_source: ClarityCodeSource::ContractInMemory(code),
+            name,
+            deployer,
+            clarity_version: ClarityVersion::default_for_epoch(self.current_epoch),
+            epoch: self.current_epoch,
+        };
+
+        match self.deploy_contract(&contract, self.show_costs, None) {
+            Ok(result) => {
+                for diagnostic in result.diagnostics.iter() {
+                    output.append(&mut output_diagnostic(diagnostic, &contract.name, &lines));
+                }
+                if let EvaluationResult::Contract(contract_result) = &result.result {
+                    let snippet = format!("→ .{} contract successfully stored. Use (contract-call? ...) for invoking the public functions:", contract_result.contract.contract_identifier);
+                    output.push(green!(snippet));
+                }
+            }
+            Err(diagnostics) => {
+                for diagnostic in diagnostics.iter() {
+                    output.append(&mut output_diagnostic(diagnostic, &contract.name, &lines));
+                }
+            }
+        }
+    }
+
     pub fn stx_transfer(
         &mut self,
         amount: u64,
@@ -563,13 +1023,9 @@ impl Session {
             };
             return Err(vec![diagnostic]);
         }
+        self.invalidate_read_only_cache();
 
-        let mut hooks: Vec<&mut dyn EvalHook> = vec![];
-        if let Some(ref mut coverage_hook) = self.coverage_hook {
-            hooks.push(coverage_hook);
-        }
-
-        if contract.clarity_version > ClarityVersion::default_for_epoch(contract.epoch) {
+        if !clarity_versions::is_supported(contract.epoch, contract.clarity_version) {
             let diagnostic = Diagnostic {
                 level: Level::Error,
                 message: format!(
@@ -584,10 +1040,19 @@ impl Session {
 
         let contract_id =
             contract.expect_resolved_contract_identifier(Some(&self.interpreter.get_tx_sender()));
+        self.instantiate_contracts_referenced_by(contract, ast)?;
+
+        let cost_track = cost_track || self.tracks_costs();
+        let hooks = get_eval_hooks(
+            &mut self.coverage_hook,
+            &mut self.profiler_hook,
+            &mut self.hooks,
+        );
 
         let result = self.interpreter.run(contract, ast, cost_track, Some(hooks));
 
         result.inspect(|result| {
+            self.hooks.emit_events(result);
             if let EvaluationResult::Contract(contract_result) = &result.result {
                 self.contracts
                     .insert(contract_id.clone(), contract_result.contract.clone());
@@ -595,6 +1060,158 @@ impl Session {
         })
     }
 
+    /// Publishes a contract by its analysis only: the contracts depending on it type-check, and
+    /// it's executed by the first call or contract referring to it, unless the execution of
+    /// the requirements is disabled
+    pub fn defer_contract(
+        &mut self,
+        contract: &ClarityContract,
+        ast: Option<&ContractAST>,
+    ) -> Result<ExecutionResult, Vec<Diagnostic>> {
+        let sender = self.interpreter.get_tx_sender();
+        let contract_id = contract.expect_resolved_contract_identifier(Some(&sender));
+        let (ast, analysis, diagnostics) = self.interpreter.run_analysis_only(contract, ast)?;
+        let parsed_contract = ParsedContract {
+            contract_identifier: contract_id.to_string(),
+            code: contract.expect_in_memory_code_source().to_string(),
+            function_args: get_public_functions_args(&analysis),
+            ast: ast.clone(),
+            analysis: analysis.clone(),
+        };
+        self.deferred_contracts.insert(
+            contract_id,
+            DeferredContract {
+                contract: contract.clone(),
+                ast,
+                analysis,
+                sender,
+            },
+        );
+        Ok(ExecutionResult {
+            result: EvaluationResult::Contract(ContractEvaluationResult {
+                result: None,
+                contract: parsed_contract,
+            }),
+            events: vec![],
+            cost: None,
+            diagnostics,
+        })
+    }
+
+    /// Contracts published by analysis only and not executed yet
+    pub fn get_deferred_contracts(&self) -> Vec<&QualifiedContractIdentifier> {
+        self.deferred_contracts.keys().collect()
+    }
+
+    /// Deferred contracts `contract_id` refers to, directly or through the contracts it refers
+    /// to, in the order they can be instantiated
+    fn collect_deferred_dependencies(
+        &self,
+        contract_id: &QualifiedContractIdentifier,
+        visited: &mut BTreeSet<QualifiedContractIdentifier>,
+        deferred: &mut Vec<QualifiedContractIdentifier>,
+    ) {
+        if !visited.insert(contract_id.clone()) {
+            return;
+        }
+        let expressions = match self.deferred_contracts.get(contract_id) {
+            Some(contract) => &contract.ast.expressions,
+            None => match self.contracts.get(contract_id) {
+                Some(contract) => &contract.ast.expressions,
+                None => return,
+            },
+        };
+        for dependency in get_referenced_contracts(expressions).iter() {
+            self.collect_deferred_dependencies(dependency, visited, deferred);
+        }
+        if self.deferred_contracts.contains_key(contract_id) {
+            deferred.push(contract_id.clone());
+        }
+    }
+
+    /// Executes the deferred contracts an execution needs: the `referenced` ones, and their
+    /// dependencies
+    fn instantiate_deferred_contracts(
+        &mut self,
+        referenced: BTreeSet<QualifiedContractIdentifier>,
+    ) -> Result<(), Vec<Diagnostic>> {
+        if self.deferred_contracts.is_empty() {
+            return Ok(());
+        }
+        let mut visited = BTreeSet::new();
+        let mut deferred = vec![];
+        for contract_id in referenced.iter() {
+            self.collect_deferred_dependencies(contract_id, &mut visited, &mut deferred);
+        }
+        let to_diagnostic = |message: String| {
+            vec![Diagnostic {
+                level: Level::Error,
+                message,
+                spans: vec![],
+                suggestion: None,
+            }]
+        };
+        if let Some(contract_id) = deferred.first() {
+            if self.settings.repl_settings.requirements_execution == RequirementsExecution::Disabled
+            {
+                return Err(to_diagnostic(format!(
+                    "{} is published for its analysis only and can't be executed, set requirements_execution = \"lazy\" in the [repl] settings to execute it when needed",
+                    contract_id
+                )));
+            }
+        }
+        for contract_id in deferred.into_iter() {
+            let Some(contract) = self.deferred_contracts.remove(&contract_id) else {
+                continue;
+            };
+            let initial_tx_sender = self.interpreter.get_tx_sender();
+            self.interpreter.set_tx_sender(contract.sender);
+            let result = self.interpreter.instantiate_contract(
+                &contract.contract,
+                &contract.ast,
+                contract.analysis,
+                None,
+            );
+            self.interpreter.set_tx_sender(initial_tx_sender);
+            match result {
+                Ok(ExecutionResult {
+                    result: EvaluationResult::Contract(contract_result),
+                    ..
+                }) => {
+                    self.contracts.insert(contract_id, contract_result.contract);
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    return Err(to_diagnostic(format!(
+                        "unable to instantiate {}: {}",
+                        contract_id, e
+                    )))
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Instantiates the deferred contracts the top-level code of `contract` needs, the ones its
+    /// functions call are instantiated by the first call
+    fn instantiate_contracts_referenced_by(
+        &mut self,
+        contract: &ClarityContract,
+        ast: Option<&ContractAST>,
+    ) -> Result<(), Vec<Diagnostic>> {
+        if self.deferred_contracts.is_empty() {
+            return Ok(());
+        }
+        let referenced = match ast {
+            Some(ast) => get_top_level_referenced_contracts(&ast.expressions),
+            None => {
+                let (ast, _, _) = self.interpreter.build_ast(contract);
+                get_top_level_referenced_contracts(&ast.expressions)
+            }
+        };
+        self.instantiate_deferred_contracts(referenced)
+    }
+
     pub fn call_contract_fn(
         &mut self,
         contract: &str,
@@ -613,65 +1230,257 @@ impl Session {
             format!("{}.{}", initial_tx_sender, contract)
         };
 
-        self.set_tx_sender(sender);
+        let contract_id = QualifiedContractIdentifier::parse(&contract_id_str).unwrap();
+        let mut referenced = get_referenced_contracts(args);
+        referenced.insert(contract_id.clone());
+        self.instantiate_deferred_contracts(referenced)?;
+        let track_costs = track_costs || self.tracks_costs();
+
+        let cache_key = if self.read_only_cache.is_some()
+            && !track_costs
+            && self.coverage_hook.is_none()
+            && self.hooks.is_empty()
+            && self.is_read_only_function(&contract_id, method)
+        {
+            Some(ReadOnlyCallKey {
+                contract_id: contract_id_str.clone(),
+                method: method.to_string(),
+                args: args.iter().map(|arg| arg.to_string()).collect(),
+                sender: sender.to_string(),
+                epoch: self.current_epoch,
+                block_height: self.interpreter.get_block_height(),
+                burn_block_height: self.interpreter.get_burn_block_height(),
+            })
+        } else {
+            // anything else than a read-only call can write to the datastore
+            self.invalidate_read_only_cache();
+            None
+        };
+        if let (Some(key), Some(cache)) = (&cache_key, self.read_only_cache.as_mut()) {
+            if let Some(execution) = cache.get(key) {
+                return Ok(execution);
+            }
+        }
 
-        let mut hooks: Vec<&mut dyn EvalHook> = vec![];
-        if let Some(ref mut coverage_hook) = self.coverage_hook {
-            hooks.push(coverage_hook);
+        if let Err(message) = self.hooks.start_call(&contract_id, method, args) {
+            return Err(vec![Diagnostic {
+                level: Level::Error,
+                message,
+                spans: vec![],
+                suggestion: None,
+            }]);
         }
+        self.set_tx_sender(sender);
 
-        let execution = match self.interpreter.call_contract_fn(
-            &QualifiedContractIdentifier::parse(&contract_id_str).unwrap(),
-            method,
-            args,
-            self.current_epoch,
-            ClarityVersion::default_for_epoch(self.current_epoch),
-            track_costs,
-            allow_private,
-            hooks,
-        ) {
-            Ok(result) => result,
-            Err(e) => {
-                self.set_tx_sender(&initial_tx_sender);
-                return Err(vec![Diagnostic {
-                    level: Level::Error,
-                    message: format!("Error calling contract function: {e}"),
-                    spans: vec![],
-                    suggestion: None,
-                }]);
-            }
-        };
+        let hooks = get_eval_hooks(
+            &mut self.coverage_hook,
+            &mut self.profiler_hook,
+            &mut self.hooks,
+        );
+
+        let execution = self
+            .interpreter
+            .call_contract_fn(
+                &contract_id,
+                method,
+                args,
+                self.current_epoch,
+                ClarityVersion::default_for_epoch(self.current_epoch),
+                track_costs,
+                allow_private,
+                hooks,
+            )
+            .map_err(|e| format!("Error calling contract function: {e}"));
         self.set_tx_sender(&initial_tx_sender);
+        self.hooks.end_call(
+            &contract_id,
+            method,
+            execution.as_ref().map_err(String::as_str),
+        );
+        let execution = execution.map_err(|message| {
+            vec![Diagnostic {
+                level: Level::Error,
+                message,
+                spans: vec![],
+                suggestion: None,
+            }]
+        })?;
 
+        if let (Some(key), Some(cache)) = (cache_key, self.read_only_cache.as_mut()) {
+            cache.insert(key, execution.clone());
+        }
         Ok(execution)
     }
 
-    pub fn eval(
+    /// Contract id of `contract`, prefixed with the tx-sender when it's only a name
+    fn resolve_contract_id(&self, contract: &str) -> Result<QualifiedContractIdentifier, String> {
+        let contract = contract.trim_start_matches('\'');
+        let contract_id = match contract.strip_prefix('.') {
+            Some(name) => format!("{}.{}", self.get_tx_sender(), name),
+            None if !contract.contains('.') => format!("{}.{}", self.get_tx_sender(), contract),
+            None => contract.to_string(),
+        };
+        QualifiedContractIdentifier::parse(&contract_id)
+            .map_err(|_| format!("unable to parse the contract id {}", contract))
+    }
+
+    /// Principal of a standard address, or of a contract (`.treasury`, `ST1...treasury`)
+    fn resolve_principal(&self, principal: &str) -> Result<PrincipalData, String> {
+        let principal = principal.trim_start_matches('\'');
+        if principal.starts_with('.') {
+            return self
+                .resolve_contract_id(principal)
+                .map(PrincipalData::Contract);
+        }
+        PrincipalData::parse(principal)
+            .map_err(|_| format!("unable to parse the principal {}", principal))
+    }
+
+    /// Calls `method` of `contract` from the deployed contract `caller`, as `(as-contract
+    /// (contract-call? ...))` would: `tx-sender` and `contract-caller` are the caller, the
+    /// assets moved are the ones it owns.
+    pub fn call_as_contract(
         &mut self,
-        snippet: String,
-        cost_track: bool,
+        caller: &str,
+        contract: &str,
+        method: &str,
+        args: &[SymbolicExpression],
+        allow_private: bool,
+        track_costs: bool,
     ) -> Result<ExecutionResult, Vec<Diagnostic>> {
-        let contract = ClarityContract {
-            code_source: ClarityCodeSource::ContractInMemory(snippet),
-            name: format!("contract-{}", self.contracts.len()),
-            deployer: ContractDeployer::DefaultDeployer,
-            clarity_version: ClarityVersion::default_for_epoch(self.current_epoch),
-            epoch: self.current_epoch,
+        let to_diagnostic = |message: String| {
+            vec![Diagnostic {
+                level: Level::Error,
+                message,
+                spans: vec![],
+                suggestion: None,
+            }]
         };
-        let contract_identifier =
-            contract.expect_resolved_contract_identifier(Some(&self.interpreter.get_tx_sender()));
-
-        let mut hooks: Vec<&mut dyn EvalHook> = vec![];
-        if let Some(ref mut coverage_hook) = self.coverage_hook {
-            hooks.push(coverage_hook);
+        let caller_id = self.resolve_contract_id(caller).map_err(to_diagnostic)?;
+        let contract_id = self.resolve_contract_id(contract).map_err(to_diagnostic)?;
+        let mut referenced = get_referenced_contracts(args);
+        referenced.extend([caller_id.clone(), contract_id.clone()]);
+        self.instantiate_deferred_contracts(referenced)?;
+        if !self.contracts.contains_key(&caller_id) {
+            return Err(to_diagnostic(format!(
+                "{} is not deployed, it can't call {}",
+                caller_id, contract_id
+            )));
         }
+        let track_costs = track_costs || self.tracks_costs();
+        self.invalidate_read_only_cache();
+        self.hooks
+            .start_call(&contract_id, method, args)
+            .map_err(to_diagnostic)?;
+
+        let hooks = get_eval_hooks(
+            &mut self.coverage_hook,
+            &mut self.profiler_hook,
+            &mut self.hooks,
+        );
 
-        let result = self
+        let execution = self
             .interpreter
-            .run(&contract.clone(), None, cost_track, Some(hooks));
-
-        match result {
+            .call_contract_fn_as(
+                PrincipalData::Contract(caller_id),
+                &contract_id,
+                method,
+                args,
+                self.current_epoch,
+                ClarityVersion::default_for_epoch(self.current_epoch),
+                track_costs,
+                allow_private,
+                hooks,
+            )
+            .map_err(|e| format!("Error calling contract function: {e}"));
+        self.hooks.end_call(
+            &contract_id,
+            method,
+            execution.as_ref().map_err(String::as_str),
+        );
+        execution.map_err(to_diagnostic)
+    }
+
+    /// Unlocked STX of a standard or contract principal
+    pub fn get_stx_balance(&mut self, principal: &str) -> Result<u128, String> {
+        let principal = self.resolve_principal(principal)?;
+        self.interpreter.get_stx_balance(&principal)
+    }
+
+    /// Balance of the fungible token `token_name` of `token_contract`
+    pub fn get_ft_balance(
+        &mut self,
+        token_contract: &str,
+        token_name: &str,
+        principal: &str,
+    ) -> Result<u128, String> {
+        let contract_id = self.resolve_contract_id(token_contract)?;
+        let principal = self.resolve_principal(principal)?;
+        self.interpreter
+            .get_ft_balance(&contract_id, token_name, &principal)
+    }
+
+    /// Checks the STX owned by a principal, typically the treasury of a protocol
+    pub fn assert_stx_balance(&mut self, principal: &str, expected: u128) -> Result<(), String> {
+        let balance = self.get_stx_balance(principal)?;
+        if balance != expected {
+            return Err(format!(
+                "{} owns {} µSTX, expected {}",
+                principal, balance, expected
+            ));
+        }
+        Ok(())
+    }
+
+    /// Checks the balance of a fungible token owned by a principal
+    pub fn assert_ft_balance(
+        &mut self,
+        token_contract: &str,
+        token_name: &str,
+        principal: &str,
+        expected: u128,
+    ) -> Result<(), String> {
+        let balance = self.get_ft_balance(token_contract, token_name, principal)?;
+        if balance != expected {
+            return Err(format!(
+                "{} owns {} {}, expected {}",
+                principal, balance, token_name, expected
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn eval(
+        &mut self,
+        snippet: String,
+        cost_track: bool,
+    ) -> Result<ExecutionResult, Vec<Diagnostic>> {
+        let contract = ClarityContract {
+            code_source: ClarityCodeSource::ContractInMemory(snippet),
+            name: format!("contract-{}", self.contracts.len()),
+            deployer: ContractDeployer::DefaultDeployer,
+            clarity_version: ClarityVersion::default_for_epoch(self.current_epoch),
+            epoch: self.current_epoch,
+        };
+        let contract_identifier =
+            contract.expect_resolved_contract_identifier(Some(&self.interpreter.get_tx_sender()));
+        self.invalidate_read_only_cache();
+        self.instantiate_contracts_referenced_by(&contract, None)?;
+
+        let cost_track = cost_track || self.tracks_costs();
+        let hooks = get_eval_hooks(
+            &mut self.coverage_hook,
+            &mut self.profiler_hook,
+            &mut self.hooks,
+        );
+
+        let result = self
+            .interpreter
+            .run(&contract.clone(), None, cost_track, Some(hooks));
+
+        match result {
             Ok(result) => {
+                self.hooks.emit_events(&result);
                 if let EvaluationResult::Contract(contract_result) = &result.result {
                     self.contracts.insert(
                         contract_identifier.clone(),
@@ -684,6 +1493,110 @@ impl Session {
         }
     }
 
+    /// Evaluates the snippets one after the other, as `eval` would, a failing snippet not
+    /// stopping the evaluation of the next ones
+    pub fn eval_many(&mut self, snippets: Vec<String>, cost_track: bool) -> Vec<EvalOutcome> {
+        snippets
+            .into_iter()
+            .map(|snippet| match self.eval(snippet, cost_track) {
+                Ok(execution) => EvalOutcome {
+                    value: match execution.result {
+                        EvaluationResult::Snippet(result) => Some(result.result),
+                        EvaluationResult::Contract(contract) => contract.result,
+                    },
+                    events: execution.events,
+                    cost: execution.cost,
+                    diagnostics: execution.diagnostics,
+                },
+                Err(diagnostics) => EvalOutcome {
+                    value: None,
+                    events: vec![],
+                    cost: None,
+                    diagnostics,
+                },
+            })
+            .collect()
+    }
+
+    /// Mints sBTC to `recipient` the way the signers do, by completing a deposit with
+    /// `sbtc-deposit.complete-deposit-wrapper`. The sBTC contracts must be deployed in the
+    /// session (as requirements of the project), and at least one burn block mined.
+    pub fn mint_sbtc(&mut self, recipient: &str, amount: u128) -> Result<ExecutionResult, String> {
+        let recipient = PrincipalData::parse(recipient)
+            .map_err(|_| format!("unable to parse the principal {}", recipient))?;
+        let deposit_contract_id = format!("{}.sbtc-deposit", SBTC_MAINNET_ADDRESS);
+        if !self
+            .contracts
+            .keys()
+            .any(|contract_id| contract_id.to_string() == deposit_contract_id)
+        {
+            return Err(format!(
+                "{} is not deployed, add it to the requirements of the project",
+                deposit_contract_id
+            ));
+        }
+        if self.interpreter.get_burn_block_height() == 0 {
+            return Err("no burn block to complete the deposit in".to_string());
+        }
+
+        // the bitcoin transactions of the deposit are never looked up, their txids only
+        // have to be unique
+        self.sbtc_deposits += 1;
+        let seed = format!("{}-{}-{}", self.sbtc_deposits, recipient, amount);
+        let txid = Sha256Sum::from_data(format!("deposit-{}", seed).as_bytes()).to_hex();
+        let sweep_txid = Sha256Sum::from_data(format!("sweep-{}", seed).as_bytes()).to_hex();
+        let snippet = format!(
+            "(let ((burn-height (- burn-block-height u1))) (contract-call? '{deposit_contract_id} complete-deposit-wrapper 0x{txid} u0 u{amount} '{recipient} (unwrap-panic (get-burn-block-info? header-hash burn-height)) burn-height 0x{sweep_txid}))",
+        );
+
+        // the deposits are completed by the signers, the deployer of the sBTC contracts
+        // until they rotate their keys
+        let tx_sender = self.get_tx_sender();
+        self.set_tx_sender(SBTC_MAINNET_ADDRESS);
+        let result = self.eval(snippet, false);
+        self.set_tx_sender(&tx_sender);
+
+        let execution = result.map_err(|diagnostics| {
+            diagnostics
+                .into_iter()
+                .map(|diagnostic| diagnostic.message)
+                .collect::<Vec<_>>()
+                .join("\n")
+        })?;
+        match &execution.result {
+            EvaluationResult::Snippet(result) => match &result.result {
+                Value::Response(response) if response.committed => Ok(execution),
+                value => Err(format!("the deposit failed: {}", value)),
+            },
+            _ => Err("the deposit failed".to_string()),
+        }
+    }
+
+    /// Balance of sBTC of `principal`, the locked sBTC included
+    pub fn get_sbtc_balance(&mut self, principal: &str) -> Result<u128, String> {
+        let principal = PrincipalData::parse(principal)
+            .map_err(|_| format!("unable to parse the principal {}", principal))?;
+        let snippet = format!(
+            "(contract-call? '{}.sbtc-token get-balance '{})",
+            SBTC_MAINNET_ADDRESS, principal
+        );
+        let execution = self.eval(snippet, false).map_err(|_| {
+            format!(
+                "{}.sbtc-token is not deployed, add it to the requirements of the project",
+                SBTC_MAINNET_ADDRESS
+            )
+        })?;
+        match execution.result {
+            EvaluationResult::Snippet(result) => match result.result {
+                Value::Response(response) if response.committed => {
+                    response.data.expect_u128().map_err(|e| e.to_string())
+                }
+                value => Err(format!("unable to get the balance: {}", value)),
+            },
+            _ => Err("unable to get the balance".to_string()),
+        }
+    }
+
     pub fn eval_with_hooks(
         &mut self,
         snippet: String,
@@ -699,6 +1612,8 @@ impl Session {
         };
         let contract_identifier =
             contract.expect_resolved_contract_identifier(Some(&self.interpreter.get_tx_sender()));
+        self.invalidate_read_only_cache();
+        self.instantiate_contracts_referenced_by(&contract, None)?;
 
         let result = self
             .interpreter
@@ -726,20 +1641,29 @@ impl Session {
         self.keywords_reference.get(exp)
     }
 
+    /// Functions available in the Clarity version of the current epoch
     pub fn get_api_reference_index(&self) -> Vec<String> {
+        let version = ClarityVersion::default_for_epoch(self.current_epoch);
         let mut keys = self
             .api_reference
             .keys()
+            .filter(|k| {
+                is_native_available(k, NativeKind::Function, version)
+                    || is_native_available(k, NativeKind::Define, version)
+            })
             .map(|k| k.to_string())
             .collect::<Vec<String>>();
         keys.sort();
         keys
     }
 
+    /// Keywords available in the Clarity version of the current epoch
     pub fn get_clarity_keywords(&self) -> Vec<String> {
+        let version = ClarityVersion::default_for_epoch(self.current_epoch);
         let mut keys = self
             .keywords_reference
             .keys()
+            .filter(|k| is_native_available(k, NativeKind::Variable, version))
             .map(|k| k.to_string())
             .collect::<Vec<String>>();
         keys.sort();
@@ -779,6 +1703,15 @@ impl Session {
             "{}",
             "::mint_stx <principal> <amount>\t\tMint STX balance for a given principal".yellow()
         ));
+        output.push(format!(
+            "{}",
+            "::sbtc-fund <principal> <amount>\tMint sBTC (in sats) by completing a deposit"
+                .yellow()
+        ));
+        output.push(format!(
+            "{}",
+            "::sbtc-balance <principal>\t\tGet the sBTC balance of a principal".yellow()
+        ));
         output.push(format!(
             "{}",
             "::set_tx_sender <principal>\t\tSet tx-sender variable to principal".yellow()
@@ -791,6 +1724,15 @@ impl Session {
             "{}",
             "::get_contracts\t\t\t\tGet contracts".yellow()
         ));
+        output.push(format!(
+            "{}",
+            "::get_addresses\t\t\t\tGet the principals usable by name (@name)".yellow()
+        ));
+        output.push(format!(
+            "{}",
+            "::dump_state [contract-id]\t\tExport the data of a contract (or of all contracts) as JSON"
+                .yellow()
+        ));
         output.push(format!(
             "{}",
             "::get_block_height\t\t\tGet current block height".yellow()
@@ -808,6 +1750,16 @@ impl Session {
             "::advance_burn_chain_tip <count>\tSimulate mining of <count> burnchain blocks"
                 .yellow()
         ));
+        output.push(format!(
+            "{}",
+            "::advance_cycles <count> [<blocks>]\tSimulate mining up to the start of the <count>th next reward cycle, with <blocks> stacks blocks per tenure in epoch 3.0"
+                .yellow()
+        ));
+        output.push(format!(
+            "{}",
+            "::advance_time <duration> [<blocks>]\tSimulate mining the burnchain blocks of <duration> (e.g. 90d, 12h, 600s)"
+                .yellow()
+        ));
         output.push(format!(
             "{}",
             "::set_epoch <epoch>\t\t\tUpdate the current epoch".yellow()
@@ -833,6 +1785,11 @@ impl Session {
             "::get_costs <expr>\t\t\tDisplay the cost analysis".yellow()
         ));
         #[cfg(feature = "cli")]
+        output.push(format!(
+            "{}",
+            "::profile <expr>\t\t\tProfile the costs of <expr> per function".yellow()
+        ));
+        #[cfg(feature = "cli")]
         output.push(format!(
             "{}",
             "::reload \t\t\t\tReload the existing contract(s) in the session".yellow()
@@ -842,6 +1799,31 @@ impl Session {
             "{}",
             "::read <filename>\t\t\tRead expressions from a file".yellow()
         ));
+        #[cfg(feature = "cli")]
+        output.push(format!(
+            "{}",
+            "::save_session <filename>\t\tSave the commands and their output as a script".yellow()
+        ));
+        #[cfg(feature = "cli")]
+        output.push(format!(
+            "{}",
+            "::replay <filename>\t\t\tExecute the commands of a saved session".yellow()
+        ));
+        #[cfg(feature = "cli")]
+        output.push(format!(
+            "{}",
+            "::export_repro <contract> <filename>\tExport the contracts and the state involved in a failure (clarinet repro load)".yellow()
+        ));
+
+        #[cfg(feature = "cli")]
+        output.push(format!(
+            "{}",
+            "::load <filename> [as <name>] [deployer]\tDeploy the contract of a file".yellow()
+        ));
+        output.push(format!(
+            "{}",
+            "::call <contract> <function> [args...]\tCall a function of a contract".yellow()
+        ));
 
         output.push(format!(
             "{}",
@@ -912,6 +1894,120 @@ impl Session {
         }
     }
 
+    fn parse_and_advance_reward_cycles(&mut self, command: &str) -> String {
+        let args: Vec<_> = command.split(' ').skip(1).collect();
+        let count = match args.first().unwrap_or(&"1").parse::<u32>() {
+            Ok(count) => count,
+            _ => return format!("{}", "Unable to parse count".red()),
+        };
+        let stacks_blocks_per_tenure = match args.get(1).map(|blocks| blocks.parse::<u32>()) {
+            Some(Ok(blocks)) => Some(blocks),
+            Some(Err(_)) => return format!("{}", "Unable to parse stacks blocks per tenure".red()),
+            None => None,
+        };
+
+        match self.advance_reward_cycles(count, stacks_blocks_per_tenure) {
+            Ok(reward_cycle) => format!(
+                "new burn height: {}\nnew stacks height: {}\nreward cycle: {}",
+                self.interpreter.datastore.get_current_burn_block_height(),
+                self.interpreter.datastore.get_current_stacks_block_height(),
+                reward_cycle,
+            )
+            .green()
+            .to_string(),
+            Err(e) => format!("{}", e.red()),
+        }
+    }
+
+    fn parse_and_advance_time(&mut self, command: &str) -> String {
+        let args: Vec<_> = command.split(' ').skip(1).collect();
+        let Some(seconds) = args.first().and_then(|duration| parse_duration(duration)) else {
+            return format!(
+                "{}",
+                "Usage: ::advance_time <duration> [<stacks blocks per tenure>], e.g. 30d".red()
+            );
+        };
+        let stacks_blocks_per_tenure = match args.get(1).map(|blocks| blocks.parse::<u32>()) {
+            Some(Ok(blocks)) => Some(blocks),
+            Some(Err(_)) => return format!("{}", "Unable to parse stacks blocks per tenure".red()),
+            None => None,
+        };
+
+        match self.advance_time(seconds, stacks_blocks_per_tenure) {
+            Ok(_) => format!(
+                "new burn height: {}\nnew stacks height: {}\nreward cycle: {}",
+                self.interpreter.datastore.get_current_burn_block_height(),
+                self.interpreter.datastore.get_current_stacks_block_height(),
+                self.get_current_reward_cycle(),
+            )
+            .green()
+            .to_string(),
+            Err(e) => format!("{}", e.red()),
+        }
+    }
+
+    /// PoX reward cycle of the current burn block
+    pub fn get_current_reward_cycle(&self) -> u32 {
+        let constants = self.interpreter.datastore.get_constants();
+        self.interpreter
+            .datastore
+            .get_current_burn_block_height()
+            .saturating_sub(constants.burn_start_height)
+            / constants.pox_reward_cycle_length
+    }
+
+    /// Mines the burn blocks up to the first block of the reward cycle `count` cycles ahead,
+    /// returns the new reward cycle. See `advance_time` for `stacks_blocks_per_tenure`.
+    pub fn advance_reward_cycles(
+        &mut self,
+        count: u32,
+        stacks_blocks_per_tenure: Option<u32>,
+    ) -> Result<u32, String> {
+        let constants = self.interpreter.datastore.get_constants();
+        let reward_cycle = self.get_current_reward_cycle() + count;
+        let height = constants.burn_start_height + reward_cycle * constants.pox_reward_cycle_length;
+        let burn_blocks =
+            height.saturating_sub(self.interpreter.datastore.get_current_burn_block_height());
+        self.advance_burn_blocks(burn_blocks, stacks_blocks_per_tenure)?;
+        Ok(reward_cycle)
+    }
+
+    /// Mines the burn blocks covering `seconds`, a burn block every 10 minutes, returns the new
+    /// burn block height. From the epoch 3.0, each burn block starts a tenure of
+    /// `stacks_blocks_per_tenure` Stacks blocks (1 by default).
+    pub fn advance_time(
+        &mut self,
+        seconds: u64,
+        stacks_blocks_per_tenure: Option<u32>,
+    ) -> Result<u32, String> {
+        let burn_blocks = seconds.div_ceil(SECONDS_BETWEEN_BURN_BLOCKS);
+        let burn_blocks = u32::try_from(burn_blocks)
+            .map_err(|_| format!("{} seconds is too long to be mined", seconds))?;
+        self.advance_burn_blocks(burn_blocks, stacks_blocks_per_tenure)
+    }
+
+    fn advance_burn_blocks(
+        &mut self,
+        count: u32,
+        stacks_blocks_per_tenure: Option<u32>,
+    ) -> Result<u32, String> {
+        match stacks_blocks_per_tenure {
+            None | Some(1) => Ok(self.advance_burn_chain_tip(count)),
+            Some(0) => Err("a tenure has at least one stacks block".to_string()),
+            Some(_) if self.interpreter.datastore.get_current_epoch() < StacksEpochId::Epoch30 => {
+                Err("stacks blocks per tenure can only be set from epoch 3.0".to_string())
+            }
+            Some(stacks_blocks) => {
+                let mut height = self.interpreter.datastore.get_current_burn_block_height();
+                for _ in 0..count {
+                    height = self.advance_burn_chain_tip(1);
+                    self.advance_stacks_chain_tip(stacks_blocks - 1)?;
+                }
+                Ok(height)
+            }
+        }
+    }
+
     pub fn advance_chain_tip(&mut self, count: u32) -> u32 {
         let current_epoch = self.interpreter.datastore.get_current_epoch();
         if current_epoch < StacksEpochId::Epoch30 {
@@ -925,13 +2021,104 @@ impl Session {
     }
 
     pub fn advance_burn_chain_tip(&mut self, count: u32) -> u32 {
+        self.invalidate_read_only_cache();
         self.interpreter.advance_burn_chain_tip(count)
     }
 
     pub fn advance_stacks_chain_tip(&mut self, count: u32) -> Result<u32, String> {
+        self.invalidate_read_only_cache();
         self.interpreter.advance_stacks_chain_tip(count)
     }
 
+    /// Sets the `header-hash` returned by `get-burn-block-info?` at `height`
+    pub fn set_burn_block_header_hash(
+        &mut self,
+        height: u32,
+        header_hash: &str,
+    ) -> Result<(), String> {
+        let header_hash = BurnchainHeaderHash::from_hex(header_hash.trim_start_matches("0x"))
+            .map_err(|e| format!("invalid burn block header hash: {}", e))?;
+        self.invalidate_read_only_cache();
+        self.interpreter
+            .set_burn_block_header_hash(height, header_hash);
+        Ok(())
+    }
+
+    /// Sets the `pox-addrs` returned by `get-burn-block-info?` at `height`. Each address
+    /// must be a `{ version: (buff 1), hashbytes: (buff 32) }` tuple.
+    pub fn set_pox_payout_addrs(
+        &mut self,
+        height: u32,
+        addrs: Vec<Value>,
+        payout: u128,
+    ) -> Result<(), String> {
+        if addrs.len() > 2 {
+            return Err("a burn block can't have more than 2 pox addresses".to_string());
+        }
+        let mut tuples = vec![];
+        for addr in addrs {
+            let Value::Tuple(tuple) = addr else {
+                return Err(format!("invalid pox address: {}", addr));
+            };
+            let version = match tuple.get("version") {
+                Ok(Value::Sequence(SequenceData::Buffer(BuffData { data }))) => data.len() == 1,
+                _ => false,
+            };
+            let hashbytes = match tuple.get("hashbytes") {
+                Ok(Value::Sequence(SequenceData::Buffer(BuffData { data }))) => data.len() <= 32,
+                _ => false,
+            };
+            if !version || !hashbytes || tuple.data_map.len() != 2 {
+                return Err(format!(
+                    "invalid pox address: {}, expected {{ version: (buff 1), hashbytes: (buff 32) }}",
+                    Value::Tuple(tuple)
+                ));
+            }
+            tuples.push(tuple);
+        }
+        self.invalidate_read_only_cache();
+        self.interpreter
+            .set_pox_payout_addrs(height, tuples, payout);
+        Ok(())
+    }
+
+    /// `id-header-hash` of the block at `height`, to be passed to `at-block`
+    pub fn get_block_id(&self, height: u32) -> Result<String, String> {
+        self.interpreter
+            .get_block_id(height)
+            .map(|block_id| format!("0x{}", block_id.to_hex()))
+            .ok_or(format!("block {} not mined yet", height))
+    }
+
+    /// Sets the value of a data var read by `at-block` at `height` and the next blocks, until
+    /// the next write of the var. History can be fabricated by mining empty blocks and
+    /// writing the state of the contracts at some of them.
+    pub fn set_data_var_at_block(
+        &mut self,
+        height: u32,
+        contract_id: &QualifiedContractIdentifier,
+        var_name: &str,
+        value: Value,
+    ) -> Result<(), String> {
+        self.invalidate_read_only_cache();
+        self.interpreter
+            .set_data_var_at_block(height, contract_id, var_name, value)
+    }
+
+    /// Same as `set_data_var_at_block` for an entry of a map, `None` deleting the entry
+    pub fn set_map_entry_at_block(
+        &mut self,
+        height: u32,
+        contract_id: &QualifiedContractIdentifier,
+        map_name: &str,
+        key: Value,
+        value: Option<Value>,
+    ) -> Result<(), String> {
+        self.invalidate_read_only_cache();
+        self.interpreter
+            .set_map_entry_at_block(height, contract_id, map_name, key, value)
+    }
+
     fn parse_and_set_tx_sender(&mut self, command: &str) -> String {
         let args: Vec<_> = command.split(' ').collect();
 
@@ -939,9 +2126,12 @@ impl Session {
             return format!("{}", "Usage: ::set_tx_sender <address>".red());
         }
 
-        let tx_sender = args[1];
+        let tx_sender = match self.address_book.resolve_principal(args[1]) {
+            Ok(tx_sender) => tx_sender,
+            Err(e) => return e.red().to_string(),
+        };
 
-        match PrincipalData::parse_standard_principal(args[1]) {
+        match PrincipalData::parse_standard_principal(&tx_sender) {
             Ok(address) => {
                 self.interpreter.set_tx_sender(address);
                 format!("tx-sender switched to {}", tx_sender)
@@ -983,6 +2173,51 @@ impl Session {
         self.interpreter.get_assets_maps()
     }
 
+    /// Boot contracts deployed in the session. The contracts deployed with
+    /// `deploy_contract` at a boot address (such as a custom pox-4) take precedence over the
+    /// embedded boot code.
+    pub fn get_boot_contracts(&mut self) -> Vec<BootContractInfo> {
+        let mut boot_contracts = vec![];
+        for (contract_id, (boot_contract, _)) in BOOT_CONTRACTS_DATA.iter() {
+            let (code, clarity_version, epoch) = match self.contracts.get(contract_id) {
+                Some(contract) => (
+                    contract.code.clone(),
+                    contract.analysis.clarity_version,
+                    contract.analysis.epoch,
+                ),
+                None => {
+                    let key = ClarityDatastore::make_contract_hash_key(contract_id);
+                    if !self
+                        .interpreter
+                        .clarity_datastore
+                        .has_entry(&key)
+                        .unwrap_or(false)
+                    {
+                        continue;
+                    }
+                    let ClarityCodeSource::ContractInMemory(code) = &boot_contract.code_source
+                    else {
+                        continue;
+                    };
+                    (
+                        code.clone(),
+                        boot_contract.clarity_version,
+                        boot_contract.epoch,
+                    )
+                }
+            };
+            boot_contracts.push(BootContractInfo {
+                contract_id: contract_id.to_string(),
+                name: contract_id.name.to_string(),
+                address: contract_id.issuer.to_address(),
+                clarity_version: clarity_version.to_string(),
+                epoch: epoch.to_string(),
+                source_hash: Sha256Sum::from_data(code.as_bytes()).to_hex(),
+            });
+        }
+        boot_contracts
+    }
+
     pub fn toggle_costs(&mut self) -> String {
         self.show_costs = !self.show_costs;
         format!("Always show costs: {}", self.show_costs)
@@ -1003,20 +2238,19 @@ impl Session {
     }
 
     pub fn set_epoch(&mut self, cmd: &str) -> String {
-        let epoch = match cmd.split_once(' ').map(|(_, epoch)| epoch) {
-            Some("2.0") => StacksEpochId::Epoch20,
-            Some("2.05") => StacksEpochId::Epoch2_05,
-            Some("2.1") => StacksEpochId::Epoch21,
-            Some("2.2") => StacksEpochId::Epoch22,
-            Some("2.3") => StacksEpochId::Epoch23,
-            Some("2.4") => StacksEpochId::Epoch24,
-            Some("2.5") => StacksEpochId::Epoch25,
-            Some("3.0") => StacksEpochId::Epoch30,
-            Some("3.1") => StacksEpochId::Epoch31,
-            _ => {
-                return "Usage: ::set_epoch 2.0 | 2.05 | 2.1 | 2.2 | 2.3 | 2.4 | 2.5 | 3.0 | 3.1"
-                    .red()
-                    .to_string()
+        let epoch = match cmd
+            .split_once(' ')
+            .map(|(_, epoch)| EpochSpec::from_str(epoch))
+        {
+            Some(Ok(epoch)) => StacksEpochId::from(epoch),
+            Some(Err(e)) => return e.red().to_string(),
+            None => {
+                return format!(
+                    "Usage: ::set_epoch {}",
+                    EpochSpec::ALL.map(|spec| spec.as_str()).join(" | ")
+                )
+                .red()
+                .to_string()
             }
         };
         self.update_epoch(epoch);
@@ -1024,6 +2258,7 @@ impl Session {
     }
 
     pub fn update_epoch(&mut self, epoch: StacksEpochId) {
+        self.invalidate_read_only_cache();
         self.current_epoch = epoch;
         self.interpreter.set_current_epoch(epoch);
         if epoch >= StacksEpochId::Epoch30 {
@@ -1100,6 +2335,277 @@ impl Session {
         let _ = self.run_snippet(output, true, expr);
     }
 
+    /// Parses a contract id, `.contract` and `contract` being deployed by the tx-sender
+    fn resolve_contract_id(&self, contract: &str) -> Option<QualifiedContractIdentifier> {
+        let contract_id = if contract.starts_with('S') {
+            contract.to_string()
+        } else {
+            format!(
+                "{}.{}",
+                self.get_tx_sender(),
+                contract.trim_start_matches('.')
+            )
+        };
+        QualifiedContractIdentifier::parse(&contract_id).ok()
+    }
+
+    fn parse_and_dump_state(&self, cmd: &str) -> String {
+        let contract_id = match cmd.split_once(' ').map(|(_, arg)| arg.trim()) {
+            Some(contract) => match self.resolve_contract_id(contract) {
+                Some(contract_id) => Some(contract_id),
+                None => return "Usage: ::dump_state [contract-id]".red().to_string(),
+            },
+            None => None,
+        };
+        match self.dump_state(contract_id.as_ref()) {
+            Ok(dump) => serde_json::to_string_pretty(&dump).unwrap_or_default(),
+            Err(e) => e.red().to_string(),
+        }
+    }
+
+    /// Exports the data vars, maps entries and tokens of a contract, or of all the
+    /// contracts of the session (boot contracts excluded)
+    pub fn dump_state(
+        &self,
+        contract_id: Option<&QualifiedContractIdentifier>,
+    ) -> Result<StateDump, String> {
+        let contracts_ids: Vec<&QualifiedContractIdentifier> = match contract_id {
+            Some(contract_id) => {
+                if !self.contracts.contains_key(contract_id) {
+                    return Err(format!("contract {} not found", contract_id));
+                }
+                vec![contract_id]
+            }
+            None => self
+                .contracts
+                .keys()
+                .filter(|contract_id| !is_boot_contract(contract_id))
+                .collect(),
+        };
+
+        let is_single_contract = contract_id.is_some();
+        let mut dump = StateDump::default();
+        for contract_id in contracts_ids {
+            let contract_dump =
+                state_dump::dump_contract_state(&self.interpreter.clarity_datastore, contract_id);
+            // when dumping all the contracts, the ones without data are omitted
+            if is_single_contract || !contract_dump.is_empty() {
+                dump.contracts
+                    .insert(contract_id.to_string(), contract_dump);
+            }
+        }
+        Ok(dump)
+    }
+
+    /// Loads a dump produced by `dump_state`. The contracts of the dump must already be
+    /// deployed in the session, their data is overwritten by the content of the dump.
+    pub fn load_state(&mut self, dump: &StateDump) -> Result<(), String> {
+        let mut items = vec![];
+        for (contract_id, contract_dump) in dump.contracts.iter() {
+            let contract_id = QualifiedContractIdentifier::parse(contract_id)
+                .map_err(|e| format!("invalid contract id {}: {}", contract_id, e))?;
+            if !self.contracts.contains_key(&contract_id) {
+                return Err(format!("contract {} is not deployed", contract_id));
+            }
+            items.append(&mut state_dump::get_contract_state_items(
+                &contract_id,
+                contract_dump,
+            )?);
+        }
+        self.invalidate_read_only_cache();
+        self.interpreter
+            .clarity_datastore
+            .put_all_data(items)
+            .map_err(|e| format!("unable to load state: {}", e))
+    }
+
+    fn get_contracts_hashes(&self) -> BTreeMap<String, String> {
+        self.contracts
+            .iter()
+            .filter(|(contract_id, _)| !is_boot_contract(contract_id))
+            .map(|(contract_id, contract)| {
+                (
+                    contract_id.to_string(),
+                    state_dump::hash_contract_source(&contract.code),
+                )
+            })
+            .collect()
+    }
+
+    pub fn create_fixture(&self) -> Result<StateFixture, String> {
+        Ok(StateFixture {
+            contracts_hashes: self.get_contracts_hashes(),
+            state: self.dump_state(None)?,
+        })
+    }
+
+    /// Loads a fixture created with `create_fixture`. Fails if the contracts deployed in
+    /// the session differ from the ones the fixture was created with.
+    pub fn load_fixture(&mut self, fixture: &StateFixture) -> Result<(), String> {
+        let contracts_hashes = self.get_contracts_hashes();
+        for (contract_id, hash) in fixture.contracts_hashes.iter() {
+            match contracts_hashes.get(contract_id) {
+                Some(current_hash) if current_hash == hash => {}
+                Some(_) => return Err(format!("fixture is outdated: {} changed", contract_id)),
+                None => {
+                    return Err(format!(
+                        "fixture is outdated: {} is not deployed",
+                        contract_id
+                    ))
+                }
+            }
+        }
+        if let Some(contract_id) = contracts_hashes
+            .keys()
+            .find(|contract_id| !fixture.contracts_hashes.contains_key(*contract_id))
+        {
+            return Err(format!("fixture is outdated: {} was added", contract_id));
+        }
+        self.load_state(&fixture.state)
+    }
+
+    /// Exports the contracts involved in a call to `contract_id` (the contract and the ones it
+    /// depends on), their data and the balances of the accounts found in it, of their
+    /// deployers and of the tx-sender
+    pub fn create_repro_bundle(
+        &mut self,
+        contract_id: &QualifiedContractIdentifier,
+    ) -> Result<ReproBundle, String> {
+        if !self.contracts.contains_key(contract_id) {
+            return Err(format!("contract {} not found", contract_id));
+        }
+        let mut sorted = vec![];
+        sort_dependencies(
+            &self.contracts,
+            contract_id,
+            &mut BTreeSet::new(),
+            &mut sorted,
+        );
+
+        let mut state = StateDump::default();
+        let mut contracts = vec![];
+        let mut accounts = BTreeSet::from([self.get_tx_sender()]);
+        for contract_id in sorted.iter() {
+            let contract = &self.contracts[contract_id];
+            contracts.push(ReproContract {
+                contract_id: contract_id.to_string(),
+                source: contract.code.clone(),
+                clarity_version: contract.analysis.clarity_version,
+            });
+            accounts.insert(contract_id.issuer.to_address());
+            let contract_dump =
+                state_dump::dump_contract_state(&self.interpreter.clarity_datastore, contract_id);
+            if !contract_dump.is_empty() {
+                state
+                    .contracts
+                    .insert(contract_id.to_string(), contract_dump);
+            }
+        }
+        accounts.extend(repro::get_state_accounts(&state));
+
+        let stx_balances = accounts
+            .into_iter()
+            .map(|account| {
+                let balance = self.interpreter.get_balance_for_account(&account, "STX");
+                (account, balance)
+            })
+            .filter(|(_, balance)| *balance > 0)
+            .collect();
+        let snippet = self
+            .transcript
+            .iter()
+            .rev()
+            .find(|entry| !entry.command.starts_with("::"))
+            .map(|entry| entry.command.clone());
+        Ok(ReproBundle {
+            epoch: self.current_epoch,
+            block_height: self.interpreter.get_block_height(),
+            contracts,
+            state,
+            stx_balances,
+            tx_sender: self.get_tx_sender(),
+            snippet,
+        })
+    }
+
+    /// Deploys the contracts of a bundle created with `create_repro_bundle`, in the epoch of
+    /// the bundle, then restores their data, the balances and the block height
+    pub fn load_repro_bundle(&mut self, bundle: &ReproBundle) -> Result<(), String> {
+        self.update_epoch(bundle.epoch);
+        for contract in bundle.contracts.iter() {
+            let contract_id = QualifiedContractIdentifier::parse(&contract.contract_id)
+                .map_err(|e| format!("invalid contract id {}: {}", contract.contract_id, e))?;
+            let clarity_contract = ClarityContract {
+                code_source: ClarityCodeSource::ContractInMemory(contract.source.clone()),
+                name: contract_id.name.to_string(),
+                deployer: ContractDeployer::ContractIdentifier(contract_id.clone()),
+                clarity_version: contract.clarity_version,
+                epoch: bundle.epoch,
+            };
+            self.deploy_contract(&clarity_contract, false, None)
+                .map_err(|diagnostics| {
+                    let messages: Vec<String> =
+                        diagnostics.into_iter().map(|d| d.message).collect();
+                    format!("unable to deploy {}: {}", contract_id, messages.join(", "))
+                })?;
+        }
+        self.load_state(&bundle.state)?;
+
+        for (account, balance) in bundle.stx_balances.iter() {
+            let recipient = PrincipalData::parse(account)
+                .map_err(|e| format!("invalid account {}: {}", account, e))?;
+            let amount = u64::try_from(*balance)
+                .map_err(|_| format!("invalid balance of {}: {}", account, balance))?;
+            self.interpreter.mint_stx_balance(recipient, amount)?;
+        }
+        let block_height = self.interpreter.get_block_height();
+        if bundle.block_height > block_height {
+            self.advance_chain_tip(bundle.block_height - block_height);
+        }
+        PrincipalData::parse_standard_principal(&bundle.tx_sender)
+            .map_err(|e| format!("invalid tx-sender {}: {}", bundle.tx_sender, e))?;
+        self.set_tx_sender(&bundle.tx_sender);
+        Ok(())
+    }
+
+    #[cfg(feature = "cli")]
+    fn export_repro(&mut self, cmd: &str) -> String {
+        let args: Vec<&str> = cmd.split_whitespace().skip(1).collect();
+        let (contract_id, filename) = match args.as_slice() {
+            [contract, filename] => match self.resolve_contract_id(contract) {
+                Some(contract_id) => (contract_id, filename),
+                None => {
+                    return format!("invalid contract id {}", contract)
+                        .red()
+                        .to_string()
+                }
+            },
+            _ => {
+                return "Usage: ::export_repro <contract> <filename>"
+                    .red()
+                    .to_string()
+            }
+        };
+        let bundle = match self.create_repro_bundle(&contract_id) {
+            Ok(bundle) => bundle,
+            Err(e) => return e.red().to_string(),
+        };
+        let content = serde_json::to_string_pretty(&bundle).unwrap_or_default();
+        match std::fs::write(filename, content) {
+            Ok(_) => format!(
+                "{} contracts exported in {}, load them with `clarinet repro load {}`",
+                bundle.contracts.len(),
+                filename,
+                filename
+            )
+            .green()
+            .to_string(),
+            Err(err) => format!("unable to write {}: {}", filename, err)
+                .red()
+                .to_string(),
+        }
+    }
+
     pub fn get_accounts(&self) -> Option<String> {
         let accounts = self.interpreter.get_accounts();
         if accounts.is_empty() {
@@ -1140,6 +2646,17 @@ impl Session {
         Some(format!("{}", table))
     }
 
+    fn get_addresses(&self) -> String {
+        if self.address_book.is_empty() {
+            return "No named principal".into();
+        }
+        self.address_book
+            .iter()
+            .map(|(name, principal)| format!("@{}\t{}", name, principal))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
     #[cfg(feature = "cli")]
     pub fn get_contracts(&self) -> Option<String> {
         if self.contracts.is_empty() {
@@ -1172,21 +2689,135 @@ impl Session {
                 ]));
             }
         }
-        Some(format!("{}", table))
+        Some(format!("{}", table))
+    }
+
+    #[cfg(not(feature = "cli"))]
+    fn get_contracts(&self) -> Option<String> {
+        if self.contracts.is_empty() {
+            return None;
+        }
+        Some(
+            self.contracts
+                .keys()
+                .map(|contract_id| contract_id.to_string())
+                .collect::<Vec<String>>()
+                .join("\n"),
+        )
+    }
+
+    /// `::call <contract> <function> [args...]`, each argument being a Clarity value
+    fn parse_and_call(&mut self, command: &str) -> String {
+        let usage = "Usage: ::call <contract> <function> [args...]";
+        let arguments = match command.split_once(' ') {
+            Some((_, arguments)) => match split_arguments(arguments) {
+                Ok(arguments) => arguments,
+                Err(e) => return e.red().to_string(),
+            },
+            None => return usage.red().to_string(),
+        };
+        let [contract, method, values @ ..] = arguments.as_slice() else {
+            return usage.red().to_string();
+        };
+
+        let tx_sender = self.get_tx_sender();
+        let resolved_contract = match self.address_book.resolve_principal(contract) {
+            Ok(contract) => contract,
+            Err(e) => return e.red().to_string(),
+        };
+        let contract = resolved_contract
+            .strip_prefix('.')
+            .unwrap_or(&resolved_contract);
+        let contract_id = if contract.starts_with('S') {
+            contract.to_string()
+        } else {
+            format!("{}.{}", tx_sender, contract)
+        };
+        if QualifiedContractIdentifier::parse(&contract_id).is_err() {
+            return format!("invalid contract identifier: {}", contract)
+                .red()
+                .to_string();
+        }
+
+        let mut args = vec![];
+        for value in values {
+            let resolved_value = match self.address_book.resolve_expression(value) {
+                Ok(value) => value,
+                Err(e) => return e.red().to_string(),
+            };
+            match self.eval(resolved_value, false) {
+                Ok(ExecutionResult {
+                    result: EvaluationResult::Snippet(result),
+                    ..
+                }) => args.push(SymbolicExpression::atom_value(result.result)),
+                _ => return format!("invalid argument: {}", value).red().to_string(),
+            }
+        }
+
+        match self.call_contract_fn(contract, method, &args, &tx_sender, false, false) {
+            Ok(result) => {
+                let mut output = vec![];
+                if !result.events.is_empty() {
+                    output.push(black!("Events emitted"));
+                    for event in result.events.iter() {
+                        output.push(black!(format!("{}", utils::serialize_event(event))));
+                    }
+                }
+                if let EvaluationResult::Snippet(snippet_result) = &result.result {
+                    output.push(value_to_string(&snippet_result.result).green().to_string());
+                }
+                output.join("\n")
+            }
+            Err(diagnostics) => diagnostics
+                .iter()
+                .map(|diagnostic| diagnostic.message.red().to_string())
+                .collect::<Vec<String>>()
+                .join("\n"),
+        }
     }
 
-    #[cfg(not(feature = "cli"))]
-    fn get_contracts(&self) -> Option<String> {
-        if self.contracts.is_empty() {
-            return None;
+    fn parse_and_get_sbtc_balance(&mut self, command: &str) -> String {
+        let args: Vec<_> = command.split(' ').collect();
+
+        if args.len() != 2 {
+            return "Usage: ::sbtc-balance <principal>".red().to_string();
+        }
+
+        let principal = match self.address_book.resolve_principal(args[1]) {
+            Ok(principal) => principal,
+            Err(e) => return e.red().to_string(),
+        };
+        match self.get_sbtc_balance(&principal) {
+            Ok(balance) => format!("{} sats", balance),
+            Err(e) => e.red().to_string(),
+        }
+    }
+
+    fn parse_and_mint_sbtc(&mut self, command: &str) -> String {
+        let args: Vec<_> = command.split(' ').collect();
+
+        if args.len() != 3 {
+            return "Usage: ::sbtc-fund <principal> <amount>".red().to_string();
+        }
+
+        let recipient = match self.address_book.resolve_principal(args[1]) {
+            Ok(recipient) => recipient,
+            Err(e) => return e.red().to_string(),
+        };
+        let amount: u128 = match args[2].parse() {
+            Ok(amount) => amount,
+            _ => return "Unable to parse the amount".red().to_string(),
+        };
+
+        if let Err(e) = self.mint_sbtc(&recipient, amount) {
+            return e.red().to_string();
+        }
+        match self.get_sbtc_balance(&recipient) {
+            Ok(balance) => format!("→ {}: {} sats", recipient, balance)
+                .green()
+                .to_string(),
+            Err(e) => e.red().to_string(),
         }
-        Some(
-            self.contracts
-                .keys()
-                .map(|contract_id| contract_id.to_string())
-                .collect::<Vec<String>>()
-                .join("\n"),
-        )
     }
 
     fn mint_stx(&mut self, command: &str) -> String {
@@ -1198,9 +2829,14 @@ impl Session {
                 .to_string();
         }
 
-        let recipient = match PrincipalData::parse(args[1]) {
-            Ok(address) => address,
-            _ => return "Unable to parse the address".red().to_string(),
+        let recipient = match self
+            .address_book
+            .resolve_principal(args[1])
+            .map(|recipient| PrincipalData::parse(&recipient))
+        {
+            Err(e) => return e.red().to_string(),
+            Ok(Ok(address)) => address,
+            Ok(Err(_)) => return "Unable to parse the address".red().to_string(),
         };
 
         let amount: u64 = match args[2].parse() {
@@ -1208,6 +2844,7 @@ impl Session {
             _ => return "Unable to parse the balance".red().to_string(),
         };
 
+        self.invalidate_read_only_cache();
         match self.interpreter.mint_stx_balance(recipient, amount) {
             Ok(msg) => msg.green().to_string(),
             Err(err) => err.red().to_string(),
@@ -1229,8 +2866,20 @@ impl Session {
             s
         };
 
+        let version = ClarityVersion::default_for_epoch(self.current_epoch);
+        let unavailability = [
+            NativeKind::Function,
+            NativeKind::Define,
+            NativeKind::Variable,
+        ]
+        .into_iter()
+        .find_map(|kind| crate::analysis::natives::lookup_native_keyword(&keyword, kind))
+        .and_then(|native| native.get_unavailability_reason(version));
         match self.lookup_functions_or_keywords_docs(&keyword) {
-            Some(doc) => format!("{}", doc.yellow()),
+            Some(doc) => match unavailability {
+                Some(reason) => format!("{}\n\n{}", doc.yellow(), reason.red()),
+                None => format!("{}", doc.yellow()),
+            },
             None => format!(
                 "{}",
                 "It looks like there aren't matches for your search".red()
@@ -1286,6 +2935,23 @@ fn decode_hex(byte_string: &str) -> Result<Vec<u8>, DecodeHexError> {
     }
 }
 
+/// Seconds of a duration such as `90d`, `12h`, `30m` or `600s`, in seconds without a unit
+fn parse_duration(duration: &str) -> Option<u64> {
+    let (value, unit) = match duration.find(|c: char| !c.is_ascii_digit()) {
+        Some(index) => duration.split_at(index),
+        None => (duration, "s"),
+    };
+    let multiplier = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => return None,
+    };
+    value.parse::<u64>().ok()?.checked_mul(multiplier)
+}
+
 fn build_api_reference() -> HashMap<String, String> {
     let mut api_reference = HashMap::new();
     for func in NativeFunctions::ALL.iter() {
@@ -1337,6 +3003,62 @@ fn clarity_keywords() -> HashMap<String, String> {
     keywords
 }
 
+/// Split the arguments of a console command on whitespaces, keeping lists, tuples and
+/// strings such as `(list u1 u2)` or `"hello world"` together
+fn split_arguments(input: &str) -> Result<Vec<String>, String> {
+    let mut arguments = vec![];
+    let mut current = String::new();
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for c in input.chars() {
+        if in_string {
+            current.push(c);
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => {
+                in_string = true;
+                current.push(c);
+            }
+            '(' | '{' => {
+                depth += 1;
+                current.push(c);
+            }
+            ')' | '}' => {
+                depth = depth
+                    .checked_sub(1)
+                    .ok_or(format!("unexpected '{}' in arguments", c))?;
+                current.push(c);
+            }
+            c if c.is_whitespace() && depth == 0 => {
+                if !current.is_empty() {
+                    arguments.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+
+    if in_string {
+        return Err("unterminated string in arguments".to_string());
+    }
+    if depth > 0 {
+        return Err("unbalanced parentheses in arguments".to_string());
+    }
+    if !current.is_empty() {
+        arguments.push(current);
+    }
+    Ok(arguments)
+}
+
 #[allow(clippy::items_after_test_module)]
 #[cfg(test)]
 mod tests {
@@ -1388,6 +3110,112 @@ mod tests {
         assert_eq!(balance, 1000000);
     }
 
+    #[test]
+    fn named_principals_in_console_commands() {
+        let address = "ST1SJ3DTE5DN7X54YDH5D64R3BCB6A2AG2ZQ8YPD5";
+        let mut session = Session::new(SessionSettings {
+            initial_accounts: vec![Account {
+                address: address.to_owned(),
+                balance: 1000000,
+                name: "wallet_1".to_owned(),
+            }],
+            ..Default::default()
+        });
+        let _ = session.start();
+
+        let _ = session.handle_command("::set_tx_sender @wallet_1");
+        assert_eq!(session.get_tx_sender(), address);
+        let _ = session.handle_command("::mint_stx @wallet_1 1000");
+        let balance = session.interpreter.get_balance_for_account(address, "STX");
+        assert_eq!(balance, 1001000);
+
+        let result = session.handle_command("::set_tx_sender @treasury");
+        assert_eq!(result, "unknown principal @treasury".red().to_string());
+    }
+
+    #[test]
+    fn mint_sbtc() {
+        let address = "ST1SJ3DTE5DN7X54YDH5D64R3BCB6A2AG2ZQ8YPD5";
+        let mut session = Session::new(SessionSettings::default());
+        session.update_epoch(StacksEpochId::Epoch30);
+        session.advance_burn_chain_tip(1);
+        assert!(session.mint_sbtc(address, 1000).is_err());
+
+        // stubs of the sBTC contracts, with the checks of the deposits
+        let token = "(define-fungible-token sbtc-token)
+            (define-read-only (get-balance (who principal)) (ok (ft-get-balance sbtc-token who)))
+            (define-public (protocol-mint (amount uint) (recipient principal))
+                (ft-mint? sbtc-token amount recipient))";
+        let deposit = "(define-map deposits (buff 32) bool)
+            (define-public (complete-deposit-wrapper (txid (buff 32)) (vout-index uint) (amount uint) (recipient principal) (burn-hash (buff 32)) (burn-height uint) (sweep-txid (buff 32)))
+                (begin
+                    (asserts! (is-eq tx-sender 'SM3VDXK3WZZSA84XXFKAFAF15NNZX32CTSG82JFQ4) (err u1))
+                    (asserts! (is-eq (get-burn-block-info? header-hash burn-height) (some burn-hash)) (err u2))
+                    (asserts! (map-insert deposits txid true) (err u3))
+                    (contract-call? .sbtc-token protocol-mint amount recipient)))";
+        for (name, source) in [("sbtc-token", token), ("sbtc-deposit", deposit)] {
+            let contract = ClarityContract {
+                code_source: ClarityCodeSource::ContractInMemory(source.to_string()),
+                name: name.to_string(),
+                deployer: ContractDeployer::Address(SBTC_MAINNET_ADDRESS.to_string()),
+                clarity_version: ClarityVersion::Clarity3,
+                epoch: StacksEpochId::Epoch30,
+            };
+            session.deploy_contract(&contract, false, None).unwrap();
+        }
+
+        session.set_tx_sender(address);
+        session.mint_sbtc(address, 1000).unwrap();
+        session.mint_sbtc(address, 1000).unwrap();
+        assert_eq!(session.get_sbtc_balance(address), Ok(2000));
+        assert_eq!(session.get_tx_sender(), address);
+        assert_eq!(
+            session.handle_command(&format!("::sbtc-fund {} 500", address)),
+            format!("→ {}: 2500 sats", address).green().to_string()
+        );
+        assert_eq!(
+            session.handle_command(&format!("::sbtc-balance {}", address)),
+            "2500 sats"
+        );
+    }
+
+    #[test]
+    fn advance_reward_cycles_and_time() {
+        let mut session = Session::new(SessionSettings::default());
+        session.update_epoch(StacksEpochId::Epoch25);
+        let cycle_length = session
+            .interpreter
+            .datastore
+            .get_constants()
+            .pox_reward_cycle_length;
+        let reward_cycle = session.get_current_reward_cycle();
+        assert_eq!(session.advance_reward_cycles(2, None), Ok(reward_cycle + 2));
+        assert_eq!(
+            session.interpreter.get_burn_block_height(),
+            (reward_cycle + 2) * cycle_length
+        );
+        assert!(session.advance_reward_cycles(1, Some(3)).is_err());
+
+        let burn_height = session.interpreter.get_burn_block_height();
+        assert_eq!(session.advance_time(3601, None), Ok(burn_height + 7));
+
+        session.update_epoch(StacksEpochId::Epoch30);
+        let stacks_height = session.interpreter.get_block_height();
+        let burn_height = session.advance_time(24 * 60 * 60, Some(3)).unwrap();
+        assert_eq!(burn_height, session.interpreter.get_burn_block_height());
+        assert_eq!(
+            session.interpreter.get_block_height(),
+            stacks_height + 144 * 3
+        );
+        assert!(session.advance_time(600, Some(0)).is_err());
+
+        let result = session.handle_command("::advance-cycles 1");
+        assert!(result.contains(&format!("reward cycle: {}", reward_cycle + 3)));
+        assert_eq!(parse_duration("90d"), Some(90 * 24 * 60 * 60));
+        assert_eq!(parse_duration("600"), Some(600));
+        assert_eq!(parse_duration("3y"), None);
+    }
+
     #[test]
     fn epoch_switch() {
         let mut session = Session::new(SessionSettings::default());
@@ -1453,6 +3281,47 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_set_burn_block_info() {
+        let mut session = Session::new(SessionSettings::default());
+        session.update_epoch(StacksEpochId::Epoch21);
+        session.advance_burn_chain_tip(5);
+
+        let header_hash = "0x0101010101010101010101010101010101010101010101010101010101010101";
+        session.set_burn_block_header_hash(2, header_hash).unwrap();
+        let result = run_session_snippet(&mut session, "(get-burn-block-info? header-hash u2)");
+        assert_eq!(
+            result,
+            Value::some(Value::buff_from(vec![0x01; 32]).unwrap()).unwrap()
+        );
+
+        let addr = Value::Tuple(
+            TupleData::from_data(vec![
+                ("version".into(), Value::buff_from_byte(0x04)),
+                (
+                    "hashbytes".into(),
+                    Value::buff_from(vec![0x02; 20]).unwrap(),
+                ),
+            ])
+            .unwrap(),
+        );
+        session
+            .set_pox_payout_addrs(2, vec![addr.clone()], 1000)
+            .unwrap();
+        let result = run_session_snippet(&mut session, "(get-burn-block-info? pox-addrs u2)");
+        let Value::Tuple(info) = result.expect_optional().unwrap().unwrap() else {
+            panic!("expected a tuple");
+        };
+        assert_eq!(info.get("payout").unwrap(), &Value::UInt(1000));
+        let addrs = info.get("addrs").unwrap().clone().expect_list().unwrap();
+        assert_eq!(addrs, vec![addr]);
+
+        let err = session
+            .set_pox_payout_addrs(2, vec![Value::UInt(1)], 1000)
+            .unwrap_err();
+        assert!(err.starts_with("invalid pox address"));
+    }
+
     #[test]
     fn test_parse_and_advance_burn_chain_tip_epoch3() {
         let mut session = Session::new(SessionSettings::default());
@@ -1508,6 +3377,44 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_split_arguments() {
+        assert_eq!(
+            split_arguments("counter add u1  (list u1 u2) { a: 1, b: \"x y\" }"),
+            Ok(vec![
+                "counter".to_string(),
+                "add".to_string(),
+                "u1".to_string(),
+                "(list u1 u2)".to_string(),
+                "{ a: 1, b: \"x y\" }".to_string(),
+            ])
+        );
+        assert!(split_arguments("counter add (list u1").is_err());
+        assert!(split_arguments("counter add \"hello").is_err());
+    }
+
+    #[test]
+    fn call_contract_from_console() {
+        let mut session = Session::new(SessionSettings::default());
+        session.start().expect("session could not start");
+        let snippet = "(define-public (add (a uint) (b (list 2 uint))) (ok (fold + b a)))";
+        let _ = session.formatted_interpretation(
+            snippet.to_string(),
+            Some("calc".to_string()),
+            false,
+            None,
+        );
+
+        let result = session.handle_command("::call calc add u1 (list u2 u3)");
+        assert_eq!(result, "(ok u6)".green().to_string());
+
+        let result = session.handle_command("::call calc add u1 (list u2");
+        assert_eq!(
+            result,
+            "unbalanced parentheses in arguments".red().to_string()
+        );
+    }
+
     #[test]
     fn encode_error() {
         let mut session = Session::new(SessionSettings::default());
@@ -1605,6 +3512,33 @@ mod tests {
         );
     }
 
+    #[test]
+    fn eval_many() {
+        let settings = SessionSettings {
+            include_boot_contracts: vec!["costs".into(), "costs-2".into(), "costs-3".into()],
+            ..Default::default()
+        };
+        let mut session = Session::new(settings);
+        session.start().expect("session could not start");
+        let outcomes = session.eval_many(
+            vec![
+                "(define-data-var count uint u1)".to_string(),
+                "(+ 1 2)".to_string(),
+                "(+ 1 u2)".to_string(),
+                "(print u1)".to_string(),
+            ],
+            true,
+        );
+        assert_eq!(outcomes.len(), 4);
+        assert!(outcomes[0].is_success());
+        assert_eq!(outcomes[1].value, Some(Value::Int(3)));
+        assert!(outcomes[1].cost.is_some());
+        assert!(!outcomes[2].is_success());
+        assert_eq!(outcomes[2].value, None);
+        assert!(outcomes[3].is_success());
+        assert_eq!(outcomes[3].events.len(), 1);
+    }
+
     #[test]
     fn evaluate_at_block() {
         let settings = SessionSettings {
@@ -1684,6 +3618,78 @@ mod tests {
         assert_eq!(session.process_console_input("(at-block (unwrap-panic (get-block-info? id-header-hash u10000)) (contract-call? .contract get-x))").1[0], "u1".green().to_string());
     }
 
+    #[test]
+    fn fabricate_history_for_at_block() {
+        let mut session = Session::new(SessionSettings::default());
+        session.update_epoch(DEFAULT_EPOCH);
+        let snippet = "
+            (define-data-var price uint u100)
+            (define-map balances principal uint)
+            (define-read-only (get-price-at (height uint))
+                (at-block (unwrap-panic (get-stacks-block-info? id-header-hash height))
+                    (var-get price)))
+            (define-read-only (get-balance-at (height uint) (who principal))
+                (at-block (unwrap-panic (get-stacks-block-info? id-header-hash height))
+                    (map-get? balances who)))";
+        let deployer = "ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM";
+        let contract = ClarityContractBuilder::default()
+            .code_source(snippet.to_string())
+            .deployer(deployer)
+            .build();
+        let contract_id = contract.expect_resolved_contract_identifier(None);
+        session.deploy_contract(&contract, false, None).unwrap();
+        let tip = session.advance_stacks_chain_tip(10).unwrap();
+        assert!(session.get_block_id(tip).unwrap().starts_with("0x"));
+        assert!(session.get_block_id(tip + 1).is_err());
+
+        let who = PrincipalData::parse(deployer).unwrap();
+        session
+            .set_data_var_at_block(tip - 5, &contract_id, "price", Value::UInt(50))
+            .unwrap();
+        session
+            .set_map_entry_at_block(
+                tip - 7,
+                &contract_id,
+                "balances",
+                Value::Principal(who.clone()),
+                Some(Value::UInt(7)),
+            )
+            .unwrap();
+        session
+            .set_map_entry_at_block(
+                tip - 2,
+                &contract_id,
+                "balances",
+                Value::Principal(who),
+                None,
+            )
+            .unwrap();
+        let err = session
+            .set_data_var_at_block(tip - 5, &contract_id, "price", Value::Int(50))
+            .unwrap_err();
+        assert!(err.starts_with("unable to set price"));
+        assert!(session
+            .set_data_var_at_block(tip + 1, &contract_id, "price", Value::UInt(50))
+            .is_err());
+
+        let mut price_at = |height: u32| {
+            let snippet = format!("(contract-call? '{contract_id} get-price-at u{height})");
+            run_session_snippet(&mut session, &snippet)
+        };
+        assert_eq!(price_at(tip - 6), Value::UInt(100));
+        assert_eq!(price_at(tip - 5), Value::UInt(50));
+        assert_eq!(price_at(tip - 1), Value::UInt(50));
+
+        let mut balance_at = |height: u32| {
+            let snippet =
+                format!("(contract-call? '{contract_id} get-balance-at u{height} '{deployer})");
+            run_session_snippet(&mut session, &snippet)
+        };
+        assert_eq!(balance_at(tip - 8), Value::none());
+        assert_eq!(balance_at(tip - 3), Value::some(Value::UInt(7)).unwrap());
+        assert_eq!(balance_at(tip - 1), Value::none());
+    }
+
     #[test]
     fn can_deploy_a_contract() {
         let settings = SessionSettings::default();
@@ -1697,6 +3703,37 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn get_loaded_boot_contracts() {
+        let settings = SessionSettings {
+            include_boot_contracts: vec!["pox-4".into()],
+            ..Default::default()
+        };
+        let mut session = Session::new(settings);
+        assert!(session.get_boot_contracts().is_empty());
+        session.update_epoch(StacksEpochId::Epoch25);
+        session.load_boot_contracts();
+
+        let boot_contracts = session.get_boot_contracts();
+        assert_eq!(boot_contracts.len(), 2);
+        let pox_4 = boot_contracts
+            .iter()
+            .find(|contract| contract.address == BOOT_MAINNET_ADDRESS)
+            .unwrap();
+        assert_eq!(pox_4.name, "pox-4");
+        assert_eq!(pox_4.contract_id, format!("{}.pox-4", BOOT_MAINNET_ADDRESS));
+        assert_eq!(pox_4.epoch, StacksEpochId::Epoch25.to_string());
+        assert_eq!(pox_4.clarity_version, ClarityVersion::Clarity2.to_string());
+        let (_, code) = STACKS_BOOT_CODE_MAINNET
+            .iter()
+            .find(|(name, _)| *name == "pox-4")
+            .unwrap();
+        assert_eq!(
+            pox_4.source_hash,
+            Sha256Sum::from_data(code.as_bytes()).to_hex()
+        );
+    }
+
     #[test]
     fn can_call_boot_contract_fn() {
         let settings = SessionSettings {
@@ -1767,6 +3804,31 @@ mod tests {
         assert_execution_result_value(&result, Value::UInt(1));
     }
 
+    #[test]
+    fn read_only_calls_are_memoized_until_a_write() {
+        let mut session = Session::new(SessionSettings::default());
+        session.start().expect("session could not start");
+        session.update_epoch(DEFAULT_EPOCH);
+        session.enable_read_only_cache();
+        let contract = ClarityContractBuilder::default().build();
+        let _ = session.deploy_contract(&contract, false, None);
+
+        let sender = session.get_tx_sender();
+        for _ in 0..2 {
+            let result = session.call_contract_fn("contract", "get-x", &[], &sender, false, false);
+            assert_execution_result_value(&result, Value::UInt(0));
+        }
+        let _ = session.call_contract_fn("contract", "incr", &[], &sender, false, false);
+        let result = session.call_contract_fn("contract", "get-x", &[], &sender, false, false);
+        assert_execution_result_value(&result, Value::UInt(1));
+
+        let stats = session.get_read_only_cache_stats().unwrap();
+        assert_eq!(stats.hits, 1);
+        assert_eq!(stats.misses, 2);
+        assert_eq!(stats.invalidations, 1);
+        assert_eq!(stats.entries, 1);
+    }
+
     #[test]
     fn current_block_info_is_none() {
         let settings = SessionSettings::default();
@@ -1802,4 +3864,270 @@ mod tests {
 
         assert!(time_block_2 - time_block_1 == 600);
     }
+
+    #[test]
+    fn dump_and_load_state() {
+        let snippet = [
+            "(define-data-var x uint u0)",
+            "(define-map balances principal uint)",
+            "(define-fungible-token token)",
+            "(define-read-only (get-x) (ok (var-get x)))",
+            "(define-public (incr)",
+            "  (begin",
+            "    (var-set x (+ (var-get x) u1))",
+            "    (map-set balances tx-sender (var-get x))",
+            "    (ft-mint? token u100 tx-sender)))",
+        ]
+        .join("\n");
+        let contract = ClarityContractBuilder::new().code_source(snippet).build();
+
+        let mut session = Session::new(SessionSettings::default());
+        session.update_epoch(DEFAULT_EPOCH);
+        session.deploy_contract(&contract, false, None).unwrap();
+        let empty_session = session.clone();
+        session
+            .eval("(contract-call? .contract incr)".into(), false)
+            .unwrap();
+
+        let dump = session.dump_state(None).unwrap();
+        let (contract_id, contract_dump) = dump.contracts.first_key_value().unwrap();
+        assert!(contract_id.ends_with(".contract"));
+        assert_eq!(contract_dump.data_vars.len(), 1);
+        assert_eq!(contract_dump.maps.get("balances").unwrap().len(), 1);
+        assert_eq!(contract_dump.fungible_tokens.get("token").unwrap().len(), 1);
+
+        // the dump can be serialized and loaded in a fresh session
+        let json = serde_json::to_string(&dump).unwrap();
+        let dump: StateDump = serde_json::from_str(&json).unwrap();
+        let mut fresh_session = empty_session;
+        fresh_session.load_state(&dump).unwrap();
+        assert_eq!(fresh_session.dump_state(None).unwrap(), dump);
+
+        let x = run_session_snippet(&mut fresh_session, "(contract-call? .contract get-x)");
+        assert_eq!(x, Value::okay(Value::UInt(1)).unwrap());
+    }
+
+    #[test]
+    fn repro_bundle_reproduces_the_contracts_state() {
+        let token = ClarityContractBuilder::new()
+            .name("token")
+            .code_source(
+                "(define-map balances principal uint)
+                (define-public (mint (amount uint))
+                  (ok (map-set balances tx-sender amount)))
+                (define-read-only (get-balance (who principal))
+                  (default-to u0 (map-get? balances who)))"
+                    .into(),
+            )
+            .build();
+        let vault = ClarityContractBuilder::new()
+            .name("vault")
+            .code_source(
+                "(define-read-only (get-deposit) (contract-call? .token get-balance tx-sender))"
+                    .into(),
+            )
+            .build();
+        let unrelated = ClarityContractBuilder::new().name("unrelated").build();
+
+        let mut session = Session::new(SessionSettings::default());
+        session.update_epoch(DEFAULT_EPOCH);
+        for contract in [&token, &vault, &unrelated] {
+            session.deploy_contract(contract, false, None).unwrap();
+        }
+        session
+            .eval("(contract-call? .token mint u42)".into(), false)
+            .unwrap();
+        let vault_id = session.resolve_contract_id("vault").unwrap();
+        let bundle = session.create_repro_bundle(&vault_id).unwrap();
+        let contracts: Vec<&str> = bundle
+            .contracts
+            .iter()
+            .map(|contract| contract.contract_id.rsplit('.').next().unwrap())
+            .collect();
+        assert_eq!(contracts, vec!["token", "vault"]);
+
+        let json = serde_json::to_string(&bundle).unwrap();
+        let bundle: ReproBundle = serde_json::from_str(&json).unwrap();
+        let mut fresh_session = Session::new(SessionSettings::default());
+        fresh_session.load_repro_bundle(&bundle).unwrap();
+        assert_eq!(fresh_session.dump_state(None).unwrap(), bundle.state);
+        let deposit =
+            run_session_snippet(&mut fresh_session, "(contract-call? .vault get-deposit)");
+        assert_eq!(deposit, Value::UInt(42));
+    }
+
+    #[test]
+    fn fixtures_are_invalidated_when_contracts_change() {
+        let contract = ClarityContractBuilder::new().build();
+        let mut session = Session::new(SessionSettings::default());
+        session.update_epoch(DEFAULT_EPOCH);
+        session.deploy_contract(&contract, false, None).unwrap();
+        let empty_session = session.clone();
+        session
+            .eval("(contract-call? .contract incr)".into(), false)
+            .unwrap();
+        let fixture = session.create_fixture().unwrap();
+
+        let mut fresh_session = empty_session;
+        assert!(fresh_session.load_fixture(&fixture).is_ok());
+
+        let updated_contract = ClarityContractBuilder::new()
+            .code_source("(define-data-var x uint u1)".into())
+            .build();
+        let mut updated_session = Session::new(SessionSettings::default());
+        updated_session.update_epoch(DEFAULT_EPOCH);
+        updated_session
+            .deploy_contract(&updated_contract, false, None)
+            .unwrap();
+        let err = updated_session.load_fixture(&fixture).unwrap_err();
+        assert!(err.starts_with("fixture is outdated"));
+    }
+
+    #[test]
+    fn call_as_contract() {
+        let deployer = "ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM";
+        let recipient = "ST1SJ3DTE5DN7X54YDH5D64R3BCB6A2AG2ZQ8YPD5";
+        let mut session = Session::new(SessionSettings::default());
+        session.update_epoch(DEFAULT_EPOCH);
+        let token = ClarityContractBuilder::new()
+            .name("token")
+            .deployer(deployer)
+            .code_source(
+                [
+                    "(define-fungible-token gold)",
+                    "(define-public (mint (amount uint) (recipient principal))",
+                    "  (ft-mint? gold amount recipient))",
+                    "(define-public (transfer (amount uint) (recipient principal))",
+                    "  (ft-transfer? gold amount tx-sender recipient))",
+                    "(define-public (pay (amount uint) (recipient principal))",
+                    "  (stx-transfer? amount tx-sender recipient))",
+                ]
+                .join("\n"),
+            )
+            .build();
+        let treasury = ClarityContractBuilder::new()
+            .name("treasury")
+            .deployer(deployer)
+            .build();
+        session.deploy_contract(&token, false, None).unwrap();
+        session.deploy_contract(&treasury, false, None).unwrap();
+
+        let token_id = format!("{deployer}.token");
+        let treasury_id = format!("{deployer}.treasury");
+        let treasury_principal = PrincipalData::parse(&treasury_id).unwrap();
+        let recipient_principal = PrincipalData::parse(recipient).unwrap();
+        let mint = session.call_contract_fn(
+            &token_id,
+            "mint",
+            &[
+                SymbolicExpression::atom_value(Value::UInt(1000)),
+                SymbolicExpression::atom_value(Value::Principal(treasury_principal.clone())),
+            ],
+            deployer,
+            false,
+            false,
+        );
+        assert_execution_result_value(&mint, Value::okay_true());
+        session
+            .interpreter
+            .mint_stx_balance(treasury_principal, 500)
+            .unwrap();
+
+        let args = [
+            SymbolicExpression::atom_value(Value::UInt(300)),
+            SymbolicExpression::atom_value(Value::Principal(recipient_principal)),
+        ];
+        let transfer =
+            session.call_as_contract(&treasury_id, &token_id, "transfer", &args, false, false);
+        assert_execution_result_value(&transfer, Value::okay_true());
+        let pay = session.call_as_contract(&treasury_id, &token_id, "pay", &args, false, false);
+        assert_execution_result_value(&pay, Value::okay_true());
+
+        assert_eq!(
+            session.assert_ft_balance(&token_id, "gold", &treasury_id, 700),
+            Ok(())
+        );
+        assert_eq!(
+            session.assert_ft_balance(&token_id, "gold", recipient, 300),
+            Ok(())
+        );
+        assert_eq!(session.assert_stx_balance(&treasury_id, 200), Ok(()));
+        assert_eq!(
+            session.assert_stx_balance(&treasury_id, 500),
+            Err(format!("{treasury_id} owns 200 µSTX, expected 500"))
+        );
+
+        // the tx-sender of a regular call is the wallet, not the treasury
+        let transfer =
+            session.call_contract_fn(&token_id, "transfer", &args, deployer, false, false);
+        assert_execution_result_value(&transfer, Value::err_uint(1));
+        assert!(session
+            .call_as_contract(
+                &format!("{deployer}.vault"),
+                &token_id,
+                "transfer",
+                &args,
+                false,
+                false
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn deferred_requirements() {
+        let deployer = "ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM";
+        let requirement = ClarityContractBuilder::new()
+            .name("counter")
+            .deployer(deployer)
+            .code_source(
+                [
+                    "(define-data-var count uint u10)",
+                    "(define-public (increment) (begin (var-set count (+ (var-get count) u1)) (ok (var-get count))))",
+                ]
+                .join("\n"),
+            )
+            .build();
+        let project_contract = ClarityContractBuilder::new()
+            .name("app")
+            .deployer(deployer)
+            .code_source("(define-public (bump) (contract-call? .counter increment))".to_string())
+            .build();
+        let app_id = format!("{deployer}.app");
+
+        let mut settings = SessionSettings::default();
+        settings.repl_settings.requirements_execution = RequirementsExecution::Lazy;
+        let mut session = Session::new(settings.clone());
+        session.update_epoch(DEFAULT_EPOCH);
+        let deferred = session.defer_contract(&requirement, None).unwrap();
+        assert!(matches!(
+            deferred.result,
+            EvaluationResult::Contract(ContractEvaluationResult { result: None, .. })
+        ));
+        // the dependents type-check against the analysis of the requirement
+        session
+            .deploy_contract(&project_contract, false, None)
+            .unwrap();
+        assert_eq!(session.get_deferred_contracts().len(), 1);
+
+        // executed by the first call needing it, with its initial state
+        let bump = session.call_contract_fn(&app_id, "bump", &[], deployer, false, false);
+        assert_execution_result_value(&bump, Value::okay(Value::UInt(11)).unwrap());
+        assert!(session.get_deferred_contracts().is_empty());
+        let bump = session.call_contract_fn(&app_id, "bump", &[], deployer, false, false);
+        assert_execution_result_value(&bump, Value::okay(Value::UInt(12)).unwrap());
+
+        settings.repl_settings.requirements_execution = RequirementsExecution::Disabled;
+        let mut session = Session::new(settings);
+        session.update_epoch(DEFAULT_EPOCH);
+        session.defer_contract(&requirement, None).unwrap();
+        session
+            .deploy_contract(&project_contract, false, None)
+            .unwrap();
+        let errors = session
+            .call_contract_fn(&app_id, "bump", &[], deployer, false, false)
+            .unwrap_err();
+        assert!(errors[0].message.starts_with(&format!(
+            "{deployer}.counter is published for its analysis only"
+        )));
+    }
 }
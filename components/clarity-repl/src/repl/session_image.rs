@@ -0,0 +1,216 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use clarity::types::StacksEpochId;
+use clarity::vm::analysis::ContractAnalysis;
+use clarity::vm::types::{PrincipalData, QualifiedContractIdentifier, TupleData};
+use clarity::vm::ParsedContract;
+
+use super::datastore::Datastore;
+use super::{ClarityCodeSource, ClarityContract, ContractDeployer, Session, SessionSettings};
+
+/// Versions of a datastore key: the block id (hex) and the value written in it
+pub type DataEntriesImage = BTreeMap<String, Vec<(String, String)>>;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ClarityDatastoreImage {
+    pub open_chain_tip: String,
+    pub current_chain_tip: String,
+    pub block_id_lookup: BTreeMap<String, String>,
+    pub height_at_chain_tip: BTreeMap<String, u32>,
+    pub entries: DataEntriesImage,
+    /// Metadata entries, as `(contract, key, value)`
+    pub metadata: Vec<(String, String, String)>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct StacksBlockImage {
+    pub block_header_hash: String,
+    pub burn_block_header_hash: String,
+    pub consensus_hash: String,
+    pub vrf_seed: String,
+    pub stacks_block_time: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DatastoreImage {
+    pub genesis_id: String,
+    pub burn_chain_height: u32,
+    /// Time and height of the burn blocks, by header hash
+    pub burn_blocks: BTreeMap<String, (u64, u32)>,
+    pub stacks_chain_height: u32,
+    pub stacks_blocks: BTreeMap<String, StacksBlockImage>,
+    pub sortition_lookup: BTreeMap<String, String>,
+    pub tenure_blocks_height: BTreeMap<u32, u32>,
+    pub consensus_hash_lookup: BTreeMap<String, String>,
+    pub current_epoch: StacksEpochId,
+    pub current_epoch_start_height: u32,
+    pub burn_start_height: u32,
+    pub pox_prepare_length: u32,
+    pub pox_reward_cycle_length: u32,
+    pub pox_rejection_fraction: u64,
+    pub burn_block_header_hashes: BTreeMap<u32, String>,
+    pub pox_payout_addrs: BTreeMap<u32, (Vec<TupleData>, u128)>,
+}
+
+/// A contract of the session. The type map of the analysis isn't serialized, and the AST
+/// is parsed again from the source when the image is restored.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ContractImage {
+    pub contract_id: String,
+    pub code: String,
+    pub function_args: BTreeMap<String, Vec<String>>,
+    pub analysis: ContractAnalysis,
+}
+
+/// State of a session once a deployment plan is executed: the data and the metadata of
+/// the contracts, the simulated chains, the accounts and the contracts. Restoring it is
+/// much faster than executing the plan again (the boot contracts alone take a while), as
+/// long as the `fingerprint` of the inputs of the plan is unchanged.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SessionImage {
+    pub fingerprint: String,
+    pub current_epoch: StacksEpochId,
+    pub tx_sender: String,
+    pub accounts: BTreeSet<String>,
+    pub tokens: BTreeMap<String, BTreeMap<String, u128>>,
+    pub address_book: BTreeMap<String, String>,
+    pub contracts: Vec<ContractImage>,
+    pub clarity_datastore: ClarityDatastoreImage,
+    pub datastore: DatastoreImage,
+}
+
+impl SessionImage {
+    pub fn from_session(session: &Session, fingerprint: &str) -> SessionImage {
+        let contracts = session
+            .contracts
+            .values()
+            .map(|contract| ContractImage {
+                contract_id: contract.contract_identifier.clone(),
+                code: contract.code.clone(),
+                function_args: contract.function_args.clone(),
+                analysis: contract.analysis.clone(),
+            })
+            .collect();
+        SessionImage {
+            fingerprint: fingerprint.to_string(),
+            current_epoch: session.current_epoch,
+            tx_sender: session.get_tx_sender(),
+            accounts: session.interpreter.get_accounts().into_iter().collect(),
+            tokens: session.interpreter.get_assets_maps(),
+            address_book: session
+                .address_book
+                .iter()
+                .map(|(name, principal)| (name.clone(), principal.clone()))
+                .collect(),
+            contracts,
+            clarity_datastore: session.interpreter.clarity_datastore.to_image(),
+            datastore: session.interpreter.datastore.to_image(),
+        }
+    }
+
+    /// New session with the state of the image, its storage is opened from `settings`
+    pub fn restore(self, settings: SessionSettings) -> Result<Session, String> {
+        let mut session = Session::new(settings);
+        session.current_epoch = self.current_epoch;
+        session
+            .interpreter
+            .clarity_datastore
+            .import_image(&self.clarity_datastore)?;
+        session.interpreter.datastore = Datastore::from_image(&self.datastore)?;
+        session
+            .interpreter
+            .restore_accounts(self.accounts, self.tokens);
+        let tx_sender = PrincipalData::parse_standard_principal(&self.tx_sender)
+            .map_err(|e| format!("invalid tx-sender {}: {}", self.tx_sender, e))?;
+        session.interpreter.set_tx_sender(tx_sender);
+        for (name, principal) in self.address_book.iter() {
+            session.address_book.insert(name, principal)?;
+        }
+
+        for contract in self.contracts.into_iter() {
+            let contract_id = QualifiedContractIdentifier::parse(&contract.contract_id)
+                .map_err(|e| format!("invalid contract id {}: {}", contract.contract_id, e))?;
+            let mut analysis = contract.analysis;
+            let (ast, _, success) = session.interpreter.build_ast(&ClarityContract {
+                code_source: ClarityCodeSource::ContractInMemory(contract.code.clone()),
+                name: contract_id.name.to_string(),
+                deployer: ContractDeployer::ContractIdentifier(contract_id.clone()),
+                clarity_version: analysis.clarity_version,
+                epoch: analysis.epoch,
+            });
+            if !success {
+                return Err(format!("unable to parse {}", contract_id));
+            }
+            analysis.expressions = ast.expressions.clone();
+            session.contracts.insert(
+                contract_id,
+                ParsedContract {
+                    contract_identifier: contract.contract_id,
+                    code: contract.code,
+                    function_args: contract.function_args,
+                    ast,
+                    analysis,
+                },
+            );
+        }
+        Ok(session)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repl::{DEFAULT_CLARITY_VERSION, DEFAULT_EPOCH};
+
+    #[test]
+    fn test_restore_session_image() {
+        let mut session = Session::new(SessionSettings::default());
+        session.update_epoch(DEFAULT_EPOCH);
+        let deploy = session.deploy_contract(
+            &ClarityContract {
+                code_source: ClarityCodeSource::ContractInMemory(
+                    "(define-data-var count uint u0)
+(define-public (increment) (ok (var-set count (+ (var-get count) u1))))
+(define-read-only (get-count) (var-get count))"
+                        .to_string(),
+                ),
+                name: "counter".to_string(),
+                deployer: ContractDeployer::DefaultDeployer,
+                clarity_version: DEFAULT_CLARITY_VERSION,
+                epoch: DEFAULT_EPOCH,
+            },
+            false,
+            None,
+        );
+        assert!(deploy.is_ok());
+        assert!(session
+            .eval("(contract-call? .counter increment)".to_string(), false)
+            .is_ok());
+        session.advance_chain_tip(3);
+
+        let image = SessionImage::from_session(&session, "fingerprint");
+        let content = serde_json::to_string(&image).unwrap();
+        let image: SessionImage = serde_json::from_str(&content).unwrap();
+        assert_eq!(image.fingerprint, "fingerprint");
+
+        let mut restored = image.restore(SessionSettings::default()).unwrap();
+        assert_eq!(restored.contracts.len(), 1);
+        assert_eq!(
+            restored.interpreter.get_block_height(),
+            session.interpreter.get_block_height()
+        );
+        let count = restored
+            .eval("(contract-call? .counter get-count)".to_string(), false)
+            .unwrap();
+        assert_eq!(
+            format!("{:?}", count.result),
+            format!(
+                "{:?}",
+                session
+                    .eval("(contract-call? .counter get-count)".to_string(), false)
+                    .unwrap()
+                    .result
+            )
+        );
+    }
+}
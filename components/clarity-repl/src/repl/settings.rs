@@ -1,6 +1,7 @@
 use std::convert::TryInto;
 
 use crate::analysis;
+use crate::repl::storage::StorageBackendKind;
 use clarity::types::chainstate::StacksAddress;
 use clarity::types::StacksEpochId;
 use clarity::vm::types::{PrincipalData, QualifiedContractIdentifier, StandardPrincipalData};
@@ -51,11 +52,30 @@ pub struct SessionSettings {
     pub disk_cache_enabled: bool,
     pub repl_settings: Settings,
     pub epoch_id: Option<StacksEpochId>,
+    /// Latest epoch deployment plans can be executed in, `DEFAULT_EPOCH` if not set
+    pub max_epoch: Option<StacksEpochId>,
+    /// Where the data of the contracts is kept, in memory by default
+    pub storage_backend: StorageBackendKind,
+}
+
+/// How the requirements of a plan are published in simnet
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RequirementsExecution {
+    /// Analyzed and executed as they are published
+    #[default]
+    Eager,
+    /// Analyzed as they are published, executed once a call or a contract needs them
+    Lazy,
+    /// Analyzed only, the executions needing them fail
+    Disabled,
 }
 
 #[derive(Debug, Default, Clone, Deserialize, Serialize)]
 pub struct Settings {
     pub analysis: analysis::Settings,
+    #[serde(default)]
+    pub requirements_execution: RequirementsExecution,
     #[serde(skip_serializing, skip_deserializing)]
     pub clarity_wasm_mode: bool,
     #[serde(skip_serializing, skip_deserializing)]
@@ -65,6 +85,7 @@ pub struct Settings {
 #[derive(Debug, Default, Clone, Deserialize, Serialize)]
 pub struct SettingsFile {
     pub analysis: Option<analysis::SettingsFile>,
+    pub requirements_execution: Option<RequirementsExecution>,
 }
 
 impl From<SettingsFile> for Settings {
@@ -76,6 +97,7 @@ impl From<SettingsFile> for Settings {
         };
         Self {
             analysis,
+            requirements_execution: file.requirements_execution.unwrap_or_default(),
             clarity_wasm_mode: false,
             show_timings: false,
         }
@@ -0,0 +1,164 @@
+use std::collections::BTreeMap;
+
+use clarity::vm::database::{ClarityDatabase, StoreType};
+use clarity::vm::types::QualifiedContractIdentifier;
+use sha2::{Digest, Sha512_256};
+
+use super::datastore::ClarityDatastore;
+
+/// Data stored by a contract. Values are hex encoded Clarity values, as found in the
+/// datastore. Map entries and tokens are indexed by their datastore keys.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct ContractStateDump {
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub data_vars: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub maps: BTreeMap<String, BTreeMap<String, String>>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub fungible_tokens: BTreeMap<String, BTreeMap<String, String>>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub fungible_tokens_supply: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub non_fungible_tokens: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+impl ContractStateDump {
+    pub fn is_empty(&self) -> bool {
+        self == &ContractStateDump::default()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct StateDump {
+    pub contracts: BTreeMap<String, ContractStateDump>,
+}
+
+fn store_type_prefix(contract_id: &QualifiedContractIdentifier, store_type: StoreType) -> String {
+    // keys are formatted as `vm::<contract-id>::<store-type>::<name>[::<key>]`
+    ClarityDatabase::make_key_for_trip(contract_id, store_type, "")
+}
+
+fn get_entries(
+    datastore: &ClarityDatastore,
+    contract_id: &QualifiedContractIdentifier,
+    store_type: StoreType,
+) -> Vec<(String, String)> {
+    let prefix = store_type_prefix(contract_id, store_type);
+    datastore
+        .get_latest_entries_with_prefix(&prefix)
+        .into_iter()
+        .map(|(key, value)| (key[prefix.len()..].to_string(), format!("0x{value}")))
+        .collect()
+}
+
+fn get_keyed_entries(
+    datastore: &ClarityDatastore,
+    contract_id: &QualifiedContractIdentifier,
+    store_type: StoreType,
+) -> BTreeMap<String, BTreeMap<String, String>> {
+    let mut result: BTreeMap<String, BTreeMap<String, String>> = BTreeMap::new();
+    for (name, value) in get_entries(datastore, contract_id, store_type) {
+        if let Some((name, key)) = name.split_once("::") {
+            result
+                .entry(name.to_string())
+                .or_default()
+                .insert(key.to_string(), value);
+        }
+    }
+    result
+}
+
+pub fn dump_contract_state(
+    datastore: &ClarityDatastore,
+    contract_id: &QualifiedContractIdentifier,
+) -> ContractStateDump {
+    ContractStateDump {
+        data_vars: get_entries(datastore, contract_id, StoreType::Variable)
+            .into_iter()
+            .collect(),
+        maps: get_keyed_entries(datastore, contract_id, StoreType::DataMap),
+        fungible_tokens: get_keyed_entries(datastore, contract_id, StoreType::FungibleToken),
+        fungible_tokens_supply: get_entries(datastore, contract_id, StoreType::CirculatingSupply)
+            .into_iter()
+            .collect(),
+        non_fungible_tokens: get_keyed_entries(datastore, contract_id, StoreType::NonFungibleToken),
+    }
+}
+
+fn decode_value(value: &str) -> Result<String, String> {
+    let value = value.strip_prefix("0x").unwrap_or(value);
+    if value.len() % 2 != 0 || !value.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!("invalid hex value: {}", value));
+    }
+    Ok(value.to_string())
+}
+
+fn get_keyed_items(
+    contract_id: &QualifiedContractIdentifier,
+    store_type: StoreType,
+    entries: &BTreeMap<String, BTreeMap<String, String>>,
+) -> Result<Vec<(String, String)>, String> {
+    let prefix = store_type_prefix(contract_id, store_type);
+    let mut items = vec![];
+    for (name, values) in entries.iter() {
+        for (entry_key, value) in values.iter() {
+            items.push((format!("{prefix}{name}::{entry_key}"), decode_value(value)?));
+        }
+    }
+    Ok(items)
+}
+
+/// Returns the datastore items matching a contract dump
+pub fn get_contract_state_items(
+    contract_id: &QualifiedContractIdentifier,
+    dump: &ContractStateDump,
+) -> Result<Vec<(String, String)>, String> {
+    let mut items = vec![];
+    for (name, value) in dump.data_vars.iter() {
+        let key = ClarityDatabase::make_key_for_trip(contract_id, StoreType::Variable, name);
+        items.push((key, decode_value(value)?));
+    }
+    for (name, value) in dump.fungible_tokens_supply.iter() {
+        let key =
+            ClarityDatabase::make_key_for_trip(contract_id, StoreType::CirculatingSupply, name);
+        items.push((key, decode_value(value)?));
+    }
+    items.append(&mut get_keyed_items(
+        contract_id,
+        StoreType::DataMap,
+        &dump.maps,
+    )?);
+    items.append(&mut get_keyed_items(
+        contract_id,
+        StoreType::FungibleToken,
+        &dump.fungible_tokens,
+    )?);
+    items.append(&mut get_keyed_items(
+        contract_id,
+        StoreType::NonFungibleToken,
+        &dump.non_fungible_tokens,
+    )?);
+    Ok(items)
+}
+
+/// A named state, saved in `fixtures/<name>.state.json`. The hashes of the contracts
+/// sources are used to invalidate the fixture when the contracts change.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct StateFixture {
+    pub contracts_hashes: BTreeMap<String, String>,
+    pub state: StateDump,
+}
+
+pub fn get_fixture_path(name: &str) -> String {
+    format!("fixtures/{}.state.json", name)
+}
+
+pub fn hash_contract_source(source: &str) -> String {
+    let mut hasher = Sha512_256::new();
+    hasher.update(source.as_bytes());
+    hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
@@ -0,0 +1,338 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use clarity::types::chainstate::StacksBlockId;
+
+/// Storage of the data written by the contracts of a session (the versions of each key,
+/// with the block they were written in) and of the contracts metadata. The chain tips
+/// and the blocks lookups stay in `ClarityDatastore`.
+pub trait StorageBackend: fmt::Debug + Send + Sync {
+    fn push_entry(&mut self, key: &str, block_id: StacksBlockId, value: &str);
+    /// Versions of a key, oldest first
+    fn get_entries(&self, key: &str) -> Vec<(StacksBlockId, String)>;
+    fn get_keys_with_prefix(&self, prefix: &str) -> Vec<String>;
+    fn insert_metadata(&mut self, contract: &str, key: &str, value: &str);
+    fn get_metadata(&self, contract: &str, key: &str) -> Option<String>;
+    /// Every metadata entry, as `(contract, key, value)`
+    fn get_metadata_entries(&self) -> Vec<(String, String, String)>;
+    fn clone_backend(&self) -> Box<dyn StorageBackend>;
+}
+
+impl Clone for Box<dyn StorageBackend> {
+    fn clone(&self) -> Self {
+        self.clone_backend()
+    }
+}
+
+/// Backend of the sessions, selected with `SessionSettings::storage_backend`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum StorageBackendKind {
+    #[default]
+    InMemory,
+    /// SQLite database written at the given path, or in a temporary file removed with the
+    /// session, for the sessions too large to be kept in memory
+    #[cfg(all(feature = "sdk", not(target_arch = "wasm32")))]
+    Sqlite(Option<std::path::PathBuf>),
+}
+
+impl StorageBackendKind {
+    pub fn open(&self) -> Result<Box<dyn StorageBackend>, String> {
+        match self {
+            StorageBackendKind::InMemory => Ok(Box::new(InMemoryStorage::default())),
+            #[cfg(all(feature = "sdk", not(target_arch = "wasm32")))]
+            StorageBackendKind::Sqlite(path) => {
+                Ok(Box::new(sqlite::SqliteStorage::open(path.as_deref())?))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryStorage {
+    store: HashMap<String, Vec<(StacksBlockId, String)>>,
+    metadata: HashMap<(String, String), String>,
+}
+
+impl StorageBackend for InMemoryStorage {
+    fn push_entry(&mut self, key: &str, block_id: StacksBlockId, value: &str) {
+        if let Some(entries) = self.store.get_mut(key) {
+            entries.push((block_id, value.to_string()));
+        } else {
+            self.store
+                .insert(key.to_string(), vec![(block_id, value.to_string())]);
+        }
+    }
+
+    fn get_entries(&self, key: &str) -> Vec<(StacksBlockId, String)> {
+        self.store.get(key).cloned().unwrap_or_default()
+    }
+
+    fn get_keys_with_prefix(&self, prefix: &str) -> Vec<String> {
+        self.store
+            .keys()
+            .filter(|key| key.starts_with(prefix))
+            .cloned()
+            .collect()
+    }
+
+    fn insert_metadata(&mut self, contract: &str, key: &str, value: &str) {
+        self.metadata
+            .insert((contract.to_string(), key.to_string()), value.to_string());
+    }
+
+    fn get_metadata(&self, contract: &str, key: &str) -> Option<String> {
+        self.metadata
+            .get(&(contract.to_string(), key.to_string()))
+            .cloned()
+    }
+
+    fn get_metadata_entries(&self) -> Vec<(String, String, String)> {
+        self.metadata
+            .iter()
+            .map(|((contract, key), value)| (contract.clone(), key.clone(), value.clone()))
+            .collect()
+    }
+
+    fn clone_backend(&self) -> Box<dyn StorageBackend> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(all(feature = "sdk", not(target_arch = "wasm32")))]
+pub mod sqlite {
+    use std::path::{Path, PathBuf};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex;
+
+    use clarity::rusqlite::{params, Connection, OptionalExtension};
+    use clarity::types::chainstate::StacksBlockId;
+
+    use super::StorageBackend;
+
+    static TEMPORARY_DATABASES_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+    const SCHEMA: &str = "
+        PRAGMA journal_mode = WAL;
+        PRAGMA synchronous = OFF;
+        CREATE TABLE IF NOT EXISTS data (key TEXT NOT NULL, block_id BLOB NOT NULL, value TEXT NOT NULL);
+        CREATE INDEX IF NOT EXISTS data_key ON data (key);
+        CREATE TABLE IF NOT EXISTS metadata (
+            contract TEXT NOT NULL,
+            key TEXT NOT NULL,
+            value TEXT NOT NULL,
+            PRIMARY KEY (contract, key)
+        );
+        DELETE FROM data;
+        DELETE FROM metadata;
+    ";
+
+    #[derive(Debug)]
+    pub struct SqliteStorage {
+        path: PathBuf,
+        /// `None` once dropped, for the temporary database to be removed after closing it
+        connection: Option<Mutex<Connection>>,
+        is_temporary: bool,
+    }
+
+    impl SqliteStorage {
+        /// Opens an empty database, the entries of a previous session are removed
+        pub fn open(path: Option<&Path>) -> Result<SqliteStorage, String> {
+            let (path, is_temporary) = match path {
+                Some(path) => (path.to_path_buf(), false),
+                None => {
+                    let index = TEMPORARY_DATABASES_COUNT.fetch_add(1, Ordering::SeqCst);
+                    let name = format!("clarinet-session-{}-{}.sqlite", std::process::id(), index);
+                    (std::env::temp_dir().join(name), true)
+                }
+            };
+            let connection = Connection::open(&path)
+                .map_err(|e| format!("unable to open {}: {}", path.display(), e))?;
+            connection
+                .execute_batch(SCHEMA)
+                .map_err(|e| format!("unable to initialize {}: {}", path.display(), e))?;
+            Ok(SqliteStorage {
+                path,
+                connection: Some(Mutex::new(connection)),
+                is_temporary,
+            })
+        }
+
+        fn with_connection<T>(&self, f: impl FnOnce(&Connection) -> T) -> T {
+            let connection = self
+                .connection
+                .as_ref()
+                .expect("sqlite storage closed")
+                .lock()
+                .expect("sqlite storage poisoned");
+            f(&connection)
+        }
+    }
+
+    impl Drop for SqliteStorage {
+        fn drop(&mut self) {
+            drop(self.connection.take());
+            if self.is_temporary {
+                for suffix in ["", "-wal", "-shm"] {
+                    let mut path = self.path.clone().into_os_string();
+                    path.push(suffix);
+                    let _ = std::fs::remove_file(path);
+                }
+            }
+        }
+    }
+
+    impl StorageBackend for SqliteStorage {
+        fn push_entry(&mut self, key: &str, block_id: StacksBlockId, value: &str) {
+            self.with_connection(|connection| {
+                connection
+                    .prepare_cached("INSERT INTO data (key, block_id, value) VALUES (?1, ?2, ?3)")
+                    .and_then(|mut statement| {
+                        statement.execute(params![key, &block_id.0[..], value])
+                    })
+                    .expect("unable to write in the sqlite storage");
+            })
+        }
+
+        fn get_entries(&self, key: &str) -> Vec<(StacksBlockId, String)> {
+            self.with_connection(|connection| {
+                let mut statement = connection
+                    .prepare_cached(
+                        "SELECT block_id, value FROM data WHERE key = ?1 ORDER BY rowid",
+                    )
+                    .expect("unable to read the sqlite storage");
+                statement
+                    .query_map(params![key], |row| {
+                        let block_id: Vec<u8> = row.get(0)?;
+                        let value: String = row.get(1)?;
+                        Ok((block_id, value))
+                    })
+                    .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+                    .expect("unable to read the sqlite storage")
+                    .into_iter()
+                    .map(|(block_id, value)| {
+                        let block_id = StacksBlockId::from_bytes(&block_id)
+                            .expect("invalid block id in the sqlite storage");
+                        (block_id, value)
+                    })
+                    .collect()
+            })
+        }
+
+        fn get_keys_with_prefix(&self, prefix: &str) -> Vec<String> {
+            self.with_connection(|connection| {
+                let mut statement = connection
+                    .prepare_cached(
+                        "SELECT DISTINCT key FROM data WHERE substr(key, 1, length(?1)) = ?1",
+                    )
+                    .expect("unable to read the sqlite storage");
+                statement
+                    .query_map(params![prefix], |row| row.get(0))
+                    .and_then(|rows| rows.collect::<Result<Vec<String>, _>>())
+                    .expect("unable to read the sqlite storage")
+            })
+        }
+
+        fn insert_metadata(&mut self, contract: &str, key: &str, value: &str) {
+            self.with_connection(|connection| {
+                connection
+                    .prepare_cached(
+                        "INSERT OR REPLACE INTO metadata (contract, key, value) VALUES (?1, ?2, ?3)",
+                    )
+                    .and_then(|mut statement| statement.execute(params![contract, key, value]))
+                    .expect("unable to write in the sqlite storage");
+            })
+        }
+
+        fn get_metadata(&self, contract: &str, key: &str) -> Option<String> {
+            self.with_connection(|connection| {
+                connection
+                    .prepare_cached("SELECT value FROM metadata WHERE contract = ?1 AND key = ?2")
+                    .and_then(|mut statement| {
+                        statement
+                            .query_row(params![contract, key], |row| row.get(0))
+                            .optional()
+                    })
+                    .expect("unable to read the sqlite storage")
+            })
+        }
+
+        fn get_metadata_entries(&self) -> Vec<(String, String, String)> {
+            self.with_connection(|connection| {
+                let mut statement = connection
+                    .prepare_cached("SELECT contract, key, value FROM metadata")
+                    .expect("unable to read the sqlite storage");
+                statement
+                    .query_map(params![], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+                    .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+                    .expect("unable to read the sqlite storage")
+            })
+        }
+
+        /// Copies the entries in a new temporary database
+        fn clone_backend(&self) -> Box<dyn StorageBackend> {
+            let copy = SqliteStorage::open(None).expect("unable to create the sqlite storage");
+            let source = self.path.to_string_lossy().to_string();
+            copy.with_connection(|connection| {
+                connection
+                    .execute("ATTACH DATABASE ?1 AS source", params![source])
+                    .and_then(|_| {
+                        connection.execute_batch(
+                            "INSERT INTO data SELECT key, block_id, value FROM source.data ORDER BY rowid;
+                            INSERT INTO metadata SELECT * FROM source.metadata;
+                            DETACH DATABASE source;",
+                        )
+                    })
+                    .expect("unable to copy the sqlite storage");
+            });
+            Box::new(copy)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check_backend(mut storage: Box<dyn StorageBackend>) {
+        let block = |height: u8| StacksBlockId([height; 32]);
+        storage.push_entry("vm::contract::var", block(1), "u1");
+        storage.push_entry("vm::contract::var", block(2), "u2");
+        storage.push_entry("vm::other::var", block(2), "true");
+        storage.insert_metadata("contract", "analysis", "{}");
+        storage.insert_metadata("contract", "analysis", "{ }");
+
+        let copy = storage.clone();
+        storage.push_entry("vm::contract::var", block(3), "u3");
+
+        assert_eq!(
+            copy.get_entries("vm::contract::var"),
+            vec![(block(1), "u1".to_string()), (block(2), "u2".to_string())]
+        );
+        assert_eq!(storage.get_entries("vm::contract::var").len(), 3);
+        assert!(storage.get_entries("vm::unknown").is_empty());
+        assert_eq!(
+            storage.get_keys_with_prefix("vm::contract"),
+            vec!["vm::contract::var".to_string()]
+        );
+        assert_eq!(
+            copy.get_metadata("contract", "analysis"),
+            Some("{ }".to_string())
+        );
+        assert_eq!(storage.get_metadata("contract", "unknown"), None);
+        assert_eq!(
+            copy.get_metadata_entries(),
+            vec![(
+                "contract".to_string(),
+                "analysis".to_string(),
+                "{ }".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_storage_backends() {
+        check_backend(StorageBackendKind::InMemory.open().unwrap());
+        #[cfg(all(feature = "sdk", not(target_arch = "wasm32")))]
+        check_backend(StorageBackendKind::Sqlite(None).open().unwrap());
+    }
+}
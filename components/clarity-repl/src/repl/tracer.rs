@@ -1,20 +1,45 @@
 use crate::repl::interpreter::Txid;
 use crate::repl::tracer::SymbolicExpressionType::List;
+use clarity::vm::costs::ExecutionCost;
 use clarity::vm::errors::Error;
 use clarity::vm::functions::define::DefineFunctions;
 use clarity::vm::functions::NativeFunctions;
 use clarity::vm::{
     contexts::{Environment, LocalContext},
-    types::Value,
+    types::{StandardPrincipalData, Value},
     EvalHook, SymbolicExpression, SymbolicExpressionType,
 };
 use clarity::vm::{eval, ClarityVersion, EvaluationResult};
+use std::collections::HashMap;
 
 pub struct Tracer {
     stack: Vec<u64>,
     pending_call_string: Vec<String>,
     pending_args: Vec<Vec<u64>>,
     nb_of_emitted_events: usize,
+    /// Total cost of the execution when each pending call began
+    frames_costs: HashMap<u64, ExecutionCost>,
+    /// Deployer of the snippet, the frames of the contracts of other deployers (requirements,
+    /// boot contracts) are located with their full identifier
+    snippet_issuer: Option<StandardPrincipalData>,
+}
+
+/// Cost spent between two totals of an execution
+pub fn get_frame_cost(start: &ExecutionCost, end: &ExecutionCost) -> ExecutionCost {
+    ExecutionCost {
+        write_length: end.write_length.saturating_sub(start.write_length),
+        write_count: end.write_count.saturating_sub(start.write_count),
+        read_length: end.read_length.saturating_sub(start.read_length),
+        read_count: end.read_count.saturating_sub(start.read_count),
+        runtime: end.runtime.saturating_sub(start.runtime),
+    }
+}
+
+pub fn format_frame_cost(cost: &ExecutionCost) -> String {
+    format!(
+        "runtime: {}, read: {} ({} bytes), write: {} ({} bytes)",
+        cost.runtime, cost.read_count, cost.read_length, cost.write_count, cost.write_length
+    )
 }
 
 impl Tracer {
@@ -25,8 +50,26 @@ impl Tracer {
             pending_call_string: Vec::new(),
             pending_args: Vec::new(),
             nb_of_emitted_events: 0,
+            frames_costs: HashMap::new(),
+            snippet_issuer: None,
         }
     }
+
+    fn get_frame_location(&mut self, env: &Environment, expr: &SymbolicExpression) -> String {
+        let contract_id = &env.contract_context.contract_identifier;
+        let issuer = self
+            .snippet_issuer
+            .get_or_insert_with(|| contract_id.issuer.clone());
+        let contract = if contract_id.issuer == *issuer {
+            contract_id.name.to_string()
+        } else {
+            contract_id.to_string()
+        };
+        format!(
+            "{}:{}:{}",
+            contract, expr.span.start_line, expr.span.start_column
+        )
+    }
 }
 
 impl EvalHook for Tracer {
@@ -57,12 +100,7 @@ impl EvalHook for Tracer {
                                         .saturating_sub(1)
                                 ),
                                 expr,
-                                black!(format!(
-                                    "{}:{}:{}",
-                                    env.contract_context.contract_identifier.name,
-                                    expr.span.start_line,
-                                    expr.span.start_column,
-                                )),
+                                black!(self.get_frame_location(env, expr)),
                             );
 
                             let mut lines = Vec::new();
@@ -110,12 +148,7 @@ impl EvalHook for Tracer {
                             (self.stack.len() - self.pending_call_string.len()).saturating_sub(1)
                         ),
                         expr,
-                        black!(format!(
-                            "{}:{}:{}",
-                            env.contract_context.contract_identifier.name,
-                            expr.span.start_line,
-                            expr.span.start_column,
-                        )),
+                        black!(self.get_frame_location(env, expr)),
                     );
                     call.push_str(
                         format!(
@@ -139,6 +172,8 @@ impl EvalHook for Tracer {
                 }
             }
         }
+        self.frames_costs
+            .insert(expr.id, env.global_context.cost_track.get_total());
         self.stack.push(expr.id);
     }
 
@@ -176,13 +211,19 @@ impl EvalHook for Tracer {
 
         if let Some(last) = self.stack.last() {
             if *last == expr.id {
+                let cost = self.frames_costs.remove(&expr.id).map(|start| {
+                    get_frame_cost(&start, &env.global_context.cost_track.get_total())
+                });
                 if let Ok(value) = res {
                     println!(
-                        "{}└── {}",
+                        "{}└── {}  {}",
                         "│   ".repeat(
                             (self.stack.len() - self.pending_call_string.len()).saturating_sub(1)
                         ),
-                        blue!(value.to_string())
+                        blue!(value.to_string()),
+                        black!(cost
+                            .map(|cost| format_frame_cost(&cost))
+                            .unwrap_or_default())
                     );
                 }
                 self.stack.pop();
@@ -233,3 +274,32 @@ impl EvalHook for Tracer {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_computes_the_cost_of_a_frame() {
+        let start = ExecutionCost {
+            write_length: 10,
+            write_count: 1,
+            read_length: 100,
+            read_count: 2,
+            runtime: 1000,
+        };
+        let end = ExecutionCost {
+            write_length: 50,
+            write_count: 2,
+            read_length: 300,
+            read_count: 5,
+            runtime: 4500,
+        };
+        let cost = get_frame_cost(&start, &end);
+        assert_eq!(
+            format_frame_cost(&cost),
+            "runtime: 3500, read: 3 (200 bytes), write: 1 (40 bytes)"
+        );
+        assert_eq!(get_frame_cost(&end, &start).runtime, 0);
+    }
+}
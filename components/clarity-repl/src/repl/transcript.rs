@@ -0,0 +1,83 @@
+/// A command executed in the console, with its output
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TranscriptEntry {
+    pub command: String,
+    pub output: Vec<String>,
+}
+
+/// Removes the colors of a console output
+pub fn strip_colors(line: &str) -> String {
+    let mut stripped = String::with_capacity(line.len());
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // skip the escape sequence, up to its final letter
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            stripped.push(c);
+        }
+    }
+    stripped
+}
+
+/// Writes the commands of a session as a script that `::replay` can execute, one command
+/// per line, followed by its output as comments
+pub fn to_script(entries: &[TranscriptEntry]) -> String {
+    let mut script =
+        String::from(";; clarinet console session, replay it with `::replay <file>`\n");
+    for entry in entries.iter() {
+        script.push('\n');
+        script.push_str(&entry.command);
+        script.push('\n');
+        for output in entry.output.iter() {
+            for line in strip_colors(output).lines() {
+                script.push_str(";; ");
+                script.push_str(line);
+                script.push('\n');
+            }
+        }
+    }
+    script
+}
+
+/// Returns the commands of a script, comments and blank lines are ignored
+pub fn parse_script(script: &str) -> Vec<String> {
+    script
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && !line.starts_with(";;"))
+        .map(|line| line.to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_replays_the_commands_of_a_script() {
+        let entries = vec![
+            TranscriptEntry {
+                command: "::set_tx_sender @wallet_1".to_string(),
+                output: vec!["\x1b[32mtx-sender switched to ST1SJ3DTE5DN7X54YDH5D64R3BCB6A2AG2ZQ8YPD5\x1b[0m".to_string()],
+            },
+            TranscriptEntry {
+                command: "(+ 1 2)".to_string(),
+                output: vec!["3\n(ok true)".to_string()],
+            },
+        ];
+        let script = to_script(&entries);
+        assert!(
+            script.contains(";; tx-sender switched to ST1SJ3DTE5DN7X54YDH5D64R3BCB6A2AG2ZQ8YPD5\n")
+        );
+        assert!(script.contains(";; 3\n;; (ok true)\n"));
+        assert_eq!(
+            parse_script(&script),
+            vec!["::set_tx_sender @wallet_1", "(+ 1 2)"]
+        );
+    }
+}
@@ -1,4 +1,4 @@
-import { ExtensionContext } from "vscode";
+import { ExtensionContext, debug } from "vscode";
 
 import {
   LanguageClient,
@@ -7,6 +7,7 @@ import {
 } from "vscode-languageclient/node";
 
 import { initClient, clientOpts } from "./common";
+import { ClarinetDebugConfigurationProvider } from "./debugConfiguration";
 
 let client: LanguageClient;
 export async function activate(context: ExtensionContext) {
@@ -28,6 +29,13 @@ export async function activate(context: ExtensionContext) {
     clientOpts,
   );
   initClient(context, client);
+
+  context.subscriptions.push(
+    debug.registerDebugConfigurationProvider(
+      "clarinet",
+      new ClarinetDebugConfigurationProvider(),
+    ),
+  );
 }
 
 export function deactivate(): Thenable<void> | undefined {
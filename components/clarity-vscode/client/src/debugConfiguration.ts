@@ -0,0 +1,63 @@
+import * as vscode from "vscode";
+
+type ClarinetDebugConfiguration = vscode.DebugConfiguration & {
+  manifest?: string;
+  expression?: string;
+  contract?: string;
+  function?: string;
+  arguments?: string[];
+};
+
+// `.counter` refers to a project contract, `ST1...counter` to a fully qualified one
+function getContractReference(contract: string) {
+  if (contract.startsWith(".") || contract.startsWith("'")) return contract;
+  return contract.includes(".") ? `'${contract}` : `.${contract}`;
+}
+
+export function getContractCallExpression(
+  contract: string,
+  fnName: string,
+  args: string[] = [],
+) {
+  return `(${["contract-call?", getContractReference(contract), fnName, ...args].join(" ")})`;
+}
+
+/*
+ * The debug adapter only evaluates an expression, launch configurations
+ * referencing a contract function are turned into a contract-call
+ */
+export class ClarinetDebugConfigurationProvider
+  implements vscode.DebugConfigurationProvider
+{
+  resolveDebugConfiguration(
+    folder: vscode.WorkspaceFolder | undefined,
+    config: ClarinetDebugConfiguration,
+  ): vscode.ProviderResult<vscode.DebugConfiguration> {
+    if (!config.type && !config.request) {
+      vscode.window.showErrorMessage(
+        "Add a Clarinet launch configuration to debug a contract function",
+      );
+      return undefined;
+    }
+
+    if (!config.manifest && folder) {
+      config.manifest = vscode.Uri.joinPath(folder.uri, "Clarinet.toml").fsPath;
+    }
+
+    if (!config.expression) {
+      if (!config.contract || !config.function) {
+        vscode.window.showErrorMessage(
+          "Clarinet launch configurations require an expression, or a contract and a function",
+        );
+        return undefined;
+      }
+      config.expression = getContractCallExpression(
+        config.contract,
+        config.function,
+        config.arguments,
+      );
+    }
+
+    return config;
+  }
+}
@@ -27,8 +27,34 @@ pub extern crate slog_term;
 #[cfg(feature = "log")]
 pub extern crate slog_async;
 
+use std::sync::atomic::{AtomicU8, Ordering};
 use std::thread::Builder;
 
 pub fn thread_named(name: &str) -> Builder {
     Builder::new().name(name.to_string())
 }
+
+const COLORS_AUTO: u8 = 0;
+const COLORS_NEVER: u8 = 1;
+const COLORS_ALWAYS: u8 = 2;
+
+static COLORS: AtomicU8 = AtomicU8::new(COLORS_AUTO);
+
+/// Forces the colors of the output on or off, `None` restores the default (colors when
+/// stdout is a terminal)
+pub fn set_colors(enabled: Option<bool>) {
+    let colors = match enabled {
+        None => COLORS_AUTO,
+        Some(false) => COLORS_NEVER,
+        Some(true) => COLORS_ALWAYS,
+    };
+    COLORS.store(colors, Ordering::Relaxed);
+}
+
+pub fn colors_enabled() -> bool {
+    match COLORS.load(Ordering::Relaxed) {
+        COLORS_NEVER => false,
+        COLORS_ALWAYS => true,
+        _ => atty::is(atty::Stream::Stdout),
+    }
+}
@@ -0,0 +1,413 @@
+//! Fluent construction and signing of Stacks transactions.
+//!
+//! ```ignore
+//! let tx = TxBuilder::contract_call(&contract_id, function_name, args)
+//!     .network(TransactionVersion::Mainnet)
+//!     .nonce(nonce)
+//!     .fee(fee)
+//!     .sign(&[secret_key])?;
+//! ```
+
+use std::str::FromStr;
+
+use clarity::types::chainstate::StacksAddress;
+use clarity::util::secp256k1::{Secp256k1PrivateKey, Secp256k1PublicKey};
+use clarity::vm::types::{PrincipalData, QualifiedContractIdentifier, Value};
+use clarity::vm::{ClarityName, ClarityVersion, ContractName};
+
+use crate::codec::{
+    StacksString, StacksTransaction, StacksTransactionSigner, TokenTransferMemo,
+    TransactionAnchorMode, TransactionAuth, TransactionContractCall, TransactionPayload,
+    TransactionPostCondition, TransactionPostConditionMode, TransactionSmartContract,
+    TransactionSpendingCondition, TransactionVersion,
+};
+
+pub const CHAIN_ID_MAINNET: u32 = 0x00000001;
+pub const CHAIN_ID_TESTNET: u32 = 0x80000000;
+
+#[derive(Debug, Clone)]
+enum OriginKeys {
+    /// Derived from the signing key
+    Unspecified,
+    Singlesig(Secp256k1PublicKey),
+    Multisig(u16, Vec<Secp256k1PublicKey>),
+}
+
+#[derive(Debug, Clone)]
+pub struct TxBuilder {
+    payload: TransactionPayload,
+    version: TransactionVersion,
+    chain_id: u32,
+    nonce: u64,
+    fee: u64,
+    anchor_mode: TransactionAnchorMode,
+    post_condition_mode: TransactionPostConditionMode,
+    post_conditions: Vec<TransactionPostCondition>,
+    origin: OriginKeys,
+    sponsored: bool,
+}
+
+pub fn get_compressed_public_key(secret_key: &Secp256k1PrivateKey) -> Secp256k1PublicKey {
+    let mut public_key = Secp256k1PublicKey::from_private(secret_key);
+    public_key.set_compressed(true);
+    public_key
+}
+
+pub fn get_secret_key(secret_key: &[u8]) -> Result<Secp256k1PrivateKey, String> {
+    Secp256k1PrivateKey::from_slice(secret_key).map_err(|e| format!("invalid secret key: {}", e))
+}
+
+impl TxBuilder {
+    /// Testnet transaction, with no post-conditions, that can be included in any block
+    pub fn new(payload: TransactionPayload) -> Self {
+        Self {
+            payload,
+            version: TransactionVersion::Testnet,
+            chain_id: CHAIN_ID_TESTNET,
+            nonce: 0,
+            fee: 0,
+            anchor_mode: TransactionAnchorMode::Any,
+            post_condition_mode: TransactionPostConditionMode::Allow,
+            post_conditions: vec![],
+            origin: OriginKeys::Unspecified,
+            sponsored: false,
+        }
+    }
+
+    pub fn contract_call(
+        contract_id: &QualifiedContractIdentifier,
+        function_name: ClarityName,
+        function_args: Vec<Value>,
+    ) -> Self {
+        Self::new(TransactionPayload::ContractCall(TransactionContractCall {
+            address: StacksAddress::from(contract_id.issuer.clone()),
+            contract_name: contract_id.name.clone(),
+            function_name,
+            function_args,
+        }))
+    }
+
+    /// Without `clarity_version`, the contract is deployed with the default version of
+    /// the current epoch
+    pub fn contract_deploy(
+        contract_name: ContractName,
+        source: &str,
+        clarity_version: Option<ClarityVersion>,
+    ) -> Result<Self, String> {
+        let code_body = StacksString::from_str(source)
+            .map_err(|e| format!("invalid source for contract {}: {}", contract_name, e))?;
+        Ok(Self::new(TransactionPayload::SmartContract(
+            TransactionSmartContract {
+                name: contract_name,
+                code_body,
+            },
+            clarity_version,
+        )))
+    }
+
+    /// The memo is right-padded with zeros, up to 34 bytes
+    pub fn stx_transfer(
+        recipient: PrincipalData,
+        amount: u64,
+        memo: &[u8],
+    ) -> Result<Self, String> {
+        if memo.len() > 34 {
+            return Err(format!("memo is {} bytes long, maximum is 34", memo.len()));
+        }
+        let mut padded_memo = [0u8; 34];
+        padded_memo[..memo.len()].copy_from_slice(memo);
+        Ok(Self::new(TransactionPayload::TokenTransfer(
+            recipient,
+            amount,
+            TokenTransferMemo(padded_memo),
+        )))
+    }
+
+    /// Also sets the matching chain id
+    pub fn network(mut self, version: TransactionVersion) -> Self {
+        self.chain_id = match version {
+            TransactionVersion::Mainnet => CHAIN_ID_MAINNET,
+            TransactionVersion::Testnet => CHAIN_ID_TESTNET,
+        };
+        self.version = version;
+        self
+    }
+
+    pub fn chain_id(mut self, chain_id: u32) -> Self {
+        self.chain_id = chain_id;
+        self
+    }
+
+    pub fn nonce(mut self, nonce: u64) -> Self {
+        self.nonce = nonce;
+        self
+    }
+
+    /// Paid by the sponsor for sponsored transactions
+    pub fn fee(mut self, fee: u64) -> Self {
+        self.fee = fee;
+        self
+    }
+
+    pub fn anchor_mode(mut self, anchor_mode: TransactionAnchorMode) -> Self {
+        self.anchor_mode = anchor_mode;
+        self
+    }
+
+    pub fn post_condition_mode(
+        mut self,
+        post_condition_mode: TransactionPostConditionMode,
+    ) -> Self {
+        self.post_condition_mode = post_condition_mode;
+        self
+    }
+
+    pub fn post_condition(mut self, post_condition: TransactionPostCondition) -> Self {
+        self.post_conditions.push(post_condition);
+        self
+    }
+
+    pub fn post_conditions(mut self, post_conditions: Vec<TransactionPostCondition>) -> Self {
+        self.post_conditions.extend(post_conditions);
+        self
+    }
+
+    /// Origin with a single key, needed to build unsigned transactions
+    pub fn singlesig(mut self, public_key: Secp256k1PublicKey) -> Self {
+        self.origin = OriginKeys::Singlesig(public_key);
+        self
+    }
+
+    /// P2SH multisig origin. The order of the public keys is the one of the address.
+    pub fn multisig(
+        mut self,
+        signatures_required: u16,
+        public_keys: Vec<Secp256k1PublicKey>,
+    ) -> Self {
+        self.origin = OriginKeys::Multisig(signatures_required, public_keys);
+        self
+    }
+
+    /// The fee is paid by a sponsor, signing after the origin with [`sign_sponsor`]
+    pub fn sponsored(mut self) -> Self {
+        self.sponsored = true;
+        self
+    }
+
+    fn get_origin_condition(
+        &self,
+        origin: &OriginKeys,
+    ) -> Result<TransactionSpendingCondition, String> {
+        let mut condition = match origin {
+            OriginKeys::Unspecified => {
+                return Err("the origin public keys are not specified".to_string())
+            }
+            OriginKeys::Singlesig(public_key) => {
+                TransactionSpendingCondition::new_singlesig_p2pkh(*public_key)
+            }
+            OriginKeys::Multisig(signatures_required, public_keys) => {
+                if usize::from(*signatures_required) > public_keys.len() {
+                    return Err(format!(
+                        "{} signatures required with {} public keys",
+                        signatures_required,
+                        public_keys.len()
+                    ));
+                }
+                TransactionSpendingCondition::new_multisig_p2sh(
+                    *signatures_required,
+                    public_keys.clone(),
+                )
+            }
+        }
+        .ok_or("unable to build the origin spending condition")?;
+        condition.set_nonce(self.nonce);
+        if !self.sponsored {
+            condition.set_tx_fee(self.fee);
+        }
+        Ok(condition)
+    }
+
+    fn build_with_origin(&self, origin: &OriginKeys) -> Result<StacksTransaction, String> {
+        let origin_condition = self.get_origin_condition(origin)?;
+        let auth = if self.sponsored {
+            let mut sponsor_condition = TransactionSpendingCondition::new_initial_sighash();
+            sponsor_condition.set_tx_fee(self.fee);
+            TransactionAuth::Sponsored(origin_condition, sponsor_condition)
+        } else {
+            TransactionAuth::Standard(origin_condition)
+        };
+        Ok(StacksTransaction {
+            version: self.version,
+            chain_id: self.chain_id,
+            auth,
+            anchor_mode: self.anchor_mode,
+            post_condition_mode: self.post_condition_mode,
+            post_conditions: self.post_conditions.clone(),
+            payload: self.payload.clone(),
+        })
+    }
+
+    /// Unsigned transaction, for external signers. Requires [`TxBuilder::singlesig`]
+    /// or [`TxBuilder::multisig`].
+    pub fn build_unsigned(&self) -> Result<StacksTransaction, String> {
+        self.build_with_origin(&self.origin)
+    }
+
+    /// Signs the transaction with the origin keys. Multisig public keys without a
+    /// secret key are appended as is. A sponsored transaction still has to be signed
+    /// by its sponsor.
+    pub fn sign(&self, secret_keys: &[Secp256k1PrivateKey]) -> Result<StacksTransaction, String> {
+        let secret_keys: Vec<Secp256k1PrivateKey> = secret_keys
+            .iter()
+            .map(|key| {
+                let mut key = *key;
+                key.set_compress_public(true);
+                key
+            })
+            .collect();
+        let origin = match (&self.origin, secret_keys.as_slice()) {
+            (OriginKeys::Unspecified, [secret_key]) => {
+                OriginKeys::Singlesig(get_compressed_public_key(secret_key))
+            }
+            (OriginKeys::Unspecified, _) => {
+                return Err("multisig transactions require the origin public keys".to_string())
+            }
+            (origin, _) => origin.clone(),
+        };
+        let unsigned_tx = self.build_with_origin(&origin)?;
+        let mut signer = StacksTransactionSigner::new(&unsigned_tx);
+
+        match origin {
+            OriginKeys::Singlesig(public_key) => {
+                let secret_key = secret_keys
+                    .iter()
+                    .find(|key| get_compressed_public_key(key) == public_key)
+                    .ok_or("no secret key matching the origin public key")?;
+                signer
+                    .sign_origin(secret_key)
+                    .map_err(|e| format!("unable to sign transaction: {}", e))?;
+            }
+            OriginKeys::Multisig(signatures_required, public_keys) => {
+                let mut signatures = 0;
+                for public_key in public_keys.iter() {
+                    let secret_key = secret_keys
+                        .iter()
+                        .find(|key| get_compressed_public_key(key) == *public_key);
+                    match secret_key {
+                        Some(secret_key) if signatures < signatures_required => {
+                            signer
+                                .sign_origin(secret_key)
+                                .map_err(|e| format!("unable to sign transaction: {}", e))?;
+                            signatures += 1;
+                        }
+                        _ => signer
+                            .append_origin(public_key)
+                            .map_err(|e| format!("unable to sign transaction: {}", e))?,
+                    }
+                }
+                if signatures < signatures_required {
+                    return Err(format!(
+                        "{} signatures required, {} provided",
+                        signatures_required, signatures
+                    ));
+                }
+            }
+            OriginKeys::Unspecified => unreachable!(),
+        }
+
+        if self.sponsored {
+            return Ok(signer.get_tx_incomplete());
+        }
+        signer
+            .get_tx()
+            .ok_or("transaction is missing signatures".to_string())
+    }
+}
+
+/// Signs a sponsored transaction, already signed by its origin
+pub fn sign_sponsor(
+    tx: &StacksTransaction,
+    sponsor_secret_key: &Secp256k1PrivateKey,
+    sponsor_nonce: u64,
+) -> Result<StacksTransaction, String> {
+    let fee = tx
+        .auth
+        .sponsor()
+        .map(|sponsor| sponsor.tx_fee())
+        .ok_or("transaction is not sponsored")?;
+    let mut sponsor_condition = TransactionSpendingCondition::new_singlesig_p2pkh(
+        get_compressed_public_key(sponsor_secret_key),
+    )
+    .ok_or("unable to build the sponsor spending condition")?;
+    sponsor_condition.set_nonce(sponsor_nonce);
+    sponsor_condition.set_tx_fee(fee);
+
+    let mut signer = StacksTransactionSigner::new_sponsor(tx, sponsor_condition)
+        .map_err(|e| format!("unable to sign transaction: {}", e))?;
+    signer
+        .sign_sponsor(sponsor_secret_key)
+        .map_err(|e| format!("unable to sign transaction: {}", e))?;
+    signer
+        .get_tx()
+        .ok_or("transaction is missing signatures".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secret_key(byte: u8) -> Secp256k1PrivateKey {
+        Secp256k1PrivateKey::from_slice(&[byte; 32]).unwrap()
+    }
+
+    fn contract_call() -> TxBuilder {
+        let contract_id =
+            QualifiedContractIdentifier::parse("ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM.counter")
+                .unwrap();
+        TxBuilder::contract_call(&contract_id, "increment".into(), vec![Value::UInt(1)])
+            .nonce(3)
+            .fee(1000)
+    }
+
+    #[test]
+    fn test_singlesig_transaction() {
+        let tx = contract_call()
+            .network(TransactionVersion::Mainnet)
+            .sign(&[secret_key(1)])
+            .unwrap();
+        assert_eq!(tx.chain_id, CHAIN_ID_MAINNET);
+        assert_eq!(tx.get_origin_nonce(), 3);
+        assert_eq!(tx.get_tx_fee(), 1000);
+        assert!(tx.verify().is_ok());
+    }
+
+    #[test]
+    fn test_multisig_transaction() {
+        let keys = [secret_key(1), secret_key(2), secret_key(3)];
+        let public_keys = keys.iter().map(get_compressed_public_key).collect();
+        let builder = contract_call().multisig(2, public_keys);
+
+        let tx = builder.sign(&keys[1..]).unwrap();
+        assert_eq!(tx.auth.origin().num_signatures(), 2);
+        assert!(tx.verify().is_ok());
+
+        assert!(builder.sign(&keys[..1]).is_err());
+    }
+
+    #[test]
+    fn test_sponsored_transaction() {
+        let recipient = PrincipalData::parse("ST2CY5V39NHDPWSXMW9QDT3HC3GD6Q6XX4CFRK9AG").unwrap();
+        let tx = TxBuilder::stx_transfer(recipient, 10, b"memo")
+            .unwrap()
+            .fee(1000)
+            .sponsored()
+            .sign(&[secret_key(1)])
+            .unwrap();
+        assert_eq!(tx.auth.origin().tx_fee(), 0);
+
+        let tx = sign_sponsor(&tx, &secret_key(2), 7).unwrap();
+        assert_eq!(tx.get_sponsor_nonce(), Some(7));
+        assert_eq!(tx.get_tx_fee(), 1000);
+        assert!(tx.verify().is_ok());
+    }
+}
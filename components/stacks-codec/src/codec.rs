@@ -1,3 +1,4 @@
+use crate::builder::{get_secret_key, sign_sponsor, TxBuilder};
 use crate::impl_byte_array_newtype;
 
 pub use clarity::codec::StacksMessageCodec;
@@ -3004,52 +3005,126 @@ pub fn build_contract_call_transaction(
 ) -> StacksTransaction {
     let contract_id =
         QualifiedContractIdentifier::parse(&contract_id).expect("Contract identifier invalid");
+    let secret_key = get_secret_key(sender_secret_key).unwrap();
 
-    let payload = TransactionContractCall {
-        address: contract_id.issuer.into(),
-        contract_name: contract_id.name,
-        function_name: function_name.try_into().unwrap(),
-        function_args: args,
-    };
+    TxBuilder::contract_call(&contract_id, function_name.try_into().unwrap(), args)
+        .nonce(nonce)
+        .fee(fee)
+        .sign(&[secret_key])
+        .unwrap()
+}
 
-    let secret_key = Secp256k1PrivateKey::from_slice(sender_secret_key).unwrap();
-    let mut public_key = Secp256k1PublicKey::from_private(&secret_key);
-    public_key.set_compressed(true);
-
-    let anchor_mode = TransactionAnchorMode::Any;
-    let signer_addr =
-        StacksAddress::from_public_keys(0, &AddressHashMode::SerializeP2PKH, 1, &vec![public_key])
-            .unwrap();
-
-    let spending_condition = TransactionSpendingCondition::Singlesig(SinglesigSpendingCondition {
-        signer: signer_addr.bytes,
-        nonce,
-        tx_fee: fee,
-        hash_mode: SinglesigHashMode::P2PKH,
-        key_encoding: TransactionPublicKeyEncoding::Compressed,
-        signature: MessageSignature::empty(),
-    });
-
-    let auth = TransactionAuth::Standard(spending_condition);
-    let unsigned_tx = StacksTransaction {
-        version: TransactionVersion::Testnet,
-        chain_id: 0x80000000, // MAINNET=0x00000001
-        auth,
-        anchor_mode,
-        post_condition_mode: TransactionPostConditionMode::Allow,
-        post_conditions: vec![],
-        payload: TransactionPayload::ContractCall(payload),
-    };
+pub fn build_contract_deploy_transaction(
+    contract_name: String,
+    source: &str,
+    clarity_version: Option<ClarityVersion>,
+    nonce: u64,
+    fee: u64,
+    sender_secret_key: &[u8],
+) -> StacksTransaction {
+    let contract_name = ContractName::try_from(contract_name).expect("Contract name invalid");
+    let secret_key = get_secret_key(sender_secret_key).unwrap();
+
+    TxBuilder::contract_deploy(contract_name, source, clarity_version)
+        .unwrap()
+        .nonce(nonce)
+        .fee(fee)
+        .sign(&[secret_key])
+        .unwrap()
+}
 
-    let mut unsigned_tx_bytes = vec![];
-    unsigned_tx
-        .consensus_serialize(&mut unsigned_tx_bytes)
-        .expect("FATAL: invalid transaction");
+pub fn build_stx_transfer_transaction(
+    recipient: String,
+    amount: u64,
+    memo: &[u8],
+    nonce: u64,
+    fee: u64,
+    sender_secret_key: &[u8],
+) -> StacksTransaction {
+    let recipient = PrincipalData::parse(&recipient).expect("Recipient invalid");
+    let secret_key = get_secret_key(sender_secret_key).unwrap();
 
-    let mut tx_signer = StacksTransactionSigner::new(&unsigned_tx);
-    tx_signer.sign_origin(&secret_key).unwrap();
+    TxBuilder::stx_transfer(recipient, amount, memo)
+        .unwrap()
+        .nonce(nonce)
+        .fee(fee)
+        .sign(&[secret_key])
+        .unwrap()
+}
 
-    tx_signer.get_tx().unwrap()
+/// The fee is paid by the sponsor
+pub fn build_sponsored_contract_call_transaction(
+    contract_id: String,
+    function_name: String,
+    args: Vec<Value>,
+    nonce: u64,
+    sender_secret_key: &[u8],
+    sponsor_nonce: u64,
+    fee: u64,
+    sponsor_secret_key: &[u8],
+) -> StacksTransaction {
+    let contract_id =
+        QualifiedContractIdentifier::parse(&contract_id).expect("Contract identifier invalid");
+    let secret_key = get_secret_key(sender_secret_key).unwrap();
+    let sponsor_secret_key = get_secret_key(sponsor_secret_key).unwrap();
+
+    let tx = TxBuilder::contract_call(&contract_id, function_name.try_into().unwrap(), args)
+        .nonce(nonce)
+        .fee(fee)
+        .sponsored()
+        .sign(&[secret_key])
+        .unwrap();
+    sign_sponsor(&tx, &sponsor_secret_key, sponsor_nonce).unwrap()
+}
+
+/// The fee is paid by the sponsor
+pub fn build_sponsored_contract_deploy_transaction(
+    contract_name: String,
+    source: &str,
+    clarity_version: Option<ClarityVersion>,
+    nonce: u64,
+    sender_secret_key: &[u8],
+    sponsor_nonce: u64,
+    fee: u64,
+    sponsor_secret_key: &[u8],
+) -> StacksTransaction {
+    let contract_name = ContractName::try_from(contract_name).expect("Contract name invalid");
+    let secret_key = get_secret_key(sender_secret_key).unwrap();
+    let sponsor_secret_key = get_secret_key(sponsor_secret_key).unwrap();
+
+    let tx = TxBuilder::contract_deploy(contract_name, source, clarity_version)
+        .unwrap()
+        .nonce(nonce)
+        .fee(fee)
+        .sponsored()
+        .sign(&[secret_key])
+        .unwrap();
+    sign_sponsor(&tx, &sponsor_secret_key, sponsor_nonce).unwrap()
+}
+
+/// The fee is paid by the sponsor
+pub fn build_sponsored_stx_transfer_transaction(
+    recipient: String,
+    amount: u64,
+    memo: &[u8],
+    nonce: u64,
+    sender_secret_key: &[u8],
+    sponsor_nonce: u64,
+    fee: u64,
+    sponsor_secret_key: &[u8],
+) -> StacksTransaction {
+    let recipient = PrincipalData::parse(&recipient).expect("Recipient invalid");
+    let secret_key = get_secret_key(sender_secret_key).unwrap();
+    let sponsor_secret_key = get_secret_key(sponsor_secret_key).unwrap();
+
+    let tx = TxBuilder::stx_transfer(recipient, amount, memo)
+        .unwrap()
+        .nonce(nonce)
+        .fee(fee)
+        .sponsored()
+        .sign(&[secret_key])
+        .unwrap();
+    sign_sponsor(&tx, &sponsor_secret_key, sponsor_nonce).unwrap()
 }
 
 impl StacksMessageCodec for TransactionContractCall {
@@ -11,6 +11,15 @@ const {
   stacksDevnetGetStacksApiUrl,
   stacksDevnetGetStacksExplorerUrl,
   stacksDevnetGetBitcoinExplorerUrl,
+  stacksDevnetGetPorts,
+  stacksDevnetGetState,
+  stacksDevnetGetDeploymentProgress,
+  stacksDevnetGetWebhookDeliveries,
+  stacksDevnetWaitForWebhookDelivery,
+  clusterDevnetConnect,
+  clusterDevnetGetUrl,
+  clusterDevnetGetState,
+  clusterDevnetClose,
 } = require("../native/index.node");
 import {
   BitcoinChainUpdate,
@@ -330,6 +339,18 @@ export interface DevnetConfig {
    * @memberof DevnetConfig
    */
   disable_stacks_api?: boolean;
+  /**
+   * Capture the webhooks delivered on `webhook_receiver_port` (false by default)
+   * @type {boolean}
+   * @memberof DevnetConfig
+   */
+  enable_webhook_receiver?: boolean;
+  /**
+   * The port that should be used by the webhook receiver
+   * @type {number}
+   * @memberof DevnetConfig
+   */
+  webhook_receiver_port?: number;
   /**
    * Bitcoin block height starting the epoch 2.0
    * @type {number}
@@ -378,6 +399,12 @@ export interface DevnetConfig {
    * @memberof DevnetConfig
    */
   epoch_3_0?: number;
+  /**
+   * Replace the ports already in use on the host by free ones
+   * @type {boolean}
+   * @memberof DevnetConfig
+   */
+  auto_ports?: boolean;
 }
 
 /**
@@ -411,6 +438,18 @@ export interface NetworkConfig {
    * @memberof NetworkConfig
    */
   devnet?: DevnetConfig;
+  /**
+   * Deployment plan applied instead of the default one, relative to the project root.
+   * @type {string}
+   * @memberof NetworkConfig
+   */
+  deploymentPlanPath?: string;
+  /**
+   * Boot the network without deploying the project.
+   * @type {boolean}
+   * @memberof NetworkConfig
+   */
+  noDeployment?: boolean;
 }
 
 export class DevnetNetworkFactory {
@@ -462,6 +501,7 @@ export function getIsolatedNetworkConfigUsingNetworkId(
     subnet_node_rpc_port: interval + networkId * 20 + 13,
     subnet_api_port: interval + networkId * 20 + 14,
     subnet_api_events_port: interval + networkId * 20 + 15,
+    webhook_receiver_port: interval + networkId * 20 + 16,
   };
   var devnet = Object.assign(devnetDefaults, networkConfig.devnet);
   return {
@@ -469,9 +509,130 @@ export function getIsolatedNetworkConfigUsingNetworkId(
     logs,
     accounts,
     devnet: devnet,
+    deploymentPlanPath: networkConfig.deploymentPlanPath,
+    noDeployment: networkConfig.noDeployment,
   };
 }
 
+/**
+ * Update of a transaction of the project deployment, applied when the devnet boots
+ * @export
+ * @interface DeploymentProgress
+ */
+export interface DeploymentProgress {
+  /**
+   * Position of the transaction in the deployment plan
+   * @type {number}
+   * @memberof DeploymentProgress
+   */
+  index: number;
+  /**
+   * @type {string}
+   * @memberof DeploymentProgress
+   */
+  name: string;
+  /**
+   * @type {string}
+   * @memberof DeploymentProgress
+   */
+  status: "encoded" | "broadcasted" | "confirmed" | "failed";
+  /**
+   * @type {string}
+   * @memberof DeploymentProgress
+   */
+  txid?: string;
+  /**
+   * Reason of the failure, also thrown by `start`
+   * @type {string}
+   * @memberof DeploymentProgress
+   */
+  error?: string;
+  /**
+   * @type {number}
+   * @memberof DeploymentProgress
+   */
+  confirmed: number;
+  /**
+   * @type {number}
+   * @memberof DeploymentProgress
+   */
+  total: number;
+}
+
+/**
+ * Request received by the webhook receiver of the devnet
+ * @export
+ * @interface WebhookDelivery
+ */
+export interface WebhookDelivery {
+  /**
+   * @type {string}
+   * @memberof WebhookDelivery
+   */
+  received_at: string;
+  /**
+   * @type {string}
+   * @memberof WebhookDelivery
+   */
+  method: string;
+  /**
+   * @type {string}
+   * @memberof WebhookDelivery
+   */
+  path: string;
+  /**
+   * Header names are lowercased
+   * @type {Record<string, string>}
+   * @memberof WebhookDelivery
+   */
+  headers: Record<string, string>;
+  /**
+   * Parsed JSON body, or the raw body when it is not JSON
+   * @type {any}
+   * @memberof WebhookDelivery
+   */
+  body: any;
+}
+
+/**
+ * @export
+ * @interface DevnetState
+ */
+export interface DevnetState {
+  /**
+   * @type {number}
+   * @memberof DevnetState
+   */
+  bitcoin_block_height: number;
+  /**
+   * @type {number}
+   * @memberof DevnetState
+   */
+  stacks_block_height: number;
+  /**
+   * Epoch of the bitcoin tip (`"2.5"`, `"3.0"`, ...)
+   * @type {string | null}
+   * @memberof DevnetState
+   */
+  epoch: string | null;
+  /**
+   * @type {number | null}
+   * @memberof DevnetState
+   */
+  pox_cycle: number | null;
+  /**
+   * @type {boolean}
+   * @memberof DevnetState
+   */
+  boot_completed: boolean;
+  /**
+   * Hosts of the services, as reached by the chains coordinator
+   * @type {Record<string, string> | null}
+   * @memberof DevnetState
+   */
+  services: Record<string, string> | null;
+}
+
 export class DevnetNetworkOrchestrator {
   handle: any;
   lastCooldownEndedAt: Date;
@@ -491,7 +652,14 @@ export class DevnetNetworkOrchestrator {
     accounts ||= [];
     var devnet = config.devnet;
     devnet ||= {};
-    this.handle = stacksDevnetNew(manifestPath, logs, accounts, devnet);
+    this.handle = stacksDevnetNew(
+      manifestPath,
+      logs,
+      accounts,
+      devnet,
+      config.deploymentPlanPath,
+      config.noDeployment || false
+    );
     this.lastCooldownEndedAt = new Date();
     this.defaultCooldown = defaultCooldown;
     this.currentCooldown = defaultCooldown;
@@ -545,6 +713,50 @@ export class DevnetNetworkOrchestrator {
     return stacksDevnetGetBitcoinExplorerUrl.call(this.handle);
   }
 
+  /**
+   * @summary Returns the ports used by the devnet services, keyed by their Devnet.toml setting
+   * @memberof DevnetNetworkOrchestrator
+   */
+  getPorts(): Record<string, number> {
+    return stacksDevnetGetPorts.call(this.handle);
+  }
+
+  /**
+   * @summary Returns the current tips, epoch and PoX cycle of the devnet
+   * @memberof DevnetNetworkOrchestrator
+   */
+  getState(): DevnetState {
+    return stacksDevnetGetState.call(this.handle);
+  }
+
+  /**
+   * @summary Returns the deployment updates received since the previous call
+   * @memberof DevnetNetworkOrchestrator
+   */
+  getDeploymentProgress(): DeploymentProgress[] {
+    return stacksDevnetGetDeploymentProgress.call(this.handle);
+  }
+
+  /**
+   * @summary Returns the webhooks received since the previous call (`enable_webhook_receiver`)
+   * @memberof DevnetNetworkOrchestrator
+   */
+  getWebhookDeliveries(): WebhookDelivery[] {
+    return stacksDevnetGetWebhookDeliveries.call(this.handle);
+  }
+
+  /**
+   * @summary Wait for a webhook delivered to a path starting with `path`, the deliveries to
+   * other paths are discarded. Returns `undefined` after `timeoutMs`.
+   * @memberof DevnetNetworkOrchestrator
+   */
+  waitForWebhookDelivery(
+    timeoutMs: number = 10000,
+    path?: string
+  ): WebhookDelivery | undefined {
+    return stacksDevnetWaitForWebhookDelivery.call(this.handle, timeoutMs, path);
+  }
+
   /**
    * @summary Wait for the next Stacks block
    * @memberof DevnetNetworkOrchestrator
@@ -711,3 +923,81 @@ export class DevnetNetworkOrchestrator {
     return stacksDevnetTerminate.call(this.handle);
   }
 }
+
+export interface ClusterDevnetConfig {
+  clarinetManifestPath: string;
+  /**
+   * k8s namespace of the devnet
+   */
+  namespace: string;
+  /**
+   * Reach the services through the ingress of this domain
+   * (`<service>.<namespace>.<domain>`) instead of port-forwards
+   */
+  ingressDomain?: string;
+}
+
+/**
+ * Devnet running in a k8s namespace, with the getters of DevnetNetworkOrchestrator. The
+ * port-forwards use the ports of settings/Devnet.toml, as a local devnet would.
+ */
+export class ClusterDevnetConnection {
+  handle: any;
+
+  constructor(config: ClusterDevnetConfig) {
+    this.handle = clusterDevnetConnect(
+      config.clarinetManifestPath,
+      config.namespace,
+      config.ingressDomain
+    );
+  }
+
+  /**
+   * @summary Returns the local URL of the stacks-node service
+   * @memberof ClusterDevnetConnection
+   */
+  getStacksNodeUrl(): string {
+    return clusterDevnetGetUrl.call(this.handle, "stacks-blockchain");
+  }
+
+  /**
+   * @summary Returns the local URL of the bitcoin-node service
+   * @memberof ClusterDevnetConnection
+   */
+  getBitcoinNodeUrl(): string {
+    return clusterDevnetGetUrl.call(this.handle, "bitcoind-chain-coordinator");
+  }
+
+  /**
+   * @summary Returns the local URL of the stacks-api service
+   * @memberof ClusterDevnetConnection
+   */
+  getStacksApiUrl(): string {
+    return clusterDevnetGetUrl.call(this.handle, "stacks-blockchain-api");
+  }
+
+  /**
+   * @summary Returns the local URL of the stacks-explorer service
+   * @memberof ClusterDevnetConnection
+   */
+  getStacksExplorerUrl(): string {
+    return clusterDevnetGetUrl.call(this.handle, "stacks-explorer");
+  }
+
+  /**
+   * @summary Returns the state of the devnet, with the hosts of the services rewritten to
+   * their local URLs
+   * @memberof ClusterDevnetConnection
+   */
+  getState(): DevnetState {
+    return clusterDevnetGetState.call(this.handle);
+  }
+
+  /**
+   * @summary Stops the port-forwards
+   * @memberof ClusterDevnetConnection
+   */
+  close() {
+    clusterDevnetClose.call(this.handle);
+  }
+}
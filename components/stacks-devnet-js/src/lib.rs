@@ -7,8 +7,8 @@ mod serde;
 use clarinet_deployments::{get_default_deployment_path, load_deployment};
 use clarinet_files::bip39::{Language, Mnemonic};
 use clarinet_files::{
-    compute_addresses, AccountConfig, DevnetConfigFile, FileLocation, PoxStackingOrder,
-    ProjectManifest, StacksNetwork, DEFAULT_DERIVATION_PATH,
+    compute_addresses, AccountConfig, DevnetConfigFile, FileLocation, NetworkManifest,
+    PoxStackingOrder, ProjectManifest, StacksNetwork, DEFAULT_DERIVATION_PATH,
 };
 use hiro_system_kit::{o, slog, slog_async, slog_term, Drain};
 use neon::context::Context as NeonContext;
@@ -17,10 +17,16 @@ use stacks_network::chainhook_sdk::types::{
     StacksChainUpdatedWithBlocksData,
 };
 use stacks_network::chains_coordinator::BitcoinMiningCommand;
-use stacks_network::{self, Context, DevnetEvent, DevnetOrchestrator, LogLevel};
+use stacks_network::cluster_access::{ClusterAccess, ClusterConnection};
+use stacks_network::coordinator_state::{ChainsCoordinatorHandle, ChainsCoordinatorState};
+use stacks_network::{
+    self, webhooks::WebhookDelivery, Context, DeployingStatus, DevnetEvent, DevnetOrchestrator,
+    LogLevel, ProtocolDeployingData,
+};
 
 use core::panic;
 use neon::prelude::*;
+use std::cell::RefCell;
 use std::collections::BTreeMap;
 use std::fs::OpenOptions;
 use std::path::PathBuf;
@@ -68,11 +74,15 @@ struct StacksDevnet {
     mining_tx: mpsc::Sender<BitcoinMiningCommand>,
     bitcoin_block_rx: mpsc::Receiver<BitcoinChainUpdatedWithBlocksData>,
     stacks_block_rx: mpsc::Receiver<StacksChainUpdatedWithBlocksData>,
+    deployment_progress_rx: mpsc::Receiver<ProtocolDeployingData>,
+    webhook_delivery_rx: mpsc::Receiver<WebhookDelivery>,
+    coordinator_handle: ChainsCoordinatorHandle,
     bitcoin_node_url: String,
     stacks_node_url: String,
     stacks_api_url: String,
     stacks_explorer_url: String,
     bitcoin_explorer_url: String,
+    ports: BTreeMap<String, u16>,
 }
 
 enum DevnetCommand {
@@ -89,6 +99,8 @@ impl StacksDevnet {
         logs_enabled: bool,
         _accounts: BTreeMap<String, AccountConfig>,
         devnet_overrides: DevnetConfigFile,
+        deployment_plan_path: Option<String>,
+        no_deployment: bool,
     ) -> Self
     where
         C: NeonContext<'a>,
@@ -105,15 +117,31 @@ impl StacksDevnet {
         let (log_tx, _log_rx) = mpsc::channel();
         let (bitcoin_block_tx, bitcoin_block_rx) = mpsc::channel();
         let (stacks_block_tx, stacks_block_rx) = mpsc::channel();
+        let (deployment_progress_tx, deployment_progress_rx) = mpsc::channel();
+        let (webhook_delivery_tx, webhook_delivery_rx) = mpsc::channel();
 
         let channel = cx.channel();
 
         let manifest_location = get_manifest_location_or_exit(Some(manifest_location));
         let manifest = ProjectManifest::from_location(&manifest_location)
             .expect("Syntax error in Clarinet.toml.");
-        let (deployment, _) =
-            read_deployment_or_generate_default(&manifest, &StacksNetwork::Devnet)
-                .expect("Unable to generate deployment");
+        let deployment = if no_deployment {
+            DeploymentSpecification::empty(&StacksNetwork::Devnet)
+        } else if let Some(deployment_plan_path) = deployment_plan_path {
+            let mut deployment_location = manifest
+                .location
+                .get_project_root_location()
+                .expect("Unable to retrieve project root");
+            deployment_location
+                .append_path(&deployment_plan_path)
+                .expect("Invalid deployment plan path");
+            load_deployment(&manifest, &deployment_location).expect("Unable to load deployment")
+        } else {
+            let (deployment, _) =
+                read_deployment_or_generate_default(&manifest, &StacksNetwork::Devnet)
+                    .expect("Unable to generate deployment");
+            deployment
+        };
         let working_dir = devnet_overrides.working_dir.clone();
         let devnet =
             match DevnetOrchestrator::new(manifest, None, Some(devnet_overrides), true, false) {
@@ -168,12 +196,14 @@ impl StacksDevnet {
             None => None,
         };
 
+        let coordinator_handle = devnet.coordinator_handle.clone();
         let (
             bitcoin_node_url,
             stacks_node_url,
             stacks_api_url,
             stacks_explorer_url,
             bitcoin_explorer_url,
+            ports,
         ) = devnet
             .network_config
             .as_ref()
@@ -185,6 +215,7 @@ impl StacksDevnet {
                     format!("http://localhost:{}", devnet.stacks_api_port),
                     format!("http://localhost:{}", devnet.stacks_explorer_port),
                     format!("http://localhost:{}", devnet.bitcoin_explorer_port),
+                    devnet.get_ports(),
                 )
             })
             .expect("unable to read config");
@@ -201,6 +232,7 @@ impl StacksDevnet {
                             &mut None,
                             Some(log_tx),
                             false,
+                            false,
                             stacks_network::Context::empty(),
                             termination_tx,
                             None,
@@ -317,7 +349,25 @@ impl StacksDevnet {
                                     }
                                 }
                             }
-                            DevnetEvent::BootCompleted(mining_tx) => {
+                            DevnetEvent::ProtocolDeployingProgress(progress) => {
+                                if logs_enabled && progress.status != DeployingStatus::Encoded {
+                                    println!(
+                                        "[info] Deployment {}/{}: {} {}",
+                                        progress.confirmed,
+                                        progress.total,
+                                        progress.name,
+                                        progress.status
+                                    );
+                                }
+                                let _ = deployment_progress_tx.send(progress);
+                            }
+                            DevnetEvent::WebhookDelivered(delivery) => {
+                                let _ = webhook_delivery_tx.send(delivery);
+                            }
+                            DevnetEvent::BootCompleted(mining_tx, boot_report) => {
+                                if logs_enabled {
+                                    println!("[info] Devnet ready, {}", boot_report);
+                                }
                                 let _ = meta_mining_command_tx.send(mining_tx);
                                 let _ = devnet_ready_tx.send(Ok(()));
                             }
@@ -361,11 +411,15 @@ impl StacksDevnet {
             mining_tx: relaying_mining_tx,
             bitcoin_block_rx,
             stacks_block_rx,
+            deployment_progress_rx,
+            webhook_delivery_rx,
+            coordinator_handle,
             bitcoin_node_url,
             stacks_node_url,
             stacks_api_url,
             stacks_explorer_url,
             bitcoin_explorer_url,
+            ports,
         }
     }
 
@@ -388,6 +442,17 @@ impl StacksDevnet {
 
         let devnet_settings = cx.argument::<JsObject>(3)?;
 
+        let deployment_plan_path = cx
+            .argument::<JsValue>(4)?
+            .downcast::<JsString, _>(&mut cx)
+            .ok()
+            .map(|path| path.value(&mut cx));
+
+        let no_deployment = cx
+            .argument::<JsValue>(5)?
+            .downcast::<JsBoolean, _>(&mut cx)
+            .map_or(false, |no_deployment| no_deployment.value(&mut cx));
+
         let mut genesis_accounts = BTreeMap::new();
 
         for account in accounts.iter() {
@@ -583,6 +648,13 @@ impl StacksDevnet {
             overrides.postgres_port = Some(res.value(&mut cx) as u16);
         }
 
+        if let Ok(res) = devnet_settings
+            .get(&mut cx, "auto_ports")?
+            .downcast::<JsBoolean, _>(&mut cx)
+        {
+            overrides.auto_ports = Some(res.value(&mut cx));
+        }
+
         if let Ok(res) = devnet_settings
             .get(&mut cx, "postgres_username")?
             .downcast::<JsString, _>(&mut cx)
@@ -749,6 +821,20 @@ impl StacksDevnet {
             overrides.disable_stacks_api = Some(true);
         }
 
+        if let Ok(res) = devnet_settings
+            .get(&mut cx, "enable_webhook_receiver")?
+            .downcast::<JsBoolean, _>(&mut cx)
+        {
+            overrides.enable_webhook_receiver = Some(res.value(&mut cx));
+        }
+
+        if let Ok(res) = devnet_settings
+            .get(&mut cx, "webhook_receiver_port")?
+            .downcast::<JsNumber, _>(&mut cx)
+        {
+            overrides.webhook_receiver_port = Some(res.value(&mut cx) as u16);
+        }
+
         // Disable bitcoin automining default:
         if let Ok(res) = devnet_settings
             .get(&mut cx, "bitcoin_controller_automining_disabled")?
@@ -820,6 +906,7 @@ impl StacksDevnet {
                     slots,
                     btc_address,
                     auto_extend: Some(false),
+                    pool_operator: None,
                 });
             }
             overrides.pox_stacking_orders = Some(stacking_orders);
@@ -831,6 +918,8 @@ impl StacksDevnet {
             logs_enabled,
             genesis_accounts,
             overrides,
+            deployment_plan_path,
+            no_deployment,
         );
         Ok(cx.boxed(devnet))
     }
@@ -954,6 +1043,167 @@ impl StacksDevnet {
         let val = JsString::new(&mut cx, &devnet.stacks_api_url);
         Ok(val)
     }
+
+    fn js_get_ports(mut cx: FunctionContext) -> JsResult<JsValue> {
+        let devnet = cx
+            .this()
+            .downcast_or_throw::<JsBox<StacksDevnet>, _>(&mut cx)?;
+
+        let ports = serde::to_value(&mut cx, &devnet.ports).expect("Unable to serialize ports");
+        Ok(ports)
+    }
+
+    fn js_get_state(mut cx: FunctionContext) -> JsResult<JsValue> {
+        let devnet = cx
+            .this()
+            .downcast_or_throw::<JsBox<StacksDevnet>, _>(&mut cx)?;
+
+        let state: ChainsCoordinatorState = devnet.coordinator_handle.state();
+        let state = serde::to_value(&mut cx, &state).expect("Unable to serialize devnet state");
+        Ok(state)
+    }
+
+    fn js_get_deployment_progress(mut cx: FunctionContext) -> JsResult<JsValue> {
+        let devnet = cx
+            .this()
+            .downcast_or_throw::<JsBox<StacksDevnet>, _>(&mut cx)?;
+
+        let updates: Vec<ProtocolDeployingData> =
+            devnet.deployment_progress_rx.try_iter().collect();
+        let updates =
+            serde::to_value(&mut cx, &updates).expect("Unable to serialize deployment progress");
+        Ok(updates)
+    }
+
+    fn js_get_webhook_deliveries(mut cx: FunctionContext) -> JsResult<JsValue> {
+        let devnet = cx
+            .this()
+            .downcast_or_throw::<JsBox<StacksDevnet>, _>(&mut cx)?;
+
+        let deliveries: Vec<WebhookDelivery> = devnet.webhook_delivery_rx.try_iter().collect();
+        let deliveries =
+            serde::to_value(&mut cx, &deliveries).expect("Unable to serialize webhook deliveries");
+        Ok(deliveries)
+    }
+
+    fn js_wait_for_webhook_delivery(mut cx: FunctionContext) -> JsResult<JsValue> {
+        let devnet = cx
+            .this()
+            .downcast_or_throw::<JsBox<StacksDevnet>, _>(&mut cx)?;
+        let timeout = cx.argument::<JsNumber>(0)?.value(&mut cx) as u64;
+        let path_prefix = match cx.argument_opt(1) {
+            Some(arg) if arg.is_a::<JsString, _>(&mut cx) => Some(
+                arg.downcast_or_throw::<JsString, _>(&mut cx)?
+                    .value(&mut cx),
+            ),
+            _ => None,
+        };
+
+        // the deliveries to other paths are skipped
+        let deadline = std::time::Instant::now() + std::time::Duration::from_millis(timeout);
+        let delivery = loop {
+            let remaining = deadline.saturating_duration_since(std::time::Instant::now());
+            match devnet.webhook_delivery_rx.recv_timeout(remaining) {
+                Ok(delivery) => {
+                    let is_expected = path_prefix
+                        .as_ref()
+                        .map_or(true, |prefix| delivery.path.starts_with(prefix));
+                    if is_expected {
+                        break delivery;
+                    }
+                }
+                Err(_) => return Ok(cx.undefined().as_value(&mut cx)),
+            }
+        };
+
+        let delivery =
+            serde::to_value(&mut cx, &delivery).expect("Unable to serialize webhook delivery");
+        Ok(delivery)
+    }
+}
+
+/// Devnet of a k8s namespace, reached from the local machine
+struct ClusterDevnet {
+    connection: RefCell<Option<ClusterConnection>>,
+}
+
+impl Finalize for ClusterDevnet {}
+
+impl ClusterDevnet {
+    fn js_connect(mut cx: FunctionContext) -> JsResult<JsBox<ClusterDevnet>> {
+        let manifest_location = cx.argument::<JsString>(0)?.value(&mut cx);
+        let namespace = cx.argument::<JsString>(1)?.value(&mut cx);
+        let ingress_domain = cx
+            .argument::<JsValue>(2)?
+            .downcast::<JsString, _>(&mut cx)
+            .ok()
+            .map(|domain| domain.value(&mut cx));
+
+        let manifest_location = get_manifest_location_or_exit(Some(manifest_location));
+        let manifest = ProjectManifest::from_location(&manifest_location)
+            .expect("Syntax error in Clarinet.toml.");
+        let devnet_config = NetworkManifest::from_project_manifest_location(
+            &manifest.location,
+            &StacksNetwork::Devnet.get_networks(),
+            Some(&manifest.project.cache_location),
+            None,
+        )
+        .ok()
+        .and_then(|network_manifest| network_manifest.devnet);
+        let Some(devnet_config) = devnet_config else {
+            return cx.throw_error("unable to read the devnet settings");
+        };
+        let access = match ingress_domain {
+            Some(domain) => ClusterAccess::Ingress { domain },
+            None => ClusterAccess::PortForward,
+        };
+        let connection = ClusterConnection::connect(&namespace, access, &devnet_config)
+            .or_else(|err| cx.throw_error(err))?;
+        Ok(cx.boxed(ClusterDevnet {
+            connection: RefCell::new(Some(connection)),
+        }))
+    }
+
+    fn js_get_url(mut cx: FunctionContext) -> JsResult<JsValue> {
+        let service = cx.argument::<JsString>(0)?.value(&mut cx);
+        let devnet = cx
+            .this()
+            .downcast_or_throw::<JsBox<ClusterDevnet>, _>(&mut cx)?;
+
+        let url = devnet
+            .connection
+            .borrow()
+            .as_ref()
+            .and_then(|connection| connection.get_url(&service));
+        match url {
+            Some(url) => Ok(JsString::new(&mut cx, url).as_value(&mut cx)),
+            None => Ok(cx.undefined().as_value(&mut cx)),
+        }
+    }
+
+    fn js_get_state(mut cx: FunctionContext) -> JsResult<JsValue> {
+        let devnet = cx
+            .this()
+            .downcast_or_throw::<JsBox<ClusterDevnet>, _>(&mut cx)?;
+
+        let state = match devnet.connection.borrow().as_ref() {
+            Some(connection) => connection.get_status(),
+            None => Err("the connection is closed".to_string()),
+        };
+        let state = state.or_else(|err| cx.throw_error(err))?;
+        let state = serde::to_value(&mut cx, &state).expect("Unable to serialize devnet state");
+        Ok(state)
+    }
+
+    fn js_close(mut cx: FunctionContext) -> JsResult<JsUndefined> {
+        let devnet = cx
+            .this()
+            .downcast_or_throw::<JsBox<ClusterDevnet>, _>(&mut cx)?;
+
+        // the port-forwards are stopped as the connection is dropped
+        devnet.connection.borrow_mut().take();
+        Ok(cx.undefined())
+    }
 }
 
 #[neon::main]
@@ -990,6 +1240,24 @@ fn main(mut cx: ModuleContext) -> NeonResult<()> {
         "stacksDevnetGetStacksApiUrl",
         StacksDevnet::js_get_stacks_api_url,
     )?;
+    cx.export_function("stacksDevnetGetPorts", StacksDevnet::js_get_ports)?;
+    cx.export_function("stacksDevnetGetState", StacksDevnet::js_get_state)?;
+    cx.export_function("clusterDevnetConnect", ClusterDevnet::js_connect)?;
+    cx.export_function("clusterDevnetGetUrl", ClusterDevnet::js_get_url)?;
+    cx.export_function("clusterDevnetGetState", ClusterDevnet::js_get_state)?;
+    cx.export_function("clusterDevnetClose", ClusterDevnet::js_close)?;
+    cx.export_function(
+        "stacksDevnetGetDeploymentProgress",
+        StacksDevnet::js_get_deployment_progress,
+    )?;
+    cx.export_function(
+        "stacksDevnetGetWebhookDeliveries",
+        StacksDevnet::js_get_webhook_deliveries,
+    )?;
+    cx.export_function(
+        "stacksDevnetWaitForWebhookDelivery",
+        StacksDevnet::js_wait_for_webhook_delivery,
+    )?;
     Ok(())
 }
 
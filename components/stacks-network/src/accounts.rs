@@ -0,0 +1,225 @@
+//! Nonces and balances of the devnet accounts over the last blocks, shown in the dashboard.
+//! An account with transactions waiting in the mempool while its nonce stays the same is
+//! stuck, an account whose balance keeps decreasing block after block is being drained.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::io::Cursor;
+use std::time::Duration;
+
+use clarinet_files::AccountConfig;
+use clarity::codec::StacksMessageCodec;
+use clarity::util::hash::hex_bytes;
+use stacks_codec::codec::StacksTransaction;
+
+use crate::drift::parse_hex_amount;
+use crate::pox::get_json;
+
+/// Blocks kept in the history of an account
+pub const ACCOUNT_HISTORY_LENGTH: usize = 30;
+/// Blocks mined without confirming the pending transactions of an account before it is stuck
+pub const STUCK_AFTER_BLOCKS: u64 = 5;
+/// Consecutive blocks decreasing the balance of an account before it is being drained
+pub const DRAINED_AFTER_BLOCKS: usize = 5;
+
+const SPARKLINE_LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct AccountActivity {
+    pub label: String,
+    pub address: String,
+    /// Nonce of the next transaction, as known by the node
+    pub nonce: u64,
+    /// Highest nonce of the transactions of the account admitted in the mempool
+    pub pending_nonce: Option<u64>,
+    /// Unlocked and locked balance
+    pub balance: u128,
+    /// Balances at the last blocks, oldest first
+    pub balances: VecDeque<u128>,
+    /// Blocks mined since the nonce last changed
+    pub idle_blocks: u64,
+}
+
+impl AccountActivity {
+    pub fn record(&mut self, nonce: u64, balance: u128) {
+        if nonce != self.nonce || self.balances.is_empty() {
+            self.idle_blocks = 0;
+        } else {
+            self.idle_blocks += 1;
+        }
+        self.nonce = nonce;
+        if self
+            .pending_nonce
+            .is_some_and(|pending_nonce| pending_nonce < nonce)
+        {
+            self.pending_nonce = None;
+        }
+        self.balance = balance;
+        self.balances.push_back(balance);
+        while self.balances.len() > ACCOUNT_HISTORY_LENGTH {
+            self.balances.pop_front();
+        }
+    }
+
+    pub fn is_stuck(&self) -> bool {
+        self.pending_nonce.is_some() && self.idle_blocks >= STUCK_AFTER_BLOCKS
+    }
+
+    pub fn is_drained(&self) -> bool {
+        if self.balances.len() <= DRAINED_AFTER_BLOCKS {
+            return false;
+        }
+        self.balances
+            .iter()
+            .skip(self.balances.len() - DRAINED_AFTER_BLOCKS - 1)
+            .collect::<Vec<_>>()
+            .windows(2)
+            .all(|pair| pair[1] < pair[0])
+    }
+}
+
+/// One character per value, scaled between the lowest and the highest values
+pub fn get_sparkline(values: &[u128]) -> String {
+    let (Some(min), Some(max)) = (values.iter().min(), values.iter().max()) else {
+        return String::new();
+    };
+    let range = max - min;
+    values
+        .iter()
+        .map(|value| {
+            let level = if range == 0 {
+                SPARKLINE_LEVELS.len() / 2
+            } else {
+                ((value - min) * (SPARKLINE_LEVELS.len() as u128 - 1) / range) as usize
+            };
+            SPARKLINE_LEVELS[level]
+        })
+        .collect()
+}
+
+#[derive(Deserialize, Debug)]
+struct AccountResponse {
+    balance: String,
+    locked: String,
+    nonce: u64,
+}
+
+/// Keeps track of the nonces and balances of the accounts of the network manifest
+#[derive(Debug, Clone)]
+pub struct AccountsMonitor {
+    pub accounts: Vec<AccountActivity>,
+    node_rpc_url: String,
+}
+
+impl AccountsMonitor {
+    pub fn new(node_rpc_url: &str, accounts: &[AccountConfig]) -> Self {
+        Self {
+            accounts: accounts
+                .iter()
+                .map(|account| AccountActivity {
+                    label: account.label.clone(),
+                    address: account.stx_address.clone(),
+                    ..Default::default()
+                })
+                .collect(),
+            node_rpc_url: node_rpc_url.to_string(),
+        }
+    }
+
+    /// Records the nonce of a transaction admitted in the mempool, sent by a watched account
+    pub fn record_mempool_admission(&mut self, raw_tx: &str) {
+        let Ok(bytes) = hex_bytes(raw_tx.trim_start_matches("0x")) else {
+            return;
+        };
+        let Ok(tx) = StacksTransaction::consensus_deserialize(&mut Cursor::new(&bytes)) else {
+            return;
+        };
+        let sender = tx.origin_address().to_string();
+        let nonce = tx.get_origin_nonce();
+        if let Some(account) = self.accounts.iter_mut().find(|a| a.address == sender) {
+            account.pending_nonce = Some(account.pending_nonce.map_or(nonce, |n| n.max(nonce)));
+        }
+    }
+
+    /// Reads the accounts from the node, once per block. Returns the warnings of the accounts
+    /// becoming stuck or drained.
+    pub async fn refresh(&mut self) -> Result<Vec<String>, String> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(2))
+            .build()
+            .map_err(|e| e.to_string())?;
+        let mut responses = BTreeMap::new();
+        for account in self.accounts.iter() {
+            let response: AccountResponse = get_json(
+                &client,
+                format!(
+                    "{}/v2/accounts/{}?proof=0",
+                    self.node_rpc_url, account.address
+                ),
+            )
+            .await?;
+            let balance =
+                parse_hex_amount(&response.balance)? + parse_hex_amount(&response.locked)?;
+            responses.insert(account.address.clone(), (response.nonce, balance));
+        }
+
+        let mut warnings = vec![];
+        for account in self.accounts.iter_mut() {
+            let Some((nonce, balance)) = responses.get(&account.address) else {
+                continue;
+            };
+            let (was_stuck, was_drained) = (account.is_stuck(), account.is_drained());
+            account.record(*nonce, *balance);
+            if account.is_stuck() && !was_stuck {
+                warnings.push(format!(
+                    "{} ({}) looks stuck: nonce {} unchanged for {} blocks with transactions pending in the mempool",
+                    account.label, account.address, account.nonce, account.idle_blocks
+                ));
+            }
+            if account.is_drained() && !was_drained {
+                warnings.push(format!(
+                    "{} ({}) balance decreased in each of the last {} blocks",
+                    account.label, account.address, DRAINED_AFTER_BLOCKS
+                ));
+            }
+        }
+        Ok(warnings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_account_activity() {
+        let mut account = AccountActivity::default();
+        account.record(0, 1000);
+        account.pending_nonce = Some(0);
+        for _ in 0..STUCK_AFTER_BLOCKS {
+            assert!(!account.is_stuck());
+            account.record(0, 1000);
+        }
+        assert!(account.is_stuck());
+        account.record(1, 990);
+        assert!(!account.is_stuck());
+        assert_eq!(account.pending_nonce, None);
+
+        for balance in [980, 970, 960] {
+            account.record(1, balance);
+            assert!(!account.is_drained());
+        }
+        account.record(1, 950);
+        assert!(account.is_drained());
+        account.record(1, 950);
+        assert!(!account.is_drained());
+
+        for _ in 0..ACCOUNT_HISTORY_LENGTH {
+            account.record(1, 940);
+        }
+        assert_eq!(account.balances.len(), ACCOUNT_HISTORY_LENGTH);
+
+        assert_eq!(get_sparkline(&[0, 7, 14, 7]), "▁▄█▄");
+        assert_eq!(get_sparkline(&[5, 5]), "▅▅");
+        assert_eq!(get_sparkline(&[]), "");
+    }
+}
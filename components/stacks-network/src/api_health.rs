@@ -0,0 +1,103 @@
+//! Lag of the stacks-api behind the tip of the stacks-node. The API ingests the blocks and
+//! answers the queries with the same postgres pool, a test suite hammering it can slow the
+//! ingestion down until the API serves stale data; the lag is shown in the dashboard and a
+//! warning is logged when it goes over `stacks_api_max_lag`.
+
+use std::time::Duration;
+
+use crate::pox::get_json;
+
+#[derive(Deserialize, Debug)]
+struct StacksApiStatus {
+    chain_tip: Option<StacksApiChainTip>,
+}
+
+#[derive(Deserialize, Debug)]
+struct StacksApiChainTip {
+    block_height: u64,
+}
+
+/// Height of the last block indexed by the stacks-api, `None` before the first one
+pub async fn get_stacks_api_tip(stacks_api_host: &str) -> Result<Option<u64>, String> {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(2))
+        .build()
+        .map_err(|e| e.to_string())?;
+    let status: StacksApiStatus = get_json(
+        &client,
+        format!("http://{}/extended/v1/status", stacks_api_host),
+    )
+    .await?;
+    Ok(status.chain_tip.map(|chain_tip| chain_tip.block_height))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ApiHealthChange {
+    /// The API went over the maximum lag, by this number of blocks
+    Lagging(u64),
+    /// The API caught up with the node
+    Recovered,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct StacksApiHealth {
+    pub max_lag: u64,
+    pub node_height: u64,
+    pub api_height: Option<u64>,
+    is_lagging: bool,
+}
+
+impl StacksApiHealth {
+    pub fn new(max_lag: u64) -> Self {
+        StacksApiHealth {
+            max_lag,
+            ..Default::default()
+        }
+    }
+
+    pub fn get_lag(&self) -> u64 {
+        self.node_height
+            .saturating_sub(self.api_height.unwrap_or_default())
+    }
+
+    /// Records the heights at a new block of the node, returns the change of health if any
+    pub fn record(&mut self, node_height: u64, api_height: Option<u64>) -> Option<ApiHealthChange> {
+        self.node_height = node_height;
+        self.api_height = api_height;
+        let lag = self.get_lag();
+        match (self.is_lagging, lag > self.max_lag) {
+            (false, true) => {
+                self.is_lagging = true;
+                Some(ApiHealthChange::Lagging(lag))
+            }
+            (true, false) => {
+                self.is_lagging = false;
+                Some(ApiHealthChange::Recovered)
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stacks_api_health() {
+        let mut health = StacksApiHealth::new(3);
+        assert_eq!(health.record(10, Some(10)), None);
+        assert_eq!(health.record(12, Some(10)), None);
+        assert_eq!(
+            health.record(14, Some(10)),
+            Some(ApiHealthChange::Lagging(4))
+        );
+        assert_eq!(health.record(15, Some(10)), None);
+        assert_eq!(health.get_lag(), 5);
+        assert_eq!(
+            health.record(16, Some(14)),
+            Some(ApiHealthChange::Recovered)
+        );
+        assert_eq!(health.record(17, None), Some(ApiHealthChange::Lagging(17)));
+    }
+}
@@ -0,0 +1,242 @@
+//! Phases of the boot sequence of a devnet, each bounded by its own timeout
+//! (`[devnet.boot_timeouts]`). A phase running for too long stops the boot with an error
+//! naming the phase and how to get past it, and the duration of the phases is reported
+//! once the devnet is ready.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use clarinet_files::BootTimeouts;
+
+use crate::event::DevnetEvent;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BootPhase {
+    ContainerPull,
+    BitcoinReady,
+    StacksGenesis,
+    Epoch3Reached,
+    ApiSynced,
+}
+
+impl BootPhase {
+    pub fn get_timeout(&self, timeouts: &BootTimeouts) -> Duration {
+        let seconds = match self {
+            BootPhase::ContainerPull => timeouts.container_pull,
+            BootPhase::BitcoinReady => timeouts.bitcoin_ready,
+            BootPhase::StacksGenesis => timeouts.stacks_genesis,
+            BootPhase::Epoch3Reached => timeouts.epoch_3_reached,
+            BootPhase::ApiSynced => timeouts.api_synced,
+        };
+        Duration::from_secs(seconds)
+    }
+
+    /// Name of the setting of the phase in `[devnet.boot_timeouts]`
+    pub fn setting(&self) -> &'static str {
+        match self {
+            BootPhase::ContainerPull => "container_pull",
+            BootPhase::BitcoinReady => "bitcoin_ready",
+            BootPhase::StacksGenesis => "stacks_genesis",
+            BootPhase::Epoch3Reached => "epoch_3_reached",
+            BootPhase::ApiSynced => "api_synced",
+        }
+    }
+
+    pub fn remediation(&self) -> &'static str {
+        match self {
+            BootPhase::ContainerPull => "check the network connection and the access to the registries of the images (docker pull), or pull the images ahead of time",
+            BootPhase::BitcoinReady => "check the logs of the bitcoin-node container, and that the bitcoin_node_rpc_port is not used by another process",
+            BootPhase::StacksGenesis => "check the logs of the stacks-node container, the stacks-node needs the bitcoin-node and the orchestrator_port to be reachable from the containers",
+            BootPhase::Epoch3Reached => "check that the signers are running and that the stacking orders of the Devnet.toml register enough STX for the reward cycles before the epoch 3.0, or lower epoch_3_0",
+            BootPhase::ApiSynced => "check the logs of the stacks-api and postgres containers, or disable the stacks-api (disable_stacks_api = true) if it is not needed",
+        }
+    }
+}
+
+impl fmt::Display for BootPhase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            BootPhase::ContainerPull => "container pull",
+            BootPhase::BitcoinReady => "bitcoin ready",
+            BootPhase::StacksGenesis => "stacks genesis",
+            BootPhase::Epoch3Reached => "epoch 3.0 reached",
+            BootPhase::ApiSynced => "api synced",
+        };
+        write!(f, "{}", label)
+    }
+}
+
+/// Durations of the boot, sent with the ready event
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct BootReport {
+    pub total: Duration,
+    /// Time spent in each phase, the pulls of the images added up
+    pub phases: BTreeMap<BootPhase, Duration>,
+}
+
+impl fmt::Display for BootReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "boot completed in {}s", self.total.as_secs())?;
+        let phases = self
+            .phases
+            .iter()
+            .map(|(phase, duration)| format!("{}: {}s", phase, duration.as_secs()))
+            .collect::<Vec<_>>();
+        if !phases.is_empty() {
+            write!(f, " ({})", phases.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default)]
+struct BootTrackerState {
+    started_at: Option<Instant>,
+    running: BTreeMap<BootPhase, Instant>,
+    durations: BTreeMap<BootPhase, Duration>,
+    ready_at: Option<Instant>,
+    stopped: bool,
+}
+
+/// Shared by the orchestrator and the chains coordinator, entering and exiting the phases
+#[derive(Debug, Clone, Default)]
+pub struct BootTracker {
+    state: Arc<Mutex<BootTrackerState>>,
+}
+
+impl BootTracker {
+    pub fn enter(&self, phase: BootPhase) {
+        let mut state = self.state.lock().unwrap();
+        let now = Instant::now();
+        state.started_at.get_or_insert(now);
+        state.running.entry(phase).or_insert(now);
+    }
+
+    /// Ends a running phase, ignored if the phase is not running
+    pub fn exit(&self, phase: BootPhase) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(entered_at) = state.running.remove(&phase) {
+            *state.durations.entry(phase).or_default() += entered_at.elapsed();
+        }
+    }
+
+    pub fn is_running(&self, phase: BootPhase) -> bool {
+        self.state.lock().unwrap().running.contains_key(&phase)
+    }
+
+    /// Stops watching the phases, the ones still running are not reported
+    pub fn stop(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.stopped = true;
+        state.running.clear();
+    }
+
+    /// Stopped, or ready with no phase left running
+    pub fn is_done(&self) -> bool {
+        let state = self.state.lock().unwrap();
+        state.stopped || (state.ready_at.is_some() && state.running.is_empty())
+    }
+
+    /// Marks the devnet as ready. The phases still running (the epoch 3.0 can be reached
+    /// after the deployment) keep being watched but are not part of the report.
+    pub fn set_ready(&self) -> BootReport {
+        let mut state = self.state.lock().unwrap();
+        let now = Instant::now();
+        state.ready_at.get_or_insert(now);
+        BootReport {
+            total: state
+                .started_at
+                .map(|started_at| now.saturating_duration_since(started_at))
+                .unwrap_or_default(),
+            phases: state.durations.clone(),
+        }
+    }
+
+    /// First running phase over its timeout at `now`
+    pub fn get_expired_phase(&self, timeouts: &BootTimeouts, now: Instant) -> Option<BootPhase> {
+        let state = self.state.lock().unwrap();
+        state
+            .running
+            .iter()
+            .find(|(phase, entered_at)| {
+                now.saturating_duration_since(**entered_at) > phase.get_timeout(timeouts)
+            })
+            .map(|(phase, _)| *phase)
+    }
+}
+
+pub fn get_timeout_error(phase: BootPhase, timeouts: &BootTimeouts) -> String {
+    format!(
+        "devnet boot timed out: phase '{}' exceeded {}s ([devnet.boot_timeouts] {}), {}",
+        phase,
+        phase.get_timeout(timeouts).as_secs(),
+        phase.setting(),
+        phase.remediation()
+    )
+}
+
+/// Checks the running phases every second, until the tracker is stopped or a phase expires
+pub fn start_boot_watchdog(
+    tracker: BootTracker,
+    timeouts: BootTimeouts,
+    devnet_event_tx: Sender<DevnetEvent>,
+) {
+    let _ = hiro_system_kit::thread_named("Boot watchdog").spawn(move || loop {
+        std::thread::sleep(Duration::from_secs(1));
+        if tracker.is_done() {
+            break;
+        }
+        if let Some(phase) = tracker.get_expired_phase(&timeouts, Instant::now()) {
+            tracker.stop();
+            let _ =
+                devnet_event_tx.send(DevnetEvent::FatalError(get_timeout_error(phase, &timeouts)));
+            break;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_boot_phases_timeouts() {
+        let timeouts = BootTimeouts {
+            container_pull: 600,
+            bitcoin_ready: 0,
+            ..Default::default()
+        };
+        let tracker = BootTracker::default();
+        tracker.enter(BootPhase::ContainerPull);
+        tracker.exit(BootPhase::ContainerPull);
+        tracker.enter(BootPhase::ContainerPull);
+        tracker.enter(BootPhase::BitcoinReady);
+        assert!(tracker.is_running(BootPhase::ContainerPull));
+
+        let later = Instant::now() + Duration::from_secs(5);
+        assert_eq!(
+            tracker.get_expired_phase(&timeouts, later),
+            Some(BootPhase::BitcoinReady)
+        );
+        tracker.exit(BootPhase::BitcoinReady);
+        assert_eq!(tracker.get_expired_phase(&timeouts, later), None);
+
+        let report = tracker.set_ready();
+        assert_eq!(
+            report.phases.keys().collect::<Vec<_>>(),
+            vec![&BootPhase::BitcoinReady]
+        );
+        assert!(!tracker.is_done());
+        tracker.exit(BootPhase::ContainerPull);
+        assert!(tracker.is_done());
+
+        let error = get_timeout_error(BootPhase::BitcoinReady, &timeouts);
+        assert!(error.starts_with(
+            "devnet boot timed out: phase 'bitcoin ready' exceeded 0s ([devnet.boot_timeouts] bitcoin_ready)"
+        ));
+    }
+}
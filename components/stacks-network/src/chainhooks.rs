@@ -1,9 +1,18 @@
-use chainhook_sdk::chainhooks::types::{ChainhookSpecificationNetworkMap, ChainhookStore};
-use chainhook_sdk::types::{BitcoinNetwork, StacksNetwork};
+use chainhook_sdk::chainhooks::stacks::{
+    evaluate_stacks_chainhook_on_blocks, serialize_stacks_payload_to_json, StacksTriggerChainhook,
+};
+use chainhook_sdk::chainhooks::types::{
+    ChainhookSpecificationNetworkMap, ChainhookStore, StacksChainhookSpecification,
+};
+use chainhook_sdk::types::{BitcoinNetwork, BlockIdentifier, StacksBlockData, StacksNetwork};
+use chainhook_sdk::utils::Context;
 use clarinet_files::FileLocation;
-use std::fs::File;
-use std::io::BufReader;
-use std::path::PathBuf;
+use serde_json::Value as JsonValue;
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
 
 use std::fs;
 
@@ -82,3 +91,221 @@ fn get_chainhooks_files(
 
     Ok(hook_paths)
 }
+
+/// Transactions of a recorded block matching a predicate, with the payload the predicate
+/// would send for this block
+#[derive(Debug, Clone, Serialize)]
+pub struct PredicateOccurrence {
+    pub block_identifier: BlockIdentifier,
+    pub transactions: Vec<String>,
+    pub payload: JsonValue,
+}
+
+pub fn load_stacks_predicate(
+    path: &Path,
+    network: &StacksNetwork,
+) -> Result<StacksChainhookSpecification, String> {
+    match parse_chainhook_full_specification(&path.to_path_buf())? {
+        ChainhookSpecificationNetworkMap::Stacks(predicate) => {
+            let mut predicate = predicate.into_specification_for_network(network)?;
+            predicate.enabled = true;
+            Ok(predicate)
+        }
+        ChainhookSpecificationNetworkMap::Bitcoin(_) => {
+            Err("only stacks predicates can be tested against recorded blocks".to_string())
+        }
+    }
+}
+
+/// Reads the blocks of a JSON lines file, each line being a block (`StacksBlockData`) or a
+/// payload received by a chainhook, whose `apply` blocks are read.
+pub fn read_recorded_blocks(path: &Path) -> Result<Vec<StacksBlockData>, String> {
+    let file = File::open(path).map_err(|e| format!("unable to open {}: {}", path.display(), e))?;
+    let mut blocks = vec![];
+    for (index, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.map_err(|e| format!("unable to read {}: {}", path.display(), e))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let invalid_line = |e: serde_json::Error| {
+            format!("{}:{}: invalid block: {}", path.display(), index + 1, e)
+        };
+        let mut value: JsonValue = serde_json::from_str(&line).map_err(invalid_line)?;
+        match value.get_mut("apply").map(JsonValue::take) {
+            Some(apply) => {
+                let apply: Vec<StacksBlockData> =
+                    serde_json::from_value(apply).map_err(invalid_line)?;
+                blocks.extend(apply);
+            }
+            None => blocks.push(serde_json::from_value(value).map_err(invalid_line)?),
+        }
+    }
+    Ok(blocks)
+}
+
+/// Evaluates the predicate on each block, in order, as the observer does when the blocks are
+/// appended to the chain
+pub fn evaluate_predicate_on_recorded_blocks(
+    predicate: &StacksChainhookSpecification,
+    blocks: &[StacksBlockData],
+    ctx: &Context,
+) -> Vec<PredicateOccurrence> {
+    let mut occurrences = vec![];
+    for block in blocks {
+        let (apply, _, _) = evaluate_stacks_chainhook_on_blocks(vec![block], predicate, ctx);
+        let Some((transactions, _)) = apply.first() else {
+            continue;
+        };
+        let transactions = transactions
+            .iter()
+            .map(|tx| tx.transaction_identifier.hash.clone())
+            .collect();
+        let trigger = StacksTriggerChainhook {
+            chainhook: predicate,
+            apply,
+            rollback: vec![],
+        };
+        occurrences.push(PredicateOccurrence {
+            block_identifier: block.block_identifier.clone(),
+            transactions,
+            payload: serialize_stacks_payload_to_json(trigger, &HashMap::new(), ctx),
+        });
+    }
+    occurrences
+}
+
+/// Share of the transactions above which a predicate is reported as too broad
+pub const BROAD_PREDICATE_MATCH_RATE: f64 = 0.5;
+
+/// Load of a predicate over a block stream: the time spent evaluating it, and how much of the
+/// stream it matches
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PredicateLoadReport {
+    pub uuid: String,
+    pub evaluation_time_us: u64,
+    pub blocks_matched: usize,
+    pub blocks_count: usize,
+    pub transactions_matched: usize,
+    pub transactions_count: usize,
+}
+
+impl PredicateLoadReport {
+    pub fn get_match_rate(&self) -> f64 {
+        if self.transactions_count == 0 {
+            return 0.0;
+        }
+        self.transactions_matched as f64 / self.transactions_count as f64
+    }
+
+    pub fn get_average_block_time_us(&self) -> f64 {
+        if self.blocks_count == 0 {
+            return 0.0;
+        }
+        self.evaluation_time_us as f64 / self.blocks_count as f64
+    }
+
+    /// A predicate matching most of the transactions triggers on almost every block of a busy
+    /// network, it usually lacks a filter
+    pub fn is_too_broad(&self) -> bool {
+        self.transactions_matched > 0 && self.get_match_rate() > BROAD_PREDICATE_MATCH_RATE
+    }
+}
+
+/// Evaluates each predicate on the blocks, in order, without building the payloads. Nothing is
+/// sent, the reports only measure the evaluation.
+pub fn simulate_predicates_load(
+    predicates: &[StacksChainhookSpecification],
+    blocks: &[StacksBlockData],
+    ctx: &Context,
+) -> Vec<PredicateLoadReport> {
+    let transactions_count = blocks.iter().map(|block| block.transactions.len()).sum();
+    predicates
+        .iter()
+        .map(|predicate| {
+            let mut report = PredicateLoadReport {
+                uuid: predicate.uuid.clone(),
+                evaluation_time_us: 0,
+                blocks_matched: 0,
+                blocks_count: blocks.len(),
+                transactions_matched: 0,
+                transactions_count,
+            };
+            for block in blocks {
+                let start = Instant::now();
+                let (apply, _, _) =
+                    evaluate_stacks_chainhook_on_blocks(vec![block], predicate, ctx);
+                report.evaluation_time_us += start.elapsed().as_micros() as u64;
+                if let Some((transactions, _)) = apply.first() {
+                    report.blocks_matched += 1;
+                    report.transactions_matched += transactions.len();
+                }
+            }
+            report
+        })
+        .collect()
+}
+
+pub const RECORDED_BLOCKS_FILE: &str = "recorded-blocks.jsonl";
+
+/// Appends the mined blocks to `recorded-blocks.jsonl` (`record_blocks = true`), in the
+/// working directory of the devnet
+#[derive(Debug)]
+pub struct BlocksRecorder {
+    file: File,
+}
+
+impl BlocksRecorder {
+    pub fn new(working_dir: &FileLocation) -> Result<Self, String> {
+        let mut location = working_dir.clone();
+        location.append_path(RECORDED_BLOCKS_FILE)?;
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(location.to_string())
+            .map_err(|e| format!("unable to open {}: {}", location, e))?;
+        Ok(Self { file })
+    }
+
+    pub fn record_block(&mut self, block: &StacksBlockData) -> Result<(), String> {
+        let line = serde_json::to_string(block).map_err(|e| e.to_string())?;
+        writeln!(self.file, "{}", line).map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_recorded_blocks_errors() {
+        let path =
+            std::env::temp_dir().join(format!("recorded-blocks-{}.jsonl", std::process::id()));
+        std::fs::write(&path, "\n{\"apply\": []}\n{\"block_identifier\": 1}\n").unwrap();
+        let error = read_recorded_blocks(&path).unwrap_err();
+        std::fs::remove_file(&path).unwrap();
+        assert!(error.starts_with(&format!("{}:3: invalid block", path.display())));
+    }
+
+    #[test]
+    fn test_predicate_load_report() {
+        let mut report = PredicateLoadReport {
+            uuid: "transfers".to_string(),
+            evaluation_time_us: 1200,
+            blocks_matched: 3,
+            blocks_count: 4,
+            transactions_matched: 6,
+            transactions_count: 10,
+        };
+        assert_eq!(report.get_match_rate(), 0.6);
+        assert_eq!(report.get_average_block_time_us(), 300.0);
+        assert!(report.is_too_broad());
+
+        report.transactions_matched = 5;
+        assert!(!report.is_too_broad());
+
+        let empty = simulate_predicates_load(&[], &[], &Context::empty());
+        assert!(empty.is_empty());
+        report.transactions_count = 0;
+        assert_eq!(report.get_match_rate(), 0.0);
+    }
+}
@@ -1,9 +1,27 @@
 use super::ChainsCoordinatorCommand;
+use crate::accounts::AccountsMonitor;
+use crate::api_health::{get_stacks_api_tip, ApiHealthChange, StacksApiHealth};
+use crate::boot::{BootPhase, BootTracker};
+use crate::chainhooks::BlocksRecorder;
+use crate::contracts_server::{ContractsIndex, ContractsServer};
+use crate::coordinator_state::{
+    get_epoch_at_burn_height, get_epochs_schedule, ChainsCoordinatorHandle,
+};
+use crate::deployment_progress::{
+    get_deployed_contracts, get_remaining_deployment, DeploymentProgress,
+};
 
+use crate::drift::{get_balance_drifts, AccountDrift, FeesTracker, DRIFT_REPORT_FILE};
 use crate::event::send_status_update;
 use crate::event::DevnetEvent;
 use crate::event::Status;
+use crate::event::{DeployingStatus, ProtocolDeployingData};
 use crate::orchestrator::ServicesMapHosts;
+use crate::pox::get_pox_summary;
+use crate::recorder::ContractCallRecorder;
+use crate::signers::SignersMonitor;
+use crate::soak::SoakMonitor;
+use crate::webhooks::WebhookReceiver;
 
 use base58::FromBase58;
 use bitcoincore_rpc::bitcoin::Address;
@@ -16,20 +34,24 @@ use chainhook_sdk::types::BitcoinBlockSignaling;
 use chainhook_sdk::types::BitcoinChainEvent;
 use chainhook_sdk::types::StacksChainEvent;
 use chainhook_sdk::types::StacksNodeConfig;
+use chainhook_sdk::types::StacksTransactionData;
 use chainhook_sdk::utils::Context;
-use clarinet_deployments::onchain::TransactionStatus;
 use clarinet_deployments::onchain::{
     apply_on_chain_deployment, DeploymentCommand, DeploymentEvent,
 };
+use clarinet_deployments::onchain::{TransactionStatus, TransactionTracker};
+use clarinet_deployments::source_metadata::get_plan_id;
 use clarinet_deployments::types::DeploymentSpecification;
 use clarinet_files::PoxStackingOrder;
 use clarinet_files::StacksNetwork;
 use clarinet_files::DEFAULT_FIRST_BURN_HEADER_HEIGHT;
-use clarinet_files::{self, AccountConfig, DevnetConfig, NetworkManifest, ProjectManifest};
+use clarinet_files::{
+    self, AccountConfig, DevnetConfig, FileLocation, NetworkManifest, ProjectManifest,
+};
 use clarity::address::AddressHashMode;
 use clarity::types::PublicKey;
 use clarity::util::hash::{hex_bytes, Hash160};
-use clarity::vm::types::{BuffData, SequenceData, TupleData};
+use clarity::vm::types::{BuffData, PrincipalData, SequenceData, TupleData};
 use clarity::vm::ClarityName;
 use clarity::vm::Value as ClarityValue;
 use hiro_system_kit;
@@ -44,6 +66,7 @@ use stackslib::types::chainstate::StacksPrivateKey;
 use stackslib::types::chainstate::StacksPublicKey;
 use stackslib::util_lib::signed_structured_data::pox4::make_pox_4_signer_key_signature;
 use stackslib::util_lib::signed_structured_data::pox4::Pox4SignatureTopic;
+use std::collections::{BTreeMap, BTreeSet};
 use std::convert::TryFrom;
 use std::str;
 use std::str::FromStr;
@@ -70,6 +93,8 @@ pub struct DevnetEventObserverConfig {
     pub deployment_fee_rate: u64,
     pub services_map_hosts: ServicesMapHosts,
     pub network_manifest: NetworkManifest,
+    pub boot_tracker: BootTracker,
+    pub coordinator_handle: ChainsCoordinatorHandle,
 }
 
 impl DevnetEventObserverConfig {
@@ -152,6 +177,8 @@ impl DevnetEventObserverConfig {
             deployment_fee_rate: network_manifest.network.deployment_fee_rate,
             services_map_hosts,
             network_manifest,
+            boot_tracker: BootTracker::default(),
+            coordinator_handle: ChainsCoordinatorHandle::default(),
         }
     }
 }
@@ -171,32 +198,63 @@ pub async fn start_chains_coordinator(
     let mut should_deploy_protocol = true; // Will change when `stacks-network` components becomes compatible with Testnet / Mainnet setups
     let boot_completed = Arc::new(AtomicBool::new(false));
     let mut current_burn_height = 0;
+    let coordinator_handle = config.coordinator_handle.clone();
+    coordinator_handle.set_services(&config.services_map_hosts);
+    let epochs_schedule = get_epochs_schedule(&config.devnet_config);
 
     let (deployment_commands_tx, deployments_command_rx) = channel();
     let (deployment_events_tx, deployment_events_rx) = channel();
+    let working_dir = FileLocation::from_path_string(&config.devnet_config.working_dir)?;
+
+    // A devnet restarted on the chain data of a previous run resumes the deployment of the plan
+    // from the progress recorded by the previous run, once the node is up.
+    let plan_id = get_plan_id(&config.deployment)?;
+    let resumed_progress = if config.devnet_config.bind_containers_volumes {
+        DeploymentProgress::load(&working_dir, &plan_id)
+    } else {
+        None
+    };
 
     // Set-up the background task in charge of serializing / signing / publishing the contracts.
     // This tasks can take several seconds to minutes, depending on the complexity of the project.
     // We start this process as soon as possible, as a background task.
     // This thread becomes dormant once the encoding is done, and proceed to the actual deployment once
     // the event DeploymentCommand::Start is received.
-    perform_protocol_deployment(
-        &config.network_manifest,
-        &config.deployment,
-        deployment_events_tx,
-        deployments_command_rx,
-        Some(config.consolidated_bitcoin_rpc_url()),
-        Some(config.consolidated_stacks_rpc_url()),
-    );
-
-    // Set-up the background task in charge of monitoring contracts deployments.
-    // This thread will be waiting and relaying events emitted by the thread above.
-    relay_devnet_protocol_deployment(
-        deployment_events_rx,
-        &devnet_event_tx,
-        Some(mining_command_tx.clone()),
-        &boot_completed,
-    );
+    let mut resumed_deployment = match resumed_progress {
+        Some(progress) => Some((
+            progress,
+            deployment_events_tx,
+            deployments_command_rx,
+            deployment_events_rx,
+        )),
+        None => {
+            perform_protocol_deployment(
+                &config.network_manifest,
+                &config.deployment,
+                deployment_events_tx,
+                deployments_command_rx,
+                Some(config.consolidated_bitcoin_rpc_url()),
+                Some(config.consolidated_stacks_rpc_url()),
+                false,
+            );
+
+            // Set-up the background task in charge of monitoring contracts deployments.
+            // This thread will be waiting and relaying events emitted by the thread above.
+            relay_devnet_protocol_deployment(
+                deployment_events_rx,
+                &devnet_event_tx,
+                Some(mining_command_tx.clone()),
+                &boot_completed,
+                &config.boot_tracker,
+                record_deployment_progress(
+                    DeploymentProgress::new(plan_id),
+                    &working_dir,
+                    &devnet_event_tx,
+                ),
+            );
+            None
+        }
+    };
 
     let chainhooks_count = config
         .event_observer_config
@@ -254,6 +312,78 @@ pub async fn start_chains_coordinator(
 
     let enable_subnet_node = config.devnet_config.enable_subnet_node;
     let stacks_signers_keys = config.devnet_config.stacks_signers_keys.clone();
+    let mut stacking_orders_tracker = StackingOrdersTracker::default();
+    let mut signers_monitor =
+        SignersMonitor::new(&config.consolidated_stacks_rpc_url(), &stacks_signers_keys);
+    let mut accounts_monitor =
+        AccountsMonitor::new(&config.consolidated_stacks_rpc_url(), &config.accounts);
+    let mut contract_call_recorder = config
+        .devnet_config
+        .record_contract_calls
+        .then(|| ContractCallRecorder::new(&config.deployment));
+    let mut blocks_recorder = if config.devnet_config.record_blocks {
+        Some(BlocksRecorder::new(&working_dir)?)
+    } else {
+        None
+    };
+    let webhook_receiver = if config.devnet_config.enable_webhook_receiver {
+        match WebhookReceiver::start(
+            config.devnet_config.webhook_receiver_port,
+            devnet_event_tx.clone(),
+        ) {
+            Ok(receiver) => Some(receiver),
+            Err(e) => {
+                let _ = devnet_event_tx.send(DevnetEvent::FatalError(e.clone()));
+                return Err(e);
+            }
+        }
+    } else {
+        None
+    };
+    // not being able to serve the contracts doesn't prevent the devnet from running
+    let contracts_index = ContractsIndex::new(
+        &config.manifest,
+        &config.deployment,
+        format!(
+            "http://localhost:{}",
+            config.devnet_config.stacks_node_rpc_port
+        ),
+        (!config.devnet_config.disable_stacks_api)
+            .then(|| format!("http://localhost:{}", config.devnet_config.stacks_api_port)),
+    );
+    let contracts_server = match ContractsServer::start(
+        config.devnet_config.orchestrator_control_port,
+        contracts_index,
+        config.consolidated_stacks_rpc_url(),
+        coordinator_handle.clone(),
+        devnet_event_tx.clone(),
+    ) {
+        Ok(server) => Some(server),
+        Err(e) => {
+            let _ = devnet_event_tx.send(DevnetEvent::warning(e));
+            None
+        }
+    };
+    let mut fees_tracker = FeesTracker::default();
+    let mut stacks_api_health = StacksApiHealth::new(config.devnet_config.stacks_api_max_lag);
+    let mut balance_drifts_reported = config.deployment.plan.batches.is_empty();
+    let mut soak_monitor = match config.devnet_config.soak {
+        Some(ref soak) => match SoakMonitor::new(
+            soak,
+            &config.manifest,
+            &config.accounts,
+            config.deployment.contracts.keys().cloned().collect(),
+            &config.consolidated_stacks_rpc_url(),
+        ) {
+            Ok(monitor) => Some(monitor),
+            Err(e) => {
+                let message = format!("unable to start the soak mode: {}", e);
+                let _ = devnet_event_tx.send(DevnetEvent::FatalError(message.clone()));
+                return Err(message);
+            }
+        },
+        None => None,
+    };
 
     loop {
         let oper = sel.select();
@@ -261,11 +391,39 @@ pub async fn start_chains_coordinator(
             i if i == chains_coordinator_commands_oper => {
                 match oper.recv(&chains_coordinator_commands_rx) {
                     Ok(ChainsCoordinatorCommand::Terminate) => {
+                        if let Some(ref receiver) = webhook_receiver {
+                            receiver.stop();
+                        }
+                        if let Some(ref server) = contracts_server {
+                            server.stop();
+                        }
                         let _ = orchestrator_terminator_tx.send(true);
                         let _ = observer_command_tx.send(ObserverCommand::Terminate);
                         let _ = mining_command_tx.send(BitcoinMiningCommand::Pause);
                         break;
                     }
+                    Ok(ChainsCoordinatorCommand::GetSignersStatus(response_tx)) => {
+                        let _ = response_tx.send(signers_monitor.signers.clone());
+                        continue;
+                    }
+                    Ok(ChainsCoordinatorCommand::GetPoxSummary(response_tx)) => {
+                        let summary = get_pox_summary(
+                            &config.consolidated_stacks_rpc_url(),
+                            &config.accounts,
+                            &stacks_signers_keys,
+                        )
+                        .await;
+                        let _ = response_tx.send(summary);
+                        continue;
+                    }
+                    Ok(ChainsCoordinatorCommand::GetWebhookDeliveries(response_tx)) => {
+                        let deliveries = webhook_receiver
+                            .as_ref()
+                            .map(|receiver| receiver.get_deliveries())
+                            .unwrap_or_default();
+                        let _ = response_tx.send(deliveries);
+                        continue;
+                    }
                     Err(_e) => {
                         continue;
                     }
@@ -325,11 +483,12 @@ pub async fn start_chains_coordinator(
                                 &config.services_map_hosts,
                                 config.deployment_fee_rate,
                                 bitcoin_block_height as u32,
+                                &mut stacking_orders_tracker,
                             )
                             .await;
                             if let Some(tx_count) = res {
                                 let _ = devnet_event_tx.send(DevnetEvent::success(format!(
-                                    "Broadcasted {} stacking orders",
+                                    "Broadcasted {} stacking transactions",
                                     tx_count
                                 )));
                             }
@@ -353,6 +512,10 @@ pub async fn start_chains_coordinator(
                     }
                 };
 
+                coordinator_handle.set_bitcoin_tip(
+                    current_burn_height,
+                    get_epoch_at_burn_height(&epochs_schedule, current_burn_height),
+                );
                 let _ = devnet_event_tx.send(DevnetEvent::debug(log));
 
                 send_status_update(
@@ -364,8 +527,74 @@ pub async fn start_chains_coordinator(
                     &comment,
                 );
                 let _ = devnet_event_tx.send(DevnetEvent::BitcoinChainEvent(chain_update.clone()));
+                if current_burn_height >= config.devnet_config.epoch_3_0 {
+                    config.boot_tracker.exit(BootPhase::Epoch3Reached);
+                }
             }
             ObserverEvent::StacksChainEvent((chain_event, _)) => {
+                if let Some((
+                    progress,
+                    deployment_events_tx,
+                    deployments_command_rx,
+                    deployment_events_rx,
+                )) = resumed_deployment.take()
+                {
+                    // the chain of the previous run is back: its block #1 is behind
+                    let resumed = chain_event
+                        .get_latest_block_identifier()
+                        .is_some_and(|block_identifier| block_identifier.index > 1);
+                    let (deployment, progress) = if resumed {
+                        let deployed = get_deployed_contracts(
+                            &config.consolidated_stacks_rpc_url(),
+                            &config.deployment,
+                        )
+                        .await
+                        .unwrap_or_else(|e| {
+                            let _ = devnet_event_tx.send(DevnetEvent::warning(format!(
+                                "unable to list the deployed contracts: {}",
+                                e
+                            )));
+                            BTreeSet::new()
+                        });
+                        let _ = devnet_event_tx.send(DevnetEvent::info(format!(
+                            "Resuming the deployment: {} contracts already deployed, {} transactions confirmed before the restart",
+                            deployed.len(),
+                            progress.confirmed_count()
+                        )));
+                        let remaining =
+                            get_remaining_deployment(&config.deployment, &progress, &deployed);
+                        (remaining, progress)
+                    } else {
+                        let progress = DeploymentProgress::new(progress.plan_id);
+                        (config.deployment.clone(), progress)
+                    };
+                    perform_protocol_deployment(
+                        &config.network_manifest,
+                        &deployment,
+                        deployment_events_tx,
+                        deployments_command_rx,
+                        Some(config.consolidated_bitcoin_rpc_url()),
+                        Some(config.consolidated_stacks_rpc_url()),
+                        resumed,
+                    );
+                    relay_devnet_protocol_deployment(
+                        deployment_events_rx,
+                        &devnet_event_tx,
+                        Some(mining_command_tx.clone()),
+                        &boot_completed,
+                        &config.boot_tracker,
+                        record_deployment_progress(progress, &working_dir, &devnet_event_tx),
+                    );
+                    if resumed {
+                        should_deploy_protocol = false;
+                        if let Some(deployment_commands_tx) = deployment_commands_tx.take() {
+                            deployment_commands_tx
+                                .send(DeploymentCommand::Start)
+                                .map_err(|e| format!("unable to start deployment: {}", e))
+                                .unwrap();
+                        }
+                    }
+                }
                 if should_deploy_protocol {
                     if let Some(block_identifier) = chain_event.get_latest_block_identifier() {
                         if block_identifier.index == 1 {
@@ -410,6 +639,53 @@ pub async fn start_chains_coordinator(
                     }
                 };
 
+                coordinator_handle.set_stacks_tip(
+                    known_tip.block.block_identifier.index,
+                    known_tip.block.metadata.pox_cycle_index,
+                    boot_completed.load(Ordering::SeqCst),
+                );
+
+                let boot_tracker = &config.boot_tracker;
+                if boot_tracker.is_running(BootPhase::StacksGenesis) {
+                    boot_tracker.exit(BootPhase::StacksGenesis);
+                    if current_burn_height < config.devnet_config.epoch_3_0 {
+                        boot_tracker.enter(BootPhase::Epoch3Reached);
+                    }
+                    if !config.devnet_config.disable_stacks_api {
+                        boot_tracker.enter(BootPhase::ApiSynced);
+                    }
+                }
+                if boot_tracker.is_running(BootPhase::ApiSynced)
+                    && is_stacks_api_synced(
+                        &config.services_map_hosts.stacks_api_host,
+                        known_tip.block.block_identifier.index,
+                    )
+                    .await
+                {
+                    boot_tracker.exit(BootPhase::ApiSynced);
+                }
+
+                if let StacksChainEvent::ChainUpdatedWithBlocks(update) = &chain_event {
+                    for block_update in update.new_blocks.iter() {
+                        stacking_orders_tracker.process_block_transactions(
+                            &block_update.block.transactions,
+                            &devnet_event_tx,
+                        );
+                        if let Some(recorder) = contract_call_recorder.as_mut() {
+                            recorder.record_block(&block_update.block);
+                        }
+                        if let Some(recorder) = blocks_recorder.as_mut() {
+                            if let Err(e) = recorder.record_block(&block_update.block) {
+                                let _ = devnet_event_tx.send(DevnetEvent::warning(format!(
+                                    "unable to record block: {}",
+                                    e
+                                )));
+                            }
+                        }
+                        fees_tracker.record_block(&block_update.block);
+                    }
+                }
+
                 let _ = devnet_event_tx.send(DevnetEvent::StacksChainEvent(chain_event));
 
                 // Partially update the UI. With current approach a full update
@@ -451,6 +727,104 @@ pub async fn start_chains_coordinator(
                     )
                 };
                 let _ = devnet_event_tx.send(DevnetEvent::info(message));
+
+                // signers only start signing blocks in epoch 3.0
+                if !stacks_signers_keys.is_empty()
+                    && current_burn_height >= config.devnet_config.epoch_3_0
+                {
+                    match signers_monitor
+                        .refresh(known_tip.block.block_identifier.index)
+                        .await
+                    {
+                        Ok(()) => {
+                            let _ = devnet_event_tx
+                                .send(DevnetEvent::SignersStatus(signers_monitor.signers.clone()));
+                        }
+                        Err(e) => {
+                            let _ = devnet_event_tx.send(DevnetEvent::debug(format!(
+                                "unable to refresh signers status: {}",
+                                e
+                            )));
+                        }
+                    }
+                }
+
+                match accounts_monitor.refresh().await {
+                    Ok(warnings) => {
+                        let _ = devnet_event_tx.send(DevnetEvent::AccountsStatus(
+                            accounts_monitor.accounts.clone(),
+                        ));
+                        for warning in warnings {
+                            let _ = devnet_event_tx.send(DevnetEvent::warning(warning));
+                        }
+                    }
+                    Err(e) => {
+                        let _ = devnet_event_tx.send(DevnetEvent::debug(format!(
+                            "unable to refresh accounts status: {}",
+                            e
+                        )));
+                    }
+                }
+
+                // the API is only expected to keep up once synced with the node
+                if !config.devnet_config.disable_stacks_api && boot_completed.load(Ordering::SeqCst)
+                {
+                    let node_height = known_tip.block.block_identifier.index;
+                    match get_stacks_api_tip(&config.services_map_hosts.stacks_api_host).await {
+                        Ok(api_height) => {
+                            let change = stacks_api_health.record(node_height, api_height);
+                            report_stacks_api_health(
+                                &stacks_api_health,
+                                change,
+                                enable_subnet_node,
+                                &devnet_event_tx,
+                            );
+                        }
+                        Err(e) => {
+                            let _ = devnet_event_tx.send(DevnetEvent::debug(format!(
+                                "unable to get the stacks-api status: {}",
+                                e
+                            )));
+                        }
+                    }
+                }
+
+                if !balance_drifts_reported && boot_completed.load(Ordering::SeqCst) {
+                    balance_drifts_reported = true;
+                    let result = get_balance_drifts(
+                        &config.deployment,
+                        &config.accounts,
+                        &fees_tracker,
+                        &config.consolidated_stacks_rpc_url(),
+                    )
+                    .await;
+                    report_balance_drifts(result, &working_dir, &devnet_event_tx);
+                }
+
+                // the invariants are checked once the project is deployed
+                if let Some(monitor) = soak_monitor.as_mut() {
+                    if boot_completed.load(Ordering::SeqCst)
+                        && monitor
+                            .on_block(
+                                known_tip.block.block_identifier.index,
+                                &working_dir,
+                                &devnet_event_tx,
+                            )
+                            .await
+                    {
+                        if let Some(ref receiver) = webhook_receiver {
+                            receiver.stop();
+                        }
+                        if let Some(ref server) = contracts_server {
+                            server.stop();
+                        }
+                        let _ = orchestrator_terminator_tx.send(true);
+                        let _ = observer_command_tx.send(ObserverCommand::Terminate);
+                        let _ = mining_command_tx.send(BitcoinMiningCommand::Pause);
+                        let _ = devnet_event_tx.send(DevnetEvent::Terminate);
+                        break;
+                    }
+                }
             }
             ObserverEvent::NotifyBitcoinTransactionProxied => {
                 if !boot_completed.load(Ordering::SeqCst) {
@@ -511,6 +885,7 @@ pub async fn start_chains_coordinator(
                         }
                     }
                     for tx in transactions.into_iter() {
+                        accounts_monitor.record_mempool_admission(&tx.tx_data);
                         let _ = devnet_event_tx.send(DevnetEvent::MempoolAdmission(tx));
                     }
                 }
@@ -521,9 +896,128 @@ pub async fn start_chains_coordinator(
             ObserverEvent::PredicateEnabled(_) => {}
         }
     }
+
+    if let Some(recorder) = contract_call_recorder {
+        write_contract_call_recordings(&recorder, &config.manifest, &devnet_event_tx);
+    }
     Ok(())
 }
 
+fn write_contract_call_recordings(
+    recorder: &ContractCallRecorder,
+    manifest: &ProjectManifest,
+    devnet_event_tx: &Sender<DevnetEvent>,
+) {
+    if recorder.calls.is_empty() {
+        return;
+    }
+    let result = manifest
+        .location
+        .get_project_root_location()
+        .and_then(|project_root| recorder.write_recordings(&project_root, "devnet-calls"));
+    let event = match result {
+        Ok(locations) => DevnetEvent::success(format!(
+            "{} contract calls recorded in {}",
+            recorder.calls.len(),
+            locations
+                .iter()
+                .map(|location| location.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )),
+        Err(e) => DevnetEvent::error(format!("unable to write recorded contract calls: {}", e)),
+    };
+    let _ = devnet_event_tx.send(event);
+}
+
+fn report_stacks_api_health(
+    health: &StacksApiHealth,
+    change: Option<ApiHealthChange>,
+    enable_subnet_node: bool,
+    devnet_event_tx: &Sender<DevnetEvent>,
+) {
+    let api_height = health
+        .api_height
+        .map(|height| format!("#{}", height))
+        .unwrap_or("no block".to_string());
+    match change {
+        Some(ApiHealthChange::Lagging(lag)) => {
+            let _ = devnet_event_tx.send(DevnetEvent::warning(format!(
+                "stacks-api is {} blocks behind the stacks-node ({} indexed, chain_tip = #{}), the queries of the tests may slow its ingestion down",
+                lag, api_height, health.node_height
+            )));
+        }
+        Some(ApiHealthChange::Recovered) => {
+            let _ = devnet_event_tx.send(DevnetEvent::info(
+                "stacks-api caught up with the stacks-node".to_string(),
+            ));
+        }
+        None => {}
+    }
+    let lag = health.get_lag();
+    let (status, comment) = if lag > health.max_lag {
+        (
+            Status::Yellow,
+            format!("{} blocks behind (chain_tip = {})", lag, api_height),
+        )
+    } else {
+        (
+            Status::Green,
+            format!("synced (chain_tip = {})", api_height),
+        )
+    };
+    send_status_update(
+        devnet_event_tx,
+        enable_subnet_node,
+        &None,
+        "stacks-api",
+        status,
+        &comment,
+    );
+}
+
+fn report_balance_drifts(
+    result: Result<Vec<AccountDrift>, String>,
+    working_dir: &FileLocation,
+    devnet_event_tx: &Sender<DevnetEvent>,
+) {
+    let drifts = match result {
+        Ok(drifts) => drifts,
+        Err(e) => {
+            let _ = devnet_event_tx.send(DevnetEvent::warning(format!(
+                "unable to compare the balances with the deployment plan: {}",
+                e
+            )));
+            return;
+        }
+    };
+    let mut location = working_dir.clone();
+    let written = location.append_path(DRIFT_REPORT_FILE).and_then(|_| {
+        let content = serde_json::to_vec_pretty(&drifts).map_err(|e| e.to_string())?;
+        location.write_content(&content)
+    });
+    if let Err(e) = written {
+        let _ = devnet_event_tx.send(DevnetEvent::debug(format!(
+            "unable to write {}: {}",
+            DRIFT_REPORT_FILE, e
+        )));
+    }
+
+    let drained: Vec<&AccountDrift> = drifts.iter().filter(|drift| drift.is_drained()).collect();
+    for drift in drained.iter() {
+        let _ = devnet_event_tx.send(DevnetEvent::warning(format!(
+            "{} ({}) holds {} µSTX, {} µSTX less than expected from the genesis and the deployment plan",
+            drift.label, drift.address, drift.actual_balance, -drift.unexplained_drift
+        )));
+    }
+    if drained.is_empty() {
+        let _ = devnet_event_tx.send(DevnetEvent::success(format!(
+            "Balances of the {} accounts match the deployment plan",
+            drifts.len()
+        )));
+    }
+}
+
 pub fn perform_protocol_deployment(
     network_manifest: &NetworkManifest,
     deployment: &DeploymentSpecification,
@@ -531,6 +1025,7 @@ pub fn perform_protocol_deployment(
     deployment_command_rx: Receiver<DeploymentCommand>,
     override_bitcoin_rpc_url: Option<String>,
     override_stacks_rpc_url: Option<String>,
+    fetch_initial_nonces: bool,
 ) {
     let deployment = deployment.clone();
     let network_manifest = network_manifest.clone();
@@ -540,9 +1035,10 @@ pub fn perform_protocol_deployment(
             deployment,
             deployment_event_tx,
             deployment_command_rx,
-            false,
+            fetch_initial_nonces,
             override_bitcoin_rpc_url,
             override_stacks_rpc_url,
+            None,
         );
     });
 }
@@ -552,10 +1048,14 @@ pub fn relay_devnet_protocol_deployment(
     devnet_event_tx: &Sender<DevnetEvent>,
     bitcoin_mining_tx: Option<Sender<BitcoinMiningCommand>>,
     boot_completed: &Arc<AtomicBool>,
+    boot_tracker: &BootTracker,
+    mut deployment_progress: Option<(DeploymentProgress, FileLocation)>,
 ) {
     let devnet_event_tx = devnet_event_tx.clone();
     let boot_completed = boot_completed.clone();
+    let boot_tracker = boot_tracker.clone();
     let _ = hiro_system_kit::thread_named("Deployment monitoring").spawn(move || {
+        let mut statuses = BTreeMap::new();
         loop {
             let event = match deployment_events_rx.recv() {
                 Ok(event) => event,
@@ -563,19 +1063,59 @@ pub fn relay_devnet_protocol_deployment(
             };
             match event {
                 DeploymentEvent::TransactionUpdate(tracker) => {
-                    if let TransactionStatus::Error(ref message) = tracker.status {
-                        let _ = devnet_event_tx.send(DevnetEvent::error(message.into()));
+                    if let (TransactionStatus::Confirmed, Some((progress, working_dir))) =
+                        (&tracker.status, deployment_progress.as_mut())
+                    {
+                        progress.record_confirmed(&tracker.name);
+                        if let Err(e) = progress.save(working_dir) {
+                            let _ = devnet_event_tx.send(DevnetEvent::debug(format!(
+                                "unable to record the deployment progress: {}",
+                                e
+                            )));
+                        }
+                    }
+                    let progress = get_deploying_progress(&tracker, &mut statuses);
+                    let error = progress.error.clone();
+                    let _ = devnet_event_tx.send(DevnetEvent::ProtocolDeployingProgress(progress));
+                    // fail the boot instead of waiting for a deployment that won't complete
+                    if let Some(message) = error {
+                        let _ = devnet_event_tx.send(DevnetEvent::FatalError(format!(
+                            "deployment failed: {}",
+                            message
+                        )));
                         break;
                     }
                 }
-                DeploymentEvent::Interrupted(_) => {
-                    // Terminate
+                DeploymentEvent::PostCheckCompleted(result) => {
+                    let message = format!("post check {}: {}", result.check, result.message);
+                    let _ = devnet_event_tx.send(if result.passed {
+                        DevnetEvent::info(message)
+                    } else {
+                        DevnetEvent::warning(message)
+                    });
+                }
+                DeploymentEvent::WaitingForEpoch(wait) => {
+                    let _ = devnet_event_tx.send(DevnetEvent::info(format!(
+                        "batch {} waiting for epoch {} (bitcoin block #{}/{})",
+                        wait.batch_id,
+                        wait.epoch,
+                        wait.bitcoin_block_height,
+                        wait.activation_height
+                    )));
+                }
+                DeploymentEvent::Interrupted(message) => {
+                    let _ = devnet_event_tx.send(DevnetEvent::FatalError(format!(
+                        "deployment interrupted: {}",
+                        message
+                    )));
                     break;
                 }
                 DeploymentEvent::DeploymentCompleted => {
                     boot_completed.store(true, Ordering::SeqCst);
+                    let boot_report = boot_tracker.set_ready();
                     if let Some(bitcoin_mining_tx) = bitcoin_mining_tx {
-                        let _ = devnet_event_tx.send(DevnetEvent::BootCompleted(bitcoin_mining_tx));
+                        let _ = devnet_event_tx
+                            .send(DevnetEvent::BootCompleted(bitcoin_mining_tx, boot_report));
                     }
                     break;
                 }
@@ -584,6 +1124,52 @@ pub fn relay_devnet_protocol_deployment(
     });
 }
 
+/// Saves the progress of the deployment, to be updated by the relay as the transactions are
+/// confirmed
+fn record_deployment_progress(
+    progress: DeploymentProgress,
+    working_dir: &FileLocation,
+    devnet_event_tx: &Sender<DevnetEvent>,
+) -> Option<(DeploymentProgress, FileLocation)> {
+    if let Err(e) = progress.save(working_dir) {
+        let _ = devnet_event_tx.send(DevnetEvent::debug(format!(
+            "unable to record the deployment progress: {}",
+            e
+        )));
+    }
+    Some((progress, working_dir.clone()))
+}
+
+/// Records the new status of a transaction, `statuses` being keyed by transaction index
+fn get_deploying_progress(
+    tracker: &TransactionTracker,
+    statuses: &mut BTreeMap<usize, DeployingStatus>,
+) -> ProtocolDeployingData {
+    let (status, txid, error) = match &tracker.status {
+        TransactionStatus::Queued | TransactionStatus::Encoded(..) => {
+            (DeployingStatus::Encoded, None, None)
+        }
+        TransactionStatus::Broadcasted(_, txid) => {
+            (DeployingStatus::Broadcasted, Some(txid.clone()), None)
+        }
+        TransactionStatus::Confirmed => (DeployingStatus::Confirmed, None, None),
+        TransactionStatus::Error(message) => (DeployingStatus::Failed, None, Some(message.clone())),
+    };
+    statuses.insert(tracker.index, status.clone());
+    ProtocolDeployingData {
+        index: tracker.index,
+        name: tracker.name.clone(),
+        status,
+        txid,
+        error,
+        confirmed: statuses
+            .values()
+            .filter(|status| **status == DeployingStatus::Confirmed)
+            .count(),
+        total: statuses.len(),
+    }
+}
+
 fn should_publish_stacking_orders(
     current_cycle: &u32,
     pox_stacking_order: &PoxStackingOrder,
@@ -621,9 +1207,38 @@ mod tests {
             slots: 1,
             btc_address: "address_1".to_string(),
             auto_extend: Some(true),
+            pool_operator: None,
         }
     }
 
+    #[test]
+    fn test_get_deploying_progress() {
+        let tracker = |index: usize, status: TransactionStatus| TransactionTracker {
+            index,
+            name: format!(
+                "Publish ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM.contract-{}",
+                index
+            ),
+            status,
+        };
+        let mut statuses = BTreeMap::new();
+        get_deploying_progress(&tracker(0, TransactionStatus::Queued), &mut statuses);
+        get_deploying_progress(&tracker(1, TransactionStatus::Queued), &mut statuses);
+
+        let progress =
+            get_deploying_progress(&tracker(0, TransactionStatus::Confirmed), &mut statuses);
+        assert_eq!(progress.status, DeployingStatus::Confirmed);
+        assert_eq!((progress.confirmed, progress.total), (1, 2));
+
+        let progress = get_deploying_progress(
+            &tracker(1, TransactionStatus::Error("BadNonce".to_string())),
+            &mut statuses,
+        );
+        assert_eq!(progress.status, DeployingStatus::Failed);
+        assert_eq!(progress.error.as_deref(), Some("BadNonce"));
+        assert_eq!((progress.confirmed, progress.total), (1, 2));
+    }
+
     #[test]
     fn test_should_publish_stacking_orders_basic() {
         let pox_stacking_order = build_pox_stacking_order(12, 6);
@@ -665,6 +1280,57 @@ mod tests {
     }
 }
 
+/// Position in the reward cycle where the stacking orders are published. Delegated
+/// stacking orders are stacked by the pool operator at the next block.
+const STACKING_ORDERS_CYCLE_POSITION: u32 = 10;
+
+/// State of the stacking orders across reward cycles
+#[derive(Debug, Default)]
+pub struct StackingOrdersTracker {
+    /// Orders stacked at least once, that can be extended
+    published_orders: BTreeSet<usize>,
+    /// Orders which start cycle passed before they could be published
+    expired_orders: BTreeSet<usize>,
+    /// Stacking transactions waiting for confirmation, indexed by txid
+    pending_transactions: BTreeMap<String, String>,
+}
+
+impl StackingOrdersTracker {
+    fn normalize_txid(txid: &str) -> String {
+        txid.trim_start_matches("0x").to_lowercase()
+    }
+
+    /// Emits the success or failure of the stacking transactions included in a block
+    pub fn process_block_transactions(
+        &mut self,
+        transactions: &[StacksTransactionData],
+        devnet_event_tx: &Sender<DevnetEvent>,
+    ) {
+        if self.pending_transactions.is_empty() {
+            return;
+        }
+        for tx in transactions.iter() {
+            let txid = Self::normalize_txid(&tx.transaction_identifier.hash);
+            let Some(description) = self.pending_transactions.remove(&txid) else {
+                continue;
+            };
+            let event = if tx.metadata.success {
+                DevnetEvent::success(format!("{} confirmed", description))
+            } else {
+                DevnetEvent::error(format!("{} failed: {}", description, tx.metadata.result))
+            };
+            let _ = devnet_event_tx.send(event);
+        }
+    }
+}
+
+struct StackingTransaction {
+    description: String,
+    account: AccountConfig,
+    method: String,
+    arguments: Vec<ClarityValue>,
+}
+
 pub async fn publish_stacking_orders(
     devnet_config: &DevnetConfig,
     devnet_event_tx: &Sender<DevnetEvent>,
@@ -672,6 +1338,7 @@ pub async fn publish_stacking_orders(
     services_map_hosts: &ServicesMapHosts,
     fee_rate: u64,
     bitcoin_block_height: u32,
+    tracker: &mut StackingOrdersTracker,
 ) -> Option<usize> {
     let node_rpc_url = format!("http://{}", &services_map_hosts.stacks_node_host);
     let pox_info: PoxInfo = match reqwest::get(format!("{}/v2/pox", node_rpc_url)).await {
@@ -701,9 +1368,13 @@ pub async fn publish_stacking_orders(
     let pox_cycle_length = pox_info.reward_cycle_length;
     let pox_cycle_position = effective_height % pox_cycle_length;
 
-    if pox_cycle_position != 10 {
+    let is_pool_operators_turn = if pox_cycle_position == STACKING_ORDERS_CYCLE_POSITION {
+        false
+    } else if pox_cycle_position == STACKING_ORDERS_CYCLE_POSITION + 1 {
+        true
+    } else {
         return None;
-    }
+    };
 
     let pox_contract_id = pox_info.contract_id;
     let pox_version = pox_contract_id
@@ -712,8 +1383,23 @@ pub async fn publish_stacking_orders(
         .and_then(|version| version.parse().ok())
         .unwrap_or(1); // pox 1 contract is `pox.clar`
 
-    let mut transactions = 0;
+    let mut transactions = vec![];
+    let mut aggregation_commits = BTreeSet::new();
     for (i, pox_stacking_order) in devnet_config.pox_stacking_orders.iter().enumerate() {
+        let is_published = tracker.published_orders.contains(&i);
+        if !is_published && current_cycle >= pox_stacking_order.start_at_cycle {
+            if tracker.expired_orders.insert(i) {
+                let _ = devnet_event_tx.send(DevnetEvent::error(format!(
+                    "Stacking order {} ({}) was set to start at cycle {}, but cycle {} has already started",
+                    i + 1,
+                    pox_stacking_order.wallet,
+                    pox_stacking_order.start_at_cycle,
+                    current_cycle
+                )));
+            }
+            continue;
+        }
+
         if !should_publish_stacking_orders(&current_cycle, pox_stacking_order) {
             continue;
         }
@@ -733,81 +1419,193 @@ pub async fn publish_stacking_orders(
             _ => continue,
         };
 
-        transactions += 1;
-
         let stx_amount = pox_info.next_cycle.min_threshold_ustx * pox_stacking_order.slots;
-
-        let node_rpc_url_moved = node_rpc_url.clone();
-        let pox_contract_id_moved = pox_contract_id.clone();
-        let btc_address_moved = pox_stacking_order.btc_address.clone();
-        let duration = pox_stacking_order.duration;
-
         let signer_key =
             devnet_config.stacks_signers_keys[i % devnet_config.stacks_signers_keys.len()];
+        let description = |account: &AccountConfig, method: &str| {
+            format!(
+                "Stacking order {} ({}): {} `{}`",
+                i + 1,
+                pox_stacking_order.wallet,
+                account.label,
+                method
+            )
+        };
 
-        let stacking_result =
-            hiro_system_kit::thread_named("Stacking orders handler").spawn(move || {
-                let default_fee = fee_rate * 1000;
-                let stacks_rpc = StacksRpc::new(&node_rpc_url_moved);
-                let nonce = stacks_rpc.get_nonce(&account.stx_address)?;
+        let Some(ref pool_operator) = pox_stacking_order.pool_operator else {
+            if is_pool_operators_turn {
+                continue;
+            }
+            let (method, arguments) = get_stacking_tx_method_and_args(
+                pox_version,
+                bitcoin_block_height,
+                current_cycle.into(),
+                &signer_key,
+                extend_stacking,
+                &pox_stacking_order.btc_address,
+                stx_amount,
+                pox_stacking_order.duration,
+                i.try_into().unwrap(),
+            );
+            tracker.published_orders.insert(i);
+            transactions.push(StackingTransaction {
+                description: description(&account, &method),
+                account,
+                method,
+                arguments,
+            });
+            continue;
+        };
 
-                let (_, _, account_secret_key) = clarinet_files::compute_addresses(
-                    &account.mnemonic,
-                    &account.derivation,
-                    &StacksNetwork::Devnet.get_networks(),
-                );
+        let Some(operator) = accounts.iter().find(|e| &e.label == pool_operator).cloned() else {
+            continue;
+        };
 
-                let (method, arguments) = get_stacking_tx_method_and_args(
-                    pox_version,
-                    bitcoin_block_height,
-                    current_cycle.into(),
-                    &signer_key,
-                    extend_stacking,
-                    &btc_address_moved,
+        if !is_pool_operators_turn {
+            // the delegation remains valid until it's revoked, extending only concerns the operator
+            if !extend_stacking {
+                let (method, arguments) = get_delegate_stx_tx_method_and_args(
+                    &operator.stx_address,
+                    &pox_stacking_order.btc_address,
                     stx_amount,
-                    duration,
-                    i.try_into().unwrap(),
                 );
-
-                let tx = stacks_codec::codec::build_contract_call_transaction(
-                    pox_contract_id_moved,
+                transactions.push(StackingTransaction {
+                    description: description(&account, &method),
+                    account,
                     method,
                     arguments,
-                    nonce,
-                    default_fee,
-                    &hex_bytes(&account_secret_key).unwrap(),
-                );
+                });
+            }
+            tracker.published_orders.insert(i);
+            continue;
+        }
 
-                stacks_rpc.post_transaction(&tx)
+        let (method, arguments) = get_delegate_stack_tx_method_and_args(
+            bitcoin_block_height,
+            extend_stacking,
+            &account.stx_address,
+            &pox_stacking_order.btc_address,
+            stx_amount,
+            pox_stacking_order.duration,
+        );
+        transactions.push(StackingTransaction {
+            description: description(&operator, &method),
+            account: operator.clone(),
+            method,
+            arguments,
+        });
+
+        // the stacked STX only count once committed, once per pool and reward address
+        if aggregation_commits.insert((operator.label.clone(), &pox_stacking_order.btc_address)) {
+            let (method, arguments) = get_aggregation_commit_tx_method_and_args(
+                pox_version,
+                current_cycle as u128 + 1,
+                &signer_key,
+                &pox_stacking_order.btc_address,
+                u64::MAX,
+                (devnet_config.pox_stacking_orders.len() + i)
+                    .try_into()
+                    .unwrap(),
+            );
+            transactions.push(StackingTransaction {
+                description: description(&operator, &method),
+                account: operator,
+                method,
+                arguments,
             });
+        }
+    }
 
-        match stacking_result {
-            Ok(result) => {
-                if let Ok(result) = result.join() {
-                    match result {
-                        Ok(_) => {
-                            let _ = devnet_event_tx.send(DevnetEvent::success(format!(
-                                "Stacking order for {} STX submitted",
-                                stx_amount
-                            )));
-                        }
-                        Err(e) => {
-                            let _ = devnet_event_tx
-                                .send(DevnetEvent::error(format!("Unable to stack: {}", e)));
-                        }
-                    }
-                };
+    if transactions.is_empty() {
+        return None;
+    }
+
+    let default_fee = fee_rate * 1000;
+    let transactions_count = transactions.len();
+    let stacking_result =
+        hiro_system_kit::thread_named("Stacking orders handler").spawn(move || {
+            let stacks_rpc = StacksRpc::new(&node_rpc_url);
+            let mut nonces: BTreeMap<String, u64> = BTreeMap::new();
+            let mut results = vec![];
+            for tx in transactions.into_iter() {
+                let result = publish_stacking_transaction(
+                    &stacks_rpc,
+                    &mut nonces,
+                    &pox_contract_id,
+                    &tx,
+                    default_fee,
+                );
+                results.push((tx.description, result));
+            }
+            results
+        });
+
+    let results = match stacking_result.map(|handle| handle.join()) {
+        Ok(Ok(results)) => results,
+        _ => {
+            let _ = devnet_event_tx.send(DevnetEvent::error(
+                "Unable to publish stacking orders".to_string(),
+            ));
+            return None;
+        }
+    };
+
+    for (description, result) in results {
+        match result {
+            Ok(txid) => {
+                let _ = devnet_event_tx.send(DevnetEvent::info(format!(
+                    "{} submitted (txid: {})",
+                    description, txid
+                )));
+                tracker
+                    .pending_transactions
+                    .insert(StackingOrdersTracker::normalize_txid(&txid), description);
             }
             Err(e) => {
-                let _ = devnet_event_tx.send(DevnetEvent::error(format!("Unable to stack: {}", e)));
+                let _ = devnet_event_tx.send(DevnetEvent::error(format!(
+                    "{} rejected: {}",
+                    description, e
+                )));
             }
         }
     }
-    if transactions > 0 {
-        Some(transactions)
-    } else {
-        None
-    }
+    Some(transactions_count)
+}
+
+fn publish_stacking_transaction(
+    stacks_rpc: &StacksRpc,
+    nonces: &mut BTreeMap<String, u64>,
+    pox_contract_id: &str,
+    tx: &StackingTransaction,
+    fee: u64,
+) -> Result<String, String> {
+    let nonce = match nonces.get(&tx.account.stx_address) {
+        Some(nonce) => *nonce,
+        None => stacks_rpc
+            .get_nonce(&tx.account.stx_address)
+            .map_err(|e| e.to_string())?,
+    };
+
+    let (_, _, account_secret_key) = clarinet_files::compute_addresses(
+        &tx.account.mnemonic,
+        &tx.account.derivation,
+        &StacksNetwork::Devnet.get_networks(),
+    );
+
+    let transaction = stacks_codec::codec::build_contract_call_transaction(
+        pox_contract_id.to_string(),
+        tx.method.clone(),
+        tx.arguments.clone(),
+        nonce,
+        fee,
+        &hex_bytes(&account_secret_key).unwrap(),
+    );
+
+    let result = stacks_rpc
+        .post_transaction(&transaction)
+        .map_err(|e| e.to_string())?;
+    nonces.insert(tx.account.stx_address.clone(), nonce + 1);
+    Ok(result.txid)
 }
 
 pub fn invalidate_bitcoin_chain_tip(
@@ -818,6 +1616,14 @@ pub fn invalidate_bitcoin_chain_tip(
     unimplemented!()
 }
 
+/// The stacks-api is synced once it indexed the last block of the stacks-node
+async fn is_stacks_api_synced(stacks_api_host: &str, block_height: u64) -> bool {
+    matches!(
+        get_stacks_api_tip(stacks_api_host).await,
+        Ok(Some(api_height)) if api_height >= block_height
+    )
+}
+
 pub async fn mine_bitcoin_block(
     bitcoin_node_host: &str,
     bitcoin_node_username: &str,
@@ -917,21 +1723,11 @@ async fn handle_bitcoin_mining(
     }
 }
 
-fn get_stacking_tx_method_and_args(
-    pox_version: u32,
-    bitcoin_block_height: u32,
-    cycle: u128,
-    signer_key: &StacksPrivateKey,
-    extend_stacking: bool,
-    btc_address: &str,
-    stx_amount: u64,
-    duration: u32,
-    auth_id: u128,
-) -> (String, Vec<ClarityValue>) {
+fn get_pox_addr_tuple(btc_address: &str) -> ClarityValue {
     let addr_bytes = btc_address
         .from_base58()
         .expect("Unable to get bytes from btc address");
-    let pox_addr_tuple = ClarityValue::Tuple(
+    ClarityValue::Tuple(
         TupleData::from_data(vec![
             (
                 ClarityName::try_from("version".to_owned()).unwrap(),
@@ -948,7 +1744,110 @@ fn get_stacking_tx_method_and_args(
             ),
         ])
         .unwrap(),
-    );
+    )
+}
+
+fn get_principal(address: &str) -> ClarityValue {
+    ClarityValue::Principal(
+        PrincipalData::parse_standard_principal(address)
+            .expect("Unable to parse stacks address")
+            .into(),
+    )
+}
+
+fn get_delegate_stx_tx_method_and_args(
+    pool_operator: &str,
+    btc_address: &str,
+    stx_amount: u64,
+) -> (String, Vec<ClarityValue>) {
+    let arguments = vec![
+        ClarityValue::UInt(stx_amount.into()),
+        get_principal(pool_operator),
+        ClarityValue::none(),
+        ClarityValue::some(get_pox_addr_tuple(btc_address)).unwrap(),
+    ];
+    ("delegate-stx".to_string(), arguments)
+}
+
+fn get_delegate_stack_tx_method_and_args(
+    bitcoin_block_height: u32,
+    extend_stacking: bool,
+    stacker: &str,
+    btc_address: &str,
+    stx_amount: u64,
+    duration: u32,
+) -> (String, Vec<ClarityValue>) {
+    let pox_addr_tuple = get_pox_addr_tuple(btc_address);
+    if extend_stacking {
+        let arguments = vec![
+            get_principal(stacker),
+            pox_addr_tuple,
+            ClarityValue::UInt(duration.into()),
+        ];
+        return ("delegate-stack-extend".to_string(), arguments);
+    }
+    let burn_block_height: u128 = (bitcoin_block_height - 1).into();
+    let arguments = vec![
+        get_principal(stacker),
+        ClarityValue::UInt(stx_amount.into()),
+        pox_addr_tuple,
+        ClarityValue::UInt(burn_block_height),
+        ClarityValue::UInt(duration.into()),
+    ];
+    ("delegate-stack-stx".to_string(), arguments)
+}
+
+fn get_aggregation_commit_tx_method_and_args(
+    pox_version: u32,
+    reward_cycle: u128,
+    signer_key: &StacksPrivateKey,
+    btc_address: &str,
+    max_amount: u64,
+    auth_id: u128,
+) -> (String, Vec<ClarityValue>) {
+    let pox_addr_tuple = get_pox_addr_tuple(btc_address);
+    if pox_version < 4 {
+        let arguments = vec![pox_addr_tuple, ClarityValue::UInt(reward_cycle)];
+        return ("stack-aggregation-commit".to_string(), arguments);
+    }
+
+    let pox_addr = PoxAddress::try_from_pox_tuple(false, &pox_addr_tuple).unwrap();
+    let signature = make_pox_4_signer_key_signature(
+        &pox_addr,
+        signer_key,
+        reward_cycle,
+        &Pox4SignatureTopic::AggregationCommit,
+        CHAIN_ID_TESTNET,
+        1,
+        max_amount.into(),
+        auth_id,
+    )
+    .expect("Unable to make pox 4 signature");
+
+    let pub_key = StacksPublicKey::from_private(signer_key);
+    let arguments = vec![
+        pox_addr_tuple,
+        ClarityValue::UInt(reward_cycle),
+        ClarityValue::some(ClarityValue::buff_from(signature.to_rsv()).unwrap()).unwrap(),
+        ClarityValue::buff_from(pub_key.to_bytes()).unwrap(),
+        ClarityValue::UInt(max_amount.into()),
+        ClarityValue::UInt(auth_id),
+    ];
+    ("stack-aggregation-commit-indexed".to_string(), arguments)
+}
+
+fn get_stacking_tx_method_and_args(
+    pox_version: u32,
+    bitcoin_block_height: u32,
+    cycle: u128,
+    signer_key: &StacksPrivateKey,
+    extend_stacking: bool,
+    btc_address: &str,
+    stx_amount: u64,
+    duration: u32,
+    auth_id: u128,
+) -> (String, Vec<ClarityValue>) {
+    let pox_addr_tuple = get_pox_addr_tuple(btc_address);
     let pox_addr = PoxAddress::try_from_pox_tuple(false, &pox_addr_tuple).unwrap();
 
     let burn_block_height: u128 = (bitcoin_block_height - 1).into();
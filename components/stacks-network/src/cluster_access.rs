@@ -0,0 +1,235 @@
+//! Access to a devnet running in a k8s namespace from the local machine. The services are
+//! reached through `kubectl port-forward`, on the ports of `Devnet.toml` so that the local
+//! tooling doesn't tell a cluster from a docker devnet, or through the ingress of a domain.
+//! The in-cluster hosts returned by the status API are rewritten to the local URLs. The status
+//! API itself isn't exposed by the ingress, its port is always forwarded.
+
+use std::collections::BTreeMap;
+use std::process::{Child, Command, Stdio};
+
+use clarinet_files::DevnetConfig;
+
+use crate::coordinator_state::{get_devnet_status, ChainsCoordinatorState};
+use crate::orchestrator::ServicesMapHosts;
+
+pub const STACKS_NODE_SERVICE: &str = "stacks-blockchain";
+pub const STACKS_API_SERVICE: &str = "stacks-blockchain-api";
+pub const STACKS_EXPLORER_SERVICE: &str = "stacks-explorer";
+/// The chains coordinator runs next to bitcoind, and serves the orchestrator control port
+pub const CHAINS_COORDINATOR_SERVICE: &str = "bitcoind-chain-coordinator";
+const ORCHESTRATOR_CONTROL_PORT: u16 = 20446;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ClusterAccess {
+    PortForward,
+    /// The services are exposed as `<service>.<namespace>.<domain>`
+    Ingress {
+        domain: String,
+    },
+}
+
+/// A service of the namespace, and the local port it's forwarded to
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClusterService {
+    pub name: &'static str,
+    pub cluster_port: u16,
+    pub local_port: u16,
+}
+
+impl ClusterService {
+    pub fn get_cluster_host(&self, namespace: &str) -> String {
+        format!(
+            "{}.{}.svc.cluster.local:{}",
+            self.name, namespace, self.cluster_port
+        )
+    }
+}
+
+pub fn get_cluster_services(devnet_config: &DevnetConfig) -> Vec<ClusterService> {
+    vec![
+        ClusterService {
+            name: STACKS_NODE_SERVICE,
+            cluster_port: 20443,
+            local_port: devnet_config.stacks_node_rpc_port,
+        },
+        ClusterService {
+            name: STACKS_API_SERVICE,
+            cluster_port: 3999,
+            local_port: devnet_config.stacks_api_port,
+        },
+        ClusterService {
+            name: STACKS_EXPLORER_SERVICE,
+            cluster_port: 3000,
+            local_port: devnet_config.stacks_explorer_port,
+        },
+        ClusterService {
+            name: CHAINS_COORDINATOR_SERVICE,
+            cluster_port: 18443,
+            local_port: devnet_config.bitcoin_node_rpc_port,
+        },
+        ClusterService {
+            name: CHAINS_COORDINATOR_SERVICE,
+            cluster_port: ORCHESTRATOR_CONTROL_PORT,
+            local_port: devnet_config.orchestrator_control_port,
+        },
+    ]
+}
+
+/// Connection to the services of a namespace, the port-forwards stop when it's dropped
+pub struct ClusterConnection {
+    namespace: String,
+    access: ClusterAccess,
+    services: Vec<ClusterService>,
+    port_forwards: Vec<Child>,
+}
+
+impl ClusterConnection {
+    pub fn connect(
+        namespace: &str,
+        access: ClusterAccess,
+        devnet_config: &DevnetConfig,
+    ) -> Result<ClusterConnection, String> {
+        let mut connection = ClusterConnection {
+            namespace: namespace.to_string(),
+            access,
+            services: get_cluster_services(devnet_config),
+            port_forwards: vec![],
+        };
+        let forwarded = connection.services.iter().filter(|service| {
+            connection.access == ClusterAccess::PortForward
+                || service.cluster_port == ORCHESTRATOR_CONTROL_PORT
+        });
+        for service in forwarded {
+            let child = Command::new("kubectl")
+                .args([
+                    "port-forward",
+                    "--namespace",
+                    namespace,
+                    &format!("svc/{}", service.name),
+                    &format!("{}:{}", service.local_port, service.cluster_port),
+                ])
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .spawn()
+                .map_err(|e| format!("unable to forward the port of {}: {}", service.name, e))?;
+            connection.port_forwards.push(child);
+        }
+        Ok(connection)
+    }
+
+    /// Host (without scheme) the local tooling reaches `service` on
+    pub fn get_local_host(&self, service: &ClusterService) -> String {
+        match &self.access {
+            ClusterAccess::PortForward => format!("localhost:{}", service.local_port),
+            ClusterAccess::Ingress { domain } => {
+                format!("{}.{}.{}", service.name, self.namespace, domain)
+            }
+        }
+    }
+
+    pub fn get_url(&self, name: &str) -> Option<String> {
+        self.services
+            .iter()
+            .find(|service| service.name == name)
+            .map(|service| format!("http://{}", self.get_local_host(service)))
+    }
+
+    /// In-cluster hosts replaced by the local ones, the other hosts are kept
+    pub fn rewrite_services(&self, services: &ServicesMapHosts) -> ServicesMapHosts {
+        let rewrites: BTreeMap<String, String> = self
+            .services
+            .iter()
+            .map(|service| {
+                (
+                    service.get_cluster_host(&self.namespace),
+                    self.get_local_host(service),
+                )
+            })
+            .collect();
+        let rewrite = |host: &String| rewrites.get(host).cloned().unwrap_or(host.clone());
+        ServicesMapHosts {
+            bitcoin_node_host: rewrite(&services.bitcoin_node_host),
+            stacks_node_host: rewrite(&services.stacks_node_host),
+            stacks_api_host: rewrite(&services.stacks_api_host),
+            postgres_host: rewrite(&services.postgres_host),
+            stacks_explorer_host: rewrite(&services.stacks_explorer_host),
+            bitcoin_explorer_host: rewrite(&services.bitcoin_explorer_host),
+            subnet_node_host: rewrite(&services.subnet_node_host),
+            subnet_api_host: rewrite(&services.subnet_api_host),
+        }
+    }
+
+    /// State served by the chains coordinator, with the hosts of the services rewritten
+    pub fn get_status(&self) -> Result<ChainsCoordinatorState, String> {
+        let control_port = self
+            .services
+            .iter()
+            .find(|service| service.cluster_port == ORCHESTRATOR_CONTROL_PORT)
+            .map(|service| service.local_port)
+            .unwrap_or(ORCHESTRATOR_CONTROL_PORT);
+        let mut state = get_devnet_status(control_port)?;
+        state.services = state
+            .services
+            .map(|services| self.rewrite_services(&services));
+        Ok(state)
+    }
+}
+
+impl Drop for ClusterConnection {
+    fn drop(&mut self) {
+        for child in self.port_forwards.iter_mut() {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_connection(access: ClusterAccess) -> ClusterConnection {
+        ClusterConnection {
+            namespace: "team-a".to_string(),
+            access,
+            services: vec![ClusterService {
+                name: STACKS_NODE_SERVICE,
+                cluster_port: 20443,
+                local_port: 30443,
+            }],
+            port_forwards: vec![],
+        }
+    }
+
+    #[test]
+    fn test_rewrite_services() {
+        let services = ServicesMapHosts {
+            bitcoin_node_host: "localhost:18443".to_string(),
+            stacks_node_host: "stacks-blockchain.team-a.svc.cluster.local:20443".to_string(),
+            stacks_api_host: String::new(),
+            postgres_host: String::new(),
+            stacks_explorer_host: String::new(),
+            bitcoin_explorer_host: String::new(),
+            subnet_node_host: String::new(),
+            subnet_api_host: String::new(),
+        };
+
+        let port_forward = get_connection(ClusterAccess::PortForward);
+        let rewritten = port_forward.rewrite_services(&services);
+        assert_eq!(rewritten.stacks_node_host, "localhost:30443");
+        assert_eq!(rewritten.bitcoin_node_host, "localhost:18443");
+        assert_eq!(
+            port_forward.get_url(STACKS_NODE_SERVICE),
+            Some("http://localhost:30443".to_string())
+        );
+        assert_eq!(port_forward.get_url(STACKS_API_SERVICE), None);
+
+        let ingress = get_connection(ClusterAccess::Ingress {
+            domain: "devnet.example.com".to_string(),
+        });
+        assert_eq!(
+            ingress.rewrite_services(&services).stacks_node_host,
+            "stacks-blockchain.team-a.devnet.example.com"
+        );
+    }
+}
@@ -0,0 +1,344 @@
+//! Read-only index of the contracts of the devnet, served on the orchestrator control port
+//! (`http://localhost:20446/v1/contracts`), so that local wallet builds and dapp dev servers
+//! can discover the addresses and the interfaces of the contracts instead of hardcoding them
+//! in their env files. The interfaces are fetched from the stacks-node once published.
+//! The state of the chains coordinator is served along (`/v1/status`), and so are the SIP-016
+//! metadata files of the tokens (`/v1/metadata/<contract_id>.json`), the URIs set on devnet.
+
+use std::collections::BTreeMap;
+use std::io::{ErrorKind, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::Sender;
+use std::sync::Arc;
+use std::time::Duration;
+
+use clarinet_deployments::token_metadata::DEVNET_METADATA_ROUTE;
+use clarinet_deployments::types::DeploymentSpecification;
+use clarinet_deployments::versions::get_versions_matrix;
+use clarinet_files::ProjectManifest;
+use clarity_repl::repl::EpochSpec;
+use serde_json::{json, Value};
+
+use crate::coordinator_state::ChainsCoordinatorHandle;
+use crate::event::DevnetEvent;
+use crate::webhooks::read_request;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DevnetContract {
+    pub contract_id: String,
+    pub address: String,
+    pub name: String,
+    /// Label of the deployer in the plan, or its address
+    pub deployer: String,
+    pub batch_id: usize,
+    pub clarity_version: u8,
+    pub epoch: Option<EpochSpec>,
+    pub is_requirement: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ContractsIndex {
+    pub network: String,
+    pub stacks_node_url: String,
+    pub stacks_api_url: Option<String>,
+    pub contracts: Vec<DevnetContract>,
+    /// SIP-016 metadata of the token contracts, by contract id
+    #[serde(skip)]
+    pub metadata: BTreeMap<String, Value>,
+}
+
+impl ContractsIndex {
+    pub fn new(
+        manifest: &ProjectManifest,
+        deployment: &DeploymentSpecification,
+        stacks_node_url: String,
+        stacks_api_url: Option<String>,
+    ) -> Self {
+        let contracts = get_versions_matrix(manifest, deployment)
+            .into_iter()
+            .map(|contract| {
+                let (address, name) = contract
+                    .contract_id
+                    .split_once('.')
+                    .map(|(address, name)| (address.to_string(), name.to_string()))
+                    .unwrap_or_default();
+                DevnetContract {
+                    address,
+                    name,
+                    contract_id: contract.contract_id,
+                    deployer: contract.deployer,
+                    batch_id: contract.batch_id,
+                    clarity_version: contract.clarity_version,
+                    epoch: contract.epoch,
+                    is_requirement: contract.is_requirement,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        // unreadable files are left out, the plan generation reports them
+        let mut metadata = BTreeMap::new();
+        for contract in contracts.iter().filter(|contract| !contract.is_requirement) {
+            let Ok(Some(location)) = manifest.get_token_metadata_location(&contract.name) else {
+                continue;
+            };
+            let content = location.read_content_as_utf8().ok();
+            if let Some(value) = content.and_then(|content| serde_json::from_str(&content).ok()) {
+                metadata.insert(contract.contract_id.clone(), value);
+            }
+        }
+        ContractsIndex {
+            network: "devnet".to_string(),
+            stacks_node_url,
+            stacks_api_url,
+            contracts,
+            metadata,
+        }
+    }
+
+    /// Status and JSON body of a request. `get_interface` fetches the interface of a contract,
+    /// `None` until it is published.
+    pub fn respond(
+        &self,
+        method: &str,
+        path: &str,
+        get_interface: impl FnOnce(&DevnetContract) -> Result<Option<Value>, String>,
+    ) -> (u16, Value) {
+        if method != "GET" {
+            return (405, json!({ "error": "the contracts server is read-only" }));
+        }
+        let path = path.split_once('?').map_or(path, |(path, _)| path);
+        let path = path.trim_end_matches('/');
+        if path == "/v1/contracts" {
+            return (200, json!(self));
+        }
+        if let Some(file) = path.strip_prefix(DEVNET_METADATA_ROUTE) {
+            let contract_id = file.strip_suffix(".json").unwrap_or(file);
+            return match self.metadata.get(contract_id) {
+                Some(metadata) => (200, metadata.clone()),
+                None => (
+                    404,
+                    json!({ "error": format!("no metadata for {}", contract_id) }),
+                ),
+            };
+        }
+        let Some(contract_id) = path.strip_prefix("/v1/contracts/") else {
+            return (404, json!({ "error": format!("unknown route {}", path) }));
+        };
+        let Some(contract) = self
+            .contracts
+            .iter()
+            .find(|contract| contract.contract_id == contract_id)
+        else {
+            return (
+                404,
+                json!({ "error": format!("{} is not deployed by the devnet", contract_id) }),
+            );
+        };
+        match get_interface(contract) {
+            Ok(interface) => {
+                let mut body = json!(contract);
+                body["published"] = json!(interface.is_some());
+                body["interface"] = interface.unwrap_or(Value::Null);
+                (200, body)
+            }
+            Err(e) => (502, json!({ "error": e })),
+        }
+    }
+}
+
+fn fetch_interface(
+    stacks_node_url: &str,
+    contract: &DevnetContract,
+) -> Result<Option<Value>, String> {
+    let url = format!(
+        "{}/v2/contracts/interface/{}/{}",
+        stacks_node_url, contract.address, contract.name
+    );
+    let response = reqwest::blocking::Client::new()
+        .get(url)
+        .timeout(Duration::from_secs(5))
+        .send()
+        .map_err(|e| format!("unable to reach the stacks-node: {}", e))?;
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    if !response.status().is_success() {
+        return Err(format!("stacks-node responded {}", response.status()));
+    }
+    response.json().map(Some).map_err(|e| e.to_string())
+}
+
+fn write_response(stream: &mut TcpStream, status: u16, body: &Value) {
+    let reason = match status {
+        200 => "OK",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        _ => "Bad Gateway",
+    };
+    let body = body.to_string();
+    let _ = stream.write_all(
+        format!(
+            "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nAccess-Control-Allow-Origin: *\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            reason,
+            body.len(),
+            body
+        )
+        .as_bytes(),
+    );
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ContractsServer {
+    stopped: Arc<AtomicBool>,
+}
+
+impl ContractsServer {
+    /// Serves `index` on `port`, `stacks_node_url` being the stacks-node as reached by the
+    /// orchestrator, to fetch the interfaces
+    pub fn start(
+        port: u16,
+        index: ContractsIndex,
+        stacks_node_url: String,
+        coordinator_handle: ChainsCoordinatorHandle,
+        devnet_event_tx: Sender<DevnetEvent>,
+    ) -> Result<Self, String> {
+        let listener = TcpListener::bind(("0.0.0.0", port)).map_err(|e| {
+            format!(
+                "unable to start the contracts server on port {}: {}",
+                port, e
+            )
+        })?;
+        listener
+            .set_nonblocking(true)
+            .map_err(|e| format!("unable to start the contracts server: {}", e))?;
+
+        let server = ContractsServer::default();
+        let stopped = server.stopped.clone();
+        let _ = hiro_system_kit::thread_named("Contracts server").spawn(move || {
+            // the interfaces of the published contracts don't change during a run
+            let mut interfaces: BTreeMap<String, Value> = BTreeMap::new();
+            while !stopped.load(Ordering::SeqCst) {
+                let mut stream = match listener.accept() {
+                    Ok((stream, _)) => stream,
+                    Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                        std::thread::sleep(Duration::from_millis(100));
+                        continue;
+                    }
+                    Err(_) => continue,
+                };
+                let _ = stream.set_nonblocking(false);
+                let request = match read_request(&mut stream) {
+                    Ok(request) => request,
+                    Err(e) => {
+                        write_response(&mut stream, 404, &json!({ "error": e }));
+                        continue;
+                    }
+                };
+                let path = request
+                    .path
+                    .split_once('?')
+                    .map_or(request.path.as_str(), |(path, _)| path);
+                if request.method == "GET" && path.trim_end_matches('/') == "/v1/status" {
+                    write_response(&mut stream, 200, &json!(coordinator_handle.state()));
+                    continue;
+                }
+                let mut fetched = None;
+                let (status, body) = index.respond(&request.method, &request.path, |contract| {
+                    if let Some(interface) = interfaces.get(&contract.contract_id) {
+                        return Ok(Some(interface.clone()));
+                    }
+                    let interface = fetch_interface(&stacks_node_url, contract)?;
+                    fetched = interface
+                        .clone()
+                        .map(|interface| (contract.contract_id.clone(), interface));
+                    Ok(interface)
+                });
+                if let Some((contract_id, interface)) = fetched {
+                    interfaces.insert(contract_id, interface);
+                }
+                write_response(&mut stream, status, &body);
+            }
+        });
+        let _ = devnet_event_tx.send(DevnetEvent::info(format!(
+            "contracts served on http://localhost:{}/v1/contracts",
+            port
+        )));
+        Ok(server)
+    }
+
+    pub fn stop(&self) {
+        self.stopped.store(true, Ordering::SeqCst);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_index() -> ContractsIndex {
+        ContractsIndex {
+            network: "devnet".to_string(),
+            stacks_node_url: "http://localhost:20443".to_string(),
+            stacks_api_url: Some("http://localhost:3999".to_string()),
+            contracts: vec![DevnetContract {
+                contract_id: "ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM.counter".to_string(),
+                address: "ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM".to_string(),
+                name: "counter".to_string(),
+                deployer: "deployer".to_string(),
+                batch_id: 0,
+                clarity_version: 2,
+                epoch: Some(EpochSpec::Epoch2_5),
+                is_requirement: false,
+            }],
+            metadata: BTreeMap::from([(
+                "ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM.counter".to_string(),
+                json!({ "sip": 16, "name": "Counter" }),
+            )]),
+        }
+    }
+
+    #[test]
+    fn test_contracts_server_routes() {
+        let index = get_index();
+        let unpublished = |_: &DevnetContract| Ok(None);
+        let (status, body) = index.respond("GET", "/v1/contracts/?chain=devnet", unpublished);
+        assert_eq!(status, 200);
+        assert_eq!(body["contracts"][0]["name"], "counter");
+        assert_eq!(body["contracts"][0]["epoch"], "2.5");
+
+        let path = "/v1/contracts/ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM.counter";
+        let (status, body) = index.respond("GET", path, unpublished);
+        assert_eq!(status, 200);
+        assert_eq!(body["published"], false);
+        assert_eq!(body["interface"], Value::Null);
+
+        let published = |_: &DevnetContract| Ok(Some(json!({ "functions": [] })));
+        let (_, body) = index.respond("GET", path, published);
+        assert_eq!(body["published"], true);
+        assert_eq!(body["interface"], json!({ "functions": [] }));
+
+        let unreachable = |_: &DevnetContract| Err("unable to reach the stacks-node".to_string());
+        assert_eq!(index.respond("GET", path, unreachable).0, 502);
+        assert_eq!(
+            index
+                .respond("GET", "/v1/contracts/ST1.other", unpublished)
+                .0,
+            404
+        );
+        assert_eq!(index.respond("GET", "/v1/accounts", unpublished).0, 404);
+
+        let path = "/v1/metadata/ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM.counter.json";
+        let (status, body) = index.respond("GET", path, unpublished);
+        assert_eq!(status, 200);
+        assert_eq!(body["name"], "Counter");
+        assert_eq!(
+            index
+                .respond("GET", "/v1/metadata/ST1.other.json", unpublished)
+                .0,
+            404
+        );
+        assert_eq!(index.respond("POST", "/v1/contracts", unpublished).0, 405);
+    }
+}
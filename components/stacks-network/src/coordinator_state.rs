@@ -0,0 +1,136 @@
+//! Read-only state of the chains coordinator: the tips of both chains, the active epoch, the
+//! PoX cycle and the hosts of the services. The coordinator updates it as the blocks are
+//! received, and the dashboard, the JS bindings and `clarinet devnet status` (through the
+//! orchestrator control port, `/v1/status`) query it instead of following the events.
+
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use clarinet_files::DevnetConfig;
+use clarity_repl::repl::EpochSpec;
+
+use crate::orchestrator::ServicesMapHosts;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ChainsCoordinatorState {
+    pub bitcoin_block_height: u64,
+    pub stacks_block_height: u64,
+    /// Epoch of the bitcoin tip, `None` before the epoch 2.0
+    pub epoch: Option<EpochSpec>,
+    /// PoX cycle of the stacks tip
+    pub pox_cycle: Option<u32>,
+    pub boot_completed: bool,
+    /// Services as reached by the coordinator
+    pub services: Option<ServicesMapHosts>,
+}
+
+/// Shared by the chains coordinator, updating the state, and its consumers
+#[derive(Debug, Clone, Default)]
+pub struct ChainsCoordinatorHandle {
+    state: Arc<RwLock<ChainsCoordinatorState>>,
+}
+
+impl ChainsCoordinatorHandle {
+    /// Snapshot of the current state
+    pub fn state(&self) -> ChainsCoordinatorState {
+        self.state.read().unwrap().clone()
+    }
+
+    pub(crate) fn set_services(&self, services: &ServicesMapHosts) {
+        self.state.write().unwrap().services = Some(services.clone());
+    }
+
+    pub(crate) fn set_bitcoin_tip(&self, block_height: u64, epoch: Option<EpochSpec>) {
+        let mut state = self.state.write().unwrap();
+        state.bitcoin_block_height = block_height;
+        state.epoch = epoch;
+    }
+
+    pub(crate) fn set_stacks_tip(&self, block_height: u64, pox_cycle: u32, boot_completed: bool) {
+        let mut state = self.state.write().unwrap();
+        state.stacks_block_height = block_height;
+        state.pox_cycle = Some(pox_cycle);
+        state.boot_completed = boot_completed;
+    }
+}
+
+/// Start heights of the epochs, as scheduled by the devnet settings, the latest first
+pub fn get_epochs_schedule(devnet_config: &DevnetConfig) -> Vec<(u64, EpochSpec)> {
+    vec![
+        (devnet_config.epoch_3_1, EpochSpec::Epoch3_1),
+        (devnet_config.epoch_3_0, EpochSpec::Epoch3_0),
+        (devnet_config.epoch_2_5, EpochSpec::Epoch2_5),
+        (devnet_config.epoch_2_4, EpochSpec::Epoch2_4),
+        (devnet_config.epoch_2_3, EpochSpec::Epoch2_3),
+        (devnet_config.epoch_2_2, EpochSpec::Epoch2_2),
+        (devnet_config.epoch_2_1, EpochSpec::Epoch2_1),
+        (devnet_config.epoch_2_05, EpochSpec::Epoch2_05),
+        (devnet_config.epoch_2_0, EpochSpec::Epoch2_0),
+    ]
+}
+
+/// Latest epoch started at `burn_height`, `schedule` being sorted from the latest epoch
+pub fn get_epoch_at_burn_height(
+    schedule: &[(u64, EpochSpec)],
+    burn_height: u64,
+) -> Option<EpochSpec> {
+    schedule
+        .iter()
+        .find(|(start_height, _)| burn_height >= *start_height)
+        .map(|(_, epoch)| *epoch)
+}
+
+/// State of a running devnet, served on its orchestrator control port
+pub fn get_devnet_status(orchestrator_control_port: u16) -> Result<ChainsCoordinatorState, String> {
+    let url = format!("http://localhost:{}/v1/status", orchestrator_control_port);
+    let response = reqwest::blocking::Client::new()
+        .get(url)
+        .timeout(Duration::from_secs(5))
+        .send()
+        .map_err(|e| format!("unable to reach the devnet: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("devnet responded {}", response.status()));
+    }
+    response.json().map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chains_coordinator_state() {
+        // the epochs 2.0 and 2.05 start at the same height
+        let schedule = vec![
+            (142, EpochSpec::Epoch3_0),
+            (108, EpochSpec::Epoch2_5),
+            (100, EpochSpec::Epoch2_05),
+            (100, EpochSpec::Epoch2_0),
+        ];
+        assert_eq!(get_epoch_at_burn_height(&schedule, 99), None);
+        assert_eq!(
+            get_epoch_at_burn_height(&schedule, 100),
+            Some(EpochSpec::Epoch2_05)
+        );
+        assert_eq!(
+            get_epoch_at_burn_height(&schedule, 141),
+            Some(EpochSpec::Epoch2_5)
+        );
+        assert_eq!(
+            get_epoch_at_burn_height(&schedule, 142),
+            Some(EpochSpec::Epoch3_0)
+        );
+
+        let handle = ChainsCoordinatorHandle::default();
+        let consumer = handle.clone();
+        handle.set_bitcoin_tip(142, get_epoch_at_burn_height(&schedule, 142));
+        handle.set_stacks_tip(42, 3, true);
+        let state = consumer.state();
+        assert_eq!(state.bitcoin_block_height, 142);
+        assert_eq!(state.stacks_block_height, 42);
+        assert_eq!(state.epoch, Some(EpochSpec::Epoch3_0));
+        assert_eq!(state.pox_cycle, Some(3));
+        assert!(state.boot_completed);
+        assert_eq!(state.services, None);
+    }
+}
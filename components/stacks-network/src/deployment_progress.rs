@@ -0,0 +1,224 @@
+//! Progress of the deployment plan on a devnet, persisted in the working dir. When the chain
+//! data outlives the devnet (`bind_containers_volumes`), a restart after a crash resumes the
+//! deployment instead of starting it over: the transactions confirmed before the crash and
+//! the contracts the node already knows are skipped.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use clarinet_deployments::onchain::get_initial_transactions_trackers;
+use clarinet_deployments::source_metadata::fetch_deployed_source;
+use clarinet_deployments::types::{DeploymentSpecification, TransactionSpecification};
+use clarinet_files::FileLocation;
+use clarity::vm::types::QualifiedContractIdentifier;
+
+pub const DEPLOYMENT_PROGRESS_FILE: &str = "deployment-progress.json";
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DeploymentProgress {
+    pub plan_id: String,
+    /// Confirmed transactions, counted by name: the transactions sharing a name (the same call
+    /// made twice) have the same sender and are confirmed in the order of their nonces
+    pub confirmed: BTreeMap<String, usize>,
+}
+
+impl DeploymentProgress {
+    pub fn new(plan_id: String) -> Self {
+        DeploymentProgress {
+            plan_id,
+            confirmed: BTreeMap::new(),
+        }
+    }
+
+    /// Progress recorded in the working dir, if it's the one of the plan `plan_id`
+    pub fn load(working_dir: &FileLocation, plan_id: &str) -> Option<Self> {
+        let mut location = working_dir.clone();
+        location.append_path(DEPLOYMENT_PROGRESS_FILE).ok()?;
+        if !location.exists() {
+            return None;
+        }
+        let content = location.read_content().ok()?;
+        let progress: DeploymentProgress = serde_json::from_slice(&content).ok()?;
+        (progress.plan_id == plan_id).then_some(progress)
+    }
+
+    pub fn save(&self, working_dir: &FileLocation) -> Result<(), String> {
+        let mut location = working_dir.clone();
+        location.append_path(DEPLOYMENT_PROGRESS_FILE)?;
+        let content = serde_json::to_vec_pretty(self).map_err(|e| e.to_string())?;
+        location.write_content(&content)
+    }
+
+    pub fn record_confirmed(&mut self, name: &str) {
+        *self.confirmed.entry(name.to_string()).or_default() += 1;
+    }
+
+    pub fn confirmed_count(&self) -> usize {
+        self.confirmed.values().sum()
+    }
+}
+
+fn get_published_contract(tx: &TransactionSpecification) -> Option<QualifiedContractIdentifier> {
+    match tx {
+        TransactionSpecification::ContractPublish(tx) => Some(QualifiedContractIdentifier::new(
+            tx.expected_sender.clone(),
+            tx.contract_name.clone(),
+        )),
+        TransactionSpecification::RequirementPublish(tx) => Some(tx.get_remapped_contract_id()),
+        _ => None,
+    }
+}
+
+/// Contracts of the plan the node at `node_url` already knows
+pub async fn get_deployed_contracts(
+    node_url: &str,
+    deployment: &DeploymentSpecification,
+) -> Result<BTreeSet<QualifiedContractIdentifier>, String> {
+    let mut deployed = BTreeSet::new();
+    for batch in deployment.plan.batches.iter() {
+        for contract_id in batch.transactions.iter().filter_map(get_published_contract) {
+            if fetch_deployed_source(node_url, &contract_id)
+                .await?
+                .is_some()
+            {
+                deployed.insert(contract_id);
+            }
+        }
+    }
+    Ok(deployed)
+}
+
+/// The plan without the transactions executed before the restart: the confirmed ones and the
+/// publishes of the contracts `deployed`. The batches left empty are dropped.
+pub fn get_remaining_deployment(
+    deployment: &DeploymentSpecification,
+    progress: &DeploymentProgress,
+    deployed: &BTreeSet<QualifiedContractIdentifier>,
+) -> DeploymentSpecification {
+    let mut trackers = get_initial_transactions_trackers(deployment).into_iter();
+    let mut skipped: BTreeMap<String, usize> = BTreeMap::new();
+    let mut remaining = deployment.clone();
+    for batch in remaining.plan.batches.iter_mut() {
+        batch.transactions.retain(|tx| {
+            if matches!(
+                tx,
+                TransactionSpecification::EmulatedContractPublish(_)
+                    | TransactionSpecification::EmulatedContractCall(_)
+                    | TransactionSpecification::EmulatedEval(_)
+            ) {
+                return true;
+            }
+            let Some(tracker) = trackers.next() else {
+                return true;
+            };
+            if get_published_contract(tx).is_some_and(|contract_id| deployed.contains(&contract_id))
+            {
+                return false;
+            }
+            let confirmed = progress.confirmed.get(&tracker.name).copied();
+            let skipped = skipped.entry(tracker.name).or_default();
+            if *skipped < confirmed.unwrap_or_default() {
+                *skipped += 1;
+                return false;
+            }
+            true
+        });
+    }
+    remaining
+        .plan
+        .batches
+        .retain(|batch| !batch.transactions.is_empty());
+    remaining
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clarinet_deployments::types::{
+        AnchorMode, ContractCallSpecification, ContractPublishSpecification,
+        TransactionPlanSpecification, TransactionsBatchSpecification,
+    };
+    use clarinet_files::StacksNetwork;
+    use clarity::vm::{ClarityName, ClarityVersion, ContractName};
+
+    #[test]
+    fn test_get_remaining_deployment() {
+        let contract_id =
+            QualifiedContractIdentifier::parse("ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM.counter")
+                .unwrap();
+        let publish = |name: &str| {
+            TransactionSpecification::ContractPublish(ContractPublishSpecification {
+                contract_name: ContractName::try_from(name.to_string()).unwrap(),
+                expected_sender: contract_id.issuer.clone(),
+                location: FileLocation::from_path_string("/project/contracts/counter.clar")
+                    .unwrap(),
+                source: "(define-data-var count uint u0)".to_string(),
+                clarity_version: ClarityVersion::Clarity2,
+                cost: 10_000,
+                anchor_mode: AnchorMode::OnChainOnly,
+                description: None,
+            })
+        };
+        let increment = TransactionSpecification::ContractCall(ContractCallSpecification {
+            contract_id: contract_id.clone(),
+            expected_sender: contract_id.issuer.clone(),
+            method: ClarityName::try_from("increment".to_string()).unwrap(),
+            parameters: vec![],
+            cost: 1_000,
+            anchor_mode: AnchorMode::OnChainOnly,
+            description: None,
+        });
+        let deployment = DeploymentSpecification {
+            id: 0,
+            name: "devnet".to_string(),
+            network: StacksNetwork::Devnet,
+            stacks_node: None,
+            bitcoin_node: None,
+            genesis: None,
+            plan: TransactionPlanSpecification {
+                batches: vec![
+                    TransactionsBatchSpecification {
+                        id: 0,
+                        transactions: vec![publish("counter"), publish("vault")],
+                        epoch: None,
+                        post_checks: vec![],
+                        description: None,
+                    },
+                    TransactionsBatchSpecification {
+                        id: 1,
+                        transactions: vec![increment.clone(), increment],
+                        epoch: None,
+                        post_checks: vec![],
+                        description: None,
+                    },
+                ],
+                post_checks: vec![],
+            },
+            contracts: BTreeMap::new(),
+            description: None,
+        };
+
+        let mut progress = DeploymentProgress::new("84c0a3d9e2b1f7a4".to_string());
+        progress.record_confirmed(&format!("Contract call {}::increment", contract_id));
+        let deployed = BTreeSet::from([contract_id.clone()]);
+        let remaining = get_remaining_deployment(&deployment, &progress, &deployed);
+        let transactions: Vec<_> = remaining
+            .plan
+            .batches
+            .iter()
+            .map(|batch| (batch.id, batch.transactions.len()))
+            .collect();
+        assert_eq!(transactions, vec![(0, 1), (1, 1)]);
+
+        progress.record_confirmed(&format!("Contract call {}::increment", contract_id));
+        let deployed = BTreeSet::from([
+            contract_id.clone(),
+            QualifiedContractIdentifier::new(
+                contract_id.issuer.clone(),
+                ContractName::try_from("vault".to_string()).unwrap(),
+            ),
+        ]);
+        let remaining = get_remaining_deployment(&deployment, &progress, &deployed);
+        assert!(remaining.plan.batches.is_empty());
+        assert_eq!(progress.confirmed_count(), 2);
+    }
+}
@@ -0,0 +1,227 @@
+//! Balances of the devnet accounts once the deployment plan is executed, compared to the
+//! balances expected from the genesis and the plan (transactions costs, STX transfers and
+//! burns). A negative drift not explained by the fees of the transactions sent outside of
+//! the plan (such as the stacking orders) reveals a mis-specified cost or an accidental
+//! transfer.
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use chainhook_sdk::types::StacksBlockData;
+use clarinet_deployments::types::{
+    BnsOperation, DeploymentSpecification, TransactionSpecification,
+};
+use clarinet_files::AccountConfig;
+use clarity::vm::types::PrincipalData;
+
+use crate::pox::get_json;
+
+pub const DRIFT_REPORT_FILE: &str = "balance-drift.json";
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct AccountDrift {
+    pub label: String,
+    pub address: String,
+    pub genesis_balance: u128,
+    pub plan_costs: u128,
+    pub plan_transfers_out: u128,
+    pub plan_transfers_in: u128,
+    pub expected_balance: i128,
+    /// Unlocked and locked balances
+    pub actual_balance: u128,
+    /// Fees of the confirmed transactions sent by the account, in the plan or not
+    pub paid_fees: u128,
+    /// Difference not explained by the fees of the transactions outside of the plan
+    pub unexplained_drift: i128,
+}
+
+impl AccountDrift {
+    pub fn is_drained(&self) -> bool {
+        self.unexplained_drift < 0
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct AccountResponse {
+    balance: String,
+    locked: String,
+}
+
+pub(crate) fn parse_hex_amount(amount: &str) -> Result<u128, String> {
+    u128::from_str_radix(amount.trim_start_matches("0x"), 16)
+        .map_err(|e| format!("invalid amount {}: {}", amount, e))
+}
+
+/// Fees paid by the devnet accounts, keyed by address, observed in the mined blocks
+#[derive(Debug, Clone, Default)]
+pub struct FeesTracker {
+    pub fees: BTreeMap<String, u128>,
+}
+
+impl FeesTracker {
+    pub fn record_block(&mut self, block: &StacksBlockData) {
+        for tx in block.transactions.iter() {
+            *self.fees.entry(tx.metadata.sender.clone()).or_default() += tx.metadata.fee as u128;
+        }
+    }
+}
+
+/// Returns the expected balances of the accounts, without the actual ones
+pub fn get_expected_balances(
+    deployment: &DeploymentSpecification,
+    accounts: &[AccountConfig],
+) -> Vec<AccountDrift> {
+    let mut drifts: BTreeMap<String, AccountDrift> = accounts
+        .iter()
+        .map(|account| {
+            let drift = AccountDrift {
+                label: account.label.clone(),
+                address: account.stx_address.clone(),
+                genesis_balance: account.balance as u128,
+                ..Default::default()
+            };
+            (account.stx_address.clone(), drift)
+        })
+        .collect();
+
+    for batch in deployment.plan.batches.iter() {
+        for transaction in batch.transactions.iter() {
+            let (sender, cost, amount_out) = match transaction {
+                TransactionSpecification::ContractCall(tx) => (&tx.expected_sender, tx.cost, 0),
+                TransactionSpecification::ContractPublish(tx) => (&tx.expected_sender, tx.cost, 0),
+                TransactionSpecification::RequirementPublish(tx) => (&tx.remap_sender, tx.cost, 0),
+                TransactionSpecification::StxTransfer(tx) => {
+                    if let PrincipalData::Standard(ref recipient) = tx.recipient {
+                        if let Some(drift) = drifts.get_mut(&recipient.to_address()) {
+                            drift.plan_transfers_in += tx.mstx_amount as u128;
+                        }
+                    }
+                    (&tx.expected_sender, tx.cost, tx.mstx_amount)
+                }
+                TransactionSpecification::BnsOperation(tx) => {
+                    let burned = match tx.operation {
+                        BnsOperation::NamePreorder { stx_to_burn, .. } => stx_to_burn,
+                        _ => 0,
+                    };
+                    (&tx.expected_sender, tx.cost, burned)
+                }
+                TransactionSpecification::EmulatedContractCall(_)
+                | TransactionSpecification::EmulatedContractPublish(_)
+                | TransactionSpecification::EmulatedEval(_)
+                | TransactionSpecification::BtcTransfer(_) => continue,
+            };
+            if let Some(drift) = drifts.get_mut(&sender.to_address()) {
+                drift.plan_costs += cost as u128;
+                drift.plan_transfers_out += amount_out as u128;
+            }
+        }
+    }
+
+    drifts
+        .into_values()
+        .map(|mut drift| {
+            drift.expected_balance = drift.genesis_balance as i128
+                - drift.plan_costs as i128
+                - drift.plan_transfers_out as i128
+                + drift.plan_transfers_in as i128;
+            drift
+        })
+        .collect()
+}
+
+/// Compares the expected balances with the ones of the node
+pub async fn get_balance_drifts(
+    deployment: &DeploymentSpecification,
+    accounts: &[AccountConfig],
+    fees_tracker: &FeesTracker,
+    node_rpc_url: &str,
+) -> Result<Vec<AccountDrift>, String> {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(5))
+        .build()
+        .map_err(|e| e.to_string())?;
+    let mut drifts = get_expected_balances(deployment, accounts);
+    for drift in drifts.iter_mut() {
+        let response: AccountResponse = get_json(
+            &client,
+            format!("{}/v2/accounts/{}?proof=0", node_rpc_url, drift.address),
+        )
+        .await?;
+        drift.actual_balance =
+            parse_hex_amount(&response.balance)? + parse_hex_amount(&response.locked)?;
+        drift.paid_fees = fees_tracker
+            .fees
+            .get(&drift.address)
+            .copied()
+            .unwrap_or_default();
+        let fees_outside_plan = drift.paid_fees.saturating_sub(drift.plan_costs);
+        drift.unexplained_drift =
+            drift.actual_balance as i128 - drift.expected_balance + fees_outside_plan as i128;
+    }
+    Ok(drifts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clarinet_deployments::types::{
+        AnchorMode, StxTransferSpecification, TransactionPlanSpecification,
+        TransactionsBatchSpecification,
+    };
+    use clarinet_files::StacksNetwork;
+
+    fn account(label: &str, address: &str) -> AccountConfig {
+        AccountConfig {
+            label: label.to_string(),
+            mnemonic: String::new(),
+            derivation: String::new(),
+            balance: 1_000_000,
+            stx_address: address.to_string(),
+            btc_address: String::new(),
+            is_mainnet: false,
+        }
+    }
+
+    #[test]
+    fn test_get_expected_balances() {
+        let deployer = "ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM";
+        let wallet = "ST1SJ3DTE5DN7X54YDH5D64R3BCB6A2AG2ZQ8YPD5";
+        let transfer = StxTransferSpecification {
+            expected_sender: PrincipalData::parse_standard_principal(deployer).unwrap(),
+            recipient: PrincipalData::parse(wallet).unwrap(),
+            mstx_amount: 5_000,
+            memo: [0; 34],
+            cost: 200,
+            anchor_mode: AnchorMode::OnChainOnly,
+            description: None,
+        };
+        let deployment = DeploymentSpecification {
+            id: 0,
+            name: "devnet".to_string(),
+            network: StacksNetwork::Devnet,
+            stacks_node: None,
+            bitcoin_node: None,
+            genesis: None,
+            plan: TransactionPlanSpecification {
+                batches: vec![TransactionsBatchSpecification {
+                    id: 0,
+                    transactions: vec![TransactionSpecification::StxTransfer(transfer)],
+                    epoch: None,
+                    post_checks: vec![],
+                    description: None,
+                }],
+                post_checks: vec![],
+            },
+            contracts: BTreeMap::new(),
+            description: None,
+        };
+        let accounts = vec![account("deployer", deployer), account("wallet_1", wallet)];
+        let drifts = get_expected_balances(&deployment, &accounts);
+        assert_eq!(drifts.len(), 2);
+        let deployer_drift = drifts.iter().find(|d| d.label == "deployer").unwrap();
+        assert_eq!(deployer_drift.plan_costs, 200);
+        assert_eq!(deployer_drift.expected_balance, 1_000_000 - 5_200);
+        let wallet_drift = drifts.iter().find(|d| d.label == "wallet_1").unwrap();
+        assert_eq!(wallet_drift.expected_balance, 1_005_000);
+    }
+}
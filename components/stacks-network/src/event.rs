@@ -7,8 +7,12 @@ use chainhook_sdk::{
 use hiro_system_kit::slog;
 
 use crate::{
+    accounts::AccountActivity,
+    boot::BootReport,
     chains_coordinator::BitcoinMiningCommand,
     log::{LogData, LogLevel},
+    signers::SignerStatus,
+    webhooks::WebhookDelivery,
 };
 
 #[allow(dead_code)]
@@ -19,10 +23,13 @@ pub enum DevnetEvent {
     Tick,
     ServiceStatus(ServiceStatusData),
     ProtocolDeployingProgress(ProtocolDeployingData),
-    BootCompleted(Sender<BitcoinMiningCommand>),
+    BootCompleted(Sender<BitcoinMiningCommand>, BootReport),
     StacksChainEvent(StacksChainEvent),
     BitcoinChainEvent(BitcoinChainEvent),
     MempoolAdmission(MempoolAdmissionData),
+    SignersStatus(Vec<SignerStatus>),
+    AccountsStatus(Vec<AccountActivity>),
+    WebhookDelivered(WebhookDelivery),
     FatalError(String),
     Terminate,
 }
@@ -128,7 +135,35 @@ pub struct ServiceStatusData {
     pub comment: String,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeployingStatus {
+    Encoded,
+    Broadcasted,
+    Confirmed,
+    Failed,
+}
+
+impl std::fmt::Display for DeployingStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            DeployingStatus::Encoded => write!(f, "encoded"),
+            DeployingStatus::Broadcasted => write!(f, "broadcasted"),
+            DeployingStatus::Confirmed => write!(f, "confirmed"),
+            DeployingStatus::Failed => write!(f, "failed"),
+        }
+    }
+}
+
+/// Update of a transaction of the project deployment, with the progress of the deployment
+#[derive(Clone, Debug, Serialize)]
 pub struct ProtocolDeployingData {
-    pub new_contracts_deployed: Vec<String>,
+    /// Position of the transaction in the deployment plan
+    pub index: usize,
+    pub name: String,
+    pub status: DeployingStatus,
+    pub txid: Option<String>,
+    pub error: Option<String>,
+    pub confirmed: usize,
+    pub total: usize,
 }
@@ -0,0 +1,339 @@
+//! Images of the devnet services pinned to a digest (`[devnet.image_digests]`), so that a
+//! mutable tag such as `latest` moving under a CI run can't change the devnet. The digests
+//! the images resolved to are recorded in the metadata of the run.
+//!
+//! `clarinet devnet pull` fetches the images ahead of `devnet start`, in parallel: a failed pull
+//! is retried, and since docker keeps the layers already downloaded, a retry (or running the
+//! command again) resumes where the previous attempt stopped.
+
+use std::collections::BTreeMap;
+use std::sync::mpsc::Sender;
+use std::time::Duration;
+
+use bollard::image::CreateImageOptions;
+use bollard::models::CreateImageInfo;
+use bollard::Docker;
+use clarinet_files::{DevnetConfig, DevnetService};
+use futures::future::join_all;
+use futures::stream::StreamExt;
+
+use crate::orchestrator::connect_docker;
+
+/// Attempts of a pull before giving up on an image
+pub const DEFAULT_PULL_ATTEMPTS: u32 = 3;
+
+/// Image reference to pull and run. A pinned digest replaces the tag or the digest of the url.
+pub fn get_image_reference(image_url: &str, digest: Option<&str>) -> String {
+    let Some(digest) = digest else {
+        return image_url.to_string();
+    };
+    let name = image_url
+        .split_once('@')
+        .map_or(image_url, |(name, _)| name);
+    // the colon of a registry port (`localhost:5000/image`) is not a tag
+    let name = match name.rsplit_once(':') {
+        Some((repository, tag)) if !tag.contains('/') => repository,
+        _ => name,
+    };
+    format!("{}@{}", name, digest)
+}
+
+/// Digest an image reference is pinned to, if any
+pub fn get_expected_digest(reference: &str) -> Option<&str> {
+    reference.split_once('@').map(|(_, digest)| digest)
+}
+
+/// Checks the repo digests of a pulled image (`<repository>@sha256:<digest>`) against the
+/// digest of its reference, returns the digest it resolved to
+pub fn check_repo_digests(reference: &str, repo_digests: &[String]) -> Result<String, String> {
+    let digests: Vec<&str> = repo_digests
+        .iter()
+        .filter_map(|repo_digest| get_expected_digest(repo_digest))
+        .collect();
+    match get_expected_digest(reference) {
+        Some(expected) if digests.contains(&expected) => Ok(expected.to_string()),
+        Some(expected) => Err(format!(
+            "the image {} doesn't match its digest {} (resolved to {})",
+            reference,
+            expected,
+            if digests.is_empty() {
+                "no digest".to_string()
+            } else {
+                digests.join(", ")
+            }
+        )),
+        // images built locally have no repo digest
+        None => Ok(digests.first().copied().unwrap_or("local").to_string()),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ImagePullStatus {
+    /// Bytes downloaded and to download, for the layers known so far
+    Downloading {
+        current: u64,
+        total: u64,
+    },
+    /// The attempt failed, the pull is resumed after a delay
+    Retrying {
+        attempt: u32,
+        error: String,
+    },
+    /// Pulled and verified, with the digest the image resolved to
+    Pulled(String),
+    Failed(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImagePullEvent {
+    pub service: DevnetService,
+    pub reference: String,
+    pub status: ImagePullStatus,
+}
+
+/// Progress of the layers of a pull, from the messages of the docker daemon
+#[derive(Debug, Default)]
+pub struct PullProgress {
+    /// (downloaded, size, done) by layer
+    layers: BTreeMap<String, (u64, u64, bool)>,
+}
+
+impl PullProgress {
+    pub fn update(&mut self, info: &CreateImageInfo) {
+        let (Some(layer), Some(status)) = (&info.id, &info.status) else {
+            return;
+        };
+        let entry = self.layers.entry(layer.clone()).or_default();
+        match status.as_str() {
+            "Downloading" => {
+                let detail = info.progress_detail.as_ref();
+                let current = detail.and_then(|detail| detail.current).unwrap_or(0);
+                let total = detail.and_then(|detail| detail.total).unwrap_or(0);
+                entry.0 = current.max(0) as u64;
+                entry.1 = total.max(0) as u64;
+            }
+            // the extraction reports its own progress, the download is over at that point
+            "Verifying Checksum" | "Download complete" | "Extracting" | "Pull complete"
+            | "Already exists" => entry.2 = true,
+            _ => {}
+        }
+    }
+
+    /// Bytes downloaded and to download
+    pub fn get_totals(&self) -> (u64, u64) {
+        self.layers
+            .values()
+            .fold((0, 0), |(current, total), (downloaded, size, done)| {
+                let downloaded = if *done { *size } else { *downloaded };
+                (current + downloaded, total + size)
+            })
+    }
+}
+
+/// A progress bar of `width` characters, e.g. `[#####-----]  50% 12.0/24.0 MB`
+pub fn format_progress_bar(current: u64, total: u64, width: usize) -> String {
+    let ratio = if total == 0 {
+        0.0
+    } else {
+        (current as f64 / total as f64).min(1.0)
+    };
+    let filled = (ratio * width as f64).round() as usize;
+    format!(
+        "[{}{}] {:>3}% {:.1}/{:.1} MB",
+        "#".repeat(filled),
+        "-".repeat(width - filled),
+        (ratio * 100.0).round(),
+        current as f64 / 1e6,
+        total as f64 / 1e6
+    )
+}
+
+async fn create_image(
+    docker: &Docker,
+    reference: &str,
+    platform: &str,
+    on_progress: &dyn Fn(u64, u64),
+) -> Result<(), String> {
+    let mut stream = Box::pin(docker.create_image(
+        Some(CreateImageOptions {
+            from_image: reference.to_string(),
+            platform: platform.to_string(),
+            ..Default::default()
+        }),
+        None,
+        None,
+    ));
+    let mut progress = PullProgress::default();
+    while let Some(info) = stream.next().await {
+        let info = info.map_err(|e| e.to_string())?;
+        if let Some(error) = info.error {
+            return Err(error);
+        }
+        progress.update(&info);
+        let (current, total) = progress.get_totals();
+        on_progress(current, total);
+    }
+    Ok(())
+}
+
+/// Pulls the image of a service, resuming a failed pull up to `max_attempts` times, and checks
+/// it against its pinned digest
+async fn pull_service_image(
+    docker: &Docker,
+    devnet_config: &DevnetConfig,
+    service: DevnetService,
+    max_attempts: u32,
+    events_tx: &Sender<ImagePullEvent>,
+) -> Result<String, String> {
+    let reference = get_image_reference(
+        devnet_config.get_image_url(service),
+        devnet_config.get_image_digest(service),
+    );
+    let platform = devnet_config.get_resources(service).platform;
+    let send = |status| {
+        let _ = events_tx.send(ImagePullEvent {
+            service,
+            reference: reference.clone(),
+            status,
+        });
+    };
+    let on_progress = |current, total| send(ImagePullStatus::Downloading { current, total });
+
+    let mut attempt = 1;
+    let result = loop {
+        match create_image(docker, &reference, &platform, &on_progress).await {
+            Err(error) if attempt < max_attempts => {
+                send(ImagePullStatus::Retrying { attempt, error });
+                tokio::time::sleep(Duration::from_secs(2u64.pow(attempt))).await;
+                attempt += 1;
+            }
+            result => break result,
+        }
+    };
+    // a digest mismatch won't be fixed by pulling again
+    let result = match result {
+        Ok(()) => match docker.inspect_image(&reference).await {
+            Ok(image) => check_repo_digests(&reference, &image.repo_digests.unwrap_or_default()),
+            Err(e) => Err(format!("unable to inspect the image: {}", e)),
+        },
+        Err(e) => Err(e),
+    };
+    match result {
+        Ok(digest) => {
+            send(ImagePullStatus::Pulled(digest.clone()));
+            Ok(digest)
+        }
+        Err(e) => {
+            send(ImagePullStatus::Failed(e.clone()));
+            Err(format!("{}: {}", service, e))
+        }
+    }
+}
+
+/// Pulls the images of the services of the devnet in parallel, returns the digest each image
+/// resolved to. The progress of each pull is sent to `events_tx`.
+pub async fn pull_devnet_images(
+    devnet_config: &DevnetConfig,
+    max_attempts: u32,
+    events_tx: Sender<ImagePullEvent>,
+) -> Result<Vec<(DevnetService, String)>, String> {
+    let docker = connect_docker(&devnet_config.docker_host)?;
+    let services = devnet_config.get_services();
+    let results = join_all(services.iter().map(|service| {
+        pull_service_image(&docker, devnet_config, *service, max_attempts, &events_tx)
+    }))
+    .await;
+
+    let mut digests = vec![];
+    let mut errors = vec![];
+    for (service, result) in services.into_iter().zip(results) {
+        match result {
+            Ok(digest) => digests.push((service, digest)),
+            Err(e) => errors.push(e),
+        }
+    }
+    if !errors.is_empty() {
+        return Err(format!(
+            "unable to pull {} of {} images, run the command again to resume:\n{}",
+            errors.len(),
+            digests.len() + errors.len(),
+            errors.join("\n")
+        ));
+    }
+    Ok(digests)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIGEST: &str = "sha256:5f70bf18a086007016e948b04aed3b82103a36bea41755b6cddfaf10ace3c6ef";
+
+    fn get_info(layer: &str, status: &str, progress: Option<(i64, i64)>) -> CreateImageInfo {
+        CreateImageInfo {
+            id: Some(layer.to_string()),
+            status: Some(status.to_string()),
+            progress_detail: progress.map(|(current, total)| bollard::models::ProgressDetail {
+                current: Some(current),
+                total: Some(total),
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_pull_progress() {
+        let mut progress = PullProgress::default();
+        progress.update(&get_info("a", "Pulling fs layer", None));
+        progress.update(&get_info("b", "Already exists", None));
+        progress.update(&get_info("a", "Downloading", Some((2_000_000, 8_000_000))));
+        progress.update(&get_info("c", "Downloading", Some((1_000_000, 2_000_000))));
+        assert_eq!(progress.get_totals(), (3_000_000, 10_000_000));
+        progress.update(&get_info("c", "Download complete", None));
+        progress.update(&get_info("c", "Extracting", Some((5, 10))));
+        assert_eq!(progress.get_totals(), (4_000_000, 10_000_000));
+
+        assert_eq!(
+            format_progress_bar(4_000_000, 10_000_000, 10),
+            "[####------]  40% 4.0/10.0 MB"
+        );
+        assert_eq!(format_progress_bar(0, 0, 4), "[----]   0% 0.0/0.0 MB");
+    }
+
+    #[test]
+    fn test_pinned_images() {
+        assert_eq!(
+            get_image_reference("hirosystems/stacks-api:latest", None),
+            "hirosystems/stacks-api:latest"
+        );
+        assert_eq!(
+            get_image_reference("hirosystems/stacks-api:latest", Some(DIGEST)),
+            format!("hirosystems/stacks-api@{}", DIGEST)
+        );
+        assert_eq!(
+            get_image_reference("localhost:5000/stacks-node", Some(DIGEST)),
+            format!("localhost:5000/stacks-node@{}", DIGEST)
+        );
+        assert_eq!(
+            get_image_reference("postgres@sha256:0000", Some(DIGEST)),
+            format!("postgres@{}", DIGEST)
+        );
+
+        let reference = get_image_reference("postgres:alpine", Some(DIGEST));
+        let repo_digests = vec![format!("postgres@{}", DIGEST)];
+        assert_eq!(
+            check_repo_digests(&reference, &repo_digests),
+            Ok(DIGEST.to_string())
+        );
+        assert!(check_repo_digests(&reference, &["postgres@sha256:0000".to_string()]).is_err());
+        assert!(check_repo_digests(&reference, &[]).is_err());
+        assert_eq!(
+            check_repo_digests("postgres:alpine", &repo_digests),
+            Ok(DIGEST.to_string())
+        );
+        assert_eq!(
+            check_repo_digests("stacks-node:local", &[]),
+            Ok("local".to_string())
+        );
+    }
+}
@@ -0,0 +1,147 @@
+//! Newline-delimited JSON events of `clarinet devnet start --json`, written on stdout for the
+//! scripts supervising a devnet: one object per line, with an `event` field naming its type
+//! (`log`, `service_status`, `bitcoin_block`, `stacks_block`, `deployment_progress`, `ready`,
+//! `fatal` or `terminated`).
+
+use std::collections::BTreeMap;
+
+use chainhook_sdk::types::{BitcoinChainEvent, StacksChainEvent};
+use serde_json::{json, Value};
+
+use crate::event::{DevnetEvent, Status};
+use crate::log::LogLevel;
+
+fn get_level(level: &LogLevel) -> &'static str {
+    match level {
+        LogLevel::Error => "error",
+        LogLevel::Warning => "warning",
+        LogLevel::Info => "info",
+        LogLevel::Success => "success",
+        LogLevel::Debug => "debug",
+    }
+}
+
+/// JSON event of a devnet event, `None` for the events of the dashboard only (key strokes,
+/// ticks, mempool and signers updates, ...)
+pub fn get_json_event(event: &DevnetEvent) -> Option<Value> {
+    let event = match event {
+        DevnetEvent::Log(log) => json!({
+            "event": "log",
+            "level": get_level(&log.level),
+            "message": log.message,
+        }),
+        DevnetEvent::ServiceStatus(status) => json!({
+            "event": "service_status",
+            "service": status.name,
+            "status": match status.status {
+                Status::Red => "red",
+                Status::Yellow => "yellow",
+                Status::Green => "green",
+            },
+            "comment": status.comment,
+        }),
+        DevnetEvent::BitcoinChainEvent(BitcoinChainEvent::ChainUpdatedWithBlocks(update)) => {
+            let tip = update.new_blocks.last()?;
+            json!({
+                "event": "bitcoin_block",
+                "height": tip.block_identifier.index,
+                "hash": tip.block_identifier.hash,
+            })
+        }
+        DevnetEvent::StacksChainEvent(StacksChainEvent::ChainUpdatedWithBlocks(update)) => {
+            let tip = &update.new_blocks.last()?.block;
+            json!({
+                "event": "stacks_block",
+                "height": tip.block_identifier.index,
+                "hash": tip.block_identifier.hash,
+                "bitcoin_height": tip.metadata.bitcoin_anchor_block_identifier.index,
+                "transactions": tip.transactions.len(),
+            })
+        }
+        DevnetEvent::ProtocolDeployingProgress(progress) => {
+            let mut event = json!(progress);
+            event["event"] = json!("deployment_progress");
+            event
+        }
+        DevnetEvent::BootCompleted(_, report) => {
+            let phases: BTreeMap<&str, f64> = report
+                .phases
+                .iter()
+                .map(|(phase, duration)| (phase.setting(), duration.as_secs_f64()))
+                .collect();
+            json!({
+                "event": "ready",
+                "boot_duration": report.total.as_secs_f64(),
+                "phases": phases,
+            })
+        }
+        DevnetEvent::FatalError(message) => json!({
+            "event": "fatal",
+            "message": message,
+        }),
+        DevnetEvent::Terminate => json!({ "event": "terminated" }),
+        _ => return None,
+    };
+    Some(event)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::boot::{BootPhase, BootReport};
+    use crate::event::{DeployingStatus, ProtocolDeployingData, ServiceStatusData};
+
+    #[test]
+    fn test_get_json_event() {
+        let status = DevnetEvent::ServiceStatus(ServiceStatusData {
+            order: 1,
+            status: Status::Green,
+            name: "stacks-node".to_string(),
+            comment: "mining blocks".to_string(),
+        });
+        assert_eq!(
+            get_json_event(&status),
+            Some(json!({
+                "event": "service_status",
+                "service": "stacks-node",
+                "status": "green",
+                "comment": "mining blocks",
+            }))
+        );
+
+        let progress = DevnetEvent::ProtocolDeployingProgress(ProtocolDeployingData {
+            index: 0,
+            name: "Publish ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM.counter".to_string(),
+            status: DeployingStatus::Confirmed,
+            txid: Some("0x01".to_string()),
+            error: None,
+            confirmed: 1,
+            total: 2,
+        });
+        let event = get_json_event(&progress).unwrap();
+        assert_eq!(event["event"], "deployment_progress");
+        assert_eq!(event["status"], "confirmed");
+        assert_eq!(event["confirmed"], 1);
+
+        let (mining_tx, _) = std::sync::mpsc::channel();
+        let report = BootReport {
+            total: Duration::from_secs(42),
+            phases: BTreeMap::from([(BootPhase::StacksGenesis, Duration::from_secs(12))]),
+        };
+        assert_eq!(
+            get_json_event(&DevnetEvent::BootCompleted(mining_tx, report)),
+            Some(json!({
+                "event": "ready",
+                "boot_duration": 42.0,
+                "phases": { "stacks_genesis": 12.0 },
+            }))
+        );
+        assert_eq!(
+            get_json_event(&DevnetEvent::FatalError("unable to start".to_string())),
+            Some(json!({ "event": "fatal", "message": "unable to start" }))
+        );
+        assert_eq!(get_json_event(&DevnetEvent::Tick), None);
+    }
+}
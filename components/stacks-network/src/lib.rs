@@ -3,29 +3,56 @@ extern crate serde;
 #[macro_use]
 extern crate serde_derive;
 
+pub mod accounts;
+pub mod api_health;
+pub mod boot;
 mod chainhooks;
 pub mod chains_coordinator;
+pub mod cluster_access;
+pub mod contracts_server;
+pub mod coordinator_state;
+pub mod deployment_progress;
+pub mod drift;
 mod event;
+mod images;
+mod json_events;
 mod log;
 mod orchestrator;
+pub mod ports;
+pub mod pox;
+pub mod recorder;
+pub mod registry;
+pub mod service_logs;
+pub mod signers;
+pub mod soak;
+pub mod tenants;
 mod ui;
+pub mod webhooks;
 
 pub use chainhook_sdk::observer::MempoolAdmissionData;
 pub use chainhook_sdk::{self, utils::Context};
 use chainhook_sdk::{chainhooks::types::ChainhookStore, observer::ObserverCommand};
-pub use chainhooks::{load_chainhooks, parse_chainhook_full_specification};
+pub use chainhooks::{
+    evaluate_predicate_on_recorded_blocks, load_chainhooks, load_stacks_predicate,
+    parse_chainhook_full_specification, read_recorded_blocks, simulate_predicates_load,
+    PredicateLoadReport, PredicateOccurrence, BROAD_PREDICATE_MATCH_RATE,
+};
 use chains_coordinator::BitcoinMiningCommand;
 use clarinet_files::NetworkManifest;
-pub use event::DevnetEvent;
+pub use event::{DeployingStatus, DevnetEvent, ProtocolDeployingData};
+pub use images::{
+    format_progress_bar, pull_devnet_images, ImagePullEvent, ImagePullStatus, DEFAULT_PULL_ATTEMPTS,
+};
+use json_events::get_json_event;
 pub use log::{LogData, LogLevel};
-pub use orchestrator::DevnetOrchestrator;
-use orchestrator::ServicesMapHosts;
+pub use orchestrator::{DevnetOrchestrator, ServicesMapHosts, ShutdownOptions, ShutdownReport};
 use std::{
     sync::mpsc::{self, channel, Receiver, Sender},
     thread::sleep,
     time::Duration,
 };
 
+use boot::{start_boot_watchdog, BootTracker};
 use chains_coordinator::start_chains_coordinator;
 use clarinet_deployments::types::DeploymentSpecification;
 use hiro_system_kit::slog;
@@ -35,6 +62,12 @@ use self::chains_coordinator::DevnetEventObserverConfig;
 #[derive(Debug)]
 pub enum ChainsCoordinatorCommand {
     Terminate,
+    /// Query the state of the devnet signers
+    GetSignersStatus(crossbeam_channel::Sender<Vec<signers::SignerStatus>>),
+    /// Query a summary of the current PoX cycle and reward set
+    GetPoxSummary(crossbeam_channel::Sender<Result<pox::PoxSummary, String>>),
+    /// Query the webhooks received since the start of the devnet (`enable_webhook_receiver`)
+    GetWebhookDeliveries(crossbeam_channel::Sender<Vec<webhooks::WebhookDelivery>>),
 }
 
 pub fn block_on<F, R>(future: F) -> R
@@ -51,6 +84,7 @@ async fn do_run_devnet(
     chainhooks: &mut Option<ChainhookStore>,
     log_tx: Option<Sender<LogData>>,
     display_dashboard: bool,
+    json_output: bool,
     ctx: Context,
     orchestrator_terminated_tx: Sender<bool>,
     orchestrator_terminated_rx: Option<Receiver<bool>>,
@@ -101,7 +135,7 @@ async fn do_run_devnet(
         _ => ChainhookStore::new(),
     };
     let devnet_path = devnet_config.working_dir.clone();
-    let config = DevnetEventObserverConfig::new(
+    let mut config = DevnetEventObserverConfig::new(
         devnet_config.clone(),
         devnet.manifest.clone(),
         network_manifest,
@@ -111,6 +145,17 @@ async fn do_run_devnet(
         ip_address_setup,
     );
 
+    let boot_tracker = BootTracker::default();
+    config.boot_tracker = boot_tracker.clone();
+    devnet.boot_tracker = boot_tracker.clone();
+    let coordinator_handle = devnet.coordinator_handle.clone();
+    config.coordinator_handle = coordinator_handle.clone();
+    start_boot_watchdog(
+        boot_tracker,
+        devnet_config.boot_timeouts.clone(),
+        devnet_events_tx.clone(),
+    );
+
     let chains_coordinator_tx = devnet_events_tx.clone();
     let (chains_coordinator_commands_tx, chains_coordinator_commands_rx) =
         crossbeam_channel::unbounded();
@@ -180,6 +225,7 @@ async fn do_run_devnet(
             devnet_events_tx,
             devnet_events_rx,
             moved_chains_coordinator_commands_tx,
+            coordinator_handle,
             orchestrator_terminated_rx.expect(
                 "orchestrator_terminated_rx should be provided when display_dashboard set to true",
             ),
@@ -214,7 +260,13 @@ async fn do_run_devnet(
 
         if log_tx.is_none() {
             loop {
-                match devnet_events_rx.recv() {
+                let event = devnet_events_rx.recv();
+                if json_output {
+                    if let Some(json_event) = event.as_ref().ok().and_then(get_json_event) {
+                        println!("{}", json_event);
+                    }
+                }
+                match event {
                     Ok(DevnetEvent::Log(log)) => {
                         if let Some(ref log_tx) = log_tx {
                             let _ = log_tx.send(log.clone());
@@ -235,11 +287,26 @@ async fn do_run_devnet(
                             }
                         }
                     }
-                    Ok(DevnetEvent::BootCompleted(bitcoin_mining_tx)) => {
+                    Ok(DevnetEvent::BootCompleted(bitcoin_mining_tx, boot_report)) => {
+                        ctx.try_log(|logger| slog::info!(logger, "Devnet ready, {}", boot_report));
                         if !devnet_config.bitcoin_controller_automining_disabled {
                             let _ = bitcoin_mining_tx.send(BitcoinMiningCommand::Start);
                         }
                     }
+                    Ok(DevnetEvent::ProtocolDeployingProgress(progress)) => {
+                        if progress.status != DeployingStatus::Encoded {
+                            ctx.try_log(|logger| {
+                                slog::info!(
+                                    logger,
+                                    "Deployment {}/{}: {} {}",
+                                    progress.confirmed,
+                                    progress.total,
+                                    progress.name,
+                                    progress.status
+                                )
+                            })
+                        }
+                    }
                     Ok(DevnetEvent::FatalError(e)) => return Err(e),
                     Ok(DevnetEvent::Terminate) => return Ok((None, None, None)),
                     _ => {}
@@ -281,6 +348,7 @@ pub async fn do_run_chain_coordinator(
         chainhooks,
         log_tx,
         false,
+        false,
         ctx,
         orchestrator_terminated_tx,
         None,
@@ -297,6 +365,7 @@ pub async fn do_run_local_devnet(
     chainhooks: &mut Option<ChainhookStore>,
     log_tx: Option<Sender<LogData>>,
     display_dashboard: bool,
+    json_output: bool,
     ctx: Context,
     orchestrator_terminated_tx: Sender<bool>,
     orchestrator_terminated_rx: Option<Receiver<bool>>,
@@ -315,6 +384,7 @@ pub async fn do_run_local_devnet(
         chainhooks,
         log_tx,
         display_dashboard,
+        json_output,
         ctx,
         orchestrator_terminated_tx,
         orchestrator_terminated_rx,
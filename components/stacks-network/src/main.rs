@@ -28,6 +28,9 @@ struct Args {
     /// Path of the project's root
     #[clap(short, long)]
     project_root_path: Option<String>,
+    /// Network id of the devnet, unique among the devnets of the cluster
+    #[clap(long)]
+    network_id: Option<u16>,
 }
 
 fn main() {
@@ -47,9 +50,12 @@ fn main() {
         .read_content()
         .unwrap_or_else(|e| panic!("failed to read network manifest data {:?}", e));
 
-    let network_manifest: NetworkManifest =
+    let mut network_manifest: NetworkManifest =
         serde_yaml::from_slice(&network_manifest_file_content[..])
             .unwrap_or_else(|e| panic!("Devnet.toml file malformatted {:?}", e));
+    if let (Some(network_id), Some(devnet)) = (args.network_id, network_manifest.devnet.as_mut()) {
+        devnet.network_id = Some(network_id);
+    }
 
     let orchestrator =
         DevnetOrchestrator::new(manifest, Some(network_manifest.clone()), None, false, false)
@@ -1,6 +1,6 @@
 use bollard::container::{
     Config, CreateContainerOptions, KillContainerOptions, ListContainersOptions,
-    PruneContainersOptions, WaitContainerOptions,
+    PruneContainersOptions, RemoveContainerOptions, StopContainerOptions, WaitContainerOptions,
 };
 use bollard::errors::Error as DockerError;
 use bollard::exec::CreateExecOptions;
@@ -12,7 +12,9 @@ use bollard::Docker;
 use chainhook_sdk::bitcoin::hex::DisplayHex;
 use chainhook_sdk::utils::Context;
 use clarinet_files::StacksNetwork;
-use clarinet_files::{DevnetConfigFile, NetworkManifest, ProjectManifest};
+use clarinet_files::{
+    DevnetConfig, DevnetConfigFile, DevnetService, NetworkManifest, ProjectManifest,
+};
 use clarity::types::chainstate::StacksPrivateKey;
 use clarity::types::PrivateKey;
 use futures::stream::TryStreamExt;
@@ -23,10 +25,16 @@ use std::collections::HashMap;
 use std::fs::{self, File};
 use std::io::Write;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc::{Receiver, Sender};
 use std::time::Duration;
 
+use crate::boot::{BootPhase, BootTracker};
+use crate::coordinator_state::ChainsCoordinatorHandle;
 use crate::event::{send_status_update, DevnetEvent, Status};
+use crate::images::{check_repo_digests, get_image_reference};
+use crate::ports::{allocate_free_ports, PortReassignment};
+use crate::registry::{self, DevnetRunMetadata};
 
 #[derive(Debug)]
 pub struct DevnetOrchestrator {
@@ -48,8 +56,66 @@ pub struct DevnetOrchestrator {
     subnet_api_container_id: Option<String>,
     docker_client: Option<Docker>,
     services_map_hosts: Option<ServicesMapHosts>,
+    port_reassignments: Vec<PortReassignment>,
+    pub boot_tracker: BootTracker,
+    pub coordinator_handle: ChainsCoordinatorHandle,
+    network_created: bool,
+    is_shut_down: AtomicBool,
 }
+
+/// How a devnet is torn down by [`DevnetOrchestrator::shutdown`]
 #[derive(Clone, Debug)]
+pub struct ShutdownOptions {
+    /// Remove the anonymous volumes of the containers along with them
+    pub remove_volumes: bool,
+    /// Remove the working directory of the devnet (logs, configurations and chainstates)
+    pub remove_working_dir: bool,
+    /// Time given to each container to stop before it is killed, `Duration::ZERO` to kill
+    /// them right away
+    pub timeout: Duration,
+}
+
+impl Default for ShutdownOptions {
+    fn default() -> Self {
+        ShutdownOptions {
+            remove_volumes: false,
+            remove_working_dir: false,
+            timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct ShutdownReport {
+    /// Containers stopped and removed
+    pub removed_containers: Vec<String>,
+    pub removed_network: bool,
+    pub removed_working_dir: bool,
+    /// `true` when a previous call already shut the devnet down, nothing was done
+    pub already_shut_down: bool,
+    /// Resources that could not be removed
+    pub errors: Vec<String>,
+}
+
+impl Drop for DevnetOrchestrator {
+    fn drop(&mut self) {
+        if self.is_shut_down.load(Ordering::SeqCst) || !self.has_docker_resources() {
+            return;
+        }
+        let options = ShutdownOptions {
+            timeout: Duration::ZERO,
+            ..Default::default()
+        };
+        // on its own thread, the orchestrator can be dropped from within a runtime
+        std::thread::scope(|scope| {
+            let _ = scope
+                .spawn(|| hiro_system_kit::create_basic_runtime().block_on(self.shutdown(&options)))
+                .join();
+        });
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct ServicesMapHosts {
     pub bitcoin_node_host: String,
     pub stacks_node_host: String,
@@ -98,6 +164,20 @@ impl DevnetOrchestrator {
             devnet.working_dir = format!("{}", devnet_path.display());
         }
 
+        let port_reassignments = match network_config.devnet {
+            Some(ref mut devnet) if devnet.auto_ports => allocate_free_ports(devnet)?,
+            _ => vec![],
+        };
+        if let Some(ref devnet) = network_config.devnet {
+            let errors = devnet.validate();
+            if !errors.is_empty() {
+                return Err(format!(
+                    "invalid devnet settings:\n- {}",
+                    errors.join("\n- ")
+                ));
+            }
+        }
+
         let name = manifest.project.name.to_string();
         let mut network_name = name.clone();
         if let Some(ref devnet) = network_config.devnet {
@@ -111,28 +191,7 @@ impl DevnetOrchestrator {
 
         let docker_client = match should_use_docker {
             true => match network_config.devnet {
-                Some(ref devnet) => {
-                    let client = Docker::connect_with_socket(
-                        &devnet.docker_host,
-                        120,
-                        bollard::API_DEFAULT_VERSION,
-                    )
-                    .or_else(|_| Docker::connect_with_socket_defaults())
-                    .or_else(|_| {
-                        let mut user_space_docker_socket =
-                            dirs::home_dir().expect("unable to retrieve homedir");
-                        user_space_docker_socket.push(".docker");
-                        user_space_docker_socket.push("run");
-                        user_space_docker_socket.push("docker.sock");
-                        Docker::connect_with_socket(
-                            user_space_docker_socket.to_str().unwrap(),
-                            120,
-                            bollard::API_DEFAULT_VERSION,
-                        )
-                    })
-                    .map_err(|e| format!("unable to connect to docker: {:?}", e))?;
-                    Some(client)
-                }
+                Some(ref devnet) => Some(connect_docker(&devnet.docker_host)?),
                 None => unreachable!(),
             },
             false => None,
@@ -165,6 +224,11 @@ impl DevnetOrchestrator {
             subnet_node_container_id: None,
             subnet_api_container_id: None,
             services_map_hosts: None,
+            port_reassignments,
+            boot_tracker: BootTracker::default(),
+            coordinator_handle: ChainsCoordinatorHandle::default(),
+            network_created: false,
+            is_shut_down: AtomicBool::new(false),
         })
     }
 
@@ -179,10 +243,10 @@ impl DevnetOrchestrator {
             stacks_node_host: format!("stacks-blockchain.{namespace}.svc.cluster.local:20443"),
             postgres_host: format!("stacks-blockchain-api.{namespace}.svc.cluster.local:5432"),
             stacks_api_host: format!("stacks-blockchain-api.{namespace}.svc.cluster.local:3999"),
-            stacks_explorer_host: "localhost".into(), // todo (micaiah)
+            stacks_explorer_host: format!("stacks-explorer.{namespace}.svc.cluster.local:3000"),
             bitcoin_explorer_host: "localhost".into(), // todo (micaiah)
-            subnet_node_host: "localhost".into(),     // todo (micaiah)
-            subnet_api_host: "localhost".into(),      // todo (micaiah)
+            subnet_node_host: "localhost".into(),      // todo (micaiah)
+            subnet_api_host: "localhost".into(),       // todo (micaiah)
         };
 
         self.services_map_hosts = Some(services_map_hosts.clone());
@@ -233,6 +297,7 @@ impl DevnetOrchestrator {
             })?
             .id
             .ok_or("unable to retrieve network_id")?;
+        self.network_created = true;
 
         let res = docker
             .inspect_network::<&str>(&network_id, None)
@@ -315,6 +380,21 @@ impl DevnetOrchestrator {
         let _ = fs::create_dir(format!("{}/conf", devnet_config.working_dir));
         let _ = fs::create_dir(format!("{}/data", devnet_config.working_dir));
 
+        for reassignment in self.port_reassignments.iter() {
+            let _ = event_tx.send(DevnetEvent::info(format!(
+                "Port {} unavailable, {} set to {}",
+                reassignment.configured_port, reassignment.setting, reassignment.port
+            )));
+        }
+        let metadata =
+            DevnetRunMetadata::new(&self.network_name, &self.manifest.location, devnet_config);
+        if let Err(e) = metadata
+            .write()
+            .and_then(|_| registry::register_instance(&metadata))
+        {
+            let _ = event_tx.send(DevnetEvent::warning(e));
+        }
+
         let bitcoin_explorer_port = devnet_config.bitcoin_explorer_port;
         let stacks_explorer_port = devnet_config.stacks_explorer_port;
         let stacks_api_port = devnet_config.stacks_api_port;
@@ -428,9 +508,11 @@ impl DevnetOrchestrator {
             Status::Yellow,
             "booting",
         );
+        self.boot_tracker.enter(BootPhase::BitcoinReady);
         match self.boot_bitcoin_node_container().await {
             Ok(_) => {
                 self.initialize_bitcoin_node(&event_tx).await?;
+                self.boot_tracker.exit(BootPhase::BitcoinReady);
             }
             Err(message) => {
                 let _ = event_tx.send(DevnetEvent::FatalError(message.clone()));
@@ -582,7 +664,8 @@ impl DevnetOrchestrator {
             "booting",
         );
         match self.boot_stacks_node_container().await {
-            Ok(_) => {}
+            // exited by the chains coordinator, with the first Stacks block
+            Ok(_) => self.boot_tracker.enter(BootPhase::StacksGenesis),
             Err(message) => {
                 let _ = event_tx.send(DevnetEvent::FatalError(message.clone()));
                 self.kill(ctx, Some(&message)).await;
@@ -770,6 +853,7 @@ impl DevnetOrchestrator {
             },
             _ => return Err("unable to get Docker client".into()),
         };
+        let resources = devnet_config.get_resources(DevnetService::BitcoinNode);
 
         let mut port_bindings = HashMap::new();
         port_bindings.insert(
@@ -864,13 +948,15 @@ rpcport={bitcoin_node_rpc_port}
 
         let config = Config {
             labels: Some(labels),
-            image: Some(devnet_config.bitcoin_node_image_url.clone()),
+            image: Some(get_service_image(devnet_config, DevnetService::BitcoinNode)),
             // domainname: Some(self.network_name.to_string()),
             tty: None,
             exposed_ports: Some(exposed_ports),
             entrypoint: Some(vec![]),
             env: Some(env),
             host_config: Some(HostConfig {
+                nano_cpus: resources.get_nano_cpus(),
+                memory: resources.get_memory_bytes(),
                 auto_remove: Some(true),
                 binds: Some(binds),
                 network_mode: Some(self.network_name.clone()),
@@ -891,20 +977,22 @@ rpcport={bitcoin_node_rpc_port}
         Ok(config)
     }
 
-    pub async fn prepare_bitcoin_node_container(&mut self, ctx: &Context) -> Result<(), String> {
-        let (docker, devnet_config) = match (&self.docker_client, &self.network_config) {
-            (Some(ref docker), Some(ref network_config)) => match network_config.devnet {
-                Some(ref devnet_config) => (docker, devnet_config),
-                _ => return Err("unable to get devnet configuration".into()),
-            },
-            _ => return Err("unable to get Docker client".into()),
-        };
-
+    /// Pulls the image of a service, checks that it matches the digest it is pinned to and
+    /// records the digest it resolved to in the metadata of the run
+    async fn pull_image(
+        &self,
+        docker: &Docker,
+        devnet_config: &DevnetConfig,
+        service: DevnetService,
+        platform: &str,
+    ) -> Result<(), String> {
+        let reference = get_service_image(devnet_config, service);
+        self.boot_tracker.enter(BootPhase::ContainerPull);
         let _info = docker
             .create_image(
                 Some(CreateImageOptions {
-                    from_image: devnet_config.bitcoin_node_image_url.clone(),
-                    platform: devnet_config.docker_platform.clone(),
+                    from_image: reference.clone(),
+                    platform: platform.to_string(),
                     ..Default::default()
                 }),
                 None,
@@ -912,13 +1000,47 @@ rpcport={bitcoin_node_rpc_port}
             )
             .try_collect::<Vec<_>>()
             .await
-            .map_err(|e| formatted_docker_error("unable to create bitcoind image", e))?;
+            .map_err(|e| {
+                formatted_docker_error(&format!("unable to create {} image", service), e)
+            })?;
+        self.boot_tracker.exit(BootPhase::ContainerPull);
+
+        let image = docker.inspect_image(&reference).await.map_err(|e| {
+            formatted_docker_error(&format!("unable to inspect {} image", service), e)
+        })?;
+        let digest = check_repo_digests(&reference, &image.repo_digests.unwrap_or_default())
+            .map_err(|e| format!("{}: {}", service, e))?;
+        let _ = DevnetRunMetadata::record_image_digest(
+            &devnet_config.working_dir,
+            service.get_name(),
+            &digest,
+        );
+        Ok(())
+    }
+
+    pub async fn prepare_bitcoin_node_container(&mut self, ctx: &Context) -> Result<(), String> {
+        let (docker, devnet_config) = match (&self.docker_client, &self.network_config) {
+            (Some(ref docker), Some(ref network_config)) => match network_config.devnet {
+                Some(ref devnet_config) => (docker, devnet_config),
+                _ => return Err("unable to get devnet configuration".into()),
+            },
+            _ => return Err("unable to get Docker client".into()),
+        };
+        let resources = devnet_config.get_resources(DevnetService::BitcoinNode);
+
+        self.pull_image(
+            docker,
+            devnet_config,
+            DevnetService::BitcoinNode,
+            &resources.platform,
+        )
+        .await?;
 
         let config = self.prepare_bitcoin_node_config(1)?;
         let container_name = format!("bitcoin-node.{}", self.network_name);
         let options = CreateContainerOptions {
             name: container_name.as_str(),
-            platform: Some(&devnet_config.docker_platform),
+            platform: Some(&resources.platform),
         };
 
         let container = match docker
@@ -1014,6 +1136,7 @@ rpcport={bitcoin_node_rpc_port}
             },
             _ => return Err("unable to get Docker client".into()),
         };
+        let resources = devnet_config.get_resources(DevnetService::StacksNode);
 
         let mut port_bindings = HashMap::new();
         port_bindings.insert(
@@ -1175,6 +1298,10 @@ peer_port = {bitcoin_node_p2p_port}
             miner_wallet_name = devnet_config.miner_wallet_name,
         ));
 
+        if let Some(pox_2_activation) = devnet_config.pox_2_activation {
+            stacks_conf.push_str(&format!("pox_2_activation = {}\n", pox_2_activation));
+        }
+
         stacks_conf.push_str(&format!(
             r#"
 [[burnchain.epochs]]
@@ -1276,7 +1403,7 @@ start_height = {epoch_3_1}
 
         let config = Config {
             labels: Some(labels),
-            image: Some(devnet_config.stacks_node_image_url.clone()),
+            image: Some(get_service_image(devnet_config, DevnetService::StacksNode)),
             // domainname: Some(self.network_name.to_string()),
             tty: None,
             exposed_ports: Some(exposed_ports),
@@ -1288,6 +1415,8 @@ start_height = {epoch_3_1}
             ]),
             env: Some(env),
             host_config: Some(HostConfig {
+                nano_cpus: resources.get_nano_cpus(),
+                memory: resources.get_memory_bytes(),
                 auto_remove: Some(true),
                 binds: Some(binds),
                 network_mode: Some(self.network_name.clone()),
@@ -1313,26 +1442,21 @@ start_height = {epoch_3_1}
             },
             _ => return Err("unable to get Docker client".into()),
         };
+        let resources = devnet_config.get_resources(DevnetService::StacksNode);
 
-        let _info = docker
-            .create_image(
-                Some(CreateImageOptions {
-                    from_image: devnet_config.stacks_node_image_url.clone(),
-                    platform: devnet_config.docker_platform.clone(),
-                    ..Default::default()
-                }),
-                None,
-                None,
-            )
-            .try_collect::<Vec<_>>()
-            .await
-            .map_err(|e| format!("unable to create image: {}", e))?;
+        self.pull_image(
+            docker,
+            devnet_config,
+            DevnetService::StacksNode,
+            &resources.platform,
+        )
+        .await?;
 
         let config = self.prepare_stacks_node_config(boot_index)?;
 
         let options = CreateContainerOptions {
             name: format!("stacks-node.{}", self.network_name),
-            platform: Some(devnet_config.docker_platform.to_string()),
+            platform: Some(resources.platform.clone()),
         };
 
         let container = docker
@@ -1379,6 +1503,7 @@ start_height = {epoch_3_1}
             },
             _ => return Err("unable to initialize bitcoin node".to_string()),
         };
+        let resources = devnet_config.get_resources(DevnetService::StacksSigner);
 
         let signer_conf = format!(
             r#"
@@ -1430,7 +1555,10 @@ db_path = "stacks-signer-{signer_id}.sqlite"
 
         let config = Config {
             labels: Some(labels),
-            image: Some(devnet_config.stacks_signer_image_url.clone()),
+            image: Some(get_service_image(
+                devnet_config,
+                DevnetService::StacksSigner,
+            )),
             // domainname: Some(self.network_name.to_string()),
             tty: None,
             exposed_ports: None,
@@ -1442,6 +1570,8 @@ db_path = "stacks-signer-{signer_id}.sqlite"
             ]),
             env: Some(env),
             host_config: Some(HostConfig {
+                nano_cpus: resources.get_nano_cpus(),
+                memory: resources.get_memory_bytes(),
                 auto_remove: Some(true),
                 binds: Some(binds),
                 network_mode: Some(self.network_name.clone()),
@@ -1469,26 +1599,21 @@ db_path = "stacks-signer-{signer_id}.sqlite"
             },
             _ => return Err("unable to get Docker client".into()),
         };
+        let resources = devnet_config.get_resources(DevnetService::StacksSigner);
 
-        let _info = docker
-            .create_image(
-                Some(CreateImageOptions {
-                    from_image: devnet_config.stacks_signer_image_url.clone(),
-                    platform: devnet_config.docker_platform.clone(),
-                    ..Default::default()
-                }),
-                None,
-                None,
-            )
-            .try_collect::<Vec<_>>()
-            .await
-            .map_err(|e| format!("unable to create image: {}", e))?;
+        self.pull_image(
+            docker,
+            devnet_config,
+            DevnetService::StacksSigner,
+            &resources.platform,
+        )
+        .await?;
 
         let config = self.prepare_stacks_signer_config(boot_index, signer_id, signer_key)?;
 
         let options = CreateContainerOptions {
             name: format!("stacks-signer-{signer_id}.{}", self.network_name),
-            platform: Some(devnet_config.docker_platform.to_string()),
+            platform: Some(resources.platform.clone()),
         };
 
         let container = docker
@@ -1533,6 +1658,7 @@ db_path = "stacks-signer-{signer_id}.sqlite"
             },
             _ => return Err("unable to get Docker client".into()),
         };
+        let resources = devnet_config.get_resources(DevnetService::SubnetNode);
 
         let mut port_bindings = HashMap::new();
         port_bindings.insert(
@@ -1685,7 +1811,7 @@ events_keys = ["*"]
 
         let config = Config {
             labels: Some(labels),
-            image: Some(devnet_config.subnet_node_image_url.clone()),
+            image: Some(get_service_image(devnet_config, DevnetService::SubnetNode)),
             // domainname: Some(self.network_name.to_string()),
             tty: None,
             exposed_ports: Some(exposed_ports),
@@ -1696,6 +1822,8 @@ events_keys = ["*"]
             ]),
             env: Some(env),
             host_config: Some(HostConfig {
+                nano_cpus: resources.get_nano_cpus(),
+                memory: resources.get_memory_bytes(),
                 auto_remove: Some(true),
                 binds: Some(binds),
                 network_mode: Some(self.network_name.clone()),
@@ -1721,26 +1849,21 @@ events_keys = ["*"]
             },
             _ => return Err("unable to get Docker client".into()),
         };
+        let resources = devnet_config.get_resources(DevnetService::SubnetNode);
 
-        let _info = docker
-            .create_image(
-                Some(CreateImageOptions {
-                    from_image: devnet_config.subnet_node_image_url.clone(),
-                    platform: devnet_config.docker_platform.clone(),
-                    ..Default::default()
-                }),
-                None,
-                None,
-            )
-            .try_collect::<Vec<_>>()
-            .await
-            .map_err(|e| format!("unable to create image: {}", e))?;
+        self.pull_image(
+            docker,
+            devnet_config,
+            DevnetService::SubnetNode,
+            &resources.platform,
+        )
+        .await?;
 
         let config = self.prepare_subnet_node_config(boot_index)?;
 
         let options = CreateContainerOptions {
             name: format!("subnet-node.{}", self.network_name),
-            platform: Some(devnet_config.docker_platform.to_string()),
+            platform: Some(resources.platform.clone()),
         };
 
         let container = docker
@@ -1782,20 +1905,15 @@ events_keys = ["*"]
             },
             _ => return Err("unable to get Docker client".into()),
         };
+        let resources = devnet_config.get_resources(DevnetService::StacksApi);
 
-        let _info = docker
-            .create_image(
-                Some(CreateImageOptions {
-                    from_image: devnet_config.stacks_api_image_url.clone(),
-                    platform: devnet_config.docker_platform.clone(),
-                    ..Default::default()
-                }),
-                None,
-                None,
-            )
-            .try_collect::<Vec<_>>()
-            .await
-            .map_err(|e| format!("unable to create image: {}", e))?;
+        self.pull_image(
+            docker,
+            devnet_config,
+            DevnetService::StacksApi,
+            &resources.platform,
+        )
+        .await?;
 
         let mut port_bindings = HashMap::new();
         port_bindings.insert(
@@ -1843,16 +1961,21 @@ events_keys = ["*"]
             format!("FAUCET_PRIVATE_KEY={}", devnet_config.faucet_secret_key_hex),
             "NODE_ENV=development".to_string(),
         ];
+        if let Some(pool_max) = devnet_config.stacks_api_pg_connection_pool_max {
+            env.push(format!("PG_CONNECTION_POOL_MAX={}", pool_max));
+        }
         env.append(&mut devnet_config.stacks_api_env_vars.clone());
 
         let config = Config {
             labels: Some(labels),
-            image: Some(devnet_config.stacks_api_image_url.clone()),
+            image: Some(get_service_image(devnet_config, DevnetService::StacksApi)),
             // domainname: Some(self.network_name.to_string()),
             tty: None,
             exposed_ports: Some(exposed_ports),
             env: Some(env),
             host_config: Some(HostConfig {
+                nano_cpus: resources.get_nano_cpus(),
+                memory: resources.get_memory_bytes(),
                 auto_remove: Some(true),
                 network_mode: Some(self.network_name.clone()),
                 port_bindings: Some(port_bindings),
@@ -1864,7 +1987,7 @@ events_keys = ["*"]
 
         let options = CreateContainerOptions {
             name: format!("stacks-api.{}", self.network_name),
-            platform: Some(devnet_config.docker_platform.to_string()),
+            platform: Some(resources.platform.clone()),
         };
 
         let container = docker
@@ -1906,20 +2029,15 @@ events_keys = ["*"]
             },
             _ => return Err("unable to get Docker client".into()),
         };
+        let resources = devnet_config.get_resources(DevnetService::SubnetApi);
 
-        let _info = docker
-            .create_image(
-                Some(CreateImageOptions {
-                    from_image: devnet_config.subnet_api_image_url.clone(),
-                    platform: devnet_config.docker_platform.clone(),
-                    ..Default::default()
-                }),
-                None,
-                None,
-            )
-            .try_collect::<Vec<_>>()
-            .await
-            .map_err(|e| format!("unable to create image: {}", e))?;
+        self.pull_image(
+            docker,
+            devnet_config,
+            DevnetService::SubnetApi,
+            &resources.platform,
+        )
+        .await?;
 
         let mut port_bindings = HashMap::new();
         port_bindings.insert(
@@ -1971,12 +2089,14 @@ events_keys = ["*"]
 
         let config = Config {
             labels: Some(labels),
-            image: Some(devnet_config.subnet_api_image_url.clone()),
+            image: Some(get_service_image(devnet_config, DevnetService::SubnetApi)),
             // domainname: Some(self.network_name.to_string()),
             tty: None,
             exposed_ports: Some(exposed_ports),
             env: Some(env),
             host_config: Some(HostConfig {
+                nano_cpus: resources.get_nano_cpus(),
+                memory: resources.get_memory_bytes(),
                 auto_remove: Some(true),
                 network_mode: Some(self.network_name.clone()),
                 port_bindings: Some(port_bindings),
@@ -1988,7 +2108,7 @@ events_keys = ["*"]
 
         let options = CreateContainerOptions {
             name: format!("subnet-api.{}", self.network_name),
-            platform: Some(devnet_config.docker_platform.to_string()),
+            platform: Some(resources.platform.clone()),
         };
 
         let container = docker
@@ -2070,20 +2190,15 @@ events_keys = ["*"]
             },
             _ => return Err("unable to get Docker client".into()),
         };
+        let resources = devnet_config.get_resources(DevnetService::Postgres);
 
-        let _info = docker
-            .create_image(
-                Some(CreateImageOptions {
-                    from_image: devnet_config.postgres_image_url.clone(),
-                    platform: devnet_config.docker_platform.clone(),
-                    ..Default::default()
-                }),
-                None,
-                None,
-            )
-            .try_collect::<Vec<_>>()
-            .await
-            .map_err(|e| format!("unable to create image: {}", e))?;
+        self.pull_image(
+            docker,
+            devnet_config,
+            DevnetService::Postgres,
+            &resources.platform,
+        )
+        .await?;
 
         let mut port_bindings = HashMap::new();
         port_bindings.insert(
@@ -2101,7 +2216,7 @@ events_keys = ["*"]
 
         let config = Config {
             labels: Some(labels),
-            image: Some(devnet_config.postgres_image_url.clone()),
+            image: Some(get_service_image(devnet_config, DevnetService::Postgres)),
             // domainname: Some(self.network_name.to_string()),
             tty: None,
             exposed_ports: Some(exposed_ports),
@@ -2109,7 +2224,18 @@ events_keys = ["*"]
                 format!("POSTGRES_PASSWORD={}", devnet_config.postgres_password),
                 format!("POSTGRES_DB={}", devnet_config.stacks_api_postgres_database),
             ]),
+            cmd: devnet_config
+                .postgres_max_connections
+                .map(|max_connections| {
+                    vec![
+                        "postgres".to_string(),
+                        "-c".to_string(),
+                        format!("max_connections={}", max_connections),
+                    ]
+                }),
             host_config: Some(HostConfig {
+                nano_cpus: resources.get_nano_cpus(),
+                memory: resources.get_memory_bytes(),
                 auto_remove: Some(true),
                 network_mode: Some(self.network_name.clone()),
                 port_bindings: Some(port_bindings),
@@ -2121,7 +2247,7 @@ events_keys = ["*"]
 
         let options = CreateContainerOptions {
             name: format!("postgres.{}", self.network_name),
-            platform: Some(devnet_config.docker_platform.to_string()),
+            platform: Some(resources.platform.clone()),
         };
 
         let container = docker
@@ -2163,20 +2289,15 @@ events_keys = ["*"]
             },
             _ => return Err("unable to get Docker client".into()),
         };
+        let resources = devnet_config.get_resources(DevnetService::StacksExplorer);
 
-        let _info = docker
-            .create_image(
-                Some(CreateImageOptions {
-                    from_image: devnet_config.stacks_explorer_image_url.clone(),
-                    platform: devnet_config.docker_platform.clone(),
-                    ..Default::default()
-                }),
-                None,
-                None,
-            )
-            .try_collect::<Vec<_>>()
-            .await
-            .map_err(|e| format!("unable to create image: {}", e))?;
+        self.pull_image(
+            docker,
+            devnet_config,
+            DevnetService::StacksExplorer,
+            &resources.platform,
+        )
+        .await?;
         let explorer_guest_port = 3000;
         let mut port_bindings = HashMap::new();
         port_bindings.insert(
@@ -2213,12 +2334,17 @@ events_keys = ["*"]
 
         let config = Config {
             labels: Some(labels),
-            image: Some(devnet_config.stacks_explorer_image_url.clone()),
+            image: Some(get_service_image(
+                devnet_config,
+                DevnetService::StacksExplorer,
+            )),
             // domainname: Some(self.network_name.to_string()),
             tty: None,
             exposed_ports: Some(exposed_ports),
             env: Some(env),
             host_config: Some(HostConfig {
+                nano_cpus: resources.get_nano_cpus(),
+                memory: resources.get_memory_bytes(),
                 auto_remove: Some(true),
                 network_mode: Some(self.network_name.clone()),
                 port_bindings: Some(port_bindings),
@@ -2230,7 +2356,7 @@ events_keys = ["*"]
 
         let options = CreateContainerOptions {
             name: format!("stacks-explorer.{}", self.network_name),
-            platform: Some(devnet_config.docker_platform.to_string()),
+            platform: Some(resources.platform.clone()),
         };
 
         let container = docker
@@ -2277,20 +2403,15 @@ events_keys = ["*"]
             },
             _ => return Err("unable to get Docker client".into()),
         };
+        let resources = devnet_config.get_resources(DevnetService::BitcoinExplorer);
 
-        let _info = docker
-            .create_image(
-                Some(CreateImageOptions {
-                    from_image: devnet_config.bitcoin_explorer_image_url.clone(),
-                    platform: devnet_config.docker_platform.clone(),
-                    ..Default::default()
-                }),
-                None,
-                None,
-            )
-            .try_collect::<Vec<_>>()
-            .await
-            .map_err(|e| format!("unable to create image: {}", e))?;
+        self.pull_image(
+            docker,
+            devnet_config,
+            DevnetService::BitcoinExplorer,
+            &resources.platform,
+        )
+        .await?;
 
         let mut port_bindings = HashMap::new();
         port_bindings.insert(
@@ -2312,7 +2433,10 @@ events_keys = ["*"]
 
         let config = Config {
             labels: Some(labels),
-            image: Some(devnet_config.bitcoin_explorer_image_url.clone()),
+            image: Some(get_service_image(
+                devnet_config,
+                DevnetService::BitcoinExplorer,
+            )),
             // domainname: Some(self.network_name.to_string()),
             tty: None,
             exposed_ports: Some(exposed_ports),
@@ -2339,6 +2463,8 @@ events_keys = ["*"]
                 format!("BTCEXP_RPC_ALLOWALL=true",),
             ]),
             host_config: Some(HostConfig {
+                nano_cpus: resources.get_nano_cpus(),
+                memory: resources.get_memory_bytes(),
                 auto_remove: Some(true),
                 network_mode: Some(self.network_name.clone()),
                 port_bindings: Some(port_bindings),
@@ -2350,7 +2476,7 @@ events_keys = ["*"]
 
         let options = CreateContainerOptions {
             name: format!("bitcoin-explorer.{}", self.network_name),
-            platform: Some(devnet_config.docker_platform.to_string()),
+            platform: Some(resources.platform.clone()),
         };
 
         let container = docker
@@ -2494,15 +2620,13 @@ events_keys = ["*"]
 
         let bitcoin_node_config = self.prepare_bitcoin_node_config(boot_index)?;
 
-        let platform = self
-            .network_config
-            .as_ref()
-            .and_then(|c| c.devnet.as_ref())
-            .map(|c| c.docker_platform.to_string());
+        let devnet_config = self.network_config.as_ref().and_then(|c| c.devnet.as_ref());
+        let get_platform =
+            |service: DevnetService| devnet_config.map(|c| c.get_resources(service).platform);
 
         let options = CreateContainerOptions {
             name: format!("bitcoin-node.{}", self.network_name),
-            platform: platform.clone(),
+            platform: get_platform(DevnetService::BitcoinNode),
         };
         let bitcoin_node_c_id = docker
             .create_container::<String, String>(Some(options), bitcoin_node_config)
@@ -2514,7 +2638,7 @@ events_keys = ["*"]
 
         let options = CreateContainerOptions {
             name: format!("stacks-node.{}", self.network_name),
-            platform,
+            platform: get_platform(DevnetService::StacksNode),
         };
         let stacks_node_c_id = docker
             .create_container::<String, String>(Some(options), stacks_node_config)
@@ -2549,62 +2673,164 @@ events_keys = ["*"]
     }
 
     pub async fn kill(&self, ctx: &Context, fatal_message: Option<&str>) {
-        let (docker, devnet_config) = match (&self.docker_client, &self.network_config) {
-            (Some(ref docker), Some(ref network_config)) => match network_config.devnet {
-                Some(ref devnet_config) => (docker, devnet_config),
-                _ => return,
-            },
-            _ => return,
+        let options = ShutdownOptions {
+            timeout: Duration::ZERO,
+            ..Default::default()
         };
-        let options = Some(KillContainerOptions { signal: "SIGKILL" });
-
-        // Terminate containers
-        let container_ids = vec![
-            self.bitcoin_explorer_container_id.clone(),
-            self.stacks_explorer_container_id.clone(),
-            self.bitcoin_node_container_id.clone(),
-            self.stacks_api_container_id.clone(),
-            self.postgres_container_id.clone(),
-            self.stacks_node_container_id.clone(),
-            self.subnet_node_container_id.clone(),
-            self.subnet_api_container_id.clone(),
-        ];
-
-        let signers_container_ids = self.stacks_signers_containers_ids.clone();
+        let report = self.shutdown(&options).await;
+        if report.already_shut_down {
+            return;
+        }
+        for container_id in report.removed_containers.iter() {
+            ctx.try_log(|logger| slog::info!(logger, "Terminated container: {}", container_id));
+        }
+        for error in report.errors.iter() {
+            ctx.try_log(|logger| slog::warn!(logger, "{}", error));
+        }
 
-        for container_id in container_ids
-            .into_iter()
-            .flatten()
-            .chain(signers_container_ids)
+        if let Some(devnet_config) = self
+            .network_config
+            .as_ref()
+            .and_then(|network_config| network_config.devnet.as_ref())
         {
-            let _ = docker.kill_container(&container_id, options.clone()).await;
-            ctx.try_log(|logger| slog::info!(logger, "Terminating container: {}", &container_id));
-            let _ = docker.remove_container(&container_id, None).await;
+            ctx.try_log(|logger| {
+                slog::info!(
+                    logger,
+                    "Artifacts (logs, conf, chainstates) available here: {}",
+                    devnet_config.working_dir
+                )
+            });
         }
 
-        // Delete network
-        let _ = docker.remove_network(&self.network_name).await;
+        if let Some(message) = fatal_message {
+            ctx.try_log(|logger| slog::info!(logger, "⚠️  fatal error - {}", message));
+        } else {
+            ctx.try_log(|logger| slog::info!(logger, "✌️"));
+        }
+    }
 
-        ctx.try_log(|logger| slog::info!(logger, "Pruning network and containers"));
+    /// Stops and removes the containers and the network of the devnet, and unregisters it.
+    /// Once it returns, no container or network of this devnet is left, except the ones listed
+    /// in the errors of the report (e.g. the Docker daemon stopped responding). The containers
+    /// labelled with the project are pruned as well, including the ones created while the
+    /// boot was interrupted. Calling it again does nothing, and the orchestrator also calls it
+    /// when dropped, so a panic of an embedder does not leak Docker resources.
+    pub async fn shutdown(&self, options: &ShutdownOptions) -> ShutdownReport {
+        if self.is_shut_down.swap(true, Ordering::SeqCst) {
+            return ShutdownReport {
+                already_shut_down: true,
+                ..Default::default()
+            };
+        }
+        self.boot_tracker.stop();
+        let mut report = ShutdownReport::default();
+
+        if let Some(ref docker) = self.docker_client {
+            let container_ids = vec![
+                self.bitcoin_explorer_container_id.clone(),
+                self.stacks_explorer_container_id.clone(),
+                self.bitcoin_node_container_id.clone(),
+                self.stacks_api_container_id.clone(),
+                self.postgres_container_id.clone(),
+                self.stacks_node_container_id.clone(),
+                self.subnet_node_container_id.clone(),
+                self.subnet_api_container_id.clone(),
+            ];
+            let signers_container_ids = self.stacks_signers_containers_ids.clone();
+
+            for container_id in container_ids
+                .into_iter()
+                .flatten()
+                .chain(signers_container_ids)
+            {
+                if options.timeout.is_zero() {
+                    let options = Some(KillContainerOptions { signal: "SIGKILL" });
+                    let _ = docker.kill_container(&container_id, options).await;
+                } else {
+                    let options = Some(StopContainerOptions {
+                        t: options.timeout.as_secs() as i64,
+                    });
+                    let _ = docker.stop_container(&container_id, options).await;
+                }
+                // forced, the containers failing to stop are killed
+                let remove_options = Some(RemoveContainerOptions {
+                    v: options.remove_volumes,
+                    force: true,
+                    ..Default::default()
+                });
+                match docker.remove_container(&container_id, remove_options).await {
+                    Ok(_)
+                    | Err(DockerError::DockerResponseServerError {
+                        status_code: 404, ..
+                    }) => report.removed_containers.push(container_id),
+                    Err(e) => report.errors.push(format!(
+                        "unable to remove container {}: {}",
+                        container_id, e
+                    )),
+                }
+            }
 
-        self.prune().await;
-        if let Some(ref tx) = self.termination_success_tx {
-            let _ = tx.send(true);
+            if self.network_created {
+                match docker.remove_network(&self.network_name).await {
+                    Ok(_)
+                    | Err(DockerError::DockerResponseServerError {
+                        status_code: 404, ..
+                    }) => report.removed_network = true,
+                    Err(e) => report.errors.push(format!(
+                        "unable to remove network {}: {}",
+                        self.network_name, e
+                    )),
+                }
+            }
+            self.prune().await;
         }
 
-        ctx.try_log(|logger| {
-            slog::info!(
-                logger,
-                "Artifacts (logs, conf, chainstates) available here: {}",
-                devnet_config.working_dir
-            )
-        });
+        if let Err(e) = registry::unregister_instance(&self.network_name) {
+            report.errors.push(e);
+        }
 
-        if let Some(message) = fatal_message {
-            ctx.try_log(|logger| slog::info!(logger, "⚠️  fatal error - {}", message));
-        } else {
-            ctx.try_log(|logger| slog::info!(logger, "✌️"));
+        if options.remove_working_dir {
+            if let Some(devnet_config) = self
+                .network_config
+                .as_ref()
+                .and_then(|network_config| network_config.devnet.as_ref())
+            {
+                let working_dir = PathBuf::from(&devnet_config.working_dir);
+                match fs::remove_dir_all(&working_dir) {
+                    Ok(_) => report.removed_working_dir = true,
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                        report.removed_working_dir = true
+                    }
+                    Err(e) => report.errors.push(format!(
+                        "unable to remove {}: {}",
+                        working_dir.display(),
+                        e
+                    )),
+                }
+            }
         }
+
+        if let Some(ref tx) = self.termination_success_tx {
+            let _ = tx.send(true);
+        }
+        report
+    }
+
+    fn has_docker_resources(&self) -> bool {
+        self.network_created
+            || !self.stacks_signers_containers_ids.is_empty()
+            || [
+                &self.bitcoin_explorer_container_id,
+                &self.stacks_explorer_container_id,
+                &self.bitcoin_node_container_id,
+                &self.stacks_api_container_id,
+                &self.postgres_container_id,
+                &self.stacks_node_container_id,
+                &self.subnet_node_container_id,
+                &self.subnet_api_container_id,
+            ]
+            .iter()
+            .any(|container_id| container_id.is_some())
     }
 
     pub async fn prune(&self) {
@@ -3082,6 +3308,33 @@ events_keys = ["*"]
     }
 }
 
+/// Connects to the docker daemon of `docker_host`, or of the default and the user space sockets
+pub(crate) fn connect_docker(docker_host: &str) -> Result<Docker, String> {
+    Docker::connect_with_socket(docker_host, 120, bollard::API_DEFAULT_VERSION)
+        .or_else(|_| Docker::connect_with_socket_defaults())
+        .or_else(|_| {
+            let mut user_space_docker_socket =
+                dirs::home_dir().expect("unable to retrieve homedir");
+            user_space_docker_socket.push(".docker");
+            user_space_docker_socket.push("run");
+            user_space_docker_socket.push("docker.sock");
+            Docker::connect_with_socket(
+                user_space_docker_socket.to_str().unwrap(),
+                120,
+                bollard::API_DEFAULT_VERSION,
+            )
+        })
+        .map_err(|e| format!("unable to connect to docker: {:?}", e))
+}
+
+/// Image of a service, pinned to its digest if any
+fn get_service_image(devnet_config: &DevnetConfig, service: DevnetService) -> String {
+    get_image_reference(
+        devnet_config.get_image_url(service),
+        devnet_config.get_image_digest(service),
+    )
+}
+
 fn formatted_docker_error(message: &str, error: DockerError) -> String {
     let error = match &error {
         DockerError::DockerResponseServerError {
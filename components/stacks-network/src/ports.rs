@@ -0,0 +1,75 @@
+use std::net::TcpListener;
+
+use clarinet_files::DevnetConfig;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PortReassignment {
+    pub setting: String,
+    pub configured_port: u16,
+    pub port: u16,
+}
+
+/// Replaces the ports that are already taken on the host, or shared by several services,
+/// with free ports. The listeners are kept open until all the ports are allocated, so that
+/// the same port can't be picked twice.
+pub fn allocate_free_ports(
+    devnet_config: &mut DevnetConfig,
+) -> Result<Vec<PortReassignment>, String> {
+    let mut listeners = vec![];
+    let mut reassignments = vec![];
+    for (setting, port) in devnet_config.get_ports_mut() {
+        if let Ok(listener) = TcpListener::bind(("0.0.0.0", *port)) {
+            listeners.push(listener);
+            continue;
+        }
+        let listener = TcpListener::bind(("0.0.0.0", 0))
+            .map_err(|e| format!("unable to allocate a port for {}: {}", setting, e))?;
+        let allocated_port = listener
+            .local_addr()
+            .map_err(|e| format!("unable to allocate a port for {}: {}", setting, e))?
+            .port();
+        listeners.push(listener);
+        reassignments.push(PortReassignment {
+            setting: setting.to_string(),
+            configured_port: *port,
+            port: allocated_port,
+        });
+        *port = allocated_port;
+    }
+    Ok(reassignments)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clarinet_files::{
+        FileLocation, NetworkManifestEditor, StacksNetwork, DEFAULT_FAUCET_MNEMONIC,
+    };
+    use std::collections::BTreeSet;
+
+    #[test]
+    fn test_allocate_free_ports() {
+        let occupied = TcpListener::bind(("0.0.0.0", 0)).unwrap();
+        let occupied_port = occupied.local_addr().unwrap().port();
+        let content = format!(
+            "[network]\nname = \"devnet\"\n\n[accounts.deployer]\nmnemonic = \"{}\"\n\n[devnet]\nstacks_node_rpc_port = {}\nstacks_api_port = {}\n",
+            DEFAULT_FAUCET_MNEMONIC, occupied_port, occupied_port
+        );
+        let location = FileLocation::from_path_string("/tmp/settings/Devnet.toml").unwrap();
+        let editor = NetworkManifestEditor::from_content(&location, &content).unwrap();
+        let network_manifest = editor
+            .to_network_manifest(&StacksNetwork::Devnet.get_networks(), None)
+            .unwrap();
+        let mut devnet_config = network_manifest.devnet.unwrap();
+
+        let reassignments = allocate_free_ports(&mut devnet_config).unwrap();
+        let settings: Vec<&str> = reassignments.iter().map(|r| r.setting.as_str()).collect();
+        assert!(settings.contains(&"stacks_node_rpc_port"));
+        assert!(settings.contains(&"stacks_api_port"));
+        assert_ne!(devnet_config.stacks_node_rpc_port, occupied_port);
+
+        let ports = devnet_config.get_ports();
+        let unique_ports: BTreeSet<&u16> = ports.values().collect();
+        assert_eq!(unique_ports.len(), ports.len());
+    }
+}
@@ -0,0 +1,239 @@
+use std::fmt;
+
+use clarinet_files::AccountConfig;
+use clarity::util::hash::to_hex;
+use stacks_rpc_client::rpc_client::PoxInfo;
+use stackslib::types::chainstate::{StacksPrivateKey, StacksPublicKey};
+
+#[derive(Deserialize, Debug)]
+pub(crate) struct StackerSetSigner {
+    pub signing_key: String,
+    pub weight: u32,
+    pub stacked_amt: u128,
+}
+
+#[derive(Deserialize, Debug)]
+struct StackerSet {
+    signers: Option<Vec<StackerSetSigner>>,
+}
+
+#[derive(Deserialize, Debug)]
+struct StackerSetResponse {
+    stacker_set: StackerSet,
+}
+
+#[derive(Deserialize, Debug)]
+struct AccountLockup {
+    locked: String,
+    unlock_height: u64,
+}
+
+pub(crate) async fn get_json<T: serde::de::DeserializeOwned>(
+    client: &reqwest::Client,
+    url: String,
+) -> Result<T, String> {
+    client
+        .get(&url)
+        .send()
+        .await
+        .map_err(|e| format!("unable to query {}: {}", url, e))?
+        .json()
+        .await
+        .map_err(|e| format!("unable to parse response of {}: {}", url, e))
+}
+
+/// Returns the stacker set of a reward cycle, empty if the cycle has no reward set yet
+pub(crate) async fn get_stacker_set(
+    client: &reqwest::Client,
+    node_rpc_url: &str,
+    reward_cycle: u32,
+) -> Result<Vec<StackerSetSigner>, String> {
+    let response: StackerSetResponse = get_json(
+        client,
+        format!("{}/v2/stacker_set/{}", node_rpc_url, reward_cycle),
+    )
+    .await?;
+    Ok(response.stacker_set.signers.unwrap_or_default())
+}
+
+pub(crate) fn get_signer_public_key(signer_key: &StacksPrivateKey) -> String {
+    to_hex(&StacksPublicKey::from_private(signer_key).to_bytes_compressed())
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RewardSetEntry {
+    pub signing_key: String,
+    /// `stacks-signer-<n>` for the devnet signers
+    pub label: Option<String>,
+    pub weight: u32,
+    pub stacked_amount: u128,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AccountLockupEntry {
+    pub label: String,
+    pub stx_address: String,
+    pub locked_amount: u128,
+    pub unlock_height: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PoxSummary {
+    pub pox_contract_id: String,
+    pub burn_block_height: u32,
+    pub reward_cycle: u32,
+    pub reward_cycle_length: u32,
+    pub prepare_phase_length: u32,
+    /// Position of the current burn block in the reward cycle
+    pub cycle_position: u32,
+    pub next_cycle_start_height: u32,
+    pub next_cycle_min_threshold_ustx: u64,
+    pub next_cycle_stacked_ustx: u64,
+    pub reward_set: Vec<RewardSetEntry>,
+    pub lockups: Vec<AccountLockupEntry>,
+}
+
+/// Summarizes the state of PoX from the node RPC, labelling the devnet signers and accounts
+pub async fn get_pox_summary(
+    node_rpc_url: &str,
+    accounts: &[AccountConfig],
+    signers_keys: &[StacksPrivateKey],
+) -> Result<PoxSummary, String> {
+    let client = reqwest::Client::new();
+    let pox_info: PoxInfo = get_json(&client, format!("{}/v2/pox", node_rpc_url)).await?;
+
+    let effective_height = pox_info
+        .current_burnchain_block_height
+        .saturating_sub(pox_info.first_burnchain_block_height);
+    let cycle_position = effective_height % pox_info.reward_cycle_length;
+    let next_cycle_start_height =
+        pox_info.current_burnchain_block_height + pox_info.reward_cycle_length - cycle_position;
+
+    let signers_public_keys: Vec<String> = signers_keys.iter().map(get_signer_public_key).collect();
+    let reward_set = get_stacker_set(&client, node_rpc_url, pox_info.reward_cycle_id)
+        .await?
+        .into_iter()
+        .map(|signer| {
+            let signing_key = signer.signing_key.trim_start_matches("0x").to_string();
+            let label = signers_public_keys
+                .iter()
+                .position(|key| key == &signing_key)
+                .map(|i| format!("stacks-signer-{}", i));
+            RewardSetEntry {
+                signing_key,
+                label,
+                weight: signer.weight,
+                stacked_amount: signer.stacked_amt,
+            }
+        })
+        .collect();
+
+    let mut lockups = vec![];
+    for account in accounts.iter() {
+        let lockup: AccountLockup = get_json(
+            &client,
+            format!(
+                "{}/v2/accounts/{}?proof=0",
+                node_rpc_url, account.stx_address
+            ),
+        )
+        .await?;
+        let locked_amount =
+            u128::from_str_radix(lockup.locked.trim_start_matches("0x"), 16).unwrap_or(0);
+        if locked_amount > 0 {
+            lockups.push(AccountLockupEntry {
+                label: account.label.clone(),
+                stx_address: account.stx_address.clone(),
+                locked_amount,
+                unlock_height: lockup.unlock_height,
+            });
+        }
+    }
+
+    Ok(PoxSummary {
+        pox_contract_id: pox_info.contract_id,
+        burn_block_height: pox_info.current_burnchain_block_height,
+        reward_cycle: pox_info.reward_cycle_id,
+        reward_cycle_length: pox_info.reward_cycle_length,
+        prepare_phase_length: pox_info.prepare_phase_block_length,
+        cycle_position,
+        next_cycle_start_height,
+        next_cycle_min_threshold_ustx: pox_info.next_cycle.min_threshold_ustx,
+        next_cycle_stacked_ustx: pox_info.next_cycle.stacked_ustx,
+        reward_set,
+        lockups,
+    })
+}
+
+pub(crate) fn format_stx(ustx: u128) -> String {
+    format!("{}.{:06} STX", ustx / 1_000_000, ustx % 1_000_000)
+}
+
+impl fmt::Display for PoxSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "PoX contract: {}", self.pox_contract_id)?;
+        let phase = if self.cycle_position >= self.reward_cycle_length - self.prepare_phase_length {
+            "prepare phase"
+        } else {
+            "reward phase"
+        };
+        writeln!(
+            f,
+            "Cycle {} ({}), burn block #{}, position {}/{}",
+            self.reward_cycle,
+            phase,
+            self.burn_block_height,
+            self.cycle_position,
+            self.reward_cycle_length
+        )?;
+        writeln!(
+            f,
+            "Next cycle starts at burn block #{} (in {} blocks)",
+            self.next_cycle_start_height,
+            self.next_cycle_start_height - self.burn_block_height
+        )?;
+        writeln!(
+            f,
+            "Next cycle: {} stacked, minimum threshold {}",
+            format_stx(self.next_cycle_stacked_ustx.into()),
+            format_stx(self.next_cycle_min_threshold_ustx.into())
+        )?;
+
+        writeln!(f, "\nReward set ({} signers)", self.reward_set.len())?;
+        for entry in self.reward_set.iter() {
+            writeln!(
+                f,
+                "  {:<16} weight {:<4} {:>24}  {}",
+                entry.label.as_deref().unwrap_or("-"),
+                entry.weight,
+                format_stx(entry.stacked_amount),
+                entry.signing_key
+            )?;
+        }
+
+        write!(f, "\nLocked STX ({} accounts)", self.lockups.len())?;
+        for lockup in self.lockups.iter() {
+            write!(
+                f,
+                "\n  {:<16} {:>24}  unlocks at burn block #{}  {}",
+                lockup.label,
+                format_stx(lockup.locked_amount),
+                lockup.unlock_height,
+                lockup.stx_address
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_stx() {
+        assert_eq!(format_stx(0), "0.000000 STX");
+        assert_eq!(format_stx(1_250_000_000), "1250.000000 STX");
+        assert_eq!(format_stx(1_000_001), "1.000001 STX");
+    }
+}
@@ -0,0 +1,234 @@
+use std::collections::BTreeSet;
+use std::io::Cursor;
+
+use chainhook_sdk::types::{StacksBlockData, StacksTransactionKind};
+use clarinet_deployments::event_replay::value_to_clarity_expression;
+use clarinet_deployments::types::{
+    DeploymentSpecification, EmulatedContractCallSpecificationFile,
+    TransactionPlanSpecificationFile, TransactionSpecificationFile,
+    TransactionsBatchSpecificationFile,
+};
+use clarinet_files::FileLocation;
+use clarity::codec::StacksMessageCodec;
+use clarity::util::hash::hex_bytes;
+use clarity::vm::types::Value;
+use stacks_codec::codec::{StacksTransaction, TransactionPayload};
+
+const RECORDINGS_DIRECTORY: &str = "recordings";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedContractCall {
+    pub block_height: u64,
+    pub txid: String,
+    pub sender: String,
+    pub contract_id: String,
+    pub method: String,
+    /// Arguments, as Clarity expressions
+    pub arguments: Vec<String>,
+    /// Arguments, as hex serialized Clarity values
+    pub raw_arguments: Vec<String>,
+    pub success: bool,
+    pub result: String,
+}
+
+/// Records the calls made to the project contracts on devnet, so that exploratory sessions
+/// can be turned into simnet plans or SDK tests
+#[derive(Debug, Clone, Default)]
+pub struct ContractCallRecorder {
+    contracts: BTreeSet<String>,
+    pub calls: Vec<RecordedContractCall>,
+}
+
+fn decode_contract_call_arguments(raw_tx: &str) -> Option<Vec<Value>> {
+    let bytes = hex_bytes(raw_tx.trim_start_matches("0x")).ok()?;
+    let tx = StacksTransaction::consensus_deserialize(&mut Cursor::new(&bytes)).ok()?;
+    match tx.payload {
+        TransactionPayload::ContractCall(contract_call) => Some(contract_call.function_args),
+        _ => None,
+    }
+}
+
+impl ContractCallRecorder {
+    pub fn new(deployment: &DeploymentSpecification) -> Self {
+        Self {
+            contracts: deployment
+                .contracts
+                .keys()
+                .map(|contract_id| contract_id.to_string())
+                .collect(),
+            calls: vec![],
+        }
+    }
+
+    pub fn record_block(&mut self, block: &StacksBlockData) {
+        for tx in block.transactions.iter() {
+            let StacksTransactionKind::ContractCall(ref data) = tx.metadata.kind else {
+                continue;
+            };
+            if !self.contracts.contains(&data.contract_identifier) {
+                continue;
+            }
+            let Some(arguments) = decode_contract_call_arguments(&tx.metadata.raw_tx) else {
+                continue;
+            };
+            self.calls.push(RecordedContractCall {
+                block_height: block.block_identifier.index,
+                txid: tx.transaction_identifier.hash.clone(),
+                sender: tx.metadata.sender.clone(),
+                contract_id: data.contract_identifier.clone(),
+                method: data.method.clone(),
+                arguments: arguments.iter().map(value_to_clarity_expression).collect(),
+                raw_arguments: arguments
+                    .iter()
+                    .map(|arg| format!("0x{}", arg.serialize_to_hex().unwrap_or_default()))
+                    .collect(),
+                success: tx.metadata.success,
+                result: tx.metadata.result.clone(),
+            });
+        }
+    }
+
+    /// Returns a deployment plan with one `emulated-contract-call` per recorded call,
+    /// batched by block
+    pub fn to_emulated_contract_calls_plan(&self) -> TransactionPlanSpecificationFile {
+        let mut batches: Vec<TransactionsBatchSpecificationFile> = vec![];
+        let mut last_block_height = None;
+        for call in self.calls.iter() {
+            if last_block_height != Some(call.block_height) {
+                last_block_height = Some(call.block_height);
+                batches.push(TransactionsBatchSpecificationFile {
+                    id: batches.len(),
+                    transactions: vec![],
+                    epoch: None,
+                    post_checks: None,
+                    description: None,
+                });
+            }
+            let Some(batch) = batches.last_mut() else {
+                continue;
+            };
+            batch
+                .transactions
+                .push(TransactionSpecificationFile::EmulatedContractCall(
+                    EmulatedContractCallSpecificationFile {
+                        contract_id: call.contract_id.clone(),
+                        emulated_sender: call.sender.clone(),
+                        method: call.method.clone(),
+                        parameters: call.arguments.clone(),
+                        description: None,
+                    },
+                ));
+        }
+        TransactionPlanSpecificationFile {
+            batches,
+            post_checks: None,
+        }
+    }
+
+    /// Returns a vitest file replaying the recorded calls with the clarinet SDK
+    pub fn to_sdk_test(&self) -> String {
+        let mut calls = String::new();
+        for call in self.calls.iter() {
+            let arguments: Vec<String> = call
+                .raw_arguments
+                .iter()
+                .map(|arg| format!("hexToCV(\"{}\")", arg))
+                .collect();
+            calls.push_str(&format!(
+                r#"
+    // {txid}
+    {{
+      const {{ result }} = simnet.callPublicFn(
+        "{contract_id}",
+        "{method}",
+        [{arguments}],
+        "{sender}"
+      );
+      expect(cvToString(result)).toBe({expected_result:?});
+    }}
+"#,
+                txid = call.txid,
+                contract_id = call.contract_id,
+                method = call.method,
+                arguments = arguments.join(", "),
+                sender = call.sender,
+                expected_result = call.result,
+            ));
+        }
+
+        format!(
+            r#"import {{ describe, expect, it }} from "vitest";
+import {{ cvToString, hexToCV }} from "@stacks/transactions";
+
+// recorded on devnet, replaying {count} contract calls
+describe("recorded devnet session", () => {{
+  it("replays the contract calls", () => {{{calls}  }});
+}});
+"#,
+            count = self.calls.len(),
+            calls = calls,
+        )
+    }
+
+    /// Writes the plan and the SDK test in the `recordings` directory of the project
+    pub fn write_recordings(
+        &self,
+        project_root: &FileLocation,
+        name: &str,
+    ) -> Result<Vec<FileLocation>, String> {
+        let plan = serde_yaml::to_string(&self.to_emulated_contract_calls_plan())
+            .map_err(|e| format!("unable to serialize recorded calls: {}", e))?;
+
+        let mut plan_location = project_root.clone();
+        plan_location.append_path(&format!("{}/{}.plan.yaml", RECORDINGS_DIRECTORY, name))?;
+        plan_location.write_content(plan.as_bytes())?;
+
+        let mut test_location = project_root.clone();
+        test_location.append_path(&format!("{}/{}.test.ts", RECORDINGS_DIRECTORY, name))?;
+        test_location.write_content(self.to_sdk_test().as_bytes())?;
+
+        Ok(vec![plan_location, test_location])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clarity::vm::types::{
+        OptionalData, PrincipalData, ResponseData, StandardPrincipalData, TupleData,
+    };
+    use clarity::vm::ClarityName;
+
+    #[test]
+    fn test_value_to_clarity_expression() {
+        let principal = PrincipalData::Standard(StandardPrincipalData::transient());
+        let tuple = Value::Tuple(
+            TupleData::from_data(vec![
+                (
+                    ClarityName::try_from("amount".to_owned()).unwrap(),
+                    Value::UInt(10),
+                ),
+                (
+                    ClarityName::try_from("owner".to_owned()).unwrap(),
+                    Value::Principal(principal.clone()),
+                ),
+            ])
+            .unwrap(),
+        );
+        let value = Value::Response(ResponseData {
+            committed: true,
+            data: Box::new(Value::Optional(OptionalData {
+                data: Some(Box::new(tuple)),
+            })),
+        });
+        assert_eq!(
+            value_to_clarity_expression(&value),
+            format!("(ok (some {{ amount: u10, owner: '{} }}))", principal)
+        );
+        assert_eq!(
+            value_to_clarity_expression(&Value::list_from(vec![]).unwrap()),
+            "(list)"
+        );
+        assert_eq!(value_to_clarity_expression(&Value::Int(-1)), "-1");
+    }
+}
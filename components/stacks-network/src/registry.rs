@@ -0,0 +1,231 @@
+//! Running devnets are registered in the user's data directory, so that the containers
+//! and working directories of crashed runs can be found and cleaned.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+use bollard::container::{KillContainerOptions, ListContainersOptions};
+use bollard::network::PruneNetworksOptions;
+use bollard::Docker;
+use clarinet_files::{DevnetConfig, FileLocation};
+
+const RUN_METADATA_FILE: &str = "devnet-metadata.json";
+
+/// Metadata of a devnet run, written in its working directory and in the registry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DevnetRunMetadata {
+    /// Name of the docker network, also used to label the containers
+    pub network_name: String,
+    pub network_id: Option<u16>,
+    pub pid: u32,
+    pub manifest_location: String,
+    pub working_dir: String,
+    pub docker_host: String,
+    pub ports: BTreeMap<String, u16>,
+    pub started_at: String,
+    /// Digests of the images pulled for the run, keyed by service
+    #[serde(default)]
+    pub image_digests: BTreeMap<String, String>,
+}
+
+impl DevnetRunMetadata {
+    pub fn new(
+        network_name: &str,
+        manifest_location: &FileLocation,
+        devnet_config: &DevnetConfig,
+    ) -> Self {
+        Self {
+            network_name: network_name.to_string(),
+            network_id: devnet_config.network_id,
+            pid: std::process::id(),
+            manifest_location: manifest_location.to_string(),
+            working_dir: devnet_config.working_dir.clone(),
+            docker_host: devnet_config.docker_host.clone(),
+            ports: devnet_config.get_ports(),
+            started_at: chrono::Utc::now().to_rfc3339(),
+            image_digests: BTreeMap::new(),
+        }
+    }
+
+    pub fn write(&self) -> Result<(), String> {
+        let path = format!("{}/{}", self.working_dir, RUN_METADATA_FILE);
+        write_json(&path, self)
+    }
+
+    pub fn read(working_dir: &str) -> Result<Self, String> {
+        let path = format!("{}/{}", working_dir, RUN_METADATA_FILE);
+        read_json(&path)
+    }
+
+    pub fn is_running(&self) -> bool {
+        is_process_running(self.pid)
+    }
+
+    /// Adds the digest an image resolved to, in the working directory and in the registry
+    pub fn record_image_digest(
+        working_dir: &str,
+        service: &str,
+        digest: &str,
+    ) -> Result<(), String> {
+        let mut metadata = Self::read(working_dir)?;
+        metadata
+            .image_digests
+            .insert(service.to_string(), digest.to_string());
+        metadata.write()?;
+        register_instance(&metadata)
+    }
+}
+
+fn write_json(path: &str, metadata: &DevnetRunMetadata) -> Result<(), String> {
+    let content = serde_json::to_vec_pretty(metadata)
+        .map_err(|e| format!("unable to serialize devnet metadata: {}", e))?;
+    fs::write(path, content).map_err(|e| format!("unable to write {}: {}", path, e))
+}
+
+fn read_json(path: &str) -> Result<DevnetRunMetadata, String> {
+    let content = fs::read(path).map_err(|e| format!("unable to read {}: {}", path, e))?;
+    serde_json::from_slice(&content).map_err(|e| format!("unable to parse {}: {}", path, e))
+}
+
+#[cfg(unix)]
+fn is_process_running(pid: u32) -> bool {
+    Command::new("kill")
+        .args(["-0", &pid.to_string()])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn is_process_running(pid: u32) -> bool {
+    Command::new("tasklist")
+        .args(["/FI", &format!("PID eq {}", pid), "/NH"])
+        .stderr(Stdio::null())
+        .output()
+        .map(|output| String::from_utf8_lossy(&output.stdout).contains(&pid.to_string()))
+        .unwrap_or(false)
+}
+
+fn get_registry_dir() -> Result<PathBuf, String> {
+    let mut registry_dir = dirs::data_dir().ok_or("unable to retrieve the user data dir")?;
+    registry_dir.push("clarinet");
+    registry_dir.push("devnets");
+    Ok(registry_dir)
+}
+
+fn get_instance_path(network_name: &str) -> Result<PathBuf, String> {
+    let mut path = get_registry_dir()?;
+    path.push(format!("{}.json", network_name));
+    Ok(path)
+}
+
+pub fn register_instance(metadata: &DevnetRunMetadata) -> Result<(), String> {
+    let registry_dir = get_registry_dir()?;
+    fs::create_dir_all(&registry_dir)
+        .map_err(|e| format!("unable to create {}: {}", registry_dir.display(), e))?;
+    let path = get_instance_path(&metadata.network_name)?;
+    write_json(&path.to_string_lossy(), metadata)
+}
+
+pub fn unregister_instance(network_name: &str) -> Result<(), String> {
+    let path = get_instance_path(network_name)?;
+    if !path.exists() {
+        return Ok(());
+    }
+    fs::remove_file(&path).map_err(|e| format!("unable to remove {}: {}", path.display(), e))
+}
+
+/// Returns the registered instances, sorted by name. Unreadable entries are skipped.
+pub fn list_instances() -> Result<Vec<DevnetRunMetadata>, String> {
+    let registry_dir = get_registry_dir()?;
+    let Ok(entries) = fs::read_dir(&registry_dir) else {
+        return Ok(vec![]);
+    };
+    let mut instances: Vec<DevnetRunMetadata> = entries
+        .flatten()
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|entry| read_json(&entry.path().to_string_lossy()).ok())
+        .collect();
+    instances.sort_by(|a, b| a.network_name.cmp(&b.network_name));
+    Ok(instances)
+}
+
+pub fn get_instance(network_name: &str) -> Result<DevnetRunMetadata, String> {
+    let path = get_instance_path(network_name)?;
+    if !path.exists() {
+        return Err(format!("no devnet instance named {}", network_name));
+    }
+    read_json(&path.to_string_lossy())
+}
+
+/// Kills and removes the containers of an instance, and its docker network
+pub async fn remove_instance_resources(metadata: &DevnetRunMetadata) -> Result<usize, String> {
+    let docker =
+        Docker::connect_with_socket(&metadata.docker_host, 120, bollard::API_DEFAULT_VERSION)
+            .or_else(|_| Docker::connect_with_socket_defaults())
+            .map_err(|e| format!("unable to connect to docker: {:?}", e))?;
+
+    let mut filters = HashMap::new();
+    filters.insert(
+        "label".to_string(),
+        vec![format!("project={}", metadata.network_name)],
+    );
+    let containers = docker
+        .list_containers(Some(ListContainersOptions {
+            all: true,
+            filters: filters.clone(),
+            ..Default::default()
+        }))
+        .await
+        .map_err(|e| format!("unable to communicate with Docker: {}", e))?;
+
+    let options = KillContainerOptions { signal: "SIGKILL" };
+    let mut removed = 0;
+    for container_id in containers.iter().filter_map(|c| c.id.as_ref()) {
+        let _ = docker
+            .kill_container(container_id, Some(options.clone()))
+            .await;
+        if docker.remove_container(container_id, None).await.is_ok() {
+            removed += 1;
+        }
+    }
+
+    let _ = docker.remove_network(&metadata.network_name).await;
+    let _ = docker
+        .prune_networks(Some(PruneNetworksOptions { filters }))
+        .await;
+    Ok(removed)
+}
+
+/// Stops an instance: its process is terminated if it's still running, then its
+/// docker resources are removed and it's unregistered
+pub async fn stop_instance(metadata: &DevnetRunMetadata) -> Result<usize, String> {
+    if metadata.is_running() && metadata.pid != std::process::id() {
+        terminate_process(metadata.pid);
+    }
+    let removed = remove_instance_resources(metadata).await?;
+    unregister_instance(&metadata.network_name)?;
+    Ok(removed)
+}
+
+#[cfg(unix)]
+fn terminate_process(pid: u32) {
+    let _ = Command::new("kill")
+        .arg(pid.to_string())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+}
+
+#[cfg(not(unix))]
+fn terminate_process(pid: u32) {
+    let _ = Command::new("taskkill")
+        .args(["/PID", &pid.to_string(), "/F"])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+}
@@ -0,0 +1,328 @@
+//! Logs of the containers of a devnet instance (`clarinet devnet logs`). The services are
+//! selected by name and their containers found through the `project` label of the instance,
+//! the logs of several containers are merged as they come. Docker only filters by time, the
+//! level of each line is read from its content: the `INFO`/`WARN` prefix of the nodes, or the
+//! `level` field of the JSON lines of the API.
+
+use std::collections::HashMap;
+
+use bollard::container::{ListContainersOptions, LogsOptions};
+use chrono::{DateTime, Utc};
+use futures::stream::{select_all, StreamExt};
+
+use crate::orchestrator::connect_docker;
+use crate::registry::DevnetRunMetadata;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogService {
+    StacksNode,
+    BitcoinNode,
+    StacksApi,
+    Signer,
+    Postgres,
+    StacksExplorer,
+    BitcoinExplorer,
+    SubnetNode,
+    SubnetApi,
+}
+
+pub const LOG_SERVICES_NAMES: [&str; 9] = [
+    "stacks-node",
+    "bitcoind",
+    "api",
+    "signer",
+    "postgres",
+    "explorer",
+    "bitcoin-explorer",
+    "subnet-node",
+    "subnet-api",
+];
+
+impl LogService {
+    pub fn from_name(name: &str) -> Result<LogService, String> {
+        match name {
+            "stacks-node" => Ok(LogService::StacksNode),
+            "bitcoind" | "bitcoin-node" => Ok(LogService::BitcoinNode),
+            "api" | "stacks-api" => Ok(LogService::StacksApi),
+            "signer" | "signers" => Ok(LogService::Signer),
+            "postgres" => Ok(LogService::Postgres),
+            "explorer" | "stacks-explorer" => Ok(LogService::StacksExplorer),
+            "bitcoin-explorer" => Ok(LogService::BitcoinExplorer),
+            "subnet-node" => Ok(LogService::SubnetNode),
+            "subnet-api" => Ok(LogService::SubnetApi),
+            _ => Err(format!(
+                "unknown service '{}' (expected one of {})",
+                name,
+                LOG_SERVICES_NAMES.join(", ")
+            )),
+        }
+    }
+
+    /// Prefix of the names of the containers of the service
+    fn get_container_prefix(&self) -> &'static str {
+        match self {
+            LogService::StacksNode => "stacks-node.",
+            LogService::BitcoinNode => "bitcoin-node.",
+            LogService::StacksApi => "stacks-api.",
+            // one container by signer, `stacks-signer-<id>.<network>`
+            LogService::Signer => "stacks-signer-",
+            LogService::Postgres => "postgres.",
+            LogService::StacksExplorer => "stacks-explorer.",
+            LogService::BitcoinExplorer => "bitcoin-explorer.",
+            LogService::SubnetNode => "subnet-node.",
+            LogService::SubnetApi => "subnet-api.",
+        }
+    }
+
+    /// Label of the container if it belongs to the service, e.g. `stacks-signer-1`
+    pub fn get_container_label(&self, container_name: &str, network_name: &str) -> Option<String> {
+        let name = container_name.trim_start_matches('/');
+        let label = name.strip_suffix(&format!(".{}", network_name))?;
+        name.starts_with(self.get_container_prefix())
+            .then(|| label.to_string())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ServiceLogLevel {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl ServiceLogLevel {
+    pub fn from_name(name: &str) -> Result<ServiceLogLevel, String> {
+        match name.to_lowercase().as_str() {
+            "trace" => Ok(ServiceLogLevel::Trace),
+            "debug" | "debg" => Ok(ServiceLogLevel::Debug),
+            "info" => Ok(ServiceLogLevel::Info),
+            "warn" | "warning" => Ok(ServiceLogLevel::Warn),
+            "error" | "erro" | "crit" | "fatal" => Ok(ServiceLogLevel::Error),
+            _ => Err(format!("unknown log level '{}'", name)),
+        }
+    }
+
+    /// Levels of pino, the logger of the API
+    fn from_pino_level(level: u64) -> ServiceLogLevel {
+        match level {
+            0..=10 => ServiceLogLevel::Trace,
+            11..=20 => ServiceLogLevel::Debug,
+            21..=30 => ServiceLogLevel::Info,
+            31..=40 => ServiceLogLevel::Warn,
+            _ => ServiceLogLevel::Error,
+        }
+    }
+}
+
+/// Level of a log line, if it has one
+pub fn parse_log_level(line: &str) -> Option<ServiceLogLevel> {
+    let line = line.trim_start();
+    if line.starts_with('{') {
+        let value: serde_json::Value = serde_json::from_str(line).ok()?;
+        return match value.get("level")? {
+            serde_json::Value::String(level) => ServiceLogLevel::from_name(level).ok(),
+            serde_json::Value::Number(level) => {
+                level.as_u64().map(ServiceLogLevel::from_pino_level)
+            }
+            _ => None,
+        };
+    }
+    // `INFO [1712345678.123] [src/main.rs:42] ...`, possibly after a timestamp
+    line.split_whitespace()
+        .take(2)
+        .find_map(|word| ServiceLogLevel::from_name(word.trim_matches(['[', ']', ':'])).ok())
+}
+
+/// Unix timestamp of a bound of the time range: a duration before `now` (`30s`, `10m`,
+/// `2h`, `1d`) or an RFC 3339 date
+pub fn parse_log_time(value: &str, now: DateTime<Utc>) -> Result<i64, String> {
+    if let Ok(date) = DateTime::parse_from_rfc3339(value) {
+        return Ok(date.timestamp());
+    }
+    let invalid = || {
+        format!(
+            "invalid time '{}' (expected a duration such as 10m, or an RFC 3339 date)",
+            value
+        )
+    };
+    let Some((index, _)) = value.char_indices().last() else {
+        return Err(invalid());
+    };
+    let (amount, unit) = value.split_at(index);
+    let amount: i64 = amount.parse().map_err(|_| invalid())?;
+    let seconds = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        _ => return Err(invalid()),
+    };
+    Ok(now.timestamp() - amount * seconds)
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct LogsFilter {
+    /// Lines below the level are skipped, the lines without a level (continuation of a
+    /// multiline message, bitcoind) are kept
+    pub min_level: Option<ServiceLogLevel>,
+    pub since: Option<i64>,
+    pub until: Option<i64>,
+    /// Last lines of each container
+    pub tail: Option<usize>,
+    pub follow: bool,
+}
+
+impl LogsFilter {
+    /// Filter of the options of `clarinet devnet logs`, the durations are relative to now
+    pub fn from_options(
+        level: Option<&str>,
+        since: Option<&str>,
+        until: Option<&str>,
+        tail: Option<usize>,
+        follow: bool,
+    ) -> Result<LogsFilter, String> {
+        let now = Utc::now();
+        Ok(LogsFilter {
+            min_level: level.map(ServiceLogLevel::from_name).transpose()?,
+            since: since.map(|since| parse_log_time(since, now)).transpose()?,
+            until: until.map(|until| parse_log_time(until, now)).transpose()?,
+            tail,
+            follow,
+        })
+    }
+
+    pub fn keeps(&self, line: &str) -> bool {
+        match (self.min_level, parse_log_level(line)) {
+            (Some(min_level), Some(level)) => level >= min_level,
+            _ => true,
+        }
+    }
+}
+
+/// Streams the logs of the containers of `services`, calling `on_line` with the label of the
+/// container and the line, until the logs end (or the containers stop, with `follow`)
+pub async fn stream_service_logs(
+    instance: &DevnetRunMetadata,
+    services: &[LogService],
+    filter: &LogsFilter,
+    on_line: &mut dyn FnMut(&str, &str),
+) -> Result<(), String> {
+    let docker = connect_docker(&instance.docker_host)?;
+    let mut filters = HashMap::new();
+    filters.insert(
+        "label".to_string(),
+        vec![format!("project={}", instance.network_name)],
+    );
+    let containers = docker
+        .list_containers(Some(ListContainersOptions {
+            all: true,
+            filters,
+            ..Default::default()
+        }))
+        .await
+        .map_err(|e| format!("unable to communicate with Docker: {}", e))?;
+
+    let mut selected: Vec<(String, String)> = vec![];
+    for names in containers.iter().filter_map(|c| c.names.as_ref()) {
+        for name in names {
+            let label = services
+                .iter()
+                .find_map(|service| service.get_container_label(name, &instance.network_name));
+            if let Some(label) = label {
+                selected.push((label, name.trim_start_matches('/').to_string()));
+            }
+        }
+    }
+    if selected.is_empty() {
+        return Err(format!(
+            "no container of the selected services in {}",
+            instance.network_name
+        ));
+    }
+    selected.sort();
+
+    let options = LogsOptions::<String> {
+        follow: filter.follow,
+        stdout: true,
+        stderr: true,
+        since: filter.since.unwrap_or(0),
+        until: filter.until.unwrap_or(0),
+        timestamps: false,
+        tail: filter
+            .tail
+            .map(|tail| tail.to_string())
+            .unwrap_or("all".to_string()),
+    };
+    let streams = selected.into_iter().map(|(label, container_name)| {
+        docker
+            .logs(&container_name, Some(options.clone()))
+            .map(move |output| (label.clone(), output))
+            .boxed()
+    });
+    let mut logs = select_all(streams);
+    while let Some((label, output)) = logs.next().await {
+        let output = output.map_err(|e| format!("unable to read the logs of {}: {}", label, e))?;
+        let content = output.into_bytes();
+        for line in String::from_utf8_lossy(&content).lines() {
+            if filter.keeps(line) {
+                on_line(&label, line);
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_logs_filter() {
+        assert_eq!(
+            LogService::Signer.get_container_label("/stacks-signer-1.devnet.net", "devnet.net"),
+            Some("stacks-signer-1".to_string())
+        );
+        assert_eq!(
+            LogService::StacksNode.get_container_label("/stacks-node.other.net", "devnet.net"),
+            None
+        );
+        assert_eq!(
+            LogService::from_name("bitcoind"),
+            Ok(LogService::BitcoinNode)
+        );
+        assert!(LogService::from_name("miner").is_err());
+
+        assert_eq!(
+            parse_log_level("WARN [1712345678.123] [src/main.rs:42] [main] Timed out"),
+            Some(ServiceLogLevel::Warn)
+        );
+        assert_eq!(
+            parse_log_level(r#"{"level":50,"msg":"unable to connect"}"#),
+            Some(ServiceLogLevel::Error)
+        );
+        assert_eq!(
+            parse_log_level("2024-05-01T10:00:00Z UpdateTip: new best"),
+            None
+        );
+
+        let filter = LogsFilter {
+            min_level: Some(ServiceLogLevel::Warn),
+            ..Default::default()
+        };
+        assert!(filter.keeps("ERRO [1712345678.123] Failed to mine"));
+        assert!(!filter.keeps("INFO [1712345678.123] Mined block"));
+        assert!(filter.keeps("    at processTicksAndRejections"));
+
+        let now = DateTime::parse_from_rfc3339("2024-05-01T10:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert_eq!(parse_log_time("10m", now), Ok(now.timestamp() - 600));
+        assert_eq!(
+            parse_log_time("2024-05-01T09:00:00Z", now),
+            Ok(now.timestamp() - 3600)
+        );
+        assert!(parse_log_time("yesterday", now).is_err());
+    }
+}
@@ -0,0 +1,207 @@
+use std::time::Duration;
+
+use clarity::util::hash::to_hex;
+use stacks_rpc_client::rpc_client::PoxInfo;
+use stackslib::types::chainstate::StacksPrivateKey;
+
+use crate::pox::{get_json, get_signer_public_key, get_stacker_set};
+
+/// Boot address of the `signers-*` stackerdb contracts on testnet / devnet
+const SIGNERS_CONTRACTS_ADDRESS: &str = "ST000000000000000000002AMW42H";
+/// Id of the stackerdb contract where the signers write their block responses
+const BLOCK_RESPONSE_MESSAGE_ID: u32 = 1;
+
+const SIGNER_MESSAGE_BLOCK_RESPONSE: u8 = 1;
+const BLOCK_RESPONSE_ACCEPTED: u8 = 0;
+const BLOCK_RESPONSE_REJECTED: u8 = 1;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case", tag = "type")]
+pub enum SignerBlockResponse {
+    Accepted { signer_signature_hash: String },
+    Rejected { reason: String },
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SignerStatus {
+    pub signer_id: usize,
+    pub public_key: String,
+    /// `None` until the signer is part of the reward set
+    pub weight: Option<u32>,
+    pub stackerdb_slot: Option<u32>,
+    pub slot_version: Option<u32>,
+    pub last_response: Option<SignerBlockResponse>,
+    /// Height of the last stacks block known when the signer accepted a block
+    pub last_signed_block_height: Option<u64>,
+    pub rejected_proposals: u32,
+}
+
+#[derive(Deserialize, Debug)]
+struct StackerDBSlotMetadata {
+    slot_id: u32,
+    slot_version: u32,
+}
+
+/// Keeps track of the state of the devnet signers, by watching the reward set and
+/// the block responses written in stackerdb.
+#[derive(Debug, Clone)]
+pub struct SignersMonitor {
+    pub signers: Vec<SignerStatus>,
+    node_rpc_url: String,
+}
+
+impl SignersMonitor {
+    pub fn new(node_rpc_url: &str, signers_keys: &[StacksPrivateKey]) -> Self {
+        let signers = signers_keys
+            .iter()
+            .enumerate()
+            .map(|(signer_id, key)| SignerStatus {
+                signer_id,
+                public_key: get_signer_public_key(key),
+                weight: None,
+                stackerdb_slot: None,
+                slot_version: None,
+                last_response: None,
+                last_signed_block_height: None,
+                rejected_proposals: 0,
+            })
+            .collect();
+        Self {
+            signers,
+            node_rpc_url: node_rpc_url.to_string(),
+        }
+    }
+
+    /// Refreshes the signers state. Each new chunk found in the stackerdb slot of a signer
+    /// is decoded; rejections are counted.
+    pub async fn refresh(&mut self, stacks_block_height: u64) -> Result<(), String> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(2))
+            .build()
+            .map_err(|e| e.to_string())?;
+
+        let pox_info: PoxInfo = get_json(&client, format!("{}/v2/pox", self.node_rpc_url)).await?;
+        let reward_cycle = pox_info.reward_cycle_id;
+        let reward_set_signers = get_stacker_set(&client, &self.node_rpc_url, reward_cycle).await?;
+
+        let contract_name = format!("signers-{}-{}", reward_cycle % 2, BLOCK_RESPONSE_MESSAGE_ID);
+        let slots: Vec<StackerDBSlotMetadata> = get_json(
+            &client,
+            format!(
+                "{}/v2/stackerdb/{}/{}",
+                self.node_rpc_url, SIGNERS_CONTRACTS_ADDRESS, contract_name
+            ),
+        )
+        .await?;
+
+        for signer in self.signers.iter_mut() {
+            // slots are allocated in the order of the reward set
+            let position = reward_set_signers
+                .iter()
+                .position(|entry| entry.signing_key.trim_start_matches("0x") == signer.public_key);
+            let Some(position) = position else {
+                signer.weight = None;
+                signer.stackerdb_slot = None;
+                continue;
+            };
+            signer.weight = Some(reward_set_signers[position].weight);
+            let slot_id = position as u32;
+            if signer.stackerdb_slot != Some(slot_id) {
+                signer.stackerdb_slot = Some(slot_id);
+                signer.slot_version = None;
+            }
+
+            let Some(slot) = slots.iter().find(|slot| slot.slot_id == slot_id) else {
+                continue;
+            };
+            if signer.slot_version == Some(slot.slot_version) {
+                continue;
+            }
+            signer.slot_version = Some(slot.slot_version);
+
+            let url = format!(
+                "{}/v2/stackerdb/{}/{}/{}",
+                self.node_rpc_url, SIGNERS_CONTRACTS_ADDRESS, contract_name, slot_id
+            );
+            let chunk = client
+                .get(&url)
+                .send()
+                .await
+                .map_err(|e| format!("unable to query {}: {}", url, e))?
+                .bytes()
+                .await
+                .map_err(|e| format!("unable to read response of {}: {}", url, e))?;
+
+            let Some(response) = decode_block_response(&chunk) else {
+                continue;
+            };
+            match response {
+                SignerBlockResponse::Accepted { .. } => {
+                    signer.last_signed_block_height = Some(stacks_block_height)
+                }
+                SignerBlockResponse::Rejected { .. } => signer.rejected_proposals += 1,
+            }
+            signer.last_response = Some(response);
+        }
+        Ok(())
+    }
+}
+
+/// Decodes the beginning of a `SignerMessage::BlockResponse` chunk, which is enough to know
+/// which block was accepted, or why a block proposal was rejected
+pub fn decode_block_response(bytes: &[u8]) -> Option<SignerBlockResponse> {
+    let (message_type, bytes) = bytes.split_first()?;
+    if *message_type != SIGNER_MESSAGE_BLOCK_RESPONSE {
+        return None;
+    }
+    let (response_type, bytes) = bytes.split_first()?;
+    match *response_type {
+        BLOCK_RESPONSE_ACCEPTED => Some(SignerBlockResponse::Accepted {
+            signer_signature_hash: to_hex(bytes.get(0..32)?),
+        }),
+        BLOCK_RESPONSE_REJECTED => {
+            let len = u32::from_be_bytes(bytes.get(0..4)?.try_into().ok()?) as usize;
+            let reason = bytes.get(4..4 + len)?;
+            Some(SignerBlockResponse::Rejected {
+                reason: String::from_utf8_lossy(reason).to_string(),
+            })
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_block_response() {
+        let mut accepted = vec![SIGNER_MESSAGE_BLOCK_RESPONSE, BLOCK_RESPONSE_ACCEPTED];
+        accepted.extend([0xab; 32]);
+        accepted.extend([0; 65]);
+        assert_eq!(
+            decode_block_response(&accepted),
+            Some(SignerBlockResponse::Accepted {
+                signer_signature_hash: "ab".repeat(32)
+            })
+        );
+
+        let reason = "invalid parent block";
+        let mut rejected = vec![SIGNER_MESSAGE_BLOCK_RESPONSE, BLOCK_RESPONSE_REJECTED];
+        rejected.extend((reason.len() as u32).to_be_bytes());
+        rejected.extend(reason.as_bytes());
+        assert_eq!(
+            decode_block_response(&rejected),
+            Some(SignerBlockResponse::Rejected {
+                reason: reason.to_string()
+            })
+        );
+
+        // block proposals and truncated chunks are ignored
+        assert_eq!(decode_block_response(&[0, 0]), None);
+        assert_eq!(
+            decode_block_response(&[SIGNER_MESSAGE_BLOCK_RESPONSE, BLOCK_RESPONSE_ACCEPTED, 1]),
+            None
+        );
+    }
+}
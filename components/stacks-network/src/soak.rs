@@ -0,0 +1,421 @@
+//! Soak mode: the devnet keeps mining for hours while read-only invariants (e.g. the
+//! conservation of a token supply) are checked every few blocks. The metrics of the checks
+//! are kept up to date in the working directory, and a broken invariant stops the devnet
+//! after dumping the state of the accounts and contracts.
+
+use std::collections::BTreeMap;
+use std::sync::mpsc::Sender;
+use std::time::{Duration, Instant};
+
+use clarinet_files::{
+    AccountConfig, FileLocation, InvariantConfig, ProjectManifest, SoakConfig, StacksNetwork,
+};
+use clarity::util::hash::bytes_to_hex;
+use clarity::vm::types::QualifiedContractIdentifier;
+use clarity::vm::{EvaluationResult, Value};
+use clarity_repl::repl::address_book::AddressBook;
+use clarity_repl::repl::{Session, SessionSettings};
+use serde_json::json;
+
+use crate::event::DevnetEvent;
+use crate::pox::get_json;
+
+const METRICS_FILE: &str = "soak-metrics.json";
+
+/// An invariant, with its arguments and expected value evaluated once
+#[derive(Debug, Clone)]
+struct Invariant {
+    name: String,
+    contract_id: QualifiedContractIdentifier,
+    function: String,
+    args: Vec<Value>,
+    expect: Value,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct InvariantMetrics {
+    pub name: String,
+    pub checks: u64,
+    /// Checks that couldn't be performed, such as calls to a node busy mining
+    pub errors: u64,
+    pub last_result: Option<String>,
+    pub total_duration_ms: u64,
+    pub max_duration_ms: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SoakMetrics {
+    /// Unix timestamp of the first check
+    pub started_at: u64,
+    pub elapsed_secs: u64,
+    pub stacks_blocks: u64,
+    pub last_block_height: u64,
+    pub rounds: u64,
+    pub invariants: Vec<InvariantMetrics>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct InvariantViolation {
+    pub name: String,
+    pub block_height: u64,
+    pub expected: String,
+    pub actual: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct ReadOnlyCallResponse {
+    okay: bool,
+    result: Option<String>,
+    cause: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct AccountResponse {
+    balance: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct ContractVariable {
+    name: String,
+    access: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct ContractInterfaceResponse {
+    variables: Vec<ContractVariable>,
+}
+
+#[derive(Deserialize, Debug)]
+struct DataVarResponse {
+    data: String,
+}
+
+fn decode_value(hex: &str) -> Result<Value, String> {
+    Value::try_deserialize_hex_untyped(hex.trim_start_matches("0x"))
+        .map_err(|e| format!("unable to decode value: {}", e))
+}
+
+fn eval_value(
+    session: &mut Session,
+    address_book: &AddressBook,
+    expression: &str,
+) -> Result<Value, String> {
+    let snippet = address_book.resolve_expression(expression)?;
+    match session.eval(snippet, false) {
+        Ok(execution) => match execution.result {
+            EvaluationResult::Snippet(result) => Ok(result.result),
+            EvaluationResult::Contract(_) => Err(format!("{} is not a value", expression)),
+        },
+        Err(_) => Err(format!("invalid value {}", expression)),
+    }
+}
+
+fn get_invariant(
+    session: &mut Session,
+    address_book: &AddressBook,
+    config: &InvariantConfig,
+) -> Result<Invariant, String> {
+    let contract_id = if config.contract.starts_with('@') || config.contract.starts_with('S') {
+        address_book.resolve_contract_id(&config.contract)?
+    } else {
+        let deployer = address_book.resolve_principal("@deployer")?;
+        format!("{}.{}", deployer, config.contract)
+    };
+    let contract_id = QualifiedContractIdentifier::parse(&contract_id)
+        .map_err(|_| format!("invalid contract {}", contract_id))?;
+    let mut args = vec![];
+    for arg in config.args.iter() {
+        args.push(eval_value(session, address_book, arg)?);
+    }
+    Ok(Invariant {
+        name: config.name.clone(),
+        contract_id,
+        function: config.function.clone(),
+        args,
+        expect: eval_value(session, address_book, &config.expect)?,
+    })
+}
+
+pub struct SoakMonitor {
+    invariants: Vec<Invariant>,
+    check_interval: u64,
+    duration: Option<Duration>,
+    node_rpc_url: String,
+    sender: String,
+    accounts: Vec<AccountConfig>,
+    contracts: Vec<QualifiedContractIdentifier>,
+    started_at: Option<Instant>,
+    last_checked_height: Option<u64>,
+    broken: bool,
+    pub metrics: SoakMetrics,
+}
+
+impl SoakMonitor {
+    pub fn new(
+        config: &SoakConfig,
+        manifest: &ProjectManifest,
+        accounts: &[AccountConfig],
+        contracts: Vec<QualifiedContractIdentifier>,
+        node_rpc_url: &str,
+    ) -> Result<Self, String> {
+        let address_book = manifest.get_address_book(
+            &StacksNetwork::Devnet,
+            accounts
+                .iter()
+                .map(|account| (account.label.as_str(), account.stx_address.as_str())),
+        );
+        let mut session = Session::new(SessionSettings::default());
+        let mut invariants = vec![];
+        for invariant in config.invariants.iter() {
+            let invariant = get_invariant(&mut session, &address_book, invariant)
+                .map_err(|e| format!("invariant '{}': {}", invariant.name, e))?;
+            invariants.push(invariant);
+        }
+        let sender = address_book.resolve_principal("@deployer")?;
+        Ok(Self {
+            metrics: SoakMetrics {
+                invariants: invariants
+                    .iter()
+                    .map(|invariant| InvariantMetrics {
+                        name: invariant.name.clone(),
+                        ..Default::default()
+                    })
+                    .collect(),
+                ..Default::default()
+            },
+            invariants,
+            check_interval: config.check_interval,
+            duration: config
+                .duration
+                .map(|minutes| Duration::from_secs(minutes * 60)),
+            node_rpc_url: node_rpc_url.to_string(),
+            sender,
+            accounts: accounts.to_vec(),
+            contracts,
+            started_at: None,
+            last_checked_height: None,
+            broken: false,
+        })
+    }
+
+    async fn call_invariant(
+        &self,
+        client: &reqwest::Client,
+        invariant: &Invariant,
+    ) -> Result<Value, String> {
+        let url = format!(
+            "{}/v2/contracts/call-read/{}/{}/{}",
+            self.node_rpc_url,
+            invariant.contract_id.issuer,
+            invariant.contract_id.name,
+            invariant.function
+        );
+        let mut arguments = vec![];
+        for arg in invariant.args.iter() {
+            let bytes = arg
+                .serialize_to_vec()
+                .map_err(|e| format!("unable to serialize {}: {}", arg, e))?;
+            arguments.push(bytes_to_hex(&bytes));
+        }
+        let response: ReadOnlyCallResponse = client
+            .post(&url)
+            .json(&json!({ "sender": self.sender, "arguments": arguments }))
+            .send()
+            .await
+            .map_err(|e| format!("unable to query {}: {}", url, e))?
+            .json()
+            .await
+            .map_err(|e| format!("unable to parse response of {}: {}", url, e))?;
+        match (response.okay, response.result) {
+            (true, Some(result)) => decode_value(&result),
+            _ => Err(response.cause.unwrap_or("call failed".to_string())),
+        }
+    }
+
+    /// Checks the invariants, returning the first broken one
+    async fn check_invariants(&mut self, block_height: u64) -> Option<InvariantViolation> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .unwrap_or_default();
+        let mut violation = None;
+        for (index, invariant) in self.invariants.iter().enumerate() {
+            let start = Instant::now();
+            let result = self.call_invariant(&client, invariant).await;
+            let duration_ms = start.elapsed().as_millis() as u64;
+            let metrics = &mut self.metrics.invariants[index];
+            metrics.total_duration_ms += duration_ms;
+            metrics.max_duration_ms = metrics.max_duration_ms.max(duration_ms);
+            match result {
+                Ok(value) => {
+                    metrics.checks += 1;
+                    metrics.last_result = Some(value.to_string());
+                    if value != invariant.expect && violation.is_none() {
+                        violation = Some(InvariantViolation {
+                            name: invariant.name.clone(),
+                            block_height,
+                            expected: invariant.expect.to_string(),
+                            actual: value.to_string(),
+                        });
+                    }
+                }
+                Err(e) => {
+                    metrics.errors += 1;
+                    metrics.last_result = Some(format!("error: {}", e));
+                }
+            }
+        }
+        violation
+    }
+
+    /// Balances of the accounts and data vars of the contracts, as hex encoded values
+    /// for the data vars
+    async fn get_state(&self) -> Result<serde_json::Value, String> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .map_err(|e| e.to_string())?;
+        let mut balances = BTreeMap::new();
+        for account in self.accounts.iter() {
+            let response: AccountResponse = get_json(
+                &client,
+                format!(
+                    "{}/v2/accounts/{}?proof=0",
+                    self.node_rpc_url, account.stx_address
+                ),
+            )
+            .await?;
+            let balance = u128::from_str_radix(response.balance.trim_start_matches("0x"), 16)
+                .map_err(|e| format!("invalid balance of {}: {}", account.label, e))?;
+            balances.insert(account.label.clone(), balance.to_string());
+        }
+        let mut contracts = BTreeMap::new();
+        for contract_id in self.contracts.iter() {
+            let interface: ContractInterfaceResponse = get_json(
+                &client,
+                format!(
+                    "{}/v2/contracts/interface/{}/{}",
+                    self.node_rpc_url, contract_id.issuer, contract_id.name
+                ),
+            )
+            .await?;
+            let mut data_vars = BTreeMap::new();
+            for variable in interface.variables {
+                if variable.access != "variable" {
+                    continue;
+                }
+                let data_var: DataVarResponse = get_json(
+                    &client,
+                    format!(
+                        "{}/v2/data_var/{}/{}/{}?proof=0",
+                        self.node_rpc_url, contract_id.issuer, contract_id.name, variable.name
+                    ),
+                )
+                .await?;
+                let value = decode_value(&data_var.data)
+                    .map(|value| value.to_string())
+                    .unwrap_or(data_var.data);
+                data_vars.insert(variable.name, value);
+            }
+            contracts.insert(contract_id.to_string(), data_vars);
+        }
+        Ok(json!({ "balances": balances, "data_vars": contracts }))
+    }
+
+    async fn dump_state(
+        &self,
+        violation: &InvariantViolation,
+        working_dir: &FileLocation,
+    ) -> Result<FileLocation, String> {
+        let state = match self.get_state().await {
+            Ok(state) => state,
+            Err(e) => json!({ "error": e }),
+        };
+        let dump = json!({
+            "violation": violation,
+            "metrics": self.metrics,
+            "state": state,
+        });
+        let mut location = working_dir.clone();
+        location.append_path(&format!("soak-failure-{}.json", violation.block_height))?;
+        let content = serde_json::to_vec_pretty(&dump).map_err(|e| e.to_string())?;
+        location.write_content(&content)?;
+        Ok(location)
+    }
+
+    fn write_metrics(&self, working_dir: &FileLocation) -> Result<(), String> {
+        let mut location = working_dir.clone();
+        location.append_path(METRICS_FILE)?;
+        let content = serde_json::to_vec_pretty(&self.metrics).map_err(|e| e.to_string())?;
+        location.write_content(&content)
+    }
+
+    /// Checks the invariants when `check_interval` blocks were mined since the last check.
+    /// A broken invariant is reported as a fatal error. Returns `true` once the duration of
+    /// the soak test is over.
+    pub async fn on_block(
+        &mut self,
+        block_height: u64,
+        working_dir: &FileLocation,
+        devnet_event_tx: &Sender<DevnetEvent>,
+    ) -> bool {
+        if self.broken {
+            return false;
+        }
+        let started_at = match self.started_at {
+            Some(started_at) => started_at,
+            None => {
+                self.metrics.started_at = clarity::util::get_epoch_time_secs();
+                *self.started_at.insert(Instant::now())
+            }
+        };
+        self.metrics.stacks_blocks += 1;
+        self.metrics.last_block_height = block_height;
+        self.metrics.elapsed_secs = started_at.elapsed().as_secs();
+
+        let should_check = match self.last_checked_height {
+            Some(height) => block_height >= height + self.check_interval,
+            None => true,
+        };
+        if should_check {
+            self.last_checked_height = Some(block_height);
+            self.metrics.rounds += 1;
+            let violation = self.check_invariants(block_height).await;
+            if let Err(e) = self.write_metrics(working_dir) {
+                let _ = devnet_event_tx.send(DevnetEvent::debug(format!(
+                    "unable to write soak metrics: {}",
+                    e
+                )));
+            }
+            if let Some(violation) = violation {
+                self.broken = true;
+                let dump = match self.dump_state(&violation, working_dir).await {
+                    Ok(location) => format!("state dumped in {}", location),
+                    Err(e) => format!("unable to dump state: {}", e),
+                };
+                let _ = devnet_event_tx.send(DevnetEvent::FatalError(format!(
+                    "invariant '{}' broken at block #{}: expected {}, got {} ({})",
+                    violation.name, block_height, violation.expected, violation.actual, dump
+                )));
+                return false;
+            }
+            let _ = devnet_event_tx.send(DevnetEvent::info(format!(
+                "Soak: {} invariants checked at block #{} ({} min elapsed)",
+                self.invariants.len(),
+                block_height,
+                self.metrics.elapsed_secs / 60
+            )));
+        }
+
+        match self.duration {
+            Some(duration) if started_at.elapsed() >= duration => {
+                let _ = devnet_event_tx.send(DevnetEvent::success(format!(
+                    "Soak test completed: {} rounds of checks over {} blocks, no invariant broken",
+                    self.metrics.rounds, self.metrics.stacks_blocks
+                )));
+                true
+            }
+            _ => false,
+        }
+    }
+}
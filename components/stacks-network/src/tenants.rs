@@ -0,0 +1,547 @@
+//! Devnets of several projects on a shared cluster. Each tenant gets its own namespace, with a
+//! resource quota (and the default requests the quota needs to admit the pods), a network
+//! policy only letting in the traffic of the namespace and of the ingress controller, and a
+//! network id unique among the tenants of the cluster. The tenants are labeled namespaces: a
+//! controller restarting reads them back with `sync` instead of keeping a state of its own.
+//! The cluster is driven through `kubectl`, like `cluster_access`.
+
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::ops::RangeInclusive;
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+
+use clarinet_files::NetworkManifest;
+use serde_json::{json, Value as JsonValue};
+
+pub const MANAGED_BY_LABEL: &str = "app.kubernetes.io/managed-by";
+pub const TENANT_LABEL: &str = "clarinet.hiro.so/tenant";
+pub const NETWORK_ID_LABEL: &str = "clarinet.hiro.so/network-id";
+const MANAGED_BY: &str = "clarinet";
+const NAMESPACE_PREFIX: &str = "devnet-";
+/// Longest name of a namespace, a DNS label
+const MAX_NAMESPACE_LENGTH: usize = 63;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TenantQuota {
+    /// Total of the cpu limits of the pods of the namespace, in k8s quantities (`4`, `500m`)
+    pub cpu: String,
+    pub memory: String,
+    pub pods: u32,
+    /// Requests and limits of the containers that don't set theirs
+    pub default_container_cpu: String,
+    pub default_container_memory: String,
+}
+
+impl Default for TenantQuota {
+    fn default() -> Self {
+        TenantQuota {
+            cpu: "4".to_string(),
+            memory: "8Gi".to_string(),
+            pods: 20,
+            default_container_cpu: "500m".to_string(),
+            default_container_memory: "512Mi".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TenantSpec {
+    /// Name of the project, the namespace is derived from it
+    pub name: String,
+    pub quota: TenantQuota,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Tenant {
+    pub name: String,
+    pub namespace: String,
+    pub network_id: u16,
+}
+
+impl Tenant {
+    /// Sets the network id of the tenant in the network manifest passed to its coordinator
+    pub fn apply_to_network_manifest(&self, network_manifest: &mut NetworkManifest) {
+        if let Some(devnet) = network_manifest.devnet.as_mut() {
+            devnet.network_id = Some(self.network_id);
+        }
+    }
+}
+
+/// Namespace of a tenant: `devnet-<name>`, lowercased, with the characters a DNS label can't
+/// have replaced by dashes
+pub fn get_tenant_namespace(name: &str) -> Result<String, String> {
+    let sanitized: String = name
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    let sanitized = sanitized.trim_matches('-');
+    if sanitized.is_empty() {
+        return Err(format!("unable to derive a namespace from '{}'", name));
+    }
+    let namespace = format!("{}{}", NAMESPACE_PREFIX, sanitized);
+    if namespace.len() > MAX_NAMESPACE_LENGTH {
+        return Err(format!(
+            "the namespace of '{}' would exceed {} characters",
+            name, MAX_NAMESPACE_LENGTH
+        ));
+    }
+    Ok(namespace)
+}
+
+/// Resources of a tenant, as a `List` for `kubectl apply`
+pub fn get_tenant_resources(
+    tenant: &Tenant,
+    quota: &TenantQuota,
+    ingress_namespace: Option<&str>,
+) -> JsonValue {
+    let mut ingress_sources = vec![json!({ "podSelector": {} })];
+    if let Some(ingress_namespace) = ingress_namespace {
+        ingress_sources.push(json!({
+            "namespaceSelector": {
+                "matchLabels": { "kubernetes.io/metadata.name": ingress_namespace }
+            }
+        }));
+    }
+    json!({
+        "apiVersion": "v1",
+        "kind": "List",
+        "items": [
+            {
+                "apiVersion": "v1",
+                "kind": "Namespace",
+                "metadata": {
+                    "name": tenant.namespace,
+                    "labels": {
+                        MANAGED_BY_LABEL: MANAGED_BY,
+                        TENANT_LABEL: tenant.name,
+                        NETWORK_ID_LABEL: tenant.network_id.to_string(),
+                    }
+                }
+            },
+            {
+                "apiVersion": "v1",
+                "kind": "ResourceQuota",
+                "metadata": { "name": "devnet-quota", "namespace": tenant.namespace },
+                "spec": {
+                    "hard": {
+                        "limits.cpu": quota.cpu,
+                        "limits.memory": quota.memory,
+                        "requests.cpu": quota.cpu,
+                        "requests.memory": quota.memory,
+                        "pods": quota.pods.to_string(),
+                    }
+                }
+            },
+            {
+                "apiVersion": "v1",
+                "kind": "LimitRange",
+                "metadata": { "name": "devnet-defaults", "namespace": tenant.namespace },
+                "spec": {
+                    "limits": [{
+                        "type": "Container",
+                        "default": {
+                            "cpu": quota.default_container_cpu,
+                            "memory": quota.default_container_memory,
+                        },
+                        "defaultRequest": {
+                            "cpu": quota.default_container_cpu,
+                            "memory": quota.default_container_memory,
+                        }
+                    }]
+                }
+            },
+            {
+                "apiVersion": "networking.k8s.io/v1",
+                "kind": "NetworkPolicy",
+                "metadata": { "name": "devnet-isolation", "namespace": tenant.namespace },
+                "spec": {
+                    "podSelector": {},
+                    "policyTypes": ["Ingress"],
+                    "ingress": [{ "from": ingress_sources }]
+                }
+            }
+        ]
+    })
+}
+
+/// Network ids of the tenants, unique in a range
+#[derive(Debug, Clone)]
+pub struct NetworkIdAllocator {
+    range: RangeInclusive<u16>,
+    used: BTreeMap<u16, String>,
+}
+
+impl NetworkIdAllocator {
+    pub fn new(range: RangeInclusive<u16>) -> NetworkIdAllocator {
+        NetworkIdAllocator {
+            range,
+            used: BTreeMap::new(),
+        }
+    }
+
+    /// Lowest free id of the range
+    pub fn allocate(&mut self, tenant: &str) -> Result<u16, String> {
+        let Some(network_id) = self.range.clone().find(|id| !self.used.contains_key(id)) else {
+            return Err(format!(
+                "no network id left in {}-{}",
+                self.range.start(),
+                self.range.end()
+            ));
+        };
+        self.used.insert(network_id, tenant.to_string());
+        Ok(network_id)
+    }
+
+    /// Marks an id as used, for the tenants already on the cluster
+    pub fn reserve(&mut self, network_id: u16, tenant: &str) -> Result<(), String> {
+        match self.used.get(&network_id) {
+            Some(owner) if owner != tenant => Err(format!(
+                "network id {} is used by {} and {}",
+                network_id, owner, tenant
+            )),
+            _ => {
+                self.used.insert(network_id, tenant.to_string());
+                Ok(())
+            }
+        }
+    }
+
+    pub fn release(&mut self, network_id: u16) {
+        self.used.remove(&network_id);
+    }
+}
+
+/// Operations of the controller on the cluster
+pub trait ClusterClient: Send + Sync {
+    fn apply(&self, resources: &JsonValue) -> Result<(), String>;
+    fn delete_namespace(&self, namespace: &str) -> Result<(), String>;
+    /// Tenants of the namespaces labeled by the controller
+    fn list_tenants(&self) -> Result<Vec<Tenant>, String>;
+}
+
+/// Client running `kubectl` with the current context
+pub struct KubectlClient;
+
+impl KubectlClient {
+    fn run(args: &[&str], stdin: Option<&[u8]>) -> Result<Vec<u8>, String> {
+        let mut child = Command::new("kubectl")
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("unable to run kubectl: {}", e))?;
+        if let (Some(input), Some(mut child_stdin)) = (stdin, child.stdin.take()) {
+            child_stdin
+                .write_all(input)
+                .map_err(|e| format!("unable to write to kubectl: {}", e))?;
+        }
+        let output = child
+            .wait_with_output()
+            .map_err(|e| format!("unable to run kubectl: {}", e))?;
+        if !output.status.success() {
+            return Err(format!(
+                "kubectl {} failed: {}",
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(output.stdout)
+    }
+}
+
+impl ClusterClient for KubectlClient {
+    fn apply(&self, resources: &JsonValue) -> Result<(), String> {
+        let content = serde_json::to_vec(resources).map_err(|e| e.to_string())?;
+        KubectlClient::run(&["apply", "-f", "-"], Some(&content)).map(|_| ())
+    }
+
+    fn delete_namespace(&self, namespace: &str) -> Result<(), String> {
+        // the namespace terminates in the background, with the resources of the devnet
+        KubectlClient::run(&["delete", "namespace", namespace, "--wait=false"], None).map(|_| ())
+    }
+
+    fn list_tenants(&self) -> Result<Vec<Tenant>, String> {
+        let selector = format!("{}={}", MANAGED_BY_LABEL, MANAGED_BY);
+        let output =
+            KubectlClient::run(&["get", "namespaces", "-l", &selector, "-o", "json"], None)?;
+        let namespaces: JsonValue = serde_json::from_slice(&output)
+            .map_err(|e| format!("unable to parse the namespaces: {}", e))?;
+        Ok(parse_tenants(&namespaces))
+    }
+}
+
+/// Tenants of a `kubectl get namespaces -o json`, the namespaces without a valid network id
+/// are skipped
+pub fn parse_tenants(namespaces: &JsonValue) -> Vec<Tenant> {
+    let Some(items) = namespaces.get("items").and_then(|items| items.as_array()) else {
+        return vec![];
+    };
+    items
+        .iter()
+        .filter_map(|item| {
+            let metadata = item.get("metadata")?;
+            let labels = metadata.get("labels")?;
+            Some(Tenant {
+                name: labels.get(TENANT_LABEL)?.as_str()?.to_string(),
+                namespace: metadata.get("name")?.as_str()?.to_string(),
+                network_id: labels.get(NETWORK_ID_LABEL)?.as_str()?.parse().ok()?,
+            })
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone)]
+pub struct TenantControllerSettings {
+    pub network_ids: RangeInclusive<u16>,
+    /// Namespace of the ingress controller, let in by the network policies
+    pub ingress_namespace: Option<String>,
+}
+
+impl Default for TenantControllerSettings {
+    fn default() -> Self {
+        TenantControllerSettings {
+            network_ids: 1..=u16::MAX,
+            ingress_namespace: None,
+        }
+    }
+}
+
+/// Provisions and tears down the devnets of many projects, from a single process. The methods
+/// take `&self`, the controller can be shared by the threads serving the requests.
+pub struct TenantController {
+    client: Box<dyn ClusterClient>,
+    settings: TenantControllerSettings,
+    state: Mutex<ControllerState>,
+}
+
+struct ControllerState {
+    allocator: NetworkIdAllocator,
+    tenants: BTreeMap<String, Tenant>,
+}
+
+impl TenantController {
+    pub fn new(
+        client: Box<dyn ClusterClient>,
+        settings: TenantControllerSettings,
+    ) -> TenantController {
+        let allocator = NetworkIdAllocator::new(settings.network_ids.clone());
+        TenantController {
+            client,
+            settings,
+            state: Mutex::new(ControllerState {
+                allocator,
+                tenants: BTreeMap::new(),
+            }),
+        }
+    }
+
+    /// Reads back the tenants already on the cluster
+    pub fn sync(&self) -> Result<(), String> {
+        let tenants = self.client.list_tenants()?;
+        let mut state = self.state.lock().unwrap();
+        for tenant in tenants {
+            state.allocator.reserve(tenant.network_id, &tenant.name)?;
+            state.tenants.insert(tenant.name.clone(), tenant);
+        }
+        Ok(())
+    }
+
+    pub fn list(&self) -> Vec<Tenant> {
+        let state = self.state.lock().unwrap();
+        state.tenants.values().cloned().collect()
+    }
+
+    pub fn get(&self, name: &str) -> Option<Tenant> {
+        let state = self.state.lock().unwrap();
+        state.tenants.get(name).cloned()
+    }
+
+    /// Creates the namespace of a project, with its quota and its policies. The network id is
+    /// allocated before applying the resources, the lock isn't held during the call to the
+    /// cluster.
+    pub fn provision(&self, spec: &TenantSpec) -> Result<Tenant, String> {
+        let namespace = get_tenant_namespace(&spec.name)?;
+        let tenant = {
+            let mut state = self.state.lock().unwrap();
+            if state.tenants.contains_key(&spec.name) {
+                return Err(format!("{} already has a devnet", spec.name));
+            }
+            if let Some(other) = state.tenants.values().find(|t| t.namespace == namespace) {
+                return Err(format!(
+                    "{} and {} share the namespace {}",
+                    spec.name, other.name, namespace
+                ));
+            }
+            let tenant = Tenant {
+                name: spec.name.clone(),
+                namespace,
+                network_id: state.allocator.allocate(&spec.name)?,
+            };
+            state.tenants.insert(spec.name.clone(), tenant.clone());
+            tenant
+        };
+        let resources = get_tenant_resources(
+            &tenant,
+            &spec.quota,
+            self.settings.ingress_namespace.as_deref(),
+        );
+        if let Err(e) = self.client.apply(&resources) {
+            let mut state = self.state.lock().unwrap();
+            state.tenants.remove(&tenant.name);
+            state.allocator.release(tenant.network_id);
+            return Err(format!("unable to provision {}: {}", tenant.name, e));
+        }
+        Ok(tenant)
+    }
+
+    /// Deletes the namespace of a project, its network id is free again once deleted
+    pub fn teardown(&self, name: &str) -> Result<Tenant, String> {
+        let Some(tenant) = self.get(name) else {
+            return Err(format!("{} has no devnet", name));
+        };
+        self.client
+            .delete_namespace(&tenant.namespace)
+            .map_err(|e| format!("unable to tear down {}: {}", name, e))?;
+        let mut state = self.state.lock().unwrap();
+        state.tenants.remove(name);
+        state.allocator.release(tenant.network_id);
+        Ok(tenant)
+    }
+
+    /// Provisions the tenants concurrently, the results are in the order of `specs`
+    pub fn provision_all(&self, specs: &[TenantSpec]) -> Vec<Result<Tenant, String>> {
+        std::thread::scope(|scope| {
+            let handles: Vec<_> = specs
+                .iter()
+                .map(|spec| scope.spawn(move || self.provision(spec)))
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().unwrap_or(Err("provisioning panicked".into())))
+                .collect()
+        })
+    }
+
+    /// Tears down the tenants concurrently, the results are in the order of `names`
+    pub fn teardown_all(&self, names: &[String]) -> Vec<Result<Tenant, String>> {
+        std::thread::scope(|scope| {
+            let handles: Vec<_> = names
+                .iter()
+                .map(|name| scope.spawn(move || self.teardown(name)))
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().unwrap_or(Err("teardown panicked".into())))
+                .collect()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct MockClient {
+        namespaces: Mutex<BTreeMap<String, JsonValue>>,
+    }
+
+    impl ClusterClient for MockClient {
+        fn apply(&self, resources: &JsonValue) -> Result<(), String> {
+            let namespace = &resources["items"][0];
+            let name = namespace["metadata"]["name"].as_str().unwrap().to_string();
+            if name == "devnet-broken" {
+                return Err("forbidden".to_string());
+            }
+            let mut namespaces = self.namespaces.lock().unwrap();
+            namespaces.insert(name, namespace.clone());
+            Ok(())
+        }
+
+        fn delete_namespace(&self, namespace: &str) -> Result<(), String> {
+            let mut namespaces = self.namespaces.lock().unwrap();
+            namespaces.remove(namespace);
+            Ok(())
+        }
+
+        fn list_tenants(&self) -> Result<Vec<Tenant>, String> {
+            let namespaces = self.namespaces.lock().unwrap();
+            let items: Vec<JsonValue> = namespaces.values().cloned().collect();
+            Ok(parse_tenants(&json!({ "items": items })))
+        }
+    }
+
+    fn get_spec(name: &str) -> TenantSpec {
+        TenantSpec {
+            name: name.to_string(),
+            quota: TenantQuota::default(),
+        }
+    }
+
+    #[test]
+    fn test_tenant_controller() {
+        assert_eq!(
+            get_tenant_namespace("My_Project"),
+            Ok("devnet-my-project".to_string())
+        );
+        assert!(get_tenant_namespace("__").is_err());
+
+        let settings = TenantControllerSettings {
+            network_ids: 1..=3,
+            ingress_namespace: Some("ingress-nginx".to_string()),
+        };
+        let controller = TenantController::new(Box::<MockClient>::default(), settings);
+        let specs: Vec<TenantSpec> = ["alpha", "beta", "gamma"].map(get_spec).to_vec();
+        let tenants: Vec<Tenant> = controller
+            .provision_all(&specs)
+            .into_iter()
+            .map(Result::unwrap)
+            .collect();
+        let mut network_ids: Vec<u16> = tenants.iter().map(|t| t.network_id).collect();
+        network_ids.sort();
+        assert_eq!(network_ids, vec![1, 2, 3]);
+
+        assert!(controller.provision(&get_spec("alpha")).is_err());
+        assert_eq!(
+            controller.provision(&get_spec("delta")),
+            Err("no network id left in 1-3".to_string())
+        );
+
+        let alpha = controller.teardown("alpha").unwrap();
+        let delta = controller.provision(&get_spec("delta")).unwrap();
+        assert_eq!(delta.network_id, alpha.network_id);
+
+        // a failed apply frees the id it was given
+        let beta = controller.get("beta").unwrap();
+        controller.teardown("beta").unwrap();
+        assert!(controller.provision(&get_spec("broken")).is_err());
+        assert_eq!(
+            controller
+                .provision(&get_spec("epsilon"))
+                .unwrap()
+                .network_id,
+            beta.network_id
+        );
+
+        let resources = get_tenant_resources(&delta, &TenantQuota::default(), Some("ingress"));
+        assert_eq!(
+            parse_tenants(&json!({ "items": [resources["items"][0]] })),
+            vec![delta.clone()]
+        );
+        assert_eq!(resources["items"][1]["spec"]["hard"]["pods"], "20");
+        assert_eq!(
+            resources["items"][0]["metadata"]["labels"][NETWORK_ID_LABEL],
+            delta.network_id.to_string()
+        );
+        assert_eq!(
+            resources["items"][3]["spec"]["ingress"][0]["from"]
+                .as_array()
+                .unwrap()
+                .len(),
+            2
+        );
+    }
+}
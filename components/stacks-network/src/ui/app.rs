@@ -1,5 +1,8 @@
 use super::util::{StatefulList, TabsState};
+use crate::accounts::AccountActivity;
+use crate::coordinator_state::ChainsCoordinatorState;
 use crate::event::ServiceStatusData;
+use crate::signers::SignerStatus;
 use crate::{LogData, MempoolAdmissionData};
 
 use chainhook_sdk::types::{
@@ -25,6 +28,10 @@ pub struct App<'a> {
     pub mempool: StatefulList<MempoolAdmissionData>,
     pub logs: StatefulList<LogData>,
     pub services: StatefulList<ServiceStatusData>,
+    pub signers: Vec<SignerStatus>,
+    pub accounts: Vec<AccountActivity>,
+    pub coordinator_state: ChainsCoordinatorState,
+    pub show_transaction_details: bool,
 }
 
 impl<'a> App<'a> {
@@ -39,24 +46,68 @@ impl<'a> App<'a> {
             mempool: StatefulList::with_items(vec![]),
             logs: StatefulList::with_items(vec![]),
             services: StatefulList::with_items(vec![]),
+            signers: vec![],
+            accounts: vec![],
+            coordinator_state: ChainsCoordinatorState::default(),
+            show_transaction_details: false,
             subnet_enabled,
         }
     }
 
     pub fn on_up(&mut self) {
-        self.transactions.previous();
+        if !self.transactions.items.is_empty() {
+            self.transactions.previous();
+        }
     }
 
     pub fn on_down(&mut self) {
-        self.transactions.next();
+        if !self.transactions.items.is_empty() {
+            self.transactions.next();
+        }
     }
 
     pub fn on_right(&mut self) {
         self.tabs.next();
+        self.select_block_transactions();
     }
 
     pub fn on_left(&mut self) {
         self.tabs.previous();
+        self.select_block_transactions();
+    }
+
+    pub fn on_enter(&mut self) {
+        self.show_transaction_details = self.selected_transaction().is_some();
+    }
+
+    pub fn on_escape(&mut self) {
+        self.show_transaction_details = false;
+    }
+
+    /// Blocks are stored in the order they were received, while tabs display the most recent first
+    pub fn selected_block(&self) -> Option<&BlockData> {
+        if self.tabs.titles.is_empty() {
+            return None;
+        }
+        self.blocks
+            .get((self.tabs.titles.len() - 1) - self.tabs.index)
+    }
+
+    pub fn selected_transaction(&self) -> Option<&StacksTransactionData> {
+        self.transactions
+            .state
+            .selected()
+            .and_then(|index| self.transactions.items.get(index))
+    }
+
+    fn select_block_transactions(&mut self) {
+        let transactions = match self.selected_block() {
+            Some(BlockData::Block(block)) => block.transactions.clone(),
+            Some(BlockData::Microblock(microblock)) => microblock.transactions.clone(),
+            None => vec![],
+        };
+        self.transactions = StatefulList::with_items(transactions);
+        self.show_transaction_details = false;
     }
 
     pub fn on_key(&mut self, c: char) {
@@ -73,6 +124,7 @@ impl<'a> App<'a> {
         self.transactions = StatefulList::with_items(vec![]);
         self.mempool = StatefulList::with_items(vec![]);
         self.logs = StatefulList::with_items(vec![]);
+        self.show_transaction_details = false;
     }
 
     pub fn display_service_status_update(&mut self, service_update: ServiceStatusData) {
@@ -147,8 +199,11 @@ impl<'a> App<'a> {
 
         self.blocks.push(BlockData::Block(block));
 
-        if self.tabs.index != 0 {
+        // keep the selected block while a transaction is inspected
+        if self.tabs.index != 0 || self.show_transaction_details {
             self.tabs.index += 1;
+        } else {
+            self.select_block_transactions();
         }
     }
 
@@ -157,8 +212,11 @@ impl<'a> App<'a> {
             .titles
             .push_front(Span::from("[·]".to_string()).fg(Color::White));
         self.blocks.push(BlockData::Microblock(block));
-        if self.tabs.index != 0 {
+        // keep the selected block while a transaction is inspected
+        if self.tabs.index != 0 || self.show_transaction_details {
             self.tabs.index += 1;
+        } else {
+            self.select_block_transactions();
         }
     }
 }
@@ -6,7 +6,9 @@ mod ui;
 mod util;
 
 use super::DevnetEvent;
+use crate::event::DeployingStatus;
 
+use crate::coordinator_state::ChainsCoordinatorHandle;
 use crate::{chains_coordinator::BitcoinMiningCommand, ChainsCoordinatorCommand};
 
 use app::App;
@@ -29,6 +31,7 @@ pub fn start_ui(
     devnet_events_tx: Sender<DevnetEvent>,
     devnet_events_rx: Receiver<DevnetEvent>,
     chains_coordinator_commands_tx: crossbeam_channel::Sender<ChainsCoordinatorCommand>,
+    coordinator_handle: ChainsCoordinatorHandle,
     orchestrator_terminated_rx: Receiver<bool>,
     devnet_path: &str,
     subnet_enabled: bool,
@@ -39,6 +42,7 @@ pub fn start_ui(
         devnet_events_tx,
         devnet_events_rx,
         chains_coordinator_commands_tx,
+        coordinator_handle,
         orchestrator_terminated_rx,
         devnet_path,
         subnet_enabled,
@@ -55,6 +59,7 @@ pub fn do_start_ui(
     devnet_events_tx: Sender<DevnetEvent>,
     devnet_events_rx: Receiver<DevnetEvent>,
     chains_coordinator_commands_tx: crossbeam_channel::Sender<ChainsCoordinatorCommand>,
+    coordinator_handle: ChainsCoordinatorHandle,
     orchestrator_terminated_rx: Receiver<bool>,
     devnet_path: &str,
     subnet_enabled: bool,
@@ -104,6 +109,7 @@ pub fn do_start_ui(
     let mut mining_command_tx: Option<Sender<BitcoinMiningCommand>> = None;
 
     loop {
+        app.coordinator_state = coordinator_handle.state();
         terminal
             .draw(|f| ui::draw(f, &mut app))
             .map_err(|e| format!("unable to update ui: {}", e))?;
@@ -159,6 +165,8 @@ pub fn do_start_ui(
                 (KeyModifiers::NONE, KeyCode::Up) => app.on_up(),
                 (KeyModifiers::NONE, KeyCode::Right) => app.on_right(),
                 (KeyModifiers::NONE, KeyCode::Down) => app.on_down(),
+                (KeyModifiers::NONE, KeyCode::Enter) => app.on_enter(),
+                (KeyModifiers::NONE, KeyCode::Esc) => app.on_escape(),
                 _ => {}
             },
             DevnetEvent::Tick => {
@@ -238,8 +246,27 @@ pub fn do_start_ui(
             DevnetEvent::MempoolAdmission(tx) => {
                 app.add_to_mempool(tx);
             }
-            DevnetEvent::ProtocolDeployingProgress(_) => {
-                // Display something
+            DevnetEvent::SignersStatus(signers) => {
+                app.signers = signers;
+            }
+            DevnetEvent::AccountsStatus(accounts) => {
+                app.accounts = accounts;
+            }
+            DevnetEvent::ProtocolDeployingProgress(progress) => {
+                let message = format!(
+                    "Deployment {}/{}: {} {}",
+                    progress.confirmed, progress.total, progress.name, progress.status
+                );
+                match progress.status {
+                    DeployingStatus::Broadcasted => {
+                        app.display_log(DevnetEvent::log_info(message), ctx)
+                    }
+                    DeployingStatus::Confirmed => {
+                        app.display_log(DevnetEvent::log_success(message), ctx)
+                    }
+                    // failures are reported as fatal errors
+                    DeployingStatus::Encoded | DeployingStatus::Failed => {}
+                }
             }
             DevnetEvent::FatalError(message) => {
                 app.display_log(DevnetEvent::log_error(format!("Fatal: {}", message)), ctx);
@@ -250,9 +277,12 @@ pub fn do_start_ui(
                 );
                 return Err(message);
             }
-            DevnetEvent::BootCompleted(bitcoin_mining_tx) => {
+            DevnetEvent::BootCompleted(bitcoin_mining_tx, boot_report) => {
                 app.display_log(
-                    DevnetEvent::log_success("Local Devnet network ready".into()),
+                    DevnetEvent::log_success(format!(
+                        "Local Devnet network ready, {}",
+                        boot_report
+                    )),
                     ctx,
                 );
                 if automining_enabled {
@@ -261,6 +291,11 @@ pub fn do_start_ui(
                 mining_command_tx = Some(bitcoin_mining_tx);
             }
             DevnetEvent::Terminate => {
+                // the devnet is stopping by itself, at the end of a soak test
+                let _ = disable_raw_mode();
+                let _ = execute!(terminal.backend_mut(), LeaveAlternateScreen);
+                let _ = orchestrator_terminated_rx.recv();
+                let _ = terminal.show_cursor();
                 break;
             }
         }
@@ -1,17 +1,32 @@
-use super::{app::BlockData, App};
+use super::{app::BlockData, util::StatefulList, App};
 
-use crate::{event::Status, log::LogLevel};
+use crate::{
+    accounts::get_sparkline, event::Status, log::LogLevel, pox::format_stx,
+    signers::SignerBlockResponse,
+};
 
-use chainhook_sdk::types::{StacksBlockData, StacksMicroblockData, StacksTransactionData};
+use chainhook_sdk::types::{
+    StacksBlockData, StacksMicroblockData, StacksTransactionData, StacksTransactionKind,
+};
 use ratatui::{prelude::*, widgets::*};
 
+/// Accounts listed in the accounts pane, the others are only watched for warnings
+const MAX_ACCOUNTS_ROWS: usize = 10;
+
 pub fn draw(f: &mut Frame, app: &mut App) {
+    let accounts_len = if app.accounts.is_empty() {
+        0
+    } else {
+        app.accounts.len().min(MAX_ACCOUNTS_ROWS) as u16 + 2
+    };
+
     let page_components = Layout::default()
         .direction(Direction::Vertical)
         .constraints(
             [
                 Constraint::Length(0),
                 Constraint::Length(20),
+                Constraint::Length(accounts_len),
                 Constraint::Min(0),
                 Constraint::Length(0),
             ]
@@ -30,16 +45,35 @@ pub fn draw(f: &mut Frame, app: &mut App) {
 
     let service_len = nb_of_services + nb_of_signers + nb_of_subnet_services + 2;
 
+    let signers_len = if app.signers.is_empty() {
+        0
+    } else {
+        app.signers.len() as u16 + 2
+    };
+
     let top_right_components = Layout::default()
         .direction(Direction::Vertical)
-        .constraints([Constraint::Length(service_len), Constraint::Min(1)].as_ref())
+        .constraints(
+            [
+                Constraint::Length(service_len),
+                Constraint::Length(signers_len),
+                Constraint::Min(1),
+            ]
+            .as_ref(),
+        )
         .split(devnet_status_components[1]);
 
     draw_devnet_status(f, app, devnet_status_components[0]);
     draw_services_status(f, app, top_right_components[0]);
-    draw_mempool(f, app, top_right_components[1]);
-    draw_blocks(f, app, page_components[2]);
-    draw_help(f, app, page_components[3]);
+    if !app.signers.is_empty() {
+        draw_signers_status(f, app, top_right_components[1]);
+    }
+    draw_mempool(f, app, top_right_components[2]);
+    if !app.accounts.is_empty() {
+        draw_accounts(f, app, page_components[2]);
+    }
+    draw_blocks(f, app, page_components[3]);
+    draw_help(f, app, page_components[4]);
 }
 
 fn draw_services_status(f: &mut Frame, app: &mut App, area: Rect) {
@@ -68,6 +102,94 @@ fn draw_services_status(f: &mut Frame, app: &mut App, area: Rect) {
     f.render_widget(t, area);
 }
 
+fn draw_signers_status(f: &mut Frame, app: &mut App, area: Rect) {
+    let rows = app.signers.iter().map(|signer| {
+        let slot = match signer.stackerdb_slot {
+            Some(slot) => format!("slot {}", slot),
+            None => "not in reward set".to_string(),
+        };
+        let last_signed = match signer.last_signed_block_height {
+            Some(height) => format!("signed #{}", height),
+            None => "no signature".to_string(),
+        };
+        let (style, last_response) = match &signer.last_response {
+            Some(SignerBlockResponse::Accepted { .. }) => {
+                (Style::default().fg(Color::White), "accepted".to_string())
+            }
+            Some(SignerBlockResponse::Rejected { reason }) => (
+                Style::default().fg(Color::LightYellow),
+                format!("rejected: {}", reason),
+            ),
+            None => (Style::default().fg(Color::DarkGray), "-".to_string()),
+        };
+
+        Row::new(vec![
+            Cell::from(format!("stacks-signer-{}", signer.signer_id)),
+            Cell::from(slot),
+            Cell::from(last_signed),
+            Cell::from(format!("{} rejected", signer.rejected_proposals)),
+            Cell::from(last_response).style(style),
+        ])
+    });
+
+    let t = Table::new(rows, vec![] as Vec<&Constraint>)
+        .block(Block::default().borders(Borders::ALL).title("Signers"))
+        .style(Style::new().fg(Color::White))
+        .widths([
+            Constraint::Length(16),
+            Constraint::Length(17),
+            Constraint::Length(13),
+            Constraint::Length(11),
+            Constraint::Min(1),
+        ]);
+    f.render_widget(t, area);
+}
+
+fn draw_accounts(f: &mut Frame, app: &mut App, area: Rect) {
+    let rows = app.accounts.iter().take(MAX_ACCOUNTS_ROWS).map(|account| {
+        let (style, status) = if account.is_drained() {
+            (Style::default().fg(Color::LightRed), "draining".to_string())
+        } else if account.is_stuck() {
+            (
+                Style::default().fg(Color::LightYellow),
+                format!("stuck for {} blocks", account.idle_blocks),
+            )
+        } else {
+            (Style::default().fg(Color::White), "".to_string())
+        };
+        let pending = match account.pending_nonce {
+            Some(nonce) => format!("pending #{}", nonce),
+            None => "-".to_string(),
+        };
+        let balances = account.balances.iter().copied().collect::<Vec<_>>();
+
+        Row::new(vec![
+            Cell::from(account.label.clone()),
+            Cell::from(account.address.clone()),
+            Cell::from(format!("nonce {}", account.nonce)),
+            Cell::from(pending),
+            Cell::from(format_stx(account.balance)),
+            Cell::from(get_sparkline(&balances)),
+            Cell::from(status),
+        ])
+        .style(style)
+    });
+
+    let t = Table::new(rows, vec![] as Vec<&Constraint>)
+        .block(Block::default().borders(Borders::ALL).title("Accounts"))
+        .style(Style::new().fg(Color::White))
+        .widths([
+            Constraint::Length(16),
+            Constraint::Length(42),
+            Constraint::Length(11),
+            Constraint::Length(14),
+            Constraint::Length(24),
+            Constraint::Length(32),
+            Constraint::Min(1),
+        ]);
+    f.render_widget(t, area);
+}
+
 fn draw_mempool(f: &mut Frame, app: &mut App, area: Rect) {
     let rows = app.mempool.items.iter().map(|item| {
         let cells = vec![Cell::from(item.tx_description.clone())];
@@ -108,10 +230,21 @@ fn draw_devnet_status(f: &mut Frame, app: &mut App, area: Rect) {
             ListItem::new(vec![log])
         })
         .collect();
+    let state = &app.coordinator_state;
+    let mut title = format!(
+        "Stacks Devnet - bitcoin #{} - stacks #{}",
+        state.bitcoin_block_height, state.stacks_block_height
+    );
+    if let Some(epoch) = state.epoch {
+        title.push_str(&format!(" - epoch {}", epoch));
+    }
+    if let Some(pox_cycle) = state.pox_cycle {
+        title.push_str(&format!(" - pox cycle {}", pox_cycle));
+    }
     let block = Block::default()
         .style(Style::default().fg(Color::White))
         .borders(Borders::ALL)
-        .title("Stacks Devnet");
+        .title(title);
     let inner_area = block.inner(area);
     f.render_widget(block, area);
 
@@ -148,20 +281,22 @@ fn draw_blocks(f: &mut Frame, app: &mut App, area: Rect) {
 
     f.render_widget(blocks, blocks_components[0]);
 
-    if app.tabs.titles.is_empty() {
-        return;
+    if app.show_transaction_details {
+        if let Some(transaction) = app.selected_transaction() {
+            draw_transaction_details(f, blocks_components[1], transaction);
+            return;
+        }
     }
-    let transactions = match &app.blocks[(app.tabs.titles.len() - 1) - app.tabs.index] {
-        BlockData::Block(selected_block) => {
+    match app.selected_block() {
+        Some(BlockData::Block(selected_block)) => {
             draw_block_details(f, block_details_components[0], selected_block);
-            &selected_block.transactions
         }
-        BlockData::Microblock(selected_microblock) => {
+        Some(BlockData::Microblock(selected_microblock)) => {
             draw_microblock_details(f, block_details_components[0], selected_microblock);
-            &selected_microblock.transactions
         }
+        None => return,
     };
-    draw_transactions(f, block_details_components[1], transactions);
+    draw_transactions(f, block_details_components[1], &mut app.transactions);
 }
 
 fn draw_block_details(f: &mut Frame, area: Rect, block: &StacksBlockData) {
@@ -297,8 +432,13 @@ fn draw_microblock_details(f: &mut Frame, area: Rect, microblock: &StacksMicrobl
     f.render_widget(paragraph, labels[8]);
 }
 
-fn draw_transactions(f: &mut Frame, area: Rect, transactions: &[StacksTransactionData]) {
-    let transactions: Vec<ListItem> = transactions
+fn draw_transactions(
+    f: &mut Frame,
+    area: Rect,
+    transactions: &mut StatefulList<StacksTransactionData>,
+) {
+    let items: Vec<ListItem> = transactions
+        .items
         .iter()
         .map(|t| {
             let tx_info = Line::from(vec![
@@ -321,7 +461,7 @@ fn draw_transactions(f: &mut Frame, area: Rect, transactions: &[StacksTransactio
         })
         .collect();
 
-    let list = List::new(transactions)
+    let list = List::new(items)
         .block(
             Block::default()
                 .borders(Borders::LEFT)
@@ -336,13 +476,105 @@ fn draw_transactions(f: &mut Frame, area: Rect, transactions: &[StacksTransactio
         .highlight_symbol("* ");
     let mut inner_area = area;
     inner_area.height = inner_area.height.saturating_sub(1);
-    f.render_widget(list, inner_area);
+    f.render_stateful_widget(list, inner_area, &mut transactions.state);
+}
+
+fn get_payload_lines(transaction: &StacksTransactionData) -> Vec<Line<'static>> {
+    let field = |label: &str, value: String| {
+        Line::from(vec![
+            Span::raw(format!("{}: ", label)),
+            Span::styled(value, Style::default().add_modifier(Modifier::BOLD)),
+        ])
+    };
+    match &transaction.metadata.kind {
+        StacksTransactionKind::ContractCall(data) => {
+            let mut lines = vec![
+                field("Type", "contract call".into()),
+                field("Contract", data.contract_identifier.clone()),
+                field("Function", data.method.clone()),
+            ];
+            for (index, arg) in data.args.iter().enumerate() {
+                lines.push(field(&format!("Argument #{}", index + 1), arg.clone()));
+            }
+            lines
+        }
+        StacksTransactionKind::ContractDeployment(data) => vec![
+            field("Type", "contract deployment".into()),
+            field("Contract", data.contract_identifier.clone()),
+            field("Code size", format!("{} bytes", data.code.len())),
+        ],
+        StacksTransactionKind::NativeTokenTransfer => {
+            vec![field("Type", "stx transfer".into())]
+        }
+        StacksTransactionKind::Coinbase => vec![field("Type", "coinbase".into())],
+        StacksTransactionKind::TenureChange => vec![field("Type", "tenure change".into())],
+        kind => vec![field("Type", format!("{:?}", kind))],
+    }
+}
+
+fn draw_transaction_details(f: &mut Frame, area: Rect, transaction: &StacksTransactionData) {
+    let block = Block::default()
+        .borders(Borders::NONE)
+        .title("Transaction (esc to go back)");
+    let inner_area = block.inner(area);
+    f.render_widget(block, area);
+
+    let components = Layout::default()
+        .direction(Direction::Horizontal)
+        .horizontal_margin(2)
+        .vertical_margin(1)
+        .constraints([Constraint::Length(75), Constraint::Min(1)].as_ref())
+        .split(inner_area);
+
+    let bold = Style::default().add_modifier(Modifier::BOLD);
+    let mut lines = vec![
+        Line::from(Span::styled("Transaction information", bold)),
+        Line::from(transaction.transaction_identifier.hash.clone()),
+        Line::from(format!("Sender: {}", transaction.metadata.sender)),
+        Line::from(format!(
+            "Nonce: {}    Fee: {} µSTX",
+            transaction.metadata.nonce, transaction.metadata.fee
+        )),
+        Line::from(""),
+        Line::from(Span::styled("Payload", bold)),
+    ];
+    lines.append(&mut get_payload_lines(transaction));
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled("Result", bold)));
+    lines.push(Line::from(vec![
+        Span::raw(match transaction.metadata.success {
+            true => "🟩 ",
+            false => "🟥 ",
+        }),
+        Span::raw(transaction.metadata.result.clone()),
+    ]));
+    let paragraph = Paragraph::new(lines).wrap(Wrap { trim: false });
+    f.render_widget(paragraph, components[0]);
+
+    let events: Vec<ListItem> = transaction
+        .metadata
+        .receipt
+        .events
+        .iter()
+        .map(|event| {
+            let label = serde_json::to_string(event).unwrap_or_else(|e| e.to_string());
+            ListItem::new(Line::from(label))
+        })
+        .collect();
+    let title = format!("Events ({})", events.len());
+    let list = List::new(events).block(
+        Block::default()
+            .borders(Borders::LEFT)
+            .style(Style::default().fg(Color::White))
+            .title(title),
+    );
+    f.render_widget(list, components[1]);
 }
 
 fn draw_help(f: &mut Frame, app: &mut App, area: Rect) {
     // let help =
     //     " ⬅️  ➡️  Explore blocks          ⬆️  ⬇️  Explore transactions          0️⃣  Genesis Reset";
-    let help = format!(" ⬅️  ➡️  Explore blocks          Path: {}", app.devnet_path);
+    let help = format!(" ⬅️  ➡️  Explore blocks          ⬆️  ⬇️  Select transaction          ⏎  Inspect transaction          Path: {}", app.devnet_path);
     let paragraph = Paragraph::new(help.clone())
         .style(Style::default().fg(Color::White))
         .block(Block::default().borders(Borders::NONE));
@@ -0,0 +1,201 @@
+//! Receiver of the webhooks delivered during a devnet run (`enable_webhook_receiver`), e.g.
+//! by the `http_post` actions of the chainhooks pointing to
+//! `http://localhost:<webhook_receiver_port>/...`. Every request is acknowledged and kept, for
+//! the dashboard and for the integration tests asserting the expected deliveries.
+
+use std::collections::BTreeMap;
+use std::io::{ErrorKind, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::event::DevnetEvent;
+
+const MAX_REQUEST_SIZE: usize = 16 * 1024 * 1024;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WebhookDelivery {
+    /// RFC 3339 timestamp
+    pub received_at: String,
+    pub method: String,
+    pub path: String,
+    /// Header names lowercased
+    pub headers: BTreeMap<String, String>,
+    /// JSON body, or the raw body as a string when it is not JSON
+    pub body: serde_json::Value,
+}
+
+impl WebhookDelivery {
+    pub fn summary(&self) -> String {
+        let size = match self.body {
+            serde_json::Value::Null => 0,
+            ref body => body.to_string().len(),
+        };
+        format!(
+            "webhook delivered: {} {} ({} bytes)",
+            self.method, self.path, size
+        )
+    }
+}
+
+/// Parses a buffered HTTP request, `None` until the headers and the body are received
+pub fn parse_request(buffer: &[u8]) -> Result<Option<WebhookDelivery>, String> {
+    let Some(headers_end) = buffer.windows(4).position(|w| w == b"\r\n\r\n") else {
+        return Ok(None);
+    };
+    let head = std::str::from_utf8(&buffer[..headers_end])
+        .map_err(|_| "invalid request headers".to_string())?;
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next().unwrap_or_default().split_whitespace();
+    let (Some(method), Some(path)) = (request_line.next(), request_line.next()) else {
+        return Err(format!("invalid request line '{}'", head));
+    };
+
+    let mut headers = BTreeMap::new();
+    for line in lines {
+        if let Some((name, value)) = line.split_once(':') {
+            headers.insert(name.trim().to_lowercase(), value.trim().to_string());
+        }
+    }
+    let content_length = match headers.get("content-length") {
+        Some(length) => length
+            .parse::<usize>()
+            .map_err(|_| format!("invalid content-length '{}'", length))?,
+        None => 0,
+    };
+    let body_start = headers_end + 4;
+    if buffer.len() < body_start + content_length {
+        return Ok(None);
+    }
+    let raw_body = &buffer[body_start..body_start + content_length];
+    let body = if raw_body.is_empty() {
+        serde_json::Value::Null
+    } else {
+        serde_json::from_slice(raw_body).unwrap_or_else(|_| {
+            serde_json::Value::String(String::from_utf8_lossy(raw_body).to_string())
+        })
+    };
+
+    Ok(Some(WebhookDelivery {
+        received_at: chrono::Utc::now().to_rfc3339(),
+        method: method.to_string(),
+        path: path.to_string(),
+        headers,
+        body,
+    }))
+}
+
+pub(crate) fn read_request(stream: &mut TcpStream) -> Result<WebhookDelivery, String> {
+    stream
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .map_err(|e| e.to_string())?;
+    let mut buffer = vec![];
+    let mut chunk = [0u8; 8192];
+    loop {
+        let read = stream.read(&mut chunk).map_err(|e| e.to_string())?;
+        if read == 0 {
+            return Err("connection closed before the end of the request".to_string());
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+        if let Some(delivery) = parse_request(&buffer)? {
+            return Ok(delivery);
+        }
+        if buffer.len() > MAX_REQUEST_SIZE {
+            return Err("request too large".to_string());
+        }
+    }
+}
+
+/// Deliveries received since the start of the devnet, shared with the chains coordinator
+#[derive(Debug, Clone, Default)]
+pub struct WebhookReceiver {
+    deliveries: Arc<Mutex<Vec<WebhookDelivery>>>,
+    stopped: Arc<AtomicBool>,
+}
+
+impl WebhookReceiver {
+    pub fn start(port: u16, devnet_event_tx: Sender<DevnetEvent>) -> Result<Self, String> {
+        let listener = TcpListener::bind(("0.0.0.0", port)).map_err(|e| {
+            format!(
+                "unable to start the webhook receiver on port {}: {}",
+                port, e
+            )
+        })?;
+        listener
+            .set_nonblocking(true)
+            .map_err(|e| format!("unable to start the webhook receiver: {}", e))?;
+
+        let receiver = WebhookReceiver::default();
+        let deliveries = receiver.deliveries.clone();
+        let stopped = receiver.stopped.clone();
+        let _ = hiro_system_kit::thread_named("Webhook receiver").spawn(move || {
+            while !stopped.load(Ordering::SeqCst) {
+                let mut stream = match listener.accept() {
+                    Ok((stream, _)) => stream,
+                    Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                        std::thread::sleep(Duration::from_millis(100));
+                        continue;
+                    }
+                    Err(_) => continue,
+                };
+                let _ = stream.set_nonblocking(false);
+                match read_request(&mut stream) {
+                    Ok(delivery) => {
+                        let _ = stream.write_all(
+                            b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                        );
+                        let _ = devnet_event_tx.send(DevnetEvent::info(delivery.summary()));
+                        deliveries.lock().unwrap().push(delivery.clone());
+                        let _ = devnet_event_tx.send(DevnetEvent::WebhookDelivered(delivery));
+                    }
+                    Err(e) => {
+                        let _ = stream.write_all(
+                            b"HTTP/1.1 400 Bad Request\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                        );
+                        let _ = devnet_event_tx
+                            .send(DevnetEvent::warning(format!("invalid webhook: {}", e)));
+                    }
+                }
+            }
+        });
+        Ok(receiver)
+    }
+
+    pub fn get_deliveries(&self) -> Vec<WebhookDelivery> {
+        self.deliveries.lock().unwrap().clone()
+    }
+
+    pub fn stop(&self) {
+        self.stopped.store(true, Ordering::SeqCst);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_request() {
+        let request = b"POST /chainhooks/counter HTTP/1.1\r\nHost: localhost\r\nContent-Type: application/json\r\nContent-Length: 12\r\n\r\n{\"apply\":[]}";
+        assert_eq!(parse_request(&request[..60]).unwrap(), None);
+        assert_eq!(parse_request(&request[..request.len() - 1]).unwrap(), None);
+
+        let delivery = parse_request(request).unwrap().unwrap();
+        assert_eq!(delivery.method, "POST");
+        assert_eq!(delivery.path, "/chainhooks/counter");
+        assert_eq!(
+            delivery.headers.get("content-type"),
+            Some(&"application/json".to_string())
+        );
+        assert_eq!(delivery.body, serde_json::json!({ "apply": [] }));
+
+        let delivery = parse_request(b"POST /raw HTTP/1.1\r\nContent-Length: 2\r\n\r\nok")
+            .unwrap()
+            .unwrap();
+        assert_eq!(delivery.body, serde_json::Value::String("ok".to_string()));
+
+        assert!(parse_request(b"POST\r\n\r\n").is_err());
+    }
+}
@@ -1,13 +1,15 @@
 use std::str::FromStr;
 
+use stacks_codec::builder::TxBuilder;
 use stacks_codec::codec::*;
 
+use crate::signer::{InMemorySigner, TxSigner};
+
 use clarity::address::{
     AddressHashMode, C32_ADDRESS_VERSION_MAINNET_SINGLESIG, C32_ADDRESS_VERSION_TESTNET_SINGLESIG,
 };
-use clarity::codec::StacksMessageCodec;
 use clarity::types::chainstate::StacksAddress;
-use clarity::util::secp256k1::{MessageSignature, Secp256k1PrivateKey, Secp256k1PublicKey};
+use clarity::util::secp256k1::{Secp256k1PrivateKey, Secp256k1PublicKey};
 use clarity::vm::types::{PrincipalData, QualifiedContractIdentifier};
 use clarity::vm::{ClarityName, ClarityVersion, ContractName, Value as ClarityValue};
 use hmac::Hmac;
@@ -73,44 +75,17 @@ pub fn sign_transaction_payload(
     tx_fee: u64,
     anchor_mode: TransactionAnchorMode,
 ) -> Result<StacksTransaction, String> {
-    let keypair = compute_keypair(wallet);
-    let signer_addr = compute_stacks_address(&keypair.public_key, wallet.mainnet);
-
-    let spending_condition = TransactionSpendingCondition::Singlesig(SinglesigSpendingCondition {
-        signer: signer_addr.bytes,
-        nonce,
-        tx_fee,
-        hash_mode: SinglesigHashMode::P2PKH,
-        key_encoding: TransactionPublicKeyEncoding::Compressed,
-        signature: MessageSignature::empty(),
-    });
-
-    let auth = TransactionAuth::Standard(spending_condition);
-    let unsigned_tx = StacksTransaction {
-        version: match wallet.mainnet {
-            true => TransactionVersion::Mainnet,
-            false => TransactionVersion::Testnet,
-        },
-        chain_id: match wallet.mainnet {
-            true => 0x00000001,
-            false => 0x80000000,
-        },
-        auth,
-        anchor_mode,
-        post_condition_mode: TransactionPostConditionMode::Allow,
-        post_conditions: vec![],
-        payload,
-    };
-
-    let mut unsigned_tx_bytes = vec![];
-    unsigned_tx
-        .consensus_serialize(&mut unsigned_tx_bytes)
-        .expect("FATAL: invalid transaction");
-
-    let mut tx_signer = StacksTransactionSigner::new(&unsigned_tx);
-    tx_signer.sign_origin(&keypair.secret_key).unwrap();
-    let signed_tx = tx_signer.get_tx().unwrap();
-    Ok(signed_tx)
+    let signer = InMemorySigner::from_mnemonic(&wallet.mnemonic, &wallet.derivation)?;
+    signer.sign(
+        TxBuilder::new(payload)
+            .network(match wallet.mainnet {
+                true => TransactionVersion::Mainnet,
+                false => TransactionVersion::Testnet,
+            })
+            .nonce(nonce)
+            .fee(tx_fee)
+            .anchor_mode(anchor_mode),
+    )
 }
 
 pub fn encode_contract_call(
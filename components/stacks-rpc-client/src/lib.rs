@@ -12,4 +12,6 @@ pub mod rpc_client;
 
 pub mod crypto;
 
+pub mod signer;
+
 pub use rpc_client::StacksRpc;
@@ -14,12 +14,20 @@ pub enum RpcError {
     Generic,
     StatusCode(u16),
     Message(String),
+    /// The node couldn't be reached or couldn't process the request, retrying can succeed
+    Unavailable(String),
+}
+
+impl RpcError {
+    pub fn is_transient(&self) -> bool {
+        matches!(self, RpcError::Unavailable(_))
+    }
 }
 
 impl std::fmt::Display for RpcError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match &self {
-            RpcError::Message(e) => write!(f, "{}", e),
+            RpcError::Message(e) | RpcError::Unavailable(e) => write!(f, "{}", e),
             RpcError::StatusCode(e) => write!(f, "error status code {}", e),
             RpcError::Generic => write!(f, "unknown error"),
         }
@@ -101,6 +109,23 @@ pub struct Contract {
     pub publish_height: u64,
 }
 
+#[derive(Deserialize, Debug)]
+pub struct ContractInterfaceFunctionArg {
+    pub name: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ContractInterfaceFunction {
+    pub name: String,
+    pub access: String,
+    pub args: Vec<ContractInterfaceFunctionArg>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ContractInterface {
+    pub functions: Vec<ContractInterfaceFunction>,
+}
+
 #[derive(Deserialize, Debug)]
 pub struct FeeEstimationReport {
     pub estimations: Vec<FeeEstimation>,
@@ -119,6 +144,14 @@ impl StacksRpc {
         }
     }
 
+    /// Sends the requests with `client`, for instance to go through a proxy
+    pub fn with_client(url: &str, client: Client) -> Self {
+        Self {
+            url: url.into(),
+            client,
+        }
+    }
+
     pub fn estimate_transaction_fee(
         &self,
         transaction_payload: &TransactionPayload,
@@ -151,14 +184,18 @@ impl StacksRpc {
             .header("Content-Type", "application/octet-stream")
             .body(tx)
             .send()
-            .map_err(|e| RpcError::Message(e.to_string()))?;
+            .map_err(|e| RpcError::Unavailable(e.to_string()))?;
 
-        if !res.status().is_success() {
-            let err = match res.text() {
-                Ok(message) => RpcError::Message(message),
-                Err(e) => RpcError::Message(e.to_string()),
+        let status = res.status();
+        if !status.is_success() {
+            let message = match res.text() {
+                Ok(message) => message,
+                Err(e) => e.to_string(),
             };
-            return Err(err);
+            if status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+                return Err(RpcError::Unavailable(format!("{} ({})", message, status)));
+            }
+            return Err(RpcError::Message(message));
         }
 
         let txid: String = res.json().unwrap();
@@ -180,6 +217,21 @@ impl StacksRpc {
         Ok(nonce)
     }
 
+    /// Balance of an account, in microSTX
+    pub fn get_balance(&self, address: &str) -> Result<u128, RpcError> {
+        let request_url = format!("{}/v2/accounts/{addr}", self.url, addr = address);
+
+        let res: Balance = self
+            .client
+            .get(request_url)
+            .send()
+            .map_err(|e| RpcError::Message(e.to_string()))?
+            .json()
+            .map_err(|e| RpcError::Message(e.to_string()))?;
+        u128::from_str_radix(res.balance.trim_start_matches("0x"), 16)
+            .map_err(|e| RpcError::Message(format!("invalid balance {}: {}", res.balance, e)))
+    }
+
     pub fn get_pox_info(&self) -> Result<PoxInfo, RpcError> {
         let request_url = format!("{}/v2/pox", self.url);
 
@@ -219,10 +271,37 @@ impl StacksRpc {
                 Ok(value) => Ok(value),
                 Err(e) => Err(RpcError::Message(e.to_string())),
             },
-            Err(e) => Err(RpcError::Message(e.to_string())),
+            Err(e) => Err(RpcError::Unavailable(e.to_string())),
         }
     }
 
+    /// Returns `None` if the contract isn't published
+    pub fn get_contract_interface(
+        &self,
+        principal: &str,
+        contract_name: &str,
+    ) -> Result<Option<ContractInterface>, RpcError> {
+        let request_url = format!(
+            "{}/v2/contracts/interface/{}/{}",
+            self.url, principal, contract_name
+        );
+
+        let res = self
+            .client
+            .get(request_url)
+            .send()
+            .map_err(|e| RpcError::Message(e.to_string()))?;
+        if res.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !res.status().is_success() {
+            return Err(RpcError::StatusCode(res.status().as_u16()));
+        }
+        res.json()
+            .map(Some)
+            .map_err(|e| RpcError::Message(e.to_string()))
+    }
+
     pub fn call_read_only_fn(
         &self,
         contract_addr: &str,
@@ -248,7 +327,7 @@ impl StacksRpc {
                 "arguments": arguments,
             }))
             .send()
-            .unwrap();
+            .map_err(|e| RpcError::Unavailable(e.to_string()))?;
 
         if !res.status().is_success() {
             let error = match res.text() {
@@ -261,22 +340,66 @@ impl StacksRpc {
         #[derive(Deserialize, Debug)]
         struct ReadOnlyCallResult {
             okay: bool,
-            result: String,
+            result: Option<String>,
+            cause: Option<String>,
         }
 
-        let response: ReadOnlyCallResult = res.json().unwrap();
-        if response.okay {
-            // Removing the 0x prefix
-            let raw_value = match response.result.strip_prefix("0x") {
-                Some(raw_value) => raw_value,
-                _ => panic!(),
-            };
-            let bytes = hex_bytes(raw_value).unwrap();
-            let mut cursor = Cursor::new(&bytes);
-            let value = Value::consensus_deserialize(&mut cursor).unwrap();
-            Ok(value)
-        } else {
-            Err(RpcError::Generic)
+        let response: ReadOnlyCallResult =
+            res.json().map_err(|e| RpcError::Message(e.to_string()))?;
+        match (response.okay, response.result) {
+            (true, Some(result)) => {
+                // Removing the 0x prefix
+                let raw_value = result.strip_prefix("0x").unwrap_or(&result);
+                let bytes = hex_bytes(raw_value).map_err(|e| RpcError::Message(e.to_string()))?;
+                let mut cursor = Cursor::new(&bytes);
+                Value::consensus_deserialize(&mut cursor)
+                    .map_err(|e| RpcError::Message(e.to_string()))
+            }
+            _ => Err(response
+                .cause
+                .map(RpcError::Message)
+                .unwrap_or(RpcError::Generic)),
+        }
+    }
+
+    /// Returns `None` if the map has no entry for the key
+    pub fn get_map_entry(
+        &self,
+        contract_addr: &str,
+        contract_name: &str,
+        map_name: &str,
+        key: &Value,
+    ) -> Result<Option<Value>, RpcError> {
+        let path = format!(
+            "{}/v2/map_entry/{}/{}/{}?proof=0",
+            self.url, contract_addr, contract_name, map_name
+        );
+        let key = key
+            .serialize_to_vec()
+            .map_err(|e| RpcError::Message(format!("unable to serialize the key: {}", e)))?;
+        let res = self
+            .client
+            .post(path)
+            .json(&bytes_to_hex(&key))
+            .send()
+            .map_err(|e| RpcError::Unavailable(e.to_string()))?;
+        if !res.status().is_success() {
+            return Err(RpcError::StatusCode(res.status().as_u16()));
+        }
+
+        #[derive(Deserialize, Debug)]
+        struct MapEntryResult {
+            data: String,
+        }
+
+        let response: MapEntryResult = res.json().map_err(|e| RpcError::Message(e.to_string()))?;
+        let bytes = hex_bytes(response.data.trim_start_matches("0x"))
+            .map_err(|e| RpcError::Message(e.to_string()))?;
+        let value = Value::consensus_deserialize(&mut Cursor::new(&bytes))
+            .map_err(|e| RpcError::Message(e.to_string()))?;
+        match value {
+            Value::Optional(data) => Ok(data.data.map(|value| *value)),
+            value => Err(RpcError::Message(format!("unexpected map entry {}", value))),
         }
     }
 }
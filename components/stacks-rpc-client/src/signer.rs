@@ -0,0 +1,353 @@
+//! Transactions signers. Transactions are built with the public key of the signer
+//! (see [`TxSigner::sign`]), the signer then only has to sign their origin, whether
+//! the secret key is in memory, in an encrypted keystore, or held by another process.
+
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use clarity::address::{
+    AddressHashMode, C32_ADDRESS_VERSION_MAINNET_SINGLESIG, C32_ADDRESS_VERSION_TESTNET_SINGLESIG,
+};
+use clarity::codec::StacksMessageCodec;
+use clarity::types::chainstate::StacksAddress;
+use clarity::util::hash::{bytes_to_hex, hex_bytes};
+use clarity::util::secp256k1::{Secp256k1PrivateKey, Secp256k1PublicKey};
+use hmac::Hmac;
+use pbkdf2::pbkdf2;
+use sha2::Sha256;
+use stacks_codec::builder::{get_compressed_public_key, TxBuilder};
+use stacks_codec::codec::{StacksTransaction, StacksTransactionSigner};
+use tiny_hderive::bip32::ExtendedPrivKey;
+
+use crate::crypto::get_bip39_seed_from_mnemonic;
+
+pub trait TxSigner {
+    fn get_public_key(&self) -> Result<Secp256k1PublicKey, String>;
+
+    /// Signs the origin of a transaction built with the signer's public key
+    fn sign_transaction(
+        &self,
+        unsigned_tx: &StacksTransaction,
+    ) -> Result<StacksTransaction, String>;
+
+    fn get_stacks_address(&self, mainnet: bool) -> Result<StacksAddress, String> {
+        let version = match mainnet {
+            true => C32_ADDRESS_VERSION_MAINNET_SINGLESIG,
+            false => C32_ADDRESS_VERSION_TESTNET_SINGLESIG,
+        };
+        StacksAddress::from_public_keys(
+            version,
+            &AddressHashMode::SerializeP2PKH,
+            1,
+            &vec![self.get_public_key()?],
+        )
+        .ok_or("unable to compute the signer address".to_string())
+    }
+
+    fn sign(&self, builder: TxBuilder) -> Result<StacksTransaction, String> {
+        let unsigned_tx = builder.singlesig(self.get_public_key()?).build_unsigned()?;
+        self.sign_transaction(&unsigned_tx)
+    }
+}
+
+fn sign_with_secret_key(
+    unsigned_tx: &StacksTransaction,
+    secret_key: &Secp256k1PrivateKey,
+) -> Result<StacksTransaction, String> {
+    let mut secret_key = *secret_key;
+    secret_key.set_compress_public(true);
+    let mut signer = StacksTransactionSigner::new(unsigned_tx);
+    signer
+        .sign_origin(&secret_key)
+        .map_err(|e| format!("unable to sign transaction: {}", e))?;
+    if unsigned_tx.auth.is_sponsored() {
+        return Ok(signer.get_tx_incomplete());
+    }
+    signer
+        .get_tx()
+        .ok_or("transaction is missing signatures".to_string())
+}
+
+#[derive(Clone, Debug)]
+pub struct InMemorySigner {
+    secret_key: Secp256k1PrivateKey,
+}
+
+impl InMemorySigner {
+    pub fn new(secret_key: Secp256k1PrivateKey) -> Self {
+        Self { secret_key }
+    }
+
+    pub fn from_mnemonic(mnemonic: &str, derivation: &str) -> Result<Self, String> {
+        let bip39_seed = get_bip39_seed_from_mnemonic(mnemonic, "")?;
+        let ext = ExtendedPrivKey::derive(&bip39_seed[..], derivation)
+            .map_err(|e| format!("unable to derive key ({}): {:?}", derivation, e))?;
+        let secret_key = Secp256k1PrivateKey::from_slice(&ext.secret())
+            .map_err(|e| format!("invalid secret key: {}", e))?;
+        Ok(Self::new(secret_key))
+    }
+
+    pub fn get_secret_key(&self) -> &Secp256k1PrivateKey {
+        &self.secret_key
+    }
+}
+
+impl TxSigner for InMemorySigner {
+    fn get_public_key(&self) -> Result<Secp256k1PublicKey, String> {
+        Ok(get_compressed_public_key(&self.secret_key))
+    }
+
+    fn sign_transaction(
+        &self,
+        unsigned_tx: &StacksTransaction,
+    ) -> Result<StacksTransaction, String> {
+        sign_with_secret_key(unsigned_tx, &self.secret_key)
+    }
+}
+
+const KEYSTORE_VERSION: u32 = 1;
+const KEYSTORE_KDF_ROUNDS: u32 = 600_000;
+
+/// A secret key encrypted with AES-256-GCM, using a key derived from a password
+/// with PBKDF2-HMAC-SHA256
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct EncryptedKeystore {
+    pub version: u32,
+    pub public_key: String,
+    pub kdf_rounds: u32,
+    pub salt: String,
+    pub nonce: String,
+    pub ciphertext: String,
+}
+
+fn derive_keystore_key(password: &str, salt: &[u8], rounds: u32) -> Result<[u8; 32], String> {
+    let mut key = [0u8; 32];
+    pbkdf2::<Hmac<Sha256>>(password.as_bytes(), salt, rounds, &mut key)
+        .map_err(|e| e.to_string())?;
+    Ok(key)
+}
+
+impl EncryptedKeystore {
+    pub fn encrypt(secret_key: &Secp256k1PrivateKey, password: &str) -> Result<Self, String> {
+        let mut salt = [0u8; 16];
+        OsRng.fill_bytes(&mut salt);
+        let key = derive_keystore_key(password, &salt, KEYSTORE_KDF_ROUNDS)?;
+        let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key));
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = cipher
+            .encrypt(&nonce, secret_key.to_bytes().as_slice())
+            .map_err(|e| format!("unable to encrypt secret key: {}", e))?;
+        Ok(Self {
+            version: KEYSTORE_VERSION,
+            public_key: get_compressed_public_key(secret_key).to_hex(),
+            kdf_rounds: KEYSTORE_KDF_ROUNDS,
+            salt: bytes_to_hex(&salt),
+            nonce: bytes_to_hex(nonce.as_slice()),
+            ciphertext: bytes_to_hex(&ciphertext),
+        })
+    }
+
+    pub fn decrypt(&self, password: &str) -> Result<Secp256k1PrivateKey, String> {
+        if self.version != KEYSTORE_VERSION {
+            return Err(format!("unsupported keystore version {}", self.version));
+        }
+        let decode = |field: &str, value: &str| {
+            hex_bytes(value).map_err(|e| format!("invalid keystore {}: {}", field, e))
+        };
+        let salt = decode("salt", &self.salt)?;
+        let nonce = decode("nonce", &self.nonce)?;
+        let ciphertext = decode("ciphertext", &self.ciphertext)?;
+        if nonce.len() != 12 {
+            return Err("invalid keystore nonce".to_string());
+        }
+
+        let key = derive_keystore_key(password, &salt, self.kdf_rounds)?;
+        let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key));
+        let secret_key = cipher
+            .decrypt(Nonce::from_slice(&nonce), ciphertext.as_slice())
+            .map_err(|_| "unable to decrypt keystore: invalid password".to_string())?;
+        Secp256k1PrivateKey::from_slice(&secret_key)
+            .map_err(|e| format!("invalid secret key: {}", e))
+    }
+
+    pub fn from_path(path: &Path) -> Result<Self, String> {
+        let content = std::fs::read(path)
+            .map_err(|e| format!("unable to read keystore {}: {}", path.display(), e))?;
+        serde_json::from_slice(&content)
+            .map_err(|e| format!("unable to parse keystore {}: {}", path.display(), e))
+    }
+
+    pub fn write(&self, path: &Path) -> Result<(), String> {
+        let content = serde_json::to_vec_pretty(self)
+            .map_err(|e| format!("unable to serialize keystore: {}", e))?;
+        std::fs::write(path, content)
+            .map_err(|e| format!("unable to write keystore {}: {}", path.display(), e))
+    }
+}
+
+/// Decrypts the keystore for each signature, the secret key isn't kept in memory
+#[derive(Clone, Debug)]
+pub struct KeystoreSigner {
+    keystore: EncryptedKeystore,
+    password: String,
+}
+
+impl KeystoreSigner {
+    /// Fails if the password doesn't decrypt the keystore
+    pub fn new(keystore: EncryptedKeystore, password: &str) -> Result<Self, String> {
+        keystore.decrypt(password)?;
+        Ok(Self {
+            keystore,
+            password: password.to_string(),
+        })
+    }
+}
+
+impl TxSigner for KeystoreSigner {
+    fn get_public_key(&self) -> Result<Secp256k1PublicKey, String> {
+        Secp256k1PublicKey::from_hex(&self.keystore.public_key)
+            .map_err(|e| format!("invalid keystore public key: {}", e))
+    }
+
+    fn sign_transaction(
+        &self,
+        unsigned_tx: &StacksTransaction,
+    ) -> Result<StacksTransaction, String> {
+        let secret_key = self.keystore.decrypt(&self.password)?;
+        sign_with_secret_key(unsigned_tx, &secret_key)
+    }
+}
+
+/// Delegates signatures to another program (hardware wallet bridge, KMS client...).
+/// The program is invoked as:
+/// - `<command> <args> get-public-key`, printing the hex encoded compressed public key
+/// - `<command> <args> sign-transaction`, reading the hex encoded unsigned transaction
+///   on its standard input and printing the hex encoded signed transaction
+#[derive(Clone, Debug)]
+pub struct ExternalSigner {
+    pub command: String,
+    pub args: Vec<String>,
+}
+
+impl ExternalSigner {
+    pub fn new(command: &str, args: Vec<String>) -> Self {
+        Self {
+            command: command.to_string(),
+            args,
+        }
+    }
+
+    fn run(&self, action: &str, input: Option<&str>) -> Result<String, String> {
+        let mut child = Command::new(&self.command)
+            .args(&self.args)
+            .arg(action)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("unable to run signer {}: {}", self.command, e))?;
+        if let Some(input) = input {
+            let mut stdin = child.stdin.take().ok_or("unable to write to signer")?;
+            stdin
+                .write_all(input.as_bytes())
+                .map_err(|e| format!("unable to write to signer: {}", e))?;
+        }
+        let output = child
+            .wait_with_output()
+            .map_err(|e| format!("signer {} failed: {}", self.command, e))?;
+        if !output.status.success() {
+            return Err(format!(
+                "signer {} failed: {}",
+                self.command,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+}
+
+impl TxSigner for ExternalSigner {
+    fn get_public_key(&self) -> Result<Secp256k1PublicKey, String> {
+        let public_key = self.run("get-public-key", None)?;
+        Secp256k1PublicKey::from_hex(public_key.trim_start_matches("0x"))
+            .map_err(|e| format!("invalid public key returned by signer: {}", e))
+    }
+
+    fn sign_transaction(
+        &self,
+        unsigned_tx: &StacksTransaction,
+    ) -> Result<StacksTransaction, String> {
+        let signed_tx = self.run(
+            "sign-transaction",
+            Some(&bytes_to_hex(&unsigned_tx.serialize_to_vec())),
+        )?;
+        let bytes = hex_bytes(signed_tx.trim_start_matches("0x"))
+            .map_err(|e| format!("invalid transaction returned by signer: {}", e))?;
+        let signed_tx = StacksTransaction::consensus_deserialize(&mut &bytes[..])
+            .map_err(|e| format!("invalid transaction returned by signer: {}", e))?;
+
+        // only the signature is expected to change
+        let mut unsigned = signed_tx.clone();
+        unsigned.auth = unsigned.auth.into_initial_sighash_auth();
+        let mut expected = unsigned_tx.clone();
+        expected.auth = expected.auth.into_initial_sighash_auth();
+        if unsigned != expected {
+            return Err("transaction returned by signer doesn't match".to_string());
+        }
+        signed_tx
+            .verify_origin()
+            .map_err(|e| format!("invalid signature returned by signer: {}", e))?;
+        Ok(signed_tx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clarity::vm::types::QualifiedContractIdentifier;
+    use clarity::vm::Value;
+
+    use super::*;
+
+    fn get_builder() -> TxBuilder {
+        let contract_id =
+            QualifiedContractIdentifier::parse("ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM.counter")
+                .unwrap();
+        TxBuilder::contract_call(&contract_id, "increment".into(), vec![Value::UInt(1)])
+            .nonce(1)
+            .fee(1000)
+    }
+
+    #[test]
+    fn test_in_memory_signer() {
+        let signer = InMemorySigner::from_mnemonic(
+            "twice kind fence tip hidden tilt action fragile skin nothing glory cousin green tomorrow spring wrist shed math olympic multiply hip blue scout claw",
+            "m/44'/5757'/0'/0/0",
+        )
+        .unwrap();
+        assert_eq!(
+            signer.get_stacks_address(false).unwrap().to_string(),
+            "ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM"
+        );
+        let tx = signer.sign(get_builder()).unwrap();
+        assert!(tx.verify().is_ok());
+    }
+
+    #[test]
+    fn test_keystore_signer() {
+        let secret_key = Secp256k1PrivateKey::from_slice(&[1; 32]).unwrap();
+        let keystore = EncryptedKeystore::encrypt(&secret_key, "password").unwrap();
+        assert!(KeystoreSigner::new(keystore.clone(), "wrong password").is_err());
+
+        let signer = KeystoreSigner::new(keystore, "password").unwrap();
+        assert_eq!(
+            signer.get_public_key().unwrap(),
+            get_compressed_public_key(&secret_key)
+        );
+        let tx = signer.sign(get_builder()).unwrap();
+        assert!(tx.verify().is_ok());
+    }
+}
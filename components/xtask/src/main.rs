@@ -0,0 +1,217 @@
+//! Development tasks, run with `cargo xtask <task>`.
+//!
+//! `cargo xtask bench [--save <file>] [--compare <file>] [--threshold <percent>]` runs the
+//! benchmarks of the hot paths (AST building, dependency detection, plan generation, session
+//! replay and simnet calls) and collects the median of each benchmark, in nanoseconds. With
+//! `--save`, the medians are written as a baseline; with `--compare`, they are checked against
+//! a baseline and the task fails when a benchmark got slower than the threshold (10% by default).
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::process::{self, Command, Stdio};
+
+/// Benchmark targets run by `cargo xtask bench`, by package
+const BENCHES: &[(&str, &str)] = &[
+    ("clarity-repl", "analysis"),
+    ("clarity-repl", "simnet"),
+    ("clarinet-deployments", "deployments"),
+];
+
+const DEFAULT_THRESHOLD: f64 = 10.0;
+
+type Medians = BTreeMap<String, f64>;
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let result = match args.first().map(|task| task.as_str()) {
+        Some("bench") => bench(&args[1..]),
+        _ => Err(
+            "usage: cargo xtask bench [--save <file>] [--compare <file>] [--threshold <percent>]"
+                .to_string(),
+        ),
+    };
+    if let Err(e) = result {
+        eprintln!("error: {}", e);
+        process::exit(1);
+    }
+}
+
+fn bench(args: &[String]) -> Result<(), String> {
+    let mut save = None;
+    let mut compare = None;
+    let mut threshold = DEFAULT_THRESHOLD;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or(format!("missing value of {}", arg))
+                .cloned()
+        };
+        match arg.as_str() {
+            "--save" => save = Some(value()?),
+            "--compare" => compare = Some(value()?),
+            "--threshold" => {
+                threshold = value()?
+                    .parse()
+                    .map_err(|_| "the threshold must be a percentage".to_string())?
+            }
+            _ => return Err(format!("unknown argument {}", arg)),
+        }
+    }
+
+    let mut medians = Medians::new();
+    let cargo = std::env::var("CARGO").unwrap_or_else(|_| "cargo".to_string());
+    for (package, bench) in BENCHES {
+        let output = Command::new(&cargo)
+            .args(["bench", "--package", package, "--bench", bench])
+            .stderr(Stdio::inherit())
+            .output()
+            .map_err(|e| format!("unable to run cargo bench: {}", e))?;
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        print!("{}", stdout);
+        if !output.status.success() {
+            return Err(format!("the benchmark {} of {} failed", bench, package));
+        }
+        medians.extend(parse_divan_output(&stdout));
+    }
+
+    if let Some(path) = save {
+        let json = serde_json::to_string_pretty(&medians).map_err(|e| e.to_string())?;
+        fs::write(&path, json).map_err(|e| format!("unable to write {}: {}", path, e))?;
+        println!("baseline saved to {}", path);
+    }
+
+    if let Some(path) = compare {
+        let content =
+            fs::read_to_string(&path).map_err(|e| format!("unable to read {}: {}", path, e))?;
+        let baseline: Medians = serde_json::from_str(&content)
+            .map_err(|e| format!("invalid baseline {}: {}", path, e))?;
+        let regressions = compare_medians(&baseline, &medians, threshold);
+        if !regressions.is_empty() {
+            return Err(format!(
+                "{} benchmarks are more than {}% slower than {}: {}",
+                regressions.len(),
+                threshold,
+                path,
+                regressions.join(", ")
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Prints the change of each benchmark, returns the ones slower than `threshold` percents
+fn compare_medians(baseline: &Medians, medians: &Medians, threshold: f64) -> Vec<String> {
+    let mut regressions = vec![];
+    for (name, median) in medians {
+        let Some(previous) = baseline.get(name) else {
+            println!("{:<60} {:>12} (new)", name, format_duration(*median));
+            continue;
+        };
+        let change = (median / previous - 1.0) * 100.0;
+        println!(
+            "{:<60} {:>12} {:+.1}%",
+            name,
+            format_duration(*median),
+            change
+        );
+        if change > threshold {
+            regressions.push(name.clone());
+        }
+    }
+    regressions
+}
+
+fn format_duration(nanoseconds: f64) -> String {
+    match nanoseconds {
+        n if n >= 1e9 => format!("{:.2} s", n / 1e9),
+        n if n >= 1e6 => format!("{:.2} ms", n / 1e6),
+        n if n >= 1e3 => format!("{:.2} µs", n / 1e3),
+        n => format!("{:.0} ns", n),
+    }
+}
+
+/// Medians of the table printed by divan, named after the path of each row in the tree of
+/// benchmarks, e.g. `analysis::ast_building::10`
+fn parse_divan_output(output: &str) -> Medians {
+    let mut medians = Medians::new();
+    let mut path: Vec<String> = vec![];
+    for line in output.lines() {
+        // the tree of the benchmarks prefixes the name, 3 characters per level
+        let row = line.trim_start_matches(['│', '├', '╰', '─', ' ']);
+        let depth = (line.chars().count() - row.chars().count()) / 3;
+        let columns: Vec<&str> = row.split('│').collect();
+        let Some(name) = columns[0].split_whitespace().next() else {
+            continue;
+        };
+        if depth == 0 {
+            // header of a benchmark target
+            if columns[0].contains("fastest") {
+                path = vec![name.to_string()];
+            }
+            continue;
+        }
+        if path.is_empty() {
+            continue;
+        }
+        path.truncate(depth);
+        path.push(name.to_string());
+        // the columns are fastest, slowest, median, mean, samples and iters
+        if let Some(median) = columns.get(2).and_then(|median| parse_duration(median)) {
+            medians.insert(path.join("::"), median);
+        }
+    }
+    medians
+}
+
+/// Nanoseconds of a duration printed by divan, e.g. `12.5 µs`
+fn parse_duration(duration: &str) -> Option<f64> {
+    let (value, unit) = duration.trim().split_once(' ')?;
+    let multiplier = match unit {
+        "ps" => 1e-3,
+        "ns" => 1.0,
+        "µs" | "us" => 1e3,
+        "ms" => 1e6,
+        "s" => 1e9,
+        _ => return None,
+    };
+    Some(value.parse::<f64>().ok()? * multiplier)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compare_divan_output() {
+        let output = "Timer precision: 20 ns
+analysis                 fastest       │ slowest       │ median        │ mean          │ samples │ iters
+├─ ast_building                        │               │               │               │         │
+│  ├─ 10                 1.1 ms        │ 1.5 ms        │ 1.2 ms        │ 1.2 ms        │ 100     │ 100
+│  ╰─ 100                11 ms         │ 14 ms         │ 12 ms         │ 12 ms         │ 100     │ 100
+╰─ dependency_detection                │               │               │               │         │
+   ╰─ 10                 80 µs         │ 120 µs        │ 90 µs         │ 95 µs         │ 100     │ 100
+simnet                   fastest       │ slowest       │ median        │ mean          │ samples │ iters
+╰─ simnet_noop_public    11.2 µs       │ 1.2 ms        │ 12.5 µs       │ 13.1 µs       │ 10000   │ 10000
+";
+        let medians = parse_divan_output(output);
+        assert_eq!(
+            medians,
+            Medians::from([
+                ("analysis::ast_building::10".to_string(), 1.2e6),
+                ("analysis::ast_building::100".to_string(), 12e6),
+                ("analysis::dependency_detection::10".to_string(), 90e3),
+                ("simnet::simnet_noop_public".to_string(), 12.5e3),
+            ])
+        );
+
+        let mut baseline = medians.clone();
+        baseline.insert("analysis::ast_building::100".to_string(), 10e6);
+        baseline.remove("simnet::simnet_noop_public");
+        assert_eq!(
+            compare_medians(&baseline, &medians, DEFAULT_THRESHOLD),
+            vec!["analysis::ast_building::100".to_string()]
+        );
+        assert!(compare_medians(&baseline, &medians, 25.0).is_empty());
+    }
+}